@@ -42,8 +42,10 @@ pub struct NetworkArgs {
     #[arg(long, verbatim_doc_comment, conflicts_with = "peers_file")]
     pub no_persist_peers: bool,
 
-    /// NAT resolution method.
-    #[arg(long, default_value = "any")]
+    /// NAT resolution method (any|none|upnp|publicip|extip:<IP>|extip-service:<URL>).
+    ///
+    /// With `any` and `upnp`, the RLPx and discovery ports are mapped on the UPnP gateway.
+    #[arg(long, verbatim_doc_comment, default_value = "any")]
     pub nat: NatResolver,
 }
 
@@ -57,7 +59,7 @@ impl NetworkArgs {
     ) -> NetworkConfigBuilder {
         let peers_file = (!self.no_persist_peers).then_some(&self.peers_file);
         let network_config_builder = config
-            .network_config(self.nat, peers_file.map(|f| f.as_ref().to_path_buf()))
+            .network_config(self.nat.clone(), peers_file.map(|f| f.as_ref().to_path_buf()))
             .boot_nodes(self.bootnodes.clone().unwrap_or_else(mainnet_nodes))
             .chain_spec(chain_spec);
 
@@ -3,20 +3,38 @@
 use crate::dirs::{JwtSecretPath, PlatformPath};
 use clap::Args;
 use jsonrpsee::{core::Error as RpcError, server::ServerHandle};
-use reth_network_api::{NetworkInfo, Peers};
-use reth_provider::{BlockProvider, EvmEnvProvider, HeaderProvider, StateProviderFactory};
-use reth_rpc::{JwtError, JwtSecret};
+use reth_interfaces::{
+    consensus::ForkchoiceState,
+    sync::{ChainUnwinder, SyncStateProvider},
+};
+use reth_network_api::{NetworkInfo, Peers, PeersInfo};
+use reth_primitives::Address;
+use reth_provider::{
+    AccountHistoryProvider, BlockIdProvider, BlockProvider, CanonicalTip, ChainSpecProvider,
+    EvmEnvProvider, HeaderProvider, StateProviderFactory, StorageHistoryVerifier,
+};
+use reth_rpc::{
+    eth::bytes_limit::{BytesLimits, DEFAULT_MAX_CALL_DATA_SIZE, DEFAULT_MAX_RAW_TRANSACTION_SIZE},
+    JwtError, JwtSecret, RethApiConfig, DEFAULT_MAX_RECEIPTS_RANGE_BLOCKS,
+};
 use reth_rpc_builder::{
-    constants, IpcServerBuilder, RethRpcModule, RpcModuleSelection, RpcServerConfig,
-    RpcServerHandle, ServerBuilder, TransportRpcModuleConfig,
+    constants,
+    health::{HealthServerBuilder, HealthServerHandle},
+    EthConfig, IpcServerBuilder, RethRpcModule, RpcModuleBuilder, RpcModuleConfig,
+    RpcModuleSelection, RpcServerConfig, RpcServerHandle, ServerBuilder, TransportRpcModuleConfig,
+};
+use reth_rpc_engine_api::{
+    EngineApiConfig, EngineApiHandle, PayloadValidator, PermissivePayloadValidator,
+    StrictPayloadValidator, MAX_EXTRA_DATA_SIZE,
 };
-use reth_rpc_engine_api::EngineApiHandle;
 use reth_tasks::TaskSpawner;
 use reth_transaction_pool::TransactionPool;
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::Path,
+    sync::Arc,
 };
+use tokio::sync::watch;
 
 /// Parameters for configuring the rpc more granularity via CLI
 #[derive(Debug, Args, PartialEq, Default)]
@@ -77,6 +95,65 @@ pub struct RpcServerArgs {
     /// Path to a JWT secret to use for authenticated RPC endpoints
     #[arg(long = "authrpc.jwtsecret", value_name = "PATH", global = true, required = false)]
     auth_jwtsecret: Option<PlatformPath<JwtSecretPath>>,
+
+    /// Max number of Engine API messages waiting to be processed
+    #[arg(long = "engine.max-queued-messages")]
+    pub engine_max_queued_messages: Option<usize>,
+
+    /// Only build payloads for these fee recipients, rejecting other payload attributes
+    ///
+    /// Enables the strict payload policy.
+    #[arg(long = "engine.fee-recipients", value_delimiter = ',')]
+    pub engine_fee_recipients: Option<Vec<Address>>,
+
+    /// Max size of the extra data of locally built payloads
+    ///
+    /// Enables the strict payload policy.
+    #[arg(long = "engine.max-extra-data")]
+    pub engine_max_extra_data: Option<usize>,
+
+    /// Reject payload attributes with the zero address as fee recipient
+    ///
+    /// Enables the strict payload policy.
+    #[arg(long = "engine.forbid-zero-fee-recipient")]
+    pub engine_forbid_zero_fee_recipient: bool,
+
+    /// Max number of blocks served by a `reth_getBlockReceiptsRange` request
+    #[arg(long = "rpc.max-receipts-range-blocks")]
+    pub rpc_max_receipts_range_blocks: Option<u64>,
+
+    /// Max size in bytes of the call data of a request, for example in `eth_call`
+    #[arg(long = "rpc.max-call-data-size")]
+    pub rpc_max_call_data_size: Option<usize>,
+
+    /// Max size in bytes of a transaction sent with `eth_sendRawTransaction`
+    #[arg(long = "rpc.max-raw-tx-size")]
+    pub rpc_max_raw_tx_size: Option<usize>,
+
+    /// Add the gas that pays the L1 data fee of the transaction to `eth_estimateGas`
+    #[cfg(feature = "optimism")]
+    #[arg(long = "rpc.estimate-l1-data-fee")]
+    pub rpc_estimate_l1_data_fee: bool,
+
+    /// Enable the health check server
+    #[arg(long)]
+    pub health: bool,
+
+    /// Health check server address to listen on
+    #[arg(long = "health.addr")]
+    pub health_addr: Option<IpAddr>,
+
+    /// Health check server port to listen on
+    #[arg(long = "health.port")]
+    pub health_port: Option<u16>,
+
+    /// Max number of blocks the node may be behind the fork choice head to be healthy
+    #[arg(long = "health.max-blocks-behind")]
+    pub health_max_blocks_behind: Option<u64>,
+
+    /// Min number of connected peers for the node to be healthy
+    #[arg(long = "health.min-peers")]
+    pub health_min_peers: Option<usize>,
 }
 
 impl RpcServerArgs {
@@ -103,35 +180,62 @@ impl RpcServerArgs {
         }
     }
 
+    /// Returns the config of the queue of Engine API messages.
+    pub(crate) fn engine_api_config(&self) -> EngineApiConfig {
+        let mut config = EngineApiConfig::default();
+        if let Some(max_queued_messages) = self.engine_max_queued_messages {
+            config.max_queued_messages = max_queued_messages.max(1);
+        }
+        config
+    }
+
+    /// Returns the checks of payload attributes and built payloads of the Engine API.
+    ///
+    /// Everything is accepted unless one of the payload policy flags is set.
+    pub(crate) fn payload_validator(&self) -> Arc<dyn PayloadValidator> {
+        if self.engine_fee_recipients.is_none() &&
+            self.engine_max_extra_data.is_none() &&
+            !self.engine_forbid_zero_fee_recipient
+        {
+            return Arc::new(PermissivePayloadValidator)
+        }
+        Arc::new(StrictPayloadValidator {
+            allowed_fee_recipients: self.engine_fee_recipients.clone().unwrap_or_default(),
+            max_extra_data: self.engine_max_extra_data.unwrap_or(MAX_EXTRA_DATA_SIZE),
+            forbid_zero_fee_recipient: self.engine_forbid_zero_fee_recipient,
+        })
+    }
+
     /// Convenience function for starting a rpc server with configs which extracted from cli args.
+    ///
+    /// The rpc serves the tip of the canonical chain from the given [CanonicalTip] once it is set.
     pub(crate) async fn start_rpc_server<Client, Pool, Network, Tasks>(
         &self,
         client: Client,
         pool: Pool,
         network: Network,
         executor: Tasks,
+        canonical_tip: CanonicalTip,
     ) -> Result<RpcServerHandle, RpcError>
     where
         Client: BlockProvider
             + HeaderProvider
             + StateProviderFactory
             + EvmEnvProvider
+            + AccountHistoryProvider
+            + ChainSpecProvider
             + Clone
             + Unpin
             + 'static,
         Pool: TransactionPool + Clone + 'static,
-        Network: NetworkInfo + Peers + Clone + 'static,
+        Network: NetworkInfo + Peers + SyncStateProvider + Clone + 'static,
         Tasks: TaskSpawner + Clone + 'static,
     {
-        reth_rpc_builder::launch(
-            client,
-            pool,
-            network,
-            self.transport_rpc_module_config(),
-            self.rpc_server_config(),
-            executor,
-        )
-        .await
+        RpcModuleBuilder::new(client, pool, network, executor)
+            .with_canonical_tip(canonical_tip)
+            .build(self.transport_rpc_module_config())
+            .start_server(self.rpc_server_config())
+            .await
     }
 
     /// Create Engine API server.
@@ -142,17 +246,20 @@ impl RpcServerArgs {
         network: Network,
         executor: Tasks,
         handle: EngineApiHandle,
+        unwinder: Option<Arc<dyn ChainUnwinder>>,
     ) -> Result<ServerHandle, RpcError>
     where
         Client: BlockProvider
             + HeaderProvider
             + StateProviderFactory
             + EvmEnvProvider
+            + ChainSpecProvider
+            + StorageHistoryVerifier
             + Clone
             + Unpin
             + 'static,
         Pool: TransactionPool + Clone + 'static,
-        Network: NetworkInfo + Peers + Clone + 'static,
+        Network: NetworkInfo + Peers + SyncStateProvider + Clone + 'static,
         Tasks: TaskSpawner + Clone + 'static,
     {
         let socket_address = SocketAddr::new(
@@ -166,12 +273,50 @@ impl RpcServerArgs {
             network,
             executor,
             handle,
+            unwinder,
             socket_address,
             secret,
         )
         .await
     }
 
+    /// Starts the health check server if enabled.
+    pub(crate) async fn start_health_server<Client, Network>(
+        &self,
+        client: Client,
+        network: Network,
+        forkchoice_state: watch::Receiver<ForkchoiceState>,
+    ) -> eyre::Result<Option<HealthServerHandle>>
+    where
+        Client: BlockIdProvider + 'static,
+        Network: PeersInfo + 'static,
+    {
+        if !self.health {
+            return Ok(None)
+        }
+        let socket_address = SocketAddr::new(
+            self.health_addr.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            self.health_port.unwrap_or(constants::DEFAULT_HEALTH_PORT),
+        );
+        let handle = self
+            .health_server_builder()
+            .start(socket_address, client, network, forkchoice_state)
+            .await?;
+        Ok(Some(handle))
+    }
+
+    /// Creates the [HealthServerBuilder] from cli args.
+    fn health_server_builder(&self) -> HealthServerBuilder {
+        let mut builder = HealthServerBuilder::default();
+        if let Some(max_blocks_behind) = self.health_max_blocks_behind {
+            builder = builder.max_blocks_behind(max_blocks_behind);
+        }
+        if let Some(min_peers) = self.health_min_peers {
+            builder = builder.min_peers(min_peers);
+        }
+        builder
+    }
+
     /// Creates the [TransportRpcModuleConfig] from cli args.
     fn transport_rpc_module_config(&self) -> TransportRpcModuleConfig {
         let mut config = TransportRpcModuleConfig::default();
@@ -185,7 +330,24 @@ impl RpcServerArgs {
             config = config.with_ws(self.ws_api.as_ref().unwrap_or(&rpc_modules).clone());
         }
 
-        config
+        let reth = RethApiConfig {
+            max_receipts_range_blocks: self
+                .rpc_max_receipts_range_blocks
+                .unwrap_or(DEFAULT_MAX_RECEIPTS_RANGE_BLOCKS),
+        };
+        let bytes_limits = BytesLimits {
+            max_call_data_size: self.rpc_max_call_data_size.unwrap_or(DEFAULT_MAX_CALL_DATA_SIZE),
+            max_raw_transaction_size: self
+                .rpc_max_raw_tx_size
+                .unwrap_or(DEFAULT_MAX_RAW_TRANSACTION_SIZE),
+        };
+        let eth = EthConfig {
+            bytes_limits,
+            #[cfg(feature = "optimism")]
+            estimate_l1_data_fee: self.rpc_estimate_l1_data_fee,
+            ..Default::default()
+        };
+        config.with_config(RpcModuleConfig::builder().eth(eth).reth(reth).build())
     }
 
     /// Creates the [RpcServerConfig] from cli args.
@@ -294,4 +456,22 @@ mod tests {
         );
         assert_eq!(config.ipc_endpoint().unwrap().path(), constants::DEFAULT_IPC_ENDPOINT);
     }
+
+    #[test]
+    fn test_health_server_builder() {
+        let args = CommandParser::<RpcServerArgs>::parse_from([
+            "reth",
+            "--health",
+            "--health.max-blocks-behind",
+            "8",
+            "--health.min-peers",
+            "3",
+        ])
+        .args;
+        assert!(args.health);
+        assert_eq!(
+            args.health_server_builder(),
+            HealthServerBuilder::default().max_blocks_behind(8).min_peers(3)
+        );
+    }
 }
@@ -6,10 +6,15 @@ use clap::{crate_version, Parser};
 use eyre::Context;
 use futures::{Stream, StreamExt};
 use reth_consensus::beacon::BeaconConsensus;
-use reth_db::mdbx::{Env, WriteMap};
+use reth_db::{
+    database::Database,
+    mdbx::{Env, WriteMap},
+    tables,
+    transaction::DbTx,
+};
 use reth_downloaders::{
-    bodies::bodies::BodiesDownloaderBuilder,
-    headers::reverse_headers::ReverseHeadersDownloaderBuilder, test_utils::FileClient,
+    bodies::bodies::BodiesDownloaderBuilder, file_client::FileClient,
+    headers::reverse_headers::ReverseHeadersDownloaderBuilder,
 };
 use reth_interfaces::{
     consensus::{Consensus, ForkchoiceState},
@@ -26,12 +31,12 @@ use reth_staged_sync::{
 };
 use reth_stages::{
     prelude::*,
-    stages::{ExecutionStage, SenderRecoveryStage, TotalDifficultyStage},
+    stages::{ExecutionStage, SenderRecoveryStage, TotalDifficultyStage, HEADERS},
 };
 use std::sync::Arc;
 use tracing::{debug, info};
 
-/// Syncs RLP encoded blocks from a file.
+/// Syncs RLP encoded blocks from a file or a directory of files.
 #[derive(Debug, Parser)]
 pub struct ImportCommand {
     /// The path to the configuration file to use.
@@ -65,7 +70,9 @@ pub struct ImportCommand {
     )]
     chain: Arc<ChainSpec>,
 
-    /// The path to a block file for import.
+    /// The path to a block file, or to a directory of block files, for import.
+    ///
+    /// The files of a directory are imported in the order of their names.
     ///
     /// The online stages (headers and bodies) are replaced by a file import, after which the
     /// remaining stages are executed.
@@ -91,7 +98,28 @@ impl ImportCommand {
 
         // create a new FileClient
         info!(target: "reth::cli", "Importing chain file");
-        let file_client = Arc::new(FileClient::new(&self.path).await?);
+        let mut file_client = if self.path.as_ref().is_dir() {
+            FileClient::from_dir(&self.path).await?
+        } else {
+            FileClient::new(&self.path).await?
+        };
+
+        // skip the blocks that were already imported
+        let checkpoint = db.view(|tx| HEADERS.get_progress(tx))??.unwrap_or_default();
+        if file_client.is_exhausted(checkpoint) {
+            info!(target: "reth::cli", checkpoint, "Chain file exhausted, continue syncing from the network");
+            return Ok(())
+        }
+        let local_head = db
+            .view(|tx| -> eyre::Result<_> {
+                let hash = tx.get::<tables::CanonicalHeaders>(checkpoint)?;
+                let header = tx.get::<tables::Headers>(checkpoint)?;
+                Ok(hash.zip(header))
+            })??
+            .map(|(hash, header)| header.seal(hash))
+            .ok_or_else(|| eyre::eyre!("Missing header of checkpoint {checkpoint}"))?;
+        file_client.seek(&local_head)?;
+        let file_client = Arc::new(file_client);
 
         // override the tip
         let tip = file_client.tip().expect("file client has no tip");
@@ -159,7 +187,11 @@ impl ImportCommand {
                 .set(SenderRecoveryStage {
                     commit_threshold: config.stages.sender_recovery.commit_threshold,
                 })
-                .set(ExecutionStage::new(factory, config.stages.execution.commit_threshold)),
+                .set(
+                    ExecutionStage::new(factory, config.stages.execution.commit_threshold)
+                        .with_slow_block_threshold(config.stages.execution.slow_block_threshold)
+                        .with_prune_modes(config.prune.clone()),
+                ),
             )
             .with_max_block(0)
             .build();
@@ -25,7 +25,9 @@ pub fn run() -> eyre::Result<()> {
     let runner = CliRunner::default();
 
     match opt.command {
-        Commands::Node(command) => runner.run_command_until_exit(|ctx| command.execute(ctx)),
+        Commands::Node(command) => runner
+            .with_graceful_shutdown_timeout(command.graceful_shutdown_timeout())
+            .run_command_until_exit(|ctx| command.execute(ctx)),
         Commands::Init(command) => runner.run_until_ctrl_c(command.execute()),
         Commands::Import(command) => runner.run_until_ctrl_c(command.execute()),
         Commands::Db(command) => runner.run_until_ctrl_c(command.execute()),
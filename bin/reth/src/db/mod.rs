@@ -12,9 +12,12 @@ use reth_db::{
     transaction::DbTx,
 };
 use reth_interfaces::test_utils::generators::random_block_range;
-use reth_provider::insert_canonical_block;
+use reth_primitives::{Address, BlockNumber, H256};
+use reth_provider::{
+    check_consistency, fix_consistency, insert_canonical_block, verify_storage_history,
+};
 use std::collections::BTreeMap;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 /// DB List TUI
 mod tui;
@@ -53,6 +56,25 @@ pub enum Subcommands {
     },
     /// Deletes all database entries
     Drop,
+    /// Checks the invariants between the tables and lists all violations
+    Check {
+        /// Repair the violations that can be repaired without losing data
+        #[arg(long)]
+        fix: bool,
+        /// Also verify the history of a storage slot of this account against the storage
+        /// changesets
+        #[arg(long, requires = "slot")]
+        address: Option<Address>,
+        /// The storage slot whose history is verified
+        #[arg(long, requires = "address")]
+        slot: Option<H256>,
+        /// The first block whose storage history is verified
+        #[arg(long, default_value_t = 0)]
+        from: BlockNumber,
+        /// The last block whose storage history is verified, defaults to the canonical tip
+        #[arg(long)]
+        to: Option<BlockNumber>,
+    },
 }
 
 #[derive(Parser, Debug)]
@@ -170,7 +192,7 @@ impl Command {
                     HeaderTD,
                     HeaderNumbers,
                     Headers,
-                    BlockBodies,
+                    BlockBodyIndices,
                     BlockOmmers,
                     TxHashNumber,
                     PlainStorageState,
@@ -184,6 +206,12 @@ impl Command {
             Subcommands::Drop => {
                 tool.drop(&self.db)?;
             }
+            Subcommands::Check { fix, address, slot, from, to } => {
+                tool.check(*fix)?;
+                if let (Some(address), Some(slot)) = (address, slot) {
+                    tool.check_storage_history(*address, *slot, *from, *to)?;
+                }
+            }
         }
 
         Ok(())
@@ -235,6 +263,54 @@ impl<'a, DB: Database> DbTool<'a, DB> {
             .map_err(|e| eyre::eyre!(e))
     }
 
+    /// Checks the consistency of the tables, and repairs the fixable violations if `fix` is set.
+    fn check(&mut self, fix: bool) -> Result<()> {
+        info!(target: "reth::cli", "Checking the consistency of the database");
+        let report = self.db.view(|tx| check_consistency(tx))??;
+        for violation in &report.violations {
+            warn!(target: "reth::cli", fixable = violation.is_fixable(), "{violation}");
+        }
+        if report.is_consistent() {
+            info!(target: "reth::cli", "Database is consistent");
+            return Ok(())
+        }
+
+        let fixable = report.fixable().count();
+        let violations = report.violations.len();
+        info!(target: "reth::cli", violations, fixable, "Database is inconsistent");
+        if fix && fixable > 0 {
+            let fixed = self.db.update(|tx| fix_consistency(tx, &report))??;
+            info!(target: "reth::cli", fixed, "Repaired database violations");
+        }
+        Ok(())
+    }
+
+    /// Verifies the history of the storage slot against the storage changesets.
+    fn check_storage_history(
+        &mut self,
+        address: Address,
+        slot: H256,
+        from: BlockNumber,
+        to: Option<BlockNumber>,
+    ) -> Result<()> {
+        let to = match to {
+            Some(to) => to,
+            None => {
+                let tip =
+                    self.db.view(|tx| tx.cursor_read::<tables::CanonicalHeaders>()?.last())??;
+                tip.map(|(number, _)| number).unwrap_or_default()
+            }
+        };
+        info!(target: "reth::cli", ?address, ?slot, from, to, "Verifying the storage history");
+        let divergence =
+            self.db.view(|tx| verify_storage_history(tx, address, slot, from..=to))??;
+        match divergence {
+            Some(divergence) => warn!(target: "reth::cli", "{divergence}"),
+            None => info!(target: "reth::cli", "Storage history is consistent"),
+        }
+        Ok(())
+    }
+
     fn drop(&mut self, path: &PlatformPath<DbPath>) -> Result<()> {
         info!(target: "reth::cli", "Dropping db at {}", path);
         std::fs::remove_dir_all(path).wrap_err("Dropping the database failed")?;
@@ -101,6 +101,19 @@ impl XdgPath for KnownPeersPath {
     }
 }
 
+/// Returns the path to the default journal of local transactions.
+///
+/// Refer to [dirs_next::data_dir] for cross-platform behavior.
+#[derive(Default, Debug, Clone)]
+#[non_exhaustive]
+pub struct TxPoolJournalPath;
+
+impl XdgPath for TxPoolJournalPath {
+    fn resolve() -> Option<PathBuf> {
+        database_path().map(|p| p.join("local-transactions.rlp"))
+    }
+}
+
 /// Returns the path to the reth logs directory.
 ///
 /// Refer to [dirs_next::cache_dir] for cross-platform behavior.
@@ -4,6 +4,7 @@ use futures::{Stream, StreamExt};
 use reth_network::{NetworkEvent, NetworkHandle};
 use reth_network_api::PeersInfo;
 use reth_primitives::BlockNumber;
+use reth_rpc_engine_api::EngineApiEvent;
 use reth_stages::{PipelineEvent, StageId};
 use std::time::Duration;
 use tracing::{info, warn};
@@ -53,6 +54,14 @@ impl NodeState {
         }
     }
 
+    fn handle_engine_api_event(&mut self, event: EngineApiEvent) {
+        match event {
+            EngineApiEvent::InvalidBlock { block_hash, parent_hash, error } => {
+                warn!(target: "reth::cli", %block_hash, %parent_hash, %error, "Received invalid block");
+            }
+        }
+    }
+
     async fn handle_network_event(&mut self, event: NetworkEvent) {
         match event {
             NetworkEvent::SessionEstablished { peer_id, status, .. } => {
@@ -73,6 +82,8 @@ pub enum NodeEvent {
     Network(NetworkEvent),
     /// A sync pipeline event.
     Pipeline(PipelineEvent),
+    /// An Engine API event.
+    EngineApi(EngineApiEvent),
 }
 
 impl From<NetworkEvent> for NodeEvent {
@@ -87,6 +98,12 @@ impl From<PipelineEvent> for NodeEvent {
     }
 }
 
+impl From<EngineApiEvent> for NodeEvent {
+    fn from(evt: EngineApiEvent) -> NodeEvent {
+        NodeEvent::EngineApi(evt)
+    }
+}
+
 /// Displays relevant information to the user from components of the node, and periodically
 /// displays the high-level status of the node.
 pub async fn handle_events(
@@ -107,6 +124,9 @@ pub async fn handle_events(
                     NodeEvent::Pipeline(event) => {
                         state.handle_pipeline_event(event).await;
                     }
+                    NodeEvent::EngineApi(event) => {
+                        state.handle_engine_api_event(event);
+                    }
                 }
             },
             _ = interval.tick() => {
@@ -3,9 +3,9 @@
 //! Starts the client
 use crate::{
     args::{NetworkArgs, RpcServerArgs},
-    dirs::{ConfigPath, DbPath, PlatformPath},
+    dirs::{ConfigPath, DbPath, PlatformPath, TxPoolJournalPath},
     prometheus_exporter,
-    runner::CliContext,
+    runner::{CliContext, DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT},
     utils::get_single_header,
 };
 use clap::{crate_version, Parser};
@@ -37,28 +37,42 @@ use reth_network::{
     error::NetworkError, FetchClient, NetworkConfig, NetworkHandle, NetworkManager,
 };
 use reth_network_api::NetworkInfo;
-use reth_primitives::{BlockHashOrNumber, ChainSpec, Head, H256};
-use reth_provider::{BlockProvider, HeaderProvider, ShareableDatabase};
-use reth_rpc_engine_api::{EngineApi, EngineApiHandle};
+use reth_primitives::{BlockHashOrNumber, ChainSpec, Head, SealedHeader, H256};
+use reth_provider::{
+    BlockProvider, BytecodeCache, CanonicalTip, HeaderProvider, ShareableDatabase,
+    StateProviderFactory,
+};
+use reth_rpc_engine_api::{engine_api_channel, EngineApi, EngineApiEvent, EngineApiHandle};
 use reth_staged_sync::{
     utils::{
         chainspec::genesis_value_parser,
-        init::{init_db, init_genesis},
+        init::{
+            init_db, init_from_checkpoint, init_genesis, is_state_available, TrustedCheckpoint,
+            CHECKPOINT_STATE_STAGES,
+        },
         parse_socket_address,
+        state_dump::init_checkpoint_state,
     },
     Config,
 };
 use reth_stages::{
+    pipeline_control_channel,
     prelude::*,
     stages::{ExecutionStage, SenderRecoveryStage, TotalDifficultyStage, FINISH},
+    PipelineControl,
+};
+use reth_tasks::{shutdown::GracefulShutdown, TaskExecutor};
+use reth_transaction_pool::{
+    journal::{load_local_transactions, save_local_transactions},
+    TransactionPool,
 };
-use reth_tasks::TaskExecutor;
 use std::{
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
     path::PathBuf,
     sync::Arc,
+    time::Duration,
 };
-use tokio::sync::{mpsc::unbounded_channel, watch};
+use tokio::sync::watch;
 use tracing::*;
 
 pub mod events;
@@ -106,6 +120,28 @@ pub struct Command {
     #[clap(flatten)]
     network: NetworkArgs,
 
+    /// Start syncing from a trusted block instead of genesis, formatted as `<NUMBER>:<HASH>`.
+    ///
+    /// Only applies to an empty database. The history and state below the checkpoint are not
+    /// downloaded and requests for them fail. The state above the checkpoint is unavailable
+    /// until the state of the checkpoint is imported with `--checkpoint.state`: the blocks
+    /// above it are downloaded but not executed.
+    #[arg(long, value_name = "NUMBER:HASH", verbatim_doc_comment)]
+    checkpoint: Option<TrustedCheckpoint>,
+
+    /// The state of the `--checkpoint` block, a dump in the genesis `alloc` format exported by
+    /// another node.
+    ///
+    /// The state is imported once, its state root must match the checkpoint. The blocks above
+    /// the checkpoint are executed on top of it.
+    #[arg(
+        long = "checkpoint.state",
+        value_name = "FILE",
+        verbatim_doc_comment,
+        requires = "checkpoint"
+    )]
+    checkpoint_state: Option<PathBuf>,
+
     /// Set the chain tip manually for testing purposes.
     ///
     /// NOTE: This is a temporary flag
@@ -122,9 +158,31 @@ pub struct Command {
 
     #[clap(flatten)]
     rpc: RpcServerArgs,
+
+    /// The path to the journal of local transactions.
+    ///
+    /// Local transactions in the transaction pool are written to this file on node shutdown, and
+    /// reinserted into the pool on startup.
+    #[arg(long = "txpool.journal", value_name = "FILE", verbatim_doc_comment, default_value_t)]
+    txpool_journal: PlatformPath<TxPoolJournalPath>,
+
+    /// The maximum time in seconds to wait for components to persist their state on shutdown.
+    ///
+    /// Afterwards the node exits regardless.
+    #[arg(
+        long = "shutdown-timeout",
+        value_name = "SECONDS",
+        default_value_t = DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT.as_secs()
+    )]
+    shutdown_timeout: u64,
 }
 
 impl Command {
+    /// Returns the amount of time the node's components are given to shut down gracefully.
+    pub fn graceful_shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout)
+    }
+
     /// Execute `node` command
     // TODO: RPC
     pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
@@ -151,6 +209,24 @@ impl Command {
         let (consensus, forkchoice_state_tx) = self.init_consensus()?;
         info!(target: "reth::cli", "Consensus engine initialized");
 
+        // without a tip, the fork choice state of the last run is restored so that the safe and
+        // finalized blocks resolve before the consensus layer sends the next update
+        if self.tip.is_none() {
+            if let Some(state) = shareable_db.load_forkchoice_state()? {
+                debug!(target: "reth::cli", ?state, "Restored fork choice state");
+                forkchoice_state_tx.send(state)?;
+            }
+        }
+        ctx.task_executor
+            .spawn(shareable_db.clone().persist_forkchoice_state(consensus.fork_choice_state()));
+
+        // the safe and finalized blocks are resolved from the fork choice state of the engine API,
+        // or of `--debug.tip` once the pipeline committed the tip, and the contract codes read by
+        // the rpc servers are cached
+        let shareable_db = shareable_db
+            .with_forkchoice_state(consensus.fork_choice_state())
+            .with_bytecode_cache(BytecodeCache::default());
+
         self.init_trusted_nodes(&mut config);
 
         info!(target: "reth::cli", "Connecting to P2P network");
@@ -162,6 +238,24 @@ impl Command {
         let test_transaction_pool = reth_transaction_pool::test_utils::testing_pool();
         info!(target: "reth::cli", "Test transaction pool initialized");
 
+        let journal = self.txpool_journal.as_ref().to_path_buf();
+        match load_local_transactions(&test_transaction_pool, &journal).await {
+            Ok(num_txs) => {
+                debug!(target: "reth::cli", num_txs, path=?journal, "Loaded local transactions")
+            }
+            Err(err) => {
+                warn!(target: "reth::cli", ?err, path=?journal, "Failed to load local transactions")
+            }
+        }
+        let pool = test_transaction_pool.clone();
+        ctx.task_executor
+            .spawn_critical_with_graceful_shutdown_signal("txpool journal task", |shutdown| {
+                save_local_transactions_on_shutdown(shutdown, pool, journal)
+            });
+
+        // the tip of the canonical chain is kept in memory for the rpc, it is updated by the
+        // finish stage of the pipeline
+        let canonical_tip = CanonicalTip::default();
         let _rpc_server = self
             .rpc
             .start_rpc_server(
@@ -169,14 +263,28 @@ impl Command {
                 test_transaction_pool.clone(),
                 network.clone(),
                 ctx.task_executor.clone(),
+                canonical_tip.clone(),
             )
             .await?;
         info!(target: "reth::cli", "Started RPC server");
 
-        let engine_api_handle =
+        let _health_server = self
+            .rpc
+            .start_health_server(
+                shareable_db.clone(),
+                network.clone(),
+                consensus.fork_choice_state(),
+            )
+            .await?;
+
+        let (engine_api_handle, engine_api_events) =
             self.init_engine_api(Arc::clone(&db), forkchoice_state_tx, &ctx.task_executor);
         info!(target: "reth::cli", "Engine API handler initialized");
 
+        // `reth_unwindTo` of the auth server unwinds the pipeline through its control channel,
+        // unwinds below the configured prune horizon are refused
+        let (pipeline_handle, pipeline_control) = pipeline_control_channel(config.prune.clone());
+
         let _auth_server = self
             .rpc
             .start_auth_server(
@@ -185,6 +293,7 @@ impl Command {
                 network.clone(),
                 ctx.task_executor.clone(),
                 engine_api_handle,
+                Some(Arc::new(pipeline_handle)),
             )
             .await?;
         info!(target: "reth::cli", "Started Auth server");
@@ -196,18 +305,46 @@ impl Command {
                 &consensus,
                 db.clone(),
                 &ctx.task_executor,
+                canonical_tip,
+                pipeline_control,
             )
             .await?;
 
+        let events = stream_select(events, engine_api_events.map(Into::into));
         ctx.task_executor.spawn(events::handle_events(Some(network.clone()), events));
 
         // Run pipeline
         let (rx, tx) = tokio::sync::oneshot::channel();
         info!(target: "reth::cli", "Starting sync pipeline");
-        ctx.task_executor.spawn_critical_blocking("pipeline task", async move {
-            let res = pipeline.run(db.clone()).await;
-            let _ = rx.send(res);
-        });
+        ctx.task_executor.spawn_critical_blocking_with_graceful_shutdown_signal(
+            "pipeline task",
+            |shutdown| async move {
+                pin_mut!(shutdown);
+                let outcome = tokio::select! {
+                    res = pipeline.run(db.clone()) => Err(res),
+                    guard = &mut shutdown => Ok(guard),
+                };
+                let guard = match outcome {
+                    Ok(guard) => {
+                        // dropping the pipeline aborts the database transaction of the running
+                        // stage, the stage resumes from its last committed checkpoint on restart
+                        info!(target: "reth::cli", "Pipeline interrupted by shutdown");
+                        guard
+                    }
+                    Err(res) => {
+                        let _ = rx.send(res);
+                        shutdown.await
+                    }
+                };
+
+                // flush all committed changes to disk before exiting
+                match db.sync(true) {
+                    Ok(_) => info!(target: "reth::cli", "Synced database to disk"),
+                    Err(err) => warn!(target: "reth::cli", ?err, "Failed to sync database"),
+                }
+                drop(guard);
+            },
+        );
 
         tx.await??;
 
@@ -229,9 +366,25 @@ impl Command {
         consensus: &Arc<dyn Consensus>,
         db: Arc<Env<WriteMap>>,
         task_executor: &TaskExecutor,
+        canonical_tip: CanonicalTip,
+        control: PipelineControl,
     ) -> eyre::Result<(Pipeline<Env<WriteMap>, impl SyncStateUpdater>, impl Stream<Item = NodeEvent>)>
     {
         let fetch_client = network.fetch_client().await?;
+        if let Some(checkpoint) = self.checkpoint {
+            let header = self.fetch_checkpoint_header(fetch_client.clone(), checkpoint).await?;
+            init_from_checkpoint(db.clone(), self.chain.clone(), checkpoint, header)?;
+        }
+        if let Some(path) = &self.checkpoint_state {
+            if !db.view(|tx| is_state_available(tx))?? {
+                info!(target: "reth::cli", path = %path.display(), "Importing checkpoint state");
+                let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+                let state_root = init_checkpoint_state(db.clone(), reader)?;
+                info!(target: "reth::cli", ?state_root, "Imported checkpoint state");
+            }
+        }
+        let state_available = db.view(|tx| is_state_available(tx))??;
+
         let max_block = if let Some(block) = self.max_block {
             Some(block)
         } else if let Some(tip) = self.tip {
@@ -257,8 +410,11 @@ impl Command {
                 header_downloader,
                 body_downloader,
                 network.clone(),
+                canonical_tip,
                 consensus,
                 max_block,
+                state_available,
+                control,
             )
             .await?;
 
@@ -318,16 +474,18 @@ impl Command {
         db: Arc<Env<WriteMap>>,
         forkchoice_state_tx: watch::Sender<ForkchoiceState>,
         task_executor: &TaskExecutor,
-    ) -> EngineApiHandle {
-        let (message_tx, message_rx) = unbounded_channel();
-        let engine_api = EngineApi::new(
+    ) -> (EngineApiHandle, impl Stream<Item = EngineApiEvent>) {
+        let (message_tx, message_rx) = engine_api_channel(self.rpc.engine_api_config());
+        let mut engine_api = EngineApi::new(
             ShareableDatabase::new(db, self.chain.clone()),
             self.chain.clone(),
             message_rx,
             forkchoice_state_tx,
-        );
+        )
+        .with_payload_validator(self.rpc.payload_validator());
+        let events = engine_api.events();
         task_executor.spawn(engine_api);
-        message_tx
+        (message_tx, events)
     }
 
     /// Spawns the configured network and associated tasks and returns the [NetworkHandle] connected
@@ -340,16 +498,17 @@ impl Command {
         _pool: (),
     ) -> Result<NetworkHandle, NetworkError>
     where
-        C: BlockProvider + HeaderProvider + Clone + Unpin + 'static,
+        C: BlockProvider + HeaderProvider + StateProviderFactory + Clone + Unpin + 'static,
     {
         let client = config.client.clone();
         let (handle, network, _txpool, eth) =
             NetworkManager::builder(config).await?.request_handler(client).split_with_handle();
 
         let known_peers_file = self.network.persistent_peers_file();
-        task_executor.spawn_critical_with_signal("p2p network task", |shutdown| {
-            run_network_until_shutdown(shutdown, network, known_peers_file)
-        });
+        task_executor
+            .spawn_critical_with_graceful_shutdown_signal("p2p network task", |shutdown| {
+                run_network_until_shutdown(shutdown, network, known_peers_file)
+            });
 
         task_executor.spawn_critical("p2p eth request handler", eth);
 
@@ -410,6 +569,33 @@ impl Command {
         }
     }
 
+    /// Downloads the header of the checkpoint block.
+    ///
+    /// NOTE: The download is attempted with infinite retries.
+    async fn fetch_checkpoint_header(
+        &self,
+        fetch_client: FetchClient,
+        checkpoint: TrustedCheckpoint,
+    ) -> eyre::Result<SealedHeader> {
+        info!(target: "reth::cli", %checkpoint, "Fetching checkpoint header from the network.");
+        loop {
+            match get_single_header(fetch_client.clone(), BlockHashOrNumber::Hash(checkpoint.hash))
+                .await
+            {
+                Ok(header) if header.number == checkpoint.number => {
+                    info!(target: "reth::cli", %checkpoint, "Successfully fetched checkpoint header");
+                    return Ok(header)
+                }
+                Ok(header) => {
+                    error!(target: "reth::cli", %checkpoint, number = header.number, "Checkpoint header has a different block number. Retrying...");
+                }
+                Err(error) => {
+                    error!(target: "reth::cli", %error, "Failed to fetch the checkpoint header. Retrying...");
+                }
+            }
+        }
+    }
+
     fn load_network_config(
         &self,
         config: &Config,
@@ -435,8 +621,11 @@ impl Command {
         header_downloader: H,
         body_downloader: B,
         updater: U,
+        canonical_tip: CanonicalTip,
         consensus: &Arc<dyn Consensus>,
         max_block: Option<u64>,
+        state_available: bool,
+        control: PipelineControl,
     ) -> eyre::Result<Pipeline<Env<WriteMap>, U>>
     where
         H: HeaderDownloader + 'static,
@@ -445,7 +634,7 @@ impl Command {
     {
         let stage_conf = &config.stages;
 
-        let mut builder = Pipeline::builder();
+        let mut builder = Pipeline::builder().with_control(control);
 
         if let Some(max_block) = max_block {
             debug!(target: "reth::cli", max_block, "Configuring builder to use max block");
@@ -453,35 +642,44 @@ impl Command {
         }
 
         let factory = reth_executor::Factory::new(self.chain.clone());
-        let pipeline = builder
-            .with_sync_state_updater(updater.clone())
-            .add_stages(
-                DefaultStages::new(
-                    consensus.clone(),
-                    header_downloader,
-                    body_downloader,
-                    updater,
-                    factory.clone(),
-                )
-                .set(
-                    TotalDifficultyStage::new(consensus.clone())
-                        .with_commit_threshold(stage_conf.total_difficulty.commit_threshold),
-                )
-                .set(SenderRecoveryStage {
-                    commit_threshold: stage_conf.sender_recovery.commit_threshold,
-                })
-                .set(ExecutionStage::new(factory, stage_conf.execution.commit_threshold)),
-            )
-            .build();
+        let mut stages = DefaultStages::new(
+            consensus.clone(),
+            header_downloader,
+            body_downloader,
+            (updater.clone(), canonical_tip),
+            factory.clone(),
+        )
+        .set(
+            TotalDifficultyStage::new(consensus.clone())
+                .with_commit_threshold(stage_conf.total_difficulty.commit_threshold),
+        )
+        .set(SenderRecoveryStage { commit_threshold: stage_conf.sender_recovery.commit_threshold })
+        .set(
+            ExecutionStage::new(factory, stage_conf.execution.commit_threshold)
+                .with_slow_block_threshold(stage_conf.execution.slow_block_threshold)
+                .with_prune_modes(config.prune.clone()),
+        );
+
+        if !state_available {
+            // Synced from a checkpoint whose state was not imported: the state above the
+            // checkpoint is unavailable, so the blocks above it are synced but not executed. Once
+            // the state is imported, the stages resume at the checkpoint.
+            info!(target: "reth::cli", "Checkpoint state unavailable, disabling execution stages");
+            for stage in CHECKPOINT_STATE_STAGES {
+                stages = stages.disable(stage);
+            }
+        }
+
+        let pipeline = builder.with_sync_state_updater(updater).add_stages(stages).build();
 
         Ok(pipeline)
     }
 }
 
-/// Drives the [NetworkManager] future until a [Shutdown](reth_tasks::shutdown::Shutdown) signal is
-/// received. If configured, this writes known peers to `persistent_peers_file` afterwards.
+/// Drives the [NetworkManager] future until a [GracefulShutdown] signal is received. If configured,
+/// this writes known peers to `persistent_peers_file` afterwards.
 async fn run_network_until_shutdown<C>(
-    shutdown: reth_tasks::shutdown::Shutdown,
+    shutdown: GracefulShutdown,
     network: NetworkManager<C>,
     persistent_peers_file: Option<PathBuf>,
 ) where
@@ -489,9 +687,12 @@ async fn run_network_until_shutdown<C>(
 {
     pin_mut!(network, shutdown);
 
+    let mut graceful_guard = None;
     tokio::select! {
         _ = &mut network => {},
-        _ = shutdown => {},
+        guard = shutdown => {
+            graceful_guard = Some(guard);
+        },
     }
 
     if let Some(file_path) = persistent_peers_file {
@@ -508,11 +709,40 @@ async fn run_network_until_shutdown<C>(
             }
         }
     }
+
+    drop(graceful_guard)
+}
+
+/// Waits for the [GracefulShutdown] signal and writes all local transactions of the pool to the
+/// journal file.
+async fn save_local_transactions_on_shutdown<P>(
+    shutdown: GracefulShutdown,
+    pool: P,
+    journal: PathBuf,
+) where
+    P: TransactionPool + 'static,
+{
+    let _guard = shutdown.await;
+    match save_local_transactions(&pool, &journal) {
+        Ok(num_txs) => {
+            info!(target: "reth::cli", num_txs, path=?journal, "Wrote local transactions to journal");
+        }
+        Err(err) => {
+            warn!(target: "reth::cli", ?err, path=?journal, "Failed to write local transactions to journal");
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use reth_network::config::rng_secret_key;
+    use reth_primitives::NodeRecord;
+    use reth_provider::test_utils::NoopProvider;
+    use reth_tasks::TaskManager;
+    use reth_transaction_pool::{
+        journal::read_transactions_journal, test_utils::MockTransaction, TransactionOrigin,
+    };
 
     #[test]
     fn parse_help_node_command() {
@@ -520,6 +750,25 @@ mod tests {
         assert_eq!(err.kind(), clap::error::ErrorKind::DisplayHelp);
     }
 
+    #[test]
+    fn parse_checkpoint() {
+        let hash = H256::random();
+        let args = Command::parse_from(["reth", "--checkpoint", &format!("100:{hash:?}")]);
+        assert_eq!(args.checkpoint, Some(TrustedCheckpoint { number: 100, hash }));
+        assert!(Command::try_parse_from(["reth", "--checkpoint", "100"]).is_err());
+
+        let args = Command::parse_from([
+            "reth",
+            "--checkpoint",
+            &format!("100:{hash:?}"),
+            "--checkpoint.state",
+            "state.json",
+        ]);
+        assert_eq!(args.checkpoint_state, Some(PathBuf::from("state.json")));
+        // the state belongs to a checkpoint
+        assert!(Command::try_parse_from(["reth", "--checkpoint.state", "state.json"]).is_err());
+    }
+
     #[test]
     fn parse_common_node_command_chain_args() {
         for chain in ["mainnet", "sepolia", "goerli"] {
@@ -527,4 +776,55 @@ mod tests {
             assert_eq!(args.chain.chain, chain.parse().unwrap());
         }
     }
+
+    // Spawns the network and the txpool journal tasks, triggers a graceful shutdown and checks that
+    // the persisted state can be loaded again.
+    #[test]
+    fn graceful_shutdown_persists_state() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let manager = TaskManager::new(runtime.handle().clone());
+        let executor = manager.executor();
+
+        let dir = tempfile::tempdir().unwrap();
+        let peers_file = dir.path().join("known-peers.json");
+        let journal = dir.path().join("local-transactions.rlp");
+
+        let pool = reth_transaction_pool::test_utils::testing_pool();
+
+        runtime.block_on(async {
+            let config = NetworkConfig::builder(rng_secret_key())
+                .listener_addr(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0)))
+                .disable_discv4_discovery()
+                .disable_dns_discovery()
+                .build(NoopProvider::default());
+            let network = NetworkManager::new(config).await.unwrap();
+            let peers_file = Some(peers_file.clone());
+            executor.spawn_critical_with_graceful_shutdown_signal("p2p network task", |shutdown| {
+                run_network_until_shutdown(shutdown, network, peers_file)
+            });
+
+            pool.add_transaction(TransactionOrigin::Local, MockTransaction::legacy())
+                .await
+                .unwrap();
+            pool.add_transaction(TransactionOrigin::External, MockTransaction::eip1559())
+                .await
+                .unwrap();
+            let journal_pool = pool.clone();
+            let journal = journal.clone();
+            executor
+                .spawn_critical_with_graceful_shutdown_signal("txpool journal task", |shutdown| {
+                    save_local_transactions_on_shutdown(shutdown, journal_pool, journal)
+                });
+        });
+
+        assert!(manager.graceful_shutdown_with_timeout(Duration::from_secs(10)));
+
+        let peers: Vec<NodeRecord> =
+            serde_json::from_slice(&std::fs::read(&peers_file).unwrap()).unwrap();
+        assert!(peers.is_empty());
+
+        // only the local transaction is journaled
+        let transactions = read_transactions_journal(&journal).unwrap();
+        assert_eq!(transactions.len(), 1);
+    }
 }
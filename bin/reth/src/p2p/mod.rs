@@ -99,12 +99,12 @@ impl Command {
         config.peers.connect_trusted_nodes_only = self.trusted_only;
 
         let mut network_config_builder =
-            config.network_config(self.nat, None).chain_spec(self.chain.clone());
+            config.network_config(self.nat.clone(), None).chain_spec(self.chain.clone());
 
         network_config_builder = self.discovery.apply_to_builder(network_config_builder);
 
         let network = network_config_builder
-            .build(Arc::new(ShareableDatabase::new(noop_db, self.chain.clone())))
+            .build(ShareableDatabase::new(noop_db, self.chain.clone()))
             .start_network()
             .await?;
 
@@ -2,22 +2,43 @@
 
 use futures::pin_mut;
 use reth_tasks::{TaskExecutor, TaskManager};
-use std::future::Future;
-use tracing::trace;
+use std::{future::Future, time::Duration};
+use tracing::{debug, trace, warn};
+
+/// The default amount of time tasks are given to shut down gracefully, for example to persist
+/// state, after which we exit regardless.
+pub const DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Used to execute cli commands
-#[derive(Default, Debug)]
+#[derive(Debug)]
 #[non_exhaustive]
-pub struct CliRunner;
+pub struct CliRunner {
+    /// How long to wait for tasks to shut down gracefully.
+    graceful_shutdown_timeout: Duration,
+}
+
+impl Default for CliRunner {
+    fn default() -> Self {
+        Self { graceful_shutdown_timeout: DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT }
+    }
+}
 
 // === impl CliRunner ===
 
 impl CliRunner {
+    /// Sets the amount of time tasks are given to shut down gracefully.
+    pub fn with_graceful_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.graceful_shutdown_timeout = timeout;
+        self
+    }
+
     /// Executes the given _async_ command on the tokio runtime until the command future resolves or
     /// until the process receives a `SIGINT` or `SIGTERM` signal.
     ///
     /// Tasks spawned by the command via the [TaskExecutor] are shut down and an attempt is made to
-    /// drive their shutdown to completion after the command has finished.
+    /// drive their shutdown to completion after the command has finished. Tasks that were spawned
+    /// with a graceful shutdown signal are given up to the configured graceful shutdown timeout to
+    /// finish, see [TaskManager::graceful_shutdown_with_timeout].
     pub fn run_command_until_exit<F, E>(
         self,
         command: impl FnOnce(CliContext) -> F,
@@ -33,9 +54,13 @@ impl CliRunner {
             task_manager,
             run_until_ctrl_c(command(context)),
         ))?;
-        // after the command has finished or exit signal was received we drop the task manager which
-        // fires the shutdown signal to all tasks spawned via the task executor
-        drop(task_manager);
+        // after the command has finished or exit signal was received we shutdown the task manager
+        // which fires the shutdown signal to all tasks spawned via the task executor and awaiting
+        // on tasks spawned with graceful shutdown
+        debug!(target: "reth::cli", timeout = ?self.graceful_shutdown_timeout, "Shutting down tasks");
+        if !task_manager.graceful_shutdown_with_timeout(self.graceful_shutdown_timeout) {
+            warn!(target: "reth::cli", timeout = ?self.graceful_shutdown_timeout, "Graceful shutdown timed out, exiting");
+        }
 
         // drop the tokio runtime on a separate thread because drop blocks until its pools
         // (including blocking pool) are shutdown. In other words `drop(tokio_runtime)` would block
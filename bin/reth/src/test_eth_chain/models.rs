@@ -96,7 +96,7 @@ impl From<Header> for SealedHeader {
             logs_bloom: value.bloom,
             withdrawals_root: value.withdrawals_root,
         };
-        header.seal(value.hash)
+        SealedHeader::new_unchecked(header, value.hash)
     }
 }
 
@@ -90,6 +90,10 @@ impl Consensus for BeaconConsensus {
     fn has_block_reward(&self, total_difficulty: U256, difficulty: U256) -> bool {
         !self.chain_spec.fork(Hardfork::Paris).active_at_ttd(total_difficulty, difficulty)
     }
+
+    fn has_block_hash_history(&self, timestamp: u64) -> bool {
+        self.chain_spec.fork(Hardfork::Prague).active_at_timestamp(timestamp)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,129 @@
+use reth_interfaces::{consensus::ForkchoiceState, Result};
+use reth_primitives::{BlockNumber, H256, U256};
+use reth_provider::BlockHashProvider;
+use tokio::sync::watch;
+
+/// Derives the safe and finalized blocks of the fork choice state from the canonical head, for
+/// nodes without a consensus layer client, e.g. development nodes.
+///
+/// The safe and finalized block is the canonical block `depth` blocks below the head, or the
+/// genesis block if the chain is shorter.
+///
+/// The state is written to the same channel the engine API updates. Once the state is updated by
+/// anyone else, local updates are skipped: the values of the consensus layer take precedence.
+#[derive(Debug)]
+pub struct LocalFinality {
+    /// The number of blocks between the head and the finalized block.
+    depth: u64,
+    forkchoice_state_tx: watch::Sender<ForkchoiceState>,
+    /// The last state that was derived locally.
+    last_local: ForkchoiceState,
+}
+
+impl LocalFinality {
+    /// Creates a new instance that finalizes the block `depth` blocks below the head.
+    pub fn new(depth: u64, forkchoice_state_tx: watch::Sender<ForkchoiceState>) -> Self {
+        let last_local = forkchoice_state_tx.borrow().clone();
+        Self { depth, forkchoice_state_tx, last_local }
+    }
+
+    /// Returns the sender of the fork choice state, through which a consensus layer client can
+    /// take over.
+    pub fn forkchoice_state_tx(&self) -> &watch::Sender<ForkchoiceState> {
+        &self.forkchoice_state_tx
+    }
+
+    /// Returns a receiver of the fork choice state.
+    pub fn subscribe(&self) -> watch::Receiver<ForkchoiceState> {
+        self.forkchoice_state_tx.subscribe()
+    }
+
+    /// Updates the fork choice state for the new canonical head.
+    ///
+    /// Returns `false` if the state was left as is because it was updated by someone else.
+    pub fn on_canonical_head(
+        &mut self,
+        client: &impl BlockHashProvider,
+        head_number: BlockNumber,
+        head_hash: H256,
+    ) -> Result<bool> {
+        let finalized_number = head_number.saturating_sub(self.depth);
+        let finalized_block_hash = if finalized_number == head_number {
+            head_hash
+        } else {
+            client.block_hash(U256::from(finalized_number))?.unwrap_or_default()
+        };
+        let state = ForkchoiceState {
+            head_block_hash: head_hash,
+            safe_block_hash: finalized_block_hash,
+            finalized_block_hash,
+        };
+
+        let updated = self.forkchoice_state_tx.send_if_modified(|current| {
+            if *current != self.last_local {
+                return false
+            }
+            *current = state.clone();
+            true
+        });
+        if updated {
+            self.last_local = state;
+        }
+        Ok(updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Block, Header};
+    use reth_provider::test_utils::MockEthProvider;
+
+    #[test]
+    fn finalizes_below_head() {
+        let client = MockEthProvider::default();
+        let hashes = (0..4u64)
+            .map(|number| {
+                let header = Header { number, ..Default::default() };
+                let hash = header.hash_slow();
+                client.add_block(hash, Block { header, ..Default::default() });
+                hash
+            })
+            .collect::<Vec<_>>();
+
+        let (forkchoice_state_tx, _) = watch::channel(ForkchoiceState::default());
+        let mut finality = LocalFinality::new(2, forkchoice_state_tx);
+        let state = finality.subscribe();
+
+        assert!(finality.on_canonical_head(&client, 1, hashes[1]).unwrap());
+        assert_eq!(state.borrow().finalized_block_hash, hashes[0]);
+        assert!(finality.on_canonical_head(&client, 3, hashes[3]).unwrap());
+        assert_eq!(
+            *state.borrow(),
+            ForkchoiceState {
+                head_block_hash: hashes[3],
+                safe_block_hash: hashes[1],
+                finalized_block_hash: hashes[1],
+            }
+        );
+    }
+
+    #[test]
+    fn consensus_layer_takes_precedence() {
+        let client = MockEthProvider::default();
+        let (forkchoice_state_tx, _) = watch::channel(ForkchoiceState::default());
+        let mut finality = LocalFinality::new(0, forkchoice_state_tx);
+        let state = finality.subscribe();
+
+        assert!(finality.on_canonical_head(&client, 1, H256::random()).unwrap());
+        let cl_state = ForkchoiceState {
+            head_block_hash: H256::random(),
+            safe_block_hash: H256::random(),
+            finalized_block_hash: H256::random(),
+        };
+        finality.forkchoice_state_tx().send(cl_state.clone()).unwrap();
+
+        assert!(!finality.on_canonical_head(&client, 2, H256::random()).unwrap());
+        assert_eq!(*state.borrow(), cl_state);
+    }
+}
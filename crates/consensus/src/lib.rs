@@ -9,5 +9,8 @@
 /// Beacon consensus implementation.
 pub mod beacon;
 
+/// Finality without a consensus layer client.
+pub mod finality;
+
 /// Collection of consensus validation methods.
 pub mod validation;
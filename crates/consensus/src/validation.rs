@@ -1,8 +1,9 @@
 //! Collection of methods for block validation.
 use reth_interfaces::{consensus::ConsensusError, Result as RethResult};
 use reth_primitives::{
-    BlockNumber, ChainSpec, Hardfork, Header, InvalidTransactionError, SealedBlock, SealedHeader,
-    Transaction, TransactionSignedEcRecovered, TxEip1559, TxEip2930, TxLegacy,
+    BaseFeeParams, BlockNumber, ChainSpec, Hardfork, Header, InvalidTransactionError, SealedBlock,
+    SealedHeader, Transaction, TransactionSigned, TransactionSignedEcRecovered, TxEip1559,
+    TxEip2930, TxLegacy, U256,
 };
 use reth_provider::{AccountProvider, HeaderProvider, WithdrawalsProvider};
 use std::{
@@ -10,7 +11,21 @@ use std::{
     time::SystemTime,
 };
 
-use reth_primitives::constants;
+/// The order of the secp256k1 curve.
+const SECP256K1N: U256 = U256::from_limbs([
+    0xbfd25e8cd0364141,
+    0xbaaedce6af48a03b,
+    0xfffffffffffffffe,
+    0xffffffffffffffff,
+]);
+
+/// Half the order of the secp256k1 curve, the maximum `s` value of a signature after Homestead.
+const SECP256K1N_HALF: U256 = U256::from_limbs([
+    0xdfe92f46681b20a0,
+    0x5d576e7357a4501d,
+    0xffffffffffffffff,
+    0x7fffffffffffffff,
+]);
 
 /// Validate header standalone
 pub fn validate_header_standalone(
@@ -124,6 +139,61 @@ pub fn validate_transaction_regarding_header(
     Ok(())
 }
 
+/// Validate a signed transaction without recovering the signer:
+///
+/// - The transaction type is enabled at the block
+/// - The chain id of EIP-155 and typed transactions matches the chain
+/// - The signature values are within the curve order, and `s` within its lower half after
+///   Homestead (EIP-2)
+pub fn validate_transaction_standalone(
+    transaction: &TransactionSigned,
+    chain_spec: &ChainSpec,
+    at_block_number: BlockNumber,
+) -> Result<(), ConsensusError> {
+    let chain_id = match &transaction.transaction {
+        Transaction::Legacy(TxLegacy { chain_id, .. }) => {
+            // EIP-155: Simple replay attack protection: https://eips.ethereum.org/EIPS/eip-155
+            if chain_id.is_some() &&
+                !chain_spec.fork(Hardfork::SpuriousDragon).active_at_block(at_block_number)
+            {
+                return Err(InvalidTransactionError::OldLegacyChainId.into())
+            }
+            *chain_id
+        }
+        Transaction::Eip2930(TxEip2930 { chain_id, .. }) => {
+            if !chain_spec.fork(Hardfork::Berlin).active_at_block(at_block_number) {
+                return Err(InvalidTransactionError::Eip2930Disabled.into())
+            }
+            Some(*chain_id)
+        }
+        Transaction::Eip1559(TxEip1559 { chain_id, .. }) => {
+            if !chain_spec.fork(Hardfork::London).active_at_block(at_block_number) {
+                return Err(InvalidTransactionError::Eip1559Disabled.into())
+            }
+            Some(*chain_id)
+        }
+    };
+    if chain_id.map_or(false, |chain_id| chain_id != chain_spec.chain().id()) {
+        return Err(InvalidTransactionError::ChainIdMismatch.into())
+    }
+
+    let signature = transaction.signature();
+    let max_s = if chain_spec.fork(Hardfork::Homestead).active_at_block(at_block_number) {
+        SECP256K1N_HALF
+    } else {
+        SECP256K1N - U256::from(1)
+    };
+    if signature.r == U256::ZERO ||
+        signature.r >= SECP256K1N ||
+        signature.s == U256::ZERO ||
+        signature.s > max_s
+    {
+        return Err(InvalidTransactionError::SignatureValueOutOfRange.into())
+    }
+
+    Ok(())
+}
+
 /// Iterate over all transactions, validate them against each other and against the block.
 /// There is no gas check done as [REVM](https://github.com/bluealloy/revm/blob/fd0108381799662098b7ab2c429ea719d6dfbf28/crates/revm/src/evm_impl.rs#L113-L131) already checks that.
 pub fn validate_all_transaction_regarding_block_and_nonces<
@@ -182,7 +252,7 @@ pub fn validate_all_transaction_regarding_block_and_nonces<
 ///
 /// - Compares the ommer hash in the block header to the block body
 /// - Compares the transactions root in the block header to the block body
-/// - Pre-execution transaction validation
+/// - Pre-execution transaction validation, without recovering the signers
 /// - (Optionally) Compares the receipts root in the block header to the block body
 pub fn validate_block_standalone(
     block: &SealedBlock,
@@ -191,7 +261,7 @@ pub fn validate_block_standalone(
     // Check ommers hash
     // TODO(onbjerg): This should probably be accessible directly on [Block]
     let ommers_hash =
-        reth_primitives::proofs::calculate_ommers_root(block.ommers.iter().map(|h| h.as_ref()));
+        reth_primitives::proofs::calculate_ommers_hash(block.ommers.iter().map(|h| h.as_ref()));
     if block.header.ommers_hash != ommers_hash {
         return Err(ConsensusError::BodyOmmersHashDiff {
             got: ommers_hash,
@@ -209,6 +279,11 @@ pub fn validate_block_standalone(
         })
     }
 
+    // Check the transactions that can be validated without recovering the signers
+    for transaction in block.body.iter() {
+        validate_transaction_standalone(transaction, chain_spec, block.number)?;
+    }
+
     // EIP-4895: Beacon chain push withdrawals as operations
     if chain_spec.fork(Hardfork::Shanghai).active_at_timestamp(block.timestamp) {
         let withdrawals =
@@ -243,30 +318,11 @@ pub fn validate_block_standalone(
     Ok(())
 }
 
-/// Calculate base fee for next block. EIP-1559 spec
+/// Calculate base fee for next block with the parameters of Ethereum. EIP-1559 spec
+///
+/// See [ChainSpec::next_block_base_fee] for the base fee with the parameters of a chain.
 pub fn calculate_next_block_base_fee(gas_used: u64, gas_limit: u64, base_fee: u64) -> u64 {
-    let gas_target = gas_limit / constants::EIP1559_ELASTICITY_MULTIPLIER;
-
-    if gas_used == gas_target {
-        return base_fee
-    }
-    if gas_used > gas_target {
-        let gas_used_delta = gas_used - gas_target;
-        let base_fee_delta = std::cmp::max(
-            1,
-            base_fee as u128 * gas_used_delta as u128 /
-                gas_target as u128 /
-                constants::EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR as u128,
-        );
-        base_fee + (base_fee_delta as u64)
-    } else {
-        let gas_used_delta = gas_target - gas_used;
-        let base_fee_per_gas_delta = base_fee as u128 * gas_used_delta as u128 /
-            gas_target as u128 /
-            constants::EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR as u128;
-
-        base_fee.saturating_sub(base_fee_per_gas_delta as u64)
-    }
+    BaseFeeParams::ethereum().next_block_base_fee(gas_used, gas_limit, base_fee)
 }
 
 /// Validate block in regards to parent
@@ -302,7 +358,7 @@ pub fn validate_header_regarding_parent(
     // By consensus, gas_limit is multiplied by elasticity (*2) on
     // on exact block that hardfork happens.
     if chain_spec.fork(Hardfork::London).transitions_at_block(child.number) {
-        parent_gas_limit = parent.gas_limit * constants::EIP1559_ELASTICITY_MULTIPLIER;
+        parent_gas_limit = parent.gas_limit * chain_spec.base_fee_params.elasticity_multiplier;
     }
 
     // Check gas limit, max diff between child/parent gas_limit should be  max_diff=parent_gas/1024
@@ -324,17 +380,9 @@ pub fn validate_header_regarding_parent(
     if chain_spec.fork(Hardfork::London).active_at_block(child.number) {
         let base_fee = child.base_fee_per_gas.ok_or(ConsensusError::BaseFeeMissing)?;
 
+        // This BaseFeeMissing will not happen as previous blocks are checked to have them.
         let expected_base_fee =
-            if chain_spec.fork(Hardfork::London).transitions_at_block(child.number) {
-                constants::EIP1559_INITIAL_BASE_FEE
-            } else {
-                // This BaseFeeMissing will not happen as previous blocks are checked to have them.
-                calculate_next_block_base_fee(
-                    parent.gas_used,
-                    parent.gas_limit,
-                    parent.base_fee_per_gas.ok_or(ConsensusError::BaseFeeMissing)?,
-                )
-            };
+            chain_spec.next_block_base_fee(parent).ok_or(ConsensusError::BaseFeeMissing)?;
         if expected_base_fee != base_fee {
             return Err(ConsensusError::BaseFeeDiff { expected: expected_base_fee, got: base_fee })
         }
@@ -395,7 +443,7 @@ pub fn validate_block_regarding_chain<PROV: HeaderProvider + WithdrawalsProvider
     }
 
     // Return parent header.
-    Ok(parent.seal(block.parent_hash))
+    Ok(SealedHeader::new_unchecked(parent, block.parent_hash))
 }
 
 /// Full validation of block before execution.
@@ -536,6 +584,17 @@ mod tests {
         fn headers_range(&self, _range: impl RangeBounds<BlockNumber>) -> Result<Vec<Header>> {
             Ok(vec![])
         }
+
+        fn sealed_header(&self, _number: BlockNumber) -> Result<Option<SealedHeader>> {
+            Ok(None)
+        }
+
+        fn sealed_headers_range(
+            &self,
+            _range: impl RangeBounds<BlockNumber>,
+        ) -> Result<Vec<SealedHeader>> {
+            Ok(vec![])
+        }
     }
 
     impl WithdrawalsProvider for Provider {
@@ -1,8 +1,15 @@
 //! Implementation of [`BlockIndices`] related to [`super::BlockchainTree`]
 
-use super::chain::{BlockChainId, Chain, ForkBlock};
-use reth_primitives::{BlockHash, BlockNumber, SealedBlockWithSenders};
-use std::collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet};
+use super::{
+    canonical_chain::CanonicalChain,
+    chain::{BlockChainId, Chain, ForkBlock},
+};
+use reth_interfaces::{blockchain_tree::BlockIndicesSizes, executor::Error as ExecError};
+use reth_primitives::{BlockHash, BlockNumber, SealedBlockWithSenders, TransactionMeta, TxHash};
+use std::{
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
 
 /// Internal indices of the blocks and chains.  This is main connection
 /// between blocks, chains and canonical chain.
@@ -14,12 +21,14 @@ pub struct BlockIndices {
     last_finalized_block: BlockNumber,
     /// For EVM's "BLOCKHASH" opcode we require last 256 block hashes. So we need to specify
     /// at least `additional_canonical_block_hashes`+`max_reorg_depth`, for eth that would be
-    /// 256+64.
+    /// 256+64, see `BlockchainTreeConfig::num_of_canonical_hashes`.
     num_of_additional_canonical_block_hashes: u64,
     /// Canonical chain. Contains N number (depends on `finalization_depth`) of blocks.
     /// These blocks are found in fork_to_child but not inside `blocks_to_chain` or
     /// `number_to_block` as those are chain specific indices.
-    canonical_chain: BTreeMap<BlockNumber, BlockHash>,
+    ///
+    /// Shared with the executors of tree blocks, see [BlockIndices::canonical_hashes_snapshot].
+    canonical_chain: CanonicalChain,
     /// Index needed when discarding the chain, so we can remove connected chains from tree.
     /// NOTE: It contains just a blocks that are forks as a key and not all blocks.
     fork_to_child: HashMap<BlockHash, HashSet<BlockHash>>,
@@ -28,23 +37,36 @@ pub struct BlockIndices {
     /// Utility index. Block number to block hash. Can be used for
     /// RPC to fetch all pending block in chain by its number.
     index_number_to_block: HashMap<BlockNumber, HashSet<BlockHash>>,
+    /// Utility index. Transaction hash to the blocks of the tree that include it, the transaction
+    /// can be included by the blocks of several side chains.
+    index_tx_to_block: HashMap<TxHash, Vec<TransactionMeta>>,
+    /// Block hash to the hashes of its transactions, needed to clean up the transaction index.
+    block_to_txs: HashMap<BlockHash, Vec<TxHash>>,
 }
 
 impl BlockIndices {
     /// Create new block indices structure
+    ///
+    /// The canonical chain needs to contain at least the canonical head of the database, that
+    /// anchors the tree, otherwise [ExecError::EmptyCanonicalChain] is returned.
     pub fn new(
         last_finalized_block: BlockNumber,
         num_of_additional_canonical_block_hashes: u64,
         canonical_chain: BTreeMap<BlockNumber, BlockHash>,
-    ) -> Self {
-        Self {
+    ) -> Result<Self, ExecError> {
+        if canonical_chain.is_empty() {
+            return Err(ExecError::EmptyCanonicalChain)
+        }
+        Ok(Self {
             last_finalized_block,
             num_of_additional_canonical_block_hashes,
             fork_to_child: Default::default(),
-            canonical_chain,
+            canonical_chain: CanonicalChain::new(canonical_chain),
             blocks_to_chain: Default::default(),
             index_number_to_block: Default::default(),
-        }
+            index_tx_to_block: Default::default(),
+            block_to_txs: Default::default(),
+        })
     }
 
     /// Return number of additional canonical block hashes that we need
@@ -70,7 +92,10 @@ impl BlockIndices {
 
     /// Check if block hash belongs to canonical chain.
     pub fn is_block_hash_canonical(&self, block_hash: &BlockHash) -> bool {
-        self.canonical_chain.range(self.last_finalized_block..).any(|(_, &h)| h == *block_hash)
+        self.canonical_chain
+            .inner()
+            .range(self.last_finalized_block..)
+            .any(|(_, &h)| h == *block_hash)
     }
 
     /// Last finalized block
@@ -78,15 +103,22 @@ impl BlockIndices {
         self.last_finalized_block
     }
 
+    /// Returns the location of a transaction in a block of the tree.
+    ///
+    /// If several side chains include the transaction, the block inserted last is returned.
+    pub fn pending_transaction(&self, tx_hash: &TxHash) -> Option<TransactionMeta> {
+        self.index_tx_to_block.get(tx_hash).and_then(|blocks| blocks.last()).copied()
+    }
+
     /// Insert non fork block.
     pub fn insert_non_fork_block(
         &mut self,
-        block_number: BlockNumber,
-        block_hash: BlockHash,
+        block: &SealedBlockWithSenders,
         chain_id: BlockChainId,
     ) {
-        self.index_number_to_block.entry(block_number).or_default().insert(block_hash);
-        self.blocks_to_chain.insert(block_hash, chain_id);
+        self.index_number_to_block.entry(block.number).or_default().insert(block.hash());
+        self.blocks_to_chain.insert(block.hash(), chain_id);
+        self.insert_block_transactions(block);
     }
 
     /// Insert block to chain and fork child indices of the new chain
@@ -96,12 +128,57 @@ impl BlockIndices {
             self.blocks_to_chain.insert(block.hash(), chain_id);
             // add number -> block
             self.index_number_to_block.entry(*number).or_default().insert(block.hash());
+            // add transaction -> block
+            self.insert_block_transactions(block);
         }
         let first = chain.first();
         // add parent block -> block index
         self.fork_to_child.entry(first.parent_hash).or_default().insert(first.hash());
     }
 
+    fn insert_block_transactions(&mut self, block: &SealedBlockWithSenders) {
+        let block_hash = block.hash();
+        // the pending part of a split chain is inserted again
+        if self.block_to_txs.contains_key(&block_hash) {
+            return
+        }
+        let mut tx_hashes = Vec::with_capacity(block.body.len());
+        for (index, transaction) in block.body.iter().enumerate() {
+            let meta = TransactionMeta {
+                tx_hash: transaction.hash(),
+                index: index as u64,
+                block_hash,
+                block_number: block.number,
+            };
+            self.index_tx_to_block.entry(meta.tx_hash).or_default().push(meta);
+            tx_hashes.push(meta.tx_hash);
+        }
+        self.block_to_txs.insert(block_hash, tx_hashes);
+    }
+
+    fn remove_block_transactions(&mut self, block_hash: &BlockHash) {
+        for tx_hash in self.block_to_txs.remove(block_hash).unwrap_or_default() {
+            if let Entry::Occupied(mut entry) = self.index_tx_to_block.entry(tx_hash) {
+                let blocks = entry.get_mut();
+                blocks.retain(|meta| meta.block_hash != *block_hash);
+                // remove list if empty
+                if blocks.is_empty() {
+                    entry.remove();
+                }
+            }
+        }
+    }
+
+    /// Return the number of entries of each index.
+    pub fn sizes(&self) -> BlockIndicesSizes {
+        BlockIndicesSizes {
+            canonical_chain: self.canonical_chain.len(),
+            fork_to_child: self.fork_to_child.len(),
+            blocks_to_chain: self.blocks_to_chain.len(),
+            number_to_block: self.index_number_to_block.len(),
+        }
+    }
+
     /// Get the chain ID the block belongs to
     pub fn get_blocks_chain_id(&self, block: &BlockHash) -> Option<BlockChainId> {
         self.blocks_to_chain.get(block).cloned()
@@ -110,12 +187,20 @@ impl BlockIndices {
     /// Update all block hashes. iterate over present and new list of canonical hashes and compare
     /// them. Remove all missmatches, disconnect them and return all chains that needs to be
     /// removed.
+    ///
+    /// Alongside the chains to remove, returns the chains whose fork block is now part of the new
+    /// canonical chain at a different position than before, or wasn't canonical before. Those
+    /// chains connect to the new canonical chain and can be made canonical again.
     pub fn update_block_hashes(
         &mut self,
         hashes: BTreeMap<u64, BlockHash>,
-    ) -> BTreeSet<BlockChainId> {
+    ) -> (BTreeSet<BlockChainId>, BTreeSet<BlockChainId>) {
+        let old_chain = self.canonical_chain.inner();
+        let old_canonical: HashSet<BlockHash> = old_chain.values().copied().collect();
+        let new_canonical: HashSet<BlockHash> = hashes.values().copied().collect();
+
         let mut new_hashes = hashes.iter();
-        let mut old_hashes = self.canonical_chain().clone().into_iter();
+        let mut old_hashes = old_chain.iter().map(|(number, hash)| (*number, *hash));
 
         let mut remove = Vec::new();
 
@@ -158,31 +243,55 @@ impl BlockIndices {
                 }
             }
         }
-        self.canonical_chain = hashes;
+        self.canonical_chain.replace(hashes);
+
+        let mut moved = HashSet::new();
+        let mut removed_chains = BTreeSet::new();
+        for (number, hash) in remove {
+            if new_canonical.contains(&hash) {
+                // block is still canonical at a different position, chains forking from it
+                // connect to the new canonical chain.
+                moved.insert(hash);
+            } else {
+                removed_chains.extend(self.remove_block(number, hash));
+            }
+        }
 
-        remove.into_iter().fold(BTreeSet::new(), |mut fold, (number, hash)| {
-            fold.extend(self.remove_block(number, hash));
-            fold
-        })
+        let connectable_chains = self
+            .fork_to_child
+            .iter()
+            .filter(|(fork, _)| {
+                moved.contains(*fork) ||
+                    (new_canonical.contains(*fork) && !old_canonical.contains(*fork))
+            })
+            .flat_map(|(_, children)| children.iter())
+            .filter_map(|child| self.blocks_to_chain.get(child).copied())
+            .filter(|chain_id| !removed_chains.contains(chain_id))
+            .collect();
+
+        (removed_chains, connectable_chains)
     }
 
     /// Remove chain from indices and return dependent chains that needs to be removed.
     /// Does the cleaning of the tree and removing blocks from the chain.
+    ///
+    /// Chains that fork from a block of the removed chain that is canonical are kept, as they
+    /// still connect to the canonical chain.
     pub fn remove_chain(&mut self, chain: &Chain) -> BTreeSet<BlockChainId> {
         let mut lose_chains = BTreeSet::new();
         for (block_number, block) in chain.blocks().iter() {
             let block_hash = block.hash();
+            if self.canonical_hash(block_number) == Some(block_hash) {
+                self.remove_non_fork_block(*block_number, block_hash);
+                continue
+            }
             lose_chains.extend(self.remove_block(*block_number, block_hash))
         }
         lose_chains
     }
 
-    /// Remove Blocks from indices.
-    fn remove_block(
-        &mut self,
-        block_number: BlockNumber,
-        block_hash: BlockHash,
-    ) -> BTreeSet<BlockChainId> {
+    /// Remove block from the number and chain indices, keeping the chains that fork from it.
+    fn remove_non_fork_block(&mut self, block_number: BlockNumber, block_hash: BlockHash) {
         // rm number -> block
         if let Entry::Occupied(mut entry) = self.index_number_to_block.entry(block_number) {
             let set = entry.get_mut();
@@ -196,6 +305,18 @@ impl BlockIndices {
         // rm block -> chain_id
         self.blocks_to_chain.remove(&block_hash);
 
+        // rm transaction -> block
+        self.remove_block_transactions(&block_hash);
+    }
+
+    /// Remove Blocks from indices.
+    fn remove_block(
+        &mut self,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+    ) -> BTreeSet<BlockChainId> {
+        self.remove_non_fork_block(block_number, block_hash);
+
         // rm fork -> child
         let removed_fork = self.fork_to_child.remove(&block_hash);
         removed_fork
@@ -219,15 +340,15 @@ impl BlockIndices {
         // Remove all blocks from canonical chain
         let first_number = *blocks.first_key_value().unwrap().0;
 
-        // this will remove all blocks numbers that are going to be replaced.
-        self.canonical_chain.retain(|num, _| *num < first_number);
-
         // remove them from block to chain_id index
         blocks.iter().map(|(_, b)| (b.number, b.hash(), b.parent_hash)).for_each(
             |(number, hash, parent_hash)| {
                 // rm block -> chain_id
                 self.blocks_to_chain.remove(&hash);
 
+                // rm transaction -> block, the transactions are served from the database now
+                self.remove_block_transactions(&hash);
+
                 // rm number -> block
                 if let Entry::Occupied(mut entry) = self.index_number_to_block.entry(number) {
                     let set = entry.get_mut();
@@ -249,8 +370,12 @@ impl BlockIndices {
             },
         );
 
-        // insert new canonical
-        self.canonical_chain.extend(blocks.iter().map(|(number, block)| (*number, block.hash())))
+        self.canonical_chain.update(|canonical_chain| {
+            // this will remove all blocks numbers that are going to be replaced.
+            canonical_chain.retain(|num, _| *num < first_number);
+            // insert new canonical
+            canonical_chain.extend(blocks.iter().map(|(number, block)| (*number, block.hash())))
+        })
     }
 
     /// Used for finalization of block.
@@ -263,15 +388,18 @@ impl BlockIndices {
         // Dont remove finalized_block, as sidechain can point to it.
         let finalized_blocks: Vec<BlockHash> = self
             .canonical_chain
+            .inner()
             .iter()
             .filter(|(&number, _)| number >= self.last_finalized_block && number < finalized_block)
             .map(|(_, hash)| *hash)
             .collect();
 
-        // remove unneeded canonical hashes.
-        let remove_until =
-            finalized_block.saturating_sub(self.num_of_additional_canonical_block_hashes);
-        self.canonical_chain.retain(|&number, _| number >= remove_until);
+        // remove unneeded canonical hashes, the canonical tip is always kept.
+        let remove_until = finalized_block
+            .saturating_sub(self.num_of_additional_canonical_block_hashes)
+            .min(self.canonical_tip().number);
+        self.canonical_chain
+            .update(|canonical_chain| canonical_chain.retain(|&number, _| number >= remove_until));
 
         let mut lose_chains = BTreeSet::new();
 
@@ -295,18 +423,149 @@ impl BlockIndices {
 
     /// get canonical hash
     pub fn canonical_hash(&self, block_number: &BlockNumber) -> Option<BlockHash> {
-        self.canonical_chain.get(block_number).cloned()
+        self.canonical_chain.canonical_hash(block_number)
     }
 
     /// get canonical tip
     pub fn canonical_tip(&self) -> ForkBlock {
-        let (&number, &hash) =
-            self.canonical_chain.last_key_value().expect("There is always the canonical chain");
-        ForkBlock { number, hash }
+        self.try_canonical_tip().expect("Canonical chain always contains its anchor block")
+    }
+
+    /// get canonical tip, or `None` if the canonical chain is empty.
+    pub fn try_canonical_tip(&self) -> Option<ForkBlock> {
+        self.canonical_chain.tip().map(|(number, hash)| ForkBlock { number, hash })
     }
 
     /// Canonical chain needs for execution of EVM. It should contains last 256 block hashes.
-    pub fn canonical_chain(&self) -> &BTreeMap<BlockNumber, BlockHash> {
+    ///
+    /// The returned handle can be cloned and read without access to the indices.
+    pub fn canonical_chain(&self) -> &CanonicalChain {
         &self.canonical_chain
     }
+
+    /// Returns the canonical hashes of the blocks in `from..=to` that are inside the window.
+    pub fn canonical_hashes_range(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Vec<(BlockNumber, BlockHash)> {
+        self.canonical_chain
+            .inner()
+            .range(from..=to)
+            .map(|(number, hash)| (*number, *hash))
+            .collect()
+    }
+
+    /// Returns a snapshot of the canonical chain.
+    ///
+    /// The snapshot is shared until the canonical chain changes, so executing a block against it
+    /// doesn't need to borrow the indices.
+    pub fn canonical_hashes_snapshot(&self) -> Arc<BTreeMap<BlockNumber, BlockHash>> {
+        self.canonical_chain.inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution_result::ExecutionResult;
+    use reth_interfaces::test_utils::generators::random_signed_tx;
+    use reth_primitives::H256;
+
+    fn block(number: BlockNumber, hash: u8, parent: u8) -> SealedBlockWithSenders {
+        let mut block = SealedBlockWithSenders::default();
+        block.block.header.hash = H256([hash; 32]);
+        block.block.header.header.number = number;
+        block.block.header.header.parent_hash = H256([parent; 32]);
+        block
+    }
+
+    fn chain(blocks: Vec<SealedBlockWithSenders>) -> Chain {
+        Chain::new(blocks.into_iter().map(|block| (block, ExecutionResult::default())).collect())
+    }
+
+    fn canonical(hashes: &[(BlockNumber, u8)]) -> BTreeMap<BlockNumber, BlockHash> {
+        hashes.iter().map(|(number, hash)| (*number, H256([*hash; 32]))).collect()
+    }
+
+    #[test]
+    fn restored_canonical_chain_keeps_side_chain() {
+        let mut indices = BlockIndices::new(0, 256, canonical(&[(1, 1), (2, 2), (3, 3)])).unwrap();
+
+        // previous canonical blocks 2a and 3a were reverted into the tree, 4b forks from 3a.
+        let reverted = chain(vec![block(2, 0x2a, 1), block(3, 0x3a, 0x2a)]);
+        indices.insert_chain(0, &reverted);
+        indices.insert_chain(1, &chain(vec![block(4, 0x4b, 0x3a)]));
+
+        // reverted blocks are canonical again
+        let (removed, connectable) =
+            indices.update_block_hashes(canonical(&[(1, 1), (2, 0x2a), (3, 0x3a)]));
+        assert!(removed.is_empty());
+        assert_eq!(connectable, BTreeSet::from([1]));
+
+        // discarding the now canonical chain keeps the chain that forks from it
+        assert!(indices.remove_chain(&reverted).is_empty());
+        assert_eq!(indices.get_blocks_chain_id(&H256([0x4b; 32])), Some(1));
+        assert_eq!(
+            indices.fork_to_child().get(&H256([0x3a; 32])),
+            Some(&HashSet::from([H256([0x4b; 32])]))
+        );
+    }
+
+    #[test]
+    fn canonical_block_at_different_position() {
+        let mut indices = BlockIndices::new(0, 256, canonical(&[(1, 1), (2, 2), (3, 3)])).unwrap();
+        // 4b forks from 3, 3c forks from 2
+        indices.insert_chain(1, &chain(vec![block(4, 0x4b, 3)]));
+        indices.insert_chain(2, &chain(vec![block(3, 0x3c, 2)]));
+
+        let (removed, connectable) =
+            indices.update_block_hashes(canonical(&[(1, 1), (2, 0x20), (3, 0x30), (4, 3)]));
+        assert_eq!(removed, BTreeSet::from([2]));
+        assert_eq!(connectable, BTreeSet::from([1]));
+        assert_eq!(indices.get_blocks_chain_id(&H256([0x4b; 32])), Some(1));
+    }
+
+    #[test]
+    fn indexes_transactions_of_tree_blocks() {
+        let mut indices = BlockIndices::new(0, 256, canonical(&[(1, 1)])).unwrap();
+        let tx = random_signed_tx();
+        let mut side = block(2, 0x2a, 1);
+        side.block.body = vec![random_signed_tx(), tx.clone()];
+        let mut other = block(2, 0x2b, 1);
+        other.block.body = vec![tx.clone()];
+        let mut tip = block(3, 0x3b, 0x2b);
+        tip.block.body = vec![random_signed_tx()];
+
+        indices.insert_chain(0, &chain(vec![side.clone()]));
+        indices.insert_chain(1, &chain(vec![other.clone()]));
+        indices.insert_non_fork_block(&tip, 1);
+        let meta = indices.pending_transaction(&tx.hash()).unwrap();
+        assert_eq!((meta.block_hash, meta.block_number, meta.index), (other.hash(), 2, 0));
+        assert!(indices.pending_transaction(&tip.body[0].hash()).is_some());
+
+        // the transaction is still in the side chain once the other block is canonical
+        indices.canonicalize_blocks(&BTreeMap::from([(2, other), (3, tip.clone())]));
+        let meta = indices.pending_transaction(&tx.hash()).unwrap();
+        assert_eq!((meta.block_hash, meta.index), (side.hash(), 1));
+        assert_eq!(indices.pending_transaction(&tip.body[0].hash()), None);
+
+        indices.remove_chain(&chain(vec![side]));
+        assert_eq!(indices.pending_transaction(&tx.hash()), None);
+    }
+
+    #[test]
+    fn canonical_chain_is_never_empty() {
+        assert_eq!(
+            BlockIndices::new(0, 256, BTreeMap::new()).err(),
+            Some(ExecError::EmptyCanonicalChain)
+        );
+
+        let mut indices = BlockIndices::new(0, 1, canonical(&[(1, 1), (2, 2), (3, 3)])).unwrap();
+        // finalizing past the tip keeps the tip as anchor
+        indices.finalize_canonical_blocks(10);
+        assert_eq!(*indices.canonical_chain().inner(), canonical(&[(3, 3)]));
+        let tip = indices.try_canonical_tip().unwrap();
+        assert_eq!((tip.number, tip.hash), (3, H256([3; 32])));
+    }
 }
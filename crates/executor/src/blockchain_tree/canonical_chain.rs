@@ -0,0 +1,97 @@
+//! Implementation of [`CanonicalChain`] related to [`super::BlockchainTree`]
+
+use parking_lot::RwLock;
+use reth_primitives::{BlockHash, BlockNumber};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Window of canonical block hashes kept by the tree.
+///
+/// Clones share the same window. Mutations build a new map and swap it in, so readers can take
+/// cheap [CanonicalChain::inner] snapshots without holding the tree lock, and a snapshot is never
+/// a partially updated window.
+#[derive(Debug, Clone, Default)]
+pub struct CanonicalChain {
+    chain: Arc<RwLock<Arc<BTreeMap<BlockNumber, BlockHash>>>>,
+}
+
+impl CanonicalChain {
+    /// Create new canonical chain from the given block hashes.
+    pub fn new(chain: BTreeMap<BlockNumber, BlockHash>) -> Self {
+        Self { chain: Arc::new(RwLock::new(Arc::new(chain))) }
+    }
+
+    /// Returns a snapshot of the current canonical block hashes.
+    pub fn inner(&self) -> Arc<BTreeMap<BlockNumber, BlockHash>> {
+        Arc::clone(&self.chain.read())
+    }
+
+    /// Returns the canonical hash of the block number if it is inside the window.
+    pub fn canonical_hash(&self, block_number: &BlockNumber) -> Option<BlockHash> {
+        self.chain.read().get(block_number).cloned()
+    }
+
+    /// Returns the number and hash of the highest canonical block.
+    pub fn tip(&self) -> Option<(BlockNumber, BlockHash)> {
+        self.chain.read().last_key_value().map(|(&number, &hash)| (number, hash))
+    }
+
+    /// Returns the number of canonical block hashes in the window.
+    pub fn len(&self) -> usize {
+        self.chain.read().len()
+    }
+
+    /// Returns `true` if the window is empty.
+    pub fn is_empty(&self) -> bool {
+        self.chain.read().is_empty()
+    }
+
+    /// Replace the canonical block hashes.
+    pub(crate) fn replace(&self, chain: BTreeMap<BlockNumber, BlockHash>) {
+        *self.chain.write() = Arc::new(chain);
+    }
+
+    /// Apply the update to a copy of the canonical block hashes and swap it in.
+    pub(crate) fn update(&self, f: impl FnOnce(&mut BTreeMap<BlockNumber, BlockHash>)) {
+        let mut chain = BTreeMap::clone(&self.inner());
+        f(&mut chain);
+        self.replace(chain);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::H256;
+
+    fn window(generation: u64) -> BTreeMap<BlockNumber, BlockHash> {
+        (generation..generation + 16)
+            .map(|number| (number, H256::from_low_u64_be(generation)))
+            .collect()
+    }
+
+    #[test]
+    fn snapshots_are_never_partially_updated() {
+        let chain = CanonicalChain::new(window(0));
+
+        let reader = {
+            let chain = chain.clone();
+            std::thread::spawn(move || {
+                for _ in 0..10_000 {
+                    let snapshot = chain.inner();
+                    let (&first, _) = snapshot.first_key_value().unwrap();
+                    assert_eq!(*snapshot, window(first));
+                }
+            })
+        };
+
+        for generation in 1..1_000 {
+            chain.update(|chain| {
+                chain.retain(|&number, _| number >= generation);
+                chain.extend(window(generation));
+            });
+        }
+        reader.join().unwrap();
+
+        assert_eq!(*chain.inner(), window(999));
+    }
+}
@@ -2,11 +2,19 @@
 //! have functions to split, branch and append the chain.
 use crate::{
     execution_result::ExecutionResult,
+    executor::verify_post_execution,
     substate::{SubStateData, SubStateWithProvider},
 };
-use reth_interfaces::{consensus::Consensus, executor::Error as ExecError, Error};
+use reth_interfaces::{
+    consensus::{Consensus, ConsensusError},
+    executor::Error as ExecError,
+    Error,
+};
 use reth_primitives::{BlockHash, BlockNumber, SealedBlockWithSenders, SealedHeader, U256};
-use reth_provider::{BlockExecutor, ExecutorFactory, StateProvider};
+use reth_provider::{
+    trie::{HashedPostState, PrefetchHandle},
+    BlockExecutor, ExecutorFactory, StateProvider,
+};
 use std::collections::BTreeMap;
 
 /// Internal to BlockchainTree chain identification.
@@ -15,7 +23,10 @@ pub(crate) type BlockChainId = u64;
 /// Side chain that contain it state and connect to block found in canonical chain.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Chain {
-    /// Chain substate. Updated state after execution all blocks in chain.
+    /// Chain substate. Updated state after execution of the blocks in chain.
+    ///
+    /// Only holds the changes of the blocks of this chain, a chain that forks from another side
+    /// chain doesn't copy the state of the shared blocks.
     substate: SubStateData,
     /// Changesets for block and transaction. Will be used to update tables in database.
     changesets: Vec<ExecutionResult>,
@@ -24,7 +35,7 @@ pub struct Chain {
 }
 
 /// Contains fork block and hash.
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
 pub struct ForkBlock {
     /// Block number of block that chains branches from
     pub number: u64,
@@ -52,6 +63,11 @@ impl Chain {
         (self.blocks, self.changesets, self.substate)
     }
 
+    /// Return the substate of the blocks of this chain.
+    pub fn substate(&self) -> &SubStateData {
+        &self.substate
+    }
+
     /// Return execution results of blocks
     pub fn changesets(&self) -> &Vec<ExecutionResult> {
         &self.changesets
@@ -107,6 +123,7 @@ impl Chain {
         parent_header: &SealedHeader,
         canonical_block_hashes: &BTreeMap<BlockNumber, BlockHash>,
         provider: &SP,
+        prefetch: Option<PrefetchHandle>,
         consensus: &C,
         factory: &EF,
     ) -> Result<Self, Error> {
@@ -121,6 +138,9 @@ impl Chain {
             block.clone(),
             parent_header,
             substate_with_sp,
+            provider,
+            HashedPostState::default(),
+            prefetch,
             consensus,
             factory,
         )?;
@@ -129,12 +149,20 @@ impl Chain {
     }
 
     /// Create new chain that branches out from existing side chain.
+    ///
+    /// The parent substate and post state contain the changes of all side chain blocks up to the
+    /// parent block. The new chain only keeps the changes of the new block, the shared blocks stay
+    /// in the existing chains.
+    #[allow(clippy::too_many_arguments)]
     pub fn new_chain_fork<SP: StateProvider, C: Consensus, EF: ExecutorFactory>(
         &self,
         block: SealedBlockWithSenders,
+        parent_substate: &SubStateData,
         side_chain_block_hashes: BTreeMap<BlockNumber, BlockHash>,
         canonical_block_hashes: &BTreeMap<BlockNumber, BlockHash>,
         provider: &SP,
+        parent_post_state: HashedPostState,
+        prefetch: Option<PrefetchHandle>,
         consensus: &C,
         factory: &EF,
     ) -> Result<Self, Error> {
@@ -144,15 +172,8 @@ impl Chain {
             .get(&parent_number)
             .ok_or(ExecError::BlockNumberNotFoundInChain { block_number: parent_number })?;
 
-        // revert changesets
-        let revert_from = self.changesets.len() - (self.tip().number - parent.number) as usize;
-        let mut substate = self.substate.clone();
-
-        // Revert changesets to get the state of the parent that we need to apply the change.
-        substate.revert(&self.changesets[revert_from..]);
-
         let substate_with_sp = SubStateWithProvider::new(
-            &substate,
+            parent_substate,
             provider,
             &side_chain_block_hashes,
             canonical_block_hashes,
@@ -161,26 +182,30 @@ impl Chain {
             block.clone(),
             parent,
             substate_with_sp,
+            provider,
+            parent_post_state,
+            prefetch,
             consensus,
             factory,
         )?;
-        substate.apply_one(&changeset);
-
-        let chain = Self {
-            substate,
-            changesets: vec![changeset],
-            blocks: BTreeMap::from([(block.number, block)]),
-        };
 
         // if all is okay, return new chain back. Present chain is not modified.
-        Ok(chain)
+        Ok(Self::new(vec![(block, changeset)]))
     }
 
     /// Validate and execute block and return execution result or error.
+    ///
+    /// The state root of the block is computed from the state of the provider with the changes of
+    /// the parent post state and of the block on top. With a prefetch handle, the nodes on the
+    /// paths of the keys touched during execution are loaded ahead of the state root computation.
+    #[allow(clippy::too_many_arguments)]
     fn validate_and_execute<SP: StateProvider, C: Consensus, EF: ExecutorFactory>(
         block: SealedBlockWithSenders,
         parent_block: &SealedHeader,
-        substate: SubStateWithProvider<'_, SP>,
+        substate: SubStateWithProvider<'_, &SP>,
+        provider: &SP,
+        parent_post_state: HashedPostState,
+        prefetch: Option<PrefetchHandle>,
         consensus: &C,
         factory: &EF,
     ) -> Result<ExecutionResult, Error> {
@@ -190,21 +215,53 @@ impl Chain {
 
         let (unseal, senders) = block.into_components();
         let unseal = unseal.unseal();
-        let res = factory.with_sp(substate).execute_and_verify_receipt(
-            &unseal,
-            U256::MAX,
-            Some(senders),
+        let mut executor = match prefetch.clone() {
+            Some(prefetch) => factory.with_sp_and_prefetcher(substate, prefetch),
+            None => factory.with_sp(substate),
+        };
+        let res = executor.execute_and_verify_receipt(&unseal, U256::MAX, Some(senders))?;
+        verify_post_execution(
+            &unseal.header,
+            parent_block,
+            factory.chain_spec(),
+            res.tx_changesets.iter().map(|changeset| &changeset.receipt),
         )?;
+
+        let mut post_state = parent_post_state;
+        post_state.extend(HashedPostState::from_execution_results(
+            std::slice::from_ref(&res),
+            unseal.number,
+            factory.chain_spec(),
+        ));
+        let state_root = match &prefetch {
+            Some(prefetch) => provider.state_root_with_cache(post_state, prefetch.cache())?,
+            None => provider.state_root(post_state)?,
+        };
+        if state_root != unseal.state_root {
+            return Err(ConsensusError::BodyStateRootDiff {
+                got: state_root,
+                expected: unseal.state_root,
+            }
+            .into())
+        }
         Ok(res)
     }
 
     /// Append block to this chain
+    ///
+    /// The parent substate and post state contain the changes of all side chain blocks up to the
+    /// tip. Without a parent substate, the substate of this chain is used, which is only complete
+    /// if the chain joins the canonical chain.
+    #[allow(clippy::too_many_arguments)]
     pub fn append_block<SP: StateProvider, C: Consensus, EF: ExecutorFactory>(
         &mut self,
         block: SealedBlockWithSenders,
+        parent_substate: Option<&SubStateData>,
         side_chain_block_hashes: BTreeMap<BlockNumber, BlockHash>,
         canonical_block_hashes: &BTreeMap<BlockNumber, BlockHash>,
         provider: &SP,
+        parent_post_state: HashedPostState,
+        prefetch: Option<PrefetchHandle>,
         consensus: &C,
         factory: &EF,
     ) -> Result<(), Error> {
@@ -214,11 +271,14 @@ impl Chain {
             block.clone(),
             parent_block,
             SubStateWithProvider::new(
-                &self.substate,
+                parent_substate.unwrap_or(&self.substate),
                 provider,
                 &side_chain_block_hashes,
                 canonical_block_hashes,
             ),
+            provider,
+            parent_post_state,
+            prefetch,
             consensus,
             factory,
         )?;
@@ -229,7 +289,7 @@ impl Chain {
     }
 
     /// Merge two chains into one by appending received chain to the current one.
-    /// Take substate from newest one.
+    /// Apply the changes of the received chain on top of the current substate.
     pub fn append_chain(&mut self, chain: Chain) -> Result<(), Error> {
         let chain_tip = self.tip();
         if chain_tip.hash != chain.fork_block_hash() {
@@ -239,9 +299,9 @@ impl Chain {
             }
             .into())
         }
+        self.substate.apply(&chain.changesets);
         self.blocks.extend(chain.blocks.into_iter());
         self.changesets.extend(chain.changesets.into_iter());
-        self.substate = chain.substate;
         Ok(())
     }
 
@@ -263,7 +323,9 @@ impl Chain {
                         None
                     }
                 });
-                let Some(block_number) = block_number else { return ChainSplit::NoSplitPending(self)};
+                let Some(block_number) = block_number else {
+                    return ChainSplit::NoSplitPending(self)
+                };
                 // If block number is same as tip whole chain is becoming canonical.
                 if block_number == chain_tip {
                     return ChainSplit::NoSplitCanonical(self)
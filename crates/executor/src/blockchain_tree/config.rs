@@ -0,0 +1,138 @@
+//! Configuration of the [`BlockchainTree`](super::BlockchainTree)
+use reth_primitives::ChainSpec;
+use tracing::warn;
+
+/// The configuration of the blockchain tree.
+///
+/// The tree keeps the canonical block hashes of the reorg window and, below it, the block hashes
+/// the `BLOCKHASH` opcode of the chain can access, so that blocks of side chains can be executed
+/// without reading block hashes from the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockchainTreeConfig {
+    /// Finalization window. Number of blocks that can be reorged
+    max_reorg_depth: u64,
+    /// Number of blocks after the finalized block that are kept in the tree. It should be more
+    /// than the finalization window
+    max_blocks_in_chain: u64,
+    /// Number of canonical block hashes kept below the finalization window
+    num_of_additional_canonical_block_hashes: u64,
+}
+
+impl BlockchainTreeConfig {
+    /// Creates a config that keeps the block hashes of the `BLOCKHASH` window of the chain below
+    /// the finalization window.
+    pub fn new(
+        chain_spec: &ChainSpec,
+        max_reorg_depth: u64,
+        max_blocks_in_chain: u64,
+    ) -> Result<Self, BlockchainTreeConfigError> {
+        Self::with_additional_canonical_block_hashes(
+            chain_spec,
+            max_reorg_depth,
+            max_blocks_in_chain,
+            chain_spec.block_hash_window(),
+        )
+    }
+
+    /// Creates a config that keeps a custom number of canonical block hashes below the
+    /// finalization window.
+    ///
+    /// Fewer hashes than the `BLOCKHASH` window of the chain are accepted with a warning, the
+    /// older hashes are then read from the database.
+    pub fn with_additional_canonical_block_hashes(
+        chain_spec: &ChainSpec,
+        max_reorg_depth: u64,
+        max_blocks_in_chain: u64,
+        num_of_additional_canonical_block_hashes: u64,
+    ) -> Result<Self, BlockchainTreeConfigError> {
+        if max_reorg_depth > max_blocks_in_chain {
+            return Err(BlockchainTreeConfigError::ReorgDepthAboveChainSize {
+                max_reorg_depth,
+                max_blocks_in_chain,
+            })
+        }
+        if num_of_additional_canonical_block_hashes == 0 {
+            return Err(BlockchainTreeConfigError::NoAdditionalCanonicalBlockHashes)
+        }
+        let block_hash_window = chain_spec.block_hash_window();
+        if num_of_additional_canonical_block_hashes < block_hash_window {
+            warn!(
+                target: "blockchain_tree",
+                num_of_additional_canonical_block_hashes,
+                block_hash_window,
+                "The tree keeps fewer canonical block hashes than the BLOCKHASH window"
+            );
+        }
+        Ok(Self { max_reorg_depth, max_blocks_in_chain, num_of_additional_canonical_block_hashes })
+    }
+
+    /// Returns the finalization window, the number of blocks that can be reorged.
+    pub fn max_reorg_depth(&self) -> u64 {
+        self.max_reorg_depth
+    }
+
+    /// Returns the number of blocks after the finalized block that are kept in the tree.
+    pub fn max_blocks_in_chain(&self) -> u64 {
+        self.max_blocks_in_chain
+    }
+
+    /// Returns the number of canonical block hashes kept below the finalization window.
+    pub fn num_of_additional_canonical_block_hashes(&self) -> u64 {
+        self.num_of_additional_canonical_block_hashes
+    }
+
+    /// Returns the number of canonical block hashes kept by the tree, for ethereum that would be
+    /// 64 + 256.
+    pub fn num_of_canonical_hashes(&self) -> u64 {
+        self.max_reorg_depth + self.num_of_additional_canonical_block_hashes
+    }
+}
+
+/// Errors of an invalid [BlockchainTreeConfig].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BlockchainTreeConfigError {
+    /// The reorg window does not fit into the blocks kept in the tree.
+    #[error("Reorg depth {max_reorg_depth} exceeds max blocks in chain {max_blocks_in_chain}")]
+    ReorgDepthAboveChainSize {
+        /// The configured reorg window
+        max_reorg_depth: u64,
+        /// The configured number of blocks kept in the tree
+        max_blocks_in_chain: u64,
+    },
+    /// No canonical block hashes below the reorg window would be kept.
+    #[error("The number of additional canonical block hashes must not be zero")]
+    NoAdditionalCanonicalBlockHashes,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{BLOCKHASH_WINDOW, MAINNET};
+
+    #[test]
+    fn derived_from_chain_spec() {
+        let config = BlockchainTreeConfig::new(&MAINNET, 64, 128).unwrap();
+        assert_eq!(config.num_of_additional_canonical_block_hashes(), BLOCKHASH_WINDOW);
+        assert_eq!(config.num_of_canonical_hashes(), 64 + 256);
+    }
+
+    #[test]
+    fn rejects_invalid() {
+        assert_eq!(
+            BlockchainTreeConfig::with_additional_canonical_block_hashes(&MAINNET, 64, 128, 0),
+            Err(BlockchainTreeConfigError::NoAdditionalCanonicalBlockHashes)
+        );
+        assert_eq!(
+            BlockchainTreeConfig::new(&MAINNET, 64, 32),
+            Err(BlockchainTreeConfigError::ReorgDepthAboveChainSize {
+                max_reorg_depth: 64,
+                max_blocks_in_chain: 32
+            })
+        );
+        // fewer hashes than the window are only warned about
+        let config =
+            BlockchainTreeConfig::with_additional_canonical_block_hashes(&MAINNET, 1, 2, 3)
+                .unwrap();
+        assert_eq!(config.num_of_canonical_hashes(), 4);
+    }
+}
@@ -1,23 +1,47 @@
 //! Implementation of [`BlockchainTree`]
 pub mod block_indices;
+pub mod canonical_chain;
 pub mod chain;
+pub mod config;
+pub mod pending;
+pub mod reorgs;
+pub mod shareable;
 
 use self::{
     block_indices::BlockIndices,
+    canonical_chain::CanonicalChain,
     chain::{ChainSplit, SplitAt},
+    config::BlockchainTreeConfig,
+    pending::PendingBlockState,
+    reorgs::ReorgHistory,
 };
+use crate::{execution_result::ExecutionResult, executor::recover_signers, substate::SubStateData};
 use chain::{BlockChainId, Chain, ForkBlock};
 use reth_db::{cursor::DbCursorRO, database::Database, tables, transaction::DbTx};
-use reth_interfaces::{consensus::Consensus, executor::Error as ExecError, Error};
-use reth_primitives::{BlockHash, BlockNumber, ChainSpec, SealedBlock, SealedBlockWithSenders};
+use reth_interfaces::{
+    blockchain_tree::{BlockchainTreeState, ReorgEvent, ReorgStats, SideChainState, TreeBlock},
+    consensus::Consensus,
+    executor::Error as ExecError,
+    Error,
+};
+use reth_metrics_common::execution::ExecutionMetrics;
+use reth_primitives::{
+    BlockHash, BlockNumber, ChainSpec, SealedBlock, SealedBlockWithSenders, SealedHeader,
+    TransactionMeta, TransactionSignedEcRecovered, TxHash, U256,
+};
 use reth_provider::{
-    providers::ChainState, ExecutorFactory, HeaderProvider, ShareableDatabase,
-    StateProviderFactory, Transaction,
+    providers::ChainState,
+    recover_canonical_commit,
+    trie::{HashedPostState, PrefetchHandle, TriePrefetcher, DEFAULT_PARALLEL_THRESHOLD},
+    BytecodeCache, CanonicalRecovery, CanonicalTip, ExecutorFactory, HeaderProvider,
+    ShareableDatabase, StateProviderFactory, Transaction,
 };
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
+use tracing::warn;
 
 #[cfg_attr(doc, aquamarine::aquamarine)]
 /// Tree of chains and its identifications.
@@ -65,11 +89,22 @@ pub struct BlockchainTree<DB: Database, C: Consensus, EF: ExecutorFactory> {
     block_chain_id_generator: u64,
     /// Indices to block and their connection.
     block_indices: BlockIndices,
-    /// Number of block after finalized block that we are storing. It should be more then
-    /// finalization window
-    max_blocks_in_chain: u64,
-    /// Finalization windows. Number of blocks that can be reorged
-    max_reorg_depth: u64,
+    /// The reorg window and the number of blocks and canonical block hashes kept in the tree.
+    config: BlockchainTreeConfig,
+    /// Whether trie nodes are prefetched in the background while blocks are executed.
+    trie_prefetch: bool,
+    /// The throughput metrics of the inserted blocks, their execution time includes the validation
+    /// and the state root computation.
+    metrics: ExecutionMetrics,
+    /// The tip of the canonical chain shared with the rpc, updated whenever blocks become
+    /// canonical.
+    canonical_tip: Option<CanonicalTip>,
+    /// The most recent reorgs of the canonical chain.
+    reorgs: ReorgHistory,
+    /// Whether canonical commits fail after the blocks are written and before the checkpoints are
+    /// updated, to simulate a crash.
+    #[cfg(any(test, feature = "failpoints"))]
+    fail_canonical_commit: bool,
     /// Externals
     externals: Externals<DB, C, EF>,
 }
@@ -84,12 +119,32 @@ struct Externals<DB: Database, C: Consensus, EF: ExecutorFactory> {
     executor_factory: EF,
     /// Chain spec
     chain_spec: Arc<ChainSpec>,
+    /// The cache of the contract codes read during execution.
+    bytecode_cache: Option<BytecodeCache>,
 }
 
 impl<DB: Database, C: Consensus, EF: ExecutorFactory> Externals<DB, C, EF> {
     /// Return sharable database helper structure.
+    ///
+    /// The state roots of blocks on top of the canonical tip are computed in parallel if enough
+    /// keys changed.
     fn sharable_db(&self) -> ShareableDatabase<&DB> {
-        ShareableDatabase::new(&self.db, self.chain_spec.clone())
+        let db = ShareableDatabase::new(&self.db, self.chain_spec.clone())
+            .with_parallel_state_root(DEFAULT_PARALLEL_THRESHOLD);
+        match self.bytecode_cache {
+            Some(ref bytecode_cache) => db.with_bytecode_cache(bytecode_cache.clone()),
+            None => db,
+        }
+    }
+
+    /// Runs the closure with a handle to a trie prefetcher over the database if prefetching is
+    /// enabled.
+    fn with_prefetcher<T>(&self, enabled: bool, f: impl FnOnce(Option<PrefetchHandle>) -> T) -> T {
+        if enabled {
+            TriePrefetcher::new(&self.db).run(|prefetch| f(Some(prefetch)))
+        } else {
+            f(None)
+        }
     }
 }
 
@@ -108,19 +163,26 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
         consensus: C,
         executor_factory: EF,
         chain_spec: Arc<ChainSpec>,
-        max_reorg_depth: u64,
-        max_blocks_in_chain: u64,
-        num_of_additional_canonical_block_hashes: u64,
+        config: BlockchainTreeConfig,
     ) -> Result<Self, Error> {
-        if max_reorg_depth > max_blocks_in_chain {
-            panic!("Side chain size should be more then finalization window");
+        let max_reorg_depth = config.max_reorg_depth();
+
+        // repair a canonical commit that was interrupted by a crash before the tip is read
+        {
+            let mut tx = Transaction::new(&db)?;
+            let recovery = recover_canonical_commit(&tx, &chain_spec)
+                .map_err(|e| ExecError::CanonicalRecovery { inner: e.to_string() })?;
+            if recovery != CanonicalRecovery::Consistent {
+                warn!(target: "blockchain_tree", ?recovery, "Recovered partial canonical commit");
+                tx.commit()?;
+            }
         }
 
         let last_canonical_hashes = db
             .tx()?
             .cursor_read::<tables::CanonicalHeaders>()?
             .walk_back(None)?
-            .take((max_reorg_depth + num_of_additional_canonical_block_hashes) as usize)
+            .take(config.num_of_canonical_hashes() as usize)
             .collect::<Result<Vec<(BlockNumber, BlockHash)>, _>>()?;
 
         // TODO(rakita) save last finalized block inside database but for now just take
@@ -134,7 +196,8 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
                 last_canonical_hashes.last().cloned().unwrap_or_default()
             };
 
-        let externals = Externals { db, consensus, executor_factory, chain_spec };
+        let externals =
+            Externals { db, consensus, executor_factory, chain_spec, bytecode_cache: None };
 
         Ok(Self {
             externals,
@@ -142,14 +205,167 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
             chains: Default::default(),
             block_indices: BlockIndices::new(
                 last_finalized_block_number,
-                num_of_additional_canonical_block_hashes,
+                config.num_of_additional_canonical_block_hashes(),
                 BTreeMap::from_iter(last_canonical_hashes.into_iter()),
-            ),
-            max_blocks_in_chain,
-            max_reorg_depth,
+            )?,
+            config,
+            trie_prefetch: false,
+            metrics: ExecutionMetrics::new("blockchain_tree.execution"),
+            canonical_tip: None,
+            reorgs: Default::default(),
+            #[cfg(any(test, feature = "failpoints"))]
+            fail_canonical_commit: false,
+        })
+    }
+
+    /// Makes the following canonical commits fail after the blocks are written and before the
+    /// checkpoints are updated, nothing of the commit is persisted.
+    #[cfg(any(test, feature = "failpoints"))]
+    pub fn set_canonical_commit_failpoint(&mut self, enabled: bool) {
+        self.fail_canonical_commit = enabled;
+    }
+
+    /// Returns the config of the tree.
+    pub fn config(&self) -> &BlockchainTreeConfig {
+        &self.config
+    }
+
+    /// Returns a handle to the canonical block hashes of the tree, that can be read concurrently
+    /// without holding the tree.
+    pub fn canonical_chain(&self) -> CanonicalChain {
+        self.block_indices.canonical_chain().clone()
+    }
+
+    /// Enables prefetching of the trie nodes touched by a block while it is executed, so that they
+    /// are cached by the time its state root is computed.
+    pub fn with_trie_prefetch(mut self, trie_prefetch: bool) -> Self {
+        self.trie_prefetch = trie_prefetch;
+        self
+    }
+
+    /// Reads the contract codes of the executed blocks through the given cache, e.g. a cache
+    /// shared with the rpc handlers.
+    pub fn with_bytecode_cache(mut self, bytecode_cache: BytecodeCache) -> Self {
+        self.externals.bytecode_cache = Some(bytecode_cache);
+        self
+    }
+
+    /// Sets the given [CanonicalTip] to the canonical tip of the tree and updates it whenever
+    /// blocks are committed to the canonical chain.
+    pub fn with_canonical_tip(mut self, canonical_tip: CanonicalTip) -> Self {
+        let tip = self.block_indices.canonical_tip();
+        canonical_tip.update(tip.number, tip.hash);
+        self.canonical_tip = Some(canonical_tip);
+        self
+    }
+
+    /// Sets the execution time above which a block is logged as slow.
+    pub fn with_slow_block_threshold(mut self, threshold: Duration) -> Self {
+        self.metrics = self.metrics.with_slow_block_threshold(threshold);
+        self
+    }
+
+    /// Returns the most recent reorgs of the canonical chain.
+    pub fn reorg_stats(&self) -> ReorgStats {
+        self.reorgs.stats()
+    }
+
+    /// Returns the transaction with the given hash from a block of the tree, and the location of
+    /// the transaction in the block.
+    pub fn pending_transaction(
+        &self,
+        tx_hash: &TxHash,
+    ) -> Option<(TransactionSignedEcRecovered, TransactionMeta)> {
+        let meta = self.block_indices.pending_transaction(tx_hash)?;
+        let chain_id = self.block_indices.get_blocks_chain_id(&meta.block_hash)?;
+        let block = self.chains.get(&chain_id)?.blocks().get(&meta.block_number)?;
+        let index = meta.index as usize;
+        let transaction = block.body.get(index)?.clone();
+        let signer = *block.senders.get(index)?;
+        Some((TransactionSignedEcRecovered::from_signed_transaction(transaction, signer), meta))
+    }
+
+    /// Returns the block with the given hash if it is in one of the side chains of the tree.
+    pub fn block_by_hash(&self, block_hash: BlockHash) -> Option<&SealedBlockWithSenders> {
+        let chain_id = self.block_indices.get_blocks_chain_id(&block_hash)?;
+        self.chains.get(&chain_id)?.blocks().values().find(|block| block.hash() == block_hash)
+    }
+
+    /// Returns the block with the given hash if it is in one of the side chains of the tree,
+    /// together with the state of its parent to replay it on.
+    pub fn pending_block_state(&self, block_hash: BlockHash) -> Option<PendingBlockState> {
+        let chain_id = self.block_indices.get_blocks_chain_id(&block_hash)?;
+        let chain = self.chains.get(&chain_id)?;
+        let block = chain.blocks().values().find(|block| block.hash() == block_hash)?.clone();
+        let fork = self.canonical_fork(chain_id)?;
+
+        // the parent is in the same chain, in the chain it forks from or canonical
+        let (parent_chain_id, parent_number) = if block.number > chain.first().number {
+            (Some(chain_id), block.number - 1)
+        } else {
+            let fork_block = chain.fork_block();
+            (self.block_indices.get_blocks_chain_id(&fork_block.hash), fork_block.number)
+        };
+        let substate = match parent_chain_id {
+            Some(parent_chain_id) => self
+                .chain_substate(parent_chain_id, parent_number)
+                .unwrap_or_else(|| self.chains[&parent_chain_id].substate().clone()),
+            None => SubStateData::default(),
+        };
+
+        // collect the ancestors of the block down to the fork block
+        let mut side_chain_hashes = BTreeMap::new();
+        let mut side_chain_difficulty = block.difficulty;
+        let mut ancestor_chain_id = Some(chain_id);
+        let mut ancestor_number = block.number - 1;
+        while let Some(chain) = ancestor_chain_id.and_then(|chain_id| self.chains.get(&chain_id)) {
+            for ancestor in chain.blocks().range(..=ancestor_number).values() {
+                side_chain_hashes.insert(ancestor.number, ancestor.hash());
+                side_chain_difficulty += ancestor.difficulty;
+            }
+            let fork_block = chain.fork_block();
+            ancestor_chain_id = self.block_indices.get_blocks_chain_id(&fork_block.hash);
+            ancestor_number = fork_block.number;
+        }
+
+        Some(PendingBlockState {
+            block,
+            fork,
+            substate,
+            side_chain_hashes,
+            canonical_hashes: self.block_indices.canonical_hashes_snapshot(),
+            side_chain_difficulty,
         })
     }
 
+    /// Returns a snapshot of the tree that only contains the numbers and hashes of its blocks.
+    pub fn snapshot(&self) -> BlockchainTreeState {
+        let canonical_tip = self.block_indices.canonical_tip();
+        let mut side_chains = self
+            .chains
+            .iter()
+            .map(|(&id, chain)| {
+                let fork_block = chain.fork_block();
+                SideChainState {
+                    id,
+                    fork_block: TreeBlock { number: fork_block.number, hash: fork_block.hash },
+                    first_block: chain.first().number,
+                    last_block: chain.tip().number,
+                    tip_hash: chain.tip().hash(),
+                }
+            })
+            .collect::<Vec<_>>();
+        side_chains.sort_unstable_by_key(|chain| chain.id);
+
+        BlockchainTreeState {
+            canonical_tip: TreeBlock { number: canonical_tip.number, hash: canonical_tip.hash },
+            last_finalized_block: self.block_indices.last_finalized_block(),
+            side_chains,
+            indices: self.block_indices.sizes(),
+            buffered_blocks: 0,
+        }
+    }
+
     /// Fork side chain or append the block if parent is the top of the chain
     fn fork_side_chain(
         &mut self,
@@ -157,6 +373,8 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
         chain_id: BlockChainId,
     ) -> Result<(), Error> {
         let block_hashes = self.all_chain_hashes(chain_id);
+        let parent_post_state = self.chain_post_state(chain_id, block.number - 1);
+        let parent_substate = self.chain_substate(chain_id, block.number - 1);
 
         // get canonical fork.
         let canonical_fork =
@@ -169,7 +387,7 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
             .ok_or(ExecError::BlockChainIdConsistency { chain_id })?;
         let chain_tip = parent_chain.tip().hash();
 
-        let canonical_block_hashes = self.block_indices.canonical_chain();
+        let canonical_block_hashes = self.block_indices.canonical_hashes_snapshot();
 
         // get canonical tip
         let (_, canonical_tip_hash) =
@@ -184,27 +402,37 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
 
         // append the block if it is continuing the chain.
         if chain_tip == block.parent_hash {
-            let block_hash = block.hash();
-            let block_number = block.number;
-            parent_chain.append_block(
-                block,
-                block_hashes,
-                canonical_block_hashes,
-                &provider,
-                &self.externals.consensus,
-                &self.externals.executor_factory,
-            )?;
+            self.externals.with_prefetcher(self.trie_prefetch, |prefetch| {
+                parent_chain.append_block(
+                    block,
+                    parent_substate.as_ref(),
+                    block_hashes,
+                    &canonical_block_hashes,
+                    &provider,
+                    parent_post_state,
+                    prefetch,
+                    &self.externals.consensus,
+                    &self.externals.executor_factory,
+                )
+            })?;
             drop(provider);
-            self.block_indices.insert_non_fork_block(block_number, block_hash, chain_id)
+            self.block_indices.insert_non_fork_block(parent_chain.tip(), chain_id)
         } else {
-            let chain = parent_chain.new_chain_fork(
-                block,
-                block_hashes,
-                canonical_block_hashes,
-                &provider,
-                &self.externals.consensus,
-                &self.externals.executor_factory,
-            )?;
+            let parent_substate =
+                parent_substate.unwrap_or_else(|| parent_chain.substate().clone());
+            let chain = self.externals.with_prefetcher(self.trie_prefetch, |prefetch| {
+                parent_chain.new_chain_fork(
+                    block,
+                    &parent_substate,
+                    block_hashes,
+                    &canonical_block_hashes,
+                    &provider,
+                    parent_post_state,
+                    prefetch,
+                    &self.externals.consensus,
+                    &self.externals.executor_factory,
+                )
+            })?;
             // release the lifetime with a drop
             drop(provider);
             self.insert_chain(chain);
@@ -215,7 +443,7 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
 
     /// Fork canonical chain by creating new chain
     pub fn fork_canonical_chain(&mut self, block: SealedBlockWithSenders) -> Result<(), Error> {
-        let canonical_block_hashes = self.block_indices.canonical_chain();
+        let canonical_block_hashes = self.block_indices.canonical_hashes_snapshot();
         let (_, canonical_tip) =
             canonical_block_hashes.last_key_value().map(|(i, j)| (*i, *j)).unwrap_or_default();
 
@@ -231,15 +459,18 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
             ChainState::boxed(db.history_by_block_number(block.number - 1)?)
         };
 
-        let parent_header = parent_header.seal(block.parent_hash);
-        let chain = Chain::new_canonical_fork(
-            &block,
-            &parent_header,
-            canonical_block_hashes,
-            &provider,
-            &self.externals.consensus,
-            &self.externals.executor_factory,
-        )?;
+        let parent_header = SealedHeader::new_unchecked(parent_header, block.parent_hash);
+        let chain = self.externals.with_prefetcher(self.trie_prefetch, |prefetch| {
+            Chain::new_canonical_fork(
+                &block,
+                &parent_header,
+                &canonical_block_hashes,
+                &provider,
+                prefetch,
+                &self.externals.consensus,
+                &self.externals.executor_factory,
+            )
+        })?;
         drop(provider);
         self.insert_chain(chain);
         Ok(())
@@ -267,6 +498,82 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
         hashes
     }
 
+    /// Collects the state changes of the side chain blocks from the canonical fork up to and
+    /// including the given block of the chain. These changes are on top of the state of the
+    /// canonical fork.
+    fn chain_post_state(
+        &self,
+        chain_id: BlockChainId,
+        block_number: BlockNumber,
+    ) -> HashedPostState {
+        let chain_spec = self.externals.chain_spec.as_ref();
+        let mut chain_id = chain_id;
+        let mut block_number = block_number;
+        let mut post_states = Vec::new();
+        while let Some(chain) = self.chains.get(&chain_id) {
+            let first_block = chain.first().number;
+            let changesets = &chain.changesets()[..(block_number + 1 - first_block) as usize];
+            post_states.push(HashedPostState::from_execution_results(
+                changesets,
+                first_block,
+                chain_spec,
+            ));
+
+            let fork_block = chain.fork_block();
+            match self.block_indices.get_blocks_chain_id(&fork_block.hash) {
+                Some(fork_chain_id) => {
+                    chain_id = fork_chain_id;
+                    block_number = fork_block.number;
+                }
+                // the chain joins the canonical chain
+                None => break,
+            }
+        }
+
+        let mut post_state = HashedPostState::default();
+        for chain_post_state in post_states.into_iter().rev() {
+            post_state.extend(chain_post_state);
+        }
+        post_state
+    }
+
+    /// Builds the substate of the side chain blocks from the canonical fork up to and including the
+    /// given block of the chain, the state a child of the block is executed on.
+    ///
+    /// Chains only keep the substate of their own blocks, so the substate is rebuilt from the
+    /// changesets of the chain and the chains it forks from. Returns `None` if the chain joins the
+    /// canonical chain and the block is its tip, the substate of the chain is complete then.
+    fn chain_substate(
+        &self,
+        chain_id: BlockChainId,
+        block_number: BlockNumber,
+    ) -> Option<SubStateData> {
+        let mut chain_id = chain_id;
+        let mut block_number = block_number;
+        let mut changesets = Vec::new();
+        while let Some(chain) = self.chains.get(&chain_id) {
+            let first_block = chain.first().number;
+            changesets.push(&chain.changesets()[..(block_number + 1 - first_block) as usize]);
+
+            let fork_block = chain.fork_block();
+            match self.block_indices.get_blocks_chain_id(&fork_block.hash) {
+                Some(fork_chain_id) => {
+                    chain_id = fork_chain_id;
+                    block_number = fork_block.number;
+                }
+                // the chain joins the canonical chain
+                None if changesets.len() == 1 && block_number == chain.tip().number => return None,
+                None => break,
+            }
+        }
+
+        let mut substate = SubStateData::default();
+        for chain_changesets in changesets.into_iter().rev() {
+            substate.apply(chain_changesets);
+        }
+        Some(substate)
+    }
+
     /// Getting the canonical fork would tell use what kind of Provider we should execute block on.
     /// If it is latest state provider or history state provider
     /// Return None if chain_id is not known.
@@ -304,7 +611,8 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
     /// Insert block inside tree. recover transaction signers and
     /// internaly call [`BlockchainTree::insert_block_with_senders`] fn.
     pub fn insert_block(&mut self, block: SealedBlock) -> Result<bool, Error> {
-        let block = block.seal_with_senders().ok_or(ExecError::SenderRecoveryError)?;
+        let senders = recover_signers(&block.body)?;
+        let block = SealedBlockWithSenders::new(block, senders).expect("a sender per transaction");
         self.insert_block_with_senders(&block)
     }
 
@@ -321,42 +629,20 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
         &mut self,
         block: &SealedBlockWithSenders,
     ) -> Result<bool, Error> {
-        // check if block number is inside pending block slide
-        let last_finalized_block = self.block_indices.last_finalized_block();
-        if block.number <= last_finalized_block {
-            return Err(ExecError::PendingBlockIsFinalized {
-                block_number: block.number,
-                block_hash: block.hash(),
-                last_finalized: last_finalized_block,
-            }
-            .into())
-        }
-
-        // we will not even try to insert blocks that are too far in future.
-        if block.number > last_finalized_block + self.max_blocks_in_chain {
-            return Err(ExecError::PendingBlockIsInFuture {
-                block_number: block.number,
-                block_hash: block.hash(),
-                last_finalized: last_finalized_block,
-            }
-            .into())
-        }
-
-        // check if block is already inside Tree
-        if self.block_indices.contains_pending_block_hash(block.hash()) {
-            // block is known return that is inserted
-            return Ok(true)
-        }
-
-        // check if block is part of canonical chain
-        if self.block_indices.canonical_hash(&block.number) == Some(block.hash()) {
-            // block is part of canonical chain
+        if self.is_block_known(block)? {
             return Ok(true)
         }
 
         // check if block parent can be found in Tree
         if let Some(parent_chain) = self.block_indices.get_blocks_chain_id(&block.parent_hash) {
+            let started_at = Instant::now();
             self.fork_side_chain(block.clone(), parent_chain)?;
+            self.metrics.record_block(
+                block.number,
+                block.hash(),
+                block.gas_used,
+                started_at.elapsed(),
+            );
             // TODO save pending block to database
             // https://github.com/paradigmxyz/reth/issues/1713
             return Ok(true)
@@ -365,7 +651,14 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
         // if not found, check if the parent can be found inside canonical chain.
         if Some(block.parent_hash) == self.block_indices.canonical_hash(&(block.number - 1)) {
             // create new chain that points to that block
+            let started_at = Instant::now();
             self.fork_canonical_chain(block.clone())?;
+            self.metrics.record_block(
+                block.number,
+                block.hash(),
+                block.gas_used,
+                started_at.elapsed(),
+            );
             // TODO save pending block to database
             // https://github.com/paradigmxyz/reth/issues/1713
             return Ok(true)
@@ -376,6 +669,72 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
         Ok(false)
     }
 
+    /// Inserts a block that was executed by the caller, e.g. a block built by the node itself,
+    /// without executing and validating it again.
+    ///
+    /// Returns `false` if neither the parent of the block is part of the tree nor the canonical
+    /// chain, see [BlockchainTree::insert_block_with_senders].
+    pub fn insert_executed_block(
+        &mut self,
+        block: SealedBlockWithSenders,
+        result: ExecutionResult,
+    ) -> Result<bool, Error> {
+        if self.is_block_known(&block)? {
+            return Ok(true)
+        }
+
+        if let Some(chain_id) = self.block_indices.get_blocks_chain_id(&block.parent_hash) {
+            let chain = self
+                .chains
+                .get_mut(&chain_id)
+                .ok_or(ExecError::BlockChainIdConsistency { chain_id })?;
+            if chain.tip().hash() == block.parent_hash {
+                chain.append_chain(Chain::new(vec![(block, result)]))?;
+                self.block_indices.insert_non_fork_block(chain.tip(), chain_id);
+            } else {
+                // chains only hold the changes of their own blocks, a fork of a side chain starts
+                // with the block
+                self.insert_chain(Chain::new(vec![(block, result)]));
+            }
+            return Ok(true)
+        }
+
+        if Some(block.parent_hash) == self.block_indices.canonical_hash(&(block.number - 1)) {
+            self.insert_chain(Chain::new(vec![(block, result)]));
+            return Ok(true)
+        }
+        Ok(false)
+    }
+
+    /// Returns `true` if the block is already part of the tree or the canonical chain, and an error
+    /// if it is outside of the window of blocks the tree accepts.
+    fn is_block_known(&self, block: &SealedBlockWithSenders) -> Result<bool, Error> {
+        // check if block number is inside pending block slide
+        let last_finalized_block = self.block_indices.last_finalized_block();
+        if block.number <= last_finalized_block {
+            return Err(ExecError::PendingBlockIsFinalized {
+                block_number: block.number,
+                block_hash: block.hash(),
+                last_finalized: last_finalized_block,
+            }
+            .into())
+        }
+
+        // we will not even try to insert blocks that are too far in future.
+        if block.number > last_finalized_block + self.config.max_blocks_in_chain() {
+            return Err(ExecError::PendingBlockIsInFuture {
+                block_number: block.number,
+                block_hash: block.hash(),
+                last_finalized: last_finalized_block,
+            }
+            .into())
+        }
+
+        // check if block is already inside Tree or part of canonical chain
+        Ok(self.block_indices.contains_pending_block_hash(block.hash()) ||
+            self.block_indices.canonical_hash(&block.number) == Some(block.hash()))
+    }
+
     /// Do finalization of blocks. Remove them from tree
     pub fn finalize_block(&mut self, finalized_block: BlockNumber) {
         let mut remove_chains = self.block_indices.finalize_canonical_blocks(finalized_block);
@@ -388,34 +747,36 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
     }
 
     /// Update canonical hashes. Reads last N canonical blocks from database and update all indices.
+    ///
+    /// Returns the chains whose fork block became canonical with the update. They are kept in the
+    /// tree and can be made canonical with [BlockchainTree::make_canonical].
     pub fn update_canonical_hashes(
         &mut self,
         last_finalized_block: BlockNumber,
-    ) -> Result<(), Error> {
+    ) -> Result<BTreeSet<BlockChainId>, Error> {
         self.finalize_block(last_finalized_block);
 
-        let num_of_canonical_hashes =
-            self.max_reorg_depth + self.block_indices.num_of_additional_canonical_block_hashes();
-
         let last_canonical_hashes = self
             .externals
             .db
             .tx()?
             .cursor_read::<tables::CanonicalHeaders>()?
             .walk_back(None)?
-            .take(num_of_canonical_hashes as usize)
+            .take(self.config.num_of_canonical_hashes() as usize)
             .collect::<Result<BTreeMap<BlockNumber, BlockHash>, _>>()?;
 
-        let mut remove_chains = self.block_indices.update_block_hashes(last_canonical_hashes);
+        let (mut remove_chains, mut connectable_chains) =
+            self.block_indices.update_block_hashes(last_canonical_hashes);
 
         // remove all chains that got discarded
-        while let Some(chain_id) = remove_chains.first() {
-            if let Some(chain) = self.chains.remove(chain_id) {
+        while let Some(chain_id) = remove_chains.pop_first() {
+            if let Some(chain) = self.chains.remove(&chain_id) {
                 remove_chains.extend(self.block_indices.remove_chain(&chain));
             }
         }
 
-        Ok(())
+        connectable_chains.retain(|chain_id| self.chains.contains_key(chain_id));
+        Ok(connectable_chains)
     }
 
     /// Split chain and return canonical part of it. Pending part reinsert inside tree
@@ -479,7 +840,9 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
         // if joins to the tip
         if new_canon_chain.fork_block_hash() == old_tip.hash {
             // append to database
-            self.commit_canonical(new_canon_chain)?;
+            let mut tx = Transaction::new(&self.externals.db)?;
+            self.commit_canonical(&mut tx, new_canon_chain)?;
+            tx.commit()?;
         } else {
             // it forks to canonical block that is not the tip.
 
@@ -491,8 +854,54 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
 
             // revert `N` blocks from current canonical chain and put them inside BlockchanTree
             // This is main reorgs on tables.
-            let old_canon_chain = self.revert_canonical(canon_fork.number)?;
-            self.commit_canonical(new_canon_chain)?;
+            //
+            // The old blocks are reverted and the new blocks committed in the same transaction,
+            // so readers never see the canonical hashes of one chain with the receipts and
+            // changesets of the other, or a chain that ends at the fork block.
+            let mut tx = Transaction::new(&self.externals.db)?;
+            let old_canon_chain = self.revert_canonical(&mut tx, canon_fork.number)?;
+            let new_tip = new_canon_chain.tip().hash();
+            let new_transactions = new_canon_chain
+                .blocks()
+                .values()
+                .flat_map(|block| block.body.iter().map(|transaction| transaction.hash()))
+                .collect::<HashSet<_>>();
+            self.commit_canonical(&mut tx, new_canon_chain)?;
+
+            // the transactions of the reverted blocks are either unindexed or reincluded by the
+            // new canonical blocks
+            #[cfg(debug_assertions)]
+            for block in old_canon_chain.blocks().values() {
+                for transaction in block.body.iter() {
+                    debug_assert!(
+                        reth_provider::is_canonical_tx_lookup(&*tx, transaction.hash())?,
+                        "reverted transaction {:?} is indexed outside the canonical chain",
+                        transaction.hash()
+                    );
+                }
+            }
+
+            tx.commit()?;
+            drop(tx);
+
+            // the transactions that are only in the reverted blocks go back to the pool
+            let reinjected = old_canon_chain
+                .blocks()
+                .values()
+                .flat_map(|block| block.body.iter())
+                .filter(|transaction| !new_transactions.contains(&transaction.hash()))
+                .count();
+            self.reorgs.record(ReorgEvent {
+                depth: (old_tip.number - canon_fork.number).into(),
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs()
+                    .into(),
+                old_tip: old_tip.hash,
+                new_tip,
+                reinjected_transactions: (reinjected as u64).into(),
+            });
 
             // TODO we can potentially merge now reverted canonical chain with
             // one of the chain from the tree. Low priority.
@@ -501,37 +910,57 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
             self.insert_chain(old_canon_chain);
         }
 
+        if let Some(canonical_tip) = &self.canonical_tip {
+            let tip = self.block_indices.canonical_tip();
+            canonical_tip.update(tip.number, tip.hash);
+        }
+
         Ok(())
     }
 
-    /// Commit chain for it to become canonical. Assume we are doing pending operation to db.
-    fn commit_canonical(&mut self, chain: Chain) -> Result<(), Error> {
-        let mut tx = Transaction::new(&self.externals.db)?;
-
+    /// Writes the chain to the transaction for it to become canonical, the transaction is committed
+    /// by the caller.
+    ///
+    /// The blocks and their state are written first, then the checkpoints of all stages are moved
+    /// to the new tip. Both are committed at once, if a commit is interrupted anyway the next start
+    /// of the tree repairs it, see [recover_canonical_commit].
+    ///
+    /// The blocks of the chain were executed and their state roots verified when they were
+    /// inserted into the tree, so their execution results are written as they are and the state
+    /// trie is only updated once for the whole chain.
+    fn commit_canonical(&self, tx: &mut Transaction<'_, DB>, chain: Chain) -> Result<(), Error> {
         let new_tip = chain.tip().number;
         let (blocks, changesets, _) = chain.into_inner();
-        for item in blocks.into_iter().zip(changesets.into_iter()) {
-            let ((_, block), changeset) = item;
-            tx.insert_block(block, self.externals.chain_spec.as_ref(), changeset)
-                .map_err(|e| ExecError::CanonicalCommit { inner: e.to_string() })?;
+        tx.insert_blocks(
+            blocks.into_values().zip(changesets).collect(),
+            self.externals.chain_spec.as_ref(),
+        )
+        .map_err(|e| ExecError::CanonicalCommit { inner: e.to_string() })?;
+
+        #[cfg(any(test, feature = "failpoints"))]
+        if self.fail_canonical_commit {
+            return Err(ExecError::CanonicalCommit { inner: "failpoint".to_string() }.into())
         }
+
         // update pipeline progress.
         tx.update_pipeline_stages(new_tip)
             .map_err(|e| ExecError::PipelineStatusUpdate { inner: e.to_string() })?;
 
-        tx.commit()?;
-
         Ok(())
     }
 
     /// Revert canonical blocks from database and insert them to pending table
     /// Revert should be non inclusive, and revert_until should stay in db.
     /// Return the chain that represent reverted canonical blocks.
-    fn revert_canonical(&mut self, revert_until: BlockNumber) -> Result<Chain, Error> {
+    ///
+    /// The transaction is committed by the caller.
+    fn revert_canonical(
+        &self,
+        tx: &mut Transaction<'_, DB>,
+        revert_until: BlockNumber,
+    ) -> Result<Chain, Error> {
         // read data that is needed for new sidechain
 
-        let mut tx = Transaction::new(&self.externals.db)?;
-
         // read block and execution result from database. and remove traces of block from tables.
         let blocks_and_execution = tx
             .take_block_and_execution_range(
@@ -544,8 +973,6 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
         tx.update_pipeline_stages(revert_until)
             .map_err(|e| ExecError::PipelineStatusUpdate { inner: e.to_string() })?;
 
-        tx.commit()?;
-
         let chain = Chain::new(blocks_and_execution);
 
         Ok(chain)
@@ -555,16 +982,31 @@ impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTree<DB, C, EF>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::executor::Executor;
     use parking_lot::Mutex;
     use reth_db::{
         mdbx::{test_utils::create_test_rw_db, Env, WriteMap},
         transaction::DbTxMut,
     };
-    use reth_interfaces::test_utils::TestConsensus;
-    use reth_primitives::{hex_literal::hex, proofs::EMPTY_ROOT, ChainSpecBuilder, H256, MAINNET};
+    use reth_interfaces::{
+        blockchain_tree::BlockIndicesSizes, consensus::ConsensusError, test_utils::TestConsensus,
+    };
+    use reth_primitives::{
+        hex_literal::hex, keccak256, proofs::EMPTY_ROOT, Account, Address, Bytecode,
+        ChainSpecBuilder, Receipt, H256, MAINNET, U256,
+    };
     use reth_provider::{
-        execution_result::ExecutionResult, insert_block, test_utils::blocks::BlockChainTestData,
-        BlockExecutor, StateProvider,
+        check_consistency,
+        execution_result::{
+            AccountChangeSet, AccountInfoChangeSet, ExecutionResult, TransactionChangeSet,
+        },
+        insert_block,
+        test_utils::blocks::BlockChainTestData,
+        BlockExecutor, ConsistencyViolation, StateProvider,
+    };
+    use reth_revm::{
+        database::{State, SubState},
+        tracing::{CallTraceArena, TraceInspectorConfig},
     };
     use std::collections::HashSet;
 
@@ -636,6 +1078,11 @@ mod tests {
         (db, consensus, executor_factory, chain_spec)
     }
 
+    /// A small tree that keeps three canonical block hashes below a reorg window of one block.
+    fn tree_config(chain_spec: &ChainSpec) -> BlockchainTreeConfig {
+        BlockchainTreeConfig::with_additional_canonical_block_hashes(chain_spec, 1, 2, 3).unwrap()
+    }
+
     fn setup(mut genesis: SealedBlock, externals: &TestExternals) {
         // insert genesis to db.
 
@@ -714,10 +1161,12 @@ mod tests {
         // last finalized block would be number 9.
         setup(data.genesis, &externals);
 
-        // make tree
+        // make tree, prefetching the trie nodes of the executed blocks
         let (db, consensus, exec_factory, chain_spec) = externals;
-        let mut tree =
-            BlockchainTree::new(db, consensus, exec_factory, chain_spec, 1, 2, 3).unwrap();
+        let config = tree_config(&chain_spec);
+        let mut tree = BlockchainTree::new(db, consensus, exec_factory, chain_spec, config)
+            .unwrap()
+            .with_trie_prefetch(true);
 
         // genesis block 10 is already canonical
         assert_eq!(tree.make_canonical(&H256::zero()), Ok(()));
@@ -758,7 +1207,7 @@ mod tests {
         // |
         TreeTester::default()
             .with_chain_num(1)
-            .with_block_to_chain(HashMap::from([(block1.hash, 0), (block2.hash, 0)]))
+            .with_block_to_chain(HashMap::from([(block1.hash, 0), (block2.hash(), 0)]))
             .with_fork_to_child(HashMap::from([(block1.parent_hash, HashSet::from([block1.hash]))]))
             .assert(&tree);
 
@@ -900,7 +1349,7 @@ mod tests {
             .assert(&tree);
 
         // update canonical block to b2, this would make b2a be removed
-        assert_eq!(tree.update_canonical_hashes(12), Ok(()));
+        assert_eq!(tree.update_canonical_hashes(12), Ok(BTreeSet::new()));
         // Trie state:
         // b2 (canon)
         // |
@@ -914,4 +1363,543 @@ mod tests {
             .with_fork_to_child(HashMap::from([]))
             .assert(&tree);
     }
+
+    #[test]
+    fn state_root_mismatch() {
+        let data = BlockChainTestData::default();
+        let (mut block1, exec1) = data.blocks[0].clone();
+        block1.number = 11;
+        let state_root = block1.state_root;
+        block1.state_root = H256::zero();
+
+        let externals = externals(vec![exec1]);
+        setup(data.genesis, &externals);
+        let (db, consensus, exec_factory, chain_spec) = externals;
+        let config = tree_config(&chain_spec);
+        let mut tree =
+            BlockchainTree::new(db, consensus, exec_factory, chain_spec, config).unwrap();
+
+        // the state root of the block doesn't match the state after execution
+        assert_eq!(
+            tree.insert_block_with_senders(&block1),
+            Err(ConsensusError::BodyStateRootDiff { got: state_root, expected: H256::zero() }
+                .into())
+        );
+        TreeTester::default().with_chain_num(0).assert(&tree);
+    }
+
+    #[test]
+    fn gas_used_mismatch() {
+        let data = BlockChainTestData::default();
+        let (mut block1, exec1) = data.blocks[0].clone();
+        block1.number = 11;
+        let gas_used = block1.gas_used;
+        block1.gas_used = gas_used + 1;
+
+        let externals = externals(vec![exec1]);
+        setup(data.genesis, &externals);
+        let (db, consensus, exec_factory, chain_spec) = externals;
+        let config = tree_config(&chain_spec);
+        let mut tree =
+            BlockchainTree::new(db, consensus, exec_factory, chain_spec, config).unwrap();
+
+        // the gas used of the header doesn't match the gas used of the receipts
+        assert_eq!(
+            tree.insert_block_with_senders(&block1),
+            Err(ExecError::BlockGasUsed { got: gas_used, expected: gas_used + 1 }.into())
+        );
+        TreeTester::default().with_chain_num(0).assert(&tree);
+    }
+
+    #[test]
+    fn base_fee_mismatch() {
+        let mut data = BlockChainTestData::default();
+        data.genesis.header.header.base_fee_per_gas = Some(1_000_000_000);
+        let (mut block1, exec1) = data.blocks[0].clone();
+        block1.number = 11;
+        block1.base_fee_per_gas = Some(875_000_000);
+
+        let externals = externals(vec![exec1]);
+        setup(data.genesis, &externals);
+        let (db, consensus, exec_factory, chain_spec) = externals;
+        let config = tree_config(&chain_spec);
+        let mut tree =
+            BlockchainTree::new(db, consensus, exec_factory, chain_spec, config).unwrap();
+
+        // the parent has no gas limit, its base fee is kept
+        assert_eq!(
+            tree.insert_block_with_senders(&block1),
+            Err(ExecError::BlockBaseFeeDiff {
+                got: Some(875_000_000),
+                expected: Some(1_000_000_000)
+            }
+            .into())
+        );
+        TreeTester::default().with_chain_num(0).assert(&tree);
+    }
+
+    #[test]
+    fn tree_snapshot() {
+        let data = BlockChainTestData::default();
+        let genesis_hash = data.genesis.hash();
+        let (mut block1, exec1) = data.blocks[0].clone();
+        block1.number = 11;
+        block1.state_root =
+            H256(hex!("5d035ccb3e75a9057452ff060b773b213ec1fc353426174068edfc3971a0b6bd"));
+        let (mut block2, exec2) = data.blocks[1].clone();
+        block2.number = 12;
+        block2.state_root =
+            H256(hex!("90101a13dd059fa5cca99ed93d1dc23657f63626c5b8f993a2ccbdf7446b64f8"));
+        let mut block1a = block1.clone();
+        block1a.hash = H256([0x33; 32]);
+
+        let externals = externals(vec![exec1.clone(), exec2, exec1]);
+        setup(data.genesis, &externals);
+        let (db, consensus, exec_factory, chain_spec) = externals;
+        let config = tree_config(&chain_spec);
+        let mut tree =
+            BlockchainTree::new(db, consensus, exec_factory, chain_spec, config).unwrap();
+        tree.finalize_block(10);
+
+        assert_eq!(tree.insert_block_with_senders(&block1), Ok(true));
+        assert_eq!(tree.insert_block_with_senders(&block2), Ok(true));
+        assert_eq!(tree.insert_block_with_senders(&block1a), Ok(true));
+
+        // Trie state:
+        // b2
+        // |
+        // b1  b1a (side chains)
+        // |  /
+        // |/
+        // g1 (10)
+        let fork_block = TreeBlock { number: 10, hash: genesis_hash };
+        assert_eq!(
+            tree.snapshot(),
+            BlockchainTreeState {
+                canonical_tip: fork_block,
+                last_finalized_block: 10,
+                side_chains: vec![
+                    SideChainState {
+                        id: 0,
+                        fork_block,
+                        first_block: 11,
+                        last_block: 12,
+                        tip_hash: block2.hash(),
+                    },
+                    SideChainState {
+                        id: 1,
+                        fork_block,
+                        first_block: 11,
+                        last_block: 11,
+                        tip_hash: block1a.hash(),
+                    },
+                ],
+                indices: BlockIndicesSizes {
+                    canonical_chain: 4,
+                    fork_to_child: 1,
+                    blocks_to_chain: 3,
+                    number_to_block: 2,
+                },
+                buffered_blocks: 0,
+            }
+        );
+    }
+
+    /// Inserts the block with the given execution result, fixing its state root on the way.
+    fn insert_executed_block(
+        tree: &mut BlockchainTree<Arc<Env<WriteMap>>, TestConsensus, TestFactory>,
+        exec_results: &Mutex<Vec<ExecutionResult>>,
+        block: &mut SealedBlockWithSenders,
+        exec_result: ExecutionResult,
+    ) {
+        loop {
+            exec_results.lock().push(exec_result.clone());
+            match tree.insert_block_with_senders(block) {
+                Ok(inserted) => {
+                    assert!(inserted);
+                    return
+                }
+                Err(Error::Consensus(ConsensusError::BodyStateRootDiff { got, .. })) => {
+                    block.state_root = got
+                }
+                Err(err) => panic!("failed to insert block {}: {err}", block.number),
+            }
+        }
+    }
+
+    #[test]
+    fn sibling_tips_share_prefix_state() {
+        let data = BlockChainTestData::default();
+        let (template, _) = data.blocks[0].clone();
+        // every block creates an account with one storage slot
+        let exec_result = |account: u64| ExecutionResult {
+            tx_changesets: vec![TransactionChangeSet {
+                receipt: Receipt { cumulative_gas_used: template.gas_used, ..Default::default() },
+                changeset: BTreeMap::from([(
+                    Address::from_low_u64_be(account),
+                    AccountChangeSet {
+                        account: AccountInfoChangeSet::Created {
+                            new: Account { nonce: 1, ..Default::default() },
+                        },
+                        storage: BTreeMap::from([(U256::from(1), (U256::ZERO, U256::from(1)))]),
+                        ..Default::default()
+                    },
+                )]),
+                new_bytecodes: BTreeMap::new(),
+            }],
+            ..Default::default()
+        };
+
+        let externals = externals(vec![]);
+        setup(data.genesis, &externals);
+        let (db, consensus, exec_factory, chain_spec) = externals;
+        let exec_results = exec_factory.exec_result.clone();
+        let config =
+            BlockchainTreeConfig::with_additional_canonical_block_hashes(&chain_spec, 1, 100, 3)
+                .unwrap();
+        let mut tree =
+            BlockchainTree::new(db, consensus, exec_factory, chain_spec, config).unwrap();
+        tree.finalize_block(10);
+
+        // a prefix of 64 blocks on top of the canonical block 10
+        let mut parent_hash = template.parent_hash;
+        let mut prefix_results = Vec::new();
+        for number in 11..=74 {
+            let mut block = template.clone();
+            block.number = number;
+            block.parent_hash = parent_hash;
+            block.hash = H256::from_low_u64_be(number);
+            insert_executed_block(&mut tree, &exec_results, &mut block, exec_result(number));
+            parent_hash = block.hash();
+            prefix_results.push(exec_result(number));
+        }
+
+        // two sibling tips on top of the prefix
+        let mut tips = Vec::new();
+        for tip in [1, 2] {
+            let mut block = template.clone();
+            block.number = 75;
+            block.parent_hash = parent_hash;
+            block.hash = H256::from_low_u64_be(1000 + tip);
+            insert_executed_block(&mut tree, &exec_results, &mut block, exec_result(1000 + tip));
+            tips.push(block);
+        }
+
+        // the second tip forks the chain of the prefix without copying its state
+        TreeTester::default().with_chain_num(2).assert(&tree);
+        let entries =
+            tree.chains.values().map(|chain| chain.substate().num_entries()).sum::<usize>();
+        assert_eq!(entries, 2 * (64 + 2));
+
+        // both tips are found and the fork is executed on the full state of its parent
+        for tip in &tips {
+            assert_eq!(tree.block_by_hash(tip.hash()), Some(tip));
+        }
+        let fork_chain = tree.block_indices.get_blocks_chain_id(&tips[1].hash()).unwrap();
+        let mut expected = SubStateData::default();
+        expected.apply(&prefix_results);
+        expected.apply_one(&exec_result(1002));
+        assert_eq!(tree.chain_substate(fork_chain, 75), Some(expected));
+
+        // the fork is extended on top of the shared prefix
+        let mut block = template.clone();
+        block.number = 76;
+        block.parent_hash = tips[1].hash();
+        block.hash = H256::from_low_u64_be(2000);
+        insert_executed_block(&mut tree, &exec_results, &mut block, exec_result(2000));
+        assert_eq!(tree.block_indices.get_blocks_chain_id(&block.hash()), Some(fork_chain));
+        assert_eq!(tree.chains[&fork_chain].substate().num_entries(), 4);
+
+        // making the fork canonical keeps the other tip in the tree
+        assert_eq!(tree.make_canonical(&block.hash()), Ok(()));
+        assert_eq!(tree.block_by_hash(tips[0].hash()), Some(&tips[0]));
+        assert_eq!(tree.block_by_hash(tips[1].hash()), None);
+    }
+
+    #[test]
+    fn interrupted_canonical_commit() {
+        let data = BlockChainTestData::default();
+        let (mut block1, exec1) = data.blocks[0].clone();
+        block1.number = 11;
+        block1.state_root =
+            H256(hex!("5d035ccb3e75a9057452ff060b773b213ec1fc353426174068edfc3971a0b6bd"));
+        let (mut block2, exec2) = data.blocks[1].clone();
+        block2.number = 12;
+        block2.state_root =
+            H256(hex!("90101a13dd059fa5cca99ed93d1dc23657f63626c5b8f993a2ccbdf7446b64f8"));
+
+        let externals = externals(vec![exec2.clone(), exec1]);
+        setup(data.genesis, &externals);
+        let (db, consensus, exec_factory, chain_spec) = externals;
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::SyncStage>("Finish".to_string(), 10).unwrap();
+        tx.commit().unwrap();
+        let checkpoint =
+            || db.tx().unwrap().get::<tables::SyncStage>("Finish".to_string()).unwrap();
+
+        let config = tree_config(&chain_spec);
+        let mut tree =
+            BlockchainTree::new(db.clone(), consensus, exec_factory, chain_spec.clone(), config)
+                .unwrap();
+        tree.finalize_block(10);
+        assert_eq!(tree.insert_block_with_senders(&block1), Ok(true));
+        assert_eq!(tree.insert_block_with_senders(&block2), Ok(true));
+        assert_eq!(tree.make_canonical(&block1.hash()), Ok(()));
+        assert_eq!(checkpoint(), Some(11));
+
+        // the commit of the second block fails midway, none of it is persisted
+        tree.set_canonical_commit_failpoint(true);
+        assert!(tree.make_canonical(&block2.hash()).is_err());
+        drop(tree);
+        assert_eq!(db.tx().unwrap().get::<tables::CanonicalHeaders>(12).unwrap(), None);
+        assert_eq!(checkpoint(), Some(11));
+
+        // a commit that only persisted the blocks is rolled forward on the next start
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        tx.insert_block(block2.clone(), &chain_spec, exec2).unwrap();
+        tx.commit().unwrap();
+        drop(tx);
+        let config = tree_config(&chain_spec);
+        let tree = BlockchainTree::new(
+            db.clone(),
+            TestConsensus::default(),
+            TestFactory::new(chain_spec.clone()),
+            chain_spec,
+            config,
+        )
+        .unwrap();
+        assert_eq!(tree.block_indices.canonical_tip().hash, block2.hash());
+        assert_eq!(checkpoint(), Some(12));
+    }
+
+    #[test]
+    fn reorg_removes_receipts_and_lookups_of_reverted_blocks() {
+        let data = BlockChainTestData::default();
+        let (mut block1, exec1) = data.blocks[0].clone();
+        block1.number = 11;
+        block1.state_root =
+            H256(hex!("5d035ccb3e75a9057452ff060b773b213ec1fc353426174068edfc3971a0b6bd"));
+        let (mut block2, exec2) = data.blocks[1].clone();
+        block2.number = 12;
+        block2.state_root =
+            H256(hex!("90101a13dd059fa5cca99ed93d1dc23657f63626c5b8f993a2ccbdf7446b64f8"));
+        let mut block1a = block1.clone();
+        block1a.hash = H256([0x33; 32]);
+
+        let externals = externals(vec![exec1.clone(), exec2, exec1]);
+        setup(data.genesis, &externals);
+        let (db, consensus, exec_factory, chain_spec) = externals;
+        let config = tree_config(&chain_spec);
+        let mut tree =
+            BlockchainTree::new(db.clone(), consensus, exec_factory, chain_spec, config).unwrap();
+        tree.finalize_block(10);
+
+        assert_eq!(tree.insert_block_with_senders(&block1), Ok(true));
+        assert_eq!(tree.insert_block_with_senders(&block2), Ok(true));
+        assert_eq!(tree.make_canonical(&block2.hash()), Ok(()));
+
+        // the receipts of both canonical transactions are served
+        let tx = db.tx_mut().unwrap();
+        for tx_number in 0..=1 {
+            tx.put::<tables::Receipts>(tx_number, Receipt::default()).unwrap();
+        }
+        tx.commit().unwrap();
+
+        // force a reorg that reverts both blocks and reuses only the first transaction number
+        assert_eq!(tree.insert_block_with_senders(&block1a), Ok(true));
+        assert_eq!(tree.make_canonical(&block1a.hash()), Ok(()));
+
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.cursor_read::<tables::Receipts>().unwrap().first().unwrap(), None);
+        // both blocks include the same transaction, its hash pointed to the reverted second one
+        let hash = block1a.body[0].hash();
+        assert_eq!(tx.get::<tables::TxHashNumber>(hash).unwrap(), Some(0));
+        let report = check_consistency(&tx).unwrap();
+        assert!(!report.violations.iter().any(|violation| matches!(
+            violation,
+            ConsistencyViolation::DanglingTxLookup { .. } |
+                ConsistencyViolation::NonCanonicalTxLookup { .. }
+        )));
+    }
+
+    #[test]
+    fn reorgs_are_recorded() {
+        let data = BlockChainTestData::default();
+        let (mut block1, exec1) = data.blocks[0].clone();
+        block1.number = 11;
+        block1.state_root =
+            H256(hex!("5d035ccb3e75a9057452ff060b773b213ec1fc353426174068edfc3971a0b6bd"));
+        let (mut block2, exec2) = data.blocks[1].clone();
+        block2.number = 12;
+        block2.state_root =
+            H256(hex!("90101a13dd059fa5cca99ed93d1dc23657f63626c5b8f993a2ccbdf7446b64f8"));
+        let mut block1a = block1.clone();
+        block1a.hash = H256([0x33; 32]);
+
+        let externals = externals(vec![exec1.clone(), exec2, exec1]);
+        setup(data.genesis, &externals);
+        let (db, consensus, exec_factory, chain_spec) = externals;
+        let config = tree_config(&chain_spec);
+        let mut tree =
+            BlockchainTree::new(db, consensus, exec_factory, chain_spec, config).unwrap();
+        tree.finalize_block(10);
+
+        // extending the canonical chain is not a reorg
+        assert_eq!(tree.insert_block_with_senders(&block1), Ok(true));
+        assert_eq!(tree.insert_block_with_senders(&block2), Ok(true));
+        assert_eq!(tree.make_canonical(&block2.hash()), Ok(()));
+        assert_eq!(tree.reorg_stats(), ReorgStats::default());
+
+        // b1a replaces both canonical blocks
+        assert_eq!(tree.insert_block_with_senders(&block1a), Ok(true));
+        assert_eq!(tree.make_canonical(&block1a.hash()), Ok(()));
+        // b1 replaces b1a again
+        assert_eq!(tree.make_canonical(&block1.hash()), Ok(()));
+
+        let stats = tree.reorg_stats();
+        assert_eq!(stats.total_reorgs, 2u64.into());
+        let reorgs = stats
+            .reorgs
+            .iter()
+            .map(|reorg| (reorg.depth.as_u64(), reorg.old_tip, reorg.new_tip))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reorgs,
+            vec![(2, block2.hash(), block1a.hash()), (1, block1a.hash(), block1.hash())]
+        );
+        // the blocks include the same transaction, none of them goes back to the pool
+        assert!(stats.reorgs.iter().all(|reorg| reorg.reinjected_transactions.is_zero()));
+        assert!(stats.reorgs.iter().all(|reorg| !reorg.timestamp.is_zero()));
+    }
+
+    #[test]
+    fn executed_blocks_are_not_executed_again() {
+        let data = BlockChainTestData::default();
+        let (mut block1, exec1) = data.blocks[0].clone();
+        block1.number = 11;
+        block1.state_root =
+            H256(hex!("5d035ccb3e75a9057452ff060b773b213ec1fc353426174068edfc3971a0b6bd"));
+        let (mut block2, exec2) = data.blocks[1].clone();
+        block2.number = 12;
+        block2.state_root =
+            H256(hex!("90101a13dd059fa5cca99ed93d1dc23657f63626c5b8f993a2ccbdf7446b64f8"));
+        let mut orphan = block2.clone();
+        orphan.parent_hash = H256([0x44; 32]);
+        orphan.hash = H256([0x55; 32]);
+
+        // the executor has no results, executing any block fails
+        let externals = externals(Vec::new());
+        setup(data.genesis, &externals);
+        let (db, consensus, exec_factory, chain_spec) = externals;
+        let config = tree_config(&chain_spec);
+        let mut tree =
+            BlockchainTree::new(db.clone(), consensus, exec_factory, chain_spec, config).unwrap();
+        tree.finalize_block(10);
+
+        assert_eq!(tree.insert_executed_block(block1.clone(), exec1.clone()), Ok(true));
+        assert_eq!(tree.insert_executed_block(block2.clone(), exec2), Ok(true));
+        assert_eq!(tree.insert_executed_block(block1.clone(), exec1.clone()), Ok(true));
+        assert_eq!(tree.insert_executed_block(orphan, exec1), Ok(false));
+        TreeTester::default()
+            .with_chain_num(1)
+            .with_block_to_chain(HashMap::from([(block1.hash(), 0), (block2.hash(), 0)]))
+            .assert(&tree);
+        assert!(tree.pending_transaction(&block2.body[0].hash()).is_some());
+
+        assert_eq!(tree.make_canonical(&block2.hash()), Ok(()));
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.get::<tables::CanonicalHeaders>(12).unwrap(), Some(block2.hash()));
+    }
+
+    /// Replays the transactions of the block on the given state of its parent.
+    fn trace_block<SP: StateProvider>(
+        chain_spec: &Arc<ChainSpec>,
+        parent: SP,
+        block: &SealedBlockWithSenders,
+        total_difficulty: U256,
+    ) -> Vec<(CallTraceArena, u64)> {
+        Executor::new(chain_spec.clone(), SubState::new(State::new(parent)))
+            .trace_transactions(
+                &block.block.clone().unseal(),
+                total_difficulty,
+                Some(block.senders.clone()),
+                TraceInspectorConfig::default_geth(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn pending_block_traces_match_canonical_traces() {
+        let data = BlockChainTestData::default();
+        let (mut block1, mut exec1) = data.blocks[0].clone();
+        block1.number = 11;
+        let (mut block2, exec2) = data.blocks[1].clone();
+        block2.number = 12;
+
+        // block 1 deploys the contract that the transaction of block 2 calls and funds its sender,
+        // the contract runs `sstore(5, add(sload(5), blockhash(sub(number(), 1))))`
+        let code = hex!("4360019003406005540160055500");
+        let code_hash = keccak256(code);
+        let contract = Address::from(hex!("095e7baea6a6c7c4c2dfeb977efac326af552d87"));
+        let tx_changeset = &mut exec1.tx_changesets[0];
+        tx_changeset.changeset.insert(
+            contract,
+            AccountChangeSet {
+                account: AccountInfoChangeSet::Created {
+                    new: Account { nonce: 1, balance: U256::ZERO, bytecode_hash: Some(code_hash) },
+                },
+                storage: BTreeMap::from([(U256::from(5), (U256::ZERO, U256::from(10)))]),
+                ..Default::default()
+            },
+        );
+        tx_changeset.changeset.insert(
+            block2.senders[0],
+            AccountChangeSet {
+                account: AccountInfoChangeSet::Created {
+                    new: Account { balance: U256::from(10u64.pow(18)), ..Default::default() },
+                },
+                ..Default::default()
+            },
+        );
+        tx_changeset.new_bytecodes.insert(code_hash, Bytecode::new_raw(code.to_vec().into()));
+
+        let externals = externals(vec![]);
+        setup(data.genesis, &externals);
+        let (db, consensus, exec_factory, chain_spec) = externals;
+        let exec_results = exec_factory.exec_result.clone();
+        let config = tree_config(&chain_spec);
+        let mut tree =
+            BlockchainTree::new(db.clone(), consensus, exec_factory, chain_spec.clone(), config)
+                .unwrap();
+        tree.finalize_block(10);
+        insert_executed_block(&mut tree, &exec_results, &mut block1, exec1);
+        insert_executed_block(&mut tree, &exec_results, &mut block2, exec2);
+
+        // before the fork choice, block 2 is traced on the state of the tree
+        let provider = ShareableDatabase::new(db.clone(), chain_spec.clone());
+        let pending = tree.pending_block_state(block2.hash()).unwrap();
+        assert_eq!(pending.fork.hash, block1.parent_hash);
+        assert_eq!(pending.side_chain_hashes, BTreeMap::from([(11, block1.hash())]));
+        let fork_td = provider.header_td(&pending.fork.hash).unwrap().unwrap();
+        let total_difficulty = fork_td + pending.side_chain_difficulty;
+        let fork_state = provider.history_by_block_number(pending.fork.number).unwrap();
+        let pending_traces =
+            trace_block(&chain_spec, pending.state(fork_state), &block2, total_difficulty);
+
+        // the contract read the hash of the side chain parent
+        let (arena, gas_used) = &pending_traces[0];
+        let frame = arena.geth_traces(U256::from(*gas_used), Default::default());
+        assert!(!frame.failed);
+        let blockhash = frame.struct_logs.iter().position(|log| log.op == "BLOCKHASH").unwrap();
+        let stack = frame.struct_logs[blockhash + 1].stack.clone().unwrap();
+        assert_eq!(stack.last(), Some(&U256::from_be_bytes(block1.hash().0)));
+
+        // once canonical, block 2 is traced on the state of the database with the same result
+        assert_eq!(tree.make_canonical(&block2.hash()), Ok(()));
+        assert!(tree.pending_block_state(block2.hash()).is_none());
+        assert_eq!(provider.header_td_by_number(12).unwrap(), Some(total_difficulty));
+        let parent_state = provider.history_by_block_number(11).unwrap();
+        let canonical_traces = trace_block(&chain_spec, parent_state, &block2, total_difficulty);
+        assert_eq!(pending_traces, canonical_traces);
+    }
 }
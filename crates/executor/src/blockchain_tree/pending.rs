@@ -0,0 +1,51 @@
+//! The state of the blocks of the tree, to replay them before they are canonical.
+
+use super::chain::ForkBlock;
+use crate::substate::{SubStateData, SubStateWithProvider};
+use reth_primitives::{BlockHash, BlockNumber, SealedBlockWithSenders, U256};
+use reth_provider::StateProvider;
+use std::{collections::BTreeMap, fmt::Debug, sync::Arc};
+
+/// A block of the tree with the state of its parent.
+///
+/// Side chains only keep the changes of their blocks, the state of the parent is the state of the
+/// canonical fork block with the changes of the side chain blocks up to the parent on top.
+#[derive(Debug, Clone)]
+pub struct PendingBlockState {
+    /// The block.
+    pub block: SealedBlockWithSenders,
+    /// The canonical block the side chain of the block forks from.
+    pub fork: ForkBlock,
+    /// The changes of the side chain blocks after the fork block up to and including the parent.
+    pub substate: SubStateData,
+    /// The hashes of the side chain blocks after the fork block up to and including the parent.
+    pub side_chain_hashes: BTreeMap<BlockNumber, BlockHash>,
+    /// The canonical block hashes kept by the tree.
+    pub canonical_hashes: Arc<BTreeMap<BlockNumber, BlockHash>>,
+    /// The summed difficulty of the side chain blocks up to and including the block, which is
+    /// added to the total difficulty of the fork block.
+    pub side_chain_difficulty: U256,
+}
+
+impl PendingBlockState {
+    /// Returns the state of the parent of the block, given the state of the fork block.
+    ///
+    /// The `BLOCKHASH` opcode sees the side chain ancestors of the block.
+    pub fn state<SP: StateProvider>(&self, fork_state: SP) -> SubStateWithProvider<'_, SP> {
+        SubStateWithProvider::new(
+            &self.substate,
+            fork_state,
+            &self.side_chain_hashes,
+            &self.canonical_hashes,
+        )
+    }
+}
+
+/// Gives access to the state of the blocks of the tree, e.g. to trace blocks that are not yet
+/// canonical.
+#[auto_impl::auto_impl(&, Arc)]
+pub trait PendingStateViewer: Debug + Send + Sync {
+    /// Returns the block with the given hash if it is in one of the side chains of the tree,
+    /// together with the state of its parent.
+    fn pending_block_state(&self, block_hash: BlockHash) -> Option<PendingBlockState>;
+}
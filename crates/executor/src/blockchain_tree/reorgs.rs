@@ -0,0 +1,73 @@
+//! History of the reorgs of the canonical chain.
+
+use metrics::{Counter, Histogram};
+use reth_interfaces::blockchain_tree::{ReorgEvent, ReorgStats};
+use reth_metrics_derive::Metrics;
+use std::collections::VecDeque;
+
+/// Max number of reorgs kept in the [ReorgHistory].
+pub const MAX_REORG_EVENTS: usize = 256;
+
+#[derive(Metrics)]
+#[metrics(scope = "blockchain_tree")]
+struct ReorgMetrics {
+    /// The total number of reorgs of the canonical chain
+    reorgs_total: Counter,
+    /// The number of canonical blocks removed by a reorg
+    reorg_depth: Histogram,
+}
+
+/// The most recent reorgs of the canonical chain, at most [MAX_REORG_EVENTS].
+#[derive(Debug, Default)]
+pub struct ReorgHistory {
+    /// The recorded reorgs, oldest first.
+    events: VecDeque<ReorgEvent>,
+    /// Number of reorgs recorded so far, including the evicted ones.
+    total: u64,
+    metrics: ReorgMetrics,
+}
+
+impl ReorgHistory {
+    /// Records a reorg, evicting the oldest one if the history is full.
+    pub fn record(&mut self, event: ReorgEvent) {
+        self.metrics.reorgs_total.increment(1);
+        self.metrics.reorg_depth.record(event.depth.as_u64() as f64);
+        if self.events.len() == MAX_REORG_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+        self.total += 1;
+    }
+
+    /// Returns the recorded reorgs.
+    pub fn stats(&self) -> ReorgStats {
+        ReorgStats {
+            total_reorgs: self.total.into(),
+            reorgs: self.events.iter().copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::H256;
+
+    #[test]
+    fn evicts_oldest_reorgs() {
+        let mut history = ReorgHistory::default();
+        for depth in 1..=MAX_REORG_EVENTS as u64 + 2 {
+            history.record(ReorgEvent {
+                depth: depth.into(),
+                old_tip: H256::from_low_u64_be(depth),
+                ..Default::default()
+            });
+        }
+
+        let stats = history.stats();
+        assert_eq!(stats.total_reorgs, (MAX_REORG_EVENTS as u64 + 2).into());
+        assert_eq!(stats.reorgs.len(), MAX_REORG_EVENTS);
+        assert_eq!(stats.reorgs[0].depth, 3u64.into());
+        assert_eq!(stats.reorgs.last().unwrap().depth, (MAX_REORG_EVENTS as u64 + 2).into());
+    }
+}
@@ -0,0 +1,91 @@
+//! Wrapper around [`BlockchainTree`] that can be shared between threads.
+
+use super::{
+    pending::{PendingBlockState, PendingStateViewer},
+    BlockchainTree,
+};
+use parking_lot::RwLock;
+use reth_db::database::Database;
+use reth_interfaces::{
+    blockchain_tree::{
+        BlockchainTreeEngine, BlockchainTreeState, BlockchainTreeViewer, ReorgStats,
+    },
+    consensus::Consensus,
+    Error,
+};
+use reth_primitives::{
+    BlockHash, BlockNumber, SealedBlock, TransactionMeta, TransactionSignedEcRecovered, TxHash,
+};
+use reth_provider::ExecutorFactory;
+use std::{fmt, sync::Arc};
+
+/// Shareable [`BlockchainTree`], readers like the RPC only take the lock for as long as it takes
+/// to copy the data they need.
+pub struct ShareableBlockchainTree<DB: Database, C: Consensus, EF: ExecutorFactory> {
+    /// The blockchain tree
+    pub tree: Arc<RwLock<BlockchainTree<DB, C, EF>>>,
+}
+
+impl<DB: Database, C: Consensus, EF: ExecutorFactory> ShareableBlockchainTree<DB, C, EF> {
+    /// Create a new shareable blockchain tree.
+    pub fn new(tree: BlockchainTree<DB, C, EF>) -> Self {
+        Self { tree: Arc::new(RwLock::new(tree)) }
+    }
+}
+
+impl<DB: Database, C: Consensus, EF: ExecutorFactory> Clone for ShareableBlockchainTree<DB, C, EF> {
+    fn clone(&self) -> Self {
+        Self { tree: Arc::clone(&self.tree) }
+    }
+}
+
+impl<DB: Database, C: Consensus, EF: ExecutorFactory> fmt::Debug
+    for ShareableBlockchainTree<DB, C, EF>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShareableBlockchainTree").finish_non_exhaustive()
+    }
+}
+
+impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTreeViewer
+    for ShareableBlockchainTree<DB, C, EF>
+{
+    fn tree_state(&self) -> BlockchainTreeState {
+        self.tree.read().snapshot()
+    }
+
+    fn reorg_stats(&self) -> ReorgStats {
+        self.tree.read().reorg_stats()
+    }
+
+    fn pending_transaction_by_hash(
+        &self,
+        tx_hash: TxHash,
+    ) -> Option<(TransactionSignedEcRecovered, TransactionMeta)> {
+        self.tree.read().pending_transaction(&tx_hash)
+    }
+}
+
+impl<DB: Database, C: Consensus, EF: ExecutorFactory> BlockchainTreeEngine
+    for ShareableBlockchainTree<DB, C, EF>
+{
+    fn insert_block(&self, block: SealedBlock) -> Result<bool, Error> {
+        self.tree.write().insert_block(block)
+    }
+
+    fn update_canonical_hashes(&self, unwound_to: BlockNumber) -> Result<(), Error> {
+        let mut tree = self.tree.write();
+        // the unwound blocks are no longer finalized
+        let last_finalized_block = tree.block_indices.last_finalized_block().min(unwound_to);
+        tree.update_canonical_hashes(last_finalized_block)?;
+        Ok(())
+    }
+}
+
+impl<DB: Database, C: Consensus, EF: ExecutorFactory> PendingStateViewer
+    for ShareableBlockchainTree<DB, C, EF>
+{
+    fn pending_block_state(&self, block_hash: BlockHash) -> Option<PendingBlockState> {
+        self.tree.read().pending_block_state(block_hash)
+    }
+}
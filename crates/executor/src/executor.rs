@@ -1,52 +1,69 @@
 use crate::execution_result::{
     AccountChangeSet, AccountInfoChangeSet, ExecutionResult, TransactionChangeSet,
 };
-use reth_interfaces::executor::Error;
+use reth_interfaces::{executor::Error, provider::ProviderError};
 use reth_primitives::{
-    bloom::logs_bloom, Account, Address, Block, Bloom, ChainSpec, Hardfork, Header, Log, Receipt,
-    TransactionSigned, H256, U256,
+    constants::{BLOCKHASH_WINDOW, HISTORY_SERVE_WINDOW, HISTORY_STORAGE_ADDRESS},
+    proofs, Account, Address, Block, BlockNumber, Bloom, ChainSpec, Hardfork, Header, Log, Receipt,
+    TransactionSigned, H256, KECCAK_EMPTY, U256,
 };
-use reth_provider::{BlockExecutor, StateProvider};
+use reth_provider::{trie::PrefetchHandle, BlockExecutor, StateProvider};
 use reth_revm::{
     config::{WEI_2ETH, WEI_3ETH, WEI_5ETH},
     database::SubState,
-    env::{fill_cfg_and_block_env, fill_tx_env},
-    into_reth_log, to_reth_acc,
+    into_reth_log, to_reth_acc, EthEvmConfig, EvmConfig,
+};
+use reth_revm_inspectors::{
+    opcode_counter::OpcodeCounter,
+    stack::{InspectorStack, InspectorStackConfig},
+    tracing::{CallTraceArena, TraceInspectorConfig, TracingInspector},
 };
-use reth_revm_inspectors::stack::{InspectorStack, InspectorStackConfig};
 use revm::{
     db::AccountState,
     primitives::{
         hash_map::{self, Entry},
         Account as RevmAccount, AccountInfo, Bytecode, ResultAndState,
     },
-    EVM,
+    Database, EVM,
 };
 use std::{
     collections::{BTreeMap, HashMap},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 /// Main block executor
-pub struct Executor<DB>
+///
+/// The EVM is configured by the [EvmConfig], which defaults to the EVM of Ethereum mainnet.
+pub struct Executor<DB, E = EthEvmConfig>
 where
     DB: StateProvider,
 {
     /// The configured chain-spec
     pub chain_spec: Arc<ChainSpec>,
     evm: EVM<SubState<DB>>,
+    evm_config: E,
     stack: InspectorStack,
+    /// Handle to send the keys touched by transactions to the trie prefetcher.
+    prefetch: Option<PrefetchHandle>,
 }
 
-impl<DB> From<Arc<ChainSpec>> for Executor<DB>
+impl<DB, E> From<Arc<ChainSpec>> for Executor<DB, E>
 where
     DB: StateProvider,
+    E: EvmConfig + Default,
 {
     /// Instantiates a new executor from the chainspec. Must call
     /// `with_db` to set the database before executing.
     fn from(chain_spec: Arc<ChainSpec>) -> Self {
         let evm = EVM::new();
-        Executor { chain_spec, evm, stack: InspectorStack::new(InspectorStackConfig::default()) }
+        Executor {
+            chain_spec,
+            evm,
+            evm_config: E::default(),
+            stack: InspectorStack::new(InspectorStackConfig::default()),
+            prefetch: None,
+        }
     }
 }
 
@@ -56,10 +73,28 @@ where
 {
     /// Creates a new executor from the given chain spec and database.
     pub fn new(chain_spec: Arc<ChainSpec>, db: SubState<DB>) -> Self {
+        Self::with_evm_config(chain_spec, db, EthEvmConfig::default())
+    }
+}
+
+impl<DB, E> Executor<DB, E>
+where
+    DB: StateProvider,
+    E: EvmConfig,
+{
+    /// Creates a new executor from the given chain spec and database, that runs transactions in
+    /// the EVM configured by the [EvmConfig].
+    pub fn with_evm_config(chain_spec: Arc<ChainSpec>, db: SubState<DB>, evm_config: E) -> Self {
         let mut evm = EVM::new();
         evm.database(db);
 
-        Executor { chain_spec, evm, stack: InspectorStack::new(InspectorStackConfig::default()) }
+        Executor {
+            chain_spec,
+            evm,
+            evm_config,
+            stack: InspectorStack::new(InspectorStackConfig::default()),
+            prefetch: None,
+        }
     }
 
     /// Configures the executor with the given inspectors.
@@ -68,6 +103,13 @@ where
         self
     }
 
+    /// Sends the accounts and storage slots touched by each executed transaction to the trie
+    /// prefetcher.
+    pub fn with_prefetcher(mut self, prefetch: PrefetchHandle) -> Self {
+        self.prefetch = Some(prefetch);
+        self
+    }
+
     /// Gives a reference to the database
     pub fn db(&mut self) -> &mut SubState<DB> {
         self.evm.db().expect("db to not be moved")
@@ -85,19 +127,53 @@ where
                 Err(Error::SenderRecoveryError)
             }
         } else {
-            body.iter().map(|tx| tx.recover_signer().ok_or(Error::SenderRecoveryError)).collect()
+            recover_signers(body)
         }
     }
 
-    /// Initializes the config and block env.
+    /// Initializes the config and block env, and the custom precompiles and opcode gas of the
+    /// spec.
     fn init_env(&mut self, header: &Header, total_difficulty: U256) {
-        fill_cfg_and_block_env(
+        self.evm_config.fill_cfg_and_block_env(
             &mut self.evm.env.cfg,
             &mut self.evm.env.block,
             &self.chain_spec,
             header,
             total_difficulty,
         );
+
+        self.stack.set_evm_config(&self.evm_config, self.evm.env.cfg.spec_id);
+    }
+
+    /// Loads the hashes of the ancestors that are accessible to the `BLOCKHASH` opcode with a
+    /// single range read, instead of a lookup per opcode.
+    fn load_block_hashes(&mut self, number: BlockNumber) -> Result<(), Error> {
+        let Some(end) = number.checked_sub(1) else { return Ok(()) };
+        let db = self.db();
+
+        // The ancestors of the previously executed block are already cached.
+        let mut start = number.saturating_sub(BLOCKHASH_WINDOW);
+        while start <= end && db.block_hashes.contains_key(&U256::from(start)) {
+            start += 1;
+        }
+        if start > end {
+            return Ok(())
+        }
+
+        match db.db.state().canonical_hashes_range(start, end) {
+            Ok(hashes) => {
+                for (number, hash) in (start..).zip(hashes) {
+                    db.block_hashes.insert(U256::from(number), hash);
+                }
+                Ok(())
+            }
+            // Ancestors that are missing from the database, e.g. below the lowest available block
+            // of a checkpoint sync, are still looked up by the opcode.
+            Err(reth_interfaces::Error::Provider(
+                ProviderError::CanonicalHeader { .. } | ProviderError::BlockUnavailable { .. },
+            )) => Ok(()),
+            Err(_) => Err(Error::ProviderError),
+        }
     }
 
     /// Commit change to database and return change diff that is used to update state and create
@@ -364,6 +440,74 @@ where
         }
     }
 
+    /// Writes the hash of the parent block to the history contract of
+    /// [EIP-2935](https://eips.ethereum.org/EIPS/eip-2935) after Prague, before the transactions
+    /// of the block are executed.
+    ///
+    /// The hash is stored in the slot of the parent number in the ring buffer of the contract.
+    /// Returns the storage changes of the contract, which are empty before Prague.
+    pub fn apply_block_hash_history(
+        &mut self,
+        header: &Header,
+    ) -> Result<BTreeMap<U256, (U256, U256)>, Error> {
+        let mut changes = BTreeMap::new();
+        if header.number == 0 ||
+            !self.chain_spec.fork(Hardfork::Prague).active_at_timestamp(header.timestamp)
+        {
+            return Ok(changes)
+        }
+
+        let db = self.db();
+        let contract =
+            db.load_account(HISTORY_STORAGE_ADDRESS).map_err(|_| Error::ProviderError)?;
+        if contract.info.code_hash.0 == KECCAK_EMPTY.0 {
+            return Err(Error::HistoryContractMissing)
+        }
+
+        let slot = U256::from((header.number - 1) % HISTORY_SERVE_WINDOW);
+        let old = db.storage(HISTORY_STORAGE_ADDRESS, slot).map_err(|_| Error::ProviderError)?;
+        let new = U256::from_be_bytes(header.parent_hash.0);
+        db.insert_account_storage(HISTORY_STORAGE_ADDRESS, slot, new)
+            .map_err(|_| Error::ProviderError)?;
+        changes.insert(slot, (old, new));
+        Ok(changes)
+    }
+
+    /// Applies the changes made after the transactions of the block, the block rewards, the
+    /// withdrawals and the DAO fork, and returns the changes of the whole block.
+    ///
+    /// [BlockExecutor::execute] calls this after [Executor::execute_transactions]. Block builders
+    /// call it directly, since they only fill in the gas used of the header after executing the
+    /// transactions.
+    pub fn apply_post_block_changes(
+        &mut self,
+        block: &Block,
+        total_difficulty: U256,
+        block_hash_history: BTreeMap<U256, (U256, U256)>,
+        tx_changesets: Vec<TransactionChangeSet>,
+    ) -> Result<ExecutionResult, Error> {
+        let mut block_changesets = BTreeMap::default();
+        let balance_increments = self.post_block_balance_increments(block, total_difficulty)?;
+        for (address, increment) in balance_increments {
+            let changeset = self.account_balance_increment_changeset(address, increment)?;
+            block_changesets.insert(address, changeset);
+        }
+
+        if self.chain_spec.fork(Hardfork::Dao).transitions_at_block(block.number) {
+            for (address, changeset) in self.dao_fork_changeset()? {
+                // No account collision between rewarded accounts and DAO fork related accounts.
+                block_changesets.insert(address, changeset);
+            }
+        }
+
+        let mut block_storage_changesets = BTreeMap::default();
+        if !block_hash_history.is_empty() {
+            block_storage_changesets.insert(HISTORY_STORAGE_ADDRESS, block_hash_history);
+        }
+
+        Ok(ExecutionResult { tx_changesets, block_changesets, block_storage_changesets })
+    }
+
     /// Runs a single transaction in the configured environment and proceeds
     /// to return the result and state diff (without applying it).
     ///
@@ -374,7 +518,7 @@ where
         sender: Address,
     ) -> Result<ResultAndState, Error> {
         // Fill revm structure.
-        fill_tx_env(&mut self.evm.env.tx, transaction, sender);
+        self.evm_config.fill_tx_env(&mut self.evm.env.tx, transaction, sender);
 
         let hash = transaction.hash();
         let out = if self.stack.should_inspect(&self.evm.env, hash) {
@@ -405,23 +549,40 @@ where
         let senders = self.recover_senders(&block.body, senders)?;
 
         self.init_env(&block.header, total_difficulty);
+        self.load_block_hashes(block.header.number)?;
 
         let mut cumulative_gas_used = 0;
         // output of execution
         let mut tx_changesets = Vec::with_capacity(block.body.len());
 
-        for (transaction, sender) in block.body.iter().zip(senders.into_iter()) {
+        for (tx_index, (transaction, sender)) in block.body.iter().zip(senders).enumerate() {
             // The sum of the transaction’s gas limit, Tg, and the gas utilised in this block prior,
             // must be no greater than the block’s gasLimit.
             let block_available_gas = block.header.gas_limit - cumulative_gas_used;
             if transaction.gas_limit() > block_available_gas {
-                return Err(Error::TransactionGasLimitMoreThenAvailableBlockGas {
-                    transaction_gas_limit: transaction.gas_limit(),
-                    block_available_gas,
-                })
+                return Err(transaction_error(
+                    tx_index,
+                    transaction,
+                    Error::TransactionGasLimitMoreThenAvailableBlockGas {
+                        transaction_gas_limit: transaction.gas_limit(),
+                        block_available_gas,
+                    },
+                ))
             }
             // Execute transaction.
-            let ResultAndState { result, state } = self.transact(transaction, sender)?;
+            let ResultAndState { result, state } = self
+                .transact(transaction, sender)
+                .map_err(|err| transaction_error(tx_index, transaction, err))?;
+
+            // warm up the trie nodes for the state root while the next transactions execute
+            if let Some(prefetch) = &self.prefetch {
+                for (address, account) in &state {
+                    prefetch.prefetch_account(*address);
+                    for slot in account.storage.keys() {
+                        prefetch.prefetch_storage(*address, H256(slot.to_be_bytes()));
+                    }
+                }
+            }
 
             // commit changes
             let (changeset, new_bytecodes) = self.commit_changes(state);
@@ -432,7 +593,7 @@ where
             // cast revm logs to reth logs
             let logs: Vec<Log> = result.logs().into_iter().map(into_reth_log).collect();
 
-            // Push transaction changeset and calculate header bloom filter for receipt.
+            // Push transaction changeset and receipt.
             tx_changesets.push(TransactionChangeSet {
                 receipt: Receipt {
                     tx_type: transaction.tx_type(),
@@ -440,8 +601,12 @@ where
                     // receipts`.
                     success: result.is_success(),
                     cumulative_gas_used,
-                    bloom: logs_bloom(logs.iter()),
                     logs,
+                    // deposit transactions are not executed
+                    #[cfg(feature = "optimism")]
+                    deposit_nonce: None,
+                    #[cfg(feature = "optimism")]
+                    deposit_receipt_version: None,
                 },
                 changeset,
                 new_bytecodes,
@@ -450,11 +615,115 @@ where
 
         Ok((tx_changesets, cumulative_gas_used))
     }
+
+    /// Replays the transactions of the block and records the call traces of every transaction,
+    /// together with the gas it used.
+    ///
+    /// The state is expected to be the state of the parent block.
+    pub fn trace_transactions(
+        &mut self,
+        block: &Block,
+        total_difficulty: U256,
+        senders: Option<Vec<Address>>,
+        config: TraceInspectorConfig,
+    ) -> Result<Vec<(CallTraceArena, u64)>, Error> {
+        let senders = self.recover_senders(&block.body, senders)?;
+
+        self.apply_block_hash_history(&block.header)?;
+        self.init_env(&block.header, total_difficulty);
+        self.load_block_hashes(block.header.number)?;
+
+        let mut traces = Vec::with_capacity(block.body.len());
+        for (tx_index, (transaction, sender)) in block.body.iter().zip(senders).enumerate() {
+            self.stack.tracer = Some(TracingInspector::new(config));
+            let ResultAndState { result, state } = self
+                .transact(transaction, sender)
+                .map_err(|err| transaction_error(tx_index, transaction, err))?;
+            let tracer = self.stack.tracer.take().expect("tracer is set");
+
+            // the next transactions are traced on top of the changes of this one
+            self.commit_changes(state);
+            traces.push((tracer.finalize(), result.gas_used()));
+        }
+
+        Ok(traces)
+    }
+
+    /// Executes the transactions of the block one by one and measures the wall time, the gas
+    /// and the state accesses of each transaction.
+    ///
+    /// Only the execution of a transaction is timed, committing its changes to the cached state is
+    /// not.
+    pub fn profile_transactions(
+        &mut self,
+        block: &Block,
+        total_difficulty: U256,
+        senders: Option<Vec<Address>>,
+    ) -> Result<Vec<TransactionProfile>, Error> {
+        let senders = self.recover_senders(&block.body, senders)?;
+
+        self.apply_block_hash_history(&block.header)?;
+        self.init_env(&block.header, total_difficulty);
+        self.load_block_hashes(block.header.number)?;
+
+        let mut profiles = Vec::with_capacity(block.body.len());
+        for (tx_index, (transaction, sender)) in block.body.iter().zip(senders).enumerate() {
+            self.stack.opcode_counter = Some(OpcodeCounter::default());
+            let started_at = Instant::now();
+            let ResultAndState { result, state } = self
+                .transact(transaction, sender)
+                .map_err(|err| transaction_error(tx_index, transaction, err))?;
+            let elapsed = started_at.elapsed();
+            let counter = self.stack.opcode_counter.take().expect("counter is set");
+
+            let state_reads =
+                state.values().map(|account| 1 + account.storage.len() as u64).sum::<u64>();
+            let (changes, _) = self.commit_changes(state);
+            let state_writes = changes
+                .values()
+                .map(|change| {
+                    let account =
+                        !matches!(change.account, AccountInfoChangeSet::NoChange { .. }) as u64;
+                    let slots = change.storage.values().filter(|(old, new)| old != new).count();
+                    account + slots as u64
+                })
+                .sum();
+
+            profiles.push(TransactionProfile {
+                elapsed,
+                gas_used: result.gas_used(),
+                state_reads,
+                state_writes,
+                sloads: counter.sloads,
+                sstores: counter.sstores,
+            });
+        }
+
+        Ok(profiles)
+    }
+}
+
+/// The execution profile of a transaction, see [Executor::profile_transactions].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionProfile {
+    /// Wall time of the execution.
+    pub elapsed: Duration,
+    /// Gas used by the transaction.
+    pub gas_used: u64,
+    /// Number of accounts and storage slots loaded by the transaction.
+    pub state_reads: u64,
+    /// Number of accounts and storage slots changed by the transaction.
+    pub state_writes: u64,
+    /// Number of executed `SLOAD`s.
+    pub sloads: u64,
+    /// Number of executed `SSTORE`s.
+    pub sstores: u64,
 }
 
-impl<DB> BlockExecutor<DB> for Executor<DB>
+impl<DB, E> BlockExecutor<DB> for Executor<DB, E>
 where
     DB: StateProvider,
+    E: EvmConfig,
 {
     fn execute(
         &mut self,
@@ -462,6 +731,7 @@ where
         total_difficulty: U256,
         senders: Option<Vec<Address>>,
     ) -> Result<ExecutionResult, Error> {
+        let block_hash_history = self.apply_block_hash_history(&block.header)?;
         let (tx_changesets, cumulative_gas_used) =
             self.execute_transactions(block, total_difficulty, senders)?;
 
@@ -470,21 +740,7 @@ where
             return Err(Error::BlockGasUsed { got: cumulative_gas_used, expected: block.gas_used })
         }
 
-        let mut block_changesets = BTreeMap::default();
-        let balance_increments = self.post_block_balance_increments(block, total_difficulty)?;
-        for (address, increment) in balance_increments {
-            let changeset = self.account_balance_increment_changeset(address, increment)?;
-            block_changesets.insert(address, changeset);
-        }
-
-        if self.chain_spec.fork(Hardfork::Dao).transitions_at_block(block.number) {
-            for (address, changeset) in self.dao_fork_changeset()? {
-                // No account collision between rewarded accounts and DAO fork related accounts.
-                block_changesets.insert(address, changeset);
-            }
-        }
-
-        Ok(ExecutionResult { tx_changesets, block_changesets })
+        self.apply_post_block_changes(block, total_difficulty, block_hash_history, tx_changesets)
     }
 
     fn execute_and_verify_receipt(
@@ -511,20 +767,46 @@ where
     }
 }
 
+/// Recovers the signers of the transactions.
+///
+/// Returns an error with the index and hash of the first transaction whose signer can't be
+/// recovered.
+pub fn recover_signers(body: &[TransactionSigned]) -> Result<Vec<Address>, Error> {
+    body.iter()
+        .enumerate()
+        .map(|(tx_index, transaction)| {
+            transaction
+                .recover_signer()
+                .ok_or_else(|| transaction_error(tx_index, transaction, Error::SenderRecoveryError))
+        })
+        .collect()
+}
+
+/// Wraps the error of a transaction of the block with the index and hash of the transaction.
+fn transaction_error(tx_index: usize, transaction: &TransactionSigned, source: Error) -> Error {
+    Error::TransactionError { tx_index, tx_hash: transaction.hash(), source: Box::new(source) }
+}
+
 /// Verify receipts
 pub fn verify_receipt<'a>(
     expected_receipts_root: H256,
     expected_logs_bloom: Bloom,
-    receipts: impl Iterator<Item = &'a Receipt> + Clone,
+    receipts: impl Iterator<Item = &'a Receipt>,
 ) -> Result<(), Error> {
+    // Calculate the bloom of every receipt once, it's needed for the root and the header bloom.
+    let receipts_with_bloom = receipts.map(Receipt::with_bloom_ref).collect::<Vec<_>>();
+
     // Check receipts root.
-    let receipts_root = reth_primitives::proofs::calculate_receipt_root(receipts.clone());
+    let receipts_root = proofs::calculate_receipt_root_parallel(
+        &receipts_with_bloom,
+        proofs::DEFAULT_PARALLEL_RECEIPT_ROOT_THRESHOLD,
+    );
     if receipts_root != expected_receipts_root {
         return Err(Error::ReceiptRootDiff { got: receipts_root, expected: expected_receipts_root })
     }
 
     // Create header log bloom.
-    let logs_bloom = receipts.fold(Bloom::zero(), |bloom, r| bloom | r.bloom);
+    let logs_bloom = receipts_with_bloom.iter().fold(Bloom::zero(), |bloom, r| bloom | r.bloom);
     if logs_bloom != expected_logs_bloom {
         return Err(Error::BloomLogDiff {
             expected: Box::new(expected_logs_bloom),
@@ -534,14 +816,52 @@ pub fn verify_receipt<'a>(
     Ok(())
 }
 
+/// Cross-checks the header of an executed block against the receipts of the execution and the
+/// parent header.
+///
+/// The gas used of the header has to match the cumulative gas used of the last receipt, and after
+/// London the base fee has to match the base fee derived from the parent.
+pub fn verify_post_execution<'a>(
+    header: &Header,
+    parent: &Header,
+    chain_spec: &ChainSpec,
+    receipts: impl Iterator<Item = &'a Receipt>,
+) -> Result<(), Error> {
+    let gas_used = receipts.last().map(|receipt| receipt.cumulative_gas_used).unwrap_or_default();
+    if header.gas_used != gas_used {
+        return Err(Error::BlockGasUsed { got: gas_used, expected: header.gas_used })
+    }
+
+    let expected_base_fee = chain_spec.next_block_base_fee(parent);
+    if header.base_fee_per_gas != expected_base_fee {
+        return Err(Error::BlockBaseFeeDiff {
+            got: header.base_fee_per_gas,
+            expected: expected_base_fee,
+        })
+    }
+
+    // TODO: after Cancun, check the `blob_gas_used` of the header against the blob gas of the
+    // transactions and return `Error::BlockBlobGasUsed`, once headers have the EIP-4844 fields.
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        tables,
+        transaction::DbTxMut,
+    };
     use reth_primitives::{
         hex_literal::hex, keccak256, Account, Address, Bytecode, Bytes, ChainSpecBuilder,
-        ForkCondition, StorageKey, H256, MAINNET, U256,
+        ForkCondition, Signature, StorageKey, Transaction, TransactionKind, TxLegacy, H256,
+        MAINNET, U256,
+    };
+    use reth_provider::{
+        AccountProvider, BlockHashProvider, ShareableDatabase, StateProvider, StateProviderFactory,
     };
-    use reth_provider::{AccountProvider, BlockHashProvider, StateProvider};
     use reth_revm::database::State;
     use reth_rlp::Decodable;
     use std::{collections::HashMap, str::FromStr};
@@ -607,6 +927,13 @@ mod tests {
         ) -> reth_interfaces::Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
             todo!()
         }
+
+        fn state_root(
+            &self,
+            _post_state: reth_provider::trie::HashedPostState,
+        ) -> reth_interfaces::Result<H256> {
+            todo!()
+        }
     }
 
     #[test]
@@ -834,6 +1161,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn block_hash_history_after_prague() {
+        let chain_spec = Arc::new(ChainSpecBuilder::from(&*MAINNET).prague_activated().build());
+        let header =
+            Header { number: 8192, parent_hash: H256::repeat_byte(1), ..Header::default() };
+
+        // the history contract is required after Prague
+        let db = SubState::new(State::new(StateProviderTest::default()));
+        let mut executor = Executor::new(chain_spec.clone(), db);
+        assert_eq!(executor.apply_block_hash_history(&header), Err(Error::HistoryContractMissing));
+
+        // the slot of the parent holds the hash of the block one window earlier
+        let mut db = StateProviderTest::default();
+        db.insert_account(
+            HISTORY_STORAGE_ADDRESS,
+            Account::default(),
+            Some(hex!("00").into()),
+            HashMap::from([(H256::zero(), U256::from(1))]),
+        );
+        let mut executor = Executor::new(chain_spec, SubState::new(State::new(db)));
+        let parent_hash = U256::from_be_bytes(header.parent_hash.0);
+        assert_eq!(
+            executor.apply_block_hash_history(&header).unwrap(),
+            BTreeMap::from([(U256::ZERO, (U256::from(1), parent_hash))])
+        );
+        assert_eq!(executor.db().storage(HISTORY_STORAGE_ADDRESS, U256::ZERO), Ok(parent_hash));
+    }
+
     #[test]
     fn test_selfdestruct() {
         // Modified version of eth test. Storage is added for selfdestructed account to see
@@ -1010,4 +1365,119 @@ mod tests {
         let account = db.load_account(account).unwrap();
         assert_eq!(account.account_state, AccountState::StorageCleared);
     }
+
+    #[test]
+    fn transaction_gas_limit_above_available_block_gas() {
+        let sender = Address::from_low_u64_be(1);
+        let mut db = StateProviderTest::default();
+        db.insert_account(sender, Account::default(), None, HashMap::default());
+
+        let transfer = |nonce, gas_limit| {
+            TransactionSigned::from_transaction_and_signature(
+                Transaction::Legacy(TxLegacy {
+                    chain_id: Some(1),
+                    nonce,
+                    gas_limit,
+                    to: TransactionKind::Call(Address::from_low_u64_be(2)),
+                    ..Default::default()
+                }),
+                Signature::default(),
+            )
+        };
+        // the last transaction fits the block gas limit, but not the gas left by the first one
+        let block = Block {
+            header: Header { number: 1, gas_limit: 50_000, ..Default::default() },
+            body: vec![transfer(0, 21_000), transfer(1, 30_000)],
+            ..Default::default()
+        };
+
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().berlin_activated().build());
+        let mut executor = Executor::new(chain_spec, SubState::new(State::new(db)));
+        assert_eq!(
+            executor.execute_transactions(&block, U256::ZERO, Some(vec![sender; 2])).unwrap_err(),
+            Error::TransactionError {
+                tx_index: 1,
+                tx_hash: block.body[1].hash(),
+                source: Box::new(Error::TransactionGasLimitMoreThenAvailableBlockGas {
+                    transaction_gas_limit: 30_000,
+                    block_available_gas: 29_000,
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn post_execution_gas_used_mismatch() {
+        let chain_spec = ChainSpecBuilder::mainnet().berlin_activated().build();
+        let receipts = [
+            Receipt { cumulative_gas_used: 21_000, ..Default::default() },
+            Receipt { cumulative_gas_used: 42_000, ..Default::default() },
+        ];
+        let parent = Header::default();
+        let header = Header { number: 1, gas_used: 42_000, ..Default::default() };
+        assert_eq!(verify_post_execution(&header, &parent, &chain_spec, receipts.iter()), Ok(()));
+
+        let header = Header { gas_used: 21_000, ..header };
+        assert_eq!(
+            verify_post_execution(&header, &parent, &chain_spec, receipts.iter()),
+            Err(Error::BlockGasUsed { got: 42_000, expected: 21_000 })
+        );
+    }
+
+    #[test]
+    fn post_execution_base_fee_mismatch() {
+        let chain_spec = ChainSpecBuilder::mainnet().london_activated().build();
+        let parent = Header {
+            gas_limit: 30_000_000,
+            gas_used: 30_000_000,
+            base_fee_per_gas: Some(1_000_000_000),
+            ..Default::default()
+        };
+        // a full parent raises the base fee by 1/8
+        let header =
+            Header { number: 1, base_fee_per_gas: Some(1_125_000_000), ..Default::default() };
+        assert_eq!(
+            verify_post_execution(&header, &parent, &chain_spec, std::iter::empty()),
+            Ok(())
+        );
+
+        let header = Header { base_fee_per_gas: Some(1_000_000_000), ..header };
+        assert_eq!(
+            verify_post_execution(&header, &parent, &chain_spec, std::iter::empty()),
+            Err(Error::BlockBaseFeeDiff {
+                got: Some(1_000_000_000),
+                expected: Some(1_125_000_000)
+            })
+        );
+    }
+
+    #[test]
+    fn block_hashes_across_substate_and_canonical_headers() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        db.update(|tx| {
+            for number in 0..300 {
+                tx.put::<tables::CanonicalHeaders>(number, H256::from_low_u64_be(number))?;
+            }
+            Ok::<_, reth_db::Error>(())
+        })
+        .unwrap()
+        .unwrap();
+        let provider = ShareableDatabase::new(db, Arc::new(MAINNET.clone()));
+
+        // the substate already holds the hashes of the oldest ancestors of block 300
+        let mut substate = SubState::new(State::new(provider.latest().unwrap()));
+        for number in 44..200 {
+            substate.block_hashes.insert(U256::from(number), H256::from_low_u64_be(number + 1000));
+        }
+        let mut executor = Executor::new(Arc::new(MAINNET.clone()), substate);
+        executor.load_block_hashes(300).unwrap();
+
+        // the rest of the window is read from the canonical headers
+        let block_hashes = &executor.db().block_hashes;
+        assert_eq!(block_hashes.len(), BLOCKHASH_WINDOW as usize);
+        for number in 44..300 {
+            let expected = if number < 200 { number + 1000 } else { number };
+            assert_eq!(block_hashes[&U256::from(number)], H256::from_low_u64_be(expected));
+        }
+    }
 }
@@ -1,30 +1,53 @@
 use reth_primitives::ChainSpec;
-use reth_provider::{ExecutorFactory, StateProvider};
-use reth_revm::database::{State, SubState};
+use reth_provider::{trie::PrefetchHandle, ExecutorFactory, StateProvider};
+use reth_revm::{
+    database::{State, SubState},
+    EthEvmConfig, EvmConfig,
+};
 
 use crate::executor::Executor;
 use std::sync::Arc;
 
 /// Factory that spawn Executor.
 #[derive(Clone, Debug)]
-pub struct Factory {
+pub struct Factory<E = EthEvmConfig> {
     chain_spec: Arc<ChainSpec>,
+    evm_config: E,
 }
 
 impl Factory {
     /// Create new factory
     pub fn new(chain_spec: Arc<ChainSpec>) -> Self {
-        Self { chain_spec }
+        Self { chain_spec, evm_config: EthEvmConfig::default() }
     }
 }
 
-impl ExecutorFactory for Factory {
-    type Executor<SP: StateProvider> = Executor<SP>;
+impl<E> Factory<E> {
+    /// Configures the EVM of the spawned executors.
+    pub fn with_evm_config<C: EvmConfig>(self, evm_config: C) -> Factory<C> {
+        Factory { chain_spec: self.chain_spec, evm_config }
+    }
+}
+
+impl<E> ExecutorFactory for Factory<E>
+where
+    E: EvmConfig + Clone,
+{
+    type Executor<SP: StateProvider> = Executor<SP, E>;
 
     /// Executor with [`StateProvider`]
     fn with_sp<SP: StateProvider>(&self, sp: SP) -> Self::Executor<SP> {
         let substate = SubState::new(State::new(sp));
-        Executor::new(self.chain_spec.clone(), substate)
+        Executor::with_evm_config(self.chain_spec.clone(), substate, self.evm_config.clone())
+    }
+
+    /// Executor with [`StateProvider`] that feeds the trie prefetcher
+    fn with_sp_and_prefetcher<SP: StateProvider>(
+        &self,
+        sp: SP,
+        prefetch: PrefetchHandle,
+    ) -> Self::Executor<SP> {
+        self.with_sp(sp).with_prefetcher(prefetch)
     }
 
     /// Return internal chainspec
@@ -2,7 +2,7 @@
 
 use reth_interfaces::{provider::ProviderError, Result};
 use reth_primitives::{Account, Address, BlockHash, BlockNumber, Bytecode, Bytes, H256, U256};
-use reth_provider::{AccountProvider, BlockHashProvider, StateProvider};
+use reth_provider::{trie::HashedPostState, AccountProvider, BlockHashProvider, StateProvider};
 use std::collections::{hash_map::Entry, BTreeMap, HashMap};
 
 use crate::execution_result::{AccountInfoChangeSet, ExecutionResult};
@@ -18,6 +18,12 @@ pub struct SubStateData {
 }
 
 impl SubStateData {
+    /// Returns the number of accounts, storage slots and bytecodes held by the substate.
+    pub fn num_entries(&self) -> usize {
+        self.accounts.values().map(|account| 1 + account.storage.len()).sum::<usize>() +
+            self.bytecodes.len()
+    }
+
     /// Apply changesets to substate.
     pub fn apply(&mut self, changesets: &[ExecutionResult]) {
         for changeset in changesets {
@@ -44,6 +50,10 @@ impl SubStateData {
         for (address, change) in changeset.block_changesets.iter() {
             self.apply_account(address, change)
         }
+        // apply block storage changes
+        for (address, storage) in changeset.block_storage_changesets.iter() {
+            self.apply_storage(address, storage)
+        }
     }
 
     /// Apply account changeset to substate
@@ -93,6 +103,9 @@ impl SubStateData {
     pub fn revert(&mut self, changesets: &[ExecutionResult]) {
         for changeset in changesets.iter().rev() {
             // revert block changeset
+            for (address, storage) in changeset.block_storage_changesets.iter() {
+                self.revert_storage(address, storage)
+            }
             for (address, change) in changeset.block_changesets.iter() {
                 self.revert_account(address, change)
             }
@@ -226,7 +239,7 @@ pub struct SubStateWithProvider<'a, SP: StateProvider> {
     provider: SP,
     /// side chain block hashes
     sidechain_block_hashes: &'a BTreeMap<BlockNumber, BlockHash>,
-    /// Last N canonical hashes,
+    /// Last N canonical hashes, the provider is only asked for the hashes below them.
     canonical_block_hashes: &'a BTreeMap<BlockNumber, BlockHash>,
 }
 
@@ -253,12 +266,45 @@ impl<'a, SP: StateProvider> BlockHashProvider for SubStateWithProvider<'a, SP> {
             return Ok(Some(sidechain_block_hash))
         }
 
-        Ok(Some(
-            self.canonical_block_hashes
+        if let Some(canonical_block_hash) = self.canonical_block_hashes.get(&block_number).cloned()
+        {
+            return Ok(Some(canonical_block_hash))
+        }
+
+        // The canonical hashes cover the last 256 blocks and the reorg depth, only older hashes are
+        // read from the database.
+        let below_window = self
+            .canonical_block_hashes
+            .first_key_value()
+            .map_or(true, |(first_number, _)| block_number < *first_number);
+        if below_window {
+            return self.provider.block_hash(number)
+        }
+
+        Err(ProviderError::BlockchainTreeBlockHash { block_number }.into())
+    }
+
+    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>> {
+        // The hashes below the canonical window are read from the database in one go.
+        let window_start = self
+            .canonical_block_hashes
+            .first_key_value()
+            .map_or(BlockNumber::MAX, |(first_number, _)| *first_number);
+        let mut hashes = if start < window_start {
+            self.provider.canonical_hashes_range(start, end.min(window_start - 1))?
+        } else {
+            Vec::new()
+        };
+
+        for block_number in start.max(window_start)..=end {
+            let hash = self
+                .sidechain_block_hashes
                 .get(&block_number)
-                .cloned()
-                .ok_or(ProviderError::BlockchainTreeBlockHash { block_number })?,
-        ))
+                .or_else(|| self.canonical_block_hashes.get(&block_number))
+                .ok_or(ProviderError::BlockchainTreeBlockHash { block_number })?;
+            hashes.push(*hash);
+        }
+        Ok(hashes)
     }
 }
 
@@ -297,6 +343,12 @@ impl<'a, SP: StateProvider> StateProvider for SubStateWithProvider<'a, SP> {
         Err(ProviderError::HistoryStateRoot.into())
     }
 
+    /// The substate can't be expressed as changes to the provider state, so the state root is
+    /// unknown.
+    fn state_root(&self, _post_state: HashedPostState) -> Result<H256> {
+        Err(ProviderError::HistoryStateRoot.into())
+    }
+
     fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytecode>> {
         if let Some((_, bytecode)) = self.substate.bytecodes.get(&code_hash).cloned() {
             return Ok(Some(bytecode))
@@ -304,3 +356,203 @@ impl<'a, SP: StateProvider> StateProvider for SubStateWithProvider<'a, SP> {
         self.provider.bytecode_by_hash(code_hash)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::Executor;
+    use reth_primitives::{
+        hex_literal::hex, keccak256, Block, ChainSpecBuilder, Header, Signature, Transaction,
+        TransactionKind, TransactionSigned, TxLegacy,
+    };
+    use reth_revm::database::{State, SubState};
+    use std::{
+        str::FromStr,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    /// A state provider that counts the block hash lookups.
+    #[derive(Debug, Default)]
+    struct CountingProvider {
+        accounts: HashMap<Address, Account>,
+        contracts: HashMap<H256, Bytecode>,
+        block_hash_reads: AtomicUsize,
+    }
+
+    impl BlockHashProvider for CountingProvider {
+        fn block_hash(&self, number: U256) -> Result<Option<H256>> {
+            self.block_hash_reads.fetch_add(1, Ordering::Relaxed);
+            Ok(Some(H256::from_low_u64_be(number.as_limbs()[0])))
+        }
+
+        fn canonical_hashes_range(
+            &self,
+            start: BlockNumber,
+            end: BlockNumber,
+        ) -> Result<Vec<H256>> {
+            // a range is read with a single lookup
+            self.block_hash_reads.fetch_add(1, Ordering::Relaxed);
+            Ok((start..=end).map(H256::from_low_u64_be).collect())
+        }
+    }
+
+    impl AccountProvider for CountingProvider {
+        fn basic_account(&self, address: Address) -> Result<Option<Account>> {
+            Ok(self.accounts.get(&address).cloned())
+        }
+    }
+
+    impl StateProvider for CountingProvider {
+        fn storage(
+            &self,
+            _account: Address,
+            _storage_key: reth_primitives::StorageKey,
+        ) -> Result<Option<reth_primitives::StorageValue>> {
+            Ok(None)
+        }
+
+        fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytecode>> {
+            Ok(self.contracts.get(&code_hash).cloned())
+        }
+
+        fn proof(
+            &self,
+            _address: Address,
+            _keys: &[H256],
+        ) -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
+            unimplemented!()
+        }
+
+        fn state_root(&self, _post_state: HashedPostState) -> Result<H256> {
+            unimplemented!()
+        }
+    }
+
+    /// Executes a block at `number` with a call to a contract that stores `BLOCKHASH(number - 10)`
+    /// and returns the stored hash.
+    fn execute_blockhash(
+        provider: &CountingProvider,
+        number: BlockNumber,
+        canonical_block_hashes: &BTreeMap<BlockNumber, BlockHash>,
+    ) -> U256 {
+        let contract = Address::from_str("095e7baea6a6c7c4c2dfeb977efac326af552d87").unwrap();
+        let sender = Address::from_str("a94f5374fce5edbc8e2a8697c15331677e6ebf0b").unwrap();
+
+        let substate = SubStateData::default();
+        let sidechain_block_hashes = BTreeMap::new();
+        let db = SubStateWithProvider::new(
+            &substate,
+            provider,
+            &sidechain_block_hashes,
+            canonical_block_hashes,
+        );
+
+        let transaction = Transaction::Legacy(TxLegacy {
+            chain_id: Some(1),
+            gas_limit: 100_000,
+            to: TransactionKind::Call(contract),
+            ..Default::default()
+        });
+        let block = Block {
+            header: Header { number, gas_limit: 1_000_000, ..Default::default() },
+            body: vec![TransactionSigned::from_transaction_and_signature(
+                transaction,
+                Signature::default(),
+            )],
+            ..Default::default()
+        };
+
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().berlin_activated().build());
+        let mut executor = Executor::new(chain_spec, SubState::new(State::new(db)));
+        let (changesets, _) =
+            executor.execute_transactions(&block, U256::ZERO, Some(vec![sender])).unwrap();
+
+        let storage = &changesets[0].changeset.get(&contract).unwrap().storage;
+        storage.get(&U256::ZERO).unwrap().1
+    }
+
+    fn provider() -> CountingProvider {
+        // PUSH1 10 NUMBER SUB BLOCKHASH PUSH1 0 SSTORE
+        let code = Bytes::from(hex!("600a43034060005500").as_slice());
+        let contract = Address::from_str("095e7baea6a6c7c4c2dfeb977efac326af552d87").unwrap();
+        let code_hash = keccak256(&code);
+
+        let mut provider = CountingProvider::default();
+        provider.accounts.insert(
+            contract,
+            Account { nonce: 1, balance: U256::ZERO, bytecode_hash: Some(code_hash) },
+        );
+        provider.contracts.insert(code_hash, Bytecode::new_raw(code.into()));
+        provider
+    }
+
+    #[test]
+    fn blockhash_from_canonical_window() {
+        let provider = provider();
+        let canonical_block_hashes =
+            (0..=300).map(|number| (number, H256::from_low_u64_be(number + 1000))).collect();
+
+        let hash = execute_blockhash(&provider, 301, &canonical_block_hashes);
+        assert_eq!(hash, U256::from(1291));
+        assert_eq!(provider.block_hash_reads.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn blockhash_below_canonical_window() {
+        let provider = provider();
+        let canonical_block_hashes =
+            (295..=300).map(|number| (number, H256::from_low_u64_be(number + 1000))).collect();
+
+        let hash = execute_blockhash(&provider, 301, &canonical_block_hashes);
+        assert_eq!(hash, U256::from(291));
+        assert_eq!(provider.block_hash_reads.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn blockhash_near_genesis() {
+        let provider = provider();
+        let canonical_block_hashes =
+            (0..=11).map(|number| (number, H256::from_low_u64_be(number + 1000))).collect();
+
+        // fewer than 256 ancestors are loaded for the block
+        let hash = execute_blockhash(&provider, 12, &canonical_block_hashes);
+        assert_eq!(hash, U256::from(1002));
+        assert_eq!(provider.block_hash_reads.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn canonical_hashes_range_across_database_boundary() {
+        let provider = provider();
+        let substate = SubStateData::default();
+        let sidechain_block_hashes = BTreeMap::from([(300, H256::from_low_u64_be(2300))]);
+        let canonical_block_hashes =
+            (295..=300).map(|number| (number, H256::from_low_u64_be(number + 1000))).collect();
+        let db = SubStateWithProvider::new(
+            &substate,
+            &provider,
+            &sidechain_block_hashes,
+            &canonical_block_hashes,
+        );
+
+        let expected = (290..295)
+            .map(H256::from_low_u64_be)
+            .chain((295..300).map(|number| H256::from_low_u64_be(number + 1000)))
+            .chain([H256::from_low_u64_be(2300)])
+            .collect::<Vec<_>>();
+        assert_eq!(db.canonical_hashes_range(290, 300).unwrap(), expected);
+        assert_eq!(provider.block_hash_reads.load(Ordering::Relaxed), 1);
+
+        // the range within the window is not read from the database
+        assert_eq!(db.canonical_hashes_range(296, 297).unwrap(), expected[6..8]);
+        assert_eq!(provider.block_hash_reads.load(Ordering::Relaxed), 1);
+
+        // the tree is expected to hold every hash above the window start
+        assert_eq!(
+            db.canonical_hashes_range(300, 301).unwrap_err(),
+            ProviderError::BlockchainTreeBlockHash { block_number: 301 }.into()
+        );
+    }
+}
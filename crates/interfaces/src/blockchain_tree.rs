@@ -0,0 +1,43 @@
+use crate::Error;
+use reth_primitives::{
+    BlockNumber, SealedBlock, TransactionMeta, TransactionSignedEcRecovered, TxHash,
+};
+use std::fmt::Debug;
+
+/// Re-export the blockchain tree snapshot types
+pub use reth_rpc_types::{
+    BlockIndicesSizes, BlockchainTreeState, ReorgEvent, ReorgStats, SideChainState, TreeBlock,
+};
+
+/// Gives read access to the blockchain tree of blocks that are not yet canonical.
+#[auto_impl::auto_impl(&, Arc)]
+pub trait BlockchainTreeViewer: Debug + Send + Sync {
+    /// Returns a snapshot of the chains and indices of the tree.
+    fn tree_state(&self) -> BlockchainTreeState;
+
+    /// Returns the most recent reorgs of the canonical chain.
+    fn reorg_stats(&self) -> ReorgStats;
+
+    /// Returns the transaction with the given hash if it is included in a block of the tree, and
+    /// the location of the transaction in the block.
+    fn pending_transaction_by_hash(
+        &self,
+        tx_hash: TxHash,
+    ) -> Option<(TransactionSignedEcRecovered, TransactionMeta)>;
+}
+
+/// Inserts blocks into the blockchain tree.
+#[auto_impl::auto_impl(&, Arc)]
+pub trait BlockchainTreeEngine: Debug + Send + Sync {
+    /// Executes the block on top of its parent and inserts it into the tree.
+    ///
+    /// Returns `false` if the parent of the block is not known.
+    fn insert_block(&self, block: SealedBlock) -> Result<bool, Error>;
+
+    /// Reloads the canonical hashes from the database after the canonical chain was unwound to
+    /// the given block outside of the tree, e.g. by the pipeline.
+    ///
+    /// The chains that fork from an unwound block are dropped, and blocks above the given block
+    /// can be inserted again.
+    fn update_canonical_hashes(&self, unwound_to: BlockNumber) -> Result<(), Error>;
+}
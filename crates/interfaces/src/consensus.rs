@@ -52,6 +52,13 @@ pub trait Consensus: Debug + Send + Sync {
     ///
     /// More info [here](https://github.com/paradigmxyz/reth/issues/237)
     fn has_block_reward(&self, total_difficulty: U256, difficulty: U256) -> bool;
+
+    /// After Prague every block writes the hash of its parent to the history contract of
+    /// [EIP-2935](https://eips.ethereum.org/EIPS/eip-2935).
+    ///
+    /// Like the block reward, the write is stored in a transition after the transactions of the
+    /// block.
+    fn has_block_hash_history(&self, timestamp: u64) -> bool;
 }
 
 /// Consensus Errors
@@ -1,16 +1,25 @@
-use reth_primitives::{Header, H256};
-use std::sync::Arc;
+use reth_primitives::{Address, Header, Receipt, SealedBlock, H256, U256};
+use std::{collections::BTreeMap, sync::Arc};
 use tokio::sync::mpsc::UnboundedReceiver;
 
 /// Type alias for a receiver that receives [NewBlockNotification]
 pub type NewBlockNotifications = UnboundedReceiver<NewBlockNotification>;
 
+/// Type alias for a receiver that receives [CanonStateNotification]
+pub type CanonStateNotifications = UnboundedReceiver<CanonStateNotification>;
+
 /// A type that allows to register chain related event subscriptions.
 pub trait ChainEventSubscriptions: Send + Sync {
     /// Get notified when a new block was imported.
     fn subscribe_new_blocks(&self) -> NewBlockNotifications;
 }
 
+/// A type that allows to register subscriptions to changes of the canonical chain.
+pub trait CanonStateSubscriptions: Send + Sync {
+    /// Get notified when blocks are committed to or removed from the canonical chain.
+    fn subscribe_canon_state(&self) -> CanonStateNotifications;
+}
+
 /// A notification that's emitted when a new block was imported.
 #[derive(Clone, Debug)]
 pub struct NewBlockNotification {
@@ -19,3 +28,63 @@ pub struct NewBlockNotification {
     /// The block header of the new block
     pub header: Arc<Header>,
 }
+
+/// A block of the canonical chain with the receipts of its transactions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CanonBlock {
+    /// The block.
+    pub block: SealedBlock,
+    /// The receipts of the transactions of the block, in the same order.
+    pub receipts: Vec<Receipt>,
+    /// The storage changes of the block by account.
+    pub storage: BTreeMap<Address, StorageChanges>,
+}
+
+/// The storage changes of an account by a block.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageChanges {
+    /// Whether the storage was wiped, by a selfdestruct, before the slots were changed.
+    ///
+    /// All slots that are not in `slots` are zero after the block.
+    pub wiped: bool,
+    /// The changed slots with their values after the block.
+    pub slots: BTreeMap<H256, U256>,
+}
+
+/// A notification that's emitted when the canonical chain changed.
+///
+/// The blocks of a notification are ordered by ascending block number.
+#[derive(Clone, Debug)]
+pub enum CanonStateNotification {
+    /// Blocks were appended to the canonical chain.
+    Commit {
+        /// The new canonical blocks.
+        new: Arc<Vec<CanonBlock>>,
+    },
+    /// Blocks were removed from the canonical chain and replaced by other blocks.
+    Reorg {
+        /// The blocks that are no longer canonical.
+        old: Arc<Vec<CanonBlock>>,
+        /// The new canonical blocks.
+        new: Arc<Vec<CanonBlock>>,
+    },
+}
+
+impl CanonStateNotification {
+    /// Returns the blocks that are no longer canonical.
+    pub fn reverted(&self) -> &[CanonBlock] {
+        match self {
+            CanonStateNotification::Commit { .. } => &[],
+            CanonStateNotification::Reorg { old, .. } => old,
+        }
+    }
+
+    /// Returns the new canonical blocks.
+    pub fn committed(&self) -> &[CanonBlock] {
+        match self {
+            CanonStateNotification::Commit { new } | CanonStateNotification::Reorg { new, .. } => {
+                new
+            }
+        }
+    }
+}
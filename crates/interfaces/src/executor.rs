@@ -13,6 +13,9 @@ pub enum Error {
     ExecutionFatalError,
     #[error("Failed to recover sender for transaction")]
     SenderRecoveryError,
+    /// A transaction of the block is invalid.
+    #[error("Transaction {tx_index} ({tx_hash:?}) is invalid: {source}")]
+    TransactionError { tx_index: usize, tx_hash: H256, source: Box<Error> },
     #[error("Receipt cumulative gas used {got:?} is different from expected {expected:?}")]
     ReceiptCumulativeGasUsedDiff { got: u64, expected: u64 },
     #[error("Receipt log count {got:?} is different from expected {expected:?}.")]
@@ -32,6 +35,16 @@ pub enum Error {
     },
     #[error("Block gas used {got} is different from expected gas used {expected}.")]
     BlockGasUsed { got: u64, expected: u64 },
+    #[error("Block base fee {got:?} is different from expected base fee {expected:?} derived from the parent.")]
+    BlockBaseFeeDiff { got: Option<u64>, expected: Option<u64> },
+    /// The blob gas used of the header doesn't match the blob gas of the transactions of the
+    /// block, after Cancun.
+    ///
+    /// Not returned yet, headers don't have the blob gas fields of EIP-4844.
+    #[error("Block blob gas used {got} is different from expected blob gas used {expected}.")]
+    BlockBlobGasUsed { got: u64, expected: u64 },
+    #[error("The history contract of EIP-2935 is not deployed, it is required after Prague.")]
+    HistoryContractMissing,
     #[error("Provider error")]
     ProviderError,
     #[error("BlockChainId can't be found in BlockchainTree with internal index {chain_id}")]
@@ -62,6 +75,10 @@ pub enum Error {
     CanonicalRevert { inner: String },
     #[error("Transaction error on commit: {inner:?}")]
     CanonicalCommit { inner: String },
+    #[error("Transaction error on recovery of a partial canonical commit: {inner:?}")]
+    CanonicalRecovery { inner: String },
     #[error("Transaction error on pipeline status update: {inner:?}")]
     PipelineStatusUpdate { inner: String },
+    #[error("Canonical chain is empty, the genesis block needs to be inserted first")]
+    EmptyCanonicalChain,
 }
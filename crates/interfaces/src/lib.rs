@@ -7,6 +7,9 @@
 
 //! Reth interface bindings
 
+/// Blockchain tree traits.
+pub mod blockchain_tree;
+
 /// Consensus traits.
 pub mod consensus;
 
@@ -54,3 +54,11 @@ pub struct NoopStatusUpdater;
 impl StatusUpdater for NoopStatusUpdater {
     fn update_status(&self, _: Head) {}
 }
+
+/// Updates the status of both updaters, e.g. of the network and of the rpc.
+impl<A: StatusUpdater, B: StatusUpdater> StatusUpdater for (A, B) {
+    fn update_status(&self, head: Head) {
+        self.0.update_status(head);
+        self.1.update_status(head);
+    }
+}
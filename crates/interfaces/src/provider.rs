@@ -1,4 +1,4 @@
-use reth_primitives::{Address, BlockHash, BlockNumber, TransitionId, TxNumber, H256};
+use reth_primitives::{Address, BlockHash, BlockNumber, TransitionId, TxNumber, H256, U256};
 
 /// Bundled errors variants thrown by various providers.
 #[allow(missing_docs)]
@@ -19,6 +19,9 @@ pub enum ProviderError {
     /// A block body is missing.
     #[error("Block body not found for block #{number}")]
     BlockBody { number: BlockNumber },
+    /// A requested block number doesn't fit into a [BlockNumber].
+    #[error("Block number {0} exceeds the range of block numbers")]
+    BlockNumberOverflow(U256),
     /// The block transition id for a certain block number is missing.
     #[error("Block transition id does not exist for block #{block_number}")]
     BlockTransition { block_number: BlockNumber },
@@ -50,6 +53,12 @@ pub enum ProviderError {
         /// The transaction id
         id: TxNumber,
     },
+    /// The receipts of a block are missing.
+    #[error("Receipts not found for block #{number}")]
+    Receipts {
+        /// The block number key
+        number: BlockNumber,
+    },
     /// A ommers are missing.
     #[error("Block ommers not found for block #{number}")]
     Ommers {
@@ -68,6 +77,22 @@ pub enum ProviderError {
     /// Reached the end of the transaction sender table.
     #[error("Got to the end of the transaction sender table")]
     EndOfTransactionSenderTable,
+    /// The block is below the lowest available block of the database, for example because the
+    /// node was synced from a trusted checkpoint.
+    #[error("Block #{number} is unavailable, the history of this node starts at block #{lowest_available}")]
+    BlockUnavailable {
+        /// The requested block number
+        number: BlockNumber,
+        /// The lowest block with available data
+        lowest_available: BlockNumber,
+    },
+    /// The state of the block is unavailable, because the node was synced from a trusted
+    /// checkpoint and the block was not executed on top of the state at the checkpoint yet.
+    #[error("State of block #{number} is unavailable, the blocks above the checkpoint were not executed")]
+    StateUnavailable {
+        /// The requested block number
+        number: BlockNumber,
+    },
     /// Missing block hash in BlockchainTree
     #[error("Missing block hash for block #{block_number:?} in blockchain tree")]
     BlockchainTreeBlockHash { block_number: BlockNumber },
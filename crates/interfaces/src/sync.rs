@@ -1,6 +1,8 @@
 //! Traits used when interacting with the sync status of the network.
 
 use reth_primitives::BlockNumber;
+use thiserror::Error;
+use tokio::sync::watch;
 
 /// A type that provides information about whether the node is currently syncing and the network is
 /// currently serving syncing related requests.
@@ -8,6 +10,9 @@ use reth_primitives::BlockNumber;
 pub trait SyncStateProvider: Send + Sync {
     /// Returns `true` if the network is undergoing sync.
     fn is_syncing(&self) -> bool;
+
+    /// Returns a receiver that observes every [SyncState] update.
+    fn subscribe_sync_state(&self) -> watch::Receiver<SyncState>;
 }
 
 /// An updater for updating the [SyncState] of the network.
@@ -70,8 +75,77 @@ impl SyncStateProvider for NoopSyncStateUpdate {
     fn is_syncing(&self) -> bool {
         false
     }
+
+    /// The returned receiver is always [SyncState::Idle] and never changes.
+    fn subscribe_sync_state(&self) -> watch::Receiver<SyncState> {
+        watch::channel(SyncState::Idle).1
+    }
 }
 
 impl SyncStateUpdater for NoopSyncStateUpdate {
     fn update_sync_state(&self, _state: SyncState) {}
 }
+
+/// A type that unwinds the local chain on request, e.g. the sync pipeline.
+#[async_trait::async_trait]
+pub trait ChainUnwinder: Send + Sync {
+    /// Unwinds the local chain to the given canonical block, all blocks above it are removed.
+    async fn unwind_to(&self, target: BlockNumber) -> Result<UnwindSummary, UnwindRequestError>;
+}
+
+/// The outcome of a requested unwind, see [ChainUnwinder::unwind_to].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct UnwindSummary {
+    /// The block the chain was unwound to.
+    pub target: BlockNumber,
+    /// The unwound stages, in the order they were unwound.
+    pub stages: Vec<StageUnwindSummary>,
+}
+
+/// The unwind of a single stage.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct StageUnwindSummary {
+    /// The id of the stage.
+    pub stage: String,
+    /// The progress of the stage before the unwind.
+    pub from: BlockNumber,
+    /// The progress of the stage after the unwind.
+    pub to: BlockNumber,
+}
+
+/// Errors of a requested unwind, see [ChainUnwinder::unwind_to].
+#[derive(Error, Clone, Eq, PartialEq, Debug)]
+pub enum UnwindRequestError {
+    /// The chain is syncing, the unwind can be retried once the sync reached the tip.
+    #[error("Pipeline is syncing, retry once it reached the tip")]
+    PipelineBusy,
+    /// The target is above the tip of the local chain.
+    #[error("Unwind target {target} is above the tip {tip}")]
+    AboveTip {
+        /// The requested target.
+        target: BlockNumber,
+        /// The tip of the local chain.
+        tip: BlockNumber,
+    },
+    /// The history needed to unwind to the target was pruned.
+    #[error("Unwind target {target} is below the prune horizon {horizon}")]
+    BelowPruneHorizon {
+        /// The requested target.
+        target: BlockNumber,
+        /// The lowest block the chain can be unwound to.
+        horizon: BlockNumber,
+    },
+    /// The unwind of a stage failed.
+    #[error("Unwind failed: {0}")]
+    Failed(String),
+    /// The pipeline is not running.
+    #[error("Pipeline is not running")]
+    PipelineClosed,
+}
+
+impl UnwindRequestError {
+    /// Whether the same unwind may succeed if it is requested again later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, UnwindRequestError::PipelineBusy)
+    }
+}
@@ -362,4 +362,8 @@ impl Consensus for TestConsensus {
     fn has_block_reward(&self, _: U256, _: U256) -> bool {
         true
     }
+
+    fn has_block_hash_history(&self, _: u64) -> bool {
+        false
+    }
 }
@@ -2,6 +2,7 @@
 
 mod bodies;
 mod headers;
+mod sync;
 
 /// Generators for different data structures like block headers, block bodies and ranges of those.
 pub mod generators;
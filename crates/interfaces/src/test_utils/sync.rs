@@ -0,0 +1,14 @@
+use crate::sync::{SyncState, SyncStateProvider};
+use reth_network_api::test_utils::NoopNetwork;
+use tokio::sync::watch;
+
+/// The network of tests is never syncing.
+impl SyncStateProvider for NoopNetwork {
+    fn is_syncing(&self) -> bool {
+        false
+    }
+
+    fn subscribe_sync_state(&self) -> watch::Receiver<SyncState> {
+        watch::channel(SyncState::Idle).1
+    }
+}
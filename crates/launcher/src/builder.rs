@@ -0,0 +1,562 @@
+//! Configuration of the components of a node.
+
+use crate::{
+    dev::dev_chain_spec,
+    handle::NodeHandle,
+    maintain::{discard_expired_transactions, reinject_orphaned_transactions, EXPIRY_INTERVAL},
+    miner::{init_genesis_state, DevBlockchainTree, DevMiner, MiningMode},
+};
+use eyre::{eyre, WrapErr};
+use jsonrpsee::{core::server::rpc_module::Methods, RpcModule};
+use reth_consensus::{beacon::BeaconConsensus, finality::LocalFinality};
+use reth_db::{
+    database::Database,
+    mdbx::{Env, WriteMap},
+    tables,
+    transaction::DbTx,
+};
+use reth_executor::{
+    blockchain_tree::{config::BlockchainTreeConfig, BlockchainTree},
+    Factory,
+};
+use reth_interfaces::{
+    blockchain_tree::BlockchainTreeEngine,
+    consensus::{Consensus, ForkchoiceState},
+};
+use reth_network::{
+    config::rng_secret_key, NetworkConfig, NetworkConfigBuilder, NetworkManager, TreeBlockImport,
+};
+use reth_primitives::{Address, ChainSpec, Head};
+use reth_provider::{BytecodeCache, CanonicalTip, ShareableDatabase};
+use reth_revm::{EthEvmConfig, EvmConfig};
+use reth_rpc_builder::{
+    RpcModuleBuilder, RpcModuleSelection, RpcServerConfig, TransportRpcModuleConfig,
+    TransportRpcModules,
+};
+use reth_staged_sync::utils::init::{init_db, init_genesis};
+use reth_stages::stages::FINISH;
+use reth_tasks::TaskManager;
+use reth_transaction_pool::{
+    blobstore::DiskFileBlobStore, CoinbaseTipOrdering, EthTransactionValidator, Pool, PoolConfig,
+    PooledTransaction, TransactionOrdering, TransactionPool,
+};
+use std::{
+    fmt,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    sync::Arc,
+};
+use tempfile::TempDir;
+use tokio::{runtime::Handle, sync::watch};
+use tracing::*;
+
+/// How many blocks below the tip the blocks built by a development node can be reorged.
+const DEV_MAX_REORG_DEPTH: u64 = 64;
+
+/// How many blocks above the finalized block the tree of a development node keeps.
+const DEV_MAX_BLOCKS_IN_CHAIN: u64 = 2 * DEV_MAX_REORG_DEPTH;
+
+/// The pool [NodeBuilder]s use by default, see the [PoolBuilder] impl of [PoolConfig].
+///
+/// The pending transactions are ordered by their tip unless a custom ordering is set with an
+/// [EthPoolBuilder].
+pub type EthPool<Client, T = CoinbaseTipOrdering<PooledTransaction>> =
+    Pool<EthTransactionValidator<Client, PooledTransaction>, T>;
+
+/// Creates the transaction pool of a node.
+///
+/// This is implemented for [PoolConfig], which configures an [EthPool], for [EthPoolBuilder], which
+/// configures an [EthPool] with a custom [TransactionOrdering], and for closures that create a
+/// custom pool from the provider and the chain spec of the node.
+pub trait PoolBuilder<Client> {
+    /// The pool that is created.
+    type Pool: TransactionPool + Clone + 'static;
+
+    /// Creates the pool that reads the state of the chain from the client.
+    fn build_pool(self, client: Client, chain_spec: Arc<ChainSpec>) -> Self::Pool;
+}
+
+impl<Client> PoolBuilder<Client> for PoolConfig
+where
+    Client: reth_provider::StateProviderFactory + 'static,
+{
+    type Pool = EthPool<Client>;
+
+    fn build_pool(self, client: Client, chain_spec: Arc<ChainSpec>) -> Self::Pool {
+        EthPoolBuilder { config: self, ordering: CoinbaseTipOrdering::default() }
+            .build_pool(client, chain_spec)
+    }
+}
+
+/// Configures an [EthPool] that orders the pending transactions, and with that the transactions
+/// of mined blocks, with a custom [TransactionOrdering].
+#[derive(Debug, Clone, Default)]
+pub struct EthPoolBuilder<T> {
+    /// The settings of the pool.
+    pub config: PoolConfig,
+    /// The ordering of the pending transactions.
+    pub ordering: T,
+}
+
+impl<Client, T> PoolBuilder<Client> for EthPoolBuilder<T>
+where
+    Client: reth_provider::StateProviderFactory + 'static,
+    T: TransactionOrdering<Transaction = PooledTransaction>,
+{
+    type Pool = EthPool<Client, T>;
+
+    fn build_pool(self, client: Client, chain_spec: Arc<ChainSpec>) -> Self::Pool {
+        let validator = EthTransactionValidator::new(
+            client,
+            chain_spec.chain().id(),
+            chain_spec.genesis().gas_limit,
+        )
+        .with_minimal_protocol_basefee(self.config.minimal_protocol_basefee);
+        Pool::new(validator, self.ordering, self.config)
+    }
+}
+
+impl<Client, F, P> PoolBuilder<Client> for F
+where
+    F: FnOnce(Client, Arc<ChainSpec>) -> P,
+    P: TransactionPool + Clone + 'static,
+{
+    type Pool = P;
+
+    fn build_pool(self, client: Client, chain_spec: Arc<ChainSpec>) -> Self::Pool {
+        self(client, chain_spec)
+    }
+}
+
+/// The rpc modules of a node before the servers are started, see [NodeBuilder::with_rpc].
+#[derive(Debug)]
+pub struct RpcModules {
+    transports: TransportRpcModules<()>,
+    in_process: RpcModule<()>,
+    blockchain_tree: Arc<dyn BlockchainTreeEngine>,
+}
+
+impl RpcModules {
+    /// Returns the modules of the configured transports.
+    pub fn transports(&mut self) -> &mut TransportRpcModules<()> {
+        &mut self.transports
+    }
+
+    /// Returns the module that is called in process, see [NodeHandle::rpc].
+    pub fn in_process(&mut self) -> &mut RpcModule<()> {
+        &mut self.in_process
+    }
+
+    /// Returns the blockchain tree of the node, e.g. for the `reth_unwindTo` handler that updates
+    /// the tree after the unwind.
+    pub fn blockchain_tree(&self) -> Arc<dyn BlockchainTreeEngine> {
+        Arc::clone(&self.blockchain_tree)
+    }
+
+    /// Merges the given methods into the modules of all configured transports and the in process
+    /// module.
+    pub fn merge_configured(&mut self, other: impl Into<Methods>) -> eyre::Result<()> {
+        let other = other.into();
+        self.transports.merge_configured(other.clone())?;
+        self.in_process.merge(other)?;
+        Ok(())
+    }
+}
+
+/// Extends the [RpcModules] before the servers are started.
+type RpcHook = Box<dyn FnOnce(&mut RpcModules) -> eyre::Result<()> + Send>;
+
+/// Composes the components of a node and launches them.
+///
+/// All components have defaults, which make up a development node: [NodeBuilder::dev] opens a
+/// database in a temporary directory, the chain is the [development chain](crate::dev), the p2p
+/// network only listens on localhost without any discovery, the http server serves the standard
+/// rpc modules on a random local port, and a block is mined as soon as a transaction is ready.
+pub struct NodeBuilder<DB = Env<WriteMap>, P = PoolConfig> {
+    database: Arc<DB>,
+    /// The temporary directory of the database, removed when the node is dropped.
+    datadir: Option<TempDir>,
+    chain_spec: Arc<ChainSpec>,
+    pool: P,
+    network: NetworkConfigBuilder,
+    rpc_modules: TransportRpcModuleConfig,
+    rpc_server: RpcServerConfig,
+    rpc: Option<RpcHook>,
+    mining: MiningMode,
+    evm_config: Arc<dyn EvmConfig>,
+    fee_recipient: Option<Address>,
+    finality_depth: u64,
+    bytecode_cache: BytecodeCache,
+    trie_prefetch: bool,
+}
+
+// === impl NodeBuilder ===
+
+impl NodeBuilder {
+    /// Creates a builder for a development node with a fresh database in a temporary directory.
+    ///
+    /// The blob sidecars of the pool are stored in the `blobstore` directory next to the database.
+    pub fn dev() -> eyre::Result<Self> {
+        let datadir = TempDir::new().wrap_err("failed to create the data directory")?;
+        let db = init_db(datadir.path())?;
+        let blob_store =
+            DiskFileBlobStore::open(datadir.path().join("blobstore"), Default::default())
+                .wrap_err("failed to open the blob store")?;
+        let mut builder = Self::new(Arc::new(db));
+        builder.pool.blob_store = Some(Arc::new(blob_store));
+        builder.datadir = Some(datadir);
+        Ok(builder)
+    }
+}
+
+impl<DB> NodeBuilder<DB> {
+    /// Creates a builder for a development node on top of the given database.
+    pub fn new(database: Arc<DB>) -> Self {
+        let local = |port| SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, port));
+        Self {
+            database,
+            datadir: None,
+            chain_spec: Arc::new(dev_chain_spec()),
+            pool: PoolConfig::default(),
+            network: NetworkConfig::builder(rng_secret_key())
+                .listener_addr(local(0))
+                .disable_discv4_discovery()
+                .disable_dns_discovery(),
+            rpc_modules: TransportRpcModuleConfig::set_http(RpcModuleSelection::Standard),
+            rpc_server: RpcServerConfig::http(Default::default()).with_http_address(local(0)),
+            rpc: None,
+            mining: MiningMode::default(),
+            evm_config: Arc::new(EthEvmConfig::default()),
+            fee_recipient: None,
+            finality_depth: 0,
+            bytecode_cache: BytecodeCache::default(),
+            trie_prefetch: false,
+        }
+    }
+}
+
+impl<DB, P> NodeBuilder<DB, P> {
+    /// Sets the database of the node.
+    ///
+    /// The genesis block of the chain spec is written to it on launch if it's empty.
+    pub fn with_database<D>(self, database: Arc<D>) -> NodeBuilder<D, P> {
+        let Self {
+            chain_spec,
+            pool,
+            network,
+            rpc_modules,
+            rpc_server,
+            rpc,
+            mining,
+            evm_config,
+            fee_recipient,
+            finality_depth,
+            bytecode_cache,
+            trie_prefetch,
+            ..
+        } = self;
+        NodeBuilder {
+            database,
+            datadir: None,
+            chain_spec,
+            pool,
+            network,
+            rpc_modules,
+            rpc_server,
+            rpc,
+            mining,
+            evm_config,
+            fee_recipient,
+            finality_depth,
+            bytecode_cache,
+            trie_prefetch,
+        }
+    }
+
+    /// Sets the chain of the node.
+    pub fn with_chain_spec(mut self, chain_spec: Arc<ChainSpec>) -> Self {
+        self.chain_spec = chain_spec;
+        self
+    }
+
+    /// Sets the transaction pool of the node, either a [PoolConfig] for the default pool, an
+    /// [EthPoolBuilder] for the default pool with a custom ordering or a closure that creates a
+    /// custom pool, see [PoolBuilder].
+    pub fn with_pool<T>(self, pool: T) -> NodeBuilder<DB, T> {
+        let Self {
+            database,
+            datadir,
+            chain_spec,
+            network,
+            rpc_modules,
+            rpc_server,
+            rpc,
+            mining,
+            evm_config,
+            fee_recipient,
+            finality_depth,
+            bytecode_cache,
+            trie_prefetch,
+            ..
+        } = self;
+        NodeBuilder {
+            database,
+            datadir,
+            chain_spec,
+            pool,
+            network,
+            rpc_modules,
+            rpc_server,
+            rpc,
+            mining,
+            evm_config,
+            fee_recipient,
+            finality_depth,
+            bytecode_cache,
+            trie_prefetch,
+        }
+    }
+
+    /// Sets the config of the p2p network.
+    ///
+    /// The chain spec, the head, the task executor and the import of announced blocks into the
+    /// blockchain tree are set on launch.
+    pub fn with_network(mut self, network: NetworkConfigBuilder) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Sets the rpc modules to serve on each transport.
+    pub fn with_rpc_modules(mut self, rpc_modules: TransportRpcModuleConfig) -> Self {
+        self.rpc_modules = rpc_modules;
+        self
+    }
+
+    /// Sets the config of the rpc servers.
+    pub fn with_rpc_server(mut self, rpc_server: RpcServerConfig) -> Self {
+        self.rpc_server = rpc_server;
+        self
+    }
+
+    /// Sets a closure that can extend the configured rpc modules before the servers are started.
+    pub fn with_rpc<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut RpcModules) -> eyre::Result<()> + Send + 'static,
+    {
+        self.rpc = Some(Box::new(f));
+        self
+    }
+
+    /// Sets when blocks are mined.
+    pub fn with_mining(mut self, mining: MiningMode) -> Self {
+        self.mining = mining;
+        self
+    }
+
+    /// Sets the configuration of the EVM, which executes the mined blocks and the calls of the
+    /// rpc servers.
+    pub fn with_evm_config(mut self, evm_config: impl EvmConfig) -> Self {
+        self.evm_config = Arc::new(evm_config);
+        self
+    }
+
+    /// Sets the beneficiary of the mined blocks, which is also returned by `eth_coinbase`.
+    pub fn with_fee_recipient(mut self, fee_recipient: Address) -> Self {
+        self.fee_recipient = Some(fee_recipient);
+        self
+    }
+
+    /// Sets how many blocks below the head the `safe` and `finalized` blocks are, `0` by default.
+    ///
+    /// Without a consensus layer client, the safe and finalized blocks are derived from the head
+    /// whenever a block is mined.
+    pub fn with_finality_depth(mut self, depth: u64) -> Self {
+        self.finality_depth = depth;
+        self
+    }
+
+    /// Sets the cache of the contract codes, shared by the miner and the rpc servers.
+    ///
+    /// By default, a cache of
+    /// [DEFAULT_BYTECODE_CACHE_SIZE](reth_provider::providers::DEFAULT_BYTECODE_CACHE_SIZE) bytes
+    /// is used.
+    pub fn with_bytecode_cache(mut self, bytecode_cache: BytecodeCache) -> Self {
+        self.bytecode_cache = bytecode_cache;
+        self
+    }
+
+    /// Enables prefetching of the trie nodes touched by the mined blocks while they are executed,
+    /// see [BlockchainTree::with_trie_prefetch]. Disabled by default.
+    pub fn with_trie_prefetch(mut self, trie_prefetch: bool) -> Self {
+        self.trie_prefetch = trie_prefetch;
+        self
+    }
+}
+
+impl<DB, P> NodeBuilder<DB, P>
+where
+    DB: Database + 'static,
+    P: PoolBuilder<ShareableDatabase<Arc<DB>>>,
+{
+    /// Spawns all components of the node on the current runtime.
+    ///
+    /// # Panics
+    ///
+    /// If called outside of a tokio runtime.
+    pub async fn launch(self) -> eyre::Result<NodeHandle<DB, P::Pool>> {
+        let Self {
+            database,
+            datadir,
+            chain_spec,
+            pool,
+            network,
+            rpc_modules,
+            rpc_server,
+            rpc,
+            mining,
+            evm_config,
+            fee_recipient,
+            finality_depth,
+            bytecode_cache,
+            trie_prefetch,
+        } = self;
+
+        let task_manager = TaskManager::new(Handle::current());
+        let task_executor = task_manager.executor();
+
+        init_genesis(database.clone(), chain_spec.clone())?;
+        init_genesis_state(database.as_ref(), &chain_spec)?;
+        let head = lookup_head(database.as_ref())?;
+
+        let (forkchoice_state_tx, forkchoice_state_rx) = watch::channel(ForkchoiceState::default());
+        let mut finality = LocalFinality::new(finality_depth, forkchoice_state_tx);
+        let provider = ShareableDatabase::new(database.clone(), chain_spec.clone())
+            .with_bytecode_cache(bytecode_cache.clone());
+        finality.on_canonical_head(&provider, head.number, head.hash)?;
+        let provider = provider.with_forkchoice_state(forkchoice_state_rx.clone());
+
+        let pool = pool.build_pool(provider.clone(), chain_spec.clone());
+
+        let canonical_tip = CanonicalTip::default();
+        canonical_tip.update(head.number, head.hash);
+        let consensus: Arc<dyn Consensus> =
+            Arc::new(BeaconConsensus::new(chain_spec.clone(), forkchoice_state_rx.clone()));
+        let tree = BlockchainTree::new(
+            database.clone(),
+            consensus,
+            Factory::new(chain_spec.clone()).with_evm_config(evm_config.clone()),
+            chain_spec.clone(),
+            BlockchainTreeConfig::new(&chain_spec, DEV_MAX_REORG_DEPTH, DEV_MAX_BLOCKS_IN_CHAIN)?,
+        )?
+        .with_bytecode_cache(bytecode_cache.clone())
+        .with_canonical_tip(canonical_tip.clone())
+        .with_trie_prefetch(trie_prefetch);
+        let tree = DevBlockchainTree::new(tree);
+
+        let config = network
+            .chain_spec(chain_spec.clone())
+            .set_head(head)
+            .with_task_executor(Box::new(task_executor.clone()))
+            // blocks announced by peers before the merge are imported into the tree
+            .block_import(Box::new(TreeBlockImport::new(tree.clone())))
+            .build(provider.clone());
+        let (network, network_manager, _, eth) = NetworkManager::builder(config)
+            .await?
+            .request_handler(provider.clone())
+            .split_with_handle();
+        task_executor.spawn_critical("p2p network task", network_manager);
+        task_executor.spawn_critical("p2p eth request handler", eth);
+
+        let mut miner =
+            DevMiner::new(database, chain_spec, evm_config.clone(), pool.clone(), tree.clone())
+                .with_fee_recipient(fee_recipient.unwrap_or_default())
+                .with_finality(finality)
+                .with_bytecode_cache(bytecode_cache);
+        task_executor.spawn_critical(
+            "txpool reinjection",
+            reinject_orphaned_transactions(pool.clone(), miner.subscribe_canon_state()),
+        );
+        task_executor.spawn_critical(
+            "txpool maintenance",
+            discard_expired_transactions(pool.clone(), EXPIRY_INTERVAL),
+        );
+
+        let mut rpc_builder = RpcModuleBuilder::new(
+            provider.clone(),
+            pool.clone(),
+            network.clone(),
+            task_executor.clone(),
+        )
+        .with_evm_config(evm_config)
+        .with_canon_state_notifications(miner.subscribe_canon_state())
+        .with_blockchain_tree(Arc::new(tree.clone()))
+        .with_pending_state(Arc::new(tree.clone()))
+        .with_canonical_tip(canonical_tip);
+        if let Some(fee_recipient) = fee_recipient {
+            rpc_builder = rpc_builder.with_fee_recipient(fee_recipient);
+        }
+        let (transports, in_process) = rpc_builder.build_with_in_process(rpc_modules);
+        let mut modules =
+            RpcModules { transports, in_process, blockchain_tree: Arc::new(tree.clone()) };
+        if let Some(rpc) = rpc {
+            rpc(&mut modules)?;
+        }
+        let RpcModules { transports, in_process, .. } = modules;
+        let rpc_server = rpc_server.start(transports).await?;
+
+        let (miner, miner_handle) = miner.spawn(mining);
+        task_executor.spawn_critical_blocking("dev miner", miner);
+        info!(target: "reth::launcher", number = head.number, "Launched node");
+
+        Ok(NodeHandle {
+            provider,
+            pool,
+            network,
+            rpc: in_process,
+            rpc_server,
+            miner: miner_handle,
+            tree,
+            task_executor,
+            task_manager,
+            datadir,
+        })
+    }
+}
+
+impl<DB: fmt::Debug, P: fmt::Debug> fmt::Debug for NodeBuilder<DB, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeBuilder")
+            .field("database", &self.database)
+            .field("datadir", &self.datadir)
+            .field("chain_spec", &self.chain_spec)
+            .field("pool", &self.pool)
+            .field("network", &self.network)
+            .field("rpc_modules", &self.rpc_modules)
+            .field("mining", &self.mining)
+            .field("evm_config", &self.evm_config)
+            .field("fee_recipient", &self.fee_recipient)
+            .field("finality_depth", &self.finality_depth)
+            .field("bytecode_cache", &self.bytecode_cache)
+            .field("trie_prefetch", &self.trie_prefetch)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the canonical tip of the database.
+fn lookup_head<DB: Database>(db: &DB) -> eyre::Result<Head> {
+    let tx = db.tx()?;
+    let number = FINISH.get_progress(&tx)?.unwrap_or_default();
+    let hash = tx
+        .get::<tables::CanonicalHeaders>(number)?
+        .ok_or_else(|| eyre!("the hash of block {number} is missing"))?;
+    let header = tx
+        .get::<tables::Headers>(number)?
+        .ok_or_else(|| eyre!("the header of block {number} is missing"))?;
+    let total_difficulty = tx.get::<tables::HeaderTD>(number)?.unwrap_or_default();
+    Ok(Head {
+        number,
+        hash,
+        difficulty: header.difficulty,
+        total_difficulty: total_difficulty.into(),
+        timestamp: header.timestamp,
+    })
+}
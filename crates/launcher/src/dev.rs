@@ -0,0 +1,38 @@
+//! The development chain.
+
+use reth_primitives::{
+    hex_literal::hex, Address, Chain, ChainSpec, ChainSpecBuilder, Genesis, GenesisAccount, H160,
+    H256, U256,
+};
+
+/// The chain id of the development chain.
+pub const DEV_CHAIN_ID: u64 = 1337;
+
+/// The secret key of the funded account of the development chain.
+pub const DEV_SECRET_KEY: H256 =
+    H256(hex!("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"));
+
+/// The address of the funded account of the development chain, See [DEV_SECRET_KEY].
+pub const DEV_ADDRESS: Address = H160(hex!("f39fd6e51aad88f6f4ce6ab8827279cfffb92266"));
+
+/// The genesis balance of [DEV_ADDRESS]: 10000 ether.
+pub const DEV_BALANCE: u128 = 10_000 * 10u128.pow(18);
+
+/// The gas limit of the blocks of the development chain.
+pub const DEV_GAS_LIMIT: u64 = 30_000_000;
+
+/// Returns the spec of the development chain.
+///
+/// All hardforks up to and including the merge are active at genesis, and [DEV_ADDRESS] is the
+/// only funded account.
+pub fn dev_chain_spec() -> ChainSpec {
+    let genesis = Genesis::default().with_gas_limit(DEV_GAS_LIMIT).extend_accounts([(
+        DEV_ADDRESS,
+        GenesisAccount::default().with_balance(U256::from(DEV_BALANCE)),
+    )]);
+    ChainSpecBuilder::default()
+        .chain(Chain::Id(DEV_CHAIN_ID))
+        .genesis(genesis)
+        .paris_activated()
+        .build()
+}
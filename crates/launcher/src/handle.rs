@@ -0,0 +1,118 @@
+//! Access to the components of a running node.
+
+use crate::miner::{DevBlockchainTree, MinerHandle};
+use jsonrpsee::RpcModule;
+use reth_db::database::Database;
+use reth_network::NetworkHandle;
+use reth_provider::ShareableDatabase;
+use reth_rpc_builder::RpcServerHandle;
+use reth_tasks::{TaskExecutor, TaskManager};
+use std::{fmt, sync::Arc, time::Duration};
+use tempfile::TempDir;
+use tracing::*;
+
+/// How long [NodeHandle::shutdown] waits for the tasks of the node to finish.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Handle to a node launched by [NodeBuilder::launch](crate::NodeBuilder::launch).
+///
+/// The tasks of the node are stopped when this is dropped, see also [NodeHandle::shutdown].
+pub struct NodeHandle<DB: Database, Pool> {
+    pub(crate) provider: ShareableDatabase<Arc<DB>>,
+    pub(crate) pool: Pool,
+    pub(crate) network: NetworkHandle,
+    pub(crate) rpc: RpcModule<()>,
+    pub(crate) rpc_server: RpcServerHandle,
+    pub(crate) miner: MinerHandle,
+    pub(crate) tree: DevBlockchainTree<DB>,
+    pub(crate) task_executor: TaskExecutor,
+    pub(crate) task_manager: TaskManager,
+    /// Dropped last, after all tasks that use the database are stopped.
+    pub(crate) datadir: Option<TempDir>,
+}
+
+// === impl NodeHandle ===
+
+impl<DB: Database, Pool> NodeHandle<DB, Pool> {
+    /// Returns the provider of the node's database.
+    pub fn provider(&self) -> &ShareableDatabase<Arc<DB>> {
+        &self.provider
+    }
+
+    /// Returns the transaction pool of the node.
+    pub fn pool(&self) -> &Pool {
+        &self.pool
+    }
+
+    /// Returns the handle to the p2p network of the node.
+    pub fn network(&self) -> &NetworkHandle {
+        &self.network
+    }
+
+    /// Returns the module with the rpc methods of all transports, which can be called in process,
+    /// See [RpcModule::call].
+    pub fn rpc(&self) -> &RpcModule<()> {
+        &self.rpc
+    }
+
+    /// Returns the handle to the rpc servers of the node.
+    pub fn rpc_server(&self) -> &RpcServerHandle {
+        &self.rpc_server
+    }
+
+    /// Returns the handle to mine blocks.
+    pub fn miner(&self) -> &MinerHandle {
+        &self.miner
+    }
+
+    /// Returns the blockchain tree that holds the blocks of the node that are not yet canonical.
+    pub fn blockchain_tree(&self) -> &DevBlockchainTree<DB> {
+        &self.tree
+    }
+
+    /// Returns the executor to spawn tasks that are stopped with the node.
+    pub fn task_executor(&self) -> &TaskExecutor {
+        &self.task_executor
+    }
+
+    /// Stops the rpc servers and all tasks of the node.
+    ///
+    /// Returns `true` if all tasks finished in time.
+    pub async fn shutdown(self) -> bool {
+        let Self {
+            provider,
+            pool,
+            network,
+            rpc,
+            rpc_server,
+            miner,
+            tree,
+            task_executor,
+            task_manager,
+            datadir,
+        } = self;
+        if let Err(error) = rpc_server.stop() {
+            warn!(target: "reth::launcher", ?error, "Failed to stop the rpc servers");
+        }
+        let graceful = tokio::task::spawn_blocking(move || {
+            task_manager.graceful_shutdown_with_timeout(SHUTDOWN_TIMEOUT)
+        })
+        .await
+        .unwrap_or_default();
+        drop((provider, pool, network, rpc, miner, tree, task_executor));
+        drop(datadir);
+        graceful
+    }
+}
+
+impl<DB: Database, Pool: fmt::Debug> fmt::Debug for NodeHandle<DB, Pool> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeHandle")
+            .field("pool", &self.pool)
+            .field("network", &self.network)
+            .field("rpc_server", &self.rpc_server)
+            .field("miner", &self.miner)
+            .field("datadir", &self.datadir)
+            .finish_non_exhaustive()
+    }
+}
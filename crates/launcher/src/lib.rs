@@ -0,0 +1,46 @@
+#![warn(missing_docs, unreachable_pub)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![doc(test(
+    no_crate_inject,
+    attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
+))]
+
+//! Compose and launch reth nodes programmatically.
+//!
+//! The [NodeBuilder] puts together the database, the transaction pool, the p2p network and the rpc
+//! servers of a node, and [NodeBuilder::launch] spawns them. The returned [NodeHandle] gives access
+//! to all components.
+//!
+//! The defaults of all components make up a development node, which mines blocks from the
+//! transactions of its pool on top of a fresh database.
+//!
+//! # Examples
+//!
+//! Launch a development node, mine a block and query it over rpc
+//!
+//! ```no_run
+//! use jsonrpsee::rpc_params;
+//! use reth_launcher::NodeBuilder;
+//! use reth_primitives::U256;
+//!
+//! # async fn run() -> eyre::Result<()> {
+//! let node = NodeBuilder::dev()?.launch().await?;
+//!
+//! let block = node.miner().mine_block().await?;
+//! let number: U256 = node.rpc().call("eth_blockNumber", rpc_params![]).await?;
+//! assert_eq!(number, U256::from(block.number));
+//!
+//! node.shutdown().await;
+//! # Ok(())
+//! # }
+//! ```
+
+mod builder;
+pub mod dev;
+mod handle;
+mod maintain;
+mod miner;
+
+pub use builder::{EthPool, EthPoolBuilder, NodeBuilder, PoolBuilder, RpcModules};
+pub use handle::NodeHandle;
+pub use miner::{DevBlockchainTree, MinerHandle, MiningMode};
@@ -0,0 +1,68 @@
+//! Maintenance of the transaction pool of a node.
+
+use reth_interfaces::events::{CanonStateNotification, CanonStateNotifications};
+use reth_primitives::FromRecoveredTransaction;
+use reth_transaction_pool::{TransactionOrigin, TransactionPool};
+use std::{collections::HashSet, time::Duration};
+use tracing::*;
+
+/// How often the pool discards expired transactions, like geth's eviction interval.
+pub(crate) const EXPIRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Adds the transactions of the blocks a reorg removed from the canonical chain back to the pool,
+/// unless the new canonical blocks include them.
+///
+/// Runs until the sender of the notifications is dropped.
+pub(crate) async fn reinject_orphaned_transactions<Pool: TransactionPool>(
+    pool: Pool,
+    mut notifications: CanonStateNotifications,
+) {
+    while let Some(notification) = notifications.recv().await {
+        let CanonStateNotification::Reorg { old, new } = notification else { continue };
+
+        let included = new
+            .iter()
+            .flat_map(|block| block.block.body.iter().map(|tx| tx.hash()))
+            .collect::<HashSet<_>>();
+        let orphaned = old
+            .iter()
+            .flat_map(|block| block.block.body.iter())
+            .filter(|tx| !included.contains(&tx.hash()))
+            .filter_map(|tx| tx.clone().into_ecrecovered())
+            .map(Pool::Transaction::from_recovered_transaction)
+            .collect::<Vec<_>>();
+        if orphaned.is_empty() {
+            continue
+        }
+
+        let num_txs = orphaned.len();
+        match pool.add_transactions(TransactionOrigin::External, orphaned).await {
+            Ok(results) => {
+                let rejected = results.iter().filter(|result| result.is_err()).count();
+                debug!(target: "reth::launcher::maintain", num_txs, rejected, "Reinjected orphaned transactions");
+            }
+            Err(error) => {
+                warn!(target: "reth::launcher::maintain", ?error, num_txs, "Failed to reinject orphaned transactions")
+            }
+        }
+    }
+}
+
+/// Discards the expired transactions of the pool at the given interval, so they don't stay in the
+/// pool while no new blocks arrive.
+///
+/// Runs forever.
+pub(crate) async fn discard_expired_transactions<Pool: TransactionPool>(
+    pool: Pool,
+    interval: Duration,
+) {
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let num_txs = pool.discard_expired().len();
+        if num_txs > 0 {
+            debug!(target: "reth::launcher::maintain", num_txs, "Discarded expired transactions");
+        }
+    }
+}
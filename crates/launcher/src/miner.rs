@@ -0,0 +1,545 @@
+//! Block production of development nodes.
+
+use eyre::{eyre, WrapErr};
+use reth_consensus::finality::LocalFinality;
+use reth_db::{cursor::DbCursorRO, database::Database, tables, transaction::DbTx};
+use reth_executor::{blockchain_tree::shareable::ShareableBlockchainTree, Factory};
+use reth_interfaces::{
+    consensus::Consensus,
+    events::{CanonBlock, CanonStateNotification, CanonStateNotifications, StorageChanges},
+};
+use reth_primitives::{
+    proofs, Address, Block, BlockNumber, Bloom, ChainSpec, Hardfork, Header, SealedBlock,
+    SealedBlockWithSenders, StorageEntry, EMPTY_OMMER_ROOT, H256, U256,
+};
+use reth_provider::{
+    execution_result::ExecutionResult,
+    trie::{DBTrieLoader, HashedPostState},
+    BytecodeCache, ExecutorFactory, ShareableDatabase, StateProvider, StateProviderFactory,
+    Transaction,
+};
+use reth_revm::EvmConfig;
+use reth_stages::stages::FINISH;
+use reth_transaction_pool::{
+    BestTransactions, OnNewBlockEvent, PoolTransaction, StateDiff, TransactionPool, ValidationHead,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    future,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::Interval,
+};
+use tracing::*;
+
+/// Determines when a development node mines blocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MiningMode {
+    /// Mine a block as soon as transactions are ready to be included.
+    #[default]
+    Instant,
+    /// Mine a block at a fixed interval, even if it is empty.
+    Interval(Duration),
+    /// Only mine the blocks requested through the [MinerHandle].
+    Manual,
+}
+
+/// The blockchain tree of a development node, which holds the built blocks until they are made
+/// canonical.
+pub type DevBlockchainTree<DB> =
+    ShareableBlockchainTree<Arc<DB>, Arc<dyn Consensus>, Factory<Arc<dyn EvmConfig>>>;
+
+/// A request to the miner, with the channel to send the outcome to.
+enum MinerRequest {
+    /// Build a block and make it canonical.
+    Mine(oneshot::Sender<eyre::Result<SealedBlock>>),
+    /// Build a block and only insert it into the tree.
+    Build(oneshot::Sender<eyre::Result<SealedBlock>>),
+    /// Make a block of the tree and its ancestors canonical.
+    MakeCanonical(H256, oneshot::Sender<eyre::Result<()>>),
+}
+
+/// Handle to the miner of a development node.
+#[derive(Debug, Clone)]
+pub struct MinerHandle {
+    to_miner: mpsc::UnboundedSender<MinerRequest>,
+}
+
+impl MinerHandle {
+    /// Mines a block with the best transactions of the pool, regardless of the [MiningMode].
+    ///
+    /// Returns the block once it is canonical.
+    pub async fn mine_block(&self) -> eyre::Result<SealedBlock> {
+        let (tx, rx) = oneshot::channel();
+        self.send(MinerRequest::Mine(tx))?;
+        rx.await.map_err(|_| eyre!("miner stopped"))?
+    }
+
+    /// Builds a block with the best transactions of the pool on top of the canonical tip and
+    /// inserts it into the blockchain tree, like a payload received with `engine_newPayload`.
+    ///
+    /// The block and its transactions are served by the rpc, but the block only becomes canonical
+    /// with [MinerHandle::make_canonical].
+    pub async fn build_block(&self) -> eyre::Result<SealedBlock> {
+        let (tx, rx) = oneshot::channel();
+        self.send(MinerRequest::Build(tx))?;
+        rx.await.map_err(|_| eyre!("miner stopped"))?
+    }
+
+    /// Makes a block built with [MinerHandle::build_block] and its ancestors canonical, like a
+    /// `engine_forkchoiceUpdated` that sets it as the head.
+    ///
+    /// If the block is not a descendant of the canonical tip, the canonical chain is reorged.
+    pub async fn make_canonical(&self, block_hash: H256) -> eyre::Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.send(MinerRequest::MakeCanonical(block_hash, tx))?;
+        rx.await.map_err(|_| eyre!("miner stopped"))?
+    }
+
+    fn send(&self, request: MinerRequest) -> eyre::Result<()> {
+        self.to_miner.send(request).map_err(|_| eyre!("miner stopped"))
+    }
+}
+
+/// Mines blocks on top of the canonical chain in the database.
+///
+/// Blocks are not sealed by any consensus: they are executed and inserted into the blockchain tree
+/// directly, which writes them with all the tables the pipeline would write once they are made
+/// canonical.
+pub(crate) struct DevMiner<DB: Database, Pool> {
+    db: Arc<DB>,
+    chain_spec: Arc<ChainSpec>,
+    factory: Factory<Arc<dyn EvmConfig>>,
+    pool: Pool,
+    /// The blocks that are built but not yet canonical, and the side chains.
+    tree: DevBlockchainTree<DB>,
+    /// The built blocks above the finalized block of the tree, with their receipts and storage
+    /// changes for the notifications once they become canonical or are reorged.
+    built: HashMap<H256, CanonBlock>,
+    /// The hashes of the built blocks that are canonical.
+    canonical: BTreeMap<BlockNumber, H256>,
+    /// The block up to which the tree was finalized.
+    finalized: BlockNumber,
+    /// The beneficiary of the mined blocks.
+    fee_recipient: Address,
+    /// Receivers of the mined blocks once they are canonical.
+    canon_state_listeners: Vec<mpsc::UnboundedSender<CanonStateNotification>>,
+    /// Updates the safe and finalized blocks for the mined blocks.
+    finality: Option<LocalFinality>,
+    /// The cache of the contract codes read during execution.
+    bytecode_cache: Option<BytecodeCache>,
+}
+
+impl<DB, Pool> DevMiner<DB, Pool>
+where
+    DB: Database + 'static,
+    Pool: TransactionPool + 'static,
+{
+    /// Create new instance that mines the transactions of the pool and inserts the blocks into
+    /// the tree.
+    pub(crate) fn new(
+        db: Arc<DB>,
+        chain_spec: Arc<ChainSpec>,
+        evm_config: Arc<dyn EvmConfig>,
+        pool: Pool,
+        tree: DevBlockchainTree<DB>,
+    ) -> Self {
+        let factory = Factory::new(chain_spec.clone()).with_evm_config(evm_config);
+        Self {
+            db,
+            chain_spec,
+            factory,
+            pool,
+            tree,
+            built: HashMap::new(),
+            canonical: BTreeMap::new(),
+            finalized: 0,
+            fee_recipient: Address::zero(),
+            canon_state_listeners: Vec::new(),
+            finality: None,
+            bytecode_cache: None,
+        }
+    }
+
+    /// Sets the beneficiary of the mined blocks.
+    pub(crate) fn with_fee_recipient(mut self, fee_recipient: Address) -> Self {
+        self.fee_recipient = fee_recipient;
+        self
+    }
+
+    /// Sets the [LocalFinality] that derives the safe and finalized blocks from the mined blocks.
+    pub(crate) fn with_finality(mut self, finality: LocalFinality) -> Self {
+        self.finality = Some(finality);
+        self
+    }
+
+    /// Reads the contract codes through the given cache, e.g. a cache shared with the rpc servers.
+    pub(crate) fn with_bytecode_cache(mut self, bytecode_cache: BytecodeCache) -> Self {
+        self.bytecode_cache = Some(bytecode_cache);
+        self
+    }
+
+    /// Returns a provider over the database that reads the contract codes through the cache.
+    fn provider(&self) -> ShareableDatabase<&DB> {
+        let provider = ShareableDatabase::new(self.db.as_ref(), self.chain_spec.clone());
+        match self.bytecode_cache {
+            Some(ref bytecode_cache) => provider.with_bytecode_cache(bytecode_cache.clone()),
+            None => provider,
+        }
+    }
+
+    /// Returns a receiver of a [CanonStateNotification] for every block that is mined.
+    pub(crate) fn subscribe_canon_state(&mut self) -> CanonStateNotifications {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.canon_state_listeners.push(tx);
+        rx
+    }
+
+    /// Returns the future that mines blocks according to the mode, and the handle to request
+    /// blocks.
+    ///
+    /// The future resolves once all handles are dropped.
+    pub(crate) fn spawn(
+        self,
+        mode: MiningMode,
+    ) -> (impl future::Future<Output = ()> + Send + 'static, MinerHandle) {
+        let (to_miner, requests) = mpsc::unbounded_channel();
+        (self.run(mode, requests), MinerHandle { to_miner })
+    }
+
+    async fn run(mut self, mode: MiningMode, mut requests: mpsc::UnboundedReceiver<MinerRequest>) {
+        let mut pending = self.pool.pending_transactions_listener();
+        let mut interval = match mode {
+            MiningMode::Interval(period) => Some(tokio::time::interval(period)),
+            _ => None,
+        };
+
+        loop {
+            tokio::select! {
+                request = requests.recv() => {
+                    let Some(request) = request else { break };
+                    match request {
+                        MinerRequest::Mine(tx) => {
+                            let _ = tx.send(self.mine_block());
+                        }
+                        MinerRequest::Build(tx) => {
+                            let _ = tx.send(self.build_block());
+                        }
+                        MinerRequest::MakeCanonical(block_hash, tx) => {
+                            let _ = tx.send(self.make_canonical(block_hash));
+                        }
+                    }
+                }
+                Some(_) = pending.recv(), if mode == MiningMode::Instant => {
+                    // include all transactions that became ready in the meantime in one block
+                    while pending.try_recv().is_ok() {}
+                    self.mine_block_logged();
+                }
+                _ = tick(&mut interval), if interval.is_some() => {
+                    self.mine_block_logged();
+                }
+            }
+        }
+    }
+
+    fn mine_block_logged(&mut self) {
+        if let Err(error) = self.mine_block() {
+            warn!(target: "reth::launcher::miner", ?error, "Failed to mine block");
+        }
+    }
+
+    /// Mines a block with the best transactions of the pool on top of the canonical tip and makes
+    /// it canonical.
+    pub(crate) fn mine_block(&mut self) -> eyre::Result<SealedBlock> {
+        let block = self.build_block()?;
+        self.make_canonical(block.hash())?;
+        Ok(block)
+    }
+
+    /// Builds a block with the best transactions of the pool on top of the canonical tip and
+    /// inserts it into the tree.
+    ///
+    /// If the block can not be executed, its transactions are removed from the pool so that they
+    /// don't keep failing the next blocks.
+    pub(crate) fn build_block(&mut self) -> eyre::Result<SealedBlock> {
+        let tx = self.db.tx()?;
+        let (parent_number, parent_hash) = tx
+            .cursor_read::<tables::CanonicalHeaders>()?
+            .last()?
+            .ok_or_else(|| eyre!("the genesis block is missing"))?;
+        let parent = tx
+            .get::<tables::Headers>(parent_number)?
+            .ok_or_else(|| eyre!("the header of the canonical tip is missing"))?;
+        let parent_td = tx.get::<tables::HeaderTD>(parent_number)?.unwrap_or_default();
+        drop(tx);
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_secs())
+            .max(parent.timestamp + 1);
+        let base_fee_per_gas = self.chain_spec.next_block_base_fee(&parent);
+        let withdrawals =
+            self.chain_spec.fork(Hardfork::Shanghai).active_at_timestamp(timestamp).then(Vec::new);
+
+        let mut body = Vec::new();
+        let mut senders = Vec::new();
+        let mut gas_limit = 0;
+        // skips the transactions below the base fee of the block, transactions without a tip are
+        // included as long as the block has room for them
+        let mut best = match base_fee_per_gas {
+            Some(base_fee) => self.pool.best_transactions_with_base_fee(base_fee),
+            None => self.pool.best_transactions(),
+        };
+        while let Some(pool_tx) = best.next() {
+            let (transaction, sender) =
+                pool_tx.transaction.to_recovered_transaction().to_components();
+            // Reserving the whole gas limit of every transaction keeps each limit within the gas
+            // left in the block, which the executor checks before each transaction.
+            if gas_limit + transaction.gas_limit() > parent.gas_limit {
+                // also skips the transactions of the sender with higher nonces
+                best.mark_invalid(&pool_tx);
+                continue
+            }
+            gas_limit += transaction.gas_limit();
+            body.push(transaction);
+            senders.push(sender);
+        }
+
+        let header = Header {
+            parent_hash,
+            ommers_hash: EMPTY_OMMER_ROOT,
+            beneficiary: self.fee_recipient,
+            transactions_root: proofs::calculate_transaction_root(&body),
+            withdrawals_root: withdrawals.as_ref().map(proofs::calculate_withdrawals_root),
+            number: parent_number + 1,
+            gas_limit: parent.gas_limit,
+            timestamp,
+            base_fee_per_gas,
+            ..Default::default()
+        };
+        let mut block = Block { header, body, ommers: Vec::new(), withdrawals };
+        let total_difficulty = parent_td.0 + block.difficulty;
+
+        let result = self.execute(&mut block, total_difficulty, senders.clone());
+        let result = match result {
+            Ok(result) => result,
+            Err(err) => {
+                self.pool.remove_invalid(block.body.iter().map(|tx| tx.hash()));
+                return Err(err)
+            }
+        };
+
+        let block = block.seal_slow();
+        let block_with_senders = SealedBlockWithSenders::new(block.clone(), senders)
+            .expect("one sender per transaction");
+        let receipts =
+            result.tx_changesets.iter().map(|changeset| changeset.receipt.clone()).collect();
+        let storage = storage_changes(&result);
+        if !self.tree.tree.write().insert_executed_block(block_with_senders, result)? {
+            return Err(eyre!("the parent of block {} is not part of the tree", block.number))
+        }
+        debug!(target: "reth::launcher::miner", number = block.number, hash = ?block.hash(), txs = block.body.len(), "Built block");
+
+        self.built.insert(block.hash(), CanonBlock { block: block.clone(), receipts, storage });
+        Ok(block)
+    }
+
+    /// Makes the built block and its ancestors canonical, reorging the canonical chain if the
+    /// block doesn't descend from the canonical tip.
+    ///
+    /// The pool and the listeners are notified about the new canonical blocks.
+    pub(crate) fn make_canonical(&mut self, block_hash: H256) -> eyre::Result<()> {
+        self.tree.tree.write().make_canonical(&block_hash)?;
+
+        // the blocks that became canonical, from the new tip down to the fork block
+        let mut new = Vec::new();
+        let mut hash = block_hash;
+        while let Some(block) = self.built.get(&hash) {
+            if self.canonical.get(&block.block.number) == Some(&hash) {
+                break
+            }
+            new.push(block.clone());
+            hash = block.block.parent_hash;
+        }
+        new.reverse();
+        let Some(tip) = new.last().map(|block| block.block.clone()) else { return Ok(()) };
+
+        let first = new[0].block.number;
+        let old = self
+            .canonical
+            .split_off(&first)
+            .values()
+            .filter_map(|hash| self.built.get(hash).cloned())
+            .collect::<Vec<_>>();
+        self.canonical.extend(new.iter().map(|block| (block.block.number, block.block.hash())));
+        info!(target: "reth::launcher::miner", number = tip.number, hash = ?tip.hash(), blocks = new.len(), reorged = old.len(), "Made block canonical");
+
+        if let Some(finality) = &mut self.finality {
+            let provider = self.provider();
+            finality.on_canonical_head(&provider, tip.number, tip.hash())?;
+        }
+
+        // blocks below the reorg window can no longer be reorged
+        let finalized = tip.number.saturating_sub(self.tree.tree.read().config().max_reorg_depth());
+        let head = ValidationHead::new(&self.chain_spec, &tip.header);
+        self.pool.on_new_block(OnNewBlockEvent {
+            hash: tip.hash(),
+            pending_block_base_fee: head.base_fee.unwrap_or_default() as u128,
+            state_changes: StateDiff {},
+            mined_transactions: new
+                .iter()
+                .flat_map(|block| block.block.body.iter().map(|tx| tx.hash()))
+                .collect(),
+            finalized_block: Some(finalized),
+            head,
+        });
+
+        let notification = if old.is_empty() {
+            CanonStateNotification::Commit { new: Arc::new(new) }
+        } else {
+            CanonStateNotification::Reorg { old: Arc::new(old), new: Arc::new(new) }
+        };
+        for listener in &self.canon_state_listeners {
+            let _ = listener.send(notification.clone());
+        }
+
+        // the tree drops the side chains that fork below the finalized block
+        if finalized > self.finalized {
+            self.tree.tree.write().finalize_block(finalized);
+            self.finalized = finalized;
+            self.built.retain(|_, block| block.block.number > finalized);
+            self.canonical = self.canonical.split_off(&(finalized + 1));
+        }
+
+        Ok(())
+    }
+
+    /// Executes the block on top of the latest state and fills in the fields of the header that
+    /// commit to the execution.
+    ///
+    /// The header commits to the gas used and the receipts, which are filled in after the
+    /// transactions are executed and before the changes after the transactions are applied.
+    fn execute(
+        &self,
+        block: &mut Block,
+        total_difficulty: U256,
+        senders: Vec<Address>,
+    ) -> eyre::Result<ExecutionResult> {
+        let provider = self.provider();
+        let state = provider.latest()?;
+
+        let mut executor = self.factory.with_sp(&state);
+        let block_hash_history = executor.apply_block_hash_history(&block.header)?;
+        let (tx_changesets, gas_used) =
+            executor.execute_transactions(block, total_difficulty, Some(senders))?;
+        let receipts = tx_changesets
+            .iter()
+            .map(|changeset| changeset.receipt.with_bloom_ref())
+            .collect::<Vec<_>>();
+        block.header.gas_used = gas_used;
+        block.header.receipts_root = proofs::calculate_receipt_root_parallel(
+            &receipts,
+            proofs::DEFAULT_PARALLEL_RECEIPT_ROOT_THRESHOLD,
+        );
+        block.header.logs_bloom = receipts.iter().fold(Bloom::zero(), |bloom, r| bloom | r.bloom);
+
+        let result = executor.apply_post_block_changes(
+            block,
+            total_difficulty,
+            block_hash_history,
+            tx_changesets,
+        )?;
+        let post_state = HashedPostState::from_execution_results(
+            std::slice::from_ref(&result),
+            block.number,
+            &self.chain_spec,
+        );
+        block.header.state_root = state.state_root(post_state)?;
+
+        Ok(result)
+    }
+}
+
+/// Returns the storage changes of the executed block, applying the changes made before its
+/// transactions and then the changes of its transactions in order.
+fn storage_changes(result: &ExecutionResult) -> BTreeMap<Address, StorageChanges> {
+    let mut changes = BTreeMap::<Address, StorageChanges>::new();
+    for (address, storage) in &result.block_storage_changesets {
+        let slots = storage.iter().map(|(slot, (_, new))| (H256(slot.to_be_bytes()), *new));
+        changes.entry(*address).or_default().slots.extend(slots);
+    }
+    for (address, account) in result.tx_changesets.iter().flat_map(|tx| &tx.changeset) {
+        if !account.wipe_storage && account.storage.is_empty() {
+            continue
+        }
+        let changes = changes.entry(*address).or_default();
+        if account.wipe_storage {
+            changes.wiped = true;
+            changes.slots.clear();
+        }
+        let slots = account.storage.iter().map(|(slot, (_, new))| (H256(slot.to_be_bytes()), *new));
+        changes.slots.extend(slots);
+    }
+    changes
+}
+
+/// Writes the hashed state and the state trie of the genesis block, which are not written by
+/// [init_genesis](reth_staged_sync::utils::init::init_genesis), so that blocks can be mined on top
+/// of it, and the checkpoint of the finish stage.
+///
+/// Does nothing if the chain already advanced past genesis or the trie exists.
+pub(crate) fn init_genesis_state<DB: Database>(
+    db: &DB,
+    chain_spec: &ChainSpec,
+) -> eyre::Result<()> {
+    let mut tx = Transaction::new(db)?;
+    let tip = tx.cursor_read::<tables::CanonicalHeaders>()?.last()?.map(|(number, _)| number);
+    if tip != Some(0) {
+        return Ok(())
+    }
+    // the blockchain tree only moves the checkpoints that exist when it commits the mined blocks
+    if FINISH.get_progress(tx.deref())?.is_none() {
+        FINISH.save_progress(tx.deref(), 0)?;
+    }
+    if tx.cursor_read::<tables::AccountsTrie>()?.first()?.is_some() {
+        tx.commit()?;
+        return Ok(())
+    }
+
+    // the plain state of the genesis block holds the accounts of the genesis alloc with their
+    // codes and storage
+    let accounts = tx
+        .cursor_read::<tables::PlainAccountState>()?
+        .walk(None)?
+        .map(|entry| entry.map(|(address, account)| (address, Some(account))))
+        .collect::<Result<Vec<_>, _>>()?;
+    tx.insert_account_for_hashing(accounts)?;
+    let mut storages = BTreeMap::<Address, Vec<(H256, U256)>>::new();
+    for entry in tx.cursor_dup_read::<tables::PlainStorageState>()?.walk(None)? {
+        let (address, StorageEntry { key, value }) = entry?;
+        storages.entry(address).or_default().push((key, value));
+    }
+    tx.insert_storage_for_hashing(storages)?;
+
+    let root = DBTrieLoader::new(tx.deref_mut()).calculate_root()?.root()?;
+    let expected = chain_spec.genesis_header().state_root;
+    if root != expected {
+        return Err(eyre!("genesis state root mismatch: got {root:?}, expected {expected:?}"))
+    }
+    tx.commit().wrap_err("failed to write the genesis state")?;
+    Ok(())
+}
+
+/// Waits for the next tick of the interval, if any.
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => future::pending().await,
+    }
+}
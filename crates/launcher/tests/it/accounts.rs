@@ -0,0 +1,101 @@
+use jsonrpsee::{rpc_params, RpcModule};
+use reth_launcher::{
+    dev::{DEV_ADDRESS, DEV_CHAIN_ID, DEV_SECRET_KEY},
+    MiningMode, NodeBuilder,
+};
+use reth_primitives::{
+    contract::create_address, hex_literal::hex, keccak256, sign_message, Address, BlockId,
+    BlockNumberOrTag, Bytes, Transaction, TransactionKind, TransactionSigned, TxLegacy, H256,
+    KECCAK_EMPTY, U256, U64,
+};
+use reth_rpc_builder::{RethRpcModule, TransportRpcModuleConfig};
+use reth_rpc_types::AccountInfo;
+
+/// Returns the info of the account read via `eth_getBalance`, `eth_getTransactionCount` and
+/// `eth_getCode`.
+async fn eth_account_info(rpc: &RpcModule<()>, address: Address, block: BlockId) -> AccountInfo {
+    let balance: U256 = rpc.call("eth_getBalance", rpc_params![address, block]).await.unwrap();
+    let nonce: U256 =
+        rpc.call("eth_getTransactionCount", rpc_params![address, block]).await.unwrap();
+    let code: Bytes = rpc.call("eth_getCode", rpc_params![address, block]).await.unwrap();
+    AccountInfo {
+        balance,
+        nonce: U64::from(nonce.to::<u64>()),
+        code_hash: if code.is_empty() { KECCAK_EMPTY } else { keccak256(&code) },
+        code: (!code.is_empty()).then_some(code),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn accounts_info_matches_eth_endpoints() {
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_mining(MiningMode::Manual)
+        .with_rpc_modules(TransportRpcModuleConfig::set_http([
+            RethRpcModule::Eth,
+            RethRpcModule::Reth,
+        ]))
+        .launch()
+        .await
+        .unwrap();
+
+    // deploys a contract whose runtime code is `invalid`
+    let tx = Transaction::Legacy(TxLegacy {
+        chain_id: Some(DEV_CHAIN_ID),
+        nonce: 0,
+        gas_price: 2_000_000_000,
+        gas_limit: 100_000,
+        to: TransactionKind::Create,
+        value: 0,
+        input: Bytes::from(hex!("60fe60005360016000f3").to_vec()),
+    });
+    let signature = sign_message(DEV_SECRET_KEY, tx.signature_hash()).unwrap();
+    let tx = TransactionSigned::from_transaction_and_signature(tx, signature);
+    let _: H256 = node
+        .rpc()
+        .call("eth_sendRawTransaction", rpc_params![Bytes::from(tx.envelope_encoded())])
+        .await
+        .unwrap();
+    node.miner().mine_block().await.unwrap();
+
+    let contract = create_address(DEV_ADDRESS, 0);
+    let missing = Address::from_low_u64_be(0xdead);
+    let addresses = vec![contract, missing, DEV_ADDRESS, contract];
+    for block in [BlockNumberOrTag::Latest.into(), BlockNumberOrTag::Earliest.into()] {
+        let mut expected = Vec::new();
+        for address in &addresses {
+            expected.push(eth_account_info(node.rpc(), *address, block).await);
+        }
+        let infos: Vec<AccountInfo> = node
+            .rpc()
+            .call("reth_getAccountsInfo", rpc_params![addresses.clone(), block])
+            .await
+            .unwrap();
+        assert_eq!(infos, expected);
+        let info: AccountInfo =
+            node.rpc().call("reth_getAccountInfo", rpc_params![contract, block]).await.unwrap();
+        assert_eq!(info, expected[0]);
+    }
+
+    // non-existent accounts are zeroed, not null
+    let info: AccountInfo = node
+        .rpc()
+        .call("reth_getAccountInfo", rpc_params![missing, None::<BlockId>])
+        .await
+        .unwrap();
+    assert_eq!(info, AccountInfo { code_hash: KECCAK_EMPTY, ..Default::default() });
+    let info: AccountInfo =
+        node.rpc().call("reth_getAccountInfo", rpc_params![contract]).await.unwrap();
+    assert_eq!(info.code, Some(Bytes::from(vec![0xfe])));
+    assert_eq!(info.nonce, U64::from(1));
+
+    let too_many = vec![missing; 1_001];
+    let err = node
+        .rpc()
+        .call::<_, Vec<AccountInfo>>("reth_getAccountsInfo", rpc_params![too_many])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("exceeds the max of 1000"));
+
+    assert!(node.shutdown().await);
+}
@@ -0,0 +1,75 @@
+use jsonrpsee::rpc_params;
+use reth_launcher::{
+    dev::{DEV_ADDRESS, DEV_CHAIN_ID, DEV_SECRET_KEY},
+    MiningMode, NodeBuilder,
+};
+use reth_primitives::{
+    sign_message, Address, BlockNumberOrTag, Bytes, Transaction, TransactionKind,
+    TransactionSigned, TxEip1559, H256, U128, U256,
+};
+use reth_provider::{ChainSpecProvider, HeaderProvider};
+use reth_rpc_types::{AccessListWithGasUsed, CallRequest};
+
+fn sign(nonce: u64, max_fee_per_gas: u128) -> TransactionSigned {
+    let tx = Transaction::Eip1559(TxEip1559 {
+        chain_id: DEV_CHAIN_ID,
+        nonce,
+        gas_limit: 21_000,
+        max_fee_per_gas,
+        max_priority_fee_per_gas: 0,
+        to: TransactionKind::Call(Address::from_low_u64_be(0xbeef)),
+        value: 1,
+        ..Default::default()
+    });
+    let signature = sign_message(DEV_SECRET_KEY, tx.signature_hash()).unwrap();
+    TransactionSigned::from_transaction_and_signature(tx, signature)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pending_base_fee_agrees_across_rpc_pool_and_miner() {
+    let node = NodeBuilder::dev().unwrap().with_mining(MiningMode::Manual).launch().await.unwrap();
+
+    // an empty block lowers the base fee below the initial base fee
+    node.miner().mine_block().await.unwrap();
+    let provider = node.provider();
+    let latest = provider.header_by_number(1).unwrap().unwrap();
+    let base_fee = provider.chain_spec().next_block_base_fee(&latest).unwrap() as u128;
+    assert!(base_fee < latest.base_fee_per_gas.unwrap() as u128);
+
+    // calls on the pending block are executed with the base fee
+    let request = |max_fee_per_gas: u128| CallRequest {
+        from: Some(DEV_ADDRESS),
+        to: Some(Address::from_low_u64_be(0xbeef)),
+        max_fee_per_gas: Some(U128::from(max_fee_per_gas)),
+        gas: Some(U256::from(21_000)),
+        ..Default::default()
+    };
+    let pending = BlockNumberOrTag::Pending;
+    let _: AccessListWithGasUsed = node
+        .rpc()
+        .call("eth_createAccessList", rpc_params![request(base_fee), pending])
+        .await
+        .unwrap();
+    node.rpc()
+        .call::<_, AccessListWithGasUsed>(
+            "eth_createAccessList",
+            rpc_params![request(base_fee - 1), pending],
+        )
+        .await
+        .unwrap_err();
+
+    // the pool keeps both transactions, the miner only includes the one paying the base fee
+    let txs = [sign(0, base_fee), sign(1, base_fee - 1)];
+    for tx in &txs {
+        let _: H256 = node
+            .rpc()
+            .call("eth_sendRawTransaction", rpc_params![Bytes::from(tx.envelope_encoded())])
+            .await
+            .unwrap();
+    }
+    let mined = node.miner().mine_block().await.unwrap();
+    assert_eq!(mined.base_fee_per_gas, Some(base_fee as u64));
+    assert_eq!(mined.body, vec![txs[0].clone()]);
+
+    assert!(node.shutdown().await);
+}
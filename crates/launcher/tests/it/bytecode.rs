@@ -0,0 +1,100 @@
+use jsonrpsee::rpc_params;
+use reth_launcher::{dev::dev_chain_spec, MiningMode, NodeBuilder};
+use reth_primitives::{hex_literal::hex, keccak256, Address, Bytes, GenesisAccount, H256};
+use reth_provider::BytecodeCache;
+use reth_rpc_builder::{RethRpcModule, TransportRpcModuleConfig};
+use reth_rpc_types::CallRequest;
+use std::sync::Arc;
+
+/// Returns the code of an EIP-1167 minimal proxy that delegates all calls to the implementation.
+fn clone_code(implementation: Address) -> Bytes {
+    let mut code = hex!("363d3d373d3d3d363d73").to_vec();
+    code.extend_from_slice(implementation.as_bytes());
+    code.extend_from_slice(&hex!("5af43d82803e903d91602b57fd5bf3"));
+    code.into()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn calls_to_clones_read_the_code_once() {
+    // the implementation returns 42: `mstore(0, 42) return(0, 32)`
+    let implementation = Address::from_low_u64_be(0x1000);
+    let clones = (1..=50).map(|i| Address::from_low_u64_be(0x2000 + i)).collect::<Vec<_>>();
+    let code = clone_code(implementation);
+    let mut chain_spec = dev_chain_spec();
+    chain_spec.genesis = chain_spec.genesis.extend_accounts(
+        clones
+            .iter()
+            .map(|clone| (*clone, GenesisAccount::default().with_code(Some(code.clone()))))
+            .chain([(
+                implementation,
+                GenesisAccount::default().with_code(Some(hex!("602a60005260206000f3").into())),
+            )]),
+    );
+
+    let cache = BytecodeCache::default();
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_chain_spec(Arc::new(chain_spec))
+        .with_mining(MiningMode::Manual)
+        .with_rpc_modules(TransportRpcModuleConfig::set_http([
+            RethRpcModule::Eth,
+            RethRpcModule::Reth,
+        ]))
+        .with_bytecode_cache(cache.clone())
+        .launch()
+        .await
+        .unwrap();
+
+    let misses = cache.misses();
+    for clone in clones {
+        let request = CallRequest { to: Some(clone), ..Default::default() };
+        let output: Bytes = node.rpc().call("eth_call", rpc_params![request]).await.unwrap();
+        assert_eq!(H256::from_slice(&output), H256::from_low_u64_be(42));
+    }
+    // one read of the code shared by the clones and one of the implementation
+    assert_eq!(cache.misses() - misses, 2);
+    assert_eq!(cache.len(), 2);
+
+    let shared: Option<Bytes> =
+        node.rpc().call("reth_getCodeByHash", rpc_params![keccak256(&code)]).await.unwrap();
+    assert_eq!(shared, Some(code));
+    let unknown: Option<Bytes> =
+        node.rpc().call("reth_getCodeByHash", rpc_params![H256::random()]).await.unwrap();
+    assert_eq!(unknown, None);
+
+    assert!(node.shutdown().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn genesis_code_and_storage_are_part_of_the_state() {
+    let contract = Address::from_low_u64_be(0x1000);
+    let code = Bytes::from(hex!("602a60005260206000f3").to_vec());
+    let slot = H256::from_low_u64_be(1);
+    let value = H256::from_low_u64_be(42);
+    let mut chain_spec = dev_chain_spec();
+    chain_spec.genesis = chain_spec.genesis.extend_accounts([(
+        contract,
+        GenesisAccount::default()
+            .with_code(Some(code.clone()))
+            .with_storage(Some([(slot, value)].into_iter().collect())),
+    )]);
+
+    // the genesis state root commits to the code and the storage
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_chain_spec(Arc::new(chain_spec))
+        .with_mining(MiningMode::Manual)
+        .launch()
+        .await
+        .unwrap();
+    node.miner().mine_block().await.unwrap();
+
+    let genesis_code: Bytes =
+        node.rpc().call("eth_getCode", rpc_params![contract, "earliest"]).await.unwrap();
+    assert_eq!(genesis_code, code);
+    let stored: H256 =
+        node.rpc().call("eth_getStorageAt", rpc_params![contract, slot, "latest"]).await.unwrap();
+    assert_eq!(stored, value);
+
+    assert!(node.shutdown().await);
+}
@@ -0,0 +1,187 @@
+use jsonrpsee::{core::Error as RpcError, rpc_params, types::error::CallError};
+use reth_db::database::Database;
+use reth_interfaces::sync::NoopSyncStateUpdate;
+use reth_launcher::{
+    dev::{dev_chain_spec, DEV_ADDRESS, DEV_CHAIN_ID, DEV_SECRET_KEY},
+    MiningMode, NodeBuilder,
+};
+use reth_primitives::{
+    contract::create_address, hex_literal::hex, sign_message, BlockId, BlockNumber,
+    BlockNumberOrTag, Bytes, Transaction, TransactionKind, TransactionSigned, TxLegacy, H256, U256,
+};
+use reth_provider::{insert_canonical_block, Transaction as DbTransaction};
+use reth_rpc_types::{error::EthRpcErrorCode, Block};
+use reth_staged_sync::utils::{
+    init::{init_db, init_from_checkpoint, init_genesis, TrustedCheckpoint},
+    state_dump::{export_state, init_checkpoint_state},
+};
+use reth_stages::{
+    sets::OfflineStages,
+    stages::{BODIES, FINISH, HEADERS, SENDER_RECOVERY, TOTAL_DIFFICULTY, TRANSACTION_LOOKUP},
+    ExecInput, ExecOutput, Pipeline, Stage, StageError, StageId, UnwindInput, UnwindOutput,
+};
+use std::sync::Arc;
+
+/// Reports the blocks above the checkpoint as downloaded.
+///
+/// The blocks are inserted by the test, like the headers and bodies stages of a node synced from
+/// the checkpoint would.
+#[derive(Debug)]
+struct SyncedBlocksStage(BlockNumber);
+
+#[async_trait::async_trait]
+impl<DB: Database> Stage<DB> for SyncedBlocksStage {
+    fn id(&self) -> StageId {
+        StageId("SyncedBlocks")
+    }
+
+    async fn execute(
+        &mut self,
+        _: &mut DbTransaction<'_, DB>,
+        _: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        Ok(ExecOutput { stage_progress: self.0, done: true })
+    }
+
+    async fn unwind(
+        &mut self,
+        _: &mut DbTransaction<'_, DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        Ok(UnwindOutput { stage_progress: input.unwind_to })
+    }
+}
+
+fn sign(nonce: u64, to: TransactionKind, input: Bytes) -> Bytes {
+    let tx = Transaction::Legacy(TxLegacy {
+        chain_id: Some(DEV_CHAIN_ID),
+        nonce,
+        gas_price: 2_000_000_000,
+        gas_limit: 100_000,
+        to,
+        value: 0,
+        input,
+    });
+    let signature = sign_message(DEV_SECRET_KEY, tx.signature_hash()).unwrap();
+    Bytes::from(TransactionSigned::from_transaction_and_signature(tx, signature).envelope_encoded())
+}
+
+fn rpc_error_code(err: RpcError) -> i32 {
+    match err {
+        RpcError::Call(CallError::Custom(err)) => err.code(),
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn checkpoint_sync_executes_blocks_above_the_checkpoint() {
+    let node = NodeBuilder::dev().unwrap().with_mining(MiningMode::Manual).launch().await.unwrap();
+
+    // deploys a contract that stores the block number in slot 0 and in the slot of the number,
+    // every later block calls it
+    let init_code = hex!("67434355436000550060005260086018f3");
+    let contract = create_address(DEV_ADDRESS, 0);
+    let txs = std::iter::once(sign(0, TransactionKind::Create, Bytes::from(init_code.to_vec())))
+        .chain((1..200).map(|nonce| sign(nonce, TransactionKind::Call(contract), Bytes::new())));
+    let mut blocks = Vec::new();
+    for tx in txs {
+        let _: H256 = node.rpc().call("eth_sendRawTransaction", rpc_params![tx]).await.unwrap();
+        blocks.push(node.miner().mine_block().await.unwrap());
+    }
+    assert_eq!(blocks.last().unwrap().number, 200);
+
+    let mut dump = Vec::new();
+    export_state(node.provider(), 100, &mut dump).unwrap();
+
+    // a new database anchored at block 100 with the state of the checkpoint
+    let checkpoint = TrustedCheckpoint { number: 100, hash: blocks[99].hash() };
+    let chain_spec = Arc::new(dev_chain_spec());
+    let datadir = tempfile::TempDir::new().unwrap();
+    let db = Arc::new(init_db(datadir.path()).unwrap());
+    init_genesis(db.clone(), chain_spec.clone()).unwrap();
+    init_from_checkpoint(db.clone(), chain_spec.clone(), checkpoint, blocks[99].header.clone())
+        .unwrap();
+    let state_root = init_checkpoint_state(db.clone(), &dump[..]).unwrap();
+    assert_eq!(state_root, blocks[99].state_root);
+
+    // the blocks above the checkpoint are downloaded and executed on top of its state
+    let tx = db.tx_mut().unwrap();
+    for block in blocks[100..].iter().cloned() {
+        insert_canonical_block(&tx, block, None, false).unwrap();
+    }
+    for stage in [HEADERS, TOTAL_DIFFICULTY, BODIES, SENDER_RECOVERY, TRANSACTION_LOOKUP, FINISH] {
+        stage.save_progress(&tx, 200).unwrap();
+    }
+    tx.commit().unwrap();
+    let mut pipeline: Pipeline<_, NoopSyncStateUpdate> = Pipeline::builder()
+        .add_stage(SyncedBlocksStage(200))
+        .add_stages(OfflineStages::new(reth_executor::Factory::new(chain_spec)))
+        .with_max_block(200)
+        .build();
+    pipeline.run(db.clone()).await.unwrap();
+
+    let synced = NodeBuilder::new(db).with_mining(MiningMode::Manual).launch().await.unwrap();
+
+    // the history and state below the checkpoint are unavailable
+    for number in [50u64, 100] {
+        let err = synced
+            .rpc()
+            .call::<_, Option<Block>>(
+                "eth_getBlockByNumber",
+                rpc_params![BlockNumberOrTag::Number(number), false],
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(rpc_error_code(err), EthRpcErrorCode::ResourceNotFound.code(), "block {number}");
+        let err = synced
+            .rpc()
+            .call::<_, H256>(
+                "eth_getStorageAt",
+                rpc_params![contract, U256::ZERO, BlockId::from(number)],
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(rpc_error_code(err), EthRpcErrorCode::ResourceNotFound.code(), "block {number}");
+    }
+
+    // the blocks and state above the checkpoint match the node they were mined by
+    for number in [101u64, 150, 200] {
+        let block: Option<Block> = synced
+            .rpc()
+            .call("eth_getBlockByNumber", rpc_params![BlockNumberOrTag::Number(number), false])
+            .await
+            .unwrap();
+        assert_eq!(block.unwrap().header.hash, Some(blocks[number as usize - 1].hash()));
+
+        for slot in [U256::ZERO, U256::from(120)] {
+            let block_id = BlockId::from(number);
+            let expected: H256 = node
+                .rpc()
+                .call("eth_getStorageAt", rpc_params![contract, slot, block_id])
+                .await
+                .unwrap();
+            let stored: H256 = synced
+                .rpc()
+                .call("eth_getStorageAt", rpc_params![contract, slot, block_id])
+                .await
+                .unwrap();
+            assert_eq!(stored, expected, "block {number}, slot {slot}");
+        }
+    }
+    let stored: H256 =
+        synced.rpc().call("eth_getStorageAt", rpc_params![contract, U256::ZERO]).await.unwrap();
+    assert_eq!(stored, H256::from_low_u64_be(200));
+
+    // the synced node builds on the state of the tip
+    let tx = sign(200, TransactionKind::Call(contract), Bytes::new());
+    let _: H256 = synced.rpc().call("eth_sendRawTransaction", rpc_params![tx]).await.unwrap();
+    let mined = synced.miner().mine_block().await.unwrap();
+    assert_eq!(mined.number, 201);
+    assert_eq!(mined.parent_hash, blocks[199].hash());
+    let stored: H256 =
+        synced.rpc().call("eth_getStorageAt", rpc_params![contract, U256::ZERO]).await.unwrap();
+    assert_eq!(stored, H256::from_low_u64_be(201));
+
+    assert!(synced.shutdown().await);
+    assert!(node.shutdown().await);
+}
@@ -0,0 +1,206 @@
+use jsonrpsee::rpc_params;
+use reth_eth_wire::EthVersion;
+use reth_launcher::{
+    dev::{dev_chain_spec, DEV_ADDRESS, DEV_BALANCE, DEV_CHAIN_ID, DEV_SECRET_KEY},
+    MiningMode, NodeBuilder,
+};
+use reth_primitives::{
+    sign_message, Address, BlockNumberOrTag, Bytes, Transaction, TransactionKind,
+    TransactionSigned, TxEip1559, TxLegacy, U256, U64,
+};
+use reth_rpc_types::{Block, BlockTransactions};
+use reth_transaction_pool::PoolConfig;
+use std::sync::Arc;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dev_node_mines_empty_block() {
+    let node = NodeBuilder::dev().unwrap().launch().await.unwrap();
+
+    let mined = node.miner().mine_block().await.unwrap();
+    assert_eq!(mined.number, 1);
+
+    let number: U256 = node.rpc().call("eth_blockNumber", rpc_params![]).await.unwrap();
+    assert_eq!(number, U256::from(1));
+
+    let block: Option<Block> = node
+        .rpc()
+        .call("eth_getBlockByNumber", rpc_params![BlockNumberOrTag::Number(1), false])
+        .await
+        .unwrap();
+    let block = block.unwrap();
+    assert_eq!(block.header.hash, Some(mined.hash()));
+    assert_eq!(block.transactions, BlockTransactions::Hashes(vec![]));
+
+    assert!(node.shutdown().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn block_number_follows_mined_blocks() {
+    let node = NodeBuilder::dev().unwrap().with_mining(MiningMode::Manual).launch().await.unwrap();
+
+    let number: U256 = node.rpc().call("eth_blockNumber", rpc_params![]).await.unwrap();
+    assert_eq!(number, U256::from(0));
+    let syncing: bool = node.rpc().call("eth_syncing", rpc_params![]).await.unwrap();
+    assert!(!syncing);
+
+    for expected in 1..=3u64 {
+        node.miner().mine_block().await.unwrap();
+        let number: U256 = node.rpc().call("eth_blockNumber", rpc_params![]).await.unwrap();
+        assert_eq!(number, U256::from(expected));
+        let block: Option<Block> = node
+            .rpc()
+            .call("eth_getBlockByNumber", rpc_params![BlockNumberOrTag::Latest, false])
+            .await
+            .unwrap();
+        assert_eq!(block.unwrap().header.number, Some(U256::from(expected)));
+    }
+
+    assert!(node.shutdown().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dev_node_mines_transfer() {
+    let node = NodeBuilder::dev().unwrap().with_mining(MiningMode::Manual).launch().await.unwrap();
+
+    let recipient = Address::from_low_u64_be(0xbeef);
+    let value = 10u128.pow(18);
+    let gas_price = 2_000_000_000;
+    let tx = Transaction::Legacy(TxLegacy {
+        chain_id: Some(DEV_CHAIN_ID),
+        nonce: 0,
+        gas_price,
+        gas_limit: 21_000,
+        to: TransactionKind::Call(recipient),
+        value,
+        ..Default::default()
+    });
+    let signature = sign_message(DEV_SECRET_KEY, tx.signature_hash()).unwrap();
+    let tx = TransactionSigned::from_transaction_and_signature(tx, signature);
+
+    let hash: reth_primitives::H256 = node
+        .rpc()
+        .call("eth_sendRawTransaction", rpc_params![Bytes::from(tx.envelope_encoded())])
+        .await
+        .unwrap();
+    assert_eq!(hash, tx.hash());
+
+    let mined = node.miner().mine_block().await.unwrap();
+    assert_eq!(mined.body, vec![tx]);
+
+    let balance: U256 = node.rpc().call("eth_getBalance", rpc_params![recipient]).await.unwrap();
+    assert_eq!(balance, U256::from(value));
+    let balance: U256 = node.rpc().call("eth_getBalance", rpc_params![DEV_ADDRESS]).await.unwrap();
+    let cost = value + 21_000 * gas_price;
+    assert_eq!(balance, U256::from(DEV_BALANCE - cost));
+
+    // the transaction is removed from the pool, so the next block is empty
+    let mined = node.miner().mine_block().await.unwrap();
+    assert_eq!(mined.number, 2);
+    assert!(mined.body.is_empty());
+
+    assert!(node.shutdown().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn zero_base_fee_chain_mines_zero_fee_transfer() {
+    let mut chain_spec = dev_chain_spec();
+    chain_spec.genesis = chain_spec.genesis.with_base_fee(Some(0));
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_chain_spec(Arc::new(chain_spec))
+        .with_pool(PoolConfig { minimal_protocol_basefee: 0, ..Default::default() })
+        .with_mining(MiningMode::Manual)
+        .launch()
+        .await
+        .unwrap();
+
+    let recipient = Address::from_low_u64_be(0xbeef);
+    let value = 10u128.pow(18);
+    let tx = Transaction::Eip1559(TxEip1559 {
+        chain_id: DEV_CHAIN_ID,
+        nonce: 0,
+        gas_limit: 21_000,
+        max_fee_per_gas: 0,
+        max_priority_fee_per_gas: 0,
+        to: TransactionKind::Call(recipient),
+        value,
+        ..Default::default()
+    });
+    let signature = sign_message(DEV_SECRET_KEY, tx.signature_hash()).unwrap();
+    let tx = TransactionSigned::from_transaction_and_signature(tx, signature);
+    let _: reth_primitives::H256 = node
+        .rpc()
+        .call("eth_sendRawTransaction", rpc_params![Bytes::from(tx.envelope_encoded())])
+        .await
+        .unwrap();
+
+    // the base fee stays at zero, so the transaction pays nothing for its gas
+    let mined = node.miner().mine_block().await.unwrap();
+    assert_eq!(mined.base_fee_per_gas, Some(0));
+    assert_eq!(mined.body, vec![tx]);
+    let balance: U256 = node.rpc().call("eth_getBalance", rpc_params![DEV_ADDRESS]).await.unwrap();
+    assert_eq!(balance, U256::from(DEV_BALANCE - value));
+
+    assert!(node.shutdown().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dev_node_chain_info() {
+    let node = NodeBuilder::dev().unwrap().with_mining(MiningMode::Manual).launch().await.unwrap();
+
+    let chain_id: U64 = node.rpc().call("eth_chainId", rpc_params![]).await.unwrap();
+    assert_eq!(chain_id, U64::from(DEV_CHAIN_ID));
+    let version: U64 = node.rpc().call("eth_protocolVersion", rpc_params![]).await.unwrap();
+    assert_eq!(version, U64::from(EthVersion::LATEST as u64));
+    let err = node.rpc().call::<_, Address>("eth_coinbase", rpc_params![]).await.unwrap_err();
+    assert!(err.to_string().contains("etherbase not set"));
+
+    assert!(node.shutdown().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dev_node_mines_to_fee_recipient() {
+    let fee_recipient = Address::from_low_u64_be(0xfee);
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_mining(MiningMode::Manual)
+        .with_fee_recipient(fee_recipient)
+        .launch()
+        .await
+        .unwrap();
+
+    let coinbase: Address = node.rpc().call("eth_coinbase", rpc_params![]).await.unwrap();
+    assert_eq!(coinbase, fee_recipient);
+    let mined = node.miner().mine_block().await.unwrap();
+    assert_eq!(mined.beneficiary, fee_recipient);
+
+    assert!(node.shutdown().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dev_node_resolves_finality_tags() {
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_mining(MiningMode::Manual)
+        .with_finality_depth(2)
+        .launch()
+        .await
+        .unwrap();
+
+    let rpc = node.rpc();
+    let block_number = |tag: BlockNumberOrTag| async move {
+        let block: Option<Block> =
+            rpc.call("eth_getBlockByNumber", rpc_params![tag, false]).await.unwrap();
+        block.map(|block| block.header.number.unwrap().to::<u64>())
+    };
+    assert_eq!(block_number(BlockNumberOrTag::Finalized).await, Some(0));
+
+    for _ in 0..3 {
+        node.miner().mine_block().await.unwrap();
+    }
+    assert_eq!(block_number(BlockNumberOrTag::Finalized).await, Some(1));
+    assert_eq!(block_number(BlockNumberOrTag::Safe).await, Some(1));
+    assert_eq!(block_number(BlockNumberOrTag::Latest).await, Some(3));
+
+    assert!(node.shutdown().await);
+}
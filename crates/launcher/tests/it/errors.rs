@@ -0,0 +1,44 @@
+use jsonrpsee::{
+    core::Error as RpcError,
+    rpc_params,
+    types::error::{CallError, INTERNAL_ERROR_CODE},
+};
+use reth_db::{
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_launcher::{MiningMode, NodeBuilder};
+use reth_primitives::H256;
+use reth_rpc_types::Block;
+use reth_staged_sync::utils::init::init_db;
+use std::sync::Arc;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_block_is_null_and_inconsistent_database_an_error() {
+    let datadir = tempfile::TempDir::new().unwrap();
+    let db = Arc::new(init_db(datadir.path()).unwrap());
+    let node = NodeBuilder::new(db.clone()).with_mining(MiningMode::Manual).launch().await.unwrap();
+    let mined = node.miner().mine_block().await.unwrap();
+
+    let block: Option<Block> =
+        node.rpc().call("eth_getBlockByHash", rpc_params![H256::random(), false]).await.unwrap();
+    assert!(block.is_none());
+
+    // the header of the block is known, but its body is missing
+    let tx = db.tx_mut().unwrap();
+    tx.delete::<tables::BlockBodyIndices>(mined.number, None).unwrap();
+    tx.commit().unwrap();
+
+    let err = node
+        .rpc()
+        .call::<_, Option<Block>>("eth_getBlockByHash", rpc_params![mined.hash(), false])
+        .await
+        .unwrap_err();
+    match err {
+        RpcError::Call(CallError::Custom(err)) => assert_eq!(err.code(), INTERNAL_ERROR_CODE),
+        err => panic!("unexpected error {err:?}"),
+    }
+
+    assert!(node.shutdown().await);
+}
@@ -0,0 +1,112 @@
+use jsonrpsee::rpc_params;
+use reth_launcher::{
+    dev::{DEV_CHAIN_ID, DEV_SECRET_KEY},
+    MiningMode, NodeBuilder,
+};
+use reth_primitives::{
+    sign_message, Address, Bytes, Transaction, TransactionKind, TransactionSigned, TxLegacy, H256,
+    U256,
+};
+use reth_revm::{
+    revm::{interpreter::opcode, precompile::PrecompileResult, primitives::SpecId},
+    CustomPrecompiles, EvmConfig, OpcodeGasAdjustments,
+};
+use reth_rpc_types::{CallRequest, TransactionReceipt};
+use std::sync::Arc;
+
+/// An [EvmConfig] with a precompile at `0x0100` that returns its input reversed.
+#[derive(Debug)]
+struct ReversePrecompileConfig;
+
+impl ReversePrecompileConfig {
+    fn address() -> Address {
+        Address::from_low_u64_be(0x0100)
+    }
+
+    fn reverse(input: &[u8], _gas_limit: u64) -> PrecompileResult {
+        Ok((100, input.iter().rev().copied().collect()))
+    }
+}
+
+impl EvmConfig for ReversePrecompileConfig {
+    fn precompiles(&self, _spec_id: SpecId) -> CustomPrecompiles {
+        CustomPrecompiles::from([(Self::address(), Arc::new(Self::reverse) as _)])
+    }
+}
+
+/// An [EvmConfig] that charges 10,000 more gas for `SLOAD`.
+#[derive(Debug)]
+struct ExpensiveSloadConfig;
+
+impl EvmConfig for ExpensiveSloadConfig {
+    fn opcode_gas(&self, _spec_id: SpecId) -> OpcodeGasAdjustments {
+        OpcodeGasAdjustments::from([(opcode::SLOAD, 10_000)])
+    }
+}
+
+fn call_request() -> CallRequest {
+    CallRequest {
+        to: Some(ReversePrecompileConfig::address()),
+        data: Some(Bytes::from(vec![1, 2, 3])),
+        ..Default::default()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn eth_call_custom_precompile() {
+    let node = NodeBuilder::dev().unwrap().with_evm_config(ReversePrecompileConfig).launch().await;
+    let node = node.unwrap();
+
+    let output: Bytes = node.rpc().call("eth_call", rpc_params![call_request()]).await.unwrap();
+    assert_eq!(output, Bytes::from(vec![3, 2, 1]));
+
+    // blocks are mined with the same config
+    let mined = node.miner().mine_block().await.unwrap();
+    assert_eq!(mined.number, 1);
+
+    assert!(node.shutdown().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn eth_call_without_custom_precompile() {
+    let node = NodeBuilder::dev().unwrap().launch().await.unwrap();
+
+    let output: Bytes = node.rpc().call("eth_call", rpc_params![call_request()]).await.unwrap();
+    assert!(output.is_empty());
+
+    assert!(node.shutdown().await);
+}
+
+/// Mines a contract creation whose init code executes `PUSH1 0x00; SLOAD; STOP` on a dev node with
+/// the given config and returns the gas used by the transaction.
+async fn sload_gas_used(builder: NodeBuilder) -> U256 {
+    let node = builder.with_mining(MiningMode::Manual).launch().await.unwrap();
+
+    let tx = Transaction::Legacy(TxLegacy {
+        chain_id: Some(DEV_CHAIN_ID),
+        nonce: 0,
+        gas_price: 2_000_000_000,
+        gas_limit: 100_000,
+        to: TransactionKind::Create,
+        value: 0,
+        input: Bytes::from(vec![opcode::PUSH1, 0x00, opcode::SLOAD, opcode::STOP]),
+    });
+    let signature = sign_message(DEV_SECRET_KEY, tx.signature_hash()).unwrap();
+    let tx = TransactionSigned::from_transaction_and_signature(tx, signature);
+    let raw = Bytes::from(tx.envelope_encoded());
+    let hash: H256 = node.rpc().call("eth_sendRawTransaction", rpc_params![raw]).await.unwrap();
+    node.miner().mine_block().await.unwrap();
+
+    let receipt: TransactionReceipt =
+        node.rpc().call("eth_getTransactionReceipt", rpc_params![hash]).await.unwrap();
+    assert!(node.shutdown().await);
+    receipt.gas_used.unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mined_transaction_with_opcode_gas_adjustment() {
+    let gas_used = sload_gas_used(NodeBuilder::dev().unwrap()).await;
+    let adjusted =
+        sload_gas_used(NodeBuilder::dev().unwrap().with_evm_config(ExpensiveSloadConfig)).await;
+    assert_eq!(adjusted, gas_used + U256::from(10_000));
+}
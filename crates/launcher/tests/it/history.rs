@@ -0,0 +1,68 @@
+use jsonrpsee::rpc_params;
+use reth_launcher::{dev::dev_chain_spec, MiningMode, NodeBuilder};
+use reth_primitives::{
+    constants::{HISTORY_SERVE_WINDOW, HISTORY_STORAGE_ADDRESS},
+    hex_literal::hex,
+    BlockId, ForkCondition, GenesisAccount, Hardfork, H256, U256,
+};
+use reth_provider::{StateProvider, StateProviderFactory};
+use std::sync::Arc;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn prague_blocks_write_parent_hashes_to_history_contract() {
+    let mut chain_spec = dev_chain_spec();
+    chain_spec.hardforks.insert(Hardfork::Shanghai, ForkCondition::Timestamp(0));
+    chain_spec.hardforks.insert(Hardfork::Prague, ForkCondition::Timestamp(0));
+    // the slots are written by the node, the code only has to exist
+    chain_spec.genesis = chain_spec.genesis.extend_accounts([(
+        HISTORY_STORAGE_ADDRESS,
+        GenesisAccount::default().with_code(Some(hex!("00").into())),
+    )]);
+    let genesis_hash = chain_spec.genesis_hash();
+
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_chain_spec(Arc::new(chain_spec))
+        .with_mining(MiningMode::Manual)
+        .launch()
+        .await
+        .unwrap();
+
+    let mut hashes = vec![genesis_hash];
+    for _ in 0..4 {
+        hashes.push(node.miner().mine_block().await.unwrap().hash());
+    }
+
+    // every block stored the hash of its parent, the tip is not stored yet
+    for (number, hash) in hashes.iter().enumerate() {
+        let slot = U256::from(number as u64 % HISTORY_SERVE_WINDOW);
+        let stored: H256 = node
+            .rpc()
+            .call("eth_getStorageAt", rpc_params![HISTORY_STORAGE_ADDRESS, slot])
+            .await
+            .unwrap();
+        let expected = if number + 1 < hashes.len() { *hash } else { H256::zero() };
+        assert_eq!(stored, expected, "block {number}");
+    }
+
+    // the state of block 2 only has the hashes of its ancestors
+    for (slot, expected) in [(1u64, hashes[1]), (2, H256::zero())] {
+        let stored: H256 = node
+            .rpc()
+            .call(
+                "eth_getStorageAt",
+                rpc_params![HISTORY_STORAGE_ADDRESS, U256::from(slot), BlockId::from(2u64)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(stored, expected, "slot {slot}");
+    }
+
+    let state = node.provider().latest().unwrap();
+    for (number, hash) in hashes.iter().enumerate().take(4) {
+        assert_eq!(state.history_block_hash(number as u64, 4).unwrap(), Some(*hash));
+    }
+    assert_eq!(state.history_block_hash(4, 4).unwrap(), None);
+
+    assert!(node.shutdown().await);
+}
@@ -0,0 +1,18 @@
+mod accounts;
+mod basefee;
+mod bytecode;
+mod checkpoint;
+mod dev;
+mod errors;
+mod evm_config;
+mod history;
+mod ordering;
+mod pubsub;
+mod receipts;
+mod state_dump;
+mod timing;
+mod tree;
+mod unwind;
+mod witness;
+
+fn main() {}
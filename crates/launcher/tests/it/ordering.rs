@@ -0,0 +1,80 @@
+use jsonrpsee::rpc_params;
+use reth_launcher::{
+    dev::{DEV_CHAIN_ID, DEV_SECRET_KEY},
+    EthPoolBuilder, MiningMode, NodeBuilder,
+};
+use reth_primitives::{
+    hex_literal::hex, sign_message, Address, Bytes, Transaction, TransactionKind,
+    TransactionSigned, TxLegacy, H160, H256,
+};
+use reth_transaction_pool::{PoolTransaction, PooledTransaction, TransactionOrdering};
+
+/// The secret key of the account [VipOrdering] prefers.
+const VIP_SECRET_KEY: H256 =
+    H256(hex!("59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d"));
+
+/// The address of [VIP_SECRET_KEY].
+const VIP_ADDRESS: Address = H160(hex!("70997970c51812dc3a010c7d01b50e0d17dc79c8"));
+
+/// A [TransactionOrdering] that prefers the transactions of [VIP_ADDRESS] over all others, which
+/// are ordered by their tip.
+#[derive(Debug, Default)]
+struct VipOrdering;
+
+impl TransactionOrdering for VipOrdering {
+    type Priority = (bool, u128);
+    type Transaction = PooledTransaction;
+
+    fn priority(&self, transaction: &Self::Transaction, base_fee: u128) -> Self::Priority {
+        (
+            transaction.sender() == VIP_ADDRESS,
+            transaction.effective_tip_per_gas(base_fee).unwrap_or_default(),
+        )
+    }
+}
+
+fn transfer(secret_key: H256, nonce: u64, gas_price: u128, to: Address) -> TransactionSigned {
+    let tx = Transaction::Legacy(TxLegacy {
+        chain_id: Some(DEV_CHAIN_ID),
+        nonce,
+        gas_price,
+        gas_limit: 21_000,
+        to: TransactionKind::Call(to),
+        value: 10u128.pow(18),
+        ..Default::default()
+    });
+    let signature = sign_message(secret_key, tx.signature_hash()).unwrap();
+    TransactionSigned::from_transaction_and_signature(tx, signature)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dev_node_mines_with_custom_ordering() {
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_mining(MiningMode::Manual)
+        .with_pool(EthPoolBuilder { config: Default::default(), ordering: VipOrdering })
+        .launch()
+        .await
+        .unwrap();
+    let rpc = node.rpc();
+    let send = |tx: TransactionSigned| async move {
+        let _: H256 = rpc
+            .call("eth_sendRawTransaction", rpc_params![Bytes::from(tx.envelope_encoded())])
+            .await
+            .unwrap();
+    };
+
+    // fund the preferred account
+    send(transfer(DEV_SECRET_KEY, 0, 2_000_000_000, VIP_ADDRESS)).await;
+    node.miner().mine_block().await.unwrap();
+
+    // the preferred account pays the lower tip but is included first
+    let other = transfer(DEV_SECRET_KEY, 1, 5_000_000_000, Address::from_low_u64_be(0xbeef));
+    let vip = transfer(VIP_SECRET_KEY, 0, 2_000_000_000, Address::from_low_u64_be(0xbeef));
+    send(other.clone()).await;
+    send(vip.clone()).await;
+    let mined = node.miner().mine_block().await.unwrap();
+    assert_eq!(mined.body, vec![vip, other]);
+
+    assert!(node.shutdown().await);
+}
@@ -0,0 +1,94 @@
+use jsonrpsee::rpc_params;
+use reth_interfaces::sync::{SyncState, SyncStateUpdater};
+use reth_launcher::{
+    dev::{DEV_ADDRESS, DEV_CHAIN_ID, DEV_SECRET_KEY},
+    MiningMode, NodeBuilder,
+};
+use reth_primitives::{
+    contract::create_address, hex_literal::hex, sign_message, Bytes, Transaction, TransactionKind,
+    TransactionSigned, TxLegacy, H256, U64,
+};
+use reth_rpc_builder::{RethRpcModule, TransportRpcModuleConfig};
+use reth_rpc_types::{
+    pubsub::{PubSubSyncStatus, SyncStatusMetadata},
+    StorageChangeNotification, StorageSubscriptionParams,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn subscribe_sync_state() {
+    let node = NodeBuilder::dev().unwrap().with_mining(MiningMode::Manual).launch().await.unwrap();
+
+    let mut sub = node.rpc().subscribe("eth_subscribe", rpc_params!["syncing"]).await.unwrap();
+    let (status, _) = sub.next::<PubSubSyncStatus>().await.unwrap().unwrap();
+    assert_eq!(status, PubSubSyncStatus::Simple(false));
+
+    node.network().update_sync_state(SyncState::Downloading { target_block: 100 });
+    let (status, _) = sub.next::<PubSubSyncStatus>().await.unwrap().unwrap();
+    assert_eq!(
+        status,
+        PubSubSyncStatus::Detailed(SyncStatusMetadata {
+            syncing: true,
+            starting_block: 0,
+            current_block: 0,
+            highest_block: Some(100),
+        })
+    );
+
+    // no update while the node keeps syncing
+    node.network().update_sync_state(SyncState::Executing { target_block: 100 });
+    node.network().update_sync_state(SyncState::Idle);
+    let (status, _) = sub.next::<PubSubSyncStatus>().await.unwrap().unwrap();
+    assert_eq!(status, PubSubSyncStatus::Simple(false));
+
+    assert!(node.shutdown().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn subscribe_storage_changes() {
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_mining(MiningMode::Manual)
+        .with_rpc_modules(TransportRpcModuleConfig::set_http([
+            RethRpcModule::Eth,
+            RethRpcModule::Reth,
+        ]))
+        .launch()
+        .await
+        .unwrap();
+
+    let contract = create_address(DEV_ADDRESS, 0);
+    let slot = H256::zero();
+    let params = StorageSubscriptionParams { address: contract, keys: vec![] };
+    let err = node.rpc().subscribe("reth_subscribe", rpc_params!["storage", params]).await;
+    assert!(err.unwrap_err().to_string().contains("at least one storage key is required"));
+    let params = StorageSubscriptionParams { address: contract, keys: vec![slot] };
+    let mut sub =
+        node.rpc().subscribe("reth_subscribe", rpc_params!["storage", params]).await.unwrap();
+
+    // deploys a contract whose init code stores 42 in slot 0: `sstore(0, 42)`
+    let tx = Transaction::Legacy(TxLegacy {
+        chain_id: Some(DEV_CHAIN_ID),
+        nonce: 0,
+        gas_price: 2_000_000_000,
+        gas_limit: 100_000,
+        to: TransactionKind::Create,
+        value: 0,
+        input: Bytes::from(hex!("602a600055").to_vec()),
+    });
+    let signature = sign_message(DEV_SECRET_KEY, tx.signature_hash()).unwrap();
+    let tx = TransactionSigned::from_transaction_and_signature(tx, signature);
+    let _: H256 = node
+        .rpc()
+        .call("eth_sendRawTransaction", rpc_params![Bytes::from(tx.envelope_encoded())])
+        .await
+        .unwrap();
+    let mined = node.miner().mine_block().await.unwrap();
+
+    let (notification, _) = sub.next::<StorageChangeNotification>().await.unwrap().unwrap();
+    assert_eq!(notification.block_hash, mined.hash());
+    assert_eq!(notification.block_number, U64::from(1));
+    assert_eq!(notification.address, contract);
+    assert_eq!(notification.storage.get(&slot), Some(&H256::from_low_u64_be(42)));
+
+    assert!(node.shutdown().await);
+}
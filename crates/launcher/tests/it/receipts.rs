@@ -0,0 +1,79 @@
+use jsonrpsee::rpc_params;
+use reth_launcher::{
+    dev::{DEV_ADDRESS, DEV_CHAIN_ID, DEV_SECRET_KEY},
+    MiningMode, NodeBuilder,
+};
+use reth_primitives::{
+    hex_literal::hex, sign_message, Address, Bytes, Transaction, TransactionKind,
+    TransactionSigned, TxLegacy, H256,
+};
+use reth_rpc_builder::{RethRpcModule, TransportRpcModuleConfig};
+use reth_rpc_types::{TransactionReceipt, TransactionsWithReceipts};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn receipt_contract_address_of_create() {
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_mining(MiningMode::Manual)
+        .with_rpc_modules(TransportRpcModuleConfig::set_http([
+            RethRpcModule::Eth,
+            RethRpcModule::Ots,
+        ]))
+        .launch()
+        .await
+        .unwrap();
+
+    // deploys a contract whose runtime code is `invalid`, then calls the zero address
+    let kinds = [TransactionKind::Create, TransactionKind::Call(Address::zero())];
+    for (nonce, to) in kinds.into_iter().enumerate() {
+        let input = if to == TransactionKind::Create {
+            Bytes::from(hex!("60fe60005360016000f3").to_vec())
+        } else {
+            Bytes::default()
+        };
+        let tx = Transaction::Legacy(TxLegacy {
+            chain_id: Some(DEV_CHAIN_ID),
+            nonce: nonce as u64,
+            gas_price: 2_000_000_000,
+            gas_limit: 100_000,
+            to,
+            value: 0,
+            input,
+        });
+        let signature = sign_message(DEV_SECRET_KEY, tx.signature_hash()).unwrap();
+        let tx = TransactionSigned::from_transaction_and_signature(tx, signature);
+        let _: H256 = node
+            .rpc()
+            .call("eth_sendRawTransaction", rpc_params![Bytes::from(tx.envelope_encoded())])
+            .await
+            .unwrap();
+    }
+    node.miner().mine_block().await.unwrap();
+
+    let page: TransactionsWithReceipts = node
+        .rpc()
+        .call("ots_searchTransactionsBefore", rpc_params![DEV_ADDRESS, 0, 10])
+        .await
+        .unwrap();
+    assert_eq!(page.txs.len(), 2);
+    for (tx, receipt) in page.txs.iter().zip(&page.receipts) {
+        assert_eq!(receipt.receipt.contract_address, tx.contract_address());
+        let eth_receipt: Option<TransactionReceipt> =
+            node.rpc().call("eth_getTransactionReceipt", rpc_params![tx.hash]).await.unwrap();
+        assert_eq!(eth_receipt.as_ref(), Some(&receipt.receipt));
+        if tx.to.is_some() {
+            assert_eq!(receipt.receipt.contract_address, None);
+            continue
+        }
+        // the contract was deployed at the address of the receipt
+        let contract = receipt.receipt.contract_address.unwrap();
+        let code: Bytes = node.rpc().call("eth_getCode", rpc_params![contract]).await.unwrap();
+        assert_eq!(code, Bytes::from(vec![0xfe]));
+    }
+    assert!(page.txs.iter().any(|tx| tx.to.is_none()));
+    let unknown: Option<TransactionReceipt> =
+        node.rpc().call("eth_getTransactionReceipt", rpc_params![H256::random()]).await.unwrap();
+    assert_eq!(unknown, None);
+
+    assert!(node.shutdown().await);
+}
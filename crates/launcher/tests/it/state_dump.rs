@@ -0,0 +1,61 @@
+use jsonrpsee::rpc_params;
+use reth_launcher::{
+    dev::{dev_chain_spec, DEV_ADDRESS, DEV_CHAIN_ID, DEV_SECRET_KEY},
+    MiningMode, NodeBuilder,
+};
+use reth_primitives::{
+    contract::create_address, hex_literal::hex, sign_message, Bytes, Transaction, TransactionKind,
+    TransactionSigned, TxLegacy, H256,
+};
+use reth_provider::HeaderProvider;
+use reth_staged_sync::utils::{
+    init::init_db,
+    state_dump::{export_state, init_from_state_dump},
+};
+use std::sync::Arc;
+
+fn sign(nonce: u64, to: TransactionKind, input: Bytes) -> Bytes {
+    let tx = Transaction::Legacy(TxLegacy {
+        chain_id: Some(DEV_CHAIN_ID),
+        nonce,
+        gas_price: 2_000_000_000,
+        gas_limit: 100_000,
+        to,
+        value: 0,
+        input,
+    });
+    let signature = sign_message(DEV_SECRET_KEY, tx.signature_hash()).unwrap();
+    Bytes::from(TransactionSigned::from_transaction_and_signature(tx, signature).envelope_encoded())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn exported_state_initializes_a_new_database() {
+    let node = NodeBuilder::dev().unwrap().with_mining(MiningMode::Manual).launch().await.unwrap();
+
+    // deploys a contract that stores the block number in slot 0 and in the slot of the number
+    let init_code = hex!("67434355436000550060005260086018f3");
+    let contract = create_address(DEV_ADDRESS, 0);
+    let txs = std::iter::once(sign(0, TransactionKind::Create, Bytes::from(init_code.to_vec())))
+        .chain((1..10).map(|nonce| sign(nonce, TransactionKind::Call(contract), Bytes::new())));
+    for tx in txs {
+        let _: H256 = node.rpc().call("eth_sendRawTransaction", rpc_params![tx]).await.unwrap();
+        node.miner().mine_block().await.unwrap();
+    }
+
+    // the state of block 5 reverts the changes of the later blocks
+    for block in [5, 10] {
+        let header = node.provider().header_by_number(block).unwrap().unwrap();
+        let mut dump = Vec::new();
+        let state_root = export_state(node.provider(), block, &mut dump).unwrap();
+        assert_eq!(state_root, header.state_root);
+
+        // the state root is computed from the imported state
+        let datadir = tempfile::TempDir::new().unwrap();
+        let db = Arc::new(init_db(datadir.path()).unwrap());
+        let imported_root =
+            init_from_state_dump(db, Arc::new(dev_chain_spec()), &dump[..], state_root).unwrap();
+        assert_eq!(imported_root, header.state_root, "block {block}");
+    }
+
+    assert!(node.shutdown().await);
+}
@@ -0,0 +1,93 @@
+use jsonrpsee::rpc_params;
+use reth_launcher::{
+    dev::{DEV_ADDRESS, DEV_CHAIN_ID, DEV_SECRET_KEY},
+    MiningMode, NodeBuilder,
+};
+use reth_primitives::{
+    contract::create_address, hex_literal::hex, sign_message, Address, BlockId, BlockNumberOrTag,
+    Bytes, Transaction, TransactionKind, TransactionSigned, TxLegacy, H256, U64,
+};
+use reth_rpc_builder::{RethRpcModule, TransportRpcModuleConfig};
+use reth_rpc_types::TransactionTiming;
+
+/// Init code of a contract that writes and reads slot 0 in a loop of 10000 iterations when called.
+const LOOP_CONTRACT: [u8; 33] =
+    hex!("6015600c60003960156000f36127105b6001900380600055600054508060035700");
+
+fn sign(
+    nonce: u64,
+    to: TransactionKind,
+    gas_limit: u64,
+    value: u128,
+    input: Bytes,
+) -> TransactionSigned {
+    let tx = Transaction::Legacy(TxLegacy {
+        chain_id: Some(DEV_CHAIN_ID),
+        nonce,
+        gas_price: 2_000_000_000,
+        gas_limit,
+        to,
+        value,
+        input,
+    });
+    let signature = sign_message(DEV_SECRET_KEY, tx.signature_hash()).unwrap();
+    TransactionSigned::from_transaction_and_signature(tx, signature)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn block_timing_profiles_transactions() {
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_mining(MiningMode::Manual)
+        .with_rpc_modules(TransportRpcModuleConfig::set_http([
+            RethRpcModule::Eth,
+            RethRpcModule::Reth,
+        ]))
+        .launch()
+        .await
+        .unwrap();
+
+    // block 1 deploys the contract, block 2 runs the loop and transfers ether
+    let contract = create_address(DEV_ADDRESS, 0);
+    let recipient = Address::from_low_u64_be(0xbeef);
+    let txs = [
+        sign(0, TransactionKind::Create, 100_000, 0, Bytes::from(LOOP_CONTRACT.to_vec())),
+        sign(1, TransactionKind::Call(contract), 5_000_000, 0, Bytes::default()),
+        sign(2, TransactionKind::Call(recipient), 21_000, 1, Bytes::default()),
+    ];
+    let mut hashes = Vec::new();
+    for (index, tx) in txs.into_iter().enumerate() {
+        let hash: H256 = node
+            .rpc()
+            .call("eth_sendRawTransaction", rpc_params![Bytes::from(tx.envelope_encoded())])
+            .await
+            .unwrap();
+        hashes.push(hash);
+        if index == 0 {
+            node.miner().mine_block().await.unwrap();
+        }
+    }
+    let mined = node.miner().mine_block().await.unwrap();
+    assert_eq!(mined.body.len(), 2);
+
+    let block_id = BlockId::from(BlockNumberOrTag::Number(2));
+    let timings: Vec<TransactionTiming> =
+        node.rpc().call("reth_traceBlockTiming", rpc_params![block_id]).await.unwrap();
+    assert_eq!(timings.len(), 2);
+    let (heavy, transfer) = (timings[0], timings[1]);
+    assert_eq!((heavy.transaction_hash, heavy.transaction_index), (hashes[1], U64::from(0)));
+    assert_eq!((transfer.transaction_hash, transfer.transaction_index), (hashes[2], U64::from(1)));
+
+    assert_eq!(transfer.gas_used, U64::from(21_000));
+    assert!(heavy.gas_used > U64::from(1_000_000));
+    assert_eq!((heavy.sloads, heavy.sstores), (U64::from(10_000), U64::from(10_000)));
+    assert_eq!((transfer.sloads, transfer.sstores), (U64::zero(), U64::zero()));
+    assert!(transfer.state_writes >= U64::from(2));
+
+    // the loop is the slowest transaction of the block
+    let mut by_time = timings.clone();
+    by_time.sort_by_key(|timing| std::cmp::Reverse(timing.time_micros));
+    assert_eq!(by_time[0].transaction_hash, hashes[1]);
+
+    assert!(node.shutdown().await);
+}
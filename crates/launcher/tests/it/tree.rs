@@ -0,0 +1,142 @@
+use jsonrpsee::rpc_params;
+use reth_launcher::{
+    dev::{DEV_CHAIN_ID, DEV_SECRET_KEY},
+    MiningMode, NodeBuilder,
+};
+use reth_primitives::{
+    sign_message, Address, Bytes, Transaction as PrimitiveTransaction, TransactionKind,
+    TransactionSigned, TxLegacy, H256, U256,
+};
+use reth_rpc_builder::{RethRpcModule, TransportRpcModuleConfig};
+use reth_rpc_types::{ReorgStats, Transaction};
+use reth_transaction_pool::TransactionPool;
+
+/// Signs a transfer of the development account with the given nonce.
+fn transfer(nonce: u64) -> Bytes {
+    let tx = PrimitiveTransaction::Legacy(TxLegacy {
+        chain_id: Some(DEV_CHAIN_ID),
+        nonce,
+        gas_price: 2_000_000_000,
+        gas_limit: 21_000,
+        to: TransactionKind::Call(Address::repeat_byte(0x11)),
+        value: 1,
+        input: Bytes::default(),
+    });
+    let signature = sign_message(DEV_SECRET_KEY, tx.signature_hash()).unwrap();
+    TransactionSigned::from_transaction_and_signature(tx, signature).envelope_encoded().into()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transaction_moves_from_pool_to_tree_to_canonical_chain() {
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_mining(MiningMode::Manual)
+        .with_rpc_modules(TransportRpcModuleConfig::set_http([RethRpcModule::Eth]))
+        .launch()
+        .await
+        .unwrap();
+
+    let hash: H256 =
+        node.rpc().call("eth_sendRawTransaction", rpc_params![transfer(0)]).await.unwrap();
+
+    // only in the pool
+    let tx: Transaction =
+        node.rpc().call("eth_getTransactionByHash", rpc_params![hash]).await.unwrap();
+    assert_eq!(tx.block_hash, None);
+
+    // in a block of the tree that is not canonical yet
+    let block = node.miner().build_block().await.unwrap();
+    let tx: Transaction =
+        node.rpc().call("eth_getTransactionByHash", rpc_params![hash]).await.unwrap();
+    assert_eq!(tx.block_hash, Some(block.hash()));
+    assert_eq!(tx.block_number, Some(block.number.into()));
+    let number: U256 = node.rpc().call("eth_blockNumber", rpc_params![]).await.unwrap();
+    assert_eq!(number, U256::ZERO);
+    assert!(node.blockchain_tree().tree.read().block_by_hash(block.hash()).is_some());
+
+    // in the canonical chain
+    node.miner().make_canonical(block.hash()).await.unwrap();
+    let tx: Transaction =
+        node.rpc().call("eth_getTransactionByHash", rpc_params![hash]).await.unwrap();
+    assert_eq!(tx.block_hash, Some(block.hash()));
+    let number: U256 = node.rpc().call("eth_blockNumber", rpc_params![]).await.unwrap();
+    assert_eq!(number, U256::from(block.number));
+    assert!(node.blockchain_tree().tree.read().block_by_hash(block.hash()).is_none());
+
+    assert!(node.shutdown().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reorg_between_built_blocks() {
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_mining(MiningMode::Manual)
+        .with_rpc_modules(TransportRpcModuleConfig::set_http([
+            RethRpcModule::Eth,
+            RethRpcModule::Reth,
+        ]))
+        .launch()
+        .await
+        .unwrap();
+
+    let _: H256 =
+        node.rpc().call("eth_sendRawTransaction", rpc_params![transfer(0)]).await.unwrap();
+    let first = node.miner().build_block().await.unwrap();
+    // the sibling also includes the second transfer
+    let second: H256 =
+        node.rpc().call("eth_sendRawTransaction", rpc_params![transfer(1)]).await.unwrap();
+    let sibling = node.miner().build_block().await.unwrap();
+    assert_eq!(first.parent_hash, sibling.parent_hash);
+
+    node.miner().make_canonical(first.hash()).await.unwrap();
+    node.miner().make_canonical(sibling.hash()).await.unwrap();
+
+    let tx: Transaction =
+        node.rpc().call("eth_getTransactionByHash", rpc_params![second]).await.unwrap();
+    assert_eq!(tx.block_hash, Some(sibling.hash()));
+    let stats: ReorgStats = node.rpc().call("reth_getReorgStats", rpc_params![]).await.unwrap();
+    assert_eq!(stats.total_reorgs, 1u64.into());
+    assert_eq!(stats.reorgs[0].old_tip, first.hash());
+    assert_eq!(stats.reorgs[0].new_tip, sibling.hash());
+
+    assert!(node.shutdown().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn orphaned_transactions_return_to_the_pool() {
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_mining(MiningMode::Manual)
+        .with_rpc_modules(TransportRpcModuleConfig::set_http([
+            RethRpcModule::Eth,
+            RethRpcModule::Reth,
+        ]))
+        .launch()
+        .await
+        .unwrap();
+
+    let hash: H256 =
+        node.rpc().call("eth_sendRawTransaction", rpc_params![transfer(0)]).await.unwrap();
+    let block = node.miner().build_block().await.unwrap();
+    // the sibling doesn't include the transfer
+    node.pool().remove_invalid([hash]);
+    let sibling = node.miner().build_block().await.unwrap();
+    assert!(sibling.body.is_empty());
+
+    node.miner().make_canonical(block.hash()).await.unwrap();
+    assert!(!node.pool().contains(&hash));
+    node.miner().make_canonical(sibling.hash()).await.unwrap();
+
+    // reinjected in the background
+    for _ in 0..100 {
+        if node.pool().contains(&hash) {
+            break
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(node.pool().contains(&hash));
+    let stats: ReorgStats = node.rpc().call("reth_getReorgStats", rpc_params![]).await.unwrap();
+    assert_eq!(stats.reorgs[0].reinjected_transactions, 1u64.into());
+
+    assert!(node.shutdown().await);
+}
@@ -0,0 +1,165 @@
+use jsonrpsee::{
+    core::Error as RpcError,
+    rpc_params,
+    types::error::{CallError, INVALID_PARAMS_CODE},
+};
+use reth_db::database::Database;
+use reth_interfaces::{consensus::ForkchoiceState, sync::NoopSyncStateUpdate};
+use reth_launcher::{dev::dev_chain_spec, MiningMode, NodeBuilder};
+use reth_primitives::{BlockNumber, ChainSpec, PruneMode, PruneModes, U256};
+use reth_provider::{BlockHashProvider, ShareableDatabase, Transaction};
+use reth_rpc::RethAdminApi;
+use reth_rpc_api::RethAdminApiServer;
+use reth_rpc_engine_api::{engine_api_channel, EngineApi};
+use reth_rpc_types::{error::EthRpcErrorCode, UnwindResult};
+use reth_staged_sync::utils::init::init_db;
+use reth_stages::{
+    pipeline_control_channel,
+    stages::{FinishStage, FINISH},
+    ExecInput, ExecOutput, Pipeline, Stage, StageError, StageId, UnwindInput, UnwindOutput,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::watch;
+
+/// Follows the tip sent by the test, like the headers stage follows the tip of the consensus
+/// layer client.
+///
+/// The blocks are written by the miner of the node, the stage removes them on unwind like the
+/// stages of a synced node.
+#[derive(Debug)]
+struct TipStage {
+    tip: watch::Receiver<BlockNumber>,
+    chain_spec: Arc<ChainSpec>,
+}
+
+#[async_trait::async_trait]
+impl<DB: Database> Stage<DB> for TipStage {
+    fn id(&self) -> StageId {
+        StageId("Tip")
+    }
+
+    async fn execute(
+        &mut self,
+        _: &mut Transaction<'_, DB>,
+        input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        while *self.tip.borrow() <= input.stage_progress.unwrap_or_default() {
+            if self.tip.changed().await.is_err() {
+                std::future::pending::<()>().await
+            }
+        }
+        Ok(ExecOutput { stage_progress: *self.tip.borrow(), done: true })
+    }
+
+    async fn unwind(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        tx.take_block_and_execution_range(&self.chain_spec, (input.unwind_to + 1)..)?;
+        Ok(UnwindOutput { stage_progress: input.unwind_to })
+    }
+}
+
+fn rpc_error_code(err: RpcError) -> i32 {
+    match err {
+        RpcError::Call(CallError::Custom(err)) => err.code(),
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unwind_is_refused_until_the_pipeline_synced() {
+    let datadir = tempfile::TempDir::new().unwrap();
+    let db = Arc::new(init_db(datadir.path()).unwrap());
+    let chain_spec = Arc::new(dev_chain_spec());
+
+    // blocks before the last 10 are pruned
+    let prune_modes = PruneModes { receipts: Some(PruneMode::Distance(10)), ..Default::default() };
+    let (pipeline_handle, pipeline_control) = pipeline_control_channel(prune_modes);
+    let (engine_tx, engine_rx) = engine_api_channel(Default::default());
+    let storage_history = ShareableDatabase::new(db.clone(), chain_spec.clone());
+    let unwinder = Arc::new(pipeline_handle.clone());
+
+    let node = NodeBuilder::new(db.clone())
+        .with_mining(MiningMode::Manual)
+        .with_rpc(move |modules| {
+            let admin = RethAdminApi::new(unwinder, engine_tx, Arc::new(storage_history))
+                .with_blockchain_tree(modules.blockchain_tree());
+            modules.in_process().merge(admin.into_rpc())?;
+            Ok(())
+        })
+        .launch()
+        .await
+        .unwrap();
+    let (forkchoice_state_tx, _forkchoice_state_rx) = watch::channel(ForkchoiceState::default());
+    tokio::spawn(EngineApi::new(
+        node.provider().clone(),
+        chain_spec.clone(),
+        engine_rx,
+        forkchoice_state_tx,
+    ));
+
+    let mut mined = Vec::new();
+    for _ in 0..15 {
+        mined.push(node.miner().mine_block().await.unwrap());
+    }
+
+    let (tip_tx, tip) = watch::channel(0);
+    let mut pipeline: Pipeline<_, NoopSyncStateUpdate> = Pipeline::builder()
+        .add_stage(TipStage { tip, chain_spec })
+        .add_stage(FinishStage::new(node.network().clone()))
+        .with_control(pipeline_control)
+        .build();
+    let pipeline_db = db.clone();
+    tokio::spawn(async move { pipeline.run(pipeline_db).await });
+
+    // the pipeline waits for the tip of its first pass
+    let err =
+        node.rpc().call::<_, UnwindResult>("reth_unwindTo", rpc_params![2u64]).await.unwrap_err();
+    assert_eq!(rpc_error_code(err), EthRpcErrorCode::ResourceUnavailable.code());
+
+    tip_tx.send(15).unwrap();
+    for _ in 0..100 {
+        if !pipeline_handle.is_busy() {
+            break
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(!pipeline_handle.is_busy());
+    // the tip the pipeline follows after the unwind, the new blocks are mined by the node
+    tip_tx.send(5).unwrap();
+
+    let err =
+        node.rpc().call::<_, UnwindResult>("reth_unwindTo", rpc_params![16u64]).await.unwrap_err();
+    assert_eq!(rpc_error_code(err), INVALID_PARAMS_CODE);
+    let err =
+        node.rpc().call::<_, UnwindResult>("reth_unwindTo", rpc_params![4u64]).await.unwrap_err();
+    assert_eq!(rpc_error_code(err), EthRpcErrorCode::ResourceNotFound.code());
+
+    let unwound: UnwindResult = node.rpc().call("reth_unwindTo", rpc_params![5u64]).await.unwrap();
+    assert_eq!(unwound.block_number, 5u64.into());
+    assert_eq!(unwound.block_hash, mined[4].hash());
+    assert_eq!(
+        unwound.stages.iter().map(|stage| stage.stage.as_str()).collect::<Vec<_>>(),
+        ["Finish", "Tip"]
+    );
+    assert_eq!(FINISH.get_progress(&db.tx().unwrap()).unwrap(), Some(5));
+    assert_eq!(node.provider().block_hash(U256::from(6)).unwrap(), None);
+
+    // the blockchain tree builds the new blocks on top of the unwound head
+    let mut parent = mined[4].clone();
+    for _ in 0..10 {
+        let block = node.miner().mine_block().await.unwrap();
+        assert_eq!(block.number, parent.number + 1);
+        assert_eq!(block.parent_hash, parent.hash());
+        assert_eq!(
+            node.provider().block_hash(U256::from(block.number)).unwrap(),
+            Some(block.hash())
+        );
+        parent = block;
+    }
+    assert_ne!(parent.hash(), mined[14].hash());
+
+    assert!(node.shutdown().await);
+}
@@ -0,0 +1,103 @@
+use jsonrpsee::rpc_params;
+use reth_executor::Factory;
+use reth_launcher::{
+    dev::{DEV_ADDRESS, DEV_CHAIN_ID, DEV_SECRET_KEY},
+    MiningMode, NodeBuilder,
+};
+use reth_primitives::{
+    contract::create_address, hex_literal::hex, sign_message, Address, BlockNumberOrTag, Bytes,
+    Transaction, TransactionKind, TransactionSigned, TxLegacy, H256,
+};
+use reth_provider::{
+    trie::{HashedPostState, WitnessStateProvider},
+    BlockExecutor, BlockProvider, ChainSpecProvider, ExecutorFactory, HeaderProvider,
+    StateProvider,
+};
+use reth_rpc_builder::{RethRpcModule, TransportRpcModuleConfig};
+use reth_rpc_types::ExecutionWitness;
+
+/// Init code of a contract that stores the hash of the previous block in slot 0 when called:
+/// `sstore(0, blockhash(sub(number(), 1)))`.
+const BLOCKHASH_CONTRACT: [u8; 22] = hex!("600a600c600039600a6000f343600190034060005500");
+
+fn sign(nonce: u64, to: TransactionKind, value: u128, input: Bytes) -> TransactionSigned {
+    let tx = Transaction::Legacy(TxLegacy {
+        chain_id: Some(DEV_CHAIN_ID),
+        nonce,
+        gas_price: 2_000_000_000,
+        gas_limit: 100_000,
+        to,
+        value,
+        input,
+    });
+    let signature = sign_message(DEV_SECRET_KEY, tx.signature_hash()).unwrap();
+    TransactionSigned::from_transaction_and_signature(tx, signature)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn execution_witness_reproduces_state_root() {
+    let node = NodeBuilder::dev()
+        .unwrap()
+        .with_mining(MiningMode::Manual)
+        .with_rpc_modules(TransportRpcModuleConfig::set_http([
+            RethRpcModule::Eth,
+            RethRpcModule::Debug,
+        ]))
+        .launch()
+        .await
+        .unwrap();
+
+    // block 1 deploys the contract, block 2 calls it and transfers ether
+    let contract = create_address(DEV_ADDRESS, 0);
+    let txs = [
+        sign(0, TransactionKind::Create, 0, Bytes::from(BLOCKHASH_CONTRACT.to_vec())),
+        sign(1, TransactionKind::Call(contract), 0, Bytes::default()),
+        sign(2, TransactionKind::Call(Address::from_low_u64_be(0xbeef)), 1, Bytes::default()),
+    ];
+    for (index, tx) in txs.into_iter().enumerate() {
+        let _: H256 = node
+            .rpc()
+            .call("eth_sendRawTransaction", rpc_params![Bytes::from(tx.envelope_encoded())])
+            .await
+            .unwrap();
+        if index == 0 {
+            node.miner().mine_block().await.unwrap();
+        }
+    }
+    let mined = node.miner().mine_block().await.unwrap();
+    assert_eq!(mined.number, 2);
+    assert_eq!(mined.body.len(), 2);
+
+    let witness: ExecutionWitness = node
+        .rpc()
+        .call("debug_executionWitness", rpc_params![BlockNumberOrTag::Number(2)])
+        .await
+        .unwrap();
+    let provider = node.provider();
+    let parent = provider.header_by_number(1).unwrap().unwrap();
+    assert_eq!(witness.block_hashes.get(&1), Some(&parent.hash_slow()));
+    assert_eq!(witness.codes.len(), 1);
+    // the contract and the slot it writes are part of the witness
+    assert!(witness.keys.contains(&Bytes::from(contract.as_bytes())));
+    assert!(witness.keys.contains(&Bytes::from(H256::zero().as_bytes())));
+
+    // re-execute the block against the witness only
+    let state = WitnessStateProvider::new(
+        parent.state_root,
+        &witness.state,
+        witness.codes.clone(),
+        witness.block_hashes.clone(),
+    );
+    let chain_spec = provider.chain_spec();
+    let block = provider.block(2.into()).unwrap().unwrap();
+    let total_difficulty = provider.header_td_by_number(2).unwrap().unwrap();
+    let result = Factory::new(chain_spec.clone())
+        .with_sp(&state)
+        .execute_and_verify_receipt(&block, total_difficulty, None)
+        .unwrap();
+
+    let post_state = HashedPostState::from_execution_results(&[result], 2, &chain_spec);
+    assert_eq!(state.state_root(post_state).unwrap(), block.state_root);
+
+    assert!(node.shutdown().await);
+}
@@ -0,0 +1,155 @@
+//! Metrics of block execution, shared by the execution stage and the blockchain tree.
+//!
+//! Besides the totals, the throughput is reported in blocks and millions of gas per second of
+//! execution time, over a sliding window of the recently executed blocks.
+
+use metrics::{Counter, Gauge, Histogram};
+use reth_metrics_derive::Metrics;
+use reth_primitives::{BlockHash, BlockNumber};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// The default execution time above which a block is logged as slow.
+pub const DEFAULT_SLOW_BLOCK_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// The default duration of the sliding window over which the throughput is computed.
+pub const DEFAULT_THROUGHPUT_WINDOW: Duration = Duration::from_secs(30);
+
+#[derive(Metrics)]
+#[metrics(dynamic = true)]
+struct BlockExecutionMetrics {
+    /// The total amount of gas used by the executed blocks
+    gas_processed_total: Counter,
+    /// The total number of executed blocks
+    blocks_processed_total: Counter,
+    /// The millions of gas executed per second over the throughput window
+    mgas_per_second: Gauge,
+    /// The number of blocks executed per second over the throughput window
+    blocks_per_second: Gauge,
+    /// The execution time of a block in seconds
+    block_execution_duration_seconds: Histogram,
+}
+
+/// Tracks the throughput of block execution.
+#[derive(Debug)]
+pub struct ExecutionMetrics {
+    /// The scope of the metrics, also included in the slow block logs.
+    scope: &'static str,
+    metrics: BlockExecutionMetrics,
+    window: ThroughputWindow,
+    /// The execution time above which a block is logged as slow.
+    slow_block_threshold: Duration,
+}
+
+impl ExecutionMetrics {
+    /// Creates the metrics with the given scope, with the [DEFAULT_SLOW_BLOCK_THRESHOLD] and the
+    /// [DEFAULT_THROUGHPUT_WINDOW].
+    pub fn new(scope: &'static str) -> Self {
+        Self {
+            scope,
+            metrics: BlockExecutionMetrics::new(scope),
+            window: ThroughputWindow::new(DEFAULT_THROUGHPUT_WINDOW),
+            slow_block_threshold: DEFAULT_SLOW_BLOCK_THRESHOLD,
+        }
+    }
+
+    /// Sets the execution time above which a block is logged as slow.
+    pub fn with_slow_block_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_block_threshold = threshold;
+        self
+    }
+
+    /// Sets the duration of the sliding window over which the throughput is computed.
+    pub fn with_throughput_window(mut self, window: Duration) -> Self {
+        self.window = ThroughputWindow::new(window);
+        self
+    }
+
+    /// Records the execution of a block that used the given gas and took the given time.
+    pub fn record_block(
+        &mut self,
+        number: BlockNumber,
+        hash: BlockHash,
+        gas_used: u64,
+        elapsed: Duration,
+    ) {
+        self.metrics.gas_processed_total.increment(gas_used);
+        self.metrics.blocks_processed_total.increment(1);
+        self.metrics.block_execution_duration_seconds.record(elapsed.as_secs_f64());
+
+        self.window.record(Instant::now(), gas_used, elapsed);
+        if let Some((blocks_per_second, gas_per_second)) = self.window.throughput() {
+            self.metrics.blocks_per_second.set(blocks_per_second);
+            self.metrics.mgas_per_second.set(gas_per_second / 1_000_000.0);
+        }
+
+        if elapsed > self.slow_block_threshold {
+            warn!(target: "execution", scope = self.scope, number, ?hash, gas_used, ?elapsed, "Slow block execution");
+        }
+    }
+}
+
+/// The executed blocks within a sliding window of time.
+#[derive(Debug)]
+struct ThroughputWindow {
+    /// The duration of the window.
+    window: Duration,
+    /// When the blocks were executed, with their gas used and execution time.
+    blocks: VecDeque<(Instant, u64, Duration)>,
+    /// The gas used by the blocks in the window.
+    gas_used: u64,
+    /// The execution time of the blocks in the window.
+    elapsed: Duration,
+}
+
+impl ThroughputWindow {
+    fn new(window: Duration) -> Self {
+        Self { window, blocks: VecDeque::new(), gas_used: 0, elapsed: Duration::ZERO }
+    }
+
+    /// Adds a block that was executed at the given time and drops the blocks that left the window.
+    fn record(&mut self, now: Instant, gas_used: u64, elapsed: Duration) {
+        self.blocks.push_back((now, gas_used, elapsed));
+        self.gas_used += gas_used;
+        self.elapsed += elapsed;
+
+        while let Some((executed_at, gas_used, elapsed)) = self.blocks.front().copied() {
+            if now.duration_since(executed_at) <= self.window {
+                break
+            }
+            self.blocks.pop_front();
+            self.gas_used -= gas_used;
+            self.elapsed -= elapsed;
+        }
+    }
+
+    /// Returns the blocks and gas per second of execution time, or `None` if no time was spent.
+    fn throughput(&self) -> Option<(f64, f64)> {
+        let secs = self.elapsed.as_secs_f64();
+        (secs > 0.0).then(|| (self.blocks.len() as f64 / secs, self.gas_used as f64 / secs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throughput_window() {
+        let mut window = ThroughputWindow::new(Duration::from_secs(10));
+        assert_eq!(window.throughput(), None);
+
+        let start = Instant::now();
+        window.record(start, 30_000_000, Duration::from_millis(500));
+        window.record(start + Duration::from_secs(5), 10_000_000, Duration::from_millis(500));
+        assert_eq!(window.throughput(), Some((2.0, 40_000_000.0)));
+
+        // the first block leaves the window
+        window.record(start + Duration::from_secs(12), 20_000_000, Duration::from_secs(1));
+        assert_eq!(window.blocks.len(), 2);
+        assert_eq!(window.throughput(), Some((4.0 / 3.0, 20_000_000.0)));
+    }
+}
@@ -7,4 +7,5 @@
 
 //! Common metric types that can be used across the Reth codebase
 
+pub mod execution;
 pub mod metered_sender;
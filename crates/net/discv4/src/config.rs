@@ -104,7 +104,7 @@ impl Discv4Config {
     /// Returns the corresponding [`ResolveNatInterval`], if a [NatResolver] and an interval was
     /// configured
     pub fn resolve_external_ip_interval(&self) -> Option<ResolveNatInterval> {
-        let resolver = self.external_ip_resolver?;
+        let resolver = self.external_ip_resolver.clone()?;
         let interval = self.resolve_external_ip_interval?;
         Some(ResolveNatInterval::interval(resolver, interval))
     }
@@ -30,6 +30,7 @@ use discv5::{
     ConnectionDirection, ConnectionState,
 };
 use enr::{Enr, EnrBuilder};
+use parking_lot::Mutex;
 use proto::{EnrRequest, EnrResponse};
 use reth_primitives::{
     bytes::{Bytes, BytesMut},
@@ -121,6 +122,8 @@ pub struct Discv4 {
     local_addr: SocketAddr,
     /// channel to send commands over to the service
     to_service: mpsc::Sender<Discv4Command>,
+    /// The local node record, kept up to date by the service.
+    node_record: Arc<Mutex<NodeRecord>>,
 }
 
 // === impl Discv4 ===
@@ -204,7 +207,8 @@ impl Discv4 {
         let (to_service, rx) = mpsc::channel(100);
         let service =
             Discv4Service::new(socket, local_addr, local_node_record, secret_key, config, Some(rx));
-        let discv4 = Discv4 { local_addr, to_service };
+        let discv4 =
+            Discv4 { local_addr, to_service, node_record: Arc::clone(&service.shared_node_record) };
         Ok((discv4, service))
     }
 
@@ -213,6 +217,13 @@ impl Discv4 {
         self.local_addr
     }
 
+    /// Returns the current node record of the local node.
+    ///
+    /// Note: this will include the external address if resolved.
+    pub fn node_record(&self) -> NodeRecord {
+        *self.node_record.lock()
+    }
+
     /// Sets the [Interval] used for periodically looking up targets over the network
     pub fn set_lookup_interval(&self, duration: Duration) {
         self.safe_send_to_service(Discv4Command::SetLookupInterval(duration))
@@ -291,6 +302,14 @@ impl Discv4 {
         self.safe_send_to_service(cmd);
     }
 
+    /// Sets the external ip
+    ///
+    /// This will update our [`NodeRecord`]'s address and the ip of the EIP-868 [`Enr`].
+    pub fn set_external_ip_addr(&self, ip: IpAddr) {
+        let cmd = Discv4Command::SetExternalIp(ip);
+        self.safe_send_to_service(cmd);
+    }
+
     /// Sets the pair in the EIP-868 [`Enr`] of the node.
     ///
     /// If the key already exists, this will update it.
@@ -343,6 +362,8 @@ pub struct Discv4Service {
     local_eip_868_enr: Enr<SecretKey>,
     /// Local ENR of the server.
     local_node_record: NodeRecord,
+    /// The local node record shared with the [`Discv4`] frontend.
+    shared_node_record: Arc<Mutex<NodeRecord>>,
     /// The secret key used to sign payloads
     secret_key: SecretKey,
     /// The UDP socket for sending and receiving messages.
@@ -462,6 +483,7 @@ impl Discv4Service {
             local_address,
             local_eip_868_enr,
             local_node_record,
+            shared_node_record: Arc::new(Mutex::new(local_node_record)),
             _socket: socket,
             kbuckets,
             secret_key,
@@ -505,6 +527,7 @@ impl Discv4Service {
             info!(target : "discv4",  ?external_ip, "Updating external ip");
             self.local_node_record.address = external_ip;
             let _ = self.local_eip_868_enr.set_ip(external_ip, &self.secret_key);
+            *self.shared_node_record.lock() = self.local_node_record;
         }
     }
 
@@ -1449,9 +1472,13 @@ impl Discv4Service {
                                     &self.secret_key,
                                 );
                             }
+                            Discv4Command::SetExternalIp(ip) => {
+                                self.set_external_ip_addr(ip);
+                            }
                             Discv4Command::SetTcpPort(port) => {
                                 debug!(target: "discv4", %port, "Update tcp port");
                                 self.local_node_record.tcp_port = port;
+                                *self.shared_node_record.lock() = self.local_node_record;
                                 if self.local_node_record.address.is_ipv4() {
                                     let _ = self.local_eip_868_enr.set_tcp4(port, &self.secret_key);
                                 } else {
@@ -1612,6 +1639,7 @@ pub(crate) async fn receive_loop(udp: Arc<UdpSocket>, tx: IngressSender, local_i
 /// The commands sent from the frontend to the service
 enum Discv4Command {
     SetTcpPort(u16),
+    SetExternalIp(IpAddr),
     SetEIP868RLPPair { key: Vec<u8>, rlp: Bytes },
     Ban(PeerId, IpAddr),
     BanPeer(PeerId),
@@ -2195,6 +2223,34 @@ mod tests {
         let _ = discv4.lookup_self().await;
     }
 
+    #[tokio::test]
+    async fn test_set_external_ip() {
+        reth_tracing::init_test_tracing();
+
+        let config = Discv4Config::builder().external_ip_resolver(None).build();
+        let (discv4, mut service) = create_discv4_with_config(config).await;
+        let seq = service.local_eip_868_enr.seq();
+
+        let ip = Ipv4Addr::new(1, 2, 3, 4);
+        discv4.set_external_ip_addr(ip.into());
+        poll_fn(|cx| {
+            let _ = service.poll(cx);
+            Poll::Ready(())
+        })
+        .await;
+
+        assert_eq!(service.local_enr().address, IpAddr::from(ip));
+        assert_eq!(service.local_eip_868_enr.ip4(), Some(ip));
+        assert!(service.local_eip_868_enr.seq() > seq);
+        // the frontend observes the updated record
+        assert_eq!(discv4.node_record(), service.local_enr());
+
+        // setting the same ip again doesn't change the ENR
+        let seq = service.local_eip_868_enr.seq();
+        service.set_external_ip_addr(ip.into());
+        assert_eq!(service.local_eip_868_enr.seq(), seq);
+    }
+
     // sends a PING packet with wrong 'to' field and expects a PONG response.
     #[tokio::test(flavor = "multi_thread")]
     async fn test_check_wrong_to() {
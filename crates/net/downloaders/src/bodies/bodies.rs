@@ -144,7 +144,7 @@ where
             }
 
             // Add header to the result collection
-            headers.push(header.seal(hash));
+            headers.push(SealedHeader::new_unchecked(header, hash));
 
             // Increment current block number
             current_block_num += 1;
@@ -239,12 +239,19 @@ mod tests {
         bodies::test_utils::zip_blocks,
         test_utils::{generate_bodies, TestBodiesClient, TEST_SCOPE},
     };
+    use assert_matches::assert_matches;
+    use reth_consensus::beacon::BeaconConsensus;
     use reth_interfaces::{
+        consensus::{ConsensusError, ForkchoiceState},
         p2p::bodies::response::BlockResponse,
         test_utils::{generators::random_header_range, TestConsensus},
     };
-    use reth_primitives::H256;
+    use reth_primitives::{
+        proofs, sign_message, ChainSpecBuilder, Header, InvalidTransactionError, Signature,
+        Transaction, TransactionKind, TransactionSigned, TxLegacy, EMPTY_OMMER_ROOT, H256, U256,
+    };
     use std::sync::Arc;
+    use tokio::sync::watch;
 
     /// Check if future returns empty bodies without dispathing any requests.
     #[tokio::test]
@@ -292,4 +299,71 @@ mod tests {
             (headers.into_iter().filter(|h| !h.is_empty()).count() as u64 + 1) / 2
         );
     }
+
+    /// Check that a body with an invalid signature fails validation before the signers are
+    /// recovered.
+    #[tokio::test]
+    async fn request_rejects_tampered_signature() {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().london_activated().build());
+        let consensus =
+            BeaconConsensus::new(chain_spec.clone(), watch::channel(ForkchoiceState::default()).1);
+
+        let tx = Transaction::Legacy(TxLegacy {
+            chain_id: Some(chain_spec.chain().id()),
+            gas_limit: 21_000,
+            to: TransactionKind::Call(Default::default()),
+            ..Default::default()
+        });
+        let signature = sign_message(H256::from_low_u64_be(1), tx.signature_hash()).unwrap();
+        let block_with = |transactions: Vec<TransactionSigned>| {
+            let header = Header {
+                number: 1,
+                ommers_hash: EMPTY_OMMER_ROOT,
+                transactions_root: proofs::calculate_transaction_root(transactions.iter()),
+                base_fee_per_gas: Some(7),
+                ..Default::default()
+            };
+            (header.seal_slow(), BlockBody { transactions, ommers: vec![], withdrawals: None })
+        };
+
+        let (header, body) = block_with(vec![TransactionSigned::from_transaction_and_signature(
+            tx.clone(),
+            signature,
+        )]);
+        let mut fut = BodiesRequestFuture::new(
+            Arc::new(TestBodiesClient::default()),
+            Arc::new(consensus),
+            Priority::Normal,
+            DownloaderMetrics::new(TEST_SCOPE),
+        );
+        fut.headers = VecDeque::from([header.clone()]);
+        fut.try_buffer_blocks(vec![body.clone()]).unwrap();
+        assert_eq!(
+            fut.buffer.pop(),
+            Some(BlockResponse::Full(SealedBlock {
+                header,
+                body: body.transactions,
+                ommers: vec![],
+                withdrawals: None,
+            }))
+        );
+
+        // `s` beyond the curve order, the transactions root still matches the body
+        let tampered = Signature { s: U256::MAX - signature.s, ..signature };
+        let (header, body) =
+            block_with(vec![TransactionSigned::from_transaction_and_signature(tx, tampered)]);
+        fut.headers = VecDeque::from([header.clone()]);
+        assert_matches!(
+            fut.try_buffer_blocks(vec![body]),
+            Err(DownloadError::BodyValidation {
+                error: ConsensusError::InvalidTransaction(
+                    InvalidTransactionError::SignatureValueOutOfRange
+                ),
+                ..
+            })
+        );
+        // the header is put back to be requested again
+        assert_eq!(fut.headers, VecDeque::from([header]));
+        assert!(fut.buffer.is_empty());
+    }
 }
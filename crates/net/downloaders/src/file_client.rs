@@ -1,4 +1,4 @@
-use super::file_codec::BlockFileCodec;
+use crate::file_codec::BlockFileCodec;
 use itertools::Either;
 use reth_eth_wire::BlockBody;
 use reth_interfaces::{
@@ -12,38 +12,31 @@ use reth_interfaces::{
     sync::{SyncState, SyncStateProvider, SyncStateUpdater},
 };
 use reth_primitives::{
-    Block, BlockHash, BlockHashOrNumber, BlockNumber, Header, HeadersDirection, PeerId, H256,
-};
-use reth_rlp::{Decodable, Header as RlpHeader};
-use std::{
-    collections::HashMap,
-    iter::zip,
-    path::Path,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
+    Block, BlockHash, BlockHashOrNumber, BlockNumber, Header, HeadersDirection, PeerId,
+    SealedHeader, H256,
 };
+use std::{collections::HashMap, path::Path, sync::Arc};
 use thiserror::Error;
-use tokio::{
-    fs::File,
-    io::{AsyncReadExt, BufReader},
-};
+use tokio::{fs::File, io::AsyncReadExt, sync::watch};
 use tokio_stream::StreamExt;
 use tokio_util::codec::FramedRead;
 use tracing::{trace, warn};
 
-/// Front-end API for fetching chain data from a file.
+/// Front-end API for fetching chain data from a file, or from a directory of files.
 ///
 /// Blocks are assumed to be written one after another in a file, as rlp bytes.
 ///
 /// For example, if the file contains 3 blocks, the file is assumed to be encoded as follows:
 /// rlp(block1) || rlp(block2) || rlp(block3)
 ///
+/// The blocks of a directory are read from its files in the order of the file names, e.g. an
+/// export split into `00000.rlp`, `00001.rlp`, ... Each block must be the child of the block before
+/// it, so a file client always serves a single continuous chain.
+///
 /// Blocks are assumed to have populated transactions, so reading headers will also buffer
 /// transactions in memory for use in the bodies stage.
 ///
-/// This reads the entire file into memory, so it is not suitable for large files.
+/// This reads all files into memory, so it is not suitable for large exports.
 #[derive(Debug)]
 pub struct FileClient {
     /// The buffered headers retrieved when fetching new bodies.
@@ -55,8 +48,11 @@ pub struct FileClient {
     /// The buffered bodies retrieved when fetching new headers.
     bodies: HashMap<BlockHash, BlockBody>,
 
-    /// Represents if we are currently syncing.
-    is_syncing: Arc<AtomicBool>,
+    /// The number and hash of the highest block that was read.
+    tip: Option<(BlockNumber, BlockHash)>,
+
+    /// The current sync state.
+    sync_state: Arc<watch::Sender<SyncState>>,
 }
 
 /// An error that can occur when constructing and using a [`FileClient`](FileClient).
@@ -69,6 +65,17 @@ pub enum FileClientError {
     /// An error occurred when decoding blocks, headers, or rlp headers from the file.
     #[error(transparent)]
     Rlp(#[from] reth_rlp::DecodeError),
+
+    /// A block is not the child of the block before it.
+    #[error("Block {number} does not extend block {parent_number} ({parent_hash:?}).")]
+    Discontinuity {
+        /// The number of the block.
+        number: BlockNumber,
+        /// The number of the expected parent.
+        parent_number: BlockNumber,
+        /// The hash of the expected parent.
+        parent_hash: BlockHash,
+    },
 }
 
 impl FileClient {
@@ -78,48 +85,132 @@ impl FileClient {
         FileClient::from_file(file).await
     }
 
+    /// Create a new file client from all files of a directory, read in the order of their names.
+    pub async fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self, FileClientError> {
+        let mut paths = Vec::new();
+        let mut entries = tokio::fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_file() {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+
+        let mut client = Self::empty();
+        for path in paths {
+            trace!(target: "downloaders::file", ?path, "Reading block file");
+            client.read_file(File::open(path).await?).await?;
+        }
+
+        trace!(blocks = client.headers.len(), "Initialized file client");
+        Ok(client)
+    }
+
     /// Initialize the [`FileClient`](FileClient) with a file directly.
-    pub(crate) async fn from_file(mut file: File) -> Result<Self, FileClientError> {
+    pub(crate) async fn from_file(file: File) -> Result<Self, FileClientError> {
+        let mut client = Self::empty();
+        client.read_file(file).await?;
+
+        trace!(blocks = client.headers.len(), "Initialized file client");
+        Ok(client)
+    }
+
+    fn empty() -> Self {
+        Self {
+            headers: HashMap::new(),
+            hash_to_number: HashMap::new(),
+            bodies: HashMap::new(),
+            tip: None,
+            sync_state: Arc::new(watch::channel(SyncState::Idle).0),
+        }
+    }
+
+    /// Reads the blocks of the file, which must extend the blocks that were read before.
+    async fn read_file(&mut self, mut file: File) -> Result<(), FileClientError> {
         // get file len from metadata before reading
         let metadata = file.metadata().await?;
         let file_len = metadata.len();
 
         // read the entire file into memory
         let mut reader = vec![];
-        file.read_to_end(&mut reader).await.unwrap();
-
-        let mut headers = HashMap::new();
-        let mut hash_to_number = HashMap::new();
-        let mut bodies = HashMap::new();
+        file.read_to_end(&mut reader).await?;
 
         // use with_capacity to make sure the internal buffer contains the entire file
         let mut stream = FramedRead::with_capacity(&reader[..], BlockFileCodec, file_len as usize);
 
         while let Some(block_res) = stream.next().await {
-            let block = block_res?;
-            let block_hash = block.header.hash_slow();
-
-            // add to the internal maps
-            headers.insert(block.header.number, block.header.clone());
-            hash_to_number.insert(block_hash, block.header.number);
-            bodies.insert(
-                block_hash,
-                BlockBody {
-                    transactions: block.body,
-                    ommers: block.ommers,
-                    withdrawals: block.withdrawals,
-                },
-            );
+            self.insert_block(block_res?)?;
         }
+        Ok(())
+    }
 
-        trace!(blocks = headers.len(), "Initialized file client");
+    fn insert_block(&mut self, block: Block) -> Result<(), FileClientError> {
+        let block_hash = block.header.hash_slow();
+        let block_number = block.header.number;
+        if let Some((parent_number, parent_hash)) = self.tip {
+            if block_number != parent_number + 1 || block.header.parent_hash != parent_hash {
+                return Err(FileClientError::Discontinuity {
+                    number: block_number,
+                    parent_number,
+                    parent_hash,
+                })
+            }
+        }
 
-        Ok(Self { headers, hash_to_number, bodies, is_syncing: Arc::new(Default::default()) })
+        // add to the internal maps
+        self.headers.insert(block_number, block.header);
+        self.hash_to_number.insert(block_hash, block_number);
+        self.bodies.insert(
+            block_hash,
+            BlockBody {
+                transactions: block.body,
+                ommers: block.ommers,
+                withdrawals: block.withdrawals,
+            },
+        );
+        self.tip = Some((block_number, block_hash));
+        Ok(())
     }
 
     /// Get the tip hash of the chain.
     pub fn tip(&self) -> Option<H256> {
-        self.headers.get(&(self.headers.len() as u64)).map(|h| h.hash_slow())
+        self.tip.map(|(_, hash)| hash)
+    }
+
+    /// Get the tip number of the chain.
+    pub fn tip_number(&self) -> Option<BlockNumber> {
+        self.tip.map(|(number, _)| number)
+    }
+
+    /// Drops the blocks up to and including the local head, e.g. the checkpoint of the headers
+    /// stage, so that only the blocks the pipeline still needs are kept.
+    ///
+    /// Returns an error if the remaining blocks don't extend the local head.
+    pub fn seek(&mut self, local_head: &SealedHeader) -> Result<(), FileClientError> {
+        self.headers.retain(|number, _| *number > local_head.number);
+        self.hash_to_number.retain(|_, number| *number > local_head.number);
+        let hash_to_number = &self.hash_to_number;
+        self.bodies.retain(|hash, _| hash_to_number.contains_key(hash));
+
+        match self.headers.values().min_by_key(|header| header.number) {
+            Some(first)
+                if first.number != local_head.number + 1 ||
+                    first.parent_hash != local_head.hash() =>
+            {
+                Err(FileClientError::Discontinuity {
+                    number: first.number,
+                    parent_number: local_head.number,
+                    parent_hash: local_head.hash(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns `true` if the files end at or below the checkpoint, so the remaining blocks have
+    /// to be downloaded from the network.
+    pub fn is_exhausted(&self, checkpoint: BlockNumber) -> bool {
+        self.tip.map_or(true, |(number, _)| number <= checkpoint)
     }
 
     /// Use the provided bodies as the file client's block body buffer.
@@ -134,6 +225,11 @@ impl FileClient {
         for (number, header) in &self.headers {
             self.hash_to_number.insert(header.hash_slow(), *number);
         }
+        self.tip = self
+            .headers
+            .iter()
+            .max_by_key(|(number, _)| **number)
+            .map(|(number, header)| (*number, header.hash_slow()));
         self
     }
 }
@@ -226,14 +322,17 @@ impl DownloadClient for FileClient {
 
 impl SyncStateProvider for FileClient {
     fn is_syncing(&self) -> bool {
-        self.is_syncing.load(Ordering::Relaxed)
+        self.sync_state.borrow().is_syncing()
+    }
+
+    fn subscribe_sync_state(&self) -> watch::Receiver<SyncState> {
+        self.sync_state.subscribe()
     }
 }
 
 impl SyncStateUpdater for FileClient {
     fn update_sync_state(&self, state: SyncState) {
-        let is_syncing = state.is_syncing();
-        self.is_syncing.store(is_syncing, Ordering::Relaxed)
+        self.sync_state.send_replace(state);
     }
 }
 
@@ -243,13 +342,12 @@ mod tests {
     use crate::{
         bodies::{
             bodies::BodiesDownloaderBuilder,
-            test_utils::{create_raw_bodies, insert_headers, zip_blocks},
+            test_utils::{insert_headers, zip_blocks},
         },
         headers::{reverse_headers::ReverseHeadersDownloaderBuilder, test_utils::child_header},
         test_utils::{generate_bodies, generate_bodies_file},
     };
     use assert_matches::assert_matches;
-    use futures::SinkExt;
     use futures_util::stream::StreamExt;
     use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
     use reth_interfaces::{
@@ -257,16 +355,19 @@ mod tests {
             bodies::downloader::BodyDownloader,
             headers::downloader::{HeaderDownloader, SyncTarget},
         },
-        test_utils::TestConsensus,
+        test_utils::{generators::random_block_range, TestConsensus},
     };
-    use reth_primitives::SealedHeader;
+    use reth_primitives::{SealedBlock, SealedHeader};
     use reth_rlp::Encodable;
-    use std::{
-        io::{Read, Seek, SeekFrom, Write},
-        sync::Arc,
-    };
-    use tokio::io::{AsyncSeekExt, AsyncWriteExt, BufWriter};
-    use tokio_util::codec::FramedWrite;
+
+    /// Writes the blocks to the file as rlp bytes, one after another.
+    fn write_blocks(path: &Path, blocks: &[SealedBlock]) {
+        let mut buf = Vec::new();
+        for block in blocks {
+            block.clone().unseal().encode(&mut buf);
+        }
+        std::fs::write(path, buf).unwrap();
+    }
 
     #[tokio::test]
     async fn streams_bodies_from_buffer() {
@@ -329,8 +430,7 @@ mod tests {
     #[tokio::test]
     async fn test_download_headers_from_file() {
         // Generate some random blocks
-        let db = create_test_db::<WriteMap>(EnvKind::RW);
-        let (file, headers, mut bodies) = generate_bodies_file(0..20).await;
+        let (file, headers, _) = generate_bodies_file(0..20).await;
 
         // now try to read them back
         let client = Arc::new(FileClient::from_file(file).await.unwrap());
@@ -375,4 +475,44 @@ mod tests {
             Some(Ok(res)) => assert_eq!(res, zip_blocks(headers.iter(), &mut bodies))
         );
     }
+
+    #[tokio::test]
+    async fn reads_and_seeks_directory() {
+        let blocks = random_block_range(0..30, H256::zero(), 0..2);
+        let dir = tempfile::tempdir().unwrap();
+        write_blocks(&dir.path().join("00000.rlp"), &blocks[..10]);
+        write_blocks(&dir.path().join("00001.rlp"), &blocks[10..]);
+
+        let mut client = FileClient::from_dir(dir.path()).await.unwrap();
+        assert_eq!(client.tip(), Some(blocks[29].hash()));
+        assert!(!client.is_exhausted(20));
+        assert!(client.is_exhausted(29));
+
+        // the local head must be the parent of the remaining blocks
+        let mut other = FileClient::from_dir(dir.path()).await.unwrap();
+        let mut forked = blocks[19].header.clone().unseal();
+        forked.extra_data = vec![1].into();
+        assert_matches!(
+            other.seek(&forked.seal_slow()),
+            Err(FileClientError::Discontinuity { number: 20, parent_number: 19, .. })
+        );
+
+        client.seek(&blocks[19].header).unwrap();
+        assert_eq!(client.headers.len(), 10);
+        assert_eq!(client.bodies.len(), 10);
+        assert!(!client.hash_to_number.contains_key(&blocks[19].hash()));
+    }
+
+    #[tokio::test]
+    async fn rejects_gap_between_files() {
+        let blocks = random_block_range(0..30, H256::zero(), 0..2);
+        let dir = tempfile::tempdir().unwrap();
+        write_blocks(&dir.path().join("00000.rlp"), &blocks[..10]);
+        write_blocks(&dir.path().join("00001.rlp"), &blocks[11..]);
+
+        assert_matches!(
+            FileClient::from_dir(dir.path()).await,
+            Err(FileClientError::Discontinuity { number: 11, parent_number: 9, .. })
+        );
+    }
 }
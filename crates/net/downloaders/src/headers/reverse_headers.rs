@@ -1008,7 +1008,7 @@ mod tests {
         assert!(downloader.sync_target_request.is_some());
 
         downloader.sync_target_request.take();
-        let target = SyncTarget::Gap(Header::default().seal(H256::random()));
+        let target = SyncTarget::Gap(Header::default().seal_slow());
         downloader.update_sync_target(target);
         assert!(downloader.sync_target_request.is_none());
         assert_matches!(
@@ -1032,7 +1032,7 @@ mod tests {
         downloader.queued_validated_headers.push(header.clone());
         let mut next = header.as_ref().clone();
         next.number += 1;
-        downloader.update_local_head(next.seal(H256::random()));
+        downloader.update_local_head(next.seal_slow());
         assert!(downloader.queued_validated_headers.is_empty());
     }
 
@@ -17,5 +17,9 @@ pub mod headers;
 /// Common downloader metrics.
 pub mod metrics;
 
+/// A downloader client that serves blocks from local block files.
+pub mod file_client;
+mod file_codec;
+
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
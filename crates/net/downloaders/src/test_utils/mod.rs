@@ -1,6 +1,6 @@
 #![allow(unused)]
 //! Test helper impls
-use crate::bodies::test_utils::create_raw_bodies;
+use crate::{bodies::test_utils::create_raw_bodies, file_codec::BlockFileCodec};
 use futures::SinkExt;
 use reth_eth_wire::BlockBody;
 use reth_interfaces::test_utils::generators::random_block_range;
@@ -61,10 +61,6 @@ pub(crate) async fn generate_bodies_file(
     (file, headers, bodies)
 }
 
-mod file_client;
-mod file_codec;
 mod test_client;
 
-pub use file_client::{FileClient, FileClientError};
-pub(crate) use file_codec::BlockFileCodec;
 pub use test_client::TestBodiesClient;
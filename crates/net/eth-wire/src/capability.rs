@@ -1,6 +1,6 @@
 //! All capability related types
 
-use crate::{version::ParseVersionError, EthMessage, EthVersion};
+use crate::{protocol::Protocol, version::ParseVersionError, EthMessage, EthVersion};
 use reth_codecs::add_arbitrary_tests;
 use reth_primitives::bytes::{BufMut, Bytes};
 use reth_rlp::{Decodable, DecodeError, Encodable, RlpDecodable, RlpEncodable};
@@ -181,8 +181,11 @@ pub enum SharedCapability {
     /// The `eth` capability.
     Eth { version: EthVersion, offset: u8 },
 
-    /// An unknown capability.
-    UnknownCapability { name: SmolStr, version: u8, offset: u8 },
+    /// Any other capability.
+    ///
+    /// `messages` is the number of message ids reserved by the capability, this is only known for
+    /// additional protocols that were registered locally, see [`Protocol`].
+    UnknownCapability { name: SmolStr, version: u8, offset: u8, messages: u8 },
 }
 
 impl SharedCapability {
@@ -190,10 +193,33 @@ impl SharedCapability {
     pub(crate) fn new(name: &str, version: u8, offset: u8) -> Result<Self, SharedCapabilityError> {
         match name {
             "eth" => Ok(Self::Eth { version: EthVersion::try_from(version)?, offset }),
-            _ => Ok(Self::UnknownCapability { name: name.into(), version, offset }),
+            _ => Ok(Self::UnknownCapability { name: name.into(), version, offset, messages: 0 }),
         }
     }
 
+    /// Creates a new [`SharedCapability`] for a locally supported [`Protocol`] at the given
+    /// offset.
+    pub(crate) fn from_protocol(
+        protocol: &Protocol,
+        offset: u8,
+    ) -> Result<Self, SharedCapabilityError> {
+        let Protocol { cap, messages } = protocol;
+        match cap.name.as_str() {
+            "eth" => Self::new("eth", cap.version as u8, offset),
+            name => Ok(Self::UnknownCapability {
+                name: name.into(),
+                version: cap.version as u8,
+                offset,
+                messages: *messages,
+            }),
+        }
+    }
+
+    /// Whether this is the `eth` capability.
+    pub fn is_eth(&self) -> bool {
+        matches!(self, SharedCapability::Eth { .. })
+    }
+
     /// Returns the name of the capability.
     pub fn name(&self) -> &str {
         match self {
@@ -222,9 +248,17 @@ impl SharedCapability {
     pub fn num_messages(&self) -> Result<u8, SharedCapabilityError> {
         match self {
             SharedCapability::Eth { version, .. } => Ok(version.total_messages()),
+            SharedCapability::UnknownCapability { messages, .. } if *messages > 0 => Ok(*messages),
             _ => Err(SharedCapabilityError::UnknownCapability),
         }
     }
+
+    /// Whether the given absolute message id falls into the message id space of this capability.
+    pub fn contains_message_id(&self, id: u8) -> bool {
+        let offset = self.offset();
+        let messages = self.num_messages().unwrap_or_default();
+        id >= offset && id - offset < messages
+    }
 }
 
 /// An error that may occur while creating a [`SharedCapability`].
@@ -283,4 +317,18 @@ mod tests {
         assert!(capabilities.supports_eth_v67());
         assert!(capabilities.supports_eth_v68());
     }
+
+    #[test]
+    fn from_custom_protocol() {
+        let protocol = Protocol::new(Capability::new("ping".into(), 1), 2);
+        let capability = SharedCapability::from_protocol(&protocol, 0x1d).unwrap();
+
+        assert_eq!(capability.name(), "ping");
+        assert_eq!(capability.version(), 1);
+        assert_eq!(capability.num_messages().unwrap(), 2);
+        assert!(!capability.contains_message_id(0x1c));
+        assert!(capability.contains_message_id(0x1d));
+        assert!(capability.contains_message_id(0x1e));
+        assert!(!capability.contains_message_id(0x1f));
+    }
 }
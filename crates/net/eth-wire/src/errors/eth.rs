@@ -19,6 +19,8 @@ pub enum EthStreamError {
     EthInvalidMessageError(EthVersion, EthMessageID),
     #[error("message size ({0}) exceeds max length (10MB)")]
     MessageTooBig(usize),
+    #[error("{id:?} message size ({size}) exceeds max length ({max_size})")]
+    MessageSizeLimitExceeded { id: EthMessageID, size: usize, max_size: usize },
     #[error("TransactionHashes invalid len of fields: hashes_len={hashes_len} types_len={types_len} sizes_len={sizes_len}")]
     TransactionHashesInvalidLenOfFields { hashes_len: usize, types_len: usize, sizes_len: usize },
 }
@@ -35,6 +37,29 @@ impl EthStreamError {
         }
     }
 
+    /// Returns `true` if the peer violated the protocol, by sending a message that is too large,
+    /// that can't be decoded or that is not allowed.
+    pub fn is_protocol_violation(&self) -> bool {
+        match self {
+            EthStreamError::P2PStreamError(err) => matches!(
+                err,
+                P2PStreamError::Rlp(_) |
+                    P2PStreamError::Snap(_) |
+                    P2PStreamError::MessageTooBig { .. } |
+                    P2PStreamError::EmptyProtocolMessage |
+                    P2PStreamError::UnknownReservedMessageId(_)
+            ),
+            EthStreamError::EthHandshakeError(err) => {
+                err.disconnect_reason() == DisconnectReason::ProtocolBreach
+            }
+            EthStreamError::EthInvalidMessageError(..) |
+            EthStreamError::MessageTooBig(_) |
+            EthStreamError::MessageSizeLimitExceeded { .. } |
+            EthStreamError::TransactionHashesInvalidLenOfFields { .. } => true,
+            EthStreamError::ParseVersionError(_) => false,
+        }
+    }
+
     /// Returns the [io::Error] if it was caused by IO
     pub fn as_io(&self) -> Option<&io::Error> {
         if let EthStreamError::P2PStreamError(P2PStreamError::Io(io)) = self {
@@ -66,6 +91,8 @@ pub enum EthHandshakeError {
     NonStatusMessageInHandshake,
     #[error("no response received when sending out handshake")]
     NoResponse,
+    #[error("timed out waiting for the status message of the peer")]
+    Timeout,
     #[error(transparent)]
     InvalidFork(#[from] ValidationError),
     #[error("mismatched genesis in Status message. expected: {expected:?}, got: {got:?}")]
@@ -77,3 +104,29 @@ pub enum EthHandshakeError {
     #[error("total difficulty bitlen is too large. maximum: {maximum:?}, got: {got:?}")]
     TotalDifficultyBitLenTooLarge { maximum: usize, got: usize },
 }
+
+// === impl EthHandshakeError ===
+
+impl EthHandshakeError {
+    /// Returns the [`DisconnectReason`] that should be sent to the peer if the handshake failed
+    /// with this error.
+    ///
+    /// Peers on a different network or an incompatible fork are useless to us, everything else is
+    /// a violation of the protocol.
+    pub fn disconnect_reason(&self) -> DisconnectReason {
+        match self {
+            EthHandshakeError::NoResponse | EthHandshakeError::Timeout => {
+                DisconnectReason::DisconnectRequested
+            }
+            EthHandshakeError::InvalidFork(_) |
+            EthHandshakeError::MismatchedGenesis { .. } |
+            EthHandshakeError::MismatchedChain { .. } => DisconnectReason::UselessPeer,
+            EthHandshakeError::StatusNotInHandshake |
+            EthHandshakeError::NonStatusMessageInHandshake |
+            EthHandshakeError::MismatchedProtocolVersion { .. } |
+            EthHandshakeError::TotalDifficultyBitLenTooLarge { .. } => {
+                DisconnectReason::ProtocolBreach
+            }
+        }
+    }
+}
@@ -35,6 +35,8 @@ pub enum P2PStreamError {
     PingBeforeHandshake,
     #[error("too many messages buffered before sending")]
     SendBufferFull,
+    #[error("subprotocol {0} doesn't keep up with the messages of the peer")]
+    ProtocolBufferFull(String),
     #[error("disconnected")]
     Disconnected(DisconnectReason),
     #[error("unknown disconnect reason: {0}")]
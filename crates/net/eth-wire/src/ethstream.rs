@@ -1,7 +1,7 @@
 use crate::{
     errors::{EthHandshakeError, EthStreamError},
     message::{EthBroadcastMessage, ProtocolBroadcastMessage},
-    types::{EthMessage, ProtocolMessage, Status},
+    types::{EthMessage, EthMessageID, ProtocolMessage, Status},
     CanDisconnect, DisconnectReason, EthVersion,
 };
 use futures::{ready, Sink, SinkExt, StreamExt};
@@ -10,10 +10,11 @@ use reth_primitives::{
     bytes::{Bytes, BytesMut},
     ForkFilter,
 };
-use reth_rlp::Encodable;
+use reth_rlp::{Decodable, Encodable};
 use std::{
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio_stream::Stream;
 
@@ -21,6 +22,97 @@ use tokio_stream::Stream;
 // https://github.com/ethereum/go-ethereum/blob/30602163d5d8321fbc68afdcbbaf2362b2641bde/eth/protocols/eth/protocol.go#L50
 pub const MAX_MESSAGE_SIZE: usize = 10 * 1024 * 1024;
 
+/// Default maximum size of a request message, which only contains block hashes, transaction hashes
+/// or a range of blocks.
+pub const DEFAULT_MAX_REQUEST_SIZE: usize = 256 * 1024;
+
+/// Default maximum size of a `NewBlockHashes` or `NewPooledTransactionHashes` announcement.
+// geth announces at most 4096 hashes per message
+pub const DEFAULT_MAX_ANNOUNCEMENT_SIZE: usize = 256 * 1024;
+
+/// Default maximum size of a `Transactions` broadcast.
+// geth broadcasts up to 100KB of transactions per message, plus a single transaction of at most
+// 128KB
+pub const DEFAULT_MAX_TRANSACTIONS_SIZE: usize = 1024 * 1024;
+
+/// The maximum size of an `eth` message, per message type.
+///
+/// Messages that exceed the limit of their type are rejected before they are decoded. No limit can
+/// exceed [MAX_MESSAGE_SIZE].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageSizeLimits {
+    /// Maximum size of `Status`, `GetBlockHeaders`, `GetBlockBodies`, `GetPooledTransactions`,
+    /// `GetNodeData` and `GetReceipts` messages.
+    ///
+    /// Default is [DEFAULT_MAX_REQUEST_SIZE]
+    pub requests: usize,
+    /// Maximum size of `NewBlockHashes` and `NewPooledTransactionHashes` messages.
+    ///
+    /// Default is [DEFAULT_MAX_ANNOUNCEMENT_SIZE]
+    pub announcements: usize,
+    /// Maximum size of `Transactions` messages.
+    ///
+    /// Default is [DEFAULT_MAX_TRANSACTIONS_SIZE]
+    pub transactions: usize,
+    /// Maximum size of `NewBlock` messages.
+    ///
+    /// Default is [MAX_MESSAGE_SIZE]
+    pub new_block: usize,
+    /// Maximum size of `BlockHeaders`, `BlockBodies`, `PooledTransactions`, `NodeData` and
+    /// `Receipts` messages.
+    ///
+    /// Default is [MAX_MESSAGE_SIZE]
+    pub responses: usize,
+}
+
+impl MessageSizeLimits {
+    /// Returns the maximum size of a message of the given type.
+    pub fn max_size(&self, id: EthMessageID) -> usize {
+        let max_size = match id {
+            EthMessageID::Status |
+            EthMessageID::GetBlockHeaders |
+            EthMessageID::GetBlockBodies |
+            EthMessageID::GetPooledTransactions |
+            EthMessageID::GetNodeData |
+            EthMessageID::GetReceipts => self.requests,
+            EthMessageID::NewBlockHashes | EthMessageID::NewPooledTransactionHashes => {
+                self.announcements
+            }
+            EthMessageID::Transactions => self.transactions,
+            EthMessageID::NewBlock => self.new_block,
+            EthMessageID::BlockHeaders |
+            EthMessageID::BlockBodies |
+            EthMessageID::PooledTransactions |
+            EthMessageID::NodeData |
+            EthMessageID::Receipts => self.responses,
+        };
+        max_size.min(MAX_MESSAGE_SIZE)
+    }
+}
+
+impl Default for MessageSizeLimits {
+    fn default() -> Self {
+        Self {
+            requests: DEFAULT_MAX_REQUEST_SIZE,
+            announcements: DEFAULT_MAX_ANNOUNCEMENT_SIZE,
+            transactions: DEFAULT_MAX_TRANSACTIONS_SIZE,
+            new_block: MAX_MESSAGE_SIZE,
+            responses: MAX_MESSAGE_SIZE,
+        }
+    }
+}
+
+/// [`HANDSHAKE_TIMEOUT`] determines the amount of time to wait for the `Status` message of the
+/// remote peer before the `eth` handshake is considered failed.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The maximum bit length of the total difficulty a peer may announce.
+///
+/// TD at mainnet block #7753254 is 76 bits. If it becomes 100 million times larger, it will still
+/// fit within 100 bits
+const MAX_TOTAL_DIFFICULTY_BITS: usize = 100;
+
 /// An un-authenticated [`EthStream`]. This is consumed and returns a [`EthStream`] after the
 /// `Status` handshake is completed.
 #[pin_project]
@@ -66,7 +158,13 @@ where
         let our_status_bytes = our_status_bytes.freeze();
         self.inner.send(our_status_bytes).await?;
 
-        let their_msg_res = self.inner.next().await;
+        let their_msg_res = match tokio::time::timeout(HANDSHAKE_TIMEOUT, self.inner.next()).await {
+            Ok(res) => res,
+            Err(_) => {
+                self.inner.disconnect(DisconnectReason::DisconnectRequested).await?;
+                return Err(EthStreamError::EthHandshakeError(EthHandshakeError::Timeout))
+            }
+        };
 
         let their_msg = match their_msg_res {
             Some(msg) => msg,
@@ -91,56 +189,20 @@ where
             }
         };
 
-        // The following checks should match the checks in go-ethereum:
-        // https://github.com/ethereum/go-ethereum/blob/9244d5cd61f3ea5a7645fdf2a1a96d53421e412f/eth/protocols/eth/handshake.go#L87-L89
         match msg.message {
             EthMessage::Status(resp) => {
                 tracing::trace!(
                     status=%resp,
                     "validating incoming eth status from peer"
                 );
-                if status.genesis != resp.genesis {
-                    self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
-                    return Err(EthHandshakeError::MismatchedGenesis {
-                        expected: status.genesis,
-                        got: resp.genesis,
-                    }
-                    .into())
-                }
-
-                if status.version != resp.version {
-                    self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
-                    return Err(EthHandshakeError::MismatchedProtocolVersion {
-                        expected: status.version,
-                        got: resp.version,
-                    }
-                    .into())
-                }
-
-                if status.chain != resp.chain {
-                    self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
-                    return Err(EthHandshakeError::MismatchedChain {
-                        expected: status.chain,
-                        got: resp.chain,
-                    }
-                    .into())
-                }
-
-                // TD at mainnet block #7753254 is 76 bits. If it becomes 100 million times
-                // larger, it will still fit within 100 bits
-                if status.total_difficulty.bit_len() > 100 {
-                    self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
-                    return Err(EthHandshakeError::TotalDifficultyBitLenTooLarge {
-                        maximum: 100,
-                        got: status.total_difficulty.bit_len(),
-                    }
-                    .into())
-                }
-
-                if let Err(err) =
-                    fork_filter.validate(resp.forkid).map_err(EthHandshakeError::InvalidFork)
-                {
-                    self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
+                if let Err(err) = validate_status(&status, &resp, &fork_filter) {
+                    let reason = err.disconnect_reason();
+                    tracing::debug!(
+                        ?reason,
+                        %err,
+                        "disconnecting peer after invalid eth status"
+                    );
+                    self.inner.disconnect(reason).await?;
                     return Err(err.into())
                 }
 
@@ -160,12 +222,54 @@ where
     }
 }
 
+/// Validates the `Status` message of the remote peer against our own `Status`.
+///
+/// The following checks should match the checks in go-ethereum:
+/// <https://github.com/ethereum/go-ethereum/blob/9244d5cd61f3ea5a7645fdf2a1a96d53421e412f/eth/protocols/eth/handshake.go#L87-L89>
+fn validate_status(
+    status: &Status,
+    resp: &Status,
+    fork_filter: &ForkFilter,
+) -> Result<(), EthHandshakeError> {
+    if status.genesis != resp.genesis {
+        return Err(EthHandshakeError::MismatchedGenesis {
+            expected: status.genesis,
+            got: resp.genesis,
+        })
+    }
+
+    if status.version != resp.version {
+        return Err(EthHandshakeError::MismatchedProtocolVersion {
+            expected: status.version,
+            got: resp.version,
+        })
+    }
+
+    if status.chain != resp.chain {
+        return Err(EthHandshakeError::MismatchedChain { expected: status.chain, got: resp.chain })
+    }
+
+    if resp.total_difficulty.bit_len() > MAX_TOTAL_DIFFICULTY_BITS {
+        return Err(EthHandshakeError::TotalDifficultyBitLenTooLarge {
+            maximum: MAX_TOTAL_DIFFICULTY_BITS,
+            got: resp.total_difficulty.bit_len(),
+        })
+    }
+
+    // reject peers that are on an incompatible fork or stale, see EIP-2124
+    fork_filter.validate(resp.forkid)?;
+
+    Ok(())
+}
+
 /// An `EthStream` wraps over any `Stream` that yields bytes and makes it
 /// compatible with eth-networking protocol messages, which get RLP encoded/decoded.
 #[pin_project]
 #[derive(Debug)]
 pub struct EthStream<S> {
     version: EthVersion,
+    /// The maximum size of incoming messages, per message type.
+    limits: MessageSizeLimits,
     #[pin]
     inner: S,
 }
@@ -174,7 +278,18 @@ impl<S> EthStream<S> {
     /// Creates a new unauthed [`EthStream`] from a provided stream. You will need
     /// to manually handshake a peer.
     pub fn new(version: EthVersion, inner: S) -> Self {
-        Self { version, inner }
+        Self { version, limits: Default::default(), inner }
+    }
+
+    /// Sets the maximum size of incoming messages, per message type.
+    pub fn with_message_size_limits(mut self, limits: MessageSizeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns the maximum size of incoming messages, per message type.
+    pub fn message_size_limits(&self) -> &MessageSizeLimits {
+        &self.limits
     }
 
     /// Returns the eth version.
@@ -238,6 +353,18 @@ where
             return Poll::Ready(Some(Err(EthStreamError::MessageTooBig(bytes.len()))))
         }
 
+        // reject oversized messages before decoding them, unknown message ids fail to decode below
+        if let Ok(id) = EthMessageID::decode(&mut bytes.as_ref()) {
+            let max_size = this.limits.max_size(id);
+            if bytes.len() > max_size {
+                return Poll::Ready(Some(Err(EthStreamError::MessageSizeLimitExceeded {
+                    id,
+                    size: bytes.len(),
+                    max_size,
+                })))
+            }
+        }
+
         let msg = match ProtocolMessage::decode_message(*this.version, &mut bytes.as_ref()) {
             Ok(m) => m,
             Err(err) => {
@@ -312,23 +439,68 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::UnauthedEthStream;
+    use super::{MessageSizeLimits, UnauthedEthStream};
     use crate::{
         capability::Capability,
         errors::{EthHandshakeError, EthStreamError},
         hello::HelloMessage,
         p2pstream::{ProtocolVersion, UnauthedP2PStream},
-        types::{broadcast::BlockHashNumber, EthMessage, EthVersion, Status},
-        EthStream, PassthroughCodec,
+        types::{
+            broadcast::BlockHashNumber, EthMessage, EthMessageID, EthVersion, ProtocolMessage,
+            Status,
+        },
+        DisconnectReason, EthStream, PassthroughCodec,
     };
     use ethers_core::types::Chain;
     use futures::{SinkExt, StreamExt};
     use reth_ecies::{stream::ECIESStream, util::pk2id};
-    use reth_primitives::{ForkFilter, Head, H256, U256};
+    use reth_primitives::{
+        bytes::BytesMut, ForkFilter, ForkHash, ForkId, Head, ValidationError, H256, U256,
+    };
+    use reth_rlp::Encodable;
     use secp256k1::{SecretKey, SECP256K1};
     use tokio::net::{TcpListener, TcpStream};
     use tokio_util::codec::Decoder;
 
+    fn test_status(fork_filter: &ForkFilter, genesis: H256) -> Status {
+        Status {
+            version: EthVersion::Eth67 as u8,
+            chain: Chain::Mainnet.into(),
+            total_difficulty: U256::ZERO,
+            blockhash: H256::random(),
+            genesis,
+            forkid: fork_filter.current(),
+        }
+    }
+
+    /// Performs the handshake against a scripted peer that responds with the given `Status`
+    /// regardless of the status we send, and asserts that the peer was disconnected if the
+    /// handshake failed.
+    async fn handshake_with_scripted_peer(
+        status: Status,
+        fork_filter: ForkFilter,
+        their_status: Status,
+    ) -> Result<Status, EthStreamError> {
+        let (local, remote) = tokio::io::duplex(1024);
+        let mut remote = PassthroughCodec::default().framed(remote);
+
+        let mut their_status_bytes = BytesMut::new();
+        ProtocolMessage::from(EthMessage::Status(their_status)).encode(&mut their_status_bytes);
+        remote.send(their_status_bytes.freeze()).await.unwrap();
+
+        let stream = PassthroughCodec::default().framed(local);
+        let res = UnauthedEthStream::new(stream).handshake(status, fork_filter).await;
+
+        // the scripted peer receives our status
+        assert!(remote.next().await.unwrap().is_ok());
+        if res.is_err() {
+            // and then the connection is closed
+            assert!(remote.next().await.is_none());
+        }
+
+        res.map(|(_, status)| status)
+    }
+
     #[tokio::test]
     async fn can_handshake() {
         let genesis = H256::random();
@@ -511,6 +683,41 @@ mod tests {
         handle.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn reject_message_exceeding_size_limit() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+        let hashes = (0..10).map(|number| BlockHashNumber { hash: H256::random(), number });
+        let test_msg = EthMessage::NewBlockHashes(hashes.collect::<Vec<_>>().into());
+
+        let handle = tokio::spawn(async move {
+            let (incoming, _) = listener.accept().await.unwrap();
+            let stream = PassthroughCodec::default().framed(incoming);
+            let limits = MessageSizeLimits { announcements: 100, ..Default::default() };
+            let mut stream =
+                EthStream::new(EthVersion::Eth67, stream).with_message_size_limits(limits);
+
+            let err = stream.next().await.unwrap().unwrap_err();
+            assert!(err.is_protocol_violation());
+            assert!(matches!(
+                err,
+                EthStreamError::MessageSizeLimitExceeded {
+                    id: EthMessageID::NewBlockHashes,
+                    max_size: 100,
+                    ..
+                }
+            ));
+        });
+
+        let outgoing = TcpStream::connect(local_addr).await.unwrap();
+        let sink = PassthroughCodec::default().framed(outgoing);
+        let mut client_stream = EthStream::new(EthVersion::Eth67, sink);
+
+        client_stream.send(test_msg).await.unwrap();
+
+        handle.await.unwrap();
+    }
+
     #[tokio::test]
     async fn can_write_and_read_ecies() {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -634,4 +841,113 @@ mod tests {
         // make sure the server receives the message and asserts before ending the test
         handle.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn scripted_peer_handshake() {
+        let genesis = H256::random();
+        let fork_filter = ForkFilter::new(Head::default(), genesis, Vec::new());
+        let status = test_status(&fork_filter, genesis);
+
+        let their_status = handshake_with_scripted_peer(status, fork_filter, status).await.unwrap();
+        assert_eq!(their_status, status);
+    }
+
+    #[tokio::test]
+    async fn fail_handshake_on_mismatched_genesis() {
+        let genesis = H256::random();
+        let fork_filter = ForkFilter::new(Head::default(), genesis, Vec::new());
+        let status = test_status(&fork_filter, genesis);
+        let their_status = Status { genesis: H256::random(), ..status };
+
+        let err =
+            handshake_with_scripted_peer(status, fork_filter, their_status).await.unwrap_err();
+        match err {
+            EthStreamError::EthHandshakeError(err) => {
+                assert!(matches!(err, EthHandshakeError::MismatchedGenesis { .. }));
+                assert_eq!(err.disconnect_reason(), DisconnectReason::UselessPeer);
+            }
+            err => unreachable!("{err:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn fail_handshake_on_mismatched_chain() {
+        let genesis = H256::random();
+        let fork_filter = ForkFilter::new(Head::default(), genesis, Vec::new());
+        let status = test_status(&fork_filter, genesis);
+        let their_status = Status { chain: Chain::Goerli.into(), ..status };
+
+        let err =
+            handshake_with_scripted_peer(status, fork_filter, their_status).await.unwrap_err();
+        match err {
+            EthStreamError::EthHandshakeError(err) => {
+                assert!(matches!(err, EthHandshakeError::MismatchedChain { .. }));
+                assert_eq!(err.disconnect_reason(), DisconnectReason::UselessPeer);
+            }
+            err => unreachable!("{err:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn fail_handshake_on_incompatible_fork() {
+        let genesis = H256::random();
+        let fork_filter = ForkFilter::new(Head::default(), genesis, Vec::new());
+        let status = test_status(&fork_filter, genesis);
+        let their_status = Status {
+            forkid: ForkId { hash: ForkHash([0xde, 0xad, 0xbe, 0xef]), next: 0 },
+            ..status
+        };
+
+        let err =
+            handshake_with_scripted_peer(status, fork_filter, their_status).await.unwrap_err();
+        match err {
+            EthStreamError::EthHandshakeError(err) => {
+                assert!(matches!(
+                    err,
+                    EthHandshakeError::InvalidFork(
+                        ValidationError::LocalIncompatibleOrStale { .. }
+                    )
+                ));
+                assert_eq!(err.disconnect_reason(), DisconnectReason::UselessPeer);
+            }
+            err => unreachable!("{err:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn fail_handshake_on_remote_high_td() {
+        let genesis = H256::random();
+        let fork_filter = ForkFilter::new(Head::default(), genesis, Vec::new());
+        let status = test_status(&fork_filter, genesis);
+        // only the remote announces an unreasonable total difficulty
+        let their_status =
+            Status { total_difficulty: U256::from(2).pow(U256::from(100)), ..status };
+
+        let err =
+            handshake_with_scripted_peer(status, fork_filter, their_status).await.unwrap_err();
+        match err {
+            EthStreamError::EthHandshakeError(err) => {
+                assert!(matches!(
+                    err,
+                    EthHandshakeError::TotalDifficultyBitLenTooLarge { maximum: 100, got: 101 }
+                ));
+                assert_eq!(err.disconnect_reason(), DisconnectReason::ProtocolBreach);
+            }
+            err => unreachable!("{err:?}"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fail_handshake_on_timeout() {
+        let genesis = H256::random();
+        let fork_filter = ForkFilter::new(Head::default(), genesis, Vec::new());
+        let status = test_status(&fork_filter, genesis);
+
+        // the remote never responds
+        let (local, _remote) = tokio::io::duplex(1024);
+        let stream = PassthroughCodec::default().framed(local);
+        let res = UnauthedEthStream::new(stream).handshake(status, fork_filter).await;
+
+        assert!(matches!(res, Err(EthStreamError::EthHandshakeError(EthHandshakeError::Timeout))));
+    }
 }
@@ -14,6 +14,7 @@ mod ethstream;
 mod hello;
 mod p2pstream;
 mod pinger;
+pub mod protocol;
 pub use builder::*;
 pub mod types;
 pub use types::*;
@@ -25,7 +26,10 @@ pub use tokio_util::codec::{
 
 pub use crate::{
     disconnect::{CanDisconnect, DisconnectReason},
-    ethstream::{EthStream, UnauthedEthStream, MAX_MESSAGE_SIZE},
+    ethstream::{
+        EthStream, MessageSizeLimits, UnauthedEthStream, DEFAULT_MAX_ANNOUNCEMENT_SIZE,
+        DEFAULT_MAX_REQUEST_SIZE, DEFAULT_MAX_TRANSACTIONS_SIZE, MAX_MESSAGE_SIZE,
+    },
     hello::HelloMessage,
     p2pstream::{P2PMessage, P2PMessageID, P2PStream, ProtocolVersion, UnauthedP2PStream},
 };
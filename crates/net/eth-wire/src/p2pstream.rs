@@ -4,7 +4,8 @@ use crate::{
     disconnect::CanDisconnect,
     errors::{P2PHandshakeError, P2PStreamError},
     pinger::{Pinger, PingerEvent},
-    DisconnectReason, HelloMessage,
+    protocol::{Protocol, ProtocolProxy},
+    DisconnectReason, EthVersion, HelloMessage,
 };
 use futures::{Sink, SinkExt, StreamExt};
 use metrics::counter;
@@ -22,6 +23,7 @@ use std::{
     task::{ready, Context, Poll},
     time::Duration,
 };
+use tokio::sync::mpsc::error::TrySendError;
 use tokio_stream::Stream;
 
 #[cfg(feature = "serde")]
@@ -64,12 +66,24 @@ const MAX_P2P_CAPACITY: usize = 64;
 pub struct UnauthedP2PStream<S> {
     #[pin]
     inner: S,
+    /// Additional, non `eth`, protocols that are supported locally.
+    protocols: Vec<Protocol>,
 }
 
 impl<S> UnauthedP2PStream<S> {
     /// Create a new `UnauthedP2PStream` from a type `S` which implements `Stream` and `Sink`.
     pub fn new(inner: S) -> Self {
-        Self { inner }
+        Self { inner, protocols: Vec::new() }
+    }
+
+    /// Sets the additional protocols that are supported locally.
+    ///
+    /// The capabilities of these protocols are expected to be part of the local `Hello` message,
+    /// this is required to reserve the message id space of these protocols if they are shared
+    /// with the peer.
+    pub fn with_protocols(mut self, protocols: Vec<Protocol>) -> Self {
+        self.protocols = protocols;
+        self
     }
 }
 
@@ -141,20 +155,25 @@ where
             })
         }
 
-        // determine shared capabilities (currently returns only one capability)
+        // determine shared capabilities, the eth capability is the primary capability of the
+        // stream
+        let local_protocols = local_protocols(hello.capabilities, &self.protocols);
         let capability_res =
-            set_capability_offsets(hello.capabilities, their_hello.capabilities.clone());
+            set_protocol_offsets(local_protocols, their_hello.capabilities.clone());
 
-        let shared_capability = match capability_res {
+        let shared_capabilities = match capability_res {
             Err(err) => {
                 // we don't share any capabilities, send a disconnect message
                 self.send_disconnect(DisconnectReason::UselessPeer).await?;
                 Err(err)
             }
-            Ok(cap) => Ok(cap),
+            Ok(caps) => Ok(caps),
         }?;
 
-        let stream = P2PStream::new(self.inner, shared_capability);
+        let shared_capability =
+            shared_capabilities.iter().find(|cap| cap.is_eth()).cloned().expect("checked; qed");
+        let mut stream = P2PStream::new(self.inner, shared_capability);
+        stream.shared_capabilities = shared_capabilities;
 
         Ok((stream, their_hello))
     }
@@ -209,6 +228,12 @@ pub struct P2PStream<S> {
     /// The supported capability for this stream.
     shared_capability: SharedCapability,
 
+    /// All capabilities shared with the peer, including the primary `shared_capability`.
+    shared_capabilities: Vec<SharedCapability>,
+
+    /// Additional subprotocols multiplexed over this stream.
+    protocols: Vec<ProtocolProxy>,
+
     /// Outgoing messages buffered for sending to the underlying stream.
     outgoing_messages: VecDeque<Bytes>,
 
@@ -227,7 +252,9 @@ impl<S> P2PStream<S> {
             encoder: snap::raw::Encoder::new(),
             decoder: snap::raw::Decoder::new(),
             pinger: Pinger::new(PING_INTERVAL, PING_TIMEOUT),
+            shared_capabilities: vec![capability.clone()],
             shared_capability: capability,
+            protocols: Vec::new(),
             outgoing_messages: VecDeque::new(),
             disconnecting: false,
         }
@@ -238,6 +265,19 @@ impl<S> P2PStream<S> {
         &self.shared_capability
     }
 
+    /// Returns all capabilities shared with the peer.
+    pub fn shared_capabilities(&self) -> &[SharedCapability] {
+        &self.shared_capabilities
+    }
+
+    /// Installs an additional subprotocol on this stream.
+    ///
+    /// All incoming messages within the message id space of the protocol's capability are
+    /// delivered to the protocol and all messages of the protocol are sent to the peer.
+    pub fn install_protocol(&mut self, proxy: ProtocolProxy) {
+        self.protocols.push(proxy);
+    }
+
     /// Returns `true` if the connection is about to disconnect.
     pub fn is_disconnecting(&self) -> bool {
         self.disconnecting
@@ -397,6 +437,36 @@ where
                     // we have received an unknown reserved message
                     return Poll::Ready(Some(Err(P2PStreamError::UnknownReservedMessageId(id))))
                 }
+                _ if !this.shared_capability.contains_message_id(id) &&
+                    this.shared_capabilities.iter().any(|cap| cap.contains_message_id(id)) =>
+                {
+                    // this message belongs to an additional subprotocol, so we deliver it to the
+                    // protocol with a message id relative to the capability's offset
+                    if let Some(idx) = this
+                        .protocols
+                        .iter()
+                        .position(|proxy| proxy.shared_capability().contains_message_id(id))
+                    {
+                        let proxy = &this.protocols[idx];
+                        decompress_buf[0] = id - proxy.shared_capability().offset();
+                        match proxy.on_message(decompress_buf) {
+                            Ok(()) => {}
+                            Err(TrySendError::Full(_)) => {
+                                // the protocol doesn't keep up with the messages of the peer
+                                let name = proxy.shared_capability().name().to_string();
+                                return Poll::Ready(Some(Err(P2PStreamError::ProtocolBufferFull(
+                                    name,
+                                ))))
+                            }
+                            Err(TrySendError::Closed(_)) => {
+                                // the protocol dropped its connection, its messages are dropped
+                                this.protocols.swap_remove(idx);
+                            }
+                        }
+                    } else {
+                        tracing::trace!(id, "dropping message of uninstalled subprotocol");
+                    }
+                }
                 _ => {
                     // we have received a message that is outside the `p2p` reserved message space,
                     // so it is a subprotocol message.
@@ -456,6 +526,9 @@ where
             }
         }
 
+        // queue in the messages of all additional subprotocols
+        this.poll_protocols(cx)?;
+
         match this.inner.poll_ready_unpin(cx) {
             Poll::Pending => {}
             Poll::Ready(Err(err)) => return Poll::Ready(Err(P2PStreamError::Io(err))),
@@ -483,25 +556,10 @@ where
             return Err(P2PStreamError::SendBufferFull)
         }
 
-        let mut compressed = BytesMut::zeroed(1 + snap::raw::max_compress_len(item.len() - 1));
-        let compressed_size =
-            this.encoder.compress(&item[1..], &mut compressed[1..]).map_err(|err| {
-                tracing::debug!(
-                    ?err,
-                    msg=%hex::encode(&item[1..]),
-                    "error compressing p2p message"
-                );
-                err
-            })?;
-
-        // truncate the compressed buffer to the actual compressed size (plus one for the message
-        // id)
-        compressed.truncate(compressed_size + 1);
-
         // all messages sent in this stream are subprotocol messages, so we need to switch the
         // message id based on the offset
-        compressed[0] = item[0] + this.shared_capability.offset();
-        this.outgoing_messages.push_back(compressed.freeze());
+        let compressed = compress_message(this.encoder, &item, this.shared_capability.offset())?;
+        this.outgoing_messages.push_back(compressed);
 
         Ok(())
     }
@@ -533,6 +591,73 @@ where
     }
 }
 
+impl<S> P2PStream<S> {
+    /// Queues in all outgoing messages of the installed subprotocols until the buffer is full.
+    fn poll_protocols(&mut self, cx: &mut Context<'_>) -> Result<(), P2PStreamError> {
+        let mut idx = 0;
+        while idx < self.protocols.len() {
+            if self.outgoing_messages.len() >= MAX_P2P_CAPACITY {
+                return Ok(())
+            }
+            match self.protocols[idx].poll_outgoing(cx) {
+                Poll::Ready(Some(msg)) => {
+                    let offset = self.protocols[idx].shared_capability().offset();
+                    let compressed = compress_message(&mut self.encoder, &msg, offset)?;
+                    self.outgoing_messages.push_back(compressed);
+                }
+                Poll::Ready(None) => {
+                    // the protocol dropped its connection
+                    self.protocols.swap_remove(idx);
+                }
+                Poll::Pending => idx += 1,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compresses the payload of the given message with snappy and adds the `offset` to the message
+/// id.
+fn compress_message(
+    encoder: &mut snap::raw::Encoder,
+    item: &Bytes,
+    offset: u8,
+) -> Result<Bytes, P2PStreamError> {
+    let mut compressed = BytesMut::zeroed(1 + snap::raw::max_compress_len(item.len() - 1));
+    let compressed_size = encoder.compress(&item[1..], &mut compressed[1..]).map_err(|err| {
+        tracing::debug!(
+            ?err,
+            msg=%hex::encode(&item[1..]),
+            "error compressing p2p message"
+        );
+        err
+    })?;
+
+    // truncate the compressed buffer to the actual compressed size (plus one for the message
+    // id)
+    compressed.truncate(compressed_size + 1);
+    compressed[0] = item[0] + offset;
+
+    Ok(compressed.freeze())
+}
+
+/// Returns all locally supported protocols of the given capabilities.
+///
+/// The number of messages of `eth` capabilities is known, all other capabilities must be part of
+/// the additional `protocols`, unknown capabilities are skipped.
+fn local_protocols(capabilities: Vec<Capability>, protocols: &[Protocol]) -> Vec<Protocol> {
+    capabilities
+        .into_iter()
+        .filter_map(|cap| {
+            if cap.name == "eth" {
+                EthVersion::try_from(cap.version as u8).ok().map(Protocol::eth)
+            } else {
+                protocols.iter().find(|protocol| protocol.cap == cap).cloned()
+            }
+        })
+        .collect()
+}
+
 /// Determines the offsets for each shared capability between the input list of peer
 /// capabilities and the input list of locally supported capabilities.
 ///
@@ -543,8 +668,29 @@ pub fn set_capability_offsets(
     local_capabilities: Vec<Capability>,
     peer_capabilities: Vec<Capability>,
 ) -> Result<SharedCapability, P2PStreamError> {
+    let shared_capabilities =
+        set_protocol_offsets(local_protocols(local_capabilities, &[]), peer_capabilities)?;
+
+    // NOTE: additional protocols are multiplexed via `P2PStream::install_protocol`, the primary
+    // capability of the stream is the `eth` capability.
+    shared_capabilities
+        .into_iter()
+        .find(|cap| cap.is_eth())
+        .ok_or(P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedCapabilities))
+}
+
+/// Determines the offsets for all shared capabilities between the input list of peer capabilities
+/// and the input list of locally supported protocols.
+///
+/// The returned capabilities are ordered by their offset. Fails if the `eth` capability isn't
+/// shared.
+pub fn set_protocol_offsets(
+    local_protocols: Vec<Protocol>,
+    peer_capabilities: Vec<Capability>,
+) -> Result<Vec<SharedCapability>, P2PStreamError> {
     // find intersection of capabilities
-    let our_capabilities = local_capabilities.into_iter().collect::<HashSet<_>>();
+    let our_capabilities =
+        local_protocols.iter().map(|protocol| protocol.cap.clone()).collect::<HashSet<_>>();
 
     // map of capability name to version
     let mut shared_capabilities = HashMap::new();
@@ -594,35 +740,33 @@ pub fn set_capability_offsets(
     // alphabetic order.
     let mut offset = MAX_RESERVED_MESSAGE_ID + 1;
     for name in shared_capability_names {
-        let version = shared_capabilities.get(&name).unwrap();
+        let version = *shared_capabilities.get(&name).unwrap();
+        let protocol = local_protocols
+            .iter()
+            .find(|protocol| protocol.cap.name == name && protocol.cap.version == version)
+            .expect("capability is shared; qed");
 
-        let shared_capability = SharedCapability::new(&name, *version as u8, offset)?;
+        let shared_capability = SharedCapability::from_protocol(protocol, offset)?;
 
-        match shared_capability {
-            SharedCapability::UnknownCapability { .. } => {
-                // Capabilities which are not shared are ignored
-                tracing::debug!("unknown capability: name={:?}, version={}", name, version,);
-            }
-            SharedCapability::Eth { .. } => {
-                // increment the offset if the capability is known
-                offset += shared_capability.num_messages()?;
+        match shared_capability.num_messages() {
+            Ok(messages) => {
+                // increment the offset if the number of messages of the capability is known
+                offset += messages;
 
                 shared_with_offsets.push(shared_capability);
             }
+            Err(_) => {
+                // Capabilities without any messages are ignored
+                tracing::debug!("unknown capability: name={:?}, version={}", name, version,);
+            }
         }
     }
 
-    // TODO: support multiple capabilities - we would need a new Stream type to go on top of
-    // `P2PStream` containing its capability. `P2PStream` would still send pings and handle
-    // pongs, but instead contain a map of capabilities to their respective stream / channel.
-    // Each channel would be responsible for containing the offset for that stream and would
-    // only increment / decrement message IDs.
-    // NOTE: since the `P2PStream` currently only supports one capability, we set the
-    // capability with the lowest offset.
-    Ok(shared_with_offsets
-        .first()
-        .ok_or(P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedCapabilities))?
-        .clone())
+    if !shared_with_offsets.iter().any(|cap| cap.is_eth()) {
+        return Err(P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedCapabilities))
+    }
+
+    Ok(shared_with_offsets)
 }
 
 /// This represents only the reserved `p2p` subprotocol messages.
@@ -984,6 +1128,31 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_custom_protocol_offsets() {
+        let ping = Capability::new("ping".into(), 1);
+        let local_protocols =
+            vec![Protocol::eth(EthVersion::Eth67), Protocol::new(ping.clone(), 2)];
+        let peer_capabilities = vec![ping, EthVersion::Eth67.into(), EthVersion::Eth66.into()];
+
+        let shared = set_protocol_offsets(local_protocols, peer_capabilities).unwrap();
+
+        // capabilities are ordered by name, `eth` comes first
+        let eth_offset = MAX_RESERVED_MESSAGE_ID + 1;
+        assert_eq!(
+            shared,
+            vec![
+                SharedCapability::Eth { version: EthVersion::Eth67, offset: eth_offset },
+                SharedCapability::UnknownCapability {
+                    name: "ping".into(),
+                    version: 1,
+                    offset: eth_offset + EthVersion::Eth67.total_messages(),
+                    messages: 2,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_peer_capability_version_too_low() {
         let local_capabilities: Vec<Capability> = vec![EthVersion::Eth67.into()];
@@ -0,0 +1,190 @@
+//! Support for additional RLPx subprotocols that are multiplexed over the same connection as the
+//! `eth` protocol.
+
+use crate::{
+    capability::{Capability, SharedCapability},
+    EthVersion,
+};
+use futures::Stream;
+use reth_primitives::bytes::{Bytes, BytesMut};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// The number of messages that are buffered in each direction between a subprotocol and the
+/// session it's multiplexed over.
+pub const PROTOCOL_CHANNEL_CAPACITY: usize = 256;
+
+/// A RLPx subprotocol and the number of message ids it reserves in the message id space of a
+/// connection.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Protocol {
+    /// The capability the protocol advertises in the `Hello` message.
+    pub cap: Capability,
+    /// The number of message ids reserved by this protocol.
+    pub messages: u8,
+}
+
+impl Protocol {
+    /// Create a new protocol with the given capability and number of messages.
+    pub fn new(cap: Capability, messages: u8) -> Self {
+        Self { cap, messages }
+    }
+
+    /// Returns the `eth` protocol for the given version.
+    pub fn eth(version: EthVersion) -> Self {
+        Self { cap: version.into(), messages: version.total_messages() }
+    }
+}
+
+/// Creates a connected pair of [`ProtocolProxy`] and [`ProtocolConnection`] for a negotiated
+/// capability.
+///
+/// The [`ProtocolProxy`] must be installed on the [`P2PStream`](crate::P2PStream) of the session,
+/// see [`P2PStream::install_protocol`](crate::P2PStream::install_protocol), the
+/// [`ProtocolConnection`] is handed to the subprotocol implementation.
+///
+/// Both directions buffer at most [`PROTOCOL_CHANNEL_CAPACITY`] messages: a protocol that sends
+/// faster than the session writes waits in [`ProtocolConnection::send`], and the session is
+/// disconnected if the protocol doesn't keep up with the messages of the peer.
+pub fn protocol_channel(cap: SharedCapability) -> (ProtocolProxy, ProtocolConnection) {
+    let (to_protocol, from_wire) = mpsc::channel(PROTOCOL_CHANNEL_CAPACITY);
+    let (to_wire, from_protocol) = mpsc::channel(PROTOCOL_CHANNEL_CAPACITY);
+    let proxy = ProtocolProxy { cap: cap.clone(), to_protocol, from_protocol };
+    let conn = ProtocolConnection { cap, from_wire, to_wire };
+    (proxy, conn)
+}
+
+/// The handle a subprotocol uses to exchange messages with the remote peer of a session.
+///
+/// The first byte of all incoming and outgoing messages is the message id _relative_ to the
+/// protocol, so that `0x00` is always the first message of the protocol regardless of the offset
+/// that was negotiated for the session.
+///
+/// The stream ends when the session was closed.
+#[derive(Debug)]
+pub struct ProtocolConnection {
+    /// The negotiated capability of the protocol.
+    cap: SharedCapability,
+    /// Messages received from the remote.
+    from_wire: mpsc::Receiver<BytesMut>,
+    /// Messages to send to the remote.
+    to_wire: mpsc::Sender<Bytes>,
+}
+
+// === impl ProtocolConnection ===
+
+impl ProtocolConnection {
+    /// Returns the negotiated capability of this connection.
+    pub fn shared_capability(&self) -> &SharedCapability {
+        &self.cap
+    }
+
+    /// Queues in a message that's sent to the remote peer, waiting until the session has room
+    /// for it.
+    ///
+    /// Returns the message if the session was already closed.
+    pub async fn send(&self, msg: Bytes) -> Result<(), Bytes> {
+        if msg.is_empty() {
+            // nothing to send, a message must at least contain its id
+            return Ok(())
+        }
+        self.to_wire.send(msg).await.map_err(|err| err.0)
+    }
+
+    /// Queues in a message that's sent to the remote peer if the session has room for it.
+    pub fn try_send(&self, msg: Bytes) -> Result<(), TrySendError<Bytes>> {
+        if msg.is_empty() {
+            return Ok(())
+        }
+        self.to_wire.try_send(msg)
+    }
+}
+
+impl Stream for ProtocolConnection {
+    type Item = BytesMut;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().from_wire.poll_recv(cx)
+    }
+}
+
+/// The [`P2PStream`](crate::P2PStream) side of a [`ProtocolConnection`].
+#[derive(Debug)]
+pub struct ProtocolProxy {
+    /// The negotiated capability of the protocol.
+    cap: SharedCapability,
+    /// Delivers incoming messages to the protocol.
+    to_protocol: mpsc::Sender<BytesMut>,
+    /// Outgoing messages of the protocol.
+    from_protocol: mpsc::Receiver<Bytes>,
+}
+
+// === impl ProtocolProxy ===
+
+impl ProtocolProxy {
+    /// Returns the negotiated capability of the protocol.
+    pub fn shared_capability(&self) -> &SharedCapability {
+        &self.cap
+    }
+
+    /// Delivers a message, with an id relative to the protocol, to the protocol.
+    ///
+    /// Fails if the protocol hasn't consumed the buffered messages yet, or if it dropped its
+    /// connection.
+    pub(crate) fn on_message(&self, msg: BytesMut) -> Result<(), TrySendError<BytesMut>> {
+        self.to_protocol.try_send(msg)
+    }
+
+    /// Polls the next message the protocol wants to send to the remote.
+    pub(crate) fn poll_outgoing(&mut self, cx: &mut Context<'_>) -> Poll<Option<Bytes>> {
+        self.from_protocol.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    fn channel() -> (ProtocolProxy, ProtocolConnection) {
+        let cap = SharedCapability::new("test", 1, 0x20).unwrap();
+        protocol_channel(cap)
+    }
+
+    #[tokio::test]
+    async fn incoming_messages_are_bounded() {
+        let (proxy, mut conn) = channel();
+        for id in 0..PROTOCOL_CHANNEL_CAPACITY {
+            proxy.on_message(BytesMut::from(&[id as u8][..])).unwrap();
+        }
+        let full = proxy.on_message(BytesMut::from(&[0][..]));
+        assert!(matches!(full, Err(TrySendError::Full(_))));
+
+        assert_eq!(conn.next().await.unwrap()[..], [0]);
+        proxy.on_message(BytesMut::from(&[1][..])).unwrap();
+
+        drop(conn);
+        let closed = proxy.on_message(BytesMut::from(&[2][..]));
+        assert!(matches!(closed, Err(TrySendError::Closed(_))));
+    }
+
+    #[tokio::test]
+    async fn outgoing_messages_are_bounded() {
+        let (mut proxy, conn) = channel();
+        for id in 0..PROTOCOL_CHANNEL_CAPACITY {
+            conn.send(Bytes::from(vec![id as u8])).await.unwrap();
+        }
+        assert!(matches!(conn.try_send(Bytes::from_static(&[0])), Err(TrySendError::Full(_))));
+
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(proxy.poll_outgoing(&mut cx), Poll::Ready(Some(Bytes::from_static(&[0]))));
+        conn.try_send(Bytes::from_static(&[1])).unwrap();
+
+        drop(proxy);
+        assert_eq!(conn.send(Bytes::from_static(&[2])).await, Err(Bytes::from_static(&[2])));
+    }
+}
@@ -0,0 +1,102 @@
+//! Decoding fuzzing for `eth` messages received from peers.
+//!
+//! Feeds random bytes into the message decoder and checks that decoding never panics and that the
+//! memory allocated while decoding is bounded by the size of the input, not by the lengths the
+//! input claims.
+use proptest::prelude::*;
+use reth_eth_wire::{EthVersion, ProtocolMessage};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Allocator that keeps track of the peak number of allocated bytes.
+struct PeakAlloc {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = self.current.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            self.peak.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.current.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc { current: AtomicUsize::new(0), peak: AtomicUsize::new(0) };
+
+/// Fixed amount of memory decoding may allocate regardless of the input size.
+const BASE_ALLOCATION: usize = 64 * 1024;
+
+/// Memory decoding may allocate per byte of input, decoded types are larger than their encoding.
+const ALLOCATION_PER_BYTE: usize = 128;
+
+/// Decodes the message and returns the peak number of bytes allocated while decoding.
+fn decode_and_measure(version: EthVersion, msg: &[u8]) -> usize {
+    let baseline = ALLOCATOR.current.load(Ordering::SeqCst);
+    ALLOCATOR.peak.store(baseline, Ordering::SeqCst);
+
+    let _ = ProtocolMessage::decode_message(version, &mut &msg[..]);
+
+    ALLOCATOR.peak.load(Ordering::SeqCst).saturating_sub(baseline)
+}
+
+fn eth_version() -> impl Strategy<Value = EthVersion> {
+    prop_oneof![Just(EthVersion::Eth66), Just(EthVersion::Eth67), Just(EthVersion::Eth68)]
+}
+
+/// A message id followed by a random payload, which is wrapped in a list header half of the time
+/// so that decoding gets past the outer list of most messages.
+fn eth_message() -> impl Strategy<Value = Vec<u8>> {
+    (0u8..=0x10, any::<bool>(), prop::collection::vec(any::<u8>(), 0..2048)).prop_map(
+        |(id, wrap, payload)| {
+            let mut msg = vec![id];
+            if wrap {
+                reth_rlp::Header { list: true, payload_length: payload.len() }.encode(&mut msg);
+            }
+            msg.extend(payload);
+            msg
+        },
+    )
+}
+
+/// Runs all cases in a single test, so that no other test allocates concurrently.
+#[test]
+fn fuzz_decode_message() {
+    let mut runner = proptest::test_runner::TestRunner::new(ProptestConfig::with_cases(10_000));
+    runner
+        .run(&(eth_version(), eth_message()), |(version, msg)| {
+            let allocated = decode_and_measure(version, &msg);
+            let max_allocated = BASE_ALLOCATION + ALLOCATION_PER_BYTE * msg.len();
+            prop_assert!(allocated <= max_allocated, "allocated {allocated} bytes for {msg:x?}");
+            Ok(())
+        })
+        .unwrap();
+
+    // lists that claim to be much larger than the input
+    for id in 0u8..=0x10 {
+        for header in [
+            vec![0xf8, 0xff],
+            vec![0xfb, 0xff, 0xff, 0xff, 0xff],
+            vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            vec![0xbf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        ] {
+            let mut msg = vec![id];
+            msg.extend(header);
+            msg.extend([0xc0; 32]);
+            for version in [EthVersion::Eth66, EthVersion::Eth67, EthVersion::Eth68] {
+                assert!(decode_and_measure(version, &msg) <= BASE_ALLOCATION);
+            }
+        }
+    }
+}
@@ -5,26 +5,43 @@
     attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
 ))]
 
-//! Helpers for resolving the external IP.
+//! Helpers for resolving the external IP and for mapping ports on the gateway of the local network.
 
 use igd::aio::search_gateway;
 use pin_project_lite::pin_project;
 use std::{
     fmt,
     future::{poll_fn, Future},
+    io,
     net::{AddrParseError, IpAddr},
     pin::Pin,
     str::FromStr,
     task::{ready, Context, Poll},
     time::Duration,
 };
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
 use tracing::warn;
 
+mod port_mapping;
+pub use port_mapping::{
+    MappingProtocol, PortMapper, PortMapperFuture, PortMapping, PortMappingError,
+    PortMappingService, UpnpPortMapper, DEFAULT_PORT_MAPPING_LEASE,
+};
+
 #[cfg(feature = "serde")]
 use serde_with::{DeserializeFromStr, SerializeDisplay};
 
+/// The max time to wait for the response of an external IP service.
+const EXTERNAL_IP_SERVICE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The max size of the response of an external IP service.
+const MAX_EXTERNAL_IP_SERVICE_RESPONSE_SIZE: u64 = 4 * 1024;
+
 /// All builtin resolvers.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Default, Hash)]
 #[cfg_attr(feature = "serde", derive(SerializeDisplay, DeserializeFromStr))]
 pub enum NatResolver {
     /// Resolve with any available resolver.
@@ -36,6 +53,9 @@ pub enum NatResolver {
     PublicIp,
     /// Use the given [IpAddr]
     ExternalIp(IpAddr),
+    /// Resolve the external IP via a plain `http://` service that responds with the IP as text,
+    /// for example `http://ifconfig.me/ip`
+    ExternalIpFromService(String),
     /// Resolve nothing
     None,
 }
@@ -47,6 +67,12 @@ impl NatResolver {
     pub async fn external_addr(self) -> Option<IpAddr> {
         external_addr_with(self).await
     }
+
+    /// Returns whether the resolver maps the ports of the node on the gateway of the local network
+    /// via UPnP, see [PortMappingService].
+    pub fn maps_ports(&self) -> bool {
+        matches!(self, NatResolver::Any | NatResolver::Upnp)
+    }
 }
 
 impl fmt::Display for NatResolver {
@@ -56,6 +82,7 @@ impl fmt::Display for NatResolver {
             NatResolver::Upnp => f.write_str("upnp"),
             NatResolver::PublicIp => f.write_str("publicip"),
             NatResolver::ExternalIp(ip) => write!(f, "extip:{ip}"),
+            NatResolver::ExternalIpFromService(url) => write!(f, "extip-service:{url}"),
             NatResolver::None => f.write_str("none"),
         }
     }
@@ -70,6 +97,9 @@ pub enum ParseNatResolverError {
     /// Failed to parse due to unknown variant
     #[error("Unknown Nat Resolver variant: {0}")]
     UnknonwVariant(String),
+    /// Failed to parse the URL of an external IP service
+    #[error("Invalid external IP service URL, expected `http://host[:port][/path]`: {0}")]
+    InvalidServiceUrl(String),
 }
 
 impl FromStr for NatResolver {
@@ -84,6 +114,9 @@ impl FromStr for NatResolver {
             s => {
                 if let Some(ip) = s.strip_prefix("extip:") {
                     NatResolver::ExternalIp(ip.parse::<IpAddr>()?)
+                } else if let Some(url) = s.strip_prefix("extip-service:") {
+                    HttpUrl::parse(url)?;
+                    NatResolver::ExternalIpFromService(url.to_string())
                 } else {
                     return Err(ParseNatResolverError::UnknonwVariant(format!(
                         "Unknown Nat Resolver: {s}"
@@ -145,7 +178,7 @@ impl ResolveNatInterval {
     ///    if the attempt was unsuccessful.
     pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<Option<IpAddr>> {
         if self.interval.poll_tick(cx).is_ready() {
-            self.future = Some(Box::pin(self.resolver.external_addr()));
+            self.future = Some(Box::pin(self.resolver.clone().external_addr()));
         }
 
         if let Some(mut fut) = self.future.take() {
@@ -179,6 +212,7 @@ pub async fn external_addr_with(resolver: NatResolver) -> Option<IpAddr> {
         NatResolver::Upnp => resolve_external_ip_upnp().await,
         NatResolver::PublicIp => resolve_external_ip().await,
         NatResolver::ExternalIp(ip) => Some(ip),
+        NatResolver::ExternalIpFromService(url) => resolve_external_ip_from_service(&url).await,
         NatResolver::None => None,
     }
 }
@@ -248,6 +282,73 @@ async fn resolve_external_ip() -> Option<IpAddr> {
     public_ip::addr().await
 }
 
+async fn resolve_external_ip_from_service(url: &str) -> Option<IpAddr> {
+    match tokio::time::timeout(EXTERNAL_IP_SERVICE_TIMEOUT, query_external_ip_service(url)).await {
+        Ok(Ok(ip)) => Some(ip),
+        Ok(Err(err)) => {
+            warn!(target: "net::nat", %url, %err, "failed to resolve external ip via service");
+            None
+        }
+        Err(_) => {
+            warn!(target: "net::nat", %url, "external ip service timed out");
+            None
+        }
+    }
+}
+
+/// Sends a `GET` request to the service and parses the body of the response as [IpAddr].
+async fn query_external_ip_service(url: &str) -> io::Result<IpAddr> {
+    let url =
+        HttpUrl::parse(url).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let mut stream = TcpStream::connect((url.host, url.port)).await?;
+    let request =
+        format!("GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n", url.path, url.host);
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = Vec::new();
+    stream.take(MAX_EXTERNAL_IP_SERVICE_RESPONSE_SIZE).read_to_end(&mut response).await?;
+    parse_external_ip_service_response(&response)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid response"))
+}
+
+/// Returns the IP in the body of a successful HTTP response.
+fn parse_external_ip_service_response(response: &[u8]) -> Option<IpAddr> {
+    let response = std::str::from_utf8(response).ok()?;
+    let (head, body) = response.split_once("\r\n\r\n")?;
+    let status = head.lines().next()?.split_whitespace().nth(1)?;
+    if status != "200" {
+        return None
+    }
+    body.trim().parse().ok()
+}
+
+/// The parts of a plain `http://` URL.
+#[derive(Debug, PartialEq, Eq)]
+struct HttpUrl<'a> {
+    host: &'a str,
+    port: u16,
+    path: &'a str,
+}
+
+impl<'a> HttpUrl<'a> {
+    fn parse(url: &'a str) -> Result<Self, ParseNatResolverError> {
+        let invalid = || ParseNatResolverError::InvalidServiceUrl(url.to_string());
+        let rest = url.strip_prefix("http://").ok_or_else(invalid)?;
+        let (authority, path) = match rest.find('/') {
+            Some(idx) => rest.split_at(idx),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| invalid())?),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(invalid())
+        }
+        Ok(Self { host, port, path })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -282,5 +383,65 @@ mod tests {
         let s = "extip:0.0.0.0";
         assert_eq!(ip, s.parse().unwrap());
         assert_eq!(ip.to_string().as_str(), s);
+
+        let service = NatResolver::ExternalIpFromService("http://ifconfig.me/ip".to_string());
+        let s = "extip-service:http://ifconfig.me/ip";
+        assert_eq!(service, s.parse().unwrap());
+        assert_eq!(service.to_string().as_str(), s);
+        assert!("extip-service:https://ifconfig.me/ip".parse::<NatResolver>().is_err());
+        assert!("extip-service:http://:80".parse::<NatResolver>().is_err());
+    }
+
+    #[test]
+    fn test_parse_http_url() {
+        assert_eq!(
+            HttpUrl::parse("http://ifconfig.me").unwrap(),
+            HttpUrl { host: "ifconfig.me", port: 80, path: "/" }
+        );
+        assert_eq!(
+            HttpUrl::parse("http://127.0.0.1:8080/ip").unwrap(),
+            HttpUrl { host: "127.0.0.1", port: 8080, path: "/ip" }
+        );
+        assert!(HttpUrl::parse("http://127.0.0.1:port/ip").is_err());
+    }
+
+    #[test]
+    fn test_parse_service_response() {
+        let ip = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        let ok = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\n\r\n1.2.3.4\n";
+        assert_eq!(parse_external_ip_service_response(ok), Some(ip));
+        let not_found = b"HTTP/1.1 404 Not Found\r\n\r\n1.2.3.4";
+        assert_eq!(parse_external_ip_service_response(not_found), None);
+        let html = b"HTTP/1.1 200 OK\r\n\r\n<html></html>";
+        assert_eq!(parse_external_ip_service_response(html), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolver_selection() {
+        let ip = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        assert_eq!(external_addr_with(NatResolver::ExternalIp(ip)).await, Some(ip));
+        assert_eq!(external_addr_with(NatResolver::None).await, None);
+
+        assert!(NatResolver::Any.maps_ports());
+        assert!(NatResolver::Upnp.maps_ports());
+        assert!(!NatResolver::PublicIp.maps_ports());
+        assert!(!NatResolver::ExternalIp(ip).maps_ports());
+        assert!(!NatResolver::None.maps_ports());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_from_service() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/ip", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await.unwrap();
+            assert!(request.starts_with(b"GET /ip HTTP/1.0\r\n"));
+            stream.write_all(b"HTTP/1.0 200 OK\r\n\r\n10.0.0.1\n").await.unwrap();
+        });
+
+        let ip = external_addr_with(NatResolver::ExternalIpFromService(url)).await;
+        assert_eq!(ip, Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))));
     }
 }
@@ -0,0 +1,315 @@
+//! Port mappings on the gateway of the local network, so that nodes behind a NAT are reachable.
+
+use igd::{
+    aio::search_gateway, AddPortError, GetExternalIpError, PortMappingProtocol, SearchError,
+};
+use std::{
+    fmt,
+    future::Future,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+    pin::Pin,
+    time::Duration,
+};
+use tracing::{debug, warn};
+
+/// The default duration of a port mapping lease, the mappings are renewed after half of it.
+pub const DEFAULT_PORT_MAPPING_LEASE: Duration = Duration::from_secs(20 * 60);
+
+/// The description of the port mappings on the gateway.
+const PORT_MAPPING_DESCRIPTION: &str = "reth";
+
+/// The transport protocol of a [PortMapping].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum MappingProtocol {
+    /// TCP, used by RLPx
+    Tcp,
+    /// UDP, used by discovery
+    Udp,
+}
+
+impl fmt::Display for MappingProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MappingProtocol::Tcp => f.write_str("tcp"),
+            MappingProtocol::Udp => f.write_str("udp"),
+        }
+    }
+}
+
+impl From<MappingProtocol> for PortMappingProtocol {
+    fn from(protocol: MappingProtocol) -> Self {
+        match protocol {
+            MappingProtocol::Tcp => PortMappingProtocol::TCP,
+            MappingProtocol::Udp => PortMappingProtocol::UDP,
+        }
+    }
+}
+
+/// Maps a port of the gateway to the same port of the local node.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct PortMapping {
+    /// The transport protocol of the port.
+    pub protocol: MappingProtocol,
+    /// The mapped port.
+    pub port: u16,
+}
+
+impl PortMapping {
+    /// Creates a mapping of the TCP port.
+    pub fn tcp(port: u16) -> Self {
+        Self { protocol: MappingProtocol::Tcp, port }
+    }
+
+    /// Creates a mapping of the UDP port.
+    pub fn udp(port: u16) -> Self {
+        Self { protocol: MappingProtocol::Udp, port }
+    }
+}
+
+/// Errors that can occur when mapping ports.
+#[derive(Debug, thiserror::Error)]
+pub enum PortMappingError {
+    /// No UPnP gateway was found in the local network.
+    #[error("failed to find upnp gateway: {0}")]
+    Search(#[from] SearchError),
+    /// The gateway didn't return its external IP.
+    #[error("failed to resolve external ip via upnp gateway: {0}")]
+    ExternalIp(#[from] GetExternalIpError),
+    /// The gateway rejected the port mapping.
+    #[error("failed to add port mapping: {0}")]
+    AddPort(#[from] AddPortError),
+    /// Failed to determine the local address of the node.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// The future returned by a [PortMapper].
+pub type PortMapperFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<T, PortMappingError>> + Send + 'a>>;
+
+/// Creates port mappings on the gateway of the local network.
+pub trait PortMapper: Send + Sync {
+    /// Returns the external IP of the gateway.
+    fn external_ip(&self) -> PortMapperFuture<'_, IpAddr>;
+
+    /// Maps the port of the gateway to the same port of the local node, for the duration of the
+    /// lease.
+    fn add_port_mapping(&self, mapping: PortMapping, lease: Duration) -> PortMapperFuture<'_, ()>;
+}
+
+/// A [PortMapper] that uses the UPnP gateway of the local network.
+///
+/// The gateway is searched on every call, so that a restarted or replaced gateway is picked up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UpnpPortMapper;
+
+impl PortMapper for UpnpPortMapper {
+    fn external_ip(&self) -> PortMapperFuture<'_, IpAddr> {
+        Box::pin(async move {
+            let gateway = search_gateway(Default::default()).await?;
+            Ok(gateway.get_external_ip().await?)
+        })
+    }
+
+    fn add_port_mapping(&self, mapping: PortMapping, lease: Duration) -> PortMapperFuture<'_, ()> {
+        Box::pin(async move {
+            let gateway = search_gateway(Default::default()).await?;
+            let local_ip = local_ip_towards(gateway.addr)?;
+            gateway
+                .add_port(
+                    mapping.protocol.into(),
+                    mapping.port,
+                    SocketAddr::new(local_ip, mapping.port),
+                    lease.as_secs() as u32,
+                    PORT_MAPPING_DESCRIPTION,
+                )
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+/// Returns the IP of the local interface that is used to reach the given address.
+///
+/// Connecting a UDP socket only selects the route, nothing is sent.
+fn local_ip_towards(addr: SocketAddr) -> io::Result<IpAddr> {
+    let unspecified: IpAddr =
+        if addr.is_ipv4() { Ipv4Addr::UNSPECIFIED.into() } else { Ipv6Addr::UNSPECIFIED.into() };
+    let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0))?;
+    socket.connect(addr)?;
+    Ok(socket.local_addr()?.ip())
+}
+
+/// Keeps the port mappings of the node alive and tracks the external IP of the gateway.
+///
+/// Mappings that fail are logged and retried on the next renewal, so a node without a UPnP gateway
+/// keeps working with the external IP resolved by other means.
+#[derive(Debug)]
+pub struct PortMappingService<M> {
+    mapper: M,
+    mappings: Vec<PortMapping>,
+    lease: Duration,
+    external_ip: Option<IpAddr>,
+}
+
+// === impl PortMappingService ===
+
+impl<M: PortMapper> PortMappingService<M> {
+    /// Creates a new service that maps the given ports with the [DEFAULT_PORT_MAPPING_LEASE].
+    pub fn new(mapper: M, mappings: impl IntoIterator<Item = PortMapping>) -> Self {
+        Self {
+            mapper,
+            mappings: mappings.into_iter().collect(),
+            lease: DEFAULT_PORT_MAPPING_LEASE,
+            external_ip: None,
+        }
+    }
+
+    /// Sets the duration of the port mapping leases.
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    /// Returns the last resolved external IP of the gateway.
+    pub fn external_ip(&self) -> Option<IpAddr> {
+        self.external_ip
+    }
+
+    /// Creates or renews all port mappings and resolves the external IP of the gateway.
+    ///
+    /// Returns the external IP if it changed since the last renewal.
+    pub async fn renew(&mut self) -> Option<IpAddr> {
+        for mapping in self.mappings.iter().copied() {
+            match self.mapper.add_port_mapping(mapping, self.lease).await {
+                Ok(()) => {
+                    debug!(target: "net::nat", port = mapping.port, protocol = %mapping.protocol, "mapped port")
+                }
+                Err(err) => {
+                    warn!(target: "net::nat", ?err, port = mapping.port, protocol = %mapping.protocol, "failed to map port")
+                }
+            }
+        }
+
+        match self.mapper.external_ip().await {
+            Ok(ip) if self.external_ip != Some(ip) => {
+                self.external_ip = Some(ip);
+                Some(ip)
+            }
+            Ok(_) => None,
+            Err(err) => {
+                warn!(target: "net::nat", ?err, "failed to resolve external ip via port mapper");
+                None
+            }
+        }
+    }
+
+    /// Renews the port mappings after every half lease, and calls `on_external_ip` whenever the
+    /// external IP of the gateway changes.
+    ///
+    /// This never returns.
+    pub async fn run(mut self, mut on_external_ip: impl FnMut(IpAddr)) {
+        let mut interval = tokio::time::interval(self.lease / 2);
+        loop {
+            interval.tick().await;
+            if let Some(ip) = self.renew().await {
+                on_external_ip(ip);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// A [PortMapper] that records the mappings and returns the configured external IP.
+    #[derive(Debug, Default, Clone)]
+    struct MockPortMapper {
+        external_ip: Arc<Mutex<Option<IpAddr>>>,
+        mappings: Arc<Mutex<Vec<(PortMapping, Duration)>>>,
+        reject: Arc<Mutex<Option<MappingProtocol>>>,
+    }
+
+    impl PortMapper for MockPortMapper {
+        fn external_ip(&self) -> PortMapperFuture<'_, IpAddr> {
+            let ip = *self.external_ip.lock().unwrap();
+            Box::pin(
+                async move { ip.ok_or_else(|| io::ErrorKind::NotFound.into()).map_err(Into::into) },
+            )
+        }
+
+        fn add_port_mapping(
+            &self,
+            mapping: PortMapping,
+            lease: Duration,
+        ) -> PortMapperFuture<'_, ()> {
+            let res = if *self.reject.lock().unwrap() == Some(mapping.protocol) {
+                Err(io::Error::from(io::ErrorKind::ConnectionRefused).into())
+            } else {
+                self.mappings.lock().unwrap().push((mapping, lease));
+                Ok(())
+            };
+            Box::pin(async move { res })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_renew_mappings() {
+        let mapper = MockPortMapper::default();
+        let ip = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        *mapper.external_ip.lock().unwrap() = Some(ip);
+
+        let lease = Duration::from_secs(60);
+        let mut service = PortMappingService::new(
+            mapper.clone(),
+            [PortMapping::tcp(30303), PortMapping::udp(30304)],
+        )
+        .with_lease(lease);
+
+        assert_eq!(service.renew().await, Some(ip));
+        assert_eq!(service.external_ip(), Some(ip));
+        assert_eq!(
+            *mapper.mappings.lock().unwrap(),
+            vec![(PortMapping::tcp(30303), lease), (PortMapping::udp(30304), lease)]
+        );
+
+        // an unchanged ip is only reported once
+        assert_eq!(service.renew().await, None);
+        assert_eq!(mapper.mappings.lock().unwrap().len(), 4);
+
+        let new_ip = IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8));
+        *mapper.external_ip.lock().unwrap() = Some(new_ip);
+        assert_eq!(service.renew().await, Some(new_ip));
+    }
+
+    #[tokio::test]
+    async fn test_renew_failures() {
+        let mapper = MockPortMapper::default();
+        let mut service = PortMappingService::new(
+            mapper.clone(),
+            [PortMapping::tcp(30303), PortMapping::udp(30303)],
+        );
+
+        // no gateway
+        assert_eq!(service.renew().await, None);
+        assert_eq!(service.external_ip(), None);
+
+        // a rejected mapping doesn't prevent the other mappings
+        let ip = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        *mapper.external_ip.lock().unwrap() = Some(ip);
+        *mapper.reject.lock().unwrap() = Some(MappingProtocol::Tcp);
+        assert_eq!(service.renew().await, Some(ip));
+        assert_eq!(
+            mapper.mappings.lock().unwrap().last().map(|(mapping, _)| *mapping),
+            Some(PortMapping::udp(30303))
+        );
+
+        // the last known ip is kept if the gateway disappears
+        *mapper.external_ip.lock().unwrap() = None;
+        assert_eq!(service.renew().await, None);
+        assert_eq!(service.external_ip(), Some(ip));
+    }
+}
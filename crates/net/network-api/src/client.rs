@@ -0,0 +1,87 @@
+use std::fmt;
+
+/// The client family of a peer, parsed from the client id it announced in its RLPx `Hello`
+/// message, e.g. `Geth/v1.11.6-stable-ea9e62ca/linux-amd64/go1.20.3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClientKind {
+    /// go-ethereum
+    Geth,
+    /// Nethermind
+    Nethermind,
+    /// reth
+    Reth,
+    /// Erigon
+    Erigon,
+    /// Hyperledger Besu
+    Besu,
+    /// Any other, unknown or malformed client id.
+    Other,
+}
+
+impl ClientKind {
+    /// All client families.
+    pub const ALL: [ClientKind; 6] = [
+        ClientKind::Geth,
+        ClientKind::Nethermind,
+        ClientKind::Reth,
+        ClientKind::Erigon,
+        ClientKind::Besu,
+        ClientKind::Other,
+    ];
+
+    /// Parses the client family from the client id of a peer.
+    ///
+    /// The name of the client is the first `/` separated part of the id and is matched case
+    /// insensitively. Ids that don't start with a known name are [ClientKind::Other].
+    pub fn from_client_version(client_version: &str) -> Self {
+        let name = client_version.split('/').next().unwrap_or_default().trim();
+        Self::ALL
+            .into_iter()
+            .find(|kind| name.eq_ignore_ascii_case(kind.as_str()))
+            .unwrap_or(ClientKind::Other)
+    }
+
+    /// Returns the lowercase name of the client family.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClientKind::Geth => "geth",
+            ClientKind::Nethermind => "nethermind",
+            ClientKind::Reth => "reth",
+            ClientKind::Erigon => "erigon",
+            ClientKind::Besu => "besu",
+            ClientKind::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for ClientKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_client_versions() {
+        let client_versions = [
+            ("Geth/v1.11.6-stable-ea9e62ca/linux-amd64/go1.20.3", ClientKind::Geth),
+            ("Geth/my-node/v1.10.26-stable-e5eb32ac/linux-amd64/go1.18.8", ClientKind::Geth),
+            ("Nethermind/v1.17.3+6d8e8d5b/linux-x64/dotnet7.0.4", ClientKind::Nethermind),
+            ("reth/v0.1.0-alpha.1-0b4e2a2e/x86_64-unknown-linux-gnu", ClientKind::Reth),
+            ("erigon/v2.42.0-stable-beb97784/linux-amd64/go1.20.2", ClientKind::Erigon),
+            ("besu/v23.1.2/linux-x86_64/openjdk-java-17", ClientKind::Besu),
+            ("OpenEthereum/v3.3.5-stable/x86_64-linux-musl/rustc1.58.1", ClientKind::Other),
+            ("bitcoind/1.0.0", ClientKind::Other),
+            ("gethereum/v1.0.0", ClientKind::Other),
+            ("", ClientKind::Other),
+            ("/", ClientKind::Other),
+            ("\u{0}\u{ff}geth", ClientKind::Other),
+        ];
+        for (client_version, kind) in client_versions {
+            assert_eq!(ClientKind::from_client_version(client_version), kind, "{client_version}");
+        }
+    }
+}
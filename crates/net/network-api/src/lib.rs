@@ -10,16 +10,19 @@
 //! Provides abstractions for the reth-network crate.
 
 use async_trait::async_trait;
-use reth_eth_wire::DisconnectReason;
+use reth_eth_wire::{capability::Capabilities, DisconnectReason, EthVersion};
 use reth_primitives::{NodeRecord, PeerId, H256, U256};
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+pub use client::ClientKind;
 pub use error::NetworkError;
 pub use reputation::{Reputation, ReputationChangeKind};
 
+/// Client identification of peers
+pub mod client;
 /// Network Error
 pub mod error;
 /// Reputation score
@@ -54,6 +57,7 @@ pub trait PeersInfo: Send + Sync {
 }
 
 /// Provides an API for managing the peers of the network.
+#[async_trait]
 pub trait Peers: PeersInfo {
     /// Adds a peer to the peer set.
     fn add_peer(&self, peer: PeerId, addr: SocketAddr) {
@@ -79,6 +83,14 @@ pub trait Peers: PeersInfo {
 
     /// Send a reputation change for the given peer.
     fn reputation_change(&self, peer_id: PeerId, kind: ReputationChangeKind);
+
+    /// Returns [`PeerInfo`] for all connected peers.
+    async fn get_peers(&self) -> Result<Vec<PeerInfo>, NetworkError>;
+
+    /// Returns [`PeerInfo`] for a given peer.
+    ///
+    /// Returns `None` if there's no active session to the peer.
+    async fn get_peer_by_id(&self, peer_id: PeerId) -> Result<Option<PeerInfo>, NetworkError>;
 }
 
 /// Represents the kind of peer
@@ -91,6 +103,46 @@ pub enum PeerKind {
     Trusted,
 }
 
+/// Info about an active peer session.
+#[derive(Debug, Clone)]
+pub struct PeerInfo {
+    /// Announced capabilities of the peer
+    pub capabilities: Arc<Capabilities>,
+    /// The identifier of the remote peer
+    pub remote_id: PeerId,
+    /// The client's name and version, as announced in the `Hello` message
+    pub client_version: String,
+    /// The address we're connected to
+    pub remote_addr: SocketAddr,
+    /// The direction of the session
+    pub direction: Direction,
+    /// The negotiated eth version of the session
+    pub eth_version: EthVersion,
+}
+
+impl PeerInfo {
+    /// Returns the client family of the peer, parsed from its client version.
+    pub fn client_kind(&self) -> ClientKind {
+        ClientKind::from_client_version(&self.client_version)
+    }
+}
+
+/// The direction of the connection.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    /// Incoming connection.
+    Incoming,
+    /// Outgoing connection to a specific node.
+    Outgoing(PeerId),
+}
+
+impl Direction {
+    /// Returns `true` if this an incoming connection.
+    pub fn is_incoming(&self) -> bool {
+        matches!(self, Direction::Incoming)
+    }
+}
+
 /// The status of the network being ran by the local node.
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -99,9 +151,18 @@ pub struct NetworkStatus {
     pub client_version: String,
     /// The current ethereum protocol version
     pub protocol_version: u64,
+    /// The versions of the `eth` sub-protocol the local node negotiates with its peers.
+    pub eth_protocol_versions: Vec<u64>,
     /// Information about the Ethereum Wire Protocol.
     pub eth_protocol_info: EthProtocolInfo,
 }
+impl NetworkStatus {
+    /// Returns the highest version of the `eth` sub-protocol the local node negotiates, if any.
+    pub fn eth_protocol_version(&self) -> Option<u64> {
+        self.eth_protocol_versions.iter().max().copied()
+    }
+}
+
 /// Information about the Ethereum Wire Protocol (ETH)
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
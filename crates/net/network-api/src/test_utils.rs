@@ -1,9 +1,9 @@
 use crate::{
-    EthProtocolInfo, NetworkError, NetworkInfo, NetworkStatus, PeerKind, Peers, PeersInfo,
-    ReputationChangeKind,
+    EthProtocolInfo, NetworkError, NetworkInfo, NetworkStatus, PeerInfo, PeerKind, Peers,
+    PeersInfo, ReputationChangeKind,
 };
 use async_trait::async_trait;
-use reth_eth_wire::{DisconnectReason, ProtocolVersion};
+use reth_eth_wire::{DisconnectReason, EthVersion, ProtocolVersion};
 use reth_primitives::{rpc::Chain::Mainnet, NodeRecord, PeerId};
 use std::net::{IpAddr, SocketAddr};
 
@@ -23,6 +23,7 @@ impl NetworkInfo for NoopNetwork {
         Ok(NetworkStatus {
             client_version: "reth-test".to_string(),
             protocol_version: ProtocolVersion::V5 as u64,
+            eth_protocol_versions: vec![EthVersion::Eth66 as u64, EthVersion::Eth67 as u64],
             eth_protocol_info: EthProtocolInfo {
                 difficulty: Default::default(),
                 head: Default::default(),
@@ -47,6 +48,7 @@ impl PeersInfo for NoopNetwork {
     }
 }
 
+#[async_trait]
 impl Peers for NoopNetwork {
     fn add_peer_kind(&self, _peer: PeerId, _kind: PeerKind, _addr: SocketAddr) {}
 
@@ -57,4 +59,12 @@ impl Peers for NoopNetwork {
     fn disconnect_peer_with_reason(&self, _peer: PeerId, _reason: DisconnectReason) {}
 
     fn reputation_change(&self, _peer_id: PeerId, _kind: ReputationChangeKind) {}
+
+    async fn get_peers(&self) -> Result<Vec<PeerInfo>, NetworkError> {
+        Ok(Vec::new())
+    }
+
+    async fn get_peer_by_id(&self, _peer_id: PeerId) -> Result<Option<PeerInfo>, NetworkError> {
+        Ok(None)
+    }
 }
@@ -4,12 +4,13 @@ use crate::{
     error::NetworkError,
     import::{BlockImport, ProofOfStakeBlockImport},
     peers::PeersConfig,
+    protocol::{RlpxSubProtocolHandler, RlpxSubProtocols},
     session::SessionsConfig,
     NetworkHandle, NetworkManager,
 };
 use reth_discv4::{Discv4Config, Discv4ConfigBuilder, DEFAULT_DISCOVERY_PORT};
-use reth_primitives::{ChainSpec, ForkFilter, Head, NodeRecord, PeerId, MAINNET};
-use reth_provider::{BlockProvider, HeaderProvider};
+use reth_primitives::{ChainSpec, ForkFilter, Hardfork, Head, NodeRecord, PeerId, MAINNET};
+use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory};
 use reth_tasks::{TaskSpawner, TokioTaskExecutor};
 use secp256k1::{SecretKey, SECP256K1};
 use std::{
@@ -73,6 +74,8 @@ pub struct NetworkConfig<C> {
     pub status: Status,
     /// Sets the hello message for the p2p handshake in RLPx
     pub hello_message: HelloMessage,
+    /// Additional RLPx subprotocols that run alongside `eth`.
+    pub extra_protocols: RlpxSubProtocols,
 }
 
 // === impl NetworkConfig ===
@@ -103,7 +106,7 @@ impl<C> NetworkConfig<C> {
 
 impl<C> NetworkConfig<C>
 where
-    C: BlockProvider + HeaderProvider + Clone + Unpin + 'static,
+    C: BlockProvider + HeaderProvider + StateProviderFactory + Clone + Unpin + 'static,
 {
     /// Starts the networking stack given a [NetworkConfig] and returns a handle to the network.
     pub async fn start_network(self) -> Result<NetworkHandle, NetworkError> {
@@ -141,8 +144,9 @@ pub struct NetworkConfigBuilder {
     sessions_config: Option<SessionsConfig>,
     /// The network's chain spec
     chain_spec: Arc<ChainSpec>,
-    /// The default mode of the network.
-    network_mode: NetworkMode,
+    /// The block importer type.
+    #[serde(skip)]
+    block_import: Option<Box<dyn BlockImport>>,
     /// The executor to use for spawning tasks.
     #[serde(skip)]
     executor: Option<Box<dyn TaskSpawner>>,
@@ -150,6 +154,9 @@ pub struct NetworkConfigBuilder {
     hello_message: Option<HelloMessage>,
     /// Head used to start set for the fork filter and status.
     head: Option<Head>,
+    /// Additional RLPx subprotocols that run alongside `eth`.
+    #[serde(skip)]
+    extra_protocols: RlpxSubProtocols,
 }
 
 // === impl NetworkConfigBuilder ===
@@ -167,10 +174,11 @@ impl NetworkConfigBuilder {
             peers_config: None,
             sessions_config: None,
             chain_spec: Arc::new(MAINNET.clone()),
-            network_mode: Default::default(),
+            block_import: None,
             executor: None,
             hello_message: None,
             head: None,
+            extra_protocols: Default::default(),
         }
     }
 
@@ -212,12 +220,28 @@ impl NetworkConfigBuilder {
         self
     }
 
+    /// Registers an additional RLPx subprotocol that runs alongside `eth` on all sessions.
+    ///
+    /// The capability of the protocol is added to the `Hello` message.
+    pub fn add_rlpx_sub_protocol(mut self, handler: impl RlpxSubProtocolHandler) -> Self {
+        self.extra_protocols.push(handler);
+        self
+    }
+
     /// Set a custom peer config for how peers are handled
     pub fn peer_config(mut self, config: PeersConfig) -> Self {
         self.peers_config = Some(config);
         self
     }
 
+    /// Sets the [`BlockImport`] that handles the blocks announced via `NewBlock` before the merge.
+    ///
+    /// Defaults to the [`ProofOfStakeBlockImport`] which ignores all blocks.
+    pub fn block_import(mut self, block_import: Box<dyn BlockImport>) -> Self {
+        self.block_import = Some(block_import);
+        self
+    }
+
     /// Sets the executor to use for spawning tasks.
     ///
     /// If `None`, then [tokio::spawn] is used for spawning tasks.
@@ -300,10 +324,11 @@ impl NetworkConfigBuilder {
             peers_config,
             sessions_config,
             chain_spec,
-            network_mode,
+            block_import,
             executor,
             hello_message,
             head,
+            extra_protocols,
         } = self;
 
         let listener_addr = listener_addr.unwrap_or_else(|| {
@@ -314,6 +339,13 @@ impl NetworkConfigBuilder {
             hello_message.unwrap_or_else(|| HelloMessage::builder(peer_id).build());
         hello_message.port = listener_addr.port();
 
+        // announce all additional protocols
+        for cap in extra_protocols.capabilities() {
+            if !hello_message.capabilities.contains(&cap) {
+                hello_message.capabilities.push(cap);
+            }
+        }
+
         let head = head.unwrap_or(Head {
             hash: chain_spec.genesis_hash(),
             number: 0,
@@ -325,6 +357,9 @@ impl NetworkConfigBuilder {
         // set the status
         let status = Status::spec_builder(&chain_spec, &head).build();
 
+        // block propagation is only valid before the merge
+        let network_mode = NetworkMode::at_head(&chain_spec, &head);
+
         // set a fork filter based on the chain spec and head
         let fork_filter = chain_spec.fork_filter(head);
 
@@ -352,12 +387,13 @@ impl NetworkConfigBuilder {
             peers_config: peers_config.unwrap_or_default(),
             sessions_config: sessions_config.unwrap_or_default(),
             chain_spec,
-            block_import: Box::<ProofOfStakeBlockImport>::default(),
+            block_import: block_import.unwrap_or_else(|| Box::<ProofOfStakeBlockImport>::default()),
             network_mode,
             executor: executor.unwrap_or_else(|| Box::<TokioTaskExecutor>::default()),
             status,
             hello_message,
             fork_filter,
+            extra_protocols,
         }
     }
 }
@@ -380,6 +416,16 @@ pub enum NetworkMode {
 // === impl NetworkMode ===
 
 impl NetworkMode {
+    /// Returns the mode of the chain at the given head, [`NetworkMode::Stake`] once the merge
+    /// ([`Hardfork::Paris`]) is active.
+    pub fn at_head(chain_spec: &ChainSpec, head: &Head) -> Self {
+        if chain_spec.fork(Hardfork::Paris).active_at_head(head) {
+            NetworkMode::Stake
+        } else {
+            NetworkMode::Work
+        }
+    }
+
     /// Returns true if network has entered proof-of-stake
     pub fn is_stake(&self) -> bool {
         matches!(self, NetworkMode::Stake)
@@ -391,7 +437,7 @@ mod tests {
     use super::*;
     use rand::thread_rng;
     use reth_dns_discovery::tree::LinkEntry;
-    use reth_primitives::{Chain, ForkHash};
+    use reth_primitives::{Chain, ForkHash, U256};
     use reth_provider::test_utils::NoopProvider;
     use std::collections::BTreeMap;
 
@@ -439,4 +485,19 @@ mod tests {
         assert_eq!(status.forkid.hash, genesis_fork_hash);
         assert_eq!(fork_filter.current().hash, genesis_fork_hash);
     }
+
+    #[test]
+    fn test_network_mode_at_head() {
+        let config = builder().build(NoopProvider::default());
+        assert_eq!(config.network_mode, NetworkMode::Work);
+
+        // the terminal total difficulty of mainnet is reached
+        let head = Head {
+            hash: MAINNET.genesis_hash(),
+            total_difficulty: U256::from(58_750_000_000_000_000_000_000_u128),
+            ..Default::default()
+        };
+        let config = builder().set_head(head).build(NoopProvider::default());
+        assert_eq!(config.network_mode, NetworkMode::Stake);
+    }
 }
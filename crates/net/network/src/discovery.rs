@@ -2,10 +2,11 @@
 
 use crate::error::NetworkError;
 use futures::StreamExt;
-use reth_discv4::{DiscoveryUpdate, Discv4, Discv4Config};
+use reth_discv4::{DiscoveryUpdate, Discv4, Discv4Config, NatResolver};
 use reth_dns_discovery::{
     DnsDiscoveryConfig, DnsDiscoveryHandle, DnsDiscoveryService, DnsNodeRecordUpdate, DnsResolver,
 };
+use reth_net_nat::{PortMapping, PortMappingService, UpnpPortMapper};
 use reth_primitives::{ForkId, NodeRecord, PeerId};
 use secp256k1::SecretKey;
 use std::{
@@ -33,6 +34,8 @@ pub struct Discovery {
     discv4_updates: Option<ReceiverStream<DiscoveryUpdate>>,
     /// The handle to the spawned discv4 service
     _discv4_service: Option<JoinHandle<()>>,
+    /// The handle to the spawned task that maps the ports of the node on the UPnP gateway
+    _port_mapping_service: Option<JoinHandle<()>>,
     /// Handler to interact with the DNS discovery service
     _dns_discovery: Option<DnsDiscoveryHandle>,
     /// Updates from the DNS discovery service.
@@ -88,6 +91,7 @@ impl Discovery {
             discv4,
             discv4_updates,
             _discv4_service,
+            _port_mapping_service: None,
             discovered_nodes: Default::default(),
             queued_events: Default::default(),
             _dns_disc_service,
@@ -118,6 +122,27 @@ impl Discovery {
         }
     }
 
+    /// Returns the frontend of the discv4 service, if enabled.
+    pub(crate) fn discv4(&self) -> Option<Discv4> {
+        self.discv4.clone()
+    }
+
+    /// Spawns a task that maps the given RLPx port and the port of the discv4 service on the UPnP
+    /// gateway of the local network, if the [NatResolver] uses UPnP, see
+    /// [NatResolver::maps_ports].
+    ///
+    /// The external IP of the gateway is set as the IP of the local node record.
+    pub(crate) fn spawn_port_mapping(&mut self, nat: &NatResolver, rlpx_port: u16) {
+        let Some(discv4) = self.discv4.clone() else { return };
+        if !nat.maps_ports() {
+            return
+        }
+        let mappings = [PortMapping::tcp(rlpx_port), PortMapping::udp(discv4.local_addr().port())];
+        let service = PortMappingService::new(UpnpPortMapper, mappings);
+        self._port_mapping_service =
+            Some(tokio::spawn(service.run(move |ip| discv4.set_external_ip_addr(ip))));
+    }
+
     /// Returns the id with which the local identifies itself in the network
     pub(crate) fn local_id(&self) -> PeerId {
         self.local_enr.id
@@ -207,6 +232,7 @@ impl Discovery {
             discv4_updates: Default::default(),
             queued_events: Default::default(),
             _discv4_service: Default::default(),
+            _port_mapping_service: None,
             _dns_discovery: None,
             dns_discovery_updates: None,
             _dns_disc_service: None,
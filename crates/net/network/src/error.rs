@@ -78,7 +78,9 @@ impl SessionError for EthStreamError {
             EthStreamError::P2PStreamError(P2PStreamError::HandshakeError(
                 P2PHandshakeError::NonHelloMessageInHandshake,
             )) => true,
-            EthStreamError::EthHandshakeError(err) => !matches!(err, EthHandshakeError::NoResponse),
+            EthStreamError::EthHandshakeError(err) => {
+                !matches!(err, EthHandshakeError::NoResponse | EthHandshakeError::Timeout)
+            }
             _ => false,
         }
     }
@@ -113,7 +115,9 @@ impl SessionError for EthStreamError {
                         P2PStreamError::MismatchedProtocolVersion { .. }
                 )
             }
-            EthStreamError::EthHandshakeError(err) => !matches!(err, EthHandshakeError::NoResponse),
+            EthStreamError::EthHandshakeError(err) => {
+                !matches!(err, EthHandshakeError::NoResponse | EthHandshakeError::Timeout)
+            }
             _ => false,
         }
     }
@@ -141,10 +145,15 @@ impl SessionError for EthStreamError {
         match self {
             // timeouts
             EthStreamError::EthHandshakeError(EthHandshakeError::NoResponse) |
+            EthStreamError::EthHandshakeError(EthHandshakeError::Timeout) |
             EthStreamError::P2PStreamError(P2PStreamError::HandshakeError(
                 P2PHandshakeError::NoResponse,
             )) |
             EthStreamError::P2PStreamError(P2PStreamError::PingTimeout) => Some(BackoffKind::Low),
+            // a local subprotocol that didn't keep up with the peer
+            EthStreamError::P2PStreamError(P2PStreamError::ProtocolBufferFull(_)) => {
+                Some(BackoffKind::Low)
+            }
             // malformed messages
             EthStreamError::P2PStreamError(P2PStreamError::Rlp(_)) |
             EthStreamError::P2PStreamError(P2PStreamError::UnknownReservedMessageId(_)) |
@@ -231,5 +240,9 @@ mod tests {
             P2PHandshakeError::NoResponse,
         ));
         assert_eq!(err.should_backoff(), Some(BackoffKind::Low));
+
+        let err = EthStreamError::EthHandshakeError(EthHandshakeError::Timeout);
+        assert!(!err.is_fatal_protocol_error());
+        assert_eq!(err.should_backoff(), Some(BackoffKind::Low));
     }
 }
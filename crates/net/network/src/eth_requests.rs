@@ -1,23 +1,29 @@
 //! Blocks/Headers management for the p2p network.
 
 use crate::peers::PeersHandle;
-use futures::StreamExt;
+use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
 use reth_eth_wire::{
     BlockBodies, BlockBody, BlockHeaders, GetBlockBodies, GetBlockHeaders, GetNodeData,
     GetReceipts, NodeData, Receipts,
 };
 use reth_interfaces::p2p::error::RequestResult;
-use reth_primitives::{BlockHashOrNumber, Header, HeadersDirection, PeerId, U256};
-use reth_provider::{BlockProvider, HeaderProvider};
+use reth_primitives::{
+    BlockHashOrNumber, BlockNumber, Bytes, Header, HeadersDirection, PeerId, ReceiptWithBloom, U256,
+};
+use reth_provider::{BlockProvider, HeaderProvider, StateProvider, StateProviderFactory};
+use reth_rlp::Encodable;
 use std::{
     borrow::Borrow,
+    collections::HashMap,
     future::Future,
     hash::Hash,
+    iter,
     pin::Pin,
     task::{Context, Poll},
 };
 use tokio::sync::{mpsc::UnboundedReceiver, oneshot};
 use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::trace;
 
 // Limits: <https://github.com/ethereum/go-ethereum/blob/b0d44338bbcefee044f1f635a84487cbbd8f0538/eth/protocols/eth/handler.go#L34-L56>
 
@@ -32,6 +38,16 @@ const MAX_HEADERS_SERVE: usize = 1024;
 /// SOFT_RESPONSE_LIMIT.
 const MAX_BODIES_SERVE: usize = 1024;
 
+/// Maximum number of block receipts to serve.
+///
+/// Used to limit lookups.
+const MAX_RECEIPTS_SERVE: usize = 1024;
+
+/// Maximum number of state trie nodes or contract codes to serve.
+///
+/// Used to limit lookups.
+const MAX_NODE_DATA_SERVE: usize = 1024;
+
 /// Estimated size in bytes of an RLP encoded body.
 // TODO: check 24kb blocksize assumption
 const APPROX_BODY_SIZE: usize = 24 * 1024;
@@ -39,11 +55,19 @@ const APPROX_BODY_SIZE: usize = 24 * 1024;
 /// Maximum size of replies to data retrievals.
 const SOFT_RESPONSE_LIMIT: usize = 2 * 1024 * 1024;
 
-/// Estimated size in bytes of an RLP encoded header.
-const APPROX_HEADER_SIZE: usize = 500;
+/// Default maximum number of requests that are served concurrently.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 16;
+
+/// Default maximum number of requests of a single peer that are served concurrently.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS_PER_PEER: usize = 2;
 
 /// Manages eth related requests on top of the p2p network.
 ///
+/// Requests are served by a bounded pool of blocking tasks, so that database reads don't block the
+/// handler or the sessions. If all workers are busy, incoming requests are not accepted until a
+/// worker finished. Requests of a peer that already has the maximum number of requests in flight
+/// are dropped, so a single peer can't monopolize the database reads.
+///
 /// This can be spawned to another task and is supposed to be run as background service.
 #[must_use = "Manager does nothing unless polled."]
 pub struct EthRequestHandler<C> {
@@ -55,6 +79,14 @@ pub struct EthRequestHandler<C> {
     peers: PeersHandle,
     /// Incoming request from the [NetworkManager](crate::NetworkManager).
     incoming_requests: UnboundedReceiverStream<IncomingEthRequest>,
+    /// Requests that are currently served, resolving to the peer that sent the request.
+    inflight_requests: FuturesUnordered<Pin<Box<dyn Future<Output = PeerId> + Send>>>,
+    /// Number of requests that are currently served, per peer.
+    inflight_requests_per_peer: HashMap<PeerId, usize>,
+    /// Maximum number of requests that are served concurrently.
+    max_concurrent_requests: usize,
+    /// Maximum number of requests of a single peer that are served concurrently.
+    max_concurrent_requests_per_peer: usize,
 }
 
 // === impl EthRequestHandler ===
@@ -65,126 +97,257 @@ impl<C> EthRequestHandler<C> {
         peers: PeersHandle,
         incoming: UnboundedReceiver<IncomingEthRequest>,
     ) -> Self {
-        Self { client, peers, incoming_requests: UnboundedReceiverStream::new(incoming) }
+        Self {
+            client,
+            peers,
+            incoming_requests: UnboundedReceiverStream::new(incoming),
+            inflight_requests: Default::default(),
+            inflight_requests_per_peer: Default::default(),
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            max_concurrent_requests_per_peer: DEFAULT_MAX_CONCURRENT_REQUESTS_PER_PEER,
+        }
+    }
+
+    /// Sets the maximum number of requests that are served concurrently.
+    ///
+    /// Default is [DEFAULT_MAX_CONCURRENT_REQUESTS]
+    pub fn with_max_concurrent_requests(mut self, max: usize) -> Self {
+        self.max_concurrent_requests = max.max(1);
+        self
+    }
+
+    /// Sets the maximum number of requests of a single peer that are served concurrently.
+    ///
+    /// Default is [DEFAULT_MAX_CONCURRENT_REQUESTS_PER_PEER]
+    pub fn with_max_concurrent_requests_per_peer(mut self, max: usize) -> Self {
+        self.max_concurrent_requests_per_peer = max.max(1);
+        self
     }
 }
 
 impl<C> EthRequestHandler<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + StateProviderFactory + Clone + 'static,
 {
-    /// Returns the list of requested heders
-    fn get_headers_response(&self, request: GetBlockHeaders) -> Vec<Header> {
-        let GetBlockHeaders { start_block, limit, skip, direction } = request;
-
-        let mut headers = Vec::new();
-
-        let mut block: BlockHashOrNumber = match start_block {
-            BlockHashOrNumber::Hash(start) => start.into(),
-            BlockHashOrNumber::Number(num) => {
-                if let Some(hash) = self.client.block_hash(U256::from(num)).unwrap_or_default() {
-                    hash.into()
-                } else {
-                    return headers
-                }
-            }
-        };
+    /// Serves the request on a blocking task, or drops it if the peer has too many requests in
+    /// flight.
+    fn on_request(&mut self, request: IncomingEthRequest) {
+        let peer_id = request.peer_id();
+        let inflight = self.inflight_requests_per_peer.entry(peer_id).or_default();
+        if *inflight >= self.max_concurrent_requests_per_peer {
+            trace!(target: "net::eth", ?peer_id, "Dropping request, too many requests in flight");
+            return
+        }
+        *inflight += 1;
 
-        let skip = skip as u64;
-        let mut total_bytes = APPROX_HEADER_SIZE;
-
-        for _ in 0..limit {
-            if let Some(header) = self.client.header_by_hash_or_number(block).unwrap_or_default() {
-                match direction {
-                    HeadersDirection::Rising => {
-                        if let Some(next) = (header.number + 1).checked_add(skip) {
-                            block = next.into()
-                        } else {
-                            break
-                        }
-                    }
-                    HeadersDirection::Falling => {
-                        if skip > 0 {
-                            // prevent under flows for block.number == 0 and `block.number - skip <
-                            // 0`
-                            if let Some(next) =
-                                header.number.checked_sub(1).and_then(|num| num.checked_sub(skip))
-                            {
-                                block = next.into()
-                            } else {
-                                break
-                            }
-                        } else {
-                            block = header.parent_hash.into()
-                        }
-                    }
-                }
-
-                headers.push(header);
-
-                if headers.len() >= MAX_HEADERS_SERVE {
-                    break
-                }
-
-                total_bytes += APPROX_HEADER_SIZE;
-
-                if total_bytes > SOFT_RESPONSE_LIMIT {
-                    break
-                }
-            } else {
-                break
+        let client = self.client.clone();
+        let task = tokio::task::spawn_blocking(move || serve_request(&client, request));
+        self.inflight_requests.push(Box::pin(task.map(move |_| peer_id)));
+    }
+
+    /// Invoked when a request of the peer was served.
+    fn on_request_served(&mut self, peer_id: PeerId) {
+        if let Some(inflight) = self.inflight_requests_per_peer.get_mut(&peer_id) {
+            *inflight -= 1;
+            if *inflight == 0 {
+                self.inflight_requests_per_peer.remove(&peer_id);
             }
         }
+    }
+}
 
-        headers
+/// Reads the response to the request from the database and sends it.
+fn serve_request<C>(client: &C, request: IncomingEthRequest)
+where
+    C: BlockProvider + HeaderProvider + StateProviderFactory,
+{
+    match request {
+        IncomingEthRequest::GetBlockHeaders { request, response, .. } => {
+            let _ = response.send(Ok(BlockHeaders(get_headers_response(client, request))));
+        }
+        IncomingEthRequest::GetBlockBodies { request, response, .. } => {
+            let _ = response.send(Ok(BlockBodies(get_bodies_response(client, request))));
+        }
+        IncomingEthRequest::GetNodeData { request, response, .. } => {
+            let _ = response.send(Ok(NodeData(get_node_data_response(client, request))));
+        }
+        IncomingEthRequest::GetReceipts { request, response, .. } => {
+            let _ = response.send(Ok(Receipts(get_receipts_response(client, request))));
+        }
     }
+}
 
-    fn on_headers_request(
-        &mut self,
-        _peer_id: PeerId,
-        request: GetBlockHeaders,
-        response: oneshot::Sender<RequestResult<BlockHeaders>>,
-    ) {
-        let headers = self.get_headers_response(request);
-        let _ = response.send(Ok(BlockHeaders(headers)));
+/// Returns the list of requested headers.
+///
+/// Headers are returned from the start block in the direction of the request, `skip` blocks apart,
+/// until a header is missing, `limit` or [MAX_HEADERS_SERVE] headers are returned or the response
+/// exceeds the [SOFT_RESPONSE_LIMIT]. Only the canonical chain is traversed: if the start block is
+/// requested by the hash of a block that isn't canonical, only that header is returned.
+fn get_headers_response<C>(client: &C, request: GetBlockHeaders) -> Vec<Header>
+where
+    C: BlockProvider + HeaderProvider,
+{
+    let GetBlockHeaders { start_block, limit, skip, direction } = request;
+    if limit == 0 {
+        return Vec::new()
     }
 
-    fn on_bodies_request(
-        &mut self,
-        _peer_id: PeerId,
-        request: GetBlockBodies,
-        response: oneshot::Sender<RequestResult<BlockBodies>>,
-    ) {
-        let mut bodies = Vec::new();
+    let start = match start_block {
+        BlockHashOrNumber::Number(number) => number,
+        BlockHashOrNumber::Hash(hash) => {
+            let Some(header) = client.header(&hash).unwrap_or_default() else { return Vec::new() };
+            let canonical = client.block_hash(U256::from(header.number)).unwrap_or_default();
+            if canonical != Some(hash) {
+                return vec![header]
+            }
+            header.number
+        }
+    };
+
+    let numbers = header_numbers(start, limit, skip, direction);
+    let mut headers = if skip == 0 {
+        // contiguous headers are read with a single walk over the table
+        let (first, last) = (numbers[0], numbers[numbers.len() - 1]);
+        let mut headers =
+            client.headers_range(first.min(last)..=first.max(last)).unwrap_or_default();
+        if direction == HeadersDirection::Falling {
+            headers.reverse();
+        }
+        headers
+    } else {
+        numbers
+            .iter()
+            .map_while(|number| client.header_by_number(*number).unwrap_or_default())
+            .collect()
+    };
+
+    // the response ends at the first missing header
+    let available = headers.iter().zip(&numbers).take_while(|(header, n)| header.number == **n);
+    let mut len = 0;
+    let mut total_bytes = 0;
+    for (header, _) in available {
+        len += 1;
+        total_bytes += header.length();
+        if total_bytes > SOFT_RESPONSE_LIMIT {
+            break
+        }
+    }
+    headers.truncate(len);
 
-        let mut total_bytes = APPROX_BODY_SIZE;
+    headers
+}
 
-        for hash in request.0 {
-            if let Some(block) = self.client.block_by_hash(hash).unwrap_or_default() {
-                let body = BlockBody {
-                    transactions: block.body,
-                    ommers: block.ommers,
-                    withdrawals: block.withdrawals,
-                };
+/// Returns the numbers of the headers of a request, starting at `start` and `skip` blocks apart in
+/// the direction of the request, capped at [MAX_HEADERS_SERVE] numbers.
+///
+/// The sequence ends early if the next number would overflow or underflow.
+fn header_numbers(
+    start: BlockNumber,
+    limit: u64,
+    skip: u32,
+    direction: HeadersDirection,
+) -> Vec<BlockNumber> {
+    let step = skip as u64 + 1;
+    let limit = limit.min(MAX_HEADERS_SERVE as u64) as usize;
+    iter::successors(Some(start), |number| match direction {
+        HeadersDirection::Rising => number.checked_add(step),
+        HeadersDirection::Falling => number.checked_sub(step),
+    })
+    .take(limit)
+    .collect()
+}
+
+/// Returns the list of requested block bodies
+fn get_bodies_response<C: BlockProvider>(client: &C, request: GetBlockBodies) -> Vec<BlockBody> {
+    let mut bodies = Vec::new();
 
-                bodies.push(body);
+    let mut total_bytes = APPROX_BODY_SIZE;
 
-                total_bytes += APPROX_BODY_SIZE;
+    for hash in request.0 {
+        if let Some(block) = client.block_by_hash(hash).unwrap_or_default() {
+            let body = BlockBody {
+                transactions: block.body,
+                ommers: block.ommers,
+                withdrawals: block.withdrawals,
+            };
 
-                if total_bytes > SOFT_RESPONSE_LIMIT {
-                    break
-                }
+            bodies.push(body);
+
+            total_bytes += APPROX_BODY_SIZE;
+
+            if total_bytes > SOFT_RESPONSE_LIMIT {
+                break
+            }
 
-                if bodies.len() >= MAX_BODIES_SERVE {
-                    break
-                }
-            } else {
+            if bodies.len() >= MAX_BODIES_SERVE {
                 break
             }
+        } else {
+            break
+        }
+    }
+
+    bodies
+}
+
+/// Returns the receipts of the requested blocks.
+///
+/// The receipts of unknown blocks are empty.
+fn get_receipts_response<C: BlockProvider>(
+    client: &C,
+    request: GetReceipts,
+) -> Vec<Vec<ReceiptWithBloom>> {
+    let mut receipts = Vec::new();
+
+    let mut total_bytes = 0;
+
+    for hash in request.0.into_iter().take(MAX_RECEIPTS_SERVE) {
+        let block_receipts = client
+            .receipts_by_block(hash.into())
+            .unwrap_or_default()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|receipt| receipt.with_bloom())
+            .collect::<Vec<_>>();
+
+        total_bytes += block_receipts.length();
+        receipts.push(block_receipts);
+
+        if total_bytes > SOFT_RESPONSE_LIMIT {
+            break
         }
+    }
+
+    receipts
+}
+
+/// Returns the requested node data.
+///
+/// Only contract codes are served, since state trie nodes are not stored by their hash. The entries
+/// of unknown hashes are empty.
+fn get_node_data_response<C: StateProviderFactory>(client: &C, request: GetNodeData) -> Vec<Bytes> {
+    let Ok(state) = client.latest() else { return Vec::new() };
 
-        let _ = response.send(Ok(BlockBodies(bodies)));
+    let mut node_data = Vec::new();
+
+    let mut total_bytes = 0;
+
+    for hash in request.0.into_iter().take(MAX_NODE_DATA_SERVE) {
+        let code = state
+            .bytecode_by_hash(hash)
+            .unwrap_or_default()
+            .map(|code| code.original_bytes().into())
+            .unwrap_or_default();
+
+        total_bytes += code.len();
+        node_data.push(code);
+
+        if total_bytes > SOFT_RESPONSE_LIMIT {
+            break
+        }
     }
+
+    node_data
 }
 
 /// An endless future.
@@ -192,7 +355,7 @@ where
 /// This should be spawned or used as part of `tokio::select!`.
 impl<C> Future for EthRequestHandler<C>
 where
-    C: BlockProvider + HeaderProvider + Unpin,
+    C: BlockProvider + HeaderProvider + StateProviderFactory + Clone + Unpin + 'static,
 {
     type Output = ();
 
@@ -200,19 +363,19 @@ where
         let this = self.get_mut();
 
         loop {
+            while let Poll::Ready(Some(peer_id)) = this.inflight_requests.poll_next_unpin(cx) {
+                this.on_request_served(peer_id);
+            }
+
+            if this.inflight_requests.len() >= this.max_concurrent_requests {
+                // all workers are busy, woken up when a request was served
+                return Poll::Pending
+            }
+
             match this.incoming_requests.poll_next_unpin(cx) {
                 Poll::Pending => return Poll::Pending,
                 Poll::Ready(None) => return Poll::Ready(()),
-                Poll::Ready(Some(incoming)) => match incoming {
-                    IncomingEthRequest::GetBlockHeaders { peer_id, request, response } => {
-                        this.on_headers_request(peer_id, request, response)
-                    }
-                    IncomingEthRequest::GetBlockBodies { peer_id, request, response } => {
-                        this.on_bodies_request(peer_id, request, response)
-                    }
-                    IncomingEthRequest::GetNodeData { .. } => {}
-                    IncomingEthRequest::GetReceipts { .. } => {}
-                },
+                Poll::Ready(Some(incoming)) => this.on_request(incoming),
             }
         }
     }
@@ -271,3 +434,97 @@ pub enum IncomingEthRequest {
         response: oneshot::Sender<RequestResult<Receipts>>,
     },
 }
+
+// === impl IncomingEthRequest ===
+
+impl IncomingEthRequest {
+    /// Returns the peer that sent the request.
+    pub fn peer_id(&self) -> PeerId {
+        match self {
+            IncomingEthRequest::GetBlockHeaders { peer_id, .. } |
+            IncomingEthRequest::GetBlockBodies { peer_id, .. } |
+            IncomingEthRequest::GetNodeData { peer_id, .. } |
+            IncomingEthRequest::GetReceipts { peer_id, .. } => *peer_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Block, H256};
+    use reth_provider::test_utils::MockEthProvider;
+
+    /// Returns a provider with a canonical chain of the given length, and the hashes of the blocks.
+    fn chain(len: u64) -> (MockEthProvider, Vec<H256>) {
+        let client = MockEthProvider::default();
+        let mut hashes = Vec::new();
+        for number in 0..len {
+            let parent_hash = hashes.last().copied().unwrap_or_default();
+            let header = Header { number, parent_hash, ..Default::default() };
+            let hash = header.hash_slow();
+            client.add_block(hash, Block { header, ..Default::default() });
+            hashes.push(hash);
+        }
+        (client, hashes)
+    }
+
+    fn numbers(
+        client: &MockEthProvider,
+        start_block: BlockHashOrNumber,
+        limit: u64,
+        skip: u32,
+        direction: HeadersDirection,
+    ) -> Vec<BlockNumber> {
+        let request = GetBlockHeaders { start_block, limit, skip, direction };
+        get_headers_response(client, request).into_iter().map(|header| header.number).collect()
+    }
+
+    #[test]
+    fn headers_with_skip_and_direction() {
+        let (client, hashes) = chain(10);
+        let rising = HeadersDirection::Rising;
+        let falling = HeadersDirection::Falling;
+
+        assert_eq!(numbers(&client, 0.into(), 3, 0, rising), vec![0, 1, 2]);
+        assert_eq!(numbers(&client, 7.into(), 5, 0, rising), vec![7, 8, 9]);
+        assert_eq!(numbers(&client, 2.into(), 5, 0, falling), vec![2, 1, 0]);
+        assert_eq!(numbers(&client, hashes[5].into(), 3, 1, falling), vec![5, 3, 1]);
+        assert_eq!(numbers(&client, 2.into(), 4, 2, rising), vec![2, 5, 8]);
+        assert_eq!(numbers(&client, 8.into(), 5, 3, falling), vec![8, 4, 0]);
+        assert_eq!(numbers(&client, hashes[9].into(), 0, 0, falling), Vec::<u64>::new());
+        // the response ends at the first missing header
+        assert_eq!(numbers(&client, 12.into(), 5, 0, falling), Vec::<u64>::new());
+        assert_eq!(numbers(&client, H256::random().into(), 5, 0, falling), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn non_canonical_start_block() {
+        let (client, _) = chain(3);
+        let header = Header { number: 1, extra_data: vec![1].into(), ..Default::default() };
+        let hash = header.hash_slow();
+        client.add_header(hash, header);
+
+        let request = GetBlockHeaders {
+            start_block: hash.into(),
+            limit: 3,
+            skip: 0,
+            direction: HeadersDirection::Rising,
+        };
+        let headers = get_headers_response(&client, request);
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].hash_slow(), hash);
+    }
+
+    #[test]
+    fn header_numbers_overflow() {
+        let rising = HeadersDirection::Rising;
+        let falling = HeadersDirection::Falling;
+
+        assert_eq!(header_numbers(u64::MAX - 1, 3, 0, rising), vec![u64::MAX - 1, u64::MAX]);
+        assert_eq!(header_numbers(u64::MAX - 1, 3, u32::MAX, rising), vec![u64::MAX - 1]);
+        let step = u32::MAX as u64 + 1;
+        assert_eq!(header_numbers(step, 3, u32::MAX, falling), vec![step, 0]);
+        assert_eq!(header_numbers(0, u64::MAX, 0, rising).len(), MAX_HEADERS_SERVE);
+    }
+}
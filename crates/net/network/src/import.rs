@@ -1,9 +1,17 @@
-use crate::message::NewBlockMessage;
-use reth_primitives::PeerId;
-use std::task::{Context, Poll};
+use crate::{cache::LruCache, message::NewBlockMessage};
+use reth_interfaces::blockchain_tree::BlockchainTreeEngine;
+use reth_primitives::{PeerId, H256};
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    num::NonZeroUsize,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+use tracing::trace;
 
 /// Abstraction over block import.
-pub trait BlockImport: Send + Sync {
+pub trait BlockImport: Debug + Send + Sync {
     /// Invoked for a received `NewBlock` broadcast message from the peer.
     ///
     /// > When a `NewBlock` announcement message is received from a peer, the client first verifies
@@ -48,6 +56,9 @@ pub enum BlockImportError {
     /// Consensus error
     #[error(transparent)]
     Consensus(#[from] reth_interfaces::consensus::ConsensusError),
+    /// The block failed to be inserted into the blockchain tree
+    #[error(transparent)]
+    Tree(#[from] reth_interfaces::Error),
 }
 
 /// An implementation of `BlockImport` used in Proof-of-Stake consensus that does nothing.
@@ -64,3 +75,266 @@ impl BlockImport for ProofOfStakeBlockImport {
         Poll::Pending
     }
 }
+
+/// The number of blocks a [`TreeBlockImport`] executes at the same time.
+pub const MAX_CONCURRENT_BLOCK_IMPORTS: usize = 4;
+
+/// The number of blocks a [`TreeBlockImport`] queues while all imports are busy, further blocks
+/// are dropped.
+pub const MAX_QUEUED_BLOCK_IMPORTS: usize = 64;
+
+/// The number of hashes of recently received blocks a [`TreeBlockImport`] remembers to drop
+/// duplicate announcements.
+const RECENT_BLOCK_IMPORTS: usize = 1024;
+
+/// A finished import of a block, with the outcome of the insertion into the tree.
+type ImportResult = (PeerId, NewBlockMessage, Result<bool, reth_interfaces::Error>);
+
+/// A [`BlockImport`] for proof-of-work networks that inserts the announced blocks into the
+/// blockchain tree.
+///
+/// The blocks are executed on blocking threads, at most [`MAX_CONCURRENT_BLOCK_IMPORTS`] at a
+/// time, the blocks received in the meantime are queued up to [`MAX_QUEUED_BLOCK_IMPORTS`].
+/// Blocks that were received recently, from the same or another peer, are not imported again.
+///
+/// A block that was inserted is relayed via `NewBlock` to a subset of the peers and then announced
+/// via `NewBlockHashes` to the rest, blocks with an unknown parent are dropped.
+#[derive(Debug)]
+pub struct TreeBlockImport<T> {
+    /// The tree the blocks are inserted into.
+    tree: T,
+    /// The hashes of the recently received blocks.
+    recent: LruCache<H256>,
+    /// The blocks waiting for an import slot.
+    queued: VecDeque<(PeerId, NewBlockMessage)>,
+    /// The number of blocks that are being imported.
+    in_flight: usize,
+    /// Sender half of the channel the finished imports are sent to, it has room for all imports
+    /// in flight.
+    to_results: mpsc::Sender<ImportResult>,
+    /// The finished imports.
+    results: mpsc::Receiver<ImportResult>,
+    /// The outcomes that are ready to be returned.
+    outcomes: VecDeque<BlockImportOutcome>,
+}
+
+impl<T> TreeBlockImport<T> {
+    /// Creates a block import that inserts the blocks into the given tree.
+    pub fn new(tree: T) -> Self {
+        let (to_results, results) = mpsc::channel(MAX_CONCURRENT_BLOCK_IMPORTS);
+        Self {
+            tree,
+            recent: LruCache::new(NonZeroUsize::new(RECENT_BLOCK_IMPORTS).expect("not zero")),
+            queued: VecDeque::new(),
+            in_flight: 0,
+            to_results,
+            results,
+            outcomes: VecDeque::new(),
+        }
+    }
+}
+
+impl<T> TreeBlockImport<T>
+where
+    T: BlockchainTreeEngine + Clone + 'static,
+{
+    /// Starts the imports of the queued blocks while there are free slots.
+    fn spawn_queued(&mut self) {
+        while self.in_flight < MAX_CONCURRENT_BLOCK_IMPORTS {
+            let Some((peer_id, incoming_block)) = self.queued.pop_front() else { return };
+            self.in_flight += 1;
+            let tree = self.tree.clone();
+            let to_results = self.to_results.clone();
+            tokio::task::spawn_blocking(move || {
+                let block = incoming_block.block.block.clone().seal_slow();
+                let result = tree.insert_block(block);
+                // the channel has room for every import in flight
+                let _ = to_results.blocking_send((peer_id, incoming_block, result));
+            });
+        }
+    }
+}
+
+impl<T> BlockImport for TreeBlockImport<T>
+where
+    T: BlockchainTreeEngine + Clone + 'static,
+{
+    fn on_new_block(&mut self, peer_id: PeerId, incoming_block: NewBlockMessage) {
+        if !self.recent.insert(incoming_block.hash) {
+            trace!(target: "net::import", ?peer_id, hash=?incoming_block.hash, "Dropping known block");
+            return
+        }
+        if self.queued.len() >= MAX_QUEUED_BLOCK_IMPORTS {
+            trace!(target: "net::import", ?peer_id, hash=?incoming_block.hash, "Dropping block, import queue is full");
+            return
+        }
+        self.queued.push_back((peer_id, incoming_block));
+        self.spawn_queued();
+    }
+
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<BlockImportOutcome> {
+        loop {
+            if let Some(outcome) = self.outcomes.pop_front() {
+                return Poll::Ready(outcome)
+            }
+
+            let Poll::Ready(Some((peer_id, incoming_block, result))) = self.results.poll_recv(cx)
+            else {
+                return Poll::Pending
+            };
+            self.in_flight -= 1;
+            self.spawn_queued();
+
+            match result {
+                Ok(true) => {
+                    self.outcomes.push_back(BlockImportOutcome {
+                        peer: peer_id,
+                        result: Ok(BlockValidation::ValidHeader { block: incoming_block.clone() }),
+                    });
+                    self.outcomes.push_back(BlockImportOutcome {
+                        peer: peer_id,
+                        result: Ok(BlockValidation::ValidBlock { block: incoming_block }),
+                    });
+                }
+                Ok(false) => {
+                    trace!(
+                        target: "net::import",
+                        ?peer_id,
+                        hash=?incoming_block.hash,
+                        "Dropping block with unknown parent"
+                    );
+                }
+                Err(err) => {
+                    self.outcomes
+                        .push_back(BlockImportOutcome { peer: peer_id, result: Err(err.into()) });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_eth_wire::NewBlock;
+    use reth_interfaces::{consensus::ConsensusError, Error};
+    use reth_primitives::{Block, BlockNumber, Header, SealedBlock, H256};
+    use std::{
+        future::poll_fn,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+    };
+
+    /// A tree that only knows the parent of the blocks with the zero parent hash.
+    #[derive(Debug, Clone)]
+    struct TestTree;
+
+    impl BlockchainTreeEngine for TestTree {
+        fn insert_block(&self, block: SealedBlock) -> Result<bool, Error> {
+            if block.gas_used > block.gas_limit {
+                return Err(ConsensusError::HeaderGasUsedExceedsGasLimit {
+                    gas_used: block.gas_used,
+                    gas_limit: block.gas_limit,
+                }
+                .into())
+            }
+            Ok(block.parent_hash.is_zero())
+        }
+
+        fn update_canonical_hashes(&self, _unwound_to: BlockNumber) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    fn new_block(header: Header) -> NewBlockMessage {
+        let block = Block { header, ..Default::default() };
+        let hash = block.clone().seal_slow().hash();
+        NewBlockMessage { hash, block: Arc::new(NewBlock { block, td: Default::default() }) }
+    }
+
+    #[tokio::test]
+    async fn relays_inserted_blocks() {
+        let mut import = TreeBlockImport::new(TestTree);
+        let peer_id = PeerId::random();
+
+        // dropped without an outcome
+        let orphan = new_block(Header { parent_hash: H256::random(), ..Default::default() });
+        import.on_new_block(peer_id, orphan);
+        let block = new_block(Header { number: 1, ..Default::default() });
+        import.on_new_block(peer_id, block.clone());
+
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert_eq!(outcome.peer, peer_id);
+        assert!(matches!(
+            outcome.result,
+            Ok(BlockValidation::ValidHeader { block: b }) if b.hash == block.hash
+        ));
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert!(matches!(
+            outcome.result,
+            Ok(BlockValidation::ValidBlock { block: b }) if b.hash == block.hash
+        ));
+    }
+
+    /// A tree that accepts all blocks once the lock is released and counts the insertions.
+    #[derive(Debug, Clone, Default)]
+    struct CountingTree {
+        lock: Arc<Mutex<()>>,
+        inserted: Arc<AtomicUsize>,
+    }
+
+    impl BlockchainTreeEngine for CountingTree {
+        fn insert_block(&self, _block: SealedBlock) -> Result<bool, Error> {
+            let _guard = self.lock.lock().unwrap();
+            self.inserted.fetch_add(1, Ordering::SeqCst);
+            Ok(true)
+        }
+
+        fn update_canonical_hashes(&self, _unwound_to: BlockNumber) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn bounds_and_dedupes_imports() {
+        let tree = CountingTree::default();
+        let mut import = TreeBlockImport::new(tree.clone());
+        let peer_id = PeerId::random();
+
+        let guard = tree.lock.lock().unwrap();
+        let blocks = (0..=MAX_CONCURRENT_BLOCK_IMPORTS as u64)
+            .map(|number| new_block(Header { number, ..Default::default() }))
+            .collect::<Vec<_>>();
+        for block in &blocks {
+            import.on_new_block(peer_id, block.clone());
+        }
+        // announced again by another peer
+        import.on_new_block(PeerId::random(), blocks[0].clone());
+        assert_eq!(import.in_flight, MAX_CONCURRENT_BLOCK_IMPORTS);
+        assert_eq!(import.queued.len(), 1);
+        drop(guard);
+
+        for _ in 0..2 * blocks.len() {
+            let outcome = poll_fn(|cx| import.poll(cx)).await;
+            assert!(outcome.result.is_ok());
+        }
+        assert_eq!(tree.inserted.load(Ordering::SeqCst), blocks.len());
+        assert_eq!(import.in_flight, 0);
+        assert!(import.queued.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reports_invalid_blocks() {
+        let mut import = TreeBlockImport::new(TestTree);
+        let peer_id = PeerId::random();
+
+        let invalid = new_block(Header { gas_used: 2, gas_limit: 1, ..Default::default() });
+        import.on_new_block(peer_id, invalid);
+
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert_eq!(outcome.peer, peer_id);
+        assert!(matches!(outcome.result, Err(BlockImportError::Tree(Error::Consensus(_)))));
+    }
+}
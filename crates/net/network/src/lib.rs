@@ -31,7 +31,7 @@
 //!    - `ETH request Task`: is a spawned
 //!      [`EthRequestHandler`](crate::eth_requests::EthRequestHandler) future that:
 //!
-//!        * Responds to incoming ETH related requests: `Headers`, `Bodies`
+//!        * Responds to incoming ETH related requests: `Headers`, `Bodies`, `Receipts`, `NodeData`
 //!
 //!    - `Discovery Task`: is a spawned [`Discv4`](reth_discv4::Discv4) future that handles peer
 //!      discovery and emits new peers to the `Network`
@@ -130,6 +130,7 @@ mod message;
 mod metrics;
 mod network;
 pub mod peers;
+pub mod protocol;
 mod session;
 mod state;
 mod swarm;
@@ -138,10 +139,14 @@ pub mod transactions;
 pub use builder::NetworkBuilder;
 pub use config::{NetworkConfig, NetworkConfigBuilder};
 pub use fetch::FetchClient;
+pub use import::{
+    BlockImport, BlockImportError, BlockImportOutcome, BlockValidation, ProofOfStakeBlockImport,
+    TreeBlockImport, MAX_CONCURRENT_BLOCK_IMPORTS, MAX_QUEUED_BLOCK_IMPORTS,
+};
 pub use manager::{NetworkEvent, NetworkManager};
-pub use message::PeerRequest;
+pub use message::{NewBlockMessage, PeerRequest};
 pub use network::NetworkHandle;
-pub use peers::PeersConfig;
-pub use session::PeerInfo;
+pub use peers::{ConnectionLimits, PeersConfig};
+pub use session::{Direction, PeerInfo, RequestTimeouts, SessionsConfig};
 
 pub use reth_eth_wire::DisconnectReason;
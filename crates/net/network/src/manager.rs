@@ -16,14 +16,14 @@
 //! to the local node. Once a (tcp) connection is established, both peers start to authenticate a [RLPx session](https://github.com/ethereum/devp2p/blob/master/rlpx.md) via a handshake. If the handshake was successful, both peers announce their capabilities and are now ready to exchange sub-protocol messages via the RLPx session.
 
 use crate::{
-    config::NetworkConfig,
+    config::{NetworkConfig, NetworkMode},
     discovery::Discovery,
-    error::NetworkError,
+    error::{NetworkError, SessionError},
     eth_requests::IncomingEthRequest,
     import::{BlockImport, BlockImportOutcome, BlockValidation},
     listener::ConnectionListener,
     message::{NewBlockMessage, PeerMessage, PeerRequest, PeerRequestSender},
-    metrics::NetworkMetrics,
+    metrics::{ClientsPeerMetrics, NetworkMetrics},
     network::{NetworkHandle, NetworkHandleMessage},
     peers::{PeersHandle, PeersManager},
     session::SessionManager,
@@ -39,8 +39,8 @@ use reth_eth_wire::{
     DisconnectReason, EthVersion, Status,
 };
 use reth_net_common::bandwidth_meter::BandwidthMeter;
-use reth_network_api::{EthProtocolInfo, NetworkStatus, ReputationChangeKind};
-use reth_primitives::{NodeRecord, PeerId, H256};
+use reth_network_api::{ClientKind, EthProtocolInfo, NetworkStatus, ReputationChangeKind};
+use reth_primitives::{ForkCondition, Hardfork, NodeRecord, PeerId, H256};
 use reth_provider::BlockProvider;
 use std::{
     net::SocketAddr,
@@ -53,7 +53,7 @@ use std::{
 };
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::UnboundedReceiverStream;
-use tracing::{error, info, trace, warn};
+use tracing::{debug, error, info, trace, warn};
 /// Manages the _entire_ state of the network.
 ///
 /// This is an endless [`Future`] that consistently drives the state of the entire network forward.
@@ -92,6 +92,8 @@ pub struct NetworkManager<C> {
     from_handle_rx: UnboundedReceiverStream<NetworkHandleMessage>,
     /// Handles block imports according to the `eth` protocol.
     block_import: Box<dyn BlockImport>,
+    /// The activation of the merge, after which the network switches to [`NetworkMode::Stake`].
+    merge: ForkCondition,
     /// All listeners for high level network events.
     event_listeners: NetworkEventListeners,
     /// Sender half to send events to the
@@ -107,6 +109,8 @@ pub struct NetworkManager<C> {
     num_active_peers: Arc<AtomicUsize>,
     /// Metrics for the Network
     metrics: NetworkMetrics,
+    /// Metrics for the connected peers of every client family
+    client_metrics: ClientsPeerMetrics,
 }
 
 // === impl NetworkManager ===
@@ -163,6 +167,7 @@ where
             status,
             fork_filter,
             dns_discovery_config,
+            extra_protocols,
             ..
         } = config;
 
@@ -172,6 +177,8 @@ where
         let incoming = ConnectionListener::bind(listener_addr).await?;
         let listener_address = Arc::new(Mutex::new(incoming.local_address()));
 
+        let nat =
+            discovery_v4_config.as_ref().and_then(|config| config.external_ip_resolver.clone());
         discovery_v4_config = discovery_v4_config.map(|mut disc_config| {
             // merge configured boot nodes
             disc_config.bootstrap_nodes.extend(boot_nodes.clone());
@@ -179,9 +186,13 @@ where
             disc_config
         });
 
-        let discovery =
+        let mut discovery =
             Discovery::new(discovery_addr, secret_key, discovery_v4_config, dns_discovery_config)
                 .await?;
+        if let Some(nat) = nat {
+            discovery.spawn_port_mapping(&nat, listener_address.lock().port());
+        }
+        let discv4 = discovery.discv4();
         // need to retrieve the addr here since provided port could be `0`
         let local_peer_id = discovery.local_id();
 
@@ -195,6 +206,7 @@ where
             status,
             hello_message,
             fork_filter,
+            extra_protocols,
             bandwidth_meter.clone(),
         );
 
@@ -219,6 +231,7 @@ where
             network_mode,
             bandwidth_meter,
             Arc::new(AtomicU64::new(chain_spec.chain.id())),
+            discv4,
         );
 
         Ok(Self {
@@ -226,11 +239,13 @@ where
             handle,
             from_handle_rx: UnboundedReceiverStream::new(from_handle_rx),
             block_import,
+            merge: chain_spec.fork(Hardfork::Paris),
             event_listeners: Default::default(),
             to_transactions_manager: None,
             to_eth_request_handler: None,
             num_active_peers,
             metrics: Default::default(),
+            client_metrics: Default::default(),
         })
     }
 
@@ -322,6 +337,12 @@ where
         NetworkStatus {
             client_version: hello_message.client_version,
             protocol_version: hello_message.protocol_version as u64,
+            eth_protocol_versions: hello_message
+                .capabilities
+                .iter()
+                .filter(|capability| capability.name == "eth")
+                .map(|capability| capability.version as u64)
+                .collect(),
             eth_protocol_info: EthProtocolInfo {
                 difficulty: status.total_difficulty,
                 head: status.blockhash,
@@ -427,7 +448,7 @@ where
     /// Enforces [EIP-3675](https://eips.ethereum.org/EIPS/eip-3675#devp2p) consensus rules for the network protocol
     ///
     /// Depending on the mode of the network:
-    ///    - disconnect peer if in POS
+    ///    - penalize and disconnect peer if in POS
     ///    - execute the closure if in POW
     fn within_pow_or_disconnect<F>(&mut self, peer_id: PeerId, only_pow: F)
     where
@@ -435,6 +456,11 @@ where
     {
         // reject message in POS
         if self.handle.mode().is_stake() {
+            trace!(target : "net", ?peer_id, "Received block announcement after the merge");
+            self.swarm
+                .state_mut()
+                .peers_mut()
+                .apply_reputation_change(&peer_id, ReputationChangeKind::BadProtocol);
             // connections to peers which send invalid messages should be terminated
             self.swarm
                 .sessions_mut()
@@ -455,7 +481,7 @@ where
             }
             PeerMessage::NewBlock(block) => {
                 self.within_pow_or_disconnect(peer_id, move |this| {
-                    this.swarm.state_mut().on_new_block(peer_id, block.hash);
+                    this.swarm.state_mut().on_new_block(peer_id, &block);
                     // start block import process
                     this.block_import.on_new_block(peer_id, block);
                 });
@@ -538,6 +564,10 @@ where
                 let _ = tx.send(self.status());
             }
             NetworkHandleMessage::StatusUpdate { head } => {
+                if !self.handle.mode().is_stake() && self.merge.active_at_head(&head) {
+                    info!(target : "net", "Merge reached, disabling block propagation");
+                    self.handle.set_mode(NetworkMode::Stake);
+                }
                 if let Some(transition) = self.swarm.sessions_mut().on_status_update(head) {
                     self.swarm.state_mut().update_fork_id(transition.current);
                 }
@@ -631,6 +661,7 @@ where
                         SwarmEvent::SessionEstablished {
                             peer_id,
                             remote_addr,
+                            client_version,
                             capabilities,
                             version,
                             messages,
@@ -640,10 +671,15 @@ where
                             let total_active =
                                 this.num_active_peers.fetch_add(1, Ordering::Relaxed) + 1;
                             this.metrics.connected_peers.set(total_active as f64);
+                            this.client_metrics
+                                .get(ClientKind::from_client_version(&client_version))
+                                .connected_peers
+                                .increment(1.0);
                             info!(
                                 target : "net",
                                 ?remote_addr,
                                 ?peer_id,
+                                %client_version,
                                 ?total_active,
                                 "Session established"
                             );
@@ -676,14 +712,24 @@ where
                                 .tracked_peers
                                 .set(this.swarm.state().peers().num_known_peers() as f64);
                         }
-                        SwarmEvent::SessionClosed { peer_id, remote_addr, error } => {
+                        SwarmEvent::SessionClosed {
+                            peer_id,
+                            remote_addr,
+                            client_version,
+                            error,
+                        } => {
                             let total_active =
                                 this.num_active_peers.fetch_sub(1, Ordering::Relaxed) - 1;
                             this.metrics.connected_peers.set(total_active as f64);
+                            this.client_metrics
+                                .get(ClientKind::from_client_version(&client_version))
+                                .connected_peers
+                                .decrement(1.0);
                             trace!(
                                 target : "net",
                                 ?remote_addr,
                                 ?peer_id,
+                                %client_version,
                                 ?total_active,
                                 ?error,
                                 "Session disconnected"
@@ -691,6 +737,16 @@ where
 
                             let mut reason = None;
                             if let Some(ref err) = error {
+                                if err.is_fatal_protocol_error() {
+                                    debug!(
+                                        target : "net",
+                                        ?remote_addr,
+                                        ?peer_id,
+                                        %client_version,
+                                        ?err,
+                                        "Banning peer after fatal protocol error"
+                                    );
+                                }
                                 // If the connection was closed due to an error, we report the peer
                                 this.swarm.state_mut().peers_mut().on_active_session_dropped(
                                     &remote_addr,
@@ -735,7 +791,7 @@ where
                                 this.swarm
                                     .state_mut()
                                     .peers_mut()
-                                    .on_incoming_pending_session_gracefully_closed();
+                                    .on_incoming_pending_session_gracefully_closed(remote_addr.ip());
                             }
                             this.metrics.closed_sessions.increment(1);
                             this.metrics
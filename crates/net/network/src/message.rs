@@ -11,7 +11,7 @@ use reth_eth_wire::{
     SharedTransactions, Transactions,
 };
 use reth_interfaces::p2p::error::{RequestError, RequestResult};
-use reth_primitives::{Bytes, Header, PeerId, Receipt, TransactionSigned, H256};
+use reth_primitives::{Bytes, Header, PeerId, ReceiptWithBloom, TransactionSigned, H256};
 use std::{
     fmt,
     sync::Arc,
@@ -102,6 +102,21 @@ pub enum PeerRequest {
     GetReceipts { request: GetReceipts, response: oneshot::Sender<RequestResult<Receipts>> },
 }
 
+/// The type of a [`PeerRequest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum PeerRequestKind {
+    /// `GetBlockHeaders` request.
+    BlockHeaders,
+    /// `GetBlockBodies` request.
+    BlockBodies,
+    /// `GetPooledTransactions` request.
+    PooledTransactions,
+    /// `GetNodeData` request.
+    NodeData,
+    /// `GetReceipts` request.
+    Receipts,
+}
+
 // === impl PeerRequest ===
 
 impl PeerRequest {
@@ -121,6 +136,17 @@ impl PeerRequest {
         };
     }
 
+    /// Returns the type of the request.
+    pub(crate) fn kind(&self) -> PeerRequestKind {
+        match self {
+            PeerRequest::GetBlockHeaders { .. } => PeerRequestKind::BlockHeaders,
+            PeerRequest::GetBlockBodies { .. } => PeerRequestKind::BlockBodies,
+            PeerRequest::GetPooledTransactions { .. } => PeerRequestKind::PooledTransactions,
+            PeerRequest::GetNodeData { .. } => PeerRequestKind::NodeData,
+            PeerRequest::GetReceipts { .. } => PeerRequestKind::Receipts,
+        }
+    }
+
     /// Returns the [`EthMessage`] for this type
     pub fn create_request_message(&self, request_id: u64) -> EthMessage {
         match self {
@@ -199,7 +225,7 @@ pub enum PeerResponseResult {
     BlockBodies(RequestResult<Vec<BlockBody>>),
     PooledTransactions(RequestResult<Vec<TransactionSigned>>),
     NodeData(RequestResult<Vec<Bytes>>),
-    Receipts(RequestResult<Vec<Vec<Receipt>>>),
+    Receipts(RequestResult<Vec<Vec<ReceiptWithBloom>>>),
 }
 
 // === impl PeerResponseResult ===
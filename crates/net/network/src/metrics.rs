@@ -1,5 +1,7 @@
+use crate::message::PeerRequestKind;
 use metrics::{Counter, Gauge};
 use reth_metrics_derive::Metrics;
+use reth_network_api::ClientKind;
 
 /// Metrics for the entire network, handled by NetworkManager
 #[derive(Metrics)]
@@ -33,6 +35,42 @@ pub struct NetworkMetrics {
     pub(crate) invalid_messages_received: Counter,
 }
 
+/// Metrics for the connections rejected for a single reason, labeled with the reason
+#[derive(Metrics)]
+#[metrics(scope = "network.rejected")]
+pub struct RejectedConnectionMetrics {
+    /// Total number of rejected connections
+    pub(crate) connections: Counter,
+}
+
+/// [RejectedConnectionMetrics] for every reason a connection is rejected, handled by the
+/// PeersManager
+#[derive(Debug)]
+pub struct RejectedConnectionsMetrics {
+    /// Inbound connections from a banned ip address
+    pub(crate) ip_banned: RejectedConnectionMetrics,
+    /// Inbound connections exceeding the inbound slots
+    pub(crate) too_many_peers: RejectedConnectionMetrics,
+    /// Inbound connections exceeding the connections of a single ip address
+    pub(crate) ip_limit: RejectedConnectionMetrics,
+    /// Outbound dials postponed by the dial rate limit
+    pub(crate) dial_throttled: RejectedConnectionMetrics,
+}
+
+impl Default for RejectedConnectionsMetrics {
+    fn default() -> Self {
+        let labeled = |reason: &'static str| {
+            RejectedConnectionMetrics::new_with_labels(&[("reason", reason)])
+        };
+        Self {
+            ip_banned: labeled("ip_banned"),
+            too_many_peers: labeled("too_many_peers"),
+            ip_limit: labeled("ip_limit"),
+            dial_throttled: labeled("dial_throttled"),
+        }
+    }
+}
+
 /// Metrics for the TransactionsManager
 #[derive(Metrics)]
 #[metrics(scope = "network")]
@@ -40,3 +78,107 @@ pub struct TransactionsManagerMetrics {
     /// Total number of propagated transactions
     pub(crate) propagated_transactions: Counter,
 }
+
+/// Metrics for the requests of a single type sent to peers, handled by each active session
+#[derive(Metrics)]
+#[metrics(scope = "network.requests")]
+pub struct EthRequestMetrics {
+    /// Total number of requests sent to peers
+    pub(crate) requests: Counter,
+
+    /// Total number of requests that timed out before the peer responded
+    pub(crate) timeouts: Counter,
+}
+
+/// [EthRequestMetrics] for every request type, labeled with the type of the request
+#[derive(Debug)]
+pub struct EthRequestsMetrics {
+    block_headers: EthRequestMetrics,
+    block_bodies: EthRequestMetrics,
+    pooled_transactions: EthRequestMetrics,
+    node_data: EthRequestMetrics,
+    receipts: EthRequestMetrics,
+}
+
+impl EthRequestsMetrics {
+    /// Returns the metrics of the given request type.
+    pub(crate) fn get(&self, kind: PeerRequestKind) -> &EthRequestMetrics {
+        match kind {
+            PeerRequestKind::BlockHeaders => &self.block_headers,
+            PeerRequestKind::BlockBodies => &self.block_bodies,
+            PeerRequestKind::PooledTransactions => &self.pooled_transactions,
+            PeerRequestKind::NodeData => &self.node_data,
+            PeerRequestKind::Receipts => &self.receipts,
+        }
+    }
+}
+
+impl Default for EthRequestsMetrics {
+    fn default() -> Self {
+        let labeled =
+            |request: &'static str| EthRequestMetrics::new_with_labels(&[("request", request)]);
+        Self {
+            block_headers: labeled("block_headers"),
+            block_bodies: labeled("block_bodies"),
+            pooled_transactions: labeled("pooled_transactions"),
+            node_data: labeled("node_data"),
+            receipts: labeled("receipts"),
+        }
+    }
+}
+
+/// Metrics for the connected peers of a single client family, labeled with the client family
+#[derive(Metrics)]
+#[metrics(scope = "network.clients")]
+pub struct ClientPeerMetrics {
+    /// Number of currently connected peers that run the client
+    pub(crate) connected_peers: Gauge,
+}
+
+/// [ClientPeerMetrics] for every [ClientKind], labeled with the name of the client family
+#[derive(Debug)]
+pub struct ClientsPeerMetrics {
+    geth: ClientPeerMetrics,
+    nethermind: ClientPeerMetrics,
+    reth: ClientPeerMetrics,
+    erigon: ClientPeerMetrics,
+    besu: ClientPeerMetrics,
+    other: ClientPeerMetrics,
+}
+
+impl ClientsPeerMetrics {
+    /// Returns the metrics of the given client family.
+    pub(crate) fn get(&self, kind: ClientKind) -> &ClientPeerMetrics {
+        match kind {
+            ClientKind::Geth => &self.geth,
+            ClientKind::Nethermind => &self.nethermind,
+            ClientKind::Reth => &self.reth,
+            ClientKind::Erigon => &self.erigon,
+            ClientKind::Besu => &self.besu,
+            ClientKind::Other => &self.other,
+        }
+    }
+}
+
+impl Default for ClientsPeerMetrics {
+    fn default() -> Self {
+        let labeled =
+            |kind: ClientKind| ClientPeerMetrics::new_with_labels(&[("client", kind.as_str())]);
+        Self {
+            geth: labeled(ClientKind::Geth),
+            nethermind: labeled(ClientKind::Nethermind),
+            reth: labeled(ClientKind::Reth),
+            erigon: labeled(ClientKind::Erigon),
+            besu: labeled(ClientKind::Besu),
+            other: labeled(ClientKind::Other),
+        }
+    }
+}
+
+/// Metrics for the messages received from a single peer, labeled with the id of the peer
+#[derive(Metrics)]
+#[metrics(scope = "network.peer")]
+pub struct PeerMessageMetrics {
+    /// Total number of messages that were too large, couldn't be decoded or weren't allowed
+    pub(crate) malformed_messages: Counter,
+}
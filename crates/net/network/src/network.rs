@@ -4,6 +4,7 @@ use crate::{
 };
 use async_trait::async_trait;
 use parking_lot::Mutex;
+use reth_discv4::Discv4;
 use reth_eth_wire::{DisconnectReason, NewBlock, NewPooledTransactionHashes, SharedTransactions};
 use reth_interfaces::{
     p2p::headers::client::StatusUpdater,
@@ -21,7 +22,7 @@ use std::{
         Arc,
     },
 };
-use tokio::sync::{mpsc, mpsc::UnboundedSender, oneshot};
+use tokio::sync::{mpsc, mpsc::UnboundedSender, oneshot, watch};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 /// A _shareable_ network frontend. Used to interact with the network.
@@ -47,6 +48,7 @@ impl NetworkHandle {
         network_mode: NetworkMode,
         bandwidth_meter: BandwidthMeter,
         chain_id: Arc<AtomicU64>,
+        discv4: Option<Discv4>,
     ) -> Self {
         let inner = NetworkInner {
             num_active_peers,
@@ -54,10 +56,12 @@ impl NetworkHandle {
             listener_address,
             local_peer_id,
             peers,
-            network_mode,
+            is_stake: AtomicBool::new(network_mode.is_stake()),
             bandwidth_meter,
             is_syncing: Arc::new(Default::default()),
+            sync_state: watch::channel(SyncState::Idle).0,
             chain_id,
+            discv4,
         };
         Self { inner: Arc::new(inner) }
     }
@@ -94,28 +98,18 @@ impl NetworkHandle {
         rx.await
     }
 
-    /// Returns [`PeerInfo`] for all connected peers
-    pub async fn get_peers(&self) -> Result<Vec<PeerInfo>, oneshot::error::RecvError> {
-        let (tx, rx) = oneshot::channel();
-        let _ = self.manager().send(NetworkHandleMessage::GetPeerInfo(tx));
-        rx.await
-    }
-
-    /// Returns [`PeerInfo`] for a given peer.
-    ///
-    /// Returns `None` if there's no active session to the peer.
-    pub async fn get_peer_by_id(
-        &self,
-        peer_id: PeerId,
-    ) -> Result<Option<PeerInfo>, oneshot::error::RecvError> {
-        let (tx, rx) = oneshot::channel();
-        let _ = self.manager().send(NetworkHandleMessage::GetPeerInfoById(peer_id, tx));
-        rx.await
+    /// Returns the mode of the network, either pow, or pos
+    pub fn mode(&self) -> NetworkMode {
+        if self.inner.is_stake.load(Ordering::Relaxed) {
+            NetworkMode::Stake
+        } else {
+            NetworkMode::Work
+        }
     }
 
-    /// Returns the mode of the network, either pow, or pos
-    pub fn mode(&self) -> &NetworkMode {
-        &self.inner.network_mode
+    /// Sets the mode of the network, invoked once the chain transitioned to proof-of-stake.
+    pub(crate) fn set_mode(&self, mode: NetworkMode) {
+        self.inner.is_stake.store(mode.is_stake(), Ordering::Relaxed);
     }
 
     /// Sends a [`NetworkHandleMessage`] to the manager
@@ -179,10 +173,18 @@ impl PeersInfo for NetworkHandle {
     fn local_node_record(&self) -> NodeRecord {
         let id = *self.peer_id();
         let socket_addr = *self.inner.listener_address.lock();
-        NodeRecord::new(socket_addr, id)
+        let mut record = NodeRecord::new(socket_addr, id);
+        if let Some(discv4) = &self.inner.discv4 {
+            // the discovery service tracks the external ip of the node
+            let discv4_record = discv4.node_record();
+            record.address = discv4_record.address;
+            record.udp_port = discv4_record.udp_port;
+        }
+        record
     }
 }
 
+#[async_trait]
 impl Peers for NetworkHandle {
     /// Sends a message to the [`NetworkManager`](crate::NetworkManager) to add a peer to the known
     /// set, with the given kind.
@@ -212,6 +214,18 @@ impl Peers for NetworkHandle {
     fn reputation_change(&self, peer_id: PeerId, kind: ReputationChangeKind) {
         self.send_message(NetworkHandleMessage::ReputationChange(peer_id, kind));
     }
+
+    async fn get_peers(&self) -> Result<Vec<PeerInfo>, NetworkError> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.manager().send(NetworkHandleMessage::GetPeerInfo(tx));
+        rx.await.map_err(Into::into)
+    }
+
+    async fn get_peer_by_id(&self, peer_id: PeerId) -> Result<Option<PeerInfo>, NetworkError> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.manager().send(NetworkHandleMessage::GetPeerInfoById(peer_id, tx));
+        rx.await.map_err(Into::into)
+    }
 }
 
 #[async_trait]
@@ -242,12 +256,17 @@ impl SyncStateProvider for NetworkHandle {
     fn is_syncing(&self) -> bool {
         self.inner.is_syncing.load(Ordering::Relaxed)
     }
+
+    fn subscribe_sync_state(&self) -> watch::Receiver<SyncState> {
+        self.inner.sync_state.subscribe()
+    }
 }
 
 impl SyncStateUpdater for NetworkHandle {
     fn update_sync_state(&self, state: SyncState) {
         let is_syncing = state.is_syncing();
-        self.inner.is_syncing.store(is_syncing, Ordering::Relaxed)
+        self.inner.is_syncing.store(is_syncing, Ordering::Relaxed);
+        self.inner.sync_state.send_replace(state);
     }
 }
 
@@ -263,14 +282,18 @@ struct NetworkInner {
     local_peer_id: PeerId,
     /// Access to the all the nodes.
     peers: PeersHandle,
-    /// The mode of the network
-    network_mode: NetworkMode,
+    /// Whether the network is in proof-of-stake mode, see [`NetworkMode`]
+    is_stake: AtomicBool,
     /// Used to measure inbound & outbound bandwidth across network streams (currently unused)
     bandwidth_meter: BandwidthMeter,
     /// Represents if the network is currently syncing.
     is_syncing: Arc<AtomicBool>,
+    /// The current sync state, observed by sync state subscriptions.
+    sync_state: watch::Sender<SyncState>,
     /// The chain id
     chain_id: Arc<AtomicU64>,
+    /// The frontend of the discovery service, if enabled.
+    discv4: Option<Discv4>,
 }
 
 /// Internal messages that can be passed to the  [`NetworkManager`](crate::NetworkManager).
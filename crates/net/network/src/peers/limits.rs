@@ -0,0 +1,168 @@
+//! Limits on the connections of a single ip address and on the rate of outbound dials.
+
+use reth_primitives::PeerId;
+use std::{
+    collections::{HashMap, VecDeque},
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+/// Default maximum number of inbound connections from a single ip address.
+const DEFAULT_MAX_INBOUND_PER_IP: usize = 3;
+
+/// Limits that protect the connection slots against many connections from a single host, see
+/// [`PeersConfig`](crate::PeersConfig).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConnectionLimits {
+    /// Maximum allowed inbound connections, pending or active, from a single ip address.
+    pub max_inbound_per_ip: usize,
+    /// Maximum number of outbound connections that are dialed at once.
+    pub dial_burst: u32,
+    /// Number of outbound connections that are dialed per second once the burst is used up.
+    pub dials_per_second: u32,
+    /// Number of failed inbound handshakes within the window after which the ip address is
+    /// banned.
+    pub max_handshake_failures: usize,
+    /// The window in which failed inbound handshakes of an ip address are counted.
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub handshake_failure_window: Duration,
+}
+
+impl Default for ConnectionLimits {
+    fn default() -> Self {
+        Self {
+            max_inbound_per_ip: DEFAULT_MAX_INBOUND_PER_IP,
+            dial_burst: 32,
+            dials_per_second: 10,
+            max_handshake_failures: 5,
+            handshake_failure_window: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Tracks the pending and active inbound connections of every ip address.
+#[derive(Debug, Default)]
+pub(crate) struct InboundIpConnections {
+    /// Number of pending and active inbound connections per ip address.
+    per_ip: HashMap<IpAddr, usize>,
+    /// The ip addresses of the active inbound sessions.
+    sessions: HashMap<PeerId, IpAddr>,
+}
+
+impl InboundIpConnections {
+    /// Returns the number of inbound connections from the ip address.
+    pub(crate) fn num_connections(&self, ip: &IpAddr) -> usize {
+        self.per_ip.get(ip).copied().unwrap_or_default()
+    }
+
+    /// Tracks a new pending inbound connection.
+    pub(crate) fn inc(&mut self, ip: IpAddr) {
+        *self.per_ip.entry(ip).or_default() += 1;
+    }
+
+    /// Releases an inbound connection of the ip address.
+    pub(crate) fn decr(&mut self, ip: &IpAddr) {
+        if let Some(num) = self.per_ip.get_mut(ip) {
+            *num -= 1;
+            if *num == 0 {
+                self.per_ip.remove(ip);
+            }
+        }
+    }
+
+    /// Called when a pending inbound connection became an active session of the peer.
+    pub(crate) fn on_session_active(&mut self, peer_id: PeerId, ip: IpAddr) {
+        if let Some(previous) = self.sessions.insert(peer_id, ip) {
+            self.decr(&previous);
+        }
+    }
+
+    /// Called when a session of the peer was closed, releases its connection if it was inbound.
+    pub(crate) fn on_session_closed(&mut self, peer_id: &PeerId) {
+        if let Some(ip) = self.sessions.remove(peer_id) {
+            self.decr(&ip);
+        }
+    }
+}
+
+/// A token bucket that limits the rate of outbound dials.
+#[derive(Debug)]
+pub(crate) struct DialThrottle {
+    /// Maximum number of tokens.
+    capacity: f64,
+    /// Number of tokens added per second.
+    per_second: f64,
+    /// Currently available tokens.
+    tokens: f64,
+    /// When tokens were last added.
+    last_refill: Instant,
+}
+
+impl DialThrottle {
+    /// Creates a full bucket.
+    pub(crate) fn new(burst: u32, per_second: u32) -> Self {
+        let capacity = burst.max(1) as f64;
+        Self {
+            capacity,
+            per_second: per_second as f64,
+            tokens: capacity,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Returns `true` if a dial is allowed at the given time.
+    pub(crate) fn has_token(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.per_second).min(self.capacity);
+        self.last_refill = now;
+        self.tokens >= 1.0
+    }
+
+    /// Uses a token for a dial, see [`Self::has_token`].
+    pub(crate) fn consume(&mut self) {
+        self.tokens -= 1.0;
+    }
+}
+
+/// Tracks the failed inbound handshakes of every ip address.
+#[derive(Debug)]
+pub(crate) struct HandshakeFailures {
+    /// Number of failures within the window after which the ip address is banned.
+    max_failures: usize,
+    /// The window in which failures are counted.
+    window: Duration,
+    /// The times of the failures within the window per ip address.
+    failures: HashMap<IpAddr, VecDeque<Instant>>,
+}
+
+impl HandshakeFailures {
+    /// Creates a tracker that bans after `max_failures` within the window.
+    pub(crate) fn new(max_failures: usize, window: Duration) -> Self {
+        Self { max_failures, window, failures: Default::default() }
+    }
+
+    /// Records a failed handshake, returns `true` if the ip address failed too often within the
+    /// window and should be banned.
+    pub(crate) fn on_failure(&mut self, ip: IpAddr, now: Instant) -> bool {
+        let window = self.window;
+        let failures = self.failures.entry(ip).or_default();
+        while failures.front().map_or(false, |at| now.saturating_duration_since(*at) > window) {
+            failures.pop_front();
+        }
+        failures.push_back(now);
+        if failures.len() >= self.max_failures {
+            self.failures.remove(&ip);
+            return true
+        }
+        false
+    }
+
+    /// Removes the ip addresses without failures within the window.
+    pub(crate) fn evict(&mut self, now: Instant) {
+        let window = self.window;
+        self.failures.retain(|_, failures| {
+            failures.back().map_or(false, |at| now.saturating_duration_since(*at) <= window)
+        });
+    }
+}
@@ -1,6 +1,8 @@
 use crate::{
     error::{BackoffKind, SessionError},
+    metrics::RejectedConnectionsMetrics,
     peers::{
+        limits::{ConnectionLimits, DialThrottle, HandshakeFailures, InboundIpConnections},
         reputation::{is_banned_reputation, BACKOFF_REPUTATION_CHANGE, DEFAULT_REPUTATION},
         ReputationChangeWeights, DEFAULT_MAX_PEERS_INBOUND, DEFAULT_MAX_PEERS_OUTBOUND,
     },
@@ -106,6 +108,18 @@ pub(crate) struct PeersManager {
     backoff_durations: PeerBackoffDurations,
     /// If non-trusted peers should be connected to
     connect_trusted_nodes_only: bool,
+    /// Maximum allowed inbound connections from a single ip address.
+    max_inbound_per_ip: usize,
+    /// Tracks the inbound connections of every ip address.
+    inbound_ips: InboundIpConnections,
+    /// Limits the rate of outbound dials.
+    dial_throttle: DialThrottle,
+    /// Whether the next dial is held back by the dial throttle, so it is only counted once.
+    dial_throttled: bool,
+    /// Tracks failed inbound handshakes to ban ip addresses that fail repeatedly.
+    handshake_failures: HandshakeFailures,
+    /// Metrics for rejected connections.
+    rejected_connections: RejectedConnectionsMetrics,
 }
 
 impl PeersManager {
@@ -121,6 +135,7 @@ impl PeersManager {
             trusted_nodes,
             connect_trusted_nodes_only,
             basic_nodes,
+            connection_limits,
             ..
         } = config;
         let (manager_tx, handle_rx) = mpsc::unbounded_channel();
@@ -155,6 +170,18 @@ impl PeersManager {
             ban_duration,
             backoff_durations,
             connect_trusted_nodes_only,
+            max_inbound_per_ip: connection_limits.max_inbound_per_ip,
+            inbound_ips: Default::default(),
+            dial_throttle: DialThrottle::new(
+                connection_limits.dial_burst,
+                connection_limits.dials_per_second,
+            ),
+            dial_throttled: false,
+            handshake_failures: HandshakeFailures::new(
+                connection_limits.max_handshake_failures,
+                connection_limits.handshake_failure_window,
+            ),
+            rejected_connections: Default::default(),
         }
     }
 
@@ -177,31 +204,48 @@ impl PeersManager {
     /// Invoked when a new _incoming_ tcp connection is accepted.
     ///
     /// returns an error if the inbound ip address is on the ban list or
-    /// we have reached our limit for max inbound connections
+    /// we have reached our limit for max inbound connections, in total or from this ip address.
+    /// The limit per ip address does not apply to the addresses of trusted peers.
     pub(crate) fn on_incoming_pending_session(
         &mut self,
         addr: IpAddr,
     ) -> Result<(), InboundConnectionError> {
         if self.ban_list.is_banned_ip(&addr) {
+            self.rejected_connections.ip_banned.connections.increment(1);
             return Err(InboundConnectionError::IpBanned)
         }
         if !self.connection_info.has_in_capacity() {
+            self.rejected_connections.too_many_peers.connections.increment(1);
             return Err(InboundConnectionError::ExceedsLimit(self.connection_info.max_inbound))
         }
+        if self.inbound_ips.num_connections(&addr) >= self.max_inbound_per_ip &&
+            !self.is_trusted_ip(&addr)
+        {
+            self.rejected_connections.ip_limit.connections.increment(1);
+            return Err(InboundConnectionError::ExceedsIpLimit(self.max_inbound_per_ip))
+        }
         // keep track of new connection
         self.connection_info.inc_in();
+        self.inbound_ips.inc(addr);
         Ok(())
     }
 
+    /// Returns whether a trusted peer is known at the ip address.
+    fn is_trusted_ip(&self, addr: &IpAddr) -> bool {
+        self.peers.values().any(|peer| peer.is_trusted() && peer.addr.ip() == *addr)
+    }
+
     /// Invoked when a previous call to [Self::on_incoming_pending_session] succeeded but it was
     /// rejected.
-    pub(crate) fn on_incoming_pending_session_rejected_internally(&mut self) {
+    pub(crate) fn on_incoming_pending_session_rejected_internally(&mut self, addr: IpAddr) {
         self.connection_info.decr_in();
+        self.inbound_ips.decr(&addr);
     }
 
     /// Invoked when a pending session was closed.
-    pub(crate) fn on_incoming_pending_session_gracefully_closed(&mut self) {
-        self.connection_info.decr_in()
+    pub(crate) fn on_incoming_pending_session_gracefully_closed(&mut self, addr: IpAddr) {
+        self.connection_info.decr_in();
+        self.inbound_ips.decr(&addr)
     }
 
     /// Returns the number of currently active inbound connections.
@@ -229,9 +273,13 @@ impl PeersManager {
                 self.queued_actions
                     .push_back(PeerAction::DiscoveryBanIp { ip_addr: remote_addr.ip() })
             }
+        } else if self.handshake_failures.on_failure(remote_addr.ip(), std::time::Instant::now()) {
+            debug!(target: "net::peers", ?remote_addr, "Banning ip after repeated handshake failures");
+            self.ban_ip(remote_addr.ip());
         }
 
-        self.connection_info.decr_in()
+        self.connection_info.decr_in();
+        self.inbound_ips.decr(&remote_addr.ip())
     }
 
     /// Called when a new _incoming_ active session was established to the given peer.
@@ -241,6 +289,9 @@ impl PeersManager {
     /// If the reputation of the peer is below the `BANNED_REPUTATION` threshold, a disconnect will
     /// be scheduled.
     pub(crate) fn on_active_inbound_session(&mut self, peer_id: PeerId, addr: SocketAddr) {
+        // the connection of the ip address is released once the session is closed
+        self.inbound_ips.on_session_active(peer_id, addr.ip());
+
         // we only need to check the peer id here as the ip address will have been checked at
         // on_inbound_pending_session
         if self.ban_list.is_banned_peer(&peer_id) {
@@ -335,6 +386,8 @@ impl PeersManager {
 
     /// Gracefully disconnected an active session
     pub(crate) fn on_active_session_gracefully_closed(&mut self, peer_id: PeerId) {
+        self.inbound_ips.on_session_closed(&peer_id);
+
         match self.peers.entry(peer_id) {
             Entry::Occupied(mut entry) => {
                 self.connection_info.decr_state(entry.get().state);
@@ -368,6 +421,7 @@ impl PeersManager {
         peer_id: &PeerId,
         err: &EthStreamError,
     ) {
+        self.inbound_ips.on_session_closed(peer_id);
         self.on_connection_failure(remote_addr, peer_id, err, ReputationChangeKind::Dropped)
     }
 
@@ -446,9 +500,12 @@ impl PeersManager {
     ///
     /// If the session was an outgoing connection, this means that the peer initiated a connection
     /// to us at the same time and this connection is already established.
-    pub(crate) fn on_already_connected(&mut self, direction: Direction) {
+    pub(crate) fn on_already_connected(&mut self, remote_addr: SocketAddr, direction: Direction) {
         match direction {
-            Direction::Incoming => {}
+            Direction::Incoming => {
+                // release the connection of the ip address
+                self.inbound_ips.decr(&remote_addr.ip());
+            }
             Direction::Outgoing(_) => {
                 // need to decrement the outgoing counter
                 self.connection_info.decr_out();
@@ -601,10 +658,11 @@ impl PeersManager {
     /// [`PeerAction::Connect`] actions.
     ///
     /// New connections are only initiated, if slots are available and appropriate peers are
-    /// available.
+    /// available, and at most at the configured dial rate.
     fn fill_outbound_slots(&mut self) {
         // as long as there a slots available try to fill them with the best peers
         while self.connection_info.has_out_capacity() {
+            let throttled = !self.dial_throttle.has_token(std::time::Instant::now());
             let action = {
                 let (peer_id, peer) = match self.best_unconnected() {
                     Some(peer) => peer,
@@ -616,12 +674,27 @@ impl PeersManager {
                     break
                 }
 
-                trace!(target : "net::peers",  ?peer_id, addr=?peer.addr, "schedule outbound connection");
+                if throttled {
+                    None
+                } else {
+                    trace!(target : "net::peers",  ?peer_id, addr=?peer.addr, "schedule outbound connection");
+
+                    peer.state = PeerConnectionState::Out;
+                    Some(PeerAction::Connect { peer_id, remote_addr: peer.addr })
+                }
+            };
 
-                peer.state = PeerConnectionState::Out;
-                PeerAction::Connect { peer_id, remote_addr: peer.addr }
+            // the peer is dialed on one of the next refills, where it is not counted again
+            let Some(action) = action else {
+                if !self.dial_throttled {
+                    self.dial_throttled = true;
+                    self.rejected_connections.dial_throttled.connections.increment(1);
+                }
+                break
             };
 
+            self.dial_throttled = false;
+            self.dial_throttle.consume();
             self.connection_info.inc_out();
             self.queued_actions.push_back(action);
         }
@@ -657,7 +730,9 @@ impl PeersManager {
             }
 
             if self.unban_interval.poll_tick(cx).is_ready() {
-                let (_, unbanned_peers) = self.ban_list.evict(std::time::Instant::now());
+                let now = std::time::Instant::now();
+                let (_, unbanned_peers) = self.ban_list.evict(now);
+                self.handshake_failures.evict(now);
 
                 for peer_id in unbanned_peers {
                     if let Some(peer) = self.peers.get_mut(&peer_id) {
@@ -969,6 +1044,9 @@ pub struct PeersConfig {
     /// How long to backoff peers that are we failed to connect to for non-fatal reasons, such as
     /// [`DisconnectReason::TooManyPeers`].
     pub backoff_durations: PeerBackoffDurations,
+    /// Limits on the connections of a single ip address and on the rate of outbound dials.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub connection_limits: ConnectionLimits,
 }
 
 impl Default for PeersConfig {
@@ -984,6 +1062,7 @@ impl Default for PeersConfig {
             trusted_nodes: Default::default(),
             connect_trusted_nodes_only: false,
             basic_nodes: Default::default(),
+            connection_limits: Default::default(),
         }
     }
 }
@@ -1017,6 +1096,26 @@ impl PeersConfig {
         self
     }
 
+    /// Maximum allowed inbound connections from a single ip address.
+    pub fn with_max_inbound_per_ip(mut self, max_inbound_per_ip: usize) -> Self {
+        self.connection_limits.max_inbound_per_ip = max_inbound_per_ip;
+        self
+    }
+
+    /// Maximum number of outbound connections dialed at once and per second afterwards.
+    pub fn with_dial_rate_limit(mut self, burst: u32, per_second: u32) -> Self {
+        self.connection_limits.dial_burst = burst;
+        self.connection_limits.dials_per_second = per_second;
+        self
+    }
+
+    /// Ban ip addresses that fail the inbound handshake `max_failures` times within the window.
+    pub fn with_handshake_failure_ban(mut self, max_failures: usize, window: Duration) -> Self {
+        self.connection_limits.max_handshake_failures = max_failures;
+        self.connection_limits.handshake_failure_window = window;
+        self
+    }
+
     /// Nodes to always connect to.
     pub fn with_trusted_nodes(mut self, nodes: HashSet<NodeRecord>) -> Self {
         self.trusted_nodes = nodes;
@@ -1106,6 +1205,7 @@ impl Default for PeerBackoffDurations {
 #[derive(Debug, Error)]
 pub enum InboundConnectionError {
     ExceedsLimit(usize),
+    ExceedsIpLimit(usize),
     IpBanned,
 }
 
@@ -1122,7 +1222,7 @@ mod test {
         error::BackoffKind,
         peers::{
             manager::{ConnectionInfo, PeerBackoffDurations, PeerConnectionState},
-            PeerAction,
+            InboundConnectionError, PeerAction,
         },
         session::PendingSessionHandshakeError,
         PeersConfig,
@@ -1522,7 +1622,7 @@ mod test {
 
         assert!(peers.on_incoming_pending_session(socket_addr.ip()).is_ok());
         assert_eq!(peers.connection_info.num_inbound, 1);
-        peers.on_incoming_pending_session_rejected_internally();
+        peers.on_incoming_pending_session_rejected_internally(socket_addr.ip());
         assert_eq!(peers.connection_info.num_inbound, 0);
     }
 
@@ -1533,7 +1633,7 @@ mod test {
 
         assert!(peers.on_incoming_pending_session(socket_addr.ip()).is_ok());
         assert_eq!(peers.connection_info.num_inbound, 1);
-        peers.on_incoming_pending_session_gracefully_closed();
+        peers.on_incoming_pending_session_gracefully_closed(socket_addr.ip());
         assert_eq!(peers.connection_info.num_inbound, 0);
     }
 
@@ -1571,6 +1671,90 @@ mod test {
         assert!(peers.on_incoming_pending_session(socket_addr.ip()).is_ok());
     }
 
+    #[tokio::test]
+    async fn test_inbound_ip_limit() {
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let other_ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2));
+        let config = PeersConfig::default().with_max_inbound_per_ip(2);
+        let mut peers = PeersManager::new(config);
+
+        assert!(peers.on_incoming_pending_session(ip).is_ok());
+        assert!(peers.on_incoming_pending_session(ip).is_ok());
+        assert!(matches!(
+            peers.on_incoming_pending_session(ip),
+            Err(InboundConnectionError::ExceedsIpLimit(2))
+        ));
+        assert!(peers.on_incoming_pending_session(other_ip).is_ok());
+        assert_eq!(peers.connection_info.num_inbound, 3);
+
+        // a closed pending session frees a connection of the ip
+        peers.on_incoming_pending_session_gracefully_closed(ip);
+        assert!(peers.on_incoming_pending_session(ip).is_ok());
+
+        // an active session keeps its connection until it is closed
+        let peer = PeerId::random();
+        peers.on_active_inbound_session(peer, SocketAddr::new(ip, 30303));
+        assert!(peers.on_incoming_pending_session(ip).is_err());
+        peers.on_active_session_gracefully_closed(peer);
+        assert!(peers.on_incoming_pending_session(ip).is_ok());
+
+        // trusted peers are not limited by their ip address
+        peers.add_trusted_peer(PeerId::random(), SocketAddr::new(ip, 30303));
+        assert!(peers.on_incoming_pending_session(ip).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_ban_after_repeated_handshake_failures() {
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 30303);
+        let config = PeersConfig::default().with_handshake_failure_ban(3, Duration::from_secs(60));
+        let mut peers = PeersManager::new(config);
+        let err = PendingSessionHandshakeError::Eth(EthStreamError::EthHandshakeError(
+            EthHandshakeError::Timeout,
+        ));
+
+        for _ in 0..2 {
+            assert!(peers.on_incoming_pending_session(socket_addr.ip()).is_ok());
+            peers.on_incoming_pending_session_dropped(socket_addr, &err);
+            assert!(!peers.ban_list.is_banned_ip(&socket_addr.ip()));
+        }
+
+        assert!(peers.on_incoming_pending_session(socket_addr.ip()).is_ok());
+        peers.on_incoming_pending_session_dropped(socket_addr, &err);
+        assert!(peers.ban_list.is_banned_ip(&socket_addr.ip()));
+        assert!(matches!(
+            peers.on_incoming_pending_session(socket_addr.ip()),
+            Err(InboundConnectionError::IpBanned)
+        ));
+        assert_eq!(peers.connection_info.num_inbound, 0);
+    }
+
+    #[tokio::test]
+    async fn test_dial_rate_limit() {
+        let config = PeersConfig::default().with_dial_rate_limit(2, 0);
+        let mut peers = PeersManager::new(config);
+        for port in 0..4 {
+            let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8000 + port);
+            peers.add_peer(PeerId::random(), socket_addr, None);
+        }
+
+        let mut dials = 0;
+        poll_fn(|cx| {
+            while let Poll::Ready(action) = peers.poll(cx) {
+                if matches!(action, PeerAction::Connect { .. }) {
+                    dials += 1;
+                }
+            }
+            Poll::Ready(())
+        })
+        .await;
+
+        // the other peers are dialed once tokens are added to the bucket
+        assert_eq!(dials, 2);
+        assert_eq!(peers.connection_info.num_outbound, 2);
+        // the held back dial is counted once, not on every refill
+        assert!(peers.dial_throttled);
+    }
+
     #[tokio::test]
     async fn test_reputation_change_connected() {
         let peer = PeerId::random();
@@ -1725,7 +1909,8 @@ mod test {
             Ok(_) => panic!(),
             Err(err) => match err {
                 super::InboundConnectionError::IpBanned {} => {}
-                super::InboundConnectionError::ExceedsLimit { .. } => {
+                super::InboundConnectionError::ExceedsLimit { .. } |
+                super::InboundConnectionError::ExceedsIpLimit { .. } => {
                     panic!()
                 }
             },
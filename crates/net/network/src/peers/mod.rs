@@ -1,9 +1,11 @@
 //! Peer related implementations
 
+mod limits;
 mod manager;
 mod reputation;
 
 pub(crate) use manager::{InboundConnectionError, PeerAction, PeersManager};
+pub use limits::ConnectionLimits;
 pub use manager::{Peer, PeersConfig, PeersHandle};
 pub use reputation::ReputationChangeWeights;
 pub use reth_network_api::PeerKind;
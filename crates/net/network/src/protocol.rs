@@ -0,0 +1,69 @@
+//! Support for custom RLPx subprotocols that run alongside `eth` on the same peer connections.
+
+use crate::session::Direction;
+use reth_eth_wire::{
+    capability::Capability,
+    protocol::{Protocol, ProtocolConnection},
+};
+use reth_primitives::PeerId;
+use std::{fmt, sync::Arc};
+
+/// A handler for a custom RLPx subprotocol.
+///
+/// The capability of the protocol is announced in the `Hello` message of every session. If the
+/// protocol is shared with the peer, the handler is notified about the new connection once the
+/// session is established.
+pub trait RlpxSubProtocolHandler: Send + Sync + 'static {
+    /// Returns the protocol, its capability and the number of messages it reserves.
+    fn protocol(&self) -> Protocol;
+
+    /// Invoked when a new session to a peer negotiated this protocol.
+    ///
+    /// The [`ProtocolConnection`] is the handle to exchange messages with the peer over this
+    /// protocol, it terminates when the session is closed.
+    fn on_connection(&self, peer_id: PeerId, direction: Direction, conn: ProtocolConnection);
+}
+
+/// All additional RLPx subprotocols registered on the network.
+#[derive(Clone, Default)]
+pub struct RlpxSubProtocols {
+    handlers: Vec<Arc<dyn RlpxSubProtocolHandler>>,
+}
+
+// === impl RlpxSubProtocols ===
+
+impl RlpxSubProtocols {
+    /// Registers a new subprotocol handler.
+    pub fn push(&mut self, handler: impl RlpxSubProtocolHandler) {
+        self.handlers.push(Arc::new(handler));
+    }
+
+    /// Returns `true` if no subprotocols are registered.
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Returns all registered protocols.
+    pub fn protocols(&self) -> Vec<Protocol> {
+        self.handlers.iter().map(|handler| handler.protocol()).collect()
+    }
+
+    /// Returns the capabilities of all registered protocols.
+    pub fn capabilities(&self) -> impl Iterator<Item = Capability> + '_ {
+        self.handlers.iter().map(|handler| handler.protocol().cap)
+    }
+
+    /// Returns the handler for the protocol with the given name and version.
+    pub(crate) fn find(&self, name: &str, version: u8) -> Option<&Arc<dyn RlpxSubProtocolHandler>> {
+        self.handlers.iter().find(|handler| {
+            let cap = handler.protocol().cap;
+            cap.name == name && cap.version == version as usize
+        })
+    }
+}
+
+impl fmt::Debug for RlpxSubProtocols {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RlpxSubProtocols").field("protocols", &self.protocols()).finish()
+    }
+}
@@ -2,8 +2,9 @@
 
 use crate::{
     message::{NewBlockMessage, PeerMessage, PeerRequest, PeerResponse, PeerResponseResult},
+    metrics::{EthRequestsMetrics, PeerMessageMetrics},
     session::{
-        config::INITIAL_REQUEST_TIMEOUT,
+        config::RequestTimeouts,
         handle::{ActiveSessionMessage, SessionCommand},
         SessionId,
     },
@@ -43,8 +44,6 @@ use tracing::{debug, error, info, trace, warn};
 
 /// Minimum timeout value
 const MINIMUM_TIMEOUT: Duration = Duration::from_secs(2);
-/// Maximum timeout value
-const MAXIMUM_TIMEOUT: Duration = INITIAL_REQUEST_TIMEOUT;
 /// How much the new measurements affect the current timeout (X percent)
 const SAMPLE_IMPACT: f64 = 0.1;
 /// Amount of RTTs before timeout
@@ -87,10 +86,18 @@ pub(crate) struct ActiveSession {
     pub(crate) received_requests_from_remote: Vec<ReceivedRequest>,
     /// Buffered messages that should be handled and sent to the peer.
     pub(crate) queued_outgoing: VecDeque<OutgoingMessage>,
-    /// The maximum time we wait for a response from a peer.
+    /// The maximum time we wait for a response from a peer, adapted to the peer's round trip
+    /// time.
     pub(crate) internal_request_timeout: Arc<AtomicU64>,
     /// Interval when to check for timed out requests.
     pub(crate) internal_request_timeout_interval: Interval,
+    /// The maximum time we wait for a response from a peer, per request type.
+    ///
+    /// A request times out at the adaptive `internal_request_timeout` or at the timeout of its
+    /// type, whichever comes first.
+    pub(crate) request_timeouts: RequestTimeouts,
+    /// Metrics of the requests sent to the peer.
+    pub(crate) request_metrics: EthRequestsMetrics,
     /// If an [ActiveSession] does not receive a response at all within this duration then it is
     /// considered a protocol violation and the session will initiate a drop.
     pub(crate) protocol_breach_request_timeout: Duration,
@@ -228,7 +235,9 @@ impl ActiveSession {
     }
 
     /// Handle an internal peer request that will be sent to the remote.
-    fn on_internal_peer_request(&mut self, request: PeerRequest, deadline: Instant) {
+    fn on_internal_peer_request(&mut self, request: PeerRequest) {
+        let deadline = self.request_deadline(&request);
+        self.request_metrics.get(request.kind()).requests.increment(1);
         let request_id = self.next_id();
         let msg = request.create_request_message(request_id);
         self.queued_outgoing.push_back(msg.into());
@@ -255,8 +264,7 @@ impl ActiveSession {
                 }
             }
             PeerMessage::EthRequest(req) => {
-                let deadline = self.request_deadline();
-                self.on_internal_peer_request(req, deadline);
+                self.on_internal_peer_request(req);
             }
             PeerMessage::SendTransactions(msg) => {
                 self.queued_outgoing.push_back(EthBroadcastMessage::Transactions(msg).into());
@@ -271,9 +279,16 @@ impl ActiveSession {
     }
 
     /// Returns the deadline timestamp at which the request times out
-    fn request_deadline(&self) -> Instant {
-        Instant::now() +
-            Duration::from_millis(self.internal_request_timeout.load(Ordering::Relaxed))
+    ///
+    /// This is the adaptive timeout of the peer, capped at the timeout of the request's type.
+    fn request_deadline(&self, request: &PeerRequest) -> Instant {
+        let timeout = self.current_request_timeout().min(self.request_timeouts.get(request.kind()));
+        Instant::now() + timeout
+    }
+
+    /// Returns the current adaptive request timeout of the peer.
+    fn current_request_timeout(&self) -> Duration {
+        Duration::from_millis(self.internal_request_timeout.load(Ordering::Relaxed))
     }
 
     /// Handle a Response to the peer
@@ -348,6 +363,24 @@ impl ActiveSession {
             .try_send(ActiveSessionMessage::BadMessage { peer_id: self.remote_peer_id });
     }
 
+    /// Handles a message of the peer that violates the protocol, because it's too large, can't be
+    /// decoded or is not allowed.
+    ///
+    /// The peer's reputation is slashed and the session disconnects with
+    /// [`DisconnectReason::ProtocolBreach`].
+    fn on_protocol_violation(&mut self, error: EthStreamError, cx: &mut Context<'_>) -> Poll<()> {
+        debug!(target: "net::session", ?error, remote_peer_id=?self.remote_peer_id, "peer violated the protocol");
+        PeerMessageMetrics::new_with_labels(&[("peer_id", self.remote_peer_id.to_string())])
+            .malformed_messages
+            .increment(1);
+        // NOTE: we clone here so there's enough capacity to deliver this message
+        let _ = self
+            .to_session
+            .clone()
+            .try_send(ActiveSessionMessage::ProtocolBreach { peer_id: self.remote_peer_id });
+        self.try_disconnect(DisconnectReason::ProtocolBreach, cx)
+    }
+
     /// Report back that this session has been closed.
     fn emit_disconnect(&self) {
         trace!(target: "net::session", remote_peer_id=?self.remote_peer_id, "emitting disconnect");
@@ -416,7 +449,7 @@ impl ActiveSession {
             if req.is_timed_out(now) {
                 if req.is_waiting() {
                     warn!(target: "net::session", ?id, remote_peer_id=?self.remote_peer_id, "timed out outgoing request");
-                    req.timeout();
+                    req.timeout(&self.request_metrics);
                 } else if now - req.timestamp > self.protocol_breach_request_timeout {
                     return true
                 }
@@ -430,22 +463,32 @@ impl ActiveSession {
     fn update_request_timeout(&mut self, sent: Instant, received: Instant) {
         let elapsed = received.saturating_duration_since(sent);
 
-        let current = Duration::from_millis(self.internal_request_timeout.load(Ordering::Relaxed));
-        let request_timeout = calculate_new_timeout(current, elapsed);
+        let request_timeout = calculate_new_timeout(
+            self.current_request_timeout(),
+            elapsed,
+            self.request_timeouts.longest(),
+        );
         self.internal_request_timeout.store(request_timeout.as_millis() as u64, Ordering::Relaxed);
-        self.internal_request_timeout_interval = tokio::time::interval(request_timeout);
+        self.internal_request_timeout_interval =
+            tokio::time::interval(request_timeout.min(self.request_timeouts.shortest()));
     }
 }
 
 /// Calculates a new timeout using an updated estimation of the RTT
+///
+/// The timeout never exceeds `max_timeout`, unless that's below the minimum timeout.
 #[inline]
-fn calculate_new_timeout(current_timeout: Duration, estimated_rtt: Duration) -> Duration {
+fn calculate_new_timeout(
+    current_timeout: Duration,
+    estimated_rtt: Duration,
+    max_timeout: Duration,
+) -> Duration {
     let new_timeout = estimated_rtt.mul_f64(SAMPLE_IMPACT) * TIMEOUT_SCALING;
 
     // this dampens sudden changes by taking a weighted mean of the old and new values
     let smoothened_timeout = current_timeout.mul_f64(1.0 - SAMPLE_IMPACT) + new_timeout;
 
-    smoothened_timeout.clamp(MINIMUM_TIMEOUT, MAXIMUM_TIMEOUT)
+    smoothened_timeout.clamp(MINIMUM_TIMEOUT, max_timeout.max(MINIMUM_TIMEOUT))
 }
 
 impl Future for ActiveSession {
@@ -488,11 +531,9 @@ impl Future for ActiveSession {
                 }
             }
 
-            let deadline = this.request_deadline();
-
             while let Poll::Ready(Some(req)) = this.internal_request_tx.poll_next_unpin(cx) {
                 progress = true;
-                this.on_internal_peer_request(req, deadline);
+                this.on_internal_peer_request(req);
             }
 
             // Advance all active requests.
@@ -574,8 +615,7 @@ impl Future for ActiveSession {
                                     }
                                     OnIncomingMessageOutcome::BadMessage { error, message } => {
                                         error!(target: "net::session", ?error, msg=?message,  remote_peer_id=?this.remote_peer_id, "received invalid protocol message");
-                                        this.close_on_error(error);
-                                        return Poll::Ready(())
+                                        return this.on_protocol_violation(error, cx)
                                     }
                                     OnIncomingMessageOutcome::NoCapacity(msg) => {
                                         // failed to send due to lack of capacity
@@ -585,6 +625,9 @@ impl Future for ActiveSession {
                                 }
                             }
                             Err(err) => {
+                                if err.is_protocol_violation() {
+                                    return this.on_protocol_violation(err, cx)
+                                }
                                 error!(target: "net::session", ?err, remote_peer_id=?this.remote_peer_id, "failed to receive message");
                                 this.close_on_error(err);
                                 return Poll::Ready(())
@@ -646,11 +689,12 @@ impl InflightRequest {
         matches!(self.request, RequestState::Waiting(_))
     }
 
-    fn timeout(&mut self) {
+    fn timeout(&mut self, metrics: &EthRequestsMetrics) {
         let mut req = RequestState::TimedOut;
         std::mem::swap(&mut self.request, &mut req);
 
         if let RequestState::Waiting(req) = req {
+            metrics.get(req.kind()).timeouts.increment(1);
             req.send_err_response(RequestError::Timeout);
         }
     }
@@ -714,10 +758,11 @@ mod tests {
     };
     use reth_ecies::util::pk2id;
     use reth_eth_wire::{
-        GetBlockBodies, HelloMessage, Status, StatusBuilder, UnauthedEthStream, UnauthedP2PStream,
+        BlockBodies, GetBlockBodies, HelloMessage, MessageSizeLimits, Status, StatusBuilder,
+        UnauthedEthStream, UnauthedP2PStream,
     };
     use reth_net_common::bandwidth_meter::BandwidthMeter;
-    use reth_primitives::{ForkFilter, Hardfork, MAINNET};
+    use reth_primitives::{ForkFilter, Hardfork, H256, MAINNET};
     use secp256k1::{SecretKey, SECP256K1};
     use std::time::Duration;
     use tokio::{net::TcpListener, sync::mpsc};
@@ -732,6 +777,9 @@ mod tests {
         active_session_tx: mpsc::Sender<ActiveSessionMessage>,
         active_session_rx: ReceiverStream<ActiveSessionMessage>,
         to_sessions: Vec<mpsc::Sender<SessionCommand>>,
+        to_session_requests: Vec<mpsc::Sender<PeerRequest>>,
+        request_timeouts: RequestTimeouts,
+        message_size_limits: MessageSizeLimits,
         secret_key: SecretKey,
         local_peer_id: PeerId,
         hello: HelloMessage,
@@ -796,6 +844,7 @@ mod tests {
                 self.hello.clone(),
                 self.status,
                 self.fork_filter.clone(),
+                Default::default(),
             ));
 
             let mut stream = ReceiverStream::new(pending_sessions_rx);
@@ -809,10 +858,11 @@ mod tests {
                     conn,
                     ..
                 } => {
-                    let (_to_session_tx, messages_rx) = mpsc::channel(10);
+                    let (to_session_tx, messages_rx) = mpsc::channel(10);
                     let (commands_to_session, commands_rx) = mpsc::channel(10);
 
                     self.to_sessions.push(commands_to_session);
+                    self.to_session_requests.push(to_session_tx);
 
                     ActiveSession {
                         next_id: 0,
@@ -828,15 +878,17 @@ mod tests {
                         pending_message_to_session: None,
                         internal_request_tx: ReceiverStream::new(messages_rx).fuse(),
                         inflight_requests: Default::default(),
-                        conn,
+                        conn: conn.with_message_size_limits(self.message_size_limits),
                         queued_outgoing: Default::default(),
                         received_requests_from_remote: Default::default(),
                         internal_request_timeout_interval: tokio::time::interval(
-                            INITIAL_REQUEST_TIMEOUT,
+                            self.request_timeouts.shortest(),
                         ),
                         internal_request_timeout: Arc::new(AtomicU64::new(
-                            INITIAL_REQUEST_TIMEOUT.as_millis() as u64,
+                            self.request_timeouts.longest().as_millis() as u64,
                         )),
+                        request_timeouts: self.request_timeouts,
+                        request_metrics: Default::default(),
                         protocol_breach_request_timeout: PROTOCOL_BREACH_REQUEST_TIMEOUT,
                     }
                 }
@@ -860,6 +912,9 @@ mod tests {
                 active_session_tx,
                 active_session_rx: ReceiverStream::new(active_session_rx),
                 to_sessions: vec![],
+                to_session_requests: vec![],
+                request_timeouts: Default::default(),
+                message_size_limits: Default::default(),
                 hello: eth_hello(&secret_key),
                 secret_key,
                 local_peer_id,
@@ -977,15 +1032,12 @@ mod tests {
         tokio::task::spawn(fut);
 
         let (incoming, _) = listener.accept().await.unwrap();
+        builder.request_timeouts.block_bodies = request_timeout;
         let mut session = builder.connect_incoming(incoming).await;
-        session
-            .internal_request_timeout
-            .store(request_timeout.as_millis() as u64, Ordering::Relaxed);
         session.protocol_breach_request_timeout = drop_timeout;
-        session.internal_request_timeout_interval = tokio::time::interval(request_timeout);
         let (tx, rx) = oneshot::channel();
         let req = PeerRequest::GetBlockBodies { request: GetBlockBodies(vec![]), response: tx };
-        session.on_internal_peer_request(req, Instant::now());
+        session.on_internal_peer_request(req);
         tokio::spawn(session);
 
         let err = rx.await.unwrap().unwrap_err();
@@ -999,6 +1051,128 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_request_type_timeout() {
+        reth_tracing::init_test_tracing();
+
+        let mut builder = SessionBuilder::default();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        // the peer never responds
+        let fut = builder.with_client_stream(local_addr, move |client_stream| async move {
+            let _client_stream = client_stream;
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        });
+        tokio::task::spawn(fut);
+
+        let (incoming, _) = listener.accept().await.unwrap();
+        builder.request_timeouts.block_bodies = Duration::from_millis(100);
+        let session = builder.connect_incoming(incoming).await;
+        // the adaptive timeout is much longer than the timeout of the request type
+        assert_eq!(session.current_request_timeout(), INITIAL_REQUEST_TIMEOUT);
+        tokio::spawn(session);
+
+        let (tx, rx) = oneshot::channel();
+        let req = PeerRequest::GetBlockBodies { request: GetBlockBodies(vec![]), response: tx };
+        builder.to_session_requests[0].send(req).await.unwrap();
+
+        let res = tokio::time::timeout(Duration::from_secs(2), rx).await.unwrap().unwrap();
+        assert_eq!(res.unwrap_err(), RequestError::Timeout);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_adaptive_request_timeout() {
+        reth_tracing::init_test_tracing();
+
+        let mut builder = SessionBuilder::default();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        // the peer responds to the first request late and to the second request right away
+        let fut = builder.with_client_stream(local_addr, move |mut client_stream| async move {
+            for delay in [Duration::from_millis(1500), Duration::from_millis(100)] {
+                let request_id = match client_stream.next().await.unwrap().unwrap() {
+                    EthMessage::GetBlockBodies(RequestPair { request_id, .. }) => request_id,
+                    msg => unreachable!("{msg:?}"),
+                };
+                tokio::time::sleep(delay).await;
+                client_stream
+                    .send(EthMessage::BlockBodies(RequestPair {
+                        request_id,
+                        message: BlockBodies(vec![]),
+                    }))
+                    .await
+                    .unwrap();
+            }
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        });
+        tokio::task::spawn(fut);
+
+        let (incoming, _) = listener.accept().await.unwrap();
+        let mut session = builder.connect_incoming(incoming).await;
+        let adaptive_timeout = Arc::clone(&session.internal_request_timeout);
+        adaptive_timeout.store(500, Ordering::Relaxed);
+        session.internal_request_timeout_interval =
+            tokio::time::interval(Duration::from_millis(100));
+        tokio::spawn(session);
+
+        // the first response misses the adaptive deadline
+        let (tx, rx) = oneshot::channel();
+        let req = PeerRequest::GetBlockBodies { request: GetBlockBodies(vec![]), response: tx };
+        builder.to_session_requests[0].send(req).await.unwrap();
+        assert_eq!(rx.await.unwrap().unwrap_err(), RequestError::Timeout);
+
+        // the late response raises the adaptive timeout
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while adaptive_timeout.load(Ordering::Relaxed) < MINIMUM_TIMEOUT.as_millis() as u64 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let (tx, rx) = oneshot::channel();
+        let req = PeerRequest::GetBlockBodies { request: GetBlockBodies(vec![]), response: tx };
+        builder.to_session_requests[0].send(req).await.unwrap();
+        assert_eq!(rx.await.unwrap().unwrap(), BlockBodies(vec![]));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_disconnect_on_oversized_message() {
+        reth_tracing::init_test_tracing();
+
+        let mut builder = SessionBuilder::default();
+        builder.message_size_limits =
+            MessageSizeLimits { announcements: 100, ..Default::default() };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let fut = builder.with_client_stream(local_addr, move |mut client_stream| async move {
+            let hashes = (0..10).map(|_| H256::random()).collect::<Vec<_>>();
+            client_stream
+                .send(EthMessage::NewPooledTransactionHashes66(hashes.into()))
+                .await
+                .unwrap();
+            let err = client_stream.next().await.unwrap().unwrap_err();
+            assert_eq!(err.as_disconnected(), Some(DisconnectReason::ProtocolBreach));
+        });
+
+        let (incoming, _) = listener.accept().await.unwrap();
+        let session = builder.connect_incoming(incoming).await;
+        tokio::spawn(session);
+
+        match builder.active_session_rx.next().await.unwrap() {
+            ActiveSessionMessage::ProtocolBreach { .. } => {}
+            ev => unreachable!("{ev:?}"),
+        }
+
+        fut.await;
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_keep_alive() {
         let mut builder = SessionBuilder::default();
@@ -1089,13 +1263,23 @@ mod tests {
         // timeout for an RTT of `rtt`
         let timeout = rtt * TIMEOUT_SCALING;
 
+        let max_timeout = timeout * 4;
+
         // if rtt hasn't changed, timeout shouldn't change
-        assert_eq!(calculate_new_timeout(timeout, rtt), timeout);
+        assert_eq!(calculate_new_timeout(timeout, rtt, max_timeout), timeout);
 
         // if rtt changed, the new timeout should change less than it
-        assert!(calculate_new_timeout(timeout, rtt / 2) < timeout);
-        assert!(calculate_new_timeout(timeout, rtt / 2) > timeout / 2);
-        assert!(calculate_new_timeout(timeout, rtt * 2) > timeout);
-        assert!(calculate_new_timeout(timeout, rtt * 2) < timeout * 2);
+        assert!(calculate_new_timeout(timeout, rtt / 2, max_timeout) < timeout);
+        assert!(calculate_new_timeout(timeout, rtt / 2, max_timeout) > timeout / 2);
+        assert!(calculate_new_timeout(timeout, rtt * 2, max_timeout) > timeout);
+        assert!(calculate_new_timeout(timeout, rtt * 2, max_timeout) < timeout * 2);
+
+        // the timeout is bounded
+        assert_eq!(calculate_new_timeout(timeout, rtt * 100, max_timeout), max_timeout);
+        assert_eq!(calculate_new_timeout(timeout, rtt, timeout / 2), timeout / 2);
+        assert_eq!(
+            calculate_new_timeout(timeout, Duration::ZERO, max_timeout / 100),
+            MINIMUM_TIMEOUT
+        );
     }
 }
@@ -1,9 +1,11 @@
 //! Configuration types for [SessionManager](crate::session::SessionManager).
 
 use crate::{
+    message::PeerRequestKind,
     peers::{DEFAULT_MAX_PEERS_INBOUND, DEFAULT_MAX_PEERS_OUTBOUND},
     session::{Direction, ExceedsSessionLimit},
 };
+use reth_eth_wire::MessageSizeLimits;
 use std::time::Duration;
 
 /// Default request timeout for a single request.
@@ -11,6 +13,11 @@ use std::time::Duration;
 /// This represents the amount of time we wait for a response until we consider it timed out.
 pub const INITIAL_REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
 
+/// Default request timeout for a `GetPooledTransactions` request.
+///
+/// Transactions are also announced by other peers, so it's cheaper to give up early.
+pub const POOLED_TRANSACTIONS_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Default timeout after which we'll consider the peer to be in violation of the protocol.
 ///
 /// This is the time a peer has to answer a response.
@@ -28,15 +35,22 @@ pub struct SessionsConfig {
     ///
     /// By default, no limits will be enforced.
     pub limits: SessionLimits,
-    /// The maximum initial time we wait for a response from the peer before we timeout a request
-    /// _internally_.
-    pub initial_internal_request_timeout: Duration,
+    /// The maximum time we wait for a response from the peer before we timeout a request
+    /// _internally_, per request type.
+    ///
+    /// The timeout of a request adapts to the round trip time of the peer, but never exceeds the
+    /// timeout of the request's type.
+    pub request_timeouts: RequestTimeouts,
     /// The amount of time we continue to wait for a response from the peer, even if we timed it
-    /// out internally (`initial_internal_request_timeout`). Timeouts are not penalized but the
+    /// out internally (`request_timeouts`). Timeouts are not penalized but the
     /// session directly, however if a peer fails to respond at all (within
     /// `PROTOCOL_BREACH_REQUEST_TIMEOUT`) this is considered a protocol violation and results in a
     /// dropped session.
     pub protocol_breach_request_timeout: Duration,
+    /// The maximum size of messages received from peers, per message type.
+    ///
+    /// A peer that sends a larger message violates the protocol and is disconnected.
+    pub message_size_limits: MessageSizeLimits,
 }
 
 impl Default for SessionsConfig {
@@ -53,8 +67,9 @@ impl Default for SessionsConfig {
             // the buffer will have capacity for 3 messages per session (average).
             session_event_buffer: (DEFAULT_MAX_PEERS_OUTBOUND + DEFAULT_MAX_PEERS_INBOUND) * 2,
             limits: Default::default(),
-            initial_internal_request_timeout: INITIAL_REQUEST_TIMEOUT,
+            request_timeouts: Default::default(),
             protocol_breach_request_timeout: PROTOCOL_BREACH_REQUEST_TIMEOUT,
+            message_size_limits: Default::default(),
         }
     }
 }
@@ -69,6 +84,90 @@ impl SessionsConfig {
         self.session_event_buffer = n;
         self
     }
+
+    /// Sets the maximum size of messages received from peers, per message type.
+    pub fn with_message_size_limits(mut self, message_size_limits: MessageSizeLimits) -> Self {
+        self.message_size_limits = message_size_limits;
+        self
+    }
+
+    /// Sets the maximum time we wait for responses from peers, per request type.
+    pub fn with_request_timeouts(mut self, request_timeouts: RequestTimeouts) -> Self {
+        self.request_timeouts = request_timeouts;
+        self
+    }
+}
+
+/// The maximum time we wait for the response to a request, per request type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RequestTimeouts {
+    /// Timeout of `GetBlockHeaders` requests.
+    ///
+    /// Default is [INITIAL_REQUEST_TIMEOUT]
+    pub block_headers: Duration,
+    /// Timeout of `GetBlockBodies` requests.
+    ///
+    /// Default is [INITIAL_REQUEST_TIMEOUT]
+    pub block_bodies: Duration,
+    /// Timeout of `GetReceipts` requests.
+    ///
+    /// Default is [INITIAL_REQUEST_TIMEOUT]
+    pub receipts: Duration,
+    /// Timeout of `GetPooledTransactions` requests.
+    ///
+    /// Default is [POOLED_TRANSACTIONS_REQUEST_TIMEOUT]
+    pub pooled_transactions: Duration,
+    /// Timeout of `GetNodeData` requests.
+    ///
+    /// Default is [INITIAL_REQUEST_TIMEOUT]
+    pub node_data: Duration,
+}
+
+impl RequestTimeouts {
+    /// Returns the timeout of the given request type.
+    pub(crate) fn get(&self, kind: PeerRequestKind) -> Duration {
+        match kind {
+            PeerRequestKind::BlockHeaders => self.block_headers,
+            PeerRequestKind::BlockBodies => self.block_bodies,
+            PeerRequestKind::Receipts => self.receipts,
+            PeerRequestKind::PooledTransactions => self.pooled_transactions,
+            PeerRequestKind::NodeData => self.node_data,
+        }
+    }
+
+    /// Returns the shortest timeout of all request types.
+    pub(crate) fn shortest(&self) -> Duration {
+        self.iter().min().expect("not empty")
+    }
+
+    /// Returns the longest timeout of all request types.
+    pub(crate) fn longest(&self) -> Duration {
+        self.iter().max().expect("not empty")
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Duration> {
+        [
+            self.block_headers,
+            self.block_bodies,
+            self.receipts,
+            self.pooled_transactions,
+            self.node_data,
+        ]
+        .into_iter()
+    }
+}
+
+impl Default for RequestTimeouts {
+    fn default() -> Self {
+        Self {
+            block_headers: INITIAL_REQUEST_TIMEOUT,
+            block_bodies: INITIAL_REQUEST_TIMEOUT,
+            receipts: INITIAL_REQUEST_TIMEOUT,
+            pooled_transactions: POOLED_TRANSACTIONS_REQUEST_TIMEOUT,
+            node_data: INITIAL_REQUEST_TIMEOUT,
+        }
+    }
 }
 
 /// Limits for sessions.
@@ -210,4 +309,13 @@ mod tests {
         limits.inc_pending_inbound();
         assert!(limits.ensure_pending_inbound().is_err());
     }
+    #[test]
+    fn test_request_timeouts() {
+        let timeouts =
+            RequestTimeouts { block_headers: Duration::from_secs(5), ..Default::default() };
+        assert_eq!(timeouts.get(PeerRequestKind::BlockHeaders), Duration::from_secs(5));
+        assert_eq!(timeouts.get(PeerRequestKind::Receipts), INITIAL_REQUEST_TIMEOUT);
+        assert_eq!(timeouts.shortest(), Duration::from_secs(5));
+        assert_eq!(timeouts.longest(), INITIAL_REQUEST_TIMEOUT);
+    }
 }
@@ -1,7 +1,7 @@
 //! Session handles
 use crate::{
     message::PeerMessage,
-    session::{Direction, SessionId},
+    session::{Direction, PeerInfo, SessionId},
 };
 use reth_ecies::{stream::ECIESStream, ECIESError};
 use reth_eth_wire::{
@@ -75,21 +75,18 @@ impl ActiveSessionHandle {
         // Note: we clone the sender which ensures the channel has capacity to send the message
         let _ = self.commands_to_session.clone().try_send(SessionCommand::Disconnect { reason });
     }
-}
 
-/// Info about an active peer session.
-#[derive(Debug, Clone)]
-pub struct PeerInfo {
-    /// Announced capabilities of the peer
-    pub capabilities: Arc<Capabilities>,
-    /// The identifier of the remote peer
-    pub remote_id: PeerId,
-    /// The client's name and version
-    pub client_version: String,
-    /// The address we're connected to
-    pub remote_addr: SocketAddr,
-    /// The direction of the session
-    pub direction: Direction,
+    /// Returns the [`PeerInfo`] of the session.
+    pub(crate) fn peer_info(&self) -> PeerInfo {
+        PeerInfo {
+            remote_id: self.remote_id,
+            direction: self.direction,
+            remote_addr: self.remote_addr,
+            capabilities: self.capabilities.clone(),
+            client_version: self.client_version.clone(),
+            eth_version: self.version,
+        }
+    }
 }
 
 /// Events a pending session can produce.
@@ -1,6 +1,8 @@
 //! Support for handling peer sessions.
+pub use crate::message::PeerRequestSender;
 use crate::{
     message::PeerMessage,
+    protocol::RlpxSubProtocols,
     session::{
         active::ActiveSession,
         config::SessionCounter,
@@ -10,20 +12,22 @@ use crate::{
         },
     },
 };
-pub use crate::{message::PeerRequestSender, session::handle::PeerInfo};
 use fnv::FnvHashMap;
 use futures::{future::Either, io, FutureExt, StreamExt};
 use reth_ecies::{stream::ECIESStream, ECIESError};
 use reth_eth_wire::{
     capability::{Capabilities, CapabilityMessage},
     errors::EthStreamError,
-    DisconnectReason, EthVersion, HelloMessage, Status, UnauthedEthStream, UnauthedP2PStream,
+    protocol::protocol_channel,
+    DisconnectReason, EthVersion, HelloMessage, MessageSizeLimits, Status, UnauthedEthStream,
+    UnauthedP2PStream,
 };
 use reth_metrics_common::metered_sender::MeteredSender;
 use reth_net_common::{
     bandwidth_meter::{BandwidthMeter, MeteredStream},
     stream::HasRemoteAddr,
 };
+pub use reth_network_api::{Direction, PeerInfo};
 use reth_primitives::{ForkFilter, ForkId, ForkTransition, Head, PeerId};
 use reth_tasks::TaskSpawner;
 use secp256k1::SecretKey;
@@ -46,7 +50,7 @@ use tracing::{instrument, trace};
 mod active;
 mod config;
 mod handle;
-pub use config::SessionsConfig;
+pub use config::{RequestTimeouts, SessionsConfig};
 
 /// Internal identifier for active sessions.
 #[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Eq, Hash)]
@@ -60,12 +64,14 @@ pub(crate) struct SessionManager {
     next_id: usize,
     /// Keeps track of all sessions
     counter: SessionCounter,
-    ///  The maximum initial time an [ActiveSession] waits for a response from the peer before it
-    /// responds to an _internal_ request with a `TimeoutError`
-    initial_internal_request_timeout: Duration,
+    /// The maximum time an [ActiveSession] waits for a response from the peer before it responds
+    /// to an _internal_ request with a `TimeoutError`, per request type.
+    request_timeouts: RequestTimeouts,
     /// If an [ActiveSession] does not receive a response at all within this duration then it is
     /// considered a protocol violation and the session will initiate a drop.
     protocol_breach_request_timeout: Duration,
+    /// The maximum size of messages an [ActiveSession] accepts from the peer, per message type.
+    message_size_limits: MessageSizeLimits,
     /// The secret key used for authenticating sessions.
     secret_key: SecretKey,
     /// The `Status` message to send to peers.
@@ -74,6 +80,8 @@ pub(crate) struct SessionManager {
     hello_message: HelloMessage,
     /// The [`ForkFilter`] used to validate the peer's `Status` message.
     fork_filter: ForkFilter,
+    /// Additional RLPx subprotocols that are multiplexed over the sessions.
+    extra_protocols: RlpxSubProtocols,
     /// Size of the command buffer per session.
     session_command_buffer: usize,
     /// The executor for spawned tasks.
@@ -114,6 +122,7 @@ impl SessionManager {
         status: Status,
         hello_message: HelloMessage,
         fork_filter: ForkFilter,
+        extra_protocols: RlpxSubProtocols,
         bandwidth_meter: BandwidthMeter,
     ) -> Self {
         let (pending_sessions_tx, pending_sessions_rx) = mpsc::channel(config.session_event_buffer);
@@ -122,12 +131,14 @@ impl SessionManager {
         Self {
             next_id: 0,
             counter: SessionCounter::new(config.limits),
-            initial_internal_request_timeout: config.initial_internal_request_timeout,
+            request_timeouts: config.request_timeouts,
             protocol_breach_request_timeout: config.protocol_breach_request_timeout,
+            message_size_limits: config.message_size_limits,
             secret_key,
             status,
             hello_message,
             fork_filter,
+            extra_protocols,
             session_command_buffer: config.session_command_buffer,
             executor,
             pending_sessions: Default::default(),
@@ -209,6 +220,7 @@ impl SessionManager {
         let hello_message = self.hello_message.clone();
         let status = self.status;
         let fork_filter = self.fork_filter.clone();
+        let extra_protocols = self.extra_protocols.clone();
         self.spawn(start_pending_incoming_session(
             disconnect_rx,
             session_id,
@@ -219,6 +231,7 @@ impl SessionManager {
             hello_message,
             status,
             fork_filter,
+            extra_protocols,
         ));
 
         let handle = PendingSessionHandle {
@@ -239,6 +252,7 @@ impl SessionManager {
         let hello_message = self.hello_message.clone();
         let fork_filter = self.fork_filter.clone();
         let status = self.status;
+        let extra_protocols = self.extra_protocols.clone();
         let band_with_meter = self.bandwidth_meter.clone();
         self.spawn(start_pending_outbound_session(
             disconnect_rx,
@@ -250,6 +264,7 @@ impl SessionManager {
             hello_message,
             status,
             fork_filter,
+            extra_protocols,
             band_with_meter,
         ));
 
@@ -324,6 +339,12 @@ impl SessionManager {
         Some(session)
     }
 
+    /// Removes the active session and returns the client version of the peer, empty if there was
+    /// no session.
+    fn remove_active_session_client(&mut self, id: &PeerId) -> String {
+        self.remove_active_session(id).map(|session| session.client_version).unwrap_or_default()
+    }
+
     /// This polls all the session handles and returns [`SessionEvent`].
     ///
     /// Active sessions are prioritized.
@@ -342,8 +363,12 @@ impl SessionManager {
                             ?peer_id,
                             "gracefully disconnected active session."
                         );
-                        self.remove_active_session(&peer_id);
-                        Poll::Ready(SessionEvent::Disconnected { peer_id, remote_addr })
+                        let client_version = self.remove_active_session_client(&peer_id);
+                        Poll::Ready(SessionEvent::Disconnected {
+                            peer_id,
+                            remote_addr,
+                            client_version,
+                        })
                     }
                     ActiveSessionMessage::ClosedOnConnectionError {
                         peer_id,
@@ -351,10 +376,11 @@ impl SessionManager {
                         error,
                     } => {
                         trace!(target : "net::session",  ?peer_id, ?error,"closed session.");
-                        self.remove_active_session(&peer_id);
+                        let client_version = self.remove_active_session_client(&peer_id);
                         Poll::Ready(SessionEvent::SessionClosedOnConnectionError {
                             remote_addr,
                             peer_id,
+                            client_version,
                             error,
                         })
                     }
@@ -424,9 +450,8 @@ impl SessionManager {
 
                 let messages = PeerRequestSender::new(peer_id, to_session_tx);
 
-                let timeout = Arc::new(AtomicU64::new(
-                    self.initial_internal_request_timeout.as_millis() as u64,
-                ));
+                let timeout =
+                    Arc::new(AtomicU64::new(self.request_timeouts.longest().as_millis() as u64));
 
                 // negotiated version
                 let version = conn.version();
@@ -442,13 +467,15 @@ impl SessionManager {
                     pending_message_to_session: None,
                     internal_request_tx: ReceiverStream::new(messages_rx).fuse(),
                     inflight_requests: Default::default(),
-                    conn,
+                    conn: conn.with_message_size_limits(self.message_size_limits),
                     queued_outgoing: Default::default(),
                     received_requests_from_remote: Default::default(),
                     internal_request_timeout_interval: tokio::time::interval(
-                        self.initial_internal_request_timeout,
+                        self.request_timeouts.shortest(),
                     ),
                     internal_request_timeout: Arc::clone(&timeout),
+                    request_timeouts: self.request_timeouts,
+                    request_metrics: Default::default(),
                     protocol_breach_request_timeout: self.protocol_breach_request_timeout,
                 };
 
@@ -462,7 +489,7 @@ impl SessionManager {
                     established: Instant::now(),
                     capabilities: Arc::clone(&capabilities),
                     commands_to_session,
-                    client_version: client_id,
+                    client_version: client_id.clone(),
                     remote_addr,
                 };
 
@@ -472,6 +499,7 @@ impl SessionManager {
                 Poll::Ready(SessionEvent::SessionEstablished {
                     peer_id,
                     remote_addr,
+                    client_version: client_id,
                     version,
                     capabilities,
                     status,
@@ -553,29 +581,14 @@ impl SessionManager {
 
     /// Returns [`PeerInfo`] for all connected peers
     pub(crate) fn get_peer_info(&self) -> Vec<PeerInfo> {
-        self.active_sessions
-            .values()
-            .map(|session| PeerInfo {
-                remote_id: session.remote_id,
-                direction: session.direction,
-                remote_addr: session.remote_addr,
-                capabilities: session.capabilities.clone(),
-                client_version: session.client_version.clone(),
-            })
-            .collect()
+        self.active_sessions.values().map(ActiveSessionHandle::peer_info).collect()
     }
 
     /// Returns [`PeerInfo`] for a given peer.
     ///
     /// Returns `None` if there's no active session to the peer.
     pub(crate) fn get_peer_info_by_id(&self, peer_id: PeerId) -> Option<PeerInfo> {
-        self.active_sessions.get(&peer_id).map(|session| PeerInfo {
-            remote_id: session.remote_id,
-            direction: session.direction,
-            remote_addr: session.remote_addr,
-            capabilities: session.capabilities.clone(),
-            client_version: session.client_version.clone(),
-        })
+        self.active_sessions.get(&peer_id).map(ActiveSessionHandle::peer_info)
     }
 }
 
@@ -588,6 +601,8 @@ pub(crate) enum SessionEvent {
     SessionEstablished {
         peer_id: PeerId,
         remote_addr: SocketAddr,
+        /// The client id of the peer, announced in its `Hello` message
+        client_version: String,
         capabilities: Arc<Capabilities>,
         /// negotiated eth version
         version: EthVersion,
@@ -648,6 +663,8 @@ pub(crate) enum SessionEvent {
         peer_id: PeerId,
         /// The socket we were connected to.
         remote_addr: SocketAddr,
+        /// The client id of the peer.
+        client_version: String,
         /// The error that caused the session to close
         error: EthStreamError,
     },
@@ -655,6 +672,8 @@ pub(crate) enum SessionEvent {
     Disconnected {
         peer_id: PeerId,
         remote_addr: SocketAddr,
+        /// The client id of the peer.
+        client_version: String,
     },
 }
 
@@ -665,22 +684,6 @@ pub(crate) enum PendingSessionHandshakeError {
     Ecies(ECIESError),
 }
 
-/// The direction of the connection.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum Direction {
-    /// Incoming connection.
-    Incoming,
-    /// Outgoing connection to a specific node.
-    Outgoing(PeerId),
-}
-
-impl Direction {
-    /// Returns `true` if this an incoming connection.
-    pub(crate) fn is_incoming(&self) -> bool {
-        matches!(self, Direction::Incoming)
-    }
-}
-
 /// The error thrown when the max configured limit has been reached and no more connections are
 /// accepted.
 #[derive(Debug, Clone, thiserror::Error)]
@@ -701,6 +704,7 @@ pub(crate) async fn start_pending_incoming_session(
     hello: HelloMessage,
     status: Status,
     fork_filter: ForkFilter,
+    extra_protocols: RlpxSubProtocols,
 ) {
     authenticate(
         disconnect_rx,
@@ -713,6 +717,7 @@ pub(crate) async fn start_pending_incoming_session(
         hello,
         status,
         fork_filter,
+        extra_protocols,
     )
     .await
 }
@@ -730,6 +735,7 @@ async fn start_pending_outbound_session(
     hello: HelloMessage,
     status: Status,
     fork_filter: ForkFilter,
+    extra_protocols: RlpxSubProtocols,
     bandwidth_meter: BandwidthMeter,
 ) {
     let stream = match TcpStream::connect(remote_addr).await {
@@ -757,6 +763,7 @@ async fn start_pending_outbound_session(
         hello,
         status,
         fork_filter,
+        extra_protocols,
     )
     .await
 }
@@ -774,6 +781,7 @@ async fn authenticate(
     hello: HelloMessage,
     status: Status,
     fork_filter: ForkFilter,
+    extra_protocols: RlpxSubProtocols,
 ) {
     let stream = match get_eciess_stream(stream, secret_key, direction).await {
         Ok(stream) => stream,
@@ -790,7 +798,7 @@ async fn authenticate(
         }
     };
 
-    let unauthed = UnauthedP2PStream::new(stream);
+    let unauthed = UnauthedP2PStream::new(stream).with_protocols(extra_protocols.protocols());
 
     let auth = authenticate_stream(
         unauthed,
@@ -800,6 +808,7 @@ async fn authenticate(
         hello,
         status,
         fork_filter,
+        extra_protocols,
     )
     .boxed();
 
@@ -847,9 +856,10 @@ async fn authenticate_stream(
     hello: HelloMessage,
     status: Status,
     fork_filter: ForkFilter,
+    extra_protocols: RlpxSubProtocols,
 ) -> PendingSessionEvent {
     // conduct the p2p handshake and return the authenticated stream
-    let (p2p_stream, their_hello) = match stream.handshake(hello).await {
+    let (mut p2p_stream, their_hello) = match stream.handshake(hello).await {
         Ok(stream_res) => stream_res,
         Err(err) => {
             return PendingSessionEvent::Disconnected {
@@ -861,6 +871,20 @@ async fn authenticate_stream(
         }
     };
 
+    // install all additional protocols that are shared with the peer, their handlers are notified
+    // once the session is established
+    let mut protocol_connections = Vec::new();
+    for cap in p2p_stream.shared_capabilities().to_vec() {
+        if cap.is_eth() {
+            continue
+        }
+        if let Some(handler) = extra_protocols.find(cap.name(), cap.version()) {
+            let (proxy, conn) = protocol_channel(cap);
+            p2p_stream.install_protocol(proxy);
+            protocol_connections.push((handler.clone(), conn));
+        }
+    }
+
     // if the hello handshake was successful we can try status handshake
     //
     // Before trying status handshake, set up the version to shared_capability
@@ -877,6 +901,10 @@ async fn authenticate_stream(
             }
         }
     };
+    for (handler, conn) in protocol_connections {
+        handler.on_connection(their_hello.id, direction, conn);
+    }
+
     PendingSessionEvent::Established {
         session_id,
         remote_addr,
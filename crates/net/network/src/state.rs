@@ -15,7 +15,7 @@ use reth_eth_wire::{
     capability::Capabilities, BlockHashNumber, DisconnectReason, NewBlockHashes, Status,
 };
 use reth_network_api::PeerKind;
-use reth_primitives::{ForkId, PeerId, H256};
+use reth_primitives::{ForkId, PeerId, H256, U256};
 use reth_provider::BlockProvider;
 use std::{
     collections::{HashMap, VecDeque},
@@ -135,6 +135,7 @@ where
             peer,
             ActivePeer {
                 best_hash: status.blockhash,
+                total_difficulty: status.total_difficulty,
                 capabilities,
                 request_tx,
                 pending_response: None,
@@ -231,18 +232,33 @@ where
 
     /// Invoked after a `NewBlock` message was received by the peer.
     ///
-    /// This will keep track of blocks we know a peer has
-    pub(crate) fn on_new_block(&mut self, peer_id: PeerId, hash: H256) {
-        // Mark the blocks as seen
+    /// This will keep track of blocks we know a peer has and of the peer's head, which is the
+    /// announced block if its total difficulty is higher than the peer's previous head.
+    pub(crate) fn on_new_block(&mut self, peer_id: PeerId, msg: &NewBlockMessage) {
         if let Some(peer) = self.active_peers.get_mut(&peer_id) {
-            peer.blocks.insert(hash);
+            // Mark the blocks as seen
+            peer.blocks.insert(msg.hash);
+
+            let td = U256::from(msg.block.td);
+            if td > peer.total_difficulty {
+                peer.best_hash = msg.hash;
+                peer.total_difficulty = td;
+                let number = msg.block.block.header.number;
+                self.state_fetcher.update_peer_block(&peer_id, msg.hash, number);
+            }
         }
     }
 
     /// Invoked for a `NewBlockHashes` broadcast message.
     pub(crate) fn on_new_block_hashes(&mut self, peer_id: PeerId, hashes: Vec<BlockHashNumber>) {
-        // Mark the blocks as seen
         if let Some(peer) = self.active_peers.get_mut(&peer_id) {
+            // the highest announced block is the peer's new head
+            if let Some(best) = hashes.iter().max_by_key(|b| b.number) {
+                if self.state_fetcher.update_peer_block(&peer_id, best.hash, best.number) {
+                    peer.best_hash = best.hash;
+                }
+            }
+            // Mark the blocks as seen
             peer.blocks.extend(hashes.into_iter().map(|b| b.hash));
         }
     }
@@ -455,6 +471,8 @@ where
 pub(crate) struct ActivePeer {
     /// Best block of the peer.
     pub(crate) best_hash: H256,
+    /// Total difficulty of the best block of the peer.
+    pub(crate) total_difficulty: U256,
     /// The capabilities of the remote peer.
     #[allow(unused)]
     pub(crate) capabilities: Arc<Capabilities>,
@@ -506,15 +524,19 @@ pub(crate) enum StateAction {
 #[cfg(test)]
 mod tests {
     use crate::{
-        discovery::Discovery, fetch::StateFetcher, message::PeerRequestSender, peers::PeersManager,
-        state::NetworkState, PeerRequest,
+        discovery::Discovery,
+        fetch::StateFetcher,
+        message::{NewBlockMessage, PeerRequestSender},
+        peers::PeersManager,
+        state::NetworkState,
+        PeerRequest,
     };
     use reth_eth_wire::{
         capability::{Capabilities, Capability},
-        BlockBodies, BlockBody, EthVersion, Status,
+        BlockBodies, BlockBody, BlockHashNumber, EthVersion, NewBlock, Status,
     };
     use reth_interfaces::p2p::{bodies::client::BodiesClient, error::RequestError};
-    use reth_primitives::{Header, PeerId, H256};
+    use reth_primitives::{Block, Header, PeerId, H256, U128, U256};
     use reth_provider::test_utils::NoopProvider;
     use std::{
         future::poll_fn,
@@ -598,4 +620,46 @@ mod tests {
         assert!(resp.is_err());
         assert_eq!(resp.unwrap_err(), RequestError::ConnectionDropped);
     }
+
+    #[test]
+    fn test_track_peer_head() {
+        let mut state = state();
+        let peer_id = PeerId::random();
+        let (tx, _session_rx) = mpsc::channel(1);
+        let status = Status { total_difficulty: U256::from(10u64), ..Default::default() };
+        state.on_session_activated(
+            peer_id,
+            capabilities(),
+            status,
+            PeerRequestSender::new(peer_id, tx),
+            Arc::new(AtomicU64::new(1)),
+        );
+
+        let new_block = |number: u64, td: u64| {
+            let block =
+                Block { header: Header { number, ..Default::default() }, ..Default::default() };
+            NewBlockMessage {
+                hash: H256::random(),
+                block: Arc::new(NewBlock { block, td: U128::from(td) }),
+            }
+        };
+
+        // higher td, becomes the head of the peer
+        let head = new_block(2, 20);
+        state.on_new_block(peer_id, &head);
+        let peer = &state.active_peers[&peer_id];
+        assert_eq!(peer.best_hash, head.hash);
+        assert_eq!(peer.total_difficulty, U256::from(20u64));
+
+        // lower td, only marked as seen
+        let uncle = new_block(2, 15);
+        state.on_new_block(peer_id, &uncle);
+        let peer = &state.active_peers[&peer_id];
+        assert_eq!(peer.best_hash, head.hash);
+        assert!(peer.blocks.contains(&uncle.hash));
+
+        let hash = H256::random();
+        state.on_new_block_hashes(peer_id, vec![BlockHashNumber { hash, number: 3 }]);
+        assert_eq!(state.active_peers[&peer_id].best_hash, hash);
+    }
 }
@@ -125,6 +125,7 @@ where
             SessionEvent::SessionEstablished {
                 peer_id,
                 remote_addr,
+                client_version,
                 capabilities,
                 version,
                 status,
@@ -142,6 +143,7 @@ where
                 Some(SwarmEvent::SessionEstablished {
                     peer_id,
                     remote_addr,
+                    client_version,
                     capabilities,
                     version,
                     messages,
@@ -151,7 +153,7 @@ where
             }
             SessionEvent::AlreadyConnected { peer_id, remote_addr, direction } => {
                 trace!( target: "net", ?peer_id, ?remote_addr, ?direction, "already connected");
-                self.state.peers_mut().on_already_connected(direction);
+                self.state.peers_mut().on_already_connected(remote_addr, direction);
                 None
             }
             SessionEvent::ValidMessage { peer_id, message } => {
@@ -166,13 +168,28 @@ where
             SessionEvent::OutgoingPendingSessionClosed { remote_addr, peer_id, error } => {
                 Some(SwarmEvent::OutgoingPendingSessionClosed { remote_addr, peer_id, error })
             }
-            SessionEvent::Disconnected { peer_id, remote_addr } => {
+            SessionEvent::Disconnected { peer_id, remote_addr, client_version } => {
                 self.state.on_session_closed(peer_id);
-                Some(SwarmEvent::SessionClosed { peer_id, remote_addr, error: None })
+                Some(SwarmEvent::SessionClosed {
+                    peer_id,
+                    remote_addr,
+                    client_version,
+                    error: None,
+                })
             }
-            SessionEvent::SessionClosedOnConnectionError { peer_id, remote_addr, error } => {
+            SessionEvent::SessionClosedOnConnectionError {
+                peer_id,
+                remote_addr,
+                client_version,
+                error,
+            } => {
                 self.state.on_session_closed(peer_id);
-                Some(SwarmEvent::SessionClosed { peer_id, remote_addr, error: Some(error) })
+                Some(SwarmEvent::SessionClosed {
+                    peer_id,
+                    remote_addr,
+                    client_version,
+                    error: Some(error),
+                })
             }
             SessionEvent::OutgoingConnectionError { remote_addr, peer_id, error } => {
                 Some(SwarmEvent::OutgoingConnectionError { peer_id, remote_addr, error })
@@ -213,6 +230,13 @@ where
                                 DisconnectReason::TooManyPeers,
                             );
                         }
+                        InboundConnectionError::ExceedsIpLimit(limit) => {
+                            trace!(target: "net", %limit, ?remote_addr, "Exceeded incoming connection limit of the ip address; disconnecting");
+                            self.sessions.disconnect_incoming_connection(
+                                stream,
+                                DisconnectReason::TooManyPeers,
+                            );
+                        }
                     }
                     return None
                 }
@@ -225,7 +249,7 @@ where
                         warn!(target: "net", ?err, "Incoming connection rejected");
                         self.state_mut()
                             .peers_mut()
-                            .on_incoming_pending_session_rejected_internally();
+                            .on_incoming_pending_session_rejected_internally(remote_addr.ip());
                     }
                 }
             }
@@ -390,6 +414,8 @@ pub(crate) enum SwarmEvent {
     SessionEstablished {
         peer_id: PeerId,
         remote_addr: SocketAddr,
+        /// The client id the peer announced
+        client_version: String,
         capabilities: Arc<Capabilities>,
         /// negotiated eth version
         version: EthVersion,
@@ -400,6 +426,8 @@ pub(crate) enum SwarmEvent {
     SessionClosed {
         peer_id: PeerId,
         remote_addr: SocketAddr,
+        /// The client id the peer announced
+        client_version: String,
         /// Whether the session was closed due to an error
         error: Option<EthStreamError>,
     },
@@ -1,14 +1,17 @@
 //! A network implementation for testing purposes.
 
 use crate::{
-    error::NetworkError, eth_requests::EthRequestHandler, NetworkConfig, NetworkConfigBuilder,
-    NetworkEvent, NetworkHandle, NetworkManager,
+    error::NetworkError, eth_requests::EthRequestHandler, import::BlockImport,
+    protocol::RlpxSubProtocolHandler, NetworkConfig, NetworkConfigBuilder, NetworkEvent,
+    NetworkHandle, NetworkManager,
 };
 use futures::{FutureExt, StreamExt};
 use pin_project::pin_project;
 use reth_eth_wire::{capability::Capability, DisconnectReason, HelloBuilder};
-use reth_primitives::PeerId;
-use reth_provider::{test_utils::NoopProvider, BlockProvider, HeaderProvider};
+use reth_primitives::{Head, PeerId};
+use reth_provider::{
+    test_utils::NoopProvider, BlockProvider, HeaderProvider, StateProviderFactory,
+};
 use secp256k1::SecretKey;
 use std::{
     fmt,
@@ -122,7 +125,7 @@ where
 
 impl<C> Testnet<C>
 where
-    C: BlockProvider + HeaderProvider + Unpin + 'static,
+    C: BlockProvider + HeaderProvider + StateProviderFactory + Clone + Unpin + 'static,
 {
     /// Spawns the testnet to a separate task
     pub fn spawn(self) -> TestnetHandle<C> {
@@ -176,7 +179,7 @@ impl<C> fmt::Debug for Testnet<C> {
 
 impl<C> Future for Testnet<C>
 where
-    C: BlockProvider + HeaderProvider + Unpin,
+    C: BlockProvider + HeaderProvider + StateProviderFactory + Clone + Unpin + 'static,
 {
     type Output = ();
 
@@ -249,7 +252,7 @@ where
 
 impl<C> Future for Peer<C>
 where
-    C: BlockProvider + HeaderProvider + Unpin,
+    C: BlockProvider + HeaderProvider + StateProviderFactory + Clone + Unpin + 'static,
 {
     type Output = ();
 
@@ -312,6 +315,43 @@ where
         Self { config, client, secret_key }
     }
 
+    /// Initialize the network with the given client id in its `Hello` message.
+    pub fn with_client_version(client: C, client_version: impl Into<String>) -> Self {
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+
+        let builder = Self::network_config_builder(secret_key);
+        let hello_message =
+            HelloBuilder::new(builder.get_peer_id()).client_version(client_version).build();
+        let config = builder.hello_message(hello_message).build(client.clone());
+
+        Self { config, client, secret_key }
+    }
+
+    /// Initialize the network with an additional RLPx subprotocol.
+    pub fn with_rlpx_sub_protocol(client: C, handler: impl RlpxSubProtocolHandler) -> Self {
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let config = Self::network_config_builder(secret_key)
+            .add_rlpx_sub_protocol(handler)
+            .build(client.clone());
+        Self { config, client, secret_key }
+    }
+
+    /// Initialize the network with the given head of the chain.
+    pub fn with_head(client: C, head: Head) -> Self {
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let config = Self::network_config_builder(secret_key).set_head(head).build(client.clone());
+        Self { config, client, secret_key }
+    }
+
+    /// Initialize the network with the given [`BlockImport`] for announced blocks.
+    pub fn with_block_import(client: C, block_import: Box<dyn BlockImport>) -> Self {
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let config = Self::network_config_builder(secret_key)
+            .block_import(block_import)
+            .build(client.clone());
+        Self { config, client, secret_key }
+    }
+
     fn network_config_builder(secret_key: SecretKey) -> NetworkConfigBuilder {
         NetworkConfigBuilder::new(secret_key)
             .listener_addr(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)))
@@ -8,6 +8,7 @@ use crate::{
     NetworkHandle,
 };
 use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
+use rand::seq::IteratorRandom;
 use reth_eth_wire::{
     EthVersion, GetPooledTransactions, NewPooledTransactionHashes, NewPooledTransactionHashes66,
     NewPooledTransactionHashes68, PooledTransactions, Transactions,
@@ -23,7 +24,7 @@ use reth_transaction_pool::{
     ValidPoolTransaction,
 };
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     future::Future,
     num::NonZeroUsize,
     pin::Pin,
@@ -40,6 +41,12 @@ const PEER_TRANSACTION_CACHE_LIMIT: usize = 1024 * 10;
 /// Soft limit for NewPooledTransactions
 const NEW_POOLED_TRANSACTION_HASHES_SOFT_LIMIT: usize = 4096;
 
+/// Soft limit for the byte size of a `PooledTransactions` response on a `GetPooledTransactions`
+/// request.
+///
+/// See also <https://github.com/ethereum/go-ethereum/blob/30602163d5d8321fbc68afdcbbaf2362b2641bde/eth/protocols/eth/handler.go#L40>
+const POOLED_TRANSACTIONS_RESPONSE_SOFT_LIMIT: usize = 2 * 1024 * 1024;
+
 /// The future for inserting a function into the pool
 pub type PoolImportFuture = Pin<Box<dyn Future<Output = PoolResult<TxHash>> + Send + 'static>>;
 
@@ -156,6 +163,10 @@ where
     }
 
     /// Request handler for an incoming request for transactions
+    ///
+    /// Transactions are served in the order they were requested, hashes unknown to the pool are
+    /// skipped. The response is capped at [`POOLED_TRANSACTIONS_RESPONSE_SOFT_LIMIT`], the peer is
+    /// expected to re-request any transactions that didn't make it into the response.
     fn on_get_pooled_transactions(
         &mut self,
         peer_id: PeerId,
@@ -163,12 +174,13 @@ where
         response: oneshot::Sender<RequestResult<PooledTransactions>>,
     ) {
         if let Some(peer) = self.peers.get_mut(&peer_id) {
-            let transactions = self
-                .pool
-                .get_all(request.0)
-                .into_iter()
-                .map(|tx| tx.transaction.to_recovered_transaction().into_signed())
-                .collect::<Vec<_>>();
+            let transactions = pooled_transactions_with_soft_limit(
+                self.pool
+                    .get_all(request.0)
+                    .into_iter()
+                    .map(|tx| tx.transaction.to_recovered_transaction().into_signed()),
+                POOLED_TRANSACTIONS_RESPONSE_SOFT_LIMIT,
+            );
 
             // we sent a response at which point we assume that the peer is aware of the transaction
             peer.transactions.extend(transactions.iter().map(|tx| tx.hash()));
@@ -214,6 +226,10 @@ where
 
     /// Propagate the transactions to all connected peers either as full objects or hashes
     ///
+    /// Full transactions are sent to a random subset of `sqrt(peers)` peers, all other peers only
+    /// receive the hashes. Transactions the peer is already aware of, because it either sent them
+    /// to us or they were already sent to it, are skipped.
+    ///
     /// The message for new pooled hashes depends on the negotiated version of the stream.
     /// See [NewPooledTransactionHashes](NewPooledTransactionHashes)
     fn propagate_transactions(
@@ -222,12 +238,18 @@ where
     ) -> PropagatedTransactions {
         let mut propagated = PropagatedTransactions::default();
 
-        // send full transactions to a fraction fo the connected peers (square root of the total
-        // number of connected peers)
-        let max_num_full = (self.peers.len() as f64).sqrt() as usize + 1;
-
-        // Note: Assuming ~random~ order due to random state of the peers map hasher
-        for (peer_idx, (peer_id, peer)) in self.peers.iter_mut().enumerate() {
+        // send full transactions to a random fraction of the connected peers (square root of the
+        // total number of connected peers)
+        let max_num_full = (self.peers.len() as f64).sqrt() as usize;
+        let full_peers = self
+            .peers
+            .keys()
+            .copied()
+            .choose_multiple(&mut rand::thread_rng(), max_num_full)
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        for (peer_id, peer) in self.peers.iter_mut() {
             // filter all transactions unknown to the peer
             let mut hashes = PooledTransactionsHashesBuilder::new(peer.version);
             let mut full_transactions = Vec::new();
@@ -241,7 +263,7 @@ where
 
             if !full_transactions.is_empty() {
                 // determine whether to send full tx objects or hashes.
-                if peer_idx > max_num_full {
+                if !full_peers.contains(peer_id) {
                     // enforce tx soft limit per message for the (unlikely) event the number of
                     // hashes exceeds it
                     new_pooled_hashes.truncate(NEW_POOLED_TRANSACTION_HASHES_SOFT_LIMIT);
@@ -541,6 +563,27 @@ where
     }
 }
 
+/// Collects the given transactions in order until their accumulated encoded length exceeds the
+/// `soft_limit`.
+///
+/// The transaction that crosses the limit is still included, so that a non empty request is never
+/// answered with an empty response.
+fn pooled_transactions_with_soft_limit(
+    transactions: impl IntoIterator<Item = TransactionSigned>,
+    soft_limit: usize,
+) -> Vec<TransactionSigned> {
+    let mut size = 0;
+    let mut pooled = Vec::new();
+    for tx in transactions {
+        if size >= soft_limit {
+            break
+        }
+        size += tx.length();
+        pooled.push(tx);
+    }
+    pooled
+}
+
 /// A transaction that's about to be propagated to multiple peers.
 struct PropagateTransaction {
     tx_type: u8,
@@ -671,6 +714,7 @@ mod tests {
     use super::*;
     use crate::{NetworkConfigBuilder, NetworkManager};
     use reth_interfaces::sync::{SyncState, SyncStateUpdater};
+    use reth_primitives::{Bytes, Signature, Transaction, TxLegacy};
     use reth_provider::test_utils::NoopProvider;
     use reth_transaction_pool::test_utils::testing_pool;
     use secp256k1::SecretKey;
@@ -705,4 +749,96 @@ mod tests {
 
         assert!(pool.is_empty());
     }
+
+    fn tx_with_input_len(nonce: u64, input_len: usize) -> TransactionSigned {
+        let tx = Transaction::Legacy(TxLegacy {
+            nonce,
+            input: Bytes::from(vec![0u8; input_len]),
+            ..Default::default()
+        });
+        TransactionSigned::from_transaction_and_signature(tx, Signature::default())
+    }
+
+    #[test]
+    fn test_pooled_transactions_soft_limit() {
+        let txs = (0..10).map(|nonce| tx_with_input_len(nonce, 512 * 1024)).collect::<Vec<_>>();
+        let total = txs.iter().map(|tx| tx.length()).sum::<usize>();
+        assert!(total > POOLED_TRANSACTIONS_RESPONSE_SOFT_LIMIT);
+
+        let pooled = pooled_transactions_with_soft_limit(
+            txs.clone(),
+            POOLED_TRANSACTIONS_RESPONSE_SOFT_LIMIT,
+        );
+
+        // a prefix is served, the transaction that crosses the limit is the last one included
+        assert_eq!(pooled.len(), 4);
+        assert_eq!(pooled[..], txs[..4]);
+        let served = pooled.iter().map(|tx| tx.length()).sum::<usize>();
+        assert!(served - pooled.last().unwrap().length() < POOLED_TRANSACTIONS_RESPONSE_SOFT_LIMIT);
+    }
+
+    #[test]
+    fn test_pooled_transactions_soft_limit_single_large_tx() {
+        let tx = tx_with_input_len(0, POOLED_TRANSACTIONS_RESPONSE_SOFT_LIMIT * 2);
+        let pooled = pooled_transactions_with_soft_limit(
+            vec![tx.clone(), tx_with_input_len(1, 0)],
+            POOLED_TRANSACTIONS_RESPONSE_SOFT_LIMIT,
+        );
+        assert_eq!(pooled, vec![tx]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_propagate_full_to_sqrt_peers() {
+        reth_tracing::init_test_tracing();
+
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+
+        let client = NoopProvider::default();
+        let pool = testing_pool();
+        let config = NetworkConfigBuilder::new(secret_key).build(client);
+        let (_handle, _network, mut transactions, _) = NetworkManager::new(config)
+            .await
+            .unwrap()
+            .into_builder()
+            .transactions(pool.clone())
+            .split_with_handle();
+
+        let mut sessions = Vec::new();
+        for _ in 0..9 {
+            let peer_id = PeerId::random();
+            let (to_session_tx, to_session_rx) = mpsc::channel(1);
+            sessions.push(to_session_rx);
+            transactions.peers.insert(
+                peer_id,
+                Peer {
+                    transactions: LruCache::new(
+                        NonZeroUsize::new(PEER_TRANSACTION_CACHE_LIMIT).unwrap(),
+                    ),
+                    request_tx: PeerRequestSender::new(peer_id, to_session_tx),
+                    version: EthVersion::Eth68,
+                },
+            );
+        }
+
+        let tx = Arc::new(tx_with_input_len(0, 0));
+        let hash = tx.hash();
+        let propagated =
+            transactions.propagate_transactions(vec![PropagateTransaction::new(Arc::clone(&tx))]);
+
+        let kinds = propagated.0.get(&hash).unwrap();
+        assert_eq!(kinds.len(), 9);
+        let num_full = kinds.iter().filter(|kind| matches!(kind, PropagateKind::Full(_))).count();
+        assert_eq!(num_full, 3);
+        let peers = kinds
+            .iter()
+            .map(|kind| match kind {
+                PropagateKind::Full(peer) | PropagateKind::Hash(peer) => *peer,
+            })
+            .collect::<HashSet<_>>();
+        assert_eq!(peers.len(), 9);
+
+        // all peers are now aware of the transaction
+        let propagated = transactions.propagate_transactions(vec![PropagateTransaction::new(tx)]);
+        assert!(propagated.0.is_empty());
+    }
 }
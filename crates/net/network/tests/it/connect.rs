@@ -16,7 +16,7 @@ use reth_network::{
     },
     NetworkConfigBuilder, NetworkEvent, NetworkManager, PeersConfig,
 };
-use reth_network_api::{NetworkInfo, Peers, PeersInfo};
+use reth_network_api::{ClientKind, NetworkInfo, Peers, PeersInfo};
 use reth_primitives::{HeadersDirection, NodeRecord, PeerId};
 use reth_provider::test_utils::NoopProvider;
 use reth_transaction_pool::test_utils::testing_pool;
@@ -196,6 +196,42 @@ async fn test_get_peer_by_id() {
     assert!(peer.is_none());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_peer_client_versions() {
+    reth_tracing::init_test_tracing();
+    let mut net = Testnet::default();
+
+    let client = NoopProvider::default();
+    let client_versions = [
+        ("Geth/v1.11.6-stable-ea9e62ca/linux-amd64/go1.20.3", ClientKind::Geth),
+        ("Nethermind/v1.17.3+6d8e8d5b/linux-x64/dotnet7.0.4", ClientKind::Nethermind),
+        ("erigon/v2.42.0-stable-beb97784/linux-amd64/go1.20.2", ClientKind::Erigon),
+        ("besu/v23.1.2/linux-x86_64/openjdk-java-17", ClientKind::Besu),
+        ("\u{1f980}", ClientKind::Other),
+    ];
+    let mut configs = vec![PeerConfig::default()];
+    for (client_version, _) in client_versions {
+        configs.push(PeerConfig::with_client_version(client, client_version));
+    }
+    net.extend_peer_with_config(configs).await.unwrap();
+
+    let handles = net.handles().collect::<Vec<_>>();
+    let _handle = net.spawn();
+
+    let mut listener0 = NetworkEventStream::new(handles[0].event_listener());
+    for handle in &handles[1..] {
+        handles[0].add_peer(*handle.peer_id(), handle.local_addr());
+        let _ = listener0.next_session_established().await.unwrap();
+    }
+
+    for (handle, (client_version, kind)) in handles[1..].iter().zip(client_versions) {
+        let peer = handles[0].get_peer_by_id(*handle.peer_id()).await.unwrap().unwrap();
+        assert_eq!(peer.client_version, client_version);
+        assert_eq!(peer.client_kind(), kind);
+    }
+    assert_eq!(handles[0].get_peers().await.unwrap().len(), client_versions.len());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_connect_with_boot_nodes() {
@@ -578,3 +614,33 @@ async fn test_disconnect_incoming_when_exceeded_incoming_connections() {
 
     assert_eq!(handle.num_connected_peers(), 0);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_disconnect_incoming_when_exceeded_ip_connections() {
+    reth_tracing::init_test_tracing();
+
+    // all peers of the testnet connect from the loopback address
+    let net = Testnet::create(3).await;
+    let (reth_p2p, reth_disc) = unused_tcp_udp();
+    let secret_key = SecretKey::new(&mut rand::thread_rng());
+    let peers_config = PeersConfig::default().with_max_inbound_per_ip(2);
+
+    let config = NetworkConfigBuilder::new(secret_key)
+        .listener_addr(reth_p2p)
+        .discovery_addr(reth_disc)
+        .peer_config(peers_config)
+        .build(NoopProvider::default());
+    let network = NetworkManager::new(config).await.unwrap();
+    let handle = network.handle().clone();
+
+    for other_peer_handle in net.handles() {
+        other_peer_handle.add_peer(*handle.peer_id(), handle.local_addr());
+    }
+
+    tokio::task::spawn(network);
+    let _handle = net.spawn();
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    assert_eq!(handle.num_connected_peers(), 2);
+}
@@ -0,0 +1,95 @@
+//! Tests for `NewBlock` gossip before and after the merge
+
+use reth_eth_wire::NewBlock;
+use reth_interfaces::{blockchain_tree::BlockchainTreeEngine, Error};
+use reth_network::{
+    test_utils::{NetworkEventStream, PeerConfig, Testnet},
+    TreeBlockImport,
+};
+use reth_network_api::{NetworkInfo, Peers};
+use reth_primitives::{Block, BlockNumber, Head, Header, SealedBlock, H256, MAINNET, U128, U256};
+use reth_provider::test_utils::NoopProvider;
+use tokio::sync::mpsc;
+
+/// A tree that accepts all blocks and reports the hashes of the inserted blocks.
+#[derive(Debug, Clone)]
+struct RecordingTree(mpsc::UnboundedSender<H256>);
+
+impl BlockchainTreeEngine for RecordingTree {
+    fn insert_block(&self, block: SealedBlock) -> Result<bool, Error> {
+        let _ = self.0.send(block.hash());
+        Ok(true)
+    }
+
+    fn update_canonical_hashes(&self, _unwound_to: BlockNumber) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+fn new_block() -> (NewBlock, H256) {
+    let block = Block { header: Header { number: 1, ..Default::default() }, ..Default::default() };
+    let hash = block.clone().seal_slow().hash();
+    (NewBlock { block, td: U128::from(1u64) }, hash)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_import_new_block_pre_merge() {
+    reth_tracing::init_test_tracing();
+
+    let mut net = Testnet::create(1).await;
+    let (tx, mut inserted) = mpsc::unbounded_channel();
+    let import = TreeBlockImport::new(RecordingTree(tx));
+    let receiver = PeerConfig::with_block_import(NoopProvider::default(), Box::new(import));
+    net.add_peer_with_config(receiver).await.unwrap();
+
+    let mut handles = net.handles();
+    let sender = handles.next().unwrap();
+    let receiver = handles.next().unwrap();
+    drop(handles);
+    assert!(!sender.mode().is_stake());
+    assert!(!receiver.mode().is_stake());
+
+    let _handle = net.spawn();
+
+    let mut events = NetworkEventStream::new(sender.event_listener());
+    sender.add_peer(*receiver.peer_id(), receiver.local_addr());
+    assert_eq!(events.peer_added_and_established().await.unwrap(), *receiver.peer_id());
+
+    let (block, hash) = new_block();
+    sender.announce_block(block, hash);
+
+    assert_eq!(inserted.recv().await.unwrap(), hash);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reject_new_block_post_merge() {
+    reth_tracing::init_test_tracing();
+
+    let mut net = Testnet::create(1).await;
+    // the total difficulty of the head reached the terminal total difficulty of mainnet
+    let head = Head {
+        hash: MAINNET.genesis_hash(),
+        total_difficulty: U256::from(58_750_000_000_000_000_000_000_u128),
+        ..Default::default()
+    };
+    net.add_peer_with_config(PeerConfig::with_head(NoopProvider::default(), head)).await.unwrap();
+
+    let mut handles = net.handles();
+    let sender = handles.next().unwrap();
+    let receiver = handles.next().unwrap();
+    drop(handles);
+    assert!(!sender.mode().is_stake());
+    assert!(receiver.mode().is_stake());
+
+    let _handle = net.spawn();
+
+    let mut events = NetworkEventStream::new(receiver.event_listener());
+    sender.add_peer(*receiver.peer_id(), receiver.local_addr());
+    assert_eq!(events.next_session_established().await.unwrap(), *sender.peer_id());
+
+    let (block, hash) = new_block();
+    sender.announce_block(block, hash);
+
+    let (peer_id, _) = events.next_session_closed().await.unwrap();
+    assert_eq!(peer_id, *sender.peer_id());
+}
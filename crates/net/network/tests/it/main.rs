@@ -1,4 +1,6 @@
 mod connect;
+mod gossip;
+mod multiplex;
 mod requests;
 mod session;
 
@@ -0,0 +1,136 @@
+//! Testing custom RLPx subprotocols multiplexed alongside `eth`
+
+use futures::StreamExt;
+use reth_eth_wire::{
+    capability::Capability,
+    protocol::{Protocol, ProtocolConnection},
+};
+use reth_interfaces::p2p::headers::client::{HeadersClient, HeadersRequest};
+use reth_network::{
+    protocol::RlpxSubProtocolHandler,
+    test_utils::{NetworkEventStream, PeerConfig, Testnet},
+    Direction,
+};
+use reth_network_api::{NetworkInfo, Peers};
+use reth_primitives::{
+    bytes::{BufMut, Bytes, BytesMut},
+    Header, HeadersDirection, PeerId, H256,
+};
+use reth_provider::test_utils::MockEthProvider;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// A trivial ping/pong protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PingPongMessage {
+    Ping(u8),
+    Pong(u8),
+}
+
+impl PingPongMessage {
+    fn protocol() -> Protocol {
+        Protocol::new(Capability::new("ping".into(), 1), 2)
+    }
+
+    fn encode(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(2);
+        match self {
+            PingPongMessage::Ping(val) => {
+                buf.put_u8(0x00);
+                buf.put_u8(*val);
+            }
+            PingPongMessage::Pong(val) => {
+                buf.put_u8(0x01);
+                buf.put_u8(*val);
+            }
+        }
+        buf.freeze()
+    }
+
+    fn decode(buf: &[u8]) -> Option<Self> {
+        match buf {
+            [0x00, val] => Some(PingPongMessage::Ping(*val)),
+            [0x01, val] => Some(PingPongMessage::Pong(*val)),
+            _ => None,
+        }
+    }
+}
+
+/// Forwards all new connections of the ping/pong protocol
+struct PingPongHandler {
+    conns: mpsc::UnboundedSender<(PeerId, ProtocolConnection)>,
+}
+
+impl RlpxSubProtocolHandler for PingPongHandler {
+    fn protocol(&self) -> Protocol {
+        PingPongMessage::protocol()
+    }
+
+    fn on_connection(&self, peer_id: PeerId, _direction: Direction, conn: ProtocolConnection) {
+        let _ = self.conns.send((peer_id, conn));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_ping_pong_subprotocol() {
+    reth_tracing::init_test_tracing();
+    let mock_provider = Arc::new(MockEthProvider::default());
+
+    let mut net = Testnet::default();
+
+    let (conns0_tx, mut conns0) = mpsc::unbounded_channel();
+    let (conns1_tx, mut conns1) = mpsc::unbounded_channel();
+    net.extend_peer_with_config(vec![
+        PeerConfig::with_rlpx_sub_protocol(
+            mock_provider.clone(),
+            PingPongHandler { conns: conns0_tx },
+        ),
+        PeerConfig::with_rlpx_sub_protocol(
+            mock_provider.clone(),
+            PingPongHandler { conns: conns1_tx },
+        ),
+    ])
+    .await
+    .unwrap();
+
+    // install request handlers
+    net.for_each_mut(|peer| peer.install_request_handler());
+
+    let handle0 = net.peers()[0].handle();
+    let mut events0 = NetworkEventStream::new(handle0.event_listener());
+
+    let handle1 = net.peers()[1].handle();
+
+    let _handle = net.spawn();
+
+    let fetch0 = handle0.fetch_client().await.unwrap();
+
+    handle0.add_peer(*handle1.peer_id(), handle1.local_addr());
+    let connected = events0.next_session_established().await.unwrap();
+    assert_eq!(connected, *handle1.peer_id());
+
+    let (peer0, mut conn0) = conns0.recv().await.unwrap();
+    let (peer1, mut conn1) = conns1.recv().await.unwrap();
+    assert_eq!(peer0, *handle1.peer_id());
+    assert_eq!(peer1, *handle0.peer_id());
+
+    for val in 0..10 {
+        conn0.send(PingPongMessage::Ping(val).encode()).await.unwrap();
+        let msg = conn1.next().await.unwrap();
+        assert_eq!(PingPongMessage::decode(&msg), Some(PingPongMessage::Ping(val)));
+
+        conn1.send(PingPongMessage::Pong(val).encode()).await.unwrap();
+        let msg = conn0.next().await.unwrap();
+        assert_eq!(PingPongMessage::decode(&msg), Some(PingPongMessage::Pong(val)));
+    }
+
+    // eth requests are still served on the same connection
+    let header = Header { number: 1, ..Default::default() };
+    let hash = H256::random();
+    mock_provider.add_header(hash, header.clone());
+
+    let req = HeadersRequest { start: hash.into(), limit: 1, direction: HeadersDirection::Falling };
+    let res = fetch0.get_headers(req).await;
+    assert!(res.is_ok(), "{res:?}");
+    assert_eq!(res.unwrap().1, vec![header]);
+}
@@ -1,19 +1,26 @@
 //! Tests for eth related requests
 
 use rand::Rng;
-use reth_eth_wire::BlockBody;
+use reth_eth_wire::{capability::Capability, BlockBody, EthVersion, GetNodeData, GetReceipts};
 use reth_interfaces::p2p::{
     bodies::client::BodiesClient,
     headers::client::{HeadersClient, HeadersRequest},
 };
-use reth_network::test_utils::{NetworkEventStream, Testnet};
+use reth_network::{
+    test_utils::{NetworkEventStream, PeerConfig, Testnet},
+    PeerRequest,
+};
 use reth_network_api::{NetworkInfo, Peers};
 use reth_primitives::{
-    Block, Bytes, Header, HeadersDirection, Signature, Transaction, TransactionKind,
-    TransactionSigned, TxEip2930, H256, U256,
+    keccak256, Address, Block, Bytes, Header, HeadersDirection, Log, Receipt, Signature,
+    Transaction, TransactionKind, TransactionSigned, TxEip2930, TxType, H256, U256,
+};
+use reth_provider::{
+    test_utils::{ExtendedAccount, MockEthProvider},
+    ReceiptProvider,
 };
-use reth_provider::test_utils::MockEthProvider;
 use std::sync::Arc;
+use tokio::sync::oneshot;
 
 /// Returns a new [`TransactionSigned`] with some random parameters
 pub fn rng_transaction(rng: &mut impl rand::RngCore) -> TransactionSigned {
@@ -121,3 +128,115 @@ async fn test_get_header() {
         assert_eq!(headers[0], header);
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_receipts() {
+    reth_tracing::init_test_tracing();
+    let mut rng = rand::thread_rng();
+    let mock_provider = Arc::new(MockEthProvider::default());
+
+    let mut net = Testnet::create_with(2, mock_provider.clone()).await;
+
+    // install request handlers
+    net.for_each_mut(|peer| peer.install_request_handler());
+
+    let handle0 = net.peers()[0].handle();
+    let mut events0 = NetworkEventStream::new(handle0.event_listener());
+
+    let handle1 = net.peers()[1].handle();
+
+    let _handle = net.spawn();
+
+    handle0.add_peer(*handle1.peer_id(), handle1.local_addr());
+    let connected = events0.next_session_established().await.unwrap();
+    assert_eq!(connected, *handle1.peer_id());
+
+    // the receipts of some blocks
+    let mut block_hashes = Vec::new();
+    for _ in 0..10 {
+        let block_hash = H256::random();
+        let receipts = (0..rng.gen_range(0..5))
+            .map(|i| Receipt {
+                tx_type: TxType::EIP1559,
+                success: rng.gen(),
+                cumulative_gas_used: 21_000 * (i + 1),
+                logs: vec![Log {
+                    address: Address::random(),
+                    topics: vec![H256::random()],
+                    data: Bytes::from(vec![1, 2, 3]),
+                }],
+            })
+            .collect();
+        mock_provider.add_receipts(block_hash, receipts);
+        block_hashes.push(block_hash);
+    }
+
+    // sync the receipts from the peer, including the receipts of an unknown block
+    let mut request = block_hashes.clone();
+    request.push(H256::random());
+    let (tx, rx) = oneshot::channel();
+    handle0.send_request(
+        *handle1.peer_id(),
+        PeerRequest::GetReceipts { request: GetReceipts(request), response: tx },
+    );
+    let receipts = rx.await.unwrap().unwrap().0;
+    assert_eq!(receipts.len(), block_hashes.len() + 1);
+    assert!(receipts.last().unwrap().is_empty());
+
+    let synced = MockEthProvider::default();
+    for (block_hash, receipts) in block_hashes.iter().zip(receipts) {
+        synced.add_receipts(*block_hash, receipts.into_iter().map(|r| r.receipt).collect());
+    }
+    for block_hash in block_hashes {
+        assert_eq!(
+            synced.receipts_by_block(block_hash.into()).unwrap(),
+            mock_provider.receipts_by_block(block_hash.into()).unwrap()
+        );
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_node_data() {
+    reth_tracing::init_test_tracing();
+    let mock_provider = Arc::new(MockEthProvider::default());
+
+    // `GetNodeData` was removed in eth/67
+    let mut net = Testnet::create_with(0, mock_provider.clone()).await;
+    let capabilities = vec![Capability::new("eth".into(), EthVersion::Eth66 as usize)];
+    for _ in 0..2 {
+        let config = PeerConfig::with_capabilities(mock_provider.clone(), capabilities.clone());
+        net.add_peer_with_config(config).await.unwrap();
+    }
+
+    // install request handlers
+    net.for_each_mut(|peer| peer.install_request_handler());
+
+    let handle0 = net.peers()[0].handle();
+    let mut events0 = NetworkEventStream::new(handle0.event_listener());
+
+    let handle1 = net.peers()[1].handle();
+
+    let _handle = net.spawn();
+
+    handle0.add_peer(*handle1.peer_id(), handle1.local_addr());
+    let connected = events0.next_session_established().await.unwrap();
+    assert_eq!(connected, *handle1.peer_id());
+
+    let code = Bytes::from(vec![0x60, 0x00, 0x60, 0x00, 0xf3]);
+    mock_provider.add_account(
+        Address::random(),
+        ExtendedAccount::new(0, U256::ZERO).with_bytecode(code.clone()),
+    );
+
+    // contract codes are served, unknown hashes are empty
+    let (tx, rx) = oneshot::channel();
+    handle0.send_request(
+        *handle1.peer_id(),
+        PeerRequest::GetNodeData {
+            request: GetNodeData(vec![keccak256(&code), H256::random()]),
+            response: tx,
+        },
+    );
+    let node_data = rx.await.unwrap().unwrap().0;
+    assert_eq!(node_data, vec![code, Bytes::default()]);
+}
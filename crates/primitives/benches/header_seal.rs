@@ -0,0 +1,49 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use pprof::criterion::{Output, PProfProfiler};
+use rand::Rng;
+use reth_primitives::{Header, H256};
+
+/// The number of headers the headers stage writes per batch by default.
+const BATCH_SIZE: u64 = 10_000;
+
+/// Benchmarks the hot loop of the headers stage: the downloaded headers are sealed once and then
+/// split into the headers and hashes that are written to the database.
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let headers = (0..BATCH_SIZE)
+        .map(|number| Header { number, parent_hash: H256(rng.gen()), ..Default::default() })
+        .collect::<Vec<_>>();
+    let sealed = headers.iter().cloned().map(Header::seal_slow).collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("Headers");
+    group.bench_function("seal and split", |b| {
+        b.iter_batched(
+            || headers.clone(),
+            |headers| {
+                for header in headers {
+                    black_box(header.seal_slow().split());
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("split sealed", |b| {
+        b.iter_batched(
+            || sealed.clone(),
+            |sealed| {
+                for header in sealed {
+                    black_box(header.split());
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
+    targets = criterion_benchmark
+}
+criterion_main!(benches);
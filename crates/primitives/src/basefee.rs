@@ -0,0 +1,168 @@
+//! Base fee derivation of [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559).
+
+use crate::constants::{EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR, EIP1559_ELASTICITY_MULTIPLIER};
+use serde::{Deserialize, Serialize};
+
+/// The parameters the base fee of a block is derived from its parent with.
+///
+/// Chains like Optimism change the base fee slower and allow larger blocks than Ethereum. Both
+/// parameters are divisors and must not be zero, parameters with a zero are rejected when they are
+/// deserialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", try_from = "RawBaseFeeParams")]
+pub struct BaseFeeParams {
+    /// The base fee changes by at most `1 / max_change_denominator` between two blocks.
+    pub max_change_denominator: u64,
+    /// The gas target of a block is its gas limit divided by the elasticity multiplier.
+    pub elasticity_multiplier: u64,
+}
+
+impl BaseFeeParams {
+    /// Creates the parameters, fails if one of them is zero.
+    pub fn new(
+        max_change_denominator: u64,
+        elasticity_multiplier: u64,
+    ) -> Result<Self, InvalidBaseFeeParams> {
+        let params = Self { max_change_denominator, elasticity_multiplier };
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Checks that none of the parameters is zero.
+    pub fn validate(&self) -> Result<(), InvalidBaseFeeParams> {
+        if self.max_change_denominator == 0 {
+            return Err(InvalidBaseFeeParams::ZeroMaxChangeDenominator)
+        }
+        if self.elasticity_multiplier == 0 {
+            return Err(InvalidBaseFeeParams::ZeroElasticityMultiplier)
+        }
+        Ok(())
+    }
+
+    /// The parameters of Ethereum.
+    pub const fn ethereum() -> Self {
+        Self {
+            max_change_denominator: EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR,
+            elasticity_multiplier: EIP1559_ELASTICITY_MULTIPLIER,
+        }
+    }
+
+    /// Returns the base fee of the child of a block with the given gas used, gas limit and base
+    /// fee.
+    pub fn next_block_base_fee(&self, gas_used: u64, gas_limit: u64, base_fee: u64) -> u64 {
+        let gas_target = gas_limit / self.elasticity_multiplier;
+
+        if gas_used == gas_target {
+            return base_fee
+        }
+        if gas_used > gas_target {
+            let gas_used_delta = gas_used - gas_target;
+            let base_fee_delta = std::cmp::max(
+                1,
+                base_fee as u128 * gas_used_delta as u128 /
+                    gas_target as u128 /
+                    self.max_change_denominator as u128,
+            );
+            base_fee + (base_fee_delta as u64)
+        } else {
+            let gas_used_delta = gas_target - gas_used;
+            let base_fee_per_gas_delta = base_fee as u128 * gas_used_delta as u128 /
+                gas_target as u128 /
+                self.max_change_denominator as u128;
+
+            base_fee.saturating_sub(base_fee_per_gas_delta as u64)
+        }
+    }
+}
+
+impl Default for BaseFeeParams {
+    fn default() -> Self {
+        Self::ethereum()
+    }
+}
+
+/// The fields of [BaseFeeParams] before they are validated.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBaseFeeParams {
+    max_change_denominator: u64,
+    elasticity_multiplier: u64,
+}
+
+impl TryFrom<RawBaseFeeParams> for BaseFeeParams {
+    type Error = InvalidBaseFeeParams;
+
+    fn try_from(raw: RawBaseFeeParams) -> Result<Self, Self::Error> {
+        Self::new(raw.max_change_denominator, raw.elasticity_multiplier)
+    }
+}
+
+/// Error of [BaseFeeParams] that would divide by zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidBaseFeeParams {
+    /// The max change denominator is zero.
+    #[error("the base fee max change denominator must not be zero")]
+    ZeroMaxChangeDenominator,
+    /// The elasticity multiplier is zero.
+    #[error("the base fee elasticity multiplier must not be zero")]
+    ZeroElasticityMultiplier,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_FEE: u64 = 1_000_000_000;
+    const GAS_LIMIT: u64 = 30_000_000;
+
+    #[test]
+    fn base_fee_at_gas_target() {
+        let params = BaseFeeParams::ethereum();
+        assert_eq!(params.next_block_base_fee(GAS_LIMIT / 2, GAS_LIMIT, BASE_FEE), BASE_FEE);
+        // one gas above or below the target moves the base fee
+        let target = GAS_LIMIT / 2;
+        assert_eq!(params.next_block_base_fee(target + 1, GAS_LIMIT, BASE_FEE), BASE_FEE + 8);
+        assert_eq!(params.next_block_base_fee(target - 1, GAS_LIMIT, BASE_FEE), BASE_FEE - 8);
+    }
+
+    #[test]
+    fn base_fee_of_empty_block() {
+        let params = BaseFeeParams::ethereum();
+        assert_eq!(params.next_block_base_fee(0, GAS_LIMIT, BASE_FEE), 875_000_000);
+        assert_eq!(params.next_block_base_fee(0, GAS_LIMIT, 7), 7);
+        assert_eq!(params.next_block_base_fee(0, GAS_LIMIT, 0), 0);
+    }
+
+    #[test]
+    fn base_fee_of_full_block() {
+        let params = BaseFeeParams::ethereum();
+        assert_eq!(params.next_block_base_fee(GAS_LIMIT, GAS_LIMIT, BASE_FEE), 1_125_000_000);
+        // the base fee increases by at least one
+        assert_eq!(params.next_block_base_fee(GAS_LIMIT, GAS_LIMIT, 7), 8);
+    }
+
+    #[test]
+    fn base_fee_with_custom_params() {
+        let params = BaseFeeParams { max_change_denominator: 50, elasticity_multiplier: 6 };
+        assert_eq!(params.next_block_base_fee(GAS_LIMIT / 6, GAS_LIMIT, BASE_FEE), BASE_FEE);
+        assert_eq!(params.next_block_base_fee(GAS_LIMIT, GAS_LIMIT, BASE_FEE), 1_100_000_000);
+        assert_eq!(params.next_block_base_fee(0, GAS_LIMIT, BASE_FEE), 980_000_000);
+    }
+
+    #[test]
+    fn zero_params_are_rejected() {
+        assert_eq!(BaseFeeParams::new(8, 2), Ok(BaseFeeParams::ethereum()));
+        assert_eq!(BaseFeeParams::new(0, 2), Err(InvalidBaseFeeParams::ZeroMaxChangeDenominator));
+        assert_eq!(BaseFeeParams::new(8, 0), Err(InvalidBaseFeeParams::ZeroElasticityMultiplier));
+
+        let params: BaseFeeParams =
+            serde_json::from_str(r#"{"maxChangeDenominator":50,"elasticityMultiplier":6}"#)
+                .unwrap();
+        assert_eq!(params, BaseFeeParams { max_change_denominator: 50, elasticity_multiplier: 6 });
+        let err = serde_json::from_str::<BaseFeeParams>(
+            r#"{"maxChangeDenominator":50,"elasticityMultiplier":0}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("elasticity multiplier must not be zero"));
+    }
+}
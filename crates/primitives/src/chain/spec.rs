@@ -1,10 +1,10 @@
 use crate::{
-    constants::{EIP1559_INITIAL_BASE_FEE, EMPTY_WITHDRAWALS},
+    constants::{BLOCKHASH_WINDOW, EIP1559_INITIAL_BASE_FEE, EMPTY_WITHDRAWALS},
     forkid::ForkFilterKey,
     header::Head,
     proofs::genesis_state_root,
-    BlockNumber, Chain, ForkFilter, ForkHash, ForkId, Genesis, GenesisAccount, Hardfork, Header,
-    H160, H256, U256,
+    BaseFeeParams, BlockNumber, Chain, ForkFilter, ForkHash, ForkId, Genesis, GenesisAccount,
+    Hardfork, Header, H160, H256, U256,
 };
 use ethers_core::utils::Genesis as EthersGenesis;
 use hex_literal::hex;
@@ -43,6 +43,7 @@ pub static MAINNET: Lazy<ChainSpec> = Lazy::new(|| ChainSpec {
             },
         ),
     ]),
+    base_fee_params: BaseFeeParams::ethereum(),
 });
 
 /// The Goerli spec
@@ -63,6 +64,7 @@ pub static GOERLI: Lazy<ChainSpec> = Lazy::new(|| ChainSpec {
             ForkCondition::TTD { fork_block: None, total_difficulty: U256::from(10_790_000) },
         ),
     ]),
+    base_fee_params: BaseFeeParams::ethereum(),
 });
 
 /// The Sepolia spec
@@ -95,6 +97,7 @@ pub static SEPOLIA: Lazy<ChainSpec> = Lazy::new(|| ChainSpec {
         ),
         (Hardfork::Shanghai, ForkCondition::Timestamp(1677557088)),
     ]),
+    base_fee_params: BaseFeeParams::ethereum(),
 });
 
 /// An Ethereum chain specification.
@@ -121,6 +124,10 @@ pub struct ChainSpec {
 
     /// The active hard forks and their activation conditions
     pub hardforks: BTreeMap<Hardfork, ForkCondition>,
+
+    /// The parameters the base fee of a block is derived from its parent with
+    #[serde(default)]
+    pub base_fee_params: BaseFeeParams,
 }
 
 impl ChainSpec {
@@ -138,9 +145,10 @@ impl ChainSpec {
 
     /// Get the header for the genesis block.
     pub fn genesis_header(&self) -> Header {
-        // If London is activated at genesis, we set the initial base fee as per EIP-1559.
+        // If London is activated at genesis, we set the initial base fee as per EIP-1559, unless
+        // the genesis sets its own.
         let base_fee_per_gas = if self.fork(Hardfork::London).active_at_block(0) {
-            Some(EIP1559_INITIAL_BASE_FEE)
+            Some(self.genesis.base_fee_per_gas.unwrap_or(EIP1559_INITIAL_BASE_FEE))
         } else {
             None
         };
@@ -178,6 +186,31 @@ impl ChainSpec {
         }
     }
 
+    /// Returns the number of most recent block hashes the `BLOCKHASH` opcode can access on this
+    /// chain.
+    pub fn block_hash_window(&self) -> u64 {
+        BLOCKHASH_WINDOW
+    }
+
+    /// Returns the base fee of the child of the given block, or `None` if EIP-1559 is not active at
+    /// the child.
+    ///
+    /// The first London block has the initial base fee, all later blocks derive their base fee
+    /// from the parent with the [BaseFeeParams] of the chain.
+    pub fn next_block_base_fee(&self, parent: &Header) -> Option<u64> {
+        let london = self.fork(Hardfork::London);
+        let number = parent.number + 1;
+        if !london.active_at_block(number) {
+            return None
+        }
+        if london.transitions_at_block(number) {
+            return Some(EIP1559_INITIAL_BASE_FEE)
+        }
+        parent.base_fee_per_gas.map(|base_fee| {
+            self.base_fee_params.next_block_base_fee(parent.gas_used, parent.gas_limit, base_fee)
+        })
+    }
+
     /// Returns the forks in this specification and their activation conditions.
     pub fn hardforks(&self) -> &BTreeMap<Hardfork, ForkCondition> {
         &self.hardforks
@@ -256,6 +289,7 @@ impl From<EthersGenesis> for ChainSpec {
             mix_hash: genesis.mix_hash.0.into(),
             coinbase: genesis.coinbase.0.into(),
             extra_data: genesis.extra_data.0.into(),
+            base_fee_per_gas: None,
             alloc,
         };
 
@@ -306,6 +340,7 @@ impl From<EthersGenesis> for ChainSpec {
             genesis: genesis_block,
             genesis_hash: None,
             hardforks,
+            base_fee_params: BaseFeeParams::ethereum(),
         }
     }
 }
@@ -347,6 +382,7 @@ pub struct ChainSpecBuilder {
     chain: Option<Chain>,
     genesis: Option<Genesis>,
     hardforks: BTreeMap<Hardfork, ForkCondition>,
+    base_fee_params: BaseFeeParams,
 }
 
 impl ChainSpecBuilder {
@@ -356,6 +392,7 @@ impl ChainSpecBuilder {
             chain: Some(MAINNET.chain),
             genesis: Some(MAINNET.genesis.clone()),
             hardforks: MAINNET.hardforks.clone(),
+            base_fee_params: MAINNET.base_fee_params,
         }
     }
 
@@ -371,6 +408,12 @@ impl ChainSpecBuilder {
         self
     }
 
+    /// Set the parameters of the base fee derivation.
+    pub fn base_fee_params(mut self, base_fee_params: BaseFeeParams) -> Self {
+        self.base_fee_params = base_fee_params;
+        self
+    }
+
     /// Add the given fork with the given activation condition to the spec.
     pub fn with_fork(mut self, fork: Hardfork, condition: ForkCondition) -> Self {
         self.hardforks.insert(fork, condition);
@@ -456,18 +499,29 @@ impl ChainSpecBuilder {
         self
     }
 
+    /// Enable Prague at genesis.
+    pub fn prague_activated(mut self) -> Self {
+        self = self.shanghai_activated();
+        self.hardforks.insert(Hardfork::Prague, ForkCondition::Timestamp(0));
+        self
+    }
+
     /// Build the resulting [`ChainSpec`].
     ///
     /// # Panics
     ///
     /// This function panics if the chain ID and genesis is not set ([`Self::chain`] and
-    /// [`Self::genesis`])
+    /// [`Self::genesis`]), or if one of the [`BaseFeeParams`] is zero.
     pub fn build(self) -> ChainSpec {
+        if let Err(err) = self.base_fee_params.validate() {
+            panic!("Invalid base fee params: {err}")
+        }
         ChainSpec {
             chain: self.chain.expect("The chain is required"),
             genesis: self.genesis.expect("The genesis is required"),
             genesis_hash: None,
             hardforks: self.hardforks,
+            base_fee_params: self.base_fee_params,
         }
     }
 }
@@ -478,6 +532,7 @@ impl From<&ChainSpec> for ChainSpecBuilder {
             chain: Some(value.chain),
             genesis: Some(value.genesis.clone()),
             hardforks: value.hardforks.clone(),
+            base_fee_params: value.base_fee_params,
         }
     }
 }
@@ -597,8 +652,9 @@ impl ForkCondition {
 #[cfg(test)]
 mod tests {
     use crate::{
-        AllGenesisFormats, Chain, ChainSpec, ChainSpecBuilder, ForkCondition, ForkHash, ForkId,
-        Genesis, Hardfork, Head, GOERLI, H256, MAINNET, SEPOLIA, U256,
+        constants::EIP1559_INITIAL_BASE_FEE, AllGenesisFormats, BaseFeeParams, Chain, ChainSpec,
+        ChainSpecBuilder, ForkCondition, ForkHash, ForkId, Genesis, Hardfork, Head, Header, GOERLI,
+        H256, MAINNET, SEPOLIA, U256,
     };
     use bytes::BytesMut;
     use ethers_core::types as EtherType;
@@ -1155,4 +1211,23 @@ mod tests {
         let hash = chainspec.genesis_header().hash_slow();
         assert_eq!(hash, expected_hash);
     }
+
+    #[test]
+    fn next_block_base_fee_around_london() {
+        let spec = ChainSpecBuilder::mainnet()
+            .with_fork(Hardfork::London, ForkCondition::Block(10))
+            .base_fee_params(BaseFeeParams { max_change_denominator: 50, elasticity_multiplier: 6 })
+            .build();
+        let parent = |number, base_fee_per_gas| Header {
+            number,
+            gas_limit: 30_000_000,
+            base_fee_per_gas,
+            ..Default::default()
+        };
+
+        assert_eq!(spec.next_block_base_fee(&parent(8, None)), None);
+        assert_eq!(spec.next_block_base_fee(&parent(9, None)), Some(EIP1559_INITIAL_BASE_FEE));
+        // an empty block decreases the base fee by the configured max change
+        assert_eq!(spec.next_block_base_fee(&parent(10, Some(1_000_000_000))), Some(980_000_000));
+    }
 }
@@ -1,6 +1,6 @@
 //! Ethereum protocol-related constants
 
-use crate::H256;
+use crate::{H160, H256};
 use hex_literal::hex;
 
 /// The first four bytes of the call data for a function call specifies the function to be called.
@@ -15,6 +15,17 @@ pub const EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
 /// Elasticity multiplier as defined in [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559)
 pub const EIP1559_ELASTICITY_MULTIPLIER: u64 = 2;
 
+/// Number of most recent block hashes the `BLOCKHASH` opcode can access.
+pub const BLOCKHASH_WINDOW: u64 = 256;
+
+/// Address of the system contract that keeps the most recent block hashes as defined in
+/// [EIP-2935](https://eips.ethereum.org/EIPS/eip-2935)
+pub const HISTORY_STORAGE_ADDRESS: H160 = H160(hex!("0000f90827f1c53a10cb7a02335b175320002935"));
+
+/// Number of block hashes kept by the history contract of
+/// [EIP-2935](https://eips.ethereum.org/EIPS/eip-2935), its slots are used as a ring buffer.
+pub const HISTORY_SERVE_WINDOW: u64 = 8191;
+
 /// Multiplier for converting gwei to wei.
 pub const GWEI_TO_WEI: u64 = 1_000_000_000;
 
@@ -349,7 +349,9 @@ impl Filter {
                     ValueOrArray::Value(s) => {
                         vec![*s]
                     }
-                    ValueOrArray::Array(s) => s.clone(),
+                    // an empty array or a null entry matches any topic
+                    ValueOrArray::Array(s) if s.is_empty() || s.contains(&None) => vec![None],
+                    ValueOrArray::Array(s) => dedup_topics(s.clone()),
                 }
             } else {
                 vec![None]
@@ -503,11 +505,17 @@ impl<'de> Deserialize<'de> for Filter {
 
                 // maximum allowed filter len
                 if topics_vec.len() > 4 {
-                    return Err(serde::de::Error::custom("exceeded maximum topics len"))
+                    return Err(serde::de::Error::custom(format!(
+                        "exceeded maximum topics len: got {} topics, at most 4 are allowed",
+                        topics_vec.len()
+                    )))
                 }
                 let mut topics: [Option<Topic>; 4] = [None, None, None, None];
                 for (idx, topic) in topics_vec.into_iter().enumerate() {
-                    topics[idx] = topic;
+                    topics[idx] = topic.map(|topic| match topic {
+                        ValueOrArray::Array(topics) => ValueOrArray::Array(dedup_topics(topics)),
+                        topic => topic,
+                    });
                 }
 
                 let block_option = if let Some(block_hash) = block_hash {
@@ -784,46 +792,37 @@ impl FilteredParams {
         true
     }
 
+    /// Returns `true` if every topic position of the filter matches the topic of the log at that
+    /// position.
+    ///
+    /// A position matches any topic if it's null, an empty array or an array with a null entry.
+    /// Otherwise the log must have a topic at that position that is one of the filter's topics.
     pub fn filter_topics(&self, log: &Log) -> bool {
-        let mut out: bool = true;
-        for topic in self.flat_topics.iter().cloned() {
+        let Some(filter) = &self.filter else { return true };
+        filter.topics.iter().enumerate().all(|(idx, topic)| {
+            let log_topic = log.topics.get(idx);
             match topic {
-                ValueOrArray::Value(single) => {
-                    if let Some(single) = single {
-                        if !log.topics.starts_with(&[single]) {
-                            out = false;
-                        }
-                    }
-                }
-                ValueOrArray::Array(multi) => {
-                    if multi.is_empty() {
-                        out = true;
-                        continue
-                    }
-                    // Shrink the topics until the last item is Some.
-                    let mut new_multi = multi;
-                    while new_multi.iter().last().unwrap_or(&Some(H256::default())).is_none() {
-                        new_multi.pop();
-                    }
-                    // We can discard right away any logs with lesser topics than the filter.
-                    if new_multi.len() > log.topics.len() {
-                        out = false;
-                        break
-                    }
-                    let replaced: Option<Vec<H256>> =
-                        self.replace(log, ValueOrArray::Array(new_multi));
-                    if let Some(replaced) = replaced {
-                        out = false;
-                        if log.topics.starts_with(&replaced[..]) {
-                            out = true;
-                            break
-                        }
-                    }
+                None | Some(ValueOrArray::Value(None)) => true,
+                Some(ValueOrArray::Value(Some(topic))) => log_topic == Some(topic),
+                Some(ValueOrArray::Array(topics)) => {
+                    topics.is_empty() ||
+                        topics.contains(&None) ||
+                        log_topic.map_or(false, |log_topic| topics.contains(&Some(*log_topic)))
                 }
             }
+        })
+    }
+}
+
+/// Removes duplicate topics, keeping the first occurrence of each.
+fn dedup_topics(topics: Vec<Option<H256>>) -> Vec<Option<H256>> {
+    let mut out = Vec::with_capacity(topics.len());
+    for topic in topics {
+        if !out.contains(&topic) {
+            out.push(topic);
         }
-        out
     }
+    out
 }
 
 fn topics_to_bloom_filter(topics: &ValueOrArray<Option<H256>>) -> BloomFilter {
@@ -961,6 +960,24 @@ mod tests {
         assert_eq!(ser, json!({ "address" : addr, "topics": [t0, t1_padded, t2, t3_padded]}));
     }
 
+    #[test]
+    fn filter_block_hash_serde() {
+        let hash = H256::random();
+        let filter: Filter = serde_json::from_value(json!({ "blockHash": hash })).unwrap();
+        assert_eq!(filter.block_option, FilterBlockOption::AtBlockHash(hash));
+        assert_eq!(serialize(&filter), json!({ "blockHash": hash, "topics": [] }));
+
+        // `blockHash` is mutually exclusive with `fromBlock` and `toBlock`, in any order
+        for value in [
+            json!({ "blockHash": hash, "fromBlock": "0x1" }),
+            json!({ "fromBlock": "0x1", "blockHash": hash }),
+            json!({ "blockHash": hash, "toBlock": "latest" }),
+            json!({ "toBlock": "latest", "blockHash": hash }),
+        ] {
+            assert!(serde_json::from_value::<Filter>(value).is_err());
+        }
+    }
+
     fn build_bloom(address: Address, topic1: H256, topic2: H256) -> Bloom {
         let mut block_bloom = Bloom::default();
         block_bloom.accrue(Input::Raw(&address[..]));
@@ -1210,4 +1227,77 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn topic_matching_matrix() {
+        let [t0, t1, t2, t3] = [1, 2, 3, 4].map(H256::from_low_u64_be);
+        let other = H256::from_low_u64_be(99);
+        // logs with 0 to 4 topics
+        let logs = (0..=4)
+            .map(|len| Log { topics: [t0, t1, t2, t3][..len].to_vec(), ..Default::default() })
+            .collect::<Vec<_>>();
+
+        // the topics of the filter and the min number of topics of the matching logs, `None` if
+        // no log matches
+        let cases = [
+            (json!([]), Some(0)),
+            (json!(null), Some(0)),
+            (json!([null]), Some(0)),
+            (json!([[]]), Some(0)),
+            (json!([null, null, null, null]), Some(0)),
+            (json!([[], [], [], []]), Some(0)),
+            (json!([[t0, null]]), Some(0)),
+            (json!([t0]), Some(1)),
+            (json!([[t0]]), Some(1)),
+            (json!([[other, t0]]), Some(1)),
+            (json!([[t0, t0, t0]]), Some(1)),
+            (json!([t0, []]), Some(1)),
+            (json!([[], t1]), Some(2)),
+            (json!([null, t1]), Some(2)),
+            (json!([null, [t1, other]]), Some(2)),
+            (json!([t0, null, t2]), Some(3)),
+            (json!([[], null, [t2]]), Some(3)),
+            (json!([null, null, null, t3]), Some(4)),
+            (json!([t0, t1, t2, t3]), Some(4)),
+            (json!([[t0, other], [t1, other], [t2, other], [t3, other]]), Some(4)),
+            (json!([other]), None),
+            (json!([[other]]), None),
+            (json!([t1]), None),
+            (json!([null, t0]), None),
+            (json!([t0, t1, t2, other]), None),
+            (json!([null, null, null, [other, t2]]), None),
+        ];
+
+        for (topics, min_topics) in cases {
+            let filter: Filter = serde_json::from_value(json!({ "topics": topics })).unwrap();
+            let params = FilteredParams::new(Some(filter));
+            let blooms = FilteredParams::topics_filter(&Some(params.flat_topics.clone()));
+            for log in &logs {
+                let expected = min_topics.map_or(false, |min| log.topics.len() >= min);
+                assert_eq!(
+                    params.filter_topics(log),
+                    expected,
+                    "topics {topics} against log with {} topics",
+                    log.topics.len()
+                );
+                if expected {
+                    // the bloom filter must never exclude a matching log
+                    let bloom = crate::bloom::logs_bloom([log]);
+                    assert!(FilteredParams::matches_topics(bloom, &blooms), "topics {topics}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dedup_and_limit_topics() {
+        let topic = H256::from_low_u64_be(1);
+        let filter: Filter =
+            serde_json::from_value(json!({ "topics": [[topic, topic, null, null]] })).unwrap();
+        assert_eq!(filter.topics[0], Some(ValueOrArray::Array(vec![Some(topic), None])));
+
+        let topics = json!({ "topics": [null, null, null, null, null] });
+        let err = serde_json::from_value::<Filter>(topics).unwrap_err();
+        assert!(err.to_string().contains("got 5 topics"), "{err}");
+    }
 }
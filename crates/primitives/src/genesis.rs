@@ -32,6 +32,11 @@ pub struct Genesis {
     pub mix_hash: H256,
     /// The genesis header coinbase address.
     pub coinbase: Address,
+    /// The genesis header base fee, if London is active at genesis.
+    ///
+    /// Defaults to the initial base fee of EIP-1559.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<u64>,
     /// The initial state of accounts in the genesis block.
     pub alloc: HashMap<Address, GenesisAccount>,
 }
@@ -79,6 +84,12 @@ impl Genesis {
         self
     }
 
+    /// Set the base fee.
+    pub fn with_base_fee(mut self, base_fee: Option<u64>) -> Self {
+        self.base_fee_per_gas = base_fee;
+        self
+    }
+
     /// Add accounts to the genesis block. If the address is already present,
     /// the account is updated.
     pub fn extend_accounts(
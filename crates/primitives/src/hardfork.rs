@@ -39,6 +39,20 @@ pub enum Hardfork {
     Paris,
     /// Shanghai.
     Shanghai,
+    /// Prague.
+    Prague,
+    /// Bedrock, the first hardfork of Optimism.
+    #[cfg(feature = "optimism")]
+    Bedrock,
+    /// Regolith, adds the deposit nonce to the receipts of deposit transactions.
+    #[cfg(feature = "optimism")]
+    Regolith,
+    /// Canyon, adds the deposit receipt version to the receipts of deposit transactions.
+    #[cfg(feature = "optimism")]
+    Canyon,
+    /// Ecotone, prices the data of transactions with the L1 blob base fee.
+    #[cfg(feature = "optimism")]
+    Ecotone,
 }
 
 impl Hardfork {
@@ -82,6 +96,15 @@ impl FromStr for Hardfork {
             "grayglacier" => Hardfork::GrayGlacier,
             "paris" => Hardfork::Paris,
             "shanghai" => Hardfork::Shanghai,
+            "prague" => Hardfork::Prague,
+            #[cfg(feature = "optimism")]
+            "bedrock" => Hardfork::Bedrock,
+            #[cfg(feature = "optimism")]
+            "regolith" => Hardfork::Regolith,
+            #[cfg(feature = "optimism")]
+            "canyon" => Hardfork::Canyon,
+            #[cfg(feature = "optimism")]
+            "ecotone" => Hardfork::Ecotone,
             _ => return Err(format!("Unknown hardfork: {s}")),
         };
         Ok(hardfork)
@@ -120,6 +143,7 @@ mod tests {
             "grayglacier",
             "PARIS",
             "ShAnGhAI",
+            "pRaGuE",
         ];
         let expected_hardforks = [
             Hardfork::Frontier,
@@ -138,6 +162,7 @@ mod tests {
             Hardfork::GrayGlacier,
             Hardfork::Paris,
             Hardfork::Shanghai,
+            Hardfork::Prague,
         ];
 
         let hardforks: Vec<Hardfork> =
@@ -146,6 +171,20 @@ mod tests {
         assert_eq!(hardforks, expected_hardforks);
     }
 
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn check_optimism_hardfork_from_str() {
+        let hardforks: Vec<Hardfork> = ["beDrock", "regolith", "CANYON", "ecoTone"]
+            .iter()
+            .map(|h| Hardfork::from_str(h).unwrap())
+            .collect();
+
+        assert_eq!(
+            hardforks,
+            [Hardfork::Bedrock, Hardfork::Regolith, Hardfork::Canyon, Hardfork::Ecotone]
+        );
+    }
+
     #[test]
     fn check_nonexistent_hardfork_from_str() {
         assert!(Hardfork::from_str("not a hardfork").is_err());
@@ -158,6 +197,7 @@ mod tests {
             genesis: Genesis::default(),
             genesis_hash: None,
             hardforks: BTreeMap::from([(Hardfork::Frontier, ForkCondition::Never)]),
+            base_fee_params: Default::default(),
         };
 
         assert_eq!(Hardfork::Frontier.fork_id(&spec), None);
@@ -170,6 +210,7 @@ mod tests {
             genesis: Genesis::default(),
             genesis_hash: None,
             hardforks: BTreeMap::from([(Hardfork::Shanghai, ForkCondition::Never)]),
+            base_fee_params: Default::default(),
         };
 
         assert_eq!(Hardfork::Shanghai.fork_filter(&spec), None);
@@ -147,15 +147,18 @@ impl Header {
 
     /// Seal the header with a known hash.
     ///
-    /// WARNING: This method does not perform validation whether the hash is correct.
+    /// In debug builds this asserts that the hash is the hash of the header. Use
+    /// [`SealedHeader::new_unchecked`] for headers that are read with their hashes from the
+    /// database.
     pub fn seal(self, hash: H256) -> SealedHeader {
+        debug_assert_eq!(self.hash_slow(), hash, "header sealed with a wrong hash");
         SealedHeader { header: self, hash }
     }
 
     /// Calculate hash and seal the Header so that it can't be changed.
     pub fn seal_slow(self) -> SealedHeader {
         let hash = self.hash_slow();
-        self.seal(hash)
+        SealedHeader { header: self, hash }
     }
 
     fn header_payload_length(&self) -> usize {
@@ -290,11 +293,24 @@ pub struct SealedHeader {
 }
 
 impl SealedHeader {
+    /// Creates a sealed header from a header and its hash without checking the hash.
+    ///
+    /// This is meant for headers that are read along with their hashes from the database, the
+    /// hashes of those were computed or checked once when the headers were sealed first.
+    pub fn new_unchecked(header: Header, hash: BlockHash) -> Self {
+        Self { header, hash }
+    }
+
     /// Extract raw header that can be modified.
     pub fn unseal(self) -> Header {
         self.header
     }
 
+    /// Split the sealed header into the header and its hash, without hashing the header again.
+    pub fn split(self) -> (Header, BlockHash) {
+        (self.header, self.hash)
+    }
+
     /// Return header/block hash.
     pub fn hash(&self) -> BlockHash {
         self.hash
@@ -353,7 +369,7 @@ impl From<&Block<EthersH256>> for SealedHeader {
     fn from(block: &Block<EthersH256>) -> Self {
         let header = Header::from(block);
         match block.hash {
-            Some(hash) => header.seal(hash.0.into()),
+            Some(hash) => SealedHeader::new_unchecked(header, hash.0.into()),
             None => header.seal_slow(),
         }
     }
@@ -640,4 +656,21 @@ mod tests {
         direction.encode(&mut buf);
         assert_eq!(direction, HeadersDirection::decode(&mut buf.as_slice()).unwrap());
     }
+
+    #[test]
+    fn seal_keeps_hash() {
+        let header = Header { number: 1, ..Default::default() };
+        let hash = header.hash_slow();
+
+        let sealed = header.clone().seal(hash);
+        assert_eq!(sealed, header.clone().seal_slow());
+        assert_eq!(sealed.clone().split(), (header, hash));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "header sealed with a wrong hash")]
+    fn seal_with_wrong_hash() {
+        Header::default().seal(H256::zero());
+    }
 }
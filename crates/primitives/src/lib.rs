@@ -10,6 +10,7 @@
 //! This crate contains Ethereum primitive types and helper functions.
 
 mod account;
+mod basefee;
 mod bits;
 mod block;
 pub mod bloom;
@@ -28,6 +29,7 @@ mod integer_list;
 mod log;
 mod net;
 mod peer;
+mod prune;
 mod receipt;
 mod storage;
 mod transaction;
@@ -37,6 +39,7 @@ mod withdrawal;
 pub mod proofs;
 
 pub use account::{Account, Bytecode};
+pub use basefee::{BaseFeeParams, InvalidBaseFeeParams};
 pub use bits::H512;
 pub use block::{
     Block, BlockHashOrNumber, BlockId, BlockNumberOrTag, SealedBlock, SealedBlockWithSenders,
@@ -48,7 +51,8 @@ pub use chain::{
 };
 pub use checkpoints::{AccountHashingCheckpoint, ProofCheckpoint, StorageHashingCheckpoint};
 pub use constants::{
-    EMPTY_OMMER_ROOT, GOERLI_GENESIS, KECCAK_EMPTY, MAINNET_GENESIS, SEPOLIA_GENESIS,
+    BLOCKHASH_WINDOW, EMPTY_OMMER_ROOT, GOERLI_GENESIS, KECCAK_EMPTY, MAINNET_GENESIS,
+    SEPOLIA_GENESIS,
 };
 pub use forkid::{ForkFilter, ForkHash, ForkId, ForkTransition, ValidationError};
 pub use genesis::{Genesis, GenesisAccount};
@@ -59,15 +63,19 @@ pub use integer_list::IntegerList;
 pub use log::Log;
 pub use net::NodeRecord;
 pub use peer::{PeerId, WithPeerId};
-pub use receipt::Receipt;
+pub use prune::{PruneMode, PruneModes, ReceiptsLogPruneConfig};
+pub use receipt::{Receipt, ReceiptWithBloom, ReceiptWithBloomRef};
 pub use revm_primitives::JumpMap;
 pub use serde_helper::JsonU256;
 pub use storage::{StorageEntry, StorageTrieEntry};
+#[cfg(feature = "optimism")]
+pub use transaction::DEPOSIT_TX_TYPE_ID;
 pub use transaction::{
-    util::secp256k1::sign_message, AccessList, AccessListItem, AccessListWithGasUsed,
-    FromRecoveredTransaction, IntoRecoveredTransaction, InvalidTransactionError, Signature,
-    Transaction, TransactionKind, TransactionSigned, TransactionSignedEcRecovered, TxEip1559,
-    TxEip2930, TxLegacy, TxType, EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID, LEGACY_TX_TYPE_ID,
+    util::secp256k1::sign_message, AccessList, AccessListItem, FromRecoveredTransaction,
+    IntoRecoveredTransaction, InvalidTransactionError, Signature, Transaction,
+    TransactionDecodeError, TransactionKind, TransactionMeta, TransactionSigned,
+    TransactionSignedEcRecovered, TxEip1559, TxEip2930, TxLegacy, TxType, EIP1559_TX_TYPE_ID,
+    EIP2930_TX_TYPE_ID, LEGACY_TX_TYPE_ID,
 };
 pub use withdrawal::Withdrawal;
 
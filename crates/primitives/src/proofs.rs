@@ -1,11 +1,14 @@
 use crate::{
-    keccak256, Address, Bytes, GenesisAccount, Header, Log, Receipt, TransactionSigned, Withdrawal,
-    H256,
+    keccak256, Address, Bytes, GenesisAccount, Header, Log, ReceiptWithBloom, ReceiptWithBloomRef,
+    TransactionSigned, Withdrawal, H256,
 };
+#[cfg(feature = "optimism")]
+use crate::{ChainSpec, Hardfork};
 use bytes::BytesMut;
 use hash_db::Hasher;
 use hex_literal::hex;
 use plain_hasher::PlainHasher;
+use rayon::prelude::*;
 use reth_rlp::Encodable;
 use std::collections::HashMap;
 use triehash::{ordered_trie_root, sec_trie_root};
@@ -18,6 +21,10 @@ pub const EMPTY_LIST_HASH: H256 =
 pub const EMPTY_ROOT: H256 =
     H256(hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"));
 
+/// Number of receipts from which [calculate_receipt_root_parallel] encodes the receipts in
+/// parallel by default.
+pub const DEFAULT_PARALLEL_RECEIPT_ROOT_THRESHOLD: usize = 1_000;
+
 /// A [Hasher] that calculates a keccak256 hash of the given data.
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct KeccakHasher;
@@ -33,37 +40,104 @@ impl Hasher for KeccakHasher {
     }
 }
 
+/// Calculates the root of an ordered trie of the given items.
+///
+/// The keys of the trie are the RLP encoded indices of the items and the values are the items
+/// encoded with `encode`.
+pub fn ordered_trie_root_with_encoder<T, F>(
+    items: impl IntoIterator<Item = T>,
+    mut encode: F,
+) -> H256
+where
+    F: FnMut(T, &mut Vec<u8>),
+{
+    ordered_trie_root::<KeccakHasher, _>(items.into_iter().map(|item| {
+        let mut value = Vec::new();
+        encode(item, &mut value);
+        value
+    }))
+}
+
 /// Calculate a transaction root.
 ///
-/// `(rlp(index), encoded(tx))` pairs.
+/// `(rlp(index), encoded(tx))` pairs, typed transactions are encoded as EIP-2718 envelopes.
 pub fn calculate_transaction_root<'a>(
     transactions: impl IntoIterator<Item = &'a TransactionSigned>,
 ) -> H256 {
-    ordered_trie_root::<KeccakHasher, _>(transactions.into_iter().map(|tx| {
-        let mut tx_rlp = Vec::new();
-        tx.encode_inner(&mut tx_rlp, false);
-        tx_rlp
-    }))
+    ordered_trie_root_with_encoder(transactions, |tx, buf| tx.encode_enveloped(buf))
 }
 
 /// Calculates the root hash of the withdrawals.
 pub fn calculate_withdrawals_root<'a>(
     withdrawals: impl IntoIterator<Item = &'a Withdrawal>,
 ) -> H256 {
-    ordered_trie_root::<KeccakHasher, _>(withdrawals.into_iter().map(|withdrawal| {
-        let mut withdrawal_rlp = Vec::new();
-        withdrawal.encode(&mut withdrawal_rlp);
-        withdrawal_rlp
-    }))
+    ordered_trie_root_with_encoder(withdrawals, |withdrawal, buf| withdrawal.encode(buf))
 }
 
 /// Calculates the receipt root for a header.
-pub fn calculate_receipt_root<'a>(receipts: impl Iterator<Item = &'a Receipt>) -> H256 {
-    ordered_trie_root::<KeccakHasher, _>(receipts.into_iter().map(|receipt| {
-        let mut receipt_rlp = Vec::new();
-        receipt.encode_inner(&mut receipt_rlp, false);
-        receipt_rlp
-    }))
+///
+/// `(rlp(index), encoded(receipt))` pairs, typed receipts are encoded as EIP-2718 envelopes.
+pub fn calculate_receipt_root<'a>(
+    receipts: impl IntoIterator<Item = &'a ReceiptWithBloom>,
+) -> H256 {
+    calculate_receipt_root_ref(receipts.into_iter().map(|r| r.as_receipt_with_bloom_ref()))
+}
+
+/// Calculates the receipt root for a header from references to the receipts, see
+/// [calculate_receipt_root].
+pub fn calculate_receipt_root_ref<'a>(
+    receipts: impl IntoIterator<Item = ReceiptWithBloomRef<'a>>,
+) -> H256 {
+    ordered_trie_root_with_encoder(receipts, |receipt, buf| receipt.encode_inner(buf, false))
+}
+
+/// Calculates the receipt root for a header like [calculate_receipt_root_ref], encoding the
+/// receipts in parallel if there are at least `threshold` of them.
+///
+/// The trie is then built from the encoded receipts, so the root is the same for any threshold.
+pub fn calculate_receipt_root_parallel(
+    receipts: &[ReceiptWithBloomRef<'_>],
+    threshold: usize,
+) -> H256 {
+    if receipts.len() < threshold {
+        return calculate_receipt_root_ref(receipts.iter().cloned())
+    }
+    let encoded = receipts
+        .par_iter()
+        .map(|receipt| {
+            let mut value = Vec::new();
+            receipt.encode_inner(&mut value, false);
+            value
+        })
+        .collect::<Vec<_>>();
+    ordered_trie_root::<KeccakHasher, _>(encoded)
+}
+
+/// Calculates the receipt root for a header of an Optimism chain at the given timestamp, see
+/// [calculate_receipt_root].
+///
+/// Between Regolith and Canyon, the deposit nonces of the receipts of deposit transactions are
+/// not part of the receipts root.
+#[cfg(feature = "optimism")]
+pub fn calculate_receipt_root_optimism(
+    receipts: &[ReceiptWithBloom],
+    chain_spec: &ChainSpec,
+    timestamp: u64,
+) -> H256 {
+    if chain_spec.fork(Hardfork::Regolith).active_at_timestamp(timestamp) &&
+        !chain_spec.fork(Hardfork::Canyon).active_at_timestamp(timestamp)
+    {
+        let receipts = receipts
+            .iter()
+            .cloned()
+            .map(|mut receipt| {
+                receipt.receipt.deposit_nonce = None;
+                receipt
+            })
+            .collect::<Vec<_>>();
+        return calculate_receipt_root(&receipts)
+    }
+    calculate_receipt_root(receipts)
 }
 
 /// Calculates the log root for headers.
@@ -74,11 +148,14 @@ pub fn calculate_log_root<'a>(logs: impl Iterator<Item = &'a Log> + Clone) -> H2
     keccak256(logs_rlp)
 }
 
-/// Calculates the root hash for ommer/uncle headers.
-pub fn calculate_ommers_root<'a>(ommers: impl Iterator<Item = &'a Header> + Clone) -> H256 {
-    // RLP Encode
+/// Calculates the ommers hash of a header, the hash of the RLP list of the ommer headers.
+pub fn calculate_ommers_hash<'a, I>(ommers: I) -> H256
+where
+    I: IntoIterator<Item = &'a Header>,
+    I::IntoIter: Clone,
+{
     let mut ommers_rlp = Vec::new();
-    reth_rlp::encode_iter(ommers, &mut ommers_rlp);
+    reth_rlp::encode_iter(ommers.into_iter(), &mut ommers_rlp);
     keccak256(ommers_rlp)
 }
 
@@ -99,12 +176,20 @@ mod tests {
 
     use std::{collections::HashMap, str::FromStr};
 
+    #[cfg(feature = "optimism")]
+    use crate::Hardfork;
     use crate::{
         hex_literal::hex,
-        proofs::{calculate_receipt_root, calculate_transaction_root, genesis_state_root},
-        Address, Block, Bloom, GenesisAccount, Log, Receipt, TxType, H160, H256, U256,
+        keccak256,
+        proofs::{
+            calculate_ommers_hash, calculate_receipt_root, calculate_receipt_root_parallel,
+            calculate_receipt_root_ref, calculate_transaction_root, genesis_state_root,
+        },
+        Address, Block, Bloom, Bytes, GenesisAccount, Log, Receipt, ReceiptWithBloom, Signature,
+        Transaction, TransactionKind, TransactionSigned, TxEip1559, TxType, H160, H256, MAINNET,
+        MAINNET_GENESIS, U256,
     };
-    use reth_rlp::Decodable;
+    use reth_rlp::{Decodable, Encodable};
 
     use super::{calculate_withdrawals_root, EMPTY_ROOT};
 
@@ -116,18 +201,75 @@ mod tests {
 
         let tx_root = calculate_transaction_root(block.body.iter());
         assert_eq!(block.transactions_root, tx_root, "Must be the same");
+        assert_eq!(calculate_ommers_hash(&block.ommers), block.ommers_hash);
+    }
+
+    #[test]
+    fn check_mainnet_genesis_and_first_block_roots() {
+        // the state root of the mainnet genesis block
+        // https://etherscan.io/block/0
+        assert_eq!(
+            genesis_state_root(&MAINNET.genesis.alloc),
+            H256(hex!("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544"))
+        );
+
+        // https://etherscan.io/block/1
+        let data = &hex!("f90216f90211a0d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d493479405a56e2d52c817161883f50c441c3228cfe54d9fa0d67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421b90100000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000008503ff80000001821388808455ba422499476574682f76312e302e302f6c696e75782f676f312e342e32a0969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f5988539bd4979fef1ec4c0c0");
+        let block: Block = Block::decode(&mut data.as_slice()).unwrap();
+        assert_eq!(
+            block.header.hash_slow(),
+            H256(hex!("88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6"))
+        );
+        assert_eq!(block.parent_hash, MAINNET_GENESIS);
+        assert_eq!(calculate_transaction_root(&block.body), block.transactions_root);
+        assert_eq!(calculate_receipt_root(&Vec::<ReceiptWithBloom>::new()), block.receipts_root);
+        assert_eq!(calculate_ommers_hash(&block.ommers), block.ommers_hash);
+    }
+
+    #[test]
+    fn check_typed_transaction_root() {
+        let tx = TransactionSigned::from_transaction_and_signature(
+            Transaction::Eip1559(TxEip1559 {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 21_000,
+                max_fee_per_gas: 2_000_000_000,
+                max_priority_fee_per_gas: 1_000_000_000,
+                to: TransactionKind::Call(Address::repeat_byte(0x11)),
+                value: 1,
+                ..Default::default()
+            }),
+            Signature { r: U256::from(1), s: U256::from(2), odd_y_parity: true },
+        );
+
+        // a trie with a single leaf: the key is rlp(0) and the value is the EIP-2718 envelope
+        let path = Bytes::from(vec![0x20, 0x80]);
+        let value = Bytes::from(tx.envelope_encoded().to_vec());
+        let mut leaf = Vec::new();
+        reth_rlp::Header { list: true, payload_length: path.length() + value.length() }
+            .encode(&mut leaf);
+        path.encode(&mut leaf);
+        value.encode(&mut leaf);
+
+        assert_eq!(calculate_transaction_root(&[tx]), keccak256(leaf));
     }
 
     #[test]
     fn check_receipt_root() {
         let logs = vec![Log { address: H160::zero(), topics: vec![], data: Default::default() }];
         let bloom =  Bloom(hex!("00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001"));
-        let receipt = Receipt {
-            tx_type: TxType::EIP2930,
-            success: true,
-            cumulative_gas_used: 102068,
+        let receipt = ReceiptWithBloom {
+            receipt: Receipt {
+                tx_type: TxType::EIP2930,
+                success: true,
+                #[cfg(feature = "optimism")]
+                deposit_nonce: None,
+                #[cfg(feature = "optimism")]
+                deposit_receipt_version: None,
+                cumulative_gas_used: 102068,
+                logs,
+            },
             bloom,
-            logs,
         };
         let receipt = vec![receipt];
         let root = calculate_receipt_root(receipt.iter());
@@ -137,6 +279,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parallel_receipt_root_matches_serial() {
+        proptest::proptest!(|(receipts: Vec<Receipt>)| {
+            let receipts = receipts.iter().map(Receipt::with_bloom_ref).collect::<Vec<_>>();
+            let serial = calculate_receipt_root_ref(receipts.iter().cloned());
+            // encoded in parallel, and serially below the threshold
+            assert_eq!(calculate_receipt_root_parallel(&receipts, 0), serial);
+            assert_eq!(calculate_receipt_root_parallel(&receipts, receipts.len() + 1), serial);
+        });
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn optimism_receipt_root_omits_the_deposit_nonce_between_regolith_and_canyon() {
+        use crate::{proofs::calculate_receipt_root_optimism, Chain, ChainSpec, ForkCondition};
+
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::Id(10))
+            .genesis(Default::default())
+            .with_fork(Hardfork::Regolith, ForkCondition::Timestamp(100))
+            .with_fork(Hardfork::Canyon, ForkCondition::Timestamp(200))
+            .build();
+        let receipts = |deposit_nonce, deposit_receipt_version| {
+            vec![
+                Receipt {
+                    tx_type: TxType::DEPOSIT,
+                    success: true,
+                    cumulative_gas_used: 46_913,
+                    logs: vec![],
+                    deposit_nonce,
+                    deposit_receipt_version,
+                }
+                .with_bloom(),
+                Receipt {
+                    tx_type: TxType::EIP1559,
+                    success: true,
+                    cumulative_gas_used: 67_913,
+                    logs: vec![Log {
+                        address: H160::zero(),
+                        topics: vec![],
+                        data: Bytes::default(),
+                    }],
+                    deposit_nonce: None,
+                    deposit_receipt_version: None,
+                }
+                .with_bloom(),
+            ]
+        };
+
+        // bedrock receipts don't have a deposit nonce
+        let bedrock = receipts(None, None);
+        assert_eq!(
+            calculate_receipt_root_optimism(&bedrock, &chain_spec, 99),
+            calculate_receipt_root(&bedrock)
+        );
+
+        let regolith = receipts(Some(4_100_000), None);
+        assert_eq!(
+            calculate_receipt_root_optimism(&regolith, &chain_spec, 100),
+            calculate_receipt_root(&bedrock)
+        );
+        assert_ne!(calculate_receipt_root(&regolith), calculate_receipt_root(&bedrock));
+
+        let canyon = receipts(Some(4_100_000), Some(1));
+        assert_eq!(
+            calculate_receipt_root_optimism(&canyon, &chain_spec, 200),
+            calculate_receipt_root(&canyon)
+        );
+        assert_ne!(calculate_receipt_root(&canyon), calculate_receipt_root(&regolith));
+    }
+
     #[test]
     fn check_withdrawals_root() {
         // Single withdrawal, amount 0
@@ -148,6 +361,9 @@ mod tests {
         assert_eq!(withdrawals.len(), 1);
         let withdrawals_root = calculate_withdrawals_root(withdrawals.iter());
         assert_eq!(block.withdrawals_root, Some(withdrawals_root));
+        // the block has neither transactions nor ommers
+        assert_eq!(calculate_transaction_root(&block.body), block.transactions_root);
+        assert_eq!(calculate_ommers_hash(&block.ommers), block.ommers_hash);
 
         // 4 withdrawals, identical indices
         // https://github.com/ethereum/tests/blob/9760400e667eba241265016b02644ef62ab55de2/BlockchainTests/EIPTests/bc4895-withdrawals/twoIdenticalIndex.json
@@ -0,0 +1,90 @@
+//! Types to configure the pruning of historical data.
+
+use crate::{Address, BlockNumber};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Which blocks of a segment are pruned, relative to the tip of the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PruneMode {
+    /// Prune all blocks.
+    Full,
+    /// Keep the last `N + 1` blocks, including the tip, and prune the blocks before them.
+    Distance(u64),
+    /// Prune the blocks before the given block. The given block is kept.
+    Before(BlockNumber),
+}
+
+impl PruneMode {
+    /// Returns the highest block that is pruned with the given tip, or `None` if no block is
+    /// pruned.
+    pub fn prune_target_block(&self, tip: BlockNumber) -> Option<BlockNumber> {
+        match self {
+            PruneMode::Full => Some(tip),
+            PruneMode::Distance(distance) => tip.checked_sub(*distance)?.checked_sub(1),
+            PruneMode::Before(block) => (*block).min(tip + 1).checked_sub(1),
+        }
+    }
+
+    /// Returns `true` if the block is pruned with the given tip.
+    pub fn should_prune(&self, block: BlockNumber, tip: BlockNumber) -> bool {
+        self.prune_target_block(tip).map_or(false, |target| block <= target)
+    }
+}
+
+/// Prune modes of the receipts of the transactions that emitted logs from the given addresses.
+///
+/// Receipts with logs from one of the addresses are kept until the mode of that address prunes
+/// them, even if the receipts segment is pruned earlier.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptsLogPruneConfig(pub BTreeMap<Address, PruneMode>);
+
+impl ReceiptsLogPruneConfig {
+    /// Returns `true` if a receipt of the block with a log from one of the addresses is kept with
+    /// the given tip.
+    pub fn retains<'a>(
+        &self,
+        mut addresses: impl Iterator<Item = &'a Address>,
+        block: BlockNumber,
+        tip: BlockNumber,
+    ) -> bool {
+        addresses
+            .any(|address| self.0.get(address).map_or(false, |mode| !mode.should_prune(block, tip)))
+    }
+}
+
+/// Prune modes of the pruned segments, `None` keeps the whole history of a segment.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PruneModes {
+    /// Prune mode of the transaction receipts.
+    pub receipts: Option<PruneMode>,
+    /// Receipts kept by the addresses of their logs, overriding [PruneModes::receipts].
+    pub receipts_log_filter: ReceiptsLogPruneConfig,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prune_target_block() {
+        assert_eq!(PruneMode::Full.prune_target_block(100), Some(100));
+        assert_eq!(PruneMode::Distance(10).prune_target_block(100), Some(89));
+        assert_eq!(PruneMode::Distance(100).prune_target_block(100), None);
+        assert_eq!(PruneMode::Before(50).prune_target_block(100), Some(49));
+        assert_eq!(PruneMode::Before(0).prune_target_block(100), None);
+        // blocks after the tip are not pruned yet
+        assert_eq!(PruneMode::Before(200).prune_target_block(100), Some(100));
+    }
+
+    #[test]
+    fn receipts_log_filter() {
+        let contract = Address::from_low_u64_be(1);
+        let filter = ReceiptsLogPruneConfig(BTreeMap::from([(contract, PruneMode::Before(50))]));
+        assert!(filter.retains([contract].iter(), 50, 100));
+        assert!(!filter.retains([contract].iter(), 49, 100));
+        assert!(!filter.retains([Address::from_low_u64_be(2)].iter(), 50, 100));
+    }
+}
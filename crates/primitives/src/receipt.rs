@@ -1,11 +1,16 @@
-use crate::{Bloom, Log, TxType};
+use crate::{bloom::logs_bloom, Bloom, Log, TxType};
 use bytes::{Buf, BufMut, BytesMut};
-use reth_codecs::{main_codec, Compact};
+use reth_codecs::{add_arbitrary_tests, derive_arbitrary, main_codec, Compact};
 use reth_rlp::{length_of_length, Decodable, Encodable};
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
 /// Receipt containing result of transaction execution.
-#[main_codec]
+///
+/// The bloom filter of the logs is not part of the receipt since it can be calculated from the
+/// logs, see [ReceiptWithBloom].
+#[main_codec(no_arbitrary)]
+#[add_arbitrary_tests(compact)]
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
 pub struct Receipt {
     /// Receipt type.
@@ -16,66 +21,121 @@ pub struct Receipt {
     pub success: bool,
     /// Gas used
     pub cumulative_gas_used: u64,
-    /// Bloom filter.
-    pub bloom: Bloom,
     /// Log send from contracts.
     pub logs: Vec<Log>,
+    /// The nonce of the deposit transaction of the receipt, since Regolith.
+    ///
+    /// Only set for the receipts of deposit transactions.
+    #[cfg(feature = "optimism")]
+    pub deposit_nonce: Option<u64>,
+    /// The version of the receipt of a deposit transaction, since Canyon.
+    ///
+    /// Only set for the receipts of deposit transactions.
+    #[cfg(feature = "optimism")]
+    pub deposit_receipt_version: Option<u64>,
 }
 
 impl Receipt {
-    /// Returns the rlp header for the receipt payload.
-    fn receipt_rlp_header(&self) -> reth_rlp::Header {
-        let mut rlp_head = reth_rlp::Header { list: true, payload_length: 0 };
-
-        rlp_head.payload_length += self.success.length();
-        rlp_head.payload_length += self.cumulative_gas_used.length();
-        rlp_head.payload_length += self.bloom.length();
-        rlp_head.payload_length += self.logs.length();
+    /// Calculates the bloom filter of the logs of the receipt.
+    pub fn bloom_slow(&self) -> Bloom {
+        logs_bloom(self.logs.iter())
+    }
 
-        rlp_head
+    /// Calculates the bloom filter of the receipt and returns it together with the receipt.
+    pub fn with_bloom(self) -> ReceiptWithBloom {
+        self.into()
     }
 
-    /// Encodes the receipt data.
-    fn encode_fields(&self, out: &mut dyn BufMut) {
-        self.receipt_rlp_header().encode(out);
-        self.success.encode(out);
-        self.cumulative_gas_used.encode(out);
-        self.bloom.encode(out);
-        self.logs.encode(out);
+    /// Calculates the bloom filter of the receipt and returns it together with a reference to the
+    /// receipt.
+    pub fn with_bloom_ref(&self) -> ReceiptWithBloomRef<'_> {
+        self.into()
     }
+}
 
-    /// Encode receipt with or without the header data.
-    pub fn encode_inner(&self, out: &mut dyn BufMut, with_header: bool) {
-        if matches!(self.tx_type, TxType::Legacy) {
-            self.encode_fields(out);
-            return
+/// Builds a receipt from arbitrary fields.
+///
+/// Only the receipts of deposit transactions have a deposit nonce, and only those with a deposit
+/// nonce have a receipt version, like the receipts that can be decoded.
+#[cfg(any(test, feature = "arbitrary"))]
+#[cfg_attr(not(feature = "optimism"), allow(unused_variables))]
+fn arbitrary_receipt(
+    tx_type: TxType,
+    success: bool,
+    cumulative_gas_used: u64,
+    logs: Vec<Log>,
+    deposit_nonce: Option<u64>,
+    deposit_receipt_version: Option<u64>,
+) -> Receipt {
+    #[cfg(feature = "optimism")]
+    if tx_type == TxType::DEPOSIT {
+        return Receipt {
+            tx_type,
+            success,
+            cumulative_gas_used,
+            logs,
+            deposit_nonce,
+            deposit_receipt_version: deposit_nonce.and(deposit_receipt_version),
         }
+    }
+    Receipt {
+        tx_type,
+        success,
+        cumulative_gas_used,
+        logs,
+        #[cfg(feature = "optimism")]
+        deposit_nonce: None,
+        #[cfg(feature = "optimism")]
+        deposit_receipt_version: None,
+    }
+}
 
-        let mut payload = BytesMut::new();
-        self.encode_fields(&mut payload);
+#[cfg(any(test, feature = "arbitrary"))]
+impl proptest::arbitrary::Arbitrary for Receipt {
+    type Parameters = ();
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::{any, Strategy};
 
-        if with_header {
-            let payload_length = payload.len() + 1;
-            let header = reth_rlp::Header { list: false, payload_length };
-            header.encode(out);
-        }
+        any::<(TxType, bool, u64, Vec<Log>, Option<u64>, Option<u64>)>()
+            .prop_map(|(tx_type, success, cumulative_gas_used, logs, nonce, version)| {
+                arbitrary_receipt(tx_type, success, cumulative_gas_used, logs, nonce, version)
+            })
+            .boxed()
+    }
 
-        match self.tx_type {
-            TxType::EIP2930 => {
-                out.put_u8(0x01);
-            }
-            TxType::EIP1559 => {
-                out.put_u8(0x02);
-            }
-            _ => unreachable!("legacy handled; qed."),
-        }
-        out.put_slice(payload.as_ref());
+    type Strategy = proptest::strategy::BoxedStrategy<Receipt>;
+}
+
+#[cfg(any(test, feature = "arbitrary"))]
+impl<'a> arbitrary::Arbitrary<'a> for Receipt {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let (tx_type, success, cumulative_gas_used, logs, nonce, version) = u.arbitrary()?;
+        Ok(arbitrary_receipt(tx_type, success, cumulative_gas_used, logs, nonce, version))
     }
+}
 
-    /// Returns the length of the receipt data.
-    fn receipt_length(&self) -> usize {
-        let rlp_head = self.receipt_rlp_header();
-        length_of_length(rlp_head.payload_length) + rlp_head.payload_length
+/// [Receipt] with the bloom filter of its logs.
+///
+/// This is the form of the receipt that is sent over the network and that the receipts root
+/// commits to.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ReceiptWithBloom {
+    /// Bloom filter of the logs.
+    pub bloom: Bloom,
+    /// The receipt.
+    pub receipt: Receipt,
+}
+
+impl ReceiptWithBloom {
+    /// Returns a [ReceiptWithBloomRef] of the receipt.
+    pub fn as_receipt_with_bloom_ref(&self) -> ReceiptWithBloomRef<'_> {
+        ReceiptWithBloomRef { bloom: self.bloom, receipt: &self.receipt }
+    }
+
+    /// Encode receipt with or without the header data.
+    pub fn encode_inner(&self, out: &mut dyn BufMut, with_header: bool) {
+        self.as_receipt_with_bloom_ref().encode_inner(out, with_header)
     }
 
     /// Decodes the receipt payload
@@ -86,13 +146,39 @@ impl Receipt {
             return Err(reth_rlp::DecodeError::UnexpectedString)
         }
         let started_len = b.len();
-        let this = Self {
+        let success = reth_rlp::Decodable::decode(b)?;
+        let cumulative_gas_used = reth_rlp::Decodable::decode(b)?;
+        let bloom = reth_rlp::Decodable::decode(b)?;
+        let logs = reth_rlp::Decodable::decode(b)?;
+        // the deposit nonce and receipt version are appended to the receipts of deposit
+        // transactions since Regolith and Canyon
+        #[cfg(feature = "optimism")]
+        let (deposit_nonce, deposit_receipt_version) = if tx_type == TxType::DEPOSIT {
+            let deposit_nonce = if started_len - b.len() < rlp_head.payload_length {
+                Some(reth_rlp::Decodable::decode(b)?)
+            } else {
+                None
+            };
+            let deposit_receipt_version = if started_len - b.len() < rlp_head.payload_length {
+                Some(reth_rlp::Decodable::decode(b)?)
+            } else {
+                None
+            };
+            (deposit_nonce, deposit_receipt_version)
+        } else {
+            (None, None)
+        };
+        let receipt = Receipt {
             tx_type,
-            success: reth_rlp::Decodable::decode(b)?,
-            cumulative_gas_used: reth_rlp::Decodable::decode(b)?,
-            bloom: reth_rlp::Decodable::decode(b)?,
-            logs: reth_rlp::Decodable::decode(b)?,
+            success,
+            cumulative_gas_used,
+            logs,
+            #[cfg(feature = "optimism")]
+            deposit_nonce,
+            #[cfg(feature = "optimism")]
+            deposit_receipt_version,
         };
+        let this = Self { bloom, receipt };
         let consumed = started_len - b.len();
         if consumed != rlp_head.payload_length {
             return Err(reth_rlp::DecodeError::ListLengthMismatch {
@@ -105,24 +191,22 @@ impl Receipt {
     }
 }
 
-impl Encodable for Receipt {
-    fn length(&self) -> usize {
-        let mut payload_len = self.receipt_length();
-        // account for eip-2718 type prefix and set the list
-        if matches!(self.tx_type, TxType::EIP1559 | TxType::EIP2930) {
-            payload_len += 1;
-            // we include a string header for typed receipts, so include the length here
-            payload_len += length_of_length(payload_len);
-        }
+impl From<Receipt> for ReceiptWithBloom {
+    fn from(receipt: Receipt) -> Self {
+        Self { bloom: receipt.bloom_slow(), receipt }
+    }
+}
 
-        payload_len
+impl Encodable for ReceiptWithBloom {
+    fn length(&self) -> usize {
+        self.as_receipt_with_bloom_ref().length()
     }
     fn encode(&self, out: &mut dyn BufMut) {
         self.encode_inner(out, true)
     }
 }
 
-impl Decodable for Receipt {
+impl Decodable for ReceiptWithBloom {
     fn decode(buf: &mut &[u8]) -> Result<Self, reth_rlp::DecodeError> {
         // a receipt is either encoded as a string (non legacy) or a list (legacy).
         // We should not consume the buffer if we are decoding a legacy receipt, so let's
@@ -145,6 +229,11 @@ impl Decodable for Receipt {
                     buf.advance(1);
                     Self::decode_receipt(buf, TxType::EIP1559)
                 } else {
+                    #[cfg(feature = "optimism")]
+                    if receipt_type == 0x7E {
+                        buf.advance(1);
+                        return Self::decode_receipt(buf, TxType::DEPOSIT)
+                    }
                     Err(reth_rlp::DecodeError::Custom("invalid receipt type"))
                 }
             }
@@ -156,10 +245,122 @@ impl Decodable for Receipt {
     }
 }
 
+/// A reference to a [Receipt] with the bloom filter of its logs, to encode a receipt without
+/// cloning it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptWithBloomRef<'a> {
+    /// Bloom filter of the logs.
+    pub bloom: Bloom,
+    /// The receipt.
+    pub receipt: &'a Receipt,
+}
+
+impl<'a> From<&'a Receipt> for ReceiptWithBloomRef<'a> {
+    fn from(receipt: &'a Receipt) -> Self {
+        Self { bloom: receipt.bloom_slow(), receipt }
+    }
+}
+
+impl<'a> ReceiptWithBloomRef<'a> {
+    /// Returns the rlp header for the receipt payload.
+    fn receipt_rlp_header(&self) -> reth_rlp::Header {
+        let mut rlp_head = reth_rlp::Header { list: true, payload_length: 0 };
+
+        rlp_head.payload_length += self.receipt.success.length();
+        rlp_head.payload_length += self.receipt.cumulative_gas_used.length();
+        rlp_head.payload_length += self.bloom.length();
+        rlp_head.payload_length += self.receipt.logs.length();
+        #[cfg(feature = "optimism")]
+        if self.receipt.tx_type == TxType::DEPOSIT {
+            if let Some(deposit_nonce) = self.receipt.deposit_nonce {
+                rlp_head.payload_length += deposit_nonce.length();
+            }
+            if let Some(deposit_receipt_version) = self.receipt.deposit_receipt_version {
+                rlp_head.payload_length += deposit_receipt_version.length();
+            }
+        }
+
+        rlp_head
+    }
+
+    /// Encodes the receipt data.
+    fn encode_fields(&self, out: &mut dyn BufMut) {
+        self.receipt_rlp_header().encode(out);
+        self.receipt.success.encode(out);
+        self.receipt.cumulative_gas_used.encode(out);
+        self.bloom.encode(out);
+        self.receipt.logs.encode(out);
+        #[cfg(feature = "optimism")]
+        if self.receipt.tx_type == TxType::DEPOSIT {
+            if let Some(deposit_nonce) = self.receipt.deposit_nonce {
+                deposit_nonce.encode(out);
+            }
+            if let Some(deposit_receipt_version) = self.receipt.deposit_receipt_version {
+                deposit_receipt_version.encode(out);
+            }
+        }
+    }
+
+    /// Encode receipt with or without the header data.
+    pub fn encode_inner(&self, out: &mut dyn BufMut, with_header: bool) {
+        if matches!(self.receipt.tx_type, TxType::Legacy) {
+            self.encode_fields(out);
+            return
+        }
+
+        let mut payload = BytesMut::new();
+        self.encode_fields(&mut payload);
+
+        if with_header {
+            let payload_length = payload.len() + 1;
+            let header = reth_rlp::Header { list: false, payload_length };
+            header.encode(out);
+        }
+
+        match self.receipt.tx_type {
+            TxType::EIP2930 => {
+                out.put_u8(0x01);
+            }
+            TxType::EIP1559 => {
+                out.put_u8(0x02);
+            }
+            #[cfg(feature = "optimism")]
+            TxType::DEPOSIT => {
+                out.put_u8(0x7E);
+            }
+            _ => unreachable!("legacy handled; qed."),
+        }
+        out.put_slice(payload.as_ref());
+    }
+
+    /// Returns the length of the receipt data.
+    fn receipt_length(&self) -> usize {
+        let rlp_head = self.receipt_rlp_header();
+        length_of_length(rlp_head.payload_length) + rlp_head.payload_length
+    }
+}
+
+impl<'a> Encodable for ReceiptWithBloomRef<'a> {
+    fn length(&self) -> usize {
+        let mut payload_len = self.receipt_length();
+        // account for eip-2718 type prefix and set the list
+        if self.receipt.tx_type != TxType::Legacy {
+            payload_len += 1;
+            // we include a string header for typed receipts, so include the length here
+            payload_len += length_of_length(payload_len);
+        }
+
+        payload_len
+    }
+    fn encode(&self, out: &mut dyn BufMut) {
+        self.encode_inner(out, true)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{hex_literal::hex, Address, H256};
+    use crate::{bloom::Input, hex_literal::hex, Address, H256};
     use ethers_core::types::Bytes;
     use reth_rlp::{Decodable, Encodable};
     use std::str::FromStr;
@@ -170,25 +371,31 @@ mod tests {
         let expected = hex!("f901668001b9010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f85ff85d940000000000000000000000000000000000000011f842a0000000000000000000000000000000000000000000000000000000000000deada0000000000000000000000000000000000000000000000000000000000000beef830100ff");
 
         let mut data = vec![];
-        let receipt = Receipt {
-            tx_type: TxType::Legacy,
+        let receipt = ReceiptWithBloom {
+            receipt: Receipt {
+                tx_type: TxType::Legacy,
+                cumulative_gas_used: 0x1u64,
+                logs: vec![Log {
+                    address: Address::from_str("0000000000000000000000000000000000000011").unwrap(),
+                    topics: vec![
+                        H256::from_str(
+                            "000000000000000000000000000000000000000000000000000000000000dead",
+                        )
+                        .unwrap(),
+                        H256::from_str(
+                            "000000000000000000000000000000000000000000000000000000000000beef",
+                        )
+                        .unwrap(),
+                    ],
+                    data: Bytes::from_str("0100ff").unwrap().0.into(),
+                }],
+                success: false,
+                #[cfg(feature = "optimism")]
+                deposit_nonce: None,
+                #[cfg(feature = "optimism")]
+                deposit_receipt_version: None,
+            },
             bloom: [0; 256].into(),
-            cumulative_gas_used: 0x1u64,
-            logs: vec![Log {
-                address: Address::from_str("0000000000000000000000000000000000000011").unwrap(),
-                topics: vec![
-                    H256::from_str(
-                        "000000000000000000000000000000000000000000000000000000000000dead",
-                    )
-                    .unwrap(),
-                    H256::from_str(
-                        "000000000000000000000000000000000000000000000000000000000000beef",
-                    )
-                    .unwrap(),
-                ],
-                data: Bytes::from_str("0100ff").unwrap().0.into(),
-            }],
-            success: false,
         };
 
         receipt.encode(&mut data);
@@ -204,28 +411,165 @@ mod tests {
         let data = hex!("f901668001b9010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f85ff85d940000000000000000000000000000000000000011f842a0000000000000000000000000000000000000000000000000000000000000deada0000000000000000000000000000000000000000000000000000000000000beef830100ff");
 
         // EIP658Receipt
-        let expected = Receipt {
-            tx_type: TxType::Legacy,
+        let expected = ReceiptWithBloom {
+            receipt: Receipt {
+                tx_type: TxType::Legacy,
+                cumulative_gas_used: 0x1u64,
+                logs: vec![Log {
+                    address: Address::from_str("0000000000000000000000000000000000000011").unwrap(),
+                    topics: vec![
+                        H256::from_str(
+                            "000000000000000000000000000000000000000000000000000000000000dead",
+                        )
+                        .unwrap(),
+                        H256::from_str(
+                            "000000000000000000000000000000000000000000000000000000000000beef",
+                        )
+                        .unwrap(),
+                    ],
+                    data: Bytes::from_str("0100ff").unwrap().0.into(),
+                }],
+                success: false,
+                #[cfg(feature = "optimism")]
+                deposit_nonce: None,
+                #[cfg(feature = "optimism")]
+                deposit_receipt_version: None,
+            },
             bloom: [0; 256].into(),
-            cumulative_gas_used: 0x1u64,
-            logs: vec![Log {
-                address: Address::from_str("0000000000000000000000000000000000000011").unwrap(),
-                topics: vec![
-                    H256::from_str(
-                        "000000000000000000000000000000000000000000000000000000000000dead",
-                    )
-                    .unwrap(),
-                    H256::from_str(
-                        "000000000000000000000000000000000000000000000000000000000000beef",
-                    )
-                    .unwrap(),
-                ],
-                data: Bytes::from_str("0100ff").unwrap().0.into(),
-            }],
-            success: false,
         };
 
-        let receipt = Receipt::decode(&mut &data[..]).unwrap();
+        let receipt = ReceiptWithBloom::decode(&mut &data[..]).unwrap();
+        assert_eq!(receipt, expected);
+    }
+
+    #[test]
+    fn receipt_with_bloom() {
+        let address = Address::from_low_u64_be(0x11);
+        let topic = H256::from_low_u64_be(0xdead);
+        let receipt = Receipt {
+            tx_type: TxType::EIP1559,
+            success: true,
+            #[cfg(feature = "optimism")]
+            deposit_nonce: None,
+            #[cfg(feature = "optimism")]
+            deposit_receipt_version: None,
+            cumulative_gas_used: 21_000,
+            logs: vec![Log { address, topics: vec![topic], data: Default::default() }],
+        };
+
+        let bloom = receipt.bloom_slow();
+        assert!(bloom.contains_bloom(&Bloom::from(Input::Raw(address.as_bytes()))));
+        assert!(bloom.contains_bloom(&Bloom::from(Input::Raw(topic.as_bytes()))));
+
+        let mut encoded = vec![];
+        receipt.with_bloom_ref().encode(&mut encoded);
+        assert_eq!(receipt.with_bloom_ref().length(), encoded.len());
+
+        let with_bloom = receipt.clone().with_bloom();
+        assert_eq!(with_bloom, ReceiptWithBloom { bloom, receipt });
+        let mut owned = vec![];
+        with_bloom.encode(&mut owned);
+        assert_eq!(owned, encoded);
+        assert_eq!(ReceiptWithBloom::decode(&mut &encoded[..]).unwrap(), with_bloom);
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn deposit_receipt_roundtrip() {
+        // the receipts of deposit transactions since Bedrock, Regolith and Canyon
+        for (deposit_nonce, deposit_receipt_version) in
+            [(None, None), (Some(4_100_000), None), (Some(4_100_000), Some(1))]
+        {
+            let receipt = Receipt {
+                tx_type: TxType::DEPOSIT,
+                success: true,
+                cumulative_gas_used: 46_913,
+                logs: vec![],
+                deposit_nonce,
+                deposit_receipt_version,
+            }
+            .with_bloom();
+
+            let mut encoded = vec![];
+            receipt.encode_inner(&mut encoded, false);
+            assert_eq!(encoded[0], 0x7E);
+            let mut with_header = vec![];
+            receipt.encode(&mut with_header);
+            assert_eq!(receipt.length(), with_header.len());
+            assert_eq!(ReceiptWithBloom::decode(&mut &with_header[..]).unwrap(), receipt);
+
+            let json = serde_json::to_string(&receipt).unwrap();
+            assert_eq!(serde_json::from_str::<ReceiptWithBloom>(&json).unwrap(), receipt);
+        }
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn decode_regolith_deposit_receipt() {
+        use crate::{
+            proofs::{calculate_receipt_root, calculate_receipt_root_optimism},
+            Chain, ChainSpec, ForkCondition, Hardfork,
+        };
+
+        // Test vector from the `decode_deposit_receipt_regolith_roundtrip` test of ethers-core
+        // 2.0.14
+        let data = hex!("7ef9010c0182b741b9010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000c0833d3bbf");
+        let expected = Receipt {
+            tx_type: TxType::DEPOSIT,
+            success: true,
+            cumulative_gas_used: 46_913,
+            logs: vec![],
+            deposit_nonce: Some(4_012_991),
+            deposit_receipt_version: None,
+        }
+        .with_bloom();
+
+        let mut encoded = vec![];
+        expected.encode_inner(&mut encoded, false);
+        assert_eq!(encoded, data);
+        let mut with_header = vec![];
+        expected.encode(&mut with_header);
+        let receipt = ReceiptWithBloom::decode(&mut &with_header[..]).unwrap();
         assert_eq!(receipt, expected);
+
+        let json = serde_json::to_string(&receipt).unwrap();
+        assert_eq!(serde_json::from_str::<ReceiptWithBloom>(&json).unwrap(), receipt);
+
+        // the deposit nonce is left out of the receipts root until Canyon
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::Id(10))
+            .genesis(Default::default())
+            .with_fork(Hardfork::Regolith, ForkCondition::Timestamp(100))
+            .with_fork(Hardfork::Canyon, ForkCondition::Timestamp(200))
+            .build();
+        let mut without_nonce = receipt.clone();
+        without_nonce.receipt.deposit_nonce = None;
+        assert_eq!(
+            calculate_receipt_root_optimism(&[receipt.clone()], &chain_spec, 100),
+            calculate_receipt_root(&[without_nonce])
+        );
+        assert_eq!(
+            calculate_receipt_root_optimism(&[receipt.clone()], &chain_spec, 200),
+            calculate_receipt_root(&[receipt])
+        );
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn deposit_fields_are_only_encoded_for_deposit_receipts() {
+        let receipt = Receipt {
+            tx_type: TxType::EIP1559,
+            success: true,
+            cumulative_gas_used: 21_000,
+            logs: vec![],
+            deposit_nonce: Some(1),
+            deposit_receipt_version: Some(1),
+        };
+        let mut encoded = vec![];
+        receipt.with_bloom_ref().encode(&mut encoded);
+
+        let decoded = ReceiptWithBloom::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(decoded.receipt.deposit_nonce, None);
+        assert_eq!(decoded.receipt.deposit_receipt_version, None);
     }
 }
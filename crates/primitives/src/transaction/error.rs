@@ -1,4 +1,5 @@
 use crate::U256;
+use reth_rlp::DecodeError;
 
 /// Represents error variants that can happen when trying to validate a
 /// [Transaction](crate::Transaction)
@@ -44,4 +45,73 @@ pub enum InvalidTransactionError {
     /// Thrown if the sender of a transaction is a contract.
     #[error("Transaction signer has bytecode set.")]
     SignerAccountHasBytecode,
+    /// Thrown if the `r` or `s` value of the signature is out of range.
+    #[error("Transaction signature values are out of range.")]
+    SignatureValueOutOfRange,
+}
+
+/// Represents errors that can happen when decoding a raw transaction, see
+/// [TransactionSigned::decode_enveloped](crate::TransactionSigned::decode_enveloped).
+///
+/// The offsets are the positions in the raw transaction where the decoding failed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+pub enum TransactionDecodeError {
+    /// The raw transaction is empty.
+    #[error("empty input")]
+    EmptyInput,
+    /// The type of the EIP-2718 typed transaction is unknown.
+    #[error("unknown transaction type {0:#04x}")]
+    UnknownType(u8),
+    /// The RLP header of the transaction fields is invalid.
+    #[error("rlp: {error} at offset {offset}")]
+    Header {
+        /// The offset of the header.
+        offset: usize,
+        /// The RLP error.
+        error: DecodeError,
+    },
+    /// A field of the transaction could not be decoded.
+    #[error("rlp: {error} at field {field}")]
+    Field {
+        /// The name of the field.
+        field: &'static str,
+        /// The offset of the field.
+        offset: usize,
+        /// The RLP error.
+        error: DecodeError,
+    },
+    /// The `v` value of a legacy signature neither is 27 or 28 nor encodes an EIP-155 chain id.
+    #[error("invalid chain id for signer")]
+    InvalidChainId {
+        /// The offset of the `v` value.
+        offset: usize,
+    },
+}
+
+impl TransactionDecodeError {
+    /// Returns the offset in the raw transaction where the decoding failed.
+    pub fn offset(&self) -> usize {
+        match self {
+            TransactionDecodeError::EmptyInput | TransactionDecodeError::UnknownType(_) => 0,
+            TransactionDecodeError::Header { offset, .. } |
+            TransactionDecodeError::Field { offset, .. } |
+            TransactionDecodeError::InvalidChainId { offset } => *offset,
+        }
+    }
+}
+
+impl From<TransactionDecodeError> for DecodeError {
+    fn from(err: TransactionDecodeError) -> Self {
+        match err {
+            TransactionDecodeError::EmptyInput => DecodeError::InputTooShort,
+            TransactionDecodeError::UnknownType(_) => {
+                DecodeError::Custom("unsupported typed transaction type")
+            }
+            TransactionDecodeError::Header { error, .. } |
+            TransactionDecodeError::Field { error, .. } => error,
+            TransactionDecodeError::InvalidChainId { .. } => {
+                DecodeError::Custom("invalid chain id for signer")
+            }
+        }
+    }
 }
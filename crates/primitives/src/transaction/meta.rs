@@ -0,0 +1,14 @@
+use crate::{BlockHash, BlockNumber, TxHash};
+
+/// The location of a transaction in a block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransactionMeta {
+    /// Hash of the transaction.
+    pub tx_hash: TxHash,
+    /// Index of the transaction in the block.
+    pub index: u64,
+    /// Hash of the block.
+    pub block_hash: BlockHash,
+    /// Number of the block.
+    pub block_number: BlockNumber,
+}
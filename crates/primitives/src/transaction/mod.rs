@@ -1,17 +1,21 @@
 use crate::{keccak256, Address, Bytes, ChainId, TxHash, H256};
-pub use access_list::{AccessList, AccessListItem, AccessListWithGasUsed};
+pub use access_list::{AccessList, AccessListItem};
 use bytes::{Buf, BytesMut};
 use derive_more::{AsRef, Deref};
-pub use error::InvalidTransactionError;
+pub use error::{InvalidTransactionError, TransactionDecodeError};
+pub use meta::TransactionMeta;
 use reth_codecs::{add_arbitrary_tests, main_codec, Compact};
 use reth_rlp::{
     length_of_length, Decodable, DecodeError, Encodable, Header, EMPTY_LIST_CODE, EMPTY_STRING_CODE,
 };
 pub use signature::Signature;
+#[cfg(feature = "optimism")]
+pub use tx_type::DEPOSIT_TX_TYPE_ID;
 pub use tx_type::{TxType, EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID, LEGACY_TX_TYPE_ID};
 
 mod access_list;
 mod error;
+mod meta;
 mod signature;
 mod tx_type;
 pub(crate) mod util;
@@ -661,29 +665,38 @@ impl TransactionSigned {
     /// Decodes legacy transaction from the data buffer.
     ///
     /// This expects `rlp(legacy_tx)`
-    fn decode_rlp_legacy_transaction(data: &mut &[u8]) -> Result<TransactionSigned, DecodeError> {
+    fn decode_rlp_legacy_transaction(
+        data: &mut &[u8],
+    ) -> Result<TransactionSigned, TransactionDecodeError> {
         // keep this around, so we can use it to calculate the hash
         let original_encoding = *data;
 
-        let header = Header::decode(data)?;
+        let mut decoder = FieldDecoder::new(data);
+        let header = decoder.header()?;
 
         let mut transaction = Transaction::Legacy(TxLegacy {
-            nonce: Decodable::decode(data)?,
-            gas_price: Decodable::decode(data)?,
-            gas_limit: Decodable::decode(data)?,
-            to: Decodable::decode(data)?,
-            value: Decodable::decode(data)?,
-            input: Bytes(Decodable::decode(data)?),
+            nonce: decoder.field("nonce")?,
+            gas_price: decoder.field("gasPrice")?,
+            gas_limit: decoder.field("gas")?,
+            to: decoder.field("to")?,
+            value: decoder.field("value")?,
+            input: Bytes(decoder.field("input")?),
             chain_id: None,
         });
-        let (signature, extracted_id) = Signature::decode_with_eip155_chain_id(data)?;
+        let v_offset = decoder.offset();
+        let v = decoder.field("v")?;
+        let r = decoder.field("r")?;
+        let s = decoder.field("s")?;
+        let (odd_y_parity, extracted_id) = Signature::extract_eip155_chain_id(v)
+            .ok_or(TransactionDecodeError::InvalidChainId { offset: v_offset })?;
         if let Some(id) = extracted_id {
             transaction.set_chain_id(id);
         }
 
         let tx_length = header.payload_length + header.length();
         let hash = keccak256(&original_encoding[..tx_length]);
-        let signed = TransactionSigned { transaction, hash, signature };
+        let signed =
+            TransactionSigned { transaction, hash, signature: Signature { r, s, odd_y_parity } };
         Ok(signed)
     }
 
@@ -692,48 +705,61 @@ impl TransactionSigned {
     /// CAUTION: this expects that `data` is `[id, rlp(tx)]`
     fn decode_enveloped_typed_transaction(
         data: &mut &[u8],
-    ) -> Result<TransactionSigned, DecodeError> {
+    ) -> Result<TransactionSigned, TransactionDecodeError> {
         // keep this around so we can use it to calculate the hash
         let original_encoding = *data;
 
-        let tx_type = *data.first().ok_or(DecodeError::InputTooShort)?;
-        data.advance(1);
+        let tx_type = *data.first().ok_or(TransactionDecodeError::EmptyInput)?;
+        if tx_type != EIP2930_TX_TYPE_ID && tx_type != EIP1559_TX_TYPE_ID {
+            return Err(TransactionDecodeError::UnknownType(tx_type))
+        }
+
+        let mut decoder = FieldDecoder::new(data);
+        decoder.buf.advance(1);
         // decode the list header for the rest of the transaction
-        let header = Header::decode(data)?;
+        let header_offset = decoder.offset();
+        let header = decoder.header()?;
         if !header.list {
-            return Err(DecodeError::Custom("typed tx fields must be encoded as a list"))
+            return Err(TransactionDecodeError::Header {
+                offset: header_offset,
+                error: DecodeError::UnexpectedString,
+            })
         }
 
         // length of tx encoding = tx type byte (size = 1) + length of header + payload length
         let tx_length = 1 + header.length() + header.payload_length;
 
         // decode common fields
-        let transaction = match tx_type {
-            1 => Transaction::Eip2930(TxEip2930 {
-                chain_id: Decodable::decode(data)?,
-                nonce: Decodable::decode(data)?,
-                gas_price: Decodable::decode(data)?,
-                gas_limit: Decodable::decode(data)?,
-                to: Decodable::decode(data)?,
-                value: Decodable::decode(data)?,
-                input: Bytes(Decodable::decode(data)?),
-                access_list: Decodable::decode(data)?,
-            }),
-            2 => Transaction::Eip1559(TxEip1559 {
-                chain_id: Decodable::decode(data)?,
-                nonce: Decodable::decode(data)?,
-                max_priority_fee_per_gas: Decodable::decode(data)?,
-                max_fee_per_gas: Decodable::decode(data)?,
-                gas_limit: Decodable::decode(data)?,
-                to: Decodable::decode(data)?,
-                value: Decodable::decode(data)?,
-                input: Bytes(Decodable::decode(data)?),
-                access_list: Decodable::decode(data)?,
-            }),
-            _ => return Err(DecodeError::Custom("unsupported typed transaction type")),
+        let transaction = if tx_type == EIP2930_TX_TYPE_ID {
+            Transaction::Eip2930(TxEip2930 {
+                chain_id: decoder.field("chainId")?,
+                nonce: decoder.field("nonce")?,
+                gas_price: decoder.field("gasPrice")?,
+                gas_limit: decoder.field("gas")?,
+                to: decoder.field("to")?,
+                value: decoder.field("value")?,
+                input: Bytes(decoder.field("input")?),
+                access_list: decoder.field("accessList")?,
+            })
+        } else {
+            Transaction::Eip1559(TxEip1559 {
+                chain_id: decoder.field("chainId")?,
+                nonce: decoder.field("nonce")?,
+                max_priority_fee_per_gas: decoder.field("maxPriorityFeePerGas")?,
+                max_fee_per_gas: decoder.field("maxFeePerGas")?,
+                gas_limit: decoder.field("gas")?,
+                to: decoder.field("to")?,
+                value: decoder.field("value")?,
+                input: Bytes(decoder.field("input")?),
+                access_list: decoder.field("accessList")?,
+            })
         };
 
-        let signature = Signature::decode(data)?;
+        let signature = Signature {
+            odd_y_parity: decoder.field("yParity")?,
+            r: decoder.field("r")?,
+            s: decoder.field("s")?,
+        };
 
         let hash = keccak256(&original_encoding[..tx_length]);
         let signed = TransactionSigned { transaction, hash, signature };
@@ -746,11 +772,13 @@ impl TransactionSigned {
     /// For legacy transactions, the format is encoded as: `rlp(tx)`
     /// For EIP-2718 typed transaction, the format is encoded as the type of the transaction
     /// followed by the rlp of the transaction: `type` + `rlp(tx)`
-    pub fn decode_enveloped(tx: Bytes) -> Result<Self, DecodeError> {
+    ///
+    /// The returned errors contain the offset in `tx` where the decoding failed.
+    pub fn decode_enveloped(tx: Bytes) -> Result<Self, TransactionDecodeError> {
         let mut data = tx.as_ref();
 
         if data.is_empty() {
-            return Err(DecodeError::InputTooShort)
+            return Err(TransactionDecodeError::EmptyInput)
         }
 
         // Check if the tx is a list
@@ -763,6 +791,36 @@ impl TransactionSigned {
     }
 }
 
+/// Decodes the fields of a transaction and keeps track of their offsets for
+/// [TransactionDecodeError]s.
+struct FieldDecoder<'a, 'b> {
+    buf: &'b mut &'a [u8],
+    /// The length of the buffer when decoding started.
+    len: usize,
+}
+
+impl<'a, 'b> FieldDecoder<'a, 'b> {
+    fn new(buf: &'b mut &'a [u8]) -> Self {
+        let len = buf.len();
+        Self { buf, len }
+    }
+
+    /// Returns the offset of the next field.
+    fn offset(&self) -> usize {
+        self.len - self.buf.len()
+    }
+
+    fn header(&mut self) -> Result<Header, TransactionDecodeError> {
+        let offset = self.offset();
+        Header::decode(self.buf).map_err(|error| TransactionDecodeError::Header { offset, error })
+    }
+
+    fn field<T: Decodable>(&mut self, field: &'static str) -> Result<T, TransactionDecodeError> {
+        let offset = self.offset();
+        T::decode(self.buf).map_err(|error| TransactionDecodeError::Field { field, offset, error })
+    }
+}
+
 impl From<TransactionSignedEcRecovered> for TransactionSigned {
     fn from(recovered: TransactionSignedEcRecovered) -> Self {
         recovered.signed_transaction
@@ -791,7 +849,7 @@ impl Decodable for TransactionSigned {
 
         // if the transaction is encoded as a string then it is a typed transaction
         if !header.list {
-            TransactionSigned::decode_enveloped_typed_transaction(buf)
+            Ok(TransactionSigned::decode_enveloped_typed_transaction(buf)?)
         } else {
             let tx = TransactionSigned::decode_rlp_legacy_transaction(&mut original_encoding)?;
 
@@ -940,8 +998,8 @@ impl IntoRecoveredTransaction for TransactionSignedEcRecovered {
 mod tests {
     use crate::{
         transaction::{signature::Signature, TransactionKind, TxEip1559, TxEip2930, TxLegacy},
-        AccessList, Address, Bytes, Transaction, TransactionSigned, TransactionSignedEcRecovered,
-        H256, U256,
+        AccessList, Address, Bytes, Transaction, TransactionDecodeError, TransactionSigned,
+        TransactionSignedEcRecovered, H256, U256,
     };
     use bytes::BytesMut;
     use ethers_core::utils::hex;
@@ -1267,6 +1325,83 @@ mod tests {
         assert_eq!(encoded, input);
     }
 
+    #[test]
+    fn decode_enveloped_errors() {
+        assert_eq!(
+            TransactionSigned::decode_enveloped(Bytes::default()),
+            Err(TransactionDecodeError::EmptyInput)
+        );
+
+        let err = TransactionSigned::decode_enveloped(vec![0x03, 0xc0].into()).unwrap_err();
+        assert_eq!(err, TransactionDecodeError::UnknownType(0x03));
+        assert_eq!(err.to_string(), "unknown transaction type 0x03");
+
+        // a typed transaction with a string instead of the list of fields
+        let err = TransactionSigned::decode_enveloped(vec![0x02, 0x80].into()).unwrap_err();
+        assert_eq!(
+            err,
+            TransactionDecodeError::Header { offset: 1, error: DecodeError::UnexpectedString }
+        );
+
+        // a legacy transaction with a nonce that does not fit into a u64
+        let raw = hex::decode("ca89010000000000000000").unwrap();
+        let err = TransactionSigned::decode_enveloped(raw.into()).unwrap_err();
+        assert_eq!(
+            err,
+            TransactionDecodeError::Field {
+                field: "nonce",
+                offset: 1,
+                error: DecodeError::Overflow
+            }
+        );
+        assert_eq!(err.to_string(), "rlp: overflow at field nonce");
+
+        // the ropsten transaction of `decode_raw_tx_and_recover_signer`
+        let raw = hex::decode("f88b8212b085028fa6ae00830f424094aad593da0c8116ef7d2d594dd6a63241bccfc26c80a48318b64b000000000000000000000000641c5d790f862a58ec7abcfd644c0442e9c201b32aa0a6ef9e170bca5ffb7ac05433b13b7043de667fbb0b4a5e45d3b54fb2d6efcc63a0037ec2c05c3d60c5f5f78244ce0a3859e3a18a36c61efb061b383507d3ce19d2").unwrap();
+
+        // v is followed by r and s, 33 bytes each
+        let v_offset = raw.len() - 67;
+        assert_eq!(raw[v_offset], 0x2a);
+        let mut invalid_v = raw.clone();
+        invalid_v[v_offset] = 29;
+        let err = TransactionSigned::decode_enveloped(invalid_v.into()).unwrap_err();
+        assert_eq!(err, TransactionDecodeError::InvalidChainId { offset: v_offset });
+        assert_eq!(err.to_string(), "invalid chain id for signer");
+
+        // the list header claims one byte more than there is
+        let mut too_long = raw;
+        too_long[1] += 1;
+        let err = TransactionSigned::decode_enveloped(too_long.into()).unwrap_err();
+        assert_eq!(
+            err,
+            TransactionDecodeError::Header { offset: 0, error: DecodeError::InputTooShort }
+        );
+    }
+
+    #[test]
+    fn decode_truncated_envelopes() {
+        let legacy = hex::decode("f88b8212b085028fa6ae00830f424094aad593da0c8116ef7d2d594dd6a63241bccfc26c80a48318b64b000000000000000000000000641c5d790f862a58ec7abcfd644c0442e9c201b32aa0a6ef9e170bca5ffb7ac05433b13b7043de667fbb0b4a5e45d3b54fb2d6efcc63a0037ec2c05c3d60c5f5f78244ce0a3859e3a18a36c61efb061b383507d3ce19d2").unwrap();
+        let eip1559 = hex::decode("02f871018302a90f808504890aef60826b6c94ddf4c5025d1a5742cf12f74eec246d4432c295e487e09c3bbcc12b2b80c080a0f21a4eacd0bf8fea9c5105c543be5a1d8c796516875710fafafdf16d16d8ee23a001280915021bb446d1973501a67f93d2b38894a514b976e7b46dc2fe54598d76").unwrap();
+
+        for (raw, header_offset) in [(legacy, 0), (eip1559, 1)] {
+            assert!(TransactionSigned::decode_enveloped(raw.clone().into()).is_ok());
+
+            // the list header claims more bytes than there are
+            for len in 1..raw.len() {
+                let err = TransactionSigned::decode_enveloped(raw[..len].to_vec().into())
+                    .expect_err("truncated envelope decoded");
+                assert_eq!(
+                    err,
+                    TransactionDecodeError::Header {
+                        offset: header_offset,
+                        error: DecodeError::InputTooShort
+                    },
+                    "unexpected error for {len} bytes"
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_decode_signed_ec_recovered_transaction() {
         // random tx: <https://etherscan.io/getRawTx?tx=0x9448608d36e721ef403c53b00546068a6474d6cbab6816c3926de449898e7bce>
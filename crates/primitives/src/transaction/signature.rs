@@ -1,6 +1,6 @@
 use crate::{transaction::util::secp256k1, Address, H256, U256};
 use reth_codecs::{main_codec, Compact};
-use reth_rlp::{Decodable, DecodeError, Encodable};
+use reth_rlp::Encodable;
 
 /// r, s: Values corresponding to the signature of the
 /// transaction and used to determine the sender of
@@ -46,23 +46,16 @@ impl Signature {
         }
     }
 
-    /// Decodes the `v`, `r`, `s` values without a RLP header.
-    /// This will return a chain ID if the `v` value is EIP-155 compatible.
-    pub(crate) fn decode_with_eip155_chain_id(
-        buf: &mut &[u8],
-    ) -> Result<(Self, Option<u64>), DecodeError> {
-        let v = u64::decode(buf)?;
-        let r = Decodable::decode(buf)?;
-        let s = Decodable::decode(buf)?;
-        if v >= 35 {
+    /// Returns the y parity and the chain ID of a legacy `v` value.
+    ///
+    /// Returns `None` if the value neither is 27 or 28 nor EIP-155 compatible.
+    pub(crate) fn extract_eip155_chain_id(v: u64) -> Option<(bool, Option<u64>)> {
+        match v {
             // EIP-155: v = {0, 1} + CHAIN_ID * 2 + 35
-            let odd_y_parity = ((v - 35) % 2) != 0;
-            let chain_id = (v - 35) >> 1;
-            Ok((Signature { r, s, odd_y_parity }, Some(chain_id)))
-        } else {
+            35.. => Some((((v - 35) % 2) != 0, Some((v - 35) >> 1))),
             // non-EIP-155 legacy scheme
-            let odd_y_parity = (v - 27) != 0;
-            Ok((Signature { r, s, odd_y_parity }, None))
+            27 | 28 => Some((v == 28, None)),
+            _ => None,
         }
     }
 
@@ -78,15 +71,6 @@ impl Signature {
         self.s.encode(out);
     }
 
-    /// Decodes the `odd_y_parity`, `r`, `s` values without a RLP header.
-    pub(crate) fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
-        Ok(Signature {
-            odd_y_parity: Decodable::decode(buf)?,
-            r: Decodable::decode(buf)?,
-            s: Decodable::decode(buf)?,
-        })
-    }
-
     /// Recover signature from hash.
     pub(crate) fn recover_signer(&self, hash: H256) -> Option<Address> {
         let mut sig: [u8; 65] = [0; 65];
@@ -117,6 +101,7 @@ mod tests {
     use std::str::FromStr;
 
     use bytes::BytesMut;
+    use reth_rlp::Decodable;
 
     use crate::{Address, Signature, H256, U256};
 
@@ -147,18 +132,37 @@ mod tests {
         let mut encoded = BytesMut::new();
         signature.encode_with_eip155_chain_id(&mut encoded, None);
         assert_eq!(encoded.len(), signature.payload_len_with_eip155_chain_id(None));
-        let (decoded, chain_id) = Signature::decode_with_eip155_chain_id(&mut &*encoded).unwrap();
+        let (decoded, chain_id) = decode_with_eip155_chain_id(&mut &*encoded);
         assert_eq!(signature, decoded);
         assert_eq!(None, chain_id);
 
         let mut encoded = BytesMut::new();
         signature.encode_with_eip155_chain_id(&mut encoded, Some(1));
         assert_eq!(encoded.len(), signature.payload_len_with_eip155_chain_id(Some(1)));
-        let (decoded, chain_id) = Signature::decode_with_eip155_chain_id(&mut &*encoded).unwrap();
+        let (decoded, chain_id) = decode_with_eip155_chain_id(&mut &*encoded);
         assert_eq!(signature, decoded);
         assert_eq!(Some(1), chain_id);
     }
 
+    fn decode_with_eip155_chain_id(buf: &mut &[u8]) -> (Signature, Option<u64>) {
+        let v = u64::decode(buf).unwrap();
+        let r = Decodable::decode(buf).unwrap();
+        let s = Decodable::decode(buf).unwrap();
+        let (odd_y_parity, chain_id) = Signature::extract_eip155_chain_id(v).unwrap();
+        (Signature { r, s, odd_y_parity }, chain_id)
+    }
+
+    #[test]
+    fn extract_eip155_chain_id() {
+        assert_eq!(Signature::extract_eip155_chain_id(27), Some((false, None)));
+        assert_eq!(Signature::extract_eip155_chain_id(28), Some((true, None)));
+        assert_eq!(Signature::extract_eip155_chain_id(37), Some((false, Some(1))));
+        assert_eq!(Signature::extract_eip155_chain_id(38), Some((true, Some(1))));
+        assert_eq!(Signature::extract_eip155_chain_id(0), None);
+        assert_eq!(Signature::extract_eip155_chain_id(29), None);
+        assert_eq!(Signature::extract_eip155_chain_id(34), None);
+    }
+
     #[test]
     fn test_payload_len() {
         let signature = Signature { r: U256::default(), s: U256::default(), odd_y_parity: false };
@@ -172,7 +176,12 @@ mod tests {
         let mut encoded = BytesMut::new();
         signature.encode(&mut encoded);
         assert_eq!(encoded.len(), signature.payload_len());
-        let decoded = Signature::decode(&mut &*encoded).unwrap();
+        let buf = &mut &*encoded;
+        let decoded = Signature {
+            odd_y_parity: Decodable::decode(buf).unwrap(),
+            r: Decodable::decode(buf).unwrap(),
+            s: Decodable::decode(buf).unwrap(),
+        };
         assert_eq!(signature, decoded);
     }
 
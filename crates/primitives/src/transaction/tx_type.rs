@@ -11,6 +11,10 @@ pub const EIP2930_TX_TYPE_ID: u8 = 1;
 /// Identifier for [TxEip1559](crate::TxEip1559) transaction.
 pub const EIP1559_TX_TYPE_ID: u8 = 2;
 
+/// Identifier for the deposit transactions of Optimism.
+#[cfg(feature = "optimism")]
+pub const DEPOSIT_TX_TYPE_ID: u8 = 126;
+
 /// Transaction Type
 #[derive_arbitrary(compact)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
@@ -22,6 +26,9 @@ pub enum TxType {
     EIP2930 = 1_isize,
     /// Transaction with Priority fee
     EIP1559 = 2_isize,
+    /// Optimism deposit transaction
+    #[cfg(feature = "optimism")]
+    DEPOSIT = 126_isize,
 }
 
 impl From<TxType> for u8 {
@@ -30,6 +37,8 @@ impl From<TxType> for u8 {
             TxType::Legacy => LEGACY_TX_TYPE_ID,
             TxType::EIP2930 => EIP2930_TX_TYPE_ID,
             TxType::EIP1559 => EIP1559_TX_TYPE_ID,
+            #[cfg(feature = "optimism")]
+            TxType::DEPOSIT => DEPOSIT_TX_TYPE_ID,
         }
     }
 }
@@ -40,6 +49,8 @@ impl Compact for TxType {
             TxType::Legacy => 0,
             TxType::EIP2930 => 1,
             TxType::EIP1559 => 2,
+            #[cfg(feature = "optimism")]
+            TxType::DEPOSIT => 3,
         }
     }
 
@@ -48,6 +59,8 @@ impl Compact for TxType {
             match identifier {
                 0 => TxType::Legacy,
                 1 => TxType::EIP2930,
+                #[cfg(feature = "optimism")]
+                3 => TxType::DEPOSIT,
                 _ => TxType::EIP1559,
             },
             buf,
@@ -0,0 +1,52 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use reth_primitives::{sign_message, Transaction, TxLegacy, H256};
+use reth_revm::ecrecover::EcrecoverCache;
+
+/// Number of `ecrecover` calls of the replayed block.
+const CALLS: u64 = 1_000;
+
+/// Returns the `ecrecover` inputs of a block stuffed with precompile calls.
+fn block_inputs() -> Vec<Vec<u8>> {
+    (0..CALLS)
+        .map(|nonce| {
+            let hash =
+                Transaction::Legacy(TxLegacy { nonce, ..Default::default() }).signature_hash();
+            let signature = sign_message(H256::from_low_u64_be(1), hash).unwrap();
+            let mut input = hash.as_bytes().to_vec();
+            input.extend(H256::from_low_u64_be(27 + signature.odd_y_parity as u64).as_bytes());
+            input.extend(signature.r.to_be_bytes::<32>());
+            input.extend(signature.s.to_be_bytes::<32>());
+            input
+        })
+        .collect()
+}
+
+fn replay(cache: &EcrecoverCache, inputs: &[Vec<u8>]) {
+    for input in inputs {
+        cache.call(input, 3_000).unwrap();
+    }
+}
+
+/// Benchmarks the precompile time of replaying the same block twice, for example by two tracing
+/// calls: the second replay is answered from the cache.
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let inputs = block_inputs();
+    let mut group = c.benchmark_group("ecrecover cache");
+
+    group.bench_function("first replay", |b| {
+        b.iter_batched(
+            || EcrecoverCache::new(CALLS as u32),
+            |cache| replay(&cache, &inputs),
+            BatchSize::SmallInput,
+        )
+    });
+
+    let cache = EcrecoverCache::new(CALLS as u32);
+    replay(&cache, &inputs);
+    group.bench_function("second replay", |b| b.iter(|| replay(&cache, &inputs)));
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -10,6 +10,15 @@
 /// An inspector implementation for an EIP2930 Accesslist
 pub mod access_list;
 
+/// An inspector that counts the executed storage opcodes
+pub mod opcode_counter;
+
+/// An inspector that adjusts the gas cost of opcodes
+pub mod opcode_gas;
+
+/// An inspector that answers calls to custom precompiles
+pub mod precompiles;
+
 /// An inspector stack abstracting the implementation details of
 /// each inspector and allowing to hook on block/transaciton execution,
 /// used in the main RETH executor.
@@ -0,0 +1,34 @@
+use revm::{
+    interpreter::{opcode, InstructionResult, Interpreter},
+    Database, EVMData, Inspector,
+};
+
+/// An [Inspector] that counts the storage opcodes executed by a transaction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpcodeCounter {
+    /// Number of executed `SLOAD`s.
+    pub sloads: u64,
+    /// Number of executed `SSTORE`s.
+    pub sstores: u64,
+}
+
+impl<DB> Inspector<DB> for OpcodeCounter
+where
+    DB: Database,
+{
+    fn step(
+        &mut self,
+        interpreter: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> InstructionResult {
+        let pc = interpreter.program_counter();
+        match interpreter.contract.bytecode.bytecode()[pc] {
+            opcode::SLOAD => self.sloads += 1,
+            opcode::SSTORE => self.sstores += 1,
+            _ => (),
+        }
+
+        InstructionResult::Continue
+    }
+}
@@ -0,0 +1,118 @@
+use reth_revm_primitives::OpcodeGasAdjustments;
+use revm::{
+    interpreter::{InstructionResult, Interpreter},
+    Database, EVMData, Inspector,
+};
+
+/// An [Inspector] that adjusts the gas cost of opcodes, see
+/// [EvmConfig::opcode_gas](reth_revm_primitives::EvmConfig::opcode_gas).
+///
+/// Additional gas is charged before the opcode is executed, so the opcode runs out of gas if the
+/// adjusted cost exceeds the remaining gas. Removed gas is returned after the opcode is executed,
+/// at most the gas the call frame used so far.
+#[derive(Clone, Debug, Default)]
+pub struct OpcodeGasInspector {
+    adjustments: OpcodeGasAdjustments,
+    /// The gas to return after the current opcode.
+    pending_refund: u64,
+}
+
+impl OpcodeGasInspector {
+    /// Creates an inspector that applies the given adjustments.
+    pub fn new(adjustments: OpcodeGasAdjustments) -> Self {
+        Self { adjustments, pending_refund: 0 }
+    }
+
+    /// Returns true if there are no adjustments to apply.
+    pub fn is_empty(&self) -> bool {
+        self.adjustments.is_empty()
+    }
+}
+
+impl<DB> Inspector<DB> for OpcodeGasInspector
+where
+    DB: Database,
+{
+    fn step(
+        &mut self,
+        interpreter: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> InstructionResult {
+        let pc = interpreter.program_counter();
+        let opcode = interpreter.contract.bytecode.bytecode()[pc];
+        match self.adjustments.get(&opcode) {
+            Some(&adjustment) if adjustment >= 0 => {
+                if !interpreter.gas.record_cost(adjustment as u64) {
+                    return InstructionResult::OutOfGas
+                }
+            }
+            Some(&adjustment) => self.pending_refund = adjustment.unsigned_abs(),
+            None => (),
+        }
+
+        InstructionResult::Continue
+    }
+
+    fn step_end(
+        &mut self,
+        interpreter: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+        _eval: InstructionResult,
+    ) -> InstructionResult {
+        let refund = std::mem::take(&mut self.pending_refund);
+        if refund > 0 {
+            let used = interpreter.gas.spend() - interpreter.gas.memory();
+            interpreter.gas.erase_cost(refund.min(used));
+        }
+
+        InstructionResult::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Address, Bytes};
+    use revm::{
+        db::{CacheDB, EmptyDB},
+        interpreter::opcode,
+        primitives::{AccountInfo, Bytecode, TransactTo, U256},
+        EVM,
+    };
+
+    /// Calls a contract that executes `PUSH1 0x00; SLOAD; STOP` with the adjustments and returns
+    /// the used gas.
+    fn sload_gas_used(adjustments: OpcodeGasAdjustments) -> u64 {
+        let contract = Address::from_low_u64_be(0x1000);
+        let code = Bytes::from(vec![opcode::PUSH1, 0x00, opcode::SLOAD, opcode::STOP]);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo { code: Some(Bytecode::new_raw(code.0)), ..Default::default() },
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 100_000;
+        evm.env.tx.gas_price = U256::ZERO;
+        evm.inspect(OpcodeGasInspector::new(adjustments)).unwrap().result.gas_used()
+    }
+
+    #[test]
+    fn adjusts_opcode_gas() {
+        let gas_used = sload_gas_used(OpcodeGasAdjustments::default());
+
+        let raised = OpcodeGasAdjustments::from([(opcode::SLOAD, 1_000)]);
+        assert_eq!(sload_gas_used(raised), gas_used + 1_000);
+
+        let lowered = OpcodeGasAdjustments::from([(opcode::SLOAD, -100)]);
+        assert_eq!(sload_gas_used(lowered), gas_used - 100);
+
+        // the adjusted cost doesn't fit the gas limit, the call runs out of gas
+        let exceeding = OpcodeGasAdjustments::from([(opcode::SLOAD, 200_000)]);
+        assert_eq!(sload_gas_used(exceeding), 100_000);
+    }
+}
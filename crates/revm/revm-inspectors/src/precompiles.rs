@@ -0,0 +1,65 @@
+use reth_primitives::bytes::Bytes;
+use reth_revm_primitives::CustomPrecompiles;
+use revm::{
+    interpreter::{CallInputs, Gas, InstructionResult},
+    precompile::Error as PrecompileError,
+    Database, EVMData, Inspector,
+};
+use std::fmt;
+
+/// An [Inspector] that answers the calls to custom precompiles, see
+/// [EvmConfig::precompiles](reth_revm_primitives::EvmConfig::precompiles).
+///
+/// The calls are answered before the EVM looks up its own precompiles, so a custom precompile
+/// replaces a precompile of the spec at the same address.
+#[derive(Clone, Default)]
+pub struct PrecompilesInspector {
+    precompiles: CustomPrecompiles,
+}
+
+impl PrecompilesInspector {
+    /// Creates an inspector that answers calls to the given precompiles.
+    pub fn new(precompiles: CustomPrecompiles) -> Self {
+        Self { precompiles }
+    }
+
+    /// Returns true if there are no precompiles to answer calls to.
+    pub fn is_empty(&self) -> bool {
+        self.precompiles.is_empty()
+    }
+}
+
+impl fmt::Debug for PrecompilesInspector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrecompilesInspector")
+            .field("precompiles", &self.precompiles.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<DB> Inspector<DB> for PrecompilesInspector
+where
+    DB: Database,
+{
+    fn call(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CallInputs,
+        _is_static: bool,
+    ) -> (InstructionResult, Gas, Bytes) {
+        let mut gas = Gas::new(inputs.gas_limit);
+        let Some(precompile) = self.precompiles.get(&inputs.contract) else {
+            return (InstructionResult::Continue, gas, Bytes::new())
+        };
+
+        match precompile(&inputs.input, inputs.gas_limit) {
+            Ok((gas_used, output)) if gas.record_cost(gas_used) => {
+                (InstructionResult::Return, gas, output.into())
+            }
+            Ok(_) | Err(PrecompileError::OutOfGas) => {
+                (InstructionResult::OutOfGas, gas, Bytes::new())
+            }
+            Err(_) => (InstructionResult::PrecompileError, gas, Bytes::new()),
+        }
+    }
+}
@@ -1,8 +1,13 @@
+use crate::{
+    opcode_counter::OpcodeCounter, opcode_gas::OpcodeGasInspector,
+    precompiles::PrecompilesInspector, tracing::TracingInspector,
+};
 use reth_primitives::{bytes::Bytes, Address, TxHash, H256};
+use reth_revm_primitives::EvmConfig;
 use revm::{
     inspectors::CustomPrintTracer,
     interpreter::{CallInputs, CreateInputs, Gas, InstructionResult, Interpreter},
-    primitives::Env,
+    primitives::{Env, SpecId},
     Database, EVMData, Inspector,
 };
 
@@ -37,6 +42,22 @@ pub struct InspectorStack {
     pub custom_print_tracer: Option<CustomPrintTracer>,
     /// The provided hook
     pub hook: Hook,
+    /// Answers the calls to custom precompiles.
+    ///
+    /// If set, all transactions are inspected.
+    pub precompiles: Option<PrecompilesInspector>,
+    /// Adjusts the gas cost of opcodes.
+    ///
+    /// If set, all transactions are inspected.
+    pub opcode_gas: Option<OpcodeGasInspector>,
+    /// Records the call traces of the transactions.
+    ///
+    /// If set, all transactions are inspected.
+    pub tracer: Option<TracingInspector>,
+    /// Counts the executed storage opcodes of the transactions.
+    ///
+    /// If set, all transactions are inspected.
+    pub opcode_counter: Option<OpcodeCounter>,
 }
 
 impl InspectorStack {
@@ -51,8 +72,26 @@ impl InspectorStack {
         stack
     }
 
+    /// Sets the inspectors that apply the custom precompiles and the opcode gas adjustments of the
+    /// [EvmConfig] for the given spec.
+    pub fn set_evm_config<E: EvmConfig + ?Sized>(&mut self, evm_config: &E, spec_id: SpecId) {
+        let precompiles = evm_config.precompiles(spec_id);
+        self.precompiles =
+            (!precompiles.is_empty()).then(|| PrecompilesInspector::new(precompiles));
+        let opcode_gas = evm_config.opcode_gas(spec_id);
+        self.opcode_gas = (!opcode_gas.is_empty()).then(|| OpcodeGasInspector::new(opcode_gas));
+    }
+
     /// Check if the inspector should be used.
     pub fn should_inspect(&self, env: &Env, tx_hash: TxHash) -> bool {
+        if self.precompiles.is_some() ||
+            self.opcode_gas.is_some() ||
+            self.tracer.is_some() ||
+            self.opcode_counter.is_some()
+        {
+            return true
+        }
+
         match self.hook {
             Hook::None => false,
             Hook::Block(block) => env.block.number.to::<u64>() == block,
@@ -96,7 +135,7 @@ where
         data: &mut EVMData<'_, DB>,
         is_static: bool,
     ) -> InstructionResult {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [&mut self.custom_print_tracer, &mut self.tracer], {
             let status = inspector.initialize_interp(interpreter, data, is_static);
 
             // Allow inspectors to exit early
@@ -114,14 +153,24 @@ where
         data: &mut EVMData<'_, DB>,
         is_static: bool,
     ) -> InstructionResult {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
-            let status = inspector.step(interpreter, data, is_static);
-
-            // Allow inspectors to exit early
-            if status != InstructionResult::Continue {
-                return status
+        // the gas is adjusted first, so the tracer sees the adjusted cost
+        call_inspectors!(
+            inspector,
+            [
+                &mut self.opcode_gas,
+                &mut self.custom_print_tracer,
+                &mut self.tracer,
+                &mut self.opcode_counter
+            ],
+            {
+                let status = inspector.step(interpreter, data, is_static);
+
+                // Allow inspectors to exit early
+                if status != InstructionResult::Continue {
+                    return status
+                }
             }
-        });
+        );
 
         InstructionResult::Continue
     }
@@ -133,7 +182,7 @@ where
         topics: &[H256],
         data: &Bytes,
     ) {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [&mut self.custom_print_tracer, &mut self.tracer], {
             inspector.log(evm_data, address, topics, data);
         });
     }
@@ -145,14 +194,18 @@ where
         is_static: bool,
         eval: InstructionResult,
     ) -> InstructionResult {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
-            let status = inspector.step_end(interpreter, data, is_static, eval);
-
-            // Allow inspectors to exit early
-            if status != InstructionResult::Continue {
-                return status
+        call_inspectors!(
+            inspector,
+            [&mut self.opcode_gas, &mut self.custom_print_tracer, &mut self.tracer],
+            {
+                let status = inspector.step_end(interpreter, data, is_static, eval);
+
+                // Allow inspectors to exit early
+                if status != InstructionResult::Continue {
+                    return status
+                }
             }
-        });
+        );
 
         InstructionResult::Continue
     }
@@ -163,14 +216,19 @@ where
         inputs: &mut CallInputs,
         is_static: bool,
     ) -> (InstructionResult, Gas, Bytes) {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
-            let (status, gas, retdata) = inspector.call(data, inputs, is_static);
-
-            // Allow inspectors to exit early
-            if status != InstructionResult::Continue {
-                return (status, gas, retdata)
+        // the tracer is called first, it also sees the calls that are answered by the precompiles
+        call_inspectors!(
+            inspector,
+            [&mut self.tracer, &mut self.precompiles, &mut self.custom_print_tracer],
+            {
+                let (status, gas, retdata) = inspector.call(data, inputs, is_static);
+
+                // Allow inspectors to exit early
+                if status != InstructionResult::Continue {
+                    return (status, gas, retdata)
+                }
             }
-        });
+        );
 
         (InstructionResult::Continue, Gas::new(inputs.gas_limit), Bytes::new())
     }
@@ -184,7 +242,7 @@ where
         out: Bytes,
         is_static: bool,
     ) -> (InstructionResult, Gas, Bytes) {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [&mut self.custom_print_tracer, &mut self.tracer], {
             let (new_ret, new_gas, new_out) =
                 inspector.call_end(data, inputs, remaining_gas, ret, out.clone(), is_static);
 
@@ -203,7 +261,7 @@ where
         data: &mut EVMData<'_, DB>,
         inputs: &mut CreateInputs,
     ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [&mut self.custom_print_tracer, &mut self.tracer], {
             let (status, addr, gas, retdata) = inspector.create(data, inputs);
 
             // Allow inspectors to exit early
@@ -224,7 +282,7 @@ where
         remaining_gas: Gas,
         out: Bytes,
     ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [&mut self.custom_print_tracer, &mut self.tracer], {
             let (new_ret, new_address, new_gas, new_retdata) =
                 inspector.create_end(data, inputs, ret, address, remaining_gas, out.clone());
 
@@ -237,7 +295,7 @@ where
     }
 
     fn selfdestruct(&mut self, contract: Address, target: Address) {
-        call_inspectors!(inspector, [&mut self.custom_print_tracer], {
+        call_inspectors!(inspector, [&mut self.custom_print_tracer, &mut self.tracer], {
             Inspector::<DB>::selfdestruct(inspector, contract, target);
         });
     }
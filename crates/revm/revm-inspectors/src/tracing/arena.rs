@@ -2,7 +2,7 @@ use crate::tracing::types::{CallTrace, CallTraceNode, LogCallOrder};
 use reth_primitives::{Address, JsonU256, H256, U256};
 use reth_rpc_types::trace::{
     geth::{DefaultFrame, GethDebugTracingOptions, StructLog},
-    parity::{ActionType, TransactionTrace},
+    parity::{TraceResult, TransactionTrace},
 };
 use revm::interpreter::{opcode, InstructionResult};
 use std::collections::{BTreeMap, HashMap};
@@ -50,31 +50,45 @@ impl CallTraceArena {
         }
     }
 
-    /// Returns the traces of the transaction for `trace_transaction`
+    /// Returns the traces of the transaction for `trace_transaction`, in the order the calls were
+    /// entered.
     pub fn parity_traces(&self) -> Vec<TransactionTrace> {
-        let traces = Vec::with_capacity(self.arena.len());
-        for (_idx, node) in self.arena.iter().cloned().enumerate() {
-            let _action = node.parity_action();
-            let _result = node.parity_result();
-
-            let _action_type = if node.status() == InstructionResult::SelfDestruct {
-                ActionType::Selfdestruct
-            } else {
-                node.kind().into()
-            };
-
-            todo!()
+        self.arena
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| {
+                let result = if node.status() == InstructionResult::SelfDestruct {
+                    None
+                } else if node.trace.success {
+                    Some(TraceResult::Success { result: node.parity_result() })
+                } else {
+                    Some(TraceResult::Error { error: node.parity_error() })
+                };
+                TransactionTrace {
+                    trace_address: self.trace_address(idx),
+                    subtraces: node.children.len(),
+                    action: node.parity_action(),
+                    result,
+                }
+            })
+            .collect()
+    }
 
-            // let trace = TransactionTrace {
-            //     action,
-            //     result: Some(result),
-            //     trace_address: self.info.trace_address(idx),
-            //     subtraces: node.children.len(),
-            // };
-            // traces.push(trace)
+    /// Returns the position of the call among the subcalls of each of its ancestors, outermost
+    /// first.
+    fn trace_address(&self, mut idx: usize) -> Vec<usize> {
+        let mut address = Vec::new();
+        while let Some(parent) = self.arena[idx].parent {
+            let position = self.arena[parent]
+                .children
+                .iter()
+                .position(|child| *child == idx)
+                .expect("call is a child of its parent");
+            address.push(position);
+            idx = parent;
         }
-
-        traces
+        address.reverse();
+        address
     }
 
     /// Recursively fill in the geth trace by going through the traces
@@ -154,6 +168,7 @@ impl CallTraceArena {
             failed: !main_trace.success,
             gas: JsonU256(receipt_gas_used),
             return_value: main_trace.output.clone().into(),
+            gas_refund: Some(main_trace.gas_refund),
             struct_logs,
         }
     }
@@ -10,8 +10,8 @@ use reth_primitives::{bytes::Bytes, Address, H256, U256};
 use revm::{
     inspectors::GasInspector,
     interpreter::{
-        opcode, return_ok, CallInputs, CallScheme, CreateInputs, Gas, InstructionResult,
-        Interpreter, OpCode,
+        opcode, return_ok, return_revert, CallInputs, CallScheme, CreateInputs, Gas,
+        InstructionResult, Interpreter, OpCode,
     },
     Database, EVMData, Inspector, JournalEntry,
 };
@@ -138,6 +138,7 @@ impl TracingInspector {
     fn fill_trace_on_call_end(
         &mut self,
         status: InstructionResult,
+        gas: &Gas,
         gas_used: u64,
         output: Bytes,
         created_address: Option<Address>,
@@ -149,8 +150,17 @@ impl TracingInspector {
         trace.status = status;
         trace.success = success;
         trace.gas_used = gas_used;
+        trace.gas_refund = gas.refunded().max(0) as u64;
         trace.output = output;
 
+        // The gas that is handed back to the caller is part of the cost of the calling step, like
+        // in geth where the cost of a call includes all the gas forwarded to it.
+        if matches!(status, return_ok!() | return_revert!()) {
+            if let Some(StackStep { trace_idx, step_idx }) = self.step_stack.last().copied() {
+                self.traces.arena[trace_idx].trace.steps[step_idx].gas_cost += gas.remaining();
+            }
+        }
+
         if let Some(address) = created_address {
             // A new contract was created via CREATE
             trace.address = address;
@@ -167,7 +177,23 @@ impl TracingInspector {
     /// of a call.
     fn start_step<DB: Database>(&mut self, interp: &mut Interpreter, data: &mut EVMData<'_, DB>) {
         let trace_idx = self.last_trace_idx();
+
+        // The refund counter of the interpreter only covers the current call and its successful
+        // subcalls, the refunds of the transaction before the call are those of the calling step.
+        let refund_base = self.traces.arena[trace_idx]
+            .parent
+            .and_then(|parent| self.traces.arena[parent].trace.steps.last())
+            .map_or(0, |step| step.gas_refund_counter);
+        let gas_refund_counter = (refund_base as i64 + interp.gas.refunded()).max(0) as u64;
+
         let trace = &mut self.traces.arena[trace_idx];
+        let gas = self.gas_inspector.as_ref().gas_remaining();
+
+        // The cost of the previous step of the call is the gas it consumed up to this step, on top
+        // of the gas its subcalls handed back.
+        if let Some(previous) = trace.trace.steps.last_mut() {
+            previous.gas_cost += previous.gas.saturating_sub(gas);
+        }
 
         self.step_stack.push(StackStep { trace_idx, step_idx: trace.trace.steps.len() });
 
@@ -186,10 +212,10 @@ impl TracingInspector {
             contract: interp.contract.address,
             stack,
             memory,
-            gas: self.gas_inspector.as_ref().gas_remaining(),
-            gas_refund_counter: interp.gas.refunded() as u64,
+            gas,
+            gas_refund_counter,
 
-            // fields will be populated end of call
+            // fields will be populated by the next step or at the end of the call
             gas_cost: 0,
             state_diff: None,
             status: InstructionResult::Continue,
@@ -235,8 +261,11 @@ impl TracingInspector {
                     _ => None,
                 };
             }
+        }
 
-            step.gas_cost = step.gas - self.gas_inspector.as_ref().gas_remaining();
+        // The last step of a call has no next step to take the remaining gas from.
+        if status != InstructionResult::Continue {
+            step.gas_cost += step.gas.saturating_sub(interp.gas.remaining());
         }
 
         // set the status
@@ -342,6 +371,7 @@ where
 
         self.fill_trace_on_call_end(
             ret,
+            &gas,
             gas_used(data.env.cfg.spec_id, gas.spend(), gas.refunded() as u64),
             out.clone(),
             None,
@@ -400,6 +430,7 @@ where
 
         self.fill_trace_on_call_end(
             status,
+            &gas,
             gas_used(data.env.cfg.spec_id, gas.spend(), gas.refunded() as u64),
             code.into(),
             address,
@@ -420,3 +451,119 @@ struct StackStep {
     trace_idx: usize,
     step_idx: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{hex_literal::hex, keccak256};
+    use reth_rpc_types::trace::{
+        geth::{DefaultFrame, GethDebugTracingOptions},
+        parity::{Action, CallAction, TraceResult},
+    };
+    use revm::{
+        db::{CacheDB, EmptyDB},
+        primitives::{AccountInfo, Bytecode, SpecId, TransactTo},
+        EVM,
+    };
+
+    /// Compares the struct logs with the ones of geth in `testdata/geth/sstore_selfdestruct.json`.
+    ///
+    /// The contract sets a cold slot from zero, resets it to zero, which refunds the set, and
+    /// selfdestructs to a new account. The fixture has the format of the `debug_traceCall`
+    /// result of geth for the same call, with the costs of the EIP-2929 and EIP-3529 schedule.
+    /// It is not a recording yet, `testdata/geth/regenerate.sh` replaces it with the one of geth.
+    #[test]
+    fn struct_logs_gas_match_geth() {
+        let contract = Address::from_low_u64_be(0x1000);
+        let beneficiary = Address::from_low_u64_be(0x2000);
+        // PUSH1 1 PUSH1 0 SSTORE PUSH1 0 PUSH1 0 SSTORE PUSH20 beneficiary SELFDESTRUCT
+        let mut code = hex!("6001600055600060005573").to_vec();
+        code.extend_from_slice(beneficiary.as_bytes());
+        code.push(opcode::SELFDESTRUCT);
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                balance: U256::from(1),
+                nonce: 1,
+                code_hash: keccak256(&code),
+                code: Some(Bytecode::new_raw(code.into())),
+            },
+        );
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.cfg.spec_id = SpecId::LONDON;
+        evm.env.tx.caller = Address::from_low_u64_be(1);
+        evm.env.tx.transact_to = TransactTo::Call(contract);
+        evm.env.tx.gas_limit = 100_000;
+
+        let mut inspector = TracingInspector::new(TraceInspectorConfig::default_geth());
+        let result = evm.inspect(&mut inspector).unwrap().result;
+        assert!(result.is_success());
+
+        let opts = GethDebugTracingOptions {
+            disable_stack: Some(true),
+            disable_storage: Some(true),
+            ..Default::default()
+        };
+        let frame = inspector.finalize().geth_traces(U256::from(result.gas_used()), opts);
+
+        let mut expected: DefaultFrame =
+            serde_json::from_str(include_str!("../../testdata/geth/sstore_selfdestruct.json"))
+                .unwrap();
+        // geth doesn't return the refund counter, the set of the slot is refunded
+        expected.gas_refund = Some(19900);
+        assert_eq!(frame, expected);
+    }
+
+    #[test]
+    fn parity_traces_of_reverted_subcall() {
+        let caller = Address::from_low_u64_be(0x1000);
+        let callee = Address::from_low_u64_be(0x2000);
+        // PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH20 callee GAS CALL STOP
+        let mut caller_code = hex!("6000600060006000600073").to_vec();
+        caller_code.extend_from_slice(callee.as_bytes());
+        caller_code.extend_from_slice(&hex!("5af100"));
+        // PUSH1 0 PUSH1 0 REVERT
+        let callee_code = hex!("60006000fd").to_vec();
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, code) in [(caller, caller_code), (callee, callee_code)] {
+            db.insert_account_info(
+                address,
+                AccountInfo {
+                    balance: U256::ZERO,
+                    nonce: 1,
+                    code_hash: keccak256(&code),
+                    code: Some(Bytecode::new_raw(code.into())),
+                },
+            );
+        }
+
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.cfg.spec_id = SpecId::LONDON;
+        evm.env.tx.caller = Address::from_low_u64_be(1);
+        evm.env.tx.transact_to = TransactTo::Call(caller);
+        evm.env.tx.gas_limit = 100_000;
+
+        let mut inspector = TracingInspector::new(TraceInspectorConfig::default_parity());
+        let result = evm.inspect(&mut inspector).unwrap().result;
+        assert!(result.is_success());
+
+        let traces = inspector.finalize().parity_traces();
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[0].trace_address, Vec::<usize>::new());
+        assert_eq!(traces[0].subtraces, 1);
+        assert!(matches!(traces[0].result, Some(TraceResult::Success { .. })));
+        assert_eq!(traces[1].trace_address, vec![0]);
+        assert_eq!(traces[1].subtraces, 0);
+        assert!(matches!(
+            traces[1].action,
+            Action::Call(CallAction { from, to, .. }) if from == caller && to == callee
+        ));
+        assert_eq!(traces[1].result, Some(TraceResult::Error { error: "Reverted".to_string() }));
+    }
+}
@@ -101,6 +101,8 @@ pub(crate) struct CallTrace {
     pub(crate) output: Bytes,
     /// The gas cost of the call
     pub(crate) gas_used: u64,
+    /// The refund counter at the end of the call, including the refunds of successful subcalls
+    pub(crate) gas_refund: u64,
     /// The status of the trace's call
     pub(crate) status: InstructionResult,
     /// call context of the runtime
@@ -122,6 +124,7 @@ impl Default for CallTrace {
             data: Default::default(),
             output: Default::default(),
             gas_used: Default::default(),
+            gas_refund: Default::default(),
             status: InstructionResult::Continue,
             call_context: Default::default(),
             steps: Default::default(),
@@ -174,6 +177,15 @@ impl CallTraceNode {
         }
     }
 
+    /// Returns the error of a failed call for a parity trace
+    pub(crate) fn parity_error(&self) -> String {
+        match self.status() {
+            InstructionResult::Revert => "Reverted".to_string(),
+            InstructionResult::OutOfGas => "Out of gas".to_string(),
+            status => format!("{status:?}"),
+        }
+    }
+
     /// Returns the `Action` for a parity trace
     pub(crate) fn parity_action(&self) -> Action {
         if self.status() == InstructionResult::SelfDestruct {
@@ -241,10 +253,10 @@ pub struct CallTraceStep {
     pub memory: Memory,
     /// Remaining gas before step execution
     pub gas: u64,
-    /// Gas refund counter before step execution
+    /// Gas refund counter of the transaction before step execution
     pub gas_refund_counter: u64,
     // Fields filled in `step_end`
-    /// Gas cost of step execution
+    /// Gas cost of step execution, including the gas forwarded to a subcall like geth
     pub gas_cost: u64,
     /// Change of the contract state after step execution (effect of the SLOAD/SSTORE instructions)
     pub state_diff: Option<(U256, U256)>,
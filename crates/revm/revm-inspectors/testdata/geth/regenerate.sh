@@ -0,0 +1,39 @@
+#!/usr/bin/env bash
+# Records the geth struct log fixtures of the tracing inspector.
+#
+# Usage: ./regenerate.sh <rpc url>
+#
+# The fixtures must be recorded with geth v1.11.6 in dev mode, started with
+#
+#   geth --dev --http --http.api eth,debug
+#
+# Every fixture is the `result` of a `debug_traceCall` of the node, formatted by jq. The code of
+# the called contract is set with a state override, the transaction is the one of the test that
+# compares against the fixture.
+set -euo pipefail
+
+RPC_URL=${1:?usage: $0 <rpc url>}
+DIR=$(cd "$(dirname "$0")" && pwd)
+
+CONTRACT=0x0000000000000000000000000000000000001000
+BENEFICIARY=0000000000000000000000000000000000002000
+
+trace_call() {
+    curl -sf -X POST -H 'Content-Type: application/json' \
+        --data "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"debug_traceCall\",\"params\":$1}" \
+        "$RPC_URL" |
+        jq '.result'
+}
+
+# PUSH1 1 PUSH1 0 SSTORE PUSH1 0 PUSH1 0 SSTORE PUSH20 beneficiary SELFDESTRUCT
+CODE=0x6001600055600060005573${BENEFICIARY}ff
+trace_call "[
+    {\"from\":\"0x0000000000000000000000000000000000000001\",\"to\":\"$CONTRACT\",\"gas\":\"0x186a0\"},
+    \"latest\",
+    {
+        \"disableStack\":true,
+        \"disableStorage\":true,
+        \"stateOverrides\":{\"$CONTRACT\":{\"code\":\"$CODE\",\"balance\":\"0x1\",\"nonce\":\"0x1\"}}
+    }
+]" >"$DIR/sstore_selfdestruct.json"
+echo sstore_selfdestruct.json
@@ -0,0 +1,118 @@
+//! Hooks to configure the EVM of chains derived from Ethereum.
+
+use crate::env;
+use reth_primitives::{Address, ChainSpec, Header, TransactionSigned, U256};
+use revm::{
+    precompile::PrecompileResult,
+    primitives::{BlockEnv, CfgEnv, SpecId, TxEnv},
+};
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// A precompile: takes the input and the gas limit of the call and returns the gas used and the
+/// output.
+///
+/// Precompiles are shared closures, so they can hold state like a cache of their results.
+pub type PrecompileFn = Arc<dyn Fn(&[u8], u64) -> PrecompileResult + Send + Sync>;
+
+/// Precompiles that are added to, or replace, the precompiles of the active hardfork.
+pub type CustomPrecompiles = HashMap<Address, PrecompileFn>;
+
+/// Gas added to, or removed from if negative, the gas cost of the opcodes of the active hardfork,
+/// by opcode.
+pub type OpcodeGasAdjustments = HashMap<u8, i64>;
+
+/// Configures the EVM that executes transactions.
+///
+/// The default implementations of all hooks configure the EVM of Ethereum mainnet, see
+/// [EthEvmConfig]. Chains derived from Ethereum override the hooks they need to change.
+pub trait EvmConfig: Send + Sync + fmt::Debug + 'static {
+    /// Fills the [CfgEnv] fields according to the chain spec and given header.
+    fn fill_cfg_env(
+        &self,
+        cfg_env: &mut CfgEnv,
+        chain_spec: &ChainSpec,
+        header: &Header,
+        total_difficulty: U256,
+    ) {
+        env::fill_cfg_env(cfg_env, chain_spec, header, total_difficulty)
+    }
+
+    /// Fills the [BlockEnv] from the header.
+    fn fill_block_env(&self, block_env: &mut BlockEnv, header: &Header, after_merge: bool) {
+        env::fill_block_env(block_env, header, after_merge)
+    }
+
+    /// Fills the [TxEnv] from the transaction and its sender.
+    fn fill_tx_env(&self, tx_env: &mut TxEnv, transaction: &TransactionSigned, sender: Address) {
+        env::fill_tx_env(tx_env, transaction, sender)
+    }
+
+    /// Returns the precompiles to add to the precompiles of the given spec.
+    ///
+    /// A precompile at the address of a precompile of the spec replaces it. Calls to the returned
+    /// precompiles are answered by an [Inspector](revm::Inspector), so transactions are always
+    /// inspected if this is not empty.
+    fn precompiles(&self, spec_id: SpecId) -> CustomPrecompiles {
+        let _ = spec_id;
+        CustomPrecompiles::default()
+    }
+
+    /// Returns the adjustments of the gas cost of the opcodes of the given spec.
+    ///
+    /// Like the custom precompiles, the adjustments are applied by an
+    /// [Inspector](revm::Inspector), so transactions are always inspected if this is not empty.
+    fn opcode_gas(&self, spec_id: SpecId) -> OpcodeGasAdjustments {
+        let _ = spec_id;
+        OpcodeGasAdjustments::default()
+    }
+
+    /// Convenience function to call both [EvmConfig::fill_cfg_env] and
+    /// [EvmConfig::fill_block_env].
+    fn fill_cfg_and_block_env(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        chain_spec: &ChainSpec,
+        header: &Header,
+        total_difficulty: U256,
+    ) {
+        self.fill_cfg_env(cfg, chain_spec, header, total_difficulty);
+        let after_merge = cfg.spec_id >= SpecId::MERGE;
+        self.fill_block_env(block_env, header, after_merge);
+    }
+}
+
+/// The [EvmConfig] of Ethereum mainnet.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct EthEvmConfig;
+
+impl EvmConfig for EthEvmConfig {}
+
+impl<T: EvmConfig + ?Sized> EvmConfig for Arc<T> {
+    fn fill_cfg_env(
+        &self,
+        cfg_env: &mut CfgEnv,
+        chain_spec: &ChainSpec,
+        header: &Header,
+        total_difficulty: U256,
+    ) {
+        (**self).fill_cfg_env(cfg_env, chain_spec, header, total_difficulty)
+    }
+
+    fn fill_block_env(&self, block_env: &mut BlockEnv, header: &Header, after_merge: bool) {
+        (**self).fill_block_env(block_env, header, after_merge)
+    }
+
+    fn fill_tx_env(&self, tx_env: &mut TxEnv, transaction: &TransactionSigned, sender: Address) {
+        (**self).fill_tx_env(tx_env, transaction, sender)
+    }
+
+    fn precompiles(&self, spec_id: SpecId) -> CustomPrecompiles {
+        (**self).precompiles(spec_id)
+    }
+
+    fn opcode_gas(&self, spec_id: SpecId) -> OpcodeGasAdjustments {
+        (**self).opcode_gas(spec_id)
+    }
+}
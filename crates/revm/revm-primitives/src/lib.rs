@@ -11,6 +11,12 @@ pub mod config;
 /// Helpers for configuring revm [Env](revm::primitives::Env)
 pub mod env;
 
+/// Hooks to customize the EVM
+pub mod evm_config;
+pub use evm_config::{
+    CustomPrecompiles, EthEvmConfig, EvmConfig, OpcodeGasAdjustments, PrecompileFn,
+};
+
 /// Helpers for type compatibility between reth and revm types
 mod compat;
 pub use compat::*;
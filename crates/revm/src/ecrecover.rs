@@ -0,0 +1,285 @@
+use crate::{CustomPrecompiles, EvmConfig, OpcodeGasAdjustments, PrecompileFn};
+use metrics::Counter;
+use parking_lot::Mutex;
+use reth_metrics_derive::Metrics;
+use reth_primitives::{Address, ChainSpec, Header, TransactionSigned, H160, U256};
+use revm::{
+    precompile::{
+        Error as PrecompileError, Precompile, PrecompileResult, Precompiles,
+        SpecId as PrecompilesSpecId,
+    },
+    primitives::{BlockEnv, CfgEnv, SpecId, TxEnv},
+};
+use schnellru::{ByLength, LruMap};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// Default number of `ecrecover` results kept by the [EcrecoverCache].
+pub const DEFAULT_ECRECOVER_CACHE_SIZE: u32 = 10_000;
+
+/// The address of the `ecrecover` precompile.
+pub const ECRECOVER_ADDRESS: Address =
+    H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+/// Length of the `ecrecover` input: the message hash, `v`, `r` and `s`, each padded to 32 bytes.
+const ECRECOVER_INPUT_LEN: usize = 128;
+
+/// Bounded LRU cache of the results of the `ecrecover` precompile, keyed by the message hash and
+/// the signature.
+///
+/// The same signatures are recovered again when blocks are re-executed after a reorg or replayed
+/// by tracing calls. Clones share the same cache, so one cache serves the executor and the rpc
+/// handlers.
+#[derive(Clone)]
+pub struct EcrecoverCache {
+    inner: Arc<EcrecoverCacheInner>,
+}
+
+struct EcrecoverCacheInner {
+    /// The uncached precompile.
+    ecrecover: fn(&[u8], u64) -> PrecompileResult,
+    /// Gas used and output of the successful calls.
+    results: Mutex<LruMap<[u8; ECRECOVER_INPUT_LEN], (u64, Vec<u8>), ByLength>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    metrics: EcrecoverCacheMetrics,
+}
+
+impl EcrecoverCache {
+    /// Creates a cache that keeps up to `max_entries` results.
+    pub fn new(max_entries: u32) -> Self {
+        let ecrecover = match Precompiles::new(PrecompilesSpecId::HOMESTEAD).get(&ECRECOVER_ADDRESS)
+        {
+            Some(Precompile::Standard(ecrecover) | Precompile::Custom(ecrecover)) => ecrecover,
+            None => unreachable!("ecrecover is a precompile of all specs"),
+        };
+        let inner = EcrecoverCacheInner {
+            ecrecover,
+            results: Mutex::new(LruMap::new(ByLength::new(max_entries))),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            metrics: Default::default(),
+        };
+        Self { inner: Arc::new(inner) }
+    }
+
+    /// Runs the `ecrecover` precompile, answering from the cache if the same message hash and
+    /// signature were recovered before.
+    pub fn call(&self, input: &[u8], gas_limit: u64) -> PrecompileResult {
+        // the precompile reads the input as if it was right padded with zeros
+        let mut key = [0u8; ECRECOVER_INPUT_LEN];
+        let len = input.len().min(ECRECOVER_INPUT_LEN);
+        key[..len].copy_from_slice(&input[..len]);
+
+        if let Some((gas_used, output)) = self.inner.results.lock().get(&key) {
+            self.inner.hits.fetch_add(1, Ordering::Relaxed);
+            self.inner.metrics.hits.increment(1);
+            if *gas_used > gas_limit {
+                return Err(PrecompileError::OutOfGas)
+            }
+            return Ok((*gas_used, output.clone()))
+        }
+
+        self.inner.misses.fetch_add(1, Ordering::Relaxed);
+        self.inner.metrics.misses.increment(1);
+        let result = (self.inner.ecrecover)(&key, gas_limit);
+        if let Ok(ref result) = result {
+            self.inner.results.lock().insert(key, result.clone());
+        }
+        result
+    }
+
+    /// Returns the cached `ecrecover` as a precompile.
+    pub fn precompile(&self) -> PrecompileFn {
+        let cache = self.clone();
+        Arc::new(move |input: &[u8], gas_limit: u64| cache.call(input, gas_limit))
+    }
+
+    /// Returns the number of cached results.
+    pub fn len(&self) -> usize {
+        self.inner.results.lock().len()
+    }
+
+    /// Returns `true` if no results are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the share of the calls answered from the cache, `0.0` if there were no calls.
+    pub fn hit_rate(&self) -> f64 {
+        let hits = self.inner.hits.load(Ordering::Relaxed);
+        let misses = self.inner.misses.load(Ordering::Relaxed);
+        if hits + misses == 0 {
+            return 0.0
+        }
+        hits as f64 / (hits + misses) as f64
+    }
+}
+
+impl Default for EcrecoverCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_ECRECOVER_CACHE_SIZE)
+    }
+}
+
+impl fmt::Debug for EcrecoverCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EcrecoverCache")
+            .field("len", &self.len())
+            .field("hit_rate", &self.hit_rate())
+            .finish()
+    }
+}
+
+/// Metrics of the [EcrecoverCache].
+#[derive(Metrics)]
+#[metrics(scope = "executor.ecrecover_cache")]
+struct EcrecoverCacheMetrics {
+    /// Number of `ecrecover` calls answered from the cache
+    hits: Counter,
+    /// Number of `ecrecover` calls that ran the precompile
+    misses: Counter,
+}
+
+/// An [EvmConfig] that answers the calls to the `ecrecover` precompile from an [EcrecoverCache].
+///
+/// All other hooks are delegated to the wrapped config. A custom `ecrecover` precompile of the
+/// wrapped config takes precedence over the cached one.
+#[derive(Debug, Clone)]
+pub struct EcrecoverCacheEvmConfig<C> {
+    inner: C,
+    cache: EcrecoverCache,
+}
+
+impl<C> EcrecoverCacheEvmConfig<C> {
+    /// Wraps the config, caching the `ecrecover` results in the given cache.
+    pub fn new(inner: C, cache: EcrecoverCache) -> Self {
+        Self { inner, cache }
+    }
+
+    /// Returns the cache of the `ecrecover` results.
+    pub fn cache(&self) -> &EcrecoverCache {
+        &self.cache
+    }
+}
+
+impl<C: EvmConfig> EvmConfig for EcrecoverCacheEvmConfig<C> {
+    fn fill_cfg_env(
+        &self,
+        cfg_env: &mut CfgEnv,
+        chain_spec: &ChainSpec,
+        header: &Header,
+        total_difficulty: U256,
+    ) {
+        self.inner.fill_cfg_env(cfg_env, chain_spec, header, total_difficulty)
+    }
+
+    fn fill_block_env(&self, block_env: &mut BlockEnv, header: &Header, after_merge: bool) {
+        self.inner.fill_block_env(block_env, header, after_merge)
+    }
+
+    fn fill_tx_env(&self, tx_env: &mut TxEnv, transaction: &TransactionSigned, sender: Address) {
+        self.inner.fill_tx_env(tx_env, transaction, sender)
+    }
+
+    fn precompiles(&self, spec_id: SpecId) -> CustomPrecompiles {
+        let mut precompiles = self.inner.precompiles(spec_id);
+        precompiles.entry(ECRECOVER_ADDRESS).or_insert_with(|| self.cache.precompile());
+        precompiles
+    }
+
+    fn opcode_gas(&self, spec_id: SpecId) -> OpcodeGasAdjustments {
+        self.inner.opcode_gas(spec_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EthEvmConfig;
+    use reth_primitives::{sign_message, Transaction, TxLegacy, H256};
+
+    /// Returns the `ecrecover` input of a transaction signed by the key, and the signer.
+    fn signed(secret: u64, nonce: u64) -> (Vec<u8>, Address) {
+        let tx = Transaction::Legacy(TxLegacy { nonce, ..Default::default() });
+        let hash = tx.signature_hash();
+        let signature = sign_message(H256::from_low_u64_be(secret), hash).unwrap();
+        let mut input = hash.as_bytes().to_vec();
+        input.extend(H256::from_low_u64_be(27 + signature.odd_y_parity as u64).as_bytes());
+        input.extend(signature.r.to_be_bytes::<32>());
+        input.extend(signature.s.to_be_bytes::<32>());
+        let signer = TransactionSigned::from_transaction_and_signature(tx, signature)
+            .recover_signer()
+            .unwrap();
+        (input, signer)
+    }
+
+    fn input(secret: u64, nonce: u64) -> Vec<u8> {
+        signed(secret, nonce).0
+    }
+
+    #[test]
+    fn matches_uncached_precompile() {
+        let cache = EcrecoverCache::new(16);
+        let uncached = cache.inner.ecrecover;
+
+        let (valid, signer) = signed(1, 0);
+        let mut invalid = valid.clone();
+        invalid[63] = 29;
+        let inputs = [valid.clone(), invalid, valid[..100].to_vec(), [valid, vec![1; 8]].concat()];
+        for input in &inputs {
+            let expected = uncached(input, 3_000).unwrap();
+            // miss, then hit
+            assert_eq!(cache.call(input, 3_000).unwrap(), expected);
+            assert_eq!(cache.call(input, 3_000).unwrap(), expected);
+        }
+        let (_, output) = cache.call(&inputs[0], 3_000).unwrap();
+        assert_eq!(&output[12..], signer.as_bytes());
+        // the longer input shares the entry of the valid input
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.hit_rate(), 6.0 / 9.0);
+    }
+
+    #[test]
+    fn out_of_gas_is_not_cached() {
+        let cache = EcrecoverCache::new(16);
+        let input = input(2, 0);
+
+        assert_eq!(cache.call(&input, 2_999), Err(PrecompileError::OutOfGas));
+        assert!(cache.is_empty());
+        assert!(cache.call(&input, 3_000).is_ok());
+        // a cached result still respects the gas limit of the call
+        assert_eq!(cache.call(&input, 2_999), Err(PrecompileError::OutOfGas));
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = EcrecoverCache::new(2);
+        let inputs = [input(1, 0), input(1, 1), input(1, 2)];
+        for input in &inputs {
+            cache.call(input, 3_000).unwrap();
+        }
+        assert_eq!(cache.len(), 2);
+        cache.call(&inputs[0], 3_000).unwrap();
+        assert_eq!(cache.hit_rate(), 0.0);
+    }
+
+    #[test]
+    fn evm_config_adds_cached_ecrecover() {
+        let config = EcrecoverCacheEvmConfig::new(EthEvmConfig::default(), Default::default());
+
+        let precompiles = config.precompiles(SpecId::SHANGHAI);
+        let ecrecover = precompiles.get(&ECRECOVER_ADDRESS).unwrap();
+        ecrecover(&input(3, 0), 3_000).unwrap();
+        // the precompiles of all specs share the cache of the config
+        assert_eq!(config.cache().len(), 1);
+        let ecrecover = config.precompiles(SpecId::LONDON).remove(&ECRECOVER_ADDRESS).unwrap();
+        ecrecover(&input(3, 0), 3_000).unwrap();
+        assert_eq!(config.cache().hit_rate(), 0.5);
+    }
+}
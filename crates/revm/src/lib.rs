@@ -10,6 +10,13 @@
 /// Contains glue code for integrating reth database into revm's [Database](revm::Database).
 pub mod database;
 
+/// A cache of the results of the `ecrecover` precompile.
+pub mod ecrecover;
+
+/// The fee Optimism chains charge for the data transactions post to L1.
+#[cfg(feature = "optimism")]
+pub mod optimism;
+
 /// reexport for convenience
 pub use reth_revm_inspectors::*;
 /// reexport for convenience
@@ -0,0 +1,377 @@
+use reth_interfaces::Result;
+use reth_primitives::{hex_literal::hex, Address, Block, ChainSpec, Hardfork, H160, H256, U256};
+use reth_provider::StateProvider;
+
+/// The address of the `L1Block` predeploy, updated by the first deposit transaction of every
+/// block with the fee parameters of the L1 origin of the block.
+pub const L1_BLOCK_CONTRACT: Address = H160(hex!("4200000000000000000000000000000000000015"));
+
+/// The selector of `setL1BlockValues`, the call of the L1 attributes deposit transaction since
+/// Bedrock.
+pub const L1_INFO_BEDROCK_SELECTOR: [u8; 4] = hex!("015d8eb9");
+
+/// The selector of `setL1BlockValuesEcotone`, the call of the L1 attributes deposit transaction
+/// since Ecotone.
+pub const L1_INFO_ECOTONE_SELECTOR: [u8; 4] = hex!("440a5e20");
+
+/// The L1 data gas charged for the signature of a transaction that is not signed yet, like the
+/// `getL1Fee` function of the `GasPriceOracle` predeploy does.
+pub const UNSIGNED_TX_SIGNATURE_DATA_GAS: u64 = 68 * NON_ZERO_BYTE_DATA_GAS;
+
+/// The L1 data gas of a zero byte of a transaction.
+const ZERO_BYTE_DATA_GAS: u64 = 4;
+
+/// The L1 data gas of a non-zero byte of a transaction.
+const NON_ZERO_BYTE_DATA_GAS: u64 = 16;
+
+/// The scalars of the fee parameters are in millionths.
+const SCALAR_DECIMALS: u64 = 1_000_000;
+
+/// The storage slots of the fee parameters of the `L1Block` predeploy.
+const BASE_FEE_SLOT: u64 = 1;
+const SCALARS_SLOT: u64 = 3;
+const FEE_OVERHEAD_SLOT: u64 = 5;
+const FEE_SCALAR_SLOT: u64 = 6;
+const BLOB_BASE_FEE_SLOT: u64 = 7;
+
+/// The fee parameters of the L1 origin of a block of an Optimism chain, that price the data the
+/// transactions of the block post to L1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct L1BlockInfo {
+    /// The base fee of the L1 origin block.
+    pub l1_base_fee: U256,
+    /// The overhead added to the data gas of every transaction, before Ecotone.
+    pub l1_fee_overhead: U256,
+    /// The scalar of the L1 fee, before Ecotone.
+    pub l1_fee_scalar: U256,
+    /// The blob base fee of the L1 origin block, since Ecotone.
+    pub l1_blob_base_fee: U256,
+    /// The scalar of the L1 base fee, since Ecotone.
+    pub l1_base_fee_scalar: U256,
+    /// The scalar of the L1 blob base fee, since Ecotone.
+    pub l1_blob_base_fee_scalar: U256,
+}
+
+impl L1BlockInfo {
+    /// Parses the fee parameters from the call data of the L1 attributes deposit transaction,
+    /// the first transaction of every block.
+    ///
+    /// Returns `None` if the call data is neither a `setL1BlockValues` nor a
+    /// `setL1BlockValuesEcotone` call.
+    pub fn from_calldata(calldata: &[u8]) -> Option<Self> {
+        let (selector, data) = (calldata.get(..4)?, &calldata[4..]);
+        if selector == L1_INFO_BEDROCK_SELECTOR {
+            // eight abi encoded words: number, timestamp, basefee, hash, sequence number,
+            // batcher hash, fee overhead and fee scalar
+            if data.len() != 8 * 32 {
+                return None
+            }
+            let word = |index: usize| {
+                U256::try_from_be_slice(&data[index * 32..(index + 1) * 32]).expect("32 bytes")
+            };
+            Some(Self {
+                l1_base_fee: word(2),
+                l1_fee_overhead: word(6),
+                l1_fee_scalar: word(7),
+                ..Default::default()
+            })
+        } else if selector == L1_INFO_ECOTONE_SELECTOR {
+            // packed: base fee scalar (4), blob base fee scalar (4), sequence number (8),
+            // timestamp (8), number (8), base fee (32), blob base fee (32), hash (32) and batcher
+            // hash (32)
+            if data.len() != 160 {
+                return None
+            }
+            let be = |bytes: &[u8]| U256::try_from_be_slice(bytes).expect("at most 32 bytes");
+            Some(Self {
+                l1_base_fee_scalar: be(&data[..4]),
+                l1_blob_base_fee_scalar: be(&data[4..8]),
+                l1_base_fee: be(&data[32..64]),
+                l1_blob_base_fee: be(&data[64..96]),
+                ..Default::default()
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Parses the fee parameters of a block from its first transaction, the L1 attributes
+    /// deposit transaction.
+    pub fn from_block(block: &Block) -> Option<Self> {
+        Self::from_calldata(block.body.first()?.input())
+    }
+
+    /// Reads the fee parameters from the storage of the `L1Block` predeploy.
+    ///
+    /// The state after a block has the parameters of the block, since the first transaction of
+    /// the block sets them.
+    pub fn from_state<S: StateProvider>(state: &S) -> Result<Self> {
+        let slot = |slot: u64| -> Result<U256> {
+            Ok(state.storage(L1_BLOCK_CONTRACT, H256::from_low_u64_be(slot))?.unwrap_or_default())
+        };
+        // the sequence number in the lowest 8 bytes, followed by the blob base fee scalar and the
+        // base fee scalar
+        let scalars = slot(SCALARS_SLOT)?;
+        let u32_mask = U256::from(u32::MAX);
+        Ok(Self {
+            l1_base_fee: slot(BASE_FEE_SLOT)?,
+            l1_fee_overhead: slot(FEE_OVERHEAD_SLOT)?,
+            l1_fee_scalar: slot(FEE_SCALAR_SLOT)?,
+            l1_blob_base_fee: slot(BLOB_BASE_FEE_SLOT)?,
+            l1_base_fee_scalar: (scalars >> 96) & u32_mask,
+            l1_blob_base_fee_scalar: (scalars >> 64) & u32_mask,
+        })
+    }
+
+    /// Returns whether the data of the transactions of a block at the timestamp is priced with
+    /// the Ecotone formula.
+    ///
+    /// The L1 attributes of the block that activates Ecotone still have the Bedrock format, so
+    /// that block is priced with the Bedrock formula.
+    pub fn is_ecotone(&self, chain_spec: &ChainSpec, timestamp: u64) -> bool {
+        chain_spec.fork(Hardfork::Ecotone).active_at_timestamp(timestamp) &&
+            !(self.l1_base_fee_scalar.is_zero() && self.l1_blob_base_fee_scalar.is_zero())
+    }
+
+    /// Returns the gas the data of the transaction uses on L1, that is the
+    /// `l1GasUsed` of its receipt.
+    ///
+    /// `data_gas` is the gas of the encoded transaction, see [data_gas].
+    pub fn l1_gas_used(&self, data_gas: U256, chain_spec: &ChainSpec, timestamp: u64) -> U256 {
+        if self.is_ecotone(chain_spec, timestamp) {
+            data_gas
+        } else {
+            data_gas + self.l1_fee_overhead
+        }
+    }
+
+    /// Returns the fee the transaction pays for its data on L1, that is the `l1Fee` of its
+    /// receipt.
+    ///
+    /// `data_gas` is the gas of the encoded transaction, see [data_gas].
+    pub fn l1_fee(&self, data_gas: U256, chain_spec: &ChainSpec, timestamp: u64) -> U256 {
+        let decimals = U256::from(SCALAR_DECIMALS);
+        if self.is_ecotone(chain_spec, timestamp) {
+            let byte_gas = U256::from(NON_ZERO_BYTE_DATA_GAS);
+            let scaled_base_fee = byte_gas * self.l1_base_fee * self.l1_base_fee_scalar;
+            let scaled_blob_base_fee = self.l1_blob_base_fee * self.l1_blob_base_fee_scalar;
+            data_gas * (scaled_base_fee + scaled_blob_base_fee) / (byte_gas * decimals)
+        } else {
+            self.l1_gas_used(data_gas, chain_spec, timestamp) * self.l1_base_fee *
+                self.l1_fee_scalar /
+                decimals
+        }
+    }
+}
+
+/// Returns the L1 data gas of a transaction from its EIP-2718 encoding.
+///
+/// Before Regolith, 68 non-zero bytes are charged for the signature on top of the encoding.
+pub fn data_gas(tx: &[u8], chain_spec: &ChainSpec, timestamp: u64) -> U256 {
+    let mut data_gas = tx
+        .iter()
+        .map(|byte| if *byte == 0 { ZERO_BYTE_DATA_GAS } else { NON_ZERO_BYTE_DATA_GAS })
+        .sum::<u64>();
+    if !chain_spec.fork(Hardfork::Regolith).active_at_timestamp(timestamp) {
+        data_gas += UNSIGNED_TX_SIGNATURE_DATA_GAS;
+    }
+    U256::from(data_gas)
+}
+
+/// Formats a fee scalar as a decimal, like the `l1FeeScalar` of receipts, e.g. `0.684` for
+/// `684000`.
+pub fn format_fee_scalar(scalar: U256) -> String {
+    let decimals = U256::from(SCALAR_DECIMALS);
+    let (integer, fraction) = (scalar / decimals, scalar % decimals);
+    if fraction.is_zero() {
+        return integer.to_string()
+    }
+    let fraction = format!("{fraction:0>6}");
+    format!("{integer}.{}", fraction.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{
+        Chain, ChainSpecBuilder, ForkCondition, Transaction, TransactionSigned, TxLegacy,
+    };
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+
+    /// A chain with Regolith at 100 and Ecotone at 200.
+    fn chain_spec() -> ChainSpec {
+        ChainSpecBuilder::default()
+            .chain(Chain::Id(11155420))
+            .genesis(Default::default())
+            .with_fork(Hardfork::Bedrock, ForkCondition::Block(0))
+            .with_fork(Hardfork::Regolith, ForkCondition::Timestamp(100))
+            .with_fork(Hardfork::Ecotone, ForkCondition::Timestamp(200))
+            .build()
+    }
+
+    fn word(value: u64) -> [u8; 32] {
+        H256::from_low_u64_be(value).0
+    }
+
+    #[test]
+    fn parse_bedrock_l1_info() {
+        let mut calldata = L1_INFO_BEDROCK_SELECTOR.to_vec();
+        for value in [4_000_000, 1_700_000_000, 1_000_000_000, 7, 3, 9, 188, 684_000] {
+            calldata.extend_from_slice(&word(value));
+        }
+
+        let info = L1BlockInfo::from_calldata(&calldata).unwrap();
+        assert_eq!(
+            info,
+            L1BlockInfo {
+                l1_base_fee: U256::from(1_000_000_000),
+                l1_fee_overhead: U256::from(188),
+                l1_fee_scalar: U256::from(684_000),
+                ..Default::default()
+            }
+        );
+        assert_eq!(L1BlockInfo::from_calldata(&calldata[..calldata.len() - 1]), None);
+
+        let block = Block {
+            body: vec![TransactionSigned {
+                transaction: Transaction::Legacy(TxLegacy {
+                    input: calldata.into(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(L1BlockInfo::from_block(&block), Some(info));
+        assert_eq!(L1BlockInfo::from_block(&Block::default()), None);
+    }
+
+    #[test]
+    fn parse_ecotone_l1_info() {
+        let mut calldata = L1_INFO_ECOTONE_SELECTOR.to_vec();
+        calldata.extend_from_slice(&1368u32.to_be_bytes());
+        calldata.extend_from_slice(&810_949u32.to_be_bytes());
+        calldata.extend_from_slice(&3u64.to_be_bytes());
+        calldata.extend_from_slice(&1_700_000_000u64.to_be_bytes());
+        calldata.extend_from_slice(&4_000_000u64.to_be_bytes());
+        calldata.extend_from_slice(&word(1_000_000_000));
+        calldata.extend_from_slice(&word(1));
+        calldata.extend_from_slice(&word(7));
+        calldata.extend_from_slice(&word(9));
+
+        let info = L1BlockInfo::from_calldata(&calldata).unwrap();
+        assert_eq!(
+            info,
+            L1BlockInfo {
+                l1_base_fee: U256::from(1_000_000_000),
+                l1_blob_base_fee: U256::from(1),
+                l1_base_fee_scalar: U256::from(1368),
+                l1_blob_base_fee_scalar: U256::from(810_949),
+                ..Default::default()
+            }
+        );
+        assert_eq!(L1BlockInfo::from_calldata(&[0u8; 164]), None);
+    }
+
+    #[test]
+    fn read_l1_info_from_state() {
+        let scalars = (U256::from(1368) << 96) | (U256::from(810_949) << 64) | U256::from(3);
+        let state = MockEthProvider::default();
+        state.add_account(
+            L1_BLOCK_CONTRACT,
+            ExtendedAccount::new(0, U256::ZERO).extend_storage([
+                (H256::from_low_u64_be(BASE_FEE_SLOT), U256::from(1_000_000_000)),
+                (H256::from_low_u64_be(SCALARS_SLOT), scalars),
+                (H256::from_low_u64_be(FEE_OVERHEAD_SLOT), U256::from(188)),
+                (H256::from_low_u64_be(FEE_SCALAR_SLOT), U256::from(684_000)),
+                (H256::from_low_u64_be(BLOB_BASE_FEE_SLOT), U256::from(1)),
+            ]),
+        );
+
+        assert_eq!(
+            L1BlockInfo::from_state(&state).unwrap(),
+            L1BlockInfo {
+                l1_base_fee: U256::from(1_000_000_000),
+                l1_fee_overhead: U256::from(188),
+                l1_fee_scalar: U256::from(684_000),
+                l1_blob_base_fee: U256::from(1),
+                l1_base_fee_scalar: U256::from(1368),
+                l1_blob_base_fee_scalar: U256::from(810_949),
+            }
+        );
+    }
+
+    #[test]
+    fn bedrock_l1_fee() {
+        let chain_spec = chain_spec();
+        let info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000_000_000),
+            l1_fee_overhead: U256::from(188),
+            l1_fee_scalar: U256::from(684_000),
+            ..Default::default()
+        };
+        // two zero and two non-zero bytes
+        let tx = [0, 0, 1, 2];
+
+        // the signature is charged before Regolith
+        let data = data_gas(&tx, &chain_spec, 99);
+        assert_eq!(data, U256::from(40 + 68 * 16));
+        assert_eq!(info.l1_gas_used(data, &chain_spec, 99), U256::from(1316));
+        assert_eq!(info.l1_fee(data, &chain_spec, 99), U256::from(900_144_000_000u64));
+
+        let data = data_gas(&tx, &chain_spec, 100);
+        assert_eq!(data, U256::from(40));
+        assert_eq!(info.l1_gas_used(data, &chain_spec, 100), U256::from(228));
+        assert_eq!(info.l1_fee(data, &chain_spec, 100), U256::from(155_952_000_000u64));
+
+        // the block that activates Ecotone still has Bedrock L1 attributes
+        assert!(!info.is_ecotone(&chain_spec, 200));
+        assert_eq!(info.l1_fee(data, &chain_spec, 200), U256::from(155_952_000_000u64));
+    }
+
+    #[test]
+    fn ecotone_l1_fee() {
+        let chain_spec = chain_spec();
+        let info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000_000_000),
+            l1_blob_base_fee: U256::from(1),
+            l1_base_fee_scalar: U256::from(1368),
+            l1_blob_base_fee_scalar: U256::from(810_949),
+            ..Default::default()
+        };
+        let data = data_gas(&[0, 0, 1, 2], &chain_spec, 200);
+
+        assert!(info.is_ecotone(&chain_spec, 200));
+        assert_eq!(info.l1_gas_used(data, &chain_spec, 200), U256::from(40));
+        // 40 * (16 * 1e9 * 1368 + 810949) / 16e6
+        assert_eq!(info.l1_fee(data, &chain_spec, 200), U256::from(54_720_002));
+    }
+
+    /// The receipt of OP Mainnet transaction
+    /// `0x2bc7cb4648e847712e39abd42178e35214a70bb15c568d604687661b9539b4c2` of block 121258977,
+    /// after Ecotone: `l1GasUsed` 0x800, `l1Fee` 0x105d4b2024, `l1GasPrice` 0x5d749a07e,
+    /// `l1BaseFeeScalar` 0x558, `l1BlobBaseFee` 0x1 and `l1BlobBaseFeeScalar` 0xc5fc5.
+    #[test]
+    fn ecotone_l1_fee_of_receipt() {
+        let chain_spec = chain_spec();
+        let info = L1BlockInfo {
+            l1_base_fee: U256::from(0x5d749a07eu64),
+            l1_blob_base_fee: U256::from(0x1),
+            l1_base_fee_scalar: U256::from(0x558),
+            l1_blob_base_fee_scalar: U256::from(0xc5fc5),
+            ..Default::default()
+        };
+        let data = U256::from(0x800);
+
+        assert_eq!(info.l1_gas_used(data, &chain_spec, 200), U256::from(0x800));
+        assert_eq!(info.l1_fee(data, &chain_spec, 200), U256::from(0x105d4b2024u64));
+    }
+
+    #[test]
+    fn fee_scalar_decimal() {
+        assert_eq!(format_fee_scalar(U256::from(684_000)), "0.684");
+        assert_eq!(format_fee_scalar(U256::from(1_000_000)), "1");
+        assert_eq!(format_fee_scalar(U256::from(1_500_001)), "1.500001");
+        assert_eq!(format_fee_scalar(U256::ZERO), "0");
+    }
+}
@@ -1,6 +1,6 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use reth_primitives::NodeRecord;
-use reth_rpc_types::NodeInfo;
+use reth_rpc_types::{NodeInfo, PeerInfo};
 
 /// Admin namespace rpc interface that gives access to several non-standard RPC methods.
 #[cfg_attr(not(feature = "client"), rpc(server))]
@@ -28,6 +28,10 @@ pub trait AdminApi {
     #[method(name = "admin_removeTrustedPeer")]
     fn remove_trusted_peer(&self, record: NodeRecord) -> RpcResult<bool>;
 
+    /// Returns the info of all connected peers, including the client id each peer announced.
+    #[method(name = "admin_peers")]
+    async fn peers(&self) -> RpcResult<Vec<PeerInfo>>;
+
     /// Creates an RPC subscription which serves events received from the network.
     #[subscription(
         name = "admin_peerEvents",
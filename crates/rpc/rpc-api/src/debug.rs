@@ -1,8 +1,11 @@
 use jsonrpsee::{core::RpcResult as Result, proc_macros::rpc};
 use reth_primitives::{BlockId, BlockNumberOrTag, Bytes, H256};
 use reth_rpc_types::{
-    trace::geth::{BlockTraceResult, GethDebugTracingOptions, GethTraceFrame, TraceResult},
-    CallRequest, RichBlock,
+    trace::geth::{
+        BlockTraceResult, GethDebugTracingCallOptions, GethDebugTracingOptions, GethTraceFrame,
+        TraceResult,
+    },
+    CallRequest, ExecutionWitness, RichBlock,
 };
 
 /// Debug rpc interface.
@@ -90,13 +93,20 @@ pub trait DebugApi {
     /// The block can be specified either by hash or by number as
     /// the second argument.
     /// The trace can be configured similar to `debug_traceTransaction`,
-    /// see [GethDebugTracingOptions]. The method returns the same output as
+    /// see [GethDebugTracingOptions], and the state and the block the call is executed in can be
+    /// overridden, see [GethDebugTracingCallOptions]. The method returns the same output as
     /// `debug_traceTransaction`.
     #[method(name = "debug_traceCall")]
     async fn debug_trace_call(
         &self,
         request: CallRequest,
         block_number: Option<BlockId>,
-        opts: GethDebugTracingOptions,
+        opts: GethDebugTracingCallOptions,
     ) -> Result<GethTraceFrame>;
+
+    /// Re-executes the block and returns the witness of the execution: the trie nodes of the
+    /// parent state that reveal the touched accounts and slots, the executed contract codes, the
+    /// preimages of the touched keys and the block hashes read with `BLOCKHASH`.
+    #[method(name = "debug_executionWitness")]
+    async fn debug_execution_witness(&self, block: BlockNumberOrTag) -> Result<ExecutionWitness>;
 }
@@ -1,10 +1,9 @@
 use jsonrpsee::{core::RpcResult as Result, proc_macros::rpc};
-use reth_primitives::{
-    AccessListWithGasUsed, Address, BlockId, BlockNumberOrTag, Bytes, H256, H64, U256, U64,
-};
+use reth_primitives::{Address, BlockId, BlockNumberOrTag, Bytes, H256, H64, U256, U64};
 use reth_rpc_types::{
-    state::StateOverride, CallRequest, EIP1186AccountProofResponse, FeeHistory, Index, RichBlock,
-    SyncStatus, Transaction, TransactionReceipt, TransactionRequest, Work,
+    state::StateOverride, AccessListWithGasUsed, CallRequest, EIP1186AccountProofResponse,
+    FeeHistory, Index, RichBlock, SyncStatus, Transaction, TransactionReceipt, TransactionRequest,
+    Work,
 };
 
 /// Eth rpc interface: <https://ethereum.github.io/execution-apis/api-documentation/>
@@ -16,6 +16,9 @@ mod eth;
 mod eth_filter;
 mod eth_pubsub;
 mod net;
+mod otterscan;
+mod reth;
+mod reth_pubsub;
 mod trace;
 mod web3;
 
@@ -25,9 +28,18 @@ pub use servers::*;
 /// Aggregates all server traits.
 pub mod servers {
     pub use crate::{
-        admin::AdminApiServer, debug::DebugApiServer, engine::EngineApiServer, eth::EthApiServer,
-        eth_filter::EthFilterApiServer, eth_pubsub::EthPubSubApiServer, net::NetApiServer,
-        trace::TraceApiServer, web3::Web3ApiServer,
+        admin::AdminApiServer,
+        debug::DebugApiServer,
+        engine::EngineApiServer,
+        eth::EthApiServer,
+        eth_filter::EthFilterApiServer,
+        eth_pubsub::EthPubSubApiServer,
+        net::NetApiServer,
+        otterscan::OtterscanApiServer,
+        reth::{RethAdminApiServer, RethApiServer},
+        reth_pubsub::RethPubSubApiServer,
+        trace::TraceApiServer,
+        web3::Web3ApiServer,
     };
 }
 
@@ -39,7 +51,14 @@ pub use clients::*;
 #[cfg(feature = "client")]
 pub mod clients {
     pub use crate::{
-        admin::AdminApiClient, debug::DebugApiClient, engine::EngineApiClient, eth::EthApiClient,
-        net::NetApiClient, trace::TraceApiClient, web3::Web3ApiClient,
+        admin::AdminApiClient,
+        debug::DebugApiClient,
+        engine::EngineApiClient,
+        eth::EthApiClient,
+        net::NetApiClient,
+        otterscan::OtterscanApiClient,
+        reth::{RethAdminApiClient, RethApiClient},
+        trace::TraceApiClient,
+        web3::Web3ApiClient,
     };
 }
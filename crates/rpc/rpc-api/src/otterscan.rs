@@ -0,0 +1,50 @@
+use jsonrpsee::{core::RpcResult as Result, proc_macros::rpc};
+use reth_primitives::{Address, BlockId, BlockNumber, H256};
+use reth_rpc_types::{BlockDetails, TransactionsWithReceipts};
+
+/// Otterscan rpc interface.
+///
+/// See <https://github.com/otterscan/otterscan/blob/develop/docs/custom-jsonrpc.md>
+#[cfg_attr(not(feature = "client"), rpc(server))]
+#[cfg_attr(feature = "client", rpc(server, client))]
+pub trait OtterscanApi {
+    /// Returns the hash of the transaction of the sender with the given nonce.
+    #[method(name = "ots_getTransactionBySenderAndNonce")]
+    async fn get_transaction_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: u64,
+    ) -> Result<Option<H256>>;
+
+    /// Returns whether the address has code at the given block.
+    #[method(name = "ots_hasCode")]
+    async fn has_code(&self, address: Address, block_id: Option<BlockId>) -> Result<bool>;
+
+    /// Returns the block with the ether it issued and the fees paid by its transactions.
+    #[method(name = "ots_getBlockDetails")]
+    async fn get_block_details(&self, block_number: BlockNumber) -> Result<Option<BlockDetails>>;
+
+    /// Returns a page of the transactions that changed the account, starting before the given
+    /// block and going back in time. Block `0` starts at the latest block.
+    ///
+    /// Pages contain whole blocks, so a page can hold more than `page_size` transactions.
+    #[method(name = "ots_searchTransactionsBefore")]
+    async fn search_transactions_before(
+        &self,
+        address: Address,
+        block_number: BlockNumber,
+        page_size: usize,
+    ) -> Result<TransactionsWithReceipts>;
+
+    /// Returns a page of the transactions that changed the account, starting after the given
+    /// block and going forward in time. Block `0` starts at the genesis block.
+    ///
+    /// Pages contain whole blocks, so a page can hold more than `page_size` transactions.
+    #[method(name = "ots_searchTransactionsAfter")]
+    async fn search_transactions_after(
+        &self,
+        address: Address,
+        block_number: BlockNumber,
+        page_size: usize,
+    ) -> Result<TransactionsWithReceipts>;
+}
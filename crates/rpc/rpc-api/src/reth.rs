@@ -0,0 +1,132 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use reth_primitives::{Address, BlockId, BlockNumber, BlockNumberOrTag, Bytes, Receipt, H256};
+use reth_rpc_types::{
+    AccountInfo, AccountPoolStatus, BlockchainTreeState, ImportOrigin, ImportedTransaction,
+    MultiProofResponse, ReorgStats, StorageHistoryDivergence, TransactionTiming, UnwindResult,
+};
+use std::collections::HashMap;
+
+/// Reth namespace rpc interface that gives access to reth specific RPC methods.
+#[cfg_attr(not(feature = "client"), rpc(server))]
+#[cfg_attr(feature = "client", rpc(server, client))]
+#[async_trait::async_trait]
+pub trait RethApi {
+    /// Imports the given raw transactions, as accepted by `eth_sendRawTransaction`, into the
+    /// transaction pool.
+    ///
+    /// The transactions are added as external transactions unless another origin is given, at
+    /// most 10,000 transactions of 32 MiB in total per request. Returns the outcome of every
+    /// transaction, in the same order as the given transactions.
+    #[method(name = "reth_importRawTransactions")]
+    async fn import_raw_transactions(
+        &self,
+        transactions: Vec<Bytes>,
+        origin: Option<ImportOrigin>,
+    ) -> RpcResult<Vec<ImportedTransaction>>;
+
+    /// Returns the transactions of the account in the transaction pool, split into pending and
+    /// queued ones, together with the lowest missing nonce that keeps the queued transactions
+    /// from being promoted.
+    #[method(name = "reth_getAccountPoolStatus")]
+    async fn account_pool_status(&self, address: Address) -> RpcResult<AccountPoolStatus>;
+
+    /// Returns a snapshot of the blockchain tree of blocks that are not yet canonical, for
+    /// debugging.
+    #[method(name = "reth_getBlockchainTreeState")]
+    async fn blockchain_tree_state(&self) -> RpcResult<BlockchainTreeState>;
+
+    /// Returns the most recent reorgs of the canonical chain, at most the last 256, with their
+    /// depth, time, old and new tip and the number of transactions that went back to the pool.
+    #[method(name = "reth_getReorgStats")]
+    async fn reorg_stats(&self) -> RpcResult<ReorgStats>;
+
+    /// Returns the receipts of all blocks in the given inclusive range, one list per block.
+    ///
+    /// Blocks without transactions are returned as empty lists, so the receipts of a block are at
+    /// the position of its number relative to the start of the range. The number of blocks is
+    /// capped by the node.
+    #[method(name = "reth_getBlockReceiptsRange")]
+    async fn block_receipts_range(
+        &self,
+        from: BlockNumberOrTag,
+        to: BlockNumberOrTag,
+    ) -> RpcResult<Vec<Vec<Receipt>>>;
+
+    /// Returns the balance, nonce, code hash and code of the account at the given block, or the
+    /// latest block, read from one state.
+    ///
+    /// Accounts that don't exist are returned zeroed instead of `null`.
+    #[method(name = "reth_getAccountInfo")]
+    async fn account_info(
+        &self,
+        address: Address,
+        block_id: Option<BlockId>,
+    ) -> RpcResult<AccountInfo>;
+
+    /// Returns the [AccountInfo] of multiple accounts, in the same order as the given addresses,
+    /// read from one state.
+    ///
+    /// The number of addresses is capped by the node.
+    #[method(name = "reth_getAccountsInfo")]
+    async fn accounts_info(
+        &self,
+        addresses: Vec<Address>,
+        block_id: Option<BlockId>,
+    ) -> RpcResult<Vec<AccountInfo>>;
+
+    /// Returns a proof of the given accounts and the given storage slots of each account at the
+    /// given block, or the latest block.
+    ///
+    /// The proofs share their trie nodes, so proving many accounts or slots at once is much
+    /// smaller than calling `eth_getProof` for each account. The number of accounts and the total
+    /// number of slots are capped by the node.
+    #[method(name = "reth_getMultiProof")]
+    async fn multiproof(
+        &self,
+        targets: HashMap<Address, Vec<H256>>,
+        block_id: Option<BlockId>,
+    ) -> RpcResult<MultiProofResponse>;
+
+    /// Returns the contract code with the given code hash, or `null` if the code is unknown.
+    ///
+    /// Codes are stored once per code hash, so this is the code of all accounts with the hash.
+    #[method(name = "reth_getCodeByHash")]
+    async fn code_by_hash(&self, code_hash: H256) -> RpcResult<Option<Bytes>>;
+
+    /// Re-executes the transactions of the block and returns the wall time, the gas and the state
+    /// accesses of each transaction, in the order of the block.
+    ///
+    /// The timings are measured on this node and only comparable within a block. The number of
+    /// concurrent re-executions is capped by the node.
+    #[method(name = "reth_traceBlockTiming")]
+    async fn trace_block_timing(&self, block_id: BlockId) -> RpcResult<Vec<TransactionTiming>>;
+}
+
+/// Reth namespace rpc interface of the operator methods, only served by the authenticated server.
+#[cfg_attr(not(feature = "client"), rpc(server))]
+#[cfg_attr(feature = "client", rpc(server, client))]
+#[async_trait::async_trait]
+pub trait RethAdminApi {
+    /// Unwinds the local chain to the given canonical block and resets the forkchoice state to
+    /// it, like `debug_setHead` of geth.
+    ///
+    /// Refused while the node is syncing, the request can be retried once the sync reached the
+    /// tip. Blocks whose history was pruned can't be unwound to.
+    #[method(name = "reth_unwindTo")]
+    async fn unwind_to(&self, block_number: BlockNumber) -> RpcResult<UnwindResult>;
+
+    /// Replays the value of the storage slot of the account over the given inclusive block range
+    /// from the storage changesets, and returns the first block after which it differs from the
+    /// value served by `eth_getStorageAt`, or `null` if the history is consistent.
+    ///
+    /// Meant to locate a suspected corruption of the history, the changesets of all accounts from
+    /// the start of the range are read.
+    #[method(name = "reth_verifyStorageHistory")]
+    async fn verify_storage_history(
+        &self,
+        address: Address,
+        slot: H256,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> RpcResult<Option<StorageHistoryDivergence>>;
+}
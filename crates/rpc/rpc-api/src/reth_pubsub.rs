@@ -0,0 +1,14 @@
+use jsonrpsee::proc_macros::rpc;
+use reth_rpc_types::{RethSubscriptionKind, StorageSubscriptionParams};
+
+/// Reth specific pub-sub rpc interface.
+#[rpc(server)]
+pub trait RethPubSubApi {
+    /// Create a reth specific subscription for the given params
+    #[subscription(
+        name = "reth_subscribe",
+        unsubscribe = "reth_unsubscribe",
+        item = reth_rpc_types::StorageChangeNotification
+    )]
+    fn subscribe(&self, kind: RethSubscriptionKind, params: StorageSubscriptionParams);
+}
@@ -9,13 +9,17 @@ use jsonrpsee::{
     server::{middleware, Server, ServerHandle},
     RpcModule,
 };
+use reth_interfaces::sync::{ChainUnwinder, SyncStateProvider};
 use reth_ipc::server::IpcServer;
 pub use reth_ipc::server::{Builder as IpcServerBuilder, Endpoint};
 use reth_network_api::{NetworkInfo, Peers};
-use reth_provider::{BlockProvider, EvmEnvProvider, HeaderProvider, StateProviderFactory};
+use reth_provider::{
+    BlockProvider, ChainSpecProvider, EvmEnvProvider, HeaderProvider, StateProviderFactory,
+    StorageHistoryVerifier,
+};
 use reth_rpc::{
     eth::cache::EthStateCache, AdminApi, AuthLayer, DebugApi, EngineApi, EthApi, JwtAuthValidator,
-    JwtSecret, NetApi, TraceApi, Web3Api,
+    JwtSecret, NetApi, RethAdminApi, TraceApi, Web3Api,
 };
 use reth_rpc_api::servers::*;
 use reth_rpc_engine_api::EngineApiHandle;
@@ -27,18 +31,23 @@ use std::{
     fmt,
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
     str::FromStr,
+    sync::Arc,
 };
 use strum::{AsRefStr, EnumString, EnumVariantNames, ParseError, VariantNames};
 use tower::layer::util::{Identity, Stack};
 use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 
 /// Configure and launch an auth server with `engine` and a _new_ `eth` namespace.
+///
+/// If an unwinder is given, the operator methods of the `reth` namespace are served as well, they
+/// check the storage history through the client.
 pub async fn launch<Client, Pool, Network, Tasks>(
     client: Client,
     pool: Pool,
     network: Network,
     executor: Tasks,
     handle: EngineApiHandle,
+    unwinder: Option<Arc<dyn ChainUnwinder>>,
     socket_addr: SocketAddr,
     secret: JwtSecret,
 ) -> Result<ServerHandle, RpcError>
@@ -47,23 +56,35 @@ where
         + HeaderProvider
         + StateProviderFactory
         + EvmEnvProvider
+        + ChainSpecProvider
+        + StorageHistoryVerifier
         + Clone
         + Unpin
         + 'static,
     Pool: TransactionPool + Clone + 'static,
-    Network: NetworkInfo + Peers + Clone + 'static,
+    Network: NetworkInfo + Peers + SyncStateProvider + Clone + 'static,
     Tasks: TaskSpawner + Clone + 'static,
 {
     // spawn a new cache task
     let eth_cache = EthStateCache::spawn_with(client.clone(), Default::default(), executor);
-    launch_with_eth_api(EthApi::new(client, pool, network, eth_cache), handle, socket_addr, secret)
-        .await
+    let storage_history = Arc::new(client.clone());
+    launch_with_eth_api(
+        EthApi::new(client, pool, network, eth_cache),
+        handle,
+        unwinder,
+        storage_history,
+        socket_addr,
+        secret,
+    )
+    .await
 }
 
 /// Configure and launch an auth server with existing EthApi implementation.
 pub async fn launch_with_eth_api<Client, Pool, Network>(
     eth_api: EthApi<Client, Pool, Network>,
     handle: EngineApiHandle,
+    unwinder: Option<Arc<dyn ChainUnwinder>>,
+    storage_history: Arc<dyn StorageHistoryVerifier>,
     socket_addr: SocketAddr,
     secret: JwtSecret,
 ) -> Result<ServerHandle, RpcError>
@@ -72,14 +93,18 @@ where
         + HeaderProvider
         + StateProviderFactory
         + EvmEnvProvider
+        + ChainSpecProvider
         + Clone
         + Unpin
         + 'static,
     Pool: TransactionPool + Clone + 'static,
-    Network: NetworkInfo + Peers + Clone + 'static,
+    Network: NetworkInfo + Peers + SyncStateProvider + Clone + 'static,
 {
     // Configure the module and start the server.
     let mut module = RpcModule::new(());
+    if let Some(unwinder) = unwinder {
+        module.merge(RethAdminApi::new(unwinder, handle.clone(), storage_history).into_rpc())?;
+    }
     module.merge(EngineApi::new(handle).into_rpc());
     module.merge(eth_api.into_rpc());
 
@@ -1,5 +1,10 @@
 use reth_rpc::{
-    eth::cache::{EthStateCache, EthStateCacheConfig},
+    eth::{
+        bytes_limit::BytesLimits,
+        cache::{EthStateCache, EthStateCacheConfig},
+        fee_history::FeeHistoryCacheConfig,
+        gas_oracle::GasPriceOracleConfig,
+    },
     EthApi, EthFilter, EthPubSub,
 };
 use serde::{Deserialize, Serialize};
@@ -22,4 +27,14 @@ pub struct EthHandlers<Client, Pool, Network, Events> {
 pub struct EthConfig {
     /// Settings for the caching layer
     pub cache: EthStateCacheConfig,
+    /// Settings for the cache of the fee data of recent blocks
+    pub fee_history_cache: FeeHistoryCacheConfig,
+    /// Settings for the oracle that suggests priority fees
+    pub gas_oracle: GasPriceOracleConfig,
+    /// Max lengths of the call data and raw transaction params
+    pub bytes_limits: BytesLimits,
+    /// Whether `eth_estimateGas` adds the gas that pays the L1 data fee of the transaction
+    #[cfg(feature = "optimism")]
+    #[serde(default)]
+    pub estimate_l1_data_fee: bool,
 }
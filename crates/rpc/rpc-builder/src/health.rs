@@ -0,0 +1,383 @@
+//! Health check endpoint for load balancers.
+//!
+//! The endpoint serves `GET /health` on a dedicated listener. It responds with `200 OK` if the
+//! node is synced within [HealthServerBuilder::max_blocks_behind] blocks of the fork choice head
+//! and is connected to at least [HealthServerBuilder::min_peers] peers, and with
+//! `503 Service Unavailable` otherwise. The body is a JSON [HealthReport] that lists the failed
+//! checks.
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use reth_interfaces::consensus::ForkchoiceState;
+use reth_network_api::PeersInfo;
+use reth_primitives::{BlockNumber, H256};
+use reth_provider::BlockIdProvider;
+use serde::Serialize;
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+use tokio::sync::{oneshot, watch};
+use tracing::trace;
+
+/// The path of the health check endpoint.
+pub const HEALTH_PATH: &str = "/health";
+
+/// The default number of blocks the node may be behind the fork choice head to be healthy.
+pub const DEFAULT_HEALTH_MAX_BLOCKS_BEHIND: u64 = 64;
+
+/// The default number of peers the node must be connected to to be healthy.
+pub const DEFAULT_HEALTH_MIN_PEERS: usize = 1;
+
+/// Configures and starts the health check server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthServerBuilder {
+    max_blocks_behind: u64,
+    min_peers: usize,
+}
+
+impl Default for HealthServerBuilder {
+    fn default() -> Self {
+        Self {
+            max_blocks_behind: DEFAULT_HEALTH_MAX_BLOCKS_BEHIND,
+            min_peers: DEFAULT_HEALTH_MIN_PEERS,
+        }
+    }
+}
+
+impl HealthServerBuilder {
+    /// Sets the number of blocks the node may be behind the fork choice head to be healthy.
+    pub fn max_blocks_behind(mut self, max_blocks_behind: u64) -> Self {
+        self.max_blocks_behind = max_blocks_behind;
+        self
+    }
+
+    /// Sets the number of peers the node must be connected to to be healthy.
+    pub fn min_peers(mut self, min_peers: usize) -> Self {
+        self.min_peers = min_peers;
+        self
+    }
+
+    /// Creates the [HealthCheck] of the node with the configured thresholds.
+    pub fn build<Client, Network>(
+        self,
+        client: Client,
+        network: Network,
+        forkchoice_state: watch::Receiver<ForkchoiceState>,
+    ) -> HealthCheck<Client, Network> {
+        HealthCheck {
+            client,
+            network,
+            forkchoice_state,
+            max_blocks_behind: self.max_blocks_behind,
+            min_peers: self.min_peers,
+        }
+    }
+
+    /// Starts the health check server on the given address.
+    pub async fn start<Client, Network>(
+        self,
+        addr: SocketAddr,
+        client: Client,
+        network: Network,
+        forkchoice_state: watch::Receiver<ForkchoiceState>,
+    ) -> Result<HealthServerHandle, hyper::Error>
+    where
+        Client: BlockIdProvider + 'static,
+        Network: PeersInfo + 'static,
+    {
+        let check = Arc::new(self.build(client, network, forkchoice_state));
+        let make_service = make_service_fn(move |_| {
+            let check = Arc::clone(&check);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let check = Arc::clone(&check);
+                    async move { Ok::<_, Infallible>(check.handle(req)) }
+                }))
+            }
+        });
+
+        let server = Server::try_bind(&addr)?.serve(make_service);
+        let local_addr = server.local_addr();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        tokio::spawn(server.with_graceful_shutdown(async move {
+            let _ = shutdown_rx.await;
+        }));
+
+        Ok(HealthServerHandle { local_addr, _shutdown: shutdown_tx })
+    }
+}
+
+/// A handle to the health check server, the server is stopped when the handle is dropped.
+#[derive(Debug)]
+pub struct HealthServerHandle {
+    local_addr: SocketAddr,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl HealthServerHandle {
+    /// Returns the address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+/// Checks the health of the node.
+#[derive(Debug)]
+pub struct HealthCheck<Client, Network> {
+    client: Client,
+    network: Network,
+    /// The fork choice state of the consensus layer.
+    forkchoice_state: watch::Receiver<ForkchoiceState>,
+    max_blocks_behind: u64,
+    min_peers: usize,
+}
+
+impl<Client, Network> HealthCheck<Client, Network>
+where
+    Client: BlockIdProvider,
+    Network: PeersInfo,
+{
+    /// Runs all checks.
+    pub fn check(&self) -> HealthReport {
+        let mut failures = Vec::new();
+
+        let head_hash = self.forkchoice_state.borrow().head_block_hash;
+        let (local_number, head_number) = match self.sync_status(head_hash) {
+            Ok(status) => status,
+            Err(err) => {
+                failures.push(HealthFailure::Provider { message: err.to_string() });
+                (None, None)
+            }
+        };
+        if let Some(local_number) = local_number {
+            let blocks_behind = head_number.map(|head| head.saturating_sub(local_number));
+            if blocks_behind.map_or(true, |behind| behind > self.max_blocks_behind) {
+                failures.push(HealthFailure::Syncing {
+                    blocks_behind,
+                    max_blocks_behind: self.max_blocks_behind,
+                });
+            }
+        }
+
+        let peers = self.network.num_connected_peers();
+        if peers < self.min_peers {
+            failures.push(HealthFailure::Peers { peers, min_peers: self.min_peers });
+        }
+
+        HealthReport {
+            healthy: failures.is_empty(),
+            local_number,
+            head_hash,
+            head_number,
+            peers,
+            failures,
+        }
+    }
+
+    /// Returns the number of the local head and of the fork choice head, if it is known.
+    fn sync_status(
+        &self,
+        head_hash: H256,
+    ) -> reth_interfaces::Result<(Option<BlockNumber>, Option<BlockNumber>)> {
+        let local_number = self.client.chain_info()?.best_number;
+        let head_number =
+            if head_hash.is_zero() { None } else { self.client.block_number(head_hash)? };
+        Ok((Some(local_number), head_number))
+    }
+
+    /// Serves a request to the health check endpoint.
+    fn handle(&self, req: Request<Body>) -> Response<Body> {
+        if req.method() != Method::GET || req.uri().path() != HEALTH_PATH {
+            return Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::empty())
+                .expect("valid response")
+        }
+
+        let report = self.check();
+        trace!(target: "rpc::health", ?report, "health check");
+        let status = if report.healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+        Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(&report).expect("serializable report")))
+            .expect("valid response")
+    }
+}
+
+/// The result of a health check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    /// Whether all checks passed.
+    pub healthy: bool,
+    /// The number of the local head.
+    pub local_number: Option<BlockNumber>,
+    /// The hash of the fork choice head, zero if the consensus layer didn't send one yet.
+    pub head_hash: H256,
+    /// The number of the fork choice head, if the block is known.
+    pub head_number: Option<BlockNumber>,
+    /// The number of connected peers.
+    pub peers: usize,
+    /// The checks that failed.
+    pub failures: Vec<HealthFailure>,
+}
+
+/// A failed health check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "check", rename_all = "camelCase")]
+pub enum HealthFailure {
+    /// The node is too far behind the fork choice head, or the fork choice head is unknown.
+    #[serde(rename_all = "camelCase")]
+    Syncing {
+        /// The number of blocks the node is behind, unknown if the fork choice head is unknown.
+        blocks_behind: Option<u64>,
+        /// The configured maximum.
+        max_blocks_behind: u64,
+    },
+    /// The node is connected to too few peers.
+    #[serde(rename_all = "camelCase")]
+    Peers {
+        /// The number of connected peers.
+        peers: usize,
+        /// The configured minimum.
+        min_peers: usize,
+    },
+    /// The sync status could not be read from the database.
+    Provider {
+        /// The error message.
+        message: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Block, Header, NodeRecord, PeerId};
+    use reth_provider::test_utils::MockEthProvider;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, Clone, Default)]
+    struct TestPeers(Arc<AtomicUsize>);
+
+    impl TestPeers {
+        fn set(&self, peers: usize) {
+            self.0.store(peers, Ordering::Relaxed);
+        }
+    }
+
+    impl PeersInfo for TestPeers {
+        fn num_connected_peers(&self) -> usize {
+            self.0.load(Ordering::Relaxed)
+        }
+
+        fn local_node_record(&self) -> NodeRecord {
+            NodeRecord::new(([127, 0, 0, 1], 30303).into(), PeerId::random())
+        }
+    }
+
+    fn block(number: BlockNumber) -> Block {
+        Block { header: Header { number, ..Default::default() }, ..Default::default() }
+    }
+
+    fn get(check: &HealthCheck<MockEthProvider, TestPeers>, path: &str) -> Response<Body> {
+        check.handle(Request::get(path).body(Body::empty()).unwrap())
+    }
+
+    /// Returns a health check with a local chain of 10 blocks and a fork choice head at block 10.
+    fn setup(
+    ) -> (HealthCheck<MockEthProvider, TestPeers>, watch::Sender<ForkchoiceState>, TestPeers) {
+        let client = MockEthProvider::default();
+        client.extend_blocks((0..=10).map(|number| (H256::from_low_u64_be(number), block(number))));
+        let head =
+            ForkchoiceState { head_block_hash: H256::from_low_u64_be(10), ..Default::default() };
+        let (forkchoice_tx, forkchoice_rx) = watch::channel(head);
+        let peers = TestPeers::default();
+        peers.set(1);
+        let check = HealthServerBuilder::default().max_blocks_behind(5).min_peers(1).build(
+            client,
+            peers.clone(),
+            forkchoice_rx,
+        );
+        (check, forkchoice_tx, peers)
+    }
+
+    #[test]
+    fn healthy_when_synced_with_peers() {
+        let (check, _forkchoice_tx, _peers) = setup();
+        let report = check.check();
+        assert!(report.healthy);
+        assert_eq!(report.local_number, Some(10));
+        assert_eq!(report.head_number, Some(10));
+        assert_eq!(get(&check, HEALTH_PATH).status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn unhealthy_without_peers() {
+        let (check, _forkchoice_tx, peers) = setup();
+        peers.set(0);
+        assert_eq!(get(&check, HEALTH_PATH).status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(check.check().failures, vec![HealthFailure::Peers { peers: 0, min_peers: 1 }]);
+
+        peers.set(3);
+        assert_eq!(get(&check, HEALTH_PATH).status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn unhealthy_when_behind_head() {
+        let (check, forkchoice_tx, _peers) = setup();
+
+        // the fork choice head is known but the local chain didn't reach it yet
+        let head_hash = H256::from_low_u64_be(20);
+        check.client.blocks.lock().insert(head_hash, block(20));
+        forkchoice_tx.send_modify(|state| state.head_block_hash = head_hash);
+        assert_eq!(get(&check, HEALTH_PATH).status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            check.check().failures,
+            vec![HealthFailure::Syncing { blocks_behind: Some(10), max_blocks_behind: 5 }]
+        );
+
+        // within the threshold
+        let head_hash = H256::from_low_u64_be(15);
+        check.client.blocks.lock().insert(head_hash, block(15));
+        forkchoice_tx.send_modify(|state| state.head_block_hash = head_hash);
+        assert_eq!(get(&check, HEALTH_PATH).status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn unhealthy_with_unknown_head() {
+        let (check, forkchoice_tx, _peers) = setup();
+        forkchoice_tx.send_modify(|state| state.head_block_hash = H256::zero());
+        assert_eq!(get(&check, HEALTH_PATH).status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        forkchoice_tx.send_modify(|state| state.head_block_hash = H256::from_low_u64_be(100));
+        let report = check.check();
+        assert_eq!(report.head_number, None);
+        assert_eq!(
+            report.failures,
+            vec![HealthFailure::Syncing { blocks_behind: None, max_blocks_behind: 5 }]
+        );
+    }
+
+    #[test]
+    fn serialize_report() {
+        let (check, _forkchoice_tx, peers) = setup();
+        peers.set(0);
+        let response = get(&check, HEALTH_PATH);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+        let report = serde_json::to_value(check.check()).unwrap();
+        assert_eq!(report["healthy"], false);
+        assert_eq!(report["localNumber"], 10);
+        assert_eq!(
+            report["failures"],
+            serde_json::json!([{ "check": "peers", "peers": 0, "minPeers": 1 }])
+        );
+    }
+
+    #[test]
+    fn not_found() {
+        let (check, _forkchoice_tx, _peers) = setup();
+        assert_eq!(get(&check, "/").status(), StatusCode::NOT_FOUND);
+    }
+}
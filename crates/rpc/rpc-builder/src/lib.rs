@@ -25,16 +25,17 @@
 //! Configure only a http server with a selection of [RethRpcModule]s
 //!
 //! ```
+//! use reth_interfaces::sync::SyncStateProvider;
 //! use reth_network_api::{NetworkInfo, Peers};
-//! use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory, EvmEnvProvider};
+//! use reth_provider::{AccountHistoryProvider, BlockProvider, ChainSpecProvider, HeaderProvider, StateProviderFactory, EvmEnvProvider};
 //! use reth_rpc_builder::{RethRpcModule, RpcModuleBuilder, RpcServerConfig, ServerBuilder, TransportRpcModuleConfig};
 //! use reth_tasks::TokioTaskExecutor;
 //! use reth_transaction_pool::TransactionPool;
 //! pub async fn launch<Client, Pool, Network>(client: Client, pool: Pool, network: Network)
 //! where
-//!     Client: BlockProvider + HeaderProvider + StateProviderFactory + EvmEnvProvider + Clone + Unpin + 'static,
+//!     Client: BlockProvider + HeaderProvider + StateProviderFactory + EvmEnvProvider + AccountHistoryProvider + ChainSpecProvider + Clone + Unpin + 'static,
 //!     Pool: TransactionPool + Clone + 'static,
-//!     Network: NetworkInfo + Peers + Clone + 'static,
+//!     Network: NetworkInfo + Peers + SyncStateProvider + Clone + 'static,
 //! {
 //!     // configure the rpc module per transport
 //!     let transports = TransportRpcModuleConfig::default().with_http(vec![
@@ -61,11 +62,23 @@ use jsonrpsee::{
     server::{IdProvider, Server, ServerHandle},
     RpcModule,
 };
+use reth_executor::blockchain_tree::pending::PendingStateViewer;
+use reth_interfaces::{
+    blockchain_tree::BlockchainTreeViewer,
+    events::{CanonStateNotifications, CanonStateSubscriptions},
+    sync::SyncStateProvider,
+};
 use reth_ipc::server::IpcServer;
 use reth_network_api::{NetworkInfo, Peers};
-use reth_provider::{BlockProvider, EvmEnvProvider, HeaderProvider, StateProviderFactory};
+use reth_primitives::Address;
+use reth_provider::{
+    AccountHistoryProvider, BlockProvider, CanonicalTip, ChainSpecProvider, EvmEnvProvider,
+    HeaderProvider, StateProviderFactory,
+};
+use reth_revm::{EthEvmConfig, EvmConfig};
 use reth_rpc::{
-    AdminApi, DebugApi, EthApi, EthFilter, EthSubscriptionIdProvider, NetApi, TraceApi, Web3Api,
+    AdminApi, DebugApi, EthApi, EthFilter, EthPubSub, EthSubscriptionIdProvider, NetApi,
+    OtterscanApi, RethApi, RethApiConfig, TraceApi, TracingCallGuard, Web3Api,
 };
 use reth_rpc_api::servers::*;
 use reth_transaction_pool::TransactionPool;
@@ -75,8 +88,10 @@ use std::{
     fmt,
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
     str::FromStr,
+    sync::Arc,
 };
 use strum::{AsRefStr, EnumString, EnumVariantNames, ParseError, VariantNames};
+use tokio_stream::wrappers::UnboundedReceiverStream;
 use tower::layer::util::{Identity, Stack};
 use tower_http::cors::CorsLayer;
 
@@ -93,12 +108,23 @@ mod eth;
 pub mod constants;
 pub use crate::eth::{EthConfig, EthHandlers};
 use constants::*;
-use reth_rpc::eth::cache::EthStateCache;
+use reth_rpc::eth::{
+    cache::EthStateCache,
+    events::{canon_state_broadcast_task, CanonStateBroadcaster},
+    fee_history::{fee_history_cache_new_blocks_task, FeeHistoryCache},
+};
 use reth_tasks::TaskSpawner;
 
 /// Cors utilities.
 mod cors;
 
+/// Health check endpoint.
+pub mod health;
+
+/// Request ids and tracing of RPC requests.
+pub mod request_tracing;
+use request_tracing::{RequestTracingLayer, RpcRequestLogger};
+
 /// Convenience function for starting a server in one step.
 pub async fn launch<Client, Pool, Network, Tasks>(
     client: Client,
@@ -113,11 +139,13 @@ where
         + HeaderProvider
         + StateProviderFactory
         + EvmEnvProvider
+        + AccountHistoryProvider
+        + ChainSpecProvider
         + Clone
         + Unpin
         + 'static,
     Pool: TransactionPool + Clone + 'static,
-    Network: NetworkInfo + Peers + Clone + 'static,
+    Network: NetworkInfo + Peers + SyncStateProvider + Clone + 'static,
     Tasks: TaskSpawner + Clone + 'static,
 {
     let module_config = module_config.into();
@@ -141,6 +169,18 @@ pub struct RpcModuleBuilder<Client, Pool, Network, Tasks> {
     network: Network,
     /// How additional tasks are spawned, for example in the eth pubsub namespace
     executor: Tasks,
+    /// The configuration of the EVM that executes calls
+    evm_config: Arc<dyn EvmConfig>,
+    /// The fee recipient of the blocks built by the node
+    fee_recipient: Option<Address>,
+    /// Notifications about changes of the canonical chain that keep the caches up to date
+    canon_state_notifications: Option<CanonStateNotifications>,
+    /// The blockchain tree inspected by the reth namespace
+    blockchain_tree: Option<Arc<dyn BlockchainTreeViewer>>,
+    /// The state of the blockchain tree that the debug namespace traces non-canonical blocks on
+    pending_state: Option<Arc<dyn PendingStateViewer>>,
+    /// The in-memory tip of the canonical chain that serves `eth_blockNumber`
+    canonical_tip: Option<CanonicalTip>,
 }
 
 // === impl RpcBuilder ===
@@ -148,7 +188,62 @@ pub struct RpcModuleBuilder<Client, Pool, Network, Tasks> {
 impl<Client, Pool, Network, Tasks> RpcModuleBuilder<Client, Pool, Network, Tasks> {
     /// Create a new instance of the builder
     pub fn new(client: Client, pool: Pool, network: Network, executor: Tasks) -> Self {
-        Self { client, pool, network, executor }
+        Self {
+            client,
+            pool,
+            network,
+            executor,
+            evm_config: Arc::new(EthEvmConfig::default()),
+            fee_recipient: None,
+            canon_state_notifications: None,
+            blockchain_tree: None,
+            pending_state: None,
+            canonical_tip: None,
+        }
+    }
+
+    /// Configure the EVM that executes calls, for example in `eth_call`.
+    pub fn with_evm_config(mut self, evm_config: Arc<dyn EvmConfig>) -> Self {
+        self.evm_config = evm_config;
+        self
+    }
+
+    /// Configure the fee recipient of the blocks built by the node, returned by `eth_coinbase`.
+    pub fn with_fee_recipient(mut self, fee_recipient: Address) -> Self {
+        self.fee_recipient = Some(fee_recipient);
+        self
+    }
+
+    /// Configure the notifications about changes of the canonical chain, which keep the fee
+    /// history cache of `eth_feeHistory` up to date and drive the `eth_subscribe` and
+    /// `reth_subscribe` subscriptions.
+    pub fn with_canon_state_notifications(
+        mut self,
+        canon_state_notifications: CanonStateNotifications,
+    ) -> Self {
+        self.canon_state_notifications = Some(canon_state_notifications);
+        self
+    }
+
+    /// Configure the blockchain tree returned by `reth_getBlockchainTreeState`, which also serves
+    /// the transactions of non-canonical blocks in `eth_getTransactionByHash`.
+    pub fn with_blockchain_tree(mut self, blockchain_tree: Arc<dyn BlockchainTreeViewer>) -> Self {
+        self.blockchain_tree = Some(blockchain_tree);
+        self
+    }
+
+    /// Configure the state of the blockchain tree, which serves `debug_traceBlockByHash` for blocks
+    /// that are not yet canonical.
+    pub fn with_pending_state(mut self, pending_state: Arc<dyn PendingStateViewer>) -> Self {
+        self.pending_state = Some(pending_state);
+        self
+    }
+
+    /// Configure the in-memory tip of the canonical chain, which serves `eth_blockNumber` and
+    /// resolves the `latest` block without a database read once it is set.
+    pub fn with_canonical_tip(mut self, canonical_tip: CanonicalTip) -> Self {
+        self.canonical_tip = Some(canonical_tip);
+        self
     }
 
     /// Configure the client instance.
@@ -156,8 +251,30 @@ impl<Client, Pool, Network, Tasks> RpcModuleBuilder<Client, Pool, Network, Tasks
     where
         C: BlockProvider + StateProviderFactory + EvmEnvProvider + 'static,
     {
-        let Self { pool, network, executor, .. } = self;
-        RpcModuleBuilder { client, network, pool, executor }
+        let Self {
+            pool,
+            network,
+            executor,
+            evm_config,
+            fee_recipient,
+            canon_state_notifications,
+            blockchain_tree,
+            pending_state,
+            canonical_tip,
+            ..
+        } = self;
+        RpcModuleBuilder {
+            client,
+            network,
+            pool,
+            executor,
+            evm_config,
+            fee_recipient,
+            canon_state_notifications,
+            blockchain_tree,
+            pending_state,
+            canonical_tip,
+        }
     }
 
     /// Configure the transaction pool instance.
@@ -165,8 +282,30 @@ impl<Client, Pool, Network, Tasks> RpcModuleBuilder<Client, Pool, Network, Tasks
     where
         P: TransactionPool + 'static,
     {
-        let Self { client, network, executor, .. } = self;
-        RpcModuleBuilder { client, network, pool, executor }
+        let Self {
+            client,
+            network,
+            executor,
+            evm_config,
+            fee_recipient,
+            canon_state_notifications,
+            blockchain_tree,
+            pending_state,
+            canonical_tip,
+            ..
+        } = self;
+        RpcModuleBuilder {
+            client,
+            network,
+            pool,
+            executor,
+            evm_config,
+            fee_recipient,
+            canon_state_notifications,
+            blockchain_tree,
+            pending_state,
+            canonical_tip,
+        }
     }
 
     /// Configure the network instance.
@@ -174,8 +313,30 @@ impl<Client, Pool, Network, Tasks> RpcModuleBuilder<Client, Pool, Network, Tasks
     where
         N: NetworkInfo + Peers + 'static,
     {
-        let Self { client, pool, executor, .. } = self;
-        RpcModuleBuilder { client, network, pool, executor }
+        let Self {
+            client,
+            pool,
+            executor,
+            evm_config,
+            fee_recipient,
+            canon_state_notifications,
+            blockchain_tree,
+            pending_state,
+            canonical_tip,
+            ..
+        } = self;
+        RpcModuleBuilder {
+            client,
+            network,
+            pool,
+            executor,
+            evm_config,
+            fee_recipient,
+            canon_state_notifications,
+            blockchain_tree,
+            pending_state,
+            canonical_tip,
+        }
     }
 
     /// Configure the task executor to use for additional tasks.
@@ -183,8 +344,30 @@ impl<Client, Pool, Network, Tasks> RpcModuleBuilder<Client, Pool, Network, Tasks
     where
         T: TaskSpawner + 'static,
     {
-        let Self { pool, network, client, .. } = self;
-        RpcModuleBuilder { client, network, pool, executor }
+        let Self {
+            pool,
+            network,
+            client,
+            evm_config,
+            fee_recipient,
+            canon_state_notifications,
+            blockchain_tree,
+            pending_state,
+            canonical_tip,
+            ..
+        } = self;
+        RpcModuleBuilder {
+            client,
+            network,
+            pool,
+            executor,
+            evm_config,
+            fee_recipient,
+            canon_state_notifications,
+            blockchain_tree,
+            pending_state,
+            canonical_tip,
+        }
     }
 }
 
@@ -194,11 +377,13 @@ where
         + HeaderProvider
         + StateProviderFactory
         + EvmEnvProvider
+        + AccountHistoryProvider
+        + ChainSpecProvider
         + Clone
         + Unpin
         + 'static,
     Pool: TransactionPool + Clone + 'static,
-    Network: NetworkInfo + Peers + Clone + 'static,
+    Network: NetworkInfo + Peers + SyncStateProvider + Clone + 'static,
     Tasks: TaskSpawner + Clone + 'static,
 {
     /// Configures all [RpcModule]s specific to the given [TransportRpcModuleConfig] which can be
@@ -208,7 +393,18 @@ where
     pub fn build(self, module_config: TransportRpcModuleConfig) -> TransportRpcModules<()> {
         let mut modules = TransportRpcModules::default();
 
-        let Self { client, pool, network, executor } = self;
+        let Self {
+            client,
+            pool,
+            network,
+            executor,
+            evm_config,
+            fee_recipient,
+            canon_state_notifications,
+            blockchain_tree,
+            pending_state,
+            canonical_tip,
+        } = self;
 
         if !module_config.is_empty() {
             let TransportRpcModuleConfig { http, ws, ipc, config } = module_config;
@@ -219,7 +415,13 @@ where
                 network,
                 executor,
                 config.unwrap_or_default(),
-            );
+            )
+            .with_evm_config(evm_config);
+            registry.fee_recipient = fee_recipient;
+            registry.canon_state_notifications = canon_state_notifications;
+            registry.blockchain_tree = blockchain_tree;
+            registry.pending_state = pending_state;
+            registry.canonical_tip = canonical_tip;
 
             modules.http = registry.maybe_module(http.as_ref());
             modules.ws = registry.maybe_module(ws.as_ref());
@@ -228,6 +430,53 @@ where
 
         modules
     }
+
+    /// Configures the modules like [RpcModuleBuilder::build] and also returns a module with the
+    /// [RethRpcModule]s of all transports.
+    ///
+    /// The returned module shares the handlers of the transport modules, so it can be used to call
+    /// the methods in process, See [RpcModule::call].
+    pub fn build_with_in_process(
+        self,
+        module_config: TransportRpcModuleConfig,
+    ) -> (TransportRpcModules<()>, RpcModule<()>) {
+        let Self {
+            client,
+            pool,
+            network,
+            executor,
+            evm_config,
+            fee_recipient,
+            canon_state_notifications,
+            blockchain_tree,
+            pending_state,
+            canonical_tip,
+        } = self;
+        let TransportRpcModuleConfig { http, ws, ipc, config } = module_config;
+
+        let mut namespaces = Vec::new();
+        for namespace in [&http, &ws, &ipc].into_iter().flatten().flat_map(|s| s.iter_selection()) {
+            if !namespaces.contains(&namespace) {
+                namespaces.push(namespace);
+            }
+        }
+
+        let mut registry =
+            RethModuleRegistry::new(client, pool, network, executor, config.unwrap_or_default())
+                .with_evm_config(evm_config);
+        registry.fee_recipient = fee_recipient;
+        registry.canon_state_notifications = canon_state_notifications;
+        registry.blockchain_tree = blockchain_tree;
+        registry.pending_state = pending_state;
+        registry.canonical_tip = canonical_tip;
+        let modules = TransportRpcModules {
+            http: registry.maybe_module(http.as_ref()),
+            ws: registry.maybe_module(ws.as_ref()),
+            ipc: registry.maybe_module(ipc.as_ref()),
+        };
+        let in_process = registry.module_for(&RpcModuleSelection::Selection(namespaces));
+        (modules, in_process)
+    }
 }
 
 impl Default for RpcModuleBuilder<(), (), (), ()> {
@@ -241,6 +490,8 @@ impl Default for RpcModuleBuilder<(), (), (), ()> {
 pub struct RpcModuleConfig {
     /// `eth` namespace settings
     eth: EthConfig,
+    /// `reth` namespace settings
+    reth: RethApiConfig,
 }
 
 // === impl RpcModuleConfig ===
@@ -256,6 +507,7 @@ impl RpcModuleConfig {
 #[derive(Default)]
 pub struct RpcModuleConfigBuilder {
     eth: Option<EthConfig>,
+    reth: Option<RethApiConfig>,
 }
 
 // === impl RpcModuleConfigBuilder ===
@@ -267,10 +519,16 @@ impl RpcModuleConfigBuilder {
         self
     }
 
+    /// Configures a custom reth namespace config
+    pub fn reth(mut self, reth: RethApiConfig) -> Self {
+        self.reth = Some(reth);
+        self
+    }
+
     /// Consumes the type and creates the [RpcModuleConfig]
     pub fn build(self) -> RpcModuleConfig {
-        let RpcModuleConfigBuilder { eth } = self;
-        RpcModuleConfig { eth: eth.unwrap_or_default() }
+        let RpcModuleConfigBuilder { eth, reth } = self;
+        RpcModuleConfig { eth: eth.unwrap_or_default(), reth: reth.unwrap_or_default() }
     }
 }
 
@@ -349,11 +607,13 @@ impl RpcModuleSelection {
             + HeaderProvider
             + StateProviderFactory
             + EvmEnvProvider
+            + AccountHistoryProvider
+            + ChainSpecProvider
             + Clone
             + Unpin
             + 'static,
         Pool: TransactionPool + Clone + 'static,
-        Network: NetworkInfo + Peers + Clone + 'static,
+        Network: NetworkInfo + Peers + SyncStateProvider + Clone + 'static,
         Tasks: TaskSpawner + Clone + 'static,
     {
         let mut registry = RethModuleRegistry::new(client, pool, network, executor, config);
@@ -414,6 +674,10 @@ pub enum RethRpcModule {
     Eth,
     /// `net_` module
     Net,
+    /// `ots_` module
+    Ots,
+    /// `reth_` module
+    Reth,
     /// `trace_` module
     Trace,
     /// `web3_` module
@@ -443,8 +707,23 @@ pub struct RethModuleRegistry<Client, Pool, Network, Tasks> {
     executor: Tasks,
     /// Additional settings for handlers.
     config: RpcModuleConfig,
+    /// The configuration of the EVM that executes calls
+    evm_config: Arc<dyn EvmConfig>,
+    /// The fee recipient of the blocks built by the node
+    fee_recipient: Option<Address>,
+    /// Notifications about changes of the canonical chain, consumed by the fee history cache and
+    /// the subscriptions
+    canon_state_notifications: Option<CanonStateNotifications>,
+    /// The blockchain tree inspected by the reth namespace
+    blockchain_tree: Option<Arc<dyn BlockchainTreeViewer>>,
+    /// The state of the blockchain tree that the debug namespace traces non-canonical blocks on
+    pending_state: Option<Arc<dyn PendingStateViewer>>,
+    /// The in-memory tip of the canonical chain that serves `eth_blockNumber`
+    canonical_tip: Option<CanonicalTip>,
+    /// Caps the concurrent block re-executions of the debug and reth namespaces
+    tracing_call_guard: TracingCallGuard,
     /// Holds a clone of all the eth namespace handlers
-    eth: Option<EthHandlers<Client, Pool, Network, ()>>,
+    eth: Option<EthHandlers<Client, Pool, Network, CanonStateBroadcaster>>,
     /// Contains the [Methods] of a module
     modules: HashMap<RethRpcModule, Methods>,
 }
@@ -460,7 +739,66 @@ impl<Client, Pool, Network, Tasks> RethModuleRegistry<Client, Pool, Network, Tas
         executor: Tasks,
         config: RpcModuleConfig,
     ) -> Self {
-        Self { client, pool, network, eth: None, executor, modules: Default::default(), config }
+        Self {
+            client,
+            pool,
+            network,
+            eth: None,
+            executor,
+            modules: Default::default(),
+            config,
+            evm_config: Arc::new(EthEvmConfig::default()),
+            fee_recipient: None,
+            canon_state_notifications: None,
+            blockchain_tree: None,
+            pending_state: None,
+            canonical_tip: None,
+            tracing_call_guard: Default::default(),
+        }
+    }
+
+    /// Configures the EVM that executes calls, for example in `eth_call`.
+    pub fn with_evm_config(mut self, evm_config: Arc<dyn EvmConfig>) -> Self {
+        self.evm_config = evm_config;
+        self
+    }
+
+    /// Configures the fee recipient of the blocks built by the node, returned by `eth_coinbase`.
+    pub fn with_fee_recipient(mut self, fee_recipient: Address) -> Self {
+        self.fee_recipient = Some(fee_recipient);
+        self
+    }
+
+    /// Configures the notifications about changes of the canonical chain, which keep the fee
+    /// history cache of `eth_feeHistory` up to date and drive the `eth_subscribe` and
+    /// `reth_subscribe` subscriptions.
+    pub fn with_canon_state_notifications(
+        mut self,
+        canon_state_notifications: CanonStateNotifications,
+    ) -> Self {
+        self.canon_state_notifications = Some(canon_state_notifications);
+        self
+    }
+
+    /// Configures the blockchain tree returned by `reth_getBlockchainTreeState`, which also serves
+    /// the transactions of non-canonical blocks in `eth_getTransactionByHash`.
+    pub fn with_blockchain_tree(mut self, blockchain_tree: Arc<dyn BlockchainTreeViewer>) -> Self {
+        self.blockchain_tree = Some(blockchain_tree);
+        self
+    }
+
+    /// Configures the state of the blockchain tree, which serves `debug_traceBlockByHash` for
+    /// blocks that are not yet canonical.
+    pub fn with_pending_state(mut self, pending_state: Arc<dyn PendingStateViewer>) -> Self {
+        self.pending_state = Some(pending_state);
+        self
+    }
+
+    /// Configures the in-memory tip of the canonical chain, which serves `eth_blockNumber` and
+    /// resolves the `latest` block without a database read once it is set.
+    pub fn with_canonical_tip(mut self, canonical_tip: CanonicalTip) -> Self {
+        self.canonical_tip = Some(canonical_tip);
+        self
     }
 
     /// Returns all installed methods
@@ -503,24 +841,26 @@ where
         + HeaderProvider
         + StateProviderFactory
         + EvmEnvProvider
+        + AccountHistoryProvider
+        + ChainSpecProvider
         + Clone
         + Unpin
         + 'static,
     Pool: TransactionPool + Clone + 'static,
-    Network: NetworkInfo + Peers + Clone + 'static,
+    Network: NetworkInfo + Peers + SyncStateProvider + Clone + 'static,
     Tasks: TaskSpawner + Clone + 'static,
 {
     /// Register Eth Namespace
     pub fn register_eth(&mut self) -> &mut Self {
-        let eth_api = self.eth_api();
-        self.modules.insert(RethRpcModule::Eth, eth_api.into_rpc().into());
+        let eth_methods = self.eth_methods();
+        self.modules.insert(RethRpcModule::Eth, eth_methods);
         self
     }
 
     /// Register Debug Namespace
     pub fn register_debug(&mut self) -> &mut Self {
         let eth_api = self.eth_api();
-        self.modules.insert(RethRpcModule::Debug, DebugApi::new(eth_api).into_rpc().into());
+        self.modules.insert(RethRpcModule::Debug, self.debug_api(eth_api).into_rpc().into());
         self
     }
 
@@ -534,6 +874,13 @@ where
         self
     }
 
+    /// Register Reth Namespace
+    pub fn register_reth(&mut self) -> &mut Self {
+        let reth_methods = self.reth_namespace_methods();
+        self.modules.insert(RethRpcModule::Reth, reth_methods);
+        self
+    }
+
     /// Helper function to create a [RpcModule] if it's not `None`
     fn maybe_module(&mut self, config: Option<&RpcModuleSelection>) -> Option<RpcModule<()>> {
         let config = config?;
@@ -562,6 +909,8 @@ where
     ) -> Vec<Methods> {
         let eth_api = self.eth_api();
         let eth_cache = self.eth_cache();
+        let eth_methods = self.eth_methods();
+        let reth_methods = self.reth_namespace_methods();
         namespaces
             .map(|namespace| {
                 self.modules
@@ -570,16 +919,20 @@ where
                         RethRpcModule::Admin => {
                             AdminApi::new(self.network.clone()).into_rpc().into()
                         }
-                        RethRpcModule::Debug => DebugApi::new(eth_api.clone()).into_rpc().into(),
-                        RethRpcModule::Eth => eth_api.clone().into_rpc().into(),
+                        RethRpcModule::Debug => self.debug_api(eth_api.clone()).into_rpc().into(),
+                        RethRpcModule::Eth => eth_methods.clone(),
                         RethRpcModule::Net => {
                             NetApi::new(self.network.clone(), eth_api.clone()).into_rpc().into()
                         }
-                        RethRpcModule::Trace => {
-                            TraceApi::new(self.client.clone(), eth_api.clone(), eth_cache.clone())
+                        RethRpcModule::Ots => {
+                            OtterscanApi::new(self.client.clone(), eth_api.clone())
                                 .into_rpc()
                                 .into()
                         }
+                        RethRpcModule::Reth => reth_methods.clone(),
+                        RethRpcModule::Trace => {
+                            self.trace_api(eth_api.clone(), eth_cache.clone()).into_rpc().into()
+                        }
                         RethRpcModule::Web3 => Web3Api::new(self.network.clone()).into_rpc().into(),
                     })
                     .clone()
@@ -598,7 +951,7 @@ where
     /// Creates the [EthHandlers] type the first time this is called.
     fn with_eth<F, R>(&mut self, f: F) -> R
     where
-        F: FnOnce(&EthHandlers<Client, Pool, Network, ()>) -> R,
+        F: FnOnce(&EthHandlers<Client, Pool, Network, CanonStateBroadcaster>) -> R,
     {
         if self.eth.is_none() {
             let eth_cache = EthStateCache::spawn_with(
@@ -606,17 +959,61 @@ where
                 self.config.eth.cache.clone(),
                 self.executor.clone(),
             );
-            let api = EthApi::new(
+            let mut api = EthApi::with_evm_config(
                 self.client.clone(),
                 self.pool.clone(),
                 self.network.clone(),
                 eth_cache.clone(),
+                self.evm_config.clone(),
             );
+            if let Some(fee_recipient) = self.fee_recipient {
+                api = api.with_fee_recipient(fee_recipient);
+            }
+            if let Some(blockchain_tree) = self.blockchain_tree.clone() {
+                api = api.with_blockchain_tree(blockchain_tree);
+            }
+            if let Some(canonical_tip) = self.canonical_tip.clone() {
+                api = api.with_canonical_tip(canonical_tip);
+            }
+
+            let fee_history_cache = FeeHistoryCache::new(self.config.eth.fee_history_cache);
+            let canon_state = CanonStateBroadcaster::default();
+            if let Some(notifications) = self.canon_state_notifications.take() {
+                self.executor.spawn_critical(
+                    "fee history cache",
+                    Box::pin(fee_history_cache_new_blocks_task(
+                        fee_history_cache.clone(),
+                        UnboundedReceiverStream::new(canon_state.subscribe_canon_state()),
+                    )),
+                );
+                self.executor.spawn_critical(
+                    "canon state broadcast",
+                    Box::pin(canon_state_broadcast_task(
+                        canon_state.clone(),
+                        UnboundedReceiverStream::new(notifications),
+                    )),
+                );
+            }
+            api = api
+                .with_fee_history_cache(fee_history_cache)
+                .with_gas_oracle_config(self.config.eth.gas_oracle)
+                .with_bytes_limits(self.config.eth.bytes_limits);
+            #[cfg(feature = "optimism")]
+            {
+                api = api.with_l1_data_fee_estimation(self.config.eth.estimate_l1_data_fee);
+            }
+
             let filter = EthFilter::new(self.client.clone(), self.pool.clone());
 
-            // TODO: install pubsub
+            let pubsub = EthPubSub::with_spawner(
+                self.client.clone(),
+                self.pool.clone(),
+                canon_state,
+                self.network.clone(),
+                Box::new(self.executor.clone()),
+            );
 
-            let eth = EthHandlers { api, eth_cache, filter, pubsub: None };
+            let eth = EthHandlers { api, eth_cache, filter, pubsub: Some(pubsub) };
             self.eth = Some(eth);
         }
         f(self.eth.as_ref().expect("exists; qed"))
@@ -626,6 +1023,60 @@ where
     fn eth_api(&mut self) -> EthApi<Client, Pool, Network> {
         self.with_eth(|handlers| handlers.api.clone())
     }
+
+    /// Returns the configured [EthPubSub] or creates it if it does not exist yet
+    fn eth_pubsub(&mut self) -> EthPubSub<Client, Pool, CanonStateBroadcaster, Network> {
+        self.with_eth(|handlers| handlers.pubsub.clone().expect("installed; qed"))
+    }
+
+    /// Returns the [Methods] of the eth namespace, including `eth_subscribe`
+    fn eth_methods(&mut self) -> Methods {
+        let mut module = self.eth_api().into_rpc();
+        module.merge(EthPubSubApiServer::into_rpc(self.eth_pubsub())).expect("No conflicts");
+        module.into()
+    }
+
+    /// Creates the [DebugApi], which traces the blocks of the blockchain tree if it is configured
+    fn debug_api(
+        &self,
+        eth_api: EthApi<Client, Pool, Network>,
+    ) -> DebugApi<Client, EthApi<Client, Pool, Network>> {
+        let debug_api = DebugApi::new(self.client.clone(), eth_api)
+            .with_evm_config(self.evm_config.clone())
+            .with_tracing_call_guard(self.tracing_call_guard.clone());
+        match self.pending_state.clone() {
+            Some(pending_state) => debug_api.with_pending_state(pending_state),
+            None => debug_api,
+        }
+    }
+
+    /// Creates the [TraceApi], which traces the blocks of the blockchain tree if it is configured
+    fn trace_api(
+        &self,
+        eth_api: EthApi<Client, Pool, Network>,
+        eth_cache: EthStateCache,
+    ) -> TraceApi<Client, EthApi<Client, Pool, Network>> {
+        let trace_api = TraceApi::new(self.client.clone(), eth_api, eth_cache)
+            .with_evm_config(self.evm_config.clone())
+            .with_tracing_call_guard(self.tracing_call_guard.clone());
+        match self.pending_state.clone() {
+            Some(pending_state) => trace_api.with_pending_state(pending_state),
+            None => trace_api,
+        }
+    }
+
+    /// Returns the [Methods] of the reth namespace, including `reth_subscribe`
+    fn reth_namespace_methods(&mut self) -> Methods {
+        let mut reth_api = RethApi::new(self.client.clone(), self.pool.clone(), self.config.reth)
+            .with_evm_config(self.evm_config.clone())
+            .with_tracing_call_guard(self.tracing_call_guard.clone());
+        if let Some(blockchain_tree) = self.blockchain_tree.clone() {
+            reth_api = reth_api.with_blockchain_tree(blockchain_tree);
+        }
+        let mut module = reth_api.into_rpc();
+        module.merge(RethPubSubApiServer::into_rpc(self.eth_pubsub())).expect("No conflicts");
+        module.into()
+    }
 }
 
 /// A builder type for configuring and launching the servers that will handle RPC requests.
@@ -791,15 +1242,18 @@ impl RpcServerConfig {
         )));
 
         if let Some(builder) = self.http_server_config {
+            let builder = builder.set_logger(RpcRequestLogger::default());
+            let middleware = tower::ServiceBuilder::new().layer(RequestTracingLayer::default());
             if let Some(cors) = self.http_cors_domains.as_deref().map(cors::create_cors_layer) {
                 let cors = cors.map_err(|err| RpcError::Custom(err.to_string()))?;
-                let middleware = tower::ServiceBuilder::new().layer(cors);
+                let middleware = middleware.layer(cors);
                 let http_server =
                     builder.set_middleware(middleware).build(http_socket_addr).await?;
                 server.http_local_addr = http_server.local_addr().ok();
                 server.http = Some(HttpServer::WithCors(http_server));
             } else {
-                let http_server = builder.build(http_socket_addr).await?;
+                let http_server =
+                    builder.set_middleware(middleware).build(http_socket_addr).await?;
                 server.http_local_addr = http_server.local_addr().ok();
                 server.http = Some(HttpServer::Plain(http_server));
             }
@@ -811,7 +1265,11 @@ impl RpcServerConfig {
         )));
 
         if let Some(builder) = self.ws_server_config {
-            let ws_server = builder.build(ws_socket_addr).await.unwrap();
+            let ws_server = builder
+                .set_logger(RpcRequestLogger::default())
+                .build(ws_socket_addr)
+                .await
+                .unwrap();
             server.ws_local_addr = ws_server.local_addr().ok();
             server.ws = Some(ws_server);
         }
@@ -820,7 +1278,7 @@ impl RpcServerConfig {
             let ipc_path = self
                 .ipc_endpoint
                 .unwrap_or_else(|| Endpoint::new(DEFAULT_IPC_ENDPOINT.to_string()));
-            let ipc = builder.build(ipc_path.path())?;
+            let ipc = builder.set_logger(RpcRequestLogger::default()).build(ipc_path.path())?;
             server.ipc = Some(ipc);
         }
 
@@ -928,6 +1386,19 @@ pub struct TransportRpcModules<Context> {
 // === impl TransportRpcModules ===
 
 impl TransportRpcModules<()> {
+    /// Merges the given methods into the modules of all configured transports.
+    ///
+    /// Returns `false` if no transport is configured.
+    pub fn merge_configured(&mut self, other: impl Into<Methods>) -> Result<bool, RpcError> {
+        let other = other.into();
+        let mut merged = false;
+        for module in [&mut self.http, &mut self.ws, &mut self.ipc].into_iter().flatten() {
+            module.merge(other.clone())?;
+            merged = true;
+        }
+        Ok(merged)
+    }
+
     /// Convenience function for starting a server
     pub async fn start_server(self, builder: RpcServerConfig) -> Result<RpcServerHandle, RpcError> {
         builder.start(self).await
@@ -943,16 +1414,18 @@ pub struct RpcServer {
     /// http server
     http: Option<HttpServer>,
     /// ws server
-    ws: Option<Server>,
+    ws: Option<Server<Identity, RpcRequestLogger>>,
     /// ipc server
-    ipc: Option<IpcServer>,
+    ipc: Option<IpcServer<Identity, RpcRequestLogger>>,
 }
 /// Http Servers Enum
+///
+/// Requests of both servers are traced by the [RequestTracingLayer].
 pub enum HttpServer {
     /// Http server
-    Plain(Server),
+    Plain(Server<Stack<RequestTracingLayer, Identity>, RpcRequestLogger>),
     /// Http server with cors
-    WithCors(Server<Stack<CorsLayer, Identity>>),
+    WithCors(Server<Stack<CorsLayer, Stack<RequestTracingLayer, Identity>>, RpcRequestLogger>),
 }
 
 // === impl RpcServer ===
@@ -1133,6 +1606,8 @@ mod tests {
                 "debug" =>  RethRpcModule::Debug,
                 "eth" =>  RethRpcModule::Eth,
                 "net" =>  RethRpcModule::Net,
+                "ots" =>  RethRpcModule::Ots,
+                "reth" =>  RethRpcModule::Reth,
                 "trace" =>  RethRpcModule::Trace,
                 "web3" =>  RethRpcModule::Web3,
             );
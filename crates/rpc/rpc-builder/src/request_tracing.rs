@@ -0,0 +1,172 @@
+//! Request ids and tracing of RPC requests.
+//!
+//! The [RequestTracingLayer] assigns an id to each http request and runs the request in a
+//! `rpc_request` span with the id, so that all logs of the request, including the logs of
+//! provider reads and executor calls, can be correlated. The [RpcRequestLogger] logs the method,
+//! params size, duration and outcome of each call.
+
+use hyper::{header::HeaderValue, Request, Response};
+use jsonrpsee::{
+    server::logger::{HttpRequest, Logger, MethodKind, TransportProtocol},
+    types::Params,
+};
+use std::{
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{Context, Poll},
+    time::Instant,
+};
+use tower::{Layer, Service};
+use tracing::{debug, debug_span, Instrument};
+
+/// The header of a http request with the id of the request, echoed in the response.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Maximum length of a request id passed by the client, longer ids are replaced.
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+/// Source of the ids of requests that don't pass their own id.
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Returns the id of the request: the valid `X-Request-Id` of the request, or a new short id.
+fn request_id<B>(request: &Request<B>) -> String {
+    request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .map(ToString::to_string)
+        .unwrap_or_else(|| format!("{:x}", NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed)))
+}
+
+/// A [Layer] that runs each http request in a `rpc_request` span with the id of the request.
+///
+/// The id is taken from the `X-Request-Id` header of the request or assigned if missing, and
+/// returned in the `X-Request-Id` header of the response.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct RequestTracingLayer;
+
+impl<S> Layer<S> for RequestTracingLayer {
+    type Service = RequestTracingService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestTracingService { inner }
+    }
+}
+
+/// The [Service] of the [RequestTracingLayer].
+#[derive(Debug, Clone)]
+pub struct RequestTracingService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RequestTracingService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let id = request_id(&request);
+        let span = debug_span!(target: "rpc::request", "rpc_request", id = %id);
+        let response = span.in_scope(|| self.inner.call(request)).instrument(span);
+        Box::pin(async move {
+            let mut response = response.await?;
+            if let Ok(id) = HeaderValue::from_str(&id) {
+                response.headers_mut().insert(REQUEST_ID_HEADER, id);
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// A [Logger] that logs each RPC call at debug level: the method and size of the params when the
+/// call starts, and the duration and outcome when it finished.
+///
+/// Calls of http requests are logged in the span of the [RequestTracingLayer].
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct RpcRequestLogger;
+
+impl Logger for RpcRequestLogger {
+    type Instant = Instant;
+
+    fn on_connect(&self, _remote_addr: SocketAddr, _request: &HttpRequest, _t: TransportProtocol) {}
+
+    fn on_request(&self, _transport: TransportProtocol) -> Self::Instant {
+        Instant::now()
+    }
+
+    fn on_call(
+        &self,
+        method_name: &str,
+        params: Params<'_>,
+        _kind: MethodKind,
+        transport: TransportProtocol,
+    ) {
+        let params_size = params.as_str().map_or(0, str::len);
+        debug!(target: "rpc::request", method = method_name, params_size, ?transport, "RPC call");
+    }
+
+    fn on_result(
+        &self,
+        method_name: &str,
+        success: bool,
+        started_at: Self::Instant,
+        transport: TransportProtocol,
+    ) {
+        debug!(
+            target: "rpc::request",
+            method = method_name,
+            success,
+            elapsed = ?started_at.elapsed(),
+            ?transport,
+            "RPC call finished"
+        );
+    }
+
+    fn on_response(&self, _result: &str, _started_at: Self::Instant, _t: TransportProtocol) {}
+
+    fn on_disconnect(&self, _remote_addr: SocketAddr, _t: TransportProtocol) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::Body;
+    use std::convert::Infallible;
+    use tower::{service_fn, ServiceExt};
+
+    async fn handle(_request: Request<Body>) -> Result<Response<Body>, Infallible> {
+        Ok(Response::new(Body::empty()))
+    }
+
+    #[tokio::test]
+    async fn passes_request_id_through() {
+        let service = RequestTracingLayer.layer(service_fn(handle));
+        let request = Request::builder().header(REQUEST_ID_HEADER, "abc").body(Body::empty());
+        let response = service.clone().oneshot(request.unwrap()).await.unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "abc");
+
+        // a missing or too long id is replaced
+        let first = service.clone().oneshot(Request::new(Body::empty())).await.unwrap();
+        let request = Request::builder()
+            .header(REQUEST_ID_HEADER, "a".repeat(MAX_REQUEST_ID_LEN + 1))
+            .body(Body::empty());
+        let second = service.oneshot(request.unwrap()).await.unwrap();
+        let (first, second) =
+            (&first.headers()[REQUEST_ID_HEADER], &second.headers()[REQUEST_ID_HEADER]);
+        assert!(!first.is_empty() && first.len() <= 16);
+        assert_ne!(first, second);
+    }
+}
@@ -1,22 +1,23 @@
 //! Standalone http tests
 
-use crate::utils::{launch_http, launch_http_ws, launch_ws};
+use crate::utils::{launch_http, launch_http_ws, launch_ws, test_address, test_rpc_builder};
 use jsonrpsee::{
     core::{
         client::{ClientT, SubscriptionClientT},
         error::Error,
     },
+    rpc_params,
     types::error::{CallError, ErrorCode},
 };
 use reth_primitives::{
     hex_literal::hex, Address, BlockId, BlockNumberOrTag, Bytes, NodeRecord, TxHash, H256, H64,
-    U256,
+    U256, U64,
 };
 use reth_rpc_api::{
     clients::{AdminApiClient, EthApiClient},
-    DebugApiClient, NetApiClient, TraceApiClient, Web3ApiClient,
+    DebugApiClient, NetApiClient, OtterscanApiClient, RethApiClient, TraceApiClient, Web3ApiClient,
 };
-use reth_rpc_builder::RethRpcModule;
+use reth_rpc_builder::{RethRpcModule, RpcServerConfig, TransportRpcModuleConfig};
 use reth_rpc_types::{trace::filter::TraceFilter, CallRequest, Index, TransactionRequest};
 use std::collections::HashSet;
 
@@ -30,6 +31,15 @@ fn is_unimplemented(err: Error) -> bool {
     }
 }
 
+fn is_invalid_params(err: Error) -> bool {
+    match err {
+        Error::Call(CallError::Custom(error_obj)) => {
+            error_obj.code() == ErrorCode::InvalidParams.code()
+        }
+        _ => false,
+    }
+}
+
 async fn test_basic_admin_calls<C>(client: &C)
 where
     C: ClientT + SubscriptionClientT + Sync,
@@ -42,6 +52,7 @@ where
     AdminApiClient::add_trusted_peer(client, node).await.unwrap();
     AdminApiClient::remove_trusted_peer(client, node).await.unwrap();
     AdminApiClient::node_info(client).await.unwrap();
+    AdminApiClient::peers(client).await.unwrap();
 }
 
 async fn test_basic_eth_calls<C>(client: &C)
@@ -59,8 +70,12 @@ where
     let tx = Bytes::from(hex!("02f871018303579880850555633d1b82520894eee27662c2b8eba3cd936a23f039f3189633e4c887ad591c62bdaeb180c080a07ea72c68abfb8fca1bd964f0f99132ed9280261bdca3e549546c0205e800f7d0a05b4ef3039e9c9b9babc179a1878fb825b5aaf5aed2fa8744854150157b08d6f3"));
 
     // Implemented
-    EthApiClient::protocol_version(client).await.unwrap();
-    EthApiClient::chain_id(client).await.unwrap();
+    assert_eq!(EthApiClient::protocol_version(client).await.unwrap(), U64::from(67));
+    assert_eq!(EthApiClient::chain_id(client).await.unwrap(), Some(U64::from(1)));
+    let err = EthApiClient::author(client).await.unwrap_err();
+    assert!(
+        matches!(err, Error::Call(CallError::Custom(obj)) if obj.message() == "etherbase not set")
+    );
     EthApiClient::accounts(client).await.unwrap();
     EthApiClient::block_number(client).await.unwrap();
     EthApiClient::get_code(client, address, None).await.unwrap();
@@ -84,13 +99,13 @@ where
         .unwrap_err();
     EthApiClient::create_access_list(client, call_request.clone(), None).await.unwrap();
     EthApiClient::transaction_by_hash(client, tx_hash).await.unwrap();
+    EthApiClient::transaction_receipt(client, tx_hash).await.unwrap();
     EthApiClient::transaction_by_block_hash_and_index(client, hash, index).await.unwrap();
     EthApiClient::transaction_by_block_number_and_index(client, block_number, index).await.unwrap();
+    EthApiClient::max_priority_fee_per_gas(client).await.unwrap();
 
     // Unimplemented
     assert!(is_unimplemented(EthApiClient::syncing(client).await.err().unwrap()));
-    assert!(is_unimplemented(EthApiClient::author(client).await.err().unwrap()));
-    assert!(is_unimplemented(EthApiClient::transaction_receipt(client, hash).await.err().unwrap()));
     assert!(is_unimplemented(
         EthApiClient::call(client, call_request.clone(), None, None).await.err().unwrap()
     ));
@@ -98,7 +113,6 @@ where
         EthApiClient::estimate_gas(client, call_request.clone(), None).await.err().unwrap()
     ));
     assert!(is_unimplemented(EthApiClient::gas_price(client).await.err().unwrap()));
-    assert!(is_unimplemented(EthApiClient::max_priority_fee_per_gas(client).await.err().unwrap()));
     assert!(is_unimplemented(EthApiClient::is_mining(client).await.err().unwrap()));
     assert!(is_unimplemented(EthApiClient::hashrate(client).await.err().unwrap()));
     assert!(is_unimplemented(EthApiClient::get_work(client).await.err().unwrap()));
@@ -146,6 +160,36 @@ where
     NetApiClient::is_listening(client).await.unwrap();
 }
 
+async fn test_basic_ots_calls<C>(client: &C)
+where
+    C: ClientT + SubscriptionClientT + Sync,
+{
+    let address = Address::default();
+
+    assert_eq!(
+        OtterscanApiClient::get_transaction_by_sender_and_nonce(client, address, 0).await.unwrap(),
+        None
+    );
+    assert!(!OtterscanApiClient::has_code(client, address, None).await.unwrap());
+    assert_eq!(OtterscanApiClient::get_block_details(client, 1).await.unwrap(), None);
+    let page =
+        OtterscanApiClient::search_transactions_before(client, address, 0, 10).await.unwrap();
+    assert!(page.txs.is_empty() && page.first_page && page.last_page);
+    let page = OtterscanApiClient::search_transactions_after(client, address, 0, 10).await.unwrap();
+    assert!(page.txs.is_empty() && page.first_page && page.last_page);
+}
+
+async fn test_basic_reth_calls<C>(client: &C)
+where
+    C: ClientT + SubscriptionClientT + Sync,
+{
+    let transactions = vec![Bytes::default(), Bytes::from(hex!("02f871"))];
+    let imported =
+        RethApiClient::import_raw_transactions(client, transactions, None).await.unwrap();
+    assert_eq!(imported.len(), 2);
+    assert!(imported.iter().all(|tx| tx.hash.is_none() && tx.error.is_some()));
+}
+
 async fn test_basic_trace_calls<C>(client: &C)
 where
     C: ClientT + SubscriptionClientT + Sync,
@@ -187,7 +231,8 @@ where
             .err()
             .unwrap()
     ));
-    assert!(is_unimplemented(TraceApiClient::trace_block(client, block_id).await.err().unwrap()));
+    // the default block is the genesis block, which is not executed
+    assert_eq!(TraceApiClient::trace_block(client, block_id).await.unwrap(), None);
     assert!(is_unimplemented(
         TraceApiClient::trace_filter(client, trace_filter).await.err().unwrap()
     ));
@@ -258,6 +303,21 @@ async fn test_call_eth_functions_http_and_ws() {
     test_basic_eth_calls(&client).await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_eth_coinbase_with_fee_recipient() {
+    reth_tracing::init_test_tracing();
+
+    let fee_recipient = Address::from_low_u64_be(0xfee);
+    let handle = test_rpc_builder()
+        .with_fee_recipient(fee_recipient)
+        .build(TransportRpcModuleConfig::set_http(vec![RethRpcModule::Eth]))
+        .start_server(RpcServerConfig::http(Default::default()).with_http_address(test_address()))
+        .await
+        .unwrap();
+    let client = handle.http_client().unwrap();
+    assert_eq!(EthApiClient::author(&client).await.unwrap(), fee_recipient);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_call_debug_functions_http() {
     reth_tracing::init_test_tracing();
@@ -312,6 +372,60 @@ async fn test_call_net_functions_http_and_ws() {
     test_basic_net_calls(&client).await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_ots_functions_http() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_http(vec![RethRpcModule::Ots]).await;
+    let client = handle.http_client().unwrap();
+    test_basic_ots_calls(&client).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_ots_functions_ws() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_ws(vec![RethRpcModule::Ots]).await;
+    let client = handle.ws_client().await.unwrap();
+    test_basic_ots_calls(&client).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_ots_functions_http_and_ws() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_http_ws(vec![RethRpcModule::Ots]).await;
+    let client = handle.http_client().unwrap();
+    test_basic_ots_calls(&client).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_reth_functions_http() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_http(vec![RethRpcModule::Reth]).await;
+    let client = handle.http_client().unwrap();
+    test_basic_reth_calls(&client).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_reth_functions_ws() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_ws(vec![RethRpcModule::Reth]).await;
+    let client = handle.ws_client().await.unwrap();
+    test_basic_reth_calls(&client).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_reth_functions_http_and_ws() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_http_ws(vec![RethRpcModule::Reth]).await;
+    let client = handle.http_client().unwrap();
+    test_basic_reth_calls(&client).await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_call_trace_functions_http() {
     reth_tracing::init_test_tracing();
@@ -365,3 +479,27 @@ async fn test_call_web3_functions_http_and_ws() {
     let client = handle.http_client().unwrap();
     test_basic_web3_calls(&client).await;
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_out_of_range_quantities_http() {
+    reth_tracing::init_test_tracing();
+
+    let modules = vec![RethRpcModule::Eth, RethRpcModule::Debug, RethRpcModule::Trace];
+    let handle = launch_http(modules).await;
+    let client = handle.http_client().unwrap();
+    // one more than `u64::MAX`, must not be truncated to a valid block number, count or index
+    let too_large = "0x10000000000000000";
+    let calls = [
+        ("eth_getBlockByNumber", rpc_params![too_large, false]),
+        ("eth_getBlockTransactionCountByNumber", rpc_params![too_large]),
+        ("eth_getTransactionByBlockNumberAndIndex", rpc_params!["0x1", too_large]),
+        ("eth_feeHistory", rpc_params![too_large, "latest", Vec::<f64>::new()]),
+        ("debug_traceBlockByNumber", rpc_params![too_large]),
+        ("trace_block", rpc_params![too_large]),
+        ("trace_get", rpc_params![H256::zero(), vec![too_large]]),
+    ];
+    for (method, params) in calls {
+        let err = client.request::<serde_json::Value, _>(method, params).await.unwrap_err();
+        assert!(is_invalid_params(err), "{method}");
+    }
+}
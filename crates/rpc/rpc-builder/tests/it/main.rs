@@ -1,4 +1,5 @@
 mod http;
+mod request_tracing;
 pub mod utils;
 
 fn main() {}
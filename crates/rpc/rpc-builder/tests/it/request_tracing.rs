@@ -0,0 +1,69 @@
+//! Tracing of http requests
+
+use crate::utils::launch_http;
+use jsonrpsee::{
+    core::client::ClientT,
+    http_client::{HeaderMap, HeaderValue, HttpClientBuilder},
+    rpc_params,
+};
+use reth_rpc_builder::{request_tracing::REQUEST_ID_HEADER, RethRpcModule};
+use reth_tracing::{
+    tracing::{subscriber, Level},
+    tracing_subscriber::{self, fmt::MakeWriter},
+};
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+/// Collects the formatted logs.
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn http_call_is_logged_in_request_span() {
+    let logs = CapturedLogs::default();
+    let _guard = subscriber::set_default(
+        tracing_subscriber::fmt()
+            .with_max_level(Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(logs.clone())
+            .finish(),
+    );
+
+    let handle = launch_http(vec![RethRpcModule::Web3]).await;
+    let mut headers = HeaderMap::new();
+    headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("test-request"));
+    let url = handle.http_url().unwrap();
+    let client = HttpClientBuilder::default().set_headers(headers).build(url).unwrap();
+    let _: String = client.request("web3_clientVersion", rpc_params![]).await.unwrap();
+
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let call = logs.lines().find(|line| line.contains("RPC call method=")).unwrap();
+    assert!(call.contains("rpc_request{id=test-request}"), "{call}");
+    assert!(call.contains("web3_clientVersion"), "{call}");
+    assert!(call.contains("params_size="), "{call}");
+    let result = logs.lines().find(|line| line.contains("RPC call finished")).unwrap();
+    assert!(result.contains("rpc_request{id=test-request}"), "{result}");
+    assert!(result.contains("success=true"), "{result}");
+    assert!(result.contains("elapsed="), "{result}");
+}
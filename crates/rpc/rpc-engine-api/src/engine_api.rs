@@ -1,6 +1,10 @@
-use crate::{message::EngineApiMessageVersion, EngineApiError, EngineApiMessage, EngineApiResult};
+use crate::{
+    event::EngineApiEventListeners, message::EngineApiMessageVersion, EngineApiError,
+    EngineApiEvent, EngineApiMessage, EngineApiMessages, EngineApiResult, PayloadValidator,
+    PermissivePayloadValidator,
+};
 use futures::StreamExt;
-use reth_interfaces::consensus::ForkchoiceState;
+use reth_interfaces::{consensus::ForkchoiceState, executor::Error as ExecError};
 use reth_primitives::{
     proofs::{self, EMPTY_LIST_HASH},
     BlockHash, BlockId, BlockNumber, ChainSpec, Hardfork, Header, SealedBlock, TransactionSigned,
@@ -21,11 +25,9 @@ use std::{
     sync::Arc,
     task::{ready, Context, Poll},
 };
-use tokio::sync::{mpsc, oneshot, watch};
+use tokio::sync::{oneshot, watch};
 use tokio_stream::wrappers::UnboundedReceiverStream;
-
-/// The Engine API handle.
-pub type EngineApiHandle = mpsc::UnboundedSender<EngineApiMessage>;
+use tracing::warn;
 
 /// The Engine API response sender.
 pub type EngineApiSender<Ok> = oneshot::Sender<EngineApiResult<Ok>>;
@@ -40,8 +42,12 @@ pub struct EngineApi<Client> {
     client: Client,
     /// Consensus configuration
     chain_spec: Arc<ChainSpec>,
-    message_rx: UnboundedReceiverStream<EngineApiMessage>,
+    message_rx: EngineApiMessages,
     forkchoice_state_tx: watch::Sender<ForkchoiceState>,
+    /// Checks of the payload attributes and the built payloads
+    payload_validator: Arc<dyn PayloadValidator>,
+    /// Listeners of the events of the Engine API
+    listeners: EngineApiEventListeners,
     // TODO: Placeholder for storing future blocks. Make cache bounded. Use lru
     // local_store: HashMap<H64, ExecutionPayload>,
     // remote_store: HashMap<H64, ExecutionPayload>,
@@ -54,21 +60,40 @@ impl<Client: HeaderProvider + BlockProvider + StateProviderFactory + EvmEnvProvi
     pub fn new(
         client: Client,
         chain_spec: Arc<ChainSpec>,
-        message_rx: mpsc::UnboundedReceiver<EngineApiMessage>,
+        message_rx: EngineApiMessages,
         forkchoice_state_tx: watch::Sender<ForkchoiceState>,
     ) -> Self {
         Self {
             client,
             chain_spec,
-            message_rx: UnboundedReceiverStream::new(message_rx),
+            message_rx,
             forkchoice_state_tx,
+            payload_validator: Arc::new(PermissivePayloadValidator),
+            listeners: EngineApiEventListeners::default(),
         }
     }
 
+    /// Configures the checks of the payload attributes and the built payloads.
+    ///
+    /// By default every payload is accepted, see [PermissivePayloadValidator].
+    pub fn with_payload_validator(mut self, payload_validator: Arc<dyn PayloadValidator>) -> Self {
+        self.payload_validator = payload_validator;
+        self
+    }
+
+    /// Listen for events of the Engine API.
+    pub fn events(&mut self) -> UnboundedReceiverStream<EngineApiEvent> {
+        self.listeners.new_listener()
+    }
+
     fn on_message(&mut self, msg: EngineApiMessage) {
         match msg {
             EngineApiMessage::GetPayload(payload_id, tx) => {
-                let _ = tx.send(self.get_payload(payload_id).ok_or(EngineApiError::PayloadUnknown));
+                let payload = self.get_payload(payload_id).ok_or(EngineApiError::PayloadUnknown);
+                let _ = tx.send(payload.and_then(|payload| {
+                    self.payload_validator.validate_payload(&payload)?;
+                    Ok(payload)
+                }));
             }
             EngineApiMessage::GetPayloadBodiesByHash(hashes, tx) => {
                 let _ = tx.send(self.get_payload_bodies_by_hash(hashes));
@@ -104,6 +129,9 @@ impl<Client: HeaderProvider + BlockProvider + StateProviderFactory + EvmEnvProvi
             EngineApiMessage::ExchangeTransitionConfiguration(config, tx) => {
                 let _ = tx.send(self.exchange_transition_configuration(config));
             }
+            EngineApiMessage::ResetForkchoiceState(number, tx) => {
+                let _ = tx.send(self.reset_forkchoice_state(number));
+            }
         }
     }
 
@@ -149,9 +177,10 @@ impl<Client: HeaderProvider + BlockProvider + StateProviderFactory + EvmEnvProvi
             return Err(EngineApiError::PayloadExtraData(payload.extra_data))
         }
 
-        if payload.base_fee_per_gas == U256::ZERO {
-            return Err(EngineApiError::PayloadBaseFee(payload.base_fee_per_gas))
-        }
+        let base_fee_per_gas = match u64::try_from(payload.base_fee_per_gas) {
+            Ok(base_fee) if base_fee > 0 => base_fee,
+            _ => return Err(EngineApiError::PayloadBaseFee(payload.base_fee_per_gas)),
+        };
 
         let transactions = payload
             .transactions
@@ -176,7 +205,7 @@ impl<Client: HeaderProvider + BlockProvider + StateProviderFactory + EvmEnvProvi
             gas_used: payload.gas_used.as_u64(),
             timestamp: payload.timestamp.as_u64(),
             mix_hash: payload.prev_randao,
-            base_fee_per_gas: Some(payload.base_fee_per_gas.to::<u64>()),
+            base_fee_per_gas: Some(base_fee_per_gas),
             extra_data: payload.extra_data,
             // Defaults
             ommers_hash: EMPTY_LIST_HASH,
@@ -327,10 +356,23 @@ impl<Client: HeaderProvider + BlockProvider + StateProviderFactory + EvmEnvProvi
         let mut executor = factory.with_sp(&state_provider);
         match executor.execute_and_verify_receipt(&block.unseal(), total_difficulty, None) {
             Ok(_) => Ok(PayloadStatus::new(PayloadStatusEnum::Valid, block_hash)),
-            Err(err) => Ok(PayloadStatus::new(
-                PayloadStatusEnum::Invalid { validation_error: err.to_string() },
-                parent_hash, // The parent hash is already in our database hence it is valid
-            )),
+            Err(err) => {
+                if let ExecError::TransactionError { tx_index, tx_hash, source } = &err {
+                    warn!(target: "rpc::engine_api", ?block_hash, tx_index, ?tx_hash, %source, "Invalid transaction in payload");
+                } else {
+                    warn!(target: "rpc::engine_api", ?block_hash, %err, "Invalid payload");
+                }
+                let validation_error = err.to_string();
+                self.listeners.notify(EngineApiEvent::InvalidBlock {
+                    block_hash,
+                    parent_hash,
+                    error: err,
+                });
+                Ok(PayloadStatus::new(
+                    PayloadStatusEnum::Invalid { validation_error },
+                    parent_hash, // The parent hash is already in our database hence it is valid
+                ))
+            }
         }
     }
 
@@ -418,7 +460,10 @@ impl<Client: HeaderProvider + BlockProvider + StateProviderFactory + EvmEnvProvi
             tracing::error!(target: "rpc::engine_api", ?error, "Failed to update forkchoice state");
         }
 
-        if let Some(_attr) = payload_attributes {
+        if let Some(attr) = payload_attributes {
+            // From the Engine API spec: the forkchoice state is not rolled back if the payload
+            // attributes are invalid.
+            self.payload_validator.validate_attributes(&attr)?;
             // TODO: optionally build the block
         }
 
@@ -427,6 +472,53 @@ impl<Client: HeaderProvider + BlockProvider + StateProviderFactory + EvmEnvProvi
             .with_latest_valid_hash(chain_info.best_hash))
     }
 
+    /// Resets the forkchoice state to the canonical block with the given number, after the chain
+    /// was unwound to it.
+    ///
+    /// The safe and finalized blocks are kept if they are still canonical, otherwise they are
+    /// moved to the new head.
+    pub fn reset_forkchoice_state(&self, number: BlockNumber) -> EngineApiResult<ForkchoiceState> {
+        let head_block_hash = self.client.block_hash(U256::from(number))?.ok_or_else(|| {
+            EngineApiError::Internal(
+                reth_interfaces::provider::ProviderError::CanonicalHeader { block_number: number }
+                    .into(),
+            )
+        })?;
+
+        let is_canonical = |hash: H256| -> EngineApiResult<bool> {
+            if hash.is_zero() {
+                return Ok(true)
+            }
+            Ok(match self.client.block_number(hash)? {
+                Some(block_number) => {
+                    block_number <= number &&
+                        self.client.block_hash(U256::from(block_number))? == Some(hash)
+                }
+                None => false,
+            })
+        };
+
+        let current = self.forkchoice_state_tx.borrow().clone();
+        let state = ForkchoiceState {
+            head_block_hash,
+            safe_block_hash: if is_canonical(current.safe_block_hash)? {
+                current.safe_block_hash
+            } else {
+                head_block_hash
+            },
+            finalized_block_hash: if is_canonical(current.finalized_block_hash)? {
+                current.finalized_block_hash
+            } else {
+                head_block_hash
+            },
+        };
+
+        if let Err(error) = self.forkchoice_state_tx.send(state.clone()) {
+            tracing::error!(target: "rpc::engine_api", ?error, "Failed to reset forkchoice state");
+        }
+        Ok(state)
+    }
+
     /// Called to verify network configuration parameters and ensure that Consensus and Execution
     /// layers are using the latest configuration.
     pub fn exchange_transition_configuration(
@@ -502,26 +594,26 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{engine_api_channel, EngineApiHandle};
     use assert_matches::assert_matches;
     use reth_interfaces::test_utils::generators::random_block;
     use reth_primitives::{H256, MAINNET};
     use reth_provider::test_utils::MockEthProvider;
     use std::sync::Arc;
-    use tokio::sync::{
-        mpsc::{unbounded_channel, UnboundedSender},
-        watch::Receiver as WatchReceiver,
-    };
+    use tokio::sync::watch::Receiver as WatchReceiver;
 
     fn setup_engine_api() -> (EngineApiTestHandle, EngineApi<Arc<MockEthProvider>>) {
         let chain_spec = Arc::new(MAINNET.clone());
         let client = Arc::new(MockEthProvider::default());
-        let (msg_tx, msg_rx) = unbounded_channel();
+        let (msg_tx, msg_rx) = engine_api_channel(Default::default());
         let (forkchoice_state_tx, forkchoice_state_rx) = watch::channel(ForkchoiceState::default());
         let api = EngineApi {
             client: client.clone(),
             chain_spec: chain_spec.clone(),
-            message_rx: UnboundedReceiverStream::new(msg_rx),
+            message_rx: msg_rx,
             forkchoice_state_tx,
+            payload_validator: Arc::new(PermissivePayloadValidator),
+            listeners: EngineApiEventListeners::default(),
         };
         let handle = EngineApiTestHandle { chain_spec, client, msg_tx, forkchoice_state_rx };
         (handle, api)
@@ -530,13 +622,13 @@ mod tests {
     struct EngineApiTestHandle {
         chain_spec: Arc<ChainSpec>,
         client: Arc<MockEthProvider>,
-        msg_tx: UnboundedSender<EngineApiMessage>,
+        msg_tx: EngineApiHandle,
         forkchoice_state_rx: WatchReceiver<ForkchoiceState>,
     }
 
     impl EngineApiTestHandle {
         fn send_message(&self, msg: EngineApiMessage) {
-            self.msg_tx.send(msg).expect("failed to send engine msg");
+            self.msg_tx.send(msg);
         }
 
         fn forkchoice_state(&self) -> ForkchoiceState {
@@ -553,7 +645,7 @@ mod tests {
         use reth_interfaces::test_utils::generators::random_header;
         use reth_primitives::{
             bytes::{Bytes, BytesMut},
-            Block,
+            Block, Signature,
         };
         use reth_rlp::DecodeError;
 
@@ -564,7 +656,7 @@ mod tests {
             transformed.header.transactions_root =
                 proofs::calculate_transaction_root(transformed.body.iter());
             transformed.header.ommers_hash =
-                proofs::calculate_ommers_root(transformed.ommers.iter());
+                proofs::calculate_ommers_hash(transformed.ommers.iter());
             SealedBlock {
                 header: transformed.header.seal_slow(),
                 body: transformed.body,
@@ -607,6 +699,14 @@ mod tests {
                 Err(EngineApiError::PayloadBaseFee(val)) if val == U256::ZERO
             );
 
+            // Base fee exceeding 64 bits
+            let mut payload_with_large_base_fee: ExecutionPayload = block.clone().into();
+            payload_with_large_base_fee.base_fee_per_gas = U256::from(u64::MAX) + U256::from(1);
+            assert_matches!(
+                api.try_construct_block(payload_with_large_base_fee),
+                Err(EngineApiError::PayloadBaseFee(val)) if val > U256::from(u64::MAX)
+            );
+
             // Invalid encoded transactions
             let mut payload_with_invalid_txs: ExecutionPayload = block.clone().into();
             payload_with_invalid_txs.transactions.iter_mut().for_each(|tx| {
@@ -760,6 +860,59 @@ mod tests {
             assert_matches!( result_rx.await, Ok(Ok(result)) => assert_eq!(result, expected_result));
         }
 
+        #[tokio::test]
+        async fn invalid_transaction_signature() {
+            let (handle, mut api) = setup_engine_api();
+            let mut events = api.events();
+            tokio::spawn(api);
+
+            let parent = transform_block(random_block(100, None, None, Some(0)), |mut b| {
+                b.header.difficulty =
+                    handle.chain_spec.fork(Hardfork::Paris).ttd().unwrap() + U256::from(1);
+                b
+            });
+            // the third transaction has a signature whose signer can't be recovered
+            let block = random_block(101, Some(parent.hash()), Some(3), Some(0));
+            let block = transform_block(block, |mut b| {
+                b.header.timestamp = parent.timestamp + 12;
+                let tx = b.body[2].clone();
+                let signature = Signature { r: U256::ZERO, ..tx.signature };
+                b.body[2] =
+                    TransactionSigned::from_transaction_and_signature(tx.transaction, signature);
+                b
+            });
+            let tx_hash = block.body[2].hash();
+
+            handle.client.add_block(parent.hash(), parent.clone().unseal());
+
+            let (result_tx, result_rx) = oneshot::channel();
+            handle.send_message(EngineApiMessage::NewPayload(
+                EngineApiMessageVersion::V1,
+                block.clone().into(),
+                result_tx,
+            ));
+
+            let expected_error = ExecError::TransactionError {
+                tx_index: 2,
+                tx_hash,
+                source: Box::new(ExecError::SenderRecoveryError),
+            };
+            let expected_result = PayloadStatus::new(
+                PayloadStatusEnum::Invalid { validation_error: expected_error.to_string() },
+                parent.hash(),
+            );
+            assert_matches!(result_rx.await, Ok(Ok(result)) => assert_eq!(result, expected_result));
+
+            assert_eq!(
+                events.next().await,
+                Some(EngineApiEvent::InvalidBlock {
+                    block_hash: block.hash(),
+                    parent_hash: parent.hash(),
+                    error: expected_error,
+                })
+            );
+        }
+
         // TODO: add execution tests
     }
 
@@ -902,7 +1055,9 @@ mod tests {
 
     mod fork_choice_updated {
         use super::*;
+        use crate::{PayloadPolicyError, StrictPayloadValidator};
         use reth_interfaces::test_utils::generators::random_header;
+        use reth_primitives::{Address, U64};
 
         #[tokio::test]
         async fn empty_head() {
@@ -1015,6 +1170,55 @@ mod tests {
             assert_eq!(handle.forkchoice_state(), state);
         }
 
+        #[tokio::test]
+        async fn payload_attributes_policy() {
+            let fee_recipient = Address::random();
+            let attributes = PayloadAttributes {
+                timestamp: U64::from(1),
+                prev_randao: H256::random(),
+                suggested_fee_recipient: fee_recipient,
+                withdrawals: None,
+            };
+            let strict = StrictPayloadValidator {
+                allowed_fee_recipients: vec![Address::random()],
+                ..Default::default()
+            };
+
+            let rejected = EngineApiError::InvalidPayloadAttributes(
+                PayloadPolicyError::FeeRecipientNotAllowed(fee_recipient),
+            );
+            for (validator, expected) in [
+                (Arc::new(PermissivePayloadValidator) as Arc<dyn PayloadValidator>, None),
+                (Arc::new(strict), Some(rejected)),
+            ] {
+                let (handle, api) = setup_engine_api();
+                tokio::spawn(api.with_payload_validator(validator));
+
+                let ttd = handle.chain_spec.fork(Hardfork::Paris).ttd().unwrap();
+                let mut head = random_header(100, None).unseal();
+                head.difficulty = ttd;
+                let head = head.seal_slow();
+                handle.client.add_header(head.hash(), head.clone().unseal());
+
+                let state = ForkchoiceState { head_block_hash: head.hash(), ..Default::default() };
+                let (result_tx, result_rx) = oneshot::channel();
+                handle.send_message(EngineApiMessage::ForkchoiceUpdated(
+                    EngineApiMessageVersion::V1,
+                    state.clone(),
+                    Some(attributes.clone()),
+                    result_tx,
+                ));
+
+                let result = result_rx.await.unwrap();
+                match expected {
+                    Some(err) => assert_eq!(result, Err(err)),
+                    None => assert_matches!(result, Ok(_)),
+                }
+                // the forkchoice state is applied either way
+                assert_eq!(handle.forkchoice_state(), state);
+            }
+        }
+
         #[tokio::test]
         async fn forkchoice_updated_invalid_pow() {
             let (handle, api) = setup_engine_api();
@@ -1168,4 +1372,118 @@ mod tests {
             assert_matches!(result_rx.await, Ok(Ok(config)) => assert_eq!(config, transition_config));
         }
     }
+
+    mod message_queue {
+        use super::*;
+        use crate::EngineApiConfig;
+        use futures::FutureExt;
+
+        #[tokio::test]
+        async fn bounded_and_latest_forkchoice_update_wins() {
+            let config = EngineApiConfig { max_queued_messages: 16 };
+            let (handle, mut messages) = engine_api_channel(config);
+
+            let block = random_block(100, Some(H256::random()), None, Some(0));
+            let payload: ExecutionPayload = block.into();
+
+            let mut payload_results = Vec::new();
+            let mut forkchoice_results = Vec::new();
+            for i in 0..10_000 {
+                let (result_tx, result_rx) = oneshot::channel();
+                handle.send(EngineApiMessage::NewPayload(
+                    EngineApiMessageVersion::V1,
+                    payload.clone(),
+                    result_tx,
+                ));
+                payload_results.push(result_rx);
+
+                if i % 100 == 0 {
+                    let state = ForkchoiceState {
+                        head_block_hash: H256::from_low_u64_be(i),
+                        ..Default::default()
+                    };
+                    let (result_tx, result_rx) = oneshot::channel();
+                    handle.send(EngineApiMessage::ForkchoiceUpdated(
+                        EngineApiMessageVersion::V1,
+                        state,
+                        None,
+                        result_tx,
+                    ));
+                    forkchoice_results.push(result_rx);
+                }
+            }
+            assert_eq!(handle.queued_messages(), 16);
+
+            // everything beyond the bound is answered right away
+            let syncing = PayloadStatus::from_status(PayloadStatusEnum::Syncing);
+            for result_rx in payload_results.split_off(16) {
+                assert_matches!(result_rx.await, Ok(Ok(result)) => assert_eq!(result, syncing));
+            }
+            // the latest forkchoice update is still pending
+            forkchoice_results.pop();
+            for result_rx in forkchoice_results {
+                assert_matches!(
+                    result_rx.await,
+                    Ok(Ok(result)) => assert_eq!(result, ForkchoiceUpdated::new(syncing.clone()))
+                );
+            }
+
+            for _ in 0..16 {
+                assert_matches!(
+                    messages.next().now_or_never(),
+                    Some(Some(EngineApiMessage::NewPayload(..)))
+                );
+            }
+            assert_matches!(
+                messages.next().now_or_never(),
+                Some(Some(EngineApiMessage::ForkchoiceUpdated(_, state, _, _))) => {
+                    assert_eq!(state.head_block_hash, H256::from_low_u64_be(9_900))
+                }
+            );
+            assert!(messages.next().now_or_never().is_none());
+            assert_eq!(handle.queued_messages(), 0);
+        }
+
+        #[tokio::test]
+        async fn messages_keep_arrival_order() {
+            let (handle, mut messages) = engine_api_channel(Default::default());
+            let payload: ExecutionPayload = random_block(100, None, None, Some(0)).into();
+
+            let new_payload = |parent_hash| {
+                let mut payload = payload.clone();
+                payload.parent_hash = parent_hash;
+                let (result_tx, _) = oneshot::channel();
+                EngineApiMessage::NewPayload(EngineApiMessageVersion::V1, payload, result_tx)
+            };
+            let forkchoice_updated = |head_block_hash| {
+                let state = ForkchoiceState { head_block_hash, ..Default::default() };
+                let (result_tx, _) = oneshot::channel();
+                EngineApiMessage::ForkchoiceUpdated(
+                    EngineApiMessageVersion::V1,
+                    state,
+                    None,
+                    result_tx,
+                )
+            };
+
+            // the forkchoice update is processed between the payloads it was sent between
+            handle.send(new_payload(H256::from_low_u64_be(1)));
+            handle.send(forkchoice_updated(H256::from_low_u64_be(2)));
+            handle.send(new_payload(H256::from_low_u64_be(3)));
+            // only the latest forkchoice update is kept, at its own position
+            handle.send(forkchoice_updated(H256::from_low_u64_be(4)));
+            handle.send(new_payload(H256::from_low_u64_be(5)));
+            handle.send(forkchoice_updated(H256::from_low_u64_be(6)));
+
+            let mut order = Vec::new();
+            while let Some(Some(msg)) = messages.next().now_or_never() {
+                order.push(match msg {
+                    EngineApiMessage::NewPayload(_, payload, _) => payload.parent_hash,
+                    EngineApiMessage::ForkchoiceUpdated(_, state, _, _) => state.head_block_hash,
+                    _ => unreachable!(),
+                });
+            }
+            assert_eq!(order, [1, 2, 3, 5, 6].map(H256::from_low_u64_be));
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use crate::PayloadPolicyError;
 use reth_primitives::{Bytes, H256, U256};
 use thiserror::Error;
 
@@ -6,6 +7,8 @@ pub type EngineApiResult<Ok> = Result<Ok, EngineApiError>;
 
 /// Payload unknown error code.
 pub const UNKNOWN_PAYLOAD_CODE: i32 = -38001;
+/// Invalid payload attributes error code.
+pub const INVALID_PAYLOAD_ATTRIBUTES_CODE: i32 = -38003;
 /// Request too large error code.
 pub const REQUEST_TOO_LARGE_CODE: i32 = -38004;
 
@@ -52,9 +55,15 @@ pub enum EngineApiError {
         /// The length that was requested.
         len: u64,
     },
+    /// The payload attributes or the built payload violate the payload policy.
+    #[error("Invalid payload attributes: {0}")]
+    InvalidPayloadAttributes(#[from] PayloadPolicyError),
     /// The params are invalid.
     #[error("Invalid params")]
     InvalidParams,
+    /// Too many messages are waiting to be processed by the engine.
+    #[error("Engine API message queue is full")]
+    MessageQueueFull,
     /// Terminal total difficulty mismatch during transition configuration exchange.
     #[error(
         "Invalid transition terminal total difficulty. Execution: {execution}. Consensus: {consensus}"
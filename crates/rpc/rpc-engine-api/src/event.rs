@@ -0,0 +1,44 @@
+use reth_interfaces::executor::Error as ExecError;
+use reth_primitives::H256;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+/// An event emitted by the [EngineApi][crate::EngineApi].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum EngineApiEvent {
+    /// Emitted when the execution of a new payload failed.
+    InvalidBlock {
+        /// The hash of the invalid block.
+        block_hash: H256,
+        /// The hash of the parent of the invalid block.
+        parent_hash: H256,
+        /// The reason the block is invalid.
+        ///
+        /// If a transaction of the block is invalid, this is an
+        /// [ExecError::TransactionError] with the index and the hash of the transaction.
+        error: ExecError,
+    },
+}
+
+/// Bundles all listeners for [`EngineApiEvent`]s
+#[derive(Default, Clone, Debug)]
+pub(crate) struct EngineApiEventListeners {
+    /// All listeners for events
+    listeners: Vec<mpsc::UnboundedSender<EngineApiEvent>>,
+}
+
+impl EngineApiEventListeners {
+    /// Send an event to all listeners.
+    ///
+    /// Channels that were closed are removed.
+    pub(crate) fn notify(&mut self, event: EngineApiEvent) {
+        self.listeners.retain(|listener| listener.send(event.clone()).is_ok())
+    }
+
+    /// Add a new event listener.
+    pub(crate) fn new_listener(&mut self) -> UnboundedReceiverStream<EngineApiEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.listeners.push(sender);
+        UnboundedReceiverStream::new(receiver)
+    }
+}
@@ -17,6 +17,24 @@ mod message;
 /// Engine API error.
 mod error;
 
-pub use engine_api::{EngineApi, EngineApiHandle, EngineApiSender};
+/// Events emitted by the Engine API.
+mod event;
+
+/// Checks of the payloads built by the node.
+mod payload;
+
+/// The bounded queue of Engine API messages.
+mod queue;
+
+pub use engine_api::{EngineApi, EngineApiSender};
 pub use error::*;
+pub use event::EngineApiEvent;
 pub use message::{EngineApiMessage, EngineApiMessageVersion};
+pub use payload::{
+    PayloadPolicyError, PayloadValidator, PermissivePayloadValidator, StrictPayloadValidator,
+    MAX_EXTRA_DATA_SIZE,
+};
+pub use queue::{
+    engine_api_channel, EngineApiConfig, EngineApiHandle, EngineApiMessages,
+    DEFAULT_MAX_QUEUED_MESSAGES,
+};
@@ -29,6 +29,8 @@ pub enum EngineApiMessage {
         TransitionConfiguration,
         EngineApiSender<TransitionConfiguration>,
     ),
+    /// Reset the forkchoice state to a canonical block after an unwind
+    ResetForkchoiceState(BlockNumber, EngineApiSender<ForkchoiceState>),
 }
 
 /// The version of Engine API message.
@@ -0,0 +1,174 @@
+use reth_primitives::{Address, Bytes};
+use reth_rpc_types::engine::{ExecutionPayload, PayloadAttributes};
+use thiserror::Error;
+
+/// The max size of the extra data of a block.
+pub const MAX_EXTRA_DATA_SIZE: usize = 32;
+
+/// A violation of the payload policy of a [PayloadValidator].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PayloadPolicyError {
+    /// The fee recipient is not one of the allowed fee recipients.
+    #[error("Fee recipient {0:?} is not allowed")]
+    FeeRecipientNotAllowed(Address),
+    /// The fee recipient is the zero address.
+    #[error("Zero fee recipient is not allowed")]
+    ZeroFeeRecipient,
+    /// The extra data is larger than allowed.
+    #[error("Extra data of {len} bytes exceeds max of {max} bytes")]
+    ExtraDataTooLarge {
+        /// The size of the extra data.
+        len: usize,
+        /// The max allowed size.
+        max: usize,
+    },
+}
+
+/// Chain or operator specific checks of the payloads built by the node.
+///
+/// The payload attributes of `engine_forkchoiceUpdated` are checked before a payload is built for
+/// them, locally built payloads before they are handed out by `engine_getPayload`. Violations are
+/// answered with [INVALID_PAYLOAD_ATTRIBUTES_CODE](crate::INVALID_PAYLOAD_ATTRIBUTES_CODE).
+pub trait PayloadValidator: Send + Sync {
+    /// Checks the payload attributes sent by the consensus layer.
+    fn validate_attributes(&self, attributes: &PayloadAttributes)
+        -> Result<(), PayloadPolicyError>;
+
+    /// Checks a payload built by the node.
+    fn validate_payload(&self, payload: &ExecutionPayload) -> Result<(), PayloadPolicyError>;
+}
+
+/// A [PayloadValidator] that accepts everything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PermissivePayloadValidator;
+
+impl PayloadValidator for PermissivePayloadValidator {
+    fn validate_attributes(&self, _: &PayloadAttributes) -> Result<(), PayloadPolicyError> {
+        Ok(())
+    }
+
+    fn validate_payload(&self, _: &ExecutionPayload) -> Result<(), PayloadPolicyError> {
+        Ok(())
+    }
+}
+
+/// A [PayloadValidator] that enforces the fee recipient and extra data of the payloads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrictPayloadValidator {
+    /// The fee recipients payloads may be built for, any fee recipient if empty.
+    pub allowed_fee_recipients: Vec<Address>,
+    /// Max size of the extra data of built payloads.
+    ///
+    /// Default is [MAX_EXTRA_DATA_SIZE]
+    pub max_extra_data: usize,
+    /// Whether payloads for the zero address are rejected.
+    pub forbid_zero_fee_recipient: bool,
+}
+
+impl Default for StrictPayloadValidator {
+    fn default() -> Self {
+        Self {
+            allowed_fee_recipients: Vec::new(),
+            max_extra_data: MAX_EXTRA_DATA_SIZE,
+            forbid_zero_fee_recipient: true,
+        }
+    }
+}
+
+impl StrictPayloadValidator {
+    fn validate_fee_recipient(&self, fee_recipient: Address) -> Result<(), PayloadPolicyError> {
+        if self.forbid_zero_fee_recipient && fee_recipient.is_zero() {
+            return Err(PayloadPolicyError::ZeroFeeRecipient)
+        }
+        if !self.allowed_fee_recipients.is_empty() &&
+            !self.allowed_fee_recipients.contains(&fee_recipient)
+        {
+            return Err(PayloadPolicyError::FeeRecipientNotAllowed(fee_recipient))
+        }
+        Ok(())
+    }
+
+    fn validate_extra_data(&self, extra_data: &Bytes) -> Result<(), PayloadPolicyError> {
+        if extra_data.len() > self.max_extra_data {
+            return Err(PayloadPolicyError::ExtraDataTooLarge {
+                len: extra_data.len(),
+                max: self.max_extra_data,
+            })
+        }
+        Ok(())
+    }
+}
+
+impl PayloadValidator for StrictPayloadValidator {
+    fn validate_attributes(
+        &self,
+        attributes: &PayloadAttributes,
+    ) -> Result<(), PayloadPolicyError> {
+        self.validate_fee_recipient(attributes.suggested_fee_recipient)
+    }
+
+    fn validate_payload(&self, payload: &ExecutionPayload) -> Result<(), PayloadPolicyError> {
+        self.validate_fee_recipient(payload.fee_recipient)?;
+        self.validate_extra_data(&payload.extra_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Header, SealedBlock, H256, U64};
+
+    fn attributes(suggested_fee_recipient: Address) -> PayloadAttributes {
+        PayloadAttributes {
+            timestamp: U64::from(1),
+            prev_randao: H256::zero(),
+            suggested_fee_recipient,
+            withdrawals: None,
+        }
+    }
+
+    #[test]
+    fn strict_rejects_what_permissive_accepts() {
+        let allowed = Address::from_low_u64_be(1);
+        let other = Address::from_low_u64_be(2);
+        let strict =
+            StrictPayloadValidator { allowed_fee_recipients: vec![allowed], ..Default::default() };
+
+        for fee_recipient in [allowed, other, Address::zero()] {
+            let attributes = attributes(fee_recipient);
+            assert_eq!(PermissivePayloadValidator.validate_attributes(&attributes), Ok(()));
+        }
+        assert_eq!(strict.validate_attributes(&attributes(allowed)), Ok(()));
+        assert_eq!(
+            strict.validate_attributes(&attributes(other)),
+            Err(PayloadPolicyError::FeeRecipientNotAllowed(other))
+        );
+        assert_eq!(
+            strict.validate_attributes(&attributes(Address::zero())),
+            Err(PayloadPolicyError::ZeroFeeRecipient)
+        );
+    }
+
+    #[test]
+    fn strict_caps_extra_data() {
+        let strict = StrictPayloadValidator { max_extra_data: 4, ..Default::default() };
+        let header = Header {
+            beneficiary: Address::from_low_u64_be(1),
+            extra_data: Bytes::from(vec![0; 4]),
+            ..Default::default()
+        };
+        let mut payload = ExecutionPayload::from(SealedBlock {
+            header: header.seal_slow(),
+            ..Default::default()
+        });
+        assert_eq!(strict.validate_payload(&payload), Ok(()));
+        assert_eq!(PermissivePayloadValidator.validate_payload(&payload), Ok(()));
+
+        payload.extra_data = Bytes::from(vec![0; 5]);
+        assert_eq!(
+            strict.validate_payload(&payload),
+            Err(PayloadPolicyError::ExtraDataTooLarge { len: 5, max: 4 })
+        );
+        assert_eq!(PermissivePayloadValidator.validate_payload(&payload), Ok(()));
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{EngineApiError, EngineApiMessage};
+use futures::{task::AtomicWaker, Stream};
+use metrics::{Counter, Gauge};
+use parking_lot::Mutex;
+use reth_metrics_derive::Metrics;
+use reth_rpc_types::engine::{ForkchoiceUpdated, PayloadStatus, PayloadStatusEnum};
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// The default number of messages that can be queued in front of the
+/// [`EngineApi`][crate::EngineApi], see [EngineApiConfig].
+pub const DEFAULT_MAX_QUEUED_MESSAGES: usize = 1024;
+
+/// Settings for the queue of messages to the [`EngineApi`][crate::EngineApi].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EngineApiConfig {
+    /// Max number of messages that are queued until the engine processes them.
+    ///
+    /// `engine_newPayload` messages beyond the bound are answered with `SYNCING` right away,
+    /// all other messages beyond the bound fail. Forkchoice updates don't count towards the
+    /// bound since only the latest one is kept.
+    ///
+    /// Default is [DEFAULT_MAX_QUEUED_MESSAGES]
+    pub max_queued_messages: usize,
+}
+
+impl Default for EngineApiConfig {
+    fn default() -> Self {
+        Self { max_queued_messages: DEFAULT_MAX_QUEUED_MESSAGES }
+    }
+}
+
+/// Creates the bounded queue of messages to the [`EngineApi`][crate::EngineApi].
+///
+/// # Panics
+///
+/// If the configured bound is zero.
+pub fn engine_api_channel(config: EngineApiConfig) -> (EngineApiHandle, EngineApiMessages) {
+    let (to_engine, messages) = mpsc::channel(config.max_queued_messages);
+    let shared = Arc::new(Shared::default());
+    (
+        EngineApiHandle { to_engine, shared: shared.clone() },
+        EngineApiMessages { messages, next: None, shared },
+    )
+}
+
+/// The Engine API handle that queues messages for the [`EngineApi`][crate::EngineApi].
+#[derive(Clone)]
+pub struct EngineApiHandle {
+    to_engine: mpsc::Sender<(u64, EngineApiMessage)>,
+    shared: Arc<Shared>,
+}
+
+impl EngineApiHandle {
+    /// Queues the message for the engine.
+    ///
+    /// A forkchoice update replaces the forkchoice update that is still pending, which is answered
+    /// with `SYNCING`. If the queue is full, a new payload is answered with `SYNCING` and any
+    /// other message with [EngineApiError::MessageQueueFull].
+    ///
+    /// If the engine is gone, the message is dropped together with its response channel.
+    pub fn send(&self, msg: EngineApiMessage) {
+        // messages are numbered under the lock, so the engine receives them in this order
+        let mut pending = self.shared.pending.lock();
+        let sequence = pending.next_sequence;
+        pending.next_sequence += 1;
+
+        if let EngineApiMessage::ForkchoiceUpdated(..) = msg {
+            let replaced = pending.forkchoice_update.replace((sequence, msg));
+            drop(pending);
+            if let Some((_, replaced)) = replaced {
+                self.shared.metrics.replaced_forkchoice_updates.increment(1);
+                reject(replaced);
+            }
+            self.shared.waker.wake();
+            return
+        }
+
+        self.shared.increment_queued();
+        let sent = self.to_engine.try_send((sequence, msg));
+        drop(pending);
+        match sent {
+            Ok(()) => {}
+            Err(TrySendError::Full((_, msg))) => {
+                self.shared.decrement_queued();
+                self.shared.metrics.rejected_messages.increment(1);
+                reject(msg);
+            }
+            Err(TrySendError::Closed(_)) => self.shared.decrement_queued(),
+        }
+    }
+
+    /// Returns the number of queued messages, not counting the pending forkchoice update.
+    pub fn queued_messages(&self) -> usize {
+        self.shared.queued.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for EngineApiHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EngineApiHandle")
+            .field("queued_messages", &self.queued_messages())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The stream of messages the [`EngineApi`][crate::EngineApi] processes.
+///
+/// Messages are yielded in the order they were sent. A forkchoice update that replaced a pending
+/// one is yielded at its own position.
+pub struct EngineApiMessages {
+    messages: mpsc::Receiver<(u64, EngineApiMessage)>,
+    /// The next message of the channel, held back while the pending forkchoice update was sent
+    /// before it.
+    next: Option<(u64, EngineApiMessage)>,
+    shared: Arc<Shared>,
+}
+
+impl Stream for EngineApiMessages {
+    type Item = EngineApiMessage;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.shared.waker.register(cx.waker());
+
+        let mut closed = false;
+        if this.next.is_none() {
+            match this.messages.poll_recv(cx) {
+                Poll::Ready(Some(msg)) => this.next = Some(msg),
+                Poll::Ready(None) => closed = true,
+                Poll::Pending => {}
+            }
+        }
+
+        {
+            let mut pending = this.shared.pending.lock();
+            let next_sequence = this.next.as_ref().map(|(sequence, _)| *sequence);
+            let forkchoice_update_first = match (&pending.forkchoice_update, next_sequence) {
+                (Some((sequence, _)), Some(next_sequence)) => *sequence < next_sequence,
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+            if forkchoice_update_first {
+                return Poll::Ready(pending.forkchoice_update.take().map(|(_, msg)| msg))
+            }
+        }
+
+        if let Some((_, msg)) = this.next.take() {
+            this.shared.decrement_queued();
+            return Poll::Ready(Some(msg))
+        }
+
+        if closed {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl fmt::Debug for EngineApiMessages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EngineApiMessages").finish_non_exhaustive()
+    }
+}
+
+/// State shared by the handles and the receiving end of the queue.
+#[derive(Default)]
+struct Shared {
+    /// The order of the messages and the pending forkchoice update.
+    pending: Mutex<PendingMessages>,
+    /// Wakes the engine when a forkchoice update is pending.
+    waker: AtomicWaker,
+    /// Number of messages in the channel.
+    queued: AtomicUsize,
+    metrics: EngineApiQueueMetrics,
+}
+
+/// Messages of the handles that are waiting for the engine outside of the channel.
+#[derive(Default)]
+struct PendingMessages {
+    /// The sequence number of the next message.
+    next_sequence: u64,
+    /// The latest forkchoice update that wasn't processed yet, with its sequence number.
+    forkchoice_update: Option<(u64, EngineApiMessage)>,
+}
+
+impl Shared {
+    fn increment_queued(&self) {
+        let queued = self.queued.fetch_add(1, Ordering::Relaxed) + 1;
+        self.metrics.queued_messages.set(queued as f64);
+    }
+
+    fn decrement_queued(&self) {
+        let queued = self.queued.fetch_sub(1, Ordering::Relaxed) - 1;
+        self.metrics.queued_messages.set(queued as f64);
+    }
+}
+
+/// Answers a message that is not processed by the engine.
+fn reject(msg: EngineApiMessage) {
+    let syncing = || PayloadStatus::from_status(PayloadStatusEnum::Syncing);
+    match msg {
+        EngineApiMessage::NewPayload(_, _, tx) => {
+            let _ = tx.send(Ok(syncing()));
+        }
+        EngineApiMessage::ForkchoiceUpdated(_, _, _, tx) => {
+            let _ = tx.send(Ok(ForkchoiceUpdated::new(syncing())));
+        }
+        EngineApiMessage::GetPayload(_, tx) => {
+            let _ = tx.send(Err(EngineApiError::MessageQueueFull));
+        }
+        EngineApiMessage::GetPayloadBodiesByRange(_, _, tx) |
+        EngineApiMessage::GetPayloadBodiesByHash(_, tx) => {
+            let _ = tx.send(Err(EngineApiError::MessageQueueFull));
+        }
+        EngineApiMessage::ExchangeTransitionConfiguration(_, tx) => {
+            let _ = tx.send(Err(EngineApiError::MessageQueueFull));
+        }
+        EngineApiMessage::ResetForkchoiceState(_, tx) => {
+            let _ = tx.send(Err(EngineApiError::MessageQueueFull));
+        }
+    }
+}
+
+/// Metrics of the queue of Engine API messages.
+#[derive(Metrics)]
+#[metrics(scope = "engine_api")]
+struct EngineApiQueueMetrics {
+    /// Number of messages waiting to be processed by the engine
+    queued_messages: Gauge,
+    /// Number of messages answered without processing because the queue was full
+    rejected_messages: Counter,
+    /// Number of forkchoice updates replaced by a newer forkchoice update before processing
+    replaced_forkchoice_updates: Counter,
+}
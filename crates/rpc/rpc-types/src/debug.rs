@@ -0,0 +1,25 @@
+//! Types of the `debug` namespace.
+
+use reth_primitives::{Bytes, H256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Response of `debug_executionWitness`.
+///
+/// Everything that is needed to execute a block without access to the state: the block can be
+/// re-executed on the state revealed by the trie nodes, and the state root after the execution can
+/// be computed from the same nodes.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionWitness {
+    /// The RLP encoded trie nodes of the state of the parent block, that reveal the accounts and
+    /// storage slots read and written by the block.
+    pub state: Vec<Bytes>,
+    /// The contract codes that were executed.
+    pub codes: Vec<Bytes>,
+    /// The preimages of the hashed trie keys: the addresses of the accounts and the keys of the
+    /// storage slots that were read.
+    pub keys: Vec<Bytes>,
+    /// The hashes of the blocks that were read with the `BLOCKHASH` opcode, keyed by number.
+    pub block_hashes: BTreeMap<u64, H256>,
+}
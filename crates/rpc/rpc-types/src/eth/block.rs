@@ -61,23 +61,16 @@ pub enum BlockError {
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Block {
-    /// Header of the block
+    /// Header of the block, with the total difficulty and the size of the whole block.
+    ///
+    /// The total difficulty is `None` only if representing an Uncle block.
     #[serde(flatten)]
     pub header: Header,
-    /// Total difficulty, this field is None only if representing
-    /// an Uncle block.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub total_difficulty: Option<U256>,
     /// Uncles' hashes
     pub uncles: Vec<H256>,
     /// Transactions
     #[serde(skip_serializing_if = "BlockTransactions::is_uncle")]
     pub transactions: BlockTransactions,
-    /// Integer the size of this block in bytes.
-    pub size: Option<U256>,
-    /// Base Fee for post-EIP1559 blocks.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub base_fee_per_gas: Option<U256>,
     /// Withdrawals
     #[serde(skip_serializing_if = "Option::is_none")]
     pub withdrawals: Option<Vec<Withdrawal>>,
@@ -144,7 +137,7 @@ impl Block {
                 signed_tx,
                 block_hash,
                 block_number,
-                U256::from(idx),
+                idx,
             ))
         }
 
@@ -164,36 +157,27 @@ impl Block {
     ) -> Self {
         let block_length = block.length();
         let uncles = block.ommers.into_iter().map(|h| h.hash_slow()).collect();
-        let base_fee_per_gas = block.header.base_fee_per_gas;
 
-        let header = Header::from_primitive_with_hash(block.header, block_hash);
+        let mut header = Header::from_primitive_with_hash(block.header, block_hash);
+        header.total_difficulty = Some(total_difficulty);
+        header.size = Some(U256::from(block_length));
 
-        Self {
-            header,
-            uncles,
-            transactions,
-            base_fee_per_gas: base_fee_per_gas.map(U256::from),
-            total_difficulty: Some(total_difficulty),
-            size: Some(U256::from(block_length)),
-            withdrawals: block.withdrawals,
-        }
+        Self { header, uncles, transactions, withdrawals: block.withdrawals }
     }
 
     /// Build an RPC block response representing
     /// an Uncle from its header.
     pub fn uncle_block_from_header(header: PrimitiveHeader) -> Self {
         let hash = header.hash_slow();
-        let rpc_header = Header::from_primitive_with_hash(header.clone(), hash);
+        let mut rpc_header = Header::from_primitive_with_hash(header.clone(), hash);
         let uncle_block = PrimitiveBlock { header, ..Default::default() };
-        let size = Some(U256::from(uncle_block.length()));
+        rpc_header.size = Some(U256::from(uncle_block.length()));
+        rpc_header.base_fee_per_gas = None;
         Self {
             uncles: vec![],
             header: rpc_header,
             transactions: BlockTransactions::Uncle,
-            base_fee_per_gas: None,
             withdrawals: None,
-            size,
-            total_difficulty: None,
         }
     }
 }
@@ -210,6 +194,9 @@ pub struct Header {
     #[serde(rename = "sha3Uncles")]
     pub uncles_hash: H256,
     /// Authors address
+    ///
+    /// Not returned by geth, in which case it is the zero address.
+    #[serde(default)]
     pub author: Address,
     /// Alias of `author`
     pub miner: Address,
@@ -240,6 +227,15 @@ pub struct Header {
     pub mix_hash: H256,
     /// Nonce
     pub nonce: Option<H64>,
+    /// Base Fee for post-EIP1559 blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<U256>,
+    /// Total difficulty of the chain up to this block, `null` after the merge when it is not
+    /// tracked for new headers.
+    #[serde(default)]
+    pub total_difficulty: Option<U256>,
+    /// Integer the size of this header in bytes, or of the whole block if part of a [Block].
+    pub size: Option<U256>,
 }
 
 // === impl Header ===
@@ -247,6 +243,8 @@ pub struct Header {
 impl Header {
     /// Converts the primitive header type to this RPC type
     ///
+    /// The total difficulty and the size are left empty.
+    ///
     /// CAUTION: this takes the header's hash as is and does _not_ calculate the hash.
     pub fn from_primitive_with_hash(primitive_header: PrimitiveHeader, block_hash: H256) -> Self {
         let PrimitiveHeader {
@@ -264,7 +262,7 @@ impl Header {
             timestamp,
             mix_hash,
             nonce,
-            base_fee_per_gas: _,
+            base_fee_per_gas,
             extra_data,
             withdrawals_root,
         } = primitive_header;
@@ -288,6 +286,9 @@ impl Header {
             difficulty,
             mix_hash,
             nonce: Some(nonce.to_be_bytes().into()),
+            base_fee_per_gas: base_fee_per_gas.map(U256::from),
+            total_difficulty: None,
+            size: None,
         }
     }
 }
@@ -385,20 +386,40 @@ mod tests {
                 difficulty: U256::from(13),
                 mix_hash: H256::from_low_u64_be(14),
                 nonce: Some(H64::from_low_u64_be(15)),
+                base_fee_per_gas: Some(U256::from(20)),
+                total_difficulty: Some(U256::from(100000)),
+                size: Some(U256::from(19)),
             },
-            total_difficulty: Some(U256::from(100000)),
             uncles: vec![H256::from_low_u64_be(17)],
             transactions: BlockTransactions::Hashes(vec![H256::from_low_u64_be(18)]),
-            size: Some(U256::from(19)),
-            base_fee_per_gas: Some(U256::from(20)),
             withdrawals: None,
         };
         let serialized = serde_json::to_string(&block).unwrap();
         assert_eq!(
             serialized,
-            r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000002","sha3Uncles":"0x0000000000000000000000000000000000000000000000000000000000000003","author":"0x0000000000000000000000000000000000000004","miner":"0x0000000000000000000000000000000000000004","stateRoot":"0x0000000000000000000000000000000000000000000000000000000000000005","transactionsRoot":"0x0000000000000000000000000000000000000000000000000000000000000006","receiptsRoot":"0x0000000000000000000000000000000000000000000000000000000000000007","withdrawalsRoot":"0x0000000000000000000000000000000000000000000000000000000000000008","number":"0x9","gasUsed":"0xa","gasLimit":"0xb","extraData":"0x010203","logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","timestamp":"0xc","difficulty":"0xd","mixHash":"0x000000000000000000000000000000000000000000000000000000000000000e","nonce":"0x000000000000000f","totalDifficulty":"0x186a0","uncles":["0x0000000000000000000000000000000000000000000000000000000000000011"],"transactions":["0x0000000000000000000000000000000000000000000000000000000000000012"],"size":"0x13","baseFeePerGas":"0x14"}"#
+            r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000002","sha3Uncles":"0x0000000000000000000000000000000000000000000000000000000000000003","author":"0x0000000000000000000000000000000000000004","miner":"0x0000000000000000000000000000000000000004","stateRoot":"0x0000000000000000000000000000000000000000000000000000000000000005","transactionsRoot":"0x0000000000000000000000000000000000000000000000000000000000000006","receiptsRoot":"0x0000000000000000000000000000000000000000000000000000000000000007","withdrawalsRoot":"0x0000000000000000000000000000000000000000000000000000000000000008","number":"0x9","gasUsed":"0xa","gasLimit":"0xb","extraData":"0x010203","logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","timestamp":"0xc","difficulty":"0xd","mixHash":"0x000000000000000000000000000000000000000000000000000000000000000e","nonce":"0x000000000000000f","baseFeePerGas":"0x14","totalDifficulty":"0x186a0","size":"0x13","uncles":["0x0000000000000000000000000000000000000000000000000000000000000011"],"transactions":["0x0000000000000000000000000000000000000000000000000000000000000012"]}"#
         );
         let deserialized: Block = serde_json::from_str(&serialized).unwrap();
         assert_eq!(block, deserialized);
     }
+
+    #[test]
+    fn serde_post_merge_header() {
+        let primitive = PrimitiveHeader {
+            number: 1,
+            base_fee_per_gas: Some(7),
+            withdrawals_root: Some(H256::from_low_u64_be(1)),
+            ..Default::default()
+        };
+        let mut header = Header::from_primitive_with_hash(primitive.clone(), H256::zero());
+        header.size = Some(U256::from(primitive.length()));
+
+        let value = serde_json::to_value(&header).unwrap();
+        assert_eq!(value["baseFeePerGas"], "0x7");
+        assert_eq!(value["withdrawalsRoot"], serde_json::json!(H256::from_low_u64_be(1)));
+        assert_eq!(value["size"], serde_json::json!(U256::from(primitive.length())));
+        // the total difficulty is not tracked for post-merge headers
+        assert_eq!(value.get("totalDifficulty"), Some(&serde_json::Value::Null));
+        assert_eq!(serde_json::from_value::<Header>(value).unwrap(), header);
+    }
 }
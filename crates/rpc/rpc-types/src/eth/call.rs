@@ -1,4 +1,5 @@
-use reth_primitives::{AccessList, Address, Bytes, U128, U256, U64};
+use crate::AccessList;
+use reth_primitives::{Address, Bytes, U128, U256, U64};
 use serde::{Deserialize, Serialize};
 
 /// Call request
@@ -84,7 +84,8 @@ impl From<SealedBlock> for ExecutionPayload {
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExecutionPayloadBody {
     pub transactions: Vec<Bytes>,
-    pub withdrawals: Vec<Withdrawal>,
+    /// The withdrawals of the block, `null` for blocks before Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
 }
 
 impl From<Block> for ExecutionPayloadBody {
@@ -96,7 +97,7 @@ impl From<Block> for ExecutionPayloadBody {
         });
         ExecutionPayloadBody {
             transactions: transactions.collect(),
-            withdrawals: value.withdrawals.unwrap_or_default(),
+            withdrawals: value.withdrawals,
         }
     }
 }
@@ -229,7 +230,7 @@ mod tests {
                     .collect::<Result<Vec<_>, _>>(),
             );
 
-            assert_eq!(block.withdrawals.unwrap_or_default(), payload_body.withdrawals);
+            assert_eq!(block.withdrawals, payload_body.withdrawals);
         }
     }
 }
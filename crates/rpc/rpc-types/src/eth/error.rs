@@ -9,6 +9,12 @@ pub enum EthRpcErrorCode {
     ExecutionError,
     /// <https://eips.ethereum.org/EIPS/eip-1898>
     InvalidInput,
+    /// The requested resource is not available, for example blocks below the history of the node,
+    /// see <https://eips.ethereum.org/EIPS/eip-1474>
+    ResourceNotFound,
+    /// The requested resource is temporarily unavailable and the request can be retried later,
+    /// see <https://eips.ethereum.org/EIPS/eip-1474>
+    ResourceUnavailable,
 }
 
 impl EthRpcErrorCode {
@@ -18,6 +24,8 @@ impl EthRpcErrorCode {
             EthRpcErrorCode::TransactionRejected => -32003,
             EthRpcErrorCode::ExecutionError => 3,
             EthRpcErrorCode::InvalidInput => -32000,
+            EthRpcErrorCode::ResourceNotFound => -32001,
+            EthRpcErrorCode::ResourceUnavailable => -32002,
         }
     }
 }
@@ -1,4 +1,4 @@
-use reth_primitives::{Address, Bytes, H256, U256};
+use reth_primitives::{Address, Bytes, H256, U64};
 use serde::{Deserialize, Serialize};
 
 /// Ethereum Log emitted by a transaction
@@ -14,15 +14,15 @@ pub struct Log {
     /// Hash of the block the transaction that emitted this log was mined in
     pub block_hash: Option<H256>,
     /// Number of the block the transaction that emitted this log was mined in
-    pub block_number: Option<U256>,
+    pub block_number: Option<U64>,
     /// Transaction Hash
     pub transaction_hash: Option<H256>,
     /// Index of the Transaction in the block
-    pub transaction_index: Option<U256>,
+    pub transaction_index: Option<U64>,
     /// Log Index in Block
-    pub log_index: Option<U256>,
+    pub log_index: Option<U64>,
     /// Log Index in Transaction
-    pub transaction_log_index: Option<U256>,
+    pub transaction_log_index: Option<U64>,
     /// Geth Compatibility Field: whether this log was removed
     #[serde(default)]
     pub removed: bool,
@@ -39,11 +39,11 @@ mod tests {
             topics: vec![H256::from_low_u64_be(0x1234)],
             data: Bytes::from(vec![0x12, 0x34]),
             block_hash: Some(H256::from_low_u64_be(0x1234)),
-            block_number: Some(U256::from(0x1234)),
+            block_number: Some(U64::from(0x1234)),
             transaction_hash: Some(H256::from_low_u64_be(0x1234)),
-            transaction_index: Some(U256::from(0x1234)),
-            log_index: Some(U256::from(0x1234)),
-            transaction_log_index: Some(U256::from(0x1234)),
+            transaction_index: Some(U64::from(0x1234)),
+            log_index: Some(U64::from(0x1234)),
+            transaction_log_index: Some(U64::from(0x1234)),
             removed: false,
         };
         let serialized = serde_json::to_string(&log).unwrap();
@@ -19,7 +19,7 @@ mod work;
 pub use account::*;
 pub use block::*;
 pub use call::CallRequest;
-pub use fee::{FeeHistory, FeeHistoryCache, FeeHistoryCacheItem};
+pub use fee::FeeHistory;
 pub use filter::*;
 pub use index::Index;
 pub use log::Log;
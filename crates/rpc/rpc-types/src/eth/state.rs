@@ -1,4 +1,4 @@
-//! bindings for state overrides in eth_call
+//! bindings for state and block overrides in eth_call
 
 use reth_primitives::{Address, Bytes, H256, U256};
 use serde::{Deserialize, Serialize};
@@ -18,3 +18,19 @@ pub struct AccountOverride {
     pub state: Option<HashMap<H256, H256>>,
     pub state_diff: Option<HashMap<H256, H256>>,
 }
+
+/// Overrides of the block environment a call is executed in.
+///
+/// The hashes returned by `BLOCKHASH` follow an overridden block number.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+#[allow(missing_docs)]
+pub struct BlockOverrides {
+    pub number: Option<U256>,
+    pub difficulty: Option<U256>,
+    pub time: Option<U256>,
+    pub gas_limit: Option<U256>,
+    pub coinbase: Option<Address>,
+    pub random: Option<H256>,
+    pub base_fee: Option<U256>,
+}
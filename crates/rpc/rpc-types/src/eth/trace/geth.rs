@@ -1,5 +1,6 @@
 #![allow(missing_docs)]
 /// Geth tracing types
+use crate::state::{BlockOverrides, StateOverride};
 use reth_primitives::{Bytes, JsonU256, H256, U256};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -7,6 +8,19 @@ use std::collections::BTreeMap;
 // re-exported for geth tracing types
 pub use ethers_core::types::{GethDebugTracingOptions, GethTraceFrame};
 
+/// Options of `debug_traceCall`: the tracing options together with the overrides of the state
+/// and of the block the call is executed in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethDebugTracingCallOptions {
+    #[serde(flatten)]
+    pub tracing_options: GethDebugTracingOptions,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_overrides: Option<StateOverride>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_overrides: Option<BlockOverrides>,
+}
+
 /// Result type for geth style transaction trace
 pub type TraceResult = crate::trace::common::TraceResult<serde_json::Value, String>;
 
@@ -31,6 +45,9 @@ pub struct DefaultFrame {
     pub failed: bool,
     pub gas: JsonU256,
     pub return_value: Bytes,
+    /// The refund counter at the end of the transaction, before the refund is capped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_refund: Option<u64>,
     pub struct_logs: Vec<StructLog>,
 }
 
@@ -0,0 +1,120 @@
+//! Access list related RPC values
+use reth_primitives::{
+    AccessList as PrimitiveAccessList, AccessListItem as PrimitiveAccessListItem, Address, H256,
+    U256,
+};
+use serde::{Deserialize, Serialize};
+
+/// A single account of an [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930) access list, with
+/// the storage keys of the account the transaction plans to access.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+    /// Account address that would be loaded at the start of execution
+    pub address: Address,
+    /// Keys of storage that would be loaded at the start of execution
+    ///
+    /// The snake case name is accepted for requests that were built against the previous
+    /// serialization of access lists in requests.
+    #[serde(alias = "storage_keys")]
+    pub storage_keys: Vec<H256>,
+}
+
+impl From<PrimitiveAccessListItem> for AccessListItem {
+    fn from(item: PrimitiveAccessListItem) -> Self {
+        Self { address: item.address, storage_keys: item.storage_keys }
+    }
+}
+
+impl From<AccessListItem> for PrimitiveAccessListItem {
+    fn from(item: AccessListItem) -> Self {
+        Self { address: item.address, storage_keys: item.storage_keys }
+    }
+}
+
+/// An [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930) access list, serialized as a list of
+/// [AccessListItem]s.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct AccessList(pub Vec<AccessListItem>);
+
+impl From<PrimitiveAccessList> for AccessList {
+    fn from(list: PrimitiveAccessList) -> Self {
+        Self(list.0.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<AccessList> for PrimitiveAccessList {
+    fn from(list: AccessList) -> Self {
+        Self(list.0.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Access list with the gas used by the transaction that uses it, returned by
+/// `eth_createAccessList`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListWithGasUsed {
+    /// List with accounts accessed during transaction.
+    pub access_list: AccessList,
+    /// Estimated gas used with access list.
+    pub gas_used: U256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::rpc::transaction::eip2930::{
+        AccessList as LegacyAccessList, AccessListItem as LegacyAccessListItem,
+    };
+
+    fn access_list() -> AccessList {
+        AccessList(vec![
+            AccessListItem {
+                address: Address::from_low_u64_be(1),
+                storage_keys: vec![H256::from_low_u64_be(2), H256::from_low_u64_be(3)],
+            },
+            AccessListItem { address: Address::from_low_u64_be(4), storage_keys: vec![] },
+        ])
+    }
+
+    #[test]
+    fn serde_access_list_matches_legacy() {
+        let access_list = access_list();
+        let legacy = LegacyAccessList(
+            access_list
+                .0
+                .iter()
+                .map(|item| LegacyAccessListItem {
+                    address: item.address.0.into(),
+                    storage_keys: item.storage_keys.iter().map(|key| key.0.into()).collect(),
+                })
+                .collect(),
+        );
+
+        let serialized = serde_json::to_string(&access_list).unwrap();
+        assert_eq!(serialized, serde_json::to_string(&legacy).unwrap());
+        assert_eq!(
+            serialized,
+            r#"[{"address":"0x0000000000000000000000000000000000000001","storageKeys":["0x0000000000000000000000000000000000000000000000000000000000000002","0x0000000000000000000000000000000000000000000000000000000000000003"]},{"address":"0x0000000000000000000000000000000000000004","storageKeys":[]}]"#
+        );
+        let deserialized: AccessList = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(access_list, deserialized);
+    }
+
+    #[test]
+    fn deserialize_snake_case_storage_keys() {
+        let item: AccessListItem = serde_json::from_str(
+            r#"{"address":"0x0000000000000000000000000000000000000001","storage_keys":["0x0000000000000000000000000000000000000000000000000000000000000002"]}"#,
+        )
+        .unwrap();
+        assert_eq!(item.storage_keys, vec![H256::from_low_u64_be(2)]);
+    }
+
+    #[test]
+    fn primitive_access_list_roundtrip() {
+        let access_list = access_list();
+        let primitive = PrimitiveAccessList::from(access_list.clone());
+        assert_eq!(primitive.0[0].storage_keys, access_list.0[0].storage_keys);
+        assert_eq!(AccessList::from(primitive), access_list);
+    }
+}
@@ -1,17 +1,18 @@
+mod access_list;
 mod receipt;
 mod request;
 mod signature;
 mod typed;
 
+pub use access_list::{AccessList, AccessListItem, AccessListWithGasUsed};
 pub use receipt::TransactionReceipt;
-pub use request::TransactionRequest;
+pub use request::{TransactionInputError, TransactionRequest};
 pub use signature::Signature;
 pub use typed::*;
 
 use reth_primitives::{
-    rpc::transaction::eip2930::AccessListItem, Address, BlockNumber, Bytes,
-    Transaction as PrimitiveTransaction, TransactionKind, TransactionSignedEcRecovered, TxType,
-    H256, U128, U256, U64,
+    contract::create_address, Address, BlockNumber, Bytes, Transaction as PrimitiveTransaction,
+    TransactionKind, TransactionSignedEcRecovered, TxType, H256, U128, U256, U64,
 };
 use serde::{Deserialize, Serialize};
 
@@ -26,9 +27,9 @@ pub struct Transaction {
     /// Block hash
     pub block_hash: Option<H256>,
     /// Block number
-    pub block_number: Option<U256>,
+    pub block_number: Option<U64>,
     /// Transaction Index
-    pub transaction_index: Option<U256>,
+    pub transaction_index: Option<U64>,
     /// Sender
     pub from: Address,
     /// Recipient
@@ -59,7 +60,7 @@ pub struct Transaction {
     ///
     /// Pre-pay to warm storage access.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub access_list: Option<Vec<AccessListItem>>,
+    pub access_list: Option<AccessList>,
     /// EIP2718
     ///
     /// Transaction type, Some(2) for EIP-1559 transaction,
@@ -78,12 +79,12 @@ impl Transaction {
         tx: TransactionSignedEcRecovered,
         block_hash: H256,
         block_number: BlockNumber,
-        tx_index: U256,
+        tx_index: usize,
     ) -> Self {
         let mut tx = Self::from_recovered(tx);
         tx.block_hash = Some(block_hash);
-        tx.block_number = Some(U256::from(block_number));
-        tx.transaction_index = Some(tx_index);
+        tx.block_number = Some(U64::from(block_number));
+        tx.transaction_index = Some(U64::from(tx_index));
         tx
     }
 
@@ -102,31 +103,16 @@ impl Transaction {
             TxType::Legacy => (Some(U128::from(signed_tx.max_fee_per_gas())), None),
             TxType::EIP2930 => (None, Some(U128::from(signed_tx.max_fee_per_gas()))),
             TxType::EIP1559 => (None, Some(U128::from(signed_tx.max_fee_per_gas()))),
+            // deposit transactions don't pay for gas on L2
+            #[cfg(feature = "optimism")]
+            TxType::DEPOSIT => (None, None),
         };
 
         let chain_id = signed_tx.chain_id().map(U64::from);
         let access_list = match &signed_tx.transaction {
             PrimitiveTransaction::Legacy(_) => None,
-            PrimitiveTransaction::Eip2930(tx) => Some(
-                tx.access_list
-                    .0
-                    .iter()
-                    .map(|item| AccessListItem {
-                        address: item.address.0.into(),
-                        storage_keys: item.storage_keys.iter().map(|key| key.0.into()).collect(),
-                    })
-                    .collect(),
-            ),
-            PrimitiveTransaction::Eip1559(tx) => Some(
-                tx.access_list
-                    .0
-                    .iter()
-                    .map(|item| AccessListItem {
-                        address: item.address.0.into(),
-                        storage_keys: item.storage_keys.iter().map(|key| key.0.into()).collect(),
-                    })
-                    .collect(),
-            ),
+            PrimitiveTransaction::Eip2930(tx) => Some(tx.access_list.clone().into()),
+            PrimitiveTransaction::Eip1559(tx) => Some(tx.access_list.clone().into()),
         };
 
         Self {
@@ -152,6 +138,27 @@ impl Transaction {
             transaction_type: Some(U64::from(signed_tx.tx_type() as u8)),
         }
     }
+
+    /// Returns the kind of the transaction: a contract creation if it has no recipient, otherwise
+    /// a call, which includes calls to the zero address.
+    pub fn kind(&self) -> TransactionKind {
+        match self.to {
+            Some(to) => TransactionKind::Call(to),
+            None => TransactionKind::Create,
+        }
+    }
+
+    /// Returns the address of the contract deployed by the transaction, derived from the sender
+    /// and nonce, or `None` if the transaction is not a contract creation or its nonce exceeds 64
+    /// bits.
+    pub fn contract_address(&self) -> Option<Address> {
+        match self.kind() {
+            TransactionKind::Create => {
+                u64::try_from(self.nonce).ok().map(|nonce| create_address(self.from, nonce))
+            }
+            TransactionKind::Call(_) => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -164,8 +171,8 @@ mod tests {
             hash: H256::from_low_u64_be(1),
             nonce: U256::from(2),
             block_hash: Some(H256::from_low_u64_be(3)),
-            block_number: Some(U256::from(4)),
-            transaction_index: Some(U256::from(5)),
+            block_number: Some(U64::from(4)),
+            transaction_index: Some(U64::from(5)),
             from: Address::from_low_u64_be(6),
             to: Some(Address::from_low_u64_be(7)),
             value: U256::from(8),
@@ -187,4 +194,17 @@ mod tests {
         let deserialized: Transaction = serde_json::from_str(&serialized).unwrap();
         assert_eq!(transaction, deserialized);
     }
+
+    #[test]
+    fn contract_address_of_create() {
+        let from = Address::from_low_u64_be(6);
+        let create = Transaction { from, nonce: U256::from(2), to: None, ..Default::default() };
+        assert_eq!(create.kind(), TransactionKind::Create);
+        assert_eq!(create.contract_address(), Some(create_address(from, 2)));
+
+        // a call to the zero address is not a creation
+        let call = Transaction { to: Some(Address::zero()), ..create };
+        assert_eq!(call.kind(), TransactionKind::Call(Address::zero()));
+        assert_eq!(call.contract_address(), None);
+    }
 }
@@ -8,11 +8,11 @@ pub struct TransactionReceipt {
     /// Transaction Hash.
     pub transaction_hash: Option<H256>,
     /// Index within the block.
-    pub transaction_index: Option<U256>,
+    pub transaction_index: Option<U64>,
     /// Hash of the block this transaction was included within.
     pub block_hash: Option<H256>,
     /// Number of the block this transaction was included within.
-    pub block_number: Option<U256>,
+    pub block_number: Option<U64>,
     /// Address of the sender
     pub from: Address,
     /// Address of the receiver. null when its a contract creation transaction.
@@ -42,4 +42,150 @@ pub struct TransactionReceipt {
     /// EIP-2718 Transaction type, Some(1) for AccessList transaction, None for Legacy
     #[serde(rename = "type")]
     pub transaction_type: U256,
+    /// The nonce of the deposit transaction, only set for the receipts of deposit transactions
+    /// since Regolith.
+    #[cfg(feature = "optimism")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deposit_nonce: Option<U64>,
+    /// The version of the receipt of the deposit transaction, only set for the receipts of
+    /// deposit transactions since Canyon.
+    #[cfg(feature = "optimism")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deposit_receipt_version: Option<U64>,
+    /// The fee the transaction paid for posting its data to L1, not set for deposit
+    /// transactions.
+    #[cfg(feature = "optimism")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_fee: Option<U256>,
+    /// The base fee of the L1 origin of the block of the transaction.
+    #[cfg(feature = "optimism")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_gas_price: Option<U256>,
+    /// The L1 gas the data of the transaction used.
+    #[cfg(feature = "optimism")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_gas_used: Option<U256>,
+    /// The scalar of the L1 fee as a decimal, e.g. `0.684`, only set before Ecotone.
+    #[cfg(feature = "optimism")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_fee_scalar: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_transaction_receipt() {
+        let receipt = TransactionReceipt {
+            transaction_hash: Some(H256::from_low_u64_be(1)),
+            transaction_index: Some(U64::from(2)),
+            block_hash: Some(H256::from_low_u64_be(3)),
+            block_number: Some(U64::from(u64::MAX)),
+            from: Address::from_low_u64_be(5),
+            to: Some(Address::from_low_u64_be(6)),
+            cumulative_gas_used: U256::from(21_000),
+            gas_used: Some(U256::from(21_000)),
+            contract_address: None,
+            logs: vec![],
+            state_root: None,
+            logs_bloom: Bloom::default(),
+            status_code: Some(U64::from(1)),
+            effective_gas_price: U128::from(7),
+            transaction_type: U256::from(2),
+            #[cfg(feature = "optimism")]
+            deposit_nonce: None,
+            #[cfg(feature = "optimism")]
+            deposit_receipt_version: None,
+            #[cfg(feature = "optimism")]
+            l1_fee: None,
+            #[cfg(feature = "optimism")]
+            l1_gas_price: None,
+            #[cfg(feature = "optimism")]
+            l1_gas_used: None,
+            #[cfg(feature = "optimism")]
+            l1_fee_scalar: None,
+        };
+        let serialized = serde_json::to_string(&receipt).unwrap();
+        assert_eq!(
+            serialized,
+            r#{"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000001","transactionIndex":"0x2","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000003","blockNumber":"0xffffffffffffffff","from":"0x0000000000000000000000000000000000000005","to":"0x0000000000000000000000000000000000000006","cumulativeGasUsed":"0x5208","gasUsed":"0x5208","contractAddress":null,"logs":[],"logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","status":"0x1","effectiveGasPrice":"0x7","type":"0x2"}"#
+        );
+        let deserialized: TransactionReceipt = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(receipt, deserialized);
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn serde_deposit_transaction_receipt() {
+        // the receipt of an L1 attributes deposit transaction, as returned by op-geth
+        let json = serde_json::json!({
+            "type": "0x7e",
+            "status": "0x1",
+            "cumulativeGasUsed": "0xb741",
+            "logs": [],
+            "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+            "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "contractAddress": null,
+            "gasUsed": "0xb741",
+            "effectiveGasPrice": "0x0",
+            "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+            "blockNumber": "0x7014a1b",
+            "transactionIndex": "0x0",
+            "from": "0xdeaddeaddeaddeaddeaddeaddeaddeaddead0001",
+            "to": "0x4200000000000000000000000000000000000015",
+            "depositNonce": "0x6e5cbc2",
+            "depositReceiptVersion": "0x1"
+        });
+
+        let receipt: TransactionReceipt = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(receipt.transaction_type, U256::from(0x7E));
+        assert_eq!(receipt.deposit_nonce, Some(U64::from(0x6e5cbc2)));
+        assert_eq!(receipt.deposit_receipt_version, Some(U64::from(1)));
+        assert_eq!(serde_json::to_value(&receipt).unwrap(), json);
+
+        // before Canyon there is no receipt version, and before Regolith no deposit nonce
+        let mut json = json;
+        json.as_object_mut().unwrap().remove("depositReceiptVersion");
+        let receipt: TransactionReceipt = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(receipt.deposit_receipt_version, None);
+        assert_eq!(serde_json::to_value(&receipt).unwrap(), json);
+        json.as_object_mut().unwrap().remove("depositNonce");
+        let receipt: TransactionReceipt = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(receipt.deposit_nonce, None);
+        assert_eq!(serde_json::to_value(&receipt).unwrap(), json);
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn serde_l1_fee_transaction_receipt() {
+        // the L1 fee fields of the receipt of a user transaction, in the shape op-geth returns
+        let json = serde_json::json!({
+            "type": "0x2",
+            "status": "0x1",
+            "cumulativeGasUsed": "0x1a9d8",
+            "logs": [],
+            "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+            "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "contractAddress": null,
+            "gasUsed": "0x5208",
+            "effectiveGasPrice": "0xb2d05e32",
+            "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+            "blockNumber": "0x7014a1b",
+            "transactionIndex": "0x1",
+            "from": "0x0000000000000000000000000000000000000005",
+            "to": "0x0000000000000000000000000000000000000006",
+            "l1Fee": "0x244f76ac00",
+            "l1GasPrice": "0x3b9aca00",
+            "l1GasUsed": "0xe4",
+            "l1FeeScalar": "0.684"
+        });
+
+        let receipt: TransactionReceipt = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(receipt.l1_fee, Some(U256::from(155_952_000_000u64)));
+        assert_eq!(receipt.l1_gas_price, Some(U256::from(1_000_000_000)));
+        assert_eq!(receipt.l1_gas_used, Some(U256::from(228)));
+        assert_eq!(receipt.l1_fee_scalar.as_deref(), Some("0.684"));
+        assert_eq!(serde_json::to_value(&receipt).unwrap(), json);
+    }
 }
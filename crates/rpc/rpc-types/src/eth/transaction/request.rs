@@ -1,13 +1,18 @@
-use crate::eth::transaction::typed::{
-    EIP1559TransactionRequest, EIP2930TransactionRequest, LegacyTransactionRequest,
-    TransactionKind, TypedTransactionRequest,
+use crate::eth::transaction::{
+    typed::{
+        EIP1559TransactionRequest, EIP2930TransactionRequest, LegacyTransactionRequest,
+        TransactionKind, TypedTransactionRequest,
+    },
+    AccessList,
 };
-use reth_primitives::{AccessList, Address, Bytes, U128, U256};
+use reth_primitives::{Address, Bytes, U128, U256};
 use serde::{Deserialize, Serialize};
 
 /// Represents _all_ transaction requests received from RPC
+///
+/// The call data is accepted as both `input` and `data`, but only serialized as `input`.
 #[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
+#[serde(try_from = "TransactionRequestFields")]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionRequest {
     /// from address
@@ -15,33 +20,103 @@ pub struct TransactionRequest {
     /// to address
     pub to: Option<Address>,
     /// legacy, gas Price
-    #[serde(default)]
     pub gas_price: Option<U128>,
     /// max base fee per gas sender is willing to pay
-    #[serde(default)]
     pub max_fee_per_gas: Option<U128>,
     /// miner tip
-    #[serde(default)]
     pub max_priority_fee_per_gas: Option<U128>,
     /// gas
     pub gas: Option<U256>,
     /// value of th tx in wei
     pub value: Option<U256>,
     /// Any additional data sent
-    pub data: Option<Bytes>,
+    pub input: Option<Bytes>,
     /// Transaction nonce
     pub nonce: Option<U256>,
     /// warm storage access pre-payment
-    #[serde(default)]
     pub access_list: Option<AccessList>,
     /// EIP-2718 type
     #[serde(rename = "type")]
     pub transaction_type: Option<U256>,
 }
 
+/// The fields of a [TransactionRequest] as they are sent, with the call data in `input` or `data`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+struct TransactionRequestFields {
+    from: Option<Address>,
+    to: Option<Address>,
+    #[serde(default)]
+    gas_price: Option<U128>,
+    #[serde(default)]
+    max_fee_per_gas: Option<U128>,
+    #[serde(default)]
+    max_priority_fee_per_gas: Option<U128>,
+    gas: Option<U256>,
+    value: Option<U256>,
+    #[serde(default)]
+    input: Option<Bytes>,
+    #[serde(default)]
+    data: Option<Bytes>,
+    nonce: Option<U256>,
+    #[serde(default)]
+    access_list: Option<AccessList>,
+    #[serde(rename = "type")]
+    transaction_type: Option<U256>,
+}
+
+impl TryFrom<TransactionRequestFields> for TransactionRequest {
+    type Error = TransactionInputError;
+
+    fn try_from(fields: TransactionRequestFields) -> Result<Self, Self::Error> {
+        let TransactionRequestFields {
+            from,
+            to,
+            gas_price,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            gas,
+            value,
+            input,
+            data,
+            nonce,
+            access_list,
+            transaction_type,
+        } = fields;
+        let input = match (input, data) {
+            (Some(input), Some(data)) if input != data => return Err(TransactionInputError),
+            (input, data) => input.or(data),
+        };
+        Ok(Self {
+            from,
+            to,
+            gas_price,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            gas,
+            value,
+            input,
+            nonce,
+            access_list,
+            transaction_type,
+        })
+    }
+}
+
+/// Error returned if a request sets both `input` and `data` to different values
+#[derive(Debug, thiserror::Error)]
+#[error("both \"data\" and \"input\" are set and not equal. Please use \"input\" to pass transaction call data")]
+pub struct TransactionInputError;
+
 // == impl TransactionRequest ==
 
 impl TransactionRequest {
+    /// Returns the call data of the request, sent as either `input` or `data`.
+    pub fn input(&self) -> Option<&Bytes> {
+        self.input.as_ref()
+    }
+
     /// Converts the request into a [`TypedTransactionRequest`]
     ///
     /// Returns None if mutual exclusive fields `gasPrice` and `max_fee_per_gas` are either missing
@@ -54,7 +129,7 @@ impl TransactionRequest {
             max_priority_fee_per_gas,
             gas,
             value,
-            data,
+            input,
             nonce,
             mut access_list,
             ..
@@ -67,7 +142,7 @@ impl TransactionRequest {
                     gas_price: gas_price.unwrap_or_default(),
                     gas_limit: gas.unwrap_or_default(),
                     value: value.unwrap_or(U256::ZERO),
-                    input: data.unwrap_or_default(),
+                    input: input.unwrap_or_default(),
                     kind: match to {
                         Some(to) => TransactionKind::Call(to),
                         None => TransactionKind::Create,
@@ -82,13 +157,13 @@ impl TransactionRequest {
                     gas_price: gas_price.unwrap_or_default(),
                     gas_limit: gas.unwrap_or_default(),
                     value: value.unwrap_or(U256::ZERO),
-                    input: data.unwrap_or_default(),
+                    input: input.unwrap_or_default(),
                     kind: match to {
                         Some(to) => TransactionKind::Call(to),
                         None => TransactionKind::Create,
                     },
                     chain_id: 0,
-                    access_list,
+                    access_list: access_list.into(),
                 }))
             }
             // EIP1559
@@ -100,16 +175,52 @@ impl TransactionRequest {
                     max_priority_fee_per_gas: max_priority_fee_per_gas.unwrap_or(U128::ZERO),
                     gas_limit: gas.unwrap_or_default(),
                     value: value.unwrap_or(U256::ZERO),
-                    input: data.unwrap_or_default(),
+                    input: input.unwrap_or_default(),
                     kind: match to {
                         Some(to) => TransactionKind::Call(to),
                         None => TransactionKind::Create,
                     },
                     chain_id: 0,
-                    access_list: access_list.unwrap_or_default(),
+                    access_list: access_list.map(Into::into).unwrap_or_default(),
                 }))
             }
             _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_only_data() {
+        let tx: TransactionRequest = serde_json::from_str(r#"{"data":"0x0102"}"#).unwrap();
+        assert_eq!(tx.input(), Some(&Bytes::from(vec![1, 2])));
+    }
+
+    #[test]
+    fn serde_only_input() {
+        let tx: TransactionRequest = serde_json::from_str(r#"{"input":"0x0102"}"#).unwrap();
+        assert_eq!(tx.input(), Some(&Bytes::from(vec![1, 2])));
+    }
+
+    #[test]
+    fn serde_data_and_input_equal() {
+        let tx: TransactionRequest =
+            serde_json::from_str(r#"{"data":"0x0102","input":"0x0102"}"#).unwrap();
+        assert_eq!(tx.input(), Some(&Bytes::from(vec![1, 2])));
+
+        let serialized = serde_json::to_value(&tx).unwrap();
+        assert_eq!(serialized["input"], "0x0102");
+        assert!(serialized.get("data").is_none());
+        assert_eq!(serde_json::from_value::<TransactionRequest>(serialized).unwrap(), tx);
+    }
+
+    #[test]
+    fn serde_data_and_input_different() {
+        let err = serde_json::from_str::<TransactionRequest>(r#"{"data":"0x0102","input":"0x03"}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("both \"data\" and \"input\" are set"), "{err}");
+    }
+}
@@ -10,7 +10,13 @@
 //! Provides all relevant types for the various RPC endpoints, grouped by namespace.
 
 mod admin;
+mod debug;
 mod eth;
+mod otterscan;
+mod reth;
 
 pub use admin::*;
+pub use debug::*;
 pub use eth::*;
+pub use otterscan::*;
+pub use reth::*;
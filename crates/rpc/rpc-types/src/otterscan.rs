@@ -0,0 +1,67 @@
+//! Types of the `ots` namespace, see <https://github.com/otterscan/otterscan/blob/develop/docs/custom-jsonrpc.md>
+
+use crate::{Block, Transaction, TransactionReceipt};
+use reth_primitives::{U256, U64};
+use serde::{Deserialize, Serialize};
+
+/// Response of `ots_getBlockDetails`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockDetails {
+    /// The block, without its transactions.
+    pub block: OtsBlock,
+    /// The ether issued by the block.
+    pub issuance: BlockIssuance,
+    /// Sum of the fees paid by the transactions of the block.
+    pub total_fees: U256,
+}
+
+/// A block with the number of its transactions instead of the transactions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsBlock {
+    /// The block, with an empty list of transactions.
+    #[serde(flatten)]
+    pub block: Block,
+    /// Number of transactions in the block.
+    pub transaction_count: usize,
+}
+
+/// The ether issued by a block, zero after the merge.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockIssuance {
+    /// Reward of the block beneficiary, including the rewards for the included ommers.
+    pub block_reward: U256,
+    /// Sum of the rewards of the ommer beneficiaries.
+    pub uncle_reward: U256,
+    /// Total issuance of the block.
+    pub issuance: U256,
+}
+
+/// Response of `ots_searchTransactionsBefore` and `ots_searchTransactionsAfter`.
+///
+/// The transactions are ordered from the newest to the oldest in both directions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionsWithReceipts {
+    /// The transactions of the page.
+    pub txs: Vec<Transaction>,
+    /// The receipts of the transactions, in the same order.
+    pub receipts: Vec<OtsReceipt>,
+    /// Whether the page contains the newest transactions of the address.
+    pub first_page: bool,
+    /// Whether the page contains the oldest transactions of the address.
+    pub last_page: bool,
+}
+
+/// A transaction receipt with the timestamp of its block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsReceipt {
+    /// The receipt.
+    #[serde(flatten)]
+    pub receipt: TransactionReceipt,
+    /// Timestamp of the block of the transaction.
+    pub timestamp: U64,
+}
@@ -0,0 +1,315 @@
+use reth_primitives::{Address, BlockNumber, Bytes, H256, U256, U64};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The origin the transactions imported via `reth_importRawTransactions` are added to the pool
+/// with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportOrigin {
+    /// The transactions are treated like transactions submitted to this node, which are exempt
+    /// from the per sender limits of the pool.
+    Local,
+    /// The transactions are treated like transactions received from the network.
+    #[default]
+    External,
+}
+
+/// The outcome of importing a single raw transaction via `reth_importRawTransactions`.
+///
+/// Either the hash of the transaction if it was added to the pool, or the reason it was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportedTransaction {
+    /// Hash of the transaction, if it was added to the pool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<H256>,
+    /// The reason the transaction was rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ImportedTransaction {
+    /// Creates the outcome of a transaction that was added to the pool.
+    pub fn imported(hash: H256) -> Self {
+        Self { hash: Some(hash), error: None }
+    }
+
+    /// Creates the outcome of a transaction that was rejected.
+    pub fn rejected(error: impl ToString) -> Self {
+        Self { hash: None, error: Some(error.to_string()) }
+    }
+}
+
+/// The transactions of an account in the transaction pool returned by
+/// `reth_getAccountPoolStatus`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountPoolStatus {
+    /// Nonce of the account in the latest state.
+    pub nonce: U64,
+    /// Transactions of the account that are ready to be included, ordered by nonce.
+    pub pending: Vec<PooledTransactionStatus>,
+    /// Transactions of the account that can't be included yet, ordered by nonce.
+    pub queued: Vec<PooledTransactionStatus>,
+    /// The lowest missing nonce that keeps the queued transactions from being promoted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce_gap: Option<U64>,
+}
+
+/// A transaction in the pool, see [AccountPoolStatus].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PooledTransactionStatus {
+    /// Hash of the transaction.
+    pub hash: H256,
+    /// Nonce of the transaction.
+    pub nonce: U64,
+}
+
+/// The balance, nonce and code of an account returned by `reth_getAccountInfo` and
+/// `reth_getAccountsInfo`.
+///
+/// Accounts that don't exist are returned with zero balance and nonce and the hash of the empty
+/// code.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountInfo {
+    /// Balance of the account.
+    pub balance: U256,
+    /// Nonce of the account.
+    pub nonce: U64,
+    /// Hash of the code of the account.
+    pub code_hash: H256,
+    /// Code of the account, if it has any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+}
+
+/// A proof of multiple accounts and storage slots returned by `reth_getMultiProof`.
+///
+/// Unlike the proofs of `eth_getProof`, the nodes are shared by all accounts and slots and every
+/// node is only included once.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiProofResponse {
+    /// The state root the accounts are proven against.
+    pub state_root: H256,
+    /// The RLP encoded account and storage trie nodes.
+    pub nodes: Vec<Bytes>,
+    /// The proven accounts, ordered by address.
+    pub accounts: Vec<MultiProofAccount>,
+}
+
+/// An account proven by a [MultiProofResponse].
+///
+/// Accounts that don't exist are returned with zero balance and nonce, the hash of the empty code
+/// and the root of the empty storage.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiProofAccount {
+    /// Address of the account.
+    pub address: Address,
+    /// Balance of the account.
+    pub balance: U256,
+    /// Nonce of the account.
+    pub nonce: U64,
+    /// Hash of the code of the account.
+    pub code_hash: H256,
+    /// Root of the storage trie of the account.
+    pub storage_hash: H256,
+    /// The requested slots of the account, ordered by key.
+    pub storage: Vec<MultiProofSlot>,
+}
+
+/// A storage slot proven by a [MultiProofResponse].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiProofSlot {
+    /// Key of the slot.
+    pub key: H256,
+    /// Value of the slot, zero if the slot doesn't exist.
+    pub value: U256,
+}
+
+/// A snapshot of the blockchain tree returned by `reth_getBlockchainTreeState`.
+///
+/// It only contains block numbers and hashes, the blocks and their state stay in the tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockchainTreeState {
+    /// The tip of the canonical chain.
+    pub canonical_tip: TreeBlock,
+    /// The number of the last finalized block.
+    pub last_finalized_block: BlockNumber,
+    /// The side chains of the tree, ordered by id.
+    pub side_chains: Vec<SideChainState>,
+    /// The number of entries of the tree indices.
+    pub indices: BlockIndicesSizes,
+    /// The number of blocks buffered until their parent is in the tree.
+    ///
+    /// The tree doesn't buffer blocks yet, this is always zero.
+    pub buffered_blocks: usize,
+}
+
+/// The number and hash of a block of the blockchain tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeBlock {
+    /// Number of the block.
+    pub number: BlockNumber,
+    /// Hash of the block.
+    pub hash: H256,
+}
+
+/// A side chain of the blockchain tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SideChainState {
+    /// Id of the chain in the tree.
+    pub id: u64,
+    /// The block the chain forks from.
+    pub fork_block: TreeBlock,
+    /// Number of the first block of the chain.
+    pub first_block: BlockNumber,
+    /// Number of the last block of the chain.
+    pub last_block: BlockNumber,
+    /// Hash of the last block of the chain.
+    pub tip_hash: H256,
+}
+
+/// The number of entries of each index of the blockchain tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockIndicesSizes {
+    /// Number of the canonical hashes kept for execution.
+    pub canonical_chain: usize,
+    /// Number of fork blocks with children in the tree.
+    pub fork_to_child: usize,
+    /// Number of blocks in the side chains.
+    pub blocks_to_chain: usize,
+    /// Number of block numbers with blocks in the side chains.
+    pub number_to_block: usize,
+}
+
+/// The recent reorgs of the canonical chain returned by `reth_getReorgStats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorgStats {
+    /// Number of reorgs since the node started, including the ones no longer in `reorgs`.
+    pub total_reorgs: U64,
+    /// The most recent reorgs, oldest first.
+    pub reorgs: Vec<ReorgEvent>,
+}
+
+/// A reorg of the canonical chain, see [ReorgStats].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorgEvent {
+    /// Number of canonical blocks that were removed.
+    pub depth: U64,
+    /// Unix timestamp of the reorg in seconds.
+    pub timestamp: U64,
+    /// Hash of the canonical tip before the reorg.
+    pub old_tip: H256,
+    /// Hash of the canonical tip after the reorg.
+    pub new_tip: H256,
+    /// Number of transactions of the removed blocks that are not included in the new canonical
+    /// blocks, which are reintroduced to the transaction pool.
+    pub reinjected_transactions: U64,
+}
+
+/// The execution profile of a transaction, returned by `reth_traceBlockTiming`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionTiming {
+    /// Hash of the transaction.
+    pub transaction_hash: H256,
+    /// Index of the transaction in the block.
+    pub transaction_index: U64,
+    /// Wall time of the execution in microseconds.
+    pub time_micros: U64,
+    /// Gas used by the transaction.
+    pub gas_used: U64,
+    /// Number of accounts and storage slots loaded by the transaction.
+    pub state_reads: U64,
+    /// Number of accounts and storage slots changed by the transaction.
+    pub state_writes: U64,
+    /// Number of executed `SLOAD`s.
+    pub sloads: U64,
+    /// Number of executed `SSTORE`s.
+    pub sstores: U64,
+}
+
+/// The outcome of `reth_unwindTo`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnwindResult {
+    /// Number of the block the chain was unwound to, the new head.
+    pub block_number: U64,
+    /// Hash of the new head.
+    pub block_hash: H256,
+    /// The unwound stages, in the order they were unwound.
+    pub stages: Vec<StageUnwindResult>,
+}
+
+/// The unwind of a single stage by `reth_unwindTo`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageUnwindResult {
+    /// The id of the stage.
+    pub stage: String,
+    /// The progress of the stage before the unwind.
+    pub from: U64,
+    /// The progress of the stage after the unwind.
+    pub to: U64,
+}
+
+/// The first block after which the storage history disagrees with the storage changesets,
+/// returned by `reth_verifyStorageHistory`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageHistoryDivergence {
+    /// Number of the block after which the values differ.
+    pub block_number: U64,
+    /// The value after the block, replayed from the storage changesets.
+    pub expected: U256,
+    /// The value after the block served by `eth_getStorageAt`, `null` if the changeset its
+    /// history index points to is missing.
+    pub historical: Option<U256>,
+}
+
+/// Subscription kind of `reth_subscribe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub enum RethSubscriptionKind {
+    /// Storage slots subscription.
+    ///
+    /// Fires a [StorageChangeNotification] each time the canonical chain changes the value of one
+    /// of the watched slots, see [StorageSubscriptionParams].
+    Storage,
+}
+
+/// The storage slots of an account watched by a `storage` subscription.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageSubscriptionParams {
+    /// The account of the slots.
+    pub address: Address,
+    /// The watched slots, at least one.
+    pub keys: Vec<H256>,
+}
+
+/// The new values of watched storage slots, emitted by a `storage` subscription once the block
+/// that changed them is canonical.
+///
+/// If blocks are reorged, the slots changed by the removed blocks are emitted with their values at
+/// the new canonical tip.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageChangeNotification {
+    /// Hash of the canonical tip the values are read at.
+    pub block_hash: H256,
+    /// Number of the canonical tip the values are read at.
+    pub block_number: U64,
+    /// The account of the slots.
+    pub address: Address,
+    /// The changed watched slots with their new values.
+    pub storage: BTreeMap<H256, H256>,
+}
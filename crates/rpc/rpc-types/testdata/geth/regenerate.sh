@@ -0,0 +1,86 @@
+#!/usr/bin/env bash
+# Regenerates the geth compatibility fixtures from a live node.
+#
+# Usage: ./regenerate.sh <rpc url>
+#
+# The node must be a synced geth mainnet archive node with the `debug` and `txpool` namespaces
+# enabled. Every fixture is the `result` of the response of the node, formatted by jq.
+#
+# The committed corpus only holds the fixtures recorded from mainnet nodes so far, see
+# `tests/geth_compat.rs`. The kinds without a recording are captured by this script as well.
+# `syncing/snap_syncing.json` can only be captured from a syncing node and is not regenerated.
+set -euo pipefail
+
+RPC_URL=${1:?usage: $0 <rpc url>}
+DIR=$(cd "$(dirname "$0")" && pwd)
+
+rpc() {
+    curl -sf -X POST -H 'Content-Type: application/json' \
+        --data "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"$1\",\"params\":$2}" "$RPC_URL" |
+        jq '.result'
+}
+
+# the hash of the transaction at the index of the block
+tx_hash() {
+    rpc eth_getTransactionByBlockNumberAndIndex "[\"$1\",\"$2\"]" | jq -r '.hash'
+}
+
+capture() {
+    local fixture=$1
+    shift
+    mkdir -p "$DIR/$(dirname "$fixture")"
+    rpc "$@" >"$DIR/$fixture"
+    echo "$fixture"
+}
+
+TRANSFER=0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef
+
+capture block/genesis.json eth_getBlockByNumber '["0x0",false]'
+capture block/frontier_with_transactions.json eth_getBlockByNumber '["0xb443",false]'
+capture block/byzantium_with_uncles.json eth_getBlockByNumber '["0x42ae50",false]'
+capture block/london.json eth_getBlockByNumber '["0xc5d488",false]'
+capture block/first_pos_block.json eth_getBlockByNumber '["0xed14f2",false]'
+capture block/shanghai_with_withdrawals.json eth_getBlockByNumber '["0x103ee76",false]'
+capture block/empty_post_merge.json eth_getBlockByNumber '["0x103ee82",false]'
+
+capture block_full/frontier_legacy.json eth_getBlockByNumber '["0xb443",true]'
+capture block_full/london_mixed_types.json eth_getBlockByNumber '["0xc5d488",true]'
+capture block_full/shanghai_full.json eth_getBlockByNumber '["0x103ee76",true]'
+
+capture transaction/legacy_unprotected.json eth_getTransactionByBlockNumberAndIndex '["0xb443","0x0"]'
+capture transaction/legacy_eip155.json eth_getTransactionByHash '["0x88df016429689c079f3b2f6ad39fa052532c56795b733da78a91ebe6a713944b"]'
+capture transaction/access_list.json eth_getTransactionByBlockNumberAndIndex '["0xbad4b1","0x3"]'
+capture transaction/dynamic_fee.json eth_getTransactionByHash '["0x0e07d8b53ed3d91314c80e53cf25bcde02084939395845cbb625b029d568135c"]'
+capture transaction/dynamic_fee_access_list.json eth_getTransactionByBlockNumberAndIndex '["0xed14f2","0x2a"]'
+capture transaction/contract_creation.json eth_getTransactionByBlockNumberAndIndex '["0x103ee76","0x9"]'
+PENDING=$(rpc txpool_content '[]' | jq -r '[.pending[][]][0].hash')
+capture transaction/pending.json eth_getTransactionByHash "[\"$PENDING\"]"
+
+capture receipt/pre_byzantium_root.json eth_getTransactionReceipt '["0xea1093d492a1dcb1bef708f771a99a96ff05dcab81ca76c31940300177fcf49f"]'
+capture receipt/legacy_success.json eth_getTransactionReceipt "[\"$(tx_hash 0x42ae50 0x11)\"]"
+capture receipt/failed.json eth_getTransactionReceipt "[\"$(tx_hash 0xed14f2 0x5)\"]"
+capture receipt/contract_creation.json eth_getTransactionReceipt '["0x611b173b0e0dfda94da7bfb6cb77c9f1c03e2f2149ba060e6bddfaa219942369"]'
+capture receipt/dynamic_fee_with_logs.json eth_getTransactionReceipt '["0x21f6554c28453a01e7276c1db2fc1695bb512b170818bfa98fa8136433100616"]'
+capture receipt/access_list.json eth_getTransactionReceipt "[\"$(tx_hash 0xbad4b1 0x3)\"]"
+
+capture logs/transfer_events.json eth_getLogs "[{\"fromBlock\":\"0xf42400\",\"toBlock\":\"0xf42400\",\"topics\":[\"$TRANSFER\"]}]"
+capture logs/swap_events.json eth_getLogs "[{\"fromBlock\":\"0xf42401\",\"toBlock\":\"0xf42401\",\"address\":\"0xb4e16d0168e52d35cacd2c6185b44281ec28c9dc\"}]"
+capture logs/anonymous.json eth_getLogs '[{"fromBlock":"0xf42402","toBlock":"0xf42402","topics":[[]]}]'
+capture logs/empty.json eth_getLogs "[{\"fromBlock\":\"0xf42400\",\"toBlock\":\"0xf42402\",\"topics\":[\"0x$(printf '0%.0s' {1..64})\"]}]"
+
+capture fee_history/with_rewards.json eth_feeHistory '["0x5","0x103ee70",[10,50,90]]'
+capture fee_history/without_rewards.json eth_feeHistory '["0x4","0x103ee6f",[]]'
+capture fee_history/around_london.json eth_feeHistory '["0x4","0xc5d489",[25,75]]'
+capture fee_history/single_block.json eth_feeHistory '["0x1","0x103ee76",[50]]'
+
+capture syncing/not_syncing.json eth_syncing '[]'
+
+capture struct_logs/storage.json debug_traceTransaction '["0xd01212e8ab48d2fd2ea9c4f33f8670fd1cf0cfb09d2e3c6ceddfaf54152386e5",{"enableMemory":true}]'
+capture struct_logs/transfer.json debug_traceTransaction "[\"$(tx_hash 0xc5d488 0x2)\",{}]"
+capture struct_logs/contract_call.json debug_traceTransaction "[\"$(tx_hash 0xc5d488 0x3)\",{\"enableMemory\":true}]"
+capture struct_logs/reverted.json debug_traceTransaction "[\"$(tx_hash 0xed14f2 0x5)\",{\"enableMemory\":true}]"
+capture struct_logs/out_of_gas.json debug_traceTransaction "[\"$(tx_hash 0x42ae50 0x3)\",{\"enableMemory\":true}]"
+
+capture block_traces/call_tracer.json debug_traceBlockByNumber '["0x103ee76",{"tracer":"callTracer"}]'
+capture block_traces/prestate_tracer.json debug_traceBlockByNumber '["0x103ee76",{"tracer":"prestateTracer"}]'
+capture block_traces/four_byte_tracer.json debug_traceBlockByNumber '["0x103ee76",{"tracer":"4byteTracer"}]'
@@ -0,0 +1,241 @@
+//! Compatibility of the RPC types with the JSON responses of geth.
+//!
+//! Every fixture in `testdata/geth/<kind>` is the `result` of a response of a mainnet node. It is
+//! deserialized into the reth type of the response, serialized again and compared with the
+//! fixture:
+//!
+//! - the order of object keys is irrelevant
+//! - a key that is missing on one side equals `null` on the other side
+//! - everything else, including the encoding of quantities, must match exactly
+//!
+//! Differences that are known and accepted are listed per kind, with the reason. The fixtures can
+//! be regenerated from a live node with `testdata/geth/regenerate.sh`.
+//!
+//! The committed fixtures were recorded from mainnet nodes for the test suites of ethers-rs and
+//! alloy, the transaction or block of every fixture is the one `regenerate.sh` captures. The kinds
+//! without a recorded fixture are ignored until they are captured.
+use reth_rpc_types::{
+    trace::geth::{DefaultFrame, TraceResult},
+    Block, FeeHistory, Log, SyncStatus, Transaction, TransactionReceipt,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{fmt, fs, path::PathBuf};
+
+/// A difference between the fixture and the re-serialized response that is accepted.
+struct KnownDifference {
+    /// Path of the differing value, `[]` matches any array index. Differences within the value
+    /// are accepted as well.
+    path: &'static str,
+    /// Why the difference is accepted.
+    #[allow(dead_code)]
+    reason: &'static str,
+}
+
+impl KnownDifference {
+    fn matches(&self, path: &str) -> bool {
+        let path = without_indices(path);
+        path == self.path ||
+            path.strip_prefix(self.path)
+                .map_or(false, |rest| rest.starts_with('.') || rest.starts_with('['))
+    }
+}
+
+const BLOCK_DIFFERENCES: &[KnownDifference] = &[KnownDifference {
+    path: "$.author",
+    reason: "reth returns the miner as author as well, like OpenEthereum",
+}];
+
+const SYNCING_DIFFERENCES: &[KnownDifference] = &[
+    KnownDifference { path: "$.healedBytecodeBytes", reason: SNAP_SYNC_PROGRESS },
+    KnownDifference { path: "$.healedBytecodes", reason: SNAP_SYNC_PROGRESS },
+    KnownDifference { path: "$.healedTrienodeBytes", reason: SNAP_SYNC_PROGRESS },
+    KnownDifference { path: "$.healedTrienodes", reason: SNAP_SYNC_PROGRESS },
+    KnownDifference { path: "$.healingBytecode", reason: SNAP_SYNC_PROGRESS },
+    KnownDifference { path: "$.healingTrienodes", reason: SNAP_SYNC_PROGRESS },
+    KnownDifference { path: "$.syncedAccountBytes", reason: SNAP_SYNC_PROGRESS },
+    KnownDifference { path: "$.syncedAccounts", reason: SNAP_SYNC_PROGRESS },
+    KnownDifference { path: "$.syncedBytecodeBytes", reason: SNAP_SYNC_PROGRESS },
+    KnownDifference { path: "$.syncedBytecodes", reason: SNAP_SYNC_PROGRESS },
+    KnownDifference { path: "$.syncedStorage", reason: SNAP_SYNC_PROGRESS },
+    KnownDifference { path: "$.syncedStorageBytes", reason: SNAP_SYNC_PROGRESS },
+];
+
+const SNAP_SYNC_PROGRESS: &str = "reth has no snap sync, its progress is not returned";
+
+const STRUCT_LOGS_DIFFERENCES: &[KnownDifference] = &[
+    KnownDifference { path: "$.gas", reason: "geth returns the gas as number, reth as quantity" },
+    KnownDifference {
+        path: "$.returnValue",
+        reason: "geth returns the return value as hex without 0x prefix",
+    },
+    KnownDifference {
+        path: "$.structLogs[].storage",
+        reason: "geth returns the storage slots and values as hex without 0x prefix",
+    },
+];
+
+#[test]
+#[ignore = "no fixture recorded yet, run testdata/geth/regenerate.sh"]
+fn block() {
+    check_fixtures::<Block>("block", BLOCK_DIFFERENCES);
+}
+
+#[test]
+#[ignore = "no fixture recorded yet, run testdata/geth/regenerate.sh"]
+fn block_with_full_transactions() {
+    check_fixtures::<Block>("block_full", BLOCK_DIFFERENCES);
+}
+
+#[test]
+fn transaction() {
+    check_fixtures::<Transaction>("transaction", &[]);
+}
+
+#[test]
+fn receipt() {
+    check_fixtures::<TransactionReceipt>("receipt", &[]);
+}
+
+#[test]
+#[ignore = "no fixture recorded yet, run testdata/geth/regenerate.sh"]
+fn logs() {
+    check_fixtures::<Vec<Log>>("logs", &[]);
+}
+
+#[test]
+#[ignore = "no fixture recorded yet, run testdata/geth/regenerate.sh"]
+fn fee_history() {
+    check_fixtures::<FeeHistory>("fee_history", &[]);
+}
+
+#[test]
+fn syncing() {
+    check_fixtures::<SyncStatus>("syncing", SYNCING_DIFFERENCES);
+}
+
+#[test]
+fn struct_logs() {
+    check_fixtures::<DefaultFrame>("struct_logs", STRUCT_LOGS_DIFFERENCES);
+}
+
+#[test]
+#[ignore = "no fixture recorded yet, run testdata/geth/regenerate.sh"]
+fn block_traces() {
+    check_fixtures::<Vec<TraceResult>>("block_traces", &[]);
+}
+
+/// Round trips all fixtures of the kind through `T` and fails with the differences of all
+/// incompatible fixtures.
+fn check_fixtures<T: DeserializeOwned + Serialize>(kind: &str, known: &[KnownDifference]) {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata/geth").join(kind);
+    let mut paths = fs::read_dir(&dir)
+        .unwrap_or_else(|err| panic!("failed to read {}: {err}", dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert!(!paths.is_empty(), "no fixtures in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for path in &paths {
+        let expected: Value = serde_json::from_str(&fs::read_to_string(path).unwrap())
+            .unwrap_or_else(|err| panic!("invalid fixture {}: {err}", path.display()));
+        let actual = match serde_json::from_value::<T>(expected.clone()) {
+            Ok(value) => serde_json::to_value(value).unwrap(),
+            Err(err) => {
+                failures.push(format!("{}: failed to deserialize: {err}", path.display()));
+                continue
+            }
+        };
+
+        let mut differences = Vec::new();
+        diff("$".to_string(), &expected, &actual, &mut differences);
+        differences.retain(|difference| !known.iter().any(|k| k.matches(&difference.path)));
+        if !differences.is_empty() {
+            let differences =
+                differences.iter().map(|difference| format!("  {difference}")).collect::<Vec<_>>();
+            failures.push(format!("{}:\n{}", path.display(), differences.join("\n")));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} {kind} fixtures are incompatible:\n{}",
+        failures.len(),
+        paths.len(),
+        failures.join("\n")
+    );
+}
+
+/// A value of the fixture that differs from the re-serialized response.
+struct Difference {
+    path: String,
+    expected: Value,
+    actual: Value,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: expected {}, got {}", self.path, self.expected, self.actual)
+    }
+}
+
+/// Collects the differences between the values, see the module docs for the rules.
+fn diff(path: String, expected: &Value, actual: &Value, differences: &mut Vec<Difference>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            let keys =
+                expected.keys().chain(actual.keys().filter(|key| !expected.contains_key(*key)));
+            for key in keys {
+                diff(
+                    format!("{path}.{key}"),
+                    expected.get(key).unwrap_or(&Value::Null),
+                    actual.get(key).unwrap_or(&Value::Null),
+                    differences,
+                );
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (idx, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                diff(format!("{path}[{idx}]"), expected, actual, differences);
+            }
+        }
+        // `0` and `0.0` are the same number
+        (Value::Number(expected), Value::Number(actual))
+            if expected == actual || expected.as_f64() == actual.as_f64() => {}
+        (expected, actual) if expected == actual => {}
+        (expected, actual) => differences.push(Difference {
+            path,
+            expected: expected.clone(),
+            actual: actual.clone(),
+        }),
+    }
+}
+
+/// Replaces the array indices of the path with `[]`.
+fn without_indices(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut in_index = false;
+    for c in path.chars() {
+        match c {
+            '[' => {
+                in_index = true;
+                out.push_str("[]");
+            }
+            ']' if in_index => in_index = false,
+            _ if in_index => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[test]
+fn known_difference_paths() {
+    let known = KnownDifference { path: "$.structLogs[].storage", reason: "" };
+    assert!(known.matches("$.structLogs[3].storage"));
+    assert!(known.matches("$.structLogs[3].storage.0x01"));
+    assert!(!known.matches("$.structLogs[3].storageRoot"));
+    assert!(!known.matches("$.storage"));
+}
@@ -4,7 +4,7 @@ use jsonrpsee::core::RpcResult;
 use reth_network_api::{NetworkInfo, PeerKind, Peers};
 use reth_primitives::NodeRecord;
 use reth_rpc_api::AdminApiServer;
-use reth_rpc_types::NodeInfo;
+use reth_rpc_types::{NodeInfo, PeerInfo, PeerNetworkInfo};
 
 /// `admin` API implementation.
 ///
@@ -50,6 +50,30 @@ where
         Ok(true)
     }
 
+    /// Handler for `admin_peers`
+    async fn peers(&self) -> RpcResult<Vec<PeerInfo>> {
+        let peers = self.network.get_peers().await.to_rpc_result()?;
+        let local_address = self.network.local_addr().to_string();
+        Ok(peers
+            .into_iter()
+            .map(|peer| PeerInfo {
+                id: Some(format!("{:?}", peer.remote_id)),
+                name: peer.client_version,
+                caps: peer
+                    .capabilities
+                    .capabilities()
+                    .iter()
+                    .map(|cap| format!("{}/{}", cap.name, cap.version))
+                    .collect(),
+                network: PeerNetworkInfo {
+                    remote_address: peer.remote_addr.to_string(),
+                    local_address: local_address.clone(),
+                },
+                protocols: Default::default(),
+            })
+            .collect())
+    }
+
     /// Handler for `admin_peerEvents`
     fn subscribe_peer_events(
         &self,
@@ -0,0 +1,152 @@
+use crate::eth::error::{EthApiError, EthResult};
+use reth_executor::{
+    blockchain_tree::pending::{PendingBlockState, PendingStateViewer},
+    executor::Executor,
+};
+use reth_primitives::{Address, Block, BlockNumberOrTag, H256, U256};
+use reth_provider::{
+    BlockProvider, ChainSpecProvider, HeaderProvider, StateProvider, StateProviderFactory,
+};
+use reth_revm::{
+    database::{State, SubState},
+    tracing::{CallTraceArena, TraceInspectorConfig},
+    EthEvmConfig, EvmConfig,
+};
+use std::sync::Arc;
+
+/// A replayed block and the call traces of its transactions, together with the gas each
+/// transaction used.
+#[derive(Debug)]
+pub(crate) struct TracedBlock {
+    /// The hash of the block.
+    pub(crate) hash: H256,
+    /// The block.
+    pub(crate) block: Block,
+    /// The call traces and the used gas of the transactions, in block order.
+    pub(crate) traces: Vec<(CallTraceArena, u64)>,
+}
+
+/// Replays blocks on the state of their parents with a tracing inspector, for the block traces of
+/// the `debug` and `trace` namespaces.
+///
+/// A block of the blockchain tree that is not yet canonical is replayed on the state of the tree,
+/// see [PendingBlockState].
+#[derive(Clone)]
+pub(crate) struct BlockTracer<Client> {
+    client: Client,
+    /// The configuration of the EVM that re-executes blocks.
+    evm_config: Arc<dyn EvmConfig>,
+    /// The state of the blocks of the blockchain tree that are not yet canonical.
+    pending_state: Option<Arc<dyn PendingStateViewer>>,
+}
+
+impl<Client> BlockTracer<Client> {
+    /// Creates a tracer of the canonical blocks that re-executes them with the default
+    /// [EvmConfig].
+    pub(crate) fn new(client: Client) -> Self {
+        Self { client, evm_config: Arc::new(EthEvmConfig::default()), pending_state: None }
+    }
+
+    pub(crate) fn set_evm_config(&mut self, evm_config: Arc<dyn EvmConfig>) {
+        self.evm_config = evm_config;
+    }
+
+    pub(crate) fn set_pending_state(&mut self, pending_state: Arc<dyn PendingStateViewer>) {
+        self.pending_state = Some(pending_state);
+    }
+}
+
+impl<Client> BlockTracer<Client>
+where
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + ChainSpecProvider,
+{
+    /// Traces the transactions of the block with the given hash, canonical or of the tree.
+    pub(crate) fn trace_block_by_hash(
+        &self,
+        hash: H256,
+        config: TraceInspectorConfig,
+    ) -> EthResult<TracedBlock> {
+        if let Some(number) = self.client.block_number(hash)? {
+            return self.trace_canonical_block(number, config)
+        }
+        let pending = self
+            .pending_state
+            .as_ref()
+            .and_then(|pending_state| pending_state.pending_block_state(hash))
+            .ok_or(EthApiError::UnknownBlockHash)?;
+        self.trace_pending_block(pending, config)
+    }
+
+    /// Traces the transactions of the canonical block with the given number.
+    pub(crate) fn trace_block_by_number(
+        &self,
+        block: BlockNumberOrTag,
+        config: TraceInspectorConfig,
+    ) -> EthResult<TracedBlock> {
+        let number =
+            self.client.convert_block_number(block)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        self.trace_canonical_block(number, config)
+    }
+
+    /// Traces the transactions of the canonical block on the state of its parent.
+    fn trace_canonical_block(
+        &self,
+        number: u64,
+        config: TraceInspectorConfig,
+    ) -> EthResult<TracedBlock> {
+        // the genesis block is not executed
+        let parent_number = number.checked_sub(1).ok_or(EthApiError::UnknownBlockNumber)?;
+        let block = self.client.block(number.into())?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let total_difficulty =
+            self.client.header_td_by_number(number)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let parent = self.client.history_by_block_number(parent_number)?;
+        let traces = self.trace_transactions(&parent, &block, total_difficulty, None, config)?;
+        Ok(TracedBlock { hash: block.header.hash_slow(), block, traces })
+    }
+
+    /// Traces the transactions of the block of the tree on the state of the canonical fork block
+    /// with the changes of its side chain ancestors.
+    fn trace_pending_block(
+        &self,
+        pending: PendingBlockState,
+        config: TraceInspectorConfig,
+    ) -> EthResult<TracedBlock> {
+        let fork_td =
+            self.client.header_td(&pending.fork.hash)?.ok_or(EthApiError::UnknownBlockHash)?;
+        let fork_state = self.client.history_by_block_number(pending.fork.number)?;
+        let hash = pending.block.hash();
+        let block = pending.block.block.clone().unseal();
+        let traces = self.trace_transactions(
+            pending.state(&fork_state),
+            &block,
+            fork_td + pending.side_chain_difficulty,
+            Some(pending.block.senders.clone()),
+            config,
+        )?;
+        Ok(TracedBlock { hash, block, traces })
+    }
+
+    /// Replays the transactions of the block on the state of its parent.
+    fn trace_transactions<SP: StateProvider>(
+        &self,
+        parent: SP,
+        block: &Block,
+        total_difficulty: U256,
+        senders: Option<Vec<Address>>,
+        config: TraceInspectorConfig,
+    ) -> EthResult<Vec<(CallTraceArena, u64)>> {
+        Ok(Executor::with_evm_config(
+            self.client.chain_spec(),
+            SubState::new(State::new(parent)),
+            self.evm_config.clone(),
+        )
+        .trace_transactions(block, total_difficulty, senders, config)
+        .map_err(reth_interfaces::Error::from)?)
+    }
+}
+
+impl<Client> std::fmt::Debug for BlockTracer<Client> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockTracer").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// The default max number of requests that re-execute blocks at the same time.
+pub const DEFAULT_MAX_TRACING_REQUESTS: usize = 10;
+
+/// Limits the number of requests that re-execute blocks at the same time, like the block traces.
+///
+/// Clones share their permits, so one guard can cap the requests of multiple namespaces.
+#[derive(Debug, Clone)]
+pub struct TracingCallGuard(Arc<Semaphore>);
+
+impl TracingCallGuard {
+    /// Creates a guard that allows `max_requests` requests at the same time.
+    pub fn new(max_requests: usize) -> Self {
+        Self(Arc::new(Semaphore::new(max_requests)))
+    }
+
+    /// Waits until a request is allowed, the request is counted until the permit is dropped.
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        self.0
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("Semaphore::close is never called and can't be closed; qed")
+    }
+
+    /// Waits until a request is allowed and runs the blocking `f` on the blocking pool, the
+    /// request is counted until `f` returns.
+    pub(crate) async fn spawn_blocking<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let permit = self.acquire().await;
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            f()
+        })
+        .await
+        .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()))
+    }
+}
+
+impl Default for TracingCallGuard {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_TRACING_REQUESTS)
+    }
+}
@@ -1,34 +1,151 @@
-use crate::{result::internal_rpc_err, EthApiSpec};
+use crate::{
+    block_tracer::{BlockTracer, TracedBlock},
+    eth::{
+        error::{EthApiError, EthResult},
+        revm_utils::{apply_block_overrides, apply_state_overrides, build_call_evm_env, inspect},
+    },
+    result::internal_rpc_err,
+    EthApiSpec, TracingCallGuard,
+};
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
-use reth_primitives::{BlockId, BlockNumberOrTag, Bytes, H256};
+use reth_executor::{blockchain_tree::pending::PendingStateViewer, executor::Executor};
+use reth_primitives::{
+    BlockId, BlockNumber, BlockNumberOrTag, Bytes, BLOCKHASH_WINDOW, H256, U256,
+};
+use reth_provider::{
+    trie::HashedPostState, BlockExecutor, BlockHashProvider, BlockProvider, ChainSpecProvider,
+    EvmEnvProvider, HeaderProvider, RecordingStateProvider, StateProvider, StateProviderFactory,
+};
+use reth_revm::{
+    database::{State, SubState},
+    stack::InspectorStack,
+    tracing::{TraceInspectorConfig, TracingInspector},
+    EthEvmConfig, EvmConfig,
+};
 use reth_rpc_api::DebugApiServer;
 use reth_rpc_types::{
-    trace::geth::{BlockTraceResult, GethDebugTracingOptions, GethTraceFrame, TraceResult},
-    CallRequest, RichBlock,
+    trace::geth::{
+        BlockTraceResult, DefaultFrame, GethDebugTracingCallOptions, GethDebugTracingOptions,
+        GethTraceFrame, TraceResult,
+    },
+    CallRequest, ExecutionWitness, RichBlock,
 };
+use revm::primitives::{BlockEnv, CfgEnv};
+use std::sync::Arc;
 
 /// `debug` API implementation.
 ///
 /// This type provides the functionality for handling `debug` related requests.
 #[non_exhaustive]
-pub struct DebugApi<Eth> {
+pub struct DebugApi<Client, Eth> {
+    /// The client that can interact with the chain.
+    client: Client,
     /// The implementation of `eth` API
     eth: Eth,
+    /// The configuration of the EVM that re-executes blocks.
+    evm_config: Arc<dyn EvmConfig>,
+    /// Replays the traced blocks.
+    tracer: BlockTracer<Client>,
+    /// Caps the number of concurrent block traces.
+    tracing_call_guard: TracingCallGuard,
 }
 
 // === impl DebugApi ===
 
-impl<Eth> DebugApi<Eth> {
+impl<Client, Eth> DebugApi<Client, Eth> {
     /// Create a new instance of the [DebugApi]
-    pub fn new(eth: Eth) -> Self {
-        Self { eth }
+    pub fn new(client: Client, eth: Eth) -> Self
+    where
+        Client: Clone,
+    {
+        Self {
+            tracer: BlockTracer::new(client.clone()),
+            client,
+            eth,
+            evm_config: Arc::new(EthEvmConfig::default()),
+            tracing_call_guard: Default::default(),
+        }
+    }
+
+    /// Re-executes blocks with the given [EvmConfig].
+    pub fn with_evm_config(mut self, evm_config: Arc<dyn EvmConfig>) -> Self {
+        self.tracer.set_evm_config(evm_config.clone());
+        self.evm_config = evm_config;
+        self
+    }
+
+    /// Traces the blocks of the blockchain tree that are not yet canonical on the state of the
+    /// tree.
+    pub fn with_pending_state(mut self, pending_state: Arc<dyn PendingStateViewer>) -> Self {
+        self.tracer.set_pending_state(pending_state);
+        self
+    }
+
+    /// Shares the cap of concurrent block traces with other handlers.
+    pub fn with_tracing_call_guard(mut self, tracing_call_guard: TracingCallGuard) -> Self {
+        self.tracing_call_guard = tracing_call_guard;
+        self
+    }
+}
+
+impl<Client, Eth> DebugApi<Client, Eth>
+where
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + EvmEnvProvider
+        + ChainSpecProvider
+        + 'static,
+{
+    /// Re-executes the block on the state of its parent and collects the witness of the execution.
+    fn execution_witness(&self, block: BlockNumberOrTag) -> EthResult<ExecutionWitness> {
+        let number =
+            self.client.convert_block_number(block)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        // the genesis block is not executed
+        let parent_number = number.checked_sub(1).ok_or(EthApiError::UnknownBlockNumber)?;
+        let block = self.client.block(number.into())?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let total_difficulty =
+            self.client.header_td_by_number(number)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let chain_spec = self.client.chain_spec();
+
+        // the state is recorded below the cache of the executor, so every read of the execution
+        // is recorded once, including the block hashes read with `BLOCKHASH`
+        let parent = self.client.history_by_block_number(parent_number)?;
+        let recorder = RecordingStateProvider::new(&parent);
+        let result = Executor::with_evm_config(
+            chain_spec.clone(),
+            SubState::new(State::new(&recorder)),
+            self.evm_config.clone(),
+        )
+        .execute(&block, total_difficulty, None)
+        .map_err(reth_interfaces::Error::from)?;
+        let recorded = recorder.recorded();
+
+        let post_state = HashedPostState::from_execution_results(&[result], number, &chain_spec);
+        let state = parent.witness(&recorded.hashed_targets(), post_state)?;
+
+        let codes = recorded.codes.values().map(|code| code.original_bytes().into()).collect();
+        let mut keys = Vec::new();
+        for (address, slots) in &recorded.accounts {
+            keys.push(address.as_bytes().into());
+            keys.extend(slots.iter().map(|slot| slot.as_bytes().into()));
+        }
+
+        Ok(ExecutionWitness { state, codes, keys, block_hashes: recorded.block_hashes })
     }
 }
 
 #[async_trait]
-impl<Eth> DebugApiServer for DebugApi<Eth>
+impl<Client, Eth> DebugApiServer for DebugApi<Client, Eth>
 where
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + EvmEnvProvider
+        + ChainSpecProvider
+        + Clone
+        + 'static,
     Eth: EthApiSpec + 'static,
 {
     /// Handler for `debug_getRawHeader`
@@ -78,19 +195,43 @@ where
     /// Handler for `debug_traceBlockByHash`
     async fn debug_trace_block_by_hash(
         &self,
-        _block: H256,
-        _opts: GethDebugTracingOptions,
+        block: H256,
+        opts: GethDebugTracingOptions,
     ) -> RpcResult<Vec<TraceResult>> {
-        Err(internal_rpc_err("unimplemented"))
+        if opts.tracer.is_some() {
+            return Err(EthApiError::UnsupportedTracer.into())
+        }
+        let tracer = self.tracer.clone();
+        let traces = self
+            .tracing_call_guard
+            .spawn_blocking(move || {
+                let traced =
+                    tracer.trace_block_by_hash(block, TraceInspectorConfig::default_geth())?;
+                EthResult::Ok(geth_traces(traced, &opts))
+            })
+            .await?;
+        Ok(traces)
     }
 
     /// Handler for `debug_traceBlockByNumber`
     async fn debug_trace_block_by_number(
         &self,
-        _block: BlockNumberOrTag,
-        _opts: GethDebugTracingOptions,
+        block: BlockNumberOrTag,
+        opts: GethDebugTracingOptions,
     ) -> RpcResult<Vec<TraceResult>> {
-        Err(internal_rpc_err("unimplemented"))
+        if opts.tracer.is_some() {
+            return Err(EthApiError::UnsupportedTracer.into())
+        }
+        let tracer = self.tracer.clone();
+        let traces = self
+            .tracing_call_guard
+            .spawn_blocking(move || {
+                let traced =
+                    tracer.trace_block_by_number(block, TraceInspectorConfig::default_geth())?;
+                EthResult::Ok(geth_traces(traced, &opts))
+            })
+            .await?;
+        Ok(traces)
     }
 
     /// Handler for `debug_traceTransaction`
@@ -105,15 +246,119 @@ where
     /// Handler for `debug_traceCall`
     async fn debug_trace_call(
         &self,
-        _request: CallRequest,
-        _block_number: Option<BlockId>,
-        _opts: GethDebugTracingOptions,
+        request: CallRequest,
+        block_number: Option<BlockId>,
+        opts: GethDebugTracingCallOptions,
     ) -> RpcResult<GethTraceFrame> {
-        Err(internal_rpc_err("unimplemented"))
+        if opts.tracing_options.tracer.is_some() {
+            return Err(EthApiError::UnsupportedTracer.into())
+        }
+        let at = block_number.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
+        let client = self.client.clone();
+        let evm_config = self.evm_config.clone();
+        let frame = self
+            .tracing_call_guard
+            .spawn_blocking(move || trace_call(&client, &*evm_config, request, at, opts))
+            .await?;
+        serde_json::to_value(frame)
+            .and_then(serde_json::from_value)
+            .map_err(|err| internal_rpc_err(err.to_string()))
+    }
+
+    /// Handler for `debug_executionWitness`
+    async fn debug_execution_witness(
+        &self,
+        block: BlockNumberOrTag,
+    ) -> RpcResult<ExecutionWitness> {
+        Ok(self.execution_witness(block)?)
+    }
+}
+
+/// Executes the call request with a tracing inspector on the state at the given block, with the
+/// state and block overrides of the options applied.
+fn trace_call<Client>(
+    client: &Client,
+    evm_config: &dyn EvmConfig,
+    request: CallRequest,
+    at: BlockId,
+    opts: GethDebugTracingCallOptions,
+) -> EthResult<DefaultFrame>
+where
+    Client: StateProviderFactory + EvmEnvProvider,
+{
+    let GethDebugTracingCallOptions { tracing_options, state_overrides, block_overrides } = opts;
+
+    let mut cfg = CfgEnv::default();
+    let mut block_env = BlockEnv::default();
+    client.fill_env_at(&mut cfg, &mut block_env, at)?;
+    let number = block_env.number.as_limbs()[0];
+    // like in `eth_call`, the block gas limit doesn't cap the gas of the call
+    cfg.disable_block_gas_limit = true;
+
+    let state = client.history_by_block_number(number)?;
+    let mut db = SubState::new(State::new(state));
+    if let Some(state_overrides) = state_overrides {
+        apply_state_overrides(state_overrides, &mut db)?;
     }
+    if let Some(block_overrides) = block_overrides {
+        apply_block_overrides(block_overrides, &mut block_env);
+        let overridden_number = block_env.number.as_limbs()[0];
+        if overridden_number != number {
+            load_overridden_block_hashes(&mut db, number, overridden_number)?;
+        }
+    }
+
+    let env = build_call_evm_env(cfg, block_env, request)?;
+    let mut stack = InspectorStack::default();
+    stack.set_evm_config(evm_config, env.cfg.spec_id);
+    stack.tracer = Some(TracingInspector::new(TraceInspectorConfig::default_geth()));
+    let (res, _) = inspect(&mut db, env, &mut stack)?;
+
+    let arena = stack.tracer.take().expect("tracer is set").finalize();
+    Ok(arena.geth_traces(U256::from(res.result.gas_used()), tracing_options))
+}
+
+/// Loads the ancestors of a call executed at the `block` with the number overridden to `number`
+/// that are accessible to the `BLOCKHASH` opcode.
+///
+/// The canonical hashes up to the `block` are read with a single range read. Like in geth, the
+/// blocks between the `block` and the overridden number have no hash yet and read as zero.
+fn load_overridden_block_hashes<SP: StateProvider>(
+    db: &mut SubState<SP>,
+    block: BlockNumber,
+    number: BlockNumber,
+) -> EthResult<()> {
+    let Some(end) = number.checked_sub(1) else { return Ok(()) };
+    let start = number.saturating_sub(BLOCKHASH_WINDOW);
+
+    if start <= block {
+        let hashes = db.db.state().canonical_hashes_range(start, end.min(block))?;
+        for (number, hash) in (start..).zip(hashes) {
+            db.block_hashes.insert(U256::from(number), hash);
+        }
+    }
+    for number in start.max(block + 1)..=end {
+        db.block_hashes.insert(U256::from(number), H256::zero());
+    }
+    Ok(())
+}
+
+/// Returns a geth style trace of every transaction of the replayed block.
+fn geth_traces(traced: TracedBlock, opts: &GethDebugTracingOptions) -> Vec<TraceResult> {
+    traced
+        .traces
+        .into_iter()
+        .map(|(arena, gas_used)| {
+            let frame = arena.geth_traces(U256::from(gas_used), opts.clone());
+            match serde_json::to_value(frame) {
+                Ok(result) => TraceResult::Success { result },
+                Err(err) => TraceResult::Error { error: err.to_string() },
+            }
+        })
+        .collect()
 }
 
-impl<Eth> std::fmt::Debug for DebugApi<Eth> {
+impl<Client, Eth> std::fmt::Debug for DebugApi<Client, Eth> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DebugApi").finish_non_exhaustive()
     }
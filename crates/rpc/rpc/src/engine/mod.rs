@@ -9,7 +9,7 @@ use reth_primitives::{BlockHash, BlockNumber, H64};
 use reth_rpc_api::EngineApiServer;
 use reth_rpc_engine_api::{
     EngineApiError, EngineApiHandle, EngineApiMessage, EngineApiMessageVersion, EngineApiResult,
-    REQUEST_TOO_LARGE_CODE, UNKNOWN_PAYLOAD_CODE,
+    INVALID_PAYLOAD_ATTRIBUTES_CODE, REQUEST_TOO_LARGE_CODE, UNKNOWN_PAYLOAD_CODE,
 };
 use reth_rpc_types::engine::{
     ExecutionPayload, ExecutionPayloadBodies, ForkchoiceUpdated, PayloadAttributes, PayloadStatus,
@@ -42,12 +42,13 @@ impl EngineApi {
         msg: EngineApiMessage,
         rx: Receiver<EngineApiResult<T>>,
     ) -> Result<T> {
-        let _ = self.engine_tx.send(msg);
+        self.engine_tx.send(msg);
         rx.await.map_err(|err| Error::Custom(err.to_string()))?.map_err(|err| {
             let code = match err {
                 EngineApiError::InvalidParams => INVALID_PARAMS_CODE,
                 EngineApiError::PayloadUnknown => UNKNOWN_PAYLOAD_CODE,
                 EngineApiError::PayloadRequestTooLarge { .. } => REQUEST_TOO_LARGE_CODE,
+                EngineApiError::InvalidPayloadAttributes(_) => INVALID_PAYLOAD_ATTRIBUTES_CODE,
                 // Any other server error
                 _ => jsonrpsee::types::error::INTERNAL_ERROR_CODE,
             };
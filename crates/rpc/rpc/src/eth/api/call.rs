@@ -3,27 +3,28 @@
 use crate::{
     eth::{
         error::{EthApiError, EthResult, InvalidTransactionError, RevertError},
-        revm_utils::{build_call_evm_env, get_precompiles, inspect, transact},
+        revm_utils::{
+            apply_state_overrides, build_call_evm_env, get_precompiles, inspect, transact,
+        },
         EthTransactions,
     },
     EthApi,
 };
 use ethers_core::utils::get_contract_address;
-use reth_primitives::{AccessList, Address, BlockId, BlockNumberOrTag, U256};
-use reth_provider::{BlockProvider, EvmEnvProvider, StateProvider, StateProviderFactory};
+use reth_primitives::{BlockId, BlockNumberOrTag, U256};
+use reth_provider::{
+    BlockProvider, ChainSpecProvider, EvmEnvProvider, StateProvider, StateProviderFactory,
+};
 use reth_revm::{
     access_list::AccessListInspector,
     database::{State, SubState},
+    stack::InspectorStack,
 };
-use reth_rpc_types::{
-    state::{AccountOverride, StateOverride},
-    CallRequest,
-};
+use reth_rpc_types::{state::StateOverride, AccessList, CallRequest};
 use reth_transaction_pool::TransactionPool;
 use revm::{
-    db::{CacheDB, DatabaseRef},
     primitives::{
-        BlockEnv, Bytecode, CfgEnv, Env, ExecutionResult, Halt, ResultAndState, TransactTo,
+        BlockEnv, CfgEnv, Env, ExecutionResult, Halt, ResultAndState, SpecId, TransactTo,
     },
     Database,
 };
@@ -36,9 +37,17 @@ const MIN_CREATE_GAS: u64 = 53_000u64;
 impl<Client, Pool, Network> EthApi<Client, Pool, Network>
 where
     Pool: TransactionPool + Clone + 'static,
-    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + 'static,
+    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + 'static,
     Network: Send + Sync + 'static,
 {
+    /// Returns the inspector that applies the custom precompiles and opcode gas of the spec to
+    /// calls.
+    fn call_inspector(&self, spec_id: SpecId) -> InspectorStack {
+        let mut inspector = InspectorStack::default();
+        inspector.set_evm_config(self.evm_config(), spec_id);
+        inspector
+    }
+
     /// Executes the call request at the given [BlockId]
     pub(crate) async fn call_at(
         &self,
@@ -77,7 +86,8 @@ where
             apply_state_overrides(state_overrides, &mut db)?;
         }
 
-        transact(&mut db, env)
+        let mut inspector = self.call_inspector(env.cfg.spec_id);
+        transact(&mut db, env, &mut inspector)
     }
 
     /// Estimate gas needed for execution of the `request` at the [BlockId].
@@ -88,7 +98,67 @@ where
     ) -> EthResult<U256> {
         let (cfg, block_env, at) = self.evm_env_at(at).await?;
         let state = self.state_at_block_id(at)?.ok_or_else(|| EthApiError::UnknownBlockNumber)?;
-        self.estimate_gas_with(cfg, block_env, request, &*state)
+        #[cfg(feature = "optimism")]
+        let l1_data_fee_request =
+            self.estimate_l1_data_fee.then(|| (request.clone(), block_env.clone()));
+        let gas = self.estimate_gas_with(cfg, block_env, request, &*state)?;
+        #[cfg(feature = "optimism")]
+        if let Some((request, block)) = l1_data_fee_request {
+            return Ok(gas + self.l1_data_fee_gas(&request, gas, &block, &*state)?)
+        }
+        Ok(gas)
+    }
+
+    /// Returns the gas that pays the L1 data fee of the transaction of the `request` with the
+    /// estimated `gas` limit, at the price per gas of the request or else the base fee.
+    ///
+    /// Like the `GasPriceOracle` predeploy, the data gas of the signature the transaction doesn't
+    /// have yet is added to the data gas of the unsigned transaction.
+    #[cfg(feature = "optimism")]
+    fn l1_data_fee_gas<S>(
+        &self,
+        request: &CallRequest,
+        gas: U256,
+        block: &BlockEnv,
+        state: S,
+    ) -> EthResult<U256>
+    where
+        S: StateProvider,
+    {
+        use reth_primitives::{Transaction, TransactionKind, TxEip1559};
+        use reth_revm::optimism::{data_gas, L1BlockInfo, UNSIGNED_TX_SIGNATURE_DATA_GAS};
+        use reth_rlp::Encodable;
+
+        let gas_price =
+            request.gas_price.or(request.max_fee_per_gas).map(U256::from).unwrap_or(block.basefee);
+        if gas_price.is_zero() {
+            return Ok(U256::ZERO)
+        }
+
+        let nonce = match request.nonce {
+            Some(nonce) => nonce.try_into().unwrap_or(u64::MAX),
+            None => state.account_nonce(request.from.unwrap_or_default())?.unwrap_or_default(),
+        };
+        let tx = Transaction::Eip1559(TxEip1559 {
+            chain_id: self.client().chain_spec().chain().id(),
+            nonce,
+            gas_limit: gas.try_into().unwrap_or(u64::MAX),
+            max_fee_per_gas: request.max_fee_per_gas.unwrap_or_default().to(),
+            max_priority_fee_per_gas: request.max_priority_fee_per_gas.unwrap_or_default().to(),
+            to: request.to.map(TransactionKind::Call).unwrap_or(TransactionKind::Create),
+            value: request.value.unwrap_or_default().try_into().unwrap_or(u128::MAX),
+            access_list: request.access_list.clone().unwrap_or_default().into(),
+            input: request.data.clone().unwrap_or_default(),
+        });
+        let mut unsigned = Vec::new();
+        tx.encode(&mut unsigned);
+
+        let chain_spec = self.client().chain_spec();
+        let timestamp = block.timestamp.try_into().unwrap_or(u64::MAX);
+        let data_gas = data_gas(&unsigned, &chain_spec, timestamp) +
+            U256::from(UNSIGNED_TX_SIGNATURE_DATA_GAS);
+        let l1_fee = L1BlockInfo::from_state(&state)?.l1_fee(data_gas, &chain_spec, timestamp);
+        Ok((l1_fee + gas_price - U256::from(1)) / gas_price)
     }
 
     /// Estimates the gas usage of the `request` with the state.
@@ -116,6 +186,7 @@ where
         // Configure the evm env
         let mut env = build_call_evm_env(cfg, block, request)?;
         let mut db = SubState::new(State::new(state));
+        let mut inspector = self.call_inspector(env.cfg.spec_id);
 
         // if the request is a simple transfer we can optimize
         if env.tx.data.is_empty() {
@@ -126,7 +197,7 @@ where
                         // simple transfer, check if caller has sufficient funds
                         let available_funds =
                             db.basic(env.tx.caller)?.map(|acc| acc.balance).unwrap_or_default();
-                        if env.tx.value > available_funds {
+                        if env.tx.value > available_funds && !env.cfg.disable_balance_check {
                             return Err(InvalidTransactionError::InsufficientFundsForTransfer.into())
                         }
                         return Ok(U256::from(MIN_TRANSACTION_GAS))
@@ -161,7 +232,7 @@ where
         env.block.gas_limit = gas_limit;
 
         // execute the call without writing to db
-        let (res, mut env) = transact(&mut db, env)?;
+        let (res, mut env) = transact(&mut db, env, &mut inspector)?;
         match res.result {
             ExecutionResult::Success { .. } => {
                 // succeeded
@@ -181,7 +252,7 @@ where
                 return if request_gas.is_some() || request_gas_price.is_some() {
                     let req_gas_limit = env.tx.gas_limit;
                     env.tx.gas_limit = env_gas_limit.try_into().unwrap_or(u64::MAX);
-                    let (res, _) = transact(&mut db, env)?;
+                    let (res, _) = transact(&mut db, env, &mut inspector)?;
                     match res.result {
                         ExecutionResult::Success { .. } => {
                             // transaction succeeded by manually increasing the gas limit to
@@ -225,7 +296,7 @@ where
         while (highest_gas_limit - lowest_gas_limit) > 1 {
             let mut env = env.clone();
             env.tx.gas_limit = mid_gas_limit;
-            let (res, _) = transact(&mut db, env)?;
+            let (res, _) = transact(&mut db, env, &mut inspector)?;
             match res.result {
                 ExecutionResult::Success { .. } => {
                     // cap the highest gas limit with succeeding gas limit
@@ -290,9 +361,10 @@ where
             get_contract_address(from, nonce).into()
         };
 
-        let initial = request.access_list.clone().unwrap_or_default();
+        let initial = request.access_list.clone().map(Into::into).unwrap_or_default();
 
-        let precompiles = get_precompiles(&env.cfg.spec_id);
+        let mut precompiles = get_precompiles(&env.cfg.spec_id);
+        precompiles.extend(self.evm_config().precompiles(env.cfg.spec_id).into_keys());
         let mut inspector = AccessListInspector::new(initial, from, to, precompiles);
         let (result, _env) = inspect(&mut db, env, &mut inspector)?;
 
@@ -306,75 +378,83 @@ where
             }
             ExecutionResult::Success { .. } => Ok(()),
         }?;
-        Ok(inspector.into_access_list())
+        Ok(inspector.into_access_list().into())
     }
 }
 
-/// Applies the given state overrides (a set of [AccountOverride]) to the [CacheDB].
-fn apply_state_overrides<DB>(overrides: StateOverride, db: &mut CacheDB<DB>) -> EthResult<()>
-where
-    DB: DatabaseRef,
-    EthApiError: From<<DB as DatabaseRef>::Error>,
-{
-    for (account, account_overrides) in overrides {
-        apply_account_override(account, account_overrides, db)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::{cache::EthStateCache, error::EthApiError};
+    use reth_primitives::{Address, H160, U128};
+    use reth_provider::test_utils::MockEthProvider;
+    use reth_transaction_pool::test_utils::{testing_pool, TestPool};
+
+    const ONE_ETH: u128 = 1_000_000_000_000_000_000;
+
+    fn eth_api(provider: MockEthProvider) -> EthApi<MockEthProvider, TestPool, ()> {
+        EthApi::new(
+            provider.clone(),
+            testing_pool(),
+            (),
+            EthStateCache::spawn(provider, Default::default()),
+        )
     }
-    Ok(())
-}
-
-/// Applies a single [AccountOverride] to the [CacheDB].
-fn apply_account_override<DB>(
-    account: Address,
-    account_override: AccountOverride,
-    db: &mut CacheDB<DB>,
-) -> EthResult<()>
-where
-    DB: DatabaseRef,
-    EthApiError: From<<DB as DatabaseRef>::Error>,
-{
-    let mut account_info = db.basic(account)?.unwrap_or_default();
 
-    if let Some(nonce) = account_override.nonce {
-        account_info.nonce = nonce;
-    }
-    if let Some(code) = account_override.code {
-        account_info.code = Some(Bytecode::new_raw(code.0));
-    }
-    if let Some(balance) = account_override.balance {
-        account_info.balance = balance;
+    fn transfer(gas_price: Option<U128>) -> CallRequest {
+        CallRequest {
+            to: Some(H160::random()),
+            value: Some(U256::from(ONE_ETH)),
+            gas_price,
+            ..Default::default()
+        }
     }
 
-    db.insert_account_info(account, account_info);
-
-    // We ensure that not both state and state_diff are set.
-    // If state is set, we must mark the account as "NewlyCreated", so that the old storage
-    // isn't read from
-    match (account_override.state, account_override.state_diff) {
-        (Some(_), Some(_)) => return Err(EthApiError::BothStateAndStateDiffInOverride(account)),
-        (None, None) => {
-            // nothing to do
-        }
-        (Some(new_account_state), None) => {
-            db.replace_account_storage(
-                account,
-                new_account_state
-                    .into_iter()
-                    .map(|(slot, value)| {
-                        (U256::from_be_bytes(slot.0), U256::from_be_bytes(value.0))
-                    })
-                    .collect(),
-            )?;
-        }
-        (None, Some(account_state_diff)) => {
-            for (slot, value) in account_state_diff {
-                db.insert_account_storage(
-                    account,
-                    U256::from_be_bytes(slot.0),
-                    U256::from_be_bytes(value.0),
-                )?;
-            }
-        }
-    };
+    #[tokio::test]
+    async fn call_from_implicit_sender_skips_balance_check() {
+        let provider = MockEthProvider::default();
+        let eth_api = eth_api(provider.clone());
+
+        let (res, env) = eth_api
+            .call_with(CfgEnv::default(), BlockEnv::default(), transfer(None), &provider, None)
+            .unwrap();
+        assert!(res.result.is_success());
+        assert_eq!(env.tx.caller, Address::zero());
+
+        // an explicit gas price is paid from the actual balance of the sender
+        let err = eth_api
+            .call_with(
+                CfgEnv::default(),
+                BlockEnv::default(),
+                transfer(Some(U128::from(1))),
+                &provider,
+                None,
+            )
+            .unwrap_err();
+        assert!(matches!(err, EthApiError::InvalidTransaction(_)));
+    }
 
-    Ok(())
+    #[tokio::test]
+    async fn estimate_gas_from_implicit_sender_skips_balance_check() {
+        let provider = MockEthProvider::default();
+        let eth_api = eth_api(provider.clone());
+
+        let gas = eth_api
+            .estimate_gas_with(CfgEnv::default(), BlockEnv::default(), transfer(None), &provider)
+            .unwrap();
+        assert_eq!(gas, U256::from(MIN_TRANSACTION_GAS));
+
+        let err = eth_api
+            .estimate_gas_with(
+                CfgEnv::default(),
+                BlockEnv::default(),
+                transfer(Some(U128::from(1))),
+                &provider,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            EthApiError::InvalidTransaction(InvalidTransactionError::InsufficientFundsForTransfer)
+        ));
+    }
 }
@@ -0,0 +1,130 @@
+//! Contains RPC handler implementations for fee history.
+
+use crate::{
+    eth::{
+        error::{EthApiError, EthResult},
+        fee_history::FeeHistoryEntry,
+    },
+    EthApi,
+};
+use reth_primitives::{BlockId, BlockNumber, U256};
+use reth_provider::{BlockProvider, EvmEnvProvider, StateProviderFactory};
+use reth_rpc_types::FeeHistory;
+
+impl<Client, Pool, Network> EthApi<Client, Pool, Network>
+where
+    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + 'static,
+{
+    /// Returns the fee history of the `block_count` blocks before the newest block, and of the
+    /// newest block.
+    ///
+    /// The fee data of the blocks in the
+    /// [FeeHistoryCache](crate::eth::fee_history::FeeHistoryCache) is taken from the cache, the
+    /// fee data of all other blocks is read from the database.
+    pub(crate) async fn fee_history_for_blocks(
+        &self,
+        block_count: u64,
+        newest_block: BlockId,
+        reward_percentiles: Option<Vec<f64>>,
+    ) -> EthResult<FeeHistory> {
+        if block_count == 0 {
+            return Ok(FeeHistory::default())
+        }
+
+        let Some(end_block) = self.client().block_number_for_id(newest_block)? else {
+            return Err(EthApiError::UnknownBlockNumber)
+        };
+        if end_block < block_count {
+            return Err(EthApiError::InvalidBlockRange)
+        }
+
+        if let Some(percentiles) = &reward_percentiles {
+            let in_range = percentiles.iter().all(|p| (0.0..=100.0).contains(p));
+            if !in_range || percentiles.windows(2).any(|w| w[0] > w[1]) {
+                return Err(EthApiError::InvalidRewardPercentiles)
+            }
+        }
+
+        let start_block = end_block - block_count;
+        let mut entries = self.fee_history_cache.range(start_block..=end_block);
+        for number in start_block..=end_block {
+            if !entries.contains_key(&number) {
+                let entry = self
+                    .fee_history_entry(number, reward_percentiles.is_some())?
+                    .ok_or(EthApiError::InvalidBlockRange)?;
+                entries.insert(number, entry);
+            }
+        }
+
+        let oldest_block_hash = entries[&start_block].hash;
+        Ok(FeeHistory {
+            base_fee_per_gas: entries
+                .values()
+                .map(|entry| U256::from(entry.base_fee_per_gas))
+                .collect(),
+            gas_used_ratio: entries.values().map(|entry| entry.gas_used_ratio).collect(),
+            oldest_block: U256::from_be_bytes(oldest_block_hash.0),
+            reward: reward_percentiles.map(|percentiles| {
+                entries.values().map(|entry| entry.rewards(&percentiles)).collect()
+            }),
+        })
+    }
+
+    /// Returns the suggested priority fee per gas, see
+    /// [GasPriceOracle](crate::eth::gas_oracle::GasPriceOracle).
+    ///
+    /// The fee data of the recent blocks is taken from the
+    /// [FeeHistoryCache](crate::eth::fee_history::FeeHistoryCache) where possible.
+    pub(crate) fn suggested_priority_fee(&self) -> EthResult<U256> {
+        let config = self.gas_oracle().config();
+        let latest = self.client().chain_info()?.best_number;
+        let start = latest.saturating_sub(config.max_lookback_blocks() - 1);
+        let mut cached = self.fee_history_cache.range(start..=latest);
+
+        // walk back until enough non-empty blocks are found, keeping at least the latest block
+        // and its parent for the base fee change
+        let mut entries = Vec::new();
+        let mut non_empty_blocks = 0;
+        for number in (start..=latest).rev() {
+            let entry = match cached.remove(&number) {
+                Some(entry) => entry,
+                None => match self.fee_history_entry(number, true)? {
+                    Some(entry) => entry,
+                    None => break,
+                },
+            };
+            if !entry.rewards.is_empty() {
+                non_empty_blocks += 1;
+            }
+            entries.push(entry);
+            if non_empty_blocks >= config.blocks && entries.len() >= 2 {
+                break
+            }
+        }
+        entries.reverse();
+
+        Ok(U256::from(self.gas_oracle().suggest_priority_fee(&entries)))
+    }
+
+    /// Reads the fee data of the block from the database, if the block exists.
+    ///
+    /// The transactions and receipts of the block are only read if `with_rewards` is set,
+    /// otherwise the entry has no rewards.
+    fn fee_history_entry(
+        &self,
+        number: BlockNumber,
+        with_rewards: bool,
+    ) -> EthResult<Option<FeeHistoryEntry>> {
+        let Some(header) = self.client().sealed_header(number)? else { return Ok(None) };
+        if !with_rewards {
+            return Ok(Some(FeeHistoryEntry::new(&header, &[], &[])))
+        }
+
+        let Some(block) = self.client().block(number.into())? else { return Ok(None) };
+        let receipts = self.client().receipts_by_block(number.into())?.unwrap_or_default();
+        if receipts.len() != block.body.len() {
+            return Err(EthApiError::InvalidBlockRange)
+        }
+        Ok(Some(FeeHistoryEntry::new(&header, &block.body, &receipts)))
+    }
+}
@@ -3,30 +3,35 @@
 //! The entire implementation of the namespace is quite large, hence it is divided across several
 //! files.
 
-use crate::eth::{cache::EthStateCache, error::EthResult, signer::EthSigner};
+use crate::eth::{
+    bytes_limit::BytesLimits,
+    cache::EthStateCache,
+    error::{EthApiError, EthResult},
+    fee_history::FeeHistoryCache,
+    gas_oracle::{GasPriceOracle, GasPriceOracleConfig},
+    signer::EthSigner,
+};
 use async_trait::async_trait;
-use reth_interfaces::Result;
+use reth_interfaces::{blockchain_tree::BlockchainTreeViewer, Result};
 use reth_network_api::NetworkInfo;
 use reth_primitives::{Address, BlockId, BlockNumberOrTag, ChainInfo, H256, U64};
 use reth_provider::{
-    providers::ChainState, BlockProvider, EvmEnvProvider, StateProvider as StateProviderTrait,
-    StateProviderFactory,
+    providers::ChainState, BlockProvider, CanonicalTip, ChainSpecProvider, EvmEnvProvider,
+    StateProvider as StateProviderTrait, StateProviderFactory,
 };
-use reth_rpc_types::FeeHistoryCache;
+use reth_revm::{EthEvmConfig, EvmConfig};
 use reth_transaction_pool::TransactionPool;
-use std::{num::NonZeroUsize, ops::Deref, sync::Arc};
+use std::{ops::Deref, sync::Arc};
 
 mod block;
 mod call;
+mod fees;
 mod server;
 mod sign;
 mod state;
 mod transactions;
 pub use transactions::{EthTransactions, TransactionSource};
 
-/// Cache limit of block-level fee history for `eth_feeHistory` RPC method.
-const FEE_HISTORY_CACHE_LIMIT: usize = 2048;
-
 /// `Eth` API trait.
 ///
 /// Defines core functionality of the `eth` API implementation.
@@ -38,6 +43,9 @@ pub trait EthApiSpec: EthTransactions + Send + Sync {
     /// Returns the chain id
     fn chain_id(&self) -> U64;
 
+    /// Returns the address that receives the fees of the blocks built by the client.
+    fn author(&self) -> EthResult<Address>;
+
     /// Returns client chain info
     fn chain_info(&self) -> Result<ChainInfo>;
 
@@ -58,19 +66,129 @@ pub struct EthApi<Client, Pool, Network> {
     /// All nested fields bundled together.
     inner: Arc<EthApiInner<Client, Pool, Network>>,
     fee_history_cache: FeeHistoryCache,
+    /// Suggests the priority fees of `eth_maxPriorityFeePerGas`.
+    gas_oracle: GasPriceOracle,
+    /// The fee recipient of the blocks built by the client, if any.
+    fee_recipient: Option<Address>,
+    /// The blockchain tree, serves the transactions of blocks that are not yet canonical.
+    blockchain_tree: Option<Arc<dyn BlockchainTreeViewer>>,
+    /// The tip of the canonical chain, if it is tracked in memory.
+    canonical_tip: Option<CanonicalTip>,
+    /// Max lengths of the call data and raw transaction params.
+    bytes_limits: BytesLimits,
+    /// Whether `eth_estimateGas` adds the gas that pays the L1 data fee of the transaction.
+    #[cfg(feature = "optimism")]
+    estimate_l1_data_fee: bool,
 }
 
-impl<Client, Pool, Network> EthApi<Client, Pool, Network> {
+impl<Client, Pool, Network> EthApi<Client, Pool, Network>
+where
+    Client: BlockProvider,
+{
     /// Creates a new, shareable instance.
     pub fn new(client: Client, pool: Pool, network: Network, eth_cache: EthStateCache) -> Self {
-        let inner = EthApiInner { client, pool, network, signers: Default::default(), eth_cache };
+        Self::with_evm_config(client, pool, network, eth_cache, Arc::new(EthEvmConfig::default()))
+    }
+
+    /// Creates a new, shareable instance that executes calls with the given [EvmConfig].
+    pub fn with_evm_config(
+        client: Client,
+        pool: Pool,
+        network: Network,
+        eth_cache: EthStateCache,
+        evm_config: Arc<dyn EvmConfig>,
+    ) -> Self {
+        // the block the node started at, reported by `eth_syncing`
+        let starting_block = client.chain_info().map(|info| info.best_number).unwrap_or_default();
+        let inner = EthApiInner {
+            client,
+            pool,
+            network,
+            signers: Default::default(),
+            eth_cache,
+            evm_config,
+            starting_block,
+        };
         Self {
             inner: Arc::new(inner),
-            fee_history_cache: FeeHistoryCache::new(
-                NonZeroUsize::new(FEE_HISTORY_CACHE_LIMIT).unwrap(),
-            ),
+            fee_history_cache: FeeHistoryCache::new(Default::default()),
+            gas_oracle: Default::default(),
+            fee_recipient: None,
+            blockchain_tree: None,
+            canonical_tip: None,
+            bytes_limits: Default::default(),
+            #[cfg(feature = "optimism")]
+            estimate_l1_data_fee: false,
         }
     }
+}
+
+impl<Client, Pool, Network> EthApi<Client, Pool, Network> {
+
+    /// Sets the fee recipient of the blocks built by the client, returned by `eth_coinbase`.
+    pub fn with_fee_recipient(mut self, fee_recipient: Address) -> Self {
+        self.fee_recipient = Some(fee_recipient);
+        self
+    }
+
+    /// Sets the blockchain tree, so that the transactions of blocks that are inserted but not yet
+    /// canonical, e.g. after `engine_newPayload`, are returned by `eth_getTransactionByHash`.
+    pub fn with_blockchain_tree(mut self, blockchain_tree: Arc<dyn BlockchainTreeViewer>) -> Self {
+        self.blockchain_tree = Some(blockchain_tree);
+        self
+    }
+
+    /// Sets the in-memory tip of the canonical chain, which serves `eth_blockNumber` and resolves
+    /// the `latest` block without a database read once it is set.
+    pub fn with_canonical_tip(mut self, canonical_tip: CanonicalTip) -> Self {
+        self.canonical_tip = Some(canonical_tip);
+        self
+    }
+
+    /// Makes `eth_estimateGas` add the gas that pays the fee for posting the data of the
+    /// transaction to L1 at the price per gas of the request, like wallets of Optimism chains
+    /// expect.
+    #[cfg(feature = "optimism")]
+    pub fn with_l1_data_fee_estimation(mut self, estimate_l1_data_fee: bool) -> Self {
+        self.estimate_l1_data_fee = estimate_l1_data_fee;
+        self
+    }
+
+    /// Sets the cache of the fee data of recent blocks used by `eth_feeHistory`.
+    pub fn with_fee_history_cache(mut self, fee_history_cache: FeeHistoryCache) -> Self {
+        self.fee_history_cache = fee_history_cache;
+        self
+    }
+
+    /// Sets the max lengths of the call data and raw transaction params the handlers accept.
+    pub fn with_bytes_limits(mut self, bytes_limits: BytesLimits) -> Self {
+        self.bytes_limits = bytes_limits;
+        self
+    }
+
+    /// Returns the max lengths of the call data and raw transaction params.
+    pub fn bytes_limits(&self) -> &BytesLimits {
+        &self.bytes_limits
+    }
+
+    /// Sets the settings of the oracle that suggests priority fees.
+    pub fn with_gas_oracle_config(mut self, config: GasPriceOracleConfig) -> Self {
+        self.gas_oracle = GasPriceOracle::new(config);
+        self
+    }
+
+    /// Returns the oracle that suggests priority fees.
+    pub fn gas_oracle(&self) -> &GasPriceOracle {
+        &self.gas_oracle
+    }
+
+    /// Returns the cache of the fee data of recent blocks.
+    ///
+    /// The cache is only filled by canonical state notifications, see
+    /// [fee_history_cache_new_blocks_task](crate::eth::fee_history::fee_history_cache_new_blocks_task).
+    pub fn fee_history_cache(&self) -> &FeeHistoryCache {
+        &self.fee_history_cache
+    }
 
     /// Returns the state cache frontend
     pub(crate) fn cache(&self) -> &EthStateCache {
@@ -82,6 +200,11 @@ impl<Client, Pool, Network> EthApi<Client, Pool, Network> {
         &self.inner.client
     }
 
+    /// Returns the blockchain tree, if any
+    pub(crate) fn blockchain_tree(&self) -> Option<&dyn BlockchainTreeViewer> {
+        self.blockchain_tree.as_deref()
+    }
+
     /// Returns the inner `Network`
     pub(crate) fn network(&self) -> &Network {
         &self.inner.network
@@ -91,6 +214,16 @@ impl<Client, Pool, Network> EthApi<Client, Pool, Network> {
     pub(crate) fn pool(&self) -> &Pool {
         &self.inner.pool
     }
+
+    /// Returns the configuration of the EVM
+    pub(crate) fn evm_config(&self) -> &dyn EvmConfig {
+        &*self.inner.evm_config
+    }
+
+    /// Returns the block the node started at
+    pub(crate) fn starting_block(&self) -> u64 {
+        self.inner.starting_block
+    }
 }
 
 // Transparent wrapper to enable state access helpers
@@ -130,8 +263,19 @@ impl<Client, Pool, Network> EthApi<Client, Pool, Network>
 where
     Client: BlockProvider + StateProviderFactory + EvmEnvProvider + 'static,
 {
+    /// Returns the number of the canonical tip, from the in-memory tip if it is set.
+    pub(crate) fn best_block_number(&self) -> Result<u64> {
+        match self.canonical_tip.as_ref().and_then(CanonicalTip::number) {
+            Some(number) => Ok(number),
+            None => Ok(self.client().chain_info()?.best_number),
+        }
+    }
+
     fn convert_block_number(&self, num: BlockNumberOrTag) -> Result<Option<u64>> {
-        self.client().convert_block_number(num)
+        match num {
+            BlockNumberOrTag::Latest => self.best_block_number().map(Some),
+            num => self.client().convert_block_number(num),
+        }
     }
 
     /// Helper function to execute a closure with the database at a specific block.
@@ -213,20 +357,25 @@ impl<Client, Pool, Events> std::fmt::Debug for EthApi<Client, Pool, Events> {
 impl<Client, Pool, Network> EthApiSpec for EthApi<Client, Pool, Network>
 where
     Pool: TransactionPool + Clone + 'static,
-    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + 'static,
+    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + 'static,
     Network: NetworkInfo + 'static,
 {
-    /// Returns the current ethereum protocol version.
+    /// Returns the highest `eth` protocol version the network negotiates with its peers.
     ///
     /// Note: This returns an `U64`, since this should return as hex string.
     async fn protocol_version(&self) -> Result<U64> {
         let status = self.network().network_status().await?;
-        Ok(U64::from(status.protocol_version))
+        Ok(U64::from(status.eth_protocol_version().unwrap_or_default()))
     }
 
-    /// Returns the chain id
+    /// Returns the chain id of the chain spec
     fn chain_id(&self) -> U64 {
-        U64::from(self.network().chain_id())
+        U64::from(self.client().chain_spec().chain().id())
+    }
+
+    /// Returns the configured fee recipient
+    fn author(&self) -> EthResult<Address> {
+        self.fee_recipient.ok_or(EthApiError::EtherbaseNotSet)
     }
 
     /// Returns the current info for the chain
@@ -251,4 +400,8 @@ struct EthApiInner<Client, Pool, Network> {
     signers: Vec<Box<dyn EthSigner>>,
     /// The async cache frontend for eth related data
     eth_cache: EthStateCache,
+    /// The configuration of the EVM that executes calls
+    evm_config: Arc<dyn EvmConfig>,
+    /// The number of the canonical tip when the instance was created
+    starting_block: u64,
 }
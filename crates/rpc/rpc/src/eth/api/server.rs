@@ -10,27 +10,32 @@ use crate::{
     result::{internal_rpc_err, ToRpcResult},
 };
 use jsonrpsee::core::RpcResult as Result;
-use reth_primitives::{
-    AccessListWithGasUsed, Address, BlockId, BlockNumberOrTag, Bytes, Header, H256, H64, U256, U64,
+use reth_interfaces::sync::{SyncState, SyncStateProvider};
+use reth_primitives::{Address, BlockId, BlockNumberOrTag, Bytes, H256, H64, U256, U64};
+use reth_provider::{
+    BlockProvider, ChainSpecProvider, EvmEnvProvider, HeaderProvider, StateProviderFactory,
 };
-use reth_provider::{BlockProvider, EvmEnvProvider, HeaderProvider, StateProviderFactory};
 use reth_rpc_api::EthApiServer;
 use reth_rpc_types::{
-    state::StateOverride, CallRequest, EIP1186AccountProofResponse, FeeHistory,
-    FeeHistoryCacheItem, Index, RichBlock, SyncStatus, TransactionReceipt, TransactionRequest,
+    state::StateOverride, AccessListWithGasUsed, CallRequest, EIP1186AccountProofResponse,
+    FeeHistory, Index, RichBlock, SyncInfo, SyncStatus, TransactionReceipt, TransactionRequest,
     Work,
 };
 use reth_transaction_pool::TransactionPool;
 use serde_json::Value;
-use std::collections::BTreeMap;
 
 #[async_trait::async_trait]
 impl<Client, Pool, Network> EthApiServer for EthApi<Client, Pool, Network>
 where
     Self: EthApiSpec + EthTransactions,
     Pool: TransactionPool + 'static,
-    Client: BlockProvider + HeaderProvider + StateProviderFactory + EvmEnvProvider + 'static,
-    Network: Send + Sync + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + EvmEnvProvider
+        + ChainSpecProvider
+        + 'static,
+    Network: SyncStateProvider + 'static,
 {
     /// Handler for: `eth_protocolVersion`
     async fn protocol_version(&self) -> Result<U64> {
@@ -39,12 +44,26 @@ where
 
     /// Handler for: `eth_syncing`
     fn syncing(&self) -> Result<SyncStatus> {
-        Err(internal_rpc_err("unimplemented"))
+        let highest_block = match *self.network().subscribe_sync_state().borrow() {
+            SyncState::Idle => return Ok(SyncStatus::None),
+            SyncState::Downloading { target_block } | SyncState::Executing { target_block } => {
+                target_block
+            }
+        };
+        let current_block =
+            self.best_block_number().with_message("failed to read the current block")?;
+        Ok(SyncStatus::Info(SyncInfo {
+            starting_block: U256::from(self.starting_block()),
+            current_block: U256::from(current_block),
+            highest_block: U256::from(highest_block),
+            warp_chunks_amount: None,
+            warp_chunks_processed: None,
+        }))
     }
 
     /// Handler for: `eth_coinbase`
     async fn author(&self) -> Result<Address> {
-        Err(internal_rpc_err("unimplemented"))
+        Ok(EthApiSpec::author(self)?)
     }
 
     /// Handler for: `eth_accounts`
@@ -54,9 +73,7 @@ where
 
     /// Handler for: `eth_blockNumber`
     fn block_number(&self) -> Result<U256> {
-        Ok(U256::from(
-            EthApiSpec::chain_info(self).with_message("failed to read chain info")?.best_number,
-        ))
+        Ok(U256::from(self.best_block_number().with_message("failed to read chain info")?))
     }
 
     /// Handler for: `eth_chainId`
@@ -143,8 +160,8 @@ where
     }
 
     /// Handler for: `eth_getTransactionReceipt`
-    async fn transaction_receipt(&self, _hash: H256) -> Result<Option<TransactionReceipt>> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn transaction_receipt(&self, hash: H256) -> Result<Option<TransactionReceipt>> {
+        Ok(EthApi::transaction_receipt(self, hash).await?)
     }
 
     /// Handler for: `eth_getBalance`
@@ -192,6 +209,7 @@ where
         mut request: CallRequest,
         block_number: Option<BlockId>,
     ) -> Result<AccessListWithGasUsed> {
+        self.bytes_limits().check_call_data(request.data.as_ref())?;
         let block_id = block_number.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
         let access_list = self.create_access_list_at(request.clone(), block_number).await?;
         request.access_list = Some(access_list.clone());
@@ -213,115 +231,21 @@ where
         Err(internal_rpc_err("unimplemented"))
     }
 
-    // FeeHistory is calculated based on lazy evaluation of fees for historical blocks, and further
-    // caching of it in the LRU cache.
-    // When new RPC call is executed, the cache gets locked, we check it for the historical fees
-    // according to the requested block range, and fill any cache misses (in both RPC response
-    // and cache itself) with the actual data queried from the database.
-    // To minimize the number of database seeks required to query the missing data, we calculate the
-    // first non-cached block number and last non-cached block number. After that, we query this
-    // range of consecutive blocks from the database.
     /// Handler for: `eth_feeHistory`
     async fn fee_history(
         &self,
         block_count: U64,
         newest_block: BlockId,
-        _reward_percentiles: Option<Vec<f64>>,
+        reward_percentiles: Option<Vec<f64>>,
     ) -> Result<FeeHistory> {
-        let block_count = block_count.as_u64();
-
-        if block_count == 0 {
-            return Ok(FeeHistory::default())
-        }
-
-        let Some(end_block) = self.inner.client.block_number_for_id(newest_block).to_rpc_result()? else { return Err(EthApiError::UnknownBlockNumber.into())};
-
-        if end_block < block_count {
-            return Err(EthApiError::InvalidBlockRange.into())
-        }
-
-        let start_block = end_block - block_count;
-
-        let mut fee_history_cache = self.fee_history_cache.0.lock().await;
-
-        // Sorted map that's populated in two rounds:
-        // 1. Cache entries until first non-cached block
-        // 2. Database query from the first non-cached block
-        let mut fee_history_cache_items = BTreeMap::new();
-
-        let mut first_non_cached_block = None;
-        let mut last_non_cached_block = None;
-        for block in start_block..=end_block {
-            // Check if block exists in cache, and move it to the head of the list if so
-            if let Some(fee_history_cache_item) = fee_history_cache.get(&block) {
-                fee_history_cache_items.insert(block, fee_history_cache_item.clone());
-            } else {
-                // If block doesn't exist in cache, set it as a first non-cached block to query it
-                // from the database
-                first_non_cached_block.get_or_insert(block);
-                // And last non-cached block, so we could query the database until we reach it
-                last_non_cached_block = Some(block);
-            }
-        }
-
-        // If we had any cache misses, query the database starting with the first non-cached block
-        // and ending with the last
-        if let (Some(start_block), Some(end_block)) =
-            (first_non_cached_block, last_non_cached_block)
-        {
-            let headers: Vec<Header> =
-                self.inner.client.headers_range(start_block..=end_block).to_rpc_result()?;
-
-            // We should receive exactly the amount of blocks missing from the cache
-            if headers.len() != (end_block - start_block + 1) as usize {
-                return Err(EthApiError::InvalidBlockRange.into())
-            }
-
-            for header in headers {
-                let base_fee_per_gas = header.base_fee_per_gas.
-                        unwrap_or_default(). // Zero for pre-EIP-1559 blocks
-                        try_into().unwrap(); // u64 -> U256 won't fail
-                let gas_used_ratio = header.gas_used as f64 / header.gas_limit as f64;
-
-                let fee_history_cache_item = FeeHistoryCacheItem {
-                    hash: None,
-                    base_fee_per_gas,
-                    gas_used_ratio,
-                    reward: None, // TODO: calculate rewards per transaction
-                };
-
-                // Insert missing cache entries in the map for further response composition from it
-                fee_history_cache_items.insert(header.number, fee_history_cache_item.clone());
-                // And populate the cache with new entries
-                fee_history_cache.push(header.number, fee_history_cache_item);
-            }
-        }
-
-        let oldest_block_hash =
-            self.inner.client.block_hash(start_block.try_into().unwrap()).to_rpc_result()?.unwrap();
-
-        fee_history_cache_items.get_mut(&start_block).unwrap().hash = Some(oldest_block_hash);
-        fee_history_cache.get_mut(&start_block).unwrap().hash = Some(oldest_block_hash);
-
-        // `fee_history_cache_items` now contains full requested block range (populated from both
-        // cache and database), so we can iterate over it in order and populate the response fields
-        Ok(FeeHistory {
-            base_fee_per_gas: fee_history_cache_items
-                .values()
-                .map(|item| item.base_fee_per_gas)
-                .collect(),
-            gas_used_ratio: fee_history_cache_items
-                .values()
-                .map(|item| item.gas_used_ratio)
-                .collect(),
-            oldest_block: U256::from_be_bytes(oldest_block_hash.0),
-            reward: None,
-        })
+        Ok(self
+            .fee_history_for_blocks(block_count.as_u64(), newest_block, reward_percentiles)
+            .await?)
     }
 
     /// Handler for: `eth_maxPriorityFeePerGas`
     async fn max_priority_fee_per_gas(&self) -> Result<U256> {
-        Err(internal_rpc_err("unimplemented"))
+        Ok(self.suggested_priority_fee()?)
     }
 
     /// Handler for: `eth_mining`
@@ -356,6 +280,7 @@ where
 
     /// Handler for: `eth_sendRawTransaction`
     async fn send_raw_transaction(&self, tx: Bytes) -> Result<H256> {
+        self.bytes_limits().check_raw_transaction(&tx)?;
         Ok(EthApi::send_raw_transaction(self, tx).await?)
     }
 
@@ -394,17 +319,28 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::{eth::cache::EthStateCache, EthApi};
+    use crate::{
+        eth::{cache::EthStateCache, fee_history::FeeHistoryCache},
+        EthApi,
+    };
     use jsonrpsee::{
         core::{error::Error as RpcError, RpcResult},
         types::error::{CallError, INVALID_PARAMS_CODE},
     };
     use rand::random;
+    use reth_interfaces::events::{CanonBlock, CanonStateNotification};
     use reth_network_api::test_utils::NoopNetwork;
-    use reth_primitives::{Block, BlockNumberOrTag, Header, H256, U256};
-    use reth_provider::test_utils::{MockEthProvider, NoopProvider};
+    use reth_primitives::{
+        Block, BlockNumberOrTag, Header, Receipt, SealedBlock, Signature, Transaction,
+        TransactionKind, TransactionSigned, TxEip1559, H256, U256,
+    };
+    use reth_provider::{
+        test_utils::{MockEthProvider, NoopProvider},
+        CanonicalTip,
+    };
     use reth_rpc_api::EthApiServer;
     use reth_transaction_pool::test_utils::testing_pool;
+    use std::sync::atomic::Ordering;
 
     #[tokio::test]
     /// Handler for: `eth_test_fee_history`
@@ -474,4 +410,106 @@ mod tests {
         assert_eq!(fee_history.gas_used_ratio, gas_used_ratios);
         assert_eq!(fee_history.oldest_block, U256::from_be_bytes(oldest_block.unwrap().0));
     }
+
+    #[tokio::test]
+    /// Handler for: `eth_feeHistory` served from the fee history cache
+    async fn test_fee_history_cached() {
+        let mock_provider = MockEthProvider::default();
+        let mut blocks = Vec::new();
+        let mut parent_hash = H256::zero();
+        for number in 0..=10 {
+            let base_fee_per_gas: u64 = random::<u32>().into();
+            let mut body = Vec::new();
+            let mut receipts = Vec::new();
+            let mut cumulative_gas_used = 0;
+            for _ in 0..number % 4 {
+                let max_priority_fee_per_gas: u128 = random::<u32>().into();
+                let transaction = Transaction::Eip1559(TxEip1559 {
+                    max_fee_per_gas: base_fee_per_gas as u128 + random::<u32>() as u128,
+                    max_priority_fee_per_gas,
+                    to: TransactionKind::Create,
+                    ..Default::default()
+                });
+                body.push(TransactionSigned::from_transaction_and_signature(
+                    transaction,
+                    Signature::default(),
+                ));
+                cumulative_gas_used += 21_000 + random::<u16>() as u64;
+                receipts.push(Receipt { cumulative_gas_used, ..Default::default() });
+            }
+            let header = Header {
+                number,
+                parent_hash,
+                gas_limit: 30_000_000,
+                gas_used: cumulative_gas_used,
+                base_fee_per_gas: Some(base_fee_per_gas),
+                ..Default::default()
+            };
+            let block = SealedBlock { header: header.seal_slow(), body, ..Default::default() };
+            parent_hash = block.hash();
+
+            mock_provider.add_block(block.hash(), block.clone().unseal());
+            mock_provider.add_receipts(block.hash(), receipts.clone());
+            blocks.push(CanonBlock { block, receipts, storage: Default::default() });
+        }
+
+        let uncached = EthApi::new(
+            mock_provider,
+            testing_pool(),
+            NoopNetwork::default(),
+            EthStateCache::spawn(NoopProvider::default(), Default::default()),
+        );
+        let fee_history_cache = FeeHistoryCache::new(Default::default());
+        fee_history_cache.on_canon_state(&CanonStateNotification::Commit { new: blocks.into() });
+        // the blocks are only in the cache
+        let cached = EthApi::new(
+            MockEthProvider::default(),
+            testing_pool(),
+            NoopNetwork::default(),
+            EthStateCache::spawn(NoopProvider::default(), Default::default()),
+        )
+        .with_fee_history_cache(fee_history_cache);
+
+        let percentiles = vec![0.0, 10.0, 50.0, 90.0, 100.0];
+        let expected =
+            uncached.fee_history(10.into(), 10.into(), Some(percentiles.clone())).await.unwrap();
+        let fee_history =
+            cached.fee_history(10.into(), 10.into(), Some(percentiles)).await.unwrap();
+
+        assert_eq!(fee_history.base_fee_per_gas, expected.base_fee_per_gas);
+        assert_eq!(fee_history.gas_used_ratio, expected.gas_used_ratio);
+        assert_eq!(fee_history.oldest_block, expected.oldest_block);
+        assert_eq!(fee_history.reward, expected.reward);
+        assert_eq!(fee_history.reward.unwrap().len(), 11);
+
+        let response = cached.fee_history(10.into(), 10.into(), Some(vec![50.0, 10.0])).await;
+        let Err(RpcError::Call(CallError::Custom(error_object))) = response else { unreachable!() };
+        assert_eq!(error_object.code(), INVALID_PARAMS_CODE);
+    }
+
+    #[tokio::test]
+    /// Handler for: `eth_blockNumber` served from the canonical tip
+    async fn test_block_number_from_canonical_tip() {
+        let mock_provider = MockEthProvider::default();
+        mock_provider.add_header(H256::random(), Header { number: 5, ..Default::default() });
+        let canonical_tip = CanonicalTip::default();
+        let eth_api = EthApi::new(
+            mock_provider.clone(),
+            testing_pool(),
+            NoopNetwork::default(),
+            EthStateCache::spawn(NoopProvider::default(), Default::default()),
+        )
+        .with_canonical_tip(canonical_tip.clone());
+        let calls = mock_provider.chain_info_calls.load(Ordering::Relaxed);
+
+        // the tip is unset, so the first lookup falls back to the provider
+        assert_eq!(EthApiServer::block_number(&eth_api).unwrap(), U256::from(5));
+        assert_eq!(mock_provider.chain_info_calls.load(Ordering::Relaxed), calls + 1);
+
+        canonical_tip.update(6, H256::random());
+        for _ in 0..10 {
+            assert_eq!(EthApiServer::block_number(&eth_api).unwrap(), U256::from(6));
+        }
+        assert_eq!(mock_provider.chain_info_calls.load(Ordering::Relaxed), calls + 1);
+    }
 }
@@ -1,16 +1,19 @@
 //! Contains RPC handler implementations specific to transactions
 use crate::{
-    eth::error::{EthApiError, EthResult},
+    eth::{
+        error::{EthApiError, EthResult},
+        receipt_utils::build_transaction_receipt,
+    },
     EthApi,
 };
 use async_trait::async_trait;
+use reth_interfaces::provider::ProviderError;
 use reth_primitives::{
     BlockId, BlockNumberOrTag, Bytes, FromRecoveredTransaction, IntoRecoveredTransaction,
     TransactionSigned, TransactionSignedEcRecovered, H256, U256,
 };
-use reth_provider::{BlockProvider, EvmEnvProvider, StateProviderFactory};
-use reth_rlp::Decodable;
-use reth_rpc_types::{Index, Transaction, TransactionRequest};
+use reth_provider::{BlockProvider, ChainSpecProvider, EvmEnvProvider, StateProviderFactory};
+use reth_rpc_types::{Index, Transaction, TransactionReceipt, TransactionRequest};
 use reth_transaction_pool::{TransactionOrigin, TransactionPool};
 use revm::primitives::{BlockEnv, CfgEnv};
 
@@ -25,7 +28,7 @@ pub trait EthTransactions: Send + Sync {
 
     /// Returns the transaction by hash.
     ///
-    /// Checks the pool and state.
+    /// Checks the database, the blocks of the blockchain tree and the pool, in that order.
     ///
     /// Returns `Ok(None)` if no matching transaction was found.
     async fn transaction_by_hash(&self, hash: H256) -> EthResult<Option<TransactionSource>>;
@@ -41,16 +44,25 @@ pub trait EthTransactions: Send + Sync {
 impl<Client, Pool, Network> EthTransactions for EthApi<Client, Pool, Network>
 where
     Pool: TransactionPool + Clone + 'static,
-    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + 'static,
+    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + 'static,
     Network: Send + Sync + 'static,
 {
     async fn evm_env_at(&self, at: BlockId) -> EthResult<(CfgEnv, BlockEnv, BlockId)> {
-        // TODO handle Pending state's env
         match at {
             BlockId::Number(BlockNumberOrTag::Pending) => {
-                // This should perhaps use the latest env settings and update block specific
-                // settings like basefee/number
-                unimplemented!("support pending state env")
+                // the pending block is built on the latest state, with the base fee the pool and
+                // the payload builder derive from the latest block
+                let latest_hash = self.client().chain_info()?.best_hash;
+                let latest = self
+                    .client()
+                    .header(&latest_hash)?
+                    .ok_or_else(|| EthApiError::UnknownBlockNumber)?;
+                let (cfg, mut env) = self.cache().get_evm_env(latest_hash).await?;
+                env.number = U256::from(latest.number + 1);
+                if let Some(base_fee) = self.client().chain_spec().next_block_base_fee(&latest) {
+                    env.basefee = U256::from(base_fee);
+                }
+                Ok((cfg, env, latest_hash.into()))
             }
             hash_or_num => {
                 let block_hash = self
@@ -64,28 +76,33 @@ where
     }
 
     async fn transaction_by_hash(&self, hash: H256) -> EthResult<Option<TransactionSource>> {
-        if let Some(tx) = self.pool().get(&hash).map(|tx| tx.transaction.to_recovered_transaction())
-        {
-            return Ok(Some(TransactionSource::Pool(tx)))
+        if let Some((tx, meta)) = self.client().transaction_by_hash_with_meta(hash)? {
+            let transaction =
+                tx.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)?;
+            return Ok(Some(TransactionSource::Database {
+                transaction,
+                index: meta.index as usize,
+                block_hash: meta.block_hash,
+                block_number: meta.block_number,
+            }))
         }
 
-        match self.client().transaction_by_hash(hash)? {
-            None => Ok(None),
-            Some(tx) => {
-                let transaction =
-                    tx.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)?;
-
-                let tx = TransactionSource::Database {
-                    transaction,
-                    // TODO: this is just stubbed out for now still need to fully implement tx =>
-                    // block
-                    index: 0,
-                    block_hash: Default::default(),
-                    block_number: 0,
-                };
-                Ok(Some(tx))
-            }
+        // the transaction may be included in a block that was inserted but is not yet canonical
+        if let Some((transaction, meta)) =
+            self.blockchain_tree().and_then(|tree| tree.pending_transaction_by_hash(hash))
+        {
+            return Ok(Some(TransactionSource::Database {
+                transaction,
+                index: meta.index as usize,
+                block_hash: meta.block_hash,
+                block_number: meta.block_number,
+            }))
         }
+
+        Ok(self
+            .pool()
+            .get(&hash)
+            .map(|tx| TransactionSource::Pool(tx.transaction.to_recovered_transaction())))
     }
 
     async fn transaction_by_hash_at(
@@ -126,7 +143,7 @@ where
 impl<Client, Pool, Network> EthApi<Client, Pool, Network>
 where
     Pool: TransactionPool + 'static,
-    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + 'static,
+    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + 'static,
     Network: 'static,
 {
     pub(crate) async fn send_transaction(&self, _request: TransactionRequest) -> EthResult<H256> {
@@ -162,17 +179,66 @@ where
         Ok(None)
     }
 
+    /// Returns the receipt of a transaction of the canonical chain.
+    ///
+    /// Returns `Ok(None)` if the transaction is unknown or not yet included in a canonical block.
+    pub(crate) async fn transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> EthResult<Option<TransactionReceipt>> {
+        let Some((transaction, meta)) = self.client().transaction_by_hash_with_meta(hash)? else {
+            return Ok(None)
+        };
+        let transaction =
+            transaction.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)?;
+        let header = self
+            .client()
+            .header_by_number(meta.block_number)?
+            .ok_or(EthApiError::UnknownBlockNumber)?;
+        // the receipt is built from the receipts of the transactions up to it, which must not be
+        // pruned
+        let receipts = self
+            .client()
+            .receipts_by_block_aligned(meta.block_number.into())?
+            .and_then(|receipts| {
+                receipts.into_iter().take(meta.index as usize + 1).collect::<Option<Vec<_>>>()
+            })
+            .filter(|receipts| receipts.len() > meta.index as usize)
+            .ok_or(ProviderError::Receipts { number: meta.block_number })?;
+
+        #[cfg(feature = "optimism")]
+        let encoded = transaction.envelope_encoded();
+        #[cfg_attr(not(feature = "optimism"), allow(unused_mut))]
+        let (_, mut receipt) =
+            build_transaction_receipt(transaction, meta, header.base_fee_per_gas, &receipts);
+
+        // deposit transactions don't pay for their data on L1
+        #[cfg(feature = "optimism")]
+        if receipts[meta.index as usize].tx_type != reth_primitives::TxType::DEPOSIT {
+            use reth_revm::optimism::{data_gas, format_fee_scalar, L1BlockInfo};
+
+            // the first transaction of the block sets the L1 fee parameters of the block, so the
+            // state after the block has them
+            let state = self.client().history_by_block_number(meta.block_number)?;
+            let l1_info = L1BlockInfo::from_state(&state)?;
+            let (chain_spec, timestamp) = (self.client().chain_spec(), header.timestamp);
+            let data_gas = data_gas(&encoded, &chain_spec, timestamp);
+            receipt.l1_fee = Some(l1_info.l1_fee(data_gas, &chain_spec, timestamp));
+            receipt.l1_gas_price = Some(l1_info.l1_base_fee);
+            receipt.l1_gas_used = Some(l1_info.l1_gas_used(data_gas, &chain_spec, timestamp));
+            if !l1_info.is_ecotone(&chain_spec, timestamp) {
+                receipt.l1_fee_scalar = Some(format_fee_scalar(l1_info.l1_fee_scalar));
+            }
+        }
+
+        Ok(Some(receipt))
+    }
+
     /// Decodes and recovers the transaction and submits it to the pool.
     ///
     /// Returns the hash of the transaction.
     pub(crate) async fn send_raw_transaction(&self, tx: Bytes) -> EthResult<H256> {
-        let mut data = tx.as_ref();
-        if data.is_empty() {
-            return Err(EthApiError::EmptyRawTransactionData)
-        }
-
-        let transaction = TransactionSigned::decode(&mut data)
-            .map_err(|_| EthApiError::FailedToDecodeSignedTransaction)?;
+        let transaction = TransactionSigned::decode_enveloped(tx)?;
 
         let recovered =
             transaction.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)?;
@@ -191,7 +257,7 @@ where
 pub enum TransactionSource {
     /// Transaction exists in the pool (Pending)
     Pool(TransactionSignedEcRecovered),
-    /// Transaction already executed
+    /// Transaction already executed, in a canonical block or a block of the blockchain tree
     Database {
         /// Transaction fetched via provider
         transaction: TransactionSignedEcRecovered,
@@ -222,7 +288,7 @@ impl From<TransactionSource> for Transaction {
                     transaction,
                     block_hash,
                     block_number,
-                    U256::from(index),
+                    index,
                 )
             }
         }
@@ -231,13 +297,99 @@ impl From<TransactionSource> for Transaction {
 
 #[cfg(test)]
 mod tests {
-    use crate::eth::cache::EthStateCache;
-    use reth_primitives::{hex_literal::hex, Bytes};
-    use reth_provider::test_utils::NoopProvider;
+    use super::{EthTransactions, TransactionSource};
+    use crate::eth::{cache::EthStateCache, error::EthApiError};
+    use jsonrpsee::{
+        core::Error as RpcError,
+        types::error::{CallError, INVALID_PARAMS_CODE},
+    };
+    use reth_interfaces::blockchain_tree::{BlockchainTreeState, BlockchainTreeViewer, ReorgStats};
+    use reth_primitives::{
+        hex_literal::hex, Block, Bytes, Header, TransactionDecodeError, TransactionMeta,
+        TransactionSigned, TransactionSignedEcRecovered, TxHash, H256,
+    };
+    use reth_provider::test_utils::{MockEthProvider, NoopProvider};
     use reth_transaction_pool::{test_utils::testing_pool, TransactionPool};
+    use std::sync::{Arc, Mutex};
 
     use crate::EthApi;
 
+    /// A blockchain tree with at most one transaction.
+    #[derive(Debug, Default)]
+    struct TestTree(Mutex<Option<(TransactionSignedEcRecovered, TransactionMeta)>>);
+
+    impl BlockchainTreeViewer for TestTree {
+        fn tree_state(&self) -> BlockchainTreeState {
+            unimplemented!()
+        }
+
+        fn reorg_stats(&self) -> ReorgStats {
+            unimplemented!()
+        }
+
+        fn pending_transaction_by_hash(
+            &self,
+            tx_hash: TxHash,
+        ) -> Option<(TransactionSignedEcRecovered, TransactionMeta)> {
+            self.0.lock().unwrap().clone().filter(|(_, meta)| meta.tx_hash == tx_hash)
+        }
+    }
+
+    #[tokio::test]
+    async fn transaction_by_hash_from_pool_tree_and_database() {
+        let provider = MockEthProvider::default();
+        let tree = Arc::new(TestTree::default());
+        let eth_api = EthApi::new(
+            provider.clone(),
+            testing_pool(),
+            (),
+            EthStateCache::spawn(provider.clone(), Default::default()),
+        )
+        .with_blockchain_tree(tree.clone());
+
+        // https://etherscan.io/tx/0xa694b71e6c128a2ed8e2e0f6770bddbe52e3bb8f10e8472f9a79ab81497a8b5d
+        let raw = Bytes::from(hex!("02f871018303579880850555633d1b82520894eee27662c2b8eba3cd936a23f039f3189633e4c887ad591c62bdaeb180c080a07ea72c68abfb8fca1bd964f0f99132ed9280261bdca3e549546c0205e800f7d0a05b4ef3039e9c9b9babc179a1878fb825b5aaf5aed2fa8744854150157b08d6f3"));
+        let tx = TransactionSigned::decode_enveloped(raw.clone()).unwrap();
+        let recovered = tx.clone().into_ecrecovered().unwrap();
+        let hash = eth_api.send_raw_transaction(raw).await.unwrap();
+        assert_eq!(
+            eth_api.transaction_by_hash(hash).await.unwrap(),
+            Some(TransactionSource::Pool(recovered.clone()))
+        );
+
+        // the transaction is included in a block of the tree after `engine_newPayload`
+        let tree_meta = TransactionMeta {
+            tx_hash: hash,
+            index: 3,
+            block_hash: H256::random(),
+            block_number: 7,
+        };
+        *tree.0.lock().unwrap() = Some((recovered.clone(), tree_meta));
+        assert_eq!(
+            eth_api.transaction_by_hash(hash).await.unwrap(),
+            Some(TransactionSource::Database {
+                transaction: recovered.clone(),
+                index: 3,
+                block_hash: tree_meta.block_hash,
+                block_number: 7,
+            })
+        );
+
+        // the canonical block is preferred once the transaction is in the database
+        let block_hash = H256::random();
+        let header = Header { number: 8, ..Default::default() };
+        provider.add_block(block_hash, Block { header, body: vec![tx], ..Default::default() });
+        assert_eq!(
+            eth_api.transaction_by_hash(hash).await.unwrap(),
+            Some(TransactionSource::Database {
+                transaction: recovered,
+                index: 0,
+                block_hash,
+                block_number: 8,
+            })
+        );
+    }
+
     #[tokio::test]
     async fn send_raw_transaction() {
         let noop_provider = NoopProvider::default();
@@ -276,4 +428,29 @@ mod tests {
         assert!(pool.get(&tx_1_result).is_some(), "tx1 not found in the pool");
         assert!(pool.get(&tx_2_result).is_some(), "tx2 not found in the pool");
     }
+
+    #[tokio::test]
+    async fn send_invalid_raw_transaction() {
+        let eth_api = EthApi::new(
+            NoopProvider::default(),
+            testing_pool(),
+            (),
+            EthStateCache::spawn(NoopProvider::default(), Default::default()),
+        );
+
+        let err = eth_api.send_raw_transaction(Bytes::default()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            EthApiError::InvalidRawTransaction(TransactionDecodeError::EmptyInput)
+        ));
+
+        // truncated EIP-1559 transaction
+        let err = eth_api.send_raw_transaction(Bytes::from(hex!("02f871"))).await.unwrap_err();
+        let RpcError::Call(CallError::Custom(err)) = RpcError::from(err) else {
+            panic!("expected a call error")
+        };
+        assert_eq!(err.code(), INVALID_PARAMS_CODE);
+        assert_eq!(err.message(), "rlp: input too short at offset 1");
+        assert_eq!(err.data().map(|data| data.get()), Some(r#"{"offset":1}"#));
+    }
 }
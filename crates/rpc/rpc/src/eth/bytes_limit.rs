@@ -0,0 +1,77 @@
+//! Upper bounds on the length of the [Bytes] params of `eth_` requests.
+
+use crate::eth::error::{EthApiError, EthResult};
+use reth_primitives::Bytes;
+use serde::{Deserialize, Serialize};
+
+/// The default max length of the call data of a request: 128 KiB
+pub const DEFAULT_MAX_CALL_DATA_SIZE: usize = 128 * 1024;
+
+/// The default max length of a raw transaction: 1 MiB
+pub const DEFAULT_MAX_RAW_TRANSACTION_SIZE: usize = 1024 * 1024;
+
+/// Upper bounds on the length of the [Bytes] params of requests.
+///
+/// The limits are checked by the handlers of the [EthApi](crate::EthApi) they are set on, see
+/// [EthApi::with_bytes_limits](crate::EthApi::with_bytes_limits), so servers with different
+/// settings enforce different limits.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BytesLimits {
+    /// Max length of the call data of a request in bytes.
+    ///
+    /// Default is [DEFAULT_MAX_CALL_DATA_SIZE]
+    pub max_call_data_size: usize,
+    /// Max length of a raw transaction in bytes.
+    ///
+    /// Default is [DEFAULT_MAX_RAW_TRANSACTION_SIZE]
+    pub max_raw_transaction_size: usize,
+}
+
+impl BytesLimits {
+    /// Returns an error if the call data is longer than
+    /// [`max_call_data_size`](Self::max_call_data_size).
+    pub fn check_call_data(&self, data: Option<&Bytes>) -> EthResult<()> {
+        check_len("call data", data.map_or(0, |data| data.len()), self.max_call_data_size)
+    }
+
+    /// Returns an error if the raw transaction is longer than
+    /// [`max_raw_transaction_size`](Self::max_raw_transaction_size).
+    pub fn check_raw_transaction(&self, tx: &Bytes) -> EthResult<()> {
+        check_len("raw transaction", tx.len(), self.max_raw_transaction_size)
+    }
+}
+
+impl Default for BytesLimits {
+    fn default() -> Self {
+        Self {
+            max_call_data_size: DEFAULT_MAX_CALL_DATA_SIZE,
+            max_raw_transaction_size: DEFAULT_MAX_RAW_TRANSACTION_SIZE,
+        }
+    }
+}
+
+fn check_len(param: &'static str, len: usize, max: usize) -> EthResult<()> {
+    if len > max {
+        return Err(EthApiError::ParamTooLarge { param, len, max })
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits() {
+        let limits = BytesLimits { max_call_data_size: 4, max_raw_transaction_size: 8 };
+        assert!(limits.check_call_data(None).is_ok());
+        assert!(limits.check_call_data(Some(&Bytes::from(vec![0; 4]))).is_ok());
+        let err = limits.check_call_data(Some(&Bytes::from(vec![0; 5]))).unwrap_err();
+        assert_eq!(err.to_string(), "call data of 5 bytes exceeds the limit of 4 bytes");
+
+        assert!(limits.check_raw_transaction(&Bytes::from(vec![0; 8])).is_ok());
+        let err = limits.check_raw_transaction(&Bytes::from(vec![0; 9])).unwrap_err();
+        assert_eq!(err.to_string(), "raw transaction of 9 bytes exceeds the limit of 8 bytes");
+    }
+}
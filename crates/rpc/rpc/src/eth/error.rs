@@ -1,8 +1,11 @@
 //! Implementation specific Errors for the `eth_` namespace.
 
-use crate::result::{internal_rpc_err, rpc_err};
+use crate::result::{internal_rpc_err, rpc_err, rpc_err_with_json_data};
 use jsonrpsee::{core::Error as RpcError, types::error::INVALID_PARAMS_CODE};
-use reth_primitives::{constants::SELECTOR_LEN, Address, U128, U256};
+use reth_interfaces::provider::ProviderError;
+use reth_primitives::{
+    constants::SELECTOR_LEN, Address, BlockNumber, TransactionDecodeError, U128, U256,
+};
 use reth_rpc_types::{error::EthRpcErrorCode, BlockError};
 use reth_transaction_pool::error::{InvalidPoolTransactionError, PoolError};
 use revm::primitives::{EVMError, Halt, OutOfGasError};
@@ -14,19 +17,49 @@ pub type EthResult<T> = Result<T, EthApiError>;
 #[derive(Debug, thiserror::Error)]
 #[allow(missing_docs)]
 pub enum EthApiError {
-    /// When a raw transaction is empty
-    #[error("Empty transaction data")]
-    EmptyRawTransactionData,
-    #[error("Failed to decode signed transaction")]
-    FailedToDecodeSignedTransaction,
+    /// When a raw transaction could not be decoded
+    #[error(transparent)]
+    InvalidRawTransaction(#[from] TransactionDecodeError),
     #[error("Invalid transaction signature")]
     InvalidTransactionSignature,
     #[error(transparent)]
     PoolError(RpcPoolError),
     #[error("Unknown block number")]
     UnknownBlockNumber,
+    #[error("Unknown block hash")]
+    UnknownBlockHash,
     #[error("Invalid block range")]
     InvalidBlockRange,
+    /// Thrown when a block range request covers more blocks than the node serves at once
+    #[error("Block range exceeds the max of {0} blocks")]
+    BlockRangeExceedsMax(u64),
+    /// Thrown when a quantity of a request doesn't fit the 64 bits it's used as
+    #[error("{0} exceeds the range of a 64 bit quantity")]
+    QuantityOverflow(&'static str),
+    /// Thrown when a request queries more accounts than the node serves at once
+    #[error("Number of addresses exceeds the max of {0}")]
+    TooManyAddresses(usize),
+    /// Thrown when a request queries more storage slots than the node serves at once
+    #[error("Number of storage slots exceeds the max of {0}")]
+    TooManySlots(usize),
+    /// Thrown when a request imports more transactions than the node imports at once
+    #[error("Number of transactions exceeds the max of {0}")]
+    TooManyTransactions(usize),
+    /// Thrown when a [Bytes](reth_primitives::Bytes) param is longer than the server accepts, see
+    /// [BytesLimits](crate::eth::bytes_limit::BytesLimits)
+    #[error("{param} of {len} bytes exceeds the limit of {max} bytes")]
+    ParamTooLarge { param: &'static str, len: usize, max: usize },
+    /// Thrown when the requested block is below the history of the node, for example because the
+    /// node was synced from a trusted checkpoint
+    #[error("Block #{number} is unavailable, the history of this node starts at block #{lowest_available}")]
+    BlockUnavailable { number: BlockNumber, lowest_available: BlockNumber },
+    /// Thrown when the state of a block above the trusted checkpoint the node was synced from is
+    /// requested before the block was executed
+    #[error("State of block #{number} is unavailable, the blocks above the checkpoint were not executed")]
+    StateUnavailable { number: BlockNumber },
+    /// Thrown when the reward percentiles of `eth_feeHistory` are out of range or not sorted
+    #[error("Invalid reward percentiles")]
+    InvalidRewardPercentiles,
     /// An internal error where prevrandao is not set in the evm's environment
     #[error("Prevrandao not in th EVM's environment after merge")]
     PrevrandaoNotSet,
@@ -49,9 +82,15 @@ pub enum EthApiError {
     /// conflicting `state` and `stateDiff` fields
     #[error("account {0:?} has both 'state' and 'stateDiff'")]
     BothStateAndStateDiffInOverride(Address),
+    /// Thrown when the fee recipient of the built blocks is requested but not configured
+    #[error("etherbase not set")]
+    EtherbaseNotSet,
+    /// Thrown when a tracer other than the default struct logger is requested
+    #[error("only the default struct logger tracer is supported")]
+    UnsupportedTracer,
     /// Other internal error
     #[error(transparent)]
-    Internal(#[from] reth_interfaces::Error),
+    Internal(reth_interfaces::Error),
     /// Error related to signing
     #[error(transparent)]
     Signing(#[from] SignError),
@@ -60,27 +99,75 @@ pub enum EthApiError {
 impl From<EthApiError> for RpcError {
     fn from(error: EthApiError) -> Self {
         match error {
-            EthApiError::FailedToDecodeSignedTransaction |
             EthApiError::InvalidTransactionSignature |
-            EthApiError::EmptyRawTransactionData |
             EthApiError::UnknownBlockNumber |
+            EthApiError::UnknownBlockHash |
             EthApiError::InvalidBlockRange |
+            EthApiError::BlockRangeExceedsMax(_) |
+            EthApiError::TooManyAddresses(_) |
+            EthApiError::TooManySlots(_) |
+            EthApiError::TooManyTransactions(_) |
+            EthApiError::ParamTooLarge { .. } |
+            EthApiError::QuantityOverflow(_) |
+            EthApiError::InvalidRewardPercentiles |
             EthApiError::ConflictingRequestGasPrice { .. } |
             EthApiError::ConflictingRequestGasPriceAndTipSet { .. } |
             EthApiError::RequestLegacyGasPriceAndTipSet { .. } |
             EthApiError::Signing(_) |
-            EthApiError::BothStateAndStateDiffInOverride(_) => {
+            EthApiError::BothStateAndStateDiffInOverride(_) |
+            EthApiError::UnsupportedTracer => {
                 rpc_err(INVALID_PARAMS_CODE, error.to_string(), None)
             }
+            EthApiError::InvalidRawTransaction(err) => rpc_err_with_json_data(
+                INVALID_PARAMS_CODE,
+                err.to_string(),
+                RawTransactionErrorData { offset: err.offset() },
+            ),
+            EthApiError::BlockUnavailable { .. } | EthApiError::StateUnavailable { .. } => {
+                rpc_err(EthRpcErrorCode::ResourceNotFound.code(), error.to_string(), None)
+            }
             EthApiError::InvalidTransaction(err) => err.into(),
             EthApiError::PoolError(_) |
             EthApiError::PrevrandaoNotSet |
+            EthApiError::EtherbaseNotSet |
             EthApiError::InvalidBlockData(_) |
             EthApiError::Internal(_) => internal_rpc_err(error.to_string()),
         }
     }
 }
 
+/// The data of the error of a raw transaction that could not be decoded.
+#[derive(Debug, serde::Serialize)]
+struct RawTransactionErrorData {
+    /// The offset in the raw transaction where the decoding failed.
+    offset: usize,
+}
+
+impl From<reth_interfaces::Error> for EthApiError {
+    fn from(err: reth_interfaces::Error) -> Self {
+        match err {
+            reth_interfaces::Error::Provider(err) => err.into(),
+            err => EthApiError::Internal(err),
+        }
+    }
+}
+
+impl From<ProviderError> for EthApiError {
+    fn from(err: ProviderError) -> Self {
+        match err {
+            ProviderError::BlockUnavailable { number, lowest_available } => {
+                EthApiError::BlockUnavailable { number, lowest_available }
+            }
+            ProviderError::StateUnavailable { number } => EthApiError::StateUnavailable { number },
+            ProviderError::BlockHash { .. } => EthApiError::UnknownBlockHash,
+            ProviderError::CanonicalHeader { .. } => EthApiError::UnknownBlockNumber,
+            ProviderError::BlockNumberOverflow(_) => EthApiError::QuantityOverflow("block number"),
+            // missing data of known blocks means the database is inconsistent
+            err => EthApiError::Internal(err.into()),
+        }
+    }
+}
+
 impl<T> From<EVMError<T>> for EthApiError
 where
     T: Into<EthApiError>,
@@ -363,3 +450,52 @@ pub(crate) fn decode_revert_reason(out: impl AsRef<[u8]>) -> Option<String> {
     }
     String::decode(&out[SELECTOR_LEN..]).ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unavailable_block_error() {
+        let err: EthApiError = reth_interfaces::Error::Provider(ProviderError::BlockUnavailable {
+            number: 99,
+            lowest_available: 101,
+        })
+        .into();
+        assert!(matches!(err, EthApiError::BlockUnavailable { number: 99, lowest_available: 101 }));
+
+        match RpcError::from(err) {
+            RpcError::Call(jsonrpsee::types::error::CallError::Custom(err)) => {
+                assert_eq!(err.code(), EthRpcErrorCode::ResourceNotFound.code());
+                assert_eq!(
+                    err.message(),
+                    "Block #99 is unavailable, the history of this node starts at block #101"
+                );
+            }
+            err => panic!("unexpected error {err:?}"),
+        }
+
+        let err: EthApiError = ProviderError::StateUnavailable { number: 150 }.into();
+        assert!(matches!(err, EthApiError::StateUnavailable { number: 150 }));
+
+        let err: EthApiError = ProviderError::Header { number: 1 }.into();
+        assert!(matches!(err, EthApiError::Internal(_)));
+    }
+
+    #[test]
+    fn provider_error_codes() {
+        let code = |err: ProviderError| match RpcError::from(EthApiError::from(err)) {
+            RpcError::Call(jsonrpsee::types::error::CallError::Custom(err)) => err.code(),
+            err => panic!("unexpected error {err:?}"),
+        };
+
+        let block_hash = Default::default();
+        assert_eq!(code(ProviderError::BlockHash { block_hash }), INVALID_PARAMS_CODE);
+        assert_eq!(code(ProviderError::CanonicalHeader { block_number: 1 }), INVALID_PARAMS_CODE);
+        assert_eq!(code(ProviderError::BlockNumberOverflow(U256::MAX)), INVALID_PARAMS_CODE);
+        assert_eq!(
+            code(ProviderError::BlockBody { number: 1 }),
+            jsonrpsee::types::error::INTERNAL_ERROR_CODE
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Fan out of the changes of the canonical chain to the subscriptions of the rpc handlers.
+
+use futures::{Stream, StreamExt};
+use parking_lot::Mutex;
+use reth_interfaces::events::{
+    CanonStateNotification, CanonStateNotifications, CanonStateSubscriptions,
+    ChainEventSubscriptions, NewBlockNotification, NewBlockNotifications,
+};
+use std::sync::Arc;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+/// Forwards every [CanonStateNotification] to all of its subscribers, and every committed block
+/// to the subscribers of new blocks.
+///
+/// Subscribers can be added at any time, for example by the `eth_subscribe` handlers. Closed
+/// subscriptions are removed on the next notification.
+#[derive(Debug, Clone, Default)]
+pub struct CanonStateBroadcaster {
+    inner: Arc<Mutex<Listeners>>,
+}
+
+#[derive(Debug, Default)]
+struct Listeners {
+    canon_state: Vec<UnboundedSender<CanonStateNotification>>,
+    new_blocks: Vec<UnboundedSender<NewBlockNotification>>,
+}
+
+impl CanonStateBroadcaster {
+    /// Sends the notification to all subscribers.
+    pub fn notify(&self, notification: CanonStateNotification) {
+        let mut listeners = self.inner.lock();
+        for block in notification.committed() {
+            let new_block = NewBlockNotification {
+                hash: block.block.hash(),
+                header: Arc::new(block.block.header.as_ref().clone()),
+            };
+            listeners.new_blocks.retain(|listener| listener.send(new_block.clone()).is_ok());
+        }
+        listeners.canon_state.retain(|listener| listener.send(notification.clone()).is_ok());
+    }
+}
+
+impl CanonStateSubscriptions for CanonStateBroadcaster {
+    fn subscribe_canon_state(&self) -> CanonStateNotifications {
+        let (tx, rx) = unbounded_channel();
+        self.inner.lock().canon_state.push(tx);
+        rx
+    }
+}
+
+impl ChainEventSubscriptions for CanonStateBroadcaster {
+    fn subscribe_new_blocks(&self) -> NewBlockNotifications {
+        let (tx, rx) = unbounded_channel();
+        self.inner.lock().new_blocks.push(tx);
+        rx
+    }
+}
+
+/// Forwards the notifications of the stream to all subscribers of the [CanonStateBroadcaster].
+///
+/// Resolves once the notification stream ends.
+pub async fn canon_state_broadcast_task<St>(
+    broadcaster: CanonStateBroadcaster,
+    mut notifications: St,
+) where
+    St: Stream<Item = CanonStateNotification> + Unpin + 'static,
+{
+    while let Some(notification) = notifications.next().await {
+        broadcaster.notify(notification);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_interfaces::events::CanonBlock;
+    use reth_primitives::{Header, SealedBlock};
+
+    fn block(number: u64) -> CanonBlock {
+        let header = Header { number, ..Default::default() }.seal_slow();
+        CanonBlock {
+            block: SealedBlock { header, ..Default::default() },
+            receipts: vec![],
+            storage: Default::default(),
+        }
+    }
+
+    #[test]
+    fn notifies_all_subscribers() {
+        let broadcaster = CanonStateBroadcaster::default();
+        let mut canon_state = broadcaster.subscribe_canon_state();
+        let mut new_blocks = broadcaster.subscribe_new_blocks();
+        let closed = broadcaster.subscribe_canon_state();
+        drop(closed);
+
+        broadcaster.notify(CanonStateNotification::Reorg {
+            old: Arc::new(vec![block(1)]),
+            new: Arc::new(vec![block(1), block(2)]),
+        });
+
+        let notification = canon_state.try_recv().unwrap();
+        assert_eq!(notification.committed().len(), 2);
+        assert_eq!(notification.reverted().len(), 1);
+        // only committed blocks are new blocks
+        assert_eq!(new_blocks.try_recv().unwrap().header.number, 1);
+        assert_eq!(new_blocks.try_recv().unwrap().header.number, 2);
+        assert!(new_blocks.try_recv().is_err());
+        // the closed subscription was removed
+        assert_eq!(broadcaster.inner.lock().canon_state.len(), 1);
+    }
+}
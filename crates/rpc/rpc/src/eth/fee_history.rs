@@ -0,0 +1,295 @@
+//! Cache of the fee data of recent canonical blocks for `eth_feeHistory`
+
+use futures::{Stream, StreamExt};
+use parking_lot::RwLock;
+use reth_interfaces::events::CanonStateNotification;
+use reth_primitives::{BlockNumber, Receipt, SealedHeader, TransactionSigned, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, ops::RangeInclusive, sync::Arc};
+
+/// Settings for the [FeeHistoryCache]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistoryCacheConfig {
+    /// Max number of blocks in the cache.
+    ///
+    /// Default is 2048
+    pub max_blocks: u64,
+}
+
+impl Default for FeeHistoryCacheConfig {
+    fn default() -> Self {
+        Self { max_blocks: 2048 }
+    }
+}
+
+/// Holds the fee data of the most recent canonical blocks.
+///
+/// The cache is filled from [CanonStateNotification]s, see [fee_history_cache_new_blocks_task],
+/// so that `eth_feeHistory` doesn't need to read the transactions and receipts of recent blocks on
+/// every call. The entries of blocks that are reverted by a reorg are removed.
+#[derive(Debug, Clone)]
+pub struct FeeHistoryCache {
+    config: FeeHistoryCacheConfig,
+    entries: Arc<RwLock<BTreeMap<BlockNumber, FeeHistoryEntry>>>,
+}
+
+impl FeeHistoryCache {
+    /// Creates an empty cache.
+    pub fn new(config: FeeHistoryCacheConfig) -> Self {
+        Self { config, entries: Default::default() }
+    }
+
+    /// Returns the entries of the cached blocks in the given range.
+    pub fn range(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> BTreeMap<BlockNumber, FeeHistoryEntry> {
+        self.entries.read().range(range).map(|(number, entry)| (*number, entry.clone())).collect()
+    }
+
+    /// Returns the range of the cached blocks, if any.
+    pub fn cached_range(&self) -> Option<RangeInclusive<BlockNumber>> {
+        let entries = self.entries.read();
+        let (first, _) = entries.first_key_value()?;
+        let (last, _) = entries.last_key_value()?;
+        Some(*first..=*last)
+    }
+
+    /// Applies the change of the canonical chain to the cache.
+    ///
+    /// The entries of reverted blocks are removed, and so are all entries the new blocks don't
+    /// extend.
+    pub fn on_canon_state(&self, notification: &CanonStateNotification) {
+        let mut entries = self.entries.write();
+        if let Some(first) = notification.reverted().first() {
+            entries.split_off(&first.block.number);
+        }
+
+        for committed in notification.committed() {
+            let block = &committed.block;
+            entries.split_off(&block.number);
+            let extends_cached = entries.last_key_value().map_or(true, |(number, entry)| {
+                number + 1 == block.number && entry.hash == block.parent_hash
+            });
+            if !extends_cached {
+                entries.clear();
+            }
+            entries.insert(
+                block.number,
+                FeeHistoryEntry::new(&block.header, &block.body, &committed.receipts),
+            );
+        }
+
+        while entries.len() as u64 > self.config.max_blocks {
+            entries.pop_first();
+        }
+    }
+}
+
+/// Keeps the [FeeHistoryCache] up to date with the canonical chain.
+///
+/// Resolves once the notification stream ends.
+pub async fn fee_history_cache_new_blocks_task<St>(cache: FeeHistoryCache, mut notifications: St)
+where
+    St: Stream<Item = CanonStateNotification> + Unpin + 'static,
+{
+    while let Some(notification) = notifications.next().await {
+        cache.on_canon_state(&notification);
+    }
+}
+
+/// The fee data of a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeHistoryEntry {
+    /// Hash of the block.
+    pub hash: H256,
+    /// Base fee per gas of the block. Zero for pre-EIP-1559 blocks.
+    pub base_fee_per_gas: u64,
+    /// The ratio of `gasUsed` and `gasLimit` of the block.
+    pub gas_used_ratio: f64,
+    /// Gas used by the block.
+    pub gas_used: u64,
+    /// The effective priority fees per gas of the transactions of the block with the gas they
+    /// used, sorted by ascending fee.
+    pub rewards: Vec<TxGasAndReward>,
+}
+
+impl FeeHistoryEntry {
+    /// Computes the fee data of the block with the given header, transactions and receipts.
+    ///
+    /// The rewards are only computed for the transactions that have a receipt.
+    pub fn new(
+        header: &SealedHeader,
+        transactions: &[TransactionSigned],
+        receipts: &[Receipt],
+    ) -> Self {
+        let mut previous_cumulative_gas_used = 0;
+        let mut rewards = transactions
+            .iter()
+            .zip(receipts)
+            .map(|(transaction, receipt)| {
+                let gas_used = receipt.cumulative_gas_used - previous_cumulative_gas_used;
+                previous_cumulative_gas_used = receipt.cumulative_gas_used;
+                TxGasAndReward {
+                    gas_used,
+                    reward: effective_tip(transaction, header.base_fee_per_gas),
+                }
+            })
+            .collect::<Vec<_>>();
+        rewards.sort_by_key(|tx| tx.reward);
+
+        Self {
+            hash: header.hash(),
+            base_fee_per_gas: header.base_fee_per_gas.unwrap_or_default(),
+            gas_used_ratio: header.gas_used as f64 / header.gas_limit as f64,
+            gas_used: header.gas_used,
+            rewards,
+        }
+    }
+
+    /// Returns the effective priority fees per gas at the given percentiles of the gas used by the
+    /// block.
+    ///
+    /// The percentiles must be sorted in ascending order. All rewards are zero if the block is
+    /// empty.
+    pub fn rewards(&self, percentiles: &[f64]) -> Vec<U256> {
+        if self.rewards.is_empty() {
+            return vec![U256::ZERO; percentiles.len()]
+        }
+
+        let mut index = 0;
+        let mut cumulative_gas_used = self.rewards[0].gas_used;
+        percentiles
+            .iter()
+            .map(|percentile| {
+                let threshold = (self.gas_used as f64 * percentile / 100.0) as u64;
+                while cumulative_gas_used < threshold && index < self.rewards.len() - 1 {
+                    index += 1;
+                    cumulative_gas_used += self.rewards[index].gas_used;
+                }
+                U256::from(self.rewards[index].reward)
+            })
+            .collect()
+    }
+}
+
+/// The gas used by a transaction and the effective priority fee per gas it paid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxGasAndReward {
+    /// Gas used by the transaction.
+    pub gas_used: u64,
+    /// Effective priority fee per gas paid by the transaction.
+    pub reward: u128,
+}
+
+/// Returns the priority fee per gas the transaction pays in a block with the given base fee.
+fn effective_tip(transaction: &TransactionSigned, base_fee: Option<u64>) -> u128 {
+    let max_tip =
+        transaction.max_fee_per_gas().saturating_sub(base_fee.unwrap_or_default() as u128);
+    transaction.max_priority_fee_per_gas().map_or(max_tip, |tip| tip.min(max_tip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_interfaces::events::CanonBlock;
+    use reth_primitives::{
+        Header, SealedBlock, Signature, Transaction, TransactionKind, TxEip1559,
+    };
+
+    fn block(number: BlockNumber, parent_hash: H256, tips: &[u128]) -> CanonBlock {
+        let base_fee = 7;
+        let body = tips
+            .iter()
+            .map(|tip| {
+                let transaction = Transaction::Eip1559(TxEip1559 {
+                    max_fee_per_gas: base_fee + tip,
+                    max_priority_fee_per_gas: *tip,
+                    gas_limit: 21_000,
+                    to: TransactionKind::Create,
+                    ..Default::default()
+                });
+                TransactionSigned::from_transaction_and_signature(transaction, Signature::default())
+            })
+            .collect::<Vec<_>>();
+        let receipts = (1..=tips.len() as u64)
+            .map(|index| Receipt { cumulative_gas_used: index * 21_000, ..Default::default() })
+            .collect();
+        let header = Header {
+            number,
+            parent_hash,
+            gas_limit: 100_000,
+            gas_used: tips.len() as u64 * 21_000,
+            base_fee_per_gas: Some(base_fee as u64),
+            ..Default::default()
+        };
+        CanonBlock {
+            block: SealedBlock { header: header.seal_slow(), body, ..Default::default() },
+            receipts,
+            storage: Default::default(),
+        }
+    }
+
+    fn chain(first: BlockNumber, parent_hash: H256, tips: &[&[u128]]) -> Vec<CanonBlock> {
+        let mut parent_hash = parent_hash;
+        tips.iter()
+            .enumerate()
+            .map(|(index, tips)| {
+                let block = block(first + index as u64, parent_hash, tips);
+                parent_hash = block.block.hash();
+                block
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rewards_at_percentiles() {
+        let block = block(1, H256::zero(), &[3, 1, 2, 4]);
+        let entry = FeeHistoryEntry::new(&block.block.header, &block.block.body, &block.receipts);
+        assert_eq!(entry.base_fee_per_gas, 7);
+        assert_eq!(entry.gas_used_ratio, 0.84);
+        assert_eq!(
+            entry.rewards(&[0.0, 25.0, 50.0, 75.0, 100.0]),
+            [1, 1, 2, 3, 4].map(U256::from).to_vec()
+        );
+
+        let empty = block(2, H256::zero(), &[]);
+        let entry = FeeHistoryEntry::new(&empty.block.header, &[], &[]);
+        assert_eq!(entry.rewards(&[10.0, 90.0]), vec![U256::ZERO; 2]);
+    }
+
+    #[test]
+    fn bounded_to_max_blocks() {
+        let cache = FeeHistoryCache::new(FeeHistoryCacheConfig { max_blocks: 2 });
+        let new = chain(1, H256::zero(), &[&[1], &[2], &[3]]);
+        cache.on_canon_state(&CanonStateNotification::Commit { new: Arc::new(new) });
+        assert_eq!(cache.cached_range(), Some(2..=3));
+    }
+
+    #[test]
+    fn rollback_on_reorg() {
+        let cache = FeeHistoryCache::new(Default::default());
+        let old = chain(1, H256::zero(), &[&[1], &[2], &[3]]);
+        cache.on_canon_state(&CanonStateNotification::Commit { new: Arc::new(old.clone()) });
+        assert_eq!(cache.cached_range(), Some(1..=3));
+
+        // blocks 2 and 3 are replaced by a single block 2
+        let new = chain(2, old[0].block.hash(), &[&[5]]);
+        cache.on_canon_state(&CanonStateNotification::Reorg {
+            old: Arc::new(old[1..].to_vec()),
+            new: Arc::new(new.clone()),
+        });
+        let entries = cache.range(0..=10);
+        assert_eq!(entries.keys().copied().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(entries[&1].hash, old[0].block.hash());
+        assert_eq!(entries[&2].hash, new[0].block.hash());
+        assert_eq!(entries[&2].rewards(&[50.0]), vec![U256::from(5)]);
+
+        // a commit that doesn't extend the cached blocks replaces them
+        let other = chain(3, H256::random(), &[&[6]]);
+        cache.on_canon_state(&CanonStateNotification::Commit { new: Arc::new(other.clone()) });
+        assert_eq!(cache.cached_range(), Some(3..=3));
+        assert_eq!(cache.range(3..=3)[&3].hash, other[0].block.hash());
+    }
+}
@@ -7,19 +7,29 @@ use async_trait::async_trait;
 use jsonrpsee::{core::RpcResult, server::IdProvider};
 use reth_primitives::{
     filter::{Filter, FilterBlockOption, FilteredParams},
-    U256,
+    H256, U256,
 };
 use reth_provider::{BlockProvider, EvmEnvProvider};
 use reth_rpc_api::EthFilterApiServer;
 use reth_rpc_types::{FilterChanges, FilterId, Log};
 use reth_transaction_pool::TransactionPool;
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Instant,
+};
 use tokio::sync::Mutex;
 use tracing::trace;
 
 /// The default maximum of logs in a single response.
 const DEFAULT_MAX_LOGS_IN_RESPONSE: usize = 2_000;
 
+/// The maximum depth of a reorg that is reported to a log filter.
+///
+/// The logs of this many most recent polled blocks are kept per log filter, so that they can be
+/// delivered again as removed if their block is reorged out of the chain.
+const MAX_REORG_DEPTH: usize = 64;
+
 /// `Eth` filter RPC implementation.
 #[derive(Debug, Clone)]
 pub struct EthFilter<Client, Pool> {
@@ -72,7 +82,7 @@ where
         let info = self.inner.client.chain_info().to_rpc_result()?;
         let best_number = info.best_number;
 
-        let (start_block, kind) = {
+        let (start_block, kind, mut delivered) = {
             let mut filters = self.inner.active_filters.inner.lock().await;
             let mut filter = filters.get_mut(&id).ok_or(FilterError::FilterNotFound(id))?;
 
@@ -83,7 +93,7 @@ where
             std::mem::swap(&mut filter.block, &mut block);
             filter.last_poll_timestamp = Instant::now();
 
-            (block, filter.kind.clone())
+            (block, filter.kind.clone(), std::mem::take(&mut filter.delivered))
         };
 
         match kind {
@@ -131,9 +141,25 @@ where
                     }
                 }
 
-                self.inner
-                    .filter_logs(&filter, from_block_number, to_block_number)
-                    .map(FilterChanges::Logs)
+                // deliver the logs of the blocks that are no longer canonical as removed, and poll
+                // again from the common ancestor
+                let (mut logs, first_reverted_block) =
+                    self.inner.revert_reorged_blocks(&mut delivered)?;
+                if let Some(first_reverted_block) = first_reverted_block {
+                    from_block_number = from_block_number.min(first_reverted_block);
+                }
+
+                for block in self.inner.filter_logs(&filter, from_block_number, to_block_number)? {
+                    logs.extend(block.logs.iter().cloned());
+                    delivered.push(block);
+                }
+
+                let mut filters = self.inner.active_filters.inner.lock().await;
+                if let Some(filter) = filters.get_mut(&id) {
+                    filter.delivered = delivered;
+                }
+
+                Ok(FilterChanges::Logs(logs))
             }
         }
     }
@@ -155,8 +181,8 @@ where
     }
 
     /// Handler for `eth_getLogs`
-    async fn logs(&self, _filter: Filter) -> RpcResult<Vec<Log>> {
-        todo!()
+    async fn logs(&self, filter: Filter) -> RpcResult<Vec<Log>> {
+        self.inner.logs_for_filter(filter)
     }
 }
 
@@ -191,18 +217,133 @@ where
                 block: last_poll_block_number,
                 last_poll_timestamp: Instant::now(),
                 kind,
+                delivered: Default::default(),
             },
         );
         Ok(id)
     }
 
-    /// Returns all logs in the given range that match the filter
+    /// Returns all logs that match the filter.
+    ///
+    /// A filter for a block hash, see [EIP-234](https://eips.ethereum.org/EIPS/eip-234), is scoped
+    /// to exactly that block, which doesn't have to be canonical.
+    fn logs_for_filter(&self, filter: Filter) -> RpcResult<Vec<Log>> {
+        match filter.block_option {
+            FilterBlockOption::AtBlockHash(block_hash) => {
+                let block = self
+                    .client
+                    .block(block_hash.into())
+                    .to_rpc_result()?
+                    .ok_or(EthApiError::UnknownBlockHash)?;
+                // the receipts are looked up by hash as well, the canonical block with the same
+                // number may be a different block
+                let receipts = self
+                    .client
+                    .receipts_by_block_aligned(block_hash.into())
+                    .to_rpc_result()?
+                    .ok_or(EthApiError::UnknownBlockHash)?;
+
+                let logs = logs_utils::matching_block_logs(
+                    &FilteredParams::new(Some(filter)),
+                    block_hash,
+                    block.number,
+                    block.body.into_iter().map(|tx| tx.hash).zip(receipts),
+                );
+                if logs.len() > self.max_logs_in_response {
+                    return Err(FilterError::QueryExceedsMaxResults(self.max_logs_in_response).into())
+                }
+                Ok(logs)
+            }
+            FilterBlockOption::Range { from_block, to_block } => {
+                let info = self.client.chain_info().to_rpc_result()?;
+                // both default to the latest block
+                let from_block_number = from_block
+                    .and_then(|num| info.convert_block_number(num.into()))
+                    .unwrap_or(info.best_number);
+                let to_block_number = to_block
+                    .and_then(|num| info.convert_block_number(num.into()))
+                    .unwrap_or(info.best_number)
+                    .min(info.best_number);
+                if from_block_number > to_block_number {
+                    return Err(EthApiError::InvalidBlockRange.into())
+                }
+
+                let blocks = self.filter_logs(&filter, from_block_number, to_block_number)?;
+                Ok(blocks.into_iter().flat_map(|block| block.logs).collect())
+            }
+        }
+    }
+
+    /// Removes the most recent delivered blocks that are no longer canonical.
+    ///
+    /// Returns the logs of the removed blocks, marked as removed and in the order of the blocks,
+    /// and the number of the first removed block.
+    fn revert_reorged_blocks(
+        &self,
+        delivered: &mut DeliveredBlocks,
+    ) -> RpcResult<(Vec<Log>, Option<u64>)> {
+        let mut reverted = Vec::new();
+        while let Some(block) = delivered.blocks.back() {
+            let canonical_hash =
+                self.client.block_hash(U256::from(block.number)).to_rpc_result()?;
+            if canonical_hash == Some(block.hash) {
+                // common ancestor
+                break
+            }
+            reverted.extend(delivered.blocks.pop_back());
+        }
+
+        let first_reverted_block = reverted.last().map(|block| block.number);
+        if let Some(number) = first_reverted_block {
+            trace!(target: "rpc::eth::filter", first_reverted_block = number, num_blocks = reverted.len(), "reverting reorged blocks");
+        }
+
+        let removed = reverted
+            .into_iter()
+            .rev()
+            .flat_map(|block| block.logs)
+            .map(|mut log| {
+                log.removed = true;
+                log
+            })
+            .collect();
+        Ok((removed, first_reverted_block))
+    }
+
+    /// Returns all logs in the given range that match the filter, grouped by block.
+    ///
+    /// Every existing block of the range is returned, including those without matching logs. All
+    /// blocks are from the same canonical chain: if the chain is reorged while the logs are read,
+    /// they are read once more from the new chain.
     ///
     /// Returns an error if:
     ///  - underlying database error
     ///  - amount of matches exceeds configured limit
-    fn filter_logs(&self, filter: &Filter, from_block: u64, to_block: u64) -> RpcResult<Vec<Log>> {
-        let mut all_logs = Vec::new();
+    ///  - the chain was reorged during both attempts
+    fn filter_logs(
+        &self,
+        filter: &Filter,
+        from_block: u64,
+        to_block: u64,
+    ) -> RpcResult<Vec<BlockLogs>> {
+        if let Some(blocks) = self.try_filter_logs(filter, from_block, to_block)? {
+            return Ok(blocks)
+        }
+        trace!(target: "rpc::eth::filter", from_block, to_block, "chain reorged while reading logs, retrying");
+        self.try_filter_logs(filter, from_block, to_block)?
+            .ok_or_else(|| FilterError::ChainReorged.into())
+    }
+
+    /// Returns all logs in the given range that match the filter, grouped by block, or `None` if a
+    /// block of the range was reorged out of the canonical chain while the logs were read.
+    fn try_filter_logs(
+        &self,
+        filter: &Filter,
+        from_block: u64,
+        to_block: u64,
+    ) -> RpcResult<Option<Vec<BlockLogs>>> {
+        let mut blocks = Vec::new();
+        let mut num_logs = 0;
         let filter_params = FilteredParams::new(Some(filter.clone()));
 
         let topics =
@@ -212,41 +353,63 @@ where
         let address_filter = FilteredParams::address_filter(&filter.address);
         let topics_filter = FilteredParams::topics_filter(&topics);
 
+        // the canonical hashes of the range are resolved up front and the blocks are read by hash,
+        // so the blocks of the response can't be from different chains
+        let mut canonical_hashes = Vec::new();
+        for block_number in from_block..=to_block {
+            if let Some(hash) = self.client.block_hash(U256::from(block_number)).to_rpc_result()? {
+                canonical_hashes.push((block_number, hash));
+            }
+        }
+
         // loop over the range of new blocks and check logs if the filter matches the log's bloom
         // filter
-        for block_number in from_block..=to_block {
-            if let Some(block) = self.client.block_by_number(block_number).to_rpc_result()? {
-                // only if filter matches
-                if FilteredParams::matches_address(block.header.logs_bloom, &address_filter) &&
-                    FilteredParams::matches_topics(block.header.logs_bloom, &topics_filter)
+        for (block_number, block_hash) in canonical_hashes {
+            let Some(block) = self.client.block_by_hash(block_hash).to_rpc_result()? else {
+                return Ok(None)
+            };
+            if block.hash_slow() != block_hash {
+                return Ok(None)
+            }
+            let mut logs = Vec::new();
+
+            // only if filter matches
+            if FilteredParams::matches_address(block.header.logs_bloom, &address_filter) &&
+                FilteredParams::matches_topics(block.header.logs_bloom, &topics_filter)
+            {
+                // get receipts for the block
+                if let Some(receipts) =
+                    self.client.receipts_by_block_aligned(block_hash.into()).to_rpc_result()?
                 {
-                    // get receipts for the block
-                    if let Some(receipts) =
-                        self.client.receipts_by_block(block.number.into()).to_rpc_result()?
-                    {
-                        let block_hash = block.hash_slow();
-
-                        logs_utils::append_matching_block_logs(
-                            &mut all_logs,
-                            &filter_params,
-                            block_hash,
-                            block_number,
-                            block.body.into_iter().map(|tx| tx.hash).zip(receipts),
-                        );
-
-                        // size check
-                        if all_logs.len() > self.max_logs_in_response {
-                            return Err(FilterError::QueryExceedsMaxResults(
-                                self.max_logs_in_response,
-                            )
-                            .into())
-                        }
+                    // the receipts are of the block if it's still canonical after they were read
+                    let canonical_hash =
+                        self.client.block_hash(U256::from(block_number)).to_rpc_result()?;
+                    if canonical_hash != Some(block_hash) {
+                        return Ok(None)
+                    }
+
+                    logs_utils::append_matching_block_logs(
+                        &mut logs,
+                        &filter_params,
+                        block_hash,
+                        block_number,
+                        block.body.into_iter().map(|tx| tx.hash).zip(receipts),
+                    );
+
+                    // size check
+                    num_logs += logs.len();
+                    if num_logs > self.max_logs_in_response {
+                        return Err(
+                            FilterError::QueryExceedsMaxResults(self.max_logs_in_response).into()
+                        )
                     }
                 }
             }
+
+            blocks.push(BlockLogs { number: block_number, hash: block_hash, logs });
         }
 
-        Ok(all_logs)
+        Ok(Some(blocks))
     }
 }
 
@@ -265,6 +428,36 @@ struct ActiveFilter {
     last_poll_timestamp: Instant,
     /// What kind of filter it is.
     kind: FilterKind,
+    /// The most recent blocks polled by a log filter.
+    delivered: DeliveredBlocks,
+}
+
+/// The matching logs of a block.
+#[derive(Debug)]
+struct BlockLogs {
+    /// The number of the block.
+    number: u64,
+    /// The hash of the block.
+    hash: H256,
+    /// The logs of the block that match the filter.
+    logs: Vec<Log>,
+}
+
+/// The most recent blocks whose logs were delivered to a log filter, bounded by the
+/// [MAX_REORG_DEPTH].
+#[derive(Debug, Default)]
+struct DeliveredBlocks {
+    blocks: VecDeque<BlockLogs>,
+}
+
+impl DeliveredBlocks {
+    /// Records a delivered block, forgetting the oldest block if the [MAX_REORG_DEPTH] is reached.
+    fn push(&mut self, block: BlockLogs) {
+        if self.blocks.len() == MAX_REORG_DEPTH {
+            self.blocks.pop_front();
+        }
+        self.blocks.push_back(block);
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -281,6 +474,8 @@ pub enum FilterError {
     FilterNotFound(FilterId),
     #[error("Query exceeds max results {0}")]
     QueryExceedsMaxResults(usize),
+    #[error("chain reorged while reading logs")]
+    ChainReorged,
 }
 
 // convert the error
@@ -294,6 +489,262 @@ impl From<FilterError> for jsonrpsee::core::Error {
             err @ FilterError::QueryExceedsMaxResults(_) => {
                 rpc_error_with_code(jsonrpsee::types::error::INVALID_PARAMS_CODE, err.to_string())
             }
+            err @ FilterError::ChainReorged => internal_rpc_err(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{mdbx::test_utils::create_test_rw_db, tables, transaction::DbTxMut};
+    use reth_primitives::{
+        bloom::logs_bloom, Address, Block, Bytes, Header, PruneMode, PruneModes, Receipt,
+        ReceiptsLogPruneConfig, SealedBlock, Signature, TransactionSigned, TxLegacy, MAINNET, U64,
+    };
+    use reth_provider::{
+        insert_canonical_block, test_utils::MockEthProvider, ShareableDatabase,
+        Transaction as DbTransaction,
+    };
+    use reth_transaction_pool::test_utils::testing_pool;
+    use std::{collections::BTreeMap, ops::DerefMut, time::Duration};
+
+    /// Adds a block with a single log to the provider and returns its hash.
+    fn add_block(provider: &MockEthProvider, number: u64, parent_hash: H256, data: u8) -> H256 {
+        let receipt = Receipt {
+            success: true,
+            logs: vec![reth_primitives::Log {
+                address: Address::from_low_u64_be(1),
+                topics: vec![],
+                data: Bytes::from(vec![data]),
+            }],
+            ..Default::default()
+        };
+        let header = Header {
+            number,
+            parent_hash,
+            logs_bloom: receipt.bloom_slow(),
+            // distinguishes blocks of different forks
+            extra_data: Bytes::from(vec![data]),
+            ..Default::default()
+        };
+        let block =
+            Block { header, body: vec![TransactionSigned::default()], ..Default::default() };
+        let hash = block.hash_slow();
+        provider.add_block(hash, block);
+        provider.add_receipts(hash, vec![receipt]);
+        hash
+    }
+
+    async fn poll_logs(
+        filter: &EthFilter<MockEthProvider, impl TransactionPool + 'static>,
+        id: FilterId,
+    ) -> Vec<Log> {
+        match filter.filter_changes(id).await.unwrap() {
+            FilterChanges::Logs(logs) => logs,
+            changes => panic!("unexpected filter changes {changes:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_changes_reorg() {
+        let provider = MockEthProvider::default();
+        let genesis = add_block(&provider, 0, H256::zero(), 0);
+        let filter = EthFilter::new(provider.clone(), testing_pool());
+        let id = filter.new_filter(Filter::default()).await.unwrap();
+
+        let mut chain = vec![genesis];
+        for number in 1..=3 {
+            chain.push(add_block(&provider, number, chain[number as usize - 1], number as u8));
+        }
+
+        let logs = poll_logs(&filter, id.clone()).await;
+        assert_eq!(
+            logs.iter().map(|log| log.block_hash.unwrap()).collect::<Vec<_>>(),
+            chain.clone()
+        );
+        assert!(logs.iter().all(|log| !log.removed));
+
+        // replace blocks 2 and 3 with a longer fork
+        for hash in chain.drain(2..) {
+            provider.blocks.lock().remove(&hash);
+            provider.headers.lock().remove(&hash);
+        }
+        let reverted = logs[2..].to_vec();
+        for number in 2..=4 {
+            chain.push(add_block(&provider, number, chain[number as usize - 1], 0xff));
+        }
+
+        let logs = poll_logs(&filter, id.clone()).await;
+        assert_eq!(logs.len(), 5);
+        for (log, reverted) in logs[..2].iter().zip(&reverted) {
+            assert!(log.removed);
+            assert_eq!(log.block_hash, reverted.block_hash);
+            assert_eq!(log.data, reverted.data);
+        }
+        assert_eq!(
+            logs[2..].iter().map(|log| log.block_hash.unwrap()).collect::<Vec<_>>(),
+            chain[2..].to_vec()
+        );
+        assert!(logs[2..].iter().all(|log| !log.removed));
+
+        // nothing changed
+        assert!(poll_logs(&filter, id).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_logs_at_non_canonical_block_hash() {
+        let provider = MockEthProvider::default();
+        let genesis = add_block(&provider, 0, H256::zero(), 0);
+        let canonical = add_block(&provider, 1, genesis, 1);
+        let filter = EthFilter::new(provider.clone(), testing_pool());
+
+        // a sibling of the canonical block that is only known by its hash
+        let fork = add_block(&provider, 1, genesis, 2);
+        provider.headers.lock().remove(&fork);
+
+        let logs = filter.logs(Filter::new().at_block_hash(fork)).await.unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].block_hash, Some(fork));
+        assert_eq!(logs[0].data, Bytes::from(vec![2]));
+
+        let logs = filter.logs(Filter::new().at_block_hash(canonical)).await.unwrap();
+        assert_eq!(logs[0].data, Bytes::from(vec![1]));
+
+        // scoped to the block
+        let logs = filter
+            .logs(Filter::new().at_block_hash(fork).address(Address::from_low_u64_be(2)))
+            .await
+            .unwrap();
+        assert!(logs.is_empty());
+
+        assert!(filter.logs(Filter::new().at_block_hash(H256::random())).await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_logs_consistent_during_reorgs() {
+        let provider = MockEthProvider::default();
+        let genesis = add_block(&provider, 0, H256::zero(), 0);
+        let block1 = add_block(&provider, 1, genesis, 1);
+        // two forks on top of block 1, the data of their logs identifies the fork
+        let mut forks = Vec::new();
+        for data in [2, 3] {
+            let block2 = add_block(&provider, 2, block1, data);
+            let block3 = add_block(&provider, 3, block2, data);
+            let mut blocks = provider.blocks.lock();
+            forks.push([block2, block3].map(|hash| (hash, blocks.remove(&hash).unwrap())));
+        }
+        let fork_hashes = forks.iter().map(|fork| fork.map(|(hash, _)| hash)).collect::<Vec<_>>();
+        provider.blocks.lock().extend(forks[0].clone());
+        let filter = EthFilter::new(provider.clone(), testing_pool());
+
+        let reorgs = std::thread::spawn({
+            let provider = provider.clone();
+            move || {
+                for i in 0..500 {
+                    std::thread::sleep(Duration::from_micros(50));
+                    // replaces the blocks of one fork with the other at once
+                    let mut blocks = provider.blocks.lock();
+                    for (hash, _) in &forks[i % 2] {
+                        blocks.remove(hash);
+                    }
+                    blocks.extend(forks[(i + 1) % 2].clone());
+                }
+            }
+        });
+
+        let mut consistent = 0;
+        while !reorgs.is_finished() {
+            let Ok(logs) = filter.logs(Filter::new().from_block(0u64).to_block(3u64)).await else {
+                continue
+            };
+            assert_eq!(logs.len(), 4);
+            assert!(logs.iter().all(|log| !log.removed));
+            // both blocks on top of block 1 are from the same fork
+            let fork = fork_hashes
+                .iter()
+                .position(|hashes| logs[2].block_hash == Some(hashes[0]))
+                .unwrap();
+            assert_eq!(logs[3].block_hash, Some(fork_hashes[fork][1]));
+            for log in &logs[2..] {
+                assert_eq!(log.data, Bytes::from(vec![fork as u8 + 2]));
+            }
+            consistent += 1;
+        }
+        reorgs.join().unwrap();
+        assert!(consistent > 0);
+    }
+
+    #[test]
+    fn test_delivered_blocks_bounded() {
+        let mut delivered = DeliveredBlocks::default();
+        for number in 0..MAX_REORG_DEPTH as u64 + 10 {
+            delivered.push(BlockLogs { number, hash: H256::zero(), logs: vec![] });
+        }
+        assert_eq!(delivered.blocks.len(), MAX_REORG_DEPTH);
+        assert_eq!(delivered.blocks.front().unwrap().number, 10);
+    }
+
+    #[tokio::test]
+    async fn test_logs_of_pruned_receipts() {
+        let (pruned, retained) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let receipts = [pruned, retained].map(|address| Receipt {
+            success: true,
+            logs: vec![reth_primitives::Log { address, topics: vec![], data: Bytes::default() }],
+            ..Default::default()
+        });
+        let body = (0..2)
+            .map(|nonce| {
+                let transaction =
+                    reth_primitives::Transaction::Legacy(TxLegacy { nonce, ..Default::default() });
+                TransactionSigned::from_transaction_and_signature(transaction, Signature::default())
+            })
+            .collect::<Vec<_>>();
+
+        // block 1 has a transaction whose receipt is pruned followed by one whose receipt is kept
+        let db = create_test_rw_db();
+        let mut tx = DbTransaction::new(db.as_ref()).unwrap();
+        let genesis = Header::default().seal_slow();
+        let header = Header {
+            number: 1,
+            parent_hash: genesis.hash(),
+            logs_bloom: logs_bloom(receipts.iter().flat_map(|receipt| &receipt.logs)),
+            ..Default::default()
+        }
+        .seal_slow();
+        let block_hash = header.hash();
+        let genesis = SealedBlock { header: genesis, ..Default::default() };
+        insert_canonical_block(tx.deref_mut(), genesis, None, false).unwrap();
+        let block = SealedBlock { header, body: body.clone(), ..Default::default() };
+        insert_canonical_block(tx.deref_mut(), block, Some(vec![Address::zero(); 2]), false)
+            .unwrap();
+        for (tx_number, receipt) in receipts.into_iter().enumerate() {
+            tx.put::<tables::Receipts>(tx_number as u64, receipt).unwrap();
+        }
+        tx.put::<tables::SyncStage>("Finish".to_string(), 1).unwrap();
+        let modes = PruneModes {
+            receipts: Some(PruneMode::Full),
+            receipts_log_filter: ReceiptsLogPruneConfig(BTreeMap::from([(
+                retained,
+                PruneMode::Distance(10),
+            )])),
+        };
+        assert_eq!(tx.prune_receipts(1, &modes).unwrap(), 1);
+        tx.commit().unwrap();
+
+        let client = ShareableDatabase::new(db, Arc::new(MAINNET.clone()));
+        let filter = EthFilter::new(client, testing_pool());
+        for filter_params in
+            [Filter::new().from_block(0u64).to_block(1u64), Filter::new().at_block_hash(block_hash)]
+        {
+            let logs = filter.logs(filter_params).await.unwrap();
+            assert_eq!(logs.len(), 1);
+            assert_eq!(logs[0].address, retained);
+            assert_eq!(logs[0].transaction_hash, Some(body[1].hash));
+            assert_eq!(logs[0].transaction_index, Some(U64::from(1)));
+            assert_eq!(logs[0].transaction_log_index, Some(U64::from(0)));
+            // the logs of the pruned receipt are unknown
+            assert_eq!(logs[0].log_index, None);
         }
     }
 }
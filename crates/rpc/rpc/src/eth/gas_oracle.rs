@@ -0,0 +1,192 @@
+//! Suggestions of priority fees for `eth_maxPriorityFeePerGas`
+
+use crate::eth::fee_history::FeeHistoryEntry;
+use serde::{Deserialize, Serialize};
+
+/// The default number of recent non-empty blocks the suggestion is based on.
+pub const DEFAULT_GAS_ORACLE_BLOCKS: u64 = 20;
+
+/// The default lowest suggested priority fee per gas, 1 wei.
+pub const DEFAULT_MIN_PRIORITY_FEE: u128 = 1;
+
+/// The default highest suggested priority fee per gas, 500 gwei.
+pub const DEFAULT_MAX_PRIORITY_FEE: u128 = 500_000_000_000;
+
+/// Settings for the [GasPriceOracle]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasPriceOracleConfig {
+    /// Number of recent non-empty blocks the suggestion is based on.
+    ///
+    /// Default is [DEFAULT_GAS_ORACLE_BLOCKS]
+    pub blocks: u64,
+    /// Percentile of the gas used by the transactions of a block, sorted by effective tip, at
+    /// which the tip a block required is taken.
+    ///
+    /// Default is 0, the lowest effective tip of the block
+    pub percentile: u32,
+    /// Lowest suggested priority fee per gas, which is also suggested if none of the recent
+    /// blocks has transactions.
+    ///
+    /// Default is [DEFAULT_MIN_PRIORITY_FEE]
+    pub min_priority_fee: u128,
+    /// Highest suggested priority fee per gas.
+    ///
+    /// Default is [DEFAULT_MAX_PRIORITY_FEE]
+    pub max_priority_fee: u128,
+}
+
+impl GasPriceOracleConfig {
+    /// Returns the max number of recent blocks that are searched for non-empty blocks.
+    pub fn max_lookback_blocks(&self) -> u64 {
+        self.blocks.max(1) * 2
+    }
+}
+
+impl Default for GasPriceOracleConfig {
+    fn default() -> Self {
+        Self {
+            blocks: DEFAULT_GAS_ORACLE_BLOCKS,
+            percentile: 0,
+            min_priority_fee: DEFAULT_MIN_PRIORITY_FEE,
+            max_priority_fee: DEFAULT_MAX_PRIORITY_FEE,
+        }
+    }
+}
+
+/// Suggests priority fees from the fee data of recent blocks.
+///
+/// The suggestion is the exponentially weighted moving average of the tips the most recent
+/// non-empty blocks required, where the tip a block required is the effective tip at the
+/// configured percentile of the block. If the base fee of the latest block rose by 12.5% or more,
+/// the most it can rise in one block, the block was full and the suggestion is bumped by 12.5%
+/// as well.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GasPriceOracle {
+    config: GasPriceOracleConfig,
+}
+
+impl GasPriceOracle {
+    /// Creates a new oracle with the given settings.
+    pub fn new(config: GasPriceOracleConfig) -> Self {
+        Self { config }
+    }
+
+    /// Returns the settings of the oracle.
+    pub fn config(&self) -> &GasPriceOracleConfig {
+        &self.config
+    }
+
+    /// Returns the suggested priority fee per gas.
+    ///
+    /// The entries must be the fee data of consecutive blocks with rewards, ordered by ascending
+    /// block number and ending with the latest block.
+    pub fn suggest_priority_fee(&self, entries: &[FeeHistoryEntry]) -> u128 {
+        let percentile = self.config.percentile.min(100) as f64;
+        let mut required_tips = entries
+            .iter()
+            .rev()
+            .filter(|entry| !entry.rewards.is_empty())
+            .take(self.config.blocks.max(1) as usize)
+            .map(|entry| entry.rewards(&[percentile])[0].to::<u128>())
+            .collect::<Vec<_>>();
+        required_tips.reverse();
+
+        let Some((oldest, newer)) = required_tips.split_first() else {
+            return self.config.min_priority_fee
+        };
+        let weight = 2.0 / (required_tips.len() as f64 + 1.0);
+        let average = newer
+            .iter()
+            .fold(*oldest as f64, |average, tip| weight * *tip as f64 + (1.0 - weight) * average);
+        let mut suggestion = average.round() as u128;
+
+        if let [.., parent, latest] = entries {
+            let parent_base_fee = parent.base_fee_per_gas as u128;
+            if parent_base_fee > 0 && latest.base_fee_per_gas as u128 * 8 >= parent_base_fee * 9 {
+                suggestion += suggestion / 8;
+            }
+        }
+
+        suggestion.clamp(self.config.min_priority_fee, self.config.max_priority_fee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::fee_history::TxGasAndReward;
+    use reth_primitives::H256;
+
+    const GWEI: u128 = 1_000_000_000;
+
+    fn entry(base_fee_per_gas: u64, tips: &[u128]) -> FeeHistoryEntry {
+        FeeHistoryEntry {
+            hash: H256::random(),
+            base_fee_per_gas,
+            gas_used_ratio: tips.len() as f64 * 21_000.0 / 30_000_000.0,
+            gas_used: tips.len() as u64 * 21_000,
+            rewards: tips
+                .iter()
+                .map(|reward| TxGasAndReward { gas_used: 21_000, reward: *reward })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn idle_chain() {
+        let oracle = GasPriceOracle::new(GasPriceOracleConfig {
+            min_priority_fee: GWEI,
+            ..Default::default()
+        });
+        let entries = (0..40).map(|_| entry(7, &[])).collect::<Vec<_>>();
+        assert_eq!(oracle.suggest_priority_fee(&entries), GWEI);
+        assert_eq!(oracle.suggest_priority_fee(&[]), GWEI);
+    }
+
+    #[test]
+    fn steady_load() {
+        let oracle = GasPriceOracle::default();
+        let mut entries = (0..40)
+            .map(|i| {
+                // every other block is empty and doesn't lower the suggestion
+                if i % 2 == 0 {
+                    entry(10 * GWEI as u64, &[2 * GWEI, 3 * GWEI, 5 * GWEI])
+                } else {
+                    entry(10 * GWEI as u64, &[])
+                }
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(oracle.suggest_priority_fee(&entries), 2 * GWEI);
+
+        // the percentile selects the tip the blocks required
+        let oracle =
+            GasPriceOracle::new(GasPriceOracleConfig { percentile: 50, ..Default::default() });
+        assert_eq!(oracle.suggest_priority_fee(&entries), 3 * GWEI);
+
+        // recent blocks weigh more than older blocks
+        let oracle = GasPriceOracle::default();
+        entries.extend((0..5).map(|_| entry(10 * GWEI as u64, &[4 * GWEI])));
+        let suggestion = oracle.suggest_priority_fee(&entries);
+        assert!(suggestion > 2 * GWEI + (4 * GWEI - 2 * GWEI) * 5 / 20, "{suggestion}");
+        assert!(suggestion < 4 * GWEI, "{suggestion}");
+    }
+
+    #[test]
+    fn base_fee_spike() {
+        let oracle = GasPriceOracle::default();
+        let mut entries = (0..20).map(|_| entry(8 * GWEI as u64, &[2 * GWEI])).collect::<Vec<_>>();
+        assert_eq!(oracle.suggest_priority_fee(&entries), 2 * GWEI);
+
+        // a full block raises the base fee by 12.5%
+        entries.push(entry(9 * GWEI as u64, &[2 * GWEI]));
+        assert_eq!(oracle.suggest_priority_fee(&entries), 2 * GWEI + 2 * GWEI / 8);
+
+        // the bumped suggestion is still clamped
+        let oracle = GasPriceOracle::new(GasPriceOracleConfig {
+            max_priority_fee: 2 * GWEI,
+            ..Default::default()
+        });
+        assert_eq!(oracle.suggest_priority_fee(&entries), 2 * GWEI);
+    }
+}
@@ -1,8 +1,11 @@
-use reth_primitives::{filter::FilteredParams, Receipt, TxHash, U256};
+use reth_primitives::{filter::FilteredParams, Receipt, TxHash, U64};
 use reth_rpc_types::Log;
 use revm::primitives::B256 as H256;
 
 /// Returns all matching logs of a block's receipts grouped with the hash of their transaction.
+///
+/// The receipts are in the order of the transactions of the block, with `None` for the receipts
+/// that were pruned.
 pub(crate) fn matching_block_logs<I>(
     filter: &FilteredParams,
     block_hash: H256,
@@ -10,7 +13,7 @@ pub(crate) fn matching_block_logs<I>(
     tx_and_receipts: I,
 ) -> Vec<Log>
 where
-    I: IntoIterator<Item = (TxHash, Receipt)>,
+    I: IntoIterator<Item = (TxHash, Option<Receipt>)>,
 {
     let mut all_logs = Vec::new();
     append_matching_block_logs(&mut all_logs, filter, block_hash, block_number, tx_and_receipts);
@@ -18,6 +21,8 @@ where
 }
 
 /// Appends all matching logs of a block's receipts grouped with the hash of their transaction
+///
+/// The log index in the block is unknown for the logs after a pruned receipt, and left out.
 pub(crate) fn append_matching_block_logs<I>(
     all_logs: &mut Vec<Log>,
     filter: &FilteredParams,
@@ -25,12 +30,15 @@ pub(crate) fn append_matching_block_logs<I>(
     block_number: u64,
     tx_and_receipts: I,
 ) where
-    I: IntoIterator<Item = (TxHash, Receipt)>,
+    I: IntoIterator<Item = (TxHash, Option<Receipt>)>,
 {
-    let block_number_u256 = U256::from(block_number);
     // tracks the index of a log in the entire block
-    let mut log_index: u32 = 0;
+    let mut log_index: Option<u32> = Some(0);
     for (transaction_idx, (transaction_hash, receipt)) in tx_and_receipts.into_iter().enumerate() {
+        let Some(receipt) = receipt else {
+            log_index = None;
+            continue
+        };
         let logs = receipt.logs;
         for (transaction_log_idx, log) in logs.into_iter().enumerate() {
             if log_matches_filter(block_hash, block_number, &log, filter) {
@@ -39,16 +47,16 @@ pub(crate) fn append_matching_block_logs<I>(
                     topics: log.topics,
                     data: log.data,
                     block_hash: Some(block_hash),
-                    block_number: Some(block_number_u256),
+                    block_number: Some(U64::from(block_number)),
                     transaction_hash: Some(transaction_hash),
-                    transaction_index: Some(U256::from(transaction_idx)),
-                    log_index: Some(U256::from(log_index)),
-                    transaction_log_index: Some(U256::from(transaction_log_idx)),
+                    transaction_index: Some(U64::from(transaction_idx)),
+                    log_index: log_index.map(U64::from),
+                    transaction_log_index: Some(U64::from(transaction_log_idx)),
                     removed: false,
                 };
                 all_logs.push(log);
             }
-            log_index += 1;
+            log_index = log_index.map(|index| index + 1);
         }
     }
 }
@@ -1,12 +1,17 @@
 //! `eth` namespace handler implementation.
 
 mod api;
+pub mod bytes_limit;
 pub mod cache;
 pub mod error;
+pub mod events;
+pub mod fee_history;
 mod filter;
+pub mod gas_oracle;
 mod id_provider;
 mod logs_utils;
 mod pubsub;
+pub(crate) mod receipt_utils;
 pub(crate) mod revm_utils;
 mod signer;
 
@@ -2,20 +2,28 @@
 
 use crate::eth::logs_utils;
 use futures::StreamExt;
-use jsonrpsee::{types::SubscriptionResult, SubscriptionSink};
-use reth_interfaces::{events::ChainEventSubscriptions, sync::SyncStateProvider};
-use reth_primitives::{filter::FilteredParams, BlockId, TxHash};
-use reth_provider::{BlockProvider, EvmEnvProvider};
-use reth_rpc_api::EthPubSubApiServer;
+use jsonrpsee::{
+    types::{error::INVALID_PARAMS_CODE, ErrorObject, SubscriptionResult},
+    SubscriptionSink,
+};
+use reth_interfaces::{
+    events::{CanonStateNotification, CanonStateSubscriptions, ChainEventSubscriptions},
+    sync::{SyncState, SyncStateProvider},
+};
+use reth_primitives::{filter::FilteredParams, BlockId, SealedHeader, TxHash, H256, U256, U64};
+use reth_provider::{BlockProvider, EvmEnvProvider, StateProvider, StateProviderFactory};
+use reth_rlp::Encodable;
+use reth_rpc_api::{EthPubSubApiServer, RethPubSubApiServer};
 use reth_rpc_types::{
     pubsub::{
         Params, PubSubSyncStatus, SubscriptionKind, SubscriptionResult as EthSubscriptionResult,
         SyncStatusMetadata,
     },
-    Header, Log,
+    Header, Log, RethSubscriptionKind, StorageChangeNotification, StorageSubscriptionParams,
 };
 use reth_tasks::{TaskSpawner, TokioTaskExecutor};
 use reth_transaction_pool::TransactionPool;
+use std::collections::{BTreeMap, BTreeSet};
 use tokio_stream::{
     wrappers::{ReceiverStream, UnboundedReceiverStream},
     Stream,
@@ -23,7 +31,7 @@ use tokio_stream::{
 
 /// `Eth` pubsub RPC implementation.
 ///
-/// This handles `eth_subscribe` RPC calls.
+/// This handles `eth_subscribe` RPC calls, and the reth specific `reth_subscribe` RPC calls.
 #[derive(Clone)]
 pub struct EthPubSub<Client, Pool, Events, Network> {
     /// All nested fields bundled together.
@@ -59,7 +67,7 @@ impl<Client, Pool, Events, Network> EthPubSubApiServer for EthPubSub<Client, Poo
 where
     Client: BlockProvider + EvmEnvProvider + Clone + 'static,
     Pool: TransactionPool + 'static,
-    Events: ChainEventSubscriptions + Clone + 'static,
+    Events: ChainEventSubscriptions + CanonStateSubscriptions + Clone + 'static,
     Network: SyncStateProvider + Clone + 'static,
 {
     /// Handler for `eth_subscribe`
@@ -90,14 +98,14 @@ async fn handle_accepted<Client, Pool, Events, Network>(
 ) where
     Client: BlockProvider + EvmEnvProvider + Clone + 'static,
     Pool: TransactionPool + 'static,
-    Events: ChainEventSubscriptions + Clone + 'static,
+    Events: ChainEventSubscriptions + CanonStateSubscriptions + Clone + 'static,
     Network: SyncStateProvider + Clone + 'static,
 {
     match kind {
         SubscriptionKind::NewHeads => {
             let stream = pubsub
                 .into_new_headers_stream()
-                .map(|block| EthSubscriptionResult::Header(Box::new(block.into())));
+                .map(|header| EthSubscriptionResult::Header(Box::new(header.into())));
             accepted_sink.pipe_from_stream(stream).await;
         }
         SubscriptionKind::Logs => {
@@ -118,26 +126,27 @@ async fn handle_accepted<Client, Pool, Events, Network>(
         }
         SubscriptionKind::Syncing => {
             subscription_task_spawner.spawn(Box::pin(async move {
-                // get new block subscription
-                let mut new_blocks =
-                    UnboundedReceiverStream::new(pubsub.chain_events.subscribe_new_blocks());
-                // get current sync status
-                let mut initial_sync_status = pubsub.network.is_syncing();
-                let current_sub_res = pubsub.sync_status(initial_sync_status).await;
-
+                let mut sync_state = pubsub.network.subscribe_sync_state();
                 // send the current status immediately
-                let _ = accepted_sink.send(&current_sub_res);
-
-                while (new_blocks.next().await).is_some() {
-                    let current_syncing = pubsub.network.is_syncing();
-                    // Only send a new response if the sync status has changed
-                    if current_syncing != initial_sync_status {
-                        // Update the sync status on each new block
-                        initial_sync_status = current_syncing;
-
-                        // send a new message now that the status changed
-                        let sync_status = pubsub.sync_status(current_syncing).await;
-                        let _ = accepted_sink.send(&sync_status);
+                let state = sync_state.borrow_and_update().clone();
+                let mut is_syncing = state.is_syncing();
+                let mut starting_block = pubsub.current_block();
+                let _ = accepted_sink.send(&pubsub.sync_status(&state, starting_block));
+
+                while sync_state.changed().await.is_ok() {
+                    let state = sync_state.borrow_and_update().clone();
+                    // Only send a new response if the node started or stopped syncing
+                    if state.is_syncing() == is_syncing {
+                        continue
+                    }
+                    is_syncing = state.is_syncing();
+                    if is_syncing {
+                        starting_block = pubsub.current_block();
+                    }
+
+                    let sync_status = pubsub.sync_status(&state, starting_block);
+                    if !accepted_sink.send(&sync_status).unwrap_or_default() {
+                        break
                     }
                 }
             }));
@@ -145,6 +154,100 @@ async fn handle_accepted<Client, Pool, Events, Network>(
     }
 }
 
+impl<Client, Pool, Events, Network> RethPubSubApiServer for EthPubSub<Client, Pool, Events, Network>
+where
+    Client: StateProviderFactory + Clone + 'static,
+    Pool: Send + Sync + 'static,
+    Events: CanonStateSubscriptions + 'static,
+    Network: Send + Sync + 'static,
+{
+    /// Handler for `reth_subscribe`
+    fn subscribe(
+        &self,
+        mut sink: SubscriptionSink,
+        kind: RethSubscriptionKind,
+        params: StorageSubscriptionParams,
+    ) -> SubscriptionResult {
+        match kind {
+            RethSubscriptionKind::Storage => {
+                if params.keys.is_empty() {
+                    sink.reject(ErrorObject::owned(
+                        INVALID_PARAMS_CODE,
+                        "at least one storage key is required",
+                        None::<()>,
+                    ))?;
+                    return Ok(())
+                }
+                sink.accept()?;
+
+                let client = self.inner.client.clone();
+                let stream =
+                    UnboundedReceiverStream::new(self.inner.chain_events.subscribe_canon_state())
+                        .filter_map(move |notification| {
+                            futures::future::ready(storage_change_notification(
+                                &client,
+                                &params,
+                                &notification,
+                            ))
+                        });
+                self.subscription_task_spawner.spawn(Box::pin(async move {
+                    sink.pipe_from_stream(stream).await;
+                }));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the new values of the watched slots that were changed by the notification.
+///
+/// The values are taken from the storage changes of the committed blocks. Slots that were only
+/// changed by reverted blocks are read from the state of the new canonical tip.
+fn storage_change_notification<Client: StateProviderFactory>(
+    client: &Client,
+    params: &StorageSubscriptionParams,
+    notification: &CanonStateNotification,
+) -> Option<StorageChangeNotification> {
+    let tip = &notification.committed().last()?.block;
+    let address = params.address;
+
+    let mut reverted = BTreeSet::new();
+    for changes in notification.reverted().iter().filter_map(|block| block.storage.get(&address)) {
+        reverted.extend(
+            params.keys.iter().filter(|key| changes.wiped || changes.slots.contains_key(key)),
+        );
+    }
+
+    let mut storage = BTreeMap::new();
+    for changes in notification.committed().iter().filter_map(|block| block.storage.get(&address)) {
+        for key in &params.keys {
+            match changes.slots.get(key) {
+                Some(value) => storage.insert(*key, *value),
+                None if changes.wiped => storage.insert(*key, U256::ZERO),
+                None => continue,
+            };
+        }
+    }
+
+    reverted.retain(|key| !storage.contains_key(*key));
+    if !reverted.is_empty() {
+        let state = client.history_by_block_hash(tip.hash()).ok()?;
+        for key in reverted {
+            storage.insert(*key, state.storage(address, *key).ok()?.unwrap_or_default());
+        }
+    }
+
+    if storage.is_empty() {
+        return None
+    }
+    Some(StorageChangeNotification {
+        block_hash: tip.hash(),
+        block_number: U64::from(tip.number),
+        address,
+        storage: storage.into_iter().map(|(key, value)| (key, H256(value.to_be_bytes()))).collect(),
+    })
+}
+
 impl<Client, Pool, Events, Network> std::fmt::Debug for EthPubSub<Client, Pool, Events, Network> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("EthPubSub").finish_non_exhaustive()
@@ -170,19 +273,23 @@ impl<Client, Pool, Events, Network> EthPubSubInner<Client, Pool, Events, Network
 where
     Client: BlockProvider + 'static,
 {
-    /// Returns the current sync status for the `syncing` subscription
-    async fn sync_status(&self, is_syncing: bool) -> EthSubscriptionResult {
-        let current_block =
-            self.client.chain_info().map(|info| info.best_number).unwrap_or_default();
-        if is_syncing {
-            EthSubscriptionResult::SyncState(PubSubSyncStatus::Detailed(SyncStatusMetadata {
-                syncing: true,
-                starting_block: 0,
-                current_block,
-                highest_block: Some(current_block),
-            }))
-        } else {
-            EthSubscriptionResult::SyncState(PubSubSyncStatus::Simple(false))
+    /// Returns the number of the canonical tip.
+    fn current_block(&self) -> u64 {
+        self.client.chain_info().map(|info| info.best_number).unwrap_or_default()
+    }
+
+    /// Returns the sync status of the given [SyncState] for the `syncing` subscription
+    fn sync_status(&self, state: &SyncState, starting_block: u64) -> EthSubscriptionResult {
+        match state {
+            SyncState::Idle => EthSubscriptionResult::SyncState(PubSubSyncStatus::Simple(false)),
+            SyncState::Downloading { target_block } | SyncState::Executing { target_block } => {
+                EthSubscriptionResult::SyncState(PubSubSyncStatus::Detailed(SyncStatusMetadata {
+                    syncing: true,
+                    starting_block,
+                    current_block: self.current_block(),
+                    highest_block: Some(*target_block),
+                }))
+            }
         }
     }
 }
@@ -199,17 +306,50 @@ where
 
 impl<Client, Pool, Events, Network> EthPubSubInner<Client, Pool, Events, Network>
 where
-    Client: BlockProvider + EvmEnvProvider + 'static,
-    Events: ChainEventSubscriptions + 'static,
-    Network: SyncStateProvider + 'static,
+    Client: BlockProvider + 'static,
+    Events: CanonStateSubscriptions + 'static,
 {
-    /// Returns a stream that yields all new RPC blocks.
+    /// Returns a stream that yields the headers of all blocks that become canonical.
+    ///
+    /// On a reorg the headers of the new chain are yielded in ascending order, the headers of the
+    /// removed blocks are not repeated.
     fn into_new_headers_stream(self) -> impl Stream<Item = Header> {
-        UnboundedReceiverStream::new(self.chain_events.subscribe_new_blocks()).map(|new_block| {
-            Header::from_primitive_with_hash(new_block.header.as_ref().clone(), new_block.hash)
-        })
+        UnboundedReceiverStream::new(self.chain_events.subscribe_canon_state()).flat_map(
+            move |notification| {
+                let headers = notification
+                    .committed()
+                    .iter()
+                    .map(|block| self.new_header(&block.block.header))
+                    .collect::<Vec<_>>();
+                futures::stream::iter(headers)
+            },
+        )
+    }
+
+    /// Returns the RPC header of a new canonical block.
+    ///
+    /// The total difficulty is only set for pre-merge blocks.
+    fn new_header(&self, header: &SealedHeader) -> Header {
+        let total_difficulty = if header.difficulty.is_zero() {
+            None
+        } else {
+            self.client.header_td(&header.hash()).ok().flatten()
+        };
+
+        let mut rpc_header =
+            Header::from_primitive_with_hash(header.as_ref().clone(), header.hash());
+        rpc_header.total_difficulty = total_difficulty;
+        rpc_header.size = Some(U256::from(header.as_ref().length()));
+        rpc_header
     }
+}
 
+impl<Client, Pool, Events, Network> EthPubSubInner<Client, Pool, Events, Network>
+where
+    Client: BlockProvider + EvmEnvProvider + 'static,
+    Events: ChainEventSubscriptions + 'static,
+    Network: SyncStateProvider + 'static,
+{
     /// Returns a stream that yields all logs that match the given filter.
     fn into_log_stream(self, filter: FilteredParams) -> impl Stream<Item = Log> {
         UnboundedReceiverStream::new(self.chain_events.subscribe_new_blocks())
@@ -231,9 +371,67 @@ where
                     &filter,
                     block_hash,
                     block_number,
-                    transactions.into_iter().map(|tx| tx.hash).zip(receipts),
+                    transactions.into_iter().map(|tx| tx.hash).zip(receipts.into_iter().map(Some)),
                 );
                 futures::stream::iter(all_logs)
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::events::CanonStateBroadcaster;
+    use futures::FutureExt;
+    use reth_interfaces::events::CanonBlock;
+    use reth_primitives::{Bytes, Header as PrimitiveHeader, SealedBlock};
+    use reth_provider::test_utils::NoopProvider;
+    use std::sync::Arc;
+
+    fn block(number: u64, fork: u8) -> CanonBlock {
+        let header = PrimitiveHeader {
+            number,
+            base_fee_per_gas: Some(7),
+            extra_data: Bytes::from(vec![fork]),
+            ..Default::default()
+        };
+        CanonBlock {
+            block: SealedBlock { header: header.seal_slow(), ..Default::default() },
+            receipts: vec![],
+            storage: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn new_heads_of_commit_and_reorg() {
+        let broadcaster = CanonStateBroadcaster::default();
+        let pubsub = EthPubSubInner {
+            pool: (),
+            client: NoopProvider::default(),
+            chain_events: broadcaster.clone(),
+            network: (),
+        };
+        let mut headers = pubsub.into_new_headers_stream();
+
+        let committed = block(1, 0);
+        broadcaster
+            .notify(CanonStateNotification::Commit { new: Arc::new(vec![committed.clone()]) });
+        let new = vec![block(2, 1), block(3, 1)];
+        broadcaster.notify(CanonStateNotification::Reorg {
+            old: Arc::new(vec![block(2, 0), block(3, 0)]),
+            new: Arc::new(new.clone()),
+        });
+
+        // the headers of the new chain follow the committed header in ascending order
+        for expected in [committed].iter().chain(&new) {
+            let header = headers.next().await.unwrap();
+            assert_eq!(header.hash, Some(expected.block.hash()));
+            assert_eq!(header.number, Some(U256::from(expected.block.number)));
+            assert_eq!(header.base_fee_per_gas, Some(U256::from(7)));
+            assert_eq!(header.size, Some(U256::from(expected.block.header.as_ref().length())));
+            assert_eq!(header.total_difficulty, None);
+        }
+        // the headers of the removed blocks are not sent
+        assert!(headers.next().now_or_never().is_none());
+    }
+}
@@ -0,0 +1,92 @@
+use reth_primitives::{
+    rpc, Receipt, TransactionMeta, TransactionSigned, TransactionSignedEcRecovered, U128, U256, U64,
+};
+use reth_rpc_types::{Transaction, TransactionReceipt};
+
+/// Returns the price per gas paid by the transaction in a block with the given base fee.
+pub(crate) fn effective_gas_price(transaction: &TransactionSigned, base_fee: Option<u64>) -> u128 {
+    match (transaction.max_priority_fee_per_gas(), base_fee) {
+        (Some(priority_fee), Some(base_fee)) => {
+            transaction.max_fee_per_gas().min(base_fee as u128 + priority_fee)
+        }
+        _ => transaction.max_fee_per_gas(),
+    }
+}
+
+/// Builds the rpc transaction and receipt of a mined transaction from the receipts of all
+/// transactions of its block.
+///
+/// The contract address of the receipt is only set for contract creations, see
+/// [Transaction::contract_address].
+pub(crate) fn build_transaction_receipt(
+    transaction: TransactionSignedEcRecovered,
+    meta: TransactionMeta,
+    base_fee: Option<u64>,
+    block_receipts: &[Receipt],
+) -> (Transaction, TransactionReceipt) {
+    let index = meta.index as usize;
+    let receipt = &block_receipts[index];
+    let previous_cumulative_gas_used =
+        index.checked_sub(1).map_or(0, |previous| block_receipts[previous].cumulative_gas_used);
+    // the logs are numbered across the whole block
+    let first_log_index = block_receipts[..index].iter().map(|receipt| receipt.logs.len()).sum();
+
+    let effective_gas_price = effective_gas_price(&transaction, base_fee);
+    let transaction_type = u8::from(transaction.tx_type());
+    let transaction = Transaction::from_recovered_with_block_context(
+        transaction,
+        meta.block_hash,
+        meta.block_number,
+        index,
+    );
+
+    let logs = receipt
+        .logs
+        .iter()
+        .enumerate()
+        .map(|(log_index, log)| rpc::Log {
+            address: rpc::H160(log.address.0),
+            topics: log.topics.iter().map(|topic| rpc::H256(topic.0)).collect(),
+            data: rpc::Bytes(log.data.0.clone()),
+            block_hash: Some(rpc::H256(meta.block_hash.0)),
+            block_number: Some(meta.block_number.into()),
+            transaction_hash: Some(rpc::H256(meta.tx_hash.0)),
+            transaction_index: Some(index.into()),
+            log_index: Some((first_log_index + log_index).into()),
+            transaction_log_index: Some(log_index.into()),
+            log_type: None,
+            removed: Some(false),
+        })
+        .collect();
+
+    let receipt = TransactionReceipt {
+        transaction_hash: Some(meta.tx_hash),
+        transaction_index: Some(U64::from(index)),
+        block_hash: Some(meta.block_hash),
+        block_number: Some(U64::from(meta.block_number)),
+        from: transaction.from,
+        to: transaction.to,
+        cumulative_gas_used: U256::from(receipt.cumulative_gas_used),
+        gas_used: Some(U256::from(receipt.cumulative_gas_used - previous_cumulative_gas_used)),
+        contract_address: transaction.contract_address(),
+        logs,
+        state_root: None,
+        logs_bloom: receipt.bloom_slow(),
+        status_code: Some(U64::from(receipt.success as u64)),
+        effective_gas_price: U128::from(effective_gas_price),
+        transaction_type: U256::from(transaction_type),
+        #[cfg(feature = "optimism")]
+        deposit_nonce: receipt.deposit_nonce.map(U64::from),
+        #[cfg(feature = "optimism")]
+        deposit_receipt_version: receipt.deposit_receipt_version.map(U64::from),
+        #[cfg(feature = "optimism")]
+        l1_fee: None,
+        #[cfg(feature = "optimism")]
+        l1_gas_price: None,
+        #[cfg(feature = "optimism")]
+        l1_gas_used: None,
+        #[cfg(feature = "optimism")]
+        l1_fee_scalar: None,
+    };
+    (transaction, receipt)
+}
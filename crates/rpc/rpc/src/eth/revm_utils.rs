@@ -2,10 +2,15 @@
 
 use crate::eth::error::{EthApiError, EthResult, InvalidTransactionError};
 use reth_primitives::{AccessList, Address, U128, U256};
-use reth_rpc_types::CallRequest;
+use reth_revm::stack::InspectorStack;
+use reth_rpc_types::{
+    state::{AccountOverride, BlockOverrides, StateOverride},
+    CallRequest,
+};
 use revm::{
+    db::{CacheDB, DatabaseRef},
     precompile::{Precompiles, SpecId as PrecompilesSpecId},
-    primitives::{BlockEnv, CfgEnv, Env, ResultAndState, SpecId, TransactTo, TxEnv},
+    primitives::{BlockEnv, Bytecode, CfgEnv, Env, ResultAndState, SpecId, TransactTo, TxEnv},
     Database, Inspector,
 };
 
@@ -33,14 +38,26 @@ pub(crate) fn get_precompiles(spec_id: &SpecId) -> Vec<reth_primitives::H160> {
 }
 
 /// Executes the [Env] against the given [Database] without committing state changes.
-pub(crate) fn transact<S>(db: S, env: Env) -> EthResult<(ResultAndState, Env)>
+///
+/// The custom precompiles and opcode gas of the [EvmConfig](reth_revm::EvmConfig) are applied by
+/// the given [InspectorStack], the [Env] is only inspected if there are any.
+pub(crate) fn transact<S>(
+    db: S,
+    env: Env,
+    inspector: &mut InspectorStack,
+) -> EthResult<(ResultAndState, Env)>
 where
     S: Database,
     <S as Database>::Error: Into<EthApiError>,
 {
     let mut evm = revm::EVM::with_env(env);
     evm.database(db);
-    let res = evm.transact()?;
+    // calls have no transaction hash to hook on
+    let res = if inspector.should_inspect(&evm.env, Default::default()) {
+        evm.inspect(inspector)?
+    } else {
+        evm.transact()?
+    };
     Ok((res, evm.env))
 }
 
@@ -58,11 +75,17 @@ where
 }
 
 /// Creates a new [Env] to be used for executing the [CallRequest] in `eth_call`
+///
+/// Like geth, a request without a `from` and without an explicit gas price is executed as if the
+/// zero address sender had unlimited funds.
 pub(crate) fn build_call_evm_env(
-    cfg: CfgEnv,
+    mut cfg: CfgEnv,
     block: BlockEnv,
     request: CallRequest,
 ) -> EthResult<Env> {
+    if request.from.is_none() && request.gas_price.is_none() && request.max_fee_per_gas.is_none() {
+        cfg.disable_balance_check = true;
+    }
     let tx = create_txn_env(&block, request)?;
     Ok(Env { cfg, block, tx })
 }
@@ -89,9 +112,9 @@ pub(crate) fn create_txn_env(block_env: &BlockEnv, request: CallRequest) -> EthR
     let gas_limit = gas.unwrap_or(block_env.gas_limit.min(U256::from(u64::MAX)));
 
     let env = TxEnv {
-        gas_limit: gas_limit.try_into().map_err(|_| InvalidTransactionError::GasUintOverflow)?,
+        gas_limit: gas_limit.try_into().map_err(|_| EthApiError::QuantityOverflow("gas"))?,
         nonce: nonce
-            .map(|n| n.try_into().map_err(|_| InvalidTransactionError::NonceTooHigh))
+            .map(|n| n.try_into().map_err(|_| EthApiError::QuantityOverflow("nonce")))
             .transpose()?,
         caller: from.unwrap_or_default(),
         gas_price,
@@ -100,12 +123,114 @@ pub(crate) fn create_txn_env(block_env: &BlockEnv, request: CallRequest) -> EthR
         value: value.unwrap_or_default(),
         data: data.map(|data| data.0).unwrap_or_default(),
         chain_id: chain_id.map(|c| c.as_u64()),
-        access_list: access_list.map(AccessList::flattened).unwrap_or_default(),
+        access_list: access_list
+            .map(|access_list| AccessList::from(access_list).flattened())
+            .unwrap_or_default(),
     };
 
     Ok(env)
 }
 
+/// Applies the given state overrides (a set of [AccountOverride]) to the [CacheDB].
+pub(crate) fn apply_state_overrides<DB>(
+    overrides: StateOverride,
+    db: &mut CacheDB<DB>,
+) -> EthResult<()>
+where
+    DB: DatabaseRef,
+    EthApiError: From<<DB as DatabaseRef>::Error>,
+{
+    for (account, account_overrides) in overrides {
+        apply_account_override(account, account_overrides, db)?;
+    }
+    Ok(())
+}
+
+/// Applies a single [AccountOverride] to the [CacheDB].
+pub(crate) fn apply_account_override<DB>(
+    account: Address,
+    account_override: AccountOverride,
+    db: &mut CacheDB<DB>,
+) -> EthResult<()>
+where
+    DB: DatabaseRef,
+    EthApiError: From<<DB as DatabaseRef>::Error>,
+{
+    let mut account_info = db.basic(account)?.unwrap_or_default();
+
+    if let Some(nonce) = account_override.nonce {
+        account_info.nonce = nonce;
+    }
+    if let Some(code) = account_override.code {
+        account_info.code = Some(Bytecode::new_raw(code.0));
+    }
+    if let Some(balance) = account_override.balance {
+        account_info.balance = balance;
+    }
+
+    db.insert_account_info(account, account_info);
+
+    // We ensure that not both state and state_diff are set.
+    // If state is set, we must mark the account as "NewlyCreated", so that the old storage
+    // isn't read from
+    match (account_override.state, account_override.state_diff) {
+        (Some(_), Some(_)) => return Err(EthApiError::BothStateAndStateDiffInOverride(account)),
+        (None, None) => {
+            // nothing to do
+        }
+        (Some(new_account_state), None) => {
+            db.replace_account_storage(
+                account,
+                new_account_state
+                    .into_iter()
+                    .map(|(slot, value)| {
+                        (U256::from_be_bytes(slot.0), U256::from_be_bytes(value.0))
+                    })
+                    .collect(),
+            )?;
+        }
+        (None, Some(account_state_diff)) => {
+            for (slot, value) in account_state_diff {
+                db.insert_account_storage(
+                    account,
+                    U256::from_be_bytes(slot.0),
+                    U256::from_be_bytes(value.0),
+                )?;
+            }
+        }
+    };
+
+    Ok(())
+}
+
+/// Applies the given [BlockOverrides] to the [BlockEnv] of a call.
+pub(crate) fn apply_block_overrides(overrides: BlockOverrides, block: &mut BlockEnv) {
+    let BlockOverrides { number, difficulty, time, gas_limit, coinbase, random, base_fee } =
+        overrides;
+
+    if let Some(number) = number {
+        block.number = number;
+    }
+    if let Some(difficulty) = difficulty {
+        block.difficulty = difficulty;
+    }
+    if let Some(time) = time {
+        block.timestamp = time;
+    }
+    if let Some(gas_limit) = gas_limit {
+        block.gas_limit = gas_limit;
+    }
+    if let Some(coinbase) = coinbase {
+        block.coinbase = coinbase;
+    }
+    if let Some(random) = random {
+        block.prevrandao = Some(random);
+    }
+    if let Some(base_fee) = base_fee {
+        block.basefee = base_fee;
+    }
+}
+
 /// Helper type for representing the fees of a [CallRequest]
 pub(crate) struct CallFees {
     /// EIP-1559 priority fee
@@ -172,3 +297,33 @@ impl CallFees {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_out_of_range_quantities() {
+        let block = BlockEnv::default();
+        let too_large = U256::from(u64::MAX) + U256::from(1);
+
+        let request = CallRequest { gas: Some(too_large), ..Default::default() };
+        assert!(matches!(
+            create_txn_env(&block, request),
+            Err(EthApiError::QuantityOverflow("gas"))
+        ));
+        let request = CallRequest { nonce: Some(too_large), ..Default::default() };
+        assert!(matches!(
+            create_txn_env(&block, request),
+            Err(EthApiError::QuantityOverflow("nonce"))
+        ));
+
+        let request = CallRequest {
+            gas: Some(U256::from(u64::MAX)),
+            nonce: Some(U256::from(u64::MAX)),
+            ..Default::default()
+        };
+        let tx = create_txn_env(&block, request).unwrap();
+        assert_eq!((tx.gas_limit, tx.nonce), (u64::MAX, Some(u64::MAX)));
+    }
+}
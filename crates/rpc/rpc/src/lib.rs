@@ -12,20 +12,27 @@
 //! Provides the implementation of all RPC interfaces.
 
 mod admin;
+mod block_tracer;
+mod call_guard;
 mod debug;
 mod engine;
 pub mod eth;
 mod layers;
 mod net;
+mod otterscan;
+mod reth;
 mod trace;
 mod web3;
 
 pub use admin::AdminApi;
+pub use call_guard::{TracingCallGuard, DEFAULT_MAX_TRACING_REQUESTS};
 pub use debug::DebugApi;
 pub use engine::EngineApi;
 pub use eth::{EthApi, EthApiSpec, EthFilter, EthPubSub, EthSubscriptionIdProvider};
 pub use layers::{AuthLayer, AuthValidator, JwtAuthValidator, JwtError, JwtSecret};
 pub use net::NetApi;
+pub use otterscan::OtterscanApi;
+pub use reth::{RethAdminApi, RethApi, RethApiConfig, DEFAULT_MAX_RECEIPTS_RANGE_BLOCKS};
 pub use trace::TraceApi;
 pub use web3::Web3Api;
 
@@ -0,0 +1,628 @@
+use crate::eth::{
+    error::{EthApiError, EthResult},
+    receipt_utils::{build_transaction_receipt, effective_gas_price},
+};
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult as Result;
+use reth_interfaces::provider::ProviderError;
+use reth_primitives::{
+    Address, Block, BlockId, BlockNumber, Receipt, TransactionMeta, TxNumber, H256, U256, U64,
+};
+use reth_provider::{
+    AccountHistoryProvider, AccountProvider, BlockProvider, EvmEnvProvider, StateProviderFactory,
+};
+use reth_revm::config::{WEI_2ETH, WEI_3ETH, WEI_5ETH};
+use reth_rpc_api::{EthApiServer, OtterscanApiServer};
+use reth_rpc_types::{
+    Block as RpcBlock, BlockDetails, BlockIssuance, BlockTransactions, OtsBlock, OtsReceipt,
+    TransactionsWithReceipts,
+};
+use revm::primitives::{CfgEnv, SpecId};
+
+/// `ots` API implementation.
+///
+/// This type provides the functionality for handling the `ots` requests of
+/// [Otterscan](https://github.com/otterscan/otterscan). The transactions of an address are found
+/// with the account history indices, so only the transactions that changed the account are
+/// returned.
+#[derive(Clone)]
+pub struct OtterscanApi<Client, Eth> {
+    /// The client that can interact with the chain.
+    client: Client,
+    /// The implementation of `eth` API
+    eth: Eth,
+}
+
+// === impl OtterscanApi ===
+
+impl<Client, Eth> OtterscanApi<Client, Eth> {
+    /// Create a new instance of the [OtterscanApi]
+    pub fn new(client: Client, eth: Eth) -> Self {
+        Self { client, eth }
+    }
+}
+
+impl<Client, Eth> OtterscanApi<Client, Eth>
+where
+    Client:
+        BlockProvider + StateProviderFactory + EvmEnvProvider + AccountHistoryProvider + 'static,
+{
+    /// Returns the hash of the transaction of the sender with the given nonce.
+    ///
+    /// The nonce of the sender is increased by exactly one transition of its history, which is
+    /// found with a binary search over the nonces before each transition.
+    fn transaction_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: u64,
+    ) -> EthResult<Option<H256>> {
+        let transitions = self.client.account_transitions(sender)?;
+        let nonce_before = |transition| -> EthResult<u64> {
+            Ok(self
+                .client
+                .account_before_transition(sender, transition)?
+                .map(|account| account.nonce)
+                .unwrap_or_default())
+        };
+
+        // find the first transition that happened after the nonce was used
+        let (mut low, mut high) = (0, transitions.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if nonce_before(transitions[mid])? > nonce {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        let transition = if low < transitions.len() {
+            match low.checked_sub(1) {
+                Some(index) => transitions[index],
+                // the nonce was used before the history of the account
+                None => return Ok(None),
+            }
+        } else {
+            let current_nonce = self
+                .client
+                .latest()?
+                .basic_account(sender)?
+                .map(|account| account.nonce)
+                .unwrap_or_default();
+            match transitions.last() {
+                Some(transition) if current_nonce > nonce => *transition,
+                _ => return Ok(None),
+            }
+        };
+
+        let Some((_, Some(tx_number))) = self.client.transition_location(transition)? else {
+            return Ok(None)
+        };
+        let transaction = self
+            .client
+            .transaction_by_id(tx_number)?
+            .ok_or(ProviderError::Transaction { id: tx_number })?;
+        let found = transaction.nonce() == nonce && transaction.recover_signer() == Some(sender);
+        Ok(found.then_some(transaction.hash))
+    }
+
+    /// Returns the block with its issuance and the fees paid by its transactions.
+    fn block_details(&self, block_number: BlockNumber) -> EthResult<Option<BlockDetails>> {
+        let Some(block) = self.client.block_by_number(block_number)? else { return Ok(None) };
+        let total_difficulty = self
+            .client
+            .header_td_by_number(block_number)?
+            .ok_or(ProviderError::TotalDifficulty { number: block_number })?;
+        let receipts = self.block_receipts(&block)?;
+
+        let mut cfg = CfgEnv::default();
+        self.client.fill_cfg_env_with_header(&mut cfg, &block.header)?;
+        let issuance = block_issuance(cfg.spec_id, &block);
+
+        let mut total_fees = U256::ZERO;
+        let mut cumulative_gas_used = 0;
+        for (transaction, receipt) in block.body.iter().zip(&receipts) {
+            let gas_used = receipt.cumulative_gas_used - cumulative_gas_used;
+            cumulative_gas_used = receipt.cumulative_gas_used;
+            total_fees += U256::from(gas_used) *
+                U256::from(effective_gas_price(transaction, block.base_fee_per_gas));
+        }
+
+        let transaction_count = block.body.len();
+        let mut block = RpcBlock::from_block_with_tx_hashes(block, total_difficulty, None);
+        block.transactions = BlockTransactions::Hashes(Vec::new());
+        Ok(Some(BlockDetails {
+            block: OtsBlock { block, transaction_count },
+            issuance,
+            total_fees,
+        }))
+    }
+
+    /// Returns a page of the transactions that changed the account, in the blocks before the
+    /// given block.
+    fn transactions_before(
+        &self,
+        address: Address,
+        block_number: BlockNumber,
+        page_size: usize,
+    ) -> EthResult<TransactionsWithReceipts> {
+        let transitions = self.client.account_transitions(address)?;
+        let mut locations = self.transaction_locations(transitions.into_iter().rev());
+        if block_number != 0 {
+            locations = Box::new(locations.filter(move |location| {
+                location.as_ref().map_or(true, |(number, _)| *number < block_number)
+            }));
+        }
+        let (blocks, has_more) = page_of_blocks(locations, page_size)?;
+
+        let mut page = self.transactions_with_receipts(blocks)?;
+        page.first_page = block_number == 0;
+        page.last_page = !has_more;
+        Ok(page)
+    }
+
+    /// Returns a page of the transactions that changed the account, in the blocks after the given
+    /// block.
+    fn transactions_after(
+        &self,
+        address: Address,
+        block_number: BlockNumber,
+        page_size: usize,
+    ) -> EthResult<TransactionsWithReceipts> {
+        let transitions = self.client.account_transitions(address)?;
+        let locations =
+            self.transaction_locations(transitions.into_iter()).filter(move |location| {
+                location.as_ref().map_or(true, |(number, _)| *number > block_number)
+            });
+        let (mut blocks, has_more) = page_of_blocks(locations, page_size)?;
+        // the transactions of a page are ordered from the newest to the oldest
+        blocks.reverse();
+        for (_, tx_numbers) in &mut blocks {
+            tx_numbers.reverse();
+        }
+
+        let mut page = self.transactions_with_receipts(blocks)?;
+        page.first_page = !has_more;
+        page.last_page = block_number == 0;
+        Ok(page)
+    }
+
+    /// Maps the transitions to the blocks and transactions that executed them, skipping the
+    /// block reward transitions.
+    fn transaction_locations<'a>(
+        &'a self,
+        transitions: impl Iterator<Item = u64> + 'a,
+    ) -> Box<dyn Iterator<Item = EthResult<(BlockNumber, TxNumber)>> + 'a> {
+        Box::new(transitions.filter_map(move |transition| {
+            match self.client.transition_location(transition) {
+                Ok(Some((block_number, Some(tx_number)))) => Some(Ok((block_number, tx_number))),
+                Ok(_) => None,
+                Err(err) => Some(Err(err.into())),
+            }
+        }))
+    }
+
+    /// Loads the transactions and receipts of a page.
+    fn transactions_with_receipts(
+        &self,
+        blocks: Vec<(BlockNumber, Vec<TxNumber>)>,
+    ) -> EthResult<TransactionsWithReceipts> {
+        let mut txs = Vec::new();
+        let mut receipts = Vec::new();
+        for (block_number, tx_numbers) in blocks {
+            let block = self
+                .client
+                .block_by_number(block_number)?
+                .ok_or(EthApiError::UnknownBlockNumber)?;
+            let block_receipts = self.block_receipts(&block)?;
+            let block_hash = block.header.hash_slow();
+
+            for tx_number in tx_numbers {
+                let transaction = self
+                    .client
+                    .transaction_by_id(tx_number)?
+                    .ok_or(ProviderError::Transaction { id: tx_number })?;
+                let index = block
+                    .body
+                    .iter()
+                    .position(|tx| tx.hash == transaction.hash)
+                    .ok_or(ProviderError::Transaction { id: tx_number })?;
+                let meta = TransactionMeta {
+                    tx_hash: transaction.hash,
+                    index: index as u64,
+                    block_hash,
+                    block_number,
+                };
+                let transaction = transaction
+                    .into_ecrecovered()
+                    .ok_or(EthApiError::InvalidTransactionSignature)?;
+                let (transaction, receipt) = build_transaction_receipt(
+                    transaction,
+                    meta,
+                    block.base_fee_per_gas,
+                    &block_receipts,
+                );
+
+                receipts.push(OtsReceipt { receipt, timestamp: U64::from(block.timestamp) });
+                txs.push(transaction);
+            }
+        }
+        Ok(TransactionsWithReceipts { txs, receipts, first_page: false, last_page: false })
+    }
+
+    /// Returns the receipts of all transactions of the block.
+    fn block_receipts(&self, block: &Block) -> EthResult<Vec<Receipt>> {
+        self.client
+            .receipts_by_block(block.number.into())?
+            .filter(|receipts| receipts.len() == block.body.len())
+            .ok_or_else(|| ProviderError::Receipts { number: block.number }.into())
+    }
+}
+
+#[async_trait]
+impl<Client, Eth> OtterscanApiServer for OtterscanApi<Client, Eth>
+where
+    Client:
+        BlockProvider + StateProviderFactory + EvmEnvProvider + AccountHistoryProvider + 'static,
+    Eth: EthApiServer + 'static,
+{
+    /// Handler for `ots_getTransactionBySenderAndNonce`
+    async fn get_transaction_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: u64,
+    ) -> Result<Option<H256>> {
+        Ok(self.transaction_by_sender_and_nonce(sender, nonce)?)
+    }
+
+    /// Handler for `ots_hasCode`
+    async fn has_code(&self, address: Address, block_id: Option<BlockId>) -> Result<bool> {
+        Ok(!self.eth.get_code(address, block_id).await?.is_empty())
+    }
+
+    /// Handler for `ots_getBlockDetails`
+    async fn get_block_details(&self, block_number: BlockNumber) -> Result<Option<BlockDetails>> {
+        Ok(self.block_details(block_number)?)
+    }
+
+    /// Handler for `ots_searchTransactionsBefore`
+    async fn search_transactions_before(
+        &self,
+        address: Address,
+        block_number: BlockNumber,
+        page_size: usize,
+    ) -> Result<TransactionsWithReceipts> {
+        Ok(self.transactions_before(address, block_number, page_size)?)
+    }
+
+    /// Handler for `ots_searchTransactionsAfter`
+    async fn search_transactions_after(
+        &self,
+        address: Address,
+        block_number: BlockNumber,
+        page_size: usize,
+    ) -> Result<TransactionsWithReceipts> {
+        Ok(self.transactions_after(address, block_number, page_size)?)
+    }
+}
+
+impl<Client, Eth> std::fmt::Debug for OtterscanApi<Client, Eth> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OtterscanApi").finish_non_exhaustive()
+    }
+}
+
+/// Groups the transactions by block, taking whole blocks until the page holds at least
+/// `page_size` transactions.
+///
+/// Returns the blocks of the page and whether there are more transactions after the page.
+fn page_of_blocks(
+    locations: impl Iterator<Item = EthResult<(BlockNumber, TxNumber)>>,
+    page_size: usize,
+) -> EthResult<(Vec<(BlockNumber, Vec<TxNumber>)>, bool)> {
+    let mut blocks: Vec<(BlockNumber, Vec<TxNumber>)> = Vec::new();
+    let mut len = 0;
+    for location in locations {
+        let (block_number, tx_number) = location?;
+        match blocks.last_mut() {
+            Some((number, tx_numbers)) if *number == block_number => tx_numbers.push(tx_number),
+            _ if len >= page_size => return Ok((blocks, true)),
+            _ => blocks.push((block_number, vec![tx_number])),
+        }
+        len += 1;
+    }
+    Ok((blocks, false))
+}
+
+/// Returns the ether issued by the block, following the rewards of the executor.
+fn block_issuance(spec_id: SpecId, block: &Block) -> BlockIssuance {
+    let base_reward = if spec_id >= SpecId::MERGE {
+        return BlockIssuance::default()
+    } else if spec_id >= SpecId::PETERSBURG {
+        WEI_2ETH
+    } else if spec_id >= SpecId::BYZANTIUM {
+        WEI_3ETH
+    } else {
+        WEI_5ETH
+    };
+
+    let uncle_reward = block
+        .ommers
+        .iter()
+        .map(|ommer| U256::from(((8 + ommer.number - block.number) as u128 * base_reward) >> 3))
+        .fold(U256::ZERO, |acc, reward| acc + reward);
+    let block_reward = U256::from(base_reward + (base_reward >> 5) * block.ommers.len() as u128);
+    BlockIssuance { block_reward, uncle_reward, issuance: block_reward + uncle_reward }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        mdbx::{test_utils::create_test_rw_db, Env, WriteMap},
+        tables,
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{
+        sign_message, Account, Header, SealedBlock, TransactionKind, TransactionSigned, TxLegacy,
+        TxType, MAINNET,
+    };
+    use reth_provider::{
+        execution_result::{
+            AccountChangeSet, AccountInfoChangeSet, ExecutionResult, TransactionChangeSet,
+        },
+        insert_canonical_block, ShareableDatabase, Transaction as DbTransaction,
+    };
+    use std::{collections::BTreeMap, ops::DerefMut, sync::Arc};
+
+    const GAS_PRICE: u128 = 10;
+
+    fn transfer(key: u64, nonce: u64, to: Address) -> TransactionSigned {
+        let transaction = reth_primitives::Transaction::Legacy(TxLegacy {
+            chain_id: Some(1),
+            nonce,
+            gas_price: GAS_PRICE,
+            gas_limit: 21_000,
+            to: TransactionKind::Call(to),
+            value: 1,
+            input: Default::default(),
+        });
+        let signature =
+            sign_message(H256::from_low_u64_be(key), transaction.signature_hash()).unwrap();
+        TransactionSigned::from_transaction_and_signature(transaction, signature)
+    }
+
+    fn account(nonce: u64, balance: u64) -> Account {
+        Account { nonce, balance: U256::from(balance), bytecode_hash: None }
+    }
+
+    /// Changeset of a transaction that changed the given accounts.
+    fn changeset(accounts: Vec<(Address, AccountInfoChangeSet)>) -> TransactionChangeSet {
+        TransactionChangeSet {
+            receipt: Default::default(),
+            changeset: accounts
+                .into_iter()
+                .map(|(address, account)| {
+                    (address, AccountChangeSet { account, ..Default::default() })
+                })
+                .collect(),
+            new_bytecodes: BTreeMap::new(),
+        }
+    }
+
+    /// A chain where alice sends two transactions to carol in block 1, receives a transaction
+    /// from bob in block 2 and sends another one to carol in block 3.
+    struct TestChain {
+        api: OtterscanApi<ShareableDatabase<Arc<Env<WriteMap>>>, ()>,
+        alice: Address,
+        bob: Address,
+        carol: Address,
+        transactions: Vec<TransactionSigned>,
+    }
+
+    impl TestChain {
+        fn new() -> Self {
+            let carol = Address::from_low_u64_be(3);
+            let alice_txs = [transfer(1, 0, carol), transfer(1, 1, carol)];
+            let alice = alice_txs[0].recover_signer().unwrap();
+            let bob_tx = transfer(2, 0, alice);
+            let bob = bob_tx.recover_signer().unwrap();
+            let last_tx = transfer(1, 2, carol);
+            let transactions =
+                vec![alice_txs[0].clone(), alice_txs[1].clone(), bob_tx.clone(), last_tx.clone()];
+
+            let db = create_test_rw_db();
+            let mut tx = DbTransaction::new(db.as_ref()).unwrap();
+            let bodies = [vec![], alice_txs.to_vec(), vec![bob_tx], vec![last_tx]];
+            let mut parent_hash = H256::zero();
+            let mut tx_number = 0;
+            for (number, body) in bodies.into_iter().enumerate() {
+                let header = Header {
+                    number: number as u64,
+                    parent_hash,
+                    timestamp: 1_000 + number as u64,
+                    difficulty: U256::from(1),
+                    gas_used: 21_000 * body.len() as u64,
+                    ..Default::default()
+                }
+                .seal_slow();
+                parent_hash = header.hash();
+                let senders = body.iter().map(|tx| tx.recover_signer().unwrap()).collect();
+                for index in 0..body.len() {
+                    let receipt = Receipt {
+                        tx_type: TxType::Legacy,
+                        success: true,
+                        cumulative_gas_used: 21_000 * (index as u64 + 1),
+                        logs: vec![],
+                        #[cfg(feature = "optimism")]
+                        deposit_nonce: None,
+                        #[cfg(feature = "optimism")]
+                        deposit_receipt_version: None,
+                    };
+                    tx.put::<tables::Receipts>(tx_number, receipt).unwrap();
+                    tx_number += 1;
+                }
+                let block = SealedBlock { header, body, ommers: vec![], withdrawals: None };
+                insert_canonical_block(tx.deref_mut(), block, Some(senders), false).unwrap();
+            }
+
+            let results = vec![
+                ExecutionResult {
+                    tx_changesets: vec![
+                        changeset(vec![
+                            (alice, AccountInfoChangeSet::Created { new: account(1, 0) }),
+                            (carol, AccountInfoChangeSet::Created { new: account(0, 1) }),
+                        ]),
+                        changeset(vec![
+                            (
+                                alice,
+                                AccountInfoChangeSet::Changed {
+                                    old: account(1, 0),
+                                    new: account(2, 0),
+                                },
+                            ),
+                            (
+                                carol,
+                                AccountInfoChangeSet::Changed {
+                                    old: account(0, 1),
+                                    new: account(0, 2),
+                                },
+                            ),
+                        ]),
+                    ],
+                    block_changesets: BTreeMap::new(),
+                    block_storage_changesets: BTreeMap::new(),
+                },
+                ExecutionResult {
+                    tx_changesets: vec![changeset(vec![
+                        (bob, AccountInfoChangeSet::Created { new: account(1, 0) }),
+                        (
+                            alice,
+                            AccountInfoChangeSet::Changed {
+                                old: account(2, 0),
+                                new: account(2, 1),
+                            },
+                        ),
+                    ])],
+                    block_changesets: BTreeMap::new(),
+                    block_storage_changesets: BTreeMap::new(),
+                },
+                ExecutionResult {
+                    tx_changesets: vec![changeset(vec![
+                        (
+                            alice,
+                            AccountInfoChangeSet::Changed {
+                                old: account(2, 1),
+                                new: account(3, 1),
+                            },
+                        ),
+                        (
+                            carol,
+                            AccountInfoChangeSet::Changed {
+                                old: account(0, 2),
+                                new: account(0, 3),
+                            },
+                        ),
+                    ])],
+                    block_changesets: BTreeMap::new(),
+                    block_storage_changesets: BTreeMap::new(),
+                },
+            ];
+            tx.insert_execution_result(results, &MAINNET, 0).unwrap();
+            let transitions = tx.get_account_transition_ids_from_changeset(0, 4).unwrap();
+            tx.insert_account_history_index(transitions).unwrap();
+            tx.commit().unwrap();
+
+            let client = ShareableDatabase::new(db, Arc::new(MAINNET.clone()));
+            Self { api: OtterscanApi::new(client, ()), alice, bob, carol, transactions }
+        }
+
+        fn hash(&self, index: usize) -> H256 {
+            self.transactions[index].hash
+        }
+
+        fn hashes(&self, page: &TransactionsWithReceipts) -> Vec<H256> {
+            page.txs.iter().map(|tx| tx.hash).collect()
+        }
+    }
+
+    #[test]
+    fn transaction_by_sender_and_nonce() {
+        let chain = TestChain::new();
+        let api = &chain.api;
+        for (nonce, index) in [(0, 0), (1, 1), (2, 3)] {
+            assert_eq!(
+                api.transaction_by_sender_and_nonce(chain.alice, nonce).unwrap(),
+                Some(chain.hash(index))
+            );
+        }
+        assert_eq!(api.transaction_by_sender_and_nonce(chain.alice, 3).unwrap(), None);
+        assert_eq!(api.transaction_by_sender_and_nonce(chain.bob, 0).unwrap(), Some(chain.hash(2)));
+        assert_eq!(api.transaction_by_sender_and_nonce(chain.bob, 1).unwrap(), None);
+        // carol received ether but never sent a transaction
+        assert_eq!(api.transaction_by_sender_and_nonce(chain.carol, 0).unwrap(), None);
+    }
+
+    #[test]
+    fn block_details() {
+        let chain = TestChain::new();
+        let details = chain.api.block_details(1).unwrap().unwrap();
+        assert_eq!(details.block.transaction_count, 2);
+        assert_eq!(details.block.block.transactions, BlockTransactions::Hashes(vec![]));
+        assert_eq!(details.issuance.block_reward, U256::from(WEI_5ETH));
+        assert_eq!(details.issuance.uncle_reward, U256::ZERO);
+        assert_eq!(details.issuance.issuance, U256::from(WEI_5ETH));
+        assert_eq!(details.total_fees, U256::from(2 * 21_000 * GAS_PRICE));
+
+        assert!(chain.api.block_details(4).unwrap().is_none());
+    }
+
+    #[test]
+    fn search_transactions_before() {
+        let chain = TestChain::new();
+
+        // pages hold whole blocks, newest first
+        let page = chain.api.transactions_before(chain.alice, 0, 2).unwrap();
+        assert_eq!(chain.hashes(&page), vec![chain.hash(3), chain.hash(2)]);
+        assert!(page.first_page);
+        assert!(!page.last_page);
+
+        let page = chain.api.transactions_before(chain.alice, 2, 2).unwrap();
+        assert_eq!(chain.hashes(&page), vec![chain.hash(1), chain.hash(0)]);
+        assert!(!page.first_page);
+        assert!(page.last_page);
+
+        let receipt = &page.receipts[0];
+        assert_eq!(receipt.receipt.transaction_hash, Some(chain.hash(1)));
+        assert_eq!(receipt.receipt.from, chain.alice);
+        assert_eq!(receipt.receipt.to, Some(chain.carol));
+        assert_eq!(receipt.receipt.transaction_index, Some(U64::from(1)));
+        assert_eq!(receipt.receipt.gas_used, Some(U256::from(21_000)));
+        assert_eq!(receipt.receipt.cumulative_gas_used, U256::from(42_000));
+        assert_eq!(receipt.timestamp, U64::from(1_001));
+
+        let page = chain.api.transactions_before(chain.bob, 0, 10).unwrap();
+        assert_eq!(chain.hashes(&page), vec![chain.hash(2)]);
+        assert!(page.first_page && page.last_page);
+    }
+
+    #[test]
+    fn search_transactions_after() {
+        let chain = TestChain::new();
+
+        // the first block holds two transactions, which are returned together
+        let page = chain.api.transactions_after(chain.alice, 0, 1).unwrap();
+        assert_eq!(chain.hashes(&page), vec![chain.hash(1), chain.hash(0)]);
+        assert!(!page.first_page);
+        assert!(page.last_page);
+
+        let page = chain.api.transactions_after(chain.alice, 1, 2).unwrap();
+        assert_eq!(chain.hashes(&page), vec![chain.hash(3), chain.hash(2)]);
+        assert!(page.first_page);
+        assert!(!page.last_page);
+
+        let page = chain.api.transactions_after(chain.carol, 3, 2).unwrap();
+        assert!(page.txs.is_empty());
+        assert!(page.first_page && !page.last_page);
+    }
+}
@@ -163,6 +163,17 @@ pub(crate) fn rpc_err(code: i32, msg: impl Into<String>, data: Option<&[u8]>) ->
     ))
 }
 
+/// Constructs a JSON-RPC error, consisting of `code`, `message` and `data` serialized as JSON.
+pub(crate) fn rpc_err_with_json_data(
+    code: i32,
+    msg: impl Into<String>,
+    data: impl serde::Serialize,
+) -> RpcError {
+    RpcError::Call(jsonrpsee::types::error::CallError::Custom(
+        jsonrpsee::types::error::ErrorObject::owned(code, msg.into(), Some(data)),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
 
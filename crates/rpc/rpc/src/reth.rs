@@ -0,0 +1,619 @@
+use crate::{
+    eth::error::{EthApiError, EthResult},
+    result::{internal_rpc_err, rpc_error_with_code},
+    TracingCallGuard,
+};
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult;
+use reth_executor::executor::Executor;
+use reth_interfaces::{
+    blockchain_tree::{BlockchainTreeEngine, BlockchainTreeViewer},
+    sync::{ChainUnwinder, UnwindRequestError},
+};
+use reth_primitives::{
+    proofs::EMPTY_ROOT, Account, Address, BlockId, BlockNumber, BlockNumberOrTag, Bytes, Receipt,
+    H256, KECCAK_EMPTY, U64,
+};
+use reth_provider::{
+    trie::MultiProof, AccountProvider, BlockIdProvider, BlockProvider, ChainSpecProvider,
+    HeaderProvider, ReceiptProvider, StateProvider, StateProviderFactory, StorageHistoryVerifier,
+};
+use reth_revm::{
+    database::{State, SubState},
+    EthEvmConfig, EvmConfig,
+};
+use reth_rpc_api::{RethAdminApiServer, RethApiServer};
+use reth_rpc_engine_api::{EngineApiHandle, EngineApiMessage};
+use reth_rpc_types::{
+    error::EthRpcErrorCode, AccountInfo, AccountPoolStatus, BlockchainTreeState, ImportOrigin,
+    ImportedTransaction, MultiProofAccount, MultiProofResponse, MultiProofSlot,
+    PooledTransactionStatus, ReorgStats, StageUnwindResult, StorageHistoryDivergence,
+    TransactionTiming, UnwindResult,
+};
+use reth_transaction_pool::{
+    import::import_raw_transactions, TransactionOrigin, TransactionPool, ValidPoolTransaction,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::oneshot;
+
+/// The default max number of blocks served by a `reth_getBlockReceiptsRange` request.
+pub const DEFAULT_MAX_RECEIPTS_RANGE_BLOCKS: u64 = 1_000;
+
+/// The max number of addresses of a `reth_getAccountsInfo` request.
+pub const MAX_ACCOUNTS_INFO_ADDRESSES: usize = 1_000;
+
+/// The max number of accounts of a `reth_getMultiProof` request.
+pub const MAX_MULTIPROOF_ACCOUNTS: usize = 1_000;
+
+/// The max total number of storage slots of a `reth_getMultiProof` request.
+pub const MAX_MULTIPROOF_SLOTS: usize = 10_000;
+
+/// The max number of transactions of a `reth_importRawTransactions` request.
+pub const MAX_IMPORT_TRANSACTIONS: usize = 10_000;
+
+/// The max total size in bytes of the transactions of a `reth_importRawTransactions` request:
+/// 32 MiB
+pub const MAX_IMPORT_SIZE: usize = 32 * 1024 * 1024;
+
+/// Settings for the `reth` namespace
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RethApiConfig {
+    /// Max number of blocks served by a `reth_getBlockReceiptsRange` request.
+    ///
+    /// Default is [DEFAULT_MAX_RECEIPTS_RANGE_BLOCKS]
+    pub max_receipts_range_blocks: u64,
+}
+
+impl Default for RethApiConfig {
+    fn default() -> Self {
+        Self { max_receipts_range_blocks: DEFAULT_MAX_RECEIPTS_RANGE_BLOCKS }
+    }
+}
+
+/// `reth` API implementation.
+///
+/// This type provides the functionality for handling `reth` related requests.
+pub struct RethApi<Client, Pool> {
+    /// The client that can interact with the chain
+    client: Client,
+    /// The transaction pool transactions are imported into
+    pool: Pool,
+    /// The blockchain tree, if the node runs one
+    blockchain_tree: Option<Arc<dyn BlockchainTreeViewer>>,
+    /// Settings of the handlers
+    config: RethApiConfig,
+    /// The configuration of the EVM that re-executes blocks
+    evm_config: Arc<dyn EvmConfig>,
+    /// Caps the number of concurrent block re-executions
+    tracing_call_guard: TracingCallGuard,
+}
+
+impl<Client, Pool> RethApi<Client, Pool> {
+    /// Creates a new instance of `RethApi`.
+    pub fn new(client: Client, pool: Pool, config: RethApiConfig) -> Self {
+        RethApi {
+            client,
+            pool,
+            blockchain_tree: None,
+            config,
+            evm_config: Arc::new(EthEvmConfig::default()),
+            tracing_call_guard: Default::default(),
+        }
+    }
+
+    /// Configures the blockchain tree returned by `reth_getBlockchainTreeState` and
+    /// `reth_getReorgStats`.
+    pub fn with_blockchain_tree(mut self, blockchain_tree: Arc<dyn BlockchainTreeViewer>) -> Self {
+        self.blockchain_tree = Some(blockchain_tree);
+        self
+    }
+
+    /// Re-executes blocks with the given [EvmConfig].
+    pub fn with_evm_config(mut self, evm_config: Arc<dyn EvmConfig>) -> Self {
+        self.evm_config = evm_config;
+        self
+    }
+
+    /// Shares the cap of concurrent block re-executions with other handlers.
+    pub fn with_tracing_call_guard(mut self, tracing_call_guard: TracingCallGuard) -> Self {
+        self.tracing_call_guard = tracing_call_guard;
+        self
+    }
+}
+
+impl<Client, Pool> RethApi<Client, Pool>
+where
+    Client: BlockProvider + StateProviderFactory + ChainSpecProvider + 'static,
+    Pool: TransactionPool + 'static,
+{
+    /// Returns the receipts of the blocks in the given inclusive range, read in one pass over the
+    /// receipts table.
+    fn block_receipts_range(
+        &self,
+        from: BlockNumberOrTag,
+        to: BlockNumberOrTag,
+    ) -> EthResult<Vec<Vec<Receipt>>> {
+        let from =
+            self.client.convert_block_number(from)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let to = self.client.convert_block_number(to)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        if from > to {
+            return Err(EthApiError::InvalidBlockRange)
+        }
+        if to - from >= self.config.max_receipts_range_blocks {
+            return Err(EthApiError::BlockRangeExceedsMax(self.config.max_receipts_range_blocks))
+        }
+        if to > self.client.chain_info()?.best_number {
+            return Err(EthApiError::UnknownBlockNumber)
+        }
+        Ok(self.client.receipts_by_block_range(from..=to)?)
+    }
+
+    /// Returns the info of the accounts at the given block, read from one state provider.
+    ///
+    /// The accounts are read in the order of their addresses, so that the database is walked
+    /// with one cursor.
+    fn accounts_info(
+        &self,
+        addresses: &[Address],
+        block_id: Option<BlockId>,
+    ) -> EthResult<Vec<AccountInfo>> {
+        let block_id = block_id.unwrap_or_else(|| BlockNumberOrTag::Latest.into());
+        let number =
+            self.client.block_number_for_id(block_id)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        if number == self.client.chain_info()?.best_number {
+            Self::read_accounts_info(&self.client.latest()?, addresses)
+        } else {
+            Self::read_accounts_info(&self.client.history_by_block_number(number)?, addresses)
+        }
+    }
+
+    /// Reads the info of the accounts from the state, deduplicating and sorting the addresses
+    /// for the walk.
+    fn read_accounts_info(
+        state: &impl StateProvider,
+        addresses: &[Address],
+    ) -> EthResult<Vec<AccountInfo>> {
+        let mut sorted = addresses.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        let accounts = state.basic_accounts(&sorted)?;
+
+        let mut infos = Vec::with_capacity(sorted.len());
+        for account in accounts {
+            let Account { nonce, balance, bytecode_hash } = account.unwrap_or_default();
+            let code = match bytecode_hash {
+                Some(hash) if hash != KECCAK_EMPTY => state
+                    .bytecode_by_hash(hash)?
+                    .map(|code| Bytes::from(code.original_bytes()))
+                    .filter(|code| !code.is_empty()),
+                _ => None,
+            };
+            infos.push(AccountInfo {
+                balance,
+                nonce: nonce.into(),
+                code_hash: bytecode_hash.unwrap_or(KECCAK_EMPTY),
+                code,
+            });
+        }
+        Ok(addresses
+            .iter()
+            .map(|address| {
+                let idx = sorted.binary_search(address).expect("address was queried");
+                infos[idx].clone()
+            })
+            .collect())
+    }
+
+    /// Returns the proof of the targets at the given block.
+    fn multiproof(
+        &self,
+        targets: &HashMap<Address, Vec<H256>>,
+        block_id: Option<BlockId>,
+    ) -> EthResult<MultiProofResponse> {
+        let block_id = block_id.unwrap_or_else(|| BlockNumberOrTag::Latest.into());
+        let number =
+            self.client.block_number_for_id(block_id)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let proof = if number == self.client.chain_info()?.best_number {
+            self.client.latest()?.multiproof(targets)?
+        } else {
+            self.client.history_by_block_number(number)?.multiproof(targets)?
+        };
+        Ok(multiproof_response(proof))
+    }
+
+    /// Returns the contract code with the given hash.
+    ///
+    /// Codes are never removed, so the latest state knows the codes of all blocks.
+    fn code_by_hash(&self, code_hash: H256) -> EthResult<Option<Bytes>> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Some(Bytes::default()))
+        }
+        let code = self.client.latest()?.bytecode_by_hash(code_hash)?;
+        Ok(code.map(|code| Bytes::from(code.original_bytes())))
+    }
+
+    /// Returns the transactions of the account in the pool together with the nonce of the account
+    /// in the latest state.
+    fn account_pool_status(&self, address: Address) -> EthResult<AccountPoolStatus> {
+        let nonce = self
+            .client
+            .latest()?
+            .basic_account(address)?
+            .map(|account| account.nonce)
+            .unwrap_or_default();
+        let txs = self.pool.pending_and_queued_by_sender(address);
+        let status = |txs: Vec<Arc<ValidPoolTransaction<Pool::Transaction>>>| {
+            txs.into_iter()
+                .map(|tx| PooledTransactionStatus { hash: *tx.hash(), nonce: tx.nonce().into() })
+                .collect()
+        };
+        Ok(AccountPoolStatus {
+            nonce: nonce.into(),
+            pending: status(txs.pending),
+            queued: status(txs.queued),
+            // a gap below the latest nonce was filled by a block the pool didn't process yet
+            nonce_gap: txs.nonce_gap.filter(|gap| *gap >= nonce).map(Into::into),
+        })
+    }
+}
+
+#[async_trait]
+impl<Client, Pool> RethApiServer for RethApi<Client, Pool>
+where
+    Client: BlockProvider + StateProviderFactory + ChainSpecProvider + Clone + 'static,
+    Pool: TransactionPool + 'static,
+{
+    /// Handler for `reth_importRawTransactions`
+    async fn import_raw_transactions(
+        &self,
+        transactions: Vec<Bytes>,
+        origin: Option<ImportOrigin>,
+    ) -> RpcResult<Vec<ImportedTransaction>> {
+        if transactions.len() > MAX_IMPORT_TRANSACTIONS {
+            return Err(EthApiError::TooManyTransactions(MAX_IMPORT_TRANSACTIONS).into())
+        }
+        let size = transactions.iter().map(|tx| tx.len()).sum::<usize>();
+        if size > MAX_IMPORT_SIZE {
+            return Err(EthApiError::ParamTooLarge {
+                param: "transactions",
+                len: size,
+                max: MAX_IMPORT_SIZE,
+            }
+            .into())
+        }
+
+        let origin = match origin.unwrap_or_default() {
+            ImportOrigin::Local => TransactionOrigin::Local,
+            ImportOrigin::External => TransactionOrigin::External,
+        };
+        let results = import_raw_transactions(&self.pool, origin, transactions).await;
+        Ok(results
+            .into_iter()
+            .map(|res| match res {
+                Ok(hash) => ImportedTransaction::imported(hash),
+                Err(err) => ImportedTransaction::rejected(err),
+            })
+            .collect())
+    }
+
+    /// Handler for `reth_getAccountPoolStatus`
+    async fn account_pool_status(&self, address: Address) -> RpcResult<AccountPoolStatus> {
+        Ok(RethApi::account_pool_status(self, address)?)
+    }
+
+    /// Handler for `reth_getBlockchainTreeState`
+    async fn blockchain_tree_state(&self) -> RpcResult<BlockchainTreeState> {
+        let tree = self
+            .blockchain_tree
+            .as_ref()
+            .ok_or_else(|| internal_rpc_err("blockchain tree is not available"))?;
+        Ok(tree.tree_state())
+    }
+
+    /// Handler for `reth_getReorgStats`
+    async fn reorg_stats(&self) -> RpcResult<ReorgStats> {
+        let tree = self
+            .blockchain_tree
+            .as_ref()
+            .ok_or_else(|| internal_rpc_err("blockchain tree is not available"))?;
+        Ok(tree.reorg_stats())
+    }
+
+    /// Handler for `reth_getBlockReceiptsRange`
+    async fn block_receipts_range(
+        &self,
+        from: BlockNumberOrTag,
+        to: BlockNumberOrTag,
+    ) -> RpcResult<Vec<Vec<Receipt>>> {
+        Ok(RethApi::block_receipts_range(self, from, to)?)
+    }
+
+    /// Handler for `reth_getAccountInfo`
+    async fn account_info(
+        &self,
+        address: Address,
+        block_id: Option<BlockId>,
+    ) -> RpcResult<AccountInfo> {
+        let mut infos = RethApi::accounts_info(self, &[address], block_id)?;
+        Ok(infos.remove(0))
+    }
+
+    /// Handler for `reth_getAccountsInfo`
+    async fn accounts_info(
+        &self,
+        addresses: Vec<Address>,
+        block_id: Option<BlockId>,
+    ) -> RpcResult<Vec<AccountInfo>> {
+        if addresses.len() > MAX_ACCOUNTS_INFO_ADDRESSES {
+            return Err(EthApiError::TooManyAddresses(MAX_ACCOUNTS_INFO_ADDRESSES).into())
+        }
+        Ok(RethApi::accounts_info(self, &addresses, block_id)?)
+    }
+
+    /// Handler for `reth_getMultiProof`
+    async fn multiproof(
+        &self,
+        targets: HashMap<Address, Vec<H256>>,
+        block_id: Option<BlockId>,
+    ) -> RpcResult<MultiProofResponse> {
+        if targets.len() > MAX_MULTIPROOF_ACCOUNTS {
+            return Err(EthApiError::TooManyAddresses(MAX_MULTIPROOF_ACCOUNTS).into())
+        }
+        if targets.values().map(Vec::len).sum::<usize>() > MAX_MULTIPROOF_SLOTS {
+            return Err(EthApiError::TooManySlots(MAX_MULTIPROOF_SLOTS).into())
+        }
+        Ok(RethApi::multiproof(self, &targets, block_id)?)
+    }
+
+    /// Handler for `reth_getCodeByHash`
+    async fn code_by_hash(&self, code_hash: H256) -> RpcResult<Option<Bytes>> {
+        Ok(RethApi::code_by_hash(self, code_hash)?)
+    }
+
+    /// Handler for `reth_traceBlockTiming`
+    async fn trace_block_timing(&self, block_id: BlockId) -> RpcResult<Vec<TransactionTiming>> {
+        let client = self.client.clone();
+        let evm_config = self.evm_config.clone();
+        let timings = self
+            .tracing_call_guard
+            .spawn_blocking(move || block_timing(&client, evm_config, block_id))
+            .await?;
+        Ok(timings)
+    }
+}
+
+/// Re-executes the canonical block on the state of its parent and profiles every transaction.
+fn block_timing<Client>(
+    client: &Client,
+    evm_config: Arc<dyn EvmConfig>,
+    block_id: BlockId,
+) -> EthResult<Vec<TransactionTiming>>
+where
+    Client: BlockProvider + StateProviderFactory + ChainSpecProvider,
+{
+    let number = client.block_number_for_id(block_id)?.ok_or(EthApiError::UnknownBlockNumber)?;
+    // the genesis block is not executed
+    let parent_number = number.checked_sub(1).ok_or(EthApiError::UnknownBlockNumber)?;
+    let block = client.block(number.into())?.ok_or(EthApiError::UnknownBlockNumber)?;
+    let total_difficulty =
+        client.header_td_by_number(number)?.ok_or(EthApiError::UnknownBlockNumber)?;
+    let parent = client.history_by_block_number(parent_number)?;
+
+    let profiles = Executor::with_evm_config(
+        client.chain_spec(),
+        SubState::new(State::new(parent)),
+        evm_config,
+    )
+    .profile_transactions(&block, total_difficulty, None)
+    .map_err(reth_interfaces::Error::from)?;
+
+    Ok(block
+        .body
+        .iter()
+        .zip(profiles)
+        .enumerate()
+        .map(|(index, (transaction, profile))| TransactionTiming {
+            transaction_hash: transaction.hash(),
+            transaction_index: U64::from(index),
+            time_micros: U64::from(profile.elapsed.as_micros() as u64),
+            gas_used: U64::from(profile.gas_used),
+            state_reads: U64::from(profile.state_reads),
+            state_writes: U64::from(profile.state_writes),
+            sloads: U64::from(profile.sloads),
+            sstores: U64::from(profile.sstores),
+        })
+        .collect())
+}
+
+/// Converts the proof of the provider into the rpc response, zeroing absent accounts.
+fn multiproof_response(proof: MultiProof) -> MultiProofResponse {
+    let accounts = proof
+        .accounts
+        .into_iter()
+        .map(|(address, proven)| {
+            let storage = proven
+                .storage
+                .iter()
+                .map(|(key, value)| MultiProofSlot { key: *key, value: *value })
+                .collect();
+            match proven.account {
+                Some(account) => MultiProofAccount {
+                    address,
+                    balance: account.balance(),
+                    nonce: account.nonce().into(),
+                    code_hash: account.code_hash(),
+                    storage_hash: account.storage_root(),
+                    storage,
+                },
+                None => MultiProofAccount {
+                    address,
+                    code_hash: KECCAK_EMPTY,
+                    storage_hash: EMPTY_ROOT,
+                    storage,
+                    ..Default::default()
+                },
+            }
+        })
+        .collect();
+    MultiProofResponse { state_root: proof.state_root, nodes: proof.nodes, accounts }
+}
+
+impl<Client, Pool> std::fmt::Debug for RethApi<Client, Pool> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RethApi").finish_non_exhaustive()
+    }
+}
+
+/// `reth` operator API implementation, served by the authenticated server.
+pub struct RethAdminApi {
+    /// Unwinds the local chain
+    unwinder: Arc<dyn ChainUnwinder>,
+    /// Handle to the engine whose forkchoice state is reset after an unwind
+    engine_tx: EngineApiHandle,
+    /// Checks the storage history against the storage changesets
+    storage_history: Arc<dyn StorageHistoryVerifier>,
+    /// The blockchain tree whose canonical hashes are reloaded after an unwind
+    blockchain_tree: Option<Arc<dyn BlockchainTreeEngine>>,
+}
+
+impl RethAdminApi {
+    /// Creates a new instance of `RethAdminApi`.
+    pub fn new(
+        unwinder: Arc<dyn ChainUnwinder>,
+        engine_tx: EngineApiHandle,
+        storage_history: Arc<dyn StorageHistoryVerifier>,
+    ) -> Self {
+        Self { unwinder, engine_tx, storage_history, blockchain_tree: None }
+    }
+
+    /// Configures the blockchain tree of the node.
+    ///
+    /// The tree still indexes the unwound blocks as canonical, it is updated after every unwind so
+    /// that new blocks can be inserted on top of the unwound head.
+    pub fn with_blockchain_tree(mut self, blockchain_tree: Arc<dyn BlockchainTreeEngine>) -> Self {
+        self.blockchain_tree = Some(blockchain_tree);
+        self
+    }
+}
+
+#[async_trait]
+impl RethAdminApiServer for RethAdminApi {
+    /// Handler for `reth_unwindTo`
+    async fn unwind_to(&self, block_number: BlockNumber) -> RpcResult<UnwindResult> {
+        let summary = self.unwinder.unwind_to(block_number).await.map_err(unwind_rpc_err)?;
+
+        if let Some(blockchain_tree) = &self.blockchain_tree {
+            blockchain_tree
+                .update_canonical_hashes(summary.target)
+                .map_err(|err| internal_rpc_err(err.to_string()))?;
+        }
+
+        // the forkchoice state still points to the unwound blocks, which would be synced again
+        let (tx, rx) = oneshot::channel();
+        self.engine_tx.send(EngineApiMessage::ResetForkchoiceState(block_number, tx));
+        let state = rx
+            .await
+            .map_err(|err| internal_rpc_err(err.to_string()))?
+            .map_err(|err| internal_rpc_err(err.to_string()))?;
+
+        Ok(UnwindResult {
+            block_number: U64::from(summary.target),
+            block_hash: state.head_block_hash,
+            stages: summary
+                .stages
+                .into_iter()
+                .map(|stage| StageUnwindResult {
+                    stage: stage.stage,
+                    from: U64::from(stage.from),
+                    to: U64::from(stage.to),
+                })
+                .collect(),
+        })
+    }
+
+    /// Handler for `reth_verifyStorageHistory`
+    async fn verify_storage_history(
+        &self,
+        address: Address,
+        slot: H256,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> RpcResult<Option<StorageHistoryDivergence>> {
+        if from > to {
+            return Err(EthApiError::InvalidBlockRange.into())
+        }
+        let divergence = self
+            .storage_history
+            .verify_storage_history(address, slot, from..=to)
+            .map_err(EthApiError::from)?;
+        Ok(divergence.map(|divergence| StorageHistoryDivergence {
+            block_number: U64::from(divergence.block_number),
+            expected: divergence.expected,
+            historical: divergence.historical,
+        }))
+    }
+}
+
+/// Converts a refused or failed unwind into an rpc error, refusals while syncing are retryable.
+fn unwind_rpc_err(err: UnwindRequestError) -> jsonrpsee::core::Error {
+    let code = match err {
+        UnwindRequestError::PipelineBusy => EthRpcErrorCode::ResourceUnavailable.code(),
+        UnwindRequestError::AboveTip { .. } => jsonrpsee::types::error::INVALID_PARAMS_CODE,
+        UnwindRequestError::BelowPruneHorizon { .. } => EthRpcErrorCode::ResourceNotFound.code(),
+        UnwindRequestError::Failed(_) | UnwindRequestError::PipelineClosed => {
+            jsonrpsee::types::error::INTERNAL_ERROR_CODE
+        }
+    };
+    rpc_error_with_code(code, err.to_string())
+}
+
+impl std::fmt::Debug for RethAdminApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RethAdminApi").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Block, Header, TransactionSigned, H256};
+    use reth_provider::test_utils::MockEthProvider;
+    use reth_transaction_pool::test_utils::testing_pool;
+
+    #[tokio::test]
+    async fn test_block_receipts_range() {
+        let provider = MockEthProvider::default();
+        // every other block is empty
+        let mut expected = Vec::new();
+        for number in 0..6u64 {
+            let receipts = (0..number % 2)
+                .map(|_| Receipt {
+                    success: true,
+                    cumulative_gas_used: number,
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+            let block = Block {
+                header: Header { number, ..Default::default() },
+                body: vec![TransactionSigned::default(); receipts.len()],
+                ..Default::default()
+            };
+            let hash = H256::from_low_u64_be(number);
+            provider.add_block(hash, block);
+            provider.add_receipts(hash, receipts.clone());
+            expected.push(receipts);
+        }
+        let api =
+            RethApi::new(provider, testing_pool(), RethApiConfig { max_receipts_range_blocks: 4 });
+
+        let receipts = RethApiServer::block_receipts_range(&api, 1.into(), 4.into()).await.unwrap();
+        assert_eq!(receipts, expected[1..=4]);
+
+        // the range is capped and has to be known
+        assert!(RethApiServer::block_receipts_range(&api, 1.into(), 5.into()).await.is_err());
+        assert!(RethApiServer::block_receipts_range(&api, 3.into(), 2.into()).await.is_err());
+        assert!(RethApiServer::block_receipts_range(&api, 4.into(), 6.into()).await.is_err());
+    }
+}
@@ -1,17 +1,27 @@
 use crate::{
-    eth::{cache::EthStateCache, EthTransactions},
+    block_tracer::{BlockTracer, TracedBlock},
+    eth::{
+        cache::EthStateCache,
+        error::{EthApiError, EthResult},
+        EthTransactions,
+    },
     result::internal_rpc_err,
+    TracingCallGuard,
 };
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult as Result;
-use reth_primitives::{BlockId, Bytes, H256};
-use reth_provider::{BlockProvider, EvmEnvProvider, StateProviderFactory};
+use reth_executor::blockchain_tree::pending::PendingStateViewer;
+use reth_primitives::{BlockId, Bytes, H256, U64};
+use reth_provider::{
+    BlockProvider, ChainSpecProvider, EvmEnvProvider, HeaderProvider, StateProviderFactory,
+};
+use reth_revm::{tracing::TraceInspectorConfig, EvmConfig};
 use reth_rpc_api::TraceApiServer;
 use reth_rpc_types::{
     trace::{filter::TraceFilter, parity::*},
     CallRequest, Index,
 };
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
 
 /// `trace` API implementation.
 ///
@@ -24,21 +34,88 @@ pub struct TraceApi<Client, Eth> {
     eth_api: Eth,
     /// The async cache frontend for eth related data
     eth_cache: EthStateCache,
+    /// Replays the traced blocks.
+    tracer: BlockTracer<Client>,
+    /// Caps the number of concurrent block traces.
+    tracing_call_guard: TracingCallGuard,
 }
 
 // === impl TraceApi ===
 
-impl<Client, Eth> TraceApi<Client, Eth> {
+impl<Client: Clone, Eth> TraceApi<Client, Eth> {
     /// Create a new instance of the [TraceApi]
     pub fn new(client: Client, eth_api: Eth, eth_cache: EthStateCache) -> Self {
-        Self { client, eth_api, eth_cache }
+        Self {
+            tracer: BlockTracer::new(client.clone()),
+            client,
+            eth_api,
+            eth_cache,
+            tracing_call_guard: Default::default(),
+        }
+    }
+
+    /// Re-executes blocks with the given [EvmConfig].
+    pub fn with_evm_config(mut self, evm_config: Arc<dyn EvmConfig>) -> Self {
+        self.tracer.set_evm_config(evm_config);
+        self
+    }
+
+    /// Traces the blocks of the blockchain tree that are not yet canonical on the state of the
+    /// tree.
+    pub fn with_pending_state(mut self, pending_state: Arc<dyn PendingStateViewer>) -> Self {
+        self.tracer.set_pending_state(pending_state);
+        self
+    }
+
+    /// Shares the cap of concurrent block traces with other handlers.
+    pub fn with_tracing_call_guard(mut self, tracing_call_guard: TracingCallGuard) -> Self {
+        self.tracing_call_guard = tracing_call_guard;
+        self
+    }
+}
+
+/// Returns the parity style traces of all transactions of the replayed block, localized to their
+/// transaction and block.
+///
+/// The traces of the block and ommer rewards are not included.
+fn localized_traces(traced: TracedBlock) -> Vec<LocalizedTransactionTrace> {
+    let TracedBlock { hash, block, traces } = traced;
+    let block_number = U64::from(block.header.number);
+    traces
+        .into_iter()
+        .zip(block.body)
+        .enumerate()
+        .flat_map(|(index, ((arena, _), transaction))| {
+            arena.parity_traces().into_iter().map(move |trace| LocalizedTransactionTrace {
+                trace,
+                transaction_position: Some(index),
+                transaction_hash: Some(transaction.hash),
+                block_number,
+                block_hash: hash,
+            })
+        })
+        .collect()
+}
+
+/// Returns `None` instead of the errors of unknown blocks.
+fn ok_if_known<T>(result: EthResult<T>) -> EthResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(EthApiError::UnknownBlockHash | EthApiError::UnknownBlockNumber) => Ok(None),
+        Err(err) => Err(err),
     }
 }
 
 #[async_trait]
 impl<Client, Eth> TraceApiServer for TraceApi<Client, Eth>
 where
-    Client: BlockProvider + StateProviderFactory + EvmEnvProvider + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + StateProviderFactory
+        + EvmEnvProvider
+        + ChainSpecProvider
+        + Clone
+        + 'static,
     Eth: EthTransactions + 'static,
 {
     /// Handler for `trace_call`
@@ -89,11 +166,25 @@ where
     }
 
     /// Handler for `trace_block`
+    ///
+    /// A block of the blockchain tree that is not yet canonical can be traced by its hash.
     async fn trace_block(
         &self,
-        _block_id: BlockId,
+        block_id: BlockId,
     ) -> Result<Option<Vec<LocalizedTransactionTrace>>> {
-        Err(internal_rpc_err("unimplemented"))
+        let tracer = self.tracer.clone();
+        let traces = self
+            .tracing_call_guard
+            .spawn_blocking(move || {
+                let config = TraceInspectorConfig::default_parity();
+                let traced = match block_id {
+                    BlockId::Hash(hash) => tracer.trace_block_by_hash(hash.block_hash, config),
+                    BlockId::Number(number) => tracer.trace_block_by_number(number, config),
+                };
+                ok_if_known(traced).map(|traced| traced.map(localized_traces))
+            })
+            .await?;
+        Ok(traces)
     }
 
     /// Handler for `trace_filter`
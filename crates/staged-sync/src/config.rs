@@ -5,8 +5,9 @@ use reth_downloaders::{
     headers::reverse_headers::ReverseHeadersDownloaderBuilder,
 };
 use reth_network::{config::rng_secret_key, NetworkConfigBuilder, PeersConfig};
+use reth_primitives::PruneModes;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 /// Configuration for the reth node.
 #[derive(Debug, Clone, Default, Deserialize, PartialEq, Serialize)]
@@ -17,6 +18,8 @@ pub struct Config {
     pub stages: StageConfig,
     /// Configuration for the discovery service.
     pub peers: PeersConfig,
+    /// Configuration for the pruning of historical data.
+    pub prune: PruneModes,
 }
 
 impl Config {
@@ -148,14 +151,21 @@ impl Default for SenderRecoveryConfig {
 pub struct ExecutionConfig {
     /// The maximum number of blocks to execution before committing progress to the database.
     pub commit_threshold: u64,
+    /// The execution time above which a block is logged as slow.
+    #[serde(default = "default_slow_block_threshold", with = "humantime_serde")]
+    pub slow_block_threshold: Duration,
 }
 
 impl Default for ExecutionConfig {
     fn default() -> Self {
-        Self { commit_threshold: 5_000 }
+        Self { commit_threshold: 5_000, slow_block_threshold: default_slow_block_threshold() }
     }
 }
 
+fn default_slow_block_threshold() -> Duration {
+    Duration::from_secs(1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::Config;
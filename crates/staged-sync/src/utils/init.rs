@@ -2,38 +2,158 @@ use reth_db::{
     cursor::DbCursorRO,
     database::Database,
     mdbx::{Env, WriteMap},
+    migration::migrate,
     tables,
     transaction::{DbTx, DbTxMut},
+    version::{create_db_version_file, get_db_version, DB_VERSION},
 };
-use reth_primitives::{Account, ChainSpec, H256};
-use std::{path::Path, sync::Arc};
-use tracing::debug;
+use reth_primitives::{
+    keccak256, Account, BlockNumber, Bytecode, ChainSpec, Hardfork, SealedHeader, StorageEntry,
+    H256, U256,
+};
+use reth_provider::{
+    check_chain_spec, get_lowest_available_block, set_hardforks, set_lowest_available_block,
+    ChainSpecMismatch,
+};
+use reth_stages::stages::{
+    ACCOUNT_HASHING, BODIES, EXECUTION, FINISH, HEADERS, INDEX_ACCOUNT_HISTORY,
+    INDEX_STORAGE_HISTORY, MERKLE_EXECUTION, MERKLE_UNWIND, SENDER_RECOVERY, STORAGE_HASHING,
+    TOTAL_DIFFICULTY, TRANSACTION_LOOKUP,
+};
+use std::{fmt, path::Path, str::FromStr, sync::Arc};
+use tracing::{debug, info};
 
 /// Opens up an existing database or creates a new one at the specified path.
+///
+/// Existing databases of a previous [version](reth_db::version) are migrated to the current
+/// version.
 pub fn init_db<P: AsRef<Path>>(path: P) -> eyre::Result<Env<WriteMap>> {
-    std::fs::create_dir_all(path.as_ref())?;
-    let db = reth_db::mdbx::Env::<reth_db::mdbx::WriteMap>::open(
-        path.as_ref(),
-        reth_db::mdbx::EnvKind::RW,
-    )?;
+    let path = path.as_ref();
+    let is_new = path.read_dir().map_or(true, |mut entries| entries.next().is_none());
+    // databases without a version file predate it
+    let version = match get_db_version(path)? {
+        Some(version) => version,
+        None if is_new => DB_VERSION,
+        None => 0,
+    };
+
+    std::fs::create_dir_all(path)?;
+    let db = reth_db::mdbx::Env::<reth_db::mdbx::WriteMap>::open(path, reth_db::mdbx::EnvKind::RW)?;
     db.create_tables()?;
 
+    if version < DB_VERSION {
+        info!(target: "reth::db", from = version, to = DB_VERSION, "Migrating database");
+        migrate(&db, version)?;
+    }
+    if version < DB_VERSION || is_new {
+        create_db_version_file(path)?;
+    }
+
     Ok(db)
 }
 
 /// Database initialization error type.
 #[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
 pub enum InitDatabaseError {
-    /// Attempted to reinitialize database with inconsistent genesis block
-    #[error("Genesis hash mismatch: expected {expected}, got {actual}")]
-    GenesisHashMismatch { expected: H256, actual: H256 },
+    /// Attempted to reinitialize database with a chain spec of another chain, or with different
+    /// hardfork activations
+    #[error(transparent)]
+    ChainSpecMismatch(#[from] ChainSpecMismatch),
+
+    /// Attempted to start from a checkpoint on a database that already synced blocks
+    #[error("Cannot start from checkpoint #{checkpoint}, the database already synced up to block #{synced}")]
+    AlreadySynced { checkpoint: BlockNumber, synced: BlockNumber },
+
+    /// The header fetched for the checkpoint does not match it
+    #[error("Checkpoint header mismatch: expected #{expected_number} {expected_hash}, got #{number} {hash}")]
+    CheckpointHeaderMismatch {
+        expected_number: BlockNumber,
+        expected_hash: H256,
+        number: BlockNumber,
+        hash: H256,
+    },
+
+    /// Checkpoints are only supported on chains that are past the merge, whose total difficulty
+    /// is fixed
+    #[error("Chain has no terminal total difficulty, cannot start from a checkpoint")]
+    MissingTerminalTotalDifficulty,
 
     /// Low-level database error.
     #[error(transparent)]
     DBError(#[from] reth_db::Error),
+
+    /// Provider error.
+    #[error(transparent)]
+    Provider(#[from] reth_interfaces::Error),
+}
+
+/// A trusted block the node starts syncing from instead of genesis.
+///
+/// The headers, bodies and state of the blocks below the checkpoint are not downloaded. Formatted
+/// as `<number>:<hash>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustedCheckpoint {
+    /// The number of the checkpoint block
+    pub number: BlockNumber,
+    /// The hash of the checkpoint block
+    pub hash: H256,
+}
+
+impl fmt::Display for TrustedCheckpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{:?}", self.number, self.hash)
+    }
+}
+
+impl FromStr for TrustedCheckpoint {
+    type Err = ParseTrustedCheckpointError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (number, hash) = s.split_once(':').ok_or(ParseTrustedCheckpointError::InvalidFormat)?;
+        Ok(Self {
+            number: number.parse()?,
+            hash: hash.parse().map_err(|_| ParseTrustedCheckpointError::InvalidHash)?,
+        })
+    }
+}
+
+/// Error while parsing a [TrustedCheckpoint].
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+pub enum ParseTrustedCheckpointError {
+    /// The checkpoint is not formatted as `<number>:<hash>`
+    #[error("invalid checkpoint, expected <number>:<hash>")]
+    InvalidFormat,
+    /// The block number is invalid
+    #[error("invalid checkpoint block number: {0}")]
+    InvalidNumber(#[from] std::num::ParseIntError),
+    /// The block hash is invalid
+    #[error("invalid checkpoint block hash")]
+    InvalidHash,
 }
 
+/// The stages whose progress is set to the checkpoint block by [init_from_checkpoint].
+const CHECKPOINT_STAGES: [reth_stages::StageId; 6] =
+    [HEADERS, TOTAL_DIFFICULTY, BODIES, SENDER_RECOVERY, TRANSACTION_LOOKUP, FINISH];
+
+/// The stages that need the state of the checkpoint to run on top of it.
+///
+/// Their progress is set to the checkpoint block once the state of the checkpoint was imported,
+/// see [init_checkpoint_state](crate::utils::state_dump::init_checkpoint_state). Until then the
+/// state above the checkpoint is unavailable and the stages can't run.
+pub const CHECKPOINT_STATE_STAGES: [reth_stages::StageId; 7] = [
+    EXECUTION,
+    MERKLE_UNWIND,
+    ACCOUNT_HASHING,
+    STORAGE_HASHING,
+    MERKLE_EXECUTION,
+    INDEX_STORAGE_HISTORY,
+    INDEX_ACCOUNT_HISTORY,
+];
+
 /// Write the genesis block if it has not already been written
+///
+/// The hardfork activations of the chain spec are recorded. If the database was already
+/// initialized, the chain spec must match it, see [check_chain_spec].
 #[allow(clippy::field_reassign_with_default)]
 pub fn init_genesis<DB: Database>(
     db: Arc<DB>,
@@ -43,55 +163,161 @@ pub fn init_genesis<DB: Database>(
 
     let header = chain.genesis_header();
     let hash = header.hash_slow();
+    let hardforks = chain.hardforks().iter().map(|(fork, condition)| (*fork, *condition));
+    let hardforks = hardforks.collect::<Vec<_>>();
 
-    let tx = db.tx()?;
-    if let Some((_, db_hash)) = tx.cursor_read::<tables::CanonicalHeaders>()?.first()? {
-        if db_hash == hash {
-            debug!("Genesis already written, skipping.");
-            return Ok(hash)
-        }
-
-        return Err(InitDatabaseError::GenesisHashMismatch { expected: hash, actual: db_hash })
+    let tx = db.tx_mut()?;
+    if tx.cursor_read::<tables::CanonicalHeaders>()?.first()?.is_some() {
+        check_chain_spec(&tx, &chain)?;
+        // hardforks that are scheduled after the head of the database may have changed
+        set_hardforks(&tx, &hardforks)?;
+        tx.commit()?;
+        debug!("Genesis already written, skipping.");
+        return Ok(hash)
     }
 
-    drop(tx);
     debug!("Writing genesis block.");
-    let tx = db.tx_mut()?;
 
     // Insert account state
     for (address, account) in &genesis.alloc {
+        let mut bytecode_hash = None;
+        if let Some(code) = &account.code {
+            let code_hash = keccak256(code);
+            tx.put::<tables::Bytecodes>(code_hash, Bytecode::new_raw(code.clone().into()))?;
+            bytecode_hash = Some(code_hash);
+        }
         tx.put::<tables::PlainAccountState>(
             *address,
             Account {
                 nonce: account.nonce.unwrap_or_default(),
                 balance: account.balance,
-                bytecode_hash: None,
+                bytecode_hash,
             },
         )?;
+        for (key, value) in account.storage.iter().flatten() {
+            let value = U256::from_be_bytes(value.0);
+            // zero slots are not part of the state
+            if value != U256::ZERO {
+                tx.put::<tables::PlainStorageState>(*address, StorageEntry { key: *key, value })?;
+            }
+        }
     }
 
     // Insert header
     tx.put::<tables::CanonicalHeaders>(0, hash)?;
     tx.put::<tables::HeaderNumbers>(hash, 0)?;
-    tx.put::<tables::BlockBodies>(0, Default::default())?;
+    tx.put::<tables::BlockBodyIndices>(0, Default::default())?;
     tx.put::<tables::BlockTransitionIndex>(0, 0)?;
     tx.put::<tables::HeaderTD>(0, header.difficulty.into())?;
     tx.put::<tables::Headers>(0, header)?;
+    set_hardforks(&tx, &hardforks)?;
 
     tx.commit()?;
     Ok(hash)
 }
 
+/// Anchors an unsynced database at the header of a trusted checkpoint.
+///
+/// The checkpoint header is written as if it was synced, the progress of the stages that download
+/// blocks is set to the checkpoint and the blocks below it are marked as unavailable, see
+/// [LOWEST_AVAILABLE_BLOCK](reth_provider::LOWEST_AVAILABLE_BLOCK).
+///
+/// The state at the checkpoint is not written, so the state above the checkpoint is unavailable:
+/// the blocks above it are synced but not executed until the state of the checkpoint is imported
+/// with [init_checkpoint_state](crate::utils::state_dump::init_checkpoint_state), see
+/// [is_state_available].
+///
+/// The total difficulty of the checkpoint is the terminal total difficulty of the chain, which is
+/// why only checkpoints of chains past the merge are supported.
+///
+/// Does nothing if the database was already anchored at the same checkpoint.
+pub fn init_from_checkpoint<DB: Database>(
+    db: Arc<DB>,
+    chain: Arc<ChainSpec>,
+    checkpoint: TrustedCheckpoint,
+    header: SealedHeader,
+) -> Result<(), InitDatabaseError> {
+    if header.number != checkpoint.number || header.hash() != checkpoint.hash {
+        return Err(InitDatabaseError::CheckpointHeaderMismatch {
+            expected_number: checkpoint.number,
+            expected_hash: checkpoint.hash,
+            number: header.number,
+            hash: header.hash(),
+        })
+    }
+    let total_difficulty = chain
+        .fork(Hardfork::Paris)
+        .ttd()
+        .ok_or(InitDatabaseError::MissingTerminalTotalDifficulty)?;
+
+    let tx = db.tx_mut()?;
+    let synced = HEADERS.get_progress(&tx)?.unwrap_or_default();
+    if synced > 0 {
+        if get_lowest_available_block(&tx)? == checkpoint.number + 1 &&
+            tx.get::<tables::CanonicalHeaders>(checkpoint.number)? == Some(checkpoint.hash)
+        {
+            debug!(target: "reth::db", %checkpoint, "Checkpoint already written, skipping.");
+            return Ok(())
+        }
+        return Err(InitDatabaseError::AlreadySynced { checkpoint: checkpoint.number, synced })
+    }
+
+    info!(target: "reth::db", %checkpoint, "Writing checkpoint block.");
+    let (header, hash) = header.split();
+    tx.put::<tables::CanonicalHeaders>(checkpoint.number, hash)?;
+    tx.put::<tables::HeaderNumbers>(hash, checkpoint.number)?;
+    tx.put::<tables::BlockBodyIndices>(checkpoint.number, Default::default())?;
+    tx.put::<tables::BlockTransitionIndex>(checkpoint.number, 0)?;
+    tx.put::<tables::HeaderTD>(checkpoint.number, total_difficulty.into())?;
+    tx.put::<tables::Headers>(checkpoint.number, header)?;
+
+    for stage in CHECKPOINT_STAGES {
+        stage.save_progress(&tx, checkpoint.number)?;
+    }
+    set_lowest_available_block(&tx, checkpoint.number + 1)?;
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Returns false if the database was anchored at a trusted checkpoint with
+/// [init_from_checkpoint] and the state of the checkpoint was not imported yet.
+///
+/// The state above the checkpoint is unavailable in that case, the stages of
+/// [CHECKPOINT_STATE_STAGES] can't run.
+pub fn is_state_available<'a, TX: DbTx<'a>>(tx: &TX) -> Result<bool, InitDatabaseError> {
+    Ok(get_lowest_available_block(tx)? == 0 || EXECUTION.get_progress(tx)?.is_some())
+}
+
 #[cfg(test)]
 mod tests {
 
-    use std::sync::Arc;
+    use std::{collections::HashMap, sync::Arc};
 
-    use super::{init_genesis, InitDatabaseError};
-    use reth_db::mdbx::test_utils::create_test_rw_db;
+    use super::{
+        init_db, init_from_checkpoint, init_genesis, is_state_available, ChainSpecMismatch,
+        InitDatabaseError, TrustedCheckpoint, CHECKPOINT_STATE_STAGES,
+    };
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_rw_db, DatabaseFlags},
+        migration::legacy,
+        tables,
+        transaction::{DbTx, DbTxMut},
+        version::{db_version_file_path, get_db_version, DB_VERSION},
+    };
+    use reth_interfaces::{provider::ProviderError, test_utils::generators::random_block_range};
     use reth_primitives::{
-        GOERLI, GOERLI_GENESIS, MAINNET, MAINNET_GENESIS, SEPOLIA, SEPOLIA_GENESIS,
+        keccak256, Address, BlockId, Bytes, ChainSpecBuilder, ForkCondition, GenesisAccount,
+        Hardfork, GOERLI, GOERLI_GENESIS, H256, MAINNET, MAINNET_GENESIS, SEPOLIA, SEPOLIA_GENESIS,
+        U256,
+    };
+    use reth_provider::{
+        get_hardforks, insert_canonical_block, AccountProvider, BlockProvider,
+        ChainSpecDatabaseExt, HeaderProvider, ShareableDatabase, StateProvider,
+        StateProviderFactory, TransactionsProvider,
     };
+    use reth_stages::stages::{EXECUTION, HEADERS};
 
     #[test]
     fn success_init_genesis_mainnet() {
@@ -120,6 +346,33 @@ mod tests {
         assert_eq!(genesis_hash, SEPOLIA_GENESIS);
     }
 
+    #[test]
+    fn init_genesis_writes_code_and_storage() {
+        let address = Address::from_low_u64_be(0x1000);
+        let code = Bytes::from(vec![0x60, 0x2a, 0x60, 0x00, 0x55]);
+        let storage = HashMap::from([
+            (H256::from_low_u64_be(1), H256::from_low_u64_be(42)),
+            (H256::from_low_u64_be(2), H256::zero()),
+        ]);
+        let mut chain = ChainSpecBuilder::mainnet().paris_activated().build();
+        chain.genesis = chain.genesis.extend_accounts([(
+            address,
+            GenesisAccount::default().with_code(Some(code.clone())).with_storage(Some(storage)),
+        )]);
+
+        let db = create_test_rw_db();
+        init_genesis(db.clone(), Arc::new(chain.clone())).unwrap();
+
+        let provider = ShareableDatabase::new(db, Arc::new(chain));
+        let state = provider.latest().unwrap();
+        let account = state.basic_account(address).unwrap().unwrap();
+        assert_eq!(account.bytecode_hash, Some(keccak256(&code)));
+        let written = state.account_code(address).unwrap().unwrap();
+        assert_eq!(Bytes::from(written.original_bytes()), code);
+        assert_eq!(state.storage(address, H256::from_low_u64_be(1)).unwrap(), Some(U256::from(42)));
+        assert_eq!(state.storage(address, H256::from_low_u64_be(2)).unwrap(), None);
+    }
+
     #[test]
     fn fail_init_inconsistent_db() {
         let db = create_test_rw_db();
@@ -128,12 +381,189 @@ mod tests {
         // Try to init db with a different genesis block
         let genesis_hash = init_genesis(db, Arc::new(MAINNET.clone()));
 
+        let mismatch =
+            ChainSpecMismatch::Genesis { chain_spec: MAINNET_GENESIS, database: SEPOLIA_GENESIS };
         assert_eq!(
             genesis_hash.unwrap_err(),
-            InitDatabaseError::GenesisHashMismatch {
-                expected: MAINNET_GENESIS,
-                actual: SEPOLIA_GENESIS
-            }
-        )
+            InitDatabaseError::ChainSpecMismatch(mismatch.clone())
+        );
+
+        let provider = ShareableDatabase::new(db, Arc::new(SEPOLIA.clone()));
+        assert_eq!(MAINNET.matches_database(&provider), Err(mismatch));
+        assert_eq!(SEPOLIA.matches_database(&provider), Ok(()));
+    }
+
+    #[test]
+    fn fail_init_hardfork_override() {
+        let db = create_test_rw_db();
+        let merged = ChainSpecBuilder::mainnet().paris_activated().build();
+        init_genesis(db.clone(), Arc::new(merged.clone())).unwrap();
+
+        // the database was synced with paris activated at genesis
+        let pre_merge = ChainSpecBuilder::mainnet().london_activated().build();
+        assert_eq!(
+            init_genesis(db.clone(), Arc::new(pre_merge)).unwrap_err(),
+            InitDatabaseError::ChainSpecMismatch(ChainSpecMismatch::Hardfork {
+                fork: Hardfork::Paris,
+                chain_spec: MAINNET.fork(Hardfork::Paris),
+                database: merged.fork(Hardfork::Paris),
+            })
+        );
+
+        // hardforks above the head of the database can be rescheduled
+        let rescheduled = ChainSpecBuilder::mainnet()
+            .paris_activated()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(1))
+            .build();
+        init_genesis(db.clone(), Arc::new(rescheduled)).unwrap();
+        let recorded = db.view(|tx| get_hardforks(tx)).unwrap().unwrap().unwrap();
+        assert!(recorded.contains(&(Hardfork::Shanghai, ForkCondition::Timestamp(1))));
+    }
+
+    #[test]
+    fn init_db_writes_version_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db");
+        init_db(&path).unwrap();
+        assert_eq!(get_db_version(&path).unwrap(), Some(DB_VERSION));
+    }
+
+    #[test]
+    fn init_db_migrates_database_without_version_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = init_db(dir.path()).unwrap();
+
+        // a database of version 0 has the legacy bodies table and no version file
+        let tx = db.begin_rw_txn().unwrap();
+        tx.create_db(Some(legacy::BlockBodies::const_name()), DatabaseFlags::default()).unwrap();
+        tx.commit().unwrap();
+        let body = legacy::StoredBlockBody { start_tx_id: 0, tx_count: 0 };
+        db.update(|tx| tx.put::<legacy::BlockBodies>(0, body)).unwrap().unwrap();
+        drop(db);
+        std::fs::remove_file(db_version_file_path(dir.path())).unwrap();
+
+        let db = init_db(dir.path()).unwrap();
+        assert_eq!(get_db_version(dir.path()).unwrap(), Some(DB_VERSION));
+        let indices = db.view(|tx| tx.get::<tables::BlockBodyIndices>(0)).unwrap().unwrap();
+        assert_eq!(indices, Some(Default::default()));
+    }
+
+    #[test]
+    fn parse_trusted_checkpoint() {
+        let hash = H256::random();
+        let checkpoint: TrustedCheckpoint = format!("100:{hash:?}").parse().unwrap();
+        assert_eq!(checkpoint, TrustedCheckpoint { number: 100, hash });
+        assert_eq!(checkpoint.to_string().parse::<TrustedCheckpoint>().unwrap(), checkpoint);
+
+        assert!("100".parse::<TrustedCheckpoint>().is_err());
+        assert!("abc:0x00".parse::<TrustedCheckpoint>().is_err());
+        assert!("100:0x00".parse::<TrustedCheckpoint>().is_err());
+    }
+
+    #[test]
+    fn sync_from_checkpoint() {
+        let chain = Arc::new(ChainSpecBuilder::mainnet().paris_activated().build());
+        let blocks = random_block_range(0..201, H256::zero(), 0..3);
+        let checkpoint = TrustedCheckpoint { number: 100, hash: blocks[100].hash() };
+
+        let db = create_test_rw_db();
+        init_genesis(db.clone(), chain.clone()).unwrap();
+
+        // the header must match the checkpoint
+        assert!(matches!(
+            init_from_checkpoint(db.clone(), chain.clone(), checkpoint, blocks[99].header.clone()),
+            Err(InitDatabaseError::CheckpointHeaderMismatch { .. })
+        ));
+
+        init_from_checkpoint(db.clone(), chain.clone(), checkpoint, blocks[100].header.clone())
+            .unwrap();
+        // the checkpoint can be applied again
+        init_from_checkpoint(db.clone(), chain.clone(), checkpoint, blocks[100].header.clone())
+            .unwrap();
+
+        // the blocks above the checkpoint are synced on top of it
+        let tx = db.tx_mut().unwrap();
+        for block in blocks[101..].iter().cloned() {
+            insert_canonical_block(&tx, block, None, false).unwrap();
+        }
+        HEADERS.save_progress(&tx, 200).unwrap();
+        tx.commit().unwrap();
+
+        let provider = ShareableDatabase::new(db.clone(), chain.clone());
+        for number in [0, 50, 100] {
+            let unavailable = |res: reth_interfaces::Result<_>| {
+                matches!(
+                    res,
+                    Err(reth_interfaces::Error::Provider(ProviderError::BlockUnavailable {
+                        number: n,
+                        lowest_available: 101,
+                    })) if n == number
+                )
+            };
+            assert!(unavailable(provider.header_by_number(number).map(drop)));
+            assert!(unavailable(provider.block(BlockId::Number(number.into())).map(drop)));
+            assert!(unavailable(
+                provider.transactions_by_block(BlockId::Number(number.into())).map(drop)
+            ));
+            assert!(unavailable(provider.history_by_block_number(number).map(drop)));
+        }
+        assert!(matches!(
+            provider.header(&blocks[100].hash()),
+            Err(reth_interfaces::Error::Provider(ProviderError::BlockUnavailable { .. }))
+        ));
+
+        for block in &blocks[101..] {
+            let number = block.number;
+            assert_eq!(
+                provider.header_by_number(number).unwrap(),
+                Some(block.header.clone().unseal())
+            );
+            let stored = provider.block(BlockId::Number(number.into())).unwrap().unwrap();
+            assert_eq!(stored.body, block.body);
+            // the state above the checkpoint is unavailable until the blocks were executed
+            assert!(matches!(
+                provider.history_by_block_number(number),
+                Err(reth_interfaces::Error::Provider(ProviderError::StateUnavailable { number: n }))
+                    if n == number
+            ));
+        }
+        // blocks above the checkpoint build on the terminal total difficulty, zero on this chain
+        assert_eq!(provider.header_td_by_number(100).unwrap(), Some(U256::ZERO));
+        assert_eq!(provider.header_td_by_number(101).unwrap(), Some(blocks[101].difficulty));
+
+        // the state stages only run once the state of the checkpoint was imported
+        let tx = db.tx().unwrap();
+        assert!(!is_state_available(&tx).unwrap());
+        for stage in CHECKPOINT_STATE_STAGES {
+            assert_eq!(stage.get_progress(&tx).unwrap(), None);
+        }
+        drop(tx);
+        assert!(matches!(
+            provider.latest(),
+            Err(reth_interfaces::Error::Provider(ProviderError::StateUnavailable { number: 100 }))
+        ));
+
+        let tx = db.tx_mut().unwrap();
+        EXECUTION.save_progress(&tx, 100).unwrap();
+        tx.commit().unwrap();
+        assert!(db.view(|tx| is_state_available(tx)).unwrap().unwrap());
+        assert!(provider.latest().is_ok());
+        assert!(matches!(
+            provider.history_by_block_number(101),
+            Err(reth_interfaces::Error::Provider(ProviderError::StateUnavailable { number: 101 }))
+        ));
+
+        let tx = db.tx_mut().unwrap();
+        EXECUTION.save_progress(&tx, 150).unwrap();
+        tx.commit().unwrap();
+        assert!(provider.history_by_block_number(150).is_ok());
+        assert!(provider.history_by_block_number(151).is_err());
+
+        // a synced database can't be anchored at another checkpoint
+        let other = TrustedCheckpoint { number: 150, hash: blocks[150].hash() };
+        assert!(matches!(
+            init_from_checkpoint(db, chain, other, blocks[150].header.clone()),
+            Err(InitDatabaseError::AlreadySynced { checkpoint: 150, synced: 200 })
+        ));
     }
 }
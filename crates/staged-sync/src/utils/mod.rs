@@ -14,6 +14,9 @@ pub mod chainspec;
 /// Utilities for initializing parts of the chain
 pub mod init;
 
+/// Utilities for exporting and importing the state at a block
+pub mod state_dump;
+
 /// Finds all files in a directory with a given postfix.
 pub fn find_all_files_with_postfix(path: &Path, postfix: &str) -> Vec<PathBuf> {
     WalkDir::new(path)
@@ -0,0 +1,647 @@
+//! Export and import of the full state at a block, for example to fork a dev chain into a new
+//! node.
+//!
+//! The dump is a JSON object of all accounts in the same format as the `alloc` field of a genesis
+//! file, see [GenesisAccount](reth_primitives::GenesisAccount).
+
+use crate::utils::init::CHECKPOINT_STATE_STAGES;
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    models::storage_sharded_key::StorageShardedKey,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::{
+    keccak256, Account, Address, BlockNumber, Bytecode, Bytes, ChainSpec, Header, StorageEntry,
+    H256, U256,
+};
+use reth_provider::{
+    ensure_block_available, get_lowest_available_block,
+    trie::{DBTrieLoader, TrieError},
+    AccountProvider, HistoricalStateProviderRef, ShareableDatabase, StateProvider,
+};
+use reth_stages::stages::EXECUTION;
+use serde::{
+    de::{DeserializeSeed, Error as _, MapAccess, Visitor},
+    Deserializer as _,
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{Read, Write},
+    sync::Arc,
+};
+use tracing::debug;
+
+/// State dump error type.
+#[derive(Debug, thiserror::Error)]
+pub enum StateDumpError {
+    /// The requested block is not a canonical block of the database.
+    #[error("Block #{0} is not a canonical block")]
+    UnknownBlock(BlockNumber),
+    /// The state root of the imported state doesn't match the expected state root.
+    #[error("State root mismatch: expected {expected}, got {got}")]
+    StateRootMismatch { expected: H256, got: H256 },
+    /// Attempted to import a state dump into a database that is already initialized.
+    #[error("Database is already initialized")]
+    DatabaseNotEmpty,
+    /// Attempted to import the state of a checkpoint into a database that was not anchored at a
+    /// checkpoint.
+    #[error("Database is not anchored at a checkpoint")]
+    MissingCheckpoint,
+    /// Failed to read or write the dump.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Failed to encode or decode the dump.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// Failed to compute the state root.
+    #[error(transparent)]
+    Trie(#[from] TrieError),
+    /// Failed to read the state at the block.
+    #[error(transparent)]
+    Provider(#[from] reth_interfaces::Error),
+    /// Low-level database error.
+    #[error(transparent)]
+    DBError(#[from] reth_db::Error),
+}
+
+/// Writes all accounts, with their code and storage, at the end of the given canonical block to the
+/// writer in the genesis `alloc` format.
+///
+/// The accounts are streamed in the order of their addresses, the storage slots of an account in
+/// the order of their keys, and only the entry that is written is kept in memory. The addresses and
+/// slots are the ones of the latest plain state merged with the ones of the history indices, which
+/// also hold the accounts and slots that were removed after the block. The values at the block are
+/// read with the historical state provider of the block.
+///
+/// The hashed state tables can't be streamed instead: they are keyed by the hashes of the addresses
+/// and slots, and the dump needs the addresses and slots themselves.
+///
+/// Returns the state root of the block.
+pub fn export_state<DB, W>(
+    provider: &ShareableDatabase<DB>,
+    block: BlockNumber,
+    mut writer: W,
+) -> Result<H256, StateDumpError>
+where
+    DB: Database,
+    W: Write,
+{
+    let tx = provider.db().tx()?;
+    if tx.get::<tables::CanonicalHeaders>(block)?.is_none() {
+        return Err(StateDumpError::UnknownBlock(block))
+    }
+    ensure_block_available(block, get_lowest_available_block(&tx)?)?;
+    let header = tx.get::<tables::Headers>(block)?.ok_or(StateDumpError::UnknownBlock(block))?;
+    // the first transition after the block
+    let transition = tx
+        .get::<tables::BlockTransitionIndex>(block)?
+        .ok_or(StateDumpError::UnknownBlock(block))?;
+    let state = HistoricalStateProviderRef::new(&tx, transition);
+
+    debug!(target: "sync::state_dump", block, transition, "Exporting state");
+
+    let mut account_cursor = tx.cursor_read::<tables::PlainAccountState>()?;
+    let mut account_history_cursor = tx.cursor_read::<tables::AccountHistory>()?;
+    let mut storage_cursor = tx.cursor_dup_read::<tables::PlainStorageState>()?;
+    let mut storage_history_cursor = tx.cursor_read::<tables::StorageHistory>()?;
+
+    let mut latest = account_cursor.first()?;
+    let mut history = account_history_cursor.first()?.map(|(key, _)| key.key);
+    let mut first = true;
+    writer.write_all(b"{")?;
+    loop {
+        let address = match (latest, history) {
+            (Some((latest, _)), Some(history)) => latest.min(history),
+            (Some((latest, _)), None) => latest,
+            (None, Some(history)) => history,
+            (None, None) => break,
+        };
+        let latest_account = match latest {
+            Some((latest_address, account)) if latest_address == address => {
+                latest = account_cursor.next()?;
+                Some(account)
+            }
+            _ => None,
+        };
+        // the account changed since the history starts, its value may be in a changeset
+        let account = if history == Some(address) {
+            history = next_history_address(&mut account_history_cursor, address)?;
+            state.basic_account(address)?
+        } else {
+            latest_account
+        };
+        let Some(account) = account else { continue };
+
+        if !first {
+            writer.write_all(b",")?;
+        }
+        first = false;
+
+        serde_json::to_writer(&mut writer, &address)?;
+        writer.write_all(b":{\"balance\":")?;
+        serde_json::to_writer(&mut writer, &account.balance)?;
+        if account.nonce != 0 {
+            writer.write_all(b",\"nonce\":")?;
+            serde_json::to_writer(&mut writer, &account.nonce)?;
+        }
+        if let Some(code_hash) = account.bytecode_hash {
+            if let Some(bytecode) = state.bytecode_by_hash(code_hash)? {
+                writer.write_all(b",\"code\":")?;
+                serde_json::to_writer(&mut writer, &Bytes::from(bytecode.original_bytes()))?;
+            }
+        }
+
+        // the slots of the latest storage merged with the slots of the storage history
+        let mut latest_slots = storage_cursor.walk_dup(Some(address), None)?;
+        let mut latest_slot = next_slot(&mut latest_slots, address)?;
+        let mut history_slot = storage_history_cursor
+            .seek(StorageShardedKey::new(address, H256::zero(), 0))?
+            .filter(|(key, _)| key.address == address)
+            .map(|(key, _)| key.sharded_key.key);
+        loop {
+            let key = match (latest_slot, history_slot) {
+                (Some(latest), Some(history)) => latest.key.min(history),
+                (Some(latest), None) => latest.key,
+                (None, Some(history)) => history,
+                (None, None) => break,
+            };
+            let latest_value = match latest_slot {
+                Some(entry) if entry.key == key => {
+                    latest_slot = next_slot(&mut latest_slots, address)?;
+                    entry.value
+                }
+                _ => U256::ZERO,
+            };
+            let value = if history_slot == Some(key) {
+                history_slot = next_history_slot(&mut storage_history_cursor, address, key)?;
+                state.storage(address, key)?.unwrap_or_default()
+            } else {
+                latest_value
+            };
+
+            if value != U256::ZERO {
+                writer.write_all(b",")?;
+                serde_json::to_writer(&mut writer, &key)?;
+                writer.write_all(b":")?;
+                serde_json::to_writer(&mut writer, &H256::from(value.to_be_bytes()))?;
+            }
+        }
+
+        writer.write_all(b"}")?;
+    }
+
+    writer.write_all(b"}")?;
+    writer.flush()?;
+
+    Ok(header.state_root)
+}
+
+/// Returns the next address of the account history after all shards of the given address.
+fn next_history_address<'tx, C>(
+    cursor: &mut C,
+    address: Address,
+) -> Result<Option<Address>, reth_db::Error>
+where
+    C: DbCursorRO<'tx, tables::AccountHistory>,
+{
+    while let Some((key, _)) = cursor.next()? {
+        if key.key != address {
+            return Ok(Some(key.key))
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the next slot of the storage history of the account after all shards of the given
+/// slot.
+fn next_history_slot<'tx, C>(
+    cursor: &mut C,
+    address: Address,
+    slot: H256,
+) -> Result<Option<H256>, reth_db::Error>
+where
+    C: DbCursorRO<'tx, tables::StorageHistory>,
+{
+    while let Some((key, _)) = cursor.next()? {
+        if key.address != address {
+            return Ok(None)
+        }
+        if key.sharded_key.key != slot {
+            return Ok(Some(key.sharded_key.key))
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the next slot of the latest storage of the account.
+fn next_slot(
+    slots: &mut impl Iterator<Item = Result<(Address, StorageEntry), reth_db::Error>>,
+    address: Address,
+) -> Result<Option<StorageEntry>, reth_db::Error> {
+    Ok(slots.next().transpose()?.filter(|(other, _)| *other == address).map(|(_, entry)| entry))
+}
+
+/// Initializes an empty database with the state of a dump created by [export_state].
+///
+/// The genesis block is taken from the chain spec, its state is the state of the dump instead of
+/// the genesis `alloc` of the chain spec. The state root of the imported state is computed,
+/// checked against the `expected_state_root`, which is the state root of the exported block, and
+/// set as the state root of the genesis header, so the genesis hash differs from the one of the
+/// chain spec.
+///
+/// Accounts and their storage slots are inserted while the dump is read, so neither the full state
+/// nor the full storage of a single account is kept in memory.
+pub fn init_from_state_dump<DB: Database, R: Read>(
+    db: Arc<DB>,
+    chain: Arc<ChainSpec>,
+    reader: R,
+    expected_state_root: H256,
+) -> Result<H256, StateDumpError> {
+    let tx = db.tx()?;
+    if tx.cursor_read::<tables::CanonicalHeaders>()?.first()?.is_some() {
+        return Err(StateDumpError::DatabaseNotEmpty)
+    }
+    drop(tx);
+
+    let tx = db.tx_mut()?;
+    let state_root = import_state(&tx, reader, expected_state_root)?;
+
+    let header = Header { state_root, ..chain.genesis_header() };
+    let hash = header.hash_slow();
+    tx.put::<tables::CanonicalHeaders>(0, hash)?;
+    tx.put::<tables::HeaderNumbers>(hash, 0)?;
+    tx.put::<tables::BlockBodyIndices>(0, Default::default())?;
+    tx.put::<tables::BlockTransitionIndex>(0, 0)?;
+    tx.put::<tables::HeaderTD>(0, header.difficulty.into())?;
+    tx.put::<tables::Headers>(0, header)?;
+
+    tx.commit()?;
+    Ok(state_root)
+}
+
+/// Imports the state of a trusted checkpoint from a dump created by [export_state] into a database
+/// that was anchored at the checkpoint with
+/// [init_from_checkpoint](crate::utils::init::init_from_checkpoint).
+///
+/// The state above the checkpoint is unavailable until its state is imported: the blocks above the
+/// checkpoint can't be executed without it. The state of the genesis block, which is below the
+/// checkpoint, is replaced. The state root of the imported state must match the checkpoint header.
+/// The progress of the stages of [CHECKPOINT_STATE_STAGES] is set to the checkpoint, so the
+/// pipeline executes the blocks above it.
+///
+/// Returns the state root of the checkpoint.
+pub fn init_checkpoint_state<DB: Database, R: Read>(
+    db: Arc<DB>,
+    reader: R,
+) -> Result<H256, StateDumpError> {
+    let tx = db.tx_mut()?;
+    let lowest_available = get_lowest_available_block(&tx)?;
+    let Some(checkpoint) = lowest_available.checked_sub(1) else {
+        return Err(StateDumpError::MissingCheckpoint)
+    };
+    if EXECUTION.get_progress(&tx)?.is_some() {
+        return Err(StateDumpError::DatabaseNotEmpty)
+    }
+    let header =
+        tx.get::<tables::Headers>(checkpoint)?.ok_or(StateDumpError::UnknownBlock(checkpoint))?;
+
+    tx.clear::<tables::PlainAccountState>()?;
+    tx.clear::<tables::PlainStorageState>()?;
+    tx.clear::<tables::HashedAccount>()?;
+    tx.clear::<tables::HashedStorage>()?;
+    tx.clear::<tables::AccountsTrie>()?;
+    tx.clear::<tables::StoragesTrie>()?;
+
+    let state_root = import_state(&tx, reader, header.state_root)?;
+    for stage in CHECKPOINT_STATE_STAGES {
+        stage.save_progress(&tx, checkpoint)?;
+    }
+
+    tx.commit()?;
+    Ok(state_root)
+}
+
+/// Inserts all accounts of the dump, computes the state root of the imported state and checks it
+/// against the expected state root.
+fn import_state<'db, TX, R>(
+    tx: &TX,
+    reader: R,
+    expected_state_root: H256,
+) -> Result<H256, StateDumpError>
+where
+    TX: DbTxMut<'db> + DbTx<'db>,
+    R: Read,
+{
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let num_accounts = (&mut deserializer).deserialize_map(AllocVisitor { tx })?;
+    deserializer.end()?;
+    debug!(target: "sync::state_dump", num_accounts, "Imported state dump");
+
+    let mut loader = DBTrieLoader::new(tx);
+    loader.commit_threshold = u64::MAX;
+    let state_root = loader.calculate_root()?.root()?;
+    if state_root != expected_state_root {
+        return Err(StateDumpError::StateRootMismatch {
+            expected: expected_state_root,
+            got: state_root,
+        })
+    }
+    Ok(state_root)
+}
+
+/// Inserts all accounts of the dump into the database.
+struct AllocVisitor<'a, TX> {
+    tx: &'a TX,
+}
+
+impl<'de, 'a, 'db, TX> Visitor<'de> for AllocVisitor<'a, TX>
+where
+    TX: DbTxMut<'db> + DbTx<'db>,
+{
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map of addresses to accounts")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut num_accounts = 0;
+        while let Some(address) = map.next_key::<Address>()? {
+            map.next_value_seed(AccountSeed { tx: self.tx, address })?;
+            num_accounts += 1;
+        }
+        Ok(num_accounts)
+    }
+}
+
+/// Inserts a single account of the dump, and its storage, into the database.
+struct AccountSeed<'a, TX> {
+    tx: &'a TX,
+    address: Address,
+}
+
+impl<'a, 'db, TX> AccountSeed<'a, TX>
+where
+    TX: DbTxMut<'db> + DbTx<'db>,
+{
+    fn insert_storage(&self, key: H256, value: H256) -> Result<(), reth_db::Error> {
+        let value = U256::from_be_bytes(value.0);
+        if value == U256::ZERO {
+            return Ok(())
+        }
+        self.tx.put::<tables::PlainStorageState>(self.address, StorageEntry { key, value })?;
+        self.tx.put::<tables::HashedStorage>(
+            keccak256(self.address),
+            StorageEntry { key: keccak256(key), value },
+        )
+    }
+}
+
+impl<'de, 'a, 'db, TX> DeserializeSeed<'de> for AccountSeed<'a, TX>
+where
+    TX: DbTxMut<'db> + DbTx<'db>,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, 'a, 'db, TX> Visitor<'de> for AccountSeed<'a, TX>
+where
+    TX: DbTxMut<'db> + DbTx<'db>,
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an account")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut account = Account::default();
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "balance" => account.balance = map.next_value()?,
+                "nonce" => account.nonce = map.next_value()?,
+                "code" => {
+                    let code: Bytes = map.next_value()?;
+                    let code_hash = keccak256(&code);
+                    self.tx
+                        .put::<tables::Bytecodes>(code_hash, Bytecode::new_raw(code.into()))
+                        .map_err(A::Error::custom)?;
+                    account.bytecode_hash = Some(code_hash);
+                }
+                // geth style nested storage
+                "storage" => {
+                    for (key, value) in map.next_value::<HashMap<H256, H256>>()? {
+                        self.insert_storage(key, value).map_err(A::Error::custom)?;
+                    }
+                }
+                slot => {
+                    let key = slot.parse::<H256>().map_err(A::Error::custom)?;
+                    let value: H256 = map.next_value()?;
+                    self.insert_storage(key, value).map_err(A::Error::custom)?;
+                }
+            }
+        }
+
+        self.tx
+            .put::<tables::PlainAccountState>(self.address, account)
+            .map_err(A::Error::custom)?;
+        self.tx
+            .put::<tables::HashedAccount>(keccak256(self.address), account)
+            .map_err(A::Error::custom)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        mdbx::test_utils::create_test_rw_db,
+        models::{AccountBeforeTx, ShardedKey, TransitionIdAddress},
+        TransitionList,
+    };
+    use reth_primitives::{proofs::genesis_state_root, GenesisAccount, MAINNET};
+
+    #[test]
+    fn export_and_reinit_state() {
+        let code = Bytes::from(vec![0x60, 0x00, 0x60, 0x00, 0xf3]);
+        let slot = |n: u64| H256::from_low_u64_be(n);
+
+        let (changed, destroyed, created, untouched) =
+            (Address::random(), Address::random(), Address::random(), Address::random());
+
+        // the expected state at block 10
+        let alloc = HashMap::from([
+            (
+                changed,
+                GenesisAccount::default()
+                    .with_balance(U256::from(100))
+                    .with_nonce(Some(3))
+                    .with_code(Some(code.clone()))
+                    .with_storage(Some(HashMap::from([(slot(1), slot(10)), (slot(2), slot(20))]))),
+            ),
+            (destroyed, GenesisAccount::default().with_balance(U256::from(5))),
+            (
+                untouched,
+                GenesisAccount::default()
+                    .with_balance(U256::from(7))
+                    .with_storage(Some(HashMap::from([(slot(1), slot(1))]))),
+            ),
+        ]);
+        let state_root = genesis_state_root(&alloc);
+
+        // the latest state is the state at block 11, with the block 10 values in the changesets
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        let block = 10;
+        let transition = 5;
+        tx.put::<tables::CanonicalHeaders>(block, H256::random()).unwrap();
+        tx.put::<tables::Headers>(
+            block,
+            Header { number: block, state_root, ..Default::default() },
+        )
+        .unwrap();
+        tx.put::<tables::BlockTransitionIndex>(block, transition).unwrap();
+
+        let code_hash = keccak256(&code);
+        tx.put::<tables::Bytecodes>(code_hash, Bytecode::new_raw(code.clone().into())).unwrap();
+        let changed_account =
+            Account { nonce: 3, balance: U256::from(100), bytecode_hash: Some(code_hash) };
+        let destroyed_account = Account { balance: U256::from(5), ..Default::default() };
+        tx.put::<tables::PlainAccountState>(
+            changed,
+            Account { nonce: 4, balance: U256::from(50), ..changed_account },
+        )
+        .unwrap();
+        tx.put::<tables::PlainAccountState>(
+            created,
+            Account { balance: U256::from(1), ..Default::default() },
+        )
+        .unwrap();
+        tx.put::<tables::PlainAccountState>(
+            untouched,
+            Account { balance: U256::from(7), ..Default::default() },
+        )
+        .unwrap();
+        for (address, info) in [
+            (changed, Some(changed_account)),
+            (destroyed, Some(destroyed_account)),
+            (created, None),
+        ] {
+            tx.put::<tables::AccountChangeSet>(transition, AccountBeforeTx { address, info })
+                .unwrap();
+            tx.put::<tables::AccountHistory>(
+                ShardedKey::new(address, u64::MAX),
+                TransitionList::new([transition as usize]).unwrap(),
+            )
+            .unwrap();
+        }
+
+        // slot 1 was changed, slot 2 cleared and slot 3 created after block 10
+        tx.put::<tables::PlainStorageState>(
+            changed,
+            StorageEntry { key: slot(1), value: U256::from(11) },
+        )
+        .unwrap();
+        tx.put::<tables::PlainStorageState>(
+            changed,
+            StorageEntry { key: slot(3), value: U256::from(30) },
+        )
+        .unwrap();
+        tx.put::<tables::PlainStorageState>(
+            untouched,
+            StorageEntry { key: slot(1), value: U256::from(1) },
+        )
+        .unwrap();
+        for (key, value) in [(slot(1), 10), (slot(2), 20), (slot(3), 0)] {
+            tx.put::<tables::StorageChangeSet>(
+                TransitionIdAddress((transition, changed)),
+                StorageEntry { key, value: U256::from(value) },
+            )
+            .unwrap();
+            tx.put::<tables::StorageHistory>(
+                StorageShardedKey::new(changed, key, u64::MAX),
+                TransitionList::new([transition as usize]).unwrap(),
+            )
+            .unwrap();
+        }
+        tx.commit().unwrap();
+
+        let provider = ShareableDatabase::new(db, Arc::new(MAINNET.clone()));
+        let mut dump = Vec::new();
+        let exported_root = export_state(&provider, block, &mut dump).unwrap();
+        assert_eq!(exported_root, state_root);
+
+        // the dump is a valid genesis alloc of the state at block 10
+        let exported_alloc: HashMap<Address, GenesisAccount> =
+            serde_json::from_slice(&dump).unwrap();
+        assert_eq!(exported_alloc.len(), 3);
+        // the accounts are written in the order of their addresses
+        let dump_str = std::str::from_utf8(&dump).unwrap();
+        let mut addresses = vec![changed, destroyed, untouched];
+        addresses.sort_by_key(|address| dump_str.find(&format!("{address:?}")).unwrap());
+        assert!(addresses.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(!exported_alloc.contains_key(&created));
+        assert_eq!(genesis_state_root(&exported_alloc), state_root);
+
+        // initialize a new database from the dump
+        let new_db = create_test_rw_db();
+        let root =
+            init_from_state_dump(new_db.clone(), Arc::new(MAINNET.clone()), &dump[..], state_root)
+                .unwrap();
+        assert_eq!(root, state_root);
+
+        let tx = new_db.tx().unwrap();
+        let header = tx.get::<tables::Headers>(0).unwrap().unwrap();
+        assert_eq!(header.state_root, state_root);
+        assert_eq!(tx.get::<tables::CanonicalHeaders>(0).unwrap(), Some(header.hash_slow()));
+        assert_eq!(tx.get::<tables::PlainAccountState>(changed).unwrap(), Some(changed_account));
+        assert_eq!(tx.get::<tables::PlainAccountState>(created).unwrap(), None);
+        assert!(tx.get::<tables::Bytecodes>(code_hash).unwrap().is_some());
+        drop(tx);
+
+        // the database is already initialized
+        assert!(matches!(
+            init_from_state_dump(new_db, Arc::new(MAINNET.clone()), &dump[..], state_root),
+            Err(StateDumpError::DatabaseNotEmpty)
+        ));
+    }
+
+    #[test]
+    fn init_from_state_dump_root_mismatch() {
+        let dump = br#"{"0x0000000000000000000000000000000000000001":{"balance":"0x1"}}"#;
+        let db = create_test_rw_db();
+        let res = init_from_state_dump(db, Arc::new(MAINNET.clone()), &dump[..], H256::zero());
+        assert!(matches!(res, Err(StateDumpError::StateRootMismatch { .. })));
+    }
+
+    #[test]
+    fn checkpoint_state_requires_checkpoint() {
+        let dump = br#"{"0x0000000000000000000000000000000000000001":{"balance":"0x1"}}"#;
+        let res = init_checkpoint_state(create_test_rw_db(), &dump[..]);
+        assert!(matches!(res, Err(StateDumpError::MissingCheckpoint)));
+    }
+
+    #[test]
+    fn export_unknown_block() {
+        let provider = ShareableDatabase::new(create_test_rw_db(), Arc::new(MAINNET.clone()));
+        let res = export_state(&provider, 1, Vec::new());
+        assert!(matches!(res, Err(StateDumpError::UnknownBlock(1))));
+    }
+}
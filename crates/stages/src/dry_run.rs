@@ -0,0 +1,234 @@
+use crate::{ExecInput, Stage, StageError, StageId};
+use reth_db::database::Database;
+use reth_primitives::BlockNumber;
+use reth_provider::Transaction;
+use std::{
+    ops::RangeInclusive,
+    time::{Duration, Instant},
+};
+use tracing::*;
+
+/// The [`StageId`] reported as the previous stage of a dry run.
+const DRY_RUN: StageId = StageId("DryRun");
+
+/// The max number of blocks of a dry run.
+///
+/// A dry run can't be split into committed chunks, so all the changes of a loop stay in one
+/// database transaction until it is rolled back. Longer ranges are measured by running consecutive
+/// ranges after each other.
+pub const MAX_DRY_RUN_BLOCKS: u64 = 10_000;
+
+/// The outcome of [run_stage_dry].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRunReport {
+    /// The stage that was run.
+    pub stage: StageId,
+    /// The checkpoint the stage started from.
+    pub from: BlockNumber,
+    /// The checkpoint the stage would have saved if the run was committed.
+    pub checkpoint: BlockNumber,
+    /// Whether the stage reported that it is done.
+    pub done: bool,
+    /// The duration of every loop.
+    pub elapsed: Vec<Duration>,
+}
+
+impl DryRunReport {
+    /// The number of blocks processed in a single loop.
+    pub fn blocks(&self) -> u64 {
+        self.checkpoint.saturating_sub(self.from)
+    }
+
+    /// The average duration of a single loop.
+    pub fn mean_elapsed(&self) -> Duration {
+        if self.elapsed.is_empty() {
+            return Duration::ZERO
+        }
+        self.elapsed.iter().sum::<Duration>() / self.elapsed.len() as u32
+    }
+
+    /// The average number of blocks processed per second.
+    pub fn blocks_per_second(&self) -> f64 {
+        let secs = self.mean_elapsed().as_secs_f64();
+        if secs == 0.0 {
+            return 0.0
+        }
+        self.blocks() as f64 / secs
+    }
+}
+
+/// Execute a single stage over the given block range without persisting anything.
+///
+/// The stage starts from the checkpoint `range.start()` and executes until it reaches
+/// `range.end()` or reports that it is done. Every loop runs inside a fresh
+/// [dry run transaction][Transaction::new_dry_run] which is rolled back afterwards, including any
+/// intermediate commits issued by the stage itself, so the database is left untouched.
+///
+/// The stage is run `loops` times (at least once) to get more stable measurements.
+///
+/// Fails if the range is larger than [MAX_DRY_RUN_BLOCKS].
+pub async fn run_stage_dry<DB, S>(
+    stage: &mut S,
+    db: &DB,
+    range: RangeInclusive<BlockNumber>,
+    loops: usize,
+) -> Result<DryRunReport, StageError>
+where
+    DB: Database,
+    S: Stage<DB>,
+{
+    let (from, to) = range.into_inner();
+    let blocks = to.saturating_sub(from);
+    if blocks > MAX_DRY_RUN_BLOCKS {
+        return Err(StageError::DryRunRange(blocks, MAX_DRY_RUN_BLOCKS))
+    }
+    let mut report = DryRunReport {
+        stage: stage.id(),
+        from,
+        checkpoint: from,
+        done: false,
+        elapsed: Vec::with_capacity(loops.max(1)),
+    };
+
+    for iteration in 0..loops.max(1) {
+        let mut tx = Transaction::new_dry_run(db)?;
+        let mut input =
+            ExecInput { previous_stage: Some((DRY_RUN, to)), stage_progress: Some(from) };
+
+        let started_at = Instant::now();
+        let output = loop {
+            let output = stage.execute(&mut tx, input).await?;
+            if output.done || output.stage_progress >= to {
+                break output
+            }
+            input.stage_progress = Some(output.stage_progress);
+        };
+        let elapsed = started_at.elapsed();
+
+        // Dropping the transaction aborts everything written during this loop.
+        drop(tx);
+
+        info!(
+            target: "sync::stages::dry_run",
+            stage = %report.stage,
+            iteration,
+            checkpoint = output.stage_progress,
+            ?elapsed,
+            "Dry run finished"
+        );
+
+        report.checkpoint = output.stage_progress;
+        report.done = output.done;
+        report.elapsed.push(elapsed);
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stages::{AccountHashingStage, ExecutionStage, SeedOpts},
+        test_utils::TestTransaction,
+    };
+    use reth_db::{tables, transaction::DbTxMut};
+    use reth_executor::Factory;
+    use reth_primitives::{
+        hex_literal::hex, keccak256, Account, Bytecode, ChainSpecBuilder, SealedBlock, H160, U256,
+    };
+    use reth_provider::insert_canonical_block;
+    use reth_rlp::Decodable;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn dry_run_leaves_database_untouched() {
+        let tx = TestTransaction::default();
+        AccountHashingStage::seed(
+            &mut tx.inner(),
+            SeedOpts { blocks: 0..11, accounts: 0..5, txs: 0..3, transitions: 2 },
+        )
+        .unwrap();
+
+        let plain_before = tx.table_checksum::<tables::PlainAccountState>().unwrap();
+        let hashed_before = tx.table_checksum::<tables::HashedAccount>().unwrap();
+        let progress_before = tx.table_checksum::<tables::SyncStageProgress>().unwrap();
+
+        // A small commit threshold makes the stage save intermediate checkpoints between batches.
+        let mut stage = AccountHashingStage { clean_threshold: 1, commit_threshold: 2 };
+        let report = run_stage_dry(&mut stage, tx.tx.as_ref(), 0..=10, 3).await.unwrap();
+
+        assert!(report.done);
+        assert_eq!(report.checkpoint, 10);
+        assert_eq!(report.blocks(), 10);
+        assert_eq!(report.elapsed.len(), 3);
+
+        assert!(tx.table_is_empty::<tables::HashedAccount>().unwrap());
+        assert_eq!(tx.table_checksum::<tables::PlainAccountState>().unwrap(), plain_before);
+        assert_eq!(tx.table_checksum::<tables::HashedAccount>().unwrap(), hashed_before);
+        assert_eq!(tx.table_checksum::<tables::SyncStageProgress>().unwrap(), progress_before);
+    }
+
+    #[tokio::test]
+    async fn dry_run_of_execution() {
+        let tx = TestTransaction::default();
+        let mut genesis_rlp = hex!("f901faf901f5a00000000000000000000000000000000000000000000000000000000000000000a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa045571b40ae66ca7480791bbb2887286e4e4c4b1b298b191c889d6959023a32eda056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421b901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000808502540be400808000a00000000000000000000000000000000000000000000000000000000000000000880000000000000000c0c0").as_slice();
+        let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
+        let mut block_rlp = hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0").as_slice();
+        let block = SealedBlock::decode(&mut block_rlp).unwrap();
+
+        // the pre state of the block
+        let code = hex!("5a465a905090036002900360015500");
+        let code_hash = keccak256(code);
+        let mut db_tx = tx.inner();
+        insert_canonical_block(&*db_tx, genesis, None, true).unwrap();
+        insert_canonical_block(&*db_tx, block, None, true).unwrap();
+        db_tx
+            .put::<tables::PlainAccountState>(
+                H160(hex!("1000000000000000000000000000000000000000")),
+                Account { nonce: 0, balance: U256::ZERO, bytecode_hash: Some(code_hash) },
+            )
+            .unwrap();
+        db_tx
+            .put::<tables::PlainAccountState>(
+                H160(hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b")),
+                Account {
+                    nonce: 0,
+                    balance: U256::from(0x3635c9adc5dea00000u128),
+                    bytecode_hash: None,
+                },
+            )
+            .unwrap();
+        db_tx.put::<tables::Bytecodes>(code_hash, Bytecode::new_raw(code.to_vec().into())).unwrap();
+        db_tx.commit().unwrap();
+
+        let accounts_before = tx.table_checksum::<tables::PlainAccountState>().unwrap();
+        let storage_before = tx.table_checksum::<tables::PlainStorageState>().unwrap();
+
+        let factory =
+            Factory::new(Arc::new(ChainSpecBuilder::mainnet().berlin_activated().build()));
+        let mut stage = ExecutionStage::new(factory, 100);
+        let report = run_stage_dry(&mut stage, tx.tx.as_ref(), 0..=1, 2).await.unwrap();
+
+        assert!(report.done);
+        assert_eq!(report.checkpoint, 1);
+        assert_eq!(report.elapsed.len(), 2);
+
+        assert_eq!(tx.table_checksum::<tables::PlainAccountState>().unwrap(), accounts_before);
+        assert_eq!(tx.table_checksum::<tables::PlainStorageState>().unwrap(), storage_before);
+        assert!(tx.table_is_empty::<tables::AccountChangeSet>().unwrap());
+        assert!(tx.table_is_empty::<tables::Receipts>().unwrap());
+    }
+
+    #[tokio::test]
+    async fn dry_run_range_is_capped() {
+        let tx = TestTransaction::default();
+        let mut stage = AccountHashingStage::default();
+        let err = run_stage_dry(&mut stage, tx.tx.as_ref(), 5..=MAX_DRY_RUN_BLOCKS + 6, 1)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, StageError::DryRunRange(blocks, _) if blocks == MAX_DRY_RUN_BLOCKS + 1)
+        );
+    }
+}
@@ -35,6 +35,10 @@ pub enum StageError {
     /// Invalid checkpoint passed to the stage
     #[error("Invalid stage progress: {0}")]
     StageProgress(u64),
+    /// The range of a dry run is larger than the max, see
+    /// [MAX_DRY_RUN_BLOCKS](crate::MAX_DRY_RUN_BLOCKS).
+    #[error("Dry run range of {0} blocks exceeds the max of {1}")]
+    DryRunRange(u64, u64),
     /// Download channel closed
     #[error("Download channel closed")]
     ChannelClosed,
@@ -51,6 +51,7 @@
 //!     )
 //!     .build();
 //! ```
+mod dry_run;
 mod error;
 mod id;
 mod pipeline;
@@ -69,6 +70,7 @@ pub mod stages;
 
 pub mod sets;
 
+pub use dry_run::*;
 pub use error::*;
 pub use id::*;
 pub use pipeline::*;
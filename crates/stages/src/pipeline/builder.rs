@@ -1,4 +1,4 @@
-use crate::{Pipeline, Stage, StageSet};
+use crate::{Pipeline, PipelineControl, Stage, StageSet};
 use reth_db::database::Database;
 use reth_interfaces::sync::{NoopSyncStateUpdate, SyncStateUpdater};
 use reth_primitives::BlockNumber;
@@ -62,6 +62,16 @@ where
         self
     }
 
+    /// Set the [PipelineControl] of the [PipelineHandle](crate::PipelineHandle)s unwinds can be
+    /// requested with.
+    pub fn with_control(mut self, control: PipelineControl) -> Self {
+        let PipelineControl { requests, busy, prune_modes } = control;
+        self.pipeline.unwind_requests = Some(requests);
+        self.pipeline.busy = busy;
+        self.pipeline.prune_modes = prune_modes;
+        self
+    }
+
     /// Builds the final [`Pipeline`].
     pub fn build(self) -> Pipeline<DB, U> {
         self.pipeline
@@ -0,0 +1,105 @@
+use reth_interfaces::sync::{ChainUnwinder, UnwindRequestError, UnwindSummary};
+use reth_primitives::{BlockNumber, PruneModes};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use tokio::sync::{
+    mpsc::{self, error::TrySendError},
+    oneshot,
+};
+
+/// The number of unwind requests that can wait for the pipeline, further requests are refused
+/// with [UnwindRequestError::PipelineBusy].
+pub const MAX_PENDING_UNWIND_REQUESTS: usize = 1;
+
+/// Creates a [PipelineHandle] and the [PipelineControl] to build the pipeline with (see
+/// [PipelineBuilder::with_control](crate::PipelineBuilder::with_control)).
+///
+/// Unwinds below the blocks pruned by the given prune modes are refused.
+pub fn pipeline_control_channel(prune_modes: PruneModes) -> (PipelineHandle, PipelineControl) {
+    let (to_pipeline, requests) = mpsc::channel(MAX_PENDING_UNWIND_REQUESTS);
+    // the pipeline is busy until it completed its first pass
+    let busy = Arc::new(AtomicBool::new(true));
+    let handle = PipelineHandle { to_pipeline, busy: Arc::clone(&busy) };
+    (handle, PipelineControl { requests, busy, prune_modes })
+}
+
+/// An unwind requested through a [PipelineHandle].
+#[derive(Debug)]
+pub(crate) struct UnwindRequest {
+    /// The block to unwind to.
+    pub(crate) target: BlockNumber,
+    /// The channel the outcome of the unwind is sent to.
+    pub(crate) tx: oneshot::Sender<Result<UnwindSummary, UnwindRequestError>>,
+}
+
+/// The pipeline side of [pipeline_control_channel].
+#[derive(Debug)]
+pub struct PipelineControl {
+    pub(crate) requests: mpsc::Receiver<UnwindRequest>,
+    pub(crate) busy: Arc<AtomicBool>,
+    pub(crate) prune_modes: PruneModes,
+}
+
+/// A handle to request unwinds from a running [Pipeline](crate::Pipeline).
+///
+/// Unwinds are only performed while the pipeline is idle, i.e. it has synced to the tip and no
+/// stage made progress since. Requests are refused with [UnwindRequestError::PipelineBusy]
+/// otherwise, or if [MAX_PENDING_UNWIND_REQUESTS] requests are already waiting.
+#[derive(Debug, Clone)]
+pub struct PipelineHandle {
+    to_pipeline: mpsc::Sender<UnwindRequest>,
+    busy: Arc<AtomicBool>,
+}
+
+impl PipelineHandle {
+    /// Returns `true` if the pipeline is syncing.
+    pub fn is_busy(&self) -> bool {
+        self.busy.load(Ordering::Relaxed)
+    }
+}
+
+#[async_trait::async_trait]
+impl ChainUnwinder for PipelineHandle {
+    async fn unwind_to(&self, target: BlockNumber) -> Result<UnwindSummary, UnwindRequestError> {
+        // checked again by the pipeline once it receives the request
+        if self.is_busy() {
+            return Err(UnwindRequestError::PipelineBusy)
+        }
+        let (tx, rx) = oneshot::channel();
+        self.to_pipeline.try_send(UnwindRequest { target, tx }).map_err(|err| match err {
+            TrySendError::Full(_) => UnwindRequestError::PipelineBusy,
+            TrySendError::Closed(_) => UnwindRequestError::PipelineClosed,
+        })?;
+        rx.await.map_err(|_| UnwindRequestError::PipelineClosed)?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn requests_are_refused_while_the_queue_is_full() {
+        let (handle, control) = pipeline_control_channel(PruneModes::default());
+        control.busy.store(false, Ordering::Relaxed);
+
+        let _pending = (0..MAX_PENDING_UNWIND_REQUESTS)
+            .map(|_| {
+                let (tx, rx) = oneshot::channel();
+                handle.to_pipeline.try_send(UnwindRequest { target: 1, tx }).unwrap();
+                rx
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(handle.unwind_to(1).await, Err(UnwindRequestError::PipelineBusy));
+    }
+
+    #[tokio::test]
+    async fn requests_fail_once_the_pipeline_is_dropped() {
+        let (handle, control) = pipeline_control_channel(PruneModes::default());
+        control.busy.store(false, Ordering::Relaxed);
+        drop(control);
+        assert_eq!(handle.unwind_to(1).await, Err(UnwindRequestError::PipelineClosed));
+    }
+}
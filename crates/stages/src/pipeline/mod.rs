@@ -1,25 +1,34 @@
 use crate::{error::*, util::opt, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput};
-use metrics::Gauge;
+use metrics::{Gauge, Histogram};
 use reth_db::database::Database;
-use reth_interfaces::sync::{SyncState, SyncStateUpdater};
+use reth_interfaces::sync::{
+    StageUnwindSummary, SyncState, SyncStateUpdater, UnwindRequestError, UnwindSummary,
+};
 use reth_metrics_derive::Metrics;
-use reth_primitives::BlockNumber;
+use reth_primitives::{BlockNumber, PruneModes};
 use reth_provider::Transaction;
 use std::{
     collections::HashMap,
     fmt::{Debug, Formatter},
     ops::Deref,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
+use tokio::sync::mpsc;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::*;
 
 mod builder;
+mod control;
 mod ctrl;
 mod event;
 mod set;
 
 pub use builder::*;
+pub use control::*;
 use ctrl::*;
 pub use event::*;
 pub use set::*;
@@ -73,7 +82,8 @@ pub use set::*;
 ///
 /// In case of a validation error (as determined by the consensus engine) in one of the stages, the
 /// pipeline will unwind the stages in reverse order of execution. It is also possible to
-/// request an unwind manually (see [Pipeline::unwind]).
+/// request an unwind manually (see [Pipeline::unwind]), or through a [PipelineHandle] while the
+/// pipeline is running.
 pub struct Pipeline<DB: Database, U: SyncStateUpdater> {
     stages: Vec<BoxedStage<DB>>,
     max_block: Option<BlockNumber>,
@@ -81,6 +91,13 @@ pub struct Pipeline<DB: Database, U: SyncStateUpdater> {
     sync_state_updater: Option<U>,
     progress: PipelineProgress,
     metrics: Metrics,
+    /// Unwinds requested through the [PipelineHandle]s.
+    unwind_requests: Option<mpsc::Receiver<UnwindRequest>>,
+    /// Whether a stage made progress since the last completed pass, shared with the
+    /// [PipelineHandle]s.
+    busy: Arc<AtomicBool>,
+    /// Requested unwinds below the blocks pruned by these modes are refused.
+    prune_modes: PruneModes,
 }
 
 impl<DB: Database, U: SyncStateUpdater> Default for Pipeline<DB, U> {
@@ -92,6 +109,9 @@ impl<DB: Database, U: SyncStateUpdater> Default for Pipeline<DB, U> {
             sync_state_updater: None,
             progress: PipelineProgress::default(),
             metrics: Metrics::default(),
+            unwind_requests: None,
+            busy: Arc::new(AtomicBool::new(true)),
+            prune_modes: PruneModes::default(),
         }
     }
 }
@@ -131,11 +151,39 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
 
     /// Run the pipeline in an infinite loop. Will terminate early if the user has specified
     /// a `max_block` in the pipeline.
+    ///
+    /// Unwinds requested through a [PipelineHandle] are performed in between, a pass waiting for
+    /// the next tip is abandoned for them.
     pub async fn run(&mut self, db: Arc<DB>) -> Result<(), PipelineError> {
         self.register_metrics(db.clone());
 
+        let mut unwind_requests = self.unwind_requests.take();
+        let res = self.run_with_requests(db, &mut unwind_requests).await;
+        self.unwind_requests = unwind_requests;
+        res
+    }
+
+    async fn run_with_requests(
+        &mut self,
+        db: Arc<DB>,
+        unwind_requests: &mut Option<mpsc::Receiver<UnwindRequest>>,
+    ) -> Result<(), PipelineError> {
+        self.busy.store(true, Ordering::Relaxed);
         loop {
-            let next_action = self.run_loop(db.as_ref()).await?;
+            let next_action = match unwind_requests.as_mut() {
+                Some(requests) => tokio::select! {
+                    next_action = self.run_loop(db.as_ref()) => next_action?,
+                    Some(request) = requests.recv() => {
+                        // the uncommitted work of the abandoned pass is discarded
+                        let res = self.unwind_on_request(db.as_ref(), request.target).await;
+                        let _ = request.tx.send(res);
+                        continue
+                    }
+                },
+                None => self.run_loop(db.as_ref()).await?,
+            };
+            // all stages reached the same block, the next pass waits for a new tip
+            self.busy.store(false, Ordering::Relaxed);
 
             // Terminate the loop early if it's reached the maximum user
             // configured block.
@@ -258,6 +306,66 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
         Ok(())
     }
 
+    /// Unwinds the stages to the target requested through a [PipelineHandle], unless a stage made
+    /// progress since the last completed pass.
+    async fn unwind_on_request(
+        &mut self,
+        db: &DB,
+        target: BlockNumber,
+    ) -> Result<UnwindSummary, UnwindRequestError> {
+        if self.busy.load(Ordering::Relaxed) {
+            return Err(UnwindRequestError::PipelineBusy)
+        }
+
+        let failed = |err: PipelineError| UnwindRequestError::Failed(err.to_string());
+        let before = self.stages_progress(db).map_err(failed)?;
+        let tip = before.iter().map(|(_, progress)| *progress).min().unwrap_or_default();
+        if target > tip {
+            return Err(UnwindRequestError::AboveTip { target, tip })
+        }
+        if let Some(horizon) = self
+            .prune_modes
+            .receipts
+            .and_then(|mode| mode.prune_target_block(tip))
+            .map(|pruned| pruned + 1)
+        {
+            if target < horizon {
+                return Err(UnwindRequestError::BelowPruneHorizon { target, horizon })
+            }
+        }
+
+        info!(target: "sync::pipeline", %target, %tip, "Unwinding on request");
+        self.unwind(db, target, None).await.map_err(failed)?;
+
+        let after = self.stages_progress(db).map_err(failed)?;
+        let stages = before
+            .into_iter()
+            .zip(after)
+            .rev()
+            .filter(|((_, from), (_, to))| from != to)
+            .map(|((stage_id, from), (_, to))| StageUnwindSummary {
+                stage: stage_id.to_string(),
+                from,
+                to,
+            })
+            .collect();
+        Ok(UnwindSummary { target, stages })
+    }
+
+    /// Returns the progress of all stages, in the order of execution.
+    fn stages_progress(&self, db: &DB) -> Result<Vec<(StageId, BlockNumber)>, PipelineError> {
+        let progress = db.view(|tx| {
+            self.stages
+                .iter()
+                .map(|stage| {
+                    let stage_id = stage.id();
+                    Ok((stage_id, stage_id.get_progress(tx)?.unwrap_or_default()))
+                })
+                .collect::<Result<Vec<_>, PipelineError>>()
+        })??;
+        Ok(progress)
+    }
+
     async fn execute_stage_to_completion(
         &mut self,
         db: &DB,
@@ -290,12 +398,18 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
             self.listeners
                 .notify(PipelineEvent::Running { stage_id, stage_progress: prev_progress });
 
-            match stage
+            let started_at = Instant::now();
+            let output = stage
                 .execute(&mut tx, ExecInput { previous_stage, stage_progress: prev_progress })
-                .await
-            {
+                .await;
+            self.metrics.stage_execution_duration(stage_id, started_at.elapsed());
+
+            match output {
                 Ok(out @ ExecOutput { stage_progress, done }) => {
                     made_progress |= stage_progress != prev_progress.unwrap_or_default();
+                    if made_progress {
+                        self.busy.store(true, Ordering::Relaxed);
+                    }
                     info!(
                         target: "sync::pipeline",
                         stage = %stage_id,
@@ -365,20 +479,28 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
 struct StageMetrics {
     /// The block number of the last commit for a stage.
     checkpoint: Gauge,
+    /// The duration of a single execution of a stage in seconds.
+    execution_duration_seconds: Histogram,
 }
 
 #[derive(Default)]
 struct Metrics {
-    checkpoints: HashMap<StageId, StageMetrics>,
+    stages: HashMap<StageId, StageMetrics>,
 }
 
 impl Metrics {
-    fn stage_checkpoint(&mut self, stage_id: StageId, progress: u64) {
-        self.checkpoints
+    fn stage(&mut self, stage_id: StageId) -> &StageMetrics {
+        self.stages
             .entry(stage_id)
             .or_insert_with(|| StageMetrics::new_with_labels(&[("stage", stage_id.to_string())]))
-            .checkpoint
-            .set(progress as f64);
+    }
+
+    fn stage_checkpoint(&mut self, stage_id: StageId, progress: u64) {
+        self.stage(stage_id).checkpoint.set(progress as f64);
+    }
+
+    fn stage_execution_duration(&mut self, stage_id: StageId, elapsed: Duration) {
+        self.stage(stage_id).execution_duration_seconds.record(elapsed.as_secs_f64());
     }
 }
 
@@ -426,7 +548,11 @@ mod tests {
     use crate::{StageId, UnwindOutput};
     use assert_matches::assert_matches;
     use reth_db::mdbx::{self, test_utils, EnvKind};
-    use reth_interfaces::{consensus, provider::ProviderError, sync::NoopSyncStateUpdate};
+    use reth_interfaces::{
+        consensus,
+        provider::ProviderError,
+        sync::{ChainUnwinder, NoopSyncStateUpdate},
+    };
     use tokio_stream::StreamExt;
     use utils::TestStage;
 
@@ -701,6 +827,66 @@ mod tests {
         );
     }
 
+    /// Unwinds a running pipeline through a [PipelineHandle] once it waits for the next tip.
+    #[tokio::test]
+    async fn unwind_on_request() {
+        let db = test_utils::create_test_db::<mdbx::WriteMap>(EnvKind::RW);
+        let prune_modes = PruneModes {
+            receipts: Some(reth_primitives::PruneMode::Before(3)),
+            ..Default::default()
+        };
+        let (handle, control) = pipeline_control_channel(prune_modes);
+
+        let mut pipeline: Pipeline<_, NoopSyncStateUpdate> = Pipeline::builder()
+            .add_stage(
+                TestStage::new(StageId("A"))
+                    .add_exec(Ok(ExecOutput { stage_progress: 10, done: true }))
+                    .add_unwind(Ok(UnwindOutput { stage_progress: 5 }))
+                    .wait_for_tip(),
+            )
+            .add_stage(
+                TestStage::new(StageId("B"))
+                    .add_exec(Ok(ExecOutput { stage_progress: 10, done: true }))
+                    .add_unwind(Ok(UnwindOutput { stage_progress: 5 })),
+            )
+            .with_control(control)
+            .build();
+        let mut events = pipeline.events();
+
+        // refused until the first pass is completed
+        assert!(handle.is_busy());
+        assert_eq!(handle.unwind_to(5).await, Err(UnwindRequestError::PipelineBusy));
+
+        tokio::spawn(async move {
+            pipeline.run(db).await.expect("Could not run pipeline");
+        });
+
+        // wait for the second pass, stage A waits for the next tip
+        let second_pass =
+            PipelineEvent::Running { stage_id: StageId("A"), stage_progress: Some(10) };
+        while events.next().await != Some(second_pass.clone()) {}
+        assert!(!handle.is_busy());
+
+        assert_eq!(
+            handle.unwind_to(11).await,
+            Err(UnwindRequestError::AboveTip { target: 11, tip: 10 })
+        );
+        assert_eq!(
+            handle.unwind_to(2).await,
+            Err(UnwindRequestError::BelowPruneHorizon { target: 2, horizon: 3 })
+        );
+        assert_eq!(
+            handle.unwind_to(5).await,
+            Ok(UnwindSummary {
+                target: 5,
+                stages: vec![
+                    StageUnwindSummary { stage: "B".to_string(), from: 10, to: 5 },
+                    StageUnwindSummary { stage: "A".to_string(), from: 10, to: 5 },
+                ]
+            })
+        );
+    }
+
     mod utils {
         use super::*;
         use async_trait::async_trait;
@@ -710,11 +896,24 @@ mod tests {
             id: StageId,
             exec_outputs: VecDeque<Result<ExecOutput, StageError>>,
             unwind_outputs: VecDeque<Result<UnwindOutput, StageError>>,
+            wait_for_tip: bool,
         }
 
         impl TestStage {
             pub(crate) fn new(id: StageId) -> Self {
-                Self { id, exec_outputs: VecDeque::new(), unwind_outputs: VecDeque::new() }
+                Self {
+                    id,
+                    exec_outputs: VecDeque::new(),
+                    unwind_outputs: VecDeque::new(),
+                    wait_for_tip: false,
+                }
+            }
+
+            /// Waits forever once all outputs are executed, like a stage waiting for the next
+            /// tip.
+            pub(crate) fn wait_for_tip(mut self) -> Self {
+                self.wait_for_tip = true;
+                self
             }
 
             pub(crate) fn add_exec(mut self, output: Result<ExecOutput, StageError>) -> Self {
@@ -739,9 +938,11 @@ mod tests {
                 _: &mut Transaction<'_, DB>,
                 _input: ExecInput,
             ) -> Result<ExecOutput, StageError> {
-                self.exec_outputs
-                    .pop_front()
-                    .unwrap_or_else(|| panic!("Test stage {} executed too many times.", self.id))
+                match self.exec_outputs.pop_front() {
+                    Some(output) => output,
+                    None if self.wait_for_tip => futures_util::future::pending().await,
+                    None => panic!("Test stage {} executed too many times.", self.id),
+                }
             }
 
             async fn unwind(
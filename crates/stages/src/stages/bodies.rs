@@ -6,7 +6,7 @@ use futures_util::TryStreamExt;
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW},
     database::Database,
-    models::{StoredBlockBody, StoredBlockOmmers, StoredBlockWithdrawals},
+    models::{StoredBlockBodyIndices, StoredBlockOmmers, StoredBlockWithdrawals},
     tables,
     transaction::{DbTx, DbTxMut},
 };
@@ -42,7 +42,7 @@ pub const BODIES: StageId = StageId("Bodies");
 /// The bodies are processed and data is inserted into these tables:
 ///
 /// - [`BlockOmmers`][reth_db::tables::BlockOmmers]
-/// - [`BlockBodies`][reth_db::tables::BlockBodies]
+/// - [`BlockBodyIndices`][reth_db::tables::BlockBodyIndices]
 /// - [`Transactions`][reth_db::tables::Transactions]
 /// - [`BlockTransitionIndex`][reth_db::tables::BlockTransitionIndex]
 /// - [`TxTransitionIndex`][reth_db::tables::TxTransitionIndex]
@@ -53,7 +53,7 @@ pub const BODIES: StageId = StageId("Bodies");
 ///
 /// - The header tables (see [`HeaderStage`][crate::stages::HeaderStage])
 /// - The [`BlockOmmers`][reth_db::tables::BlockOmmers] table
-/// - The [`BlockBodies`][reth_db::tables::BlockBodies] table
+/// - The [`BlockBodyIndices`][reth_db::tables::BlockBodyIndices] table
 /// - The [`Transactions`][reth_db::tables::Transactions] table
 /// - The [`BlockTransitionIndex`][reth_db::tables::BlockTransitionIndex] table
 /// - The [`TxTransitionIndex`][reth_db::tables::TxTransitionIndex] table
@@ -88,7 +88,7 @@ impl<DB: Database, D: BodyDownloader> Stage<DB> for BodyStage<D> {
         let mut td_cursor = tx.cursor_read::<tables::HeaderTD>()?;
 
         // Cursors used to write bodies, ommers and transactions
-        let mut body_cursor = tx.cursor_write::<tables::BlockBodies>()?;
+        let mut body_cursor = tx.cursor_write::<tables::BlockBodyIndices>()?;
         let mut tx_cursor = tx.cursor_write::<tables::Transactions>()?;
         let mut ommers_cursor = tx.cursor_write::<tables::BlockOmmers>()?;
         let mut withdrawals_cursor = tx.cursor_write::<tables::BlockWithdrawals>()?;
@@ -99,9 +99,10 @@ impl<DB: Database, D: BodyDownloader> Stage<DB> for BodyStage<D> {
 
         // Get id for the first transaction and first transition in the block
         let (mut current_tx_id, mut transition_id) = tx.get_next_block_ids(start_block)?;
+        let mut next_withdrawal_index = tx.get_next_withdrawal_index(start_block)?;
 
         let mut highest_block = input.stage_progress.unwrap_or_default();
-        debug!(target: "sync::stages::bodies", stage_progress = highest_block, target = end_block, start_tx_id = current_tx_id, transition_id, "Commencing sync");
+        debug!(target: "sync::stages::bodies", stage_progress = highest_block, target = end_block, first_tx_num = current_tx_id, transition_id, "Commencing sync");
 
         // Task downloader can return `None` only if the response relaying channel was closed. This
         // is a fatal error to prevent the pipeline from running forever.
@@ -113,17 +114,25 @@ impl<DB: Database, D: BodyDownloader> Stage<DB> for BodyStage<D> {
             // Write block
             let block_number = response.block_number();
             let difficulty = response.difficulty();
+            let timestamp = response.header().timestamp;
 
             let mut has_withdrawals = false;
             match response {
                 BlockResponse::Full(block) => {
+                    let withdrawal_count = block
+                        .withdrawals
+                        .as_ref()
+                        .map_or(0, |withdrawals| withdrawals.len() as u64);
                     body_cursor.append(
                         block_number,
-                        StoredBlockBody {
-                            start_tx_id: current_tx_id,
+                        StoredBlockBodyIndices {
+                            first_tx_num: current_tx_id,
                             tx_count: block.body.len() as u64,
+                            first_withdrawal_index: next_withdrawal_index,
+                            withdrawal_count,
                         },
                     )?;
+                    next_withdrawal_index += withdrawal_count;
 
                     // Write transactions
                     for transaction in block.body {
@@ -162,7 +171,11 @@ impl<DB: Database, D: BodyDownloader> Stage<DB> for BodyStage<D> {
                 BlockResponse::Empty(_) => {
                     body_cursor.append(
                         block_number,
-                        StoredBlockBody { start_tx_id: current_tx_id, tx_count: 0 },
+                        StoredBlockBodyIndices {
+                            first_tx_num: current_tx_id,
+                            first_withdrawal_index: next_withdrawal_index,
+                            ..Default::default()
+                        },
                     )?;
                 }
             };
@@ -176,7 +189,9 @@ impl<DB: Database, D: BodyDownloader> Stage<DB> for BodyStage<D> {
                 .ok_or(ProviderError::TotalDifficulty { number: block_number })?
                 .1;
             let has_reward = self.consensus.has_block_reward(td.into(), difficulty);
-            let has_post_block_transition = has_reward || has_withdrawals;
+            let has_post_block_transition = has_reward ||
+                has_withdrawals ||
+                self.consensus.has_block_hash_history(timestamp);
             if has_post_block_transition {
                 transition_id += 1;
             }
@@ -201,7 +216,7 @@ impl<DB: Database, D: BodyDownloader> Stage<DB> for BodyStage<D> {
     ) -> Result<UnwindOutput, StageError> {
         info!(target: "sync::stages::bodies", to_block = input.unwind_to, "Unwinding");
         // Cursors to unwind bodies, ommers
-        let mut body_cursor = tx.cursor_write::<tables::BlockBodies>()?;
+        let mut body_cursor = tx.cursor_write::<tables::BlockBodyIndices>()?;
         let mut transaction_cursor = tx.cursor_write::<tables::Transactions>()?;
         let mut ommers_cursor = tx.cursor_write::<tables::BlockOmmers>()?;
         let mut withdrawals_cursor = tx.cursor_write::<tables::BlockWithdrawals>()?;
@@ -231,7 +246,7 @@ impl<DB: Database, D: BodyDownloader> Stage<DB> for BodyStage<D> {
             }
 
             // Delete all transactions that belong to this block
-            for tx_id in body.tx_id_range() {
+            for tx_id in body.tx_num_range() {
                 // First delete the transaction
                 if transaction_cursor.seek_exact(tx_id)?.is_some() {
                     transaction_cursor.delete_current()?;
@@ -243,7 +258,7 @@ impl<DB: Database, D: BodyDownloader> Stage<DB> for BodyStage<D> {
             }
 
             // Delete the current body value
-            tx.delete::<tables::BlockBodies>(number, None)?;
+            tx.delete::<tables::BlockBodyIndices>(number, None)?;
         }
 
         Ok(UnwindOutput { stage_progress: input.unwind_to })
@@ -432,7 +447,7 @@ mod tests {
             cursor::DbCursorRO,
             database::Database,
             mdbx::{Env, WriteMap},
-            models::{StoredBlockBody, StoredBlockOmmers},
+            models::{StoredBlockBodyIndices, StoredBlockOmmers},
             tables,
             transaction::{DbTx, DbTxMut},
         };
@@ -538,11 +553,12 @@ mod tests {
                 if let Some(progress) = blocks.first() {
                     // Insert last progress data
                     self.tx.commit(|tx| {
-                        let body = StoredBlockBody {
-                            start_tx_id: 0,
+                        let body = StoredBlockBodyIndices {
+                            first_tx_num: 0,
                             tx_count: progress.body.len() as u64,
+                            ..Default::default()
                         };
-                        body.tx_id_range().try_for_each(|tx_id| {
+                        body.tx_num_range().try_for_each(|tx_id| {
                             let transaction = random_signed_tx();
                             tx.put::<tables::Transactions>(tx_id, transaction)?;
                             tx.put::<tables::TxTransitionIndex>(tx_id, tx_id)
@@ -555,7 +571,7 @@ mod tests {
                             progress.number,
                             block_transition_id,
                         )?;
-                        tx.put::<tables::BlockBodies>(progress.number, body)?;
+                        tx.put::<tables::BlockBodyIndices>(progress.number, body)?;
                         if !progress.ommers_hash_is_empty() {
                             tx.put::<tables::BlockOmmers>(
                                 progress.number,
@@ -590,8 +606,10 @@ mod tests {
 
         impl UnwindStageTestRunner for BodyTestRunner {
             fn validate_unwind(&self, input: UnwindInput) -> Result<(), TestRunnerError> {
-                self.tx
-                    .ensure_no_entry_above::<tables::BlockBodies, _>(input.unwind_to, |key| key)?;
+                self.tx.ensure_no_entry_above::<tables::BlockBodyIndices, _>(
+                    input.unwind_to,
+                    |key| key,
+                )?;
                 self.tx
                     .ensure_no_entry_above::<tables::BlockOmmers, _>(input.unwind_to, |key| key)?;
                 self.tx.ensure_no_entry_above::<tables::BlockTransitionIndex, _>(
@@ -614,12 +632,12 @@ mod tests {
             /// Get the last available tx id if any
             pub(crate) fn get_last_tx_id(&self) -> Result<Option<TxNumber>, TestRunnerError> {
                 let last_body = self.tx.query(|tx| {
-                    let v = tx.cursor_read::<tables::BlockBodies>()?.last()?;
+                    let v = tx.cursor_read::<tables::BlockBodyIndices>()?.last()?;
                     Ok(v)
                 })?;
                 Ok(match last_body {
                     Some((_, body)) if body.tx_count != 0 => {
-                        Some(body.start_tx_id + body.tx_count - 1)
+                        Some(body.first_tx_num + body.tx_count - 1)
                     }
                     _ => None,
                 })
@@ -635,7 +653,7 @@ mod tests {
                     // Acquire cursors on body related tables
                     let mut headers_cursor = tx.cursor_read::<tables::Headers>()?;
                     let mut td_cursor = tx.cursor_read::<tables::HeaderTD>()?;
-                    let mut bodies_cursor = tx.cursor_read::<tables::BlockBodies>()?;
+                    let mut bodies_cursor = tx.cursor_read::<tables::BlockBodyIndices>()?;
                     let mut ommers_cursor = tx.cursor_read::<tables::BlockOmmers>()?;
                     let mut block_transition_cursor = tx.cursor_read::<tables::BlockTransitionIndex>()?;
                     let mut transaction_cursor = tx.cursor_read::<tables::Transactions>()?;
@@ -675,7 +693,7 @@ mod tests {
                             assert!(stored_ommers.is_some(), "Missing ommers entry");
                         }
 
-                        for tx_id in body.tx_id_range() {
+                        for tx_id in body.tx_num_range() {
                             let tx_entry = transaction_cursor.seek_exact(tx_id)?;
                             assert!(tx_entry.is_some(), "Transaction is missing.");
                             assert_eq!(
@@ -765,7 +783,7 @@ mod tests {
                             let (num, hash) = entry?;
                             let (_, header) =
                                 header_cursor.seek_exact(num)?.expect("missing header");
-                            headers.push(header.seal(hash));
+                            headers.push(SealedHeader::new_unchecked(header, hash));
                         }
                         Ok(headers)
                     })??);
@@ -10,8 +10,13 @@ use reth_db::{
     transaction::{DbTx, DbTxMut},
 };
 use reth_interfaces::provider::ProviderError;
-use reth_primitives::{Address, Block, U256};
-use reth_provider::{BlockExecutor, ExecutorFactory, LatestStateProviderRef, Transaction};
+use reth_metrics_common::execution::ExecutionMetrics;
+use reth_primitives::{Address, Block, PruneModes, U256};
+use reth_provider::{
+    get_receipts_prune_checkpoint, set_receipts_prune_checkpoint, BlockExecutor, ExecutorFactory,
+    LatestStateProviderRef, Transaction,
+};
+use std::time::{Duration, Instant};
 use tracing::*;
 
 /// The [`StageId`] of the execution stage.
@@ -24,7 +29,7 @@ pub const EXECUTION: StageId = StageId("Execution");
 /// - [tables::CanonicalHeaders] get next block to execute.
 /// - [tables::Headers] get for revm environment variables.
 /// - [tables::HeaderTD]
-/// - [tables::BlockBodies] to get tx number
+/// - [tables::BlockBodyIndices] to get tx number
 /// - [tables::Transactions] to execute
 ///
 /// For state access [LatestStateProviderRef] provides us latest state and history state
@@ -41,7 +46,7 @@ pub const EXECUTION: StageId = StageId("Execution");
 /// - [tables::StorageChangeSet]
 ///
 /// For unwinds we are accessing:
-/// - [tables::BlockBodies] get tx index to know what needs to be unwinded
+/// - [tables::BlockBodyIndices] get tx index to know what needs to be unwinded
 /// - [tables::AccountHistory] to remove change set and apply old values to
 /// - [tables::PlainAccountState] [tables::StorageHistory] to remove change set and apply old values
 /// to [tables::PlainStorageState]
@@ -52,23 +57,45 @@ pub struct ExecutionStage<EF: ExecutorFactory> {
     pub executor_factory: EF,
     /// Commit threshold
     pub commit_threshold: u64,
+    /// The throughput metrics of the executed blocks.
+    metrics: ExecutionMetrics,
+    /// The receipts pruned after every execution, see [Transaction::prune_receipts].
+    prune_modes: PruneModes,
 }
 
 impl<EF: ExecutorFactory> ExecutionStage<EF> {
     /// Create new execution stage with specified config.
     pub fn new(executor_factory: EF, commit_threshold: u64) -> Self {
-        Self { executor_factory, commit_threshold }
+        Self {
+            executor_factory,
+            commit_threshold,
+            metrics: ExecutionMetrics::new("sync.execution"),
+            prune_modes: PruneModes::default(),
+        }
     }
 
     /// Create execution stage with executor factory and default commit threshold set to 10_000
     /// blocks
     pub fn new_default_threshold(executor_factory: EF) -> Self {
-        Self { executor_factory, commit_threshold: 10_000 }
+        Self::new(executor_factory, 10_000)
+    }
+
+    /// Sets the execution time above which a block is logged as slow.
+    pub fn with_slow_block_threshold(mut self, threshold: Duration) -> Self {
+        self.metrics = self.metrics.with_slow_block_threshold(threshold);
+        self
+    }
+
+    /// Sets the prune modes of the receipts that are pruned after the executed blocks are
+    /// written.
+    pub fn with_prune_modes(mut self, prune_modes: PruneModes) -> Self {
+        self.prune_modes = prune_modes;
+        self
     }
 
     /// Execute the stage.
     pub fn execute_inner<DB: Database>(
-        &self,
+        &mut self,
         tx: &mut Transaction<'_, DB>,
         input: ExecInput,
     ) -> Result<ExecOutput, StageError> {
@@ -81,7 +108,7 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
         // Get total difficulty
         let mut td_cursor = tx.cursor_read::<tables::HeaderTD>()?;
         // Get bodies with canonical hashes.
-        let mut bodies_cursor = tx.cursor_read::<tables::BlockBodies>()?;
+        let mut bodies_cursor = tx.cursor_read::<tables::BlockBodyIndices>()?;
         // Get ommers with canonical hashes.
         let mut ommers_cursor = tx.cursor_read::<tables::BlockOmmers>()?;
         // Get block withdrawals.
@@ -118,10 +145,10 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
             tracing::trace!(target: "sync::stages::execution", ?block_number, "Execute block.");
 
             // iterate over all transactions
-            let mut tx_walker = tx_cursor.walk(Some(body.start_tx_id))?;
+            let mut tx_walker = tx_cursor.walk(Some(body.first_tx_num))?;
             let mut transactions = Vec::with_capacity(body.tx_count as usize);
             // get next N transactions.
-            for index in body.tx_id_range() {
+            for index in body.tx_num_range() {
                 let (tx_index, tx) =
                     tx_walker.next().ok_or(ProviderError::EndOfTransactionTable)??;
                 if tx_index != index {
@@ -132,9 +159,9 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
             }
 
             // take signers
-            let mut tx_sender_walker = tx_sender.walk(Some(body.start_tx_id))?;
+            let mut tx_sender_walker = tx_sender.walk(Some(body.first_tx_num))?;
             let mut signers = Vec::with_capacity(body.tx_count as usize);
-            for index in body.tx_id_range() {
+            for index in body.tx_num_range() {
                 let (tx_index, tx) =
                     tx_sender_walker.next().ok_or(ProviderError::EndOfTransactionSenderTable)??;
                 if tx_index != index {
@@ -146,6 +173,10 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
 
             trace!(target: "sync::stages::execution", number = block_number, txs = transactions.len(), "Executing block");
 
+            let block_hash = header.hash_slow();
+            let gas_used = header.gas_used;
+            let started_at = Instant::now();
+
             // Configure the executor to use the current state.
             let changeset = executor
                 .execute_and_verify_receipt(
@@ -154,11 +185,13 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
                     Some(signers),
                 )
                 .map_err(|error| StageError::ExecutionError { block: block_number, error })?;
+            self.metrics.record_block(block_number, block_hash, gas_used, started_at.elapsed());
             changesets.push(changeset);
         }
 
         // put execution results to database
         tx.insert_execution_result(changesets, self.executor_factory.chain_spec(), last_block)?;
+        tx.prune_receipts(end_block, &self.prune_modes)?;
 
         let done = !capped;
         info!(target: "sync::stages::execution", stage_progress = end_block, done, "Sync iteration finished");
@@ -204,6 +237,11 @@ impl<EF: ExecutorFactory, DB: Database> Stage<DB> for ExecutionStage<EF> {
     ) -> Result<UnwindOutput, StageError> {
         info!(target: "sync::stages::execution", to_block = input.unwind_to, "Unwinding");
 
+        // the receipts of the unwound blocks are pruned again once they are executed again
+        if get_receipts_prune_checkpoint(&**tx)?.map_or(false, |pruned| pruned > input.unwind_to) {
+            set_receipts_prune_checkpoint(&**tx, input.unwind_to)?;
+        }
+
         // Acquire changeset cursors
         let mut account_changeset = tx.cursor_dup_write::<tables::AccountChangeSet>()?;
         let mut storage_changeset = tx.cursor_dup_write::<tables::StorageChangeSet>()?;
@@ -287,6 +325,7 @@ impl<EF: ExecutorFactory, DB: Database> Stage<DB> for ExecutionStage<EF> {
 mod tests {
     use super::*;
     use crate::test_utils::{TestTransaction, PREV_STAGE_ID};
+    use metrics::{Counter, Gauge, Histogram, Key, KeyName, Recorder, SharedString, Unit};
     use reth_db::{
         mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
         models::AccountBeforeTx,
@@ -299,10 +338,57 @@ mod tests {
     use reth_provider::insert_canonical_block;
     use reth_rlp::Decodable;
     use std::{
+        cell::RefCell,
+        collections::HashMap,
         ops::{Deref, DerefMut},
-        sync::Arc,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
     };
 
+    thread_local! {
+        /// The counters registered on the current thread.
+        static COUNTERS: RefCell<HashMap<String, Arc<AtomicU64>>> = Default::default();
+    }
+
+    /// Keeps the counters of every thread apart, so that concurrently running tests don't count
+    /// each other's blocks.
+    struct ThreadLocalRecorder;
+
+    impl Recorder for ThreadLocalRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key) -> Counter {
+            let counter = Arc::new(AtomicU64::new(0));
+            COUNTERS.with(|counters| {
+                counters.borrow_mut().insert(key.name().to_string(), counter.clone())
+            });
+            Counter::from_arc(counter)
+        }
+
+        fn register_gauge(&self, _: &Key) -> Gauge {
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, _: &Key) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    /// Returns the value of the counter registered on the current thread.
+    fn counter(name: &str) -> u64 {
+        COUNTERS
+            .with(|counters| {
+                counters.borrow().get(name).map(|counter| counter.load(Ordering::Relaxed))
+            })
+            .unwrap_or_default()
+    }
+
     fn stage() -> ExecutionStage<Factory> {
         let factory =
             Factory::new(Arc::new(ChainSpecBuilder::mainnet().berlin_activated().build()));
@@ -313,6 +399,7 @@ mod tests {
     async fn sanity_execution_of_block() {
         // TODO cleanup the setup after https://github.com/paradigmxyz/reth/issues/332
         // is merged as it has similar framework
+        let _ = metrics::set_recorder(&ThreadLocalRecorder);
         let state_db = create_test_db::<WriteMap>(EnvKind::RW);
         let mut tx = Transaction::new(state_db.as_ref()).unwrap();
         let input = ExecInput {
@@ -354,6 +441,8 @@ mod tests {
         let output = execution_stage.execute(&mut tx, input).await.unwrap();
         tx.commit().unwrap();
         assert_eq!(output, ExecOutput { stage_progress: 1, done: true });
+        assert_eq!(counter("sync.execution.blocks_processed_total"), 1);
+        assert_eq!(counter("sync.execution.gas_processed_total"), block.gas_used);
         let tx = tx.deref_mut();
         // check post state
         let account1 = H160(hex!("1000000000000000000000000000000000000000"));
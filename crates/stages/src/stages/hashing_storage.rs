@@ -226,7 +226,7 @@ mod tests {
     use reth_db::{
         cursor::{DbCursorRO, DbCursorRW},
         mdbx::{tx::Tx, WriteMap, RW},
-        models::{StoredBlockBody, TransitionIdAddress},
+        models::{StoredBlockBodyIndices, TransitionIdAddress},
     };
     use reth_interfaces::test_utils::generators::{
         random_block_range, random_contract_account_range,
@@ -326,9 +326,10 @@ mod tests {
             for progress in iter {
                 // Insert last progress data
                 self.tx.commit(|tx| {
-                    let body = StoredBlockBody {
-                        start_tx_id: tx_id,
+                    let body = StoredBlockBodyIndices {
+                        first_tx_num: tx_id,
                         tx_count: progress.body.len() as u64,
+                        ..Default::default()
                     };
 
                     progress.body.iter().try_for_each(|transaction| {
@@ -374,7 +375,7 @@ mod tests {
                     }
 
                     tx.put::<tables::BlockTransitionIndex>(progress.number, transition_id)?;
-                    tx.put::<tables::BlockBodies>(progress.number, body)
+                    tx.put::<tables::BlockBodyIndices>(progress.number, body)
                 })?;
             }
 
@@ -85,7 +85,7 @@ where
         let (_, head) = header_cursor
             .seek_exact(head_num)?
             .ok_or(ProviderError::Header { number: head_num })?;
-        let local_head = head.seal(head_hash);
+        let local_head = SealedHeader::new_unchecked(head, head_hash);
 
         // Look up the next header
         let next_header = cursor
@@ -94,7 +94,7 @@ where
                 let (_, next) = header_cursor
                     .seek_exact(next_num)?
                     .ok_or(ProviderError::Header { number: next_num })?;
-                Ok(next.seal(next_hash))
+                Ok(SealedHeader::new_unchecked(next, next_hash))
             })
             .transpose()?;
 
@@ -144,10 +144,9 @@ where
                 continue
             }
 
-            let header_hash = header.hash();
+            let (header, header_hash) = header.split();
             let header_number = header.number;
-            let header = header.unseal();
-            latest = Some(header.number);
+            latest = Some(header_number);
 
             // NOTE: HeaderNumbers are not sorted and can't be inserted with cursor.
             tx.put::<tables::HeaderNumbers>(header_hash, header_number)?;
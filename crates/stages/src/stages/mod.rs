@@ -37,3 +37,85 @@ pub use merkle::*;
 pub use sender_recovery::*;
 pub use total_difficulty::*;
 pub use tx_lookup::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::TestTransaction, Pipeline};
+    use reth_db::{
+        mdbx::{Env, WriteMap},
+        tables,
+    };
+    use reth_downloaders::{
+        bodies::bodies::BodiesDownloaderBuilder, file_client::FileClient,
+        headers::reverse_headers::ReverseHeadersDownloaderBuilder,
+    };
+    use reth_interfaces::{
+        consensus::ForkchoiceState,
+        sync::NoopSyncStateUpdate,
+        test_utils::{generators::random_block_range, TestConsensus},
+    };
+    use reth_primitives::H256;
+    use reth_rlp::Encodable;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn sync_headers_and_bodies_from_directory() {
+        // export the blocks into four files of 50 blocks
+        let blocks = random_block_range(0..200, H256::zero(), 0..2);
+        let dir = tempfile::tempdir().unwrap();
+        for (index, chunk) in blocks.chunks(50).enumerate() {
+            let mut buf = Vec::new();
+            for block in chunk {
+                block.clone().unseal().encode(&mut buf);
+            }
+            std::fs::write(dir.path().join(format!("{index:05}.rlp")), buf).unwrap();
+        }
+
+        let tx = TestTransaction::default();
+        tx.insert_headers_with_td(blocks[..1].iter().map(|block| &block.header)).unwrap();
+        tx.insert_blocks(blocks[..1].iter(), None).unwrap();
+
+        let mut client = FileClient::from_dir(dir.path()).await.unwrap();
+        client.seek(&blocks[0].header).unwrap();
+        let client = Arc::new(client);
+
+        let consensus = Arc::new(TestConsensus::default());
+        consensus
+            .notify_fork_choice_state(ForkchoiceState {
+                head_block_hash: client.tip().unwrap(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let mut pipeline: Pipeline<Env<WriteMap>, NoopSyncStateUpdate> = Pipeline::builder()
+            .add_stage(HeaderStage::new(
+                ReverseHeadersDownloaderBuilder::default().build(client.clone(), consensus.clone()),
+                consensus.clone(),
+            ))
+            .add_stage(TotalDifficultyStage::new(consensus.clone()))
+            .add_stage(BodyStage {
+                downloader: BodiesDownloaderBuilder::default().build(
+                    client.clone(),
+                    consensus.clone(),
+                    tx.inner_raw(),
+                ),
+                consensus: consensus.clone(),
+            })
+            .with_max_block(199)
+            .build();
+        pipeline.run(tx.inner_raw()).await.unwrap();
+
+        let canonical = tx.table::<tables::CanonicalHeaders>().unwrap();
+        assert_eq!(
+            canonical.into_iter().map(|(_, hash)| hash).collect::<Vec<_>>(),
+            blocks.iter().map(|block| block.hash()).collect::<Vec<_>>()
+        );
+        assert_eq!(tx.table::<tables::BlockBodyIndices>().unwrap().len(), 200);
+        assert_eq!(
+            tx.table::<tables::Transactions>().unwrap().len(),
+            blocks.iter().map(|block| block.body.len()).sum::<usize>()
+        );
+        assert!(client.is_exhausted(199));
+    }
+}
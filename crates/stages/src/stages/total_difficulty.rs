@@ -9,7 +9,7 @@ use reth_db::{
     transaction::{DbTx, DbTxMut},
 };
 use reth_interfaces::{consensus::Consensus, provider::ProviderError};
-use reth_primitives::U256;
+use reth_primitives::{SealedHeader, U256};
 use reth_provider::Transaction;
 use std::sync::Arc;
 use tracing::*;
@@ -83,7 +83,7 @@ impl<DB: Database> Stage<DB> for TotalDifficultyStage {
             let (number, hash) = entry?;
             let (_, header) =
                 cursor_headers.seek_exact(number)?.ok_or(ProviderError::Header { number })?;
-            let header = header.seal(hash);
+            let header = SealedHeader::new_unchecked(header, hash);
             td += header.difficulty;
 
             self.consensus
@@ -6,8 +6,8 @@ use reth_db::{
         tx::Tx,
         Env, EnvKind, WriteMap, RW,
     },
-    models::{AccountBeforeTx, BlockNumHash, StoredBlockBody},
-    table::Table,
+    models::{AccountBeforeTx, BlockNumHash, StoredBlockBodyIndices},
+    table::{Compress, Encode, Table},
     tables,
     transaction::{DbTx, DbTxMut},
     Error as DbError,
@@ -101,6 +101,19 @@ impl TestTransaction {
         })
     }
 
+    /// Return the keccak256 hash of the raw encoded contents of a table.
+    pub fn table_checksum<T: Table>(&self) -> Result<H256, DbError>
+    where
+        T::Key: Default + Ord,
+    {
+        let mut buf = Vec::new();
+        for (key, value) in self.table::<T>()? {
+            buf.extend_from_slice(key.encode().as_ref());
+            buf.extend_from_slice(value.compress().as_ref());
+        }
+        Ok(keccak256(buf))
+    }
+
     /// Map a collection of values and store them in the database.
     /// This function commits the transaction before exiting.
     ///
@@ -235,11 +248,12 @@ impl TestTransaction {
             blocks.into_iter().try_for_each(|block| {
                 Self::insert_header(tx, &block.header)?;
                 // Insert into body tables.
-                tx.put::<tables::BlockBodies>(
+                tx.put::<tables::BlockBodyIndices>(
                     block.number,
-                    StoredBlockBody {
-                        start_tx_id: current_tx_id,
+                    StoredBlockBodyIndices {
+                        first_tx_num: current_tx_id,
                         tx_count: block.body.len() as u64,
+                        ..Default::default()
                     },
                 )?;
                 block.body.iter().try_for_each(|body_tx| {
@@ -68,7 +68,7 @@ impl_iai!(
     HeaderTD,
     HeaderNumbers,
     Headers,
-    BlockBodies,
+    BlockBodyIndices,
     BlockOmmers,
     TxHashNumber,
     BlockTransitionIndex,
@@ -11,8 +11,10 @@
 pub mod abstraction;
 
 mod implementation;
+pub mod migration;
 pub mod tables;
 mod utils;
+pub mod version;
 
 #[cfg(feature = "mdbx")]
 /// Bindings for [MDBX](https://libmdbx.dqdkfa.ru/).
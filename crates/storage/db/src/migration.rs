@@ -0,0 +1,382 @@
+//! Migrations of existing databases to the current [version](crate::version) of the tables.
+
+use crate::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    models::StoredBlockBodyIndices,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    Error,
+};
+
+/// Tables of previous database versions that are only read by the migrations.
+pub mod legacy {
+    use crate::{
+        table::{Compress, Decompress},
+        Error,
+    };
+    use reth_codecs::{main_codec, Compact};
+    use reth_primitives::{bytes::Bytes, BlockNumber, Bloom, Log, Receipt, TxNumber, TxType};
+
+    /// The block body of database version 0: the number of the first transaction of the block and
+    /// the number of transactions.
+    #[derive(Debug, Default, Eq, PartialEq, Clone)]
+    #[main_codec]
+    pub struct StoredBlockBody {
+        /// The number of the first transaction in this block
+        pub start_tx_id: TxNumber,
+        /// The total number of transactions in the block
+        pub tx_count: u64,
+    }
+
+    impl Compress for StoredBlockBody {
+        type Compressed = Vec<u8>;
+
+        fn compress(self) -> Self::Compressed {
+            let mut buf = vec![];
+            let _ = Compact::to_compact(self, &mut buf);
+            buf
+        }
+    }
+
+    impl Decompress for StoredBlockBody {
+        fn decompress<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
+            let value = value.into();
+            let (obj, _) = Compact::from_compact(&value, value.len());
+            Ok(obj)
+        }
+    }
+
+    crate::table!(
+        /// Stores the block bodies in database version 0, replaced by
+        /// [BlockBodyIndices](crate::tables::BlockBodyIndices) in version 1.
+        ( BlockBodies ) BlockNumber | StoredBlockBody
+    );
+
+    /// The receipt of database versions 0 and 1, which also stored the bloom filter of the logs.
+    #[derive(Debug, Default, Eq, PartialEq, Clone)]
+    #[main_codec]
+    pub struct StoredReceipt {
+        /// Receipt type.
+        pub tx_type: TxType,
+        /// If transaction is executed successfully.
+        pub success: bool,
+        /// Gas used
+        pub cumulative_gas_used: u64,
+        /// Bloom filter of the logs.
+        pub bloom: Bloom,
+        /// Log send from contracts.
+        pub logs: Vec<Log>,
+    }
+
+    impl From<StoredReceipt> for Receipt {
+        fn from(receipt: StoredReceipt) -> Self {
+            let StoredReceipt { tx_type, success, cumulative_gas_used, logs, .. } = receipt;
+            Receipt {
+                tx_type,
+                success,
+                cumulative_gas_used,
+                logs,
+                // receipts of deposit transactions were never stored in the legacy format
+                #[cfg(feature = "optimism")]
+                deposit_nonce: None,
+                #[cfg(feature = "optimism")]
+                deposit_receipt_version: None,
+            }
+        }
+    }
+
+    impl Compress for StoredReceipt {
+        type Compressed = Vec<u8>;
+
+        fn compress(self) -> Self::Compressed {
+            let mut buf = vec![];
+            let _ = Compact::to_compact(self, &mut buf);
+            buf
+        }
+    }
+
+    impl Decompress for StoredReceipt {
+        fn decompress<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
+            let value = value.into();
+            let (obj, _) = Compact::from_compact(&value, value.len());
+            Ok(obj)
+        }
+    }
+
+    crate::table!(
+        /// Stores the receipts with their bloom filters in database versions 0 and 1, the
+        /// [Receipts](crate::tables::Receipts) of version 2 don't store the bloom filters.
+        ( Receipts ) TxNumber | StoredReceipt
+    );
+}
+
+/// The number of receipts that are migrated per transaction by the migration of the receipts.
+const RECEIPTS_MIGRATION_BATCH_SIZE: usize = 100_000;
+
+/// The key of the number of the next receipt to migrate in the [tables::SyncStageProgress] table,
+/// which is set while the receipts are migrated.
+pub const RECEIPTS_MIGRATION_KEY: &str = "ReceiptsMigration";
+
+/// Migrates the database from the given version to [DB_VERSION](crate::version::DB_VERSION).
+///
+/// The tables of the current version must already exist. An interrupted migration is resumed by
+/// running it again with the same version.
+pub fn migrate<DB: Database>(db: &DB, from_version: u64) -> Result<(), Error> {
+    if from_version < 1 {
+        let tx = db.tx_mut()?;
+        migrate_block_body_indices(&tx)?;
+        tx.commit()?;
+    }
+    if from_version < 2 {
+        migrate_receipts(db, RECEIPTS_MIGRATION_BATCH_SIZE)?;
+    }
+    Ok(())
+}
+
+/// Version 1 replaced the [legacy::BlockBodies] table with [tables::BlockBodyIndices], which also
+/// indexes the withdrawals of the block.
+fn migrate_block_body_indices<'a, TX: DbTxMut<'a> + DbTx<'a>>(tx: &TX) -> Result<(), Error> {
+    let mut bodies_cursor = tx.cursor_read::<legacy::BlockBodies>()?;
+    let mut withdrawals_cursor = tx.cursor_read::<tables::BlockWithdrawals>()?;
+    let mut indices_cursor = tx.cursor_write::<tables::BlockBodyIndices>()?;
+
+    let mut next_withdrawal_index = 0;
+    for entry in bodies_cursor.walk(None)? {
+        let (number, body) = entry?;
+        let withdrawal_count = withdrawals_cursor
+            .seek_exact(number)?
+            .map(|(_, block)| block.withdrawals.len() as u64)
+            .unwrap_or_default();
+        indices_cursor.append(
+            number,
+            StoredBlockBodyIndices {
+                first_tx_num: body.start_tx_id,
+                tx_count: body.tx_count,
+                first_withdrawal_index: next_withdrawal_index,
+                withdrawal_count,
+            },
+        )?;
+        next_withdrawal_index += withdrawal_count;
+    }
+
+    tx.clear::<legacy::BlockBodies>()
+}
+
+/// Version 2 stopped storing the bloom filters of the [tables::Receipts], they are calculated from
+/// the logs when needed.
+///
+/// The receipts are rewritten in place, since both versions of the table have the same name, and
+/// every batch is committed together with the number of the next receipt to migrate under
+/// [RECEIPTS_MIGRATION_KEY]. The receipts before that number are already migrated, so an
+/// interrupted migration continues from there.
+fn migrate_receipts<DB: Database>(db: &DB, batch_size: usize) -> Result<(), Error> {
+    loop {
+        let tx = db.tx_mut()?;
+        let next = tx
+            .get::<tables::SyncStageProgress>(RECEIPTS_MIGRATION_KEY.into())?
+            .and_then(|buf| Some(u64::from_be_bytes(buf.as_slice().try_into().ok()?)))
+            .unwrap_or_default();
+
+        let receipts = tx
+            .cursor_read::<legacy::Receipts>()?
+            .walk(Some(next))?
+            .take(batch_size)
+            .collect::<Result<Vec<_>, _>>()?;
+        let done = receipts.len() < batch_size;
+
+        if let Some((last, _)) = receipts.last() {
+            let next = last + 1;
+            let mut cursor = tx.cursor_write::<tables::Receipts>()?;
+            for (number, receipt) in receipts {
+                cursor.upsert(number, receipt.into())?;
+            }
+            tx.put::<tables::SyncStageProgress>(
+                RECEIPTS_MIGRATION_KEY.into(),
+                next.to_be_bytes().to_vec(),
+            )?;
+        }
+        if done {
+            tx.delete::<tables::SyncStageProgress>(RECEIPTS_MIGRATION_KEY.into(), None)?;
+        }
+        tx.commit()?;
+
+        if done {
+            return Ok(())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mdbx"))]
+mod tests {
+    use super::*;
+    use crate::{mdbx::test_utils::create_test_rw_db, models::StoredBlockWithdrawals};
+    use reth_libmdbx::DatabaseFlags;
+    use reth_primitives::{
+        bloom::logs_bloom, proofs::calculate_receipt_root, Address, Log, Receipt, ReceiptWithBloom,
+        TxType, Withdrawal, H256,
+    };
+
+    #[test]
+    fn migrate_block_bodies() {
+        let db = create_test_rw_db();
+        let tx = db.begin_rw_txn().unwrap();
+        tx.create_db(Some(legacy::BlockBodies::const_name()), DatabaseFlags::default()).unwrap();
+        tx.commit().unwrap();
+
+        // an empty genesis, a block with two transactions and two withdrawals, an empty block and
+        // a block with a transaction and a withdrawal
+        let bodies = [(0, 0), (0, 2), (2, 0), (2, 1)];
+        let withdrawals = [(1, 2), (3, 1)];
+        db.update(|tx| {
+            for (number, (start_tx_id, tx_count)) in bodies.into_iter().enumerate() {
+                tx.put::<legacy::BlockBodies>(
+                    number as u64,
+                    legacy::StoredBlockBody { start_tx_id, tx_count },
+                )
+                .unwrap();
+            }
+            for (number, count) in withdrawals {
+                tx.put::<tables::BlockWithdrawals>(
+                    number,
+                    StoredBlockWithdrawals { withdrawals: vec![Withdrawal::default(); count] },
+                )
+                .unwrap();
+            }
+        })
+        .unwrap();
+
+        migrate(db.as_ref(), 0).unwrap();
+
+        let tx = db.tx().unwrap();
+        assert!(tx.cursor_read::<legacy::BlockBodies>().unwrap().first().unwrap().is_none());
+        let indices = tx
+            .cursor_read::<tables::BlockBodyIndices>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let body = |first_tx_num, tx_count, first_withdrawal_index, withdrawal_count| {
+            StoredBlockBodyIndices {
+                first_tx_num,
+                tx_count,
+                first_withdrawal_index,
+                withdrawal_count,
+            }
+        };
+        assert_eq!(
+            indices,
+            vec![
+                (0, body(0, 0, 0, 0)),
+                (1, body(0, 2, 0, 2)),
+                (2, body(2, 0, 2, 0)),
+                (3, body(2, 1, 2, 1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn migrate_receipts_without_bloom() {
+        let db = create_test_rw_db();
+
+        // a block of receipts of all types, with and without logs
+        let log = |n: u64| Log {
+            address: Address::from_low_u64_be(n),
+            topics: vec![H256::from_low_u64_be(n)],
+            data: Default::default(),
+        };
+        let receipts = [
+            (TxType::Legacy, true, vec![]),
+            (TxType::EIP2930, false, vec![log(1)]),
+            (TxType::EIP1559, true, vec![log(2), log(3)]),
+            (TxType::Legacy, true, vec![log(4)]),
+            (TxType::EIP1559, false, vec![]),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, (tx_type, success, logs))| legacy::StoredReceipt {
+            tx_type,
+            success,
+            cumulative_gas_used: 21_000 * (index as u64 + 1),
+            bloom: logs_bloom(logs.iter()),
+            logs,
+        })
+        .collect::<Vec<_>>();
+        db.update(|tx| {
+            for (number, receipt) in receipts.iter().enumerate() {
+                tx.put::<legacy::Receipts>(number as u64, receipt.clone()).unwrap();
+            }
+        })
+        .unwrap();
+
+        // the receipts as they are sent over the network and committed to by the receipts root
+        let expected = receipts
+            .into_iter()
+            .map(|receipt| ReceiptWithBloom { bloom: receipt.bloom, receipt: receipt.into() })
+            .collect::<Vec<_>>();
+
+        migrate(db.as_ref(), 1).unwrap();
+
+        let tx = db.tx().unwrap();
+        let migrated = tx
+            .cursor_read::<tables::Receipts>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .map(|entry| entry.map(|(_, receipt)| receipt))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let with_bloom = migrated.into_iter().map(Receipt::with_bloom).collect::<Vec<_>>();
+        assert_eq!(with_bloom, expected);
+        assert_eq!(calculate_receipt_root(&with_bloom), calculate_receipt_root(&expected));
+    }
+
+    #[test]
+    fn resume_receipts_migration() {
+        let db = create_test_rw_db();
+
+        let receipt = |n: u64| legacy::StoredReceipt {
+            tx_type: TxType::Legacy,
+            success: true,
+            cumulative_gas_used: 21_000 * n,
+            bloom: Default::default(),
+            logs: vec![],
+        };
+        // the first two receipts were migrated before the migration was interrupted
+        db.update(|tx| {
+            for number in 0..2 {
+                tx.put::<tables::Receipts>(number, receipt(number + 1).into()).unwrap();
+            }
+            for number in 2..7 {
+                tx.put::<legacy::Receipts>(number, receipt(number + 1)).unwrap();
+            }
+            tx.put::<tables::SyncStageProgress>(
+                RECEIPTS_MIGRATION_KEY.into(),
+                2u64.to_be_bytes().to_vec(),
+            )
+            .unwrap();
+        })
+        .unwrap();
+
+        // migrated in three batches
+        migrate_receipts(db.as_ref(), 2).unwrap();
+
+        let tx = db.tx().unwrap();
+        assert_eq!(
+            tx.get::<tables::SyncStageProgress>(RECEIPTS_MIGRATION_KEY.into()).unwrap(),
+            None
+        );
+        let migrated = tx
+            .cursor_read::<tables::Receipts>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            migrated,
+            (0..7).map(|number| (number, Receipt::from(receipt(number + 1)))).collect::<Vec<_>>()
+        );
+    }
+}
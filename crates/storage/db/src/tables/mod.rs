@@ -13,7 +13,7 @@ use crate::{
             accounts::{AccountBeforeTx, TransitionIdAddress},
             blocks::{HeaderHash, StoredBlockOmmers},
             storage_sharded_key::StorageShardedKey,
-            ShardedKey, StoredBlockBody, StoredBlockWithdrawals,
+            ShardedKey, StoredBlockBodyIndices, StoredBlockWithdrawals,
         },
     },
 };
@@ -37,7 +37,7 @@ pub const TABLES: [(TableType, &str); 27] = [
     (TableType::Table, HeaderTD::const_name()),
     (TableType::Table, HeaderNumbers::const_name()),
     (TableType::Table, Headers::const_name()),
-    (TableType::Table, BlockBodies::const_name()),
+    (TableType::Table, BlockBodyIndices::const_name()),
     (TableType::Table, BlockOmmers::const_name()),
     (TableType::Table, BlockWithdrawals::const_name()),
     (TableType::Table, Transactions::const_name()),
@@ -132,8 +132,8 @@ table!(
 );
 
 table!(
-    /// Stores block bodies.
-    ( BlockBodies ) BlockNumber | StoredBlockBody
+    /// Stores the indices of the transactions and withdrawals of a block body.
+    ( BlockBodyIndices ) BlockNumber | StoredBlockBodyIndices
 );
 
 table!(
@@ -14,35 +14,51 @@ use serde::{Deserialize, Serialize};
 /// Total number of transactions.
 pub type NumTransactions = u64;
 
-/// The storage representation of a block.
+/// The storage representation of the indices of a block's body.
 ///
-/// It has the pointer to the transaction Number of the first
-/// transaction in the block and the total number of transactions
+/// Transactions are keyed by a global, monotonically increasing [TxNumber], so the transactions of
+/// a block are the range of `tx_count` transactions starting at `first_tx_num`. Withdrawals are
+/// numbered the same way, which matches the index of [EIP-4895](https://eips.ethereum.org/EIPS/eip-4895)
+/// withdrawals.
 #[derive(Debug, Default, Eq, PartialEq, Clone)]
 #[main_codec]
-pub struct StoredBlockBody {
-    /// The id of the first transaction in this block
-    pub start_tx_id: TxNumber,
+pub struct StoredBlockBodyIndices {
+    /// The number of the first transaction in this block
+    ///
+    /// Note: If the block is empty, this is the number of the first transaction of the next
+    /// non-empty block.
+    pub first_tx_num: TxNumber,
     /// The total number of transactions in the block
     pub tx_count: NumTransactions,
+    /// The index of the first withdrawal in this block
+    ///
+    /// Note: If the block has no withdrawals, this is the index of the next withdrawal.
+    pub first_withdrawal_index: u64,
+    /// The total number of withdrawals in the block
+    pub withdrawal_count: u64,
 }
 
-impl StoredBlockBody {
-    /// Return the range of transaction ids for this body
-    pub fn tx_id_range(&self) -> Range<u64> {
-        self.start_tx_id..self.start_tx_id + self.tx_count
+impl StoredBlockBodyIndices {
+    /// Return the range of transaction numbers for this body
+    pub fn tx_num_range(&self) -> Range<TxNumber> {
+        self.first_tx_num..self.first_tx_num + self.tx_count
     }
 
-    /// Return the index of last transaction in this block unless the block
+    /// Return the number of the last transaction in this block unless the block
     /// is empty in which case it refers to the last transaction in a previous
     /// non-empty block
-    pub fn last_tx_index(&self) -> TxNumber {
-        self.start_tx_id.saturating_add(self.tx_count).saturating_sub(1)
+    pub fn last_tx_num(&self) -> TxNumber {
+        self.first_tx_num.saturating_add(self.tx_count).saturating_sub(1)
     }
 
-    /// First transaction index.
-    pub fn first_tx_index(&self) -> TxNumber {
-        self.start_tx_id
+    /// First transaction number.
+    pub fn first_tx_num(&self) -> TxNumber {
+        self.first_tx_num
+    }
+
+    /// Return the number of the first transaction of the next block.
+    pub fn next_tx_num(&self) -> TxNumber {
+        self.first_tx_num + self.tx_count
     }
 
     /// Return a flag whether the block is empty
@@ -54,6 +70,11 @@ impl StoredBlockBody {
     pub fn tx_count(&self) -> NumTransactions {
         self.tx_count
     }
+
+    /// Return the index of the first withdrawal of the next block.
+    pub fn next_withdrawal_index(&self) -> u64 {
+        self.first_withdrawal_index + self.withdrawal_count
+    }
 }
 
 /// The storage representation of a block ommers.
@@ -181,6 +202,31 @@ mod test {
         assert_eq!(bytes, Encode::encode(key));
     }
 
+    #[test]
+    fn test_body_indices() {
+        let body = StoredBlockBodyIndices {
+            first_tx_num: 10,
+            tx_count: 3,
+            first_withdrawal_index: 4,
+            withdrawal_count: 2,
+        };
+        assert_eq!(body.tx_num_range(), 10..13);
+        assert_eq!(body.last_tx_num(), 12);
+        assert_eq!(body.next_tx_num(), 13);
+        assert_eq!(body.next_withdrawal_index(), 6);
+        assert_eq!(
+            body.clone(),
+            StoredBlockBodyIndices::decompress::<Vec<_>>(body.compress()).unwrap()
+        );
+
+        // an empty block points at the next transaction
+        let empty = StoredBlockBodyIndices { first_tx_num: 13, ..Default::default() };
+        assert!(empty.is_empty());
+        assert!(empty.tx_num_range().is_empty());
+        assert_eq!(empty.last_tx_num(), 12);
+        assert_eq!(empty.next_tx_num(), 13);
+    }
+
     #[test]
     fn test_ommer() {
         let mut ommer = StoredBlockOmmers::default();
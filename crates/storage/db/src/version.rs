@@ -0,0 +1,83 @@
+//! The version of the database layout, stored in a file next to the database.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The current version of the database layout.
+///
+/// The version is bumped on every change of the tables that existing databases need to be
+/// migrated for, see [migrate](crate::migration::migrate).
+pub const DB_VERSION: u64 = 2;
+
+/// The name of the file that stores the version of the database.
+pub const DB_VERSION_FILE_NAME: &str = "database.version";
+
+/// Errors of reading the version file of a database.
+#[derive(Debug, thiserror::Error)]
+pub enum DatabaseVersionError {
+    /// The version file could not be read.
+    #[error("Unable to read the database version file: {0}")]
+    Io(#[from] io::Error),
+    /// The version file does not contain a version.
+    #[error("Malformed database version file")]
+    Malformed,
+    /// The database was created by a newer version of reth.
+    #[error("Database version {version} is newer than the supported version {}", DB_VERSION)]
+    Unsupported {
+        /// The version of the database.
+        version: u64,
+    },
+}
+
+/// Returns the path of the version file of the database at the given path.
+pub fn db_version_file_path<P: AsRef<Path>>(db_path: P) -> PathBuf {
+    db_path.as_ref().join(DB_VERSION_FILE_NAME)
+}
+
+/// Returns the version of the database at the given path, `None` if it has no version file.
+///
+/// Databases without a version file were created before the version file was introduced, which is
+/// version 0.
+pub fn get_db_version<P: AsRef<Path>>(db_path: P) -> Result<Option<u64>, DatabaseVersionError> {
+    match fs::read_to_string(db_version_file_path(db_path)) {
+        Ok(version) => {
+            let version = version.trim().parse().map_err(|_| DatabaseVersionError::Malformed)?;
+            if version > DB_VERSION {
+                return Err(DatabaseVersionError::Unsupported { version })
+            }
+            Ok(Some(version))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Writes the current [DB_VERSION] to the version file of the database at the given path.
+pub fn create_db_version_file<P: AsRef<Path>>(db_path: P) -> io::Result<()> {
+    fs::write(db_version_file_path(db_path), DB_VERSION.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn db_version_file() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(get_db_version(dir.path()).unwrap(), None);
+
+        create_db_version_file(dir.path()).unwrap();
+        assert_eq!(get_db_version(dir.path()).unwrap(), Some(DB_VERSION));
+
+        fs::write(db_version_file_path(dir.path()), "latest").unwrap();
+        assert!(matches!(get_db_version(dir.path()), Err(DatabaseVersionError::Malformed)));
+
+        fs::write(db_version_file_path(dir.path()), (DB_VERSION + 1).to_string()).unwrap();
+        assert!(matches!(
+            get_db_version(dir.path()),
+            Err(DatabaseVersionError::Unsupported { version }) if version == DB_VERSION + 1
+        ));
+    }
+}
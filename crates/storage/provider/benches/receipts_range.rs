@@ -0,0 +1,55 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use reth_db::{
+    database::Database, mdbx::test_utils::create_test_rw_db, models::StoredBlockBodyIndices,
+    tables, transaction::DbTxMut,
+};
+use reth_primitives::{Log, Receipt, MAINNET};
+use reth_provider::{ReceiptProvider, ShareableDatabase};
+use std::sync::Arc;
+
+criterion_group!(benches, receipts_range);
+criterion_main!(benches);
+
+const NUM_BLOCKS: u64 = 1_000;
+const MAX_TRANSACTIONS: u64 = 100;
+
+fn receipts_range(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ReceiptsRange");
+    group.sample_size(10);
+
+    let db = create_test_rw_db();
+    let tx = db.tx_mut().unwrap();
+    let mut first_tx_num = 0;
+    for block_number in 0..NUM_BLOCKS {
+        // every tenth block is empty
+        let tx_count = if block_number % 10 == 0 { 0 } else { block_number % MAX_TRANSACTIONS };
+        tx.put::<tables::BlockBodyIndices>(
+            block_number,
+            StoredBlockBodyIndices { first_tx_num, tx_count, ..Default::default() },
+        )
+        .unwrap();
+        for tx_number in first_tx_num..first_tx_num + tx_count {
+            let receipt = Receipt {
+                success: true,
+                cumulative_gas_used: 21_000 * (tx_number - first_tx_num + 1),
+                logs: vec![Log::default(); 2],
+                ..Default::default()
+            };
+            tx.put::<tables::Receipts>(tx_number, receipt).unwrap();
+        }
+        first_tx_num += tx_count;
+    }
+    tx.commit().unwrap();
+
+    let provider = ShareableDatabase::new(db, Arc::new(MAINNET.clone()));
+    group.bench_function("per_block", |b| {
+        b.iter(|| {
+            (0..NUM_BLOCKS)
+                .map(|number| provider.receipts_by_block(number.into()).unwrap().unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("range", |b| {
+        b.iter(|| provider.receipts_by_block_range(0..NUM_BLOCKS).unwrap())
+    });
+}
@@ -0,0 +1,50 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use reth_db::{
+    database::Database,
+    mdbx::test_utils::create_test_rw_db,
+    tables,
+    transaction::DbTxMut,
+};
+use reth_primitives::{keccak256, Account, StorageEntry, H256, U256};
+use reth_provider::trie::ParallelStateRoot;
+
+criterion_group!(benches, state_root);
+criterion_main!(benches);
+
+fn state_root(c: &mut Criterion) {
+    let mut group = c.benchmark_group("StateRoot");
+    group.sample_size(10);
+
+    for num_accounts in [1_000u64, 10_000, 100_000] {
+        let db = create_test_rw_db();
+
+        let tx = db.tx_mut().unwrap();
+        for i in 0..num_accounts {
+            let hashed_address = keccak256(H256::from_low_u64_be(i));
+            let account = Account { nonce: i, balance: U256::from(i), bytecode_hash: None };
+            tx.put::<tables::HashedAccount>(hashed_address, account).unwrap();
+            // every tenth account is a contract with some storage
+            if i % 10 == 0 {
+                for slot in 0..10u64 {
+                    tx.put::<tables::HashedStorage>(
+                        hashed_address,
+                        StorageEntry {
+                            key: keccak256(H256::from_low_u64_be(slot)),
+                            value: U256::from(slot + 1),
+                        },
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        tx.commit().unwrap();
+
+        let state_root = ParallelStateRoot::new(db.as_ref());
+        group.bench_function(BenchmarkId::new("sequential", num_accounts), |b| {
+            b.iter(|| state_root.sequential_root().unwrap())
+        });
+        group.bench_function(BenchmarkId::new("parallel", num_accounts), |b| {
+            b.iter(|| state_root.parallel_root().unwrap())
+        });
+    }
+}
@@ -0,0 +1,98 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use reth_db::{
+    database::Database,
+    mdbx::test_utils::create_test_rw_db,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::{keccak256, Account, Address, Header, StorageEntry, H256, U256};
+use reth_provider::trie::{DBTrieLoader, HashedPostState, StateRoot, TriePrefetcher};
+
+criterion_group!(benches, trie_prefetch);
+criterion_main!(benches);
+
+const NUM_ACCOUNTS: u64 = 100_000;
+const NUM_TRANSACTIONS: u64 = 300;
+
+/// The keys touched by a transaction, the sender, the recipient and the changed slots of the
+/// recipient.
+struct Transaction {
+    sender: Address,
+    recipient: Address,
+    slots: Vec<H256>,
+}
+
+fn trie_prefetch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("TriePrefetch");
+    group.sample_size(10);
+
+    let db = create_test_rw_db();
+    let tx = db.tx_mut().unwrap();
+    for i in 0..NUM_ACCOUNTS {
+        let hashed_address = keccak256(Address::from_low_u64_be(i));
+        let account = Account { nonce: i, balance: U256::from(i), bytecode_hash: None };
+        tx.put::<tables::HashedAccount>(hashed_address, account).unwrap();
+        // every tenth account is a contract with some storage
+        if i % 10 == 0 {
+            for slot in 0..10u64 {
+                let entry = StorageEntry {
+                    key: keccak256(H256::from_low_u64_be(slot)),
+                    value: U256::from(slot + 1),
+                };
+                tx.put::<tables::HashedStorage>(hashed_address, entry).unwrap();
+            }
+        }
+    }
+    let root = DBTrieLoader::new(&tx).calculate_root().unwrap().root().unwrap();
+    tx.put::<tables::Headers>(0, Header { state_root: root, ..Default::default() }).unwrap();
+    tx.commit().unwrap();
+
+    // a block of transfers and contract calls spread over the state
+    let block = (0..NUM_TRANSACTIONS)
+        .map(|i| Transaction {
+            sender: Address::from_low_u64_be(i * 331 % NUM_ACCOUNTS + 1),
+            recipient: Address::from_low_u64_be(i * 170 % NUM_ACCOUNTS),
+            slots: (0..2).map(|slot| H256::from_low_u64_be((i + slot) % 10)).collect(),
+        })
+        .collect::<Vec<_>>();
+    let post_state = post_state(&db.tx().unwrap(), &block);
+
+    group.bench_function("cold", |b| {
+        b.iter(|| StateRoot::new(&db.tx().unwrap()).overlay_root(root, &post_state).unwrap())
+    });
+    group.bench_function("prefetched", |b| {
+        b.iter(|| {
+            TriePrefetcher::new(db.as_ref()).run(|prefetch| {
+                for transaction in &block {
+                    prefetch.prefetch_account(transaction.sender);
+                    prefetch.prefetch_account(transaction.recipient);
+                    for slot in &transaction.slots {
+                        prefetch.prefetch_storage(transaction.recipient, *slot);
+                    }
+                }
+                StateRoot::new(&db.tx().unwrap())
+                    .with_cache(prefetch.cache().clone())
+                    .overlay_root(root, &post_state)
+                    .unwrap()
+            })
+        })
+    });
+}
+
+/// Bumps the nonces of the senders and overwrites the touched slots of the contracts.
+fn post_state<'a>(tx: &impl DbTx<'a>, block: &[Transaction]) -> HashedPostState {
+    let mut post_state = HashedPostState::default();
+    for (i, transaction) in block.iter().enumerate() {
+        let hashed_sender = keccak256(transaction.sender);
+        let mut sender = tx.get::<tables::HashedAccount>(hashed_sender).unwrap().unwrap();
+        sender.nonce += 1;
+        post_state.accounts.insert(hashed_sender, Some(sender));
+
+        let hashed_recipient = keccak256(transaction.recipient);
+        let storage = post_state.storages.entry(hashed_recipient).or_default();
+        for slot in &transaction.slots {
+            storage.storage.insert(keccak256(slot), U256::from(i + 100));
+        }
+    }
+    post_state
+}
@@ -0,0 +1,865 @@
+//! Cross-table consistency checks of the database.
+use crate::{
+    ensure_block_available,
+    utils::{get_lowest_available_block, LOWEST_AVAILABLE_BLOCK},
+    HistoricalStateProviderRef, ProviderError, StateProvider, Transaction, TransactionError,
+};
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    models::{storage_sharded_key::StorageShardedKey, ShardedKey, TransitionIdAddress},
+    tables,
+    transaction::{DbTx, DbTxMut},
+    TransitionList,
+};
+use reth_interfaces::{db::Error as DbError, Error, Result};
+use reth_primitives::{
+    Address, BlockHash, BlockNumber, ChainSpec, TransitionId, TxHash, TxNumber, H256, U256,
+};
+use std::ops::{Deref, RangeInclusive};
+
+/// The stages whose checkpoints must not exceed the checkpoint of the stage before them.
+const ORDERED_STAGES: [&str; 3] = ["Headers", "Bodies", "Execution"];
+
+/// A violated invariant between the tables of the database, with the keys of the offending
+/// entries.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConsistencyViolation {
+    /// The canonical block has no header, or its hash is not mapped back to its number.
+    #[error("Canonical block #{number} ({hash:?}) has no header or hash to number entry")]
+    MissingCanonicalHeader {
+        /// The canonical block number
+        number: BlockNumber,
+        /// The canonical block hash
+        hash: BlockHash,
+    },
+    /// The transactions of the block do not directly follow the transactions of its parent.
+    #[error("Block #{number} starts at transaction {got}, expected {expected}")]
+    TxNumberGap {
+        /// The block number
+        number: BlockNumber,
+        /// The next transaction number of the parent block
+        expected: TxNumber,
+        /// The first transaction number of the block
+        got: TxNumber,
+    },
+    /// The receipt has no matching transaction.
+    #[error("Receipt of transaction {tx_number} has no transaction")]
+    ReceiptWithoutTransaction {
+        /// The transaction number of the receipt
+        tx_number: TxNumber,
+    },
+    /// The checkpoint of the stage is ahead of the checkpoint of the stage it depends on.
+    #[error(
+        "Stage {stage} is at block #{checkpoint}, ahead of stage {previous_stage} at \
+         block #{previous_checkpoint}"
+    )]
+    StageCheckpointAhead {
+        /// The stage that is ahead
+        stage: String,
+        /// The checkpoint of the stage
+        checkpoint: BlockNumber,
+        /// The stage it depends on
+        previous_stage: String,
+        /// The checkpoint of the stage it depends on
+        previous_checkpoint: BlockNumber,
+    },
+    /// The account history shard is unsorted or overlaps with the previous shard of the account.
+    #[error("Account history shard {highest_transition_id} of {address:?} is unsorted")]
+    AccountHistoryShard {
+        /// The account of the shard
+        address: Address,
+        /// The highest transition id of the shard key
+        highest_transition_id: TransitionId,
+    },
+    /// The storage history shard is unsorted or overlaps with the previous shard of the slot.
+    #[error(
+        "Storage history shard {highest_transition_id} of {address:?} slot {storage_key:?} is \
+         unsorted"
+    )]
+    StorageHistoryShard {
+        /// The account of the shard
+        address: Address,
+        /// The storage slot of the shard
+        storage_key: H256,
+        /// The highest transition id of the shard key
+        highest_transition_id: TransitionId,
+    },
+    /// The transaction hash points to a missing transaction or to a transaction with another
+    /// hash.
+    #[error("Transaction hash {hash:?} points to missing transaction {tx_number}")]
+    DanglingTxLookup {
+        /// The transaction hash
+        hash: TxHash,
+        /// The transaction number it points to
+        tx_number: TxNumber,
+    },
+    /// The transaction hash points to a transaction that is not part of a canonical block, e.g.
+    /// a transaction of a block that was reverted by a reorg.
+    #[error("Transaction hash {hash:?} points to non-canonical transaction {tx_number}")]
+    NonCanonicalTxLookup {
+        /// The transaction hash
+        hash: TxHash,
+        /// The transaction number it points to
+        tx_number: TxNumber,
+    },
+}
+
+impl ConsistencyViolation {
+    /// Returns `true` if the violation can be repaired by [fix_consistency] without losing data.
+    pub fn is_fixable(&self) -> bool {
+        matches!(
+            self,
+            ConsistencyViolation::DanglingTxLookup { .. } |
+                ConsistencyViolation::NonCanonicalTxLookup { .. }
+        )
+    }
+}
+
+/// The violations found by [check_consistency].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConsistencyReport {
+    /// All violations, in the order of the checks.
+    pub violations: Vec<ConsistencyViolation>,
+}
+
+impl ConsistencyReport {
+    /// Returns `true` if no violation was found.
+    pub fn is_consistent(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Returns the violations that can be repaired by [fix_consistency].
+    pub fn fixable(&self) -> impl Iterator<Item = &ConsistencyViolation> + '_ {
+        self.violations.iter().filter(|violation| violation.is_fixable())
+    }
+}
+
+/// Walks the tables of the database and collects all violated invariants between them:
+///
+/// - every canonical hash above the lowest available block has a header and a hash to number entry
+/// - the block body indices cover a contiguous transaction number space
+/// - every receipt has a transaction
+/// - the checkpoints of the headers, bodies and execution stages are ordered
+/// - the account and storage history shards are sorted and do not overlap
+/// - every transaction hash points to a canonical transaction with that hash
+pub fn check_consistency<'a, TX: DbTx<'a>>(tx: &TX) -> Result<ConsistencyReport> {
+    let mut report = ConsistencyReport::default();
+    check_canonical_headers(tx, &mut report)?;
+    check_tx_numbers(tx, &mut report)?;
+    check_receipts(tx, &mut report)?;
+    check_stage_checkpoints(tx, &mut report)?;
+    check_account_history(tx, &mut report)?;
+    check_storage_history(tx, &mut report)?;
+    check_tx_lookup(tx, &mut report)?;
+    Ok(report)
+}
+
+/// Repairs the fixable violations of the report, returns the number of repaired violations.
+///
+/// Dangling and non-canonical transaction hash entries are deleted, the hash is indexed again once
+/// its transaction is inserted.
+pub fn fix_consistency<'a, TX: DbTxMut<'a> + DbTx<'a>>(
+    tx: &TX,
+    report: &ConsistencyReport,
+) -> Result<usize> {
+    let mut fixed = 0;
+    for violation in report.fixable() {
+        if let ConsistencyViolation::DanglingTxLookup { hash, .. } |
+        ConsistencyViolation::NonCanonicalTxLookup { hash, .. } = violation
+        {
+            if tx.delete::<tables::TxHashNumber>(*hash, None)? {
+                fixed += 1;
+            }
+        }
+    }
+    Ok(fixed)
+}
+
+/// A block after which the storage history disagrees with the storage changesets, see
+/// [verify_storage_history].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "Storage of {address:?} slot {storage_key:?} after block #{block_number} is {historical:?} in \
+     the history, but {expected} in the changesets"
+)]
+pub struct StorageHistoryDivergence {
+    /// The account
+    pub address: Address,
+    /// The storage slot
+    pub storage_key: H256,
+    /// The first block after which the values differ
+    pub block_number: BlockNumber,
+    /// The value after the block, replayed from the storage changesets
+    pub expected: U256,
+    /// The value after the block returned by the historical state provider, `None` if the
+    /// changeset its history index points to is missing
+    pub historical: Option<U256>,
+}
+
+/// Replays the value of the storage slot over the blocks of the range from the storage
+/// changesets, and returns the first block after which the value differs from the value of the
+/// [HistoricalStateProviderRef] of the block, which serves `eth_getStorageAt`.
+///
+/// The historical state provider looks up the changes of the slot in the storage history index,
+/// while the replay walks the changesets themselves. A divergence points at changesets that are
+/// missing from the index, or at index entries without their changeset.
+pub fn verify_storage_history<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    address: Address,
+    storage_key: H256,
+    range: RangeInclusive<BlockNumber>,
+) -> Result<Option<StorageHistoryDivergence>> {
+    ensure_block_available(*range.start(), get_lowest_available_block(tx)?)?;
+    if range.is_empty() {
+        return Ok(None)
+    }
+    let first_transition = block_transition(tx, *range.start())?;
+    let last_transition = block_transition(tx, *range.end())?;
+
+    // the changes of the slot from the end of the first block up to the first change after the
+    // range, with the value before each change
+    let mut changes = Vec::new();
+    let start = TransitionIdAddress((first_transition, Address::zero()));
+    for entry in tx.cursor_read::<tables::StorageChangeSet>()?.walk(Some(start))? {
+        let (key, entry) = entry?;
+        if key.address() != address || entry.key != storage_key {
+            continue
+        }
+        changes.push((key.transition_id(), entry.value));
+        if key.transition_id() >= last_transition {
+            break
+        }
+    }
+    let plain = tx
+        .cursor_dup_read::<tables::PlainStorageState>()?
+        .seek_by_key_subkey(address, storage_key)?
+        .filter(|entry| entry.key == storage_key)
+        .map(|entry| entry.value)
+        .unwrap_or_default();
+
+    let mut changes = changes.into_iter().peekable();
+    for block_number in range {
+        let transition = block_transition(tx, block_number)?;
+        // the value after the block is the value before the next change, or the current value
+        while changes.next_if(|(change, _)| *change < transition).is_some() {}
+        let expected = changes.peek().map_or(plain, |(_, value)| *value);
+
+        let historical =
+            match HistoricalStateProviderRef::new(tx, transition).storage(address, storage_key) {
+                Ok(value) => Some(value.unwrap_or_default()),
+                Err(Error::Provider(ProviderError::StorageChangeset { .. })) => None,
+                Err(err) => return Err(err),
+            };
+        if historical != Some(expected) {
+            return Ok(Some(StorageHistoryDivergence {
+                address,
+                storage_key,
+                block_number,
+                expected,
+                historical,
+            }))
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the transition id at the end of the block.
+fn block_transition<'a, TX: DbTx<'a>>(tx: &TX, block_number: BlockNumber) -> Result<TransitionId> {
+    Ok(tx
+        .get::<tables::BlockTransitionIndex>(block_number)?
+        .ok_or(ProviderError::BlockTransition { block_number })?)
+}
+
+/// The outcome of [recover_canonical_commit].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanonicalRecovery {
+    /// The stage checkpoints match the canonical tip, or the stages are at different blocks
+    /// because the pipeline is syncing, which resumes on its own.
+    Consistent,
+    /// The state of the blocks above the checkpoints was complete, the checkpoints were moved to
+    /// the canonical tip.
+    RolledForward {
+        /// The previous checkpoint of the stages
+        from: BlockNumber,
+        /// The canonical tip
+        to: BlockNumber,
+    },
+    /// The blocks above the checkpoints were removed, or the checkpoints were moved back to the
+    /// canonical tip.
+    RolledBack {
+        /// The previous canonical tip or checkpoint
+        from: BlockNumber,
+        /// The block the chain and the checkpoints are at now
+        to: BlockNumber,
+    },
+}
+
+/// Repairs a canonical commit that was only partially written, e.g. because the node crashed
+/// between writing the state of new canonical blocks and updating the stage checkpoints.
+///
+/// Canonical commits of the blockchain tree and the miner write the blocks and their state first
+/// and then move the checkpoints of all stages to the new tip. If all stages are at the same
+/// checkpoint but the canonical chain ends at another block, the commit is repaired by comparing
+/// the checkpoint with the canonical tip:
+///
+/// - the checkpoints are ahead of the canonical tip: they are moved back to the tip
+/// - the state trie contains the state root of the canonical tip: the checkpoints are moved
+///   forward to the tip. This also holds for a tip that ends in blocks without state changes.
+/// - otherwise the blocks above the checkpoint and the changesets written after it, if any, are
+///   removed
+///
+/// The repair is written to the transaction, the caller commits it.
+pub fn recover_canonical_commit<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    chain_spec: &ChainSpec,
+) -> std::result::Result<CanonicalRecovery, TransactionError> {
+    let Some(checkpoint) = common_stage_checkpoint(tx.deref())? else {
+        return Ok(CanonicalRecovery::Consistent)
+    };
+    let (tip, _) = tx.cursor_read::<tables::CanonicalHeaders>()?.last()?.unwrap_or_default();
+
+    if tip == checkpoint {
+        return Ok(CanonicalRecovery::Consistent)
+    }
+    if tip < checkpoint {
+        tx.update_pipeline_stages(tip)?;
+        return Ok(CanonicalRecovery::RolledBack { from: checkpoint, to: tip })
+    }
+
+    // the trie is written last, its root is only present once the state of the tip is complete
+    let tip_header =
+        tx.get::<tables::Headers>(tip)?.ok_or(ProviderError::Header { number: tip })?;
+    if tx.get::<tables::AccountsTrie>(tip_header.state_root)?.is_some() {
+        tx.update_pipeline_stages(tip)?;
+        return Ok(CanonicalRecovery::RolledForward { from: checkpoint, to: tip })
+    }
+    if has_changes_after(tx.deref(), checkpoint)? {
+        tx.take_block_and_execution_range(chain_spec, (checkpoint + 1)..)?;
+    } else {
+        // only the blocks were written, there is no state to revert
+        tx.take_block_range(chain_spec, (checkpoint + 1)..)?;
+        tx.get_or_take::<tables::BlockBodyIndices, true>((checkpoint + 1)..)?;
+        tx.get_or_take::<tables::BlockTransitionIndex, true>((checkpoint + 1)..)?;
+    }
+    Ok(CanonicalRecovery::RolledBack { from: tip, to: checkpoint })
+}
+
+fn check_canonical_headers<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    report: &mut ConsistencyReport,
+) -> Result<()> {
+    let lowest_available = get_lowest_available_block(tx)?;
+    for entry in tx.cursor_read::<tables::CanonicalHeaders>()?.walk(Some(lowest_available))? {
+        let (number, hash) = entry?;
+        if tx.get::<tables::Headers>(number)?.is_none() ||
+            tx.get::<tables::HeaderNumbers>(hash)? != Some(number)
+        {
+            report.violations.push(ConsistencyViolation::MissingCanonicalHeader { number, hash });
+        }
+    }
+    Ok(())
+}
+
+fn check_tx_numbers<'a, TX: DbTx<'a>>(tx: &TX, report: &mut ConsistencyReport) -> Result<()> {
+    let mut expected = None;
+    for entry in tx.cursor_read::<tables::BlockBodyIndices>()?.walk(None)? {
+        let (number, body) = entry?;
+        if let Some(expected) = expected.filter(|expected| *expected != body.first_tx_num) {
+            report.violations.push(ConsistencyViolation::TxNumberGap {
+                number,
+                expected,
+                got: body.first_tx_num,
+            });
+        }
+        expected = Some(body.next_tx_num());
+    }
+    Ok(())
+}
+
+fn check_receipts<'a, TX: DbTx<'a>>(tx: &TX, report: &mut ConsistencyReport) -> Result<()> {
+    for entry in tx.cursor_read::<tables::Receipts>()?.walk(None)? {
+        let (tx_number, _) = entry?;
+        if tx.get::<tables::Transactions>(tx_number)?.is_none() {
+            report.violations.push(ConsistencyViolation::ReceiptWithoutTransaction { tx_number });
+        }
+    }
+    Ok(())
+}
+
+fn check_stage_checkpoints<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    report: &mut ConsistencyReport,
+) -> Result<()> {
+    for pair in ORDERED_STAGES.windows(2) {
+        let (previous_stage, stage) = (pair[0], pair[1]);
+        let previous_checkpoint =
+            tx.get::<tables::SyncStage>(previous_stage.to_string())?.unwrap_or_default();
+        let checkpoint = tx.get::<tables::SyncStage>(stage.to_string())?.unwrap_or_default();
+        if checkpoint > previous_checkpoint {
+            report.violations.push(ConsistencyViolation::StageCheckpointAhead {
+                stage: stage.to_string(),
+                checkpoint,
+                previous_stage: previous_stage.to_string(),
+                previous_checkpoint,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn check_account_history<'a, TX: DbTx<'a>>(tx: &TX, report: &mut ConsistencyReport) -> Result<()> {
+    let mut previous: Option<ShardedKey<Address>> = None;
+    for entry in tx.cursor_read::<tables::AccountHistory>()?.walk(None)? {
+        let (key, list) = entry?;
+        let previous_highest = previous
+            .filter(|previous| previous.key == key.key)
+            .map(|previous| previous.highest_transition_id);
+        if !is_valid_shard(&list, key.highest_transition_id, previous_highest) {
+            report.violations.push(ConsistencyViolation::AccountHistoryShard {
+                address: key.key,
+                highest_transition_id: key.highest_transition_id,
+            });
+        }
+        previous = Some(key);
+    }
+    Ok(())
+}
+
+fn check_storage_history<'a, TX: DbTx<'a>>(tx: &TX, report: &mut ConsistencyReport) -> Result<()> {
+    let mut previous: Option<StorageShardedKey> = None;
+    for entry in tx.cursor_read::<tables::StorageHistory>()?.walk(None)? {
+        let (key, list) = entry?;
+        let previous_highest = previous
+            .filter(|previous| {
+                previous.address == key.address && previous.sharded_key.key == key.sharded_key.key
+            })
+            .map(|previous| previous.sharded_key.highest_transition_id);
+        if !is_valid_shard(&list, key.sharded_key.highest_transition_id, previous_highest) {
+            report.violations.push(ConsistencyViolation::StorageHistoryShard {
+                address: key.address,
+                storage_key: key.sharded_key.key,
+                highest_transition_id: key.sharded_key.highest_transition_id,
+            });
+        }
+        previous = Some(key);
+    }
+    Ok(())
+}
+
+/// Returns `true` if the shard is strictly sorted, does not exceed the highest transition id of
+/// its key and starts after the highest transition id of the previous shard of the same key.
+fn is_valid_shard(
+    list: &TransitionList,
+    highest_transition_id: TransitionId,
+    previous_highest: Option<TransitionId>,
+) -> bool {
+    let mut last = previous_highest;
+    for transition_id in list.iter(0).map(|i| i as TransitionId) {
+        if last.map_or(false, |last| transition_id <= last) || transition_id > highest_transition_id
+        {
+            return false
+        }
+        last = Some(transition_id);
+    }
+    true
+}
+
+/// Returns `true` if the transaction hash is not indexed or points to a transaction of a canonical
+/// block.
+pub fn is_canonical_tx_lookup<'a, TX: DbTx<'a>>(tx: &TX, hash: TxHash) -> Result<bool> {
+    let Some(tx_number) = tx.get::<tables::TxHashNumber>(hash)? else { return Ok(true) };
+    Ok(tx_number < canonical_tx_end(tx)?)
+}
+
+/// Returns the transaction number following the last transaction of the canonical chain.
+fn canonical_tx_end<'a, TX: DbTx<'a>>(tx: &TX) -> Result<TxNumber> {
+    let last = tx.cursor_read::<tables::BlockBodyIndices>()?.last()?;
+    Ok(last.map_or(0, |(_, body)| body.next_tx_num()))
+}
+
+fn check_tx_lookup<'a, TX: DbTx<'a>>(tx: &TX, report: &mut ConsistencyReport) -> Result<()> {
+    let canonical_end = canonical_tx_end(tx)?;
+    for entry in tx.cursor_read::<tables::TxHashNumber>()?.walk(None)? {
+        let (hash, tx_number) = entry?;
+        if tx_number >= canonical_end {
+            report.violations.push(ConsistencyViolation::NonCanonicalTxLookup { hash, tx_number });
+            continue
+        }
+        let transaction = tx.get::<tables::Transactions>(tx_number)?;
+        if transaction.map_or(true, |transaction| transaction.hash() != hash) {
+            report.violations.push(ConsistencyViolation::DanglingTxLookup { hash, tx_number });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the checkpoint all stages are at, `None` if there are no checkpoints or the stages are
+/// at different blocks.
+fn common_stage_checkpoint<'a, TX: DbTx<'a>>(
+    tx: &TX,
+) -> std::result::Result<Option<BlockNumber>, DbError> {
+    let mut common = None;
+    for entry in tx.cursor_read::<tables::SyncStage>()?.walk(None)? {
+        let (stage, checkpoint) = entry?;
+        if stage == LOWEST_AVAILABLE_BLOCK {
+            continue
+        }
+        match common {
+            Some(common) if common != checkpoint => return Ok(None),
+            _ => common = Some(checkpoint),
+        }
+    }
+    Ok(common)
+}
+
+/// Returns whether there are account or storage changesets of blocks above the given block.
+fn has_changes_after<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    block_number: BlockNumber,
+) -> std::result::Result<bool, TransactionError> {
+    // the transitions of the blocks above start at the block transition of the given block
+    let transition_id = tx
+        .get::<tables::BlockTransitionIndex>(block_number)?
+        .ok_or(ProviderError::BlockTransition { block_number })?;
+    if tx.cursor_read::<tables::AccountChangeSet>()?.seek(transition_id)?.is_some() {
+        return Ok(true)
+    }
+    Ok(tx
+        .cursor_read::<tables::StorageChangeSet>()?
+        .seek(TransitionIdAddress((transition_id, Address::zero())))?
+        .is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{insert_canonical_block, test_utils::blocks::BlockChainTestData, Transaction};
+    use reth_db::mdbx::{test_utils::create_test_db, Env, EnvKind, WriteMap};
+    use reth_primitives::{
+        proofs::EMPTY_ROOT, ChainSpecBuilder, Header, Receipt, SealedBlock, StorageEntry, H160,
+        MAINNET,
+    };
+    use std::{
+        ops::{Deref, DerefMut},
+        sync::Arc,
+    };
+
+    /// Returns a database with two executed and indexed blocks on top of the genesis block, and
+    /// the transaction numbers of their transactions.
+    fn seeded_db() -> (Arc<Env<WriteMap>>, Vec<TxNumber>) {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let data = BlockChainTestData::default();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        insert_canonical_block(tx.deref_mut(), data.genesis, None, false).unwrap();
+        let mut results = Vec::new();
+        for (block, result) in data.blocks {
+            insert_canonical_block(tx.deref_mut(), block.block, Some(block.senders), true).unwrap();
+            results.push(result);
+        }
+        tx.insert_execution_result(results, &MAINNET, 0).unwrap();
+        let transitions = tx.get_account_transition_ids_from_changeset(0, 4).unwrap();
+        tx.insert_account_history_index(transitions).unwrap();
+        let transitions = tx.get_storage_transition_ids_from_changeset(0, 4).unwrap();
+        tx.insert_storage_history_index(transitions).unwrap();
+
+        let tx_numbers = vec![0, 1];
+        for tx_number in &tx_numbers {
+            tx.put::<tables::Receipts>(*tx_number, Receipt::default()).unwrap();
+        }
+        for stage in ORDERED_STAGES {
+            tx.put::<tables::SyncStage>(stage.to_string(), 2).unwrap();
+        }
+        tx.commit().unwrap();
+        (db, tx_numbers)
+    }
+
+    fn check(db: &Env<WriteMap>) -> ConsistencyReport {
+        let tx = Transaction::new(db).unwrap();
+        check_consistency(tx.deref()).unwrap()
+    }
+
+    #[test]
+    fn seeded_db_is_consistent() {
+        let (db, _) = seeded_db();
+        assert_eq!(check(&db), ConsistencyReport::default());
+    }
+
+    #[test]
+    fn missing_canonical_header() {
+        let (db, _) = seeded_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        let hash = tx.get::<tables::CanonicalHeaders>(1).unwrap().unwrap();
+        tx.delete::<tables::Headers>(1, None).unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(
+            check(&db).violations,
+            vec![ConsistencyViolation::MissingCanonicalHeader { number: 1, hash }]
+        );
+    }
+
+    #[test]
+    fn tx_number_gap() {
+        let (db, _) = seeded_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        let mut body = tx.get::<tables::BlockBodyIndices>(2).unwrap().unwrap();
+        body.first_tx_num += 1;
+        tx.put::<tables::BlockBodyIndices>(2, body).unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(
+            check(&db).violations,
+            vec![ConsistencyViolation::TxNumberGap { number: 2, expected: 1, got: 2 }]
+        );
+    }
+
+    #[test]
+    fn missing_transaction_and_fix() {
+        let (db, tx_numbers) = seeded_db();
+        let tx_number = *tx_numbers.last().unwrap();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        let hash = tx.get::<tables::Transactions>(tx_number).unwrap().unwrap().hash();
+        tx.delete::<tables::Transactions>(tx_number, None).unwrap();
+        tx.commit().unwrap();
+
+        let report = check(&db);
+        assert_eq!(
+            report.violations,
+            vec![
+                ConsistencyViolation::ReceiptWithoutTransaction { tx_number },
+                ConsistencyViolation::DanglingTxLookup { hash, tx_number },
+            ]
+        );
+        assert_eq!(report.fixable().count(), 1);
+
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        assert_eq!(fix_consistency(tx.deref(), &report).unwrap(), 1);
+        tx.commit().unwrap();
+
+        // only the receipt without transaction is left
+        assert_eq!(
+            check(&db).violations,
+            vec![ConsistencyViolation::ReceiptWithoutTransaction { tx_number }]
+        );
+    }
+
+    #[test]
+    fn non_canonical_tx_lookup_and_fix() {
+        let (db, tx_numbers) = seeded_db();
+        let canonical_hash = {
+            let tx = Transaction::new(db.as_ref()).unwrap();
+            tx.get::<tables::Transactions>(tx_numbers[0]).unwrap().unwrap().hash()
+        };
+        // a hash left behind by a reverted block whose transaction number is not reused
+        let hash = H256::from_low_u64_be(0xdead);
+        let tx_number = *tx_numbers.last().unwrap() + 1;
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        tx.put::<tables::TxHashNumber>(hash, tx_number).unwrap();
+        tx.commit().unwrap();
+
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        assert!(!is_canonical_tx_lookup(tx.deref(), hash).unwrap());
+        assert!(is_canonical_tx_lookup(tx.deref(), canonical_hash).unwrap());
+        drop(tx);
+
+        let report = check(&db);
+        assert_eq!(
+            report.violations,
+            vec![ConsistencyViolation::NonCanonicalTxLookup { hash, tx_number }]
+        );
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        assert_eq!(fix_consistency(tx.deref(), &report).unwrap(), 1);
+        tx.commit().unwrap();
+        assert!(check(&db).is_consistent());
+    }
+
+    #[test]
+    fn stage_checkpoint_ahead() {
+        let (db, _) = seeded_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        tx.put::<tables::SyncStage>("Execution".to_string(), 3).unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(
+            check(&db).violations,
+            vec![ConsistencyViolation::StageCheckpointAhead {
+                stage: "Execution".to_string(),
+                checkpoint: 3,
+                previous_stage: "Bodies".to_string(),
+                previous_checkpoint: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn overlapping_history_shard() {
+        let (db, _) = seeded_db();
+        let address = H160([0x60; 20]);
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        // the new shard overlaps with the transitions of the last shard of the account
+        tx.put::<tables::AccountHistory>(
+            ShardedKey::new(address, 2),
+            TransitionList::new([0, 2]).unwrap(),
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(
+            check(&db).violations,
+            vec![ConsistencyViolation::AccountHistoryShard {
+                address,
+                highest_transition_id: u64::MAX
+            }]
+        );
+    }
+
+    #[test]
+    fn storage_history_divergence() {
+        let (db, _) = seeded_db();
+        // the slot is set to 10 by the first block and to 15 by the second block
+        let address = H160([0x60; 20]);
+        let storage_key = H256::from_low_u64_be(5);
+        let verify = |range| {
+            let tx = Transaction::new(db.as_ref()).unwrap();
+            verify_storage_history(tx.deref(), address, storage_key, range).unwrap()
+        };
+        assert_eq!(verify(0..=2), None);
+
+        // the changeset of the second block is lost, the index still points to it
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        let entry = StorageEntry { key: storage_key, value: U256::from(10) };
+        assert!(tx.delete::<tables::StorageChangeSet>((2, address).into(), Some(entry)).unwrap());
+        tx.commit().unwrap();
+
+        assert_eq!(
+            verify(0..=2),
+            Some(StorageHistoryDivergence {
+                address,
+                storage_key,
+                block_number: 1,
+                expected: U256::from(15),
+                historical: None,
+            })
+        );
+        // the state after the second block is the plain state
+        assert_eq!(verify(2..=2), None);
+    }
+
+    /// Returns a database with the first test block committed on top of the genesis block like
+    /// the blockchain tree does, with the stages at block 1.
+    fn canonical_db() -> (Arc<Env<WriteMap>>, ChainSpec, BlockChainTestData) {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let chain_spec = ChainSpecBuilder::default()
+            .chain(MAINNET.chain)
+            .genesis(MAINNET.genesis.clone())
+            .shanghai_activated()
+            .build();
+        let data = BlockChainTestData::default();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        insert_canonical_block(tx.deref_mut(), data.genesis.clone(), None, false).unwrap();
+        tx.put::<tables::AccountsTrie>(EMPTY_ROOT, vec![0x80]).unwrap();
+        let (block, result) = data.blocks[0].clone();
+        tx.insert_block(block, &chain_spec, result).unwrap();
+        for stage in ["Execution", "Finish"] {
+            tx.put::<tables::SyncStage>(stage.to_string(), 1).unwrap();
+        }
+        tx.commit().unwrap();
+        (db, chain_spec, data)
+    }
+
+    fn recover(db: &Env<WriteMap>, chain_spec: &ChainSpec) -> CanonicalRecovery {
+        let mut tx = Transaction::new(db).unwrap();
+        let recovery = recover_canonical_commit(&tx, chain_spec).unwrap();
+        tx.commit().unwrap();
+        recovery
+    }
+
+    fn checkpoints(db: &Env<WriteMap>) -> Vec<BlockNumber> {
+        let tx = Transaction::new(db).unwrap();
+        ["Execution", "Finish"]
+            .map(|stage| tx.get::<tables::SyncStage>(stage.to_string()).unwrap().unwrap())
+            .to_vec()
+    }
+
+    #[test]
+    fn recovery_rolls_forward_complete_state() {
+        let (db, chain_spec, data) = canonical_db();
+        assert_eq!(recover(&db, &chain_spec), CanonicalRecovery::Consistent);
+
+        // the second block is committed without the checkpoints
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        let (block, result) = data.blocks[1].clone();
+        tx.insert_block(block, &chain_spec, result).unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(recover(&db, &chain_spec), CanonicalRecovery::RolledForward { from: 1, to: 2 });
+        assert_eq!(checkpoints(&db), vec![2, 2]);
+        assert_eq!(recover(&db, &chain_spec), CanonicalRecovery::Consistent);
+    }
+
+    #[test]
+    fn recovery_rolls_forward_empty_blocks() {
+        let (db, chain_spec, data) = canonical_db();
+
+        // an empty block without state changes is committed without the checkpoints
+        let parent = &data.blocks[0].0.block;
+        let header = Header {
+            number: 2,
+            parent_hash: parent.hash(),
+            state_root: parent.state_root,
+            ..Default::default()
+        };
+        let block = SealedBlock { header: header.seal_slow(), ..Default::default() };
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        insert_canonical_block(tx.deref_mut(), block, Some(Vec::new()), false).unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(recover(&db, &chain_spec), CanonicalRecovery::RolledForward { from: 1, to: 2 });
+        assert_eq!(checkpoints(&db), vec![2, 2]);
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        assert!(tx.get::<tables::CanonicalHeaders>(2).unwrap().is_some());
+    }
+
+    #[test]
+    fn recovery_rolls_back_blocks_without_state() {
+        let (db, chain_spec, data) = canonical_db();
+
+        // the second block is written without its state and the checkpoints
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        let (block, result) = data.blocks[1].clone();
+        insert_canonical_block(
+            tx.deref_mut(),
+            block.block.clone(),
+            Some(block.senders.clone()),
+            true,
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(recover(&db, &chain_spec), CanonicalRecovery::RolledBack { from: 2, to: 1 });
+        assert_eq!(checkpoints(&db), vec![1, 1]);
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        assert_eq!(tx.get::<tables::CanonicalHeaders>(2).unwrap(), None);
+        assert_eq!(tx.get::<tables::BlockBodyIndices>(2).unwrap(), None);
+
+        // the block can be committed again
+        tx.insert_block(block, &chain_spec, result).unwrap();
+        tx.update_pipeline_stages(2).unwrap();
+        tx.commit().unwrap();
+        assert_eq!(recover(&db, &chain_spec), CanonicalRecovery::Consistent);
+    }
+
+    #[test]
+    fn recovery_rolls_back_checkpoints_ahead_of_tip() {
+        let (db, chain_spec, _) = canonical_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        for stage in ["Execution", "Finish"] {
+            tx.put::<tables::SyncStage>(stage.to_string(), 3).unwrap();
+        }
+        tx.commit().unwrap();
+
+        assert_eq!(recover(&db, &chain_spec), CanonicalRecovery::RolledBack { from: 3, to: 1 });
+        assert_eq!(checkpoints(&db), vec![1, 1]);
+    }
+}
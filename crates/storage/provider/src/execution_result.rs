@@ -14,6 +14,10 @@ pub struct ExecutionResult {
     /// Post block account changesets. This might include block reward, uncle rewards, withdrawals
     /// or irregular state changes (DAO fork).
     pub block_changesets: BTreeMap<Address, AccountInfoChangeSet>,
+    /// Storage changes of the block that are not made by its transactions, e.g. the parent hash
+    /// written to the history contract of EIP-2935. Key -> (OldValue, NewValue), stored in the
+    /// same transition as the post block account changesets.
+    pub block_storage_changesets: BTreeMap<Address, BTreeMap<U256, (U256, U256)>>,
 }
 
 /// After transaction is executed this structure contain
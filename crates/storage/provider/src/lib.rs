@@ -11,14 +11,16 @@
 /// Various provider traits.
 mod traits;
 pub use traits::{
-    AccountProvider, BlockExecutor, BlockHashProvider, BlockIdProvider, BlockProvider,
-    EvmEnvProvider, ExecutorFactory, HeaderProvider, ReceiptProvider, StateProvider,
-    StateProviderFactory, TransactionsProvider, WithdrawalsProvider,
+    AccountHistoryProvider, AccountProvider, BlockExecutor, BlockHashProvider, BlockIdProvider,
+    BlockProvider, ChainSpecProvider, EvmEnvProvider, ExecutorFactory, HeaderProvider,
+    ReceiptProvider, StateProvider, StateProviderFactory, StorageHistoryVerifier,
+    TransactionsProvider, WithdrawalsProvider,
 };
 
 /// Provider trait implementations.
 pub mod providers;
 pub use providers::{
+    check_chain_spec, BytecodeCache, CanonicalTip, ChainSpecDatabaseExt, ChainSpecMismatch,
     HistoricalStateProvider, HistoricalStateProviderRef, LatestStateProvider,
     LatestStateProviderRef, ShareableDatabase,
 };
@@ -29,13 +31,27 @@ pub mod trie;
 /// Execution result
 pub mod execution_result;
 
+/// Cross-table consistency checks of the database
+pub mod consistency;
+pub use consistency::{
+    check_consistency, fix_consistency, is_canonical_tx_lookup, recover_canonical_commit,
+    verify_storage_history, CanonicalRecovery, ConsistencyReport, ConsistencyViolation,
+    StorageHistoryDivergence,
+};
+
 /// Helper types for interacting with the database
 mod transaction;
 pub use transaction::{Transaction, TransactionError};
 
 /// Common database utilities.
 mod utils;
-pub use utils::{insert_block, insert_canonical_block};
+pub use utils::{
+    canonical_hashes_range, ensure_block_available, ensure_state_available, get_hardforks,
+    get_lowest_available_block, get_receipts_prune_checkpoint, insert_block,
+    insert_canonical_block, load_forkchoice_state, set_forkchoice_state, set_hardforks,
+    set_lowest_available_block, set_receipts_prune_checkpoint, FORKCHOICE_STATE, HARDFORKS,
+    LOWEST_AVAILABLE_BLOCK, RECEIPTS_PRUNE_CHECKPOINT,
+};
 
 #[cfg(any(test, feature = "test-utils"))]
 /// Common test helpers for mocking the Provider.
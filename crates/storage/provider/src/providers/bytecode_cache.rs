@@ -0,0 +1,167 @@
+use reth_interfaces::Result;
+use reth_primitives::{Bytecode, H256};
+use schnellru::{ByLength, LruMap};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Default size in bytes of the contract codes kept by the [BytecodeCache].
+pub const DEFAULT_BYTECODE_CACHE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Bounded LRU cache of contract codes, keyed by their code hashes and sized by the length of the
+/// codes.
+///
+/// Codes are stored once per code hash and never change, so cached codes don't need to be
+/// invalidated. A self-destructed account doesn't remove its code either, other accounts may still
+/// reference it. Clones share the same cache, so one cache serves the executor and the rpc
+/// handlers.
+#[derive(Clone)]
+pub struct BytecodeCache {
+    inner: Arc<BytecodeCacheInner>,
+}
+
+struct BytecodeCacheInner {
+    codes: Mutex<CachedCodes>,
+    /// Max size in bytes of the cached codes.
+    max_size: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct CachedCodes {
+    codes: LruMap<H256, Bytecode, ByLength>,
+    /// Size in bytes of the cached codes.
+    size: usize,
+}
+
+impl BytecodeCache {
+    /// Creates a cache that keeps codes of up to `max_size` bytes in total.
+    pub fn new(max_size: usize) -> Self {
+        let inner = BytecodeCacheInner {
+            codes: Mutex::new(CachedCodes { codes: LruMap::new(ByLength::new(u32::MAX)), size: 0 }),
+            max_size,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        };
+        Self { inner: Arc::new(inner) }
+    }
+
+    /// Returns the code with the given hash from the cache, or reads it with `read` and caches it.
+    ///
+    /// Missing codes are not cached, the code may be deployed later.
+    pub fn get_or_read(
+        &self,
+        code_hash: H256,
+        read: impl FnOnce() -> Result<Option<Bytecode>>,
+    ) -> Result<Option<Bytecode>> {
+        if let Some(code) = self.inner.codes.lock().expect("not poisoned").codes.get(&code_hash) {
+            self.inner.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Some(code.clone()))
+        }
+
+        self.inner.misses.fetch_add(1, Ordering::Relaxed);
+        let code = read()?;
+        if let Some(ref code) = code {
+            self.insert(code_hash, code.clone());
+        }
+        Ok(code)
+    }
+
+    fn insert(&self, code_hash: H256, code: Bytecode) {
+        let code_size = code.bytes().len();
+        if code_size > self.inner.max_size {
+            return
+        }
+        let mut cached = self.inner.codes.lock().expect("not poisoned");
+        if cached.codes.peek(&code_hash).is_some() {
+            return
+        }
+        while cached.size + code_size > self.inner.max_size {
+            match cached.codes.pop_oldest() {
+                Some((_, evicted)) => cached.size -= evicted.bytes().len(),
+                None => break,
+            }
+        }
+        cached.codes.insert(code_hash, code);
+        cached.size += code_size;
+    }
+
+    /// Returns the number of cached codes.
+    pub fn len(&self) -> usize {
+        self.inner.codes.lock().expect("not poisoned").codes.len()
+    }
+
+    /// Returns `true` if no codes are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the size in bytes of the cached codes.
+    pub fn size(&self) -> usize {
+        self.inner.codes.lock().expect("not poisoned").size
+    }
+
+    /// Returns the number of codes that were read because they were not cached.
+    pub fn misses(&self) -> u64 {
+        self.inner.misses.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of codes that were answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.inner.hits.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for BytecodeCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_BYTECODE_CACHE_SIZE)
+    }
+}
+
+impl fmt::Debug for BytecodeCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BytecodeCache")
+            .field("len", &self.len())
+            .field("size", &self.size())
+            .field("max_size", &self.inner.max_size)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::keccak256;
+
+    fn bytecode(len: usize, byte: u8) -> (H256, Bytecode) {
+        let code = vec![byte; len];
+        (keccak256(&code), Bytecode::new_raw(code.into()))
+    }
+
+    #[test]
+    fn evicts_least_recently_used_by_size() {
+        let cache = BytecodeCache::new(100);
+        let codes = [bytecode(40, 1), bytecode(40, 2), bytecode(40, 3)];
+        for (hash, code) in &codes[..2] {
+            cache.get_or_read(*hash, || Ok(Some(code.clone()))).unwrap();
+        }
+        assert_eq!(cache.size(), 80);
+
+        // the first code is used again, so the second one is evicted for the third one
+        cache.get_or_read(codes[0].0, || unreachable!("cached")).unwrap();
+        let (hash, code) = &codes[2];
+        cache.get_or_read(*hash, || Ok(Some(code.clone()))).unwrap();
+        assert_eq!((cache.len(), cache.size()), (2, 80));
+        assert_eq!(cache.get_or_read(codes[1].0, || Ok(None)).unwrap(), None);
+        assert_eq!((cache.hits(), cache.misses()), (1, 4));
+
+        // codes larger than the cache are not cached
+        let (hash, code) = bytecode(101, 4);
+        assert_eq!(cache.get_or_read(hash, || Ok(Some(code.clone()))).unwrap(), Some(code));
+        assert_eq!(cache.len(), 2);
+    }
+}
@@ -0,0 +1,55 @@
+use reth_interfaces::p2p::headers::client::StatusUpdater;
+use reth_primitives::{BlockHash, BlockNumber, Head};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// The number and hash of the tip of the canonical chain, kept in memory so that readers like the
+/// rpc handlers don't need to open a database transaction to look it up.
+///
+/// The tip is updated by whatever commits canonical blocks: the blockchain tree, the pipeline
+/// through the [StatusUpdater] of its finish stage, or the miner of a development node. It is
+/// unset until the first update, readers fall back to the database until then. Clones share the
+/// same tip.
+#[derive(Debug, Clone)]
+pub struct CanonicalTip {
+    tip: Arc<watch::Sender<Option<(BlockNumber, BlockHash)>>>,
+}
+
+impl CanonicalTip {
+    /// Creates a new instance without a tip.
+    pub fn new() -> Self {
+        Self { tip: Arc::new(watch::channel(None).0) }
+    }
+
+    /// Sets the tip of the canonical chain.
+    pub fn update(&self, number: BlockNumber, hash: BlockHash) {
+        self.tip.send_replace(Some((number, hash)));
+    }
+
+    /// Returns the number and hash of the tip, if it was set.
+    pub fn get(&self) -> Option<(BlockNumber, BlockHash)> {
+        *self.tip.borrow()
+    }
+
+    /// Returns the number of the tip, if it was set.
+    pub fn number(&self) -> Option<BlockNumber> {
+        self.get().map(|(number, _)| number)
+    }
+
+    /// Returns a receiver that is notified whenever the tip changes.
+    pub fn subscribe(&self) -> watch::Receiver<Option<(BlockNumber, BlockHash)>> {
+        self.tip.subscribe()
+    }
+}
+
+impl Default for CanonicalTip {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatusUpdater for CanonicalTip {
+    fn update_status(&self, head: Head) {
+        self.update(head.number, head.hash);
+    }
+}
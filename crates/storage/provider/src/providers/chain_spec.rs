@@ -0,0 +1,105 @@
+//! Checks of a [ChainSpec] against the chain stored in a database.
+
+use super::ShareableDatabase;
+use crate::utils::get_hardforks;
+use reth_db::{cursor::DbCursorRO, database::Database, tables, transaction::DbTx};
+use reth_interfaces::{provider::ProviderError, Error, Result};
+use reth_primitives::{ChainSpec, ForkCondition, Hardfork, Head, H256};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A mismatch between a [ChainSpec] and the chain stored in a database.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ChainSpecMismatch {
+    /// The database was initialized with the genesis of another chain.
+    #[error("Genesis hash mismatch: the chain spec has genesis {chain_spec:?}, but the database was initialized with genesis {database:?}. The database likely belongs to a different chain, select the chain it was created with or use another data directory")]
+    Genesis {
+        /// The genesis hash of the chain spec
+        chain_spec: H256,
+        /// The genesis hash stored in the database
+        database: H256,
+    },
+    /// The database contains blocks that were synced with a different activation of a hardfork.
+    #[error("Hardfork {fork:?} mismatch: the chain spec activates it at {chain_spec:?}, but the database was synced with it activated at {database:?}. The chain spec likely overrides the activation of the hardfork, remove the override or use another data directory")]
+    Hardfork {
+        /// The hardfork
+        fork: Hardfork,
+        /// The activation in the chain spec
+        chain_spec: ForkCondition,
+        /// The activation the database was synced with
+        database: ForkCondition,
+    },
+    /// Failed to read the database.
+    #[error(transparent)]
+    Database(#[from] Error),
+}
+
+/// Extension of [ChainSpec] to check it against a database.
+pub trait ChainSpecDatabaseExt {
+    /// Returns an error if the database was initialized with another genesis, or if its blocks
+    /// were synced with different activations of hardforks than the chain spec's.
+    ///
+    /// Hardforks that are not active at the head of the database may be rescheduled. An empty
+    /// database matches any chain spec.
+    fn matches_database<DB: Database>(
+        &self,
+        provider: &ShareableDatabase<DB>,
+    ) -> std::result::Result<(), ChainSpecMismatch>;
+}
+
+impl ChainSpecDatabaseExt for ChainSpec {
+    fn matches_database<DB: Database>(
+        &self,
+        provider: &ShareableDatabase<DB>,
+    ) -> std::result::Result<(), ChainSpecMismatch> {
+        provider.db.view(|tx| check_chain_spec(tx, self)).map_err(Error::from)?
+    }
+}
+
+/// Checks the chain spec against the database, see [ChainSpecDatabaseExt::matches_database].
+pub fn check_chain_spec<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    chain_spec: &ChainSpec,
+) -> std::result::Result<(), ChainSpecMismatch> {
+    let Some(genesis) = tx.get::<tables::CanonicalHeaders>(0).map_err(Error::from)? else {
+        return Ok(())
+    };
+    if genesis != chain_spec.genesis_hash() {
+        return Err(ChainSpecMismatch::Genesis {
+            chain_spec: chain_spec.genesis_hash(),
+            database: genesis,
+        })
+    }
+
+    // only the genesis of databases that predate the record can be checked
+    let Some(recorded) = get_hardforks(tx)? else { return Ok(()) };
+    let recorded = recorded.into_iter().collect::<BTreeMap<_, _>>();
+    let head = database_head(tx)?;
+    let forks = recorded.keys().chain(chain_spec.hardforks().keys()).collect::<BTreeSet<_>>();
+    for fork in forks {
+        let database = recorded.get(fork).copied().unwrap_or(ForkCondition::Never);
+        let current = chain_spec.fork(*fork);
+        // only activations that already affected the blocks of the database must match
+        let active = current.active_at_head(&head) || database.active_at_head(&head);
+        if current != database && active {
+            return Err(ChainSpecMismatch::Hardfork { fork: *fork, chain_spec: current, database })
+        }
+    }
+    Ok(())
+}
+
+/// Returns the highest canonical block of the database.
+fn database_head<'a, TX: DbTx<'a>>(tx: &TX) -> Result<Head> {
+    let (number, hash) = tx
+        .cursor_read::<tables::CanonicalHeaders>()?
+        .last()?
+        .ok_or(ProviderError::CanonicalHeader { block_number: 0 })?;
+    let header = tx.get::<tables::Headers>(number)?.ok_or(ProviderError::Header { number })?;
+    let total_difficulty = tx.get::<tables::HeaderTD>(number)?.unwrap_or_default();
+    Ok(Head {
+        number,
+        hash,
+        difficulty: header.difficulty,
+        total_difficulty: total_difficulty.into(),
+        timestamp: header.timestamp,
+    })
+}
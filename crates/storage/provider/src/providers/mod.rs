@@ -1,31 +1,49 @@
 use crate::{
-    BlockHashProvider, BlockIdProvider, BlockProvider, EvmEnvProvider, HeaderProvider,
-    ProviderError, StateProviderFactory, TransactionsProvider, WithdrawalsProvider,
+    canonical_hashes_range, ensure_block_available, ensure_state_available,
+    get_lowest_available_block, load_forkchoice_state, set_forkchoice_state,
+    trie::ParallelStateRoot, verify_storage_history, AccountHistoryProvider, BlockHashProvider,
+    BlockIdProvider, BlockProvider, ChainSpecProvider, EvmEnvProvider, HeaderProvider,
+    ProviderError, StateProviderFactory, StorageHistoryDivergence, StorageHistoryVerifier,
+    TransactionsProvider, WithdrawalsProvider,
 };
 use reth_db::{
-    cursor::DbCursorRO,
+    cursor::{DbCursorRO, DbDupCursorRO},
     database::{Database, DatabaseGAT},
+    models::ShardedKey,
     tables,
     transaction::DbTx,
 };
-use reth_interfaces::Result;
+use reth_interfaces::{consensus::ForkchoiceState, Result};
 use reth_primitives::{
-    Block, BlockHash, BlockId, BlockNumber, ChainInfo, ChainSpec, Hardfork, Head, Header, Receipt,
-    TransactionSigned, TxHash, TxNumber, Withdrawal, H256, U256,
+    Account, Address, Block, BlockHash, BlockId, BlockNumber, ChainInfo, ChainSpec, Hardfork, Head,
+    Header, Receipt, SealedHeader, TransactionMeta, TransactionSigned, TransitionId, TxHash,
+    TxNumber, Withdrawal, H256, U256,
 };
 use reth_revm_primitives::{
     config::revm_spec,
     env::{fill_block_env, fill_cfg_and_block_env, fill_cfg_env},
 };
+use reth_tracing::tracing::warn;
 use revm_primitives::{BlockEnv, CfgEnv, SpecId};
-use std::{ops::RangeBounds, sync::Arc};
+use std::{
+    ops::{RangeBounds, RangeInclusive},
+    sync::Arc,
+};
+use tokio::sync::watch;
 
+mod bytecode_cache;
+mod canonical_tip;
+mod chain_spec;
 mod state;
 use crate::traits::ReceiptProvider;
+pub use bytecode_cache::{BytecodeCache, DEFAULT_BYTECODE_CACHE_SIZE};
+pub use canonical_tip::CanonicalTip;
+pub use chain_spec::{check_chain_spec, ChainSpecDatabaseExt, ChainSpecMismatch};
 pub use state::{
     chain::ChainState,
     historical::{HistoricalStateProvider, HistoricalStateProviderRef},
     latest::{LatestStateProvider, LatestStateProviderRef},
+    recording::{RecordedState, RecordingStateProvider},
 };
 
 /// A common provider that fetches data from a database.
@@ -36,18 +54,114 @@ pub struct ShareableDatabase<DB> {
     db: DB,
     /// Chain spec
     chain_spec: Arc<ChainSpec>,
+    /// The fork choice state the safe and finalized blocks are resolved from.
+    forkchoice_state: Option<watch::Receiver<ForkchoiceState>>,
+    /// The cache of the contract codes read by the state providers.
+    bytecode_cache: Option<BytecodeCache>,
+    /// The number of changed keys from which the latest state provider computes state roots in
+    /// parallel, if enabled.
+    parallel_state_root: Option<usize>,
 }
 
 impl<DB> ShareableDatabase<DB> {
     /// create new database provider
     pub fn new(db: DB, chain_spec: Arc<ChainSpec>) -> Self {
-        Self { db, chain_spec }
+        Self {
+            db,
+            chain_spec,
+            forkchoice_state: None,
+            bytecode_cache: None,
+            parallel_state_root: None,
+        }
+    }
+
+    /// Resolves the safe and finalized blocks of the [ChainInfo] from the given fork choice state,
+    /// whether it's updated by the engine API or derived locally.
+    ///
+    /// Without a fork choice state, there are no safe and finalized blocks.
+    pub fn with_forkchoice_state(
+        mut self,
+        forkchoice_state: watch::Receiver<ForkchoiceState>,
+    ) -> Self {
+        self.forkchoice_state = Some(forkchoice_state);
+        self
+    }
+
+    /// Reads the contract codes of the state providers through the given cache.
+    ///
+    /// Pass clones of the same cache to share it, e.g. between the executor and the rpc handlers.
+    pub fn with_bytecode_cache(mut self, bytecode_cache: BytecodeCache) -> Self {
+        self.bytecode_cache = Some(bytecode_cache);
+        self
+    }
+
+    /// Computes the state roots with changes of the latest state provider with a
+    /// [ParallelStateRoot], in parallel from the given number of changed keys.
+    pub fn with_parallel_state_root(mut self, parallel_threshold: usize) -> Self {
+        self.parallel_state_root = Some(parallel_threshold);
+        self
+    }
+
+    /// Returns the database of the provider.
+    pub fn db(&self) -> &DB {
+        &self.db
+    }
+}
+
+impl<DB: Database> ShareableDatabase<DB> {
+    /// Returns [ProviderError::BlockUnavailable] if the block is below the lowest available block
+    /// of the database.
+    fn ensure_available(&self, number: BlockNumber) -> Result<()> {
+        let lowest_available = self.db.view(|tx| get_lowest_available_block(tx))??;
+        ensure_block_available(number, lowest_available)
+    }
+
+    /// Returns the number of the block with the hash if it's fully committed, i.e. not above the
+    /// best block.
+    fn committed_block_number(
+        &self,
+        hash: H256,
+        best_number: BlockNumber,
+    ) -> Result<Option<BlockNumber>> {
+        if hash.is_zero() {
+            return Ok(None)
+        }
+        Ok(self.block_number(hash)?.filter(|number| *number <= best_number))
+    }
+
+    /// Returns the fork choice state of the last run if its blocks are still canonical, see
+    /// [load_forkchoice_state].
+    pub fn load_forkchoice_state(&self) -> Result<Option<ForkchoiceState>> {
+        self.db.update(|tx| load_forkchoice_state(tx))?
+    }
+
+    /// Persists every fork choice state sent to the channel, so that the safe and finalized blocks
+    /// can be restored on restart with [Self::load_forkchoice_state].
+    ///
+    /// Resolves once all senders are dropped.
+    pub async fn persist_forkchoice_state(
+        self,
+        mut forkchoice_state: watch::Receiver<ForkchoiceState>,
+    ) {
+        while forkchoice_state.changed().await.is_ok() {
+            let state = forkchoice_state.borrow().clone();
+            let res = self.db.update(|tx| set_forkchoice_state(tx, &state));
+            if let Err(err) = res.map_err(reth_interfaces::Error::from).and_then(|res| res) {
+                warn!(target: "provider", ?err, ?state, "Failed to persist the fork choice state");
+            }
+        }
     }
 }
 
 impl<DB: Clone> Clone for ShareableDatabase<DB> {
     fn clone(&self) -> Self {
-        Self { db: self.db.clone(), chain_spec: Arc::clone(&self.chain_spec) }
+        Self {
+            db: self.db.clone(),
+            chain_spec: Arc::clone(&self.chain_spec),
+            forkchoice_state: self.forkchoice_state.clone(),
+            bytecode_cache: self.bytecode_cache.clone(),
+            parallel_state_root: self.parallel_state_root,
+        }
     }
 }
 
@@ -55,6 +169,7 @@ impl<DB: Database> HeaderProvider for ShareableDatabase<DB> {
     fn header(&self, block_hash: &BlockHash) -> Result<Option<Header>> {
         self.db.view(|tx| {
             if let Some(num) = tx.get::<tables::HeaderNumbers>(*block_hash)? {
+                ensure_block_available(num, get_lowest_available_block(tx)?)?;
                 Ok(tx.get::<tables::Headers>(num)?)
             } else {
                 Ok(None)
@@ -63,6 +178,7 @@ impl<DB: Database> HeaderProvider for ShareableDatabase<DB> {
     }
 
     fn header_by_number(&self, num: BlockNumber) -> Result<Option<Header>> {
+        self.ensure_available(num)?;
         Ok(self.db.view(|tx| tx.get::<tables::Headers>(num))??)
     }
 
@@ -91,14 +207,50 @@ impl<DB: Database> HeaderProvider for ShareableDatabase<DB> {
             })?
             .map_err(Into::into)
     }
+
+    fn sealed_header(&self, number: BlockNumber) -> Result<Option<SealedHeader>> {
+        self.ensure_available(number)?;
+        self.db.view(|tx| {
+            if let Some(hash) = tx.get::<tables::CanonicalHeaders>(number)? {
+                Ok(tx
+                    .get::<tables::Headers>(number)?
+                    .map(|header| SealedHeader::new_unchecked(header, hash)))
+            } else {
+                Ok(None)
+            }
+        })?
+    }
+
+    fn sealed_headers_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<SealedHeader>> {
+        self.db.view(|tx| {
+            let mut header_cursor = tx.cursor_read::<tables::Headers>()?;
+            let mut canonical_cursor = tx.cursor_read::<tables::CanonicalHeaders>()?;
+            canonical_cursor
+                .walk_range(range)?
+                .map(|entry| {
+                    let (number, hash) = entry?;
+                    let (_, header) = header_cursor
+                        .seek_exact(number)?
+                        .ok_or(ProviderError::Header { number })?;
+                    Ok(SealedHeader::new_unchecked(header, hash))
+                })
+                .collect::<Result<Vec<_>>>()
+        })?
+    }
 }
 
 impl<DB: Database> BlockHashProvider for ShareableDatabase<DB> {
     fn block_hash(&self, number: U256) -> Result<Option<H256>> {
-        // TODO: This unwrap is potentially unsafe
-        self.db
-            .view(|tx| tx.get::<tables::CanonicalHeaders>(number.try_into().unwrap()))?
-            .map_err(Into::into)
+        let number =
+            u64::try_from(number).map_err(|_| ProviderError::BlockNumberOverflow(number))?;
+        self.db.view(|tx| tx.get::<tables::CanonicalHeaders>(number))?.map_err(Into::into)
+    }
+
+    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>> {
+        self.db.view(|tx| canonical_hashes_range(tx, start, end))?
     }
 }
 
@@ -110,7 +262,17 @@ impl<DB: Database> BlockIdProvider for ShareableDatabase<DB> {
             .map_err(Into::<reth_interfaces::db::Error>::into)?
             .unwrap_or_default();
         let best_hash = self.block_hash(U256::from(best_number))?.unwrap_or_default();
-        Ok(ChainInfo { best_hash, best_number, last_finalized: None, safe_finalized: None })
+        let (last_finalized, safe_finalized) = match self.forkchoice_state {
+            Some(ref forkchoice_state) => {
+                let state = forkchoice_state.borrow().clone();
+                (
+                    self.committed_block_number(state.finalized_block_hash, best_number)?,
+                    self.committed_block_number(state.safe_block_hash, best_number)?,
+                )
+            }
+            None => (None, None),
+        };
+        Ok(ChainInfo { best_hash, best_number, last_finalized, safe_finalized })
     }
 
     fn block_number(&self, hash: H256) -> Result<Option<BlockNumber>> {
@@ -144,6 +306,7 @@ impl<DB: Database> BlockProvider for ShareableDatabase<DB> {
 
     fn ommers(&self, id: BlockId) -> Result<Option<Vec<Header>>> {
         if let Some(number) = self.block_number_for_id(id)? {
+            self.ensure_available(number)?;
             let tx = self.db.tx()?;
             // TODO: this can be optimized to return empty Vec post-merge
             let ommers = tx.get::<tables::BlockOmmers>(number)?.map(|o| o.ommers);
@@ -171,11 +334,51 @@ impl<DB: Database> TransactionsProvider for ShareableDatabase<DB> {
             .map_err(Into::into)
     }
 
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        let tx = self.db.tx()?;
+        let Some(id) = tx.get::<tables::TxHashNumber>(hash)? else { return Ok(None) };
+        let Some(transaction) = tx.get::<tables::Transactions>(id)? else { return Ok(None) };
+
+        // the first transactions of the blocks are ascending, search the last block that starts
+        // at or before the transaction
+        let Some((mut high, _)) = tx.cursor_read::<tables::BlockBodyIndices>()?.last()? else {
+            return Ok(None)
+        };
+        let mut low = 0;
+        while low < high {
+            let mid = low + (high - low + 1) / 2;
+            let body = tx
+                .get::<tables::BlockBodyIndices>(mid)?
+                .ok_or(ProviderError::BlockBody { number: mid })?;
+            if body.first_tx_num <= id {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        let body = tx
+            .get::<tables::BlockBodyIndices>(low)?
+            .ok_or(ProviderError::BlockBody { number: low })?;
+        let Some(block_hash) = tx.get::<tables::CanonicalHeaders>(low)? else { return Ok(None) };
+        let meta = TransactionMeta {
+            tx_hash: hash,
+            index: id - body.first_tx_num,
+            block_hash,
+            block_number: low,
+        };
+        Ok(Some((transaction, meta)))
+    }
+
     fn transactions_by_block(&self, id: BlockId) -> Result<Option<Vec<TransactionSigned>>> {
         if let Some(number) = self.block_number_for_id(id)? {
+            self.ensure_available(number)?;
             let tx = self.db.tx()?;
-            if let Some(body) = tx.get::<tables::BlockBodies>(number)? {
-                let tx_range = body.tx_id_range();
+            if let Some(body) = tx.get::<tables::BlockBodyIndices>(number)? {
+                let tx_range = body.tx_num_range();
                 return if tx_range.is_empty() {
                     Ok(Some(Vec::new()))
                 } else {
@@ -197,12 +400,12 @@ impl<DB: Database> TransactionsProvider for ShareableDatabase<DB> {
     ) -> Result<Vec<Vec<TransactionSigned>>> {
         let tx = self.db.tx()?;
         let mut results = Vec::default();
-        let mut body_cursor = tx.cursor_read::<tables::BlockBodies>()?;
+        let mut body_cursor = tx.cursor_read::<tables::BlockBodyIndices>()?;
         let mut tx_cursor = tx.cursor_read::<tables::Transactions>()?;
         for entry in body_cursor.walk_range(range)? {
             let (_, body) = entry?;
-            let tx_range = body.tx_id_range();
-            if body.tx_id_range().is_empty() {
+            let tx_range = body.tx_num_range();
+            if body.tx_num_range().is_empty() {
                 results.push(Vec::default());
             } else {
                 results.push(
@@ -236,9 +439,10 @@ impl<DB: Database> ReceiptProvider for ShareableDatabase<DB> {
 
     fn receipts_by_block(&self, block: BlockId) -> Result<Option<Vec<Receipt>>> {
         if let Some(number) = self.block_number_for_id(block)? {
+            self.ensure_available(number)?;
             let tx = self.db.tx()?;
-            if let Some(body) = tx.get::<tables::BlockBodies>(number)? {
-                let tx_range = body.tx_id_range();
+            if let Some(body) = tx.get::<tables::BlockBodyIndices>(number)? {
+                let tx_range = body.tx_num_range();
                 return if tx_range.is_empty() {
                     Ok(Some(Vec::new()))
                 } else {
@@ -253,16 +457,65 @@ impl<DB: Database> ReceiptProvider for ShareableDatabase<DB> {
         }
         Ok(None)
     }
+
+    fn receipts_by_block_aligned(&self, block: BlockId) -> Result<Option<Vec<Option<Receipt>>>> {
+        let Some(number) = self.block_number_for_id(block)? else { return Ok(None) };
+        self.ensure_available(number)?;
+        let tx = self.db.tx()?;
+        let Some(body) = tx.get::<tables::BlockBodyIndices>(number)? else { return Ok(None) };
+
+        let mut receipts = vec![None; body.tx_count() as usize];
+        for entry in tx.cursor_read::<tables::Receipts>()?.walk_range(body.tx_num_range())? {
+            let (tx_number, receipt) = entry?;
+            receipts[(tx_number - body.first_tx_num()) as usize] = Some(receipt);
+        }
+        Ok(Some(receipts))
+    }
+
+    fn receipts_by_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<Vec<Receipt>>> {
+        let tx = self.db.tx()?;
+        let bodies = tx
+            .cursor_read::<tables::BlockBodyIndices>()?
+            .walk_range(range)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let (Some((first_number, first)), Some((_, last))) = (bodies.first(), bodies.last()) else {
+            return Ok(Vec::new())
+        };
+        ensure_block_available(*first_number, get_lowest_available_block(&tx)?)?;
+
+        // walk the receipts of the whole range once and split them at the block boundaries, the
+        // receipts of pruned transactions are skipped
+        let mut receipts_cursor = tx.cursor_read::<tables::Receipts>()?;
+        let mut receipts =
+            receipts_cursor.walk_range(first.first_tx_num()..last.next_tx_num())?.peekable();
+        let mut results = Vec::with_capacity(bodies.len());
+        for (_, body) in bodies {
+            let mut block_receipts = Vec::with_capacity(body.tx_count() as usize);
+            while let Some(entry) = receipts.next_if(|entry| {
+                entry.as_ref().map_or(true, |(tx_number, _)| *tx_number < body.next_tx_num())
+            }) {
+                block_receipts.push(entry?.1);
+            }
+            results.push(block_receipts);
+        }
+        Ok(results)
+    }
 }
 
 impl<DB: Database> WithdrawalsProvider for ShareableDatabase<DB> {
     fn withdrawals_by_block(&self, id: BlockId, timestamp: u64) -> Result<Option<Vec<Withdrawal>>> {
         if self.chain_spec.fork(Hardfork::Shanghai).active_at_timestamp(timestamp) {
             if let Some(number) = self.block_number_for_id(id)? {
-                return Ok(self
+                // empty withdrawals are not stored
+                let withdrawals = self
                     .db
                     .view(|tx| tx.get::<tables::BlockWithdrawals>(number))??
-                    .map(|w| w.withdrawals))
+                    .map(|w| w.withdrawals)
+                    .unwrap_or_default();
+                return Ok(Some(withdrawals))
             }
         }
         Ok(None)
@@ -280,6 +533,12 @@ impl<DB: Database> WithdrawalsProvider for ShareableDatabase<DB> {
     }
 }
 
+impl<DB: Send + Sync> ChainSpecProvider for ShareableDatabase<DB> {
+    fn chain_spec(&self) -> Arc<ChainSpec> {
+        self.chain_spec.clone()
+    }
+}
+
 impl<DB: Database> EvmEnvProvider for ShareableDatabase<DB> {
     fn fill_env_at(&self, cfg: &mut CfgEnv, block_env: &mut BlockEnv, at: BlockId) -> Result<()> {
         let hash = self.block_hash_for_id(at)?.ok_or(ProviderError::HeaderNotFound)?;
@@ -339,24 +598,124 @@ impl<DB: Database> EvmEnvProvider for ShareableDatabase<DB> {
     }
 }
 
+impl<DB: Database> AccountHistoryProvider for ShareableDatabase<DB> {
+    fn account_transitions(&self, address: Address) -> Result<Vec<TransitionId>> {
+        let tx = self.db.tx()?;
+        let mut cursor = tx.cursor_read::<tables::AccountHistory>()?;
+        let mut transitions = Vec::new();
+        for entry in cursor.walk(Some(ShardedKey::new(address, 0)))? {
+            let (key, list) = entry?;
+            if key.key != address {
+                break
+            }
+            transitions.extend(list.iter(0).map(|i| i as TransitionId));
+        }
+        Ok(transitions)
+    }
+
+    fn account_before_transition(
+        &self,
+        address: Address,
+        transition: TransitionId,
+    ) -> Result<Option<Account>> {
+        let tx = self.db.tx()?;
+        Ok(tx
+            .cursor_dup_read::<tables::AccountChangeSet>()?
+            .seek_by_key_subkey(transition, address)?
+            .filter(|account| account.address == address)
+            .and_then(|account| account.info))
+    }
+
+    fn transition_location(
+        &self,
+        transition: TransitionId,
+    ) -> Result<Option<(BlockNumber, Option<TxNumber>)>> {
+        let tx = self.db.tx()?;
+        let Some((last_block, last_transition)) =
+            tx.cursor_read::<tables::BlockTransitionIndex>()?.last()?
+        else {
+            return Ok(None)
+        };
+        if transition >= last_transition {
+            return Ok(None)
+        }
+
+        // find the first block that ends after the transition
+        let (mut low, mut high) = (0, last_block);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let end = tx
+                .get::<tables::BlockTransitionIndex>(mid)?
+                .ok_or(ProviderError::BlockTransition { block_number: mid })?;
+            if end > transition {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        let block_number = low;
+        let first_transition = match block_number.checked_sub(1) {
+            Some(parent) => tx
+                .get::<tables::BlockTransitionIndex>(parent)?
+                .ok_or(ProviderError::BlockTransition { block_number: parent })?,
+            None => 0,
+        };
+        let body = tx
+            .get::<tables::BlockBodyIndices>(block_number)?
+            .ok_or(ProviderError::BlockBody { number: block_number })?;
+        let index = transition - first_transition;
+        let tx_number = (index < body.tx_count).then_some(body.first_tx_num + index);
+        Ok(Some((block_number, tx_number)))
+    }
+}
+
+impl<DB: Database> StorageHistoryVerifier for ShareableDatabase<DB> {
+    fn verify_storage_history(
+        &self,
+        address: Address,
+        storage_key: H256,
+        range: RangeInclusive<BlockNumber>,
+    ) -> Result<Option<StorageHistoryDivergence>> {
+        verify_storage_history(&self.db.tx()?, address, storage_key, range)
+    }
+}
+
 impl<DB: Database> StateProviderFactory for ShareableDatabase<DB> {
     type HistorySP<'a> = HistoricalStateProvider<'a,<DB as DatabaseGAT<'a>>::TX> where Self: 'a;
     type LatestSP<'a> = LatestStateProvider<'a,<DB as DatabaseGAT<'a>>::TX> where Self: 'a;
 
     /// Storage provider for latest block
+    ///
+    /// On a database synced from a trusted checkpoint, the latest state is unavailable until the
+    /// state of the checkpoint was imported, see [ensure_state_available].
     fn latest(&self) -> Result<Self::LatestSP<'_>> {
-        Ok(LatestStateProvider::new(self.db.tx()?))
+        let tx = self.db.tx()?;
+        let lowest_available = get_lowest_available_block(&tx)?;
+        if lowest_available > 0 && tx.get::<tables::SyncStage>("Execution".to_string())?.is_none() {
+            return Err(ProviderError::StateUnavailable { number: lowest_available - 1 }.into())
+        }
+        let provider =
+            LatestStateProvider::new(tx).with_bytecode_cache(self.bytecode_cache.clone());
+        Ok(match self.parallel_state_root {
+            Some(parallel_threshold) => provider.with_overlay_state_root(Box::new(
+                ParallelStateRoot::new(&self.db).with_parallel_threshold(parallel_threshold),
+            )),
+            None => provider,
+        })
     }
 
     fn history_by_block_number(&self, block_number: BlockNumber) -> Result<Self::HistorySP<'_>> {
         let tx = self.db.tx()?;
+        ensure_state_available(&tx, block_number)?;
 
         // get transition id
         let transition = tx
             .get::<tables::BlockTransitionIndex>(block_number)?
             .ok_or(ProviderError::BlockTransition { block_number })?;
 
-        Ok(HistoricalStateProvider::new(tx, transition))
+        Ok(HistoricalStateProvider::new(tx, transition)
+            .with_bytecode_cache(self.bytecode_cache.clone()))
     }
 
     fn history_by_block_hash(&self, block_hash: BlockHash) -> Result<Self::HistorySP<'_>> {
@@ -365,13 +724,15 @@ impl<DB: Database> StateProviderFactory for ShareableDatabase<DB> {
         let block_number = tx
             .get::<tables::HeaderNumbers>(block_hash)?
             .ok_or(ProviderError::BlockHash { block_hash })?;
+        ensure_state_available(&tx, block_number)?;
 
         // get transition id
         let transition = tx
             .get::<tables::BlockTransitionIndex>(block_number)?
             .ok_or(ProviderError::BlockTransition { block_number })?;
 
-        Ok(HistoricalStateProvider::new(tx, transition))
+        Ok(HistoricalStateProvider::new(tx, transition)
+            .with_bytecode_cache(self.bytecode_cache.clone()))
     }
 }
 
@@ -380,9 +741,25 @@ mod tests {
     use std::sync::Arc;
 
     use super::ShareableDatabase;
-    use crate::{BlockIdProvider, StateProviderFactory};
-    use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
-    use reth_primitives::{ChainSpecBuilder, H256};
+    use crate::{
+        insert_canonical_block, set_lowest_available_block, test_utils::blocks::BlockChainTestData,
+        AccountHistoryProvider, BlockHashProvider, BlockIdProvider, BlockProvider, HeaderProvider,
+        ProviderError, ReceiptProvider, StateProviderFactory, Transaction, TransactionsProvider,
+        WithdrawalsProvider, FORKCHOICE_STATE,
+    };
+    use reth_db::{
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        models::StoredBlockBodyIndices,
+        tables,
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_interfaces::consensus::ForkchoiceState;
+    use reth_primitives::{
+        Account, ChainSpecBuilder, ForkCondition, Hardfork, Header, Receipt, SealedBlock,
+        TransactionMeta, Withdrawal, H160, H256, MAINNET, U256,
+    };
+    use std::ops::{Deref, DerefMut};
+    use tokio::sync::watch;
 
     #[test]
     fn common_history_provider() {
@@ -404,4 +781,310 @@ mod tests {
         assert_eq!(chain_info.last_finalized, None);
         assert_eq!(chain_info.safe_finalized, None);
     }
+
+    #[test]
+    fn chain_info_from_forkchoice_state() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let data = BlockChainTestData::default();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        insert_canonical_block(tx.deref_mut(), data.genesis, None, false).unwrap();
+        let mut hashes = Vec::new();
+        for (block, _) in data.blocks {
+            hashes.push(block.block.hash());
+            insert_canonical_block(tx.deref_mut(), block.block, Some(block.senders), true).unwrap();
+        }
+        tx.put::<tables::SyncStage>("Finish".to_string(), 1).unwrap();
+        tx.commit().unwrap();
+
+        let (forkchoice_state_tx, forkchoice_state_rx) = watch::channel(ForkchoiceState::default());
+        let provider = ShareableDatabase::new(db, Arc::new(MAINNET.clone()))
+            .with_forkchoice_state(forkchoice_state_rx);
+        let chain_info = provider.chain_info().unwrap();
+        assert_eq!((chain_info.last_finalized, chain_info.safe_finalized), (None, None));
+
+        // block 2 is not fully committed yet
+        forkchoice_state_tx
+            .send(ForkchoiceState {
+                head_block_hash: hashes[1],
+                safe_block_hash: hashes[1],
+                finalized_block_hash: hashes[0],
+            })
+            .unwrap();
+        let chain_info = provider.chain_info().unwrap();
+        assert_eq!((chain_info.last_finalized, chain_info.safe_finalized), (Some(1), None));
+    }
+
+    #[test]
+    fn account_history() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let data = BlockChainTestData::default();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        insert_canonical_block(tx.deref_mut(), data.genesis, None, false).unwrap();
+        let mut results = Vec::new();
+        for (block, result) in data.blocks {
+            insert_canonical_block(tx.deref_mut(), block.block, Some(block.senders), true).unwrap();
+            results.push(result);
+        }
+        tx.insert_execution_result(results, &MAINNET, 0).unwrap();
+        let transitions = tx.get_account_transition_ids_from_changeset(0, 4).unwrap();
+        tx.insert_account_history_index(transitions).unwrap();
+        tx.commit().unwrap();
+
+        let provider = ShareableDatabase::new(db, Arc::new(MAINNET.clone()));
+        let address = H160([0x60; 20]);
+        assert_eq!(provider.account_transitions(address).unwrap(), vec![0, 2, 3]);
+        assert_eq!(provider.account_transitions(H160([0x61; 20])).unwrap(), vec![1]);
+        assert!(provider.account_transitions(H160([0x62; 20])).unwrap().is_empty());
+
+        // the account was created by the first transition
+        assert_eq!(provider.account_before_transition(address, 0).unwrap(), None);
+        assert_eq!(provider.account_before_transition(address, 1).unwrap(), None);
+        assert_eq!(
+            provider.account_before_transition(address, 2).unwrap(),
+            Some(Account { nonce: 1, balance: U256::from(10), bytecode_hash: None })
+        );
+
+        // a transaction and a withdrawal transition per block
+        assert_eq!(provider.transition_location(0).unwrap(), Some((1, Some(0))));
+        assert_eq!(provider.transition_location(1).unwrap(), Some((1, None)));
+        assert_eq!(provider.transition_location(2).unwrap(), Some((2, Some(1))));
+        assert_eq!(provider.transition_location(3).unwrap(), Some((2, None)));
+        assert_eq!(provider.transition_location(4).unwrap(), None);
+    }
+
+    #[test]
+    fn block_body_indices() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let data = BlockChainTestData::default();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        let mut hashes = vec![data.genesis.hash()];
+        insert_canonical_block(tx.deref_mut(), data.genesis, None, false).unwrap();
+        let mut transactions = vec![vec![]];
+        for (block, _) in data.blocks {
+            transactions.push(block.block.body.clone());
+            hashes.push(block.block.hash());
+            insert_canonical_block(tx.deref_mut(), block.block, Some(block.senders), true).unwrap();
+        }
+        assert_eq!(
+            tx.table::<tables::BlockBodyIndices>().unwrap(),
+            vec![
+                (0, StoredBlockBodyIndices::default()),
+                (
+                    1,
+                    StoredBlockBodyIndices {
+                        first_tx_num: 0,
+                        tx_count: 1,
+                        first_withdrawal_index: 0,
+                        withdrawal_count: 1
+                    }
+                ),
+                (
+                    2,
+                    StoredBlockBodyIndices {
+                        first_tx_num: 1,
+                        tx_count: 1,
+                        first_withdrawal_index: 1,
+                        withdrawal_count: 1
+                    }
+                ),
+            ]
+        );
+        tx.commit().unwrap();
+
+        let provider = ShareableDatabase::new(db, Arc::new(MAINNET.clone()));
+        // the genesis block has no transactions
+        assert_eq!(provider.transactions_by_block(0.into()).unwrap(), Some(vec![]));
+        assert_eq!(provider.transactions_by_block_range(0..=2).unwrap(), transactions);
+        assert_eq!(provider.transactions_by_block_range(1..).unwrap(), transactions[1..]);
+        assert!(provider.transactions_by_block_range(3..).unwrap().is_empty());
+
+        // the block of a transaction is found by its transaction number
+        let transaction = transactions[2][0].clone();
+        assert_eq!(
+            provider.transaction_by_hash_with_meta(transaction.hash()).unwrap(),
+            Some((
+                transaction.clone(),
+                TransactionMeta {
+                    tx_hash: transaction.hash(),
+                    index: 0,
+                    block_hash: hashes[2],
+                    block_number: 2
+                }
+            ))
+        );
+        assert_eq!(provider.transaction_by_hash_with_meta(H256::random()).unwrap(), None);
+    }
+
+    #[test]
+    fn sealed_headers() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let data = BlockChainTestData::default();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        let mut headers = vec![data.genesis.header.clone()];
+        insert_canonical_block(tx.deref_mut(), data.genesis, None, false).unwrap();
+        for (block, _) in data.blocks {
+            headers.push(block.block.header.clone());
+            insert_canonical_block(tx.deref_mut(), block.block, Some(block.senders), true).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let provider = ShareableDatabase::new(db, Arc::new(MAINNET.clone()));
+        // the headers are sealed with the stored hashes, the mocked genesis hash included
+        assert_eq!(provider.sealed_header(0).unwrap(), Some(headers[0].clone()));
+        assert_eq!(provider.sealed_header(2).unwrap(), Some(headers[2].clone()));
+        assert_eq!(provider.sealed_header(3).unwrap(), None);
+        assert_eq!(provider.sealed_headers_range(1..).unwrap(), headers[1..]);
+    }
+
+    #[test]
+    fn receipts_by_block_range() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+
+        // blocks without transactions are interleaved with blocks with transactions
+        let mut expected = Vec::new();
+        let mut first_tx_num = 0;
+        for (block_number, tx_count) in [0, 2, 0, 1].into_iter().enumerate() {
+            tx.put::<tables::BlockBodyIndices>(
+                block_number as u64,
+                StoredBlockBodyIndices { first_tx_num, tx_count, ..Default::default() },
+            )
+            .unwrap();
+            let mut receipts = Vec::new();
+            for tx_number in first_tx_num..first_tx_num + tx_count {
+                let receipt = Receipt { cumulative_gas_used: tx_number, ..Default::default() };
+                tx.put::<tables::Receipts>(tx_number, receipt.clone()).unwrap();
+                receipts.push(receipt);
+            }
+            expected.push(receipts);
+            first_tx_num += tx_count;
+        }
+        tx.commit().unwrap();
+
+        let provider = ShareableDatabase::new(db, Arc::new(MAINNET.clone()));
+        assert_eq!(provider.receipts_by_block_range(0..=3).unwrap(), expected);
+        assert_eq!(provider.receipts_by_block_range(2..).unwrap(), expected[2..]);
+        assert_eq!(provider.receipts_by_block_range(1..=1).unwrap(), expected[1..2]);
+        assert!(provider.receipts_by_block_range(4..).unwrap().is_empty());
+        for (block_number, receipts) in expected.into_iter().enumerate() {
+            assert_eq!(
+                provider.receipts_by_block((block_number as u64).into()).unwrap(),
+                Some(receipts)
+            );
+        }
+    }
+
+    #[test]
+    fn canonical_hashes_range() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        let hashes = (0..4).map(H256::from_low_u64_be).collect::<Vec<_>>();
+        for (block_number, hash) in hashes.iter().enumerate() {
+            tx.put::<tables::CanonicalHeaders>(block_number as u64, *hash).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let provider = ShareableDatabase::new(db, Arc::new(MAINNET.clone()));
+        // the range down to genesis has fewer than 256 hashes
+        assert_eq!(provider.canonical_hashes_range(0, 3).unwrap(), hashes);
+        assert_eq!(provider.canonical_hashes_range(2, 2).unwrap(), hashes[2..3]);
+        assert!(provider.canonical_hashes_range(3, 2).unwrap().is_empty());
+        assert_eq!(
+            provider.canonical_hashes_range(1, 5).unwrap_err(),
+            ProviderError::CanonicalHeader { block_number: 4 }.into()
+        );
+
+        // the hashes below the lowest available block are missing after a checkpoint sync
+        let mut tx = Transaction::new(provider.db.as_ref()).unwrap();
+        set_lowest_available_block(tx.deref(), 1).unwrap();
+        tx.commit().unwrap();
+        assert_eq!(
+            provider.canonical_hashes_range(0, 3).unwrap_err(),
+            ProviderError::BlockUnavailable { number: 0, lowest_available: 1 }.into()
+        );
+        assert_eq!(provider.canonical_hashes_range(1, 3).unwrap(), hashes[1..]);
+    }
+
+    #[test]
+    fn block_withdrawals() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        let withdrawals =
+            (0..16).map(|index| Withdrawal { index, ..Default::default() }).collect::<Vec<_>>();
+        // block 1 is the first Shanghai block and has no withdrawals
+        for (number, block_withdrawals) in
+            [None, Some(vec![]), Some(withdrawals.clone())].into_iter().enumerate()
+        {
+            let header =
+                Header { number: number as u64, timestamp: number as u64, ..Default::default() };
+            let block = SealedBlock {
+                header: header.seal_slow(),
+                body: vec![],
+                ommers: vec![],
+                withdrawals: block_withdrawals,
+            };
+            insert_canonical_block(tx.deref_mut(), block, None, false).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(1))
+            .build();
+        let provider = ShareableDatabase::new(db, Arc::new(chain_spec));
+        assert_eq!(provider.withdrawals_by_block(0.into(), 0).unwrap(), None);
+        assert_eq!(provider.withdrawals_by_block(1.into(), 1).unwrap(), Some(vec![]));
+        assert_eq!(provider.withdrawals_by_block(2.into(), 2).unwrap(), Some(withdrawals.clone()));
+        assert_eq!(provider.latest_withdrawal().unwrap(), withdrawals.last().cloned());
+
+        // the blocks served to the rpc omit the withdrawals before Shanghai only
+        assert_eq!(provider.block(0.into()).unwrap().unwrap().withdrawals, None);
+        assert_eq!(provider.block(1.into()).unwrap().unwrap().withdrawals, Some(vec![]));
+        assert_eq!(provider.block(2.into()).unwrap().unwrap().withdrawals, Some(withdrawals));
+    }
+
+    #[tokio::test]
+    async fn forkchoice_state_restored_on_restart() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let data = BlockChainTestData::default();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        insert_canonical_block(tx.deref_mut(), data.genesis, None, false).unwrap();
+        let mut hashes = Vec::new();
+        for (block, _) in data.blocks {
+            hashes.push(block.block.hash());
+            insert_canonical_block(tx.deref_mut(), block.block, Some(block.senders), true).unwrap();
+        }
+        tx.put::<tables::SyncStage>("Finish".to_string(), 2).unwrap();
+        tx.commit().unwrap();
+
+        let state = ForkchoiceState {
+            head_block_hash: hashes[1],
+            safe_block_hash: hashes[1],
+            finalized_block_hash: hashes[0],
+        };
+        let provider = ShareableDatabase::new(db.clone(), Arc::new(MAINNET.clone()));
+        assert_eq!(provider.load_forkchoice_state().unwrap(), None);
+        let (forkchoice_state_tx, forkchoice_state_rx) = watch::channel(ForkchoiceState::default());
+        let persist = tokio::spawn(provider.persist_forkchoice_state(forkchoice_state_rx));
+        forkchoice_state_tx.send(state.clone()).unwrap();
+        drop(forkchoice_state_tx);
+        persist.await.unwrap();
+
+        // the finalized block resolves before the engine API receives an update
+        let provider = ShareableDatabase::new(db.clone(), Arc::new(MAINNET.clone()));
+        let restored = provider.load_forkchoice_state().unwrap();
+        assert_eq!(restored, Some(state));
+        let (_forkchoice_state_tx, forkchoice_state_rx) = watch::channel(restored.unwrap());
+        let provider = provider.with_forkchoice_state(forkchoice_state_rx);
+        let chain_info = provider.chain_info().unwrap();
+        assert_eq!((chain_info.last_finalized, chain_info.safe_finalized), (Some(1), Some(2)));
+
+        // the state is discarded once block 2 is unwound
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        tx.delete::<tables::CanonicalHeaders>(2, None).unwrap();
+        tx.put::<tables::SyncStage>("Finish".to_string(), 1).unwrap();
+        tx.commit().unwrap();
+        assert_eq!(provider.load_forkchoice_state().unwrap(), None);
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        assert_eq!(tx.get::<tables::SyncStageProgress>(FORKCHOICE_STATE.into()).unwrap(), None);
+    }
 }
@@ -1,6 +1,8 @@
 use crate::{
-    providers::state::macros::delegate_provider_impls, AccountProvider, BlockHashProvider,
-    ProviderError, StateProvider,
+    canonical_hashes_range,
+    providers::{state::macros::delegate_provider_impls, BytecodeCache},
+    trie::{HashedPostState, MultiProof, StateRoot, TrieNodeCache},
+    AccountProvider, BlockHashProvider, ProviderError, StateProvider,
 };
 use reth_db::{
     cursor::{DbCursorRO, DbDupCursorRO},
@@ -10,9 +12,13 @@ use reth_db::{
 };
 use reth_interfaces::Result;
 use reth_primitives::{
-    Account, Address, Bytecode, Bytes, StorageKey, StorageValue, TransitionId, H256, U256,
+    Account, Address, BlockNumber, Bytecode, Bytes, StorageKey, StorageValue, TransitionId, H256,
+    U256,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    marker::PhantomData,
 };
-use std::marker::PhantomData;
 
 /// State provider for a given transition id which takes a tx reference.
 ///
@@ -27,6 +33,8 @@ pub struct HistoricalStateProviderRef<'a, 'b, TX: DbTx<'a>> {
     tx: &'b TX,
     /// Transition is main indexer of account and storage changes
     transition: TransitionId,
+    /// The cache the contract codes are read through
+    bytecode_cache: Option<&'b BytecodeCache>,
     /// Phantom lifetime `'a`
     _phantom: PhantomData<&'a TX>,
 }
@@ -34,7 +42,44 @@ pub struct HistoricalStateProviderRef<'a, 'b, TX: DbTx<'a>> {
 impl<'a, 'b, TX: DbTx<'a>> HistoricalStateProviderRef<'a, 'b, TX> {
     /// Create new StateProvider from history transaction number
     pub fn new(tx: &'b TX, transition: TransitionId) -> Self {
-        Self { tx, transition, _phantom: PhantomData {} }
+        Self { tx, transition, bytecode_cache: None, _phantom: PhantomData {} }
+    }
+
+    /// Reads the contract codes through the given cache.
+    pub fn with_bytecode_cache(mut self, bytecode_cache: Option<&'b BytecodeCache>) -> Self {
+        self.bytecode_cache = bytecode_cache;
+        self
+    }
+
+    /// Returns the state root of the last header, the root of the trie in the database.
+    fn state_root_of_tip(&self) -> Result<H256> {
+        Ok(self
+            .tx
+            .cursor_read::<tables::Headers>()?
+            .last()?
+            .ok_or(ProviderError::Header { number: 0 })?
+            .1
+            .state_root)
+    }
+
+    /// Returns the changes that revert the trie in the database to this state.
+    fn reverts(&self) -> Result<HashedPostState> {
+        HashedPostState::from_reverts(self.tx, self.transition)
+            .map_err(|_| ProviderError::StateTrie.into())
+    }
+
+    /// Computes the state root of the post state on top of this state.
+    fn reverted_state_root(
+        &self,
+        state_root: StateRoot<'b, TX>,
+        post_state: HashedPostState,
+    ) -> Result<H256> {
+        // the trie in the database is at the tip, so the changes since the transition are
+        // reverted before applying the post state
+        let root = self.state_root_of_tip()?;
+        let mut reverts = self.reverts()?;
+        reverts.extend(post_state);
+        state_root.overlay_root(root, &reverts).map_err(|_| ProviderError::StateTrie.into())
     }
 }
 impl<'a, 'b, TX: DbTx<'a>> AccountProvider for HistoricalStateProviderRef<'a, 'b, TX> {
@@ -73,7 +118,13 @@ impl<'a, 'b, TX: DbTx<'a>> AccountProvider for HistoricalStateProviderRef<'a, 'b
 impl<'a, 'b, TX: DbTx<'a>> BlockHashProvider for HistoricalStateProviderRef<'a, 'b, TX> {
     /// Get block hash by number.
     fn block_hash(&self, number: U256) -> Result<Option<H256>> {
-        self.tx.get::<tables::CanonicalHeaders>(number.to::<u64>()).map_err(Into::into)
+        let number =
+            u64::try_from(number).map_err(|_| ProviderError::BlockNumberOverflow(number))?;
+        self.tx.get::<tables::CanonicalHeaders>(number).map_err(Into::into)
+    }
+
+    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>> {
+        canonical_hashes_range(self.tx, start, end)
     }
 }
 
@@ -117,7 +168,11 @@ impl<'a, 'b, TX: DbTx<'a>> StateProvider for HistoricalStateProviderRef<'a, 'b,
 
     /// Get account code by its hash
     fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytecode>> {
-        self.tx.get::<tables::Bytecodes>(code_hash).map_err(Into::into)
+        let read = || self.tx.get::<tables::Bytecodes>(code_hash).map_err(Into::into);
+        match self.bytecode_cache {
+            Some(cache) => cache.get_or_read(code_hash, read),
+            None => read(),
+        }
     }
 
     /// Get account and storage proofs.
@@ -128,6 +183,47 @@ impl<'a, 'b, TX: DbTx<'a>> StateProvider for HistoricalStateProviderRef<'a, 'b,
     ) -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
         Err(ProviderError::HistoryStateRoot.into())
     }
+
+    fn state_root(&self, post_state: HashedPostState) -> Result<H256> {
+        self.reverted_state_root(StateRoot::new(self.tx), post_state)
+    }
+
+    fn state_root_with_cache(
+        &self,
+        post_state: HashedPostState,
+        cache: &TrieNodeCache,
+    ) -> Result<H256> {
+        self.reverted_state_root(StateRoot::new(self.tx).with_cache(cache.clone()), post_state)
+    }
+
+    fn witness(
+        &self,
+        targets: &BTreeMap<H256, BTreeSet<H256>>,
+        post_state: HashedPostState,
+    ) -> Result<Vec<Bytes>> {
+        let nodes = StateRoot::new(self.tx)
+            .witness(self.state_root_of_tip()?, &self.reverts()?, targets, &post_state)
+            .map_err(|_| ProviderError::StateTrie)?;
+        Ok(nodes.into_iter().map(Bytes::from).collect())
+    }
+
+    /// Collects the proof nodes and computes the state root on the same reverts of the trie.
+    fn multiproof(&self, targets: &HashMap<Address, Vec<H256>>) -> Result<MultiProof> {
+        let root = self.state_root_of_tip()?;
+        let reverts = self.reverts()?;
+        let state_root = StateRoot::new(self.tx);
+        let nodes = state_root
+            .witness(
+                root,
+                &reverts,
+                &MultiProof::hashed_targets(targets),
+                &HashedPostState::default(),
+            )
+            .map_err(|_| ProviderError::StateTrie)?;
+        let root = state_root.overlay_root(root, &reverts).map_err(|_| ProviderError::StateTrie)?;
+        MultiProof::from_nodes(root, nodes.into_iter().map(Bytes::from).collect(), targets)
+            .map_err(|_| ProviderError::StateTrie.into())
+    }
 }
 
 /// State provider for a given transition
@@ -136,6 +232,8 @@ pub struct HistoricalStateProvider<'a, TX: DbTx<'a>> {
     tx: TX,
     /// Transition is main indexer of account and storage changes
     transition: TransitionId,
+    /// The cache the contract codes are read through
+    bytecode_cache: Option<BytecodeCache>,
     /// Phantom lifetime `'a`
     _phantom: PhantomData<&'a TX>,
 }
@@ -143,13 +241,20 @@ pub struct HistoricalStateProvider<'a, TX: DbTx<'a>> {
 impl<'a, TX: DbTx<'a>> HistoricalStateProvider<'a, TX> {
     /// Create new StateProvider from history transaction number
     pub fn new(tx: TX, transition: TransitionId) -> Self {
-        Self { tx, transition, _phantom: PhantomData {} }
+        Self { tx, transition, bytecode_cache: None, _phantom: PhantomData {} }
+    }
+
+    /// Reads the contract codes through the given cache.
+    pub fn with_bytecode_cache(mut self, bytecode_cache: Option<BytecodeCache>) -> Self {
+        self.bytecode_cache = bytecode_cache;
+        self
     }
 
     /// Returns a new provider that takes the `TX` as reference
     #[inline(always)]
     fn as_ref<'b>(&'b self) -> HistoricalStateProviderRef<'a, 'b, TX> {
         HistoricalStateProviderRef::new(&self.tx, self.transition)
+            .with_bytecode_cache(self.bytecode_cache.as_ref())
     }
 }
 
@@ -1,6 +1,8 @@
 use crate::{
-    providers::state::macros::delegate_provider_impls, trie::DBTrieLoader, AccountProvider,
-    BlockHashProvider, StateProvider,
+    canonical_hashes_range,
+    providers::{state::macros::delegate_provider_impls, BytecodeCache},
+    trie::{DBTrieLoader, HashedPostState, OverlayStateRoot, StateRoot, TrieNodeCache},
+    AccountProvider, BlockHashProvider, StateProvider,
 };
 use reth_db::{
     cursor::{DbCursorRO, DbDupCursorRO},
@@ -9,15 +11,22 @@ use reth_db::{
 };
 use reth_interfaces::{provider::ProviderError, Result};
 use reth_primitives::{
-    keccak256, Account, Address, Bytecode, Bytes, StorageKey, StorageValue, H256, KECCAK_EMPTY,
-    U256,
+    keccak256, Account, Address, BlockNumber, Bytecode, Bytes, StorageKey, StorageValue, H256,
+    KECCAK_EMPTY, U256,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    marker::PhantomData,
 };
-use std::marker::PhantomData;
 
 /// State provider over latest state that takes tx reference.
 pub struct LatestStateProviderRef<'a, 'b, TX: DbTx<'a>> {
     /// database transaction
     db: &'b TX,
+    /// The cache the contract codes are read through
+    bytecode_cache: Option<&'b BytecodeCache>,
+    /// Computes the state roots with changes instead of the transaction
+    overlay_state_root: Option<&'b dyn OverlayStateRoot>,
     /// Phantom data over lifetime
     phantom: PhantomData<&'a TX>,
 }
@@ -25,7 +34,34 @@ pub struct LatestStateProviderRef<'a, 'b, TX: DbTx<'a>> {
 impl<'a, 'b, TX: DbTx<'a>> LatestStateProviderRef<'a, 'b, TX> {
     /// Create new state provider
     pub fn new(db: &'b TX) -> Self {
-        Self { db, phantom: PhantomData {} }
+        Self { db, bytecode_cache: None, overlay_state_root: None, phantom: PhantomData {} }
+    }
+
+    /// Reads the contract codes through the given cache.
+    pub fn with_bytecode_cache(mut self, bytecode_cache: Option<&'b BytecodeCache>) -> Self {
+        self.bytecode_cache = bytecode_cache;
+        self
+    }
+
+    /// Computes the state roots with changes, see [StateProvider::state_root], with the given
+    /// [OverlayStateRoot], e.g. a [ParallelStateRoot](crate::trie::ParallelStateRoot).
+    pub fn with_overlay_state_root(
+        mut self,
+        overlay_state_root: Option<&'b dyn OverlayStateRoot>,
+    ) -> Self {
+        self.overlay_state_root = overlay_state_root;
+        self
+    }
+
+    /// Returns the state root of the last header, the root of the trie in the database.
+    fn state_root_of_tip(&self) -> Result<H256> {
+        Ok(self
+            .db
+            .cursor_read::<tables::Headers>()?
+            .last()?
+            .ok_or(ProviderError::Header { number: 0 })?
+            .1
+            .state_root)
     }
 }
 
@@ -34,12 +70,29 @@ impl<'a, 'b, TX: DbTx<'a>> AccountProvider for LatestStateProviderRef<'a, 'b, TX
     fn basic_account(&self, address: Address) -> Result<Option<Account>> {
         self.db.get::<tables::PlainAccountState>(address).map_err(Into::into)
     }
+
+    /// Get basic account information of multiple accounts, walking one cursor over the plain
+    /// account state.
+    fn basic_accounts(&self, addresses: &[Address]) -> Result<Vec<Option<Account>>> {
+        let mut cursor = self.db.cursor_read::<tables::PlainAccountState>()?;
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            accounts.push(cursor.seek_exact(*address)?.map(|(_, account)| account));
+        }
+        Ok(accounts)
+    }
 }
 
 impl<'a, 'b, TX: DbTx<'a>> BlockHashProvider for LatestStateProviderRef<'a, 'b, TX> {
     /// Get block hash by number.
     fn block_hash(&self, number: U256) -> Result<Option<H256>> {
-        self.db.get::<tables::CanonicalHeaders>(number.to::<u64>()).map_err(Into::into)
+        let number =
+            u64::try_from(number).map_err(|_| ProviderError::BlockNumberOverflow(number))?;
+        self.db.get::<tables::CanonicalHeaders>(number).map_err(Into::into)
+    }
+
+    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>> {
+        canonical_hashes_range(self.db, start, end)
     }
 }
 
@@ -57,7 +110,11 @@ impl<'a, 'b, TX: DbTx<'a>> StateProvider for LatestStateProviderRef<'a, 'b, TX>
 
     /// Get account code by its hash
     fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytecode>> {
-        self.db.get::<tables::Bytecodes>(code_hash).map_err(Into::into)
+        let read = || self.db.get::<tables::Bytecodes>(code_hash).map_err(Into::into);
+        match self.bytecode_cache {
+            Some(cache) => cache.get_or_read(code_hash, read),
+            None => read(),
+        }
     }
 
     fn proof(
@@ -67,13 +124,7 @@ impl<'a, 'b, TX: DbTx<'a>> StateProvider for LatestStateProviderRef<'a, 'b, TX>
     ) -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
         let hashed_address = keccak256(address);
         let loader = DBTrieLoader::new(self.db);
-        let root = self
-            .db
-            .cursor_read::<tables::Headers>()?
-            .last()?
-            .ok_or(ProviderError::Header { number: 0 })?
-            .1
-            .state_root;
+        let root = self.state_root_of_tip()?;
 
         let (account_proof, storage_root) = loader
             .generate_acount_proof(root, hashed_address)
@@ -95,12 +146,47 @@ impl<'a, 'b, TX: DbTx<'a>> StateProvider for LatestStateProviderRef<'a, 'b, TX>
 
         Ok((account_proof, storage_root, storage_proof))
     }
+
+    fn state_root(&self, post_state: HashedPostState) -> Result<H256> {
+        let root = self.state_root_of_tip()?;
+        match self.overlay_state_root {
+            Some(state_root) => state_root.overlay_root(root, &post_state),
+            None => StateRoot::new(self.db).overlay_root(root, &post_state),
+        }
+        .map_err(|_| ProviderError::StateTrie.into())
+    }
+
+    fn state_root_with_cache(
+        &self,
+        post_state: HashedPostState,
+        cache: &TrieNodeCache,
+    ) -> Result<H256> {
+        StateRoot::new(self.db)
+            .with_cache(cache.clone())
+            .overlay_root(self.state_root_of_tip()?, &post_state)
+            .map_err(|_| ProviderError::StateTrie.into())
+    }
+
+    fn witness(
+        &self,
+        targets: &BTreeMap<H256, BTreeSet<H256>>,
+        post_state: HashedPostState,
+    ) -> Result<Vec<Bytes>> {
+        let nodes = StateRoot::new(self.db)
+            .witness(self.state_root_of_tip()?, &Default::default(), targets, &post_state)
+            .map_err(|_| ProviderError::StateTrie)?;
+        Ok(nodes.into_iter().map(Bytes::from).collect())
+    }
 }
 
 /// State provider for the latest state.
 pub struct LatestStateProvider<'a, TX: DbTx<'a>> {
     /// database transaction
     db: TX,
+    /// The cache the contract codes are read through
+    bytecode_cache: Option<BytecodeCache>,
+    /// Computes the state roots with changes instead of the transaction
+    overlay_state_root: Option<Box<dyn OverlayStateRoot + 'a>>,
     /// Phantom lifetime `'a`
     _phantom: PhantomData<&'a TX>,
 }
@@ -108,13 +194,31 @@ pub struct LatestStateProvider<'a, TX: DbTx<'a>> {
 impl<'a, TX: DbTx<'a>> LatestStateProvider<'a, TX> {
     /// Create new state provider
     pub fn new(db: TX) -> Self {
-        Self { db, _phantom: PhantomData {} }
+        Self { db, bytecode_cache: None, overlay_state_root: None, _phantom: PhantomData {} }
+    }
+
+    /// Reads the contract codes through the given cache.
+    pub fn with_bytecode_cache(mut self, bytecode_cache: Option<BytecodeCache>) -> Self {
+        self.bytecode_cache = bytecode_cache;
+        self
+    }
+
+    /// Computes the state roots with changes, see [StateProvider::state_root], with the given
+    /// [OverlayStateRoot], e.g. a [ParallelStateRoot](crate::trie::ParallelStateRoot).
+    pub fn with_overlay_state_root(
+        mut self,
+        overlay_state_root: Box<dyn OverlayStateRoot + 'a>,
+    ) -> Self {
+        self.overlay_state_root = Some(overlay_state_root);
+        self
     }
 
     /// Returns a new provider that takes the `TX` as reference
     #[inline(always)]
     fn as_ref<'b>(&'b self) -> LatestStateProviderRef<'a, 'b, TX> {
         LatestStateProviderRef::new(&self.db)
+            .with_bytecode_cache(self.bytecode_cache.as_ref())
+            .with_overlay_state_root(self.overlay_state_root.as_deref())
     }
 }
 
@@ -32,14 +32,20 @@ macro_rules! delegate_provider_impls {
             for $target =>
             AccountProvider $(where [$($generics)*])? {
                 fn basic_account(&self, address: reth_primitives::Address) -> reth_interfaces::Result<Option<reth_primitives::Account>>;
+                fn basic_accounts(&self, addresses: &[reth_primitives::Address]) -> reth_interfaces::Result<Vec<Option<reth_primitives::Account>>>;
             }
             BlockHashProvider $(where [$($generics)*])? {
                 fn block_hash(&self, number: reth_primitives::U256) -> reth_interfaces::Result<Option<reth_primitives::H256>>;
+                fn canonical_hashes_range(&self, start: reth_primitives::BlockNumber, end: reth_primitives::BlockNumber) -> reth_interfaces::Result<Vec<reth_primitives::H256>>;
             }
             StateProvider $(where [$($generics)*])?{
                 fn storage(&self, account: reth_primitives::Address, storage_key: reth_primitives::StorageKey) -> reth_interfaces::Result<Option<reth_primitives::StorageValue>>;
                 fn proof(&self, address: reth_primitives::Address, keys: &[reth_primitives::H256]) -> reth_interfaces::Result<(Vec<reth_primitives::Bytes>, reth_primitives::H256, Vec<Vec<reth_primitives::Bytes>>)>;
                 fn bytecode_by_hash(&self, code_hash: reth_primitives::H256) -> reth_interfaces::Result<Option<reth_primitives::Bytecode>>;
+                fn state_root(&self, post_state: $crate::trie::HashedPostState) -> reth_interfaces::Result<reth_primitives::H256>;
+                fn state_root_with_cache(&self, post_state: $crate::trie::HashedPostState, cache: &$crate::trie::TrieNodeCache) -> reth_interfaces::Result<reth_primitives::H256>;
+                fn witness(&self, targets: &std::collections::BTreeMap<reth_primitives::H256, std::collections::BTreeSet<reth_primitives::H256>>, post_state: $crate::trie::HashedPostState) -> reth_interfaces::Result<Vec<reth_primitives::Bytes>>;
+                fn multiproof(&self, targets: &std::collections::HashMap<reth_primitives::Address, Vec<reth_primitives::H256>>) -> reth_interfaces::Result<$crate::trie::MultiProof>;
             }
         );
     }
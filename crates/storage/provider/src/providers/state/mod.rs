@@ -3,3 +3,4 @@ pub(crate) mod chain;
 pub(crate) mod historical;
 pub(crate) mod latest;
 pub(crate) mod macros;
+pub(crate) mod recording;
@@ -0,0 +1,141 @@
+use crate::{
+    trie::{HashedPostState, MultiProof, TrieNodeCache},
+    AccountProvider, BlockHashProvider, StateProvider,
+};
+use reth_interfaces::Result;
+use reth_primitives::{
+    keccak256, Account, Address, BlockNumber, Bytecode, Bytes, StorageKey, StorageValue, H256, U256,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Mutex,
+};
+
+/// The state that was read through a [RecordingStateProvider].
+#[derive(Clone, Debug, Default)]
+pub struct RecordedState {
+    /// The accounts that were read, with the storage slots that were read of each account.
+    pub accounts: BTreeMap<Address, BTreeSet<StorageKey>>,
+    /// The contract codes that were read, keyed by their hashes.
+    pub codes: BTreeMap<H256, Bytecode>,
+    /// The block hashes that were read, keyed by the block number.
+    pub block_hashes: BTreeMap<u64, H256>,
+}
+
+impl RecordedState {
+    /// Returns the hashed addresses of the accounts with the hashed slots that were read, the
+    /// targets of [StateProvider::witness].
+    pub fn hashed_targets(&self) -> BTreeMap<H256, BTreeSet<H256>> {
+        self.accounts
+            .iter()
+            .map(|(address, slots)| (keccak256(address), slots.iter().map(keccak256).collect()))
+            .collect()
+    }
+}
+
+/// A [StateProvider] that records the accounts, storage slots, contract codes and block hashes
+/// that are read from the inner provider.
+#[derive(Debug)]
+pub struct RecordingStateProvider<SP> {
+    inner: SP,
+    recorded: Mutex<RecordedState>,
+}
+
+impl<SP> RecordingStateProvider<SP> {
+    /// Wraps the given provider.
+    pub fn new(inner: SP) -> Self {
+        Self { inner, recorded: Default::default() }
+    }
+
+    /// Returns the state that was read so far.
+    pub fn recorded(&self) -> RecordedState {
+        self.recorded.lock().expect("not poisoned").clone()
+    }
+
+    /// Returns the inner provider and the state that was read.
+    pub fn into_parts(self) -> (SP, RecordedState) {
+        (self.inner, self.recorded.into_inner().expect("not poisoned"))
+    }
+
+    fn record(&self, f: impl FnOnce(&mut RecordedState)) {
+        f(&mut self.recorded.lock().expect("not poisoned"))
+    }
+}
+
+impl<SP: StateProvider> AccountProvider for RecordingStateProvider<SP> {
+    fn basic_account(&self, address: Address) -> Result<Option<Account>> {
+        self.record(|recorded| {
+            recorded.accounts.entry(address).or_default();
+        });
+        self.inner.basic_account(address)
+    }
+}
+
+impl<SP: StateProvider> BlockHashProvider for RecordingStateProvider<SP> {
+    fn block_hash(&self, number: U256) -> Result<Option<H256>> {
+        let hash = self.inner.block_hash(number)?;
+        if let Some(hash) = hash {
+            self.record(|recorded| {
+                recorded.block_hashes.insert(number.to::<u64>(), hash);
+            });
+        }
+        Ok(hash)
+    }
+
+    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>> {
+        let hashes = self.inner.canonical_hashes_range(start, end)?;
+        self.record(|recorded| recorded.block_hashes.extend((start..).zip(hashes.iter().copied())));
+        Ok(hashes)
+    }
+}
+
+impl<SP: StateProvider> StateProvider for RecordingStateProvider<SP> {
+    fn storage(&self, account: Address, storage_key: StorageKey) -> Result<Option<StorageValue>> {
+        self.record(|recorded| {
+            recorded.accounts.entry(account).or_default().insert(storage_key);
+        });
+        self.inner.storage(account, storage_key)
+    }
+
+    fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytecode>> {
+        let code = self.inner.bytecode_by_hash(code_hash)?;
+        if let Some(code) = &code {
+            self.record(|recorded| {
+                recorded.codes.insert(code_hash, code.clone());
+            });
+        }
+        Ok(code)
+    }
+
+    fn proof(
+        &self,
+        address: Address,
+        keys: &[H256],
+    ) -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
+        self.inner.proof(address, keys)
+    }
+
+    fn state_root(&self, post_state: HashedPostState) -> Result<H256> {
+        self.inner.state_root(post_state)
+    }
+
+    fn state_root_with_cache(
+        &self,
+        post_state: HashedPostState,
+        cache: &TrieNodeCache,
+    ) -> Result<H256> {
+        self.inner.state_root_with_cache(post_state, cache)
+    }
+
+    fn witness(
+        &self,
+        targets: &BTreeMap<H256, BTreeSet<H256>>,
+        post_state: HashedPostState,
+    ) -> Result<Vec<Bytes>> {
+        self.inner.witness(targets, post_state)
+    }
+
+    fn multiproof(&self, targets: &HashMap<Address, Vec<H256>>) -> Result<MultiProof> {
+        self.inner.multiproof(targets)
+    }
+}
@@ -6,10 +6,10 @@ use crate::{
     },
     Transaction,
 };
-use reth_db::{database::Database, models::StoredBlockBody, tables};
+use reth_db::{database::Database, models::StoredBlockBodyIndices, tables};
 use reth_primitives::{
     hex_literal::hex, proofs::EMPTY_ROOT, Account, Header, Receipt, SealedBlock,
-    SealedBlockWithSenders, Withdrawal, H160, H256, U256,
+    SealedBlockWithSenders, SealedHeader, Withdrawal, H160, H256, U256,
 };
 use reth_rlp::Decodable;
 use std::collections::BTreeMap;
@@ -24,7 +24,10 @@ pub fn assert_genesis_block<DB: Database>(tx: &Transaction<'_, DB>, g: SealedBlo
     assert_eq!(tx.table::<tables::HeaderNumbers>().unwrap(), vec![(h, n)]);
     assert_eq!(tx.table::<tables::CanonicalHeaders>().unwrap(), vec![(n, h)]);
     assert_eq!(tx.table::<tables::HeaderTD>().unwrap(), vec![(n, g.difficulty.into())]);
-    assert_eq!(tx.table::<tables::BlockBodies>().unwrap(), vec![(0, StoredBlockBody::default())]);
+    assert_eq!(
+        tx.table::<tables::BlockBodyIndices>().unwrap(),
+        vec![(0, StoredBlockBodyIndices::default())]
+    );
     assert_eq!(tx.table::<tables::BlockOmmers>().unwrap(), vec![]);
     assert_eq!(tx.table::<tables::BlockWithdrawals>().unwrap(), vec![]);
     assert_eq!(tx.table::<tables::Transactions>().unwrap(), vec![]);
@@ -66,8 +69,10 @@ impl Default for BlockChainTestData {
 /// Genesis block
 pub fn genesis() -> SealedBlock {
     SealedBlock {
-        header: Header { number: 0, difficulty: U256::from(1), ..Default::default() }
-            .seal(H256::zero()),
+        header: SealedHeader::new_unchecked(
+            Header { number: 0, difficulty: U256::from(1), ..Default::default() },
+            H256::zero(),
+        ),
         body: vec![],
         ommers: vec![],
         withdrawals: Some(vec![]),
@@ -96,11 +101,13 @@ fn block1() -> (SealedBlockWithSenders, ExecutionResult) {
 
     let exec_res = ExecutionResult {
         tx_changesets: vec![TransactionChangeSet {
-            receipt: Receipt::default(), /* receipts are not saved. */
+            // receipts are not saved, the gas used matches the header.
+            receipt: Receipt { cumulative_gas_used: block.gas_used, ..Default::default() },
             changeset: BTreeMap::from([(H160([0x60; 20]), account_changeset.clone())]),
             new_bytecodes: BTreeMap::from([]),
         }],
         block_changesets: BTreeMap::from([(H160([0x61; 20]), account_changeset.account)]),
+        block_storage_changesets: BTreeMap::new(),
     };
 
     (SealedBlockWithSenders { block, senders: vec![H160([0x30; 20])] }, exec_res)
@@ -135,11 +142,13 @@ fn block2() -> (SealedBlockWithSenders, ExecutionResult) {
     };
     let exec_res = ExecutionResult {
         tx_changesets: vec![TransactionChangeSet {
-            receipt: Receipt::default(), /* receipts are not saved. */
+            // receipts are not saved, the gas used matches the header.
+            receipt: Receipt { cumulative_gas_used: block.gas_used, ..Default::default() },
             changeset: BTreeMap::from([(H160([0x60; 20]), account_changeset.clone())]),
             new_bytecodes: BTreeMap::from([]),
         }],
         block_changesets: BTreeMap::from([(H160([0x60; 20]), block_changeset)]),
+        block_storage_changesets: BTreeMap::new(),
     };
 
     (SealedBlockWithSenders { block, senders: vec![H160([0x31; 20])] }, exec_res)
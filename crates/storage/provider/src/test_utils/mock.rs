@@ -1,16 +1,24 @@
 use crate::{
-    traits::ReceiptProvider, AccountProvider, BlockHashProvider, BlockIdProvider, BlockProvider,
-    EvmEnvProvider, HeaderProvider, StateProvider, StateProviderFactory, TransactionsProvider,
+    traits::ReceiptProvider, trie::HashedPostState, AccountProvider, BlockHashProvider,
+    BlockIdProvider, BlockProvider, ChainSpecProvider, EvmEnvProvider, HeaderProvider,
+    StateProvider, StateProviderFactory, TransactionsProvider,
 };
 use parking_lot::Mutex;
 use reth_interfaces::Result;
 use reth_primitives::{
     keccak256, Account, Address, Block, BlockHash, BlockId, BlockNumber, BlockNumberOrTag,
-    Bytecode, Bytes, ChainInfo, Header, Receipt, StorageKey, StorageValue, TransactionSigned,
-    TxHash, TxNumber, H256, U256,
+    Bytecode, Bytes, ChainInfo, ChainSpec, Header, Receipt, SealedHeader, StorageKey, StorageValue,
+    TransactionMeta, TransactionSigned, TxHash, TxNumber, H256, MAINNET, U256,
 };
 use revm_primitives::{BlockEnv, CfgEnv};
-use std::{collections::HashMap, ops::RangeBounds, sync::Arc};
+use std::{
+    collections::HashMap,
+    ops::RangeBounds,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 /// A mock implementation for Provider interfaces.
 #[derive(Debug, Clone, Default)]
@@ -21,6 +29,10 @@ pub struct MockEthProvider {
     pub headers: Arc<Mutex<HashMap<H256, Header>>>,
     /// Local account store
     pub accounts: Arc<Mutex<HashMap<Address, ExtendedAccount>>>,
+    /// Local receipt store, keyed by block hash
+    pub receipts: Arc<Mutex<HashMap<H256, Vec<Receipt>>>>,
+    /// The number of chain info lookups, e.g. to assert that the tip is served from memory
+    pub chain_info_calls: Arc<AtomicUsize>,
 }
 
 /// An extended account for local store
@@ -48,6 +60,15 @@ impl ExtendedAccount {
         self.bytecode = Some(Bytecode::new_raw(bytecode.into()));
         self
     }
+
+    /// Add storage slots to the extended account, replacing the values of existing slots
+    pub fn extend_storage(
+        mut self,
+        storage: impl IntoIterator<Item = (StorageKey, StorageValue)>,
+    ) -> Self {
+        self.storage.extend(storage);
+        self
+    }
 }
 
 impl MockEthProvider {
@@ -77,6 +98,11 @@ impl MockEthProvider {
         }
     }
 
+    /// Add the receipts of a block to local receipt store
+    pub fn add_receipts(&self, block_hash: H256, receipts: Vec<Receipt>) {
+        self.receipts.lock().insert(block_hash, receipts);
+    }
+
     /// Add account to local account store
     pub fn add_account(&self, address: Address, account: ExtendedAccount) {
         self.accounts.lock().insert(address, account);
@@ -124,7 +150,30 @@ impl HeaderProvider for MockEthProvider {
         range: impl RangeBounds<reth_primitives::BlockNumber>,
     ) -> Result<Vec<Header>> {
         let lock = self.headers.lock();
-        Ok(lock.values().filter(|header| range.contains(&header.number)).cloned().collect())
+        let mut headers: Vec<_> =
+            lock.values().filter(|header| range.contains(&header.number)).cloned().collect();
+        headers.sort_by_key(|header| header.number);
+        Ok(headers)
+    }
+
+    fn sealed_header(&self, number: BlockNumber) -> Result<Option<SealedHeader>> {
+        let lock = self.headers.lock();
+        Ok(lock
+            .iter()
+            .find(|(_, header)| header.number == number)
+            .map(|(hash, header)| SealedHeader::new_unchecked(header.clone(), *hash)))
+    }
+
+    fn sealed_headers_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<SealedHeader>> {
+        let lock = self.headers.lock();
+        Ok(lock
+            .iter()
+            .filter(|(_, header)| range.contains(&header.number))
+            .map(|(hash, header)| SealedHeader::new_unchecked(header.clone(), *hash))
+            .collect())
     }
 }
 
@@ -144,6 +193,23 @@ impl TransactionsProvider for MockEthProvider {
             .find_map(|(_, block)| block.body.iter().find(|tx| tx.hash == hash).cloned()))
     }
 
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        Ok(self.blocks.lock().iter().find_map(|(block_hash, block)| {
+            block.body.iter().position(|tx| tx.hash == hash).map(|index| {
+                let meta = TransactionMeta {
+                    tx_hash: hash,
+                    index: index as u64,
+                    block_hash: *block_hash,
+                    block_number: block.number,
+                };
+                (block.body[index].clone(), meta)
+            })
+        }))
+    }
+
     fn transactions_by_block(&self, id: BlockId) -> Result<Option<Vec<TransactionSigned>>> {
         Ok(self.block(id)?.map(|b| b.body))
     }
@@ -165,8 +231,42 @@ impl ReceiptProvider for MockEthProvider {
         Ok(None)
     }
 
-    fn receipts_by_block(&self, _block: BlockId) -> Result<Option<Vec<Receipt>>> {
-        Ok(None)
+    fn receipts_by_block(&self, block: BlockId) -> Result<Option<Vec<Receipt>>> {
+        let hash = match block {
+            BlockId::Hash(hash) => hash.block_hash,
+            BlockId::Number(BlockNumberOrTag::Number(num)) => {
+                let lock = self.blocks.lock();
+                match lock.iter().find(|(_, b)| b.number == num) {
+                    Some((hash, _)) => *hash,
+                    None => return Ok(None),
+                }
+            }
+            _ => unreachable!("unused in network tests"),
+        };
+        Ok(self.receipts.lock().get(&hash).cloned())
+    }
+
+    fn receipts_by_block_aligned(&self, block: BlockId) -> Result<Option<Vec<Option<Receipt>>>> {
+        Ok(self.receipts_by_block(block)?.map(|receipts| receipts.into_iter().map(Some).collect()))
+    }
+
+    fn receipts_by_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<Vec<Receipt>>> {
+        let mut blocks = self
+            .blocks
+            .lock()
+            .iter()
+            .filter(|(_, block)| range.contains(&block.number))
+            .map(|(hash, block)| (block.number, *hash))
+            .collect::<Vec<_>>();
+        blocks.sort_unstable();
+        let receipts = self.receipts.lock();
+        Ok(blocks
+            .into_iter()
+            .map(|(_, hash)| receipts.get(&hash).cloned().unwrap_or_default())
+            .collect())
     }
 }
 
@@ -190,6 +290,7 @@ impl BlockHashProvider for MockEthProvider {
 
 impl BlockIdProvider for MockEthProvider {
     fn chain_info(&self) -> Result<ChainInfo> {
+        self.chain_info_calls.fetch_add(1, Ordering::Relaxed);
         let lock = self.headers.lock();
         Ok(lock
             .iter()
@@ -200,7 +301,7 @@ impl BlockIdProvider for MockEthProvider {
                 last_finalized: None,
                 safe_finalized: None,
             })
-            .expect("provider is empty"))
+            .unwrap_or_default())
     }
 
     fn block_number(&self, hash: H256) -> Result<Option<reth_primitives::BlockNumber>> {
@@ -260,6 +361,10 @@ impl StateProvider for MockEthProvider {
     ) -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
         todo!()
     }
+
+    fn state_root(&self, _post_state: HashedPostState) -> Result<H256> {
+        todo!()
+    }
 }
 
 impl EvmEnvProvider for MockEthProvider {
@@ -302,6 +407,12 @@ impl EvmEnvProvider for MockEthProvider {
     }
 }
 
+impl ChainSpecProvider for MockEthProvider {
+    fn chain_spec(&self) -> Arc<ChainSpec> {
+        Arc::new(MAINNET.clone())
+    }
+}
+
 impl StateProviderFactory for MockEthProvider {
     type HistorySP<'a> = &'a MockEthProvider where Self: 'a;
     type LatestSP<'a> = &'a MockEthProvider where Self: 'a;
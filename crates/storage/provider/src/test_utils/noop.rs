@@ -1,15 +1,20 @@
 use crate::{
-    traits::ReceiptProvider, AccountProvider, BlockHashProvider, BlockIdProvider, BlockProvider,
-    EvmEnvProvider, HeaderProvider, StateProvider, StateProviderFactory, TransactionsProvider,
+    traits::ReceiptProvider, trie::HashedPostState, AccountHistoryProvider, AccountProvider,
+    BlockHashProvider, BlockIdProvider, BlockProvider, ChainSpecProvider, EvmEnvProvider,
+    HeaderProvider, StateProvider, StateProviderFactory, StorageHistoryDivergence,
+    StorageHistoryVerifier, TransactionsProvider,
 };
 use reth_interfaces::Result;
 use reth_primitives::{
-    Account, Address, Block, BlockHash, BlockId, BlockNumber, Bytecode, Bytes, ChainInfo, Header,
-    Receipt, StorageKey, StorageValue, TransactionSigned, TxHash, TxNumber, H256, KECCAK_EMPTY,
-    U256,
+    proofs::EMPTY_ROOT, Account, Address, Block, BlockHash, BlockId, BlockNumber, Bytecode, Bytes,
+    ChainInfo, ChainSpec, Header, Receipt, SealedHeader, StorageKey, StorageValue, TransactionMeta,
+    TransactionSigned, TransitionId, TxHash, TxNumber, H256, KECCAK_EMPTY, MAINNET, U256,
 };
 use revm_primitives::{BlockEnv, CfgEnv};
-use std::ops::RangeBounds;
+use std::{
+    ops::{RangeBounds, RangeInclusive},
+    sync::Arc,
+};
 
 /// Supports various api interfaces for testing purposes.
 #[derive(Debug, Clone, Default, Copy)]
@@ -52,6 +57,13 @@ impl TransactionsProvider for NoopProvider {
         Ok(None)
     }
 
+    fn transaction_by_hash_with_meta(
+        &self,
+        _hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        Ok(None)
+    }
+
     fn transactions_by_block(&self, _block_id: BlockId) -> Result<Option<Vec<TransactionSigned>>> {
         Ok(None)
     }
@@ -76,6 +88,17 @@ impl ReceiptProvider for NoopProvider {
     fn receipts_by_block(&self, _block: BlockId) -> Result<Option<Vec<Receipt>>> {
         Ok(None)
     }
+
+    fn receipts_by_block_aligned(&self, _block: BlockId) -> Result<Option<Vec<Option<Receipt>>>> {
+        Ok(None)
+    }
+
+    fn receipts_by_block_range(
+        &self,
+        _range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<Vec<Receipt>>> {
+        Ok(Vec::default())
+    }
 }
 
 impl HeaderProvider for NoopProvider {
@@ -98,6 +121,17 @@ impl HeaderProvider for NoopProvider {
     fn headers_range(&self, _range: impl RangeBounds<BlockNumber>) -> Result<Vec<Header>> {
         Ok(vec![])
     }
+
+    fn sealed_header(&self, _number: BlockNumber) -> Result<Option<SealedHeader>> {
+        Ok(None)
+    }
+
+    fn sealed_headers_range(
+        &self,
+        _range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<SealedHeader>> {
+        Ok(vec![])
+    }
 }
 
 impl AccountProvider for NoopProvider {
@@ -122,6 +156,10 @@ impl StateProvider for NoopProvider {
     ) -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
         Ok((vec![], KECCAK_EMPTY, vec![]))
     }
+
+    fn state_root(&self, _post_state: HashedPostState) -> Result<H256> {
+        Ok(EMPTY_ROOT)
+    }
 }
 
 impl EvmEnvProvider for NoopProvider {
@@ -164,6 +202,44 @@ impl EvmEnvProvider for NoopProvider {
     }
 }
 
+impl ChainSpecProvider for NoopProvider {
+    fn chain_spec(&self) -> Arc<ChainSpec> {
+        Arc::new(MAINNET.clone())
+    }
+}
+
+impl AccountHistoryProvider for NoopProvider {
+    fn account_transitions(&self, _address: Address) -> Result<Vec<TransitionId>> {
+        Ok(Vec::new())
+    }
+
+    fn account_before_transition(
+        &self,
+        _address: Address,
+        _transition: TransitionId,
+    ) -> Result<Option<Account>> {
+        Ok(None)
+    }
+
+    fn transition_location(
+        &self,
+        _transition: TransitionId,
+    ) -> Result<Option<(BlockNumber, Option<TxNumber>)>> {
+        Ok(None)
+    }
+}
+
+impl StorageHistoryVerifier for NoopProvider {
+    fn verify_storage_history(
+        &self,
+        _address: Address,
+        _storage_key: H256,
+        _range: RangeInclusive<BlockNumber>,
+    ) -> Result<Option<StorageHistoryDivergence>> {
+        Ok(None)
+    }
+}
+
 impl StateProviderFactory for NoopProvider {
     type HistorySP<'a> = NoopProvider where Self: 'a;
     type LatestSP<'a> = NoopProvider where Self: 'a;
@@ -7,4 +7,12 @@ use reth_primitives::{Account, Address};
 pub trait AccountProvider: Send + Sync {
     /// Get basic account information.
     fn basic_account(&self, address: Address) -> Result<Option<Account>>;
+
+    /// Get basic account information of multiple accounts, in the order of the given addresses.
+    ///
+    /// Providers backed by the database read all accounts with one cursor, which is fastest if
+    /// the addresses are sorted.
+    fn basic_accounts(&self, addresses: &[Address]) -> Result<Vec<Option<Account>>> {
+        addresses.iter().map(|address| self.basic_account(*address)).collect()
+    }
 }
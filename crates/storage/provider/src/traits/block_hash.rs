@@ -1,6 +1,6 @@
 use auto_impl::auto_impl;
-use reth_interfaces::Result;
-use reth_primitives::{H256, U256};
+use reth_interfaces::{provider::ProviderError, Result};
+use reth_primitives::{BlockNumber, H256, U256};
 
 /// Client trait for fetching block hashes by number.
 #[auto_impl(&, Arc, Box)]
@@ -8,4 +8,17 @@ pub trait BlockHashProvider: Send + Sync {
     /// Get the hash of the block with the given number. Returns `None` if no block with this number
     /// exists.
     fn block_hash(&self, number: U256) -> Result<Option<H256>>;
+
+    /// Get the canonical hashes of the blocks in the inclusive range `start..=end`, ordered by
+    /// block number.
+    ///
+    /// Returns [ProviderError::CanonicalHeader] for the first block in the range without a hash.
+    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>> {
+        (start..=end)
+            .map(|block_number| {
+                self.block_hash(U256::from(block_number))?
+                    .ok_or_else(|| ProviderError::CanonicalHeader { block_number }.into())
+            })
+            .collect()
+    }
 }
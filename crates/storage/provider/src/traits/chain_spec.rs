@@ -0,0 +1,10 @@
+use auto_impl::auto_impl;
+use reth_primitives::ChainSpec;
+use std::sync::Arc;
+
+/// Client trait for accessing the chain spec the node runs with.
+#[auto_impl(&, Arc)]
+pub trait ChainSpecProvider: Send + Sync {
+    /// Get the [ChainSpec] of the chain.
+    fn chain_spec(&self) -> Arc<ChainSpec>;
+}
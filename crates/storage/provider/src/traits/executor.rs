@@ -1,6 +1,6 @@
 //! Executor Factory
 
-use crate::{execution_result::ExecutionResult, StateProvider};
+use crate::{execution_result::ExecutionResult, trie::PrefetchHandle, StateProvider};
 use reth_interfaces::executor::Error;
 use reth_primitives::{Address, Block, ChainSpec, U256};
 
@@ -14,6 +14,19 @@ pub trait ExecutorFactory: Send + Sync + 'static {
     /// Executor with [`StateProvider`]
     fn with_sp<SP: StateProvider>(&self, sp: SP) -> Self::Executor<SP>;
 
+    /// Executor with [`StateProvider`] that sends the keys touched during execution to the trie
+    /// prefetcher.
+    ///
+    /// Executors that don't support prefetching ignore the handle.
+    fn with_sp_and_prefetcher<SP: StateProvider>(
+        &self,
+        sp: SP,
+        prefetch: PrefetchHandle,
+    ) -> Self::Executor<SP> {
+        let _ = prefetch;
+        self.with_sp(sp)
+    }
+
     /// Return internal chainspec
     fn chain_spec(&self) -> &ChainSpec;
 }
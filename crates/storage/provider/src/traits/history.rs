@@ -0,0 +1,45 @@
+use crate::StorageHistoryDivergence;
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::{Account, Address, BlockNumber, TransitionId, TxNumber, H256};
+use std::ops::RangeInclusive;
+
+/// Client trait for reading the account history indices.
+#[auto_impl(&, Arc)]
+pub trait AccountHistoryProvider: Send + Sync {
+    /// Get the transitions that changed the account, in ascending order.
+    fn account_transitions(&self, address: Address) -> Result<Vec<TransitionId>>;
+
+    /// Get the account as it was before the given transition changed it.
+    ///
+    /// Returns `None` if the account did not exist before the transition or if the transition
+    /// did not change the account.
+    fn account_before_transition(
+        &self,
+        address: Address,
+        transition: TransitionId,
+    ) -> Result<Option<Account>>;
+
+    /// Get the block of the transition and the number of the transaction it executed.
+    ///
+    /// The transaction number is `None` for the block reward transition at the end of a block.
+    /// Returns `None` if the transition was not executed yet.
+    fn transition_location(
+        &self,
+        transition: TransitionId,
+    ) -> Result<Option<(BlockNumber, Option<TxNumber>)>>;
+}
+
+/// Client trait for checking the storage history against the storage changesets.
+#[auto_impl(&, Arc)]
+pub trait StorageHistoryVerifier: Send + Sync {
+    /// Returns the first block of the range after which the historical value of the storage slot
+    /// differs from the value replayed from the storage changesets, see
+    /// [verify_storage_history](crate::verify_storage_history).
+    fn verify_storage_history(
+        &self,
+        address: Address,
+        storage_key: H256,
+        range: RangeInclusive<BlockNumber>,
+    ) -> Result<Option<StorageHistoryDivergence>>;
+}
@@ -12,12 +12,18 @@ pub use block_hash::BlockHashProvider;
 mod block_id;
 pub use block_id::BlockIdProvider;
 
+mod chain_spec;
+pub use chain_spec::ChainSpecProvider;
+
 mod evm_env;
 pub use evm_env::EvmEnvProvider;
 
 mod header;
 pub use header::HeaderProvider;
 
+mod history;
+pub use history::{AccountHistoryProvider, StorageHistoryVerifier};
+
 mod receipts;
 pub use receipts::ReceiptProvider;
 
@@ -1,5 +1,6 @@
 use reth_interfaces::Result;
-use reth_primitives::{BlockId, Receipt, TxHash, TxNumber};
+use reth_primitives::{BlockId, BlockNumber, Receipt, TxHash, TxNumber};
+use std::ops::RangeBounds;
 
 ///  Client trait for fetching [Receipt] data .
 #[auto_impl::auto_impl(&, Arc)]
@@ -11,5 +12,19 @@ pub trait ReceiptProvider: Send + Sync {
     fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>>;
 
     /// Get receipts by block id.
+    ///
+    /// The receipts of pruned transactions are left out, see [Self::receipts_by_block_aligned].
     fn receipts_by_block(&self, block: BlockId) -> Result<Option<Vec<Receipt>>>;
+
+    /// Get the receipts of a block by block id, with `None` in place of the receipts that were
+    /// pruned, so the receipt of a transaction is at the index of the transaction in the block.
+    fn receipts_by_block_aligned(&self, block: BlockId) -> Result<Option<Vec<Option<Receipt>>>>;
+
+    /// Get receipts by block range.
+    ///
+    /// Returns the receipts of every block in the range, blocks without transactions included.
+    fn receipts_by_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<Vec<Receipt>>>;
 }
@@ -1,11 +1,16 @@
 use super::AccountProvider;
-use crate::BlockHashProvider;
+use crate::{
+    trie::{HashedPostState, MultiProof, TrieNodeCache},
+    BlockHashProvider, ProviderError,
+};
 use auto_impl::auto_impl;
 use reth_interfaces::Result;
 use reth_primitives::{
+    constants::{HISTORY_SERVE_WINDOW, HISTORY_STORAGE_ADDRESS},
     Address, BlockHash, BlockNumber, Bytecode, Bytes, StorageKey, StorageValue, H256, KECCAK_EMPTY,
     U256,
 };
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 /// An abstraction for a type that provides state data.
 #[auto_impl(&, Box)]
@@ -20,6 +25,51 @@ pub trait StateProvider: BlockHashProvider + AccountProvider + Send + Sync {
     fn proof(&self, address: Address, keys: &[H256])
         -> Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)>;
 
+    /// Returns the state root of this state with the given changes applied on top.
+    fn state_root(&self, post_state: HashedPostState) -> Result<H256>;
+
+    /// Returns the state root like [StateProvider::state_root], looking up the trie nodes in the
+    /// cache of prefetched nodes first.
+    ///
+    /// Providers that don't read the trie from the database ignore the cache.
+    fn state_root_with_cache(
+        &self,
+        post_state: HashedPostState,
+        cache: &TrieNodeCache,
+    ) -> Result<H256> {
+        let _ = cache;
+        self.state_root(post_state)
+    }
+
+    /// Returns the trie nodes of this state that are needed to read the targets and to compute the
+    /// state root with the given changes applied on top, see
+    /// [StateRoot::witness](crate::trie::StateRoot::witness).
+    ///
+    /// The targets are hashed addresses with the hashed slots of each account. Providers that don't
+    /// read the trie from the database can't collect witnesses.
+    fn witness(
+        &self,
+        targets: &BTreeMap<H256, BTreeSet<H256>>,
+        post_state: HashedPostState,
+    ) -> Result<Vec<Bytes>> {
+        let _ = (targets, post_state);
+        Err(ProviderError::StateTrie.into())
+    }
+
+    /// Returns a proof of all the given accounts and the given slots of each account, see
+    /// [MultiProof].
+    ///
+    /// The trie is walked once for all targets, nodes shared by their paths are only included once.
+    /// Like [StateProvider::witness], this requires a provider that reads the trie from the
+    /// database.
+    fn multiproof(&self, targets: &HashMap<Address, Vec<H256>>) -> Result<MultiProof> {
+        let nodes =
+            self.witness(&MultiProof::hashed_targets(targets), HashedPostState::default())?;
+        let state_root = self.state_root(HashedPostState::default())?;
+        MultiProof::from_nodes(state_root, nodes, targets)
+            .map_err(|_| ProviderError::StateTrie.into())
+    }
+
     /// Get account code by its address.
     ///
     /// Returns `None` if the account doesn't exist or account is not a contract
@@ -66,6 +116,21 @@ pub trait StateProvider: BlockHashProvider + AccountProvider + Send + Sync {
             None => Ok(None),
         }
     }
+
+    /// Get the hash of a block from the history contract of
+    /// [EIP-2935](https://eips.ethereum.org/EIPS/eip-2935), which keeps the hashes beyond the
+    /// window of the `BLOCKHASH` opcode.
+    ///
+    /// `head` is the number of the block this state is at. Returns `None` if the block is not
+    /// within the window of the contract or its hash was not written, e.g. before Prague.
+    fn history_block_hash(&self, number: BlockNumber, head: BlockNumber) -> Result<Option<H256>> {
+        if number >= head || head - number > HISTORY_SERVE_WINDOW {
+            return Ok(None)
+        }
+        let slot = H256::from_low_u64_be(number % HISTORY_SERVE_WINDOW);
+        let hash = self.storage(HISTORY_STORAGE_ADDRESS, slot)?.unwrap_or_default();
+        Ok((hash != U256::ZERO).then(|| H256(hash.to_be_bytes())))
+    }
 }
 
 /// Light wrapper that returns `StateProvider` implementations that correspond to the given
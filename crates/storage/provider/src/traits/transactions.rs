@@ -1,6 +1,6 @@
 use crate::BlockIdProvider;
 use reth_interfaces::Result;
-use reth_primitives::{BlockId, BlockNumber, TransactionSigned, TxHash, TxNumber};
+use reth_primitives::{BlockId, BlockNumber, TransactionMeta, TransactionSigned, TxHash, TxNumber};
 use std::ops::RangeBounds;
 
 ///  Client trait for fetching [TransactionSigned] related data.
@@ -12,6 +12,12 @@ pub trait TransactionsProvider: BlockIdProvider + Send + Sync {
     /// Get transaction by transaction hash.
     fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>>;
 
+    /// Get transaction by transaction hash, with the block it is included in.
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>>;
+
     /// Get transactions by block id.
     fn transactions_by_block(&self, block: BlockId) -> Result<Option<Vec<TransactionSigned>>>;
 
@@ -4,6 +4,9 @@ use reth_primitives::{BlockId, Withdrawal};
 ///  Client trait for fetching [Withdrawal] related data.
 pub trait WithdrawalsProvider: Send + Sync {
     /// Get withdrawals by block id.
+    ///
+    /// Returns `None` for blocks before Shanghai and an empty list for Shanghai blocks without
+    /// withdrawals.
     fn withdrawals_by_block(&self, id: BlockId, timestamp: u64) -> Result<Option<Vec<Withdrawal>>>;
 
     /// Get latest withdrawal from this block or earlier .
@@ -6,7 +6,7 @@ use reth_db::{
     models::{
         sharded_key,
         storage_sharded_key::{self, StorageShardedKey},
-        ShardedKey, StoredBlockBody, TransitionIdAddress,
+        ShardedKey, StoredBlockBodyIndices, TransitionIdAddress,
     },
     table::Table,
     tables,
@@ -16,8 +16,8 @@ use reth_db::{
 use reth_interfaces::{db::Error as DbError, provider::ProviderError};
 use reth_primitives::{
     keccak256, proofs::EMPTY_ROOT, Account, Address, BlockHash, BlockNumber, Bytecode, ChainSpec,
-    Hardfork, Header, Receipt, SealedBlock, SealedBlockWithSenders, StorageEntry,
-    TransactionSignedEcRecovered, TransitionId, TxNumber, H256, U256,
+    Hardfork, Header, PruneModes, Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader,
+    StorageEntry, TransactionSignedEcRecovered, TransitionId, TxNumber, H256, U256,
 };
 use reth_tracing::tracing::{info, trace};
 use std::{
@@ -30,6 +30,7 @@ use crate::{
     execution_result::{AccountInfoChangeSet, TransactionChangeSet},
     insert_canonical_block,
     trie::{DBTrieLoader, TrieError},
+    utils::{get_receipts_prune_checkpoint, set_receipts_prune_checkpoint, LOWEST_AVAILABLE_BLOCK},
 };
 
 use crate::execution_result::{AccountChangeSet, ExecutionResult};
@@ -46,6 +47,8 @@ pub struct Transaction<'this, DB: Database> {
     /// A handle to the DB.
     pub(crate) db: &'this DB,
     tx: Option<<DB as DatabaseGAT<'this>>::TXMut>,
+    /// Whether commits are suppressed, see [Transaction::new_dry_run].
+    dry_run: bool,
 }
 
 impl<'a, DB: Database> Debug for Transaction<'a, DB> {
@@ -88,12 +91,25 @@ where
     ///
     /// A new inner transaction will be opened.
     pub fn new(db: &'this DB) -> Result<Self, DbError> {
-        Ok(Self { db, tx: Some(db.tx_mut()?) })
+        Ok(Self { db, tx: Some(db.tx_mut()?), dry_run: false })
+    }
+
+    /// Create a new container whose changes are never persisted.
+    ///
+    /// [Transaction::commit] keeps the current inner transaction open instead of committing it,
+    /// so everything written through this container is discarded once it is dropped.
+    pub fn new_dry_run(db: &'this DB) -> Result<Self, DbError> {
+        Ok(Self { db, tx: Some(db.tx_mut()?), dry_run: true })
     }
 
     /// Creates a new container with given database and transaction handles.
     pub fn new_raw(db: &'this DB, tx: <DB as DatabaseGAT<'this>>::TXMut) -> Self {
-        Self { db, tx: Some(tx) }
+        Self { db, tx: Some(tx), dry_run: false }
+    }
+
+    /// Returns `true` if this container never commits, see [Transaction::new_dry_run].
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
     }
 
     /// Accessor to the internal Database
@@ -108,6 +124,10 @@ where
     /// Panics if an inner transaction does not exist. This should never be the case unless
     /// [Transaction::close] was called without following up with a call to [Transaction::open].
     pub fn commit(&mut self) -> Result<bool, DbError> {
+        if self.dry_run {
+            trace!(target: "provider::transaction", "Skipping commit of dry run transaction");
+            return Ok(false)
+        }
         let success = if let Some(tx) = self.tx.take() { tx.commit()? } else { false };
         self.tx = Some(self.db.tx_mut()?);
         Ok(success)
@@ -144,9 +164,13 @@ where
     }
 
     /// Query the block body by number.
-    pub fn get_block_body(&self, number: BlockNumber) -> Result<StoredBlockBody, TransactionError> {
-        let body =
-            self.get::<tables::BlockBodies>(number)?.ok_or(ProviderError::BlockBody { number })?;
+    pub fn get_block_body(
+        &self,
+        number: BlockNumber,
+    ) -> Result<StoredBlockBodyIndices, TransactionError> {
+        let body = self
+            .get::<tables::BlockBodyIndices>(number)?
+            .ok_or(ProviderError::BlockBody { number })?;
         Ok(body)
     }
 
@@ -173,7 +197,16 @@ where
         let last_transition = self
             .get::<tables::BlockTransitionIndex>(prev_number)?
             .ok_or(ProviderError::BlockTransition { block_number: prev_number })?;
-        Ok((prev_body.start_tx_id + prev_body.tx_count, last_transition))
+        Ok((prev_body.next_tx_num(), last_transition))
+    }
+
+    /// Get the index of the first withdrawal of the `block` by looking at the previous block.
+    /// Returns Zero for Genesis.
+    pub fn get_next_withdrawal_index(&self, block: BlockNumber) -> Result<u64, TransactionError> {
+        match block.checked_sub(1) {
+            Some(prev_number) => Ok(self.get_block_body(prev_number)?.next_withdrawal_index()),
+            None => Ok(0),
+        }
     }
 
     /// Query the block header by number
@@ -523,18 +556,48 @@ where
         chain_spec: &ChainSpec,
         changeset: ExecutionResult,
     ) -> Result<(), TransactionError> {
-        // Header, Body, SenderRecovery, TD, TxLookup stages
-        let (block, senders) = block.into_components();
-        let block_number = block.number;
-        let block_state_root = block.state_root;
-        let block_hash = block.hash();
-        let parent_block_number = block.number.saturating_sub(1);
+        self.insert_blocks(vec![(block, changeset)], chain_spec)
+    }
 
-        let (from, to) =
-            insert_canonical_block(self.deref_mut(), block, Some(senders), false).unwrap();
+    /// Insert already executed consecutive blocks and make them canonical.
+    ///
+    /// The execution results of all blocks are written at once and the state trie is updated a
+    /// single time for the whole range, so only the state root of the last block is checked. The
+    /// blocks are expected to be verified already, e.g. by the blockchain tree.
+    pub fn insert_blocks(
+        &mut self,
+        blocks: Vec<(SealedBlockWithSenders, ExecutionResult)>,
+        chain_spec: &ChainSpec,
+    ) -> Result<(), TransactionError> {
+        let Some((first, _)) = blocks.first() else { return Ok(()) };
+        let parent_block_number = first.number.saturating_sub(1);
+        let (tip, _) = blocks.last().expect("not empty");
+        let (tip_number, tip_state_root, tip_hash) = (tip.number, tip.state_root, tip.hash());
+
+        // Header, Body, SenderRecovery, TD, TxLookup stages
+        let mut transitions: Option<(TransitionId, TransitionId)> = None;
+        let mut changesets = Vec::with_capacity(blocks.len());
+        for (block, changeset) in blocks {
+            let (block, senders) = block.into_components();
+            // the write to the history contract of EIP-2935 is stored in the transition of the
+            // block
+            let has_block_hash_history =
+                chain_spec.fork(Hardfork::Prague).active_at_timestamp(block.timestamp);
+
+            let (from, to) = insert_canonical_block(
+                self.deref_mut(),
+                block,
+                Some(senders),
+                has_block_hash_history,
+            )
+            .unwrap();
+            transitions = Some((transitions.map_or(from, |(from, _)| from), to));
+            changesets.push(changeset);
+        }
+        let (from, to) = transitions.expect("at least one block");
 
         // execution stage
-        self.insert_execution_result(vec![changeset], chain_spec, parent_block_number)?;
+        self.insert_execution_result(changesets, chain_spec, parent_block_number)?;
 
         // storage hashing stage
         {
@@ -555,12 +618,12 @@ where
             let current_root = self.get_header(parent_block_number)?.state_root;
             let mut loader = DBTrieLoader::new(self.deref_mut());
             let root = loader.update_root(current_root, from..to).and_then(|e| e.root())?;
-            if root != block_state_root {
+            if root != tip_state_root {
                 return Err(TransactionError::StateTrieRootMismatch {
                     got: root,
-                    expected: block_state_root,
-                    block_number,
-                    block_hash,
+                    expected: tip_state_root,
+                    block_number: tip_number,
+                    block_hash: tip_hash,
                 })
             }
         }
@@ -608,16 +671,15 @@ where
         range: impl RangeBounds<BlockNumber> + Clone,
     ) -> Result<Vec<(BlockNumber, Vec<TransactionSignedEcRecovered>)>, TransactionError> {
         // Just read block tx id from table. as it is needed to get execution results.
-        let block_bodies = self.get_or_take::<tables::BlockBodies, false>(range)?;
+        let block_bodies = self.get_or_take::<tables::BlockBodyIndices, false>(range)?;
 
         if block_bodies.is_empty() {
             return Ok(Vec::new())
         }
 
         // iterate over and get all transaction and signers
-        let first_transaction =
-            block_bodies.first().expect("If we have headers").1.first_tx_index();
-        let last_transaction = block_bodies.last().expect("Not empty").1.last_tx_index();
+        let first_transaction = block_bodies.first().expect("If we have headers").1.first_tx_num();
+        let last_transaction = block_bodies.last().expect("Not empty").1.last_tx_num();
 
         let transactions =
             self.get_or_take::<tables::Transactions, TAKE>(first_transaction..=last_transaction)?;
@@ -625,10 +687,10 @@ where
             self.get_or_take::<tables::TxSenders, TAKE>(first_transaction..=last_transaction)?;
 
         if TAKE {
-            // rm TxHashNumber
+            // rm TxHashNumber, unless the hash points to a transaction that stays canonical
             let mut tx_hash_cursor = self.cursor_write::<tables::TxHashNumber>()?;
-            for (_, tx) in transactions.iter() {
-                if tx_hash_cursor.seek_exact(tx.hash())?.is_some() {
+            for (tx_number, tx) in transactions.iter() {
+                if tx_hash_cursor.seek_exact(tx.hash())?.map_or(false, |(_, n)| n == *tx_number) {
                     tx_hash_cursor.delete_current()?;
                 }
             }
@@ -636,6 +698,8 @@ where
             self.get_or_take::<tables::TxTransitionIndex, TAKE>(
                 first_transaction..=last_transaction,
             )?;
+            // rm Receipts, the transaction numbers are reused by the new canonical blocks
+            self.get_or_take::<tables::Receipts, TAKE>(first_transaction..=last_transaction)?;
         }
 
         // Merge transaction into blocks
@@ -644,7 +708,7 @@ where
         let mut transactions = transactions.into_iter();
         for (block_number, block_body) in block_bodies {
             let mut one_block_tx = Vec::new();
-            for _ in block_body.tx_id_range() {
+            for _ in block_body.tx_num_range() {
                 let tx = transactions.next();
                 let sender = senders.next();
 
@@ -719,7 +783,7 @@ where
             block_header_hashes_iter.into_iter(),
             block_tx_iter.into_iter()
         ) {
-            let header = header.seal(header_hash);
+            let header = SealedHeader::new_unchecked(header, header_hash);
 
             let (body, senders) = tx.into_iter().map(|tx| tx.to_components()).unzip();
 
@@ -785,7 +849,7 @@ where
 
         // NOTE: Just get block bodies dont remove them
         // it is connection point for bodies getter and execution result getter.
-        let block_bodies = self.get_or_take::<tables::BlockBodies, false>(range)?;
+        let block_bodies = self.get_or_take::<tables::BlockBodyIndices, false>(range)?;
 
         // get saved previous values
         let from_storage: TransitionIdAddress = (from, Address::zero()).into();
@@ -949,18 +1013,29 @@ where
                 });
             }
 
-            let Some((_,block_transition)) = block_transition_iter.next() else { break};
+            let Some((_, block_transition)) = block_transition_iter.next() else { break };
             // if block transition points to 1+next transition id it means that there is block
             // changeset.
             if block_transition == next_transition_id + 1 {
                 // assert last_transition_id == block_transition
                 if next_transition_id == next_changeset.0 {
                     // take block changeset
-                    block_exec_res.block_changesets = next_changeset
-                        .1
-                        .into_iter()
-                        .map(|(address, (account, _))| (address, account))
-                        .collect();
+                    for (address, (account, storage)) in next_changeset.1 {
+                        if !storage.is_empty() {
+                            block_exec_res.block_storage_changesets.insert(
+                                address,
+                                storage
+                                    .into_iter()
+                                    .map(|(key, val)| (U256::from_be_bytes(key.0), val))
+                                    .collect(),
+                            );
+                            // only the storage of the account changed
+                            if account == AccountInfoChangeSet::default() {
+                                continue
+                            }
+                        }
+                        block_exec_res.block_changesets.insert(address, account);
+                    }
                     next_changeset = changeset_iter.next().unwrap_or_default();
                 }
                 next_transition_id += 1;
@@ -1041,7 +1116,7 @@ where
         // that is why it is deleted afterwards.
         if TAKE {
             // rm block bodies
-            self.get_or_take::<tables::BlockBodies, TAKE>(range)?;
+            self.get_or_take::<tables::BlockBodyIndices, TAKE>(range)?;
         }
 
         // return them
@@ -1049,6 +1124,8 @@ where
     }
 
     /// Update all pipeline sync stage progress.
+    ///
+    /// The lowest available block is kept, it's not a stage.
     pub fn update_pipeline_stages(
         &self,
         block_number: BlockNumber,
@@ -1056,6 +1133,9 @@ where
         // iterate over
         let mut cursor = self.cursor_write::<tables::SyncStage>()?;
         while let Some((stage_name, _)) = cursor.next()? {
+            if stage_name == LOWEST_AVAILABLE_BLOCK {
+                continue
+            }
             cursor.upsert(stage_name, block_number)?
         }
 
@@ -1444,7 +1524,8 @@ where
                 current_transition_id += 1;
             }
 
-            let have_block_changeset = !results.block_changesets.is_empty();
+            let have_block_changeset = !results.block_changesets.is_empty() ||
+                !results.block_storage_changesets.is_empty();
 
             // If there are any post block changes, we will add account changesets to db.
             for (address, changeset) in results.block_changesets.into_iter() {
@@ -1457,6 +1538,26 @@ where
                 )?;
             }
 
+            // Storage changes of the block, e.g. the parent hash written to the history contract.
+            let mut cursor_storage_changeset = self.cursor_write::<tables::StorageChangeSet>()?;
+            for (address, storage) in results.block_storage_changesets.into_iter() {
+                let storage_id = TransitionIdAddress((current_transition_id, address));
+                for (key, (old_value, new_value)) in storage {
+                    let key = H256(key.to_be_bytes());
+                    trace!(target: "sync::stages::execution", ?address, current_transition_id, ?key, ?old_value, ?new_value, "Applying block storage changeset");
+                    let old_entry = StorageEntry { key, value: old_value };
+                    cursor_storage_changeset.append(storage_id, old_entry)?;
+
+                    self.delete::<tables::PlainStorageState>(address, Some(old_entry))?;
+                    if new_value != U256::ZERO {
+                        self.put::<tables::PlainStorageState>(
+                            address,
+                            StorageEntry { key, value: new_value },
+                        )?;
+                    }
+                }
+            }
+
             // Transition is incremeneted every time before Paris hardfork and after
             // Shanghai only if there are Withdrawals in the block. So it is correct to
             // to increment transition id every time there is a block changeset present.
@@ -1467,6 +1568,56 @@ where
         Ok(())
     }
 
+    /// Delete the receipts pruned by the prune modes with the given tip.
+    ///
+    /// Receipts with logs from an address of the log filter are kept as long as the mode of the
+    /// address keeps their block. Only the blocks after the
+    /// [receipts prune checkpoint](crate::RECEIPTS_PRUNE_CHECKPOINT) are visited, which is advanced
+    /// to the highest block of which all receipts are pruned. Returns the number of deleted
+    /// receipts.
+    pub fn prune_receipts(
+        &self,
+        tip: BlockNumber,
+        modes: &PruneModes,
+    ) -> Result<usize, TransactionError> {
+        let Some(target) = modes.receipts.and_then(|mode| mode.prune_target_block(tip)) else {
+            return Ok(0)
+        };
+        let checkpoint = get_receipts_prune_checkpoint(self.deref())?;
+        let start = checkpoint.map_or(0, |checkpoint| checkpoint + 1);
+        if start > target {
+            return Ok(0)
+        }
+
+        let mut bodies = self.cursor_read::<tables::BlockBodyIndices>()?;
+        let mut receipts = self.cursor_write::<tables::Receipts>()?;
+        let mut deleted = 0;
+        for entry in bodies.walk_range(start..=target)? {
+            let (block_number, body) = entry?;
+            for tx_number in body.tx_num_range() {
+                let Some((_, receipt)) = receipts.seek_exact(tx_number)? else { continue };
+                let log_addresses = receipt.logs.iter().map(|log| &log.address);
+                if modes.receipts_log_filter.retains(log_addresses, block_number, tip) {
+                    continue
+                }
+                receipts.delete_current()?;
+                deleted += 1;
+            }
+        }
+
+        // receipts kept by the log filter are visited again until their addresses prune them
+        let complete =
+            modes.receipts_log_filter.0.values().try_fold(target, |complete, mode| {
+                Some(complete.min(mode.prune_target_block(tip)?))
+            });
+        if let Some(complete) = complete.filter(|complete| Some(*complete) > checkpoint) {
+            set_receipts_prune_checkpoint(self.deref(), complete)?;
+        }
+
+        info!(target: "provider::prune", tip, target, deleted, ?complete, "Pruned receipts");
+        Ok(deleted)
+    }
+
     /// Return full table as Vec
     pub fn table<T: Table>(&self) -> Result<Vec<KeyValue<T>>, DbError>
     where
@@ -1580,10 +1731,32 @@ pub enum TransactionError {
 
 #[cfg(test)]
 mod test {
-    use crate::{insert_canonical_block, test_utils::blocks::*, Transaction};
-    use reth_db::{mdbx::test_utils::create_test_rw_db, tables, transaction::DbTxMut};
-    use reth_primitives::{proofs::EMPTY_ROOT, ChainSpecBuilder, MAINNET};
-    use std::ops::DerefMut;
+    use super::TransactionError;
+    use crate::{
+        execution_result::{
+            AccountChangeSet, AccountInfoChangeSet, ExecutionResult, TransactionChangeSet,
+        },
+        get_receipts_prune_checkpoint, insert_canonical_block,
+        test_utils::blocks::*,
+        Transaction,
+    };
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_rw_db, Env, WriteMap},
+        models::StoredBlockBodyIndices,
+        table::{Compress, Encode, Table},
+        tables,
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{
+        proofs::EMPTY_ROOT, Account, Address, ChainSpec, ChainSpecBuilder, Log, PruneMode,
+        PruneModes, Receipt, ReceiptsLogPruneConfig, SealedBlockWithSenders, H160, MAINNET, U256,
+    };
+    use std::{
+        collections::BTreeMap,
+        ops::{Deref, DerefMut},
+        sync::Arc,
+    };
 
     #[test]
     fn insert_get_take() {
@@ -1642,4 +1815,219 @@ mod test {
         // assert genesis state
         assert_genesis_block(&tx, genesis);
     }
+
+    #[test]
+    fn prune_receipts() {
+        let db = create_test_rw_db();
+        let tx = Transaction::new(db.as_ref()).unwrap();
+
+        // every block has a transaction with a log of each contract
+        let (retained, pruned) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        for block_number in 0..5 {
+            let first_tx_num = block_number * 2;
+            tx.put::<tables::BlockBodyIndices>(
+                block_number,
+                StoredBlockBodyIndices { first_tx_num, tx_count: 2, ..Default::default() },
+            )
+            .unwrap();
+            for (tx_number, address) in [(first_tx_num, retained), (first_tx_num + 1, pruned)] {
+                let receipt = Receipt {
+                    logs: vec![Log { address, ..Default::default() }],
+                    ..Default::default()
+                };
+                tx.put::<tables::Receipts>(tx_number, receipt).unwrap();
+            }
+        }
+
+        let modes = PruneModes {
+            receipts: Some(PruneMode::Distance(1)),
+            receipts_log_filter: ReceiptsLogPruneConfig(BTreeMap::from([
+                (retained, PruneMode::Distance(3)),
+                (pruned, PruneMode::Full),
+            ])),
+        };
+        assert_eq!(tx.prune_receipts(4, &modes).unwrap(), 4);
+
+        // the receipts of the last two blocks are kept, the receipts of the retained contract
+        // are kept for two more blocks
+        let remaining = tx
+            .table::<tables::Receipts>()
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        assert_eq!(remaining, vec![2, 4, 6, 7, 8, 9]);
+
+        // the receipts of the retained contract in block 1 and 2 are not pruned yet
+        assert_eq!(get_receipts_prune_checkpoint(tx.deref()).unwrap(), Some(0));
+
+        // nothing is left to prune at the same tip
+        assert_eq!(tx.prune_receipts(4, &modes).unwrap(), 0);
+        assert_eq!(tx.prune_receipts(4, &PruneModes::default()).unwrap(), 0);
+
+        // the receipts of the retained contract are pruned up to block 2 at tip 6
+        assert_eq!(tx.prune_receipts(6, &modes).unwrap(), 4);
+        let remaining = tx
+            .table::<tables::Receipts>()
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        assert_eq!(remaining, vec![6, 8]);
+        assert_eq!(get_receipts_prune_checkpoint(tx.deref()).unwrap(), Some(2));
+    }
+
+    /// Creates a database with the genesis block of [BlockChainTestData].
+    fn genesis_db() -> Arc<Env<WriteMap>> {
+        let db = create_test_rw_db();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        insert_canonical_block(tx.deref_mut(), genesis(), None, false).unwrap();
+        tx.put::<tables::AccountsTrie>(EMPTY_ROOT, vec![0x80]).unwrap();
+        tx.commit().unwrap();
+        db
+    }
+
+    /// Builds `count` executed blocks on top of the genesis, each updating the account and a new
+    /// storage slot of the same contract.
+    fn executed_chain(
+        chain_spec: &ChainSpec,
+        count: u64,
+    ) -> Vec<(SealedBlockWithSenders, ExecutionResult)> {
+        let db = genesis_db();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        let (template, _) = BlockChainTestData::default().blocks[0].clone();
+        let address = H160([0x60; 20]);
+
+        let mut parent_hash = genesis().hash();
+        let mut blocks = Vec::new();
+        for number in 1..=count {
+            let new =
+                Account { nonce: number, balance: U256::from(10 * number), bytecode_hash: None };
+            let account = if number == 1 {
+                AccountInfoChangeSet::Created { new }
+            } else {
+                let old = Account {
+                    nonce: number - 1,
+                    balance: U256::from(10 * (number - 1)),
+                    bytecode_hash: None,
+                };
+                AccountInfoChangeSet::Changed { old, new }
+            };
+            let mut changeset = AccountChangeSet { account, ..Default::default() };
+            changeset.storage.insert(U256::from(number), (U256::ZERO, U256::from(number)));
+            let exec_res = ExecutionResult {
+                tx_changesets: vec![TransactionChangeSet {
+                    receipt: Receipt {
+                        cumulative_gas_used: template.gas_used,
+                        ..Default::default()
+                    },
+                    changeset: BTreeMap::from([(address, changeset)]),
+                    new_bytecodes: BTreeMap::new(),
+                }],
+                block_changesets: BTreeMap::new(),
+                block_storage_changesets: BTreeMap::new(),
+            };
+
+            let mut header = template.header.clone().unseal();
+            header.number = number;
+            header.parent_hash = parent_hash;
+            let mut block = template.clone();
+            block.block.header = header.clone().seal_slow();
+
+            // the state root of the block is only known once its state was written
+            if let Err(TransactionError::StateTrieRootMismatch { got, .. }) =
+                tx.insert_block(block.clone(), chain_spec, exec_res.clone())
+            {
+                tx.drop().unwrap();
+                header.state_root = got;
+                block.block.header = header.seal_slow();
+                tx.insert_block(block.clone(), chain_spec, exec_res.clone()).unwrap();
+            }
+            tx.commit().unwrap();
+
+            parent_hash = block.hash();
+            blocks.push((block, exec_res));
+        }
+        blocks
+    }
+
+    /// Returns the encoded entries of the table.
+    fn raw_table<T: Table, DB: Database>(tx: &Transaction<'_, DB>) -> Vec<(Vec<u8>, Vec<u8>)>
+    where
+        T::Key: Default + Ord,
+    {
+        tx.table::<T>()
+            .unwrap()
+            .into_iter()
+            .map(|(key, value)| {
+                (key.encode().as_ref().to_vec(), value.compress().as_ref().to_vec())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn insert_blocks_matches_insert_block() {
+        let chain_spec = ChainSpecBuilder::default()
+            .chain(MAINNET.chain)
+            .genesis(MAINNET.genesis.clone())
+            .shanghai_activated()
+            .build();
+        let blocks = executed_chain(&chain_spec, 10);
+
+        // one block at a time, checking the state root of every block
+        let one_by_one = genesis_db();
+        let mut tx = Transaction::new(one_by_one.as_ref()).unwrap();
+        for (block, exec_res) in blocks.clone() {
+            tx.insert_block(block, &chain_spec, exec_res).unwrap();
+        }
+        tx.commit().unwrap();
+
+        // all blocks at once, as the blockchain tree commits a side chain
+        let batched = genesis_db();
+        let mut tx = Transaction::new(batched.as_ref()).unwrap();
+        tx.insert_blocks(blocks, &chain_spec).unwrap();
+        tx.commit().unwrap();
+
+        let one_by_one = Transaction::new(one_by_one.as_ref()).unwrap();
+        let batched = Transaction::new(batched.as_ref()).unwrap();
+        macro_rules! assert_tables_eq {
+            ($($table:ident),+) => {
+                $(
+                    assert_eq!(
+                        raw_table::<tables::$table, _>(&one_by_one),
+                        raw_table::<tables::$table, _>(&batched),
+                        stringify!($table)
+                    );
+                )+
+            };
+        }
+        assert_tables_eq!(
+            CanonicalHeaders,
+            HeaderTD,
+            HeaderNumbers,
+            Headers,
+            BlockBodyIndices,
+            BlockOmmers,
+            BlockWithdrawals,
+            Transactions,
+            TxHashNumber,
+            Receipts,
+            Logs,
+            PlainAccountState,
+            PlainStorageState,
+            Bytecodes,
+            BlockTransitionIndex,
+            TxTransitionIndex,
+            AccountHistory,
+            StorageHistory,
+            AccountChangeSet,
+            StorageChangeSet,
+            HashedAccount,
+            HashedStorage,
+            AccountsTrie,
+            StoragesTrie,
+            TxSenders
+        );
+        assert_eq!(batched.table::<tables::PlainStorageState>().unwrap().len(), 10);
+    }
 }
@@ -1,3 +1,28 @@
+mod multiproof;
+pub use multiproof::{MultiProof, ProvenAccount};
+
+mod node;
+
+mod overlay;
+pub use overlay::{HashedPostState, HashedStorage, StateRoot};
+
+mod parallel;
+pub use parallel::{OverlayStateRoot, ParallelStateRoot, DEFAULT_PARALLEL_THRESHOLD};
+
+mod prefetch;
+pub use prefetch::{PrefetchHandle, TrieNodeCache, TriePrefetcher};
+
+mod sparse;
+pub use sparse::{SparseTrie, SparseTrieError};
+
+mod verify;
+pub use verify::{
+    proven_value, verify_account_proof, verify_proof, verify_storage_proof, ProofVerificationError,
+};
+
+mod witness;
+pub use witness::WitnessStateProvider;
+
 use cita_trie::{PatriciaTrie, Trie};
 use hasher::HasherKeccak;
 use reth_codecs::Compact;
@@ -41,8 +66,14 @@ pub enum TrieError {
     /// Error when encoding/decoding a value.
     #[error("{0:?}")]
     DecodeError(#[from] DecodeError),
+    /// The database doesn't contain a trie node on the path of a changed key.
+    #[error("The trie node {0:?} wasn't found in the DB")]
+    MissingNode(H256),
     #[error("Trie requires committing a checkpoint.")]
     UnexpectedCheckpoint,
+    /// Error when building an in-memory trie.
+    #[error(transparent)]
+    SparseTrieError(#[from] SparseTrieError),
 }
 
 /// Database wrapper implementing HashDB trait, with a read-write transaction.
@@ -349,6 +380,21 @@ impl EthAccount {
     pub fn storage_root(&self) -> H256 {
         self.storage_root
     }
+
+    /// Get account's nonce.
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Get account's balance.
+    pub fn balance(&self) -> U256 {
+        self.balance
+    }
+
+    /// Get the hash of the account's bytecode.
+    pub fn code_hash(&self) -> H256 {
+        self.code_hash
+    }
 }
 
 /// A merkle proof of existence (or nonexistence) of a leaf value. Consists
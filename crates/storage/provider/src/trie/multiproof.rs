@@ -0,0 +1,238 @@
+//! Proofs of multiple accounts and storage slots at one state root.
+//!
+//! The proofs of keys with a common prefix share the nodes on the common part of their paths. A
+//! [MultiProof] contains every node once, so it is much smaller than the independent proofs of the
+//! same keys, see [StateProvider::multiproof](crate::StateProvider::multiproof).
+
+use super::{EthAccount, ProofVerificationError, SparseTrie};
+use reth_primitives::{keccak256, proofs::EMPTY_ROOT, Address, Bytes, H256, U256};
+use reth_rlp::{encode_fixed_size, Decodable, Encodable};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The proven values of an account of a [MultiProof].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProvenAccount {
+    /// The account, `None` if it doesn't exist.
+    pub account: Option<EthAccount>,
+    /// The values of the requested slots, zero if the slot doesn't exist.
+    pub storage: BTreeMap<H256, U256>,
+}
+
+impl ProvenAccount {
+    /// Returns the storage root of the account.
+    pub fn storage_root(&self) -> H256 {
+        self.account.map_or(EMPTY_ROOT, |account| account.storage_root())
+    }
+}
+
+/// A proof of multiple accounts and storage slots against one state root.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiProof {
+    /// The state root the accounts are proven against.
+    pub state_root: H256,
+    /// The RLP encoded trie nodes of all account and storage proofs, each node only once.
+    pub nodes: Vec<Bytes>,
+    /// The proven accounts.
+    pub accounts: BTreeMap<Address, ProvenAccount>,
+}
+
+impl MultiProof {
+    /// Returns the hashed addresses of the targets with the hashed slots of each account, the
+    /// targets of [StateProvider::witness](crate::StateProvider::witness).
+    pub fn hashed_targets(targets: &HashMap<Address, Vec<H256>>) -> BTreeMap<H256, BTreeSet<H256>> {
+        targets
+            .iter()
+            .map(|(address, slots)| (keccak256(address), slots.iter().map(keccak256).collect()))
+            .collect()
+    }
+
+    /// Reads the values of the targets, addresses with the requested slots of each account, from
+    /// the proof nodes.
+    ///
+    /// Fails if the nodes don't reveal the paths of all targets.
+    pub fn from_nodes(
+        state_root: H256,
+        nodes: Vec<Bytes>,
+        targets: &HashMap<Address, Vec<H256>>,
+    ) -> Result<Self, ProofVerificationError> {
+        let state = SparseTrie::from_proof_nodes(state_root, &nodes)?;
+        let mut accounts = BTreeMap::new();
+        for (address, slots) in targets {
+            let account = state
+                .get(keccak256(address))?
+                .map(|mut value| EthAccount::decode(&mut value))
+                .transpose()?;
+            let mut proven = ProvenAccount { account, storage: BTreeMap::new() };
+
+            let storage = SparseTrie::from_proof_nodes(proven.storage_root(), &nodes)?;
+            for slot in slots {
+                let value = storage
+                    .get(keccak256(slot))?
+                    .map(|mut value| U256::decode(&mut value))
+                    .transpose()?
+                    .unwrap_or_default();
+                proven.storage.insert(*slot, value);
+            }
+            accounts.insert(*address, proven);
+        }
+        Ok(Self { state_root, nodes, accounts })
+    }
+
+    /// Verifies that the nodes prove the values of all accounts and slots against the state root.
+    pub fn verify(&self) -> Result<(), ProofVerificationError> {
+        let state = SparseTrie::from_proof_nodes(self.state_root, &self.nodes)?;
+        for (address, proven) in &self.accounts {
+            let expected = proven.account.map(|account| {
+                let mut out = Vec::new();
+                account.encode(&mut out);
+                out
+            });
+            check_value(expected.as_deref(), state.get(keccak256(address))?)?;
+
+            let storage = SparseTrie::from_proof_nodes(proven.storage_root(), &self.nodes)?;
+            for (slot, value) in &proven.storage {
+                let expected = (*value != U256::ZERO).then(|| encode_fixed_size(value));
+                check_value(expected.as_deref(), storage.get(keccak256(slot))?)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_value(expected: Option<&[u8]>, got: Option<&[u8]>) -> Result<(), ProofVerificationError> {
+    if expected != got {
+        return Err(ProofVerificationError::ValueMismatch {
+            expected: expected.map(Bytes::from),
+            got: got.map(Bytes::from),
+        })
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        providers::{HistoricalStateProviderRef, LatestStateProvider},
+        trie::DBTrieLoader,
+        StateProvider,
+    };
+    use reth_db::{
+        database::Database,
+        mdbx::test_utils::create_test_rw_db,
+        models::{AccountBeforeTx, TransitionIdAddress},
+        tables,
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{Account, Header, StorageEntry};
+
+    #[test]
+    fn multiproof_of_adjacent_accounts() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        let addresses = (1..=100).map(Address::from_low_u64_be).collect::<Vec<_>>();
+        for (i, address) in addresses.iter().enumerate() {
+            let account = Account { nonce: i as u64, balance: U256::from(i), bytecode_hash: None };
+            tx.put::<tables::HashedAccount>(keccak256(address), account).unwrap();
+            // every other account has storage
+            if i % 2 == 0 {
+                for slot in 1..=3u64 {
+                    let entry = StorageEntry {
+                        key: keccak256(H256::from_low_u64_be(slot)),
+                        value: U256::from(slot),
+                    };
+                    tx.put::<tables::HashedStorage>(keccak256(address), entry).unwrap();
+                }
+            }
+        }
+        let root = DBTrieLoader::new(&tx).calculate_root().unwrap().root().unwrap();
+        tx.put::<tables::Headers>(0, Header { state_root: root, ..Default::default() }).unwrap();
+        tx.commit().unwrap();
+
+        // slot 4 doesn't exist, the last address isn't an account
+        let slots = (1..=4).map(H256::from_low_u64_be).collect::<Vec<_>>();
+        let mut targets =
+            addresses.iter().map(|address| (*address, slots.clone())).collect::<HashMap<_, _>>();
+        targets.insert(Address::from_low_u64_be(101), slots.clone());
+
+        let state = LatestStateProvider::new(db.tx().unwrap());
+        let proof = state.multiproof(&targets).unwrap();
+        assert_eq!(proof.state_root, root);
+        proof.verify().unwrap();
+
+        assert_eq!(proof.accounts.len(), 101);
+        assert_eq!(
+            proof.accounts[&Address::from_low_u64_be(101)],
+            ProvenAccount {
+                account: None,
+                storage: slots.iter().map(|slot| (*slot, U256::ZERO)).collect(),
+            }
+        );
+        let proven = &proof.accounts[&addresses[2]];
+        assert_eq!(proven.account.map(|account| account.nonce()), Some(2));
+        assert_eq!(proven.storage[&slots[2]], U256::from(3));
+        assert_eq!(proven.storage[&slots[3]], U256::ZERO);
+
+        // a tampered value doesn't verify
+        let mut tampered = proof.clone();
+        tampered.accounts.get_mut(&addresses[2]).unwrap().storage.insert(slots[0], U256::from(7));
+        assert!(tampered.verify().is_err());
+
+        // the independent proofs repeat the nodes at the top of the tries
+        let independent = addresses
+            .iter()
+            .enumerate()
+            .map(|(i, address)| {
+                let keys = if i % 2 == 0 { slots.as_slice() } else { &[] };
+                let (account_proof, _, storage_proofs) = state.proof(*address, keys).unwrap();
+                account_proof.len() + storage_proofs.iter().map(Vec::len).sum::<usize>()
+            })
+            .sum::<usize>();
+        assert!(
+            proof.nodes.len() * 3 < independent,
+            "{} shared nodes, {independent} independent nodes",
+            proof.nodes.len()
+        );
+    }
+
+    #[test]
+    fn multiproof_of_historical_state() {
+        let address = Address::from_low_u64_be(1);
+        let slot = H256::from_low_u64_be(1);
+        let state = |nonce: u64, value: u64| {
+            let db = create_test_rw_db();
+            let tx = db.tx_mut().unwrap();
+            let account = Account { nonce, balance: U256::from(1), bytecode_hash: None };
+            tx.put::<tables::HashedAccount>(keccak256(address), account).unwrap();
+            let entry = StorageEntry { key: keccak256(slot), value: U256::from(value) };
+            tx.put::<tables::HashedStorage>(keccak256(address), entry).unwrap();
+            let root = DBTrieLoader::new(&tx).calculate_root().unwrap().root().unwrap();
+            tx.put::<tables::Headers>(0, Header { state_root: root, ..Default::default() })
+                .unwrap();
+            tx.commit().unwrap();
+            (db, account, root)
+        };
+        let (_, before, root_before) = state(1, 1);
+
+        // the trie in the database is at the tip, the changesets revert it to the first transition
+        let (db, _, _) = state(2, 5);
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::AccountChangeSet>(0, AccountBeforeTx { address, info: Some(before) })
+            .unwrap();
+        tx.put::<tables::StorageChangeSet>(
+            TransitionIdAddress((0, address)),
+            StorageEntry { key: slot, value: U256::from(1) },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+        let targets = HashMap::from([(address, vec![slot])]);
+        let proof = HistoricalStateProviderRef::new(&tx, 0).multiproof(&targets).unwrap();
+        assert_eq!(proof.state_root, root_before);
+        proof.verify().unwrap();
+        let proven = &proof.accounts[&address];
+        assert_eq!(proven.account.map(|account| account.nonce()), Some(1));
+        assert_eq!(proven.storage[&slot], U256::from(1));
+    }
+}
@@ -0,0 +1,239 @@
+//! Encoding and decoding of merkle patricia trie nodes.
+
+use reth_primitives::{keccak256, H256};
+use reth_rlp::{BufMut, DecodeError, Encodable, Header, EMPTY_STRING_CODE};
+
+/// A reference to a child node, either the hash of the node or the node itself if its encoding is
+/// shorter than 32 bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ChildRef<'a> {
+    /// There is no child.
+    Empty,
+    /// The hash of the child node.
+    Hash(H256),
+    /// The RLP encoded child node.
+    Inline(&'a [u8]),
+}
+
+/// A decoded trie node, borrowing from its RLP encoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TrieNode<'a> {
+    /// A branch node with 16 children, the value is always empty in the state tries since all keys
+    /// have the same length.
+    Branch([ChildRef<'a>; 16]),
+    /// An extension node with the nibbles of the shared path and the child.
+    Extension(Vec<u8>, ChildRef<'a>),
+    /// A leaf node with the remaining nibbles of the key and the value.
+    Leaf(Vec<u8>, &'a [u8]),
+}
+
+impl<'a> TrieNode<'a> {
+    /// Decodes a RLP encoded trie node.
+    pub(crate) fn decode(mut buf: &'a [u8]) -> Result<Self, DecodeError> {
+        let header = Header::decode(&mut buf)?;
+        if !header.list {
+            return Err(DecodeError::UnexpectedString)
+        }
+        if buf.len() != header.payload_length {
+            return Err(DecodeError::ListLengthMismatch {
+                expected: header.payload_length,
+                got: buf.len(),
+            })
+        }
+
+        let mut items = Vec::with_capacity(17);
+        while !buf.is_empty() {
+            items.push(split_item(&mut buf)?);
+        }
+
+        match items.len() {
+            17 => {
+                if !matches!(decode_string(items[16])?, Some([])) {
+                    return Err(DecodeError::Custom("branch nodes with values are not supported"))
+                }
+                let mut children: [ChildRef<'a>; 16] = std::array::from_fn(|_| ChildRef::Empty);
+                for (child, item) in children.iter_mut().zip(items) {
+                    *child = ChildRef::decode(item)?;
+                }
+                Ok(TrieNode::Branch(children))
+            }
+            2 => {
+                let path = decode_string(items[0])?
+                    .ok_or(DecodeError::Custom("node path must be a string"))?;
+                let (nibbles, is_leaf) = decode_path(path)?;
+                if is_leaf {
+                    let value = decode_string(items[1])?
+                        .ok_or(DecodeError::Custom("leaf value must be a string"))?;
+                    Ok(TrieNode::Leaf(nibbles, value))
+                } else {
+                    if nibbles.is_empty() {
+                        return Err(DecodeError::Custom("empty extension node path"))
+                    }
+                    Ok(TrieNode::Extension(nibbles, ChildRef::decode(items[1])?))
+                }
+            }
+            _ => Err(DecodeError::Custom("invalid number of trie node items")),
+        }
+    }
+}
+
+impl<'a> ChildRef<'a> {
+    /// Decodes a child reference from a RLP item of a branch or extension node.
+    fn decode(item: &'a [u8]) -> Result<Self, DecodeError> {
+        match decode_string(item)? {
+            None => Ok(ChildRef::Inline(item)),
+            Some([]) => Ok(ChildRef::Empty),
+            Some(hash) if hash.len() == 32 => Ok(ChildRef::Hash(H256::from_slice(hash))),
+            Some(_) => Err(DecodeError::Custom("invalid child node reference")),
+        }
+    }
+}
+
+/// Splits the next RLP item, including its header, off the buffer.
+fn split_item<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], DecodeError> {
+    let mut payload = *buf;
+    let header = Header::decode(&mut payload)?;
+    let len = buf.len() - payload.len() + header.payload_length;
+    if len > buf.len() {
+        return Err(DecodeError::InputTooShort)
+    }
+    let (item, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(item)
+}
+
+/// Returns the payload of a RLP string, or `None` if the item is a list.
+fn decode_string(mut item: &[u8]) -> Result<Option<&[u8]>, DecodeError> {
+    let header = Header::decode(&mut item)?;
+    if item.len() < header.payload_length {
+        return Err(DecodeError::InputTooShort)
+    }
+    Ok((!header.list).then_some(&item[..header.payload_length]))
+}
+
+/// Decodes a hex-prefix encoded path into its nibbles and whether it is the path of a leaf.
+fn decode_path(path: &[u8]) -> Result<(Vec<u8>, bool), DecodeError> {
+    let (&first, rest) = path.split_first().ok_or(DecodeError::Custom("empty node path"))?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(DecodeError::Custom("invalid node path prefix"))
+    }
+
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    for byte in rest {
+        nibbles.push(byte >> 4);
+        nibbles.push(byte & 0x0f);
+    }
+    Ok((nibbles, flag & 2 == 2))
+}
+
+/// Hex-prefix encodes the nibbles of a node path.
+fn encode_path(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let odd = nibbles.len() % 2 == 1;
+    let flag = (is_leaf as u8) << 1 | odd as u8;
+
+    let mut path = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if odd {
+        path.push(flag << 4 | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        path.push(flag << 4);
+        nibbles
+    };
+    path.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    path
+}
+
+/// Returns the nibbles of the key.
+pub(crate) fn key_nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect()
+}
+
+/// RLP encodes a leaf node.
+pub(crate) fn encode_leaf(nibbles: &[u8], value: &[u8]) -> Vec<u8> {
+    encode_list(&[&encode_string(&encode_path(nibbles, true)), &encode_string(value)])
+}
+
+/// RLP encodes an extension node, the child is the RLP encoded child reference.
+pub(crate) fn encode_extension(nibbles: &[u8], child: &[u8]) -> Vec<u8> {
+    encode_list(&[&encode_string(&encode_path(nibbles, false)), child])
+}
+
+/// RLP encodes a branch node without value, the children are the RLP encoded child references.
+pub(crate) fn encode_branch(children: &[Vec<u8>; 16]) -> Vec<u8> {
+    let mut items = children.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    items.push(&[EMPTY_STRING_CODE]);
+    encode_list(&items)
+}
+
+/// Returns the RLP encoded reference to the given RLP encoded node: the node itself if it is
+/// shorter than 32 bytes, its hash otherwise.
+pub(crate) fn node_ref(node: Vec<u8>) -> Vec<u8> {
+    if node.len() < 32 {
+        node
+    } else {
+        hash_ref(keccak256(&node))
+    }
+}
+
+/// Returns the RLP encoded reference to a hashed node.
+pub(crate) fn hash_ref(hash: H256) -> Vec<u8> {
+    encode_string(hash.as_bytes())
+}
+
+fn encode_string(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len() + 9);
+    bytes.encode(&mut out);
+    out
+}
+
+fn encode_list(items: &[&[u8]]) -> Vec<u8> {
+    let payload_length = items.iter().map(|item| item.len()).sum();
+    let mut out = Vec::with_capacity(payload_length + 9);
+    Header { list: true, payload_length }.encode(&mut out);
+    for item in items {
+        out.put_slice(item);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_roundtrip() {
+        for (nibbles, is_leaf) in
+            [(vec![], true), (vec![1], false), (vec![1, 2], true), (vec![0xf, 0, 0xa], false)]
+        {
+            let path = encode_path(&nibbles, is_leaf);
+            assert_eq!(decode_path(&path).unwrap(), (nibbles, is_leaf));
+        }
+    }
+
+    #[test]
+    fn node_roundtrip() {
+        let leaf = encode_leaf(&[1, 2, 3], &[0x42]);
+        assert_eq!(TrieNode::decode(&leaf).unwrap(), TrieNode::Leaf(vec![1, 2, 3], &[0x42][..]));
+
+        let hash = H256::random();
+        let extension = encode_extension(&[4, 5], &hash_ref(hash));
+        assert_eq!(
+            TrieNode::decode(&extension).unwrap(),
+            TrieNode::Extension(vec![4, 5], ChildRef::Hash(hash))
+        );
+
+        let mut children: [Vec<u8>; 16] = std::array::from_fn(|_| vec![EMPTY_STRING_CODE]);
+        children[3] = node_ref(leaf.clone());
+        children[7] = hash_ref(hash);
+        let TrieNode::Branch(decoded) = TrieNode::decode(&encode_branch(&children)).unwrap() else {
+            panic!("expected branch node")
+        };
+        assert_eq!(decoded[3], ChildRef::Inline(&leaf));
+        assert_eq!(decoded[7], ChildRef::Hash(hash));
+        assert_eq!(decoded.iter().filter(|child| **child == ChildRef::Empty).count(), 14);
+    }
+}
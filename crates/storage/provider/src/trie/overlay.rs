@@ -0,0 +1,584 @@
+//! State root computation over the database trie with in-memory changes on top.
+
+use super::{sparse::SparseTrie, EthAccount, SparseTrieError, TrieError, TrieNodeCache};
+use crate::execution_result::{AccountInfoChangeSet, ExecutionResult};
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    models::{AccountBeforeTx, TransitionIdAddress},
+    tables,
+    transaction::DbTx,
+};
+use reth_primitives::{
+    keccak256, proofs::EMPTY_ROOT, Account, Address, BlockNumber, ChainSpec, Hardfork,
+    StorageEntry, TransitionId, H256, U256,
+};
+use reth_rlp::{encode_fixed_size, Decodable, Encodable};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The changed storage of an account, keyed by the hashed slots.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashedStorage {
+    /// Whether the storage was wiped, for example by a selfdestruct. All slots that are not in
+    /// `storage` are empty in that case.
+    pub wiped: bool,
+    /// The new values of the changed slots, zero for removed slots.
+    pub storage: BTreeMap<H256, U256>,
+}
+
+/// Changes to the state, keyed by hashed addresses and slots like the hashed state tables.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashedPostState {
+    /// The changed accounts, `None` for removed accounts.
+    pub accounts: BTreeMap<H256, Option<Account>>,
+    /// The changed storages.
+    pub storages: BTreeMap<H256, HashedStorage>,
+}
+
+impl HashedPostState {
+    /// Collects the changes of the execution results of consecutive blocks, the first of them
+    /// being `first_block`.
+    ///
+    /// The changes are applied the same way they are written to the plain state, see
+    /// [Transaction::insert_execution_result](crate::Transaction::insert_execution_result).
+    pub fn from_execution_results(
+        results: &[ExecutionResult],
+        first_block: BlockNumber,
+        chain_spec: &ChainSpec,
+    ) -> Self {
+        let mut state = Self::default();
+        for (block_number, result) in (first_block..).zip(results) {
+            let has_state_clear_eip =
+                chain_spec.fork(Hardfork::SpuriousDragon).active_at_block(block_number);
+
+            for tx_changeset in &result.tx_changesets {
+                for (address, changeset) in &tx_changeset.changeset {
+                    state.apply_account_changeset(
+                        *address,
+                        &changeset.account,
+                        has_state_clear_eip,
+                    );
+
+                    let hashed_address = keccak256(address);
+                    if changeset.wipe_storage {
+                        state.storages.insert(
+                            hashed_address,
+                            HashedStorage { wiped: true, ..Default::default() },
+                        );
+                    }
+                    if !changeset.storage.is_empty() {
+                        let storage =
+                            &mut state.storages.entry(hashed_address).or_default().storage;
+                        for (slot, (_, value)) in &changeset.storage {
+                            storage.insert(keccak256(H256(slot.to_be_bytes())), *value);
+                        }
+                    }
+                }
+            }
+
+            for (address, changeset) in &result.block_changesets {
+                state.apply_account_changeset(*address, changeset, has_state_clear_eip);
+            }
+            for (address, changes) in &result.block_storage_changesets {
+                let storage = &mut state.storages.entry(keccak256(address)).or_default().storage;
+                for (slot, (_, value)) in changes {
+                    storage.insert(keccak256(H256(slot.to_be_bytes())), *value);
+                }
+            }
+        }
+        state
+    }
+
+    /// Collects the changes that revert the current state to the state at the given transition.
+    pub fn from_reverts<'a, TX: DbTx<'a>>(
+        tx: &TX,
+        transition: TransitionId,
+    ) -> Result<Self, TrieError> {
+        let mut state = Self::default();
+
+        // changesets contain the values before the transition, so the first one of every key wins
+        for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk(Some(transition))? {
+            let (_, AccountBeforeTx { address, info }) = entry?;
+            state.accounts.entry(keccak256(address)).or_insert(info);
+        }
+
+        let start = TransitionIdAddress((transition, Address::zero()));
+        for entry in tx.cursor_read::<tables::StorageChangeSet>()?.walk(Some(start))? {
+            let (TransitionIdAddress((_, address)), StorageEntry { key, value }) = entry?;
+            state
+                .storages
+                .entry(keccak256(address))
+                .or_default()
+                .storage
+                .entry(keccak256(key))
+                .or_insert(value);
+        }
+
+        Ok(state)
+    }
+
+    /// Applies the later changes on top of these changes.
+    pub fn extend(&mut self, other: HashedPostState) {
+        self.accounts.extend(other.accounts);
+        for (hashed_address, storage) in other.storages {
+            if storage.wiped {
+                self.storages.insert(hashed_address, storage);
+            } else {
+                self.storages.entry(hashed_address).or_default().storage.extend(storage.storage);
+            }
+        }
+    }
+
+    /// Returns `true` if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.storages.is_empty()
+    }
+
+    fn apply_account_changeset(
+        &mut self,
+        address: Address,
+        changeset: &AccountInfoChangeSet,
+        has_state_clear_eip: bool,
+    ) {
+        let account = match changeset {
+            // empty accounts are not created after the state clear hardfork
+            AccountInfoChangeSet::Created { new } if has_state_clear_eip && new.is_empty() => {
+                return
+            }
+            AccountInfoChangeSet::Created { new } | AccountInfoChangeSet::Changed { new, .. } => {
+                Some(*new)
+            }
+            AccountInfoChangeSet::Destroyed { .. } => None,
+            AccountInfoChangeSet::NoChange { is_empty } if has_state_clear_eip && *is_empty => None,
+            AccountInfoChangeSet::NoChange { .. } => return,
+        };
+        self.accounts.insert(keccak256(address), account);
+    }
+}
+
+/// Computes state roots from the trie in the database with in-memory changes on top.
+///
+/// The trie is loaded into a [SparseTrie] on demand: only the nodes on the paths of the changed
+/// accounts and slots are read from the database, all other subtries enter the root with the
+/// hashes stored in their parents.
+#[derive(Debug)]
+pub struct StateRoot<'tx, TX> {
+    tx: &'tx TX,
+    cache: Option<TrieNodeCache>,
+}
+
+impl<'tx, 'db, TX: DbTx<'db>> StateRoot<'tx, TX> {
+    /// Create new instance over the tries in the database.
+    pub fn new(tx: &'tx TX) -> Self {
+        Self { tx, cache: None }
+    }
+
+    /// Set the cache of prefetched trie nodes to look up before reading the nodes from the
+    /// database.
+    pub fn with_cache(mut self, cache: TrieNodeCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Computes the root of the state with the given root in the database, after applying the
+    /// changes of the hashed post state.
+    pub fn overlay_root(
+        &self,
+        root: H256,
+        post_state: &HashedPostState,
+    ) -> Result<H256, TrieError> {
+        let mut state = SparseState::blinded(root);
+        state.apply(post_state, self)?;
+        Ok(state.root())
+    }
+}
+
+impl<'tx, 'db, TX: DbTx<'db>> TrieNodeLoader for StateRoot<'tx, TX> {
+    fn account_node(&self, hash: H256) -> Result<Vec<u8>, TrieError> {
+        if let Some(node) = self.cache.as_ref().and_then(|cache| cache.account_node(hash)) {
+            return Ok(node)
+        }
+        self.tx.get::<tables::AccountsTrie>(hash)?.ok_or(TrieError::MissingNode(hash))
+    }
+
+    fn storage_node(&self, hashed_address: H256, hash: H256) -> Result<Vec<u8>, TrieError> {
+        let cached = self.cache.as_ref().and_then(|cache| cache.storage_node(hashed_address, hash));
+        if let Some(node) = cached {
+            return Ok(node)
+        }
+        self.tx
+            .cursor_dup_read::<tables::StoragesTrie>()?
+            .seek_by_key_subkey(hashed_address, hash)?
+            .filter(|entry| entry.hash == hash)
+            .map(|entry| entry.node)
+            .ok_or(TrieError::MissingNode(hash))
+    }
+}
+
+/// Loads the nodes of the tries of a state by their hashes.
+pub(super) trait TrieNodeLoader {
+    /// Returns the node of the accounts trie with the given hash.
+    fn account_node(&self, hash: H256) -> Result<Vec<u8>, TrieError>;
+
+    /// Returns the node with the given hash of the storage trie of the account.
+    fn storage_node(&self, hashed_address: H256, hash: H256) -> Result<Vec<u8>, TrieError>;
+}
+
+/// The accounts trie and the storage tries of a state, revealed on demand.
+#[derive(Clone, Debug)]
+pub(super) struct SparseState {
+    accounts: SparseTrie,
+    /// The storage tries of the accounts that were read or changed, keyed by hashed address.
+    storages: HashMap<H256, SparseTrie>,
+}
+
+impl SparseState {
+    /// Creates the state with the given root, without revealing any nodes.
+    pub(super) fn blinded(root: H256) -> Self {
+        Self { accounts: SparseTrie::blinded(root), storages: HashMap::new() }
+    }
+
+    /// Returns the root of the state.
+    pub(super) fn root(&self) -> H256 {
+        self.accounts.root()
+    }
+
+    /// Returns the accounts trie.
+    pub(super) fn accounts(&self) -> &SparseTrie {
+        &self.accounts
+    }
+
+    /// Returns the RLP encoded nodes of all tries that were revealed or changed.
+    pub(super) fn revealed_nodes(&self) -> Vec<Vec<u8>> {
+        let mut nodes = self.accounts.revealed_nodes();
+        for trie in self.storages.values() {
+            nodes.extend(trie.revealed_nodes());
+        }
+        nodes
+    }
+
+    /// Returns the account, revealing the nodes on its path.
+    pub(super) fn account(
+        &mut self,
+        hashed_address: H256,
+        loader: &impl TrieNodeLoader,
+    ) -> Result<Option<EthAccount>, TrieError> {
+        let load_node = |hash| loader.account_node(hash);
+        with_revealed(&mut self.accounts, hashed_address, load_node, |trie| {
+            Ok(trie.get(hashed_address)?.map(<[u8]>::to_vec))
+        })?
+        .map(|leaf| EthAccount::decode(&mut leaf.as_slice()))
+        .transpose()
+        .map_err(Into::into)
+    }
+
+    /// Returns the value of the storage slot, revealing the nodes on the paths of the account and
+    /// the slot.
+    pub(super) fn storage(
+        &mut self,
+        hashed_address: H256,
+        hashed_slot: H256,
+        loader: &impl TrieNodeLoader,
+    ) -> Result<U256, TrieError> {
+        let Some(account) = self.account(hashed_address, loader)? else { return Ok(U256::ZERO) };
+        let trie = self.storage_trie(hashed_address, account.storage_root());
+        let load_node = |hash| loader.storage_node(hashed_address, hash);
+        with_revealed(trie, hashed_slot, load_node, |trie| {
+            Ok(trie.get(hashed_slot)?.map(<[u8]>::to_vec))
+        })?
+        .map(|leaf| U256::decode(&mut leaf.as_slice()))
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(Into::into)
+    }
+
+    /// Applies the changes of the hashed post state.
+    pub(super) fn apply(
+        &mut self,
+        post_state: &HashedPostState,
+        loader: &impl TrieNodeLoader,
+    ) -> Result<(), TrieError> {
+        let load_node = |hash| loader.account_node(hash);
+        let changed = post_state
+            .accounts
+            .keys()
+            .chain(post_state.storages.keys())
+            .copied()
+            .collect::<BTreeSet<_>>();
+        for hashed_address in changed {
+            let existing = self.account(hashed_address, loader)?;
+
+            let account = match post_state.accounts.get(&hashed_address) {
+                Some(Some(account)) => EthAccount::from(*account),
+                Some(None) => {
+                    self.storages.remove(&hashed_address);
+                    with_revealed(&mut self.accounts, hashed_address, load_node, |trie| {
+                        trie.remove(hashed_address)
+                    })?;
+                    continue
+                }
+                // storage changes of accounts that don't exist don't affect the root
+                None => match existing {
+                    Some(account) => account,
+                    None => continue,
+                },
+            };
+
+            let existing_root = existing.map_or(EMPTY_ROOT, |account| account.storage_root());
+            let storage_root = match post_state.storages.get(&hashed_address) {
+                Some(storage) => {
+                    if storage.wiped {
+                        self.storages.insert(hashed_address, SparseTrie::default());
+                    }
+                    self.apply_storage(hashed_address, existing_root, storage, loader)?
+                }
+                None => existing_root,
+            };
+
+            let mut out = Vec::new();
+            account.with_storage_root(storage_root).encode(&mut out);
+            with_revealed(&mut self.accounts, hashed_address, load_node, |trie| {
+                trie.update(hashed_address, out.clone())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Applies the changed slots to the storage trie of the account and returns the new storage
+    /// root.
+    fn apply_storage(
+        &mut self,
+        hashed_address: H256,
+        root: H256,
+        storage: &HashedStorage,
+        loader: &impl TrieNodeLoader,
+    ) -> Result<H256, TrieError> {
+        let trie = self.storage_trie(hashed_address, root);
+        let load_node = |hash| loader.storage_node(hashed_address, hash);
+
+        for (hashed_slot, value) in &storage.storage {
+            if *value == U256::ZERO {
+                with_revealed(trie, *hashed_slot, load_node, |trie| trie.remove(*hashed_slot))?;
+            } else {
+                let value = encode_fixed_size(value).to_vec();
+                with_revealed(trie, *hashed_slot, load_node, |trie| {
+                    trie.update(*hashed_slot, value.clone())
+                })?;
+            }
+        }
+
+        Ok(trie.root())
+    }
+
+    /// Returns the storage trie of the account, which has the given root if it wasn't used yet.
+    fn storage_trie(&mut self, hashed_address: H256, root: H256) -> &mut SparseTrie {
+        self.storages.entry(hashed_address).or_insert_with(|| SparseTrie::blinded(root))
+    }
+}
+
+/// Runs the operation on the hashed key, revealing the blinded nodes it runs into with the nodes
+/// returned by `load_node` until it succeeds.
+pub(super) fn with_revealed<T>(
+    trie: &mut SparseTrie,
+    hashed_key: H256,
+    load_node: impl Fn(H256) -> Result<Vec<u8>, TrieError>,
+    mut op: impl FnMut(&mut SparseTrie) -> Result<T, SparseTrieError>,
+) -> Result<T, TrieError> {
+    loop {
+        match op(trie) {
+            Err(SparseTrieError::BlindedNode(hash)) => {
+                let node = load_node(hash)?;
+                if !trie.reveal_node(hashed_key, hash, &node)? {
+                    return Err(SparseTrieError::BlindedNode(hash).into())
+                }
+            }
+            result => return Ok(result?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        insert_canonical_block,
+        test_utils::blocks::BlockChainTestData,
+        trie::{DBTrieLoader, TriePrefetcher},
+        Transaction,
+    };
+    use proptest::{prelude::ProptestConfig, proptest};
+    use reth_db::{
+        mdbx::{test_utils::create_test_rw_db, Env, WriteMap},
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{ChainSpecBuilder, Header, MAINNET};
+    use std::{
+        ops::{Deref, DerefMut},
+        sync::Arc,
+    };
+
+    type State = BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>;
+    type Overlay = BTreeMap<Address, (Option<Account>, bool, BTreeSet<StorageEntry>)>;
+
+    fn hashed_post_state(overlay: &Overlay) -> HashedPostState {
+        let mut post_state = HashedPostState::default();
+        for (address, (account, wiped, storage)) in overlay {
+            let hashed_address = keccak256(address);
+            post_state.accounts.insert(hashed_address, *account);
+            let storage = storage.iter().map(|entry| (keccak256(entry.key), entry.value)).collect();
+            post_state.storages.insert(hashed_address, HashedStorage { wiped: *wiped, storage });
+        }
+        post_state
+    }
+
+    fn apply_overlay(state: &mut State, overlay: Overlay) {
+        for (address, (account, wiped, storage)) in overlay {
+            let Some(account) = account else {
+                state.remove(&address);
+                continue
+            };
+            let (existing, slots) = state.entry(address).or_default();
+            *existing = account;
+            if wiped {
+                slots.clear();
+            }
+            for entry in storage {
+                slots.retain(|slot| slot.key != entry.key);
+                slots.insert(entry);
+            }
+        }
+    }
+
+    /// Writes the state to a fresh database and computes its root, leaving the trie nodes in it.
+    fn write_state(state: &State) -> (Arc<Env<WriteMap>>, H256) {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        for (address, (account, storage)) in state {
+            let hashed_address = keccak256(address);
+            tx.put::<tables::HashedAccount>(hashed_address, *account).unwrap();
+            for StorageEntry { key, value } in storage {
+                if *value != U256::ZERO {
+                    tx.put::<tables::HashedStorage>(
+                        hashed_address,
+                        StorageEntry { key: keccak256(key), value: *value },
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        let root = DBTrieLoader::new(&tx).calculate_root().unwrap().root().unwrap();
+        // the prefetcher walks the tries from the state root of the last header
+        tx.put::<tables::Headers>(0, Header { state_root: root, ..Default::default() }).unwrap();
+        tx.commit().unwrap();
+        (db, root)
+    }
+
+    fn test_overlay(mut state: State, overlay: Overlay) {
+        let (db, root) = write_state(&state);
+        let post_state = hashed_post_state(&overlay);
+        let got = StateRoot::new(&db.tx().unwrap()).overlay_root(root, &post_state).unwrap();
+
+        let prefetched = TriePrefetcher::new(db.as_ref()).run(|prefetch| {
+            for (address, (_, _, storage)) in &overlay {
+                prefetch.prefetch_account(*address);
+                for entry in storage {
+                    prefetch.prefetch_storage(*address, entry.key);
+                }
+            }
+            StateRoot::new(&db.tx().unwrap())
+                .with_cache(prefetch.cache().clone())
+                .overlay_root(root, &post_state)
+                .unwrap()
+        });
+        assert_eq!(prefetched, got);
+
+        apply_overlay(&mut state, overlay);
+        let (_, expected) = write_state(&state);
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn empty_overlay() {
+        let account = Account { nonce: 1, ..Default::default() };
+        let state = State::from([(Address::random(), (account, BTreeSet::new()))]);
+        test_overlay(state, Overlay::new());
+    }
+
+    #[test]
+    fn wiped_storage() {
+        let account = Account { nonce: 1, ..Default::default() };
+        let address = Address::random();
+        let storage = (1..10u64)
+            .map(|slot| StorageEntry { key: H256::from_low_u64_be(slot), value: U256::from(slot) })
+            .collect::<BTreeSet<_>>();
+        let new_slot = StorageEntry { key: H256::from_low_u64_be(42), value: U256::from(42) };
+        test_overlay(
+            State::from([(address, (account, storage)), (Address::random(), Default::default())]),
+            Overlay::from([(address, (Some(account), true, BTreeSet::from([new_slot])))]),
+        );
+    }
+
+    #[test]
+    fn differential_overlays() {
+        proptest!(ProptestConfig::with_cases(10), |(state: State, overlay: Overlay)| {
+            test_overlay(state, overlay);
+        });
+    }
+
+    #[test]
+    fn execution_results_and_reverts() {
+        let db = create_test_rw_db();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        let chain_spec = ChainSpecBuilder::default()
+            .chain(MAINNET.chain)
+            .genesis(MAINNET.genesis.clone())
+            .shanghai_activated()
+            .build();
+
+        let data = BlockChainTestData::default();
+        let (block1, exec_res1) = data.blocks[0].clone();
+        let (block2, exec_res2) = data.blocks[1].clone();
+        insert_canonical_block(tx.deref_mut(), data.genesis, None, false).unwrap();
+        tx.put::<tables::AccountsTrie>(EMPTY_ROOT, vec![0x80]).unwrap();
+
+        // blocks on top of the empty genesis state
+        let post_state = HashedPostState::from_execution_results(
+            &[exec_res1.clone(), exec_res2.clone()],
+            1,
+            &chain_spec,
+        );
+        let state_root = StateRoot::new(tx.deref());
+        assert_eq!(state_root.overlay_root(EMPTY_ROOT, &post_state).unwrap(), block2.state_root);
+
+        tx.insert_block(block1.clone(), &chain_spec, exec_res1).unwrap();
+        tx.insert_block(block2.clone(), &chain_spec, exec_res2).unwrap();
+
+        // revert block 2 from the database state
+        let transition = tx.get::<tables::BlockTransitionIndex>(1).unwrap().unwrap();
+        let reverts = HashedPostState::from_reverts(tx.deref(), transition).unwrap();
+        let state_root = StateRoot::new(tx.deref());
+        assert_eq!(
+            state_root.overlay_root(block2.state_root, &reverts).unwrap(),
+            block1.state_root
+        );
+    }
+
+    #[test]
+    fn extend_wiped_storage() {
+        let hashed_address = H256::random();
+        let mut post_state = HashedPostState::default();
+        post_state.storages.insert(
+            hashed_address,
+            HashedStorage {
+                wiped: false,
+                storage: BTreeMap::from([(H256::random(), U256::from(1))]),
+            },
+        );
+
+        let wiped = HashedStorage { wiped: true, storage: BTreeMap::new() };
+        post_state.extend(HashedPostState {
+            accounts: BTreeMap::new(),
+            storages: BTreeMap::from([(hashed_address, wiped.clone())]),
+        });
+        assert_eq!(post_state.storages[&hashed_address], wiped);
+    }
+}
@@ -0,0 +1,372 @@
+//! Parallel computation of the state root.
+
+use super::{
+    node::encode_branch,
+    overlay::{HashedPostState, SparseState, StateRoot},
+    sparse::SparseTrie,
+    EthAccount, TrieError,
+};
+use rayon::prelude::*;
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    tables,
+    transaction::DbTx,
+};
+use reth_primitives::{keccak256, proofs::EMPTY_ROOT, StorageEntry, H256};
+use reth_rlp::{encode_fixed_size, Encodable, EMPTY_STRING_CODE};
+use reth_tracing::tracing::*;
+
+/// The number of changed keys below which [ParallelStateRoot::overlay_root] computes the root on
+/// the calling thread.
+pub const DEFAULT_PARALLEL_THRESHOLD: usize = 1_000;
+
+/// Computes state roots from the tries and the hashed state tables in the database.
+///
+/// The state is split into the 16 subtries below the first nibble of the hashed addresses. The
+/// subtries, including the storage tries of their accounts, are computed in parallel on the rayon
+/// pool, each with its own read-only transaction, and combined into the root branch at the end.
+///
+/// [ParallelStateRoot::overlay_root] only updates the subtries with changed keys, the other
+/// subtries keep their references in the root node. [ParallelStateRoot::parallel_root] builds all
+/// subtries from the hashed state tables.
+///
+/// Unlike [DBTrieLoader](super::DBTrieLoader) no trie nodes are written to the database.
+#[derive(Debug)]
+pub struct ParallelStateRoot<'a, DB> {
+    db: &'a DB,
+    parallel_threshold: usize,
+}
+
+impl<'a, DB: Database> ParallelStateRoot<'a, DB> {
+    /// Create new instance with the [DEFAULT_PARALLEL_THRESHOLD].
+    pub fn new(db: &'a DB) -> Self {
+        Self { db, parallel_threshold: DEFAULT_PARALLEL_THRESHOLD }
+    }
+
+    /// Set the number of changed keys below which the root is computed on the calling thread.
+    pub fn with_parallel_threshold(mut self, parallel_threshold: usize) -> Self {
+        self.parallel_threshold = parallel_threshold;
+        self
+    }
+
+    /// Computes the root of the state with the given root in the database, after applying the
+    /// changes of the hashed post state, like [StateRoot::overlay_root].
+    ///
+    /// The changed keys are grouped into prefix sets by the first nibble of their hashed address.
+    /// If at least the threshold of accounts and storage slots changed, the subtries of the
+    /// nibbles with changes are updated in parallel and the unchanged subtries are not walked at
+    /// all. The root is computed on the calling thread if fewer keys changed, or if the changes
+    /// alter the shape of the root node, for example because only one subtrie is left.
+    pub fn overlay_root(
+        &self,
+        root: H256,
+        post_state: &HashedPostState,
+    ) -> Result<H256, TrieError> {
+        if changed_keys(post_state) < self.parallel_threshold {
+            return self.sequential_overlay_root(root, post_state)
+        }
+
+        // the references of the unchanged subtries are taken from the root node
+        let root_trie = match self.db.tx()?.get::<tables::AccountsTrie>(root)? {
+            Some(node) => SparseTrie::from_proof_nodes(root, [node])?,
+            None => SparseTrie::default(),
+        };
+        let Some(mut children) =
+            (0..16).map(|nibble| root_trie.root_child_ref(nibble)).collect::<Option<Vec<_>>>()
+        else {
+            return self.sequential_overlay_root(root, post_state)
+        };
+
+        let updated = prefix_sets(post_state)
+            .into_par_iter()
+            .enumerate()
+            .filter(|(_, changes)| !changes.is_empty())
+            .map(|(nibble, changes)| {
+                let tx = self.db.tx()?;
+                let mut state = SparseState::blinded(root);
+                state.apply(&changes, &StateRoot::new(&tx))?;
+                Ok((nibble, state.accounts().root_child_ref(nibble)))
+            })
+            .collect::<Result<Vec<_>, TrieError>>()?;
+        for (nibble, child) in updated {
+            // the root is no longer a branch node
+            let Some(child) = child else { return self.sequential_overlay_root(root, post_state) };
+            children[nibble] = child;
+        }
+        if children.iter().filter(|child| **child != [EMPTY_STRING_CODE]).count() < 2 {
+            return self.sequential_overlay_root(root, post_state)
+        }
+
+        let children: [Vec<u8>; 16] = children.try_into().expect("16 children");
+        Ok(keccak256(encode_branch(&children)))
+    }
+
+    /// Computes the root of the state with the changes of the hashed post state on the calling
+    /// thread.
+    fn sequential_overlay_root(
+        &self,
+        root: H256,
+        post_state: &HashedPostState,
+    ) -> Result<H256, TrieError> {
+        StateRoot::new(&self.db.tx()?).overlay_root(root, post_state)
+    }
+
+    /// Computes the state root from the hashed state tables, computing the subtries in parallel.
+    pub fn parallel_root(&self) -> Result<H256, TrieError> {
+        let subtries = (0..16u8)
+            .into_par_iter()
+            .map(|nibble| account_subtrie(&self.db.tx()?, nibble))
+            .collect::<Result<Vec<_>, TrieError>>()?;
+        Ok(combine_subtries(subtries))
+    }
+
+    /// Computes the state root from the hashed state tables, computing the subtries one after the
+    /// other.
+    pub fn sequential_root(&self) -> Result<H256, TrieError> {
+        let tx = self.db.tx()?;
+        let subtries =
+            (0..16u8).map(|nibble| account_subtrie(&tx, nibble)).collect::<Result<Vec<_>, _>>()?;
+        Ok(combine_subtries(subtries))
+    }
+}
+
+/// Computes the root of a state with in-memory changes on top of the trie in the database.
+///
+/// Lets state providers compute roots with a [ParallelStateRoot] without knowing the type of its
+/// database.
+pub trait OverlayStateRoot: Send + Sync {
+    /// Computes the root of the state with the given root in the database, after applying the
+    /// changes of the hashed post state.
+    fn overlay_root(&self, root: H256, post_state: &HashedPostState) -> Result<H256, TrieError>;
+}
+
+impl<'a, DB: Database> OverlayStateRoot for ParallelStateRoot<'a, DB> {
+    fn overlay_root(&self, root: H256, post_state: &HashedPostState) -> Result<H256, TrieError> {
+        ParallelStateRoot::overlay_root(self, root, post_state)
+    }
+}
+
+/// Returns the number of changed accounts and storage slots.
+fn changed_keys(post_state: &HashedPostState) -> usize {
+    post_state.accounts.len() +
+        post_state.storages.values().map(|storage| storage.storage.len()).sum::<usize>()
+}
+
+/// Splits the changes into the prefix sets of the 16 subtries below the root, by the first
+/// nibble of the hashed addresses.
+fn prefix_sets(post_state: &HashedPostState) -> Vec<HashedPostState> {
+    let mut prefix_sets = vec![HashedPostState::default(); 16];
+    for (hashed_address, account) in &post_state.accounts {
+        let nibble = (hashed_address.0[0] >> 4) as usize;
+        prefix_sets[nibble].accounts.insert(*hashed_address, *account);
+    }
+    for (hashed_address, storage) in &post_state.storages {
+        let nibble = (hashed_address.0[0] >> 4) as usize;
+        prefix_sets[nibble].storages.insert(*hashed_address, storage.clone());
+    }
+    prefix_sets
+}
+
+/// Builds the trie of all accounts whose hashed address starts with the given nibble.
+fn account_subtrie<'a, TX: DbTx<'a>>(tx: &TX, nibble: u8) -> Result<SparseTrie, TrieError> {
+    let mut start = H256::zero();
+    start.0[0] = nibble << 4;
+
+    let mut trie = SparseTrie::default();
+    let mut storage_cursor = tx.cursor_dup_read::<tables::HashedStorage>()?;
+    let mut num_accounts = 0usize;
+    for entry in tx.cursor_read::<tables::HashedAccount>()?.walk(Some(start))? {
+        let (hashed_address, account) = entry?;
+        if hashed_address.0[0] >> 4 != nibble {
+            break
+        }
+
+        let mut storage = SparseTrie::default();
+        for entry in storage_cursor.walk_dup(Some(hashed_address), None)? {
+            let (key, StorageEntry { key: hashed_slot, value }) = entry?;
+            if key != hashed_address {
+                break
+            }
+            storage.update(hashed_slot, encode_fixed_size(&value).to_vec())?;
+            storage.seal_left_of(hashed_slot);
+        }
+
+        let mut out = Vec::new();
+        EthAccount::from(account).with_storage_root(storage.root()).encode(&mut out);
+        trie.update(hashed_address, out)?;
+        trie.seal_left_of(hashed_address);
+        num_accounts += 1;
+    }
+
+    trace!(target: "trie::parallel", nibble, num_accounts, "Computed account subtrie");
+    Ok(trie)
+}
+
+/// Combines the subtries of the 16 nibbles into the root.
+fn combine_subtries(subtries: Vec<SparseTrie>) -> H256 {
+    let mut nonempty = subtries.iter().enumerate().filter(|(_, trie)| !trie.is_empty());
+    match (nonempty.next(), nonempty.next()) {
+        (None, _) => EMPTY_ROOT,
+        // a single subtrie is the whole trie
+        (Some((_, trie)), None) => trie.root(),
+        _ => {
+            let children = std::array::from_fn(|nibble| {
+                let trie = &subtries[nibble];
+                if trie.is_empty() {
+                    vec![EMPTY_STRING_CODE]
+                } else {
+                    trie.branch_child_ref()
+                }
+            });
+            keccak256(encode_branch(&children))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{trie::DBTrieLoader, Transaction};
+    use proptest::{prelude::ProptestConfig, proptest};
+    use reth_db::{
+        mdbx::{test_utils::create_test_rw_db, Env, WriteMap},
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{Account, Address, U256};
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        ops::Deref,
+    };
+
+    type State = BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>;
+    type StateUpdate = BTreeMap<Address, Option<(Account, BTreeSet<StorageEntry>)>>;
+
+    fn write_account<'a, TX: DbTxMut<'a> + DbTx<'a>>(
+        tx: &TX,
+        address: Address,
+        account: Option<(Account, BTreeSet<StorageEntry>)>,
+    ) {
+        let hashed_address = keccak256(address);
+        tx.delete::<tables::HashedAccount>(hashed_address, None).unwrap();
+        tx.delete::<tables::HashedStorage>(hashed_address, None).unwrap();
+
+        let Some((account, storage)) = account else { return };
+        tx.put::<tables::HashedAccount>(hashed_address, account).unwrap();
+        for StorageEntry { key, value } in storage {
+            if value != U256::ZERO {
+                tx.put::<tables::HashedStorage>(
+                    hashed_address,
+                    StorageEntry { key: keccak256(key), value },
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn assert_roots_match(tx: &mut Transaction<'_, Env<WriteMap>>) {
+        let expected =
+            DBTrieLoader::new(tx.deref()).calculate_root().unwrap().root().expect("complete root");
+        tx.commit().unwrap();
+
+        let state_root = ParallelStateRoot::new(tx.db);
+        assert_eq!(state_root.sequential_root().unwrap(), expected);
+        assert_eq!(state_root.parallel_root().unwrap(), expected);
+    }
+
+    fn test_state_updates(state: State, updates: StateUpdate) {
+        let db = create_test_rw_db();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+
+        for (address, account) in state {
+            write_account(tx.deref(), address, Some(account));
+        }
+        assert_roots_match(&mut tx);
+
+        for (address, account) in updates {
+            write_account(tx.deref(), address, account);
+        }
+        assert_roots_match(&mut tx);
+    }
+
+    /// Compares the overlay roots of the parallel and sequential computation, with the trie of
+    /// the state in the database.
+    fn test_overlay(state: State, mut updates: StateUpdate) {
+        // also changes every other existing account and removes its first slot
+        for (address, (account, storage)) in state.iter().step_by(2) {
+            let account = Account { nonce: account.nonce.wrapping_add(1), ..*account };
+            let storage = storage
+                .iter()
+                .take(1)
+                .map(|entry| StorageEntry { value: U256::ZERO, ..*entry })
+                .collect();
+            updates.entry(*address).or_insert(Some((account, storage)));
+        }
+
+        let db = create_test_rw_db();
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        for (address, account) in state {
+            write_account(tx.deref(), address, Some(account));
+        }
+        let root = DBTrieLoader::new(tx.deref()).calculate_root().unwrap().root().unwrap();
+        tx.commit().unwrap();
+
+        let mut post_state = HashedPostState::default();
+        for (address, account) in updates {
+            let hashed_address = keccak256(address);
+            let Some((account, storage)) = account else {
+                post_state.accounts.insert(hashed_address, None);
+                continue
+            };
+            post_state.accounts.insert(hashed_address, Some(account));
+            let storage = storage.into_iter().map(|entry| (keccak256(entry.key), entry.value));
+            post_state.storages.entry(hashed_address).or_default().storage.extend(storage);
+        }
+
+        let expected = StateRoot::new(&db.tx().unwrap()).overlay_root(root, &post_state).unwrap();
+        let state_root = ParallelStateRoot::new(db.as_ref());
+        assert_eq!(state_root.overlay_root(root, &post_state).unwrap(), expected);
+        let state_root = state_root.with_parallel_threshold(0);
+        assert_eq!(state_root.overlay_root(root, &post_state).unwrap(), expected);
+    }
+
+    #[test]
+    fn empty_state() {
+        let db = create_test_rw_db();
+        assert_eq!(ParallelStateRoot::new(db.as_ref()).parallel_root().unwrap(), EMPTY_ROOT);
+    }
+
+    #[test]
+    fn single_account() {
+        let account = Account { nonce: 1, ..Default::default() };
+        test_state_updates(
+            BTreeMap::from([(Address::random(), (account, BTreeSet::new()))]),
+            Default::default(),
+        );
+    }
+
+    #[test]
+    fn differential_state_updates() {
+        proptest!(ProptestConfig::with_cases(10), |(state: State, updates: StateUpdate)| {
+            test_state_updates(state, updates);
+        });
+    }
+
+    #[test]
+    fn overlay_of_single_subtrie() {
+        let account = Account { nonce: 1, ..Default::default() };
+        let state = (0..20u64)
+            .map(|i| (Address::from_low_u64_be(i), (account, BTreeSet::new())))
+            .collect::<State>();
+        // removes all but one account, which collapses the root branch
+        let updates = (1..20u64).map(|i| (Address::from_low_u64_be(i), None)).collect();
+        test_overlay(state, updates);
+    }
+
+    #[test]
+    fn differential_overlays() {
+        proptest!(ProptestConfig::with_cases(10), |(state: State, updates: StateUpdate)| {
+            test_overlay(state, updates);
+        });
+    }
+}
@@ -0,0 +1,337 @@
+//! Prefetching of trie nodes while a block is executed.
+
+use super::{overlay::with_revealed, sparse::SparseTrie, EthAccount, TrieError};
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    tables,
+    transaction::DbTx,
+};
+use reth_primitives::{keccak256, proofs::EMPTY_ROOT, Address, H256};
+use reth_rlp::Decodable;
+use reth_tracing::tracing::*;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, RwLock,
+    },
+};
+
+/// The stages whose checkpoint is the block the tries in the database are at, in order of
+/// preference: the merkle stage builds the tries, and the blockchain tree keeps the execution
+/// checkpoint in sync when it commits blocks with their tries.
+const TRIE_CHECKPOINT_STAGES: [&str; 2] = ["MerkleExecute", "Execution"];
+
+/// Trie nodes loaded ahead of the state root computation.
+///
+/// The nodes are keyed by their hashes, so a cached node is valid for every root that references
+/// it.
+#[derive(Clone, Debug, Default)]
+pub struct TrieNodeCache {
+    nodes: Arc<RwLock<CachedNodes>>,
+}
+
+#[derive(Debug, Default)]
+struct CachedNodes {
+    accounts: HashMap<H256, Vec<u8>>,
+    storages: HashMap<(H256, H256), Vec<u8>>,
+}
+
+impl TrieNodeCache {
+    /// Returns the cached node of the accounts trie with the given hash.
+    pub fn account_node(&self, hash: H256) -> Option<Vec<u8>> {
+        self.nodes.read().expect("not poisoned").accounts.get(&hash).cloned()
+    }
+
+    /// Returns the cached node with the given hash of the storage trie of the account.
+    pub fn storage_node(&self, hashed_address: H256, hash: H256) -> Option<Vec<u8>> {
+        self.nodes.read().expect("not poisoned").storages.get(&(hashed_address, hash)).cloned()
+    }
+
+    /// Returns the number of cached nodes.
+    pub fn len(&self) -> usize {
+        let nodes = self.nodes.read().expect("not poisoned");
+        nodes.accounts.len() + nodes.storages.len()
+    }
+
+    /// Returns `true` if no nodes are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert_account_node(&self, hash: H256, node: Vec<u8>) {
+        self.nodes.write().expect("not poisoned").accounts.insert(hash, node);
+    }
+
+    fn insert_storage_node(&self, hashed_address: H256, hash: H256, node: Vec<u8>) {
+        self.nodes.write().expect("not poisoned").storages.insert((hashed_address, hash), node);
+    }
+}
+
+/// Messages sent to the prefetcher thread.
+#[derive(Debug)]
+enum Message {
+    Account(Address),
+    Storage(Address, H256),
+    Stop,
+}
+
+/// Handle to a running [TriePrefetcher], to send it the keys touched during execution.
+#[derive(Clone, Debug)]
+pub struct PrefetchHandle {
+    tx: mpsc::Sender<Message>,
+    cache: TrieNodeCache,
+}
+
+impl PrefetchHandle {
+    /// Prefetches the path of the account in the accounts trie.
+    pub fn prefetch_account(&self, address: Address) {
+        let _ = self.tx.send(Message::Account(address));
+    }
+
+    /// Prefetches the path of the slot in the storage trie of the account, and the path of the
+    /// account.
+    pub fn prefetch_storage(&self, address: Address, slot: H256) {
+        let _ = self.tx.send(Message::Storage(address, slot));
+    }
+
+    /// Returns the cache the prefetched nodes are written to.
+    pub fn cache(&self) -> &TrieNodeCache {
+        &self.cache
+    }
+}
+
+/// Loads the trie nodes on the paths of the keys touched during execution on a background thread,
+/// so that they are cached by the time the state root is computed.
+///
+/// The tries are walked from the state root of the block of the merkle stage checkpoint, which is
+/// the state the tries in the database are at, see [trie_root]. Prefetching is best effort: it
+/// stops on the first error and the state root computation reads the nodes that are not cached from
+/// the database.
+#[derive(Debug)]
+pub struct TriePrefetcher<'a, DB> {
+    db: &'a DB,
+    cache: TrieNodeCache,
+}
+
+impl<'a, DB: Database> TriePrefetcher<'a, DB> {
+    /// Create new instance with an empty cache.
+    pub fn new(db: &'a DB) -> Self {
+        Self { db, cache: TrieNodeCache::default() }
+    }
+
+    /// Set the cache to write the prefetched nodes to.
+    pub fn with_cache(mut self, cache: TrieNodeCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Runs the closure with a handle to send the touched keys to, while the nodes on their paths
+    /// are loaded on a background thread. Prefetching stops when the closure returns.
+    pub fn run<T>(self, f: impl FnOnce(PrefetchHandle) -> T) -> T {
+        let (tx, rx) = mpsc::channel();
+        let done = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            let (db, cache, done) = (self.db, self.cache.clone(), &done);
+            scope.spawn(move || {
+                let result = db.tx().map_err(TrieError::from).and_then(|tx| {
+                    prefetch(&tx, &cache, rx, done)?;
+                    trace!(target: "trie::prefetch", num_nodes = cache.len(), "Prefetched trie nodes");
+                    Ok(())
+                });
+                if let Err(error) = result {
+                    debug!(target: "trie::prefetch", ?error, "Stopped prefetching trie nodes");
+                }
+            });
+
+            let out = f(PrefetchHandle { tx: tx.clone(), cache: self.cache });
+            done.store(true, Ordering::Relaxed);
+            // wake up the thread in case it waits for keys
+            let _ = tx.send(Message::Stop);
+            out
+        })
+    }
+}
+
+/// Returns the root of the tries in the database.
+///
+/// That is the state root of the block of the merkle stage checkpoint, or of the execution stage
+/// checkpoint if the merkle stage never ran. Without stage checkpoints, the blocks are committed
+/// with their tries and the root is the state root of the canonical tip.
+fn trie_root<'a, TX: DbTx<'a>>(tx: &TX) -> Result<H256, TrieError> {
+    let mut checkpoint = None;
+    for stage in TRIE_CHECKPOINT_STAGES {
+        checkpoint = tx.get::<tables::SyncStage>(stage.to_string())?;
+        if checkpoint.is_some() {
+            break
+        }
+    }
+    let number = match checkpoint {
+        Some(number) => Some(number),
+        None => tx.cursor_read::<tables::CanonicalHeaders>()?.last()?.map(|(number, _)| number),
+    };
+    let header = match number {
+        Some(number) => tx.get::<tables::Headers>(number)?,
+        None => None,
+    };
+    Ok(header.map_or(EMPTY_ROOT, |header| header.state_root))
+}
+
+/// Reveals the paths of the received keys until the prefetcher is stopped.
+fn prefetch<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    cache: &TrieNodeCache,
+    rx: mpsc::Receiver<Message>,
+    done: &AtomicBool,
+) -> Result<(), TrieError> {
+    let root = trie_root(tx)?;
+    let mut accounts = SparseTrie::blinded(root);
+    let mut storages = HashMap::<H256, SparseTrie>::new();
+    let mut seen = HashSet::new();
+
+    let load_account_node = |hash: H256| -> Result<Vec<u8>, TrieError> {
+        let node = tx.get::<tables::AccountsTrie>(hash)?.ok_or(TrieError::MissingNode(hash))?;
+        cache.insert_account_node(hash, node.clone());
+        Ok(node)
+    };
+
+    for message in rx {
+        if done.load(Ordering::Relaxed) {
+            break
+        }
+        let (address, slot) = match message {
+            Message::Account(address) => (address, None),
+            Message::Storage(address, slot) => (address, Some(slot)),
+            Message::Stop => break,
+        };
+        if !seen.insert((address, slot)) {
+            continue
+        }
+
+        let hashed_address = keccak256(address);
+        let account = with_revealed(&mut accounts, hashed_address, load_account_node, |trie| {
+            Ok(trie.get(hashed_address)?.map(<[u8]>::to_vec))
+        })?;
+        let (Some(slot), Some(account)) = (slot, account) else { continue };
+
+        let storage_root = EthAccount::decode(&mut account.as_slice())?.storage_root();
+        let storage =
+            storages.entry(hashed_address).or_insert_with(|| SparseTrie::blinded(storage_root));
+        let load_storage_node = |hash: H256| -> Result<Vec<u8>, TrieError> {
+            let node = tx
+                .cursor_dup_read::<tables::StoragesTrie>()?
+                .seek_by_key_subkey(hashed_address, hash)?
+                .filter(|entry| entry.hash == hash)
+                .map(|entry| entry.node)
+                .ok_or(TrieError::MissingNode(hash))?;
+            cache.insert_storage_node(hashed_address, hash, node.clone());
+            Ok(node)
+        };
+        let hashed_slot = keccak256(slot);
+        with_revealed(storage, hashed_slot, load_storage_node, |trie| {
+            trie.get(hashed_slot).map(|_| ())
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::{DBTrieLoader, StateRoot};
+    use reth_db::{mdbx::test_utils::create_test_rw_db, transaction::DbTxMut};
+    use reth_primitives::{Account, Header, StorageEntry, U256};
+
+    #[test]
+    fn prefetches_proof_nodes() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        let addresses = (0..100u64).map(Address::from_low_u64_be).collect::<Vec<_>>();
+        for (nonce, address) in addresses.iter().enumerate() {
+            let account = Account { nonce: nonce as u64, ..Default::default() };
+            tx.put::<tables::HashedAccount>(keccak256(address), account).unwrap();
+        }
+        for slot in 0..100u64 {
+            let entry = StorageEntry {
+                key: keccak256(H256::from_low_u64_be(slot)),
+                value: U256::from(slot + 1),
+            };
+            tx.put::<tables::HashedStorage>(keccak256(addresses[0]), entry).unwrap();
+        }
+        let root = DBTrieLoader::new(&tx).calculate_root().unwrap().root().unwrap();
+        tx.put::<tables::Headers>(0, Header { state_root: root, ..Default::default() }).unwrap();
+        tx.put::<tables::CanonicalHeaders>(0, H256::zero()).unwrap();
+        tx.commit().unwrap();
+
+        let (sender, rx) = mpsc::channel();
+        sender.send(Message::Account(addresses[1])).unwrap();
+        sender.send(Message::Storage(addresses[0], H256::from_low_u64_be(7))).unwrap();
+        sender.send(Message::Stop).unwrap();
+        let cache = TrieNodeCache::default();
+        let tx = db.tx().unwrap();
+        prefetch(&tx, &cache, rx, &AtomicBool::new(false)).unwrap();
+
+        // all hashed nodes of the proofs of the keys are cached
+        let loader = DBTrieLoader::new(&tx);
+        let (account_proof, _) =
+            loader.generate_acount_proof(root, keccak256(addresses[1])).unwrap();
+        let (contract_proof, storage_root) =
+            loader.generate_acount_proof(root, keccak256(addresses[0])).unwrap();
+        for node in account_proof.into_iter().chain(contract_proof).filter(|node| node.len() >= 32)
+        {
+            assert_eq!(cache.account_node(keccak256(&node)), Some(node));
+        }
+        let slot_proofs = loader
+            .generate_storage_proofs(
+                storage_root,
+                keccak256(addresses[0]),
+                &[keccak256(H256::from_low_u64_be(7))],
+            )
+            .unwrap();
+        for node in slot_proofs.into_iter().flatten().filter(|node| node.len() >= 32) {
+            assert_eq!(cache.storage_node(keccak256(addresses[0]), keccak256(&node)), Some(node));
+        }
+        assert!(cache.len() > 2);
+    }
+
+    #[test]
+    fn trie_root_of_checkpoint() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        assert_eq!(trie_root(&tx).unwrap(), EMPTY_ROOT);
+
+        let roots = [H256::from_low_u64_be(1), H256::from_low_u64_be(2), H256::from_low_u64_be(3)];
+        for (number, state_root) in roots.into_iter().enumerate() {
+            tx.put::<tables::Headers>(number as u64, Header { state_root, ..Default::default() })
+                .unwrap();
+            tx.put::<tables::CanonicalHeaders>(number as u64, H256::zero()).unwrap();
+        }
+        // without a pipeline, the tries are at the canonical tip
+        assert_eq!(trie_root(&tx).unwrap(), roots[2]);
+
+        // the headers are ahead of the executed blocks
+        tx.put::<tables::SyncStage>("Execution".to_string(), 1).unwrap();
+        assert_eq!(trie_root(&tx).unwrap(), roots[1]);
+
+        // the merkle stage is behind the execution stage
+        tx.put::<tables::SyncStage>("MerkleExecute".to_string(), 0).unwrap();
+        assert_eq!(trie_root(&tx).unwrap(), roots[0]);
+    }
+
+    #[test]
+    fn stops_with_closure() {
+        let db = create_test_rw_db();
+        let cache = TrieNodeCache::default();
+        let root = TriePrefetcher::new(db.as_ref()).with_cache(cache.clone()).run(|prefetch| {
+            prefetch.prefetch_account(Address::random());
+            StateRoot::new(&db.tx().unwrap())
+                .with_cache(prefetch.cache().clone())
+                .overlay_root(EMPTY_ROOT, &Default::default())
+                .unwrap()
+        });
+        assert_eq!(root, EMPTY_ROOT);
+        assert!(cache.is_empty());
+    }
+}
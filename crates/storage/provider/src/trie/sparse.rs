@@ -0,0 +1,596 @@
+//! A partial in-memory trie backed by merkle proofs.
+//!
+//! The [SparseTrie] only contains the nodes revealed by a set of proofs, all other subtries are
+//! represented by their hashes. This is enough to update the proven leaves, insert keys whose
+//! absence was proven and recompute the root, without access to the full trie.
+
+use super::node::{
+    encode_branch, encode_extension, encode_leaf, hash_ref, key_nibbles, node_ref, ChildRef,
+    TrieNode,
+};
+use reth_primitives::{keccak256, proofs::EMPTY_ROOT, H256};
+use reth_rlp::{DecodeError, EMPTY_STRING_CODE};
+use std::collections::HashMap;
+
+/// Sparse trie error types.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum SparseTrieError {
+    /// The path of the key leads to a node that was not revealed by the proofs.
+    #[error("Path of the key is not revealed, blinded node {0:?}")]
+    BlindedNode(H256),
+    /// A proof node is not a valid trie node.
+    #[error("Invalid proof node: {0:?}")]
+    InvalidNode(#[from] DecodeError),
+}
+
+/// A node of the sparse trie.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SparseNode {
+    /// The empty trie.
+    Empty,
+    /// A node that was not revealed, only its hash is known.
+    Blinded(H256),
+    /// A leaf with the remaining nibbles of the key and the RLP encoded value.
+    Leaf { key: Vec<u8>, value: Vec<u8> },
+    /// An extension with the shared nibbles.
+    Extension { key: Vec<u8>, child: Box<SparseNode> },
+    /// A branch node without value.
+    Branch { children: Box<[SparseNode; 16]> },
+}
+
+/// A merkle patricia trie of which only the parts revealed by proofs are known.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseTrie {
+    root: SparseNode,
+}
+
+impl Default for SparseTrie {
+    fn default() -> Self {
+        Self { root: SparseNode::Empty }
+    }
+}
+
+impl SparseTrie {
+    /// Creates the sparse trie with the given root from the nodes of one or more proofs, for
+    /// example all nodes of the `accountProof`s of an `eth_getProof` response.
+    ///
+    /// Nodes that are not part of the trie are ignored, subtries without proof nodes remain
+    /// blinded.
+    pub fn from_proof_nodes<T: AsRef<[u8]>>(
+        root: H256,
+        nodes: impl IntoIterator<Item = T>,
+    ) -> Result<Self, SparseTrieError> {
+        if root == EMPTY_ROOT {
+            return Ok(Self::default())
+        }
+
+        let nodes = nodes
+            .into_iter()
+            .map(|node| (keccak256(node.as_ref()), node.as_ref().to_vec()))
+            .collect::<HashMap<_, _>>();
+        Ok(Self { root: reveal(ChildRef::Hash(root), &nodes)? })
+    }
+
+    /// Creates the sparse trie with the given root, without revealing any nodes.
+    pub(crate) fn blinded(root: H256) -> Self {
+        if root == EMPTY_ROOT {
+            return Self::default()
+        }
+        Self { root: SparseNode::Blinded(root) }
+    }
+
+    /// Replaces the blinded node with the given hash by the revealed RLP encoded node.
+    ///
+    /// The node is searched on the path of the hashed key, including the siblings along the path,
+    /// which covers all blinded nodes reported by operations on that key. Returns `false` if there
+    /// is no such blinded node.
+    pub(crate) fn reveal_node(
+        &mut self,
+        hashed_key: H256,
+        hash: H256,
+        revealed: &[u8],
+    ) -> Result<bool, SparseTrieError> {
+        let nibbles = key_nibbles(hashed_key.as_bytes());
+        let mut path = nibbles.as_slice();
+        let mut node = &mut self.root;
+        loop {
+            if matches!(node, SparseNode::Blinded(blinded) if *blinded == hash) {
+                *node = reveal(ChildRef::Inline(revealed), &HashMap::new())?;
+                return Ok(true)
+            }
+            match node {
+                SparseNode::Extension { key, child } => {
+                    let Some(rest) = path.strip_prefix(key.as_slice()) else { return Ok(false) };
+                    node = child;
+                    path = rest;
+                }
+                SparseNode::Branch { children } => {
+                    let Some((nibble, rest)) = path.split_first() else { return Ok(false) };
+                    // removals report the blinded sibling the branch would be merged with
+                    let idx = children
+                        .iter()
+                        .position(|child| *child == SparseNode::Blinded(hash))
+                        .unwrap_or(*nibble as usize);
+                    node = &mut children[idx];
+                    path = rest;
+                }
+                _ => return Ok(false),
+            }
+        }
+    }
+
+    /// Returns the RLP encoded value of the hashed key, or `None` if the key does not exist.
+    pub fn get(&self, hashed_key: H256) -> Result<Option<&[u8]>, SparseTrieError> {
+        let nibbles = key_nibbles(hashed_key.as_bytes());
+        let mut path = nibbles.as_slice();
+        let mut node = &self.root;
+        loop {
+            match node {
+                SparseNode::Empty => return Ok(None),
+                SparseNode::Blinded(hash) => return Err(SparseTrieError::BlindedNode(*hash)),
+                SparseNode::Leaf { key, value } => {
+                    return Ok((key.as_slice() == path).then_some(value.as_slice()))
+                }
+                SparseNode::Extension { key, child } => {
+                    let Some(rest) = path.strip_prefix(key.as_slice()) else { return Ok(None) };
+                    node = child;
+                    path = rest;
+                }
+                SparseNode::Branch { children } => {
+                    let Some((nibble, rest)) = path.split_first() else { return Ok(None) };
+                    node = &children[*nibble as usize];
+                    path = rest;
+                }
+            }
+        }
+    }
+
+    /// Inserts or updates the RLP encoded value of the hashed key.
+    pub fn update(&mut self, hashed_key: H256, value: Vec<u8>) -> Result<(), SparseTrieError> {
+        insert(&mut self.root, &key_nibbles(hashed_key.as_bytes()), value)
+    }
+
+    /// Removes the hashed key from the trie.
+    ///
+    /// Returns `true` if the key existed. Fails without modifying the trie if the removal would
+    /// require to merge a branch with a blinded node.
+    pub fn remove(&mut self, hashed_key: H256) -> Result<bool, SparseTrieError> {
+        remove(&mut self.root, &key_nibbles(hashed_key.as_bytes()))
+    }
+
+    /// Returns `true` if the trie is empty.
+    pub fn is_empty(&self) -> bool {
+        self.root == SparseNode::Empty
+    }
+
+    /// Replaces all subtries left of the path of the hashed key with their hashes.
+    ///
+    /// When keys are inserted in ascending order, these subtries are final, so sealing them after
+    /// every insert keeps only the nodes on the path of the last key in memory.
+    pub(crate) fn seal_left_of(&mut self, hashed_key: H256) {
+        let nibbles = key_nibbles(hashed_key.as_bytes());
+        let mut path = nibbles.as_slice();
+        let mut node = &mut self.root;
+        loop {
+            match node {
+                SparseNode::Extension { key, child } => {
+                    let Some(rest) = path.strip_prefix(key.as_slice()) else { return };
+                    node = child;
+                    path = rest;
+                }
+                SparseNode::Branch { children } => {
+                    let Some((nibble, rest)) = path.split_first() else { return };
+                    let (left, right) = children.split_at_mut(*nibble as usize);
+                    left.iter_mut().for_each(seal);
+                    node = &mut right[0];
+                    path = rest;
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Returns the RLP encoded reference to the root node as the child of a branch node, with the
+    /// first nibble of its path removed.
+    ///
+    /// All keys of the trie must start with the same nibble.
+    pub(crate) fn branch_child_ref(&self) -> Vec<u8> {
+        match &self.root {
+            SparseNode::Leaf { key, value } => node_ref(encode_leaf(&key[1..], value)),
+            SparseNode::Extension { key, child } if key.len() == 1 => child_ref(child),
+            SparseNode::Extension { key, child } => {
+                node_ref(encode_extension(&key[1..], &child_ref(child)))
+            }
+            node => {
+                debug_assert!(
+                    !matches!(node, SparseNode::Branch { .. }),
+                    "keys start with different nibbles"
+                );
+                child_ref(node)
+            }
+        }
+    }
+
+    /// Returns the RLP encoded reference to the child of the root node at the nibble, or `None`
+    /// if the root is not a revealed branch node.
+    pub(crate) fn root_child_ref(&self, nibble: usize) -> Option<Vec<u8>> {
+        match &self.root {
+            SparseNode::Branch { children } => Some(child_ref(&children[nibble])),
+            _ => None,
+        }
+    }
+
+    /// Returns the RLP encoded nodes that are revealed, including the nodes that are embedded in
+    /// their parents.
+    pub(crate) fn revealed_nodes(&self) -> Vec<Vec<u8>> {
+        let mut nodes = Vec::new();
+        collect_revealed(&self.root, &mut nodes);
+        nodes
+    }
+
+    /// Computes the root of the trie.
+    pub fn root(&self) -> H256 {
+        match &self.root {
+            SparseNode::Empty => EMPTY_ROOT,
+            SparseNode::Blinded(hash) => *hash,
+            node => keccak256(encode(node)),
+        }
+    }
+}
+
+/// Builds the sparse node for the child reference from the available proof nodes.
+fn reveal(
+    child: ChildRef<'_>,
+    nodes: &HashMap<H256, Vec<u8>>,
+) -> Result<SparseNode, SparseTrieError> {
+    let node = match child {
+        ChildRef::Empty => return Ok(SparseNode::Empty),
+        ChildRef::Hash(hash) => match nodes.get(&hash) {
+            Some(node) => node.as_slice(),
+            None => return Ok(SparseNode::Blinded(hash)),
+        },
+        ChildRef::Inline(node) => node,
+    };
+
+    Ok(match TrieNode::decode(node)? {
+        TrieNode::Branch(children) => {
+            let mut revealed: Box<[SparseNode; 16]> =
+                Box::new(std::array::from_fn(|_| SparseNode::Empty));
+            for (revealed, child) in revealed.iter_mut().zip(children) {
+                *revealed = reveal(child, nodes)?;
+            }
+            SparseNode::Branch { children: revealed }
+        }
+        TrieNode::Extension(key, child) => {
+            SparseNode::Extension { key, child: Box::new(reveal(child, nodes)?) }
+        }
+        TrieNode::Leaf(key, value) => SparseNode::Leaf { key, value: value.to_vec() },
+    })
+}
+
+fn insert(node: &mut SparseNode, path: &[u8], value: Vec<u8>) -> Result<(), SparseTrieError> {
+    match node {
+        SparseNode::Empty => *node = SparseNode::Leaf { key: path.to_vec(), value },
+        SparseNode::Blinded(hash) => return Err(SparseTrieError::BlindedNode(*hash)),
+        SparseNode::Leaf { key, value: existing } => {
+            if key.as_slice() == path {
+                *existing = value
+            } else {
+                // split the leaf at the first diverging nibble
+                let shared = common_prefix(key, path);
+                if shared == key.len() || shared == path.len() {
+                    return Err(DecodeError::Custom("key is a prefix of another key").into())
+                }
+                let mut children: Box<[SparseNode; 16]> =
+                    Box::new(std::array::from_fn(|_| SparseNode::Empty));
+                children[key[shared] as usize] = SparseNode::Leaf {
+                    key: key[shared + 1..].to_vec(),
+                    value: std::mem::take(existing),
+                };
+                children[path[shared] as usize] =
+                    SparseNode::Leaf { key: path[shared + 1..].to_vec(), value };
+                *node = with_extension(&path[..shared], SparseNode::Branch { children });
+            }
+        }
+        SparseNode::Extension { key, child } => {
+            if let Some(rest) = path.strip_prefix(key.as_slice()) {
+                return insert(child, rest, value)
+            }
+
+            // split the extension at the first diverging nibble
+            let shared = common_prefix(key, path);
+            if shared == path.len() {
+                return Err(DecodeError::Custom("key is a prefix of another key").into())
+            }
+            let child = std::mem::replace(child.as_mut(), SparseNode::Empty);
+            let mut children: Box<[SparseNode; 16]> =
+                Box::new(std::array::from_fn(|_| SparseNode::Empty));
+            children[key[shared] as usize] = with_extension(&key[shared + 1..], child);
+            children[path[shared] as usize] =
+                SparseNode::Leaf { key: path[shared + 1..].to_vec(), value };
+            *node = with_extension(&path[..shared], SparseNode::Branch { children });
+        }
+        SparseNode::Branch { children } => {
+            let (nibble, rest) =
+                path.split_first().ok_or(DecodeError::Custom("key is a prefix of another key"))?;
+            return insert(&mut children[*nibble as usize], rest, value)
+        }
+    }
+    Ok(())
+}
+
+fn remove(node: &mut SparseNode, path: &[u8]) -> Result<bool, SparseTrieError> {
+    match node {
+        SparseNode::Empty => Ok(false),
+        SparseNode::Blinded(hash) => Err(SparseTrieError::BlindedNode(*hash)),
+        SparseNode::Leaf { key, .. } => {
+            if key.as_slice() != path {
+                return Ok(false)
+            }
+            *node = SparseNode::Empty;
+            Ok(true)
+        }
+        SparseNode::Extension { key, child } => {
+            let Some(rest) = path.strip_prefix(key.as_slice()) else { return Ok(false) };
+            if !remove(child, rest)? {
+                return Ok(false)
+            }
+
+            // the child branch may have been collapsed, merge it into the extension
+            let merged = match std::mem::replace(child.as_mut(), SparseNode::Empty) {
+                SparseNode::Leaf { key: child_key, value } => {
+                    SparseNode::Leaf { key: [key.as_slice(), &child_key].concat(), value }
+                }
+                SparseNode::Extension { key: child_key, child } => {
+                    SparseNode::Extension { key: [key.as_slice(), &child_key].concat(), child }
+                }
+                child => SparseNode::Extension { key: std::mem::take(key), child: Box::new(child) },
+            };
+            *node = merged;
+            Ok(true)
+        }
+        SparseNode::Branch { children } => {
+            let Some((nibble, rest)) = path.split_first() else { return Ok(false) };
+            let nibble = *nibble as usize;
+
+            // if the removal leaves a single child, the branch is merged with that child, so it
+            // must be revealed
+            let mut remaining = children
+                .iter()
+                .enumerate()
+                .filter(|(idx, child)| *idx != nibble && **child != SparseNode::Empty);
+            if let (Some((_, SparseNode::Blinded(hash))), None) =
+                (remaining.next(), remaining.next())
+            {
+                if matches!(&children[nibble], SparseNode::Leaf { key, .. } if key.as_slice() == rest)
+                {
+                    return Err(SparseTrieError::BlindedNode(*hash))
+                }
+            }
+
+            if !remove(&mut children[nibble], rest)? {
+                return Ok(false)
+            }
+
+            let mut remaining =
+                children.iter().enumerate().filter(|(_, child)| **child != SparseNode::Empty);
+            if let (Some((idx, _)), None) = (remaining.next(), remaining.next()) {
+                let child = std::mem::replace(&mut children[idx], SparseNode::Empty);
+                *node = match child {
+                    SparseNode::Leaf { key, value } => {
+                        SparseNode::Leaf { key: [&[idx as u8], key.as_slice()].concat(), value }
+                    }
+                    SparseNode::Extension { key, child } => SparseNode::Extension {
+                        key: [&[idx as u8], key.as_slice()].concat(),
+                        child,
+                    },
+                    child => SparseNode::Extension { key: vec![idx as u8], child: Box::new(child) },
+                };
+            }
+            Ok(true)
+        }
+    }
+}
+
+/// Wraps the node in an extension with the given key, unless the key is empty.
+fn with_extension(key: &[u8], node: SparseNode) -> SparseNode {
+    if key.is_empty() {
+        return node
+    }
+    match node {
+        SparseNode::Leaf { key: rest, value } => {
+            SparseNode::Leaf { key: [key, rest.as_slice()].concat(), value }
+        }
+        SparseNode::Extension { key: rest, child } => {
+            SparseNode::Extension { key: [key, rest.as_slice()].concat(), child }
+        }
+        node => SparseNode::Extension { key: key.to_vec(), child: Box::new(node) },
+    }
+}
+
+/// Replaces the node with its hash, unless it is embedded in its parent.
+fn seal(node: &mut SparseNode) {
+    if matches!(node, SparseNode::Empty | SparseNode::Blinded(_)) {
+        return
+    }
+    let encoded = encode(node);
+    if encoded.len() >= 32 {
+        *node = SparseNode::Blinded(keccak256(encoded));
+    }
+}
+
+/// Collects the RLP encodings of the node and all revealed nodes below it.
+fn collect_revealed(node: &SparseNode, nodes: &mut Vec<Vec<u8>>) {
+    match node {
+        SparseNode::Empty | SparseNode::Blinded(_) => return,
+        SparseNode::Leaf { .. } => {}
+        SparseNode::Extension { child, .. } => collect_revealed(child, nodes),
+        SparseNode::Branch { children } => {
+            children.iter().for_each(|child| collect_revealed(child, nodes))
+        }
+    }
+    nodes.push(encode(node));
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// RLP encodes the node.
+fn encode(node: &SparseNode) -> Vec<u8> {
+    match node {
+        SparseNode::Empty => vec![EMPTY_STRING_CODE],
+        SparseNode::Blinded(hash) => hash_ref(*hash),
+        SparseNode::Leaf { key, value } => encode_leaf(key, value),
+        SparseNode::Extension { key, child } => encode_extension(key, &child_ref(child)),
+        SparseNode::Branch { children } => {
+            encode_branch(&std::array::from_fn(|idx| child_ref(&children[idx])))
+        }
+    }
+}
+
+/// Returns the RLP encoded reference to the node.
+fn child_ref(node: &SparseNode) -> Vec<u8> {
+    match node {
+        SparseNode::Empty => vec![EMPTY_STRING_CODE],
+        SparseNode::Blinded(hash) => hash_ref(*hash),
+        node => node_ref(encode(node)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cita_trie::{MemoryDB, PatriciaTrie, Trie};
+    use hasher::HasherKeccak;
+    use std::{collections::BTreeMap, sync::Arc};
+
+    fn trie(entries: &HashMap<H256, Vec<u8>>) -> PatriciaTrie<MemoryDB, HasherKeccak> {
+        let mut trie =
+            PatriciaTrie::new(Arc::new(MemoryDB::new(true)), Arc::new(HasherKeccak::new()));
+        for (key, value) in entries {
+            trie.insert(key.as_bytes().to_vec(), value.clone()).unwrap();
+        }
+        trie
+    }
+
+    fn root(entries: &HashMap<H256, Vec<u8>>) -> H256 {
+        H256::from_slice(&trie(entries).root().unwrap())
+    }
+
+    fn entries(num: u64) -> HashMap<H256, Vec<u8>> {
+        (0..num)
+            .map(|i| (keccak256(H256::from_low_u64_be(i)), vec![0x42; 1 + i as usize % 40]))
+            .collect()
+    }
+
+    #[test]
+    fn update_and_insert_over_proofs() {
+        let mut entries = entries(50);
+        let mut trie = trie(&entries);
+        let root = H256::from_slice(&trie.root().unwrap());
+
+        let updated = keccak256(H256::from_low_u64_be(7));
+        let inserted = keccak256(H256::from_low_u64_be(1000));
+        let nodes = [updated, inserted]
+            .iter()
+            .flat_map(|key| trie.get_proof(key.as_bytes()).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut sparse = SparseTrie::from_proof_nodes(root, nodes).unwrap();
+        assert_eq!(sparse.root(), root);
+        assert_eq!(sparse.get(updated).unwrap(), Some(entries[&updated].as_slice()));
+        assert_eq!(sparse.get(inserted).unwrap(), None);
+
+        sparse.update(updated, vec![0x01]).unwrap();
+        sparse.update(inserted, vec![0x02; 64]).unwrap();
+        entries.insert(updated, vec![0x01]);
+        entries.insert(inserted, vec![0x02; 64]);
+        assert_eq!(sparse.root(), self::root(&entries));
+
+        // keys that are not on the proven paths are blinded
+        let blinded = entries
+            .keys()
+            .find(|key| {
+                key.as_bytes()[0] >> 4 != updated.as_bytes()[0] >> 4 &&
+                    key.as_bytes()[0] >> 4 != inserted.as_bytes()[0] >> 4
+            })
+            .unwrap();
+        assert!(matches!(sparse.get(*blinded), Err(SparseTrieError::BlindedNode(_))));
+        assert!(matches!(
+            sparse.update(*blinded, vec![0x03]),
+            Err(SparseTrieError::BlindedNode(_))
+        ));
+    }
+
+    #[test]
+    fn remove_revealed() {
+        let mut entries = entries(20);
+        let trie = trie(&entries);
+        let mut sparse = SparseTrie::from_proof_nodes(
+            self::root(&entries),
+            entries.keys().flat_map(|key| trie.get_proof(key.as_bytes()).unwrap()),
+        )
+        .unwrap();
+
+        let keys = entries.keys().copied().collect::<Vec<_>>();
+        for key in keys {
+            assert!(sparse.remove(key).unwrap());
+            assert!(!sparse.remove(key).unwrap());
+            entries.remove(&key);
+            assert_eq!(sparse.root(), self::root(&entries));
+        }
+        assert_eq!(sparse.root(), EMPTY_ROOT);
+    }
+
+    #[test]
+    fn seal_sorted_inserts() {
+        let entries = entries(100).into_iter().collect::<BTreeMap<_, _>>();
+        let mut sealed = SparseTrie::default();
+        for (key, value) in &entries {
+            sealed.update(*key, value.clone()).unwrap();
+            sealed.seal_left_of(*key);
+        }
+        assert_eq!(sealed.root(), root(&entries.into_iter().collect()));
+    }
+
+    #[test]
+    fn reveal_on_demand() {
+        let mut entries = entries(50);
+        let trie = trie(&entries);
+        let nodes = entries
+            .keys()
+            .flat_map(|key| trie.get_proof(key.as_bytes()).unwrap())
+            .map(|node| (keccak256(&node), node))
+            .collect::<HashMap<_, _>>();
+
+        let mut sparse = SparseTrie::blinded(self::root(&entries));
+        let keys = entries.keys().copied().collect::<Vec<_>>();
+        for key in keys.into_iter().take(25) {
+            loop {
+                match sparse.remove(key) {
+                    Err(SparseTrieError::BlindedNode(hash)) => {
+                        assert!(sparse.reveal_node(key, hash, &nodes[&hash]).unwrap())
+                    }
+                    removed => break assert!(removed.unwrap()),
+                }
+            }
+            entries.remove(&key);
+            assert_eq!(sparse.root(), self::root(&entries));
+        }
+    }
+
+    #[test]
+    fn build_from_empty() {
+        let entries = entries(100);
+        let mut sparse = SparseTrie::default();
+        for (key, value) in &entries {
+            sparse.update(*key, value.clone()).unwrap();
+        }
+        assert_eq!(sparse.root(), root(&entries));
+
+        // without proofs only the root is known
+        let blinded = SparseTrie::from_proof_nodes(sparse.root(), Vec::<Vec<u8>>::new()).unwrap();
+        assert_eq!(blinded.root(), sparse.root());
+    }
+}
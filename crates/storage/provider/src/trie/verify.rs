@@ -0,0 +1,306 @@
+//! Verification of merkle proofs in the [EIP-1186](https://eips.ethereum.org/EIPS/eip-1186) format
+//! without database access.
+//!
+//! A proof is the list of RLP encoded trie nodes on the path from the root to the key, as returned
+//! by `eth_getProof`. Proofs of absence end with the node where the path of the key diverges.
+
+use super::{
+    node::{key_nibbles, ChildRef, TrieNode},
+    EthAccount, SparseTrieError,
+};
+use reth_primitives::{keccak256, proofs::EMPTY_ROOT, Address, Bytes, H256, U256};
+use reth_rlp::{encode_fixed_size, DecodeError, Encodable};
+
+/// Proof verification error types.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ProofVerificationError {
+    /// The proof ended before the path of the key was resolved.
+    #[error("Missing proof node with hash {0:?}")]
+    MissingNode(H256),
+    /// The hash of a proof node does not match the reference of its parent, or the root.
+    #[error("Proof node hash mismatch: expected {expected:?}, got {got:?}")]
+    NodeHashMismatch { expected: H256, got: H256 },
+    /// The proof contains more nodes than are on the path of the key.
+    #[error("Unexpected proof nodes after the end of the path")]
+    UnusedNodes,
+    /// A proof node is not a valid trie node.
+    #[error("Invalid proof node: {0:?}")]
+    InvalidNode(#[from] DecodeError),
+    /// The proven value does not match the expected value.
+    #[error("Proven value {got:?} does not match the expected value {expected:?}")]
+    ValueMismatch { expected: Option<Bytes>, got: Option<Bytes> },
+}
+
+impl From<SparseTrieError> for ProofVerificationError {
+    fn from(err: SparseTrieError) -> Self {
+        match err {
+            SparseTrieError::BlindedNode(hash) => ProofVerificationError::MissingNode(hash),
+            SparseTrieError::InvalidNode(err) => ProofVerificationError::InvalidNode(err),
+        }
+    }
+}
+
+/// Verifies the proof of an account against the state root.
+///
+/// If `expected_account` is `None` the proof must show that the account does not exist.
+pub fn verify_account_proof<T: AsRef<[u8]>>(
+    state_root: H256,
+    address: Address,
+    expected_account: Option<EthAccount>,
+    proof: &[T],
+) -> Result<(), ProofVerificationError> {
+    let expected = expected_account.map(|account| {
+        let mut out = Vec::new();
+        account.encode(&mut out);
+        out
+    });
+    verify_proof(state_root, keccak256(address), expected.as_deref(), proof)
+}
+
+/// Verifies the proof of a storage slot against the storage root of the account.
+///
+/// Following [EIP-1186](https://eips.ethereum.org/EIPS/eip-1186), a zero value means that the
+/// proof must show that the slot does not exist.
+pub fn verify_storage_proof<T: AsRef<[u8]>>(
+    storage_root: H256,
+    slot: H256,
+    expected_value: U256,
+    proof: &[T],
+) -> Result<(), ProofVerificationError> {
+    let expected = (expected_value != U256::ZERO).then(|| encode_fixed_size(&expected_value));
+    verify_proof(storage_root, keccak256(slot), expected.as_deref(), proof)
+}
+
+/// Verifies that the proof resolves the hashed key to the expected RLP encoded value, or proves
+/// its absence if the expected value is `None`.
+pub fn verify_proof<T: AsRef<[u8]>>(
+    root: H256,
+    hashed_key: H256,
+    expected_value: Option<&[u8]>,
+    proof: &[T],
+) -> Result<(), ProofVerificationError> {
+    let value = proven_value(root, hashed_key, proof)?;
+    if value != expected_value {
+        return Err(ProofVerificationError::ValueMismatch {
+            expected: expected_value.map(Bytes::from),
+            got: value.map(Bytes::from),
+        })
+    }
+    Ok(())
+}
+
+/// Walks the proof along the path of the hashed key and returns the value of the leaf, or `None` if
+/// the proof shows that the key does not exist.
+pub fn proven_value<T: AsRef<[u8]>>(
+    root: H256,
+    hashed_key: H256,
+    proof: &[T],
+) -> Result<Option<&[u8]>, ProofVerificationError> {
+    let mut nodes = proof.iter().map(AsRef::<[u8]>::as_ref).peekable();
+    if root == EMPTY_ROOT && nodes.peek().is_none() {
+        return Ok(None)
+    }
+
+    let nibbles = key_nibbles(hashed_key.as_bytes());
+    let mut path = nibbles.as_slice();
+    let mut next = ChildRef::Hash(root);
+    let value = loop {
+        let node = match next {
+            ChildRef::Empty => break None,
+            ChildRef::Hash(expected) => {
+                let node = nodes.next().ok_or(ProofVerificationError::MissingNode(expected))?;
+                let got = keccak256(node);
+                if got != expected {
+                    return Err(ProofVerificationError::NodeHashMismatch { expected, got })
+                }
+                node
+            }
+            ChildRef::Inline(node) => {
+                // some implementations include embedded nodes in the proof as well
+                if nodes.peek() == Some(&node) {
+                    nodes.next();
+                }
+                node
+            }
+        };
+
+        match TrieNode::decode(node)? {
+            TrieNode::Branch(children) => {
+                let Some((nibble, rest)) = path.split_first() else { break None };
+                next = children[*nibble as usize].clone();
+                path = rest;
+            }
+            TrieNode::Extension(key, child) => {
+                let Some(rest) = path.strip_prefix(key.as_slice()) else { break None };
+                next = child;
+                path = rest;
+            }
+            TrieNode::Leaf(key, value) => break (key == path).then_some(value),
+        }
+    };
+
+    if nodes.next().is_some() {
+        return Err(ProofVerificationError::UnusedNodes)
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cita_trie::{MemoryDB, PatriciaTrie, Trie};
+    use hasher::HasherKeccak;
+    use reth_primitives::{hex_literal::hex, Account};
+    use std::sync::Arc;
+
+    /// Returns the root and proofs of the given keys of a trie with the given hashed entries.
+    fn trie_with_proofs(entries: &[(H256, Vec<u8>)], keys: &[H256]) -> (H256, Vec<Vec<Vec<u8>>>) {
+        let mut trie =
+            PatriciaTrie::new(Arc::new(MemoryDB::new(true)), Arc::new(HasherKeccak::new()));
+        for (key, value) in entries {
+            trie.insert(key.as_bytes().to_vec(), value.clone()).unwrap();
+        }
+        let root = H256::from_slice(&trie.root().unwrap());
+        let proofs = keys.iter().map(|key| trie.get_proof(key.as_bytes()).unwrap()).collect();
+        (root, proofs)
+    }
+
+    // account proof of `0x000d836201318ec6899a67540690382780743280` at the mainnet genesis,
+    // extracted from geth via `eth_getProof`
+    fn mainnet_genesis_proof() -> Vec<Vec<u8>> {
+        vec![
+            hex!("f90211a090dcaf88c40c7bbc95a912cbdde67c175767b31173df9ee4b0d733bfdd511c43a0babe369f6b12092f49181ae04ca173fb68d1a5456f18d20fa32cba73954052bda0473ecf8a7e36a829e75039a3b055e51b8332cbf03324ab4af2066bbd6fbf0021a0bbda34753d7aa6c38e603f360244e8f59611921d9e1f128372fec0d586d4f9e0a04e44caecff45c9891f74f6a2156735886eedf6f1a733628ebc802ec79d844648a0a5f3f2f7542148c973977c8a1e154c4300fec92f755f7846f1b734d3ab1d90e7a0e823850f50bf72baae9d1733a36a444ab65d0a6faaba404f0583ce0ca4dad92da0f7a00cbe7d4b30b11faea3ae61b7f1f2b315b61d9f6bd68bfe587ad0eeceb721a07117ef9fc932f1a88e908eaead8565c19b5645dc9e5b1b6e841c5edbdfd71681a069eb2de283f32c11f859d7bcf93da23990d3e662935ed4d6b39ce3673ec84472a0203d26456312bbc4da5cd293b75b840fc5045e493d6f904d180823ec22bfed8ea09287b5c21f2254af4e64fca76acc5cd87399c7f1ede818db4326c98ce2dc2208a06fc2d754e304c48ce6a517753c62b1a9c1d5925b89707486d7fc08919e0a94eca07b1c54f15e299bd58bdfef9741538c7828b5d7d11a489f9c20d052b3471df475a051f9dd3739a927c89e357580a4c97b40234aa01ed3d5e0390dc982a7975880a0a089d613f26159af43616fd9455bb461f4869bfede26f2130835ed067a8b967bfb80").to_vec(),
+            hex!("f90211a0dae48f5b47930c28bb116fbd55e52cd47242c71bf55373b55eb2805ee2e4a929a00f1f37f337ec800e2e5974e2e7355f10f1a4832b39b846d916c3597a460e0676a0da8f627bb8fbeead17b318e0a8e4f528db310f591bb6ab2deda4a9f7ca902ab5a0971c662648d58295d0d0aa4b8055588da0037619951217c22052802549d94a2fa0ccc701efe4b3413fd6a61a6c9f40e955af774649a8d9fd212d046a5a39ddbb67a0d607cdb32e2bd635ee7f2f9e07bc94ddbd09b10ec0901b66628e15667aec570ba05b89203dc940e6fa70ec19ad4e01d01849d3a5baa0a8f9c0525256ed490b159fa0b84227d48df68aecc772939a59afa9e1a4ab578f7b698bdb1289e29b6044668ea0fd1c992070b94ace57e48cbf6511a16aa770c645f9f5efba87bbe59d0a042913a0e16a7ccea6748ae90de92f8aef3b3dc248a557b9ac4e296934313f24f7fced5fa042373cf4a00630d94de90d0a23b8f38ced6b0f7cb818b8925fee8f0c2a28a25aa05f89d2161c1741ff428864f7889866484cef622de5023a46e795dfdec336319fa07597a017664526c8c795ce1da27b8b72455c49657113e0455552dbc068c5ba31a0d5be9089012fda2c585a1b961e988ea5efcd3a06988e150a8682091f694b37c5a0f7b0352e38c315b2d9a14d51baea4ddee1770974c806e209355233c3c89dce6ea049bf6e8df0acafd0eff86defeeb305568e44d52d2235cf340ae15c6034e2b24180").to_vec(),
+            hex!("f901f1a0cf67e0f5d5f8d70e53a6278056a14ddca46846f5ef69c7bde6810d058d4a9eda80a06732ada65afd192197fe7ce57792a7f25d26978e64e954b7b84a1f7857ac279da05439f8d011683a6fc07efb90afca198fd7270c795c835c7c85d91402cda992eaa0449b93033b6152d289045fdb0bf3f44926f831566faa0e616b7be1abaad2cb2da031be6c3752bcd7afb99b1bb102baf200f8567c394d464315323a363697646616a0a40e3ed11d906749aa501279392ffde868bd35102db41364d9c601fd651f974aa0044bfa4fe8dd1a58e6c7144da79326e94d1331c0b00373f6ae7f3662f45534b7a098005e3e48db68cb1dc9b9f034ff74d2392028ddf718b0f2084133017da2c2e7a02a62bc40414ee95b02e202a9e89babbabd24bef0abc3fc6dcd3e9144ceb0b725a0239facd895bbf092830390a8676f34b35b29792ae561f196f86614e0448a5792a0a4080f88925daff6b4ce26d188428841bd65655d8e93509f2106020e76d41eefa04918987904be42a6894256ca60203283d1b89139cf21f09f5719c44b8cdbb8f7a06201fc3ef0827e594d953b5e3165520af4fceb719e11cc95fd8d3481519bfd8ca05d0e353d596bd725b09de49c01ede0f29023f0153d7b6d401556aeb525b2959ba0cd367d0679950e9c5f2aa4298fd4b081ade2ea429d71ff390c50f8520e16e30880").to_vec(),
+            hex!("f87180808080808080a0dbee8b33c73b86df839f309f7ac92eee19836e08b39302ffa33921b3c6a09f66a06068b283d51aeeee682b8fb5458354315d0b91737441ede5e137c18b4775174a8080808080a0fe7779c7d58c2fda43eba0a6644043c86ebb9ceb4836f89e30831f23eb059ece8080").to_vec(),
+            hex!("f8719f20b71c90b0d523dd5004cf206f325748da347685071b34812e21801f5270c4b84ff84d80890ad78ebc5ac6200000a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a0c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470").to_vec(),
+        ]
+    }
+
+    const MAINNET_GENESIS_ROOT: H256 =
+        H256(hex!("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544"));
+
+    const MAINNET_ADDRESS: Address = Address(hex!("000d836201318ec6899a67540690382780743280"));
+
+    fn mainnet_account() -> EthAccount {
+        Account {
+            nonce: 0,
+            balance: U256::from(200) * U256::from(10u64.pow(18)),
+            ..Default::default()
+        }
+        .into()
+    }
+
+    #[test]
+    fn verify_mainnet_account_proof() {
+        let proof = mainnet_genesis_proof();
+        verify_account_proof(
+            MAINNET_GENESIS_ROOT,
+            MAINNET_ADDRESS,
+            Some(mainnet_account()),
+            &proof,
+        )
+        .unwrap();
+
+        // the same proof doesn't prove another balance or the absence of the account
+        let other = Account { balance: U256::from(1), ..Default::default() }.into();
+        assert!(matches!(
+            verify_account_proof(MAINNET_GENESIS_ROOT, MAINNET_ADDRESS, Some(other), &proof),
+            Err(ProofVerificationError::ValueMismatch { .. })
+        ));
+        assert!(matches!(
+            verify_account_proof(MAINNET_GENESIS_ROOT, MAINNET_ADDRESS, None, &proof),
+            Err(ProofVerificationError::ValueMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn reject_corrupted_branch_node() {
+        for idx in 0..4 {
+            let mut proof = mainnet_genesis_proof();
+            // flip a single nibble of a child hash in the branch node
+            proof[idx][10] ^= 0x01;
+            assert!(matches!(
+                verify_account_proof(
+                    MAINNET_GENESIS_ROOT,
+                    MAINNET_ADDRESS,
+                    Some(mainnet_account()),
+                    &proof
+                ),
+                Err(ProofVerificationError::NodeHashMismatch { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn reject_incomplete_or_extended_proof() {
+        let mut proof = mainnet_genesis_proof();
+        let leaf = proof.pop().unwrap();
+        assert!(matches!(
+            verify_account_proof(
+                MAINNET_GENESIS_ROOT,
+                MAINNET_ADDRESS,
+                Some(mainnet_account()),
+                &proof
+            ),
+            Err(ProofVerificationError::MissingNode(_))
+        ));
+
+        proof.push(leaf.clone());
+        proof.push(leaf);
+        assert_eq!(
+            verify_account_proof(
+                MAINNET_GENESIS_ROOT,
+                MAINNET_ADDRESS,
+                Some(mainnet_account()),
+                &proof
+            ),
+            Err(ProofVerificationError::UnusedNodes)
+        );
+    }
+
+    #[test]
+    fn verify_storage_proofs() {
+        let slots = (0..100u64).map(H256::from_low_u64_be).collect::<Vec<_>>();
+        let entries = slots
+            .iter()
+            .map(|slot| {
+                let value = U256::from(slot.to_low_u64_be() + 1);
+                (keccak256(slot), encode_fixed_size(&value).to_vec())
+            })
+            .collect::<Vec<_>>();
+
+        let missing = H256::from_low_u64_be(1000);
+        let keys = [keccak256(slots[42]), keccak256(missing)];
+        let (root, proofs) = trie_with_proofs(&entries, &keys);
+
+        verify_storage_proof(root, slots[42], U256::from(43), &proofs[0]).unwrap();
+        assert!(verify_storage_proof(root, slots[42], U256::from(42), &proofs[0]).is_err());
+        assert!(verify_storage_proof(root, slots[42], U256::ZERO, &proofs[0]).is_err());
+
+        // proof of absence
+        verify_storage_proof(root, missing, U256::ZERO, &proofs[1]).unwrap();
+        assert!(verify_storage_proof(root, missing, U256::from(1), &proofs[1]).is_err());
+
+        // a proof of a different key doesn't prove the slot
+        assert!(verify_storage_proof(root, slots[1], U256::from(2), &proofs[0]).is_err());
+
+        // corrupting a nibble of the root branch node invalidates the proof
+        let mut corrupted = proofs[0].clone();
+        corrupted[0][5] ^= 0x10;
+        assert!(matches!(
+            verify_storage_proof(root, slots[42], U256::from(43), &corrupted),
+            Err(ProofVerificationError::NodeHashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_empty_trie() {
+        let proof: Vec<Vec<u8>> = Vec::new();
+        verify_storage_proof(EMPTY_ROOT, H256::zero(), U256::ZERO, &proof).unwrap();
+        assert!(matches!(
+            verify_storage_proof(EMPTY_ROOT, H256::zero(), U256::from(1), &proof),
+            Err(ProofVerificationError::ValueMismatch { .. })
+        ));
+    }
+}
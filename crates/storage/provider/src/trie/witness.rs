@@ -0,0 +1,214 @@
+//! Witnesses of the state that blocks are executed on.
+//!
+//! A witness consists of the trie nodes that reveal the accounts and slots read during the
+//! execution, plus the nodes needed to compute the state root after the execution. This is enough
+//! to execute the block and verify its state root without access to the database, see
+//! [WitnessStateProvider].
+
+use super::{
+    overlay::{SparseState, TrieNodeLoader},
+    HashedPostState, StateRoot, TrieError,
+};
+use crate::{AccountProvider, BlockHashProvider, ProviderError, StateProvider};
+use reth_db::transaction::DbTx;
+use reth_primitives::{
+    keccak256, Account, Address, Bytecode, Bytes, StorageKey, StorageValue, H256, KECCAK_EMPTY,
+    U256,
+};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Mutex,
+};
+
+impl<'tx, 'db, TX: DbTx<'db>> StateRoot<'tx, TX> {
+    /// Collects the trie nodes needed to read the given accounts and slots, and to compute the
+    /// state root after applying the changes of the post state.
+    ///
+    /// The witnessed state is the state with the given root in the database after applying the
+    /// `reverts`, see [HashedPostState::from_reverts]. The targets are hashed addresses with the
+    /// hashed slots that are read of each account.
+    pub fn witness(
+        &self,
+        root: H256,
+        reverts: &HashedPostState,
+        targets: &BTreeMap<H256, BTreeSet<H256>>,
+        post_state: &HashedPostState,
+    ) -> Result<Vec<Vec<u8>>, TrieError> {
+        let mut reverted = SparseState::blinded(root);
+        reverted.apply(reverts, self)?;
+
+        // the nodes changed by the reverts are not in the database, all other nodes of the
+        // witnessed state are
+        let loader = RecordingLoader {
+            revealed: NodeMap::new(reverted.revealed_nodes()),
+            inner: self,
+            recorded: Default::default(),
+        };
+
+        // the same operations a stateless execution does, see `WitnessStateProvider`
+        let mut state = SparseState::blinded(reverted.root());
+        for (hashed_address, slots) in targets {
+            state.account(*hashed_address, &loader)?;
+            for hashed_slot in slots {
+                state.storage(*hashed_address, *hashed_slot, &loader)?;
+            }
+        }
+        state.apply(post_state, &loader)?;
+
+        Ok(loader.recorded.into_inner().into_values().collect())
+    }
+}
+
+/// Trie nodes keyed by their hashes.
+#[derive(Debug, Default)]
+struct NodeMap(HashMap<H256, Vec<u8>>);
+
+impl NodeMap {
+    fn new<T: AsRef<[u8]>>(nodes: impl IntoIterator<Item = T>) -> Self {
+        Self(
+            nodes
+                .into_iter()
+                .map(|node| (keccak256(node.as_ref()), node.as_ref().to_vec()))
+                .collect(),
+        )
+    }
+}
+
+impl TrieNodeLoader for NodeMap {
+    fn account_node(&self, hash: H256) -> Result<Vec<u8>, TrieError> {
+        self.0.get(&hash).cloned().ok_or(TrieError::MissingNode(hash))
+    }
+
+    fn storage_node(&self, _hashed_address: H256, hash: H256) -> Result<Vec<u8>, TrieError> {
+        self.account_node(hash)
+    }
+}
+
+/// Loads the nodes from the revealed nodes or the inner loader and records all loaded nodes.
+struct RecordingLoader<'a, L> {
+    revealed: NodeMap,
+    inner: &'a L,
+    recorded: RefCell<HashMap<H256, Vec<u8>>>,
+}
+
+impl<'a, L> RecordingLoader<'a, L> {
+    fn record(&self, hash: H256, node: Vec<u8>) -> Vec<u8> {
+        self.recorded.borrow_mut().insert(hash, node.clone());
+        node
+    }
+}
+
+impl<'a, L: TrieNodeLoader> TrieNodeLoader for RecordingLoader<'a, L> {
+    fn account_node(&self, hash: H256) -> Result<Vec<u8>, TrieError> {
+        let node = match self.revealed.0.get(&hash) {
+            Some(node) => node.clone(),
+            None => self.inner.account_node(hash)?,
+        };
+        Ok(self.record(hash, node))
+    }
+
+    fn storage_node(&self, hashed_address: H256, hash: H256) -> Result<Vec<u8>, TrieError> {
+        let node = match self.revealed.0.get(&hash) {
+            Some(node) => node.clone(),
+            None => self.inner.storage_node(hashed_address, hash)?,
+        };
+        Ok(self.record(hash, node))
+    }
+}
+
+/// A [StateProvider] over the witness of a block execution, see [StateProvider::witness].
+///
+/// The state is read from the trie nodes of the witness only, so reading accounts or slots that
+/// are not covered by the witness fails with [ProviderError::StateTrie].
+#[derive(Debug)]
+pub struct WitnessStateProvider {
+    /// The tries, revealed as they are read.
+    state: Mutex<SparseState>,
+    nodes: NodeMap,
+    codes: HashMap<H256, Bytecode>,
+    block_hashes: HashMap<u64, H256>,
+}
+
+impl WitnessStateProvider {
+    /// Creates the provider of the state with the given root from the trie nodes, contract codes
+    /// and block hashes of a witness.
+    pub fn new<T: AsRef<[u8]>>(
+        state_root: H256,
+        nodes: impl IntoIterator<Item = T>,
+        codes: impl IntoIterator<Item = Bytes>,
+        block_hashes: impl IntoIterator<Item = (u64, H256)>,
+    ) -> Self {
+        let codes = codes
+            .into_iter()
+            .map(|code| {
+                let hash = keccak256(&code);
+                (hash, Bytecode::new_raw(code.0).with_code_hash(hash))
+            })
+            .collect();
+        Self {
+            state: Mutex::new(SparseState::blinded(state_root)),
+            nodes: NodeMap::new(nodes),
+            codes,
+            block_hashes: block_hashes.into_iter().collect(),
+        }
+    }
+
+    fn with_state<T>(
+        &self,
+        f: impl FnOnce(&mut SparseState, &NodeMap) -> Result<T, TrieError>,
+    ) -> reth_interfaces::Result<T> {
+        let mut state = self.state.lock().expect("not poisoned");
+        f(&mut state, &self.nodes).map_err(|_| ProviderError::StateTrie.into())
+    }
+}
+
+impl AccountProvider for WitnessStateProvider {
+    fn basic_account(&self, address: Address) -> reth_interfaces::Result<Option<Account>> {
+        let account = self.with_state(|state, nodes| state.account(keccak256(address), nodes))?;
+        Ok(account.map(|account| Account {
+            nonce: account.nonce,
+            balance: account.balance,
+            bytecode_hash: (account.code_hash != KECCAK_EMPTY).then_some(account.code_hash),
+        }))
+    }
+}
+
+impl BlockHashProvider for WitnessStateProvider {
+    fn block_hash(&self, number: U256) -> reth_interfaces::Result<Option<H256>> {
+        Ok(self.block_hashes.get(&number.to::<u64>()).copied())
+    }
+}
+
+impl StateProvider for WitnessStateProvider {
+    fn storage(
+        &self,
+        account: Address,
+        storage_key: StorageKey,
+    ) -> reth_interfaces::Result<Option<StorageValue>> {
+        let value = self.with_state(|state, nodes| {
+            state.storage(keccak256(account), keccak256(storage_key), nodes)
+        })?;
+        Ok((value != U256::ZERO).then_some(value))
+    }
+
+    fn bytecode_by_hash(&self, code_hash: H256) -> reth_interfaces::Result<Option<Bytecode>> {
+        Ok(self.codes.get(&code_hash).cloned())
+    }
+
+    fn proof(
+        &self,
+        _address: Address,
+        _keys: &[H256],
+    ) -> reth_interfaces::Result<(Vec<Bytes>, H256, Vec<Vec<Bytes>>)> {
+        Err(ProviderError::StateTrie.into())
+    }
+
+    fn state_root(&self, post_state: HashedPostState) -> reth_interfaces::Result<H256> {
+        self.with_state(|state, nodes| {
+            let mut state = state.clone();
+            state.apply(&post_state, nodes)?;
+            Ok(state.root())
+        })
+    }
+}
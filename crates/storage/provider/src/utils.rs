@@ -1,19 +1,202 @@
 use reth_db::{
-    models::{StoredBlockBody, StoredBlockOmmers, StoredBlockWithdrawals},
+    cursor::DbCursorRO,
+    models::{StoredBlockBodyIndices, StoredBlockOmmers, StoredBlockWithdrawals},
     tables,
     transaction::{DbTx, DbTxMut},
 };
-use reth_interfaces::{provider::ProviderError, Result};
-use reth_primitives::{Address, SealedBlock, TransitionId};
+use reth_interfaces::{
+    consensus::ForkchoiceState, db::Error as DbError, provider::ProviderError, Result,
+};
+use reth_primitives::{
+    Address, BlockNumber, ForkCondition, Hardfork, SealedBlock, TransitionId, H256,
+};
+
+/// The key of the lowest available block in the [tables::SyncStage] table.
+///
+/// The headers, bodies, receipts and state of the blocks below it are absent from the database,
+/// because the node was synced from a trusted checkpoint. The key is absent if all blocks since
+/// genesis are available.
+pub const LOWEST_AVAILABLE_BLOCK: &str = "LowestAvailableBlock";
+
+/// Returns the lowest block with available data, `0` if all blocks since genesis are available.
+pub fn get_lowest_available_block<'a, TX: DbTx<'a>>(tx: &TX) -> Result<BlockNumber> {
+    Ok(tx.get::<tables::SyncStage>(LOWEST_AVAILABLE_BLOCK.to_string())?.unwrap_or_default())
+}
+
+/// Sets the lowest block with available data, see [LOWEST_AVAILABLE_BLOCK].
+pub fn set_lowest_available_block<'a, TX: DbTxMut<'a>>(tx: &TX, block: BlockNumber) -> Result<()> {
+    Ok(tx.put::<tables::SyncStage>(LOWEST_AVAILABLE_BLOCK.to_string(), block)?)
+}
+
+/// Returns [ProviderError::BlockUnavailable] if the block is below the lowest available block.
+pub fn ensure_block_available(number: BlockNumber, lowest_available: BlockNumber) -> Result<()> {
+    if number < lowest_available {
+        return Err(ProviderError::BlockUnavailable { number, lowest_available }.into())
+    }
+    Ok(())
+}
+
+/// Returns [ProviderError::BlockUnavailable] if the block is below the lowest available block and
+/// [ProviderError::StateUnavailable] if the state of the block is not available.
+///
+/// The state of a database synced from a trusted checkpoint starts at the state of the checkpoint,
+/// which is imported separately. The state above the checkpoint is unavailable until the blocks
+/// above it were executed, the blocks themselves are available as soon as they are synced.
+pub fn ensure_state_available<'a, TX: DbTx<'a>>(tx: &TX, number: BlockNumber) -> Result<()> {
+    let lowest_available = get_lowest_available_block(tx)?;
+    ensure_block_available(number, lowest_available)?;
+    if lowest_available > 0 {
+        let executed = tx.get::<tables::SyncStage>("Execution".to_string())?;
+        if executed.map_or(true, |executed| number > executed) {
+            return Err(ProviderError::StateUnavailable { number }.into())
+        }
+    }
+    Ok(())
+}
+
+/// The key of the receipts prune checkpoint in the [tables::SyncStageProgress] table.
+///
+/// The value is the big endian number of the highest block of which all receipts are pruned, see
+/// [Transaction::prune_receipts](crate::Transaction::prune_receipts). The key is absent if no
+/// block was pruned yet.
+pub const RECEIPTS_PRUNE_CHECKPOINT: &str = "ReceiptsPruneCheckpoint";
+
+/// Returns the receipts prune checkpoint, see [RECEIPTS_PRUNE_CHECKPOINT].
+pub fn get_receipts_prune_checkpoint<'a, TX: DbTx<'a>>(
+    tx: &TX,
+) -> std::result::Result<Option<BlockNumber>, DbError> {
+    Ok(tx
+        .get::<tables::SyncStageProgress>(RECEIPTS_PRUNE_CHECKPOINT.into())?
+        .and_then(|buf| Some(BlockNumber::from_be_bytes(buf.as_slice().try_into().ok()?))))
+}
+
+/// Sets the receipts prune checkpoint, see [RECEIPTS_PRUNE_CHECKPOINT].
+pub fn set_receipts_prune_checkpoint<'a, TX: DbTxMut<'a>>(
+    tx: &TX,
+    block: BlockNumber,
+) -> std::result::Result<(), DbError> {
+    tx.put::<tables::SyncStageProgress>(
+        RECEIPTS_PRUNE_CHECKPOINT.into(),
+        block.to_be_bytes().to_vec(),
+    )
+}
+
+/// The key of the last fork choice state in the [tables::SyncStageProgress] table.
+///
+/// The value is the head, safe and finalized block hashes, concatenated.
+pub const FORKCHOICE_STATE: &str = "ForkchoiceState";
+
+/// Persists the fork choice state, see [FORKCHOICE_STATE].
+pub fn set_forkchoice_state<'a, TX: DbTxMut<'a>>(tx: &TX, state: &ForkchoiceState) -> Result<()> {
+    let buf = [state.head_block_hash, state.safe_block_hash, state.finalized_block_hash]
+        .iter()
+        .flat_map(|hash| hash.0)
+        .collect::<Vec<_>>();
+    Ok(tx.put::<tables::SyncStageProgress>(FORKCHOICE_STATE.into(), buf)?)
+}
+
+/// Returns the persisted fork choice state if all of its blocks are canonical, see
+/// [FORKCHOICE_STATE]. Zero hashes, i.e. unknown safe or finalized blocks, are kept as is.
+///
+/// A state that is no longer canonical, e.g. because the chain was unwound, is removed.
+pub fn load_forkchoice_state<'a, TX: DbTxMut<'a> + DbTx<'a>>(
+    tx: &TX,
+) -> Result<Option<ForkchoiceState>> {
+    let buf = match tx.get::<tables::SyncStageProgress>(FORKCHOICE_STATE.into())? {
+        Some(buf) if buf.len() == 96 => buf,
+        Some(_) => {
+            tx.delete::<tables::SyncStageProgress>(FORKCHOICE_STATE.into(), None)?;
+            return Ok(None)
+        }
+        None => return Ok(None),
+    };
+    let state = ForkchoiceState {
+        head_block_hash: H256::from_slice(&buf[..32]),
+        safe_block_hash: H256::from_slice(&buf[32..64]),
+        finalized_block_hash: H256::from_slice(&buf[64..]),
+    };
+
+    for hash in [state.head_block_hash, state.safe_block_hash, state.finalized_block_hash] {
+        if hash.is_zero() {
+            continue
+        }
+        let canonical = match tx.get::<tables::HeaderNumbers>(hash)? {
+            Some(number) => tx.get::<tables::CanonicalHeaders>(number)? == Some(hash),
+            None => false,
+        };
+        if !canonical {
+            tx.delete::<tables::SyncStageProgress>(FORKCHOICE_STATE.into(), None)?;
+            return Ok(None)
+        }
+    }
+    Ok(Some(state))
+}
+
+/// The key of the hardfork activations the database was synced with in the
+/// [tables::SyncStageProgress] table.
+///
+/// The value is the JSON encoded list of hardforks and their activation conditions.
+pub const HARDFORKS: &str = "Hardforks";
+
+/// Records the hardfork activations the database is synced with, see [HARDFORKS].
+pub fn set_hardforks<'a, TX: DbTxMut<'a>>(
+    tx: &TX,
+    hardforks: &[(Hardfork, ForkCondition)],
+) -> Result<()> {
+    let buf = serde_json::to_vec(hardforks).expect("hardforks are serializable");
+    Ok(tx.put::<tables::SyncStageProgress>(HARDFORKS.into(), buf)?)
+}
+
+/// Returns the recorded hardfork activations, see [HARDFORKS].
+///
+/// Returns `None` if the database predates the record or the record can't be decoded.
+pub fn get_hardforks<'a, TX: DbTx<'a>>(tx: &TX) -> Result<Option<Vec<(Hardfork, ForkCondition)>>> {
+    Ok(tx
+        .get::<tables::SyncStageProgress>(HARDFORKS.into())?
+        .and_then(|buf| serde_json::from_slice(&buf).ok()))
+}
+
+/// Returns the canonical hashes of the blocks in the inclusive range `start..=end` with a single
+/// walk over [tables::CanonicalHeaders].
+///
+/// Returns [ProviderError::BlockUnavailable] if the range starts below the lowest available block
+/// and [ProviderError::CanonicalHeader] for the first block in the range without a hash.
+pub fn canonical_hashes_range<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    start: BlockNumber,
+    end: BlockNumber,
+) -> Result<Vec<H256>> {
+    if start > end {
+        return Ok(Vec::new())
+    }
+    ensure_block_available(start, get_lowest_available_block(tx)?)?;
+
+    let mut cursor = tx.cursor_read::<tables::CanonicalHeaders>()?;
+    let mut hashes = Vec::with_capacity((end - start + 1) as usize);
+    for entry in cursor.walk_range(start..=end)? {
+        let (block_number, hash) = entry?;
+        let expected = start + hashes.len() as u64;
+        if block_number != expected {
+            return Err(ProviderError::CanonicalHeader { block_number: expected }.into())
+        }
+        hashes.push(hash);
+    }
+
+    let next = start + hashes.len() as u64;
+    if next <= end {
+        return Err(ProviderError::CanonicalHeader { block_number: next }.into())
+    }
+    Ok(hashes)
+}
 
 /// Insert block data into corresponding tables. Used mainly for testing & internal tooling.
 ///
 ///
-/// Check parent dependency in [tables::HeaderNumbers] and in [tables::BlockBodies] tables.
+/// Check parent dependency in [tables::HeaderNumbers] and in [tables::BlockBodyIndices] tables.
 /// Inserts header data to [tables::CanonicalHeaders], [tables::Headers], [tables::HeaderNumbers].
 /// and transactions data to [tables::TxSenders], [tables::Transactions], [tables::TxHashNumber].
 /// and transition indexes to [tables::BlockTransitionIndex] and [tables::TxTransitionIndex].
-/// And block data [tables::BlockBodies], [tables::BlockBodies] and [tables::BlockWithdrawals].
+/// And block data [tables::BlockBodyIndices], [tables::BlockOmmers] and [tables::BlockWithdrawals].
 ///
 /// Return [TransitionId] `(from,to)`
 pub fn insert_block<'a, TX: DbTxMut<'a> + DbTx<'a>>(
@@ -56,18 +239,30 @@ pub fn insert_block<'a, TX: DbTxMut<'a> + DbTx<'a>>(
         } else {
             let prev_block_num = block.number - 1;
             let prev_body = tx
-                .get::<tables::BlockBodies>(prev_block_num)?
+                .get::<tables::BlockBodyIndices>(prev_block_num)?
                 .ok_or(ProviderError::BlockBody { number: prev_block_num })?;
             let last_transition_id = tx
                 .get::<tables::BlockTransitionIndex>(prev_block_num)?
                 .ok_or(ProviderError::BlockTransition { block_number: prev_block_num })?;
-            (prev_body.start_tx_id + prev_body.tx_count, last_transition_id)
+            (prev_body.next_tx_num(), last_transition_id)
         };
     let from_transition = transition_id;
+    let first_withdrawal_index = match block.number.checked_sub(1) {
+        Some(prev_block_num) => tx
+            .get::<tables::BlockBodyIndices>(prev_block_num)?
+            .map(|prev_body| prev_body.next_withdrawal_index())
+            .unwrap_or_default(),
+        None => 0,
+    };
     // insert body data
-    tx.put::<tables::BlockBodies>(
+    tx.put::<tables::BlockBodyIndices>(
         block.number,
-        StoredBlockBody { start_tx_id: current_tx_id, tx_count: block.body.len() as u64 },
+        StoredBlockBodyIndices {
+            first_tx_num: current_tx_id,
+            tx_count: block.body.len() as u64,
+            first_withdrawal_index,
+            withdrawal_count: block.withdrawals.as_ref().map_or(0, |w| w.len() as u64),
+        },
     )?;
 
     let senders_len = senders.as_ref().map(|s| s.len());
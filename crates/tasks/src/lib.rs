@@ -7,7 +7,9 @@
 
 //! reth task management
 
-use crate::shutdown::{signal, Shutdown, Signal};
+use crate::shutdown::{
+    signal, GracefulShutdown, GracefulShutdownGuard, GracefulTasks, Shutdown, Signal,
+};
 use dyn_clone::DynClone;
 use futures_util::{
     future::{select, BoxFuture},
@@ -15,14 +17,16 @@ use futures_util::{
 };
 use std::{
     pin::Pin,
+    sync::Arc,
     task::{ready, Context, Poll},
+    time::Duration,
 };
 use tokio::{
     runtime::Handle,
     sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
     task::JoinHandle,
 };
-use tracing::error;
+use tracing::{debug, error};
 use tracing_futures::Instrument;
 
 pub mod shutdown;
@@ -116,9 +120,11 @@ pub struct TaskManager {
     /// The [Signal] to fire when all tasks should be shutdown.
     ///
     /// This is fired on drop.
-    _signal: Signal,
+    signal: Option<Signal>,
     /// Receiver of the shutdown signal.
     on_shutdown: Shutdown,
+    /// How many [GracefulShutdown] tasks are currently active
+    graceful_tasks: Arc<GracefulTasks>,
 }
 
 // === impl TaskManager ===
@@ -127,8 +133,15 @@ impl TaskManager {
     /// Create a new instance connected to the given handle's tokio runtime.
     pub fn new(handle: Handle) -> Self {
         let (panicked_tasks_tx, panicked_tasks_rx) = unbounded_channel();
-        let (_signal, on_shutdown) = signal();
-        Self { handle, panicked_tasks_tx, panicked_tasks_rx, _signal, on_shutdown }
+        let (signal, on_shutdown) = signal();
+        Self {
+            handle,
+            panicked_tasks_tx,
+            panicked_tasks_rx,
+            signal: Some(signal),
+            on_shutdown,
+            graceful_tasks: Default::default(),
+        }
     }
 
     /// Returns a new [`TaskExecutor`] that can spawn new tasks onto the tokio runtime this type is
@@ -138,8 +151,31 @@ impl TaskManager {
             handle: self.handle.clone(),
             on_shutdown: self.on_shutdown.clone(),
             panicked_tasks_tx: self.panicked_tasks_tx.clone(),
+            graceful_tasks: Arc::clone(&self.graceful_tasks),
         }
     }
+
+    /// Fires the shutdown signal and awaits until all tasks that were spawned with a
+    /// [GracefulShutdown] signal are shutdown, or the given timeout elapsed.
+    ///
+    /// Returns `true` if all graceful tasks finished in time.
+    ///
+    /// Note: this blocks the current thread, so it must not be called from within the runtime.
+    pub fn graceful_shutdown_with_timeout(mut self, timeout: Duration) -> bool {
+        let _ = self.signal.take().map(|signal| signal.fire());
+
+        if !self.graceful_tasks.wait(timeout) {
+            debug!(
+                target: "reth::tasks",
+                pending = self.graceful_tasks.pending(),
+                "graceful shutdown timed out"
+            );
+            return false
+        }
+
+        debug!(target: "reth::tasks", "gracefully shut down");
+        true
+    }
 }
 
 /// An endless future that resolves if a critical task panicked.
@@ -170,6 +206,8 @@ pub struct TaskExecutor {
     on_shutdown: Shutdown,
     /// Sender half for sending panic signals to this type
     panicked_tasks_tx: UnboundedSender<&'static str>,
+    /// How many [GracefulShutdown] tasks are currently active
+    graceful_tasks: Arc<GracefulTasks>,
 }
 
 // === impl TaskExecutor ===
@@ -332,6 +370,84 @@ impl TaskExecutor {
 
         self.handle.spawn(task)
     }
+
+    /// This spawns a critical task onto the runtime.
+    ///
+    /// If this task panics, the [TaskManager] is notified.
+    /// The [TaskManager] will wait until the given future has completed before shutting down, see
+    /// [TaskManager::graceful_shutdown_with_timeout].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn t(executor: reth_tasks::TaskExecutor) {
+    ///
+    /// executor.spawn_critical_with_graceful_shutdown_signal("grace", |shutdown| async move {
+    ///     // await the shutdown signal
+    ///     let guard = shutdown.await;
+    ///     // do work before exiting the program
+    ///     tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    ///     // allow graceful shutdown
+    ///     drop(guard);
+    /// });
+    /// # }
+    /// ```
+    pub fn spawn_critical_with_graceful_shutdown_signal<F>(
+        &self,
+        name: &'static str,
+        f: impl FnOnce(GracefulShutdown) -> F,
+    ) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_critical_with_graceful_shutdown_signal_as(name, f, TaskKind::Default)
+    }
+
+    /// This spawns a critical blocking task onto the runtime.
+    ///
+    /// If this task panics, the [TaskManager] is notified.
+    /// The [TaskManager] will wait until the given future has completed before shutting down, see
+    /// [TaskManager::graceful_shutdown_with_timeout].
+    pub fn spawn_critical_blocking_with_graceful_shutdown_signal<F>(
+        &self,
+        name: &'static str,
+        f: impl FnOnce(GracefulShutdown) -> F,
+    ) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_critical_with_graceful_shutdown_signal_as(name, f, TaskKind::Blocking)
+    }
+
+    /// Spawns a critical task with a [GracefulShutdown] signal depending on the given [TaskKind]
+    fn spawn_critical_with_graceful_shutdown_signal_as<F>(
+        &self,
+        name: &'static str,
+        f: impl FnOnce(GracefulShutdown) -> F,
+        task_kind: TaskKind,
+    ) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let panicked_tasks_tx = self.panicked_tasks_tx.clone();
+        let on_shutdown = GracefulShutdown::new(
+            self.on_shutdown.clone(),
+            GracefulShutdownGuard::new(Arc::clone(&self.graceful_tasks)),
+        );
+        let fut = f(on_shutdown);
+
+        // wrap the task in catch unwind
+        let task = std::panic::AssertUnwindSafe(fut)
+            .catch_unwind()
+            .inspect_err(move |res| {
+                error!("Critical task `{name}` panicked: {res:?}");
+                let _ = panicked_tasks_tx.send(name);
+            })
+            .map(|_| ())
+            .in_current_span();
+
+        self.spawn_on_rt(task, task_kind)
+    }
 }
 
 impl TaskSpawner for TaskExecutor {
@@ -355,7 +471,10 @@ enum TaskKind {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::time::Duration;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
 
     #[test]
     fn test_cloneable() {
@@ -431,4 +550,36 @@ mod tests {
 
         handle.block_on(shutdown);
     }
+
+    #[test]
+    fn test_graceful_shutdown_triggered_by_executor() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let manager = TaskManager::new(runtime.handle().clone());
+        let executor = manager.executor();
+
+        let val = Arc::new(AtomicUsize::new(0));
+        let c = val.clone();
+        executor.spawn_critical_with_graceful_shutdown_signal("grace", |shutdown| async move {
+            let _guard = shutdown.await;
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            c.store(1, Ordering::Relaxed);
+        });
+
+        assert!(manager.graceful_shutdown_with_timeout(Duration::from_secs(5)));
+        assert_eq!(val.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_graceful_shutdown_timeout() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let manager = TaskManager::new(runtime.handle().clone());
+        let executor = manager.executor();
+
+        executor.spawn_critical_with_graceful_shutdown_signal("stuck", |shutdown| async move {
+            let _guard = shutdown.await;
+            futures_util::future::pending::<()>().await;
+        });
+
+        assert!(!manager.graceful_shutdown_with_timeout(Duration::from_millis(100)));
+    }
 }
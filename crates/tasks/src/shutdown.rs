@@ -7,10 +7,100 @@ use futures_util::{
 use std::{
     future::Future,
     pin::Pin,
-    task::{Context, Poll},
+    sync::{Arc, Condvar, Mutex},
+    task::{ready, Context, Poll},
+    time::Duration,
 };
 use tokio::sync::oneshot;
 
+/// A Future that resolves to a [GracefulShutdownGuard] when the shutdown event has been fired.
+///
+/// The [TaskManager](crate::TaskManager) waits until all [GracefulShutdownGuard]s that were
+/// handed out by this future are dropped, or until the configured grace period is over.
+#[derive(Debug)]
+pub struct GracefulShutdown {
+    shutdown: Shutdown,
+    guard: Option<GracefulShutdownGuard>,
+}
+
+impl GracefulShutdown {
+    pub(crate) fn new(shutdown: Shutdown, guard: GracefulShutdownGuard) -> Self {
+        Self { shutdown, guard: Some(guard) }
+    }
+}
+
+impl Future for GracefulShutdown {
+    type Output = GracefulShutdownGuard;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        ready!(self.shutdown.poll_unpin(cx));
+        Poll::Ready(self.get_mut().guard.take().expect("Future polled after completion"))
+    }
+}
+
+impl Clone for GracefulShutdown {
+    fn clone(&self) -> Self {
+        Self {
+            shutdown: self.shutdown.clone(),
+            guard: self.guard.as_ref().map(|g| GracefulShutdownGuard::new(Arc::clone(&g.0))),
+        }
+    }
+}
+
+/// A guard that fires once dropped to signal the [TaskManager](crate::TaskManager) that the
+/// [GracefulShutdown] has completed.
+#[derive(Debug)]
+#[must_use = "if unused the task will not be gracefully shutdown"]
+pub struct GracefulShutdownGuard(Arc<GracefulTasks>);
+
+impl GracefulShutdownGuard {
+    pub(crate) fn new(tasks: Arc<GracefulTasks>) -> Self {
+        *tasks.lock() += 1;
+        Self(tasks)
+    }
+}
+
+impl Drop for GracefulShutdownGuard {
+    fn drop(&mut self) {
+        let mut pending = self.0.lock();
+        *pending -= 1;
+        if *pending == 0 {
+            self.0.finished.notify_all();
+        }
+    }
+}
+
+/// Counts the [GracefulShutdownGuard]s that are still alive and wakes up waiters once all of them
+/// are dropped.
+#[derive(Debug, Default)]
+pub(crate) struct GracefulTasks {
+    pending: Mutex<usize>,
+    finished: Condvar,
+}
+
+impl GracefulTasks {
+    /// Returns the number of guards that are still alive.
+    pub(crate) fn pending(&self) -> usize {
+        *self.lock()
+    }
+
+    /// Blocks the current thread until all guards are dropped or the timeout elapsed.
+    ///
+    /// Returns `true` if all guards were dropped in time.
+    pub(crate) fn wait(&self, timeout: Duration) -> bool {
+        let (pending, _) = self
+            .finished
+            .wait_timeout_while(self.lock(), timeout, |pending| *pending > 0)
+            .unwrap_or_else(|err| err.into_inner());
+        *pending == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, usize> {
+        // the counter stays valid even if a guard was dropped while panicking
+        self.pending.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
 /// A Future that resolves when the shutdown event has been fired.
 #[derive(Debug, Clone)]
 pub struct Shutdown(Shared<oneshot::Receiver<()>>);
@@ -86,6 +176,25 @@ mod tests {
         join_all(tasks).await;
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_graceful_shutdown_guard() {
+        let (signal, shutdown) = signal();
+        let tasks = Arc::new(GracefulTasks::default());
+        let graceful = GracefulShutdown::new(shutdown, GracefulShutdownGuard::new(tasks.clone()));
+        let cloned = graceful.clone();
+        assert_eq!(tasks.pending(), 2);
+        drop(cloned);
+        assert_eq!(tasks.pending(), 1);
+
+        drop(signal);
+        let guard = graceful.await;
+        assert_eq!(tasks.pending(), 1);
+        assert!(!tasks.wait(Duration::from_millis(10)));
+        drop(guard);
+        assert_eq!(tasks.pending(), 0);
+        assert!(tasks.wait(Duration::from_millis(10)));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_drop_signal_from_thread() {
         let (signal, shutdown) = signal();
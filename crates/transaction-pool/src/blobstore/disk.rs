@@ -0,0 +1,577 @@
+//! A [BlobStore] that keeps one file per transaction.
+
+use crate::blobstore::{BlobStore, BlobStoreError, BlobTransactionSidecar};
+use parking_lot::{Mutex, RwLock};
+use reth_primitives::{keccak256, BlockNumber, H256};
+use reth_rlp::{Decodable, Encodable};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tracing::{debug, warn};
+
+/// The default number of blocks the sidecars of mined transactions are kept for after they were
+/// finalized, two epochs.
+pub const DEFAULT_BLOB_RETENTION_BLOCKS: u64 = 64;
+
+/// The length of the header of a blob file: the length of the encoded sidecar as big endian `u64`
+/// followed by its keccak256 hash.
+const HEADER_LEN: usize = 8 + 32;
+
+/// The extension of files that are still being written.
+const TMP_EXTENSION: &str = "tmp";
+
+/// The name of the file the transactions of [DiskFileBlobStore::on_mined] are persisted in.
+const MINED_FILE: &str = "mined";
+
+/// The length of a record of the mined file: the block number as big endian `u64` followed by the
+/// transaction hash.
+const MINED_RECORD_LEN: usize = 8 + 32;
+
+/// Settings for the [DiskFileBlobStore]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskFileBlobStoreConfig {
+    /// Number of blocks the sidecars of mined transactions are kept for after they were finalized,
+    /// see [DiskFileBlobStore::on_finalized].
+    ///
+    /// Default is [DEFAULT_BLOB_RETENTION_BLOCKS]
+    pub retention_blocks: u64,
+}
+
+impl Default for DiskFileBlobStoreConfig {
+    fn default() -> Self {
+        Self { retention_blocks: DEFAULT_BLOB_RETENTION_BLOCKS }
+    }
+}
+
+/// A [BlobStore] that writes the sidecar of each transaction to a file in a directory.
+///
+/// An in-memory index keeps track of the stored sidecars and their sizes. Every file starts with
+/// the length and the hash of the encoded sidecar, so that files that were only partially written,
+/// for example because the node crashed, are detected and treated as absent.
+///
+/// The sidecars of mined transactions are deleted once the blocks they were mined in are finalized,
+/// see [BlobStore::on_mined] and [BlobStore::on_finalized]. The mined transactions are persisted
+/// in the directory too, so that their sidecars are still deleted after a restart.
+#[derive(Debug, Clone)]
+pub struct DiskFileBlobStore {
+    inner: Arc<DiskFileBlobStoreInner>,
+}
+
+impl DiskFileBlobStore {
+    /// Opens the store in the given directory, which is created if it doesn't exist.
+    ///
+    /// The sidecars that are already stored in the directory are indexed, files that were only
+    /// partially written are removed. The mined transactions are loaded, except for the ones whose
+    /// sidecars are no longer stored.
+    pub fn open(
+        blob_dir: impl Into<PathBuf>,
+        config: DiskFileBlobStoreConfig,
+    ) -> Result<Self, BlobStoreError> {
+        let blob_dir = blob_dir.into();
+        fs::create_dir_all(&blob_dir)?;
+
+        let mut index = HashMap::new();
+        let mut data_size = 0;
+        for entry in fs::read_dir(&blob_dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(false, |ext| ext == TMP_EXTENSION) {
+                warn!(target: "txpool::blob", ?path, "Removing partially written blob file");
+                fs::remove_file(&path)?;
+                continue
+            }
+            if path.file_name().map_or(false, |name| name == MINED_FILE) {
+                continue
+            }
+            let Some(tx) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| H256::from_str(name).ok())
+            else {
+                continue
+            };
+
+            match blob_file_size(&path)? {
+                Some(size) => {
+                    index.insert(tx, BlobEntry { refs: 1, size });
+                    data_size += size;
+                }
+                None => {
+                    warn!(target: "txpool::blob", ?path, "Removing partially written blob file");
+                    fs::remove_file(&path)?;
+                }
+            }
+        }
+        let mined = read_mined_file(&blob_dir.join(MINED_FILE), |tx| index.contains_key(tx))?;
+        debug!(target: "txpool::blob", ?blob_dir, blobs = index.len(), data_size, mined_blocks = mined.len(), "Opened blob store");
+
+        Ok(Self {
+            inner: Arc::new(DiskFileBlobStoreInner {
+                blob_dir,
+                config,
+                index: RwLock::new(index),
+                mined: Mutex::new(mined),
+                data_size: AtomicUsize::new(data_size),
+                tmp_id: Default::default(),
+            }),
+        })
+    }
+
+    /// Returns the settings of the store.
+    pub fn config(&self) -> &DiskFileBlobStoreConfig {
+        &self.inner.config
+    }
+}
+
+impl BlobStore for DiskFileBlobStore {
+    fn insert(&self, tx: H256, sidecar: BlobTransactionSidecar) -> Result<(), BlobStoreError> {
+        if self.inner.add_ref(tx) {
+            return Ok(())
+        }
+
+        // the file is written under a unique temporary name and then renamed, so that readers never
+        // see a partially written file
+        let data = encode_blob_file(&sidecar);
+        let tmp = self.inner.tmp_path();
+        fs::write(&tmp, &data)?;
+
+        let mut index = self.inner.index.write();
+        if let Some(entry) = index.get_mut(&tx) {
+            // inserted concurrently
+            entry.refs += 1;
+            drop(index);
+            fs::remove_file(&tmp)?;
+            return Ok(())
+        }
+        if let Err(err) = fs::rename(&tmp, self.inner.blob_path(tx)) {
+            let _ = fs::remove_file(&tmp);
+            return Err(err.into())
+        }
+        index.insert(tx, BlobEntry { refs: 1, size: data.len() });
+        self.inner.data_size.fetch_add(data.len(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn delete(&self, tx: H256) -> Result<(), BlobStoreError> {
+        let mut index = self.inner.index.write();
+        match index.get_mut(&tx) {
+            Some(entry) if entry.refs > 1 => entry.refs -= 1,
+            Some(_) => {
+                self.inner.remove_entry(&mut index, tx)?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn get(&self, tx: H256) -> Result<Option<BlobTransactionSidecar>, BlobStoreError> {
+        if !self.contains(tx) {
+            return Ok(None)
+        }
+
+        let data = match fs::read(self.inner.blob_path(tx)) {
+            Ok(data) => data,
+            // deleted concurrently
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let Some(mut payload) = blob_file_payload(&data) else {
+            warn!(target: "txpool::blob", ?tx, "Removing corrupted blob file");
+            self.inner.remove(tx)?;
+            return Ok(None)
+        };
+        BlobTransactionSidecar::decode(&mut payload)
+            .map(Some)
+            .map_err(|err| BlobStoreError::Decode(tx, err))
+    }
+
+    fn contains(&self, tx: H256) -> bool {
+        self.inner.index.read().contains_key(&tx)
+    }
+
+    fn data_size(&self) -> usize {
+        self.inner.data_size.load(Ordering::Relaxed)
+    }
+
+    fn blobs_len(&self) -> usize {
+        self.inner.index.read().len()
+    }
+
+    /// Only the transactions whose sidecars are stored are recorded.
+    fn on_mined(&self, block: BlockNumber, txs: &[H256]) -> Result<(), BlobStoreError> {
+        let txs = txs.iter().copied().filter(|tx| self.contains(*tx)).collect::<Vec<_>>();
+        if txs.is_empty() {
+            return Ok(())
+        }
+        let mut mined = self.inner.mined.lock();
+        mined.entry(block).or_default().extend(txs);
+        self.inner.write_mined(&mined)
+    }
+
+    /// Deletes the sidecars of all transactions that were mined more than
+    /// [`retention_blocks`](DiskFileBlobStoreConfig::retention_blocks) blocks before the given
+    /// finalized block, regardless of how often they were inserted.
+    fn on_finalized(&self, finalized: BlockNumber) -> Result<usize, BlobStoreError> {
+        let expired = {
+            let mut mined = self.inner.mined.lock();
+            let retained =
+                mined.split_off(&finalized.saturating_sub(self.inner.config.retention_blocks));
+            let expired = std::mem::replace(&mut *mined, retained);
+            if expired.is_empty() {
+                return Ok(0)
+            }
+            self.inner.write_mined(&mined)?;
+            expired
+        };
+
+        let mut deleted = 0;
+        for tx in expired.into_values().flatten() {
+            if self.inner.remove(tx)? {
+                deleted += 1;
+            }
+        }
+        debug!(target: "txpool::blob", finalized, deleted, "Deleted blobs of finalized transactions");
+        Ok(deleted)
+    }
+}
+
+/// The state of the [DiskFileBlobStore] shared by its clones.
+#[derive(Debug)]
+struct DiskFileBlobStoreInner {
+    blob_dir: PathBuf,
+    config: DiskFileBlobStoreConfig,
+    /// All stored sidecars.
+    index: RwLock<HashMap<H256, BlobEntry>>,
+    /// The transactions with stored sidecars by the block they were mined in.
+    mined: Mutex<BTreeMap<BlockNumber, Vec<H256>>>,
+    /// The total size of all blob files.
+    data_size: AtomicUsize,
+    /// Makes the names of temporary files unique.
+    tmp_id: AtomicU64,
+}
+
+impl DiskFileBlobStoreInner {
+    fn blob_path(&self, tx: H256) -> PathBuf {
+        self.blob_dir.join(format!("{tx:x}"))
+    }
+
+    fn tmp_path(&self) -> PathBuf {
+        let id = self.tmp_id.fetch_add(1, Ordering::Relaxed);
+        self.blob_dir.join(format!("{id}.{TMP_EXTENSION}"))
+    }
+
+    /// Replaces the mined file with the given mined transactions.
+    ///
+    /// Like blob files, the file is written under a temporary name and then renamed, so a crash
+    /// leaves either the old or the new file behind.
+    fn write_mined(&self, mined: &BTreeMap<BlockNumber, Vec<H256>>) -> Result<(), BlobStoreError> {
+        let len = mined.values().map(Vec::len).sum::<usize>();
+        let mut data = Vec::with_capacity(len * MINED_RECORD_LEN);
+        for (block, txs) in mined {
+            for tx in txs {
+                data.extend_from_slice(&block.to_be_bytes());
+                data.extend_from_slice(tx.as_bytes());
+            }
+        }
+        let tmp = self.tmp_path();
+        fs::write(&tmp, data)?;
+        if let Err(err) = fs::rename(&tmp, self.blob_dir.join(MINED_FILE)) {
+            let _ = fs::remove_file(&tmp);
+            return Err(err.into())
+        }
+        Ok(())
+    }
+
+    /// Adds a reference to the sidecar of the transaction if it's stored.
+    fn add_ref(&self, tx: H256) -> bool {
+        match self.index.write().get_mut(&tx) {
+            Some(entry) => {
+                entry.refs += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the sidecar of the transaction with all its references.
+    ///
+    /// Returns whether the sidecar was stored.
+    fn remove(&self, tx: H256) -> Result<bool, BlobStoreError> {
+        self.remove_entry(&mut self.index.write(), tx)
+    }
+
+    /// Removes the sidecar of the transaction from the locked index and its file.
+    ///
+    /// The file is removed while holding the lock, so it can't race with an insert of the same
+    /// sidecar.
+    fn remove_entry(
+        &self,
+        index: &mut HashMap<H256, BlobEntry>,
+        tx: H256,
+    ) -> Result<bool, BlobStoreError> {
+        let Some(entry) = index.remove(&tx) else { return Ok(false) };
+        self.data_size.fetch_sub(entry.size, Ordering::Relaxed);
+        match fs::remove_file(self.blob_path(tx)) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(true),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// A stored sidecar.
+#[derive(Debug, Clone, Copy)]
+struct BlobEntry {
+    /// How often the sidecar was inserted but not deleted.
+    refs: usize,
+    /// The size of the blob file.
+    size: usize,
+}
+
+/// Encodes the sidecar as a blob file: the header and the RLP encoded sidecar.
+fn encode_blob_file(sidecar: &BlobTransactionSidecar) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(sidecar.length());
+    sidecar.encode(&mut payload);
+
+    let mut data = Vec::with_capacity(HEADER_LEN + payload.len());
+    data.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+    data.extend_from_slice(keccak256(&payload).as_bytes());
+    data.extend_from_slice(&payload);
+    data
+}
+
+/// Returns the RLP encoded sidecar of the blob file, or `None` if the file is incomplete or
+/// corrupted.
+fn blob_file_payload(data: &[u8]) -> Option<&[u8]> {
+    if data.len() < HEADER_LEN {
+        return None
+    }
+    let (header, payload) = data.split_at(HEADER_LEN);
+    let len = u64::from_be_bytes(header[..8].try_into().expect("8 bytes"));
+    let valid = len == payload.len() as u64 && keccak256(payload).as_bytes() == &header[8..];
+    valid.then_some(payload)
+}
+
+/// Returns the size of the blob file if its length matches the length in its header.
+///
+/// This doesn't verify the hash of the sidecar, which is done when it's read.
+fn blob_file_size(path: &Path) -> Result<Option<usize>, BlobStoreError> {
+    let mut file = fs::File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut len = [0; 8];
+    match file.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+    let complete = size.checked_sub(HEADER_LEN as u64) == Some(u64::from_be_bytes(len));
+    Ok(complete.then_some(size as usize))
+}
+
+/// Reads the mined transactions the file at the given path contains, if it exists, keeping only the
+/// transactions `f` returns `true` for.
+///
+/// An incomplete trailing record is ignored.
+fn read_mined_file(
+    path: &Path,
+    mut f: impl FnMut(&H256) -> bool,
+) -> Result<BTreeMap<BlockNumber, Vec<H256>>, BlobStoreError> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Default::default()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut mined = BTreeMap::<_, Vec<_>>::new();
+    for record in data.chunks_exact(MINED_RECORD_LEN) {
+        let (block, tx) = record.split_at(8);
+        let tx = H256::from_slice(tx);
+        if f(&tx) {
+            let block = u64::from_be_bytes(block.try_into().expect("8 bytes"));
+            mined.entry(block).or_default().push(tx);
+        }
+    }
+    Ok(mined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::Bytes;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("reth-blobstore-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn sidecar(seed: u8) -> BlobTransactionSidecar {
+        BlobTransactionSidecar {
+            blobs: vec![Bytes::from(vec![seed; 4096]), Bytes::from(vec![!seed; 4096])],
+            commitments: vec![Bytes::from(vec![seed; 48]); 2],
+            proofs: vec![Bytes::from(vec![!seed; 48]); 2],
+        }
+    }
+
+    #[test]
+    fn insert_get_delete() {
+        let dir = test_dir("insert");
+        let store = DiskFileBlobStore::open(&dir, Default::default()).unwrap();
+        let tx = H256::random();
+        assert!(store.get(tx).unwrap().is_none());
+
+        store.insert(tx, sidecar(1)).unwrap();
+        assert!(store.contains(tx));
+        assert_eq!(store.get(tx).unwrap(), Some(sidecar(1)));
+        assert_eq!(store.blobs_len(), 1);
+        let size = store.data_size();
+        assert_eq!(size as u64, fs::metadata(dir.join(format!("{tx:x}"))).unwrap().len());
+
+        // inserting the same transaction again only adds a reference
+        store.insert(tx, sidecar(1)).unwrap();
+        assert_eq!(store.data_size(), size);
+        store.delete(tx).unwrap();
+        assert_eq!(store.get(tx).unwrap(), Some(sidecar(1)));
+        store.delete(tx).unwrap();
+        assert!(!store.contains(tx));
+        assert!(store.get(tx).unwrap().is_none());
+        assert_eq!(store.data_size(), 0);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reopen_skips_partial_files() {
+        let dir = test_dir("reopen");
+        let store = DiskFileBlobStore::open(&dir, Default::default()).unwrap();
+        let complete = H256::random();
+        store.insert(complete, sidecar(1)).unwrap();
+        let size = store.data_size();
+        drop(store);
+
+        // a truncated blob file and a leftover temporary file of an interrupted write
+        let truncated = H256::random();
+        let data = encode_blob_file(&sidecar(2));
+        fs::write(dir.join(format!("{truncated:x}")), &data[..data.len() / 2]).unwrap();
+        fs::write(dir.join("7.tmp"), &data).unwrap();
+
+        let store = DiskFileBlobStore::open(&dir, Default::default()).unwrap();
+        assert_eq!(store.get(complete).unwrap(), Some(sidecar(1)));
+        assert!(!store.contains(truncated));
+        assert_eq!(store.blobs_len(), 1);
+        assert_eq!(store.data_size(), size);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // a corrupted file is treated as absent
+        let mut data = fs::read(dir.join(format!("{complete:x}"))).unwrap();
+        *data.last_mut().unwrap() ^= 0xff;
+        fs::write(dir.join(format!("{complete:x}")), data).unwrap();
+        assert!(store.get(complete).unwrap().is_none());
+        assert!(!store.contains(complete));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn delete_finalized() {
+        let dir = test_dir("finalized");
+        let store =
+            DiskFileBlobStore::open(&dir, DiskFileBlobStoreConfig { retention_blocks: 2 }).unwrap();
+        let txs = (0..4).map(|_| H256::random()).collect::<Vec<_>>();
+        for (block, tx) in txs.iter().enumerate() {
+            store.insert(*tx, sidecar(block as u8)).unwrap();
+            store.on_mined(block as u64 + 1, &[*tx]).unwrap();
+        }
+        // also referenced by the pool
+        store.insert(txs[0], sidecar(0)).unwrap();
+
+        assert_eq!(store.on_finalized(2).unwrap(), 0);
+        assert_eq!(store.on_finalized(4).unwrap(), 1);
+        assert!(!store.contains(txs[0]));
+        assert_eq!(store.on_finalized(5).unwrap(), 1);
+        assert!(!store.contains(txs[1]));
+        assert!(store.contains(txs[2]));
+        assert!(store.contains(txs[3]));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reopen_keeps_mined() {
+        let dir = test_dir("mined");
+        let config = DiskFileBlobStoreConfig { retention_blocks: 0 };
+        let store = DiskFileBlobStore::open(&dir, config).unwrap();
+        let txs = (0..3).map(|_| H256::random()).collect::<Vec<_>>();
+        for (block, tx) in txs.iter().enumerate() {
+            store.insert(*tx, sidecar(block as u8)).unwrap();
+            store.on_mined(block as u64 + 1, &[*tx]).unwrap();
+        }
+        // transactions without a stored sidecar aren't recorded
+        store.on_mined(1, &[H256::random()]).unwrap();
+        assert_eq!(store.on_finalized(2).unwrap(), 1);
+        drop(store);
+
+        // the sidecar of a mined transaction was deleted while the store was closed
+        fs::remove_file(dir.join(format!("{:x}", txs[1]))).unwrap();
+
+        let store = DiskFileBlobStore::open(&dir, config).unwrap();
+        assert_eq!(store.blobs_len(), 1);
+        assert_eq!(*store.inner.mined.lock(), BTreeMap::from([(3, vec![txs[2]])]));
+        assert_eq!(store.on_finalized(4).unwrap(), 1);
+        assert_eq!(store.blobs_len(), 0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn parallel_reads_during_delete_sweep() {
+        let dir = test_dir("parallel");
+        let store =
+            DiskFileBlobStore::open(&dir, DiskFileBlobStoreConfig { retention_blocks: 0 }).unwrap();
+        let blobs =
+            Arc::new((0..64).map(|seed| (H256::random(), sidecar(seed))).collect::<Vec<_>>());
+        for (block, (tx, sidecar)) in blobs.iter().enumerate() {
+            store.insert(*tx, sidecar.clone()).unwrap();
+            store.on_mined(block as u64, &[*tx]).unwrap();
+        }
+
+        let readers = (0..4)
+            .map(|_| {
+                let store = store.clone();
+                let blobs = blobs.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        for (tx, sidecar) in blobs.iter() {
+                            // a sidecar is either read completely or not at all
+                            if let Some(read) = store.get(*tx).unwrap() {
+                                assert_eq!(&read, sidecar);
+                            }
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // deletes the sidecars mined before block 32
+        assert_eq!(store.on_finalized(32).unwrap(), 32);
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        assert_eq!(store.blobs_len(), 32);
+        assert!(blobs[..32].iter().all(|(tx, _)| store.get(*tx).unwrap().is_none()));
+        assert!(blobs[32..]
+            .iter()
+            .all(|(tx, sidecar)| store.get(*tx).unwrap().as_ref() == Some(sidecar)));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,67 @@
+//! Storage of the blob sidecars of blob transactions.
+//!
+//! Blob sidecars are large and only needed to propagate blob transactions and to serve them to the
+//! consensus layer, so they are kept out of the pool and the database, see [DiskFileBlobStore].
+
+use reth_primitives::{BlockNumber, Bytes, H256};
+use reth_rlp_derive::{RlpDecodable, RlpEncodable};
+use std::{fmt, io};
+
+mod disk;
+pub use disk::{DiskFileBlobStore, DiskFileBlobStoreConfig, DEFAULT_BLOB_RETENTION_BLOCKS};
+
+/// Stores the blob sidecars of transactions by transaction hash.
+pub trait BlobStore: fmt::Debug + Send + Sync + 'static {
+    /// Inserts the sidecar of the transaction.
+    ///
+    /// If the sidecar of the transaction is already stored it's not stored again, but it's only
+    /// removed once it was deleted as often as it was inserted.
+    fn insert(&self, tx: H256, sidecar: BlobTransactionSidecar) -> Result<(), BlobStoreError>;
+
+    /// Deletes one reference to the sidecar of the transaction.
+    fn delete(&self, tx: H256) -> Result<(), BlobStoreError>;
+
+    /// Returns the sidecar of the transaction, if it's stored.
+    fn get(&self, tx: H256) -> Result<Option<BlobTransactionSidecar>, BlobStoreError>;
+
+    /// Whether the sidecar of the transaction is stored.
+    fn contains(&self, tx: H256) -> bool;
+
+    /// Returns the total size of all stored sidecars in bytes.
+    fn data_size(&self) -> usize;
+
+    /// Returns the number of stored sidecars.
+    fn blobs_len(&self) -> usize;
+
+    /// Records that the given transactions were mined in the block with the given number, so that
+    /// their sidecars are deleted once the block is finalized, see [BlobStore::on_finalized].
+    fn on_mined(&self, block: BlockNumber, txs: &[H256]) -> Result<(), BlobStoreError>;
+
+    /// Deletes the sidecars of the transactions that were mined in finalized blocks.
+    ///
+    /// Returns the number of deleted sidecars.
+    fn on_finalized(&self, finalized: BlockNumber) -> Result<usize, BlobStoreError>;
+}
+
+/// The blobs of a blob transaction with their KZG commitments and proofs, which are not part of
+/// the signed transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct BlobTransactionSidecar {
+    /// The blobs of the transaction.
+    pub blobs: Vec<Bytes>,
+    /// The KZG commitments of the blobs.
+    pub commitments: Vec<Bytes>,
+    /// The KZG proofs of the blobs.
+    pub proofs: Vec<Bytes>,
+}
+
+/// Errors that can occur when accessing a [BlobStore].
+#[derive(Debug, thiserror::Error)]
+pub enum BlobStoreError {
+    /// Failed to read or write a sidecar.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// A stored sidecar is malformed.
+    #[error("malformed blob sidecar of transaction {0:?}: {1}")]
+    Decode(H256, reth_rlp::DecodeError),
+}
@@ -1,6 +1,23 @@
+use crate::blobstore::BlobStore;
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
 /// Guarantees max transactions for one sender, compatible with geth/erigon
 pub(crate) const MAX_ACCOUNT_SLOTS_PER_SENDER: usize = 16;
 
+/// How long queued transactions are kept in the pool by default, 3 hours like geth.
+pub const DEFAULT_QUEUED_LIFETIME: Duration = Duration::from_secs(3 * 60 * 60);
+
+/// The minimal value the basefee can decrease to.
+///
+/// The `BASE_FEE_MAX_CHANGE_DENOMINATOR` <https://eips.ethereum.org/EIPS/eip-1559> is `8`, or 12.5%.
+/// Once the base fee has dropped to `7` WEI it cannot decrease further because 12.5% of 7 is less
+/// than 1.
+pub const MIN_PROTOCOL_BASE_FEE: u128 = 7;
+
 ///! Configuration options for the Transaction pool.
 #[derive(Debug, Clone)]
 pub struct PoolConfig {
@@ -12,6 +29,28 @@ pub struct PoolConfig {
     pub queued_limit: SubPoolLimit,
     /// Max number of executable transaction slots guaranteed per account
     pub max_account_slots: usize,
+    /// How long a queued transaction is kept in the pool before it's discarded.
+    ///
+    /// Local transactions never expire.
+    ///
+    /// Default is [DEFAULT_QUEUED_LIFETIME]
+    pub queued_lifetime: Duration,
+    /// The minimal fee cap a transaction must pay to be accepted.
+    ///
+    /// Private chains whose base fee starts at zero can set this to zero to accept zero-fee
+    /// transactions.
+    ///
+    /// Default is [MIN_PROTOCOL_BASE_FEE]
+    pub minimal_protocol_basefee: u128,
+    /// The clock the insertion time of transactions is taken from.
+    pub clock: Arc<dyn PoolClock>,
+    /// The store of the blob sidecars of the pool's transactions.
+    ///
+    /// The sidecars of mined transactions are deleted from the store once their blocks are
+    /// finalized, see [OnNewBlockEvent::finalized_block](crate::OnNewBlockEvent::finalized_block).
+    ///
+    /// Default is no store
+    pub blob_store: Option<Arc<dyn BlobStore>>,
 }
 
 impl Default for PoolConfig {
@@ -21,10 +60,30 @@ impl Default for PoolConfig {
             basefee_limit: Default::default(),
             queued_limit: Default::default(),
             max_account_slots: MAX_ACCOUNT_SLOTS_PER_SENDER,
+            queued_lifetime: DEFAULT_QUEUED_LIFETIME,
+            minimal_protocol_basefee: MIN_PROTOCOL_BASE_FEE,
+            clock: Arc::new(SystemClock),
+            blob_store: None,
         }
     }
 }
 
+/// The source of the current time of the pool.
+pub trait PoolClock: fmt::Debug + Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// A [PoolClock] that returns the system's monotonic time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl PoolClock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
 /// Size limits for a sub-pool.
 #[derive(Debug, Clone)]
 pub struct SubPoolLimit {
@@ -8,6 +8,9 @@ pub type PoolResult<T> = Result<T, PoolError>;
 /// All errors the Transaction pool can throw.
 #[derive(Debug, thiserror::Error)]
 pub enum PoolError {
+    /// Thrown if the transaction is already in the pool.
+    #[error("[{0:?}] Transaction already imported.")]
+    AlreadyImported(TxHash),
     /// Thrown if a replacement transaction's gas price is below the already imported transaction
     #[error("[{0:?}]: insufficient gas price to replace existing transaction.")]
     ReplacementUnderpriced(TxHash),
@@ -36,6 +39,7 @@ impl PoolError {
     /// Returns the hash of the transaction that resulted in this error.
     pub fn hash(&self) -> &TxHash {
         match self {
+            PoolError::AlreadyImported(hash) => hash,
             PoolError::ReplacementUnderpriced(hash) => hash,
             PoolError::ProtocolFeeCapTooLow(hash, _) => hash,
             PoolError::SpammerExceededCapacity(_, hash) => hash,
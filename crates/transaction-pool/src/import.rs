@@ -0,0 +1,257 @@
+//! Bulk import of transactions, for example to load test a node or to migrate the transactions of
+//! another node.
+//!
+//! Transactions are decoded, recovered and submitted to the pool in batches of
+//! [DEFAULT_IMPORT_BATCH_SIZE] transactions which are validated concurrently. The signers of every
+//! batch are recovered on the blocking pool, so that importing a large number of transactions does
+//! not block other tasks.
+
+use crate::{error::PoolError, PoolTransaction, TransactionOrigin, TransactionPool};
+use reth_primitives::{
+    Bytes, FromRecoveredTransaction, TransactionDecodeError, TransactionSigned, TxHash,
+};
+use reth_rlp::{Decodable, DecodeError, Header};
+use std::{io, path::Path};
+use tracing::debug;
+
+/// The number of transactions that are submitted to the pool at once.
+pub const DEFAULT_IMPORT_BATCH_SIZE: usize = 256;
+
+/// The result of importing a single transaction, the hash of the transaction if it was added to
+/// the pool.
+pub type ImportResult = Result<TxHash, ImportTransactionError>;
+
+/// Errors that can occur when importing a single transaction.
+#[derive(Debug, thiserror::Error)]
+pub enum ImportTransactionError {
+    /// The raw transaction could not be decoded.
+    #[error(transparent)]
+    InvalidRawTransaction(#[from] TransactionDecodeError),
+    /// The RLP encoded transaction could not be decoded.
+    #[error("malformed transaction: {0}")]
+    Decode(#[from] DecodeError),
+    /// The signer of the transaction could not be recovered.
+    #[error("invalid transaction signature")]
+    InvalidSignature,
+    /// The pool rejected the transaction.
+    #[error(transparent)]
+    Pool(#[from] PoolError),
+}
+
+/// Imports the given raw transactions, as accepted by `eth_sendRawTransaction`, into the pool.
+///
+/// Returns the result of every transaction, in the same order as the given transactions.
+pub async fn import_raw_transactions<P>(
+    pool: &P,
+    origin: TransactionOrigin,
+    transactions: Vec<Bytes>,
+) -> Vec<ImportResult>
+where
+    P: TransactionPool,
+{
+    let transactions = transactions
+        .into_iter()
+        .map(|tx| TransactionSigned::decode_enveloped(tx).map_err(Into::into))
+        .collect();
+    import_transactions(pool, origin, transactions).await
+}
+
+/// Imports the transactions of the file at the given path into the pool.
+///
+/// The file contains the RLP encoded transactions back to back, in the format of the
+/// [transactions journal](crate::journal). Items that are not transactions are skipped with an
+/// error. If the file is truncated, the last result is the error for the truncated item.
+///
+/// Returns the result of every transaction, in the same order as the transactions in the file.
+pub async fn import_transactions_file<P>(
+    pool: &P,
+    origin: TransactionOrigin,
+    path: &Path,
+) -> io::Result<Vec<ImportResult>>
+where
+    P: TransactionPool,
+{
+    let data = tokio::fs::read(path).await?;
+    let transactions = rlp_items(&data)
+        .into_iter()
+        .map(|item| Ok(TransactionSigned::decode(&mut item?)?))
+        .collect::<Vec<_>>();
+
+    debug!(target: "txpool", num_txs = transactions.len(), ?path, "Importing transactions from file");
+    Ok(import_transactions(pool, origin, transactions).await)
+}
+
+/// Recovers the given decoded transactions and submits them to the pool in batches.
+///
+/// Returns the result of every transaction, in the same order as the given transactions.
+pub async fn import_transactions<P>(
+    pool: &P,
+    origin: TransactionOrigin,
+    transactions: Vec<Result<TransactionSigned, ImportTransactionError>>,
+) -> Vec<ImportResult>
+where
+    P: TransactionPool,
+{
+    let mut results = Vec::with_capacity(transactions.len());
+    let mut transactions = transactions.into_iter().peekable();
+    while transactions.peek().is_some() {
+        let batch = transactions.by_ref().take(DEFAULT_IMPORT_BATCH_SIZE).collect::<Vec<_>>();
+        // signer recovery is CPU bound
+        let batch = tokio::task::spawn_blocking(move || {
+            batch
+                .into_iter()
+                .map(|tx| {
+                    tx.and_then(|tx| {
+                        tx.into_ecrecovered().ok_or(ImportTransactionError::InvalidSignature)
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()));
+
+        // `None` marks the transactions that are submitted to the pool
+        let mut batch_results = Vec::with_capacity(batch.len());
+        let mut recovered = Vec::with_capacity(batch.len());
+        for tx in batch {
+            match tx {
+                Ok(tx) => {
+                    batch_results.push(None);
+                    recovered.push(P::Transaction::from_recovered_transaction(tx));
+                }
+                Err(err) => batch_results.push(Some(Err(err))),
+            }
+        }
+
+        let hashes = recovered.iter().map(|tx| *tx.hash()).collect::<Vec<_>>();
+        let added = match pool.add_transactions(origin, recovered).await {
+            Ok(added) => added,
+            Err(err) => {
+                let err = err.to_string();
+                hashes
+                    .into_iter()
+                    .map(|hash| Err(PoolError::Other(hash, err.clone().into())))
+                    .collect()
+            }
+        };
+
+        let mut added = added.into_iter();
+        results.extend(batch_results.into_iter().map(|res| {
+            res.unwrap_or_else(|| {
+                added.next().expect("one result per transaction").map_err(Into::into)
+            })
+        }));
+    }
+    results
+}
+
+/// Splits the data into its top level RLP items.
+///
+/// Stops at the first item with an invalid header or a payload beyond the end of the data.
+fn rlp_items(mut data: &[u8]) -> Vec<Result<&[u8], DecodeError>> {
+    let mut items = Vec::new();
+    while !data.is_empty() {
+        let mut payload = data;
+        match Header::decode(&mut payload) {
+            Ok(header) if header.payload_length <= payload.len() => {
+                let len = data.len() - payload.len() + header.payload_length;
+                items.push(Ok(&data[..len]));
+                data = &data[len..];
+            }
+            Ok(_) => {
+                items.push(Err(DecodeError::InputTooShort));
+                break
+            }
+            Err(err) => {
+                items.push(Err(err));
+                break
+            }
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::testing_pool;
+    use reth_primitives::{sign_message, Signature, Transaction, TxLegacy, H256};
+    use reth_rlp::Encodable;
+
+    fn signed_transaction(nonce: u64) -> TransactionSigned {
+        let tx = Transaction::Legacy(TxLegacy { nonce, gas_limit: 21_000, ..Default::default() });
+        let secret = H256::from_low_u64_be(1337);
+        let signature = sign_message(secret, tx.signature_hash()).unwrap();
+        TransactionSigned::from_transaction_and_signature(tx, signature)
+    }
+
+    #[tokio::test]
+    async fn import_file_with_invalid_entries() {
+        let dir = std::env::temp_dir().join(format!("reth-txpool-import-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("transactions.rlp");
+
+        let transactions = (0..3).map(signed_transaction).collect::<Vec<_>>();
+        let unsigned = TransactionSigned::from_transaction_and_signature(
+            signed_transaction(3).transaction,
+            Signature::default(),
+        );
+        let mut data = Vec::new();
+        transactions[0].encode(&mut data);
+        transactions[1].encode(&mut data);
+        transactions[0].encode(&mut data);
+        unsigned.encode(&mut data);
+        // an RLP string that is not a transaction
+        Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]).encode(&mut data);
+        transactions[2].encode(&mut data);
+        // a truncated item
+        data.extend([0xf8, 0xff, 0x01]);
+        fs::write(&path, data).unwrap();
+
+        let pool = testing_pool();
+        let results =
+            import_transactions_file(&pool, TransactionOrigin::Local, &path).await.unwrap();
+        assert_eq!(results.len(), 7);
+        assert_eq!(results[0].as_ref().unwrap(), &transactions[0].hash);
+        assert_eq!(results[1].as_ref().unwrap(), &transactions[1].hash);
+        assert!(matches!(
+            results[2],
+            Err(ImportTransactionError::Pool(PoolError::AlreadyImported(hash))) if hash == transactions[0].hash
+        ));
+        assert!(matches!(results[3], Err(ImportTransactionError::InvalidSignature)));
+        assert!(matches!(results[4], Err(ImportTransactionError::Decode(_))));
+        assert_eq!(results[5].as_ref().unwrap(), &transactions[2].hash);
+        assert!(matches!(
+            results[6],
+            Err(ImportTransactionError::Decode(DecodeError::InputTooShort))
+        ));
+        assert_eq!(pool.len(), 3);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn import_raw_transactions_in_order() {
+        let transactions =
+            (0..DEFAULT_IMPORT_BATCH_SIZE as u64 + 10).map(signed_transaction).collect::<Vec<_>>();
+        let mut raw = transactions
+            .iter()
+            .map(|tx| {
+                let mut buf = Vec::new();
+                tx.encode_enveloped(&mut buf);
+                Bytes::from(buf)
+            })
+            .collect::<Vec<_>>();
+        raw.insert(1, Bytes::default());
+
+        let pool = testing_pool();
+        let results = import_raw_transactions(&pool, TransactionOrigin::Local, raw).await;
+        assert_eq!(results.len(), transactions.len() + 1);
+        assert!(matches!(
+            results[1],
+            Err(ImportTransactionError::InvalidRawTransaction(TransactionDecodeError::EmptyInput))
+        ));
+        let hashes = results.into_iter().filter_map(Result::ok).collect::<Vec<_>>();
+        assert_eq!(hashes, transactions.iter().map(|tx| tx.hash).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,148 @@
+//! Persistence of local transactions.
+//!
+//! Local transactions are transactions that were submitted to this node directly, for example via
+//! RPC. Unlike transactions received from the network, these will not be re-broadcast to us by
+//! other peers, so they are written to a journal file on shutdown and reinserted into the pool on
+//! startup.
+
+use crate::{TransactionOrigin, TransactionPool};
+use reth_primitives::{FromRecoveredTransaction, IntoRecoveredTransaction, TransactionSigned};
+use reth_rlp::Decodable;
+use std::{fs, io, path::Path};
+use tracing::{debug, trace};
+
+/// Errors that can occur when reading or writing the local transactions journal.
+#[derive(Debug, thiserror::Error)]
+pub enum TransactionsJournalError {
+    /// Failed to read or write the journal file.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The journal file is malformed.
+    #[error("malformed transactions journal: {0}")]
+    Decode(#[from] reth_rlp::DecodeError),
+}
+
+/// Writes the given transactions as RLP list to the journal file at the given path.
+///
+/// The journal is first written to a temporary file next to the journal which is then renamed, so
+/// that an interrupted write does not corrupt an existing journal.
+pub fn write_transactions_journal(
+    path: &Path,
+    transactions: &[TransactionSigned],
+) -> Result<(), TransactionsJournalError> {
+    let mut buf = Vec::new();
+    reth_rlp::encode_list::<TransactionSigned, _>(transactions, &mut buf);
+
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, buf)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Reads all transactions from the journal file at the given path.
+///
+/// Returns an empty list if the journal does not exist.
+pub fn read_transactions_journal(
+    path: &Path,
+) -> Result<Vec<TransactionSigned>, TransactionsJournalError> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    Ok(Vec::<TransactionSigned>::decode(&mut data.as_slice())?)
+}
+
+/// Writes all local transactions of the pool to the journal file at the given path.
+///
+/// Returns the number of written transactions.
+pub fn save_local_transactions<P>(pool: &P, path: &Path) -> Result<usize, TransactionsJournalError>
+where
+    P: TransactionPool,
+{
+    let transactions = pool
+        .pooled_transactions()
+        .into_iter()
+        .filter(|tx| tx.is_local())
+        .map(|tx| tx.transaction.to_recovered_transaction().into_signed())
+        .collect::<Vec<_>>();
+
+    trace!(target: "txpool", num_txs=%transactions.len(), ?path, "Saving local transactions");
+    write_transactions_journal(path, &transactions)?;
+    Ok(transactions.len())
+}
+
+/// Reads the journal file at the given path and inserts all transactions into the pool as local
+/// transactions.
+///
+/// Transactions with an invalid signature are skipped, transactions that are no longer valid are
+/// rejected by the pool as usual. Returns the number of transactions that were submitted to the
+/// pool.
+pub async fn load_local_transactions<P>(
+    pool: &P,
+    path: &Path,
+) -> Result<usize, TransactionsJournalError>
+where
+    P: TransactionPool,
+{
+    let transactions = read_transactions_journal(path)?
+        .into_iter()
+        .filter_map(|tx| tx.into_ecrecovered())
+        .map(<P::Transaction as FromRecoveredTransaction>::from_recovered_transaction)
+        .collect::<Vec<_>>();
+
+    let num_txs = transactions.len();
+    debug!(target: "txpool", num_txs, ?path, "Reinserting local transactions from journal");
+
+    for res in
+        pool.add_transactions(TransactionOrigin::Local, transactions).await.into_iter().flatten()
+    {
+        if let Err(err) = res {
+            trace!(target: "txpool", ?err, "Failed to reinsert local transaction");
+        }
+    }
+
+    Ok(num_txs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{sign_message, Transaction, TxLegacy, H256};
+
+    fn signed_transaction(nonce: u64) -> TransactionSigned {
+        let tx = Transaction::Legacy(TxLegacy { nonce, gas_limit: 21_000, ..Default::default() });
+        let secret = H256::from_low_u64_be(1337);
+        let signature = sign_message(secret, tx.signature_hash()).unwrap();
+        TransactionSigned::from_transaction_and_signature(tx, signature)
+    }
+
+    #[test]
+    fn journal_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transactions.rlp");
+
+        // reading a missing journal yields nothing
+        assert!(read_transactions_journal(&path).unwrap().is_empty());
+
+        let transactions = (0..3).map(signed_transaction).collect::<Vec<_>>();
+        write_transactions_journal(&path, &transactions).unwrap();
+
+        let read = read_transactions_journal(&path).unwrap();
+        assert_eq!(read, transactions);
+        assert!(read.into_iter().all(|tx| tx.into_ecrecovered().is_some()));
+        assert!(!path.with_extension("tmp").exists());
+    }
+
+    #[test]
+    fn malformed_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transactions.rlp");
+
+        fs::write(&path, [0xde, 0xad]).unwrap();
+        assert!(matches!(
+            read_transactions_journal(&path),
+            Err(TransactionsJournalError::Decode(_))
+        ));
+    }
+}
@@ -80,31 +80,34 @@
 //! that provides the `TransactionPool` interface.
 
 pub use crate::{
-    config::PoolConfig,
-    ordering::{CostOrdering, TransactionOrdering},
+    config::{PoolClock, PoolConfig, SystemClock, DEFAULT_QUEUED_LIFETIME, MIN_PROTOCOL_BASE_FEE},
+    ordering::{CoinbaseTipOrdering, TransactionOrdering},
     traits::{
         BestTransactions, OnNewBlockEvent, PoolTransaction, PooledTransaction, PropagateKind,
-        PropagatedTransactions, TransactionOrigin, TransactionPool,
+        PropagatedTransactions, SenderTransactions, StateDiff, TransactionOrigin, TransactionPool,
     },
     validate::{
         EthTransactionValidator, TransactionValidationOutcome, TransactionValidator,
-        ValidPoolTransaction,
+        ValidPoolTransaction, ValidationHead,
     },
 };
 use crate::{
     error::PoolResult,
-    pool::PoolInner,
+    pool::{PoolInner, TransactionEvent},
     traits::{NewTransactionEvent, PoolSize},
 };
 
 use crate::error::PoolError;
-use reth_primitives::{TxHash, U256};
-use std::{collections::HashMap, sync::Arc};
-use tokio::sync::mpsc::Receiver;
+use reth_primitives::{Address, TxHash, U256};
+use std::sync::Arc;
+use tokio::sync::mpsc::{Receiver, UnboundedReceiver};
 
+pub mod blobstore;
 mod config;
 pub mod error;
 mod identifier;
+pub mod import;
+pub mod journal;
 pub mod metrics;
 mod ordering;
 pub mod pool;
@@ -163,17 +166,20 @@ where
     }
 
     /// Returns future that validates all transaction in the given iterator.
+    ///
+    /// The outcomes are in the same order as the transactions.
     async fn validate_all(
         &self,
         origin: TransactionOrigin,
         transactions: impl IntoIterator<Item = V::Transaction>,
-    ) -> PoolResult<HashMap<TxHash, TransactionValidationOutcome<V::Transaction>>> {
+    ) -> PoolResult<Vec<TransactionValidationOutcome<V::Transaction>>> {
         let outcome = futures_util::future::join_all(
             transactions.into_iter().map(|tx| self.validate(origin, tx)),
         )
         .await
         .into_iter()
-        .collect::<HashMap<_, _>>();
+        .map(|(_, outcome)| outcome)
+        .collect();
 
         Ok(outcome)
     }
@@ -194,6 +200,14 @@ where
         (hash, outcome)
     }
 
+    /// Returns a new listener for the state changes of the transaction with the given hash.
+    pub fn transaction_event_listener(
+        &self,
+        tx_hash: TxHash,
+    ) -> UnboundedReceiver<TransactionEvent> {
+        self.pool.add_transaction_event_listener(tx_hash)
+    }
+
     /// Number of transactions in the entire pool
     pub fn len(&self) -> usize {
         self.pool.len()
@@ -249,7 +263,7 @@ where
     ) -> PoolResult<Vec<PoolResult<TxHash>>> {
         let validated = self.validate_all(origin, transactions).await?;
 
-        let transactions = self.pool.add_transactions(origin, validated.into_values());
+        let transactions = self.pool.add_transactions(origin, validated);
         Ok(transactions)
     }
 
@@ -275,6 +289,13 @@ where
         Box::new(self.pool.best_transactions())
     }
 
+    fn best_transactions_with_base_fee(
+        &self,
+        base_fee: u64,
+    ) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<Self::Transaction>>>> {
+        Box::new(self.pool.best_transactions_with_base_fee(base_fee as u128))
+    }
+
     fn remove_invalid(
         &self,
         hashes: impl IntoIterator<Item = TxHash>,
@@ -282,6 +303,10 @@ where
         self.pool.remove_invalid(hashes)
     }
 
+    fn discard_expired(&self) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        self.pool.discard_expired()
+    }
+
     fn retain_unknown(&self, hashes: &mut Vec<TxHash>) {
         self.pool.retain_unknown(hashes)
     }
@@ -300,6 +325,13 @@ where
     fn on_propagated(&self, txs: PropagatedTransactions) {
         self.inner().on_propagated(txs)
     }
+
+    fn pending_and_queued_by_sender(
+        &self,
+        sender: Address,
+    ) -> SenderTransactions<Self::Transaction> {
+        self.inner().pending_and_queued_by_sender(sender)
+    }
 }
 
 impl<V: TransactionValidator, T: TransactionOrdering> Clone for Pool<V, T> {
@@ -13,4 +13,6 @@ pub struct TxPoolMetrics {
     pub(crate) invalid_transactions: Counter,
     /// Number of removed transactions from the pool
     pub(crate) removed_transactions: Counter,
+    /// Number of queued transactions discarded because they were in the pool for too long
+    pub(crate) expired_transactions: Counter,
 }
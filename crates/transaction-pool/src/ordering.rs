@@ -1,5 +1,4 @@
 use crate::traits::PoolTransaction;
-use reth_primitives::U256;
 use std::{fmt, marker::PhantomData};
 
 /// Transaction ordering trait to determine the order of transactions.
@@ -16,26 +15,36 @@ pub trait TransactionOrdering: Send + Sync + 'static {
     /// The transaction type to determine the priority of.
     type Transaction: PoolTransaction;
 
-    /// Returns the priority score for the given transaction.
-    fn priority(&self, transaction: &Self::Transaction) -> Self::Priority;
+    /// Returns the priority score for the given transaction in a block with the given base fee.
+    ///
+    /// The priorities of the pending transactions are recomputed whenever the base fee of the
+    /// pending block changes.
+    fn priority(&self, transaction: &Self::Transaction, base_fee: u128) -> Self::Priority;
 }
 
 /// Default ordering for the pool.
 ///
-/// The transactions are ordered by their cost. The higher the cost,
-/// the higher the priority of this transaction is.
-#[derive(Debug, Default)]
+/// The transactions are ordered by the priority fee per gas they pay to the block author, see
+/// [`PoolTransaction::effective_tip_per_gas`]. The higher the tip, the higher the priority of this
+/// transaction is.
+#[derive(Debug)]
 #[non_exhaustive]
-pub struct CostOrdering<T>(PhantomData<T>);
+pub struct CoinbaseTipOrdering<T>(PhantomData<T>);
+
+impl<T> Default for CoinbaseTipOrdering<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
 
-impl<T> TransactionOrdering for CostOrdering<T>
+impl<T> TransactionOrdering for CoinbaseTipOrdering<T>
 where
     T: PoolTransaction + 'static,
 {
-    type Priority = U256;
+    type Priority = u128;
     type Transaction = T;
 
-    fn priority(&self, transaction: &Self::Transaction) -> Self::Priority {
-        transaction.cost()
+    fn priority(&self, transaction: &Self::Transaction, base_fee: u128) -> Self::Priority {
+        transaction.effective_tip_per_gas(base_fee).unwrap_or_default()
     }
 }
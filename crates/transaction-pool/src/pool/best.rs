@@ -1,9 +1,8 @@
 use crate::{
-    identifier::TransactionId,
+    identifier::{SenderId, TransactionId},
     pool::pending::{PendingTransaction, PendingTransactionRef},
-    TransactionOrdering, ValidPoolTransaction,
+    PoolTransaction, TransactionOrdering, ValidPoolTransaction,
 };
-use reth_primitives::H256 as TxHash;
 use std::{
     collections::{BTreeMap, BTreeSet, HashSet},
     sync::Arc,
@@ -26,14 +25,21 @@ pub(crate) struct BestTransactions<T: TransactionOrdering> {
     /// Once an `independent` transaction with the nonce `N` is returned, it unlocks `N+1`, which
     /// then can be moved from the `all` set to the `independent` set.
     pub(crate) independent: BTreeSet<PendingTransactionRef<T>>,
-    /// There might be the case where a yielded transactions is invalid, this will track it.
-    pub(crate) invalid: HashSet<TxHash>,
+    /// There might be the case where a yielded transactions is invalid, this will track the
+    /// senders of invalid transactions, whose transactions with higher nonces are all skipped.
+    pub(crate) invalid: HashSet<SenderId>,
+    /// If set, transactions that can't pay this base fee are skipped, together with the
+    /// transactions of the same sender with higher nonces.
+    pub(crate) base_fee: Option<u128>,
 }
 
 impl<T: TransactionOrdering> BestTransactions<T> {
     /// Mark the transaction and it's descendants as invalid.
+    ///
+    /// Since transactions of a sender are yielded in nonce order, this skips all transactions of
+    /// the sender that weren't yielded yet.
     pub(crate) fn mark_invalid(&mut self, tx: &Arc<ValidPoolTransaction<T::Transaction>>) {
-        self.invalid.insert(*tx.hash());
+        self.invalid.insert(tx.sender_id());
     }
 }
 
@@ -54,7 +60,7 @@ impl<T: TransactionOrdering> Iterator for BestTransactions<T> {
             let hash = best.transaction.hash();
 
             // skip transactions that were marked as invalid
-            if self.invalid.contains(hash) {
+            if self.invalid.contains(&best.transaction.sender_id()) {
                 debug!(
                     target: "txpool",
                     "[{:?}] skipping invalid transaction",
@@ -63,6 +69,21 @@ impl<T: TransactionOrdering> Iterator for BestTransactions<T> {
                 continue
             }
 
+            // skip transactions that can't pay the base fee, which also invalidates the
+            // descendants
+            if let Some(base_fee) = self.base_fee {
+                if best.transaction.transaction.effective_tip_per_gas(base_fee).is_none() {
+                    debug!(
+                        target: "txpool",
+                        "[{:?}] skipping transaction below base fee {}",
+                        hash,
+                        base_fee
+                    );
+                    self.invalid.insert(best.transaction.sender_id());
+                    continue
+                }
+            }
+
             // Insert transactions that just got unlocked.
             if let Some(unlocked) = self.all.get(&best.unlocks()) {
                 self.independent.insert(unlocked.transaction.clone());
@@ -79,6 +100,7 @@ mod tests {
     use crate::{
         pool::pending::PendingPool,
         test_utils::{MockOrdering, MockTransaction, MockTransactionFactory},
+        CoinbaseTipOrdering,
     };
 
     #[test]
@@ -92,7 +114,7 @@ mod tests {
         for nonce in 0..num_tx {
             let tx = tx.clone().rng_hash().with_nonce(nonce);
             let valid_tx = f.validated(tx);
-            pool.add_transaction(Arc::new(valid_tx));
+            pool.add_transaction(Arc::new(valid_tx), 0);
         }
 
         let mut best = pool.best();
@@ -118,7 +140,7 @@ mod tests {
         for nonce in 0..num_tx {
             let tx = tx.clone().rng_hash().with_nonce(nonce);
             let valid_tx = f.validated(tx);
-            pool.add_transaction(Arc::new(valid_tx));
+            pool.add_transaction(Arc::new(valid_tx), 0);
         }
 
         let mut best = pool.best();
@@ -130,4 +152,68 @@ mod tests {
         // iterator is empty
         assert!(best.next().is_none());
     }
+
+    #[test]
+    fn test_best_iter_tip_ordering() {
+        let mut pool = PendingPool::new(CoinbaseTipOrdering::<MockTransaction>::default());
+        let mut f = MockTransactionFactory::default();
+
+        let a0 = MockTransaction::eip1559().with_max_fee(100).with_priority_fee(2);
+        let a1 = a0.next().with_priority_fee(50);
+        let b0 = MockTransaction::eip1559().with_max_fee(21).with_priority_fee(20);
+        for tx in [a0.clone(), a1.clone(), b0.clone()] {
+            pool.add_transaction(Arc::new(f.validated(tx)), 0);
+        }
+
+        // the best tip of a sender is only yielded after its lower nonces
+        let hashes = pool.best().map(|tx| *tx.hash()).collect::<Vec<_>>();
+        assert_eq!(hashes, vec![b0.get_hash(), a0.get_hash(), a1.get_hash()]);
+
+        // the tip of `b0` is capped by its max fee once the base fee rises
+        pool.update_base_fee(20);
+        let hashes = pool.best().map(|tx| *tx.hash()).collect::<Vec<_>>();
+        assert_eq!(hashes, vec![a0.get_hash(), a1.get_hash(), b0.get_hash()]);
+    }
+
+    #[test]
+    fn test_best_iter_invalid_descendants() {
+        let mut pool = PendingPool::new(MockOrdering::default());
+        let mut f = MockTransactionFactory::default();
+
+        // nonces 0..=3 of one sender, added before the transaction of another sender
+        let tx = MockTransaction::eip1559();
+        for nonce in 0..4 {
+            let tx = tx.clone().rng_hash().with_nonce(nonce);
+            pool.add_transaction(Arc::new(f.validated(tx)), 0);
+        }
+        let other = MockTransaction::eip1559();
+        pool.add_transaction(Arc::new(f.validated(other.clone())), 0);
+
+        let mut best = pool.best();
+        assert_eq!(best.next().unwrap().nonce(), 0);
+        let invalid = best.next().unwrap();
+        assert_eq!(invalid.nonce(), 1);
+
+        // nonce 2 is already unlocked, but skipped together with nonce 3
+        best.mark_invalid(&invalid);
+        assert_eq!(best.next().unwrap().hash(), &other.get_hash());
+        assert!(best.next().is_none());
+    }
+
+    #[test]
+    fn test_best_iter_with_base_fee() {
+        let mut pool = PendingPool::new(MockOrdering::default());
+        let mut f = MockTransactionFactory::default();
+
+        let low = MockTransaction::eip1559().with_max_fee(10);
+        let high = MockTransaction::eip1559().with_max_fee(30);
+        for tx in [low.clone(), low.next().with_max_fee(30), high.clone()] {
+            pool.add_transaction(Arc::new(f.validated(tx)), 0);
+        }
+        assert_eq!(pool.best().count(), 3);
+
+        // the descendant of the transaction below the base fee is skipped as well
+        let hashes = pool.best_with_base_fee(20).map(|tx| *tx.hash()).collect::<Vec<_>>();
+        assert_eq!(hashes, vec![high.get_hash()]);
+    }
 }
@@ -19,10 +19,22 @@ pub enum TransactionEvent {
     ///
     /// E.g. same (sender + nonce) pair
     Replaced(TxHash),
-    /// Transaction was dropped due to configured limits.
-    Discarded,
+    /// Transaction was removed from the pool for the given reason.
+    Discarded(DiscardReason),
     /// Transaction became invalid indefinitely.
     Invalid,
     /// Transaction was propagated to peers.
     Propagated(Arc<Vec<PropagateKind>>),
 }
+
+/// The reason a transaction was removed from the pool without being mined.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DiscardReason {
+    /// Transaction is invalid or can no longer be included.
+    Invalid,
+    /// Transaction was dropped due to configured limits.
+    Limits,
+    /// Transaction was queued for longer than the configured lifetime.
+    Expired,
+}
@@ -1,9 +1,12 @@
 //! Listeners for the transaction-pool
 
-use crate::{pool::events::TransactionEvent, traits::PropagateKind};
+use crate::{
+    pool::events::{DiscardReason, TransactionEvent},
+    traits::PropagateKind,
+};
 use reth_primitives::{TxHash, H256};
 use std::{collections::HashMap, sync::Arc};
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 
 type EventBroadcast = UnboundedSender<TransactionEvent>;
 
@@ -18,6 +21,17 @@ pub(crate) struct PoolEventBroadcast {
 }
 
 impl PoolEventBroadcast {
+    /// Returns a new listener for the events of the transaction with the given hash.
+    pub(crate) fn subscribe(&mut self, tx: TxHash) -> UnboundedReceiver<TransactionEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.broadcasters
+            .entry(tx)
+            .or_insert_with(|| PoolEventBroadcaster { is_done: false, senders: Vec::new() })
+            .senders
+            .push(sender);
+        receiver
+    }
+
     /// Calls the broadcast callback with the `PoolEventBroadcaster` that belongs to the hash.
     fn broadcast_with<F>(&mut self, hash: &TxHash, callback: F)
     where
@@ -56,8 +70,8 @@ impl PoolEventBroadcast {
     }
 
     /// Notify listeners about a transaction that was discarded.
-    pub(crate) fn discarded(&mut self, tx: &TxHash) {
-        self.broadcast_with(tx, |notifier| notifier.discarded());
+    pub(crate) fn discarded(&mut self, tx: &TxHash, reason: DiscardReason) {
+        self.broadcast_with(tx, |notifier| notifier.discarded(reason));
     }
 
     /// Notify listeners that the transaction was mined
@@ -114,9 +128,9 @@ impl PoolEventBroadcaster {
         self.broadcast(TransactionEvent::Propagated(Arc::new(peers)));
     }
 
-    /// Transaction was replaced with the given transaction
-    fn discarded(&mut self) {
-        self.broadcast(TransactionEvent::Discarded);
+    /// Transaction was removed from the pool.
+    fn discarded(&mut self, reason: DiscardReason) {
+        self.broadcast(TransactionEvent::Discarded(reason));
         self.is_done = true;
     }
 }
@@ -72,16 +72,17 @@ use crate::{
     identifier::{SenderId, SenderIdentifiers, TransactionId},
     pool::{listener::PoolEventBroadcast, state::SubPool, txpool::TxPool},
     traits::{
-        NewTransactionEvent, PoolSize, PoolTransaction, PropagatedTransactions, TransactionOrigin,
+        NewTransactionEvent, PoolSize, PoolTransaction, PropagatedTransactions, SenderTransactions,
+        TransactionOrigin,
     },
     validate::{TransactionValidationOutcome, ValidPoolTransaction},
     OnNewBlockEvent, PoolConfig, TransactionOrdering, TransactionValidator,
 };
 use best::BestTransactions;
-pub use events::TransactionEvent;
+pub use events::{DiscardReason, TransactionEvent};
 use parking_lot::{Mutex, RwLock};
 use reth_primitives::{Address, TxHash, H256};
-use std::{collections::HashSet, fmt, sync::Arc, time::Instant};
+use std::{collections::HashSet, fmt, sync::Arc};
 use tokio::sync::mpsc;
 use tracing::warn;
 
@@ -170,6 +171,14 @@ where
         rx
     }
 
+    /// Adds a new listener for the state changes of the transaction with the given hash.
+    pub fn add_transaction_event_listener(
+        &self,
+        tx_hash: TxHash,
+    ) -> mpsc::UnboundedReceiver<TransactionEvent> {
+        self.event_listener.write().subscribe(tx_hash)
+    }
+
     /// Returns hashes of _all_ transactions in the pool.
     pub(crate) fn pooled_transactions_hashes(&self) -> Vec<TxHash> {
         let pool = self.pool.read();
@@ -183,9 +192,25 @@ where
     }
 
     /// Updates the entire pool after a new block was executed.
+    ///
+    /// This also discards the queued transactions that expired, see [Self::discard_expired].
     pub(crate) fn on_new_block(&self, block: OnNewBlockEvent) {
+        if let Some(blob_store) = &self.config.blob_store {
+            // if the event covers several blocks, the transactions are recorded as mined in the
+            // head, which only keeps their sidecars longer
+            if let Err(err) = blob_store.on_mined(block.head.number, &block.mined_transactions) {
+                warn!(target: "txpool", ?err, "Failed to record mined blob transactions");
+            }
+            if let Some(finalized) = block.finalized_block {
+                if let Err(err) = blob_store.on_finalized(finalized) {
+                    warn!(target: "txpool", ?err, finalized, "Failed to delete finalized blobs");
+                }
+            }
+        }
+        self.validator.on_new_head(block.head);
         let outcome = self.pool.write().on_new_block(block);
         self.notify_on_new_block(outcome);
+        self.discard_expired();
     }
 
     /// Add a single validated transaction into the pool.
@@ -208,7 +233,7 @@ where
                     transaction,
                     transaction_id,
                     propagate: false,
-                    timestamp: Instant::now(),
+                    timestamp: self.config.clock.now(),
                     origin,
                     encoded_length,
                 };
@@ -231,12 +256,12 @@ where
             }
             TransactionValidationOutcome::Invalid(tx, err) => {
                 let mut listener = self.event_listener.write();
-                listener.discarded(tx.hash());
+                listener.discarded(tx.hash(), DiscardReason::Invalid);
                 Err(PoolError::InvalidTransaction(*tx.hash(), err))
             }
             TransactionValidationOutcome::Error(tx, err) => {
                 let mut listener = self.event_listener.write();
-                listener.discarded(tx.hash());
+                listener.discarded(tx.hash(), DiscardReason::Invalid);
                 Err(PoolError::Other(*tx.hash(), err))
             }
         }
@@ -320,7 +345,7 @@ where
 
         mined.iter().for_each(|tx| listener.mined(tx, block_hash));
         promoted.iter().for_each(|tx| listener.pending(tx, None));
-        discarded.iter().for_each(|tx| listener.discarded(tx));
+        discarded.iter().for_each(|tx| listener.discarded(tx, DiscardReason::Invalid));
     }
 
     /// Fire events for the newly added transaction.
@@ -333,7 +358,7 @@ where
 
                 listener.pending(transaction.hash(), None);
                 promoted.iter().for_each(|tx| listener.pending(tx, None));
-                discarded.iter().for_each(|tx| listener.discarded(tx));
+                discarded.iter().for_each(|tx| listener.discarded(tx, DiscardReason::Invalid));
             }
             AddedTransaction::Parked { transaction, .. } => {
                 listener.queued(transaction.hash());
@@ -346,6 +371,12 @@ where
         self.pool.read().best_transactions()
     }
 
+    /// Returns an iterator that yields transactions that are ready to be included in a block with
+    /// the given base fee.
+    pub(crate) fn best_transactions_with_base_fee(&self, base_fee: u128) -> BestTransactions<T> {
+        self.pool.read().best_transactions_with_base_fee(base_fee)
+    }
+
     /// Removes and returns all matching transactions from the pool.
     pub(crate) fn remove_invalid(
         &self,
@@ -355,11 +386,23 @@ where
 
         let mut listener = self.event_listener.write();
 
-        removed.iter().for_each(|tx| listener.discarded(tx.hash()));
+        removed.iter().for_each(|tx| listener.discarded(tx.hash(), DiscardReason::Invalid));
 
         removed
     }
 
+    /// Discards the queued transactions that were in the pool for longer than the configured
+    /// [`queued_lifetime`](PoolConfig::queued_lifetime) and returns them.
+    pub(crate) fn discard_expired(&self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        let expired = self.pool.write().discard_expired();
+
+        let mut listener = self.event_listener.write();
+
+        expired.iter().for_each(|tx| listener.discarded(tx.hash(), DiscardReason::Expired));
+
+        expired
+    }
+
     /// Removes all transactions that are present in the pool.
     pub(crate) fn retain_unknown(&self, hashes: &mut Vec<TxHash>) {
         let pool = self.pool.read();
@@ -384,6 +427,18 @@ where
         self.pool.read().get_all(txs).collect()
     }
 
+    /// Returns the transactions of the sender and the nonce gap that keeps them from being
+    /// promoted, read under a single lock of the pool.
+    pub(crate) fn pending_and_queued_by_sender(
+        &self,
+        sender: Address,
+    ) -> SenderTransactions<T::Transaction> {
+        let Some(sender) = self.identifiers.read().sender_id(&sender) else {
+            return SenderTransactions::default()
+        };
+        self.pool.read().sender_transactions(sender)
+    }
+
     /// Notify about propagated transactions.
     pub(crate) fn on_propagated(&self, txs: PropagatedTransactions) {
         let mut listener = self.event_listener.write();
@@ -403,7 +458,19 @@ where
 
     /// Enforces the size limits of pool and returns the discarded transactions if violated.
     pub(crate) fn discard_worst(&self) -> HashSet<TxHash> {
-        self.pool.write().discard_worst().into_iter().map(|tx| *tx.hash()).collect()
+        let discarded = self
+            .pool
+            .write()
+            .discard_worst()
+            .into_iter()
+            .map(|tx| *tx.hash())
+            .collect::<HashSet<_>>();
+
+        let mut listener = self.event_listener.write();
+
+        discarded.iter().for_each(|tx| listener.discarded(tx, DiscardReason::Limits));
+
+        discarded
     }
 }
 
@@ -489,3 +556,96 @@ pub(crate) struct OnNewBlockOutcome {
     /// transaction that were discarded during the update
     pub(crate) discarded: Vec<TxHash>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blobstore::{BlobStore, DiskFileBlobStore, DiskFileBlobStoreConfig},
+        test_utils::{MockClock, MockOrdering, MockTransaction, NoopTransactionValidator},
+        StateDiff, ValidationHead,
+    };
+    use reth_primitives::{Header, MAINNET};
+    use std::time::Duration;
+
+    #[test]
+    fn discard_expired_queued_transactions() {
+        let clock = Arc::new(MockClock::default());
+        let config = PoolConfig {
+            queued_lifetime: Duration::from_secs(60),
+            clock: clock.clone(),
+            ..Default::default()
+        };
+        let pool =
+            PoolInner::new(NoopTransactionValidator::default(), MockOrdering::default(), config);
+        let add = |origin, transaction| {
+            let outcome = TransactionValidationOutcome::Valid {
+                balance: Default::default(),
+                state_nonce: 0,
+                transaction,
+            };
+            pool.add_transactions(origin, std::iter::once(outcome)).pop().unwrap().unwrap()
+        };
+
+        // all transactions have a nonce gap and are queued
+        let tx = MockTransaction::eip1559().inc_nonce();
+        let external = add(TransactionOrigin::External, tx.clone());
+        let descendant = add(TransactionOrigin::External, tx.next());
+        let local = add(TransactionOrigin::Local, MockTransaction::eip1559().inc_nonce());
+        let mut events = pool.add_transaction_event_listener(external);
+
+        clock.advance(Duration::from_secs(30));
+        let later = add(TransactionOrigin::External, MockTransaction::eip1559().inc_nonce());
+        assert_eq!(pool.size().queued, 4);
+
+        clock.advance(Duration::from_secs(31));
+        let expired = pool.discard_expired().iter().map(|tx| *tx.hash()).collect::<HashSet<_>>();
+        assert_eq!(expired, HashSet::from([external, descendant]));
+        assert_eq!(events.try_recv(), Ok(TransactionEvent::Discarded(DiscardReason::Expired)));
+        assert!(pool.get(&later).is_some());
+
+        clock.advance(Duration::from_secs(30));
+        let expired = pool.discard_expired().iter().map(|tx| *tx.hash()).collect::<Vec<_>>();
+        assert_eq!(expired, vec![later]);
+
+        // local transactions never expire
+        clock.advance(Duration::from_secs(24 * 60 * 60));
+        assert!(pool.discard_expired().is_empty());
+        assert!(pool.get(&local).is_some());
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn delete_blobs_of_finalized_transactions() {
+        let dir = std::env::temp_dir().join(format!("reth-pool-blobs-{}", std::process::id()));
+        let blob_store =
+            DiskFileBlobStore::open(&dir, DiskFileBlobStoreConfig { retention_blocks: 0 }).unwrap();
+        let config =
+            PoolConfig { blob_store: Some(Arc::new(blob_store.clone())), ..Default::default() };
+        let pool =
+            PoolInner::new(NoopTransactionValidator::default(), MockOrdering::default(), config);
+
+        let tx = H256::random();
+        blob_store.insert(tx, Default::default()).unwrap();
+        let on_new_block = |number, mined_transactions, finalized_block| {
+            let header = Header { number, ..Default::default() };
+            pool.on_new_block(OnNewBlockEvent {
+                hash: H256::random(),
+                pending_block_base_fee: 0,
+                state_changes: StateDiff {},
+                mined_transactions,
+                finalized_block,
+                head: ValidationHead::new(&MAINNET, &header),
+            })
+        };
+
+        on_new_block(1, vec![tx], None);
+        assert!(blob_store.contains(tx));
+        on_new_block(2, vec![], Some(1));
+        assert!(blob_store.contains(tx));
+        on_new_block(3, vec![], Some(2));
+        assert!(!blob_store.contains(tx));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
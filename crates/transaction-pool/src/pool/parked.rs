@@ -73,6 +73,13 @@ impl<T: ParkedOrd> ParkedPool<T> {
         self.remove_transaction(&worst)
     }
 
+    /// Returns an iterator over all transactions in this pool.
+    pub(crate) fn transactions(
+        &self,
+    ) -> impl Iterator<Item = &Arc<ValidPoolTransaction<T::Transaction>>> + '_ {
+        self.by_id.values().map(|tx| tx.transaction.deref())
+    }
+
     fn next_id(&mut self) -> u64 {
         let id = self.submission_id;
         self.submission_id = self.submission_id.wrapping_add(1);
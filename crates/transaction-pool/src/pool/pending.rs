@@ -6,7 +6,7 @@ use crate::{
 use reth_primitives::TxHash;
 use std::{
     cmp::Ordering,
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, HashSet},
     sync::Arc,
 };
 
@@ -80,9 +80,19 @@ impl<T: TransactionOrdering> PendingPool<T> {
             all: self.by_id.clone(),
             independent: self.independent_transactions.clone(),
             invalid: Default::default(),
+            base_fee: None,
         }
     }
 
+    /// Same as [Self::best], but skips the transactions that can't pay the given base fee and the
+    /// transactions that depend on them.
+    ///
+    /// This is useful if the block that is built has a different base fee than the pending block
+    /// the pool is updated for.
+    pub(crate) fn best_with_base_fee(&self, base_fee: u128) -> BestTransactions<T> {
+        BestTransactions { base_fee: Some(base_fee), ..self.best() }
+    }
+
     /// Returns the ancestor the given transaction, the transaction with `nonce - 1`.
     ///
     /// Note: for a transaction with nonce higher than the current on chain nonce this will always
@@ -91,18 +101,23 @@ impl<T: TransactionOrdering> PendingPool<T> {
         self.by_id.get(&id.unchecked_ancestor()?)
     }
 
-    /// Adds a new transactions to the pending queue.
+    /// Adds a new transactions to the pending queue, prioritized for the given base fee of the
+    /// pending block.
     ///
     /// # Panics
     ///
     /// if the transaction is already included
-    pub(crate) fn add_transaction(&mut self, tx: Arc<ValidPoolTransaction<T::Transaction>>) {
+    pub(crate) fn add_transaction(
+        &mut self,
+        tx: Arc<ValidPoolTransaction<T::Transaction>>,
+        base_fee: u128,
+    ) {
         assert!(!self.by_id.contains_key(tx.id()), "transaction already included");
 
         let tx_id = *tx.id();
         let submission_id = self.next_id();
 
-        let priority = self.ordering.priority(&tx.transaction);
+        let priority = self.ordering.priority(&tx.transaction, base_fee);
 
         // keep track of size
         self.size_of += tx.size();
@@ -121,6 +136,28 @@ impl<T: TransactionOrdering> PendingPool<T> {
         self.by_id.insert(tx_id, transaction);
     }
 
+    /// Recomputes the priorities of all transactions for the new base fee of the pending block.
+    pub(crate) fn update_base_fee(&mut self, base_fee: u128) {
+        let independent = self
+            .independent_transactions
+            .iter()
+            .map(|tx| *tx.transaction.id())
+            .collect::<HashSet<_>>();
+        self.all.clear();
+        self.independent_transactions.clear();
+
+        for tx in self.by_id.values_mut() {
+            let mut transaction = tx.transaction.clone();
+            transaction.priority =
+                self.ordering.priority(&transaction.transaction.transaction, base_fee);
+            if independent.contains(tx.id()) {
+                self.independent_transactions.insert(transaction.clone());
+            }
+            self.all.insert(transaction.clone());
+            *tx = Arc::new(PendingTransaction { transaction });
+        }
+    }
+
     /// Removes a _mined_ transaction from the pool.
     ///
     /// If the transactions has a descendant transaction it will advance it to the best queue.
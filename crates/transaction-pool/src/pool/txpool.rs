@@ -1,6 +1,6 @@
 //! The internal transaction pool implementation.
 use crate::{
-    config::MAX_ACCOUNT_SLOTS_PER_SENDER,
+    config::{MAX_ACCOUNT_SLOTS_PER_SENDER, MIN_PROTOCOL_BASE_FEE},
     error::{InvalidPoolTransactionError, PoolError},
     identifier::{SenderId, TransactionId},
     metrics::TxPoolMetrics,
@@ -12,7 +12,7 @@ use crate::{
         update::{Destination, PoolUpdate},
         AddedPendingTransaction, AddedTransaction, OnNewBlockOutcome,
     },
-    traits::{PoolSize, StateDiff},
+    traits::{PoolSize, SenderTransactions, StateDiff},
     OnNewBlockEvent, PoolConfig, PoolResult, PoolTransaction, TransactionOrdering,
     ValidPoolTransaction, U256,
 };
@@ -26,13 +26,6 @@ use std::{
     sync::Arc,
 };
 
-/// The minimal value the basefee can decrease to.
-///
-/// The `BASE_FEE_MAX_CHANGE_DENOMINATOR` <https://eips.ethereum.org/EIPS/eip-1559> is `8`, or 12.5%.
-/// Once the base fee has dropped to `7` WEI it cannot decrease further because 12.5% of 7 is less
-/// than 1.
-pub(crate) const MIN_PROTOCOL_BASE_FEE: u128 = 7;
-
 /// A pool that manages transactions.
 ///
 /// This pool maintains the state of all transactions and stores them accordingly.
@@ -104,7 +97,7 @@ impl<T: TransactionOrdering> TxPool<T> {
             pending_pool: PendingPool::new(ordering),
             queued_pool: Default::default(),
             basefee_pool: Default::default(),
-            all_transactions: AllTransactions::new(config.max_account_slots),
+            all_transactions: AllTransactions::new(&config),
             config,
             metrics: Default::default(),
         }
@@ -140,6 +133,15 @@ impl<T: TransactionOrdering> TxPool<T> {
         self.pending_pool.best()
     }
 
+    /// Returns an iterator that yields transactions that are ready to be included in a block with
+    /// the given base fee.
+    ///
+    /// The base fee is raised to the configured minimal protocol base fee, if it's lower.
+    pub(crate) fn best_transactions_with_base_fee(&self, base_fee: u128) -> BestTransactions<T> {
+        self.pending_pool
+            .best_with_base_fee(base_fee.max(self.all_transactions.minimal_protocol_basefee))
+    }
+
     /// Returns `true` if the transaction with the given hash is already included in this pool.
     pub(crate) fn contains(&self, tx_hash: &TxHash) -> bool {
         self.all_transactions.contains(tx_hash)
@@ -161,6 +163,32 @@ impl<T: TransactionOrdering> TxPool<T> {
         txs.into_iter().filter_map(|tx| self.get(&tx))
     }
 
+    /// Returns the transactions of the sender, split by sub-pool, and the lowest nonce missing
+    /// between the state nonce of the sender and its transactions in the pool.
+    pub(crate) fn sender_transactions(
+        &self,
+        sender: SenderId,
+    ) -> SenderTransactions<T::Transaction> {
+        let state_nonce = self.sender_info.get(&sender).map(|info| info.state_nonce);
+        let mut txs = SenderTransactions { state_nonce, ..Default::default() };
+        let mut next_nonce = state_nonce.unwrap_or_default();
+        for (id, tx) in self.all_transactions.txs_iter(sender) {
+            if txs.nonce_gap.is_none() {
+                if id.nonce > next_nonce {
+                    txs.nonce_gap = Some(next_nonce);
+                } else {
+                    next_nonce = id.next_nonce();
+                }
+            }
+            if tx.subpool.is_pending() {
+                txs.pending.push(Arc::clone(&tx.transaction));
+            } else {
+                txs.queued.push(Arc::clone(&tx.transaction));
+            }
+        }
+        txs
+    }
+
     /// Updates the entire pool after a new block was mined.
     ///
     /// This removes all mined transactions, updates according to the new base fee and rechecks
@@ -174,12 +202,19 @@ impl<T: TransactionOrdering> TxPool<T> {
         }
 
         // Apply the state changes to the total set of transactions which triggers sub-pool updates.
+        let base_fee_changed =
+            self.all_transactions.pending_basefee != event.pending_block_base_fee;
         let updates =
             self.all_transactions.update(event.pending_block_base_fee, &event.state_changes);
 
         // Process the sub-pool updates
         let UpdateOutcome { promoted, discarded } = self.process_updates(updates);
 
+        // The priority of the pending transactions can depend on the base fee
+        if base_fee_changed {
+            self.pending_pool.update_base_fee(event.pending_block_base_fee);
+        }
+
         OnNewBlockOutcome {
             block_hash: event.hash,
             mined: event.mined_transactions,
@@ -214,6 +249,10 @@ impl<T: TransactionOrdering> TxPool<T> {
         on_chain_balance: U256,
         on_chain_nonce: u64,
     ) -> PoolResult<AddedTransaction<T::Transaction>> {
+        if self.contains(tx.hash()) {
+            return Err(PoolError::AlreadyImported(*tx.hash()))
+        }
+
         // Update sender info with balance and nonce
         self.sender_info
             .entry(tx.sender_id())
@@ -383,7 +422,7 @@ impl<T: TransactionOrdering> TxPool<T> {
                 self.queued_pool.add_transaction(tx);
             }
             SubPool::Pending => {
-                self.pending_pool.add_transaction(tx);
+                self.pending_pool.add_transaction(tx, self.all_transactions.pending_basefee);
             }
             SubPool::BaseFee => {
                 self.basefee_pool.add_transaction(tx);
@@ -445,6 +484,35 @@ impl<T: TransactionOrdering> TxPool<T> {
         removed
     }
 
+    /// Discards the queued transactions that were added longer than the configured
+    /// [`queued_lifetime`](PoolConfig::queued_lifetime) ago, together with their descendants.
+    ///
+    /// Local transactions never expire.
+    pub(crate) fn discard_expired(&mut self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        let now = self.config.clock.now();
+        let lifetime = self.config.queued_lifetime;
+        let expired = self
+            .queued_pool
+            .transactions()
+            .filter(|tx| {
+                !tx.origin.is_local() && now.saturating_duration_since(tx.timestamp) > lifetime
+            })
+            .map(|tx| *tx.id())
+            .collect::<Vec<_>>();
+
+        let mut removed = Vec::new();
+        for id in expired {
+            // the transaction may already be removed as a descendant of another expired one
+            if let Some(tx) = self.remove_transaction(&id) {
+                removed.push(tx);
+                self.remove_descendants(&id, &mut removed);
+            }
+        }
+
+        self.metrics.expired_transactions.increment(removed.len() as u64);
+        removed
+    }
+
     /// Number of transactions in the entire pool
     pub(crate) fn len(&self) -> usize {
         self.all_transactions.len()
@@ -488,7 +556,8 @@ pub(crate) struct AllTransactions<T: PoolTransaction> {
     pending_basefee: u128,
     /// Minimum base fee required by the protocol.
     ///
-    /// Transactions with a lower base fee will never be included by the chain
+    /// Transactions with a lower base fee will never be included by the chain, see
+    /// [PoolConfig::minimal_protocol_basefee]
     minimal_protocol_basefee: u128,
     /// The max gas limit of the block
     block_gas_limit: u64,
@@ -504,8 +573,12 @@ pub(crate) struct AllTransactions<T: PoolTransaction> {
 
 impl<T: PoolTransaction> AllTransactions<T> {
     /// Create a new instance
-    fn new(max_account_slots: usize) -> Self {
-        Self { max_account_slots, ..Default::default() }
+    fn new(config: &PoolConfig) -> Self {
+        Self {
+            max_account_slots: config.max_account_slots,
+            minimal_protocol_basefee: config.minimal_protocol_basefee,
+            ..Default::default()
+        }
     }
 
     /// Returns an iterator over all _unique_ hashes in the pool
@@ -684,8 +757,6 @@ impl<T: PoolTransaction> AllTransactions<T> {
 
     /// Returns an iterator over all transactions for the given sender, starting with the lowest
     /// nonce
-    #[cfg(test)]
-    #[allow(unused)]
     pub(crate) fn txs_iter(
         &self,
         sender: SenderId,
@@ -1024,7 +1095,7 @@ pub(crate) enum InsertErr<T: PoolTransaction> {
     Underpriced { transaction: Arc<ValidPoolTransaction<T>>, existing: TxHash },
     /// The transactions feeCap is lower than the chain's minimum fee requirement.
     ///
-    /// See also [`MIN_PROTOCOL_BASE_FEE`]
+    /// See also [`PoolConfig::minimal_protocol_basefee`]
     ProtocolFeeCapTooLow { transaction: Arc<ValidPoolTransaction<T>>, fee_cap: u128 },
     /// Sender currently exceeds the configured limit for max account slots.
     ///
@@ -1143,7 +1214,7 @@ impl SenderInfo {
 mod tests {
     use super::*;
     use crate::{
-        test_utils::{MockTransaction, MockTransactionFactory},
+        test_utils::{mock_tx_pool, MockTransaction, MockTransactionFactory, MockTxPool},
         traits::TransactionOrigin,
     };
 
@@ -1362,6 +1433,33 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn sender_transactions_nonce_gap() {
+        let on_chain_balance = U256::from(1_000);
+        // the last transaction of the sender that was mined has nonce 3
+        let on_chain_nonce = 4;
+        let mut f = MockTransactionFactory::default();
+        let mut pool = mock_tx_pool();
+
+        let tx = MockTransaction::eip1559().with_nonce(5);
+        let sender = f.tx_id(&tx).sender;
+        pool.add_transaction(f.validated(tx.clone()), on_chain_balance, on_chain_nonce).unwrap();
+        pool.add_transaction(f.validated(tx.next()), on_chain_balance, on_chain_nonce).unwrap();
+
+        let txs = pool.sender_transactions(sender);
+        assert_eq!(txs.state_nonce, Some(4));
+        assert_eq!(txs.nonce_gap, Some(4));
+        assert!(txs.pending.is_empty());
+        assert_eq!(txs.queued.iter().map(|tx| tx.nonce()).collect::<Vec<_>>(), vec![5, 6]);
+
+        // filling the gap promotes all transactions of the sender
+        pool.add_transaction(f.validated(tx.prev()), on_chain_balance, on_chain_nonce).unwrap();
+        let txs = pool.sender_transactions(sender);
+        assert_eq!(txs.nonce_gap, None);
+        assert_eq!(txs.pending.iter().map(|tx| tx.nonce()).collect::<Vec<_>>(), vec![4, 5, 6]);
+        assert!(txs.queued.is_empty());
+    }
+
     #[test]
     fn reject_tx_over_gas_limit() {
         let on_chain_balance = U256::from(1_000);
@@ -1376,4 +1474,26 @@ mod tests {
             Err(InsertErr::TxGasLimitMoreThanAvailableBlockGas { .. })
         ));
     }
+
+    #[test]
+    fn accepts_zero_fee_with_zero_minimal_basefee() {
+        let on_chain_balance = U256::from(1_000);
+        let on_chain_nonce = 0;
+        let mut f = MockTransactionFactory::default();
+        let tx = MockTransaction::eip1559().with_max_fee(0).with_priority_fee(0);
+
+        let mut pool = mock_tx_pool();
+        let err = pool
+            .add_transaction(f.validated(tx.clone()), on_chain_balance, on_chain_nonce)
+            .unwrap_err();
+        assert!(matches!(err, PoolError::ProtocolFeeCapTooLow(_, 0)));
+
+        let config = PoolConfig { minimal_protocol_basefee: 0, ..Default::default() };
+        let mut pool = MockTxPool::new(Default::default(), config);
+        pool.add_transaction(f.validated(tx.clone()), on_chain_balance, on_chain_nonce).unwrap();
+        let best = pool.best_transactions_with_base_fee(0).collect::<Vec<_>>();
+        assert_eq!(best.len(), 1);
+        assert_eq!(*best[0].hash(), tx.get_hash());
+        assert_eq!(pool.best_transactions_with_base_fee(1).count(), 0);
+    }
 }
@@ -2,20 +2,26 @@
 
 use crate::{
     identifier::{SenderIdentifiers, TransactionId},
-    pool::txpool::{TxPool, MIN_PROTOCOL_BASE_FEE},
+    pool::txpool::TxPool,
     traits::TransactionOrigin,
-    PoolTransaction, TransactionOrdering, ValidPoolTransaction,
+    PoolClock, PoolTransaction, TransactionOrdering, ValidPoolTransaction, MIN_PROTOCOL_BASE_FEE,
 };
+use parking_lot::Mutex;
 use paste::paste;
 use rand::{
     distributions::{Uniform, WeightedIndex},
     prelude::Distribution,
 };
 use reth_primitives::{
-    Address, FromRecoveredTransaction, IntoRecoveredTransaction, Transaction, TransactionKind,
-    TransactionSignedEcRecovered, TxEip1559, TxHash, TxLegacy, TxType, H256, U128, U256,
+    Address, FromRecoveredTransaction, IntoRecoveredTransaction, Signature, Transaction,
+    TransactionKind, TransactionSigned, TransactionSignedEcRecovered, TxEip1559, TxHash, TxLegacy,
+    TxType, H256, U128, U256,
+};
+use std::{
+    ops::Range,
+    sync::Arc,
+    time::{Duration, Instant},
 };
-use std::{ops::Range, sync::Arc, time::Instant};
 
 pub(crate) type MockTxPool = TxPool<MockOrdering>;
 
@@ -414,7 +420,42 @@ impl FromRecoveredTransaction for MockTransaction {
 
 impl IntoRecoveredTransaction for MockTransaction {
     fn to_recovered_transaction(&self) -> TransactionSignedEcRecovered {
-        todo!()
+        let transaction = match self.clone() {
+            MockTransaction::Legacy { nonce, gas_price, gas_limit, to, value, .. } => {
+                Transaction::Legacy(TxLegacy {
+                    chain_id: Some(1),
+                    nonce,
+                    gas_price,
+                    gas_limit,
+                    to,
+                    value: value.to(),
+                    input: Default::default(),
+                })
+            }
+            MockTransaction::Eip1559 {
+                nonce,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                gas_limit,
+                to,
+                value,
+                ..
+            } => Transaction::Eip1559(TxEip1559 {
+                chain_id: 1,
+                nonce,
+                gas_limit,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                to,
+                value: value.to(),
+                input: Default::default(),
+                access_list: Default::default(),
+            }),
+        };
+        // mock transactions are not signed
+        let signed =
+            TransactionSigned::from_transaction_and_signature(transaction, Signature::default());
+        TransactionSignedEcRecovered::from_signed_transaction(signed, self.get_sender())
     }
 }
 
@@ -463,6 +504,31 @@ impl MockTransactionFactory {
     }
 }
 
+/// A [PoolClock] that only moves forward when it's advanced.
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<Instant>,
+}
+
+impl MockClock {
+    /// Moves the clock forward by the given duration.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock() += duration;
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self { now: Mutex::new(Instant::now()) }
+    }
+}
+
+impl PoolClock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock()
+    }
+}
+
 #[derive(Default)]
 #[non_exhaustive]
 pub struct MockOrdering;
@@ -471,7 +537,7 @@ impl TransactionOrdering for MockOrdering {
     type Priority = U256;
     type Transaction = MockTransaction;
 
-    fn priority(&self, transaction: &Self::Transaction) -> Self::Priority {
+    fn priority(&self, transaction: &Self::Transaction, _base_fee: u128) -> Self::Priority {
         transaction.cost()
     }
 }
@@ -514,5 +580,5 @@ fn test_mock_priority() {
     let o = MockOrdering;
     let lo = MockTransaction::eip1559();
     let hi = lo.next().inc_value();
-    assert!(o.priority(&hi) > o.priority(&lo));
+    assert!(o.priority(&hi, 0) > o.priority(&lo, 0));
 }
@@ -1,6 +1,10 @@
-use crate::{error::PoolResult, pool::state::SubPool, validate::ValidPoolTransaction};
+use crate::{
+    error::PoolResult,
+    pool::state::SubPool,
+    validate::{ValidPoolTransaction, ValidationHead},
+};
 use reth_primitives::{
-    Address, FromRecoveredTransaction, IntoRecoveredTransaction, PeerId, Transaction,
+    Address, BlockNumber, FromRecoveredTransaction, IntoRecoveredTransaction, PeerId, Transaction,
     TransactionKind, TransactionSignedEcRecovered, TxHash, H256, U256,
 };
 use reth_rlp::Encodable;
@@ -55,7 +59,7 @@ pub trait TransactionPool: Send + Sync + Clone {
 
     /// Adds the given _unvalidated_ transaction into the pool.
     ///
-    /// Returns a list of results.
+    /// Returns a list of results, in the same order as the given transactions.
     ///
     /// Consumer: RPC
     async fn add_transactions(
@@ -93,6 +97,19 @@ pub trait TransactionPool: Send + Sync + Clone {
         &self,
     ) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<Self::Transaction>>>>;
 
+    /// Returns an iterator that yields transactions that are ready for a block with the given base
+    /// fee.
+    ///
+    /// Unlike [Self::best_transactions], which expects the base fee of the pending block the pool
+    /// was last updated for, this skips all transactions that can't pay the given base fee, and the
+    /// transactions that depend on them.
+    ///
+    /// Consumer: Block production
+    fn best_transactions_with_base_fee(
+        &self,
+        base_fee: u64,
+    ) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<Self::Transaction>>>>;
+
     /// Removes all transactions corresponding to the given hashes.
     ///
     /// Also removes all dependent transactions.
@@ -103,6 +120,15 @@ pub trait TransactionPool: Send + Sync + Clone {
         hashes: impl IntoIterator<Item = TxHash>,
     ) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>>;
 
+    /// Discards the queued transactions that were in the pool for longer than the configured
+    /// lifetime and returns them.
+    ///
+    /// This is also done on every new block, but must be triggered periodically so transactions
+    /// expire while no blocks arrive.
+    ///
+    /// Consumer: Pool maintenance
+    fn discard_expired(&self) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>>;
+
     /// Retains only those hashes that are unknown to the pool.
     /// In other words, removes all transactions from the given set that are currently present in
     /// the pool.
@@ -128,12 +154,44 @@ pub trait TransactionPool: Send + Sync + Clone {
         txs: impl IntoIterator<Item = TxHash>,
     ) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>>;
 
+    /// Returns all transactions of the sender, split into the pending and the parked ones, and
+    /// the lowest missing nonce that keeps the parked transactions from being promoted.
+    ///
+    /// Consumer: RPC
+    fn pending_and_queued_by_sender(
+        &self,
+        sender: Address,
+    ) -> SenderTransactions<Self::Transaction>;
+
     /// Notify the pool about transactions that are propagated to peers.
     ///
     /// Consumer: P2P
     fn on_propagated(&self, txs: PropagatedTransactions);
 }
 
+/// The transactions of a sender in the pool, see
+/// [TransactionPool::pending_and_queued_by_sender].
+#[derive(Debug)]
+pub struct SenderTransactions<T: PoolTransaction> {
+    /// The nonce of the sender on the state its transactions were last validated against, if the
+    /// pool tracks the sender.
+    pub state_nonce: Option<u64>,
+    /// Transactions of the sender that are ready to be included, ordered by nonce.
+    pub pending: Vec<Arc<ValidPoolTransaction<T>>>,
+    /// Transactions of the sender that are parked in the queued or base fee sub-pool, ordered by
+    /// nonce.
+    pub queued: Vec<Arc<ValidPoolTransaction<T>>>,
+    /// The lowest nonce that is neither on chain nor in the pool but is required to promote
+    /// transactions of the sender with higher nonces.
+    pub nonce_gap: Option<u64>,
+}
+
+impl<T: PoolTransaction> Default for SenderTransactions<T> {
+    fn default() -> Self {
+        Self { state_nonce: None, pending: Vec::new(), queued: Vec::new(), nonce_gap: None }
+    }
+}
+
 /// Represents a transaction that was propagated over the network.
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
 pub struct PropagatedTransactions(pub HashMap<TxHash, Vec<PropagateKind>>);
@@ -223,6 +281,10 @@ pub struct OnNewBlockEvent {
     pub state_changes: StateDiff,
     /// All mined transactions in the block
     pub mined_transactions: Vec<H256>,
+    /// The latest finalized block, if known.
+    pub finalized_block: Option<BlockNumber>,
+    /// The view of the added block that new transactions are validated against.
+    pub head: ValidationHead,
 }
 
 /// Contains a list of changed state
@@ -285,6 +347,17 @@ pub trait PoolTransaction:
     /// This will return `None` for non-EIP1559 transactions
     fn max_priority_fee_per_gas(&self) -> Option<u128>;
 
+    /// Returns the priority fee per gas the block author receives from this transaction in a block
+    /// with the given base fee, or `None` if the transaction can't pay the base fee.
+    ///
+    /// This is `min(maxPriorityFee, maxFee - basefee)` for EIP-1559 and `gasPrice - basefee` for
+    /// legacy transactions.
+    fn effective_tip_per_gas(&self, base_fee: u128) -> Option<u128> {
+        let max_fee = self.max_fee_per_gas().unwrap_or_else(|| self.effective_gas_price());
+        let max_tip = max_fee.checked_sub(base_fee)?;
+        Some(self.max_priority_fee_per_gas().map_or(max_tip, |tip| tip.min(max_tip)))
+    }
+
     /// Returns the transaction's [`TransactionKind`], which is the address of the recipient or
     /// [`TransactionKind::Create`] if the transaction is a contract creation.
     fn kind(&self) -> &TransactionKind;
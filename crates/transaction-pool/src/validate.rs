@@ -4,15 +4,16 @@ use crate::{
     error::InvalidPoolTransactionError,
     identifier::{SenderId, TransactionId},
     traits::{PoolTransaction, TransactionOrigin},
-    MAX_INIT_CODE_SIZE, TX_MAX_SIZE,
+    MAX_INIT_CODE_SIZE, MIN_PROTOCOL_BASE_FEE, TX_MAX_SIZE,
 };
+use parking_lot::RwLock;
 use reth_primitives::{
-    Address, IntoRecoveredTransaction, InvalidTransactionError, TransactionKind,
-    TransactionSignedEcRecovered, TxHash, EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID,
-    LEGACY_TX_TYPE_ID, U256,
+    Address, BlockNumber, ChainSpec, Hardfork, Header, IntoRecoveredTransaction,
+    InvalidTransactionError, TransactionKind, TransactionSignedEcRecovered, TxHash,
+    EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID, LEGACY_TX_TYPE_ID, U256,
 };
-use reth_provider::AccountProvider;
-use std::{fmt, time::Instant};
+use reth_provider::{AccountProvider, StateProviderFactory};
+use std::{fmt, marker::PhantomData, sync::Arc, time::Instant};
 
 /// A Result type returned after checking a transaction's validity.
 #[derive(Debug)]
@@ -63,6 +64,12 @@ pub trait TransactionValidator: Send + Sync {
         transaction: Self::Transaction,
     ) -> TransactionValidationOutcome<Self::Transaction>;
 
+    /// Called when the canonical head changed, with the view of the new head transactions are
+    /// validated against from now on.
+    ///
+    /// The default implementation ignores the new head.
+    fn on_new_head(&self, _head: ValidationHead) {}
+
     /// Ensure that the code size is not greater than `max_init_code_size`.
     /// `max_init_code_size` should be configurable so this will take it as an argument.
     fn ensure_max_init_code_size(
@@ -82,28 +89,118 @@ pub trait TransactionValidator: Send + Sync {
     }
 }
 
+/// The view of the canonical head that transactions are validated against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationHead {
+    /// Number of the head block.
+    pub number: BlockNumber,
+    /// Timestamp of the head block.
+    pub timestamp: u64,
+    /// Gas limit of the head block, the max gas limit of a transaction.
+    pub gas_limit: u64,
+    /// Base fee per gas of the block built on the head block, if EIP-1559 is active, see
+    /// [ChainSpec::next_block_base_fee].
+    pub base_fee: Option<u64>,
+    /// Blob fee per gas of the head block, if blob transactions are active.
+    pub blob_fee: Option<u128>,
+    /// Fork indicator whether we are in the Shanghai stage.
+    pub shanghai: bool,
+    /// Fork indicator whether we are using EIP-2718 type transactions.
+    pub eip2718: bool,
+    /// Fork indicator whether we are using EIP-1559 type transactions.
+    pub eip1559: bool,
+}
+
+// === impl ValidationHead ===
+
+impl ValidationHead {
+    /// Returns the view of the given head block of the chain.
+    pub fn new(chain_spec: &ChainSpec, header: &Header) -> Self {
+        Self {
+            number: header.number,
+            timestamp: header.timestamp,
+            gas_limit: header.gas_limit,
+            base_fee: chain_spec.next_block_base_fee(header),
+            blob_fee: None,
+            shanghai: chain_spec.fork(Hardfork::Shanghai).active_at_timestamp(header.timestamp),
+            eip2718: chain_spec.fork(Hardfork::Berlin).active_at_block(header.number),
+            eip1559: chain_spec.fork(Hardfork::London).active_at_block(header.number),
+        }
+    }
+}
+
 /// A [TransactionValidator] implementation that validates ethereum transaction.
 #[derive(Debug, Clone)]
-pub struct EthTransactionValidator<Client> {
+pub struct EthTransactionValidator<Client, T> {
     /// Chain id
     chain_id: u64,
     /// This type fetches account info from the db
     client: Client,
-    /// Fork indicator whether we are in the Shanghai stage.
-    shanghai: bool,
-    /// Fork indicator whether we are using EIP-2718 type transactions.
-    eip2718: bool,
-    /// Fork indicator whether we are using EIP-1559 type transactions.
-    eip1559: bool,
-    /// The current max gas limit
-    current_max_gas_limit: u64,
+    /// The view of the canonical head, shared by all clones of the validator.
+    ///
+    /// It is replaced as a whole on every new head, so a validation that reads it once never sees
+    /// the fork indicators of one head and the limits of another.
+    head: Arc<RwLock<ValidationHead>>,
     /// gasprice
     gas_price: Option<u128>,
+    /// Minimal fee cap of dynamic fee transactions.
+    minimal_protocol_basefee: u128,
+    _marker: PhantomData<T>,
+}
+
+// === impl EthTransactionValidator ===
+
+impl<Client, T> EthTransactionValidator<Client, T> {
+    /// Creates a new validator for transactions of the given chain that checks the senders
+    /// against the latest state of the client.
+    ///
+    /// All transaction types are accepted, up to the given gas limit, until the first head is set
+    /// with [TransactionValidator::on_new_head].
+    pub fn new(client: Client, chain_id: u64, max_gas_limit: u64) -> Self {
+        let head = ValidationHead {
+            number: 0,
+            timestamp: 0,
+            gas_limit: max_gas_limit,
+            base_fee: None,
+            blob_fee: None,
+            shanghai: true,
+            eip2718: true,
+            eip1559: true,
+        };
+        Self::with_head(client, chain_id, head)
+    }
+
+    /// Creates a new validator for transactions of the given chain that validates against the
+    /// given view of the canonical head.
+    pub fn with_head(client: Client, chain_id: u64, head: ValidationHead) -> Self {
+        Self {
+            chain_id,
+            client,
+            head: Arc::new(RwLock::new(head)),
+            gas_price: None,
+            minimal_protocol_basefee: MIN_PROTOCOL_BASE_FEE,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the minimal fee cap of dynamic fee transactions, see
+    /// [PoolConfig::minimal_protocol_basefee](crate::PoolConfig::minimal_protocol_basefee).
+    pub fn with_minimal_protocol_basefee(mut self, minimal_protocol_basefee: u128) -> Self {
+        self.minimal_protocol_basefee = minimal_protocol_basefee;
+        self
+    }
+
+    /// Returns the view of the canonical head transactions are currently validated against.
+    pub fn head(&self) -> ValidationHead {
+        *self.head.read()
+    }
 }
 
 #[async_trait::async_trait]
-impl<T: PoolTransaction + AccountProvider + Clone> TransactionValidator
-    for EthTransactionValidator<T>
+impl<Client, T> TransactionValidator for EthTransactionValidator<Client, T>
+where
+    Client: StateProviderFactory,
+    T: PoolTransaction + Clone,
 {
     type Transaction = T;
 
@@ -112,6 +209,9 @@ impl<T: PoolTransaction + AccountProvider + Clone> TransactionValidator
         origin: TransactionOrigin,
         transaction: Self::Transaction,
     ) -> TransactionValidationOutcome<Self::Transaction> {
+        // All checks are done against the same head, even if a new head is set meanwhile
+        let head = self.head();
+
         // Checks for tx_type
         match transaction.tx_type() {
             LEGACY_TX_TYPE_ID => {
@@ -120,7 +220,7 @@ impl<T: PoolTransaction + AccountProvider + Clone> TransactionValidator
 
             EIP2930_TX_TYPE_ID => {
                 // Accept only legacy transactions until EIP-2718/2930 activates
-                if !self.eip2718 {
+                if !head.eip2718 {
                     return TransactionValidationOutcome::Invalid(
                         transaction,
                         InvalidTransactionError::Eip1559Disabled.into(),
@@ -130,7 +230,7 @@ impl<T: PoolTransaction + AccountProvider + Clone> TransactionValidator
 
             EIP1559_TX_TYPE_ID => {
                 // Reject dynamic fee transactions until EIP-1559 activates.
-                if !self.eip1559 {
+                if !head.eip1559 {
                     return TransactionValidationOutcome::Invalid(
                         transaction,
                         InvalidTransactionError::Eip1559Disabled.into(),
@@ -155,7 +255,7 @@ impl<T: PoolTransaction + AccountProvider + Clone> TransactionValidator
         }
 
         // Check whether the init code size has been exceeded.
-        if self.shanghai {
+        if head.shanghai {
             if let Err(err) =
                 self.ensure_max_init_code_size(transaction.clone(), MAX_INIT_CODE_SIZE)
             {
@@ -164,22 +264,32 @@ impl<T: PoolTransaction + AccountProvider + Clone> TransactionValidator
         }
 
         // Checks for gas limit
-        if transaction.gas_limit() > self.current_max_gas_limit {
+        if transaction.gas_limit() > head.gas_limit {
             return TransactionValidationOutcome::Invalid(
                 transaction.clone(),
                 InvalidPoolTransactionError::ExceedsGasLimit(
                     transaction.gas_limit(),
-                    self.current_max_gas_limit,
+                    head.gas_limit,
                 ),
             )
         }
 
         // Ensure max_fee_per_gas is greater than or equal to max_priority_fee_per_gas.
-        if transaction.max_fee_per_gas() <= transaction.max_priority_fee_per_gas() {
-            return TransactionValidationOutcome::Invalid(
-                transaction,
-                InvalidTransactionError::TipAboveFeeCap.into(),
-            )
+        if let (Some(max_fee), Some(max_priority_fee)) =
+            (transaction.max_fee_per_gas(), transaction.max_priority_fee_per_gas())
+        {
+            if max_fee < max_priority_fee {
+                return TransactionValidationOutcome::Invalid(
+                    transaction,
+                    InvalidTransactionError::TipAboveFeeCap.into(),
+                )
+            }
+            if max_fee < self.minimal_protocol_basefee {
+                return TransactionValidationOutcome::Invalid(
+                    transaction,
+                    InvalidTransactionError::FeeCapTooLow.into(),
+                )
+            }
         }
 
         // Drop non-local transactions under our own minimal accepted gas price or tip
@@ -198,7 +308,11 @@ impl<T: PoolTransaction + AccountProvider + Clone> TransactionValidator
             )
         }
 
-        let account = match self.client.basic_account(transaction.sender()) {
+        let account = match self
+            .client
+            .latest()
+            .and_then(|state| state.basic_account(transaction.sender()))
+        {
             Ok(account) => account,
             Err(err) => return TransactionValidationOutcome::Error(transaction, Box::new(err)),
         };
@@ -252,6 +366,10 @@ impl<T: PoolTransaction + AccountProvider + Clone> TransactionValidator
             transaction,
         }
     }
+
+    fn on_new_head(&self, head: ValidationHead) {
+        *self.head.write() = head;
+    }
 }
 
 /// A valid transaction in the pool.
@@ -362,3 +480,78 @@ impl<T: PoolTransaction> fmt::Debug for ValidPoolTransaction<T> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockTransaction;
+    use reth_provider::test_utils::NoopProvider;
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        thread,
+    };
+
+    #[tokio::test]
+    async fn validate_against_single_head() {
+        let old = ValidationHead {
+            number: 1,
+            timestamp: 1,
+            gas_limit: 1_000_000,
+            base_fee: None,
+            blob_fee: None,
+            shanghai: true,
+            eip2718: true,
+            eip1559: false,
+        };
+        // the new head activates EIP-1559 and lowers the gas limit
+        let new = ValidationHead {
+            number: 2,
+            timestamp: 2,
+            gas_limit: 10_000,
+            base_fee: Some(7),
+            eip1559: true,
+            ..old
+        };
+        let validator = EthTransactionValidator::<_, MockTransaction>::with_head(
+            NoopProvider::default(),
+            1,
+            old,
+        );
+
+        let done = Arc::new(AtomicBool::new(false));
+        let flipper = {
+            let validator = validator.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                let mut flip = false;
+                while !done.load(Ordering::Relaxed) {
+                    validator.on_new_head(if flip { new } else { old });
+                    flip = !flip;
+                }
+            })
+        };
+
+        // the transactions are rejected for their type under the old head and for their gas limit
+        // under the new head, only a mix of both heads lets them pass these checks
+        for _ in 0..10_000 {
+            let tx = MockTransaction::eip1559().with_gas_limit(21_000);
+            match validator.validate_transaction(TransactionOrigin::Local, tx).await {
+                TransactionValidationOutcome::Invalid(
+                    _,
+                    InvalidPoolTransactionError::Consensus(
+                        InvalidTransactionError::Eip1559Disabled,
+                    ),
+                ) => {}
+                TransactionValidationOutcome::Invalid(
+                    _,
+                    InvalidPoolTransactionError::ExceedsGasLimit(21_000, 10_000),
+                ) => {}
+                outcome => panic!("validated against a mix of heads: {outcome:?}"),
+            }
+        }
+
+        done.store(true, Ordering::Relaxed);
+        flipper.join().unwrap();
+        assert!(validator.head() == old || validator.head() == new);
+    }
+}